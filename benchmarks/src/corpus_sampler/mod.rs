@@ -0,0 +1,550 @@
+//! Sampling of real documents and queries from a live Omni database into the
+//! custom benchmark dataset format (`queries.json` + `documents.json`), so a
+//! production relevance complaint can be reproduced against a local index.
+//!
+//! Queries come from search clicks: the documents clicked for a query are its
+//! relevant documents, graded by click count. The sampled corpus always holds
+//! those documents and is padded with other documents up to the requested
+//! size. Padding is ordered by a hash of the document id and the seed, so the
+//! same seed over the same database yields the same sample.
+//!
+//! By default the sample is anonymized: email addresses are redacted,
+//! document ids are replaced with sequential ids and only the source type is
+//! kept from the metadata. Only documents readable by everyone are sampled
+//! unless restricted documents are explicitly included.
+
+use anyhow::Result;
+use serde::Serialize;
+use sqlx::{Pool, Postgres};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use tracing::{info, warn};
+
+use crate::datasets::{Document, Query, RelevantDoc};
+use crate::training_data::{SplitMix64, redact_emails};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Anonymization {
+    /// Replace email addresses in queries, titles and content with `[email]`.
+    pub redact_emails: bool,
+    /// Replace document ids with `doc_<n>`.
+    pub pseudonymize_ids: bool,
+    /// Drop URLs, paths and other metadata, keeping only the source type.
+    pub strip_metadata: bool,
+}
+
+impl Default for Anonymization {
+    fn default() -> Self {
+        Self {
+            redact_emails: true,
+            pseudonymize_ids: true,
+            strip_metadata: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CorpusSampleOptions {
+    /// Target corpus size. Documents relevant to a sampled query are always
+    /// included, so the corpus can be larger when queries need more.
+    pub num_documents: usize,
+    pub num_queries: usize,
+    /// Queries that must be in the sample, e.g. the ones from a complaint.
+    /// Matched after the same normalization as clicked queries.
+    pub queries: Vec<String>,
+    /// Only clicks from the last this many days are used.
+    pub since_days: i32,
+    /// A document needs at least this many clicks for a query to be
+    /// relevant to it.
+    pub min_clicks: i64,
+    /// Document content is truncated to this many characters.
+    pub max_content_chars: usize,
+    /// Also sample documents that are not readable by everyone.
+    pub include_restricted: bool,
+    pub anonymization: Anonymization,
+    /// Seed for query and document sampling, so samples are reproducible.
+    pub seed: u64,
+}
+
+/// A document clicked for a normalized query.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct QueryClick {
+    pub query: String,
+    pub document_id: String,
+    pub clicks: i64,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct SampledDocument {
+    pub id: String,
+    pub title: String,
+    pub content: String,
+    pub source_type: String,
+    pub url: Option<String>,
+    pub path: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct SampleStats {
+    pub queries: usize,
+    pub relevant_documents: usize,
+    pub documents: usize,
+}
+
+/// Written next to the dataset so a sample can be traced back to how it was
+/// taken.
+#[derive(Debug, Serialize)]
+struct SampleManifest<'a> {
+    created_at: String,
+    options: &'a CorpusSampleOptions,
+    stats: SampleStats,
+}
+
+pub struct CorpusSampler {
+    db_pool: Pool<Postgres>,
+}
+
+impl CorpusSampler {
+    pub async fn new(database_url: &str) -> Result<Self> {
+        let db_pool = sqlx::postgres::PgPoolOptions::new()
+            .max_connections(2)
+            .connect(database_url)
+            .await?;
+
+        Ok(Self { db_pool })
+    }
+
+    pub async fn sample(
+        &self,
+        options: &CorpusSampleOptions,
+        output_dir: &str,
+    ) -> Result<SampleStats> {
+        let clicks = self.load_query_clicks(options).await?;
+        info!(
+            "Loaded {} clicked (query, document) pairs from the last {} days",
+            clicks.len(),
+            options.since_days
+        );
+
+        let sampled_queries = select_queries(&clicks, options);
+        if sampled_queries.is_empty() {
+            warn!("No clicked queries matched; the sample will have documents only");
+        }
+
+        let relevant_ids: Vec<String> = sampled_queries
+            .values()
+            .flat_map(|docs| docs.keys().cloned())
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        let mut documents = self.load_documents(&relevant_ids, options).await?;
+
+        let padding = options.num_documents.saturating_sub(documents.len());
+        if padding > 0 {
+            documents.extend(self.load_padding(&relevant_ids, padding, options).await?);
+        }
+
+        let (queries, documents, stats) = build_dataset(sampled_queries, documents, options);
+        write_dataset(output_dir, &queries, &documents, options, stats)?;
+
+        Ok(stats)
+    }
+
+    async fn load_query_clicks(&self, options: &CorpusSampleOptions) -> Result<Vec<QueryClick>> {
+        let rows = sqlx::query_as::<_, QueryClick>(
+            r#"
+            SELECT
+                regexp_replace(lower(btrim(c.query)), '\s+', ' ', 'g') AS query,
+                d.id AS document_id,
+                COUNT(*) AS clicks
+            FROM search_clicks c
+            JOIN documents d ON d.id = c.document_id
+            WHERE c.created_at > NOW() - make_interval(days => $1)
+              AND ($2 OR COALESCE((d.permissions->>'public')::boolean, false))
+            GROUP BY 1, d.id
+            "#,
+        )
+        .bind(options.since_days)
+        .bind(options.include_restricted)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn load_documents(
+        &self,
+        ids: &[String],
+        options: &CorpusSampleOptions,
+    ) -> Result<Vec<SampledDocument>> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let rows = sqlx::query_as::<_, SampledDocument>(
+            r#"
+            SELECT
+                d.id,
+                d.title,
+                LEFT(COALESCE(d.content, ''), $2) AS content,
+                s.source_type::text AS source_type,
+                d.url,
+                d.metadata->>'path' AS path
+            FROM documents d
+            JOIN sources s ON s.id = d.source_id
+            WHERE d.id = ANY($1)
+            "#,
+        )
+        .bind(ids)
+        .bind(options.max_content_chars as i32)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn load_padding(
+        &self,
+        exclude_ids: &[String],
+        limit: usize,
+        options: &CorpusSampleOptions,
+    ) -> Result<Vec<SampledDocument>> {
+        let rows = sqlx::query_as::<_, SampledDocument>(
+            r#"
+            SELECT
+                d.id,
+                d.title,
+                LEFT(COALESCE(d.content, ''), $2) AS content,
+                s.source_type::text AS source_type,
+                d.url,
+                d.metadata->>'path' AS path
+            FROM documents d
+            JOIN sources s ON s.id = d.source_id
+            WHERE NOT (d.id = ANY($1))
+              AND NOT s.is_deleted
+              AND ($3 OR COALESCE((d.permissions->>'public')::boolean, false))
+            ORDER BY md5(d.id || $4)
+            LIMIT $5
+            "#,
+        )
+        .bind(exclude_ids)
+        .bind(options.max_content_chars as i32)
+        .bind(options.include_restricted)
+        .bind(options.seed.to_string())
+        .bind(limit as i64)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(rows)
+    }
+}
+
+fn normalize_query(query: &str) -> String {
+    query
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Pick the queries to sample and their relevant documents with click
+/// counts. Requested queries come first; the rest are drawn at random.
+/// Deterministic for a given seed.
+pub fn select_queries(
+    clicks: &[QueryClick],
+    options: &CorpusSampleOptions,
+) -> BTreeMap<String, BTreeMap<String, i64>> {
+    let mut by_query: BTreeMap<String, BTreeMap<String, i64>> = BTreeMap::new();
+    for click in clicks {
+        if click.query.is_empty() || click.clicks < options.min_clicks {
+            continue;
+        }
+        by_query
+            .entry(click.query.clone())
+            .or_default()
+            .insert(click.document_id.clone(), click.clicks);
+    }
+
+    let requested: Vec<String> = options.queries.iter().map(|q| normalize_query(q)).collect();
+    for query in &requested {
+        if !by_query.contains_key(query) {
+            warn!("Requested query '{}' has no qualifying clicks", query);
+        }
+    }
+
+    let mut others: Vec<String> = by_query
+        .keys()
+        .filter(|query| !requested.contains(query))
+        .cloned()
+        .collect();
+    SplitMix64::new(options.seed).shuffle(&mut others);
+
+    let remaining = options.num_queries.saturating_sub(requested.len());
+    let selected: HashSet<String> = requested
+        .into_iter()
+        .filter(|query| by_query.contains_key(query))
+        .chain(others.into_iter().take(remaining))
+        .collect();
+
+    by_query.retain(|query, _| selected.contains(query));
+    by_query
+}
+
+/// Turn the sampled queries and documents into the benchmark dataset,
+/// applying anonymization. Relevance is the click count relative to the most
+/// clicked document for the query. Queries whose documents could not be
+/// loaded are dropped.
+pub fn build_dataset(
+    sampled_queries: BTreeMap<String, BTreeMap<String, i64>>,
+    mut documents: Vec<SampledDocument>,
+    options: &CorpusSampleOptions,
+) -> (Vec<Query>, Vec<Document>, SampleStats) {
+    let anonymization = options.anonymization;
+    let redact = |text: &str| {
+        if anonymization.redact_emails {
+            redact_emails(text)
+        } else {
+            text.to_string()
+        }
+    };
+
+    let mut seen = HashSet::new();
+    documents.retain(|doc| seen.insert(doc.id.clone()));
+    documents.sort_by(|a, b| a.id.cmp(&b.id));
+
+    let dataset_ids: HashMap<String, String> = documents
+        .iter()
+        .enumerate()
+        .map(|(i, doc)| {
+            let id = if anonymization.pseudonymize_ids {
+                format!("doc_{}", i + 1)
+            } else {
+                doc.id.clone()
+            };
+            (doc.id.clone(), id)
+        })
+        .collect();
+
+    let mut queries = Vec::new();
+    let mut relevant = HashSet::new();
+    for (query, clicked) in sampled_queries {
+        let max_clicks = clicked.values().copied().max().unwrap_or(1).max(1) as f64;
+        let relevant_docs: Vec<RelevantDoc> = clicked
+            .iter()
+            .filter_map(|(document_id, clicks)| {
+                let doc_id = dataset_ids.get(document_id)?;
+                relevant.insert(doc_id.clone());
+                Some(RelevantDoc {
+                    doc_id: doc_id.clone(),
+                    relevance_score: *clicks as f64 / max_clicks,
+                })
+            })
+            .collect();
+        let text = redact(&query);
+        if relevant_docs.is_empty() || text.is_empty() {
+            continue;
+        }
+        queries.push(Query {
+            id: format!("q_{}", queries.len() + 1),
+            text,
+            relevant_docs,
+        });
+    }
+
+    let documents: Vec<Document> = documents
+        .into_iter()
+        .map(|doc| {
+            let mut metadata = HashMap::new();
+            metadata.insert("source_type".to_string(), doc.source_type.clone());
+            if !anonymization.strip_metadata {
+                if let Some(url) = &doc.url {
+                    metadata.insert("url".to_string(), url.clone());
+                }
+                if let Some(path) = &doc.path {
+                    metadata.insert("path".to_string(), path.clone());
+                }
+            }
+            Document {
+                id: dataset_ids[&doc.id].clone(),
+                title: redact(&doc.title),
+                content: redact(&doc.content),
+                metadata,
+            }
+        })
+        .collect();
+
+    let stats = SampleStats {
+        queries: queries.len(),
+        relevant_documents: relevant.len(),
+        documents: documents.len(),
+    };
+    (queries, documents, stats)
+}
+
+fn write_dataset(
+    output_dir: &str,
+    queries: &[Query],
+    documents: &[Document],
+    options: &CorpusSampleOptions,
+    stats: SampleStats,
+) -> Result<()> {
+    fs::create_dir_all(output_dir)?;
+    let dir = Path::new(output_dir);
+
+    fs::write(
+        dir.join("queries.json"),
+        serde_json::to_string_pretty(queries)?,
+    )?;
+    fs::write(
+        dir.join("documents.json"),
+        serde_json::to_string_pretty(documents)?,
+    )?;
+
+    let manifest = SampleManifest {
+        created_at: chrono::Utc::now().to_rfc3339(),
+        options,
+        stats,
+    };
+    fs::write(
+        dir.join("manifest.json"),
+        serde_json::to_string_pretty(&manifest)?,
+    )?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn click(query: &str, id: &str, clicks: i64) -> QueryClick {
+        QueryClick {
+            query: query.to_string(),
+            document_id: id.to_string(),
+            clicks,
+        }
+    }
+
+    fn document(id: &str) -> SampledDocument {
+        SampledDocument {
+            id: id.to_string(),
+            title: format!("Title {}", id),
+            content: format!("Written by owner-{}@example.com", id),
+            source_type: "google_drive".to_string(),
+            url: Some(format!("https://drive.example.com/{}", id)),
+            path: Some(format!("/Shared/{}", id)),
+        }
+    }
+
+    fn options() -> CorpusSampleOptions {
+        CorpusSampleOptions {
+            num_documents: 100,
+            num_queries: 2,
+            queries: Vec::new(),
+            since_days: 90,
+            min_clicks: 1,
+            max_content_chars: 2000,
+            include_restricted: false,
+            anonymization: Anonymization::default(),
+            seed: 42,
+        }
+    }
+
+    #[test]
+    fn test_select_queries_keeps_requested_and_is_deterministic() {
+        let mut clicks: Vec<QueryClick> = (0..10)
+            .map(|i| click(&format!("query {}", i), &format!("doc{}", i), 1))
+            .collect();
+        clicks.push(click("vacation policy", "a", 3));
+        clicks.push(click("vacation policy", "b", 1));
+
+        let mut opts = options();
+        opts.queries = vec!["  Vacation   Policy ".to_string()];
+
+        let first = select_queries(&clicks, &opts);
+        assert_eq!(first.len(), 2);
+        assert_eq!(first["vacation policy"].len(), 2);
+        assert_eq!(first, select_queries(&clicks, &opts));
+
+        opts.seed = 7;
+        let reseeded = select_queries(&clicks, &opts);
+        assert!(reseeded.contains_key("vacation policy"));
+    }
+
+    #[test]
+    fn test_select_queries_applies_min_clicks() {
+        let clicks = vec![click("roadmap", "a", 1), click("roadmap", "b", 4)];
+        let mut opts = options();
+        opts.min_clicks = 2;
+
+        let selected = select_queries(&clicks, &opts);
+        assert_eq!(selected["roadmap"].keys().collect::<Vec<_>>(), vec!["b"]);
+    }
+
+    #[test]
+    fn test_build_dataset_anonymizes_by_default() {
+        let clicks = vec![
+            click("email alice@example.com about roadmap", "b", 4),
+            click("email alice@example.com about roadmap", "a", 2),
+        ];
+        let selected = select_queries(&clicks, &options());
+        let documents = vec![document("b"), document("a"), document("c"), document("a")];
+
+        let (queries, documents, stats) = build_dataset(selected, documents, &options());
+
+        assert_eq!(
+            stats,
+            SampleStats {
+                queries: 1,
+                relevant_documents: 2,
+                documents: 3
+            }
+        );
+        assert_eq!(queries[0].text, "email [email] about roadmap");
+        let ids: Vec<&str> = documents.iter().map(|d| d.id.as_str()).collect();
+        assert_eq!(ids, vec!["doc_1", "doc_2", "doc_3"]);
+        assert_eq!(documents[0].content, "Written by [email]");
+        assert_eq!(documents[0].metadata.len(), 1);
+
+        // "b" has the most clicks; ids are assigned in original id order
+        let scores: HashMap<&str, f64> = queries[0]
+            .relevant_docs
+            .iter()
+            .map(|d| (d.doc_id.as_str(), d.relevance_score))
+            .collect();
+        assert_eq!(scores["doc_2"], 1.0);
+        assert_eq!(scores["doc_1"], 0.5);
+    }
+
+    #[test]
+    fn test_build_dataset_without_anonymization() {
+        let mut opts = options();
+        opts.anonymization = Anonymization {
+            redact_emails: false,
+            pseudonymize_ids: false,
+            strip_metadata: false,
+        };
+        let selected = select_queries(&[click("roadmap", "a", 1)], &opts);
+
+        let (queries, documents, _) = build_dataset(selected, vec![document("a")], &opts);
+
+        assert_eq!(queries[0].relevant_docs[0].doc_id, "a");
+        assert_eq!(documents[0].content, "Written by owner-a@example.com");
+        assert_eq!(
+            documents[0].metadata.get("path").map(String::as_str),
+            Some("/Shared/a")
+        );
+    }
+
+    #[test]
+    fn test_build_dataset_drops_queries_without_documents() {
+        let selected = select_queries(&[click("deleted doc", "gone", 5)], &options());
+
+        let (queries, documents, stats) = build_dataset(selected, vec![document("a")], &options());
+
+        assert!(queries.is_empty());
+        assert_eq!(documents.len(), 1);
+        assert_eq!(stats.relevant_documents, 0);
+    }
+}
@@ -3,19 +3,29 @@ use clap::{Parser, Subcommand};
 use tracing::{info, warn};
 
 mod config;
+mod corpus_sampler;
 mod datasets;
 mod evaluator;
 mod indexer;
 mod prepare_nq;
 mod reporter;
+#[cfg(feature = "resilience")]
+mod resilience;
 mod search_client;
+mod training_data;
 
 use config::BenchmarkConfig;
-use datasets::{BeirDataset, DatasetLoader, MsMarcoDataset, NaturalQuestionsDataset};
+use corpus_sampler::{Anonymization, CorpusSampleOptions, CorpusSampler};
+use datasets::{
+    BeirDataset, CustomDataset, DatasetLoader, MsMarcoDataset, NaturalQuestionsDataset,
+};
 use evaluator::BenchmarkEvaluator;
 use indexer::BenchmarkIndexer;
 use reporter::BenchmarkReporter;
+#[cfg(feature = "resilience")]
+use resilience::{ResilienceOptions, ResilienceRunner, Scenario};
 use search_client::OmniSearchClient;
+use training_data::{TrainingDataExporter, TrainingExportOptions, TripletFormat};
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
@@ -37,7 +47,7 @@ enum Commands {
         /// Configuration file path
         #[arg(short, long, default_value = "benchmarks/config/default.toml")]
         config: String,
-        /// Dataset to benchmark against
+        /// Dataset to benchmark against (beir, beir/<name>, msmarco, nq, custom)
         #[arg(short, long, default_value = "beir")]
         dataset: String,
         /// Search mode to test (fulltext, semantic, hybrid, all)
@@ -59,6 +69,111 @@ enum Commands {
         #[arg(short, long, default_value = "html")]
         format: String,
     },
+    /// Export reranker training triplets (query, positive, negative) mined
+    /// from search clicks
+    ExportTrainingData {
+        /// Omni database to read clicks from (defaults to $DATABASE_URL)
+        #[arg(long)]
+        database_url: Option<String>,
+        /// Output file
+        #[arg(
+            short,
+            long,
+            default_value = "benchmarks/results/training_triplets.jsonl"
+        )]
+        output: String,
+        /// Output format (jsonl, tsv)
+        #[arg(short, long, default_value = "jsonl")]
+        format: String,
+        /// Only use clicks from the last N days
+        #[arg(long, default_value = "90")]
+        since_days: i32,
+        /// Clicks a (query, document) pair needs to count as a positive
+        #[arg(long, default_value = "1")]
+        min_clicks: i64,
+        /// Sample at most this many queries
+        #[arg(long)]
+        max_queries: Option<usize>,
+        /// Negatives sampled for each positive
+        #[arg(long, default_value = "4")]
+        negatives_per_positive: usize,
+        /// Truncate passages to this many characters
+        #[arg(long, default_value = "2000")]
+        max_passage_chars: usize,
+        /// Also export documents that are not readable by everyone
+        #[arg(long)]
+        include_restricted: bool,
+        /// Sampling seed
+        #[arg(long, default_value = "42")]
+        seed: u64,
+    },
+    /// Sample real documents and queries from an Omni database into a
+    /// custom dataset, to reproduce relevance issues locally
+    SampleCorpus {
+        /// Omni database to sample from (defaults to $DATABASE_URL)
+        #[arg(long)]
+        database_url: Option<String>,
+        /// Output directory, read by `run --dataset custom`
+        #[arg(short, long, default_value = "benchmarks/data/custom")]
+        output_dir: String,
+        /// Target number of documents
+        #[arg(long, default_value = "1000")]
+        num_documents: usize,
+        /// Number of queries, including the ones passed with --query
+        #[arg(long, default_value = "100")]
+        num_queries: usize,
+        /// Query that must be in the sample (repeatable)
+        #[arg(long = "query")]
+        queries: Vec<String>,
+        /// Only use clicks from the last N days
+        #[arg(long, default_value = "90")]
+        since_days: i32,
+        /// Clicks a document needs to count as relevant for a query
+        #[arg(long, default_value = "1")]
+        min_clicks: i64,
+        /// Truncate document content to this many characters
+        #[arg(long, default_value = "20000")]
+        max_content_chars: usize,
+        /// Also sample documents that are not readable by everyone
+        #[arg(long)]
+        include_restricted: bool,
+        /// Keep email addresses in queries and documents
+        #[arg(long)]
+        keep_emails: bool,
+        /// Keep the original document ids
+        #[arg(long)]
+        keep_document_ids: bool,
+        /// Keep document URLs and paths
+        #[arg(long)]
+        keep_metadata: bool,
+        /// Sampling seed
+        #[arg(long, default_value = "42")]
+        seed: u64,
+    },
+    /// Inject faults into the event queue and check that retries, dead
+    /// lettering and stale recovery still resolve every event
+    #[cfg(feature = "resilience")]
+    Resilience {
+        /// Dedicated Omni database whose queue is drained (defaults to
+        /// $DATABASE_URL)
+        #[arg(long)]
+        database_url: Option<String>,
+        /// Scenario to run (retries, dead-letter, stale-recovery, all)
+        #[arg(short, long, default_value = "all")]
+        scenario: String,
+        /// Events enqueued per scenario
+        #[arg(long, default_value = "200")]
+        events: usize,
+        /// Events dequeued per batch
+        #[arg(long, default_value = "20")]
+        batch_size: i32,
+        /// Seconds before a claimed event is reclaimed as stale
+        #[arg(long, default_value = "1")]
+        stale_timeout_secs: i32,
+        /// Give up on a scenario after this many seconds
+        #[arg(long, default_value = "300")]
+        max_duration_secs: u64,
+    },
 }
 
 #[tokio::main]
@@ -97,6 +212,94 @@ async fn main() -> Result<()> {
             );
             generate_report(results_dir, format).await?;
         }
+        Commands::ExportTrainingData {
+            database_url,
+            output,
+            format,
+            since_days,
+            min_clicks,
+            max_queries,
+            negatives_per_positive,
+            max_passage_chars,
+            include_restricted,
+            seed,
+        } => {
+            let database_url = match database_url {
+                Some(url) => url.clone(),
+                None => std::env::var("DATABASE_URL")
+                    .map_err(|_| anyhow::anyhow!("--database-url or DATABASE_URL is required"))?,
+            };
+            let options = TrainingExportOptions {
+                since_days: *since_days,
+                min_clicks: *min_clicks,
+                max_queries: *max_queries,
+                negatives_per_positive: *negatives_per_positive,
+                max_passage_chars: *max_passage_chars,
+                include_restricted: *include_restricted,
+                seed: *seed,
+                format: TripletFormat::parse(format)?,
+            };
+            export_training_data(&database_url, &options, output).await?;
+        }
+        Commands::SampleCorpus {
+            database_url,
+            output_dir,
+            num_documents,
+            num_queries,
+            queries,
+            since_days,
+            min_clicks,
+            max_content_chars,
+            include_restricted,
+            keep_emails,
+            keep_document_ids,
+            keep_metadata,
+            seed,
+        } => {
+            let database_url = match database_url {
+                Some(url) => url.clone(),
+                None => std::env::var("DATABASE_URL")
+                    .map_err(|_| anyhow::anyhow!("--database-url or DATABASE_URL is required"))?,
+            };
+            let options = CorpusSampleOptions {
+                num_documents: *num_documents,
+                num_queries: *num_queries,
+                queries: queries.clone(),
+                since_days: *since_days,
+                min_clicks: *min_clicks,
+                max_content_chars: *max_content_chars,
+                include_restricted: *include_restricted,
+                anonymization: Anonymization {
+                    redact_emails: !keep_emails,
+                    pseudonymize_ids: !keep_document_ids,
+                    strip_metadata: !keep_metadata,
+                },
+                seed: *seed,
+            };
+            sample_corpus(&database_url, &options, output_dir).await?;
+        }
+        #[cfg(feature = "resilience")]
+        Commands::Resilience {
+            database_url,
+            scenario,
+            events,
+            batch_size,
+            stale_timeout_secs,
+            max_duration_secs,
+        } => {
+            let database_url = match database_url {
+                Some(url) => url.clone(),
+                None => std::env::var("DATABASE_URL")
+                    .map_err(|_| anyhow::anyhow!("--database-url or DATABASE_URL is required"))?,
+            };
+            let options = ResilienceOptions {
+                events: *events,
+                batch_size: *batch_size,
+                stale_timeout_secs: *stale_timeout_secs,
+                max_duration: std::time::Duration::from_secs(*max_duration_secs),
+            };
+            run_resilience(&database_url, &Scenario::parse(scenario)?, &options).await?;
+        }
     }
 
     Ok(())
@@ -210,6 +413,16 @@ async fn run_benchmarks(
                         NaturalQuestionsDataset::download_and_prepare(&config.datasets.nq).await?;
                     Box::new(nq_dataset)
                 }
+                "custom" => {
+                    let custom = &config.datasets.custom;
+                    let custom_dataset = CustomDataset::new(custom.data_dir.clone())
+                        .with_synthetic_generation(
+                            custom.generate_synthetic,
+                            custom.num_synthetic_queries,
+                        )
+                        .with_enterprise_domains(custom.enterprise_domains.clone());
+                    Box::new(custom_dataset)
+                }
                 _ => return Err(anyhow::anyhow!("Unsupported dataset: {}", dataset)),
             }
         };
@@ -267,6 +480,85 @@ async fn run_benchmarks(
     Ok(())
 }
 
+async fn export_training_data(
+    database_url: &str,
+    options: &TrainingExportOptions,
+    output: &str,
+) -> Result<()> {
+    if let Some(parent) = std::path::Path::new(output).parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let exporter = TrainingDataExporter::new(database_url).await?;
+    let stats = exporter.export(options, output).await?;
+
+    info!(
+        "Exported {} triplets from {} positives across {} queries to {}",
+        stats.triplets, stats.positives, stats.queries, output
+    );
+    if !options.include_restricted {
+        info!("Only documents readable by everyone were exported");
+    }
+    Ok(())
+}
+
+async fn sample_corpus(
+    database_url: &str,
+    options: &CorpusSampleOptions,
+    output_dir: &str,
+) -> Result<()> {
+    let sampler = CorpusSampler::new(database_url).await?;
+    let stats = sampler.sample(options, output_dir).await?;
+
+    info!(
+        "Sampled {} queries and {} documents ({} relevant) to {}",
+        stats.queries, stats.documents, stats.relevant_documents, output_dir
+    );
+    if options.anonymization != Anonymization::default() {
+        warn!("Sample is not fully anonymized; do not share it outside your organization");
+    }
+    Ok(())
+}
+
+#[cfg(feature = "resilience")]
+async fn run_resilience(
+    database_url: &str,
+    scenarios: &[Scenario],
+    options: &ResilienceOptions,
+) -> Result<()> {
+    let runner = ResilienceRunner::new(database_url).await?;
+
+    let mut reports = Vec::new();
+    for scenario in scenarios {
+        let report = runner.run(*scenario, options).await?;
+        match &report.failure {
+            None => info!(
+                "Scenario {} passed in {}ms: {} completed, {} dead-lettered, {} stale recovered, {} faults injected",
+                report.scenario,
+                report.elapsed_ms,
+                report.completed,
+                report.dead_letter,
+                report.stale_recovered,
+                report.injected_faults
+            ),
+            Some(failure) => warn!("Scenario {} failed: {}", report.scenario, failure),
+        }
+        reports.push(report);
+    }
+
+    let timestamp = chrono::Utc::now().format("%Y%m%d_%H%M%S");
+    let results_file = format!("benchmarks/results/resilience_{}_results.json", timestamp);
+    std::fs::create_dir_all("benchmarks/results")?;
+    std::fs::write(&results_file, serde_json::to_string_pretty(&reports)?)?;
+    info!("Results saved to: {}", results_file);
+
+    let failed = reports.iter().filter(|report| !report.passed).count();
+    if failed > 0 {
+        return Err(anyhow::anyhow!("{} resilience scenario(s) failed", failed));
+    }
+    Ok(())
+}
+
 async fn generate_report(results_dir: &str, format: &str) -> Result<()> {
     let reporter = BenchmarkReporter::new(results_dir.to_string());
 
@@ -0,0 +1,313 @@
+//! Resilience scenarios for the connector event pipeline.
+//!
+//! Each scenario enqueues a batch of events and drains it with a worker loop
+//! shaped like the indexer's: dequeue, process, mark each event completed or
+//! failed, requeue failed events and reclaim stale ones. Faults are injected
+//! into the queue and into processing, which stands in for the indexer's AI
+//! service calls, and the scenario checks every event still ends up where the
+//! pipeline's retry, dead-letter and stale recovery rules say it should.
+//!
+//! The worker drains the whole queue, so scenarios must run against a
+//! dedicated database with the Omni schema and no pending events.
+
+use anyhow::Result;
+use serde::Serialize;
+use shared::fault_injection::{self, FaultConfig, FaultTarget};
+use shared::models::ConnectorEvent;
+use shared::queue::EventQueue;
+use shared::utils::generate_ulid;
+use sqlx::{Pool, Postgres};
+use std::time::{Duration, Instant};
+use tracing::{debug, info};
+
+const ENQUEUE_CHUNK_SIZE: usize = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scenario {
+    /// Processing fails some of the time; failed events are retried until
+    /// they succeed or run out of retries.
+    Retries,
+    /// Processing always fails; every event ends in the dead letter queue.
+    DeadLetter,
+    /// Workers drop part of each batch and lose status updates; stranded
+    /// events are reclaimed and completed.
+    StaleRecovery,
+}
+
+impl Scenario {
+    pub const ALL: [Scenario; 3] = [
+        Scenario::Retries,
+        Scenario::DeadLetter,
+        Scenario::StaleRecovery,
+    ];
+
+    pub fn parse(value: &str) -> Result<Vec<Self>> {
+        match value {
+            "all" => Ok(Self::ALL.to_vec()),
+            "retries" => Ok(vec![Scenario::Retries]),
+            "dead-letter" => Ok(vec![Scenario::DeadLetter]),
+            "stale-recovery" => Ok(vec![Scenario::StaleRecovery]),
+            _ => Err(anyhow::anyhow!("Unsupported scenario: {}", value)),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Scenario::Retries => "retries",
+            Scenario::DeadLetter => "dead-letter",
+            Scenario::StaleRecovery => "stale-recovery",
+        }
+    }
+
+    fn faults(&self) -> Vec<(FaultTarget, FaultConfig)> {
+        match self {
+            Scenario::Retries => vec![(
+                FaultTarget::Ai,
+                FaultConfig {
+                    error_rate: 0.3,
+                    delay_rate: 0.1,
+                    delay_ms: 20,
+                    ..Default::default()
+                },
+            )],
+            Scenario::DeadLetter => vec![(
+                FaultTarget::Ai,
+                FaultConfig {
+                    error_rate: 1.0,
+                    ..Default::default()
+                },
+            )],
+            Scenario::StaleRecovery => vec![(
+                FaultTarget::Queue,
+                FaultConfig {
+                    error_rate: 0.1,
+                    partial_rate: 0.3,
+                    ..Default::default()
+                },
+            )],
+        }
+    }
+
+    /// Why the outcome is not what the scenario expects, if it is not.
+    fn check(&self, report: &ScenarioReport) -> Option<String> {
+        if report.unresolved > 0 {
+            return Some(format!(
+                "{} events were neither completed nor dead-lettered",
+                report.unresolved
+            ));
+        }
+        match self {
+            Scenario::Retries if report.retry_attempts == 0 => {
+                Some("No event was retried".to_string())
+            }
+            Scenario::Retries if report.completed == 0 => {
+                Some("No event completed after retrying".to_string())
+            }
+            Scenario::DeadLetter if report.dead_letter != report.events => Some(format!(
+                "{} of {} events were dead-lettered",
+                report.dead_letter, report.events
+            )),
+            Scenario::DeadLetter if !report.retries_exhausted => {
+                Some("Events were dead-lettered before using up their retries".to_string())
+            }
+            Scenario::StaleRecovery if report.stale_recovered == 0 => {
+                Some("No stale event was recovered".to_string())
+            }
+            Scenario::StaleRecovery if report.completed != report.events => Some(format!(
+                "{} of {} events completed",
+                report.completed, report.events
+            )),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ResilienceOptions {
+    pub events: usize,
+    pub batch_size: i32,
+    /// Events claimed for longer than this are reclaimed as stale.
+    pub stale_timeout_secs: i32,
+    /// Give up on draining the queue after this long.
+    pub max_duration: Duration,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScenarioReport {
+    pub scenario: &'static str,
+    pub events: i64,
+    pub completed: i64,
+    pub dead_letter: i64,
+    /// Events still pending, processing or failed when the scenario ended.
+    pub unresolved: i64,
+    /// Failed processing attempts recorded on the events.
+    pub retry_attempts: i64,
+    /// Whether every dead-lettered event had used up its retries.
+    pub retries_exhausted: bool,
+    pub stale_recovered: i64,
+    pub injected_faults: u64,
+    pub elapsed_ms: u128,
+    pub passed: bool,
+    pub failure: Option<String>,
+}
+
+#[derive(Debug, Default, sqlx::FromRow)]
+struct EventCounts {
+    completed: i64,
+    dead_letter: i64,
+    unresolved: i64,
+    retry_attempts: i64,
+    retries_exhausted: bool,
+}
+
+pub struct ResilienceRunner {
+    db_pool: Pool<Postgres>,
+    queue: EventQueue,
+}
+
+impl ResilienceRunner {
+    pub async fn new(database_url: &str) -> Result<Self> {
+        let db_pool = sqlx::postgres::PgPoolOptions::new()
+            .max_connections(4)
+            .connect(database_url)
+            .await?;
+        let queue = EventQueue::new(db_pool.clone());
+
+        Ok(Self { db_pool, queue })
+    }
+
+    pub async fn run(
+        &self,
+        scenario: Scenario,
+        options: &ResilienceOptions,
+    ) -> Result<ScenarioReport> {
+        fault_injection::reset();
+        let pending = self.queue.get_pending_count().await?;
+        if pending > 0 {
+            return Err(anyhow::anyhow!(
+                "Queue has {} pending events; run resilience scenarios against a dedicated database",
+                pending
+            ));
+        }
+
+        let source_id = generate_ulid();
+        self.enqueue_events(&source_id, options.events).await?;
+
+        info!("Running resilience scenario {}", scenario.name());
+        let injected_before = injected_faults();
+        for (target, config) in scenario.faults() {
+            fault_injection::configure(target, config);
+        }
+        let started = Instant::now();
+        let drained = self.drain(&source_id, options).await;
+        fault_injection::reset();
+        let elapsed = started.elapsed();
+        let stale_recovered = drained?;
+
+        let counts = self.event_counts(&source_id).await?;
+        sqlx::query("DELETE FROM connector_events_queue WHERE source_id = $1")
+            .bind(&source_id)
+            .execute(&self.db_pool)
+            .await?;
+
+        let mut report = ScenarioReport {
+            scenario: scenario.name(),
+            events: options.events as i64,
+            completed: counts.completed,
+            dead_letter: counts.dead_letter,
+            unresolved: counts.unresolved,
+            retry_attempts: counts.retry_attempts,
+            retries_exhausted: counts.retries_exhausted,
+            stale_recovered,
+            injected_faults: injected_faults() - injected_before,
+            elapsed_ms: elapsed.as_millis(),
+            passed: true,
+            failure: None,
+        };
+        report.failure = scenario.check(&report);
+        report.passed = report.failure.is_none();
+        Ok(report)
+    }
+
+    async fn enqueue_events(&self, source_id: &str, count: usize) -> Result<()> {
+        let sync_run_id = generate_ulid();
+        let events: Vec<ConnectorEvent> = (0..count)
+            .map(|i| ConnectorEvent::DocumentDeleted {
+                sync_run_id: sync_run_id.clone(),
+                source_id: source_id.to_string(),
+                document_id: format!("resilience-{}", i),
+            })
+            .collect();
+        for chunk in events.chunks(ENQUEUE_CHUNK_SIZE) {
+            self.queue.enqueue_batch(source_id, chunk).await?;
+        }
+        Ok(())
+    }
+
+    /// Work the queue until every event of the source is completed or
+    /// dead-lettered, returning the number of stale events reclaimed.
+    async fn drain(&self, source_id: &str, options: &ResilienceOptions) -> Result<i64> {
+        let mut stale_recovered = 0;
+        let deadline = Instant::now() + options.max_duration;
+        while Instant::now() < deadline {
+            let items = match self.queue.dequeue_batch(options.batch_size).await {
+                Ok(items) => items,
+                Err(e) => {
+                    debug!("Dequeue failed: {}", e);
+                    Vec::new()
+                }
+            };
+            for item in &items {
+                let processed = fault_injection::inject(FaultTarget::Ai, "process_event").await;
+                let marked = match processed {
+                    Ok(()) => self.queue.mark_completed(&item.id).await,
+                    Err(e) => self.queue.mark_failed(&item.id, &e.to_string()).await,
+                };
+                // The event stays claimed until stale recovery reclaims it
+                if let Err(e) = marked {
+                    debug!("Failed to record outcome of event {}: {}", item.id, e);
+                }
+            }
+
+            self.queue.retry_failed_events().await?;
+            stale_recovered += self
+                .queue
+                .recover_stale_processing_items(options.stale_timeout_secs)
+                .await?;
+
+            if self.event_counts(source_id).await?.unresolved == 0 {
+                break;
+            }
+            if items.is_empty() {
+                tokio::time::sleep(Duration::from_millis(200)).await;
+            }
+        }
+        Ok(stale_recovered)
+    }
+
+    async fn event_counts(&self, source_id: &str) -> Result<EventCounts> {
+        let counts = sqlx::query_as::<_, EventCounts>(
+            r#"
+            SELECT COUNT(*) FILTER (WHERE status = 'completed') AS completed,
+                   COUNT(*) FILTER (WHERE status = 'dead_letter') AS dead_letter,
+                   COUNT(*) FILTER (WHERE status IN ('pending', 'processing', 'failed')) AS unresolved,
+                   COALESCE(SUM(retry_count), 0)::BIGINT AS retry_attempts,
+                   COALESCE(BOOL_AND(retry_count >= max_retries) FILTER (WHERE status = 'dead_letter'), TRUE) AS retries_exhausted
+            FROM connector_events_queue
+            WHERE source_id = $1
+            "#,
+        )
+        .bind(source_id)
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        Ok(counts)
+    }
+}
+
+fn injected_faults() -> u64 {
+    FaultTarget::ALL
+        .into_iter()
+        .map(fault_injection::injected_count)
+        .sum()
+}
@@ -83,12 +83,14 @@ pub struct IndexStats {
 pub fn create_search_request(query: String, search_mode: SearchMode) -> SearchRequest {
     SearchRequest {
         query,
+        query_ast: None,
         mode: Some(search_mode),
         limit: Some(20),
         offset: Some(0),
         source_types: None,
         content_types: None,
         attribute_filters: None,
+        filter: None,
         include_facets: Some(false),
         user_email: None,
         user_id: None,
@@ -98,8 +100,27 @@ pub fn create_search_request(query: String, search_mode: SearchMode) -> SearchRe
         document_id: None,
         document_content_start_line: None,
         document_content_end_line: None,
+        fields: None,
         date_filter: None,
         person_filters: None,
+        collection_id: None,
+        debug: None,
+        auto_correct: None,
+        source_boosts: None,
+        recency_decay: None,
+        dedupe: None,
+        embedding_namespace: None,
+        rerank: None,
+        rerank_top_n: None,
+        content_kind: None,
+        language: None,
+        understand_query: None,
+        facets: None,
+        facet_filters: None,
+        intent: None,
+        translated_query: None,
+        text_query: None,
+        filter_sql: None,
     }
 }
 
@@ -0,0 +1,407 @@
+//! Export of reranker training triplets mined from search clicks.
+//!
+//! Each clicked result is a positive for the query it was clicked from.
+//! Negatives are documents that were clicked for other queries but never for
+//! this one, preferring documents from the same source type as the positive
+//! so the pairs are not trivially separable. Clicks do not record what else
+//! was shown, so these are approximate negatives rather than skipped results.
+//!
+//! By default only documents readable by everyone are exported, and email
+//! addresses are redacted from queries and passages. No user or document ids
+//! are written.
+
+use anyhow::Result;
+use serde::Serialize;
+use sqlx::{Pool, Postgres};
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use tracing::info;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TripletFormat {
+    /// `{"query", "positive", "negative"}` objects, one per line, as read by
+    /// sentence-transformers.
+    Jsonl,
+    /// `query \t positive \t negative`, as in MS MARCO's triples.train.tsv.
+    Tsv,
+}
+
+impl TripletFormat {
+    pub fn parse(value: &str) -> Result<Self> {
+        match value {
+            "jsonl" => Ok(TripletFormat::Jsonl),
+            "tsv" => Ok(TripletFormat::Tsv),
+            _ => Err(anyhow::anyhow!("Unsupported format: {}", value)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TrainingExportOptions {
+    /// Only clicks from the last this many days are used.
+    pub since_days: i32,
+    /// A (query, document) pair needs at least this many clicks to count as
+    /// a positive.
+    pub min_clicks: i64,
+    /// Queries sampled when there are more; all queries when `None`.
+    pub max_queries: Option<usize>,
+    pub negatives_per_positive: usize,
+    /// Passages are truncated to this many characters.
+    pub max_passage_chars: usize,
+    /// Also export documents that are not readable by everyone.
+    pub include_restricted: bool,
+    /// Seed for query and negative sampling, so exports are reproducible.
+    pub seed: u64,
+    pub format: TripletFormat,
+}
+
+/// A document clicked for a normalized query.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ClickedDocument {
+    pub query: String,
+    pub document_id: String,
+    pub source_type: String,
+    pub title: String,
+    pub content: String,
+    pub clicks: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Triplet {
+    pub query: String,
+    pub positive: String,
+    pub negative: String,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ExportStats {
+    pub queries: usize,
+    pub positives: usize,
+    pub triplets: usize,
+}
+
+pub struct TrainingDataExporter {
+    db_pool: Pool<Postgres>,
+}
+
+impl TrainingDataExporter {
+    pub async fn new(database_url: &str) -> Result<Self> {
+        let db_pool = sqlx::postgres::PgPoolOptions::new()
+            .max_connections(2)
+            .connect(database_url)
+            .await?;
+
+        Ok(Self { db_pool })
+    }
+
+    pub async fn export(
+        &self,
+        options: &TrainingExportOptions,
+        output: &str,
+    ) -> Result<ExportStats> {
+        let clicked = self.load_clicked_documents(options).await?;
+        info!(
+            "Loaded {} clicked (query, document) pairs from the last {} days",
+            clicked.len(),
+            options.since_days
+        );
+
+        let (triplets, stats) = build_triplets(&clicked, options);
+
+        let mut writer = BufWriter::new(File::create(output)?);
+        for triplet in &triplets {
+            match options.format {
+                TripletFormat::Jsonl => {
+                    serde_json::to_writer(&mut writer, triplet)?;
+                    writeln!(writer)?;
+                }
+                TripletFormat::Tsv => writeln!(
+                    writer,
+                    "{}\t{}\t{}",
+                    tsv_field(&triplet.query),
+                    tsv_field(&triplet.positive),
+                    tsv_field(&triplet.negative)
+                )?,
+            }
+        }
+        writer.flush()?;
+
+        Ok(stats)
+    }
+
+    async fn load_clicked_documents(
+        &self,
+        options: &TrainingExportOptions,
+    ) -> Result<Vec<ClickedDocument>> {
+        let rows = sqlx::query_as::<_, ClickedDocument>(
+            r#"
+            SELECT
+                regexp_replace(lower(btrim(c.query)), '\s+', ' ', 'g') AS query,
+                d.id AS document_id,
+                c.source_type::text AS source_type,
+                d.title,
+                LEFT(COALESCE(d.content, ''), $2) AS content,
+                COUNT(*) AS clicks
+            FROM search_clicks c
+            JOIN documents d ON d.id = c.document_id
+            WHERE c.created_at > NOW() - make_interval(days => $1)
+              AND ($3 OR COALESCE((d.permissions->>'public')::boolean, false))
+            GROUP BY 1, d.id, c.source_type, d.title, d.content
+            "#,
+        )
+        .bind(options.since_days)
+        .bind(options.max_passage_chars as i32)
+        .bind(options.include_restricted)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(rows)
+    }
+}
+
+/// Mine triplets from clicked documents. Deterministic for a given seed.
+pub fn build_triplets(
+    clicked: &[ClickedDocument],
+    options: &TrainingExportOptions,
+) -> (Vec<Triplet>, ExportStats) {
+    let mut rng = SplitMix64::new(options.seed);
+
+    // Every clicked document is a candidate negative for other queries
+    let mut documents: HashMap<&str, &ClickedDocument> = HashMap::new();
+    let mut clicked_for: HashMap<String, HashSet<&str>> = HashMap::new();
+    let mut positives: HashMap<String, Vec<&ClickedDocument>> = HashMap::new();
+    for doc in clicked {
+        let query = redact_emails(&doc.query);
+        if query.is_empty() {
+            continue;
+        }
+        documents.entry(doc.document_id.as_str()).or_insert(doc);
+        clicked_for
+            .entry(query.clone())
+            .or_default()
+            .insert(doc.document_id.as_str());
+        if doc.clicks >= options.min_clicks {
+            positives.entry(query).or_default().push(doc);
+        }
+    }
+
+    let mut candidates: Vec<&ClickedDocument> = documents.into_values().collect();
+    candidates.sort_by(|a, b| a.document_id.cmp(&b.document_id));
+
+    let mut queries: Vec<String> = positives.keys().cloned().collect();
+    queries.sort();
+    rng.shuffle(&mut queries);
+    if let Some(max) = options.max_queries {
+        queries.truncate(max);
+    }
+    queries.sort();
+
+    let mut stats = ExportStats {
+        queries: queries.len(),
+        ..Default::default()
+    };
+    let mut triplets = Vec::new();
+    for query in &queries {
+        let excluded = &clicked_for[query];
+        let mut query_positives = positives[query].clone();
+        query_positives.sort_by(|a, b| a.document_id.cmp(&b.document_id));
+
+        for positive in query_positives {
+            stats.positives += 1;
+
+            let (mut same_type, mut other_type): (Vec<&ClickedDocument>, Vec<&ClickedDocument>) =
+                candidates
+                    .iter()
+                    .filter(|doc| !excluded.contains(doc.document_id.as_str()))
+                    .partition(|doc| doc.source_type == positive.source_type);
+            rng.shuffle(&mut same_type);
+            rng.shuffle(&mut other_type);
+
+            for negative in same_type
+                .into_iter()
+                .chain(other_type)
+                .take(options.negatives_per_positive)
+            {
+                triplets.push(Triplet {
+                    query: query.clone(),
+                    positive: passage(positive),
+                    negative: passage(negative),
+                });
+            }
+        }
+    }
+    stats.triplets = triplets.len();
+
+    (triplets, stats)
+}
+
+fn passage(doc: &ClickedDocument) -> String {
+    let text = if doc.content.is_empty() {
+        doc.title.clone()
+    } else {
+        format!("{}\n{}", doc.title, doc.content)
+    };
+    redact_emails(&text)
+}
+
+/// Replace anything shaped like an email address with `[email]`.
+pub fn redact_emails(text: &str) -> String {
+    text.split_inclusive(char::is_whitespace)
+        .map(|token| {
+            let word = token.trim_end();
+            let trailing = &token[word.len()..];
+            let core = word.trim_matches(|c: char| !c.is_alphanumeric());
+            let looks_like_email = core
+                .split_once('@')
+                .is_some_and(|(local, domain)| !local.is_empty() && domain.contains('.'));
+            if looks_like_email {
+                format!("{}{}", word.replacen(core, "[email]", 1), trailing)
+            } else {
+                token.to_string()
+            }
+        })
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+fn tsv_field(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| {
+            if c == '\t' || c == '\n' || c == '\r' {
+                ' '
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+/// Small seeded generator so sampling is reproducible without a rand
+/// dependency.
+pub(crate) struct SplitMix64(u64);
+
+impl SplitMix64 {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    pub(crate) fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = (self.next_u64() % (i as u64 + 1)) as usize;
+            items.swap(i, j);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clicked(query: &str, id: &str, source_type: &str, clicks: i64) -> ClickedDocument {
+        ClickedDocument {
+            query: query.to_string(),
+            document_id: id.to_string(),
+            source_type: source_type.to_string(),
+            title: format!("Title {}", id),
+            content: format!("Content of {}", id),
+            clicks,
+        }
+    }
+
+    fn options() -> TrainingExportOptions {
+        TrainingExportOptions {
+            since_days: 90,
+            min_clicks: 1,
+            max_queries: None,
+            negatives_per_positive: 2,
+            max_passage_chars: 2000,
+            include_restricted: false,
+            seed: 7,
+            format: TripletFormat::Jsonl,
+        }
+    }
+
+    #[test]
+    fn test_negatives_exclude_documents_clicked_for_the_query() {
+        let rows = vec![
+            clicked("vacation policy", "a", "google_drive", 3),
+            clicked("vacation policy", "b", "confluence", 1),
+            clicked("expense report", "c", "google_drive", 2),
+            clicked("expense report", "d", "slack", 1),
+        ];
+
+        let (triplets, stats) = build_triplets(&rows, &options());
+
+        assert_eq!(stats.queries, 2);
+        assert_eq!(stats.positives, 4);
+        for triplet in &triplets {
+            let positive_query_docs: Vec<&str> = rows
+                .iter()
+                .filter(|r| r.query == triplet.query)
+                .map(|r| r.title.as_str())
+                .collect();
+            assert!(
+                !positive_query_docs
+                    .iter()
+                    .any(|title| triplet.negative.starts_with(title)),
+                "{:?}",
+                triplet
+            );
+        }
+
+        // Same-source-type negatives are preferred
+        let first = triplets
+            .iter()
+            .find(|t| t.query == "vacation policy" && t.positive.starts_with("Title a"))
+            .unwrap();
+        assert!(first.negative.starts_with("Title c"));
+    }
+
+    #[test]
+    fn test_sampling_controls_are_deterministic() {
+        let rows: Vec<ClickedDocument> = (0..20)
+            .map(|i| {
+                clicked(
+                    &format!("query {}", i),
+                    &format!("doc{}", i),
+                    "slack",
+                    1 + i % 3,
+                )
+            })
+            .collect();
+        let mut opts = options();
+        opts.max_queries = Some(5);
+        opts.min_clicks = 2;
+
+        let (first, stats) = build_triplets(&rows, &opts);
+        let (second, _) = build_triplets(&rows, &opts);
+        assert_eq!(first, second);
+        assert_eq!(stats.queries, 5);
+        assert_eq!(first.len(), 5 * opts.negatives_per_positive);
+
+        opts.seed = 8;
+        let (reseeded, _) = build_triplets(&rows, &opts);
+        assert_ne!(first, reseeded);
+    }
+
+    #[test]
+    fn test_redact_emails() {
+        assert_eq!(
+            redact_emails("ask jane.doe@example.com, or (ops@corp.io) today"),
+            "ask [email], or ([email]) today"
+        );
+        assert_eq!(redact_emails("no @mentions here"), "no @mentions here");
+        assert_eq!(redact_emails("   "), "");
+    }
+}
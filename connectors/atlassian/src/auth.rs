@@ -1,13 +1,57 @@
-use anyhow::{Result, anyhow};
+use anyhow::{Context, Result, anyhow};
 use chrono::Utc;
-use omni_connector_sdk::SourceType;
+use omni_connector_sdk::{ServiceCredential, SourceType};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
 use tracing::{debug, info, warn};
 
-/// Atlassian service-account credentials. We use Bearer auth against the
-/// Atlassian API gateway exclusively — direct site URLs with Basic auth (the
-/// legacy user-API-token model) are not supported.
+pub const ATLASSIAN_OAUTH_AUTHORIZE_URL: &str = "https://auth.atlassian.com/authorize";
+pub const ATLASSIAN_OAUTH_TOKEN_URL: &str = "https://auth.atlassian.com/oauth/token";
+const ACCESSIBLE_RESOURCES_URL: &str = "https://api.atlassian.com/oauth/token/accessible-resources";
+
+/// Refresh OAuth access tokens this long before they expire so a request
+/// issued right before expiry doesn't 401 mid-flight.
+const OAUTH_REFRESH_MARGIN_SECS: i64 = 300;
+
+/// Scopes the Jira sync path needs. `offline_access` is what makes Atlassian
+/// issue a refresh token at all.
+pub const JIRA_OAUTH_SCOPES: &[&str] = &["read:jira-work", "read:jira-user", "offline_access"];
+
+/// Scopes the Confluence sync path needs. The classic scopes cover the
+/// `/rest/api` endpoints; the `/api/v2` space and page listings only accept
+/// granular scopes.
+pub const CONFLUENCE_OAUTH_SCOPES: &[&str] = &[
+    "read:confluence-content.all",
+    "read:confluence-space.summary",
+    "read:confluence-user",
+    "read:confluence-groups",
+    "search:confluence",
+    "read:space:confluence",
+    "read:page:confluence",
+    "offline_access",
+];
+
+/// Scopes an OAuth grant must carry to sync the given source type. `None`
+/// means both products (e.g. webhook renewal, which doesn't know the type).
+pub fn required_oauth_scopes(source_type: Option<&SourceType>) -> Vec<&'static str> {
+    let mut scopes: Vec<&'static str> = Vec::new();
+    if source_type != Some(&SourceType::Confluence) {
+        scopes.extend(JIRA_OAUTH_SCOPES);
+    }
+    if source_type != Some(&SourceType::Jira) {
+        scopes.extend(CONFLUENCE_OAUTH_SCOPES);
+    }
+    scopes.sort_unstable();
+    scopes.dedup();
+    scopes
+}
+
+/// Atlassian credentials. We use Bearer auth against the Atlassian API
+/// gateway exclusively — direct site URLs with Basic auth (the legacy
+/// user-API-token model) are not supported. The bearer is either a
+/// service-account token or, for orgs that block API tokens, an OAuth 2.0
+/// (3LO) access token.
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct AtlassianCredentials {
     /// Site domain, e.g. "company.atlassian.net". Used to fetch the cloud_id
@@ -36,6 +80,10 @@ pub struct AtlassianCredentials {
     /// credential class from the SA token above).
     #[serde(default)]
     pub org_admin_api_key: Option<String>,
+    /// OAuth 2.0 (3LO) token set. When present, requests authenticate with
+    /// its access token and `sa_token` is empty.
+    #[serde(default)]
+    pub oauth: Option<AtlassianOAuthCredentials>,
 }
 
 impl AtlassianCredentials {
@@ -48,9 +96,21 @@ impl AtlassianCredentials {
             sa_account_id: None,
             org_id: None,
             org_admin_api_key: None,
+            oauth: None,
         }
     }
 
+    pub fn from_oauth(domain: String, cloud_id: String, oauth: AtlassianOAuthCredentials) -> Self {
+        Self {
+            oauth: Some(oauth),
+            ..Self::new(domain, cloud_id, String::new())
+        }
+    }
+
+    pub fn is_oauth(&self) -> bool {
+        self.oauth.is_some()
+    }
+
     pub fn with_sa_account_id(mut self, account_id: String) -> Self {
         self.sa_account_id = Some(account_id);
         self
@@ -67,6 +127,9 @@ impl AtlassianCredentials {
     }
 
     pub fn is_valid(&self) -> bool {
+        if let Some(oauth) = &self.oauth {
+            return !oauth.needs_refresh();
+        }
         // SA tokens don't expire (until revoked), but we re-validate every
         // 24 hours to catch revocation / scope changes early.
         let now = Utc::now().timestamp_millis();
@@ -97,7 +160,10 @@ impl AtlassianCredentials {
     }
 
     pub fn get_bearer_auth_header(&self) -> String {
-        format!("Bearer {}", self.sa_token)
+        match &self.oauth {
+            Some(oauth) => format!("Bearer {}", oauth.access_token),
+            None => format!("Bearer {}", self.sa_token),
+        }
     }
 
     /// Bearer header for the org-admin API. Returns None when org-admin
@@ -109,6 +175,113 @@ impl AtlassianCredentials {
     }
 }
 
+/// OAuth 2.0 (3LO) token set in the shape the web app's OAuth callback writes
+/// to `service_credentials.credentials`. The access-token expiry and granted
+/// scopes live on the row (`expires_at`, `config.granted_scopes`) rather than
+/// in the credentials JSON, so they are skipped by serde and filled in by
+/// `from_service_credential`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AtlassianOAuthCredentials {
+    pub access_token: String,
+    #[serde(default)]
+    pub refresh_token: Option<String>,
+    #[serde(default)]
+    pub client_id: Option<String>,
+    #[serde(default)]
+    pub client_secret: Option<String>,
+    #[serde(default)]
+    pub token_uri: Option<String>,
+    /// Access-token expiry, unix seconds.
+    #[serde(skip)]
+    pub expires_at: Option<i64>,
+    #[serde(skip)]
+    pub granted_scopes: Vec<String>,
+}
+
+impl AtlassianOAuthCredentials {
+    pub fn from_service_credential(creds: &ServiceCredential) -> Result<Self> {
+        let mut oauth: Self = serde_json::from_value(creds.credentials.clone())
+            .context("Invalid Atlassian OAuth credentials")?;
+        oauth.expires_at = creds.expires_at.map(|t| t.unix_timestamp());
+        oauth.granted_scopes = creds
+            .config
+            .get("granted_scopes")
+            .and_then(|v| v.as_array())
+            .map(|scopes| {
+                scopes
+                    .iter()
+                    .filter_map(|s| s.as_str().map(String::from))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Ok(oauth)
+    }
+
+    /// True when the access token is expired or about to be. A missing
+    /// expiry is treated as still valid — the first 401 will surface it.
+    pub fn needs_refresh(&self) -> bool {
+        self.expires_at
+            .is_some_and(|exp| exp <= Utc::now().timestamp() + OAUTH_REFRESH_MARGIN_SECS)
+    }
+
+    pub fn token_endpoint(&self) -> &str {
+        self.token_uri
+            .as_deref()
+            .unwrap_or(ATLASSIAN_OAUTH_TOKEN_URL)
+    }
+
+    /// Merge the current tokens into an existing credentials JSON object so
+    /// fields we don't model (e.g. `token_type`, `org_admin_api_key`) survive
+    /// the write back through `ServiceCredentialsRepo::update_credentials`.
+    pub fn to_credentials_json(&self, existing: &JsonValue) -> JsonValue {
+        let mut merged = existing.as_object().cloned().unwrap_or_default();
+        merged.insert(
+            "access_token".to_string(),
+            JsonValue::String(self.access_token.clone()),
+        );
+        if let Some(refresh_token) = &self.refresh_token {
+            merged.insert(
+                "refresh_token".to_string(),
+                JsonValue::String(refresh_token.clone()),
+            );
+        }
+        JsonValue::Object(merged)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct OAuthTokenResponse {
+    access_token: String,
+    #[serde(default)]
+    refresh_token: Option<String>,
+    #[serde(default)]
+    expires_in: Option<i64>,
+    #[serde(default)]
+    scope: Option<String>,
+}
+
+/// A site the OAuth grant can reach, from
+/// `api.atlassian.com/oauth/token/accessible-resources`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AccessibleResource {
+    /// The site's cloud_id.
+    pub id: String,
+    /// Site URL, e.g. "https://company.atlassian.net".
+    pub url: String,
+    pub name: String,
+    #[serde(default)]
+    pub scopes: Vec<String>,
+}
+
+impl AccessibleResource {
+    pub fn domain(&self) -> &str {
+        self.url
+            .trim_start_matches("https://")
+            .trim_start_matches("http://")
+            .trim_end_matches('/')
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AtlassianUserResponse {
     #[serde(rename = "accountId")]
@@ -207,8 +380,23 @@ impl AuthManager {
         let cloud_id = self.fetch_cloud_id(domain).await?;
         debug!("Resolved cloud_id {} for site {}", cloud_id, domain);
 
-        let mut creds =
-            AtlassianCredentials::new(domain.to_string(), cloud_id, sa_token.to_string());
+        let creds = AtlassianCredentials::new(domain.to_string(), cloud_id, sa_token.to_string());
+        let sa_account_id = self.verify_identity(&creds, source_type).await?;
+
+        Ok(match sa_account_id {
+            Some(account_id) => creds.with_sa_account_id(account_id),
+            None => creds,
+        })
+    }
+
+    /// Hit `/myself` (Jira) and `/user/current` (Confluence) with the
+    /// credentials' bearer token and return the caller's accountId. Fails if
+    /// either product rejects the token or the two disagree on identity.
+    async fn verify_identity(
+        &self,
+        creds: &AtlassianCredentials,
+        source_type: Option<&SourceType>,
+    ) -> Result<Option<String>> {
         let auth_header = creds.get_bearer_auth_header();
 
         let validate_jira = source_type != Some(&SourceType::Confluence);
@@ -241,7 +429,7 @@ impl AuthManager {
                 jira_user.display_name, jira_user.account_id
             );
             if jira_user.active == Some(false) {
-                return Err(anyhow!("Service account is not active"));
+                return Err(anyhow!("Atlassian account is not active"));
             }
             info!(
                 "Validated Jira access as {} (accountId {}, type {:?})",
@@ -280,7 +468,7 @@ impl AuthManager {
                 if confluence_user.account_id != *jira_id {
                     return Err(anyhow!(
                         "Account ID mismatch between Jira and Confluence \
-                         (Jira: {}, Confluence: {}) — the SA token must \
+                         (Jira: {}, Confluence: {}) — the token must \
                          resolve to the same identity in both products.",
                         jira_id,
                         confluence_user.account_id
@@ -294,10 +482,176 @@ impl AuthManager {
             }
         }
 
-        if let Some(account_id) = sa_account_id {
-            creds = creds.with_sa_account_id(account_id);
+        Ok(sa_account_id)
+    }
+
+    /// Exchange a refresh token for a new access token. Atlassian rotates
+    /// refresh tokens, so the returned credentials carry the new refresh
+    /// token (when issued) and must be persisted before the old one lapses.
+    pub async fn refresh_oauth_token(
+        &self,
+        oauth: &AtlassianOAuthCredentials,
+    ) -> Result<AtlassianOAuthCredentials> {
+        let refresh_token = oauth
+            .refresh_token
+            .as_deref()
+            .ok_or_else(|| anyhow!("Atlassian OAuth credentials have no refresh_token"))?;
+        let client_id = oauth
+            .client_id
+            .as_deref()
+            .ok_or_else(|| anyhow!("Atlassian OAuth credentials have no client_id"))?;
+        let client_secret = oauth
+            .client_secret
+            .as_deref()
+            .ok_or_else(|| anyhow!("Atlassian OAuth credentials have no client_secret"))?;
+
+        info!("Refreshing Atlassian OAuth access token");
+        let response = self
+            .client
+            .post(oauth.token_endpoint())
+            .json(&serde_json::json!({
+                "grant_type": "refresh_token",
+                "client_id": client_id,
+                "client_secret": client_secret,
+                "refresh_token": refresh_token,
+            }))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(anyhow!(
+                "Failed to refresh Atlassian OAuth token: HTTP {} - {}",
+                status,
+                error_text
+            ));
+        }
+
+        let token: OAuthTokenResponse = response.json().await?;
+        let mut refreshed = oauth.clone();
+        refreshed.access_token = token.access_token;
+        if token.refresh_token.is_some() {
+            refreshed.refresh_token = token.refresh_token;
+        }
+        refreshed.expires_at = token.expires_in.map(|secs| Utc::now().timestamp() + secs);
+        if let Some(scope) = token.scope {
+            refreshed.granted_scopes = scope.split_whitespace().map(String::from).collect();
+        }
+        Ok(refreshed)
+    }
+
+    /// List the sites the OAuth grant can reach, with the scopes granted on
+    /// each.
+    pub async fn fetch_accessible_resources(
+        &self,
+        access_token: &str,
+    ) -> Result<Vec<AccessibleResource>> {
+        let response = self
+            .client
+            .get(ACCESSIBLE_RESOURCES_URL)
+            .header("Authorization", format!("Bearer {}", access_token))
+            .header("Accept", "application/json")
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(anyhow!(
+                "Failed to list Atlassian accessible resources: HTTP {} - {}",
+                status,
+                error_text
+            ));
+        }
+
+        Ok(response.json().await?)
+    }
+
+    /// Check that `granted` covers everything the source type needs. An empty
+    /// grant list means the scopes are unknown (the token response may omit
+    /// them), in which case the API calls themselves are the check.
+    pub fn validate_oauth_scopes(
+        granted: &[String],
+        source_type: Option<&SourceType>,
+    ) -> Result<()> {
+        if granted.is_empty() {
+            return Ok(());
+        }
+        let missing: Vec<&str> = required_oauth_scopes(source_type)
+            .into_iter()
+            .filter(|scope| !granted.iter().any(|g| g == scope))
+            .collect();
+        if !missing.is_empty() {
+            return Err(anyhow!(
+                "Atlassian OAuth grant is missing required scopes: {}",
+                missing.join(", ")
+            ));
         }
+        Ok(())
+    }
 
+    /// Resolve the site an OAuth grant should sync and verify the token
+    /// against it. `domain` picks the site when the grant covers several;
+    /// otherwise the first accessible site is used.
+    pub async fn validate_oauth_credentials(
+        &self,
+        domain: Option<&str>,
+        oauth: AtlassianOAuthCredentials,
+        source_type: Option<&SourceType>,
+    ) -> Result<AtlassianCredentials> {
+        let resources = self.fetch_accessible_resources(&oauth.access_token).await?;
+        let resource = match domain {
+            Some(domain) => resources
+                .iter()
+                .find(|r| r.domain().eq_ignore_ascii_case(domain))
+                .ok_or_else(|| {
+                    anyhow!(
+                        "Atlassian OAuth grant does not include site {} (accessible: {})",
+                        domain,
+                        resources
+                            .iter()
+                            .map(|r| r.domain())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )
+                })?,
+            None => {
+                if resources.len() > 1 {
+                    warn!(
+                        "Atlassian OAuth grant covers {} sites and no domain is configured; using {}",
+                        resources.len(),
+                        resources[0].url
+                    );
+                }
+                resources
+                    .first()
+                    .ok_or_else(|| anyhow!("Atlassian OAuth grant has no accessible sites"))?
+            }
+        };
+
+        // Prefer the scopes granted on this site; fall back to what the token
+        // endpoint reported at exchange/refresh time.
+        let granted = if resource.scopes.is_empty() {
+            &oauth.granted_scopes
+        } else {
+            &resource.scopes
+        };
+        Self::validate_oauth_scopes(granted, source_type)?;
+
+        info!(
+            "Validating Atlassian OAuth credentials for site: {}",
+            resource.url
+        );
+        let creds = AtlassianCredentials::from_oauth(
+            resource.domain().to_string(),
+            resource.id.clone(),
+            oauth,
+        );
+        // The OAuth identity is the consenting user, not a service account,
+        // so it stays in restriction lists as a real grant: don't record it
+        // as `sa_account_id`.
+        self.verify_identity(&creds, source_type).await?;
         Ok(creds)
     }
 
@@ -306,6 +660,11 @@ impl AuthManager {
         creds: &mut AtlassianCredentials,
         source_type: Option<&SourceType>,
     ) -> Result<()> {
+        // OAuth tokens are refreshed by the SyncManager, which owns the SDK
+        // client needed to persist the rotated refresh token.
+        if creds.is_oauth() {
+            return Ok(());
+        }
         if !creds.is_valid() {
             debug!("Re-validating SA token");
             let new_creds = self
@@ -398,3 +757,86 @@ impl AuthManager {
         Ok(space_keys)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn oauth_creds(expires_at: Option<i64>) -> AtlassianOAuthCredentials {
+        AtlassianOAuthCredentials {
+            access_token: "access".to_string(),
+            refresh_token: Some("refresh".to_string()),
+            client_id: None,
+            client_secret: None,
+            token_uri: None,
+            expires_at,
+            granted_scopes: vec![],
+        }
+    }
+
+    #[test]
+    fn test_required_oauth_scopes_per_source_type() {
+        let jira = required_oauth_scopes(Some(&SourceType::Jira));
+        assert!(jira.contains(&"read:jira-work"));
+        assert!(!jira.contains(&"read:confluence-content.all"));
+
+        let confluence = required_oauth_scopes(Some(&SourceType::Confluence));
+        assert!(confluence.contains(&"read:page:confluence"));
+        assert!(!confluence.contains(&"read:jira-work"));
+
+        let both = required_oauth_scopes(None);
+        assert_eq!(
+            both.iter().filter(|s| **s == "offline_access").count(),
+            1,
+            "shared scopes are deduplicated"
+        );
+    }
+
+    #[test]
+    fn test_validate_oauth_scopes() {
+        let granted: Vec<String> = JIRA_OAUTH_SCOPES.iter().map(|s| s.to_string()).collect();
+        assert!(AuthManager::validate_oauth_scopes(&granted, Some(&SourceType::Jira)).is_ok());
+
+        let err = AuthManager::validate_oauth_scopes(&granted, Some(&SourceType::Confluence))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("read:confluence-content.all"));
+
+        // Unknown grant: defer to the API calls.
+        assert!(AuthManager::validate_oauth_scopes(&[], Some(&SourceType::Jira)).is_ok());
+    }
+
+    #[test]
+    fn test_oauth_needs_refresh() {
+        let now = Utc::now().timestamp();
+        assert!(oauth_creds(Some(now - 10)).needs_refresh());
+        assert!(oauth_creds(Some(now + 60)).needs_refresh());
+        assert!(!oauth_creds(Some(now + 3600)).needs_refresh());
+        assert!(!oauth_creds(None).needs_refresh());
+    }
+
+    #[test]
+    fn test_oauth_credentials_json_preserves_unknown_fields() {
+        let existing = json!({
+            "access_token": "old",
+            "refresh_token": "old-refresh",
+            "token_type": "Bearer",
+        });
+        let merged = oauth_creds(None).to_credentials_json(&existing);
+        assert_eq!(merged["access_token"], "access");
+        assert_eq!(merged["refresh_token"], "refresh");
+        assert_eq!(merged["token_type"], "Bearer");
+    }
+
+    #[test]
+    fn test_oauth_bearer_header_prefers_access_token() {
+        let creds = AtlassianCredentials::from_oauth(
+            "example.atlassian.net".to_string(),
+            "cloud".to_string(),
+            oauth_creds(None),
+        );
+        assert!(creds.is_oauth());
+        assert_eq!(creds.get_bearer_auth_header(), "Bearer access");
+    }
+}
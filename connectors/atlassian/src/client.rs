@@ -11,10 +11,11 @@ use tracing::debug;
 use crate::auth::AtlassianCredentials;
 use crate::models::{
     AtlassianWebhookRegistration, AtlassianWebhookRegistrationResponse,
-    ConfluenceContentRestriction, ConfluenceCqlPage, ConfluenceCqlSearchResponse,
-    ConfluenceGetPagesResponse, ConfluenceGetSpacesResponse, ConfluenceGroupMembersResponse,
-    ConfluencePage, ConfluenceSpace, ConfluenceSpacePermission, ConfluenceSpacePermissionsResponse,
-    JiraField, JiraGroupMembersResponse, JiraIssue, JiraIssueSecuritySchemeResponse,
+    ConfluenceAncestorsResponse, ConfluenceContentRestriction, ConfluenceCqlPage,
+    ConfluenceCqlSearchResponse, ConfluenceGetPagesResponse, ConfluenceGetSpacesResponse,
+    ConfluenceGroupMembersResponse, ConfluencePage, ConfluenceSpace, ConfluenceSpacePermission,
+    ConfluenceSpacePermissionsResponse, JiraComment, JiraComments, JiraField,
+    JiraGroupMembersResponse, JiraIssue, JiraIssueSecuritySchemeResponse,
     JiraPermissionSchemeResponse, JiraProjectIssueSecuritySchemeResponse, JiraProjectRolesResponse,
     JiraRoleActorsResponse, JiraSearchResponse, JiraSecurityLevelMember,
     JiraSecurityLevelMembersResponse, OrgAdminGroupMembersResponse, OrgAdminGroupsResponse,
@@ -64,6 +65,21 @@ pub trait AtlassianApi: Send + Sync {
         fields: &[String],
     ) -> Result<JiraIssue>;
 
+    /// Paginates every comment on an issue. Search results only embed the
+    /// first page of comments.
+    async fn get_jira_issue_comments(
+        &self,
+        creds: &AtlassianCredentials,
+        issue_key: &str,
+    ) -> Result<Vec<JiraComment>>;
+
+    /// Downloads the raw content of an issue attachment.
+    async fn download_jira_attachment(
+        &self,
+        creds: &AtlassianCredentials,
+        attachment_id: &str,
+    ) -> Result<Vec<u8>>;
+
     async fn get_jira_fields(&self, creds: &AtlassianCredentials) -> Result<Vec<JiraField>>;
 
     async fn get_jira_projects(
@@ -128,6 +144,14 @@ pub trait AtlassianApi: Send + Sync {
         page_id: &str,
     ) -> Result<Option<PageReadRestrictions>>;
 
+    /// Returns the ids of the pages and folders above a Confluence page,
+    /// whose read restrictions the page inherits.
+    async fn get_confluence_page_ancestors(
+        &self,
+        creds: &AtlassianCredentials,
+        page_id: &str,
+    ) -> Result<Vec<String>>;
+
     /// Returns the issue security scheme attached to a project, or Ok(None)
     /// if the project has no security scheme configured (any issue is
     /// readable to anyone with project access).
@@ -176,6 +200,11 @@ pub trait AtlassianApi: Send + Sync {
         &self,
         creds: &AtlassianCredentials,
     ) -> Result<HashMap<String, OrgGroupInfo>>;
+
+    /// The limiter guarding upstream requests, for usage reporting.
+    fn rate_limiter(&self) -> Option<&RateLimiter> {
+        None
+    }
 }
 
 /// Group entry as returned by the Atlassian organization-admin API: the
@@ -212,7 +241,7 @@ impl AtlassianClient {
         // Atlassian API rate limits: ~10 requests per second for Cloud
         Self {
             client,
-            rate_limiter: RateLimiter::new(10, 5),
+            rate_limiter: RateLimiter::new(10, 5).with_api_family("atlassian"),
         }
     }
 
@@ -311,6 +340,10 @@ impl AtlassianClient {
 
 #[async_trait]
 impl AtlassianApi for AtlassianClient {
+    fn rate_limiter(&self) -> Option<&RateLimiter> {
+        Some(&self.rate_limiter)
+    }
+
     fn get_confluence_pages<'a>(
         &'a self,
         creds: &'a AtlassianCredentials,
@@ -520,6 +553,113 @@ impl AtlassianApi for AtlassianClient {
         .await
     }
 
+    async fn get_jira_issue_comments(
+        &self,
+        creds: &AtlassianCredentials,
+        issue_key: &str,
+    ) -> Result<Vec<JiraComment>> {
+        let auth_header = creds.get_bearer_auth_header();
+        let url = format!(
+            "{}/rest/api/3/issue/{}/comment",
+            creds.jira_base(),
+            issue_key
+        );
+        let page_size: u32 = 100;
+        let mut start_at: u32 = 0;
+        let mut all_comments = Vec::new();
+
+        loop {
+            let params = vec![
+                ("maxResults", page_size.to_string()),
+                ("startAt", start_at.to_string()),
+            ];
+
+            debug!(
+                "Fetching JIRA issue {} comments (startAt={})",
+                issue_key, start_at
+            );
+
+            let client = self.client.clone();
+            let resp: JiraComments = self
+                .make_request(|| {
+                    client
+                        .get(&url)
+                        .query(&params)
+                        .header("Authorization", &auth_header)
+                        .header("Accept", "application/json")
+                })
+                .await?;
+
+            let result_count = resp.comments.len() as u32;
+            all_comments.extend(resp.comments);
+
+            if result_count == 0 || all_comments.len() as i32 >= resp.total {
+                break;
+            }
+            start_at += result_count;
+        }
+
+        Ok(all_comments)
+    }
+
+    async fn download_jira_attachment(
+        &self,
+        creds: &AtlassianCredentials,
+        attachment_id: &str,
+    ) -> Result<Vec<u8>> {
+        let auth_header = creds.get_bearer_auth_header();
+        let url = format!(
+            "{}/rest/api/3/attachment/content/{}",
+            creds.jira_base(),
+            attachment_id
+        );
+
+        debug!("Downloading JIRA attachment {}", attachment_id);
+
+        let client = self.client.clone();
+        self.rate_limiter
+            .execute_with_retry(|| async {
+                let response = client
+                    .get(&url)
+                    .header("Authorization", &auth_header)
+                    .send()
+                    .await
+                    .map_err(|e| RetryableError::Transient(e.into()))?;
+
+                match response.status() {
+                    StatusCode::OK => response
+                        .bytes()
+                        .await
+                        .map(|bytes| bytes.to_vec())
+                        .map_err(|e| RetryableError::Transient(e.into())),
+                    StatusCode::TOO_MANY_REQUESTS => {
+                        let retry_after = Self::extract_retry_after(&response);
+                        Err(RetryableError::RateLimited {
+                            retry_after,
+                            message: "Atlassian API rate limit exceeded".to_string(),
+                        })
+                    }
+                    status if status.is_server_error() => {
+                        let text = response.text().await.unwrap_or_default();
+                        Err(RetryableError::Transient(anyhow!(
+                            "Server error: HTTP {} - {}",
+                            status,
+                            text
+                        )))
+                    }
+                    status => {
+                        let text = response.text().await.unwrap_or_default();
+                        Err(RetryableError::Permanent(anyhow!(
+                            "Failed to download attachment: HTTP {} - {}",
+                            status,
+                            text
+                        )))
+                    }
+                }
+            })
+            .await
+    }
+
     async fn get_jira_fields(&self, creds: &AtlassianCredentials) -> Result<Vec<JiraField>> {
         let auth_header = creds.get_bearer_auth_header();
         let url = format!("{}/rest/api/3/field", creds.jira_base());
@@ -1030,6 +1170,54 @@ impl AtlassianApi for AtlassianClient {
         }))
     }
 
+    async fn get_confluence_page_ancestors(
+        &self,
+        creds: &AtlassianCredentials,
+        page_id: &str,
+    ) -> Result<Vec<String>> {
+        let auth_header = creds.get_bearer_auth_header();
+        let mut ancestor_ids = Vec::new();
+        let mut url = format!(
+            "{}/api/v2/pages/{}/ancestors",
+            creds.confluence_base(),
+            page_id
+        );
+        let params = vec![("limit", "250".to_string())];
+
+        loop {
+            debug!("Fetching Confluence page {} ancestors: {}", page_id, url);
+
+            let client = self.client.clone();
+            let resp: ConfluenceAncestorsResponse = self
+                .make_request(|| {
+                    client
+                        .get(&url)
+                        .query(&params)
+                        .header("Authorization", &auth_header)
+                        .header("Accept", "application/json")
+                })
+                .await?;
+
+            // Only pages and folders carry read restrictions
+            ancestor_ids.extend(
+                resp.results
+                    .into_iter()
+                    .filter(|a| a.r#type == "page" || a.r#type == "folder")
+                    .map(|a| a.id),
+            );
+
+            match resp
+                .links
+                .and_then(|links| links.next.map(|next| (links.base, next)))
+            {
+                Some((base, next)) => url = format!("{}{}", base, next),
+                None => break,
+            }
+        }
+
+        Ok(ancestor_ids)
+    }
+
     async fn get_project_issue_security_scheme(
         &self,
         creds: &AtlassianCredentials,
@@ -3,12 +3,12 @@ use chrono::{DateTime, Utc};
 use dashmap::DashMap;
 use futures::stream::StreamExt;
 use omni_connector_sdk::{DocumentPermissions, SdkClient, SyncContext};
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::sync::Arc;
 use tracing::{debug, error, info, warn};
 
 use crate::auth::AtlassianCredentials;
-use crate::client::{AtlassianApi, PageReadRestrictions};
+use crate::client::{AtlassianApi, OrgGroupInfo, PageReadRestrictions};
 use crate::models::{ConfluencePage, ConfluencePageStatus, ConfluenceSpace};
 use crate::user_resolver::UserResolver;
 
@@ -17,6 +17,17 @@ pub struct ConfluenceProcessor {
     sdk_client: SdkClient,
     user_resolver: Arc<UserResolver>,
     space_permissions_cache: DashMap<String, DocumentPermissions>,
+    /// Read restrictions per page or folder id, `None` when unrestricted.
+    /// Siblings share their ancestors' restrictions, so each is fetched once.
+    restrictions_cache: DashMap<String, Option<DocumentPermissions>>,
+    /// Ancestor ids of the children of a page, keyed by that parent's id.
+    ancestors_cache: DashMap<String, Vec<String>>,
+    /// Org-admin group directory, used before the per-group member API when
+    /// expanding groups.
+    group_directory: Arc<HashMap<String, OrgGroupInfo>>,
+    /// Member emails per groupId, for groups expanded while intersecting
+    /// space permissions with page restrictions.
+    group_members_cache: DashMap<String, Vec<String>>,
     /// groupId → display_name for groups encountered in space permissions
     /// during this sync. Drained at end of sync by SyncManager so it can
     /// emit one GroupMembershipSync event per encountered group.
@@ -25,12 +36,80 @@ pub struct ConfluenceProcessor {
     /// from connector state at sync start; the SyncManager drains this into
     /// the new state after a successful run.
     page_versions: DashMap<String, i32>,
+    /// Effective-permission fingerprint per page, keyed like `page_versions`.
+    /// Seeded from the checkpoint on incremental syncs so the permission
+    /// refresh pass can tell which pages' ACLs changed.
+    page_permissions: DashMap<String, String>,
 }
 
 fn page_version_key(space_id: &str, page_id: &str) -> String {
     format!("{}:{}", space_id, page_id)
 }
 
+/// Stable fingerprint of a page's effective permissions. Users and groups are
+/// sorted first so API ordering doesn't register as a change. FNV-1a rather
+/// than `DefaultHasher` because the value is persisted across releases.
+pub fn permissions_fingerprint(perms: &DocumentPermissions) -> String {
+    let mut users = perms.users.clone();
+    users.sort();
+    let mut groups = perms.groups.clone();
+    groups.sort();
+
+    let mut hash: u64 = 0xcbf29ce484222325;
+    let mut feed = |bytes: &[u8]| {
+        for b in bytes {
+            hash ^= *b as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    };
+    feed(if perms.public { b"public" } else { b"private" });
+    for user in &users {
+        feed(b"\0u:");
+        feed(user.as_bytes());
+    }
+    for group in &groups {
+        feed(b"\0g:");
+        feed(group.as_bytes());
+    }
+    format!("{:016x}", hash)
+}
+
+/// Access granted by both `a` and `b`. Groups granted on both sides stay
+/// groups; anyone else must be granted on both sides, directly or through a
+/// group, so `group_members` must hold the member emails of every group only
+/// one side grants.
+pub fn intersect_permissions(
+    a: &DocumentPermissions,
+    b: &DocumentPermissions,
+    group_members: &HashMap<String, Vec<String>>,
+) -> DocumentPermissions {
+    if a.public {
+        return b.clone();
+    }
+    if b.public {
+        return a.clone();
+    }
+
+    let groups: BTreeSet<&String> = a.groups.iter().filter(|g| b.groups.contains(g)).collect();
+    let granted = |perms: &DocumentPermissions| -> BTreeSet<String> {
+        perms
+            .groups
+            .iter()
+            .filter(|g| !groups.contains(g))
+            .flat_map(|g| group_members.get(g).into_iter().flatten())
+            .chain(&perms.users)
+            .cloned()
+            .collect()
+    };
+    let users = granted(a).intersection(&granted(b)).cloned().collect();
+
+    DocumentPermissions {
+        public: false,
+        users,
+        groups: groups.into_iter().cloned().collect(),
+    }
+}
+
 impl ConfluenceProcessor {
     pub fn new(client: Arc<dyn AtlassianApi>, sdk_client: SdkClient) -> Self {
         let resolver = Arc::new(UserResolver::new(client.clone(), Arc::new(HashMap::new())));
@@ -57,11 +136,34 @@ impl ConfluenceProcessor {
             sdk_client,
             user_resolver,
             space_permissions_cache: DashMap::new(),
+            restrictions_cache: DashMap::new(),
+            ancestors_cache: DashMap::new(),
+            group_directory: Arc::new(HashMap::new()),
+            group_members_cache: DashMap::new(),
             encountered_groups: DashMap::new(),
             page_versions: page_versions.into_iter().collect(),
+            page_permissions: DashMap::new(),
         }
     }
 
+    /// Seed the per-page permission fingerprints saved by the previous sync.
+    pub fn with_page_permissions(self, page_permissions: HashMap<String, String>) -> Self {
+        for (key, fingerprint) in page_permissions {
+            self.page_permissions.insert(key, fingerprint);
+        }
+        self
+    }
+
+    /// Use the org-admin group directory prefetched by the SyncManager when
+    /// expanding groups into their members.
+    pub fn with_group_directory(
+        mut self,
+        group_directory: Arc<HashMap<String, OrgGroupInfo>>,
+    ) -> Self {
+        self.group_directory = group_directory;
+        self
+    }
+
     /// Drain the current version map into a plain HashMap so the SyncManager
     /// can persist it on the connector state after a successful sync.
     pub fn drain_page_versions(&self) -> HashMap<String, i32> {
@@ -71,6 +173,15 @@ impl ConfluenceProcessor {
             .collect()
     }
 
+    /// Drain the permission fingerprints so the SyncManager can persist them
+    /// alongside the page versions.
+    pub fn drain_page_permissions(&self) -> HashMap<String, String> {
+        self.page_permissions
+            .iter()
+            .map(|entry| (entry.key().clone(), entry.value().clone()))
+            .collect()
+    }
+
     /// Drain the set of groupIds encountered in space permissions during the
     /// sync so the SyncManager can fetch their members and emit one
     /// GroupMembershipSync event per group.
@@ -81,32 +192,40 @@ impl ConfluenceProcessor {
             .collect()
     }
 
-    /// Resolve a page's effective `DocumentPermissions`. If the page has read
-    /// restrictions configured, those restrictions REPLACE the space-level
-    /// perms (Atlassian semantics: page restrictions narrow access to
-    /// specifically listed users/groups, regardless of broader space perms).
-    async fn resolve_page_permissions(
+    /// Read restrictions set directly on a page or folder, as permissions.
+    /// `None` when the content has no read restriction of its own.
+    async fn get_content_restrictions(
         &self,
         creds: &AtlassianCredentials,
-        page_id: &str,
-        space_perms: DocumentPermissions,
-    ) -> DocumentPermissions {
-        let restrictions = match self
+        content_id: &str,
+    ) -> Result<Option<DocumentPermissions>> {
+        if let Some(cached) = self.restrictions_cache.get(content_id) {
+            return Ok(cached.clone());
+        }
+
+        let restrictions = self
             .client
-            .get_confluence_page_read_restrictions(creds, page_id)
-            .await
-        {
-            Ok(Some(r)) => r,
-            Ok(None) => return space_perms,
-            Err(e) => {
-                warn!(
-                    "Failed to fetch read restrictions for page {}; falling back to space perms: {}",
-                    page_id, e
-                );
-                return space_perms;
-            }
+            .get_confluence_page_read_restrictions(creds, content_id)
+            .await?;
+        let perms = match restrictions {
+            Some(restrictions) => Some(
+                self.restriction_permissions(creds, content_id, restrictions)
+                    .await,
+            ),
+            None => None,
         };
 
+        self.restrictions_cache
+            .insert(content_id.to_string(), perms.clone());
+        Ok(perms)
+    }
+
+    async fn restriction_permissions(
+        &self,
+        creds: &AtlassianCredentials,
+        content_id: &str,
+        restrictions: PageReadRestrictions,
+    ) -> DocumentPermissions {
         let PageReadRestrictions {
             user_account_ids,
             group_ids,
@@ -131,18 +250,18 @@ impl ConfluenceProcessor {
             {
                 Ok(pairs) => user_emails.extend(pairs.into_iter().map(|(_, e)| e)),
                 Err(e) => warn!(
-                    "Failed to resolve restriction user emails for page {}: {}",
-                    page_id, e
+                    "Failed to resolve restriction user emails for content {}: {}",
+                    content_id, e
                 ),
             }
         }
 
         if !user_account_ids.is_empty() && user_emails.is_empty() {
             warn!(
-                "Page {} has individual user restrictions but none could be resolved to emails. \
+                "Content {} has individual user restrictions but none could be resolved to emails. \
                  The page will be treated as private in Omni. \
                  Configure an org-admin API key to resolve user emails.",
-                page_id
+                content_id
             );
         }
 
@@ -166,33 +285,105 @@ impl ConfluenceProcessor {
         }
     }
 
-    async fn get_space_permissions(
+    /// Ids of the pages and folders above `page`, whose read restrictions it
+    /// inherits. Cached by parent for its siblings; pages found through CQL
+    /// search carry no parent id, so theirs are always fetched.
+    async fn get_page_ancestors(
         &self,
         creds: &AtlassianCredentials,
-        space_id: &str,
-    ) -> DocumentPermissions {
-        if let Some(cached) = self.space_permissions_cache.get(space_id) {
-            return cached.clone();
+        page: &ConfluencePage,
+    ) -> Result<Vec<String>> {
+        if let Some(parent_id) = &page.parent_id
+            && let Some(cached) = self.ancestors_cache.get(parent_id)
+        {
+            return Ok(cached.clone());
         }
 
-        let perms = match self.fetch_space_permissions(creds, space_id).await {
-            Ok(p) => p,
-            Err(e) => {
-                warn!(
-                    "Failed to fetch permissions for space {}, defaulting to public: {}",
-                    space_id, e
-                );
-                DocumentPermissions {
-                    public: true,
-                    users: vec![],
-                    groups: vec![],
-                }
+        let ancestors = self
+            .client
+            .get_confluence_page_ancestors(creds, &page.id)
+            .await?;
+        if let Some(parent_id) = &page.parent_id {
+            self.ancestors_cache
+                .insert(parent_id.clone(), ancestors.clone());
+        }
+        Ok(ancestors)
+    }
+
+    /// Member emails of an Atlassian group.
+    async fn get_group_member_emails(
+        &self,
+        creds: &AtlassianCredentials,
+        group_id: &str,
+    ) -> Result<Vec<String>> {
+        if let Some(cached) = self.group_members_cache.get(group_id) {
+            return Ok(cached.clone());
+        }
+
+        let member_account_ids = match self.group_directory.get(group_id) {
+            Some(info) => info.member_account_ids.clone(),
+            None => {
+                self.client
+                    .get_confluence_group_members(creds, group_id)
+                    .await?
             }
         };
+        let mut member_emails = Vec::new();
+        if !member_account_ids.is_empty() {
+            member_emails.extend(
+                self.user_resolver
+                    .resolve_emails(creds, &member_account_ids)
+                    .await?
+                    .into_iter()
+                    .map(|(_, email)| email),
+            );
+        }
+        member_emails.sort();
+        member_emails.dedup();
+
+        self.group_members_cache
+            .insert(group_id.to_string(), member_emails.clone());
+        Ok(member_emails)
+    }
+
+    /// Access granted by both `a` and `b`, expanding the groups that only
+    /// one side grants so their members can be matched individually.
+    async fn intersect_with(
+        &self,
+        creds: &AtlassianCredentials,
+        a: &DocumentPermissions,
+        b: &DocumentPermissions,
+    ) -> Result<DocumentPermissions> {
+        let mut group_members = HashMap::new();
+        if !a.public && !b.public {
+            for group_id in a.groups.iter().chain(&b.groups) {
+                if a.groups.contains(group_id) && b.groups.contains(group_id) {
+                    continue;
+                }
+                if !group_members.contains_key(group_id) {
+                    let members = self.get_group_member_emails(creds, group_id).await?;
+                    group_members.insert(group_id.clone(), members);
+                }
+            }
+        }
+        Ok(intersect_permissions(a, b, &group_members))
+    }
+
+    /// Permissions of a space's pages before page restrictions. Not cached
+    /// when fetching fails, so the next page retries.
+    async fn get_space_permissions(
+        &self,
+        creds: &AtlassianCredentials,
+        space_id: &str,
+    ) -> Result<DocumentPermissions> {
+        if let Some(cached) = self.space_permissions_cache.get(space_id) {
+            return Ok(cached.clone());
+        }
 
+        let perms = self.fetch_space_permissions(creds, space_id).await?;
         self.space_permissions_cache
             .insert(space_id.to_string(), perms.clone());
-        perms
+        Ok(perms)
     }
 
     async fn fetch_space_permissions(
@@ -393,22 +584,7 @@ impl ConfluenceProcessor {
             source_id, sync_run_id
         );
 
-        let all_spaces = self.get_accessible_spaces(creds).await?;
-        let spaces: Vec<ConfluenceSpace> = match space_filters {
-            Some(filters) => {
-                let filtered: Vec<ConfluenceSpace> = all_spaces
-                    .into_iter()
-                    .filter(|s| filters.iter().any(|f| f.eq_ignore_ascii_case(&s.key)))
-                    .collect();
-                info!(
-                    "Filtered to {} spaces (from {} accessible)",
-                    filtered.len(),
-                    filters.len()
-                );
-                filtered
-            }
-            None => all_spaces,
-        };
+        let spaces = self.get_spaces_in_scope(creds, space_filters).await?;
         let mut total_pages_processed = 0;
 
         for space in spaces {
@@ -501,6 +677,149 @@ impl ConfluenceProcessor {
         Ok(total_pages)
     }
 
+    /// Re-resolve the effective permissions of every already-indexed page in
+    /// scope and re-emit those whose fingerprint changed. Confluence doesn't
+    /// bump a page's version (or `lastModified`) when space permissions or
+    /// page restrictions change, so the CQL-driven incremental sync alone
+    /// would leave stale ACLs in the index until the next full sync.
+    ///
+    /// Pages indexed before fingerprints were tracked have their current
+    /// fingerprint recorded without being re-emitted.
+    pub async fn refresh_page_permissions(
+        &self,
+        creds: &AtlassianCredentials,
+        source_id: &str,
+        sync_run_id: &str,
+        ctx: &SyncContext,
+        space_filters: &Option<Vec<String>>,
+    ) -> Result<u32> {
+        info!(
+            "Refreshing Confluence page permissions for source: {} (sync_run_id: {})",
+            source_id, sync_run_id
+        );
+
+        let spaces = self.get_spaces_in_scope(creds, space_filters).await?;
+        let base_url = creds.site_base();
+        let mut total_updated = 0;
+
+        for space in spaces {
+            // Collect all pages first to avoid borrow conflicts with emit_page
+            let mut pages = Vec::new();
+            {
+                let mut pages_stream = self.client.get_confluence_pages(creds, &space.id);
+                while let Some(page_result) = pages_stream.next().await {
+                    match page_result {
+                        Ok(p) => pages.push(p),
+                        Err(e) => {
+                            error!(
+                                "Failed to list pages in space {} for permission refresh: {}",
+                                space.id, e
+                            );
+                            break;
+                        }
+                    }
+                }
+            }
+
+            for page in pages {
+                if ctx.is_cancelled() {
+                    info!(
+                        "Confluence permission refresh {} cancelled after {} updates",
+                        sync_run_id, total_updated
+                    );
+                    return Ok(total_updated);
+                }
+
+                if page.status != ConfluencePageStatus::Current {
+                    continue;
+                }
+
+                // Only pages the index already holds; new pages are picked up
+                // by the content sync.
+                let key = page_version_key(&page.space_id, &page.id);
+                if !self.page_versions.contains_key(&key) {
+                    continue;
+                }
+
+                let permissions = match self.effective_page_permissions(creds, &page).await {
+                    Ok(permissions) => permissions,
+                    Err(e) => {
+                        warn!(
+                            "Failed to resolve permissions for Confluence page {}; keeping indexed permissions: {}",
+                            page.id, e
+                        );
+                        continue;
+                    }
+                };
+                let fingerprint = permissions_fingerprint(&permissions);
+                let previous = self
+                    .page_permissions
+                    .get(&key)
+                    .map(|entry| entry.value().clone());
+                match previous {
+                    Some(previous) if previous == fingerprint => continue,
+                    Some(_) => {}
+                    None => {
+                        self.page_permissions.insert(key, fingerprint);
+                        continue;
+                    }
+                }
+
+                debug!(
+                    "Permissions changed for Confluence page {} in space {}",
+                    page.title, space.key
+                );
+                let content = page.extract_plain_text();
+                if content.trim().is_empty() {
+                    continue;
+                }
+                if self
+                    .emit_page(
+                        &page,
+                        &content,
+                        permissions,
+                        source_id,
+                        sync_run_id,
+                        &base_url,
+                    )
+                    .await
+                {
+                    self.page_versions.insert(key, page.version.number);
+                    total_updated += 1;
+                }
+            }
+        }
+
+        info!(
+            "Completed Confluence permission refresh. Pages updated: {}",
+            total_updated
+        );
+        Ok(total_updated)
+    }
+
+    async fn get_spaces_in_scope(
+        &self,
+        creds: &AtlassianCredentials,
+        space_filters: &Option<Vec<String>>,
+    ) -> Result<Vec<ConfluenceSpace>> {
+        let all_spaces = self.get_accessible_spaces(creds).await?;
+        Ok(match space_filters {
+            Some(filters) => {
+                let filtered: Vec<ConfluenceSpace> = all_spaces
+                    .into_iter()
+                    .filter(|s| filters.iter().any(|f| f.eq_ignore_ascii_case(&s.key)))
+                    .collect();
+                info!(
+                    "Filtered to {} spaces (from {} accessible)",
+                    filtered.len(),
+                    filters.len()
+                );
+                filtered
+            }
+            None => all_spaces,
+        })
+    }
+
     async fn get_accessible_spaces(
         &self,
         creds: &AtlassianCredentials,
@@ -582,41 +901,28 @@ impl ConfluenceProcessor {
                 content.len()
             );
 
-            // Store content via SDK
-            let content_id = match self.sdk_client.store_content(sync_run_id, &content).await {
-                Ok(id) => id,
+            // Retried on the next sync, since the page version isn't recorded
+            let permissions = match self.effective_page_permissions(creds, &page).await {
+                Ok(permissions) => permissions,
                 Err(e) => {
-                    error!(
-                        "Failed to store content via SDK for Confluence page {}: {}",
-                        page.title, e
+                    warn!(
+                        "Failed to resolve permissions for Confluence page {}; skipping it: {}",
+                        page.id, e
                     );
                     continue;
                 }
             };
-
-            let space_perms = self.get_space_permissions(creds, &page.space_id).await;
-            let permissions = self
-                .resolve_page_permissions(creds, &page.id, space_perms)
-                .await;
-
-            let event = page.to_connector_event(
-                sync_run_id.to_string(),
-                source_id.to_string(),
-                base_url,
-                content_id,
-                permissions,
-            );
-
-            // Emit event via SDK
-            if let Err(e) = self
-                .sdk_client
-                .emit_event(sync_run_id, source_id, event)
+            if !self
+                .emit_page(
+                    &page,
+                    &content,
+                    permissions,
+                    source_id,
+                    sync_run_id,
+                    base_url,
+                )
                 .await
             {
-                error!(
-                    "Failed to emit event for Confluence page {}: {}",
-                    page.title, e
-                );
                 continue;
             }
 
@@ -627,4 +933,79 @@ impl ConfluenceProcessor {
 
         Ok(count)
     }
+
+    /// Resolve a page's effective `DocumentPermissions`: the space
+    /// permissions, narrowed by the read restrictions set on the page and on
+    /// every page or folder above it. Atlassian only lets a user view a page
+    /// if the space and each of those restrictions allow it.
+    ///
+    /// Errors rather than falling back to broader permissions, so a page is
+    /// never indexed for users who can't view it.
+    async fn effective_page_permissions(
+        &self,
+        creds: &AtlassianCredentials,
+        page: &ConfluencePage,
+    ) -> Result<DocumentPermissions> {
+        let mut perms = self.get_space_permissions(creds, &page.space_id).await?;
+
+        let mut restricted_ids = self.get_page_ancestors(creds, page).await?;
+        restricted_ids.push(page.id.clone());
+        for content_id in &restricted_ids {
+            if let Some(restrictions) = self.get_content_restrictions(creds, content_id).await? {
+                perms = self.intersect_with(creds, &perms, &restrictions).await?;
+            }
+        }
+        Ok(perms)
+    }
+
+    /// Store the page content and emit its document event, recording the
+    /// permission fingerprint once the event is accepted. Returns false (after
+    /// logging) if either SDK call fails.
+    async fn emit_page(
+        &self,
+        page: &ConfluencePage,
+        content: &str,
+        permissions: DocumentPermissions,
+        source_id: &str,
+        sync_run_id: &str,
+        base_url: &str,
+    ) -> bool {
+        // Store content via SDK
+        let content_id = match self.sdk_client.store_content(sync_run_id, content).await {
+            Ok(id) => id,
+            Err(e) => {
+                error!(
+                    "Failed to store content via SDK for Confluence page {}: {}",
+                    page.title, e
+                );
+                return false;
+            }
+        };
+
+        let fingerprint = permissions_fingerprint(&permissions);
+        let event = page.to_connector_event(
+            sync_run_id.to_string(),
+            source_id.to_string(),
+            base_url,
+            content_id,
+            permissions,
+        );
+
+        // Emit event via SDK
+        if let Err(e) = self
+            .sdk_client
+            .emit_event(sync_run_id, source_id, event)
+            .await
+        {
+            error!(
+                "Failed to emit event for Confluence page {}: {}",
+                page.title, e
+            );
+            return false;
+        }
+
+        self.page_permissions
+            .insert(page_version_key(&page.space_id, &page.id), fingerprint);
+        true
+    }
 }
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use anyhow::Result;
@@ -5,13 +6,17 @@ use async_trait::async_trait;
 use axum::http::StatusCode;
 use axum::response::Response;
 use omni_connector_sdk::{
-    ActionDefinition, ActionMode, ActionResponse, Connector, SearchOperator, ServiceCredential,
-    Source, SourceType, SyncContext, SyncType,
+    ActionDefinition, ActionMode, ActionResponse, AuthType, Connector, OAuthManifestConfig,
+    OAuthScopeSet, OAuthTokenEndpointAuthMethod, SearchOperator, ServiceCredential, Source,
+    SourceType, SyncContext, SyncType,
 };
 use serde_json::{Value as JsonValue, json};
 use tracing::info;
 
-use crate::auth::{AtlassianCredentials, AuthManager};
+use crate::auth::{
+    ATLASSIAN_OAUTH_AUTHORIZE_URL, ATLASSIAN_OAUTH_TOKEN_URL, AtlassianCredentials,
+    AtlassianOAuthCredentials, AuthManager, CONFLUENCE_OAUTH_SCOPES, JIRA_OAUTH_SCOPES,
+};
 use crate::client::{AtlassianApi, AtlassianClient};
 use crate::models::AtlassianSyncCheckpoint;
 use crate::sync::SyncManager;
@@ -45,7 +50,7 @@ impl Connector for AtlassianConnector {
     }
 
     fn description(&self) -> Option<String> {
-        Some("Connect to Confluence and Jira using an API token".to_string())
+        Some("Connect to Confluence and Jira using a service account token or OAuth".to_string())
     }
 
     fn source_types(&self) -> Vec<SourceType> {
@@ -106,6 +111,47 @@ impl Connector for AtlassianConnector {
         ]
     }
 
+    fn oauth_config(&self) -> Option<OAuthManifestConfig> {
+        let to_strings = |scopes: &[&str]| scopes.iter().map(|s| s.to_string()).collect();
+        let mut scopes = HashMap::new();
+        scopes.insert(
+            "jira".to_string(),
+            OAuthScopeSet {
+                read: to_strings(JIRA_OAUTH_SCOPES),
+                write: vec!["write:jira-work".to_string()],
+            },
+        );
+        scopes.insert(
+            "confluence".to_string(),
+            OAuthScopeSet {
+                read: to_strings(CONFLUENCE_OAUTH_SCOPES),
+                write: vec!["write:confluence-content".to_string()],
+            },
+        );
+
+        // `audience` is mandatory on Atlassian's authorize endpoint; `prompt`
+        // forces the consent screen so a refresh token is always issued.
+        let mut extra_auth_params = HashMap::new();
+        extra_auth_params.insert("audience".to_string(), "api.atlassian.com".to_string());
+        extra_auth_params.insert("prompt".to_string(), "consent".to_string());
+
+        Some(OAuthManifestConfig {
+            provider: "atlassian".to_string(),
+            auth_endpoint: ATLASSIAN_OAUTH_AUTHORIZE_URL.to_string(),
+            token_endpoint: ATLASSIAN_OAUTH_TOKEN_URL.to_string(),
+            userinfo_endpoint: "https://api.atlassian.com/me".to_string(),
+            userinfo_email_field: "email".to_string(),
+            identity_scopes: vec!["read:me".to_string(), "offline_access".to_string()],
+            scopes,
+            extra_auth_params,
+            scope_separator: " ".to_string(),
+            enrich_endpoint: None,
+            registration_endpoint: None,
+            token_endpoint_auth_method: OAuthTokenEndpointAuthMethod::ClientSecretPost,
+            resource: None,
+        })
+    }
+
     async fn sync(
         &self,
         source: Source,
@@ -159,21 +205,54 @@ pub async fn handle_search_spaces(
         None => return ActionResponse::failure("Atlassian action requires credentials"),
     };
 
-    let domain = match creds.config.get("domain").and_then(|v| v.as_str()) {
-        Some(u) => u.to_string(),
-        None => return ActionResponse::failure("Missing domain in credentials config"),
-    };
-    let sa_token = match creds.credentials.get("sa_token").and_then(|v| v.as_str()) {
-        Some(t) => t.to_string(),
-        None => return ActionResponse::failure("Missing sa_token in credentials"),
-    };
+    let auth_manager = AuthManager::new();
+    let domain = creds
+        .config
+        .get("domain")
+        .and_then(|v| v.as_str())
+        .map(String::from);
 
-    let cloud_id = match AuthManager::new().fetch_cloud_id(&domain).await {
-        Ok(id) => id,
-        Err(e) => return ActionResponse::failure(format!("Failed to resolve cloud_id: {}", e)),
-    };
+    let creds = if creds.auth_type == AuthType::OAuth {
+        let oauth = match AtlassianOAuthCredentials::from_service_credential(&creds) {
+            Ok(o) => o,
+            Err(e) => return ActionResponse::failure(e.to_string()),
+        };
+        // No refresh here: the rotated refresh token has to be persisted,
+        // which only the sync path does. Space search runs during source
+        // setup, right after consent, so the token is fresh.
+        let resources = match auth_manager
+            .fetch_accessible_resources(&oauth.access_token)
+            .await
+        {
+            Ok(r) => r,
+            Err(e) => return ActionResponse::failure(e.to_string()),
+        };
+        let resource = resources.into_iter().find(|r| {
+            domain
+                .as_deref()
+                .is_none_or(|d| r.domain().eq_ignore_ascii_case(d))
+        });
+        match resource {
+            Some(r) => AtlassianCredentials::from_oauth(r.domain().to_string(), r.id, oauth),
+            None => return ActionResponse::failure("OAuth grant has no matching Atlassian site"),
+        }
+    } else {
+        let domain = match domain {
+            Some(d) => d,
+            None => return ActionResponse::failure("Missing domain in credentials config"),
+        };
+        let sa_token = match creds.credentials.get("sa_token").and_then(|v| v.as_str()) {
+            Some(t) => t.to_string(),
+            None => return ActionResponse::failure("Missing sa_token in credentials"),
+        };
 
-    let creds = AtlassianCredentials::new(domain, cloud_id, sa_token);
+        let cloud_id = match auth_manager.fetch_cloud_id(&domain).await {
+            Ok(id) => id,
+            Err(e) => return ActionResponse::failure(format!("Failed to resolve cloud_id: {}", e)),
+        };
+
+        AtlassianCredentials::new(domain, cloud_id, sa_token)
+    };
     let client = AtlassianClient::new();
 
     match search_type.as_str() {
@@ -9,7 +9,7 @@ use tracing::{debug, error, info, warn};
 
 use crate::auth::AtlassianCredentials;
 use crate::client::AtlassianApi;
-use crate::models::JiraIssue;
+use crate::models::{JiraAttachment, JiraIssue, parse_jira_timestamp};
 use crate::user_resolver::UserResolver;
 
 const DEFAULT_JIRA_FIELDS: &[&str] = &[
@@ -26,6 +26,7 @@ const DEFAULT_JIRA_FIELDS: &[&str] = &[
     "updated",
     "labels",
     "comment",
+    "attachment",
     "components",
     "security",
 ];
@@ -39,6 +40,27 @@ fn build_fields(custom_fields: Option<&[String]>) -> Vec<String> {
     fields
 }
 
+/// Attachments larger than this are not downloaded.
+const MAX_ATTACHMENT_SIZE_BYTES: i64 = 50 * 1024 * 1024;
+
+/// Narrow `jql` to the issues that also match the source's JQL filter. The
+/// filter's own `ORDER BY`, if any, is dropped since it can't appear inside
+/// a clause.
+fn scope_jql(jql: &str, filter: Option<&str>) -> String {
+    let Some(filter) = filter else {
+        return jql.to_string();
+    };
+    let filter = match filter.to_ascii_uppercase().rfind("ORDER BY") {
+        Some(idx) => &filter[..idx],
+        None => filter,
+    };
+    let filter = filter.trim();
+    if filter.is_empty() {
+        return jql.to_string();
+    }
+    format!("({}) AND {}", filter, jql)
+}
+
 pub struct JiraProcessor {
     client: Arc<dyn AtlassianApi>,
     sdk_client: SdkClient,
@@ -58,6 +80,8 @@ pub struct JiraProcessor {
     /// and folded into `security_level_perms`, so we don't re-fetch on every
     /// issue.
     security_resolved_projects: DashMap<String, ()>,
+    /// Source-level JQL that every synced issue must also match.
+    jql_filter: Option<String>,
 }
 
 const CUSTOM_FIELDS_CACHE_TTL_DAYS: i64 = 1;
@@ -82,9 +106,15 @@ impl JiraProcessor {
             encountered_groups: DashMap::new(),
             security_level_perms: DashMap::new(),
             security_resolved_projects: DashMap::new(),
+            jql_filter: None,
         }
     }
 
+    pub fn with_jql_filter(mut self, jql_filter: Option<String>) -> Self {
+        self.jql_filter = jql_filter;
+        self
+    }
+
     /// Drain the set of groupIds encountered in project permissions during the
     /// sync so the SyncManager can fetch their members and emit one
     /// GroupMembershipSync event per group.
@@ -618,6 +648,7 @@ impl JiraProcessor {
                 jql = format!("project IN ({}) AND {}", projects_str, jql);
             }
         }
+        let jql = scope_jql(&jql, self.jql_filter.as_deref());
 
         let fields = build_fields(Some(&custom_field_ids));
         let mut total_issues = 0;
@@ -650,6 +681,7 @@ impl JiraProcessor {
                     &creds.site_base(),
                     sync_run_id,
                     creds,
+                    Some(since),
                 )
                 .await?;
 
@@ -686,7 +718,10 @@ impl JiraProcessor {
         let mut next_page_token: Option<String> = None;
         const PAGE_SIZE: u32 = 50;
 
-        let jql = format!("project = {}", project_key);
+        let jql = scope_jql(
+            &format!("project = {}", project_key),
+            self.jql_filter.as_deref(),
+        );
         let fields = build_fields(custom_fields);
 
         loop {
@@ -715,6 +750,7 @@ impl JiraProcessor {
                     &creds.site_base(),
                     sync_run_id,
                     creds,
+                    None,
                 )
                 .await?;
 
@@ -745,6 +781,9 @@ impl JiraProcessor {
         Ok(projects)
     }
 
+    /// Index a page of issues along with their attachments. On incremental
+    /// syncs `attachments_since` skips attachments added before the previous
+    /// sync, which are already indexed.
     async fn process_issues(
         &self,
         issues: Vec<JiraIssue>,
@@ -752,10 +791,13 @@ impl JiraProcessor {
         base_url: &str,
         sync_run_id: &str,
         creds: &AtlassianCredentials,
+        attachments_since: Option<DateTime<Utc>>,
     ) -> Result<u32> {
         let mut count = 0;
 
-        for issue in issues {
+        for mut issue in issues {
+            self.load_all_comments(creds, &mut issue).await;
+
             let content = issue.to_document_content();
             if content.trim().is_empty() {
                 debug!("Skipping issue {} without content", issue.key);
@@ -812,7 +854,7 @@ impl JiraProcessor {
                 source_id.to_string(),
                 base_url,
                 content_id,
-                permissions,
+                permissions.clone(),
             );
 
             // Emit event via SDK
@@ -825,9 +867,119 @@ impl JiraProcessor {
                 continue;
             }
 
+            for attachment in issue.fields.attachment.iter().flatten() {
+                let is_new = match (attachments_since, parse_jira_timestamp(&attachment.created)) {
+                    (Some(since), Some(created)) => created >= since,
+                    _ => true,
+                };
+                if !is_new {
+                    continue;
+                }
+                self.process_attachment(
+                    &issue,
+                    attachment,
+                    source_id,
+                    sync_run_id,
+                    creds,
+                    &permissions,
+                )
+                .await;
+            }
+
             count += 1;
         }
 
         Ok(count)
     }
+
+    /// Search results embed only the first page of an issue's comments; fetch
+    /// the rest so the indexed content covers the whole discussion.
+    async fn load_all_comments(&self, creds: &AtlassianCredentials, issue: &mut JiraIssue) {
+        let Some(comments) = issue.fields.comment.as_mut() else {
+            return;
+        };
+        if comments.comments.len() as i32 >= comments.total {
+            return;
+        }
+        match self.client.get_jira_issue_comments(creds, &issue.key).await {
+            Ok(all) => comments.comments = all,
+            Err(e) => warn!(
+                "Failed to fetch all comments for JIRA issue {}, indexing the first {}: {}",
+                issue.key,
+                comments.comments.len(),
+                e
+            ),
+        }
+    }
+
+    async fn process_attachment(
+        &self,
+        issue: &JiraIssue,
+        attachment: &JiraAttachment,
+        source_id: &str,
+        sync_run_id: &str,
+        creds: &AtlassianCredentials,
+        permissions: &DocumentPermissions,
+    ) {
+        if attachment.size > MAX_ATTACHMENT_SIZE_BYTES {
+            debug!(
+                "Skipping attachment {} on JIRA issue {}: {} bytes exceeds the size limit",
+                attachment.filename, issue.key, attachment.size
+            );
+            return;
+        }
+
+        let data = match self
+            .client
+            .download_jira_attachment(creds, &attachment.id)
+            .await
+        {
+            Ok(data) => data,
+            Err(e) => {
+                warn!(
+                    "Failed to download attachment {} on JIRA issue {}: {}",
+                    attachment.filename, issue.key, e
+                );
+                return;
+            }
+        };
+
+        let mime_type = attachment
+            .mime_type
+            .as_deref()
+            .unwrap_or("application/octet-stream");
+        let content_id = match self
+            .sdk_client
+            .extract_and_store_content(sync_run_id, data, mime_type, Some(&attachment.filename))
+            .await
+        {
+            Ok(id) => id,
+            Err(e) => {
+                warn!(
+                    "Failed to extract attachment {} on JIRA issue {}: {}",
+                    attachment.filename, issue.key, e
+                );
+                return;
+            }
+        };
+
+        let event = attachment.to_connector_event(
+            issue,
+            sync_run_id.to_string(),
+            source_id.to_string(),
+            &creds.site_base(),
+            content_id,
+            permissions.clone(),
+        );
+        if let Err(e) = self
+            .sdk_client
+            .emit_event(sync_run_id, source_id, event)
+            .await
+        {
+            error!(
+                "Failed to emit event for attachment {} on JIRA issue {}: {}",
+                attachment.filename, issue.key, e
+            );
+        }
+    }
 }
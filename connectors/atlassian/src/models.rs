@@ -1,4 +1,4 @@
-use chrono::DateTime;
+use chrono::{DateTime, FixedOffset};
 use omni_connector_sdk::DocumentAttributes;
 use omni_connector_sdk::{ConnectorEvent, DocumentMetadata, DocumentPermissions};
 use serde::{Deserialize, Serialize};
@@ -20,6 +20,10 @@ pub struct ConfluenceSourceConfig {
 pub struct JiraSourceConfig {
     #[serde(default)]
     pub project_filters: Option<Vec<String>>,
+    /// JQL an issue must also match to be indexed, e.g.
+    /// `issuetype != Sub-task AND labels = public`.
+    #[serde(default)]
+    pub jql_filter: Option<String>,
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Hash)]
@@ -123,7 +127,10 @@ pub struct ConfluenceContent {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConfluenceAncestor {
     pub id: String,
+    /// Not returned by the v2 ancestors endpoint.
+    #[serde(default)]
     pub title: String,
+    /// `page`, `folder`, `whiteboard`, `database` or `embed`.
     pub r#type: String,
 }
 
@@ -180,6 +187,8 @@ pub struct JiraFields {
     pub labels: Option<Vec<String>>,
     pub comment: Option<JiraComments>,
     pub components: Option<Vec<JiraComponent>>,
+    #[serde(default)]
+    pub attachment: Option<Vec<JiraAttachment>>,
     /// Issue-level security: when set, restricts the issue's read access to
     /// the holders of the named security level, narrowing the project's
     /// permission scheme grants.
@@ -281,6 +290,18 @@ pub struct JiraComment {
     pub updated: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JiraAttachment {
+    pub id: String,
+    pub filename: String,
+    pub author: Option<JiraUser>,
+    pub created: String,
+    #[serde(default)]
+    pub size: i64,
+    #[serde(rename = "mimeType")]
+    pub mime_type: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JiraComponent {
     pub id: String,
@@ -402,6 +423,15 @@ pub struct ConfluenceSpacePermissionsResponse {
     pub links: Option<ConfluenceResponseLinks>,
 }
 
+/// Content above a page in its space's tree, as listed by
+/// `/api/v2/pages/{id}/ancestors`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConfluenceAncestorsResponse {
+    pub results: Vec<ConfluenceAncestor>,
+    #[serde(rename = "_links")]
+    pub links: Option<ConfluenceResponseLinks>,
+}
+
 // ============================================================================
 // Jira Issue Security Schemes
 // /rest/api/3/project/{key}/issuesecuritylevelscheme returns the scheme
@@ -831,6 +861,15 @@ pub struct AtlassianSyncCheckpoint {
     /// and the indexer's idempotent upsert by document_id.
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     pub confluence_page_versions: HashMap<String, i32>,
+    /// Fingerprint of each Confluence page's effective read permissions,
+    /// keyed like `confluence_page_versions`. Space-permission and
+    /// page-restriction changes don't bump the page version, so incremental
+    /// syncs compare against this to find pages whose ACL changed.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub confluence_page_permissions: HashMap<String, String>,
+    /// When the Confluence permission refresh pass last walked every page.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_permission_refresh_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -1146,3 +1185,70 @@ impl JiraIssue {
         }
     }
 }
+
+/// Parse a Jira timestamp, which uses a `+0000` style offset rather than
+/// RFC 3339's `+00:00`.
+pub fn parse_jira_timestamp(value: &str) -> Option<DateTime<FixedOffset>> {
+    DateTime::parse_from_rfc3339(value)
+        .or_else(|_| DateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f%z"))
+        .ok()
+}
+
+impl JiraAttachment {
+    pub fn document_id(&self, issue: &JiraIssue) -> String {
+        format!(
+            "jira_attachment_{}_{}_{}",
+            issue.fields.project.key, issue.key, self.id
+        )
+    }
+
+    /// An attachment is indexed as its own document, searchable with the
+    /// same attributes and permissions as the issue it is attached to.
+    pub fn to_connector_event(
+        &self,
+        issue: &JiraIssue,
+        sync_run_id: String,
+        source_id: String,
+        base_url: &str,
+        content_id: String,
+        permissions: DocumentPermissions,
+    ) -> ConnectorEvent {
+        let created_at = parse_jira_timestamp(&self.created).map(|dt| {
+            OffsetDateTime::from_unix_timestamp(dt.timestamp())
+                .unwrap_or(OffsetDateTime::UNIX_EPOCH)
+        });
+
+        let mut jira_extra = HashMap::new();
+        jira_extra.insert("project_id".to_string(), json!(issue.fields.project.id));
+        jira_extra.insert("issue_key".to_string(), json!(issue.key));
+        jira_extra.insert("attachment_id".to_string(), json!(self.id));
+        let mut extra = HashMap::new();
+        extra.insert("jira".to_string(), json!(jira_extra));
+
+        let metadata = DocumentMetadata {
+            title: Some(self.filename.clone()),
+            author: self.author.as_ref().map(|a| a.display_name.clone()),
+            created_at,
+            updated_at: created_at,
+            content_type: Some("attachment".to_string()),
+            mime_type: self.mime_type.clone(),
+            size: Some(self.size.to_string()),
+            url: Some(format!("{}/browse/{}", base_url, issue.key)),
+            path: Some(format!(
+                "{}/{}/{}",
+                issue.fields.project.name, issue.key, self.filename
+            )),
+            extra: Some(extra),
+        };
+
+        ConnectorEvent::DocumentCreated {
+            sync_run_id,
+            source_id,
+            document_id: self.document_id(issue),
+            content_id,
+            metadata,
+            permissions,
+            attributes: Some(issue.to_attributes().into_attributes()),
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use anyhow::{anyhow, Context, Result};
 use chrono::Utc;
-use omni_connector_sdk::ServiceProvider;
+use omni_connector_sdk::{AuthType, ServiceProvider};
 use omni_connector_sdk::{
     ConnectorEvent, SdkClient, ServiceCredential, Source, SourceType, SyncContext, SyncType,
 };
@@ -8,7 +8,7 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use tracing::{debug, info, warn};
 
-use crate::auth::{AtlassianCredentials, AuthManager};
+use crate::auth::{AtlassianCredentials, AtlassianOAuthCredentials, AuthManager};
 use crate::client::{AtlassianApi, OrgGroupInfo};
 use crate::confluence::ConfluenceProcessor;
 use crate::jira::JiraProcessor;
@@ -18,6 +18,11 @@ use crate::models::{
 };
 use crate::user_resolver::UserResolver;
 
+/// How often incremental Confluence syncs walk every indexed page to pick up
+/// space-permission and page-restriction changes. Each pass costs one
+/// restrictions lookup per page, so it runs far less often than the sync.
+const CONFLUENCE_PERMISSION_REFRESH_INTERVAL_HOURS: i64 = 6;
+
 pub struct SyncManager {
     pub sdk_client: SdkClient,
     auth_manager: AuthManager,
@@ -66,6 +71,9 @@ impl SyncManager {
         let outcome = self
             .run_sync_inner(&source_id, &sync_run_id, &ctx, state)
             .await;
+        if let Some(rate_limiter) = self.client.rate_limiter() {
+            ctx.report_rate_limits(&[rate_limiter]).await;
+        }
 
         match outcome {
             Ok(Some(_total_processed)) => {
@@ -109,14 +117,17 @@ impl SyncManager {
             ));
         }
 
-        let project_filters: Option<Vec<String>> = if source_type == SourceType::Jira {
-            serde_json::from_value::<JiraSourceConfig>(source.config.clone())
-                .ok()
-                .and_then(|c| c.project_filters)
-                .filter(|f| !f.is_empty())
+        let jira_config: JiraSourceConfig = if source_type == SourceType::Jira {
+            serde_json::from_value(source.config.clone()).unwrap_or_default()
         } else {
-            None
+            JiraSourceConfig::default()
         };
+        let project_filters: Option<Vec<String>> =
+            jira_config.project_filters.filter(|f| !f.is_empty());
+        let jql_filter: Option<String> = jira_config
+            .jql_filter
+            .map(|jql| jql.trim().to_string())
+            .filter(|jql| !jql.is_empty());
 
         let space_filters: Option<Vec<String>> = if source_type == SourceType::Confluence {
             serde_json::from_value::<ConfluenceSourceConfig>(source.config.clone())
@@ -128,16 +139,11 @@ impl SyncManager {
         };
 
         let service_creds = self.get_service_credentials(source_id).await?;
-        let (domain, sa_token, org_id, org_admin_api_key) =
-            self.extract_atlassian_credentials(&service_creds)?;
 
         debug!("Validating Atlassian credentials...");
         let mut credentials = self
-            .get_or_validate_credentials(&domain, &sa_token, Some(&source_type))
+            .resolve_credentials(source_id, &service_creds, Some(&source_type))
             .await?;
-        if let (Some(org), Some(key)) = (org_id, org_admin_api_key) {
-            credentials = credentials.with_org_admin(org, key);
-        }
         self.auth_manager
             .ensure_valid_credentials(&mut credentials, Some(&source_type))
             .await?;
@@ -158,14 +164,15 @@ impl SyncManager {
                     HashMap::new()
                 }),
         );
-        let group_directory: HashMap<String, OrgGroupInfo> = self
-            .client
-            .get_org_group_directory(&credentials)
-            .await
-            .unwrap_or_else(|e| {
-                warn!("Failed to fetch org group directory: {}", e);
-                HashMap::new()
-            });
+        let group_directory: Arc<HashMap<String, OrgGroupInfo>> = Arc::new(
+            self.client
+                .get_org_group_directory(&credentials)
+                .await
+                .unwrap_or_else(|e| {
+                    warn!("Failed to fetch org group directory: {}", e);
+                    HashMap::new()
+                }),
+        );
         if credentials.has_org_admin() {
             info!(
                 "Loaded org directory: {} users, {} groups",
@@ -189,27 +196,37 @@ impl SyncManager {
         // events at end-of-sync.
         let sync_sdk_client = ctx.sdk_client().clone();
 
+        let mut last_permission_refresh_at = existing_checkpoint.last_permission_refresh_at;
+        let mut new_page_permissions = existing_checkpoint.confluence_page_permissions.clone();
         let (total_processed, new_page_versions, encountered_groups) = match source_type {
             SourceType::Confluence => {
-                let page_versions = if sync_mode == SyncType::Full {
-                    HashMap::new()
+                let (page_versions, page_permissions) = if sync_mode == SyncType::Full {
+                    (HashMap::new(), HashMap::new())
                 } else {
-                    existing_checkpoint.confluence_page_versions.clone()
+                    (
+                        existing_checkpoint.confluence_page_versions.clone(),
+                        existing_checkpoint.confluence_page_permissions.clone(),
+                    )
                 };
                 let processor = ConfluenceProcessor::with_page_versions_and_resolver(
                     self.client.clone(),
                     sync_sdk_client.clone(),
                     page_versions,
                     user_resolver.clone(),
-                );
-                let result = if sync_mode == SyncType::Full {
+                )
+                .with_page_permissions(page_permissions)
+                .with_group_directory(group_directory.clone());
+                let mut count = if sync_mode == SyncType::Full {
                     info!(
                         "Performing full Confluence sync for source: {}",
                         source.name
                     );
-                    processor
+                    let count = processor
                         .sync_all_spaces(&credentials, source_id, sync_run_id, ctx, &space_filters)
-                        .await
+                        .await?;
+                    // A full sync re-resolves every page's permissions.
+                    last_permission_refresh_at = Some(sync_start);
+                    count
                 } else {
                     info!(
                         "Performing incremental Confluence sync for source: {}",
@@ -224,10 +241,26 @@ impl SyncManager {
                             ctx,
                             &space_filters,
                         )
-                        .await
+                        .await?
                 };
-                let count = result?;
+                let refresh_due = last_permission_refresh_at.is_none_or(|at| {
+                    sync_start - at
+                        >= chrono::Duration::hours(CONFLUENCE_PERMISSION_REFRESH_INTERVAL_HOURS)
+                });
+                if refresh_due && !ctx.is_cancelled() {
+                    count += processor
+                        .refresh_page_permissions(
+                            &credentials,
+                            source_id,
+                            sync_run_id,
+                            ctx,
+                            &space_filters,
+                        )
+                        .await?;
+                    last_permission_refresh_at = Some(sync_start);
+                }
                 let groups = processor.drain_encountered_groups();
+                new_page_permissions = processor.drain_page_permissions();
                 (count, processor.drain_page_versions(), groups)
             }
             SourceType::Jira => {
@@ -235,7 +268,8 @@ impl SyncManager {
                     self.client.clone(),
                     sync_sdk_client.clone(),
                     user_resolver.clone(),
-                );
+                )
+                .with_jql_filter(jql_filter);
                 let result = if sync_mode == SyncType::Full {
                     info!("Performing full Jira sync for source: {}", source.name);
                     processor
@@ -305,6 +339,8 @@ impl SyncManager {
         let new_checkpoint = AtlassianSyncCheckpoint {
             last_successful_sync_at: Some(sync_start),
             confluence_page_versions: new_page_versions,
+            confluence_page_permissions: new_page_permissions,
+            last_permission_refresh_at,
         };
         ctx.save_checkpoint(serde_json::to_value(new_checkpoint)?)
             .await?;
@@ -437,10 +473,7 @@ impl SyncManager {
         Ok(creds)
     }
 
-    fn extract_atlassian_credentials(
-        &self,
-        creds: &ServiceCredential,
-    ) -> Result<(String, String, Option<String>, Option<String>)> {
+    fn extract_atlassian_credentials(&self, creds: &ServiceCredential) -> Result<(String, String)> {
         let domain = creds
             .config
             .get("domain")
@@ -455,9 +488,13 @@ impl SyncManager {
             .ok_or_else(|| anyhow::anyhow!("Missing sa_token in service credentials"))?
             .to_string();
 
-        // Optional: organization-admin credentials enable the org-admin
-        // identity-resolution path. When absent the connector falls back to
-        // the per-site bulk-user API for accountId → email resolution.
+        Ok((domain, sa_token))
+    }
+
+    /// Optional: organization-admin credentials enable the org-admin
+    /// identity-resolution path. When absent the connector falls back to
+    /// the per-site bulk-user API for accountId → email resolution.
+    fn extract_org_admin(creds: &ServiceCredential) -> (Option<String>, Option<String>) {
         let org_id = creds
             .config
             .get("org_id")
@@ -469,7 +506,73 @@ impl SyncManager {
             .and_then(|v| v.as_str())
             .map(|s| s.to_string());
 
-        Ok((domain, sa_token, org_id, org_admin_api_key))
+        (org_id, org_admin_api_key)
+    }
+
+    /// Build validated API credentials from the source's stored credential
+    /// row, branching on auth type. OAuth access tokens close to expiry are
+    /// refreshed first, and the result is written back right away: Atlassian
+    /// rotates refresh tokens, so the old one stops working soon after use.
+    async fn resolve_credentials(
+        &self,
+        source_id: &str,
+        service_creds: &ServiceCredential,
+        source_type: Option<&SourceType>,
+    ) -> Result<AtlassianCredentials> {
+        let mut credentials = if service_creds.auth_type == AuthType::OAuth {
+            let mut oauth = AtlassianOAuthCredentials::from_service_credential(service_creds)?;
+            if oauth.needs_refresh() {
+                self.fill_oauth_client(&mut oauth).await?;
+                oauth = self.auth_manager.refresh_oauth_token(&oauth).await?;
+                self.sdk_client
+                    .update_credentials(
+                        source_id,
+                        oauth.to_credentials_json(&service_creds.credentials),
+                        oauth.expires_at,
+                    )
+                    .await
+                    .context("Failed to persist refreshed Atlassian OAuth token")?;
+            }
+            let domain = service_creds.config.get("domain").and_then(|v| v.as_str());
+            self.auth_manager
+                .validate_oauth_credentials(domain, oauth, source_type)
+                .await?
+        } else {
+            let (domain, sa_token) = self.extract_atlassian_credentials(service_creds)?;
+            self.get_or_validate_credentials(&domain, &sa_token, source_type)
+                .await?
+        };
+
+        let (org_id, org_admin_api_key) = Self::extract_org_admin(service_creds);
+        if let (Some(org), Some(key)) = (org_id, org_admin_api_key) {
+            credentials = credentials.with_org_admin(org, key);
+        }
+        Ok(credentials)
+    }
+
+    /// Older OAuth rows may predate the web app storing the OAuth client on
+    /// the credential; fall back to the connector config for those.
+    async fn fill_oauth_client(&self, oauth: &mut AtlassianOAuthCredentials) -> Result<()> {
+        if oauth.client_id.is_some() && oauth.client_secret.is_some() {
+            return Ok(());
+        }
+        let connector_config = self
+            .sdk_client
+            .get_connector_config("atlassian")
+            .await
+            .context("Failed to fetch Atlassian connector config for OAuth")?;
+        let field = |key: &str| {
+            connector_config
+                .get(key)
+                .and_then(|v| v.as_str())
+                .map(String::from)
+        };
+        oauth.client_id = oauth.client_id.take().or_else(|| field("oauth_client_id"));
+        oauth.client_secret = oauth
+            .client_secret
+            .take()
+            .or_else(|| field("oauth_client_secret"));
+        Ok(())
     }
 
     async fn get_or_validate_credentials(
@@ -492,6 +595,15 @@ impl SyncManager {
             Some(url) => url,
             None => return Ok(()),
         };
+        // The legacy webhook API is site-scoped and only accepts
+        // service-account/API-token auth; 3LO apps can't register there.
+        if creds.is_oauth() {
+            debug!(
+                "Skipping webhook registration for OAuth source {}",
+                source_id
+            );
+            return Ok(());
+        }
 
         let existing_state = self
             .sdk_client
@@ -664,17 +776,8 @@ impl SyncManager {
                     }
                 };
 
-                let (domain, sa_token, _org_id, _org_admin_api_key) =
-                    match self.extract_atlassian_credentials(&service_creds) {
-                        Ok(c) => c,
-                        Err(e) => {
-                            debug!("Failed to extract credentials for {}: {}", source_id, e);
-                            continue;
-                        }
-                    };
-
                 let creds = match self
-                    .get_or_validate_credentials(&domain, &sa_token, None)
+                    .resolve_credentials(source_id, &service_creds, None)
                     .await
                 {
                     Ok(c) => c,
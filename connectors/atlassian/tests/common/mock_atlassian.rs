@@ -9,8 +9,8 @@ use omni_atlassian_connector::AtlassianApi;
 use omni_atlassian_connector::AtlassianCredentials;
 use omni_atlassian_connector::client::{OrgGroupInfo, PageReadRestrictions};
 use omni_atlassian_connector::models::{
-    ConfluenceCqlPage, ConfluencePage, ConfluenceSpace, ConfluenceSpacePermission, JiraField,
-    JiraIssue, JiraIssueSecuritySchemeResponse, JiraPermissionSchemeResponse,
+    ConfluenceCqlPage, ConfluencePage, ConfluenceSpace, ConfluenceSpacePermission, JiraComment,
+    JiraField, JiraIssue, JiraIssueSecuritySchemeResponse, JiraPermissionSchemeResponse,
     JiraProjectIssueSecuritySchemeResponse, JiraProjectRolesResponse, JiraRoleActorsResponse,
     JiraSearchResponse, JiraSecurityLevelMember,
 };
@@ -30,6 +30,10 @@ pub struct MockAtlassianApi {
     pub jira_fields: Mutex<Vec<JiraField>>,
     pub single_page: Mutex<Option<ConfluencePage>>,
     pub single_issue: Mutex<Option<JiraIssue>>,
+    /// issue_key → every comment on the issue
+    pub issue_comments: Mutex<HashMap<String, Vec<JiraComment>>>,
+    /// attachment id → raw content
+    pub attachment_contents: Mutex<HashMap<String, Vec<u8>>>,
     pub webhook_register_result: Mutex<Option<u64>>,
     pub webhook_exists: Mutex<bool>,
     pub calls: Mutex<Vec<MethodCall>>,
@@ -40,6 +44,8 @@ pub struct MockAtlassianApi {
     pub group_members: Mutex<HashMap<String, Vec<String>>>,
     pub jira_group_members: Mutex<HashMap<String, Vec<String>>>,
     pub page_restrictions: Mutex<HashMap<String, PageReadRestrictions>>,
+    /// page_id → ids of the pages and folders above it
+    pub page_ancestors: Mutex<HashMap<String, Vec<String>>>,
     /// project_key → scheme id (None = no scheme)
     pub project_security_schemes: Mutex<HashMap<String, Option<String>>>,
     /// scheme id → full scheme detail (with levels)
@@ -63,6 +69,8 @@ impl MockAtlassianApi {
             jira_fields: Mutex::new(vec![]),
             single_page: Mutex::new(None),
             single_issue: Mutex::new(None),
+            issue_comments: Mutex::new(HashMap::new()),
+            attachment_contents: Mutex::new(HashMap::new()),
             webhook_register_result: Mutex::new(None),
             webhook_exists: Mutex::new(false),
             calls: Mutex::new(vec![]),
@@ -73,6 +81,7 @@ impl MockAtlassianApi {
             group_members: Mutex::new(HashMap::new()),
             jira_group_members: Mutex::new(HashMap::new()),
             page_restrictions: Mutex::new(HashMap::new()),
+            page_ancestors: Mutex::new(HashMap::new()),
             project_security_schemes: Mutex::new(HashMap::new()),
             security_schemes: Mutex::new(HashMap::new()),
             security_level_members: Mutex::new(HashMap::new()),
@@ -187,6 +196,35 @@ impl AtlassianApi for MockAtlassianApi {
             .ok_or_else(|| anyhow::anyhow!("Issue not found"))
     }
 
+    async fn get_jira_issue_comments(
+        &self,
+        _creds: &AtlassianCredentials,
+        issue_key: &str,
+    ) -> Result<Vec<JiraComment>> {
+        self.record_call("get_jira_issue_comments", vec![issue_key.to_string()]);
+        Ok(self
+            .issue_comments
+            .lock()
+            .unwrap()
+            .get(issue_key)
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    async fn download_jira_attachment(
+        &self,
+        _creds: &AtlassianCredentials,
+        attachment_id: &str,
+    ) -> Result<Vec<u8>> {
+        self.record_call("download_jira_attachment", vec![attachment_id.to_string()]);
+        self.attachment_contents
+            .lock()
+            .unwrap()
+            .get(attachment_id)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("Attachment not found"))
+    }
+
     async fn get_jira_fields(&self, _creds: &AtlassianCredentials) -> Result<Vec<JiraField>> {
         self.record_call("get_jira_fields", vec![]);
         Ok(self.jira_fields.lock().unwrap().clone())
@@ -311,6 +349,21 @@ impl AtlassianApi for MockAtlassianApi {
         Ok(self.page_restrictions.lock().unwrap().get(page_id).cloned())
     }
 
+    async fn get_confluence_page_ancestors(
+        &self,
+        _creds: &AtlassianCredentials,
+        page_id: &str,
+    ) -> Result<Vec<String>> {
+        self.record_call("get_confluence_page_ancestors", vec![page_id.to_string()]);
+        Ok(self
+            .page_ancestors
+            .lock()
+            .unwrap()
+            .get(page_id)
+            .cloned()
+            .unwrap_or_default())
+    }
+
     async fn get_project_issue_security_scheme(
         &self,
         _creds: &AtlassianCredentials,
@@ -5,13 +5,14 @@ use common::{
     TEST_CLOUD_ID, TEST_DOMAIN, TEST_SA_TOKEN, count_queued_events, get_queued_events,
     get_queued_events_by_type, setup_test_fixture,
 };
+use omni_atlassian_connector::client::PageReadRestrictions;
 use omni_atlassian_connector::models::{
     AtlassianWebhookEvent, AtlassianWebhookIssue, AtlassianWebhookIssueFields,
     AtlassianWebhookPage, AtlassianWebhookProject, AtlassianWebhookSpace, ConfluenceContent,
     ConfluenceCqlBody, ConfluenceCqlPage, ConfluenceCqlSpace, ConfluenceCqlVersion, ConfluencePage,
     ConfluencePageBody, ConfluencePageLinks, ConfluencePageStatus, ConfluenceSpace,
-    ConfluenceVersion, JiraFields, JiraIssue, JiraIssueType, JiraProject, JiraSearchResponse,
-    JiraStatus, JiraStatusCategory,
+    ConfluenceVersion, JiraComments, JiraFields, JiraIssue, JiraIssueType, JiraProject,
+    JiraSearchResponse, JiraStatus, JiraStatusCategory,
 };
 use omni_atlassian_connector::models::{
     ConfluencePermissionOperation, ConfluencePermissionPrincipal, ConfluenceSpacePermission,
@@ -167,6 +168,7 @@ fn make_jira_issue(key: &str, summary: &str, project_key: &str) -> JiraIssue {
             labels: None,
             comment: None,
             components: None,
+            attachment: None,
             security: None,
             extra_fields: HashMap::new(),
         },
@@ -404,6 +406,93 @@ async fn test_confluence_incremental_version_dedup_skips_unchanged() -> Result<(
     Ok(())
 }
 
+#[tokio::test]
+async fn test_confluence_permission_refresh_reemits_changed_restrictions() -> Result<()> {
+    let fixture = setup_test_fixture(SourceType::Confluence).await?;
+
+    *fixture.mock_api.spaces.lock().unwrap() =
+        vec![make_confluence_space("100", "DEV", "Development")];
+    *fixture.mock_api.pages.lock().unwrap() = vec![vec![
+        make_confluence_page("1001", "Page 1", "100", 1),
+        make_confluence_page("1002", "Page 2", "100", 1),
+    ]];
+
+    let creds = test_credentials();
+    let first_processor =
+        ConfluenceProcessor::new(fixture.mock_api.clone(), fixture.sdk_client.clone());
+    let sync_run_id = fixture
+        .sdk_client
+        .create_sync_run(SOURCE_ID, SyncType::Full)
+        .await?;
+    let ctx = make_sync_context(
+        &fixture,
+        &sync_run_id,
+        SourceType::Confluence,
+        SyncType::Full,
+    );
+    first_processor
+        .sync_all_spaces(&creds, SOURCE_ID, &sync_run_id, &ctx, &None)
+        .await?;
+    fixture.sdk_client.flush_all().await?;
+    fixture.sdk_client.complete(&sync_run_id).await?;
+    let saved_page_versions = first_processor.drain_page_versions();
+    let saved_page_permissions = first_processor.drain_page_permissions();
+    assert_eq!(saved_page_permissions.len(), 2);
+
+    // Restrict one page to a group without editing it: the page version stays
+    // the same, so only the permission refresh can notice.
+    fixture.mock_api.page_restrictions.lock().unwrap().insert(
+        "1002".to_string(),
+        PageReadRestrictions {
+            user_account_ids: vec![],
+            group_ids: vec!["group-legal".to_string()],
+        },
+    );
+
+    let processor = ConfluenceProcessor::with_page_versions(
+        fixture.mock_api.clone(),
+        fixture.sdk_client.clone(),
+        saved_page_versions,
+    )
+    .with_page_permissions(saved_page_permissions.clone());
+    let sync_run_id2 = fixture
+        .sdk_client
+        .create_sync_run(SOURCE_ID, SyncType::Incremental)
+        .await?;
+    let ctx2 = make_sync_context(
+        &fixture,
+        &sync_run_id2,
+        SourceType::Confluence,
+        SyncType::Incremental,
+    );
+    let updated = processor
+        .refresh_page_permissions(&creds, SOURCE_ID, &sync_run_id2, &ctx2, &None)
+        .await?;
+    assert_eq!(updated, 1, "Only the restricted page should be re-emitted");
+
+    fixture.sdk_client.flush_all().await?;
+    let events = get_queued_events(&fixture.pool).await?;
+    assert_eq!(events.len(), 3);
+    let reemitted = events
+        .iter()
+        .rfind(|e| e["document_id"] == "confluence_page_100_1002")
+        .unwrap();
+    assert_eq!(reemitted["permissions"]["public"], false);
+    assert_eq!(reemitted["permissions"]["groups"][0], "group-legal");
+
+    let new_permissions = processor.drain_page_permissions();
+    assert_ne!(
+        new_permissions["100:1002"], saved_page_permissions["100:1002"],
+        "Fingerprint should track the new restriction"
+    );
+    assert_eq!(
+        new_permissions["100:1001"],
+        saved_page_permissions["100:1001"]
+    );
+
+    Ok(())
+}
+
 // =============================================================================
 // Jira Sync Tests
 // =============================================================================
@@ -468,6 +557,55 @@ async fn test_jira_full_sync_creates_events() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_jira_sync_scopes_issues_by_jql_filter() -> Result<()> {
+    let fixture = setup_test_fixture(SourceType::Jira).await?;
+
+    *fixture.mock_api.jira_projects.lock().unwrap() = vec![serde_json::json!({
+        "key": "PROJ",
+        "name": "Test Project",
+    })];
+
+    // Search results only embed the first page of comments
+    let mut issue = make_jira_issue("PROJ-1", "Discussed Issue", "PROJ");
+    issue.fields.comment = Some(JiraComments {
+        comments: vec![],
+        total: 120,
+    });
+    *fixture.mock_api.jira_search_response.lock().unwrap() = Some(JiraSearchResponse {
+        issues: vec![issue],
+        is_last: true,
+        next_page_token: None,
+    });
+
+    let processor = JiraProcessor::new(fixture.mock_api.clone(), fixture.sdk_client.clone())
+        .with_jql_filter(Some("labels = public ORDER BY created DESC".to_string()));
+
+    let sync_run_id = fixture
+        .sdk_client
+        .create_sync_run(SOURCE_ID, SyncType::Full)
+        .await?;
+    let ctx = make_sync_context(&fixture, &sync_run_id, SourceType::Jira, SyncType::Full);
+
+    let creds = test_credentials();
+    processor
+        .sync_all_projects(&creds, SOURCE_ID, &sync_run_id, &ctx, &None)
+        .await?;
+
+    let issue_calls = fixture.mock_api.get_calls_for("get_jira_issues");
+    assert_eq!(issue_calls.len(), 1);
+    assert_eq!(
+        issue_calls[0].args[0], "(labels = public) AND project = PROJ",
+        "Source JQL filter should narrow the project query, without its ORDER BY"
+    );
+
+    let comment_calls = fixture.mock_api.get_calls_for("get_jira_issue_comments");
+    assert_eq!(comment_calls.len(), 1, "Should page through all comments");
+    assert_eq!(comment_calls[0].args[0], "PROJ-1");
+
+    Ok(())
+}
+
 // =============================================================================
 // Webhook Handler Tests
 // =============================================================================
@@ -771,6 +909,101 @@ async fn test_confluence_sync_fetches_and_caches_space_permissions() -> Result<(
     Ok(())
 }
 
+#[tokio::test]
+async fn test_confluence_inherited_restrictions_narrow_space_permissions() -> Result<()> {
+    let fixture = setup_test_fixture(SourceType::Confluence).await?;
+
+    *fixture.mock_api.spaces.lock().unwrap() =
+        vec![make_confluence_space("100", "DEV", "Development")];
+    let parent = make_confluence_page("2001", "Parent", "100", 1);
+    let mut child = make_confluence_page("2002", "Child", "100", 1);
+    child.parent_id = Some("2001".to_string());
+    *fixture.mock_api.pages.lock().unwrap() = vec![vec![parent, child]];
+    fixture
+        .mock_api
+        .page_ancestors
+        .lock()
+        .unwrap()
+        .insert("2002".to_string(), vec!["2001".to_string()]);
+
+    // The space is readable by staff; the parent page is restricted to Alice,
+    // who is staff, and Bob, who is not.
+    fixture.mock_api.space_permissions.lock().unwrap().insert(
+        "100".to_string(),
+        vec![ConfluenceSpacePermission {
+            id: "perm1".to_string(),
+            principal: ConfluencePermissionPrincipal {
+                principal_type: "group".to_string(),
+                id: "group-staff".to_string(),
+            },
+            operation: ConfluencePermissionOperation {
+                key: "read".to_string(),
+                target_type: "space".to_string(),
+            },
+        }],
+    );
+    fixture.mock_api.page_restrictions.lock().unwrap().insert(
+        "2001".to_string(),
+        PageReadRestrictions {
+            user_account_ids: vec!["acct-alice".to_string(), "acct-bob".to_string()],
+            group_ids: vec![],
+        },
+    );
+    fixture.mock_api.group_members.lock().unwrap().insert(
+        "group-staff".to_string(),
+        vec!["acct-alice".to_string(), "acct-carol".to_string()],
+    );
+    *fixture.mock_api.bulk_users.lock().unwrap() = vec![
+        ("acct-alice".to_string(), "alice@example.com".to_string()),
+        ("acct-bob".to_string(), "bob@example.com".to_string()),
+        ("acct-carol".to_string(), "carol@example.com".to_string()),
+    ];
+
+    let processor = ConfluenceProcessor::new(fixture.mock_api.clone(), fixture.sdk_client.clone());
+    let sync_run_id = fixture
+        .sdk_client
+        .create_sync_run(SOURCE_ID, SyncType::Full)
+        .await?;
+    let ctx = make_sync_context(
+        &fixture,
+        &sync_run_id,
+        SourceType::Confluence,
+        SyncType::Full,
+    );
+    let count = processor
+        .sync_all_spaces(&test_credentials(), SOURCE_ID, &sync_run_id, &ctx, &None)
+        .await?;
+    assert_eq!(count, 2);
+
+    fixture.sdk_client.flush_all().await?;
+    let events = get_queued_events(&fixture.pool).await?;
+    assert_eq!(events.len(), 2);
+    for event in &events {
+        let perms = &event["permissions"];
+        assert_eq!(perms["public"], false);
+        assert_eq!(
+            perms["users"],
+            serde_json::json!(["alice@example.com"]),
+            "only users allowed by the space and every restriction above the page"
+        );
+        assert_eq!(perms["groups"], serde_json::json!([]));
+    }
+
+    // The parent's restriction is fetched once, not again for the child
+    let restriction_calls = fixture
+        .mock_api
+        .get_calls_for("get_confluence_page_read_restrictions");
+    assert_eq!(
+        restriction_calls
+            .iter()
+            .filter(|c| c.args[0] == "2001")
+            .count(),
+        1
+    );
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_jira_sync_fetches_and_caches_project_permissions() -> Result<()> {
     let fixture = setup_test_fixture(SourceType::Jira).await?;
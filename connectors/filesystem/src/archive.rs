@@ -0,0 +1,461 @@
+//! Zip and tar archive extraction.
+//!
+//! With `extract_archives` enabled, an archive is not indexed as a file of its
+//! own. Each file inside it becomes a child document whose path, and document
+//! id, is the archive's path followed by `!/` and the member's path within the
+//! archive. Members go through the source's extension, pattern and size
+//! filters as if the archive were a directory.
+
+use crate::models::{FileSystemFile, FileSystemSource};
+use anyhow::{Context, Result};
+use flate2::read::GzDecoder;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tokio::sync::mpsc;
+use tracing::{debug, warn};
+
+/// Separates an archive's path from a member's path within it.
+pub const MEMBER_SEPARATOR: &str = "!/";
+
+/// Members past this many in one archive are ignored.
+const MAX_ARCHIVE_MEMBERS: usize = 10_000;
+
+/// Size limit for members when the source sets none, so that a small archive
+/// that expands enormously can't exhaust memory.
+const DEFAULT_MAX_MEMBER_SIZE_BYTES: u64 = 100 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveKind {
+    Zip,
+    Tar,
+    TarGz,
+}
+
+impl ArchiveKind {
+    pub fn detect(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_string_lossy().to_lowercase();
+        if name.ends_with(".zip") {
+            Some(ArchiveKind::Zip)
+        } else if name.ends_with(".tar") {
+            Some(ArchiveKind::Tar)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(ArchiveKind::TarGz)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ArchiveMember {
+    /// Path of the member within the archive.
+    pub name: String,
+    pub size: u64,
+    pub modified_time: Option<SystemTime>,
+    /// Changes whenever the member's content does.
+    pub fingerprint: String,
+    /// Empty when the archive was only listed.
+    pub data: Vec<u8>,
+}
+
+/// Path of a member of the archive at `archive_path`.
+pub fn member_path(archive_path: &Path, member_name: &str) -> PathBuf {
+    PathBuf::from(format!(
+        "{}{}{}",
+        archive_path.to_string_lossy(),
+        MEMBER_SEPARATOR,
+        member_name
+    ))
+}
+
+/// The file a member of `archive` is indexed as. Members share the archive's
+/// permissions and fall back to its timestamps.
+pub fn member_file(archive: &FileSystemFile, member: &ArchiveMember) -> FileSystemFile {
+    let name = member
+        .name
+        .rsplit('/')
+        .next()
+        .unwrap_or(&member.name)
+        .to_string();
+    let mime_type = mime_guess::from_path(&member.name)
+        .first_or_octet_stream()
+        .to_string();
+
+    FileSystemFile {
+        path: member_path(&archive.path, &member.name),
+        name,
+        size: member.size,
+        mime_type,
+        created_time: archive.created_time,
+        modified_time: member.modified_time.or(archive.modified_time),
+        is_directory: false,
+        permissions: archive.permissions.clone(),
+        archive_path: Some(archive.path.clone()),
+    }
+}
+
+/// Read the members of an archive that pass the source's filters on a
+/// blocking thread, streaming them back so only a few are held in memory at
+/// once. With `with_data` unset members are listed without their content.
+pub fn read_members(
+    archive_path: PathBuf,
+    kind: ArchiveKind,
+    source: FileSystemSource,
+    with_data: bool,
+) -> mpsc::Receiver<Result<ArchiveMember>> {
+    let (tx, rx) = mpsc::channel(4);
+    tokio::task::spawn_blocking(move || {
+        let reader = MemberReader {
+            archive_path: &archive_path,
+            source: &source,
+            with_data,
+            tx: &tx,
+        };
+        let result = match kind {
+            ArchiveKind::Zip => reader.read_zip(),
+            ArchiveKind::Tar => File::open(&archive_path)
+                .map_err(Into::into)
+                .and_then(|file| reader.read_tar(BufReader::new(file))),
+            ArchiveKind::TarGz => File::open(&archive_path)
+                .map_err(Into::into)
+                .and_then(|file| reader.read_tar(GzDecoder::new(BufReader::new(file)))),
+        };
+        if let Err(e) = result {
+            let _ = tx.blocking_send(Err(
+                e.context(format!("Failed to read archive {}", archive_path.display()))
+            ));
+        }
+    });
+    rx
+}
+
+struct MemberReader<'a> {
+    archive_path: &'a Path,
+    source: &'a FileSystemSource,
+    with_data: bool,
+    tx: &'a mpsc::Sender<Result<ArchiveMember>>,
+}
+
+impl MemberReader<'_> {
+    fn max_member_size(&self) -> u64 {
+        self.source
+            .max_file_size_bytes
+            .unwrap_or(DEFAULT_MAX_MEMBER_SIZE_BYTES)
+    }
+
+    /// Whether a member should be read, given its path and declared size.
+    fn accepts(&self, name: &str, size: u64) -> bool {
+        if !self.source.should_include_member(self.archive_path, name) {
+            debug!(
+                "Skipping archive member due to filters: {}",
+                member_path(self.archive_path, name).display()
+            );
+            return false;
+        }
+        if size > self.max_member_size() {
+            debug!(
+                "Skipping archive member due to size limit ({} > {}): {}",
+                size,
+                self.max_member_size(),
+                member_path(self.archive_path, name).display()
+            );
+            return false;
+        }
+        true
+    }
+
+    /// Read a member's content, refusing to read past the size limit since
+    /// the size an archive declares for a member can't be trusted.
+    fn read_data(&self, name: &str, reader: impl Read) -> Option<Vec<u8>> {
+        if !self.with_data {
+            return Some(Vec::new());
+        }
+        let limit = self.max_member_size();
+        let mut data = Vec::new();
+        match reader.take(limit + 1).read_to_end(&mut data) {
+            Ok(_) if data.len() as u64 > limit => {
+                warn!(
+                    "Skipping archive member larger than declared: {}",
+                    member_path(self.archive_path, name).display()
+                );
+                None
+            }
+            Ok(_) => Some(data),
+            Err(e) => {
+                warn!(
+                    "Failed to read archive member {}: {}",
+                    member_path(self.archive_path, name).display(),
+                    e
+                );
+                None
+            }
+        }
+    }
+
+    /// Hand a member to the consumer; false once it has gone away.
+    fn send(&self, member: ArchiveMember) -> bool {
+        self.tx.blocking_send(Ok(member)).is_ok()
+    }
+
+    fn read_zip(&self) -> Result<()> {
+        let file = File::open(self.archive_path)?;
+        let mut archive =
+            zip::ZipArchive::new(BufReader::new(file)).context("Failed to open zip archive")?;
+        let modified_time = std::fs::metadata(self.archive_path)
+            .and_then(|m| m.modified())
+            .ok();
+
+        let mut members = 0;
+        for index in 0..archive.len() {
+            let entry = match archive.by_index(index) {
+                Ok(entry) => entry,
+                Err(e) => {
+                    warn!(
+                        "Skipping unreadable entry {} of {}: {}",
+                        index,
+                        self.archive_path.display(),
+                        e
+                    );
+                    continue;
+                }
+            };
+            if !entry.is_file() {
+                continue;
+            }
+            let Some(name) = entry
+                .enclosed_name()
+                .map(|path| path.to_string_lossy().replace('\\', "/"))
+            else {
+                continue;
+            };
+            if !self.accepts(&name, entry.size()) {
+                continue;
+            }
+            if members == MAX_ARCHIVE_MEMBERS {
+                warn!(
+                    "Archive {} has more than {} members, ignoring the rest",
+                    self.archive_path.display(),
+                    MAX_ARCHIVE_MEMBERS
+                );
+                break;
+            }
+            members += 1;
+
+            let size = entry.size();
+            let fingerprint = format!("{:08x}-{}", entry.crc32(), size);
+            let Some(data) = self.read_data(&name, entry) else {
+                continue;
+            };
+            let member = ArchiveMember {
+                name,
+                size,
+                modified_time,
+                fingerprint,
+                data,
+            };
+            if !self.send(member) {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    fn read_tar(&self, reader: impl Read) -> Result<()> {
+        let mut archive = tar::Archive::new(reader);
+
+        let mut members = 0;
+        for entry in archive.entries().context("Failed to open tar archive")? {
+            let entry = entry.context("Failed to read tar entry")?;
+            if !entry.header().entry_type().is_file() {
+                continue;
+            }
+            let name = match entry.path() {
+                Ok(path) => path.to_string_lossy().trim_start_matches("./").to_string(),
+                Err(e) => {
+                    warn!(
+                        "Skipping tar entry with invalid path in {}: {}",
+                        self.archive_path.display(),
+                        e
+                    );
+                    continue;
+                }
+            };
+            let size = entry.size();
+            if !self.accepts(&name, size) {
+                continue;
+            }
+            if members == MAX_ARCHIVE_MEMBERS {
+                warn!(
+                    "Archive {} has more than {} members, ignoring the rest",
+                    self.archive_path.display(),
+                    MAX_ARCHIVE_MEMBERS
+                );
+                break;
+            }
+            members += 1;
+
+            let mtime = entry.header().mtime().unwrap_or(0);
+            let modified_time = SystemTime::UNIX_EPOCH.checked_add(Duration::from_secs(mtime));
+            let fingerprint = format!("{}-{}", mtime, size);
+            let Some(data) = self.read_data(&name, entry) else {
+                continue;
+            };
+            let member = ArchiveMember {
+                name,
+                size,
+                modified_time,
+                fingerprint,
+                data,
+            };
+            if !self.send(member) {
+                break;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    fn make_source(dir: &TempDir) -> FileSystemSource {
+        FileSystemSource {
+            name: "Test Source".to_string(),
+            base_path: dir.path().to_path_buf(),
+            file_extensions: None,
+            include_patterns: None,
+            exclude_patterns: None,
+            max_file_size_bytes: None,
+            extract_archives: true,
+        }
+    }
+
+    fn write_zip(path: &Path, files: &[(&str, &str)]) {
+        let mut zip = zip::ZipWriter::new(File::create(path).unwrap());
+        for (name, content) in files {
+            zip.start_file(*name, zip::write::SimpleFileOptions::default())
+                .unwrap();
+            zip.write_all(content.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    async fn collect(mut rx: mpsc::Receiver<Result<ArchiveMember>>) -> Vec<ArchiveMember> {
+        let mut members = Vec::new();
+        while let Some(member) = rx.recv().await {
+            members.push(member.unwrap());
+        }
+        members.sort_by(|a, b| a.name.cmp(&b.name));
+        members
+    }
+
+    #[test]
+    fn test_detect_archive_kind() {
+        assert_eq!(
+            ArchiveKind::detect(Path::new("/docs/a.ZIP")),
+            Some(ArchiveKind::Zip)
+        );
+        assert_eq!(
+            ArchiveKind::detect(Path::new("/docs/a.tar")),
+            Some(ArchiveKind::Tar)
+        );
+        assert_eq!(
+            ArchiveKind::detect(Path::new("/docs/a.tar.gz")),
+            Some(ArchiveKind::TarGz)
+        );
+        assert_eq!(
+            ArchiveKind::detect(Path::new("/docs/a.tgz")),
+            Some(ArchiveKind::TarGz)
+        );
+        assert_eq!(ArchiveKind::detect(Path::new("/docs/a.gz")), None);
+    }
+
+    #[tokio::test]
+    async fn test_read_zip_members_applies_filters() {
+        let dir = TempDir::new().unwrap();
+        let archive_path = dir.path().join("docs.zip");
+        write_zip(
+            &archive_path,
+            &[
+                ("guide/intro.md", "# Intro"),
+                ("guide/notes.txt", "notes"),
+                ("build/output.md", "generated"),
+            ],
+        );
+
+        let mut source = make_source(&dir);
+        source.file_extensions = Some(vec!["md".to_string()]);
+        source.exclude_patterns = Some(vec!["build/**".to_string()]);
+
+        let members = collect(read_members(
+            archive_path.clone(),
+            ArchiveKind::Zip,
+            source,
+            true,
+        ))
+        .await;
+        assert_eq!(members.len(), 1);
+        assert_eq!(members[0].name, "guide/intro.md");
+        assert_eq!(members[0].data, b"# Intro");
+        assert_eq!(
+            member_path(&archive_path, &members[0].name),
+            PathBuf::from(format!("{}!/guide/intro.md", archive_path.display()))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_read_tar_gz_members() {
+        let dir = TempDir::new().unwrap();
+        let archive_path = dir.path().join("docs.tar.gz");
+        {
+            let encoder = flate2::write::GzEncoder::new(
+                File::create(&archive_path).unwrap(),
+                flate2::Compression::default(),
+            );
+            let mut builder = tar::Builder::new(encoder);
+            let content = b"tar content";
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_mtime(1_700_000_000);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, "readme.txt", &content[..])
+                .unwrap();
+            builder.into_inner().unwrap().finish().unwrap();
+        }
+
+        let members = collect(read_members(
+            archive_path,
+            ArchiveKind::TarGz,
+            make_source(&dir),
+            true,
+        ))
+        .await;
+        assert_eq!(members.len(), 1);
+        assert_eq!(members[0].name, "readme.txt");
+        assert_eq!(members[0].data, b"tar content");
+        assert_eq!(members[0].fingerprint, "1700000000-11");
+    }
+
+    #[tokio::test]
+    async fn test_read_members_size_limit() {
+        let dir = TempDir::new().unwrap();
+        let archive_path = dir.path().join("docs.zip");
+        let big = "x".repeat(2000);
+        write_zip(&archive_path, &[("small.txt", "small"), ("big.txt", &big)]);
+
+        let mut source = make_source(&dir);
+        source.max_file_size_bytes = Some(1000);
+
+        let members = collect(read_members(archive_path, ArchiveKind::Zip, source, false)).await;
+        assert_eq!(members.len(), 1);
+        assert_eq!(members[0].name, "small.txt");
+        assert!(members[0].data.is_empty(), "Listing should skip content");
+    }
+}
@@ -1,3 +1,4 @@
+pub mod archive;
 pub mod connector;
 pub mod models;
 pub mod scanner;
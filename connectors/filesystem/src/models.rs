@@ -1,7 +1,8 @@
+use crate::archive::ArchiveKind;
 use omni_connector_sdk::{ConnectorEvent, DocumentMetadata, DocumentPermissions};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::SystemTime;
 use time::OffsetDateTime;
 
@@ -15,6 +16,9 @@ pub struct FileSystemFile {
     pub modified_time: Option<SystemTime>,
     pub is_directory: bool,
     pub permissions: FileSystemPermissions,
+    /// The archive this file was extracted from, if it is an archive member.
+    #[serde(default)]
+    pub archive_path: Option<PathBuf>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -48,6 +52,12 @@ impl FileSystemFile {
             "executable".to_string(),
             serde_json::json!(self.permissions.executable),
         );
+        if let Some(archive_path) = &self.archive_path {
+            extra.insert(
+                "archive_path".to_string(),
+                serde_json::json!(archive_path.to_string_lossy()),
+            );
+        }
 
         let metadata = DocumentMetadata {
             title: Some(self.name.clone()),
@@ -96,9 +106,13 @@ pub struct FileSystemConfig {
     #[serde(default)]
     pub file_extensions: Option<Vec<String>>,
     #[serde(default)]
+    pub include_patterns: Option<Vec<String>>,
+    #[serde(default)]
     pub exclude_patterns: Option<Vec<String>>,
     #[serde(default)]
     pub max_file_size_bytes: Option<u64>,
+    #[serde(default)]
+    pub extract_archives: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -106,8 +120,10 @@ pub struct FileSystemSource {
     pub name: String,
     pub base_path: PathBuf,
     pub file_extensions: Option<Vec<String>>,
+    pub include_patterns: Option<Vec<String>>,
     pub exclude_patterns: Option<Vec<String>>,
     pub max_file_size_bytes: Option<u64>,
+    pub extract_archives: bool,
 }
 
 impl FileSystemConfig {
@@ -116,38 +132,138 @@ impl FileSystemConfig {
             name,
             base_path: self.base_path,
             file_extensions: self.file_extensions,
+            include_patterns: self.include_patterns,
             exclude_patterns: self.exclude_patterns,
             max_file_size_bytes: self.max_file_size_bytes,
+            extract_archives: self.extract_archives,
         }
     }
 }
 
 impl FileSystemSource {
+    /// Whether an archive at this path is indexed through its members rather
+    /// than as a file of its own.
+    pub fn is_extracted_archive(&self, file_path: &Path) -> bool {
+        self.extract_archives && ArchiveKind::detect(file_path).is_some()
+    }
+
     pub fn should_include_file(&self, file_path: &PathBuf) -> bool {
-        // Check file extension filter
-        if let Some(extensions) = &self.file_extensions {
-            if let Some(ext) = file_path.extension() {
-                let ext_str = ext.to_string_lossy().to_lowercase();
-                if !extensions.iter().any(|e| e.to_lowercase() == ext_str) {
-                    return false;
-                }
-            } else {
-                // No extension, skip if we have extension filters
-                return false;
-            }
+        let relative_paths = [self.relative_path(file_path)];
+
+        // Archives being extracted are filtered member by member, so only the
+        // exclude patterns apply to the archive itself
+        if self.is_extracted_archive(file_path) {
+            return !self.is_excluded(file_path, &relative_paths);
+        }
+
+        self.has_included_extension(file_path)
+            && self.is_included(file_path, &relative_paths)
+            && !self.is_excluded(file_path, &relative_paths)
+    }
+
+    /// Filter a member of an archive as if the archive were a directory.
+    /// Patterns are matched both against the member's path below the base
+    /// path and against its path within the archive.
+    pub fn should_include_member(&self, archive_path: &Path, member_name: &str) -> bool {
+        let virtual_path = archive_path.join(member_name);
+        let relative_paths = [
+            self.relative_path(&virtual_path),
+            member_name.trim_start_matches('/').to_string(),
+        ];
+
+        self.has_included_extension(&virtual_path)
+            && self.is_included(&virtual_path, &relative_paths)
+            && !self.is_excluded(&virtual_path, &relative_paths)
+    }
+
+    fn has_included_extension(&self, file_path: &Path) -> bool {
+        let Some(extensions) = &self.file_extensions else {
+            return true;
+        };
+        // No extension, skip if we have extension filters
+        let Some(ext) = file_path.extension() else {
+            return false;
+        };
+        let ext_str = ext.to_string_lossy().to_lowercase();
+        extensions.iter().any(|e| e.to_lowercase() == ext_str)
+    }
+
+    fn is_included(&self, file_path: &Path, relative_paths: &[String]) -> bool {
+        match &self.include_patterns {
+            Some(patterns) if !patterns.is_empty() => patterns
+                .iter()
+                .any(|pattern| matches_pattern(pattern, file_path, relative_paths)),
+            _ => true,
+        }
+    }
+
+    fn is_excluded(&self, file_path: &Path, relative_paths: &[String]) -> bool {
+        self.exclude_patterns.as_ref().is_some_and(|patterns| {
+            patterns
+                .iter()
+                .any(|pattern| matches_pattern(pattern, file_path, relative_paths))
+        })
+    }
+
+    fn relative_path(&self, file_path: &Path) -> String {
+        file_path
+            .strip_prefix(&self.base_path)
+            .unwrap_or(file_path)
+            .to_string_lossy()
+            .replace('\\', "/")
+    }
+}
+
+/// Match a filter pattern against a file.
+///
+/// Patterns without glob syntax (`*`, `?`) match anywhere in the full path,
+/// as plain substrings. Glob patterns containing `/` must match a whole
+/// relative path, with `*` and `?` staying within one path component and `**`
+/// spanning any number of them. Glob patterns without `/` match any single
+/// component, so `*.log` matches a log file at any depth.
+fn matches_pattern(pattern: &str, file_path: &Path, relative_paths: &[String]) -> bool {
+    if !pattern.contains(['*', '?']) {
+        return file_path.to_string_lossy().contains(pattern);
+    }
+
+    let pattern: Vec<char> = pattern.trim_start_matches('/').chars().collect();
+    let anchored = pattern.contains(&'/');
+    relative_paths.iter().any(|path| {
+        if anchored {
+            let path: Vec<char> = path.chars().collect();
+            glob_match(&pattern, &path)
+        } else {
+            path.split('/').any(|component| {
+                let component: Vec<char> = component.chars().collect();
+                glob_match(&pattern, &component)
+            })
         }
+    })
+}
 
-        // Check exclude patterns
-        if let Some(patterns) = &self.exclude_patterns {
-            let path_str = file_path.to_string_lossy();
-            for pattern in patterns {
-                if path_str.contains(pattern) {
-                    return false;
+fn glob_match(pattern: &[char], text: &[char]) -> bool {
+    match pattern {
+        [] => text.is_empty(),
+        ['*', '*', '/', rest @ ..] => {
+            glob_match(rest, text)
+                || (0..text.len()).any(|i| text[i] == '/' && glob_match(rest, &text[i + 1..]))
+        }
+        ['*', '*', rest @ ..] => (0..=text.len()).any(|i| glob_match(rest, &text[i..])),
+        ['*', rest @ ..] => {
+            for i in 0..=text.len() {
+                if glob_match(rest, &text[i..]) {
+                    return true;
+                }
+                if i < text.len() && text[i] == '/' {
+                    break;
                 }
             }
+            false
         }
-
-        true
+        ['?', rest @ ..] => {
+            matches!(text.first(), Some(c) if *c != '/') && glob_match(rest, &text[1..])
+        }
+        [c, rest @ ..] => text.first() == Some(c) && glob_match(rest, &text[1..]),
     }
 }
 
@@ -178,6 +294,7 @@ mod tests {
                 writable: true,
                 executable: false,
             },
+            archive_path: None,
             path,
         }
     }
@@ -201,4 +318,45 @@ mod tests {
             _ => panic!("Expected DocumentCreated event"),
         }
     }
+
+    fn make_source(include: &[&str], exclude: &[&str]) -> FileSystemSource {
+        let patterns = |p: &[&str]| Some(p.iter().map(|s| s.to_string()).collect());
+        FileSystemSource {
+            name: "Test Source".to_string(),
+            base_path: PathBuf::from("/data"),
+            file_extensions: None,
+            include_patterns: patterns(include),
+            exclude_patterns: patterns(exclude),
+            max_file_size_bytes: None,
+            extract_archives: false,
+        }
+    }
+
+    #[test]
+    fn test_glob_include_and_exclude_patterns() {
+        let source = make_source(&["docs/**/*.md", "*.txt"], &["*.draft.md", "node_modules"]);
+
+        assert!(source.should_include_file(&PathBuf::from("/data/docs/guide.md")));
+        assert!(source.should_include_file(&PathBuf::from("/data/docs/a/b/guide.md")));
+        assert!(source.should_include_file(&PathBuf::from("/data/notes/todo.txt")));
+        assert!(!source.should_include_file(&PathBuf::from("/data/other/guide.md")));
+        assert!(!source.should_include_file(&PathBuf::from("/data/docs/plan.draft.md")));
+        assert!(!source.should_include_file(&PathBuf::from("/data/node_modules/pkg/readme.txt")));
+    }
+
+    #[test]
+    fn test_archives_bypass_include_filters_when_extracting() {
+        let mut source = make_source(&["*.md"], &["backups/**"]);
+        source.file_extensions = Some(vec!["md".to_string()]);
+        assert!(!source.should_include_file(&PathBuf::from("/data/docs.zip")));
+
+        source.extract_archives = true;
+        assert!(source.should_include_file(&PathBuf::from("/data/docs.zip")));
+        assert!(!source.should_include_file(&PathBuf::from("/data/backups/old.tar.gz")));
+
+        let archive = PathBuf::from("/data/docs.zip");
+        assert!(source.should_include_member(&archive, "guide/intro.md"));
+        assert!(!source.should_include_member(&archive, "guide/notes.txt"));
+        assert!(!source.should_include_member(&archive, "backups/intro.md"));
+    }
 }
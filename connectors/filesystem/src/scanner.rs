@@ -14,6 +14,10 @@ impl FileSystemScanner {
         Self { source }
     }
 
+    pub fn source(&self) -> &FileSystemSource {
+        &self.source
+    }
+
     // TODO: stream results — materializing all FileSystemFile into a Vec
     // doesn't scale past ~10k files. Swap to an iterator/channel.
     pub async fn scan_directory(&self) -> Result<Vec<FileSystemFile>> {
@@ -79,9 +83,10 @@ impl FileSystemScanner {
             return Ok(None);
         }
 
-        // Check file size limit
+        // Check file size limit. Archives being extracted are exempt, their
+        // members are checked against it instead.
         if let Some(max_size) = self.source.max_file_size_bytes {
-            if metadata.len() > max_size {
+            if metadata.len() > max_size && !self.source.is_extracted_archive(&path) {
                 debug!(
                     "Skipping file due to size limit ({} > {}): {}",
                     metadata.len(),
@@ -109,6 +114,7 @@ impl FileSystemScanner {
             modified_time: metadata.modified().ok(),
             is_directory,
             permissions,
+            archive_path: None,
         };
 
         debug!("Processed file: {}", path.display());
@@ -138,9 +144,10 @@ impl FileSystemScanner {
             return Ok(None);
         }
 
-        // Check file size limit
+        // Check file size limit. Archives being extracted are exempt, their
+        // members are checked against it instead.
         if let Some(max_size) = self.source.max_file_size_bytes {
-            if metadata.len() > max_size {
+            if metadata.len() > max_size && !self.source.is_extracted_archive(path) {
                 debug!(
                     "Skipping file due to size limit ({} > {}): {}",
                     metadata.len(),
@@ -175,6 +182,7 @@ impl FileSystemScanner {
             modified_time,
             is_directory,
             permissions,
+            archive_path: None,
         };
 
         debug!("Got file info: {}", path.display());
@@ -230,8 +238,10 @@ mod tests {
             name: "Test Source".to_string(),
             base_path: dir.path().to_path_buf(),
             file_extensions: None,
+            include_patterns: None,
             exclude_patterns: None,
             max_file_size_bytes: None,
+            extract_archives: false,
         }
     }
 
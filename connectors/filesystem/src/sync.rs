@@ -2,7 +2,8 @@ use anyhow::{Context, Result};
 use omni_connector_sdk::SyncContext;
 use tracing::{info, warn};
 
-use crate::models::FileSystemConfig;
+use crate::archive::{self, ArchiveKind};
+use crate::models::{FileSystemConfig, FileSystemFile};
 use crate::scanner::FileSystemScanner;
 
 pub async fn run_sync(
@@ -29,45 +30,43 @@ pub async fn run_sync(
             break;
         }
 
-        let file_path = file.path.clone();
-        let file_name = file.name.clone();
-        let mime_type = file.mime_type.clone();
-
-        let data = match std::fs::read(&file_path) {
-            Ok(data) => data,
-            Err(error) => {
-                warn!("Failed to read file {}: {}", file_path.display(), error);
-                continue;
+        if let Some(kind) =
+            ArchiveKind::detect(&file.path).filter(|_| scanner.source().extract_archives)
+        {
+            let mut members =
+                archive::read_members(file.path.clone(), kind, scanner.source().clone(), true);
+            while let Some(member) = members.recv().await {
+                if ctx.is_cancelled() {
+                    break;
+                }
+                let member = match member {
+                    Ok(member) => member,
+                    Err(error) => {
+                        warn!("{:#}", error);
+                        break;
+                    }
+                };
+                let member_file = archive::member_file(&file, &member);
+                if store_and_emit(&ctx, member_file, member.data).await {
+                    total_processed += 1;
+                    if total_processed.is_multiple_of(100) {
+                        info!("Processed {} files", total_processed);
+                        let _ = ctx.increment_scanned(100).await;
+                    }
+                }
             }
-        };
+            continue;
+        }
 
-        let content_id = match ctx
-            .extract_and_store_content(data, &mime_type, Some(&file_name))
-            .await
-        {
-            Ok(content_id) => content_id,
+        let data = match std::fs::read(&file.path) {
+            Ok(data) => data,
             Err(error) => {
-                warn!(
-                    "Failed to extract/store content for {}: {}",
-                    file_path.display(),
-                    error
-                );
+                warn!("Failed to read file {}: {}", file.path.display(), error);
                 continue;
             }
         };
 
-        let event = file.to_connector_event(
-            ctx.sync_run_id().to_string(),
-            ctx.source_id().to_string(),
-            content_id,
-        );
-
-        if let Err(error) = ctx.emit_event(event).await {
-            warn!(
-                "Failed to emit event for {}: {}",
-                file_path.display(),
-                error
-            );
+        if !store_and_emit(&ctx, file, data).await {
             continue;
         }
 
@@ -91,3 +90,41 @@ pub async fn run_sync(
 
     Ok(())
 }
+
+/// Extract and store a file's content and emit its document, returning
+/// whether it was indexed.
+async fn store_and_emit(ctx: &SyncContext, file: FileSystemFile, data: Vec<u8>) -> bool {
+    let file_path = file.path.clone();
+
+    let content_id = match ctx
+        .extract_and_store_content(data, &file.mime_type, Some(&file.name))
+        .await
+    {
+        Ok(content_id) => content_id,
+        Err(error) => {
+            warn!(
+                "Failed to extract/store content for {}: {}",
+                file_path.display(),
+                error
+            );
+            return false;
+        }
+    };
+
+    let event = file.to_connector_event(
+        ctx.sync_run_id().to_string(),
+        ctx.source_id().to_string(),
+        content_id,
+    );
+
+    if let Err(error) = ctx.emit_event(event).await {
+        warn!(
+            "Failed to emit event for {}: {}",
+            file_path.display(),
+            error
+        );
+        return false;
+    }
+
+    true
+}
@@ -6,12 +6,14 @@
 //! `ctx.is_cancelled()` flips. All event emission and lifecycle calls go
 //! through the SDK — no direct database or queue access.
 
-use crate::models::{FileSystemConfig, FileSystemSource};
+use crate::archive::{self, ArchiveKind};
+use crate::models::{FileSystemConfig, FileSystemFile, FileSystemSource};
 use crate::scanner::FileSystemScanner;
 use anyhow::Result;
 use notify::{Config, Event, EventKind, RecursiveMode, Watcher};
 use omni_connector_sdk::{ConnectorEvent, DocumentMetadata, DocumentPermissions, SyncContext};
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::time::{Duration, SystemTime};
 use tokio::sync::mpsc;
 use tracing::{debug, info, warn};
@@ -23,6 +25,11 @@ enum FsEvent {
     Deleted(PathBuf),
 }
 
+/// Fingerprints of the indexed members of each archive, keyed by archive path
+/// and then member name, so that a changed archive only re-indexes the members
+/// that changed and deletes the ones that are gone.
+type ArchiveIndex = HashMap<PathBuf, HashMap<String, String>>;
+
 pub async fn run_realtime(
     source_name: String,
     source_config: FileSystemConfig,
@@ -30,6 +37,11 @@ pub async fn run_realtime(
 ) -> Result<()> {
     let source = source_config.into_source(source_name);
     let scanner = FileSystemScanner::new(source.clone());
+    let mut archive_index = if source.extract_archives {
+        build_archive_index(&scanner).await?
+    } else {
+        ArchiveIndex::new()
+    };
 
     let (tx, mut rx) = mpsc::unbounded_channel::<FsEvent>();
 
@@ -65,10 +77,10 @@ pub async fn run_realtime(
     while !ctx.is_cancelled() {
         tokio::select! {
             event = rx.recv() => match event {
-                Some(event) => match handle_event(&ctx, &scanner, event).await {
-                    Ok(Emitted::Yes) => {
-                        ctx.increment_scanned(1).await?;
-                        ctx.increment_updated(1).await?;
+                Some(event) => match handle_event(&ctx, &scanner, &mut archive_index, event).await {
+                    Ok(Emitted::Yes(count)) => {
+                        ctx.increment_scanned(count).await?;
+                        ctx.increment_updated(count).await?;
                     }
                     Ok(Emitted::Skipped) => {}
                     Err(error) => warn!("Failed to handle filesystem event: {}", error),
@@ -96,10 +108,34 @@ pub async fn run_realtime(
 }
 
 enum Emitted {
-    Yes,
+    Yes(i32),
     Skipped,
 }
 
+/// List the members of every archive already in the source.
+async fn build_archive_index(scanner: &FileSystemScanner) -> Result<ArchiveIndex> {
+    let mut index = ArchiveIndex::new();
+    for file in scanner.scan_directory().await? {
+        let Some(kind) = ArchiveKind::detect(&file.path) else {
+            continue;
+        };
+        let mut members =
+            archive::read_members(file.path.clone(), kind, scanner.source().clone(), false);
+        let mut fingerprints = HashMap::new();
+        while let Some(member) = members.recv().await {
+            match member {
+                Ok(member) => {
+                    fingerprints.insert(member.name, member.fingerprint);
+                }
+                Err(error) => warn!("{:#}", error),
+            }
+        }
+        index.insert(file.path, fingerprints);
+    }
+    info!("Indexed members of {} archives", index.len());
+    Ok(index)
+}
+
 fn translate(event: &Event, source: &FileSystemSource) -> Vec<FsEvent> {
     let mut out = Vec::new();
     for path in &event.paths {
@@ -123,18 +159,20 @@ fn translate(event: &Event, source: &FileSystemSource) -> Vec<FsEvent> {
 async fn handle_event(
     ctx: &SyncContext,
     scanner: &FileSystemScanner,
+    archive_index: &mut ArchiveIndex,
     event: FsEvent,
 ) -> Result<Emitted> {
     let (path, is_created) = match event {
         FsEvent::Deleted(path) => {
-            let connector_event = ConnectorEvent::DocumentDeleted {
-                sync_run_id: ctx.sync_run_id().to_string(),
-                source_id: ctx.source_id().to_string(),
-                document_id: path.to_string_lossy().to_string(),
-            };
-            ctx.emit_event(connector_event).await?;
-            info!("Emitted delete event for {}", path.display());
-            return Ok(Emitted::Yes);
+            if scanner.source().is_extracted_archive(&path) {
+                let members = archive_index.remove(&path).unwrap_or_default();
+                for member_name in members.keys() {
+                    emit_deleted(ctx, &archive::member_path(&path, member_name)).await?;
+                }
+                return Ok(emitted(members.len() as i32));
+            }
+            emit_deleted(ctx, &path).await?;
+            return Ok(Emitted::Yes(1));
         }
         FsEvent::Created(path) => (path, true),
         FsEvent::Modified(path) => (path, false),
@@ -148,6 +186,12 @@ async fn handle_event(
         }
     };
 
+    if let Some(kind) = ArchiveKind::detect(&file.path)
+        .filter(|_| scanner.source().is_extracted_archive(&file.path))
+    {
+        return handle_archive_change(ctx, scanner, archive_index, file, kind).await;
+    }
+
     let data = match std::fs::read(&file.path) {
         Ok(d) if !d.is_empty() => d,
         Ok(_) => {
@@ -160,6 +204,99 @@ async fn handle_event(
         }
     };
 
+    if emit_file(ctx, file, data, is_created).await? {
+        Ok(Emitted::Yes(1))
+    } else {
+        Ok(Emitted::Skipped)
+    }
+}
+
+/// Re-read a created or modified archive, emitting created and updated events
+/// for its new and changed members and deleted events for the members it no
+/// longer contains.
+async fn handle_archive_change(
+    ctx: &SyncContext,
+    scanner: &FileSystemScanner,
+    archive_index: &mut ArchiveIndex,
+    file: FileSystemFile,
+    kind: ArchiveKind,
+) -> Result<Emitted> {
+    let mut previous = archive_index.remove(&file.path).unwrap_or_default();
+    let mut current = HashMap::new();
+    let mut count = 0;
+
+    let mut members =
+        archive::read_members(file.path.clone(), kind, scanner.source().clone(), true);
+    while let Some(member) = members.recv().await {
+        let member = match member {
+            Ok(member) => member,
+            Err(error) => {
+                // The archive may be mid-write; keep what was indexed before
+                // and pick up the rest on its next modification.
+                warn!("{:#}", error);
+                previous.extend(current);
+                archive_index.insert(file.path, previous);
+                return Ok(emitted(count));
+            }
+        };
+
+        let previous_fingerprint = previous.remove(&member.name);
+        if previous_fingerprint.as_ref() == Some(&member.fingerprint) {
+            current.insert(member.name, member.fingerprint);
+            continue;
+        }
+
+        let member_file = archive::member_file(&file, &member);
+        if emit_file(
+            ctx,
+            member_file,
+            member.data,
+            previous_fingerprint.is_none(),
+        )
+        .await?
+        {
+            current.insert(member.name, member.fingerprint);
+            count += 1;
+        } else if let Some(fingerprint) = previous_fingerprint {
+            current.insert(member.name, fingerprint);
+        }
+    }
+
+    for member_name in previous.keys() {
+        emit_deleted(ctx, &archive::member_path(&file.path, member_name)).await?;
+        count += 1;
+    }
+    archive_index.insert(file.path, current);
+    Ok(emitted(count))
+}
+
+fn emitted(count: i32) -> Emitted {
+    if count > 0 {
+        Emitted::Yes(count)
+    } else {
+        Emitted::Skipped
+    }
+}
+
+async fn emit_deleted(ctx: &SyncContext, path: &Path) -> Result<()> {
+    let connector_event = ConnectorEvent::DocumentDeleted {
+        sync_run_id: ctx.sync_run_id().to_string(),
+        source_id: ctx.source_id().to_string(),
+        document_id: path.to_string_lossy().to_string(),
+    };
+    ctx.emit_event(connector_event).await?;
+    info!("Emitted delete event for {}", path.display());
+    Ok(())
+}
+
+/// Store a file's content and emit its created or updated event, returning
+/// whether it was emitted.
+async fn emit_file(
+    ctx: &SyncContext,
+    file: FileSystemFile,
+    data: Vec<u8>,
+    is_created: bool,
+) -> Result<bool> {
     let file_name = file.name.clone();
     let content_id = match ctx
         .extract_and_store_content(data, &file.mime_type, Some(&file_name))
@@ -168,10 +305,11 @@ async fn handle_event(
         Ok(id) => id,
         Err(e) => {
             warn!("Extract/store failed for {}: {}", file.path.display(), e);
-            return Ok(Emitted::Skipped);
+            return Ok(false);
         }
     };
 
+    let path = file.path.clone();
     let connector_event = if is_created {
         file.to_connector_event(
             ctx.sync_run_id().to_string(),
@@ -187,11 +325,11 @@ async fn handle_event(
         if is_created { "create" } else { "update" },
         path.display()
     );
-    Ok(Emitted::Yes)
+    Ok(true)
 }
 
 fn build_updated_event(
-    file: &crate::models::FileSystemFile,
+    file: &FileSystemFile,
     ctx: &SyncContext,
     content_id: String,
 ) -> ConnectorEvent {
@@ -78,6 +78,10 @@ impl AdminClient {
         }
     }
 
+    pub fn rate_limiter(&self) -> Option<&Arc<RateLimiter>> {
+        self.rate_limiter.as_ref()
+    }
+
     pub async fn search_users(
         &self,
         token: &str,
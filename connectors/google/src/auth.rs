@@ -427,6 +427,9 @@ pub fn get_scopes_for_source_type(source_type: SourceType) -> Vec<String> {
             );
             scopes.push("https://www.googleapis.com/auth/chat.messages.readonly".to_string());
         }
+        SourceType::GoogleCalendar => {
+            scopes.push("https://www.googleapis.com/auth/calendar.readonly".to_string());
+        }
         _ => {
             scopes.push("https://www.googleapis.com/auth/drive.readonly".to_string());
             scopes.push("https://www.googleapis.com/auth/gmail.readonly".to_string());
@@ -452,6 +455,9 @@ pub fn get_oauth_scopes_for_source_type(source_type: SourceType) -> Vec<String>
                 "https://www.googleapis.com/auth/chat.messages.readonly".to_string(),
             ]
         }
+        SourceType::GoogleCalendar => {
+            vec!["https://www.googleapis.com/auth/calendar.readonly".to_string()]
+        }
         _ => {
             vec![
                 "https://www.googleapis.com/auth/drive.readonly".to_string(),
@@ -505,6 +511,17 @@ pub fn google_max_retries() -> u32 {
         .unwrap_or(DEFAULT_GOOGLE_MAX_RETRIES)
 }
 
+/// Ceiling for each source's adaptive Drive rate limiter. Defaults to the
+/// Drive API's 12,000 req/min project quota.
+pub const DEFAULT_GOOGLE_API_RATE_LIMIT_MAX: u32 = 200;
+
+pub fn google_api_rate_limit_max() -> u32 {
+    std::env::var("GOOGLE_API_RATE_LIMIT_MAX")
+        .ok()
+        .and_then(|value| value.parse::<u32>().ok())
+        .unwrap_or(DEFAULT_GOOGLE_API_RATE_LIMIT_MAX)
+}
+
 pub fn is_auth_error(status: StatusCode) -> bool {
     status == StatusCode::UNAUTHORIZED
 }
@@ -513,6 +530,14 @@ pub fn is_rate_limit_error(status: StatusCode) -> bool {
     status == StatusCode::TOO_MANY_REQUESTS
 }
 
+/// Google reports per-user and per-project quota exhaustion as 403 with a
+/// `rateLimitExceeded` / `userRateLimitExceeded` reason rather than 429.
+fn is_quota_error(status: StatusCode, error_text: &str) -> bool {
+    status == StatusCode::FORBIDDEN
+        && (error_text.contains("rateLimitExceeded")
+            || error_text.contains("userRateLimitExceeded"))
+}
+
 pub(crate) fn parse_retry_after(headers: &HeaderMap) -> Option<StdDuration> {
     let value = headers.get(RETRY_AFTER)?.to_str().ok()?;
 
@@ -544,8 +569,11 @@ fn classify_google_api_status<T>(
                 retry_after,
                 message,
             }),
-            None => ApiResult::RetryableError(RetryableError::Transient(anyhow!(message))),
+            None => ApiResult::RetryableError(RetryableError::Throttled(anyhow!(message))),
         }
+    } else if is_quota_error(status, &error_text) {
+        let message = format!("{}: HTTP {} - {}", context, status, error_text);
+        ApiResult::RetryableError(RetryableError::Throttled(anyhow!(message)))
     } else {
         ApiResult::OtherError(anyhow!("{}: HTTP {} - {}", context, status, error_text))
     }
@@ -556,7 +584,8 @@ fn classify_google_api_status<T>(
 /// 401 responses are returned as auth errors so the caller can refresh a token.
 /// 429 responses are returned as retryable rate-limiter errors: with
 /// `Retry-After` they use the server-specified wait; without it they use the
-/// rate limiter's existing exponential backoff path.
+/// rate limiter's exponential backoff path. 403 quota errors are treated like
+/// a 429 without `Retry-After`. Both lower the adaptive rate.
 pub async fn classify_google_api_error<T>(
     response: reqwest::Response,
     context: impl Into<String>,
@@ -817,7 +846,7 @@ mod tests {
     }
 
     #[test]
-    fn test_classify_google_429_without_retry_after_as_transient() {
+    fn test_classify_google_429_without_retry_after_as_throttled() {
         let result: ApiResult<()> = classify_google_api_status(
             StatusCode::TOO_MANY_REQUESTS,
             &HeaderMap::new(),
@@ -826,10 +855,25 @@ mod tests {
         );
 
         match result {
-            ApiResult::RetryableError(RetryableError::Transient(e)) => {
+            ApiResult::RetryableError(RetryableError::Throttled(e)) => {
                 assert!(e.to_string().contains("quota exceeded"));
             }
-            _ => panic!("Expected RetryableError::Transient variant"),
+            _ => panic!("Expected RetryableError::Throttled variant"),
+        }
+    }
+
+    #[test]
+    fn test_classify_google_403_quota_as_throttled() {
+        let result: ApiResult<()> = classify_google_api_status(
+            StatusCode::FORBIDDEN,
+            &HeaderMap::new(),
+            r#"{"error":{"errors":[{"reason":"userRateLimitExceeded"}]}}"#.to_string(),
+            "test request".to_string(),
+        );
+
+        match result {
+            ApiResult::RetryableError(RetryableError::Throttled(_)) => {}
+            _ => panic!("Expected RetryableError::Throttled variant"),
         }
     }
 
@@ -0,0 +1,236 @@
+use anyhow::{Context, Result, anyhow};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use crate::auth::{ApiResult, GoogleAuth, classify_google_api_error, execute_with_auth_retry};
+use crate::rate_limits::{CALENDAR_API_FAMILY, LearnedRates};
+use omni_connector_sdk::RateLimiter;
+
+const CALENDAR_API_BASE: &str = "https://www.googleapis.com/calendar/v3";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GoogleCalendarEventTime {
+    /// Set for all-day events, as `YYYY-MM-DD`.
+    #[serde(default)]
+    pub date: Option<String>,
+    /// Set for timed events, as RFC 3339.
+    #[serde(default, rename = "dateTime")]
+    pub date_time: Option<String>,
+    #[serde(default, rename = "timeZone")]
+    pub time_zone: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GoogleCalendarPerson {
+    #[serde(default)]
+    pub email: Option<String>,
+    #[serde(default, rename = "displayName")]
+    pub display_name: Option<String>,
+    #[serde(default, rename = "self")]
+    pub is_self: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GoogleCalendarAttendee {
+    #[serde(default)]
+    pub email: Option<String>,
+    #[serde(default, rename = "displayName")]
+    pub display_name: Option<String>,
+    #[serde(default, rename = "responseStatus")]
+    pub response_status: Option<String>,
+    #[serde(default)]
+    pub optional: bool,
+    #[serde(default)]
+    pub organizer: bool,
+    /// Meeting rooms and other resources are listed as attendees.
+    #[serde(default)]
+    pub resource: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GoogleCalendarAttachment {
+    #[serde(rename = "fileUrl")]
+    pub file_url: String,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default, rename = "mimeType")]
+    pub mime_type: Option<String>,
+    #[serde(default, rename = "fileId")]
+    pub file_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GoogleCalendarEvent {
+    pub id: String,
+    /// `confirmed`, `tentative` or `cancelled`.
+    #[serde(default)]
+    pub status: Option<String>,
+    #[serde(default, rename = "htmlLink")]
+    pub html_link: Option<String>,
+    #[serde(default)]
+    pub created: Option<String>,
+    #[serde(default)]
+    pub updated: Option<String>,
+    #[serde(default)]
+    pub summary: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub location: Option<String>,
+    #[serde(default)]
+    pub creator: Option<GoogleCalendarPerson>,
+    #[serde(default)]
+    pub organizer: Option<GoogleCalendarPerson>,
+    #[serde(default)]
+    pub start: Option<GoogleCalendarEventTime>,
+    #[serde(default)]
+    pub end: Option<GoogleCalendarEventTime>,
+    #[serde(default)]
+    pub attendees: Vec<GoogleCalendarAttendee>,
+    #[serde(default)]
+    pub attachments: Vec<GoogleCalendarAttachment>,
+    #[serde(default, rename = "hangoutLink")]
+    pub hangout_link: Option<String>,
+    #[serde(default, rename = "recurringEventId")]
+    pub recurring_event_id: Option<String>,
+    /// `default`, `outOfOffice`, `focusTime`, `workingLocation`, `birthday`
+    /// or `fromGmail`.
+    #[serde(default, rename = "eventType")]
+    pub event_type: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ListEventsResponse {
+    #[serde(default)]
+    pub items: Vec<GoogleCalendarEvent>,
+    #[serde(rename = "nextPageToken")]
+    pub next_page_token: Option<String>,
+}
+
+/// Which events of a calendar to list.
+#[derive(Debug, Clone)]
+pub struct ListEventsQuery {
+    /// Events ending after this RFC 3339 time.
+    pub time_min: String,
+    /// Events starting before this RFC 3339 time.
+    pub time_max: String,
+    /// Only events changed since this RFC 3339 time, including deleted ones.
+    pub updated_min: Option<String>,
+}
+
+#[derive(Clone)]
+pub struct CalendarClient {
+    client: Client,
+    user_rate_limiters: Arc<RwLock<HashMap<String, Arc<RateLimiter>>>>,
+    learned_rates: Arc<LearnedRates>,
+}
+
+impl CalendarClient {
+    /// A client whose per-user limiters start from `learned_rates`.
+    pub fn with_learned_rates(learned_rates: Arc<LearnedRates>) -> Self {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(60))
+            .connect_timeout(Duration::from_secs(10))
+            .pool_max_idle_per_host(10)
+            .pool_idle_timeout(Duration::from_secs(90))
+            .tcp_keepalive(Duration::from_secs(60))
+            .build()
+            .expect("Failed to build HTTP client");
+        Self {
+            client,
+            user_rate_limiters: Arc::new(RwLock::new(HashMap::new())),
+            learned_rates,
+        }
+    }
+
+    fn get_or_create_user_rate_limiter(&self, user_email: &str) -> Result<Arc<RateLimiter>> {
+        {
+            let rate_limiters = self.user_rate_limiters.read().map_err(|e| {
+                anyhow!(
+                    "Failed to acquire read lock on Calendar user rate limiters: {:?}",
+                    e
+                )
+            })?;
+            if let Some(limiter) = rate_limiters.get(user_email) {
+                return Ok(limiter.clone());
+            }
+        }
+        let mut rate_limiters = self.user_rate_limiters.write().map_err(|e| {
+            anyhow!(
+                "Failed to acquire write lock on Calendar user rate limiters: {:?}",
+                e
+            )
+        })?;
+        Ok(rate_limiters
+            .entry(user_email.to_string())
+            .or_insert_with(|| self.learned_rates.limiter(CALENDAR_API_FAMILY, 10, 10))
+            .clone())
+    }
+
+    /// The per-user Calendar rate limiters created so far.
+    pub fn user_rate_limiters(&self) -> Vec<Arc<RateLimiter>> {
+        self.user_rate_limiters
+            .read()
+            .map(|map| map.values().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// List one page of a calendar's events, with recurring events expanded
+    /// into their instances.
+    pub async fn list_events(
+        &self,
+        auth: &GoogleAuth,
+        user_email: &str,
+        calendar_id: &str,
+        query: &ListEventsQuery,
+        page_token: Option<&str>,
+    ) -> Result<ListEventsResponse> {
+        let calendar_id = calendar_id.to_string();
+        let page_token = page_token.map(str::to_string);
+        let limiter = self.get_or_create_user_rate_limiter(user_email)?;
+        execute_with_auth_retry(auth, user_email, limiter, |token| {
+            let calendar_id = calendar_id.clone();
+            let page_token = page_token.clone();
+            async move {
+                let url = format!(
+                    "{}/calendars/{}/events",
+                    CALENDAR_API_BASE,
+                    urlencoding::encode(&calendar_id)
+                );
+                let mut params = vec![
+                    ("maxResults", "2500".to_string()),
+                    ("singleEvents", "true".to_string()),
+                    ("timeMin", query.time_min.clone()),
+                    ("timeMax", query.time_max.clone()),
+                ];
+                if let Some(updated_min) = &query.updated_min {
+                    params.push(("updatedMin", updated_min.clone()));
+                }
+                if let Some(token) = page_token {
+                    params.push(("pageToken", token));
+                }
+                let response = self
+                    .client
+                    .get(&url)
+                    .bearer_auth(&token)
+                    .query(&params)
+                    .send()
+                    .await?;
+                if !response.status().is_success() {
+                    return classify_google_api_error(response, "Failed to list Calendar events")
+                        .await;
+                }
+                Ok(ApiResult::Success(
+                    response
+                        .json::<ListEventsResponse>()
+                        .await
+                        .context("Failed to parse Calendar events")?,
+                ))
+            }
+        })
+        .await
+    }
+}
@@ -5,9 +5,8 @@ use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
 use std::time::Duration;
 
-use crate::auth::{
-    classify_google_api_error, execute_with_auth_retry, google_max_retries, ApiResult, GoogleAuth,
-};
+use crate::auth::{classify_google_api_error, execute_with_auth_retry, ApiResult, GoogleAuth};
+use crate::rate_limits::{LearnedRates, CHAT_API_FAMILY};
 use omni_connector_sdk::RateLimiter;
 
 const CHAT_API_BASE: &str = "https://chat.googleapis.com/v1";
@@ -214,10 +213,12 @@ pub struct ListSpaceEventsResponse {
 pub struct ChatClient {
     client: Client,
     user_rate_limiters: Arc<RwLock<HashMap<String, Arc<RateLimiter>>>>,
+    learned_rates: Arc<LearnedRates>,
 }
 
 impl ChatClient {
-    pub fn with_rate_limiter(rate_limiter: Arc<RateLimiter>) -> Self {
+    /// A client whose per-user limiters start from `learned_rates`.
+    pub fn with_learned_rates(learned_rates: Arc<LearnedRates>) -> Self {
         let client = Client::builder()
             .timeout(Duration::from_secs(60))
             .connect_timeout(Duration::from_secs(10))
@@ -226,10 +227,10 @@ impl ChatClient {
             .tcp_keepalive(Duration::from_secs(60))
             .build()
             .expect("Failed to build HTTP client");
-        let _ = rate_limiter;
         Self {
             client,
             user_rate_limiters: Arc::new(RwLock::new(HashMap::new())),
+            learned_rates,
         }
     }
 
@@ -253,10 +254,18 @@ impl ChatClient {
         })?;
         Ok(rate_limiters
             .entry(user_email.to_string())
-            .or_insert_with(|| Arc::new(RateLimiter::new(25, google_max_retries())))
+            .or_insert_with(|| self.learned_rates.limiter(CHAT_API_FAMILY, 25, 25))
             .clone())
     }
 
+    /// The per-user Chat rate limiters created so far.
+    pub fn user_rate_limiters(&self) -> Vec<Arc<RateLimiter>> {
+        self.user_rate_limiters
+            .read()
+            .map(|map| map.values().cloned().collect())
+            .unwrap_or_default()
+    }
+
     pub async fn list_spaces_for_user(
         &self,
         auth: &GoogleAuth,
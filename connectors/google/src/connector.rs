@@ -829,7 +829,10 @@ impl Connector for GoogleConnector {
     }
 
     fn description(&self) -> Option<String> {
-        Some("Connect to Google Drive, Docs, Gmail, Google Chat, and more".to_string())
+        Some(
+            "Connect to Google Drive, Docs, Gmail, Google Chat, Google Calendar, and more"
+                .to_string(),
+        )
     }
 
     fn source_types(&self) -> Vec<SourceType> {
@@ -837,6 +840,7 @@ impl Connector for GoogleConnector {
             SourceType::GoogleDrive,
             SourceType::Gmail,
             SourceType::GoogleChat,
+            SourceType::GoogleCalendar,
         ]
     }
 
@@ -8,13 +8,12 @@ use tracing::{debug, warn};
 
 use std::collections::HashMap;
 
-use crate::auth::{
-    classify_google_api_error, execute_with_auth_retry, google_max_retries, ApiResult, GoogleAuth,
-};
+use crate::auth::{classify_google_api_error, execute_with_auth_retry, ApiResult, GoogleAuth};
 use crate::models::{
     DriveChangesResponse, GoogleDriveFile, GooglePresentation, WebhookChannel,
     WebhookChannelResponse,
 };
+use crate::rate_limits::{LearnedRates, DOCS_API_FAMILY, DRIVE_API_FAMILY, SHEETS_API_FAMILY};
 use omni_connector_sdk::{RateLimiter, RetryableError};
 
 /// Content returned by `get_file_content`. Text formats are already extracted;
@@ -133,6 +132,7 @@ pub struct DriveClient {
     // Sheets has a lower per-user read quota (60 req/min). Keep it separate
     // from Docs/Slides so spreadsheet crawls cannot overrun the Sheets quota.
     user_sheets_rate_limiters: Arc<RwLock<HashMap<String, Arc<RateLimiter>>>>,
+    learned_rates: Arc<LearnedRates>,
 }
 
 impl DriveClient {
@@ -146,7 +146,8 @@ impl DriveClient {
             .build()
             .expect("Failed to build HTTP client");
 
-        let rate_limiter = Arc::new(RateLimiter::new(200, google_max_retries())); // 12000 req/min
+        let learned_rates = Arc::new(LearnedRates::default());
+        let rate_limiter = learned_rates.limiter(DRIVE_API_FAMILY, 200, 200); // 12000 req/min
         let user_rate_limiters = Arc::new(RwLock::new(HashMap::new()));
         let user_sheets_rate_limiters = Arc::new(RwLock::new(HashMap::new()));
 
@@ -155,10 +156,15 @@ impl DriveClient {
             rate_limiter,
             user_rate_limiters,
             user_sheets_rate_limiters,
+            learned_rates,
         }
     }
 
-    pub fn with_rate_limiter(rate_limiter: Arc<RateLimiter>) -> Self {
+    /// A client whose per-user limiters start from `learned_rates`.
+    pub fn with_rate_limiter(
+        rate_limiter: Arc<RateLimiter>,
+        learned_rates: Arc<LearnedRates>,
+    ) -> Self {
         let client = Client::builder()
             .timeout(Duration::from_secs(60)) // 60 second timeout for all requests
             .connect_timeout(Duration::from_secs(10)) // 10 second connection timeout
@@ -173,6 +179,7 @@ impl DriveClient {
             rate_limiter,
             user_rate_limiters: Arc::new(RwLock::new(HashMap::new())),
             user_sheets_rate_limiters: Arc::new(RwLock::new(HashMap::new())),
+            learned_rates,
         }
     }
 
@@ -312,7 +319,7 @@ impl DriveClient {
 
         let limiter = rate_limiters
             .entry(user_email.to_string())
-            .or_insert_with(|| Arc::new(RateLimiter::new(5, google_max_retries()))) // 300 req/min for each user
+            .or_insert_with(|| self.learned_rates.limiter(DOCS_API_FAMILY, 5, 5)) // 300 req/min for each user
             .clone();
 
         Ok(limiter)
@@ -340,12 +347,24 @@ impl DriveClient {
 
         let limiter = rate_limiters
             .entry(user_email.to_string())
-            .or_insert_with(|| Arc::new(RateLimiter::new(1, google_max_retries())))
+            .or_insert_with(|| self.learned_rates.limiter(SHEETS_API_FAMILY, 1, 1))
             .clone();
 
         Ok(limiter)
     }
 
+    /// The Drive rate limiter and the per-user Docs/Slides and Sheets ones
+    /// created so far.
+    pub fn rate_limiters(&self) -> Vec<Arc<RateLimiter>> {
+        let mut limiters = vec![Arc::clone(&self.rate_limiter)];
+        for map in [&self.user_rate_limiters, &self.user_sheets_rate_limiters] {
+            if let Ok(map) = map.read() {
+                limiters.extend(map.values().cloned());
+            }
+        }
+        limiters
+    }
+
     fn delete_user_rate_limiter(&self, user_email: &str) -> Result<()> {
         let mut rate_limiters = self.user_rate_limiters.write().map_err(|e| {
             anyhow!(
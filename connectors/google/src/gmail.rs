@@ -22,6 +22,7 @@ use tracing::{debug, warn};
 use crate::auth::{
     ApiResult, GoogleAuth, classify_google_api_error, execute_with_auth_retry, google_max_retries,
 };
+use crate::rate_limits::{GMAIL_API_FAMILY, LearnedRates};
 use omni_connector_sdk::RateLimiter;
 
 const GMAIL_API_BASE: &str = "https://gmail.googleapis.com/gmail/v1";
@@ -31,6 +32,7 @@ pub struct GmailClient {
     client: Client,
     rate_limiter: Arc<RateLimiter>,
     user_rate_limiters: Arc<RwLock<HashMap<String, Arc<RateLimiter>>>>,
+    learned_rates: Arc<LearnedRates>,
 }
 
 impl GmailClient {
@@ -41,16 +43,22 @@ impl GmailClient {
             .build()
             .expect("Failed to build HTTP client");
 
-        let rate_limiter = Arc::new(RateLimiter::new(200, google_max_retries()));
+        let rate_limiter =
+            Arc::new(RateLimiter::new(200, google_max_retries()).with_api_family("google_api"));
         let user_rate_limiters = Arc::new(RwLock::new(HashMap::new()));
         Self {
             client,
             rate_limiter,
             user_rate_limiters,
+            learned_rates: Arc::new(LearnedRates::default()),
         }
     }
 
-    pub fn with_rate_limiter(rate_limiter: Arc<RateLimiter>) -> Self {
+    /// A client whose per-user limiters start from `learned_rates`.
+    pub fn with_rate_limiter(
+        rate_limiter: Arc<RateLimiter>,
+        learned_rates: Arc<LearnedRates>,
+    ) -> Self {
         let client = Client::builder()
             .timeout(Duration::from_secs(60))
             .connect_timeout(Duration::from_secs(10))
@@ -65,6 +73,7 @@ impl GmailClient {
             client,
             rate_limiter,
             user_rate_limiters,
+            learned_rates,
         }
     }
 
@@ -87,12 +96,20 @@ impl GmailClient {
 
         let limiter = rate_limiters
             .entry(user_email.to_string())
-            .or_insert_with(|| Arc::new(RateLimiter::new(25, google_max_retries()))) // 1500 req/min for each user
+            .or_insert_with(|| self.learned_rates.limiter(GMAIL_API_FAMILY, 25, 25)) // 1500 req/min for each user
             .clone();
 
         Ok(limiter)
     }
 
+    /// The per-user Gmail rate limiters created so far.
+    pub fn user_rate_limiters(&self) -> Vec<Arc<RateLimiter>> {
+        self.user_rate_limiters
+            .read()
+            .map(|map| map.values().cloned().collect())
+            .unwrap_or_default()
+    }
+
     fn delete_user_rate_limiter(&self, user_email: &str) -> Result<()> {
         let mut rate_limiters = self.user_rate_limiters.write().map_err(|e| {
             anyhow!(
@@ -1,11 +1,13 @@
 pub mod admin;
 pub mod auth;
 pub mod cache;
+pub mod calendar;
 pub mod chat;
 pub mod config;
 pub mod connector;
 pub mod drive;
 pub mod gmail;
 pub mod models;
+pub mod rate_limits;
 pub mod routes;
 pub mod sync;
@@ -35,7 +35,8 @@ async fn main() -> Result<()> {
         .parse::<u32>()
         .unwrap_or(180);
     let max_retries = google_max_retries();
-    let rate_limiter = Arc::new(RateLimiter::new(api_rate_limit, max_retries));
+    let rate_limiter =
+        Arc::new(RateLimiter::new(api_rate_limit, max_retries).with_api_family("google_admin"));
     let admin_client = Arc::new(AdminClient::with_rate_limiter(rate_limiter.clone()));
 
     let sdk_client = SdkClient::from_env()?;
@@ -9,6 +9,7 @@ use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use uuid::Uuid;
 
+use crate::calendar::{GoogleCalendarEvent, GoogleCalendarEventTime};
 use crate::gmail::GmailMessage;
 
 #[derive(Debug, Clone, Serialize)]
@@ -44,6 +45,15 @@ pub struct GoogleSyncCheckpoint {
     pub gmail_history_ids: Option<HashMap<String, String>>,
     pub drive_page_tokens: Option<HashMap<String, String>>,
     pub chat: Option<GoogleChatCheckpoint>,
+    /// Rate (req/sec) the source's adaptive limiters had settled on, per API
+    /// family, at the last checkpoint. Seeds the limiters next time so a
+    /// restart doesn't begin with another round of 429s.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub learned_api_rates: Option<HashMap<String, u32>>,
+    /// Per-user time the user's calendar was last listed at. The next
+    /// incremental sync only asks for events changed since then.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub calendar_watermarks: Option<HashMap<String, String>>,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -526,6 +536,16 @@ pub struct AttachmentPointer {
     pub size: u64,
 }
 
+/// A file attached to one of a thread's messages, stored and indexed as a
+/// document of its own.
+#[derive(Debug, Clone)]
+pub struct ThreadAttachment {
+    pub pointer: AttachmentPointer,
+    /// Gmail id of the message the file is attached to.
+    pub message_id: String,
+    pub content_id: String,
+}
+
 impl GmailThread {
     pub fn new(thread_id: String) -> Self {
         Self {
@@ -763,26 +783,7 @@ impl GmailThread {
             None
         };
 
-        // Build URL using rfc822msgid search for reliable Gmail permalinks.
-        // Gmail web UI uses internal IDs that differ from API thread IDs,
-        // so a search-based URL is the most reliable way to link to a thread.
-        let url = self
-            .message_id
-            .as_ref()
-            .map(|mid| {
-                let clean_id = mid.trim_start_matches('<').trim_end_matches('>');
-                let encoded = urlencoding::encode(clean_id);
-                format!(
-                    "https://mail.google.com/mail/#search/rfc822msgid%3A{}",
-                    encoded
-                )
-            })
-            .or_else(|| {
-                Some(format!(
-                    "https://mail.google.com/mail/#all/{}",
-                    self.thread_id
-                ))
-            });
+        let url = Some(self.url());
 
         let metadata = DocumentMetadata {
             title: Some(if self.subject.is_empty() {
@@ -801,7 +802,100 @@ impl GmailThread {
             extra: Some(extra),
         };
 
-        // Split participants into users and groups based on known org groups
+        let permissions = self.to_document_permissions(known_groups, user_email);
+
+        let attributes = self.to_attributes().into_attributes();
+
+        Ok(ConnectorEvent::DocumentCreated {
+            sync_run_id: sync_run_id.to_string(),
+            source_id: source_id.to_string(),
+            document_id: canonical_external_id,
+            content_id: content_id.to_string(),
+            metadata,
+            permissions,
+            attributes: Some(attributes),
+        })
+    }
+
+    /// The attachment as a document of its own. It is visible to exactly the
+    /// thread's readers, dated and authored by the message it came with, and
+    /// links back to the thread.
+    pub fn attachment_to_connector_event(
+        &self,
+        sync_run_id: &str,
+        source_id: &str,
+        known_groups: &HashSet<String>,
+        user_email: &str,
+        attachment: &ThreadAttachment,
+    ) -> ConnectorEvent {
+        let message = self
+            .messages
+            .iter()
+            .find(|message| message.id == attachment.message_id);
+        let sent_at = message
+            .and_then(|message| message.internal_date.as_deref())
+            .and_then(|millis| millis.parse::<i64>().ok())
+            .and_then(|millis| OffsetDateTime::from_unix_timestamp(millis / 1000).ok());
+        let author = message
+            .and_then(|message| extract_header_value(message, "From"))
+            .and_then(|from| parse_email_addresses(&from).into_iter().next());
+
+        let pointer = &attachment.pointer;
+        let mut extra = HashMap::new();
+        extra.insert(
+            "parent_thread_id".to_string(),
+            json!(self.canonical_external_id()),
+        );
+        extra.insert("gmail_thread_id".to_string(), json!(self.thread_id));
+
+        let metadata = DocumentMetadata {
+            title: Some(pointer.filename.clone()),
+            author,
+            created_at: sent_at,
+            updated_at: sent_at,
+            content_type: mime_type_to_content_type(&pointer.mime_type),
+            mime_type: Some(pointer.mime_type.clone()),
+            size: Some(pointer.size.to_string()),
+            url: Some(self.url()),
+            path: Some(format!("/Gmail/{}/{}", self.subject, pointer.filename)),
+            extra: Some(extra),
+        };
+
+        ConnectorEvent::DocumentCreated {
+            sync_run_id: sync_run_id.to_string(),
+            source_id: source_id.to_string(),
+            document_id: pointer.id.clone(),
+            content_id: attachment.content_id.clone(),
+            metadata,
+            permissions: self.to_document_permissions(known_groups, user_email),
+            attributes: Some(HashMap::new()),
+        }
+    }
+
+    /// Build URL using rfc822msgid search for reliable Gmail permalinks.
+    /// Gmail web UI uses internal IDs that differ from API thread IDs,
+    /// so a search-based URL is the most reliable way to link to a thread.
+    fn url(&self) -> String {
+        match &self.message_id {
+            Some(mid) => {
+                let clean_id = mid.trim_start_matches('<').trim_end_matches('>');
+                let encoded = urlencoding::encode(clean_id);
+                format!(
+                    "https://mail.google.com/mail/#search/rfc822msgid%3A{}",
+                    encoded
+                )
+            }
+            None => format!("https://mail.google.com/mail/#all/{}", self.thread_id),
+        }
+    }
+
+    /// The thread's participants and the mailbox owner. Participants that
+    /// are known groups are granted as groups.
+    fn to_document_permissions(
+        &self,
+        known_groups: &HashSet<String>,
+        user_email: &str,
+    ) -> DocumentPermissions {
         let mut users = Vec::new();
         let mut groups = Vec::new();
         let mut permission_participants = self.participants.clone();
@@ -818,26 +912,338 @@ impl GmailThread {
         groups.sort();
         groups.dedup();
 
-        let permissions = DocumentPermissions {
+        DocumentPermissions {
             public: false,
             users,
             groups,
+        }
+    }
+}
+
+impl GoogleCalendarEvent {
+    pub fn document_id(&self) -> String {
+        format!("calendar:{}", self.id)
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.status.as_deref() == Some("cancelled")
+    }
+
+    /// Working location, focus time, out of office and birthday entries carry
+    /// nothing worth searching for.
+    pub fn is_indexable(&self) -> bool {
+        matches!(
+            self.event_type.as_deref(),
+            None | Some("default") | Some("fromGmail")
+        )
+    }
+
+    pub fn is_all_day(&self) -> bool {
+        self.start
+            .as_ref()
+            .is_some_and(|start| start.date_time.is_none() && start.date.is_some())
+    }
+
+    fn title(&self) -> String {
+        match self.summary.as_deref().map(str::trim) {
+            Some(summary) if !summary.is_empty() => summary.to_string(),
+            _ => "Untitled event".to_string(),
+        }
+    }
+
+    fn start_time(&self) -> Option<DateTime<Utc>> {
+        self.start.as_ref().and_then(parse_calendar_event_time)
+    }
+
+    fn end_time(&self) -> Option<DateTime<Utc>> {
+        self.end.as_ref().and_then(parse_calendar_event_time)
+    }
+
+    pub fn organizer_email(&self) -> Option<String> {
+        self.organizer
+            .as_ref()
+            .and_then(|organizer| organizer.email.as_deref())
+            .filter(|email| is_person_email(email))
+            .map(str::to_lowercase)
+    }
+
+    /// The organizer and every attendee that is a person rather than a room
+    /// or another calendar.
+    fn participant_emails(&self) -> Vec<String> {
+        let attendees = self
+            .attendees
+            .iter()
+            .filter(|attendee| !attendee.resource)
+            .filter_map(|attendee| attendee.email.as_deref())
+            .filter(|email| is_person_email(email))
+            .map(str::to_lowercase);
+        let mut emails: Vec<String> = self
+            .organizer_email()
+            .into_iter()
+            .chain(attendees)
+            .collect();
+        emails.sort();
+        emails.dedup();
+        emails
+    }
+
+    pub fn render_content(&self) -> String {
+        let mut lines = vec![format!("Event: {}", self.title())];
+        if self.is_all_day() {
+            let start = self.start.as_ref().and_then(|start| start.date.as_deref());
+            lines.push(format!("Date: {}", start.unwrap_or_default()));
+            lines.push("All-day event".to_string());
+        } else {
+            for (label, time) in [("Start", &self.start), ("End", &self.end)] {
+                let Some(time) = time else { continue };
+                let Some(date_time) = &time.date_time else {
+                    continue;
+                };
+                match &time.time_zone {
+                    Some(time_zone) => {
+                        lines.push(format!("{}: {} ({})", label, date_time, time_zone))
+                    }
+                    None => lines.push(format!("{}: {}", label, date_time)),
+                }
+            }
+        }
+        if self.status.as_deref() == Some("tentative") {
+            lines.push("Status: tentative".to_string());
+        }
+        if let Some(location) = self.location.as_deref().filter(|l| !l.trim().is_empty()) {
+            lines.push(format!("Location: {}", location));
+        }
+        let rooms: Vec<&str> = self
+            .attendees
+            .iter()
+            .filter(|attendee| attendee.resource)
+            .filter_map(|attendee| {
+                attendee
+                    .display_name
+                    .as_deref()
+                    .or(attendee.email.as_deref())
+            })
+            .collect();
+        if !rooms.is_empty() {
+            lines.push(format!("Rooms: {}", rooms.join(", ")));
+        }
+        if let Some(organizer) = &self.organizer
+            && let Some(organizer) = format_calendar_person(
+                organizer.display_name.as_deref(),
+                organizer.email.as_deref(),
+            )
+        {
+            lines.push(format!("Organizer: {}", organizer));
+        }
+        let attendees: Vec<String> = self
+            .attendees
+            .iter()
+            .filter(|attendee| !attendee.resource)
+            .filter_map(|attendee| {
+                let person = format_calendar_person(
+                    attendee.display_name.as_deref(),
+                    attendee.email.as_deref(),
+                )?;
+                Some(match attendee.response_status.as_deref() {
+                    Some(status @ ("accepted" | "declined" | "tentative")) => {
+                        format!("{} ({})", person, status)
+                    }
+                    _ => person,
+                })
+            })
+            .collect();
+        if !attendees.is_empty() {
+            lines.push(format!("Attendees: {}", attendees.join(", ")));
+        }
+        if let Some(link) = &self.hangout_link {
+            lines.push(format!("Meeting link: {}", link));
+        }
+        if !self.attachments.is_empty() {
+            lines.push("Attachments:".to_string());
+            for attachment in &self.attachments {
+                let title = attachment.title.as_deref().unwrap_or("Attachment");
+                lines.push(format!("- {}: {}", title, attachment.file_url));
+            }
+        }
+        if let Some(description) = &self.description {
+            let description = calendar_description_to_text(description);
+            if !description.is_empty() {
+                lines.push(String::new());
+                lines.push(description);
+            }
+        }
+        lines.join("\n")
+    }
+
+    pub fn to_attributes(&self) -> DocumentAttributes {
+        let mut attrs = HashMap::new();
+        if let Some(organizer) = self.organizer_email() {
+            attrs.insert("organizer".into(), json!(organizer));
+        }
+        let attendees = self.participant_emails();
+        if !attendees.is_empty() {
+            attrs.insert("attendees".into(), json!(attendees));
+        }
+        if let Some(start) = self.start_time() {
+            attrs.insert("date".into(), json!(start.format("%Y-%m-%d").to_string()));
+            attrs.insert("start_time".into(), json!(start.to_rfc3339()));
+        }
+        if let Some(end) = self.end_time() {
+            attrs.insert("end_time".into(), json!(end.to_rfc3339()));
+        }
+        attrs.insert("all_day".into(), json!(self.is_all_day()));
+        attrs.insert("recurring".into(), json!(self.recurring_event_id.is_some()));
+        attrs
+    }
+
+    /// The event as a document visible to its organizer, its attendees and
+    /// the owner of the calendar it was read from. It is dated by when it
+    /// takes place rather than when it was last edited, so date filters
+    /// find a meeting by the day it happened.
+    pub fn to_connector_event(
+        &self,
+        sync_run_id: &str,
+        source_id: &str,
+        content_id: &str,
+        known_groups: &HashSet<String>,
+        user_email: &str,
+    ) -> ConnectorEvent {
+        let start = self.start_time();
+        let starts_at =
+            start.and_then(|start| OffsetDateTime::from_unix_timestamp(start.timestamp()).ok());
+
+        let mut extra = HashMap::new();
+        extra.insert("event_id".to_string(), json!(self.id));
+        extra.insert("start".to_string(), json!(start.map(|t| t.to_rfc3339())));
+        extra.insert(
+            "end".to_string(),
+            json!(self.end_time().map(|t| t.to_rfc3339())),
+        );
+        extra.insert("all_day".to_string(), json!(self.is_all_day()));
+        if let Some(recurring_event_id) = &self.recurring_event_id {
+            extra.insert("recurring_event_id".to_string(), json!(recurring_event_id));
+        }
+        if !self.attachments.is_empty() {
+            extra.insert("attachments".to_string(), json!(self.attachments));
+        }
+
+        let title = self.title();
+        let metadata = DocumentMetadata {
+            path: Some(format!("/Calendar/{}", title)),
+            title: Some(title),
+            author: self.organizer.as_ref().and_then(|organizer| {
+                organizer
+                    .display_name
+                    .clone()
+                    .or_else(|| organizer.email.clone())
+            }),
+            created_at: starts_at,
+            updated_at: starts_at,
+            content_type: Some("event".to_string()),
+            mime_type: Some("text/calendar".to_string()),
+            size: None,
+            url: self.html_link.clone(),
+            extra: Some(extra),
         };
 
-        let attributes = self.to_attributes().into_attributes();
+        let mut users = Vec::new();
+        let mut groups = Vec::new();
+        let mut readers = self.participant_emails();
+        readers.push(user_email.to_lowercase());
+        for reader in readers {
+            if known_groups.contains(&reader) {
+                groups.push(reader);
+            } else {
+                users.push(reader);
+            }
+        }
+        users.sort();
+        users.dedup();
+        groups.sort();
+        groups.dedup();
 
-        Ok(ConnectorEvent::DocumentCreated {
+        ConnectorEvent::DocumentCreated {
             sync_run_id: sync_run_id.to_string(),
             source_id: source_id.to_string(),
-            document_id: canonical_external_id,
+            document_id: self.document_id(),
             content_id: content_id.to_string(),
             metadata,
-            permissions,
-            attributes: Some(attributes),
-        })
+            permissions: DocumentPermissions {
+                public: false,
+                users,
+                groups,
+            },
+            attributes: Some(self.to_attributes()),
+        }
+    }
+}
+
+/// All-day events start and end at midnight UTC of their dates.
+fn parse_calendar_event_time(time: &GoogleCalendarEventTime) -> Option<DateTime<Utc>> {
+    if let Some(date_time) = &time.date_time {
+        return DateTime::parse_from_rfc3339(date_time)
+            .ok()
+            .map(|dt| dt.with_timezone(&Utc));
+    }
+    let date = chrono::NaiveDate::parse_from_str(time.date.as_deref()?, "%Y-%m-%d").ok()?;
+    Some(date.and_hms_opt(0, 0, 0)?.and_utc())
+}
+
+/// Rooms, shared calendars and other non-person addresses are hosted on
+/// calendar.google.com.
+fn is_person_email(email: &str) -> bool {
+    email.contains('@') && !email.to_lowercase().ends_with("calendar.google.com")
+}
+
+fn format_calendar_person(display_name: Option<&str>, email: Option<&str>) -> Option<String> {
+    match (display_name, email) {
+        (Some(name), Some(email)) => Some(format!("{} <{}>", name, email)),
+        (Some(name), None) => Some(name.to_string()),
+        (None, Some(email)) => Some(email.to_string()),
+        (None, None) => None,
     }
 }
 
+/// Event descriptions may hold the little HTML the Calendar editor produces:
+/// line breaks, paragraphs, lists, links and inline formatting.
+fn calendar_description_to_text(description: &str) -> String {
+    let mut text = String::with_capacity(description.len());
+    let mut rest = description;
+    while let Some(open) = rest.find('<') {
+        text.push_str(&rest[..open]);
+        let Some(close) = rest[open..].find('>') else {
+            rest = &rest[open..];
+            break;
+        };
+        let tag = rest[open + 1..open + close]
+            .trim_start_matches('/')
+            .to_lowercase();
+        let name = tag
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .next()
+            .unwrap_or("");
+        if matches!(name, "br" | "p" | "div" | "li" | "ul" | "ol") {
+            text.push('\n');
+        }
+        rest = &rest[open + close + 1..];
+    }
+    text.push_str(rest);
+
+    let text = text
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&");
+    let lines: Vec<&str> = text.lines().map(str::trim).collect();
+    let mut result = lines.join("\n");
+    while result.contains("\n\n\n") {
+        result = result.replace("\n\n\n", "\n\n");
+    }
+    result.trim().to_string()
+}
+
 fn extract_header_value(message: &GmailMessage, header_name: &str) -> Option<String> {
     message
         .payload
@@ -1673,4 +2079,181 @@ mod tests {
             _ => panic!("Expected DocumentCreated event"),
         }
     }
+
+    #[test]
+    fn test_gmail_attachment_event_inherits_thread_permissions() {
+        let mut thread = GmailThread::new("thread123".to_string());
+        thread.add_message(gmail_message_with_headers(vec![
+            ("Message-ID", "<m1@example.com>"),
+            ("Subject", "Q3 Report"),
+            ("From", "Sender <sender@example.com>"),
+            ("To", "Finance <finance@example.com>"),
+        ]));
+        let known_groups = HashSet::from(["finance@example.com".to_string()]);
+
+        let attachment = ThreadAttachment {
+            pointer: AttachmentPointer {
+                id: "m1%40example.com:att:report.pdf:12345".to_string(),
+                filename: "report.pdf".to_string(),
+                mime_type: "application/pdf".to_string(),
+                size: 12345,
+            },
+            message_id: "msg1".to_string(),
+            content_id: "content-att".to_string(),
+        };
+        let thread_permissions = match thread
+            .to_connector_event(
+                "sync1",
+                "source1",
+                "content1",
+                &known_groups,
+                "owner@example.com",
+                &[],
+            )
+            .unwrap()
+        {
+            ConnectorEvent::DocumentCreated { permissions, .. } => permissions,
+            _ => panic!("Expected DocumentCreated event"),
+        };
+
+        let event = thread.attachment_to_connector_event(
+            "sync1",
+            "source1",
+            &known_groups,
+            "owner@example.com",
+            &attachment,
+        );
+
+        match event {
+            ConnectorEvent::DocumentCreated {
+                document_id,
+                content_id,
+                metadata,
+                permissions,
+                ..
+            } => {
+                assert_eq!(document_id, "m1%40example.com:att:report.pdf:12345");
+                assert_eq!(content_id, "content-att");
+                assert_eq!(permissions.users, thread_permissions.users);
+                assert_eq!(permissions.groups, vec!["finance@example.com".to_string()]);
+                assert_eq!(metadata.title.as_deref(), Some("report.pdf"));
+                assert_eq!(metadata.content_type.as_deref(), Some("pdf"));
+                assert_eq!(metadata.author.as_deref(), Some("sender@example.com"));
+                assert_eq!(
+                    metadata.created_at.map(|t| t.unix_timestamp()),
+                    Some(1686787200)
+                );
+                assert_eq!(
+                    metadata.path.as_deref(),
+                    Some("/Gmail/Q3 Report/report.pdf")
+                );
+                let extra = metadata.extra.expect("extra populated");
+                assert_eq!(extra["parent_thread_id"], "m1@example.com");
+                assert_eq!(extra["gmail_thread_id"], "thread123");
+            }
+            _ => panic!("Expected DocumentCreated event"),
+        }
+    }
+
+    #[test]
+    fn test_calendar_event_to_connector_event() {
+        let event: GoogleCalendarEvent = serde_json::from_value(json!({
+            "id": "evt123_20240312T150000Z",
+            "status": "confirmed",
+            "htmlLink": "https://www.google.com/calendar/event?eid=abc",
+            "summary": "Design review",
+            "description": "Agenda:<br><ul><li>Search &amp; ranking</li></ul>",
+            "organizer": { "email": "Alice@Example.com", "displayName": "Alice" },
+            "start": { "dateTime": "2024-03-12T10:00:00-05:00", "timeZone": "America/New_York" },
+            "end": { "dateTime": "2024-03-12T11:00:00-05:00", "timeZone": "America/New_York" },
+            "attendees": [
+                { "email": "alice@example.com", "organizer": true, "responseStatus": "accepted" },
+                { "email": "bob@example.com", "responseStatus": "declined" },
+                { "email": "eng@example.com" },
+                { "email": "c_123@resource.calendar.google.com", "displayName": "Room 4", "resource": true }
+            ],
+            "attachments": [
+                { "fileUrl": "https://docs.google.com/document/d/doc1", "title": "Design doc" }
+            ],
+            "recurringEventId": "evt123"
+        }))
+        .unwrap();
+        assert!(event.is_indexable());
+        assert!(!event.is_all_day());
+
+        let content = event.render_content();
+        assert!(content.starts_with("Event: Design review\n"));
+        assert!(content.contains("Start: 2024-03-12T10:00:00-05:00 (America/New_York)"));
+        assert!(content.contains("Rooms: Room 4"));
+        assert!(content.contains("Organizer: Alice <Alice@Example.com>"));
+        assert!(content.contains("bob@example.com (declined)"));
+        assert!(content.contains("- Design doc: https://docs.google.com/document/d/doc1"));
+        assert!(content.ends_with("Agenda:\n\nSearch & ranking"));
+
+        let known_groups = HashSet::from(["eng@example.com".to_string()]);
+        match event.to_connector_event(
+            "run1",
+            "src1",
+            "content1",
+            &known_groups,
+            "carol@example.com",
+        ) {
+            ConnectorEvent::DocumentCreated {
+                document_id,
+                metadata,
+                permissions,
+                attributes,
+                ..
+            } => {
+                assert_eq!(document_id, "calendar:evt123_20240312T150000Z");
+                assert_eq!(metadata.title.as_deref(), Some("Design review"));
+                assert_eq!(
+                    metadata.created_at.map(|t| t.unix_timestamp()),
+                    Some(1710255600)
+                );
+                assert_eq!(metadata.updated_at, metadata.created_at);
+                assert_eq!(
+                    permissions.users,
+                    vec![
+                        "alice@example.com".to_string(),
+                        "bob@example.com".to_string(),
+                        "carol@example.com".to_string(),
+                    ]
+                );
+                assert_eq!(permissions.groups, vec!["eng@example.com".to_string()]);
+                let attributes = attributes.expect("attributes populated");
+                assert_eq!(attributes["date"], "2024-03-12");
+                assert_eq!(attributes["start_time"], "2024-03-12T15:00:00+00:00");
+                assert_eq!(attributes["end_time"], "2024-03-12T16:00:00+00:00");
+                assert_eq!(attributes["organizer"], "alice@example.com");
+                assert_eq!(attributes["recurring"], true);
+            }
+            _ => panic!("Expected DocumentCreated event"),
+        }
+    }
+
+    #[test]
+    fn test_calendar_all_day_and_non_meeting_events() {
+        let all_day: GoogleCalendarEvent = serde_json::from_value(json!({
+            "id": "offsite",
+            "summary": "Team offsite",
+            "start": { "date": "2024-03-20" },
+            "end": { "date": "2024-03-21" }
+        }))
+        .unwrap();
+        assert!(all_day.is_all_day());
+        assert!(
+            all_day
+                .render_content()
+                .contains("Date: 2024-03-20\nAll-day event")
+        );
+        assert_eq!(all_day.to_attributes()["date"], "2024-03-20");
+
+        let working_location = GoogleCalendarEvent {
+            id: "wl".to_string(),
+            event_type: Some("workingLocation".to_string()),
+            ..Default::default()
+        };
+        assert!(!working_location.is_indexable());
+    }
 }
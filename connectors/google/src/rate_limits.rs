@@ -0,0 +1,136 @@
+//! Adaptive rate limits for the Google APIs.
+//!
+//! Each source has its own limiters, one per API family for project-wide
+//! quotas and one per family and user for per-user quotas. They back off when
+//! Google throttles them and probe back up towards the family's ceiling. The
+//! rate each family settled on is saved in the source's checkpoint, so the
+//! next sync starts from it instead of another round of 429s.
+
+use crate::auth::google_max_retries;
+use omni_connector_sdk::{AdaptiveRateConfig, RateLimiter};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+/// Drive API calls made with the source's credentials (listing files,
+/// changes and folders): 12,000 req/min per project.
+pub const DRIVE_API_FAMILY: &str = "google_drive";
+/// Docs/Slides exports: 300 req/min per user.
+pub const DOCS_API_FAMILY: &str = "google_docs";
+/// Sheets reads: 60 req/min per user.
+pub const SHEETS_API_FAMILY: &str = "google_sheets";
+/// Gmail: 1,500 req/min per user.
+pub const GMAIL_API_FAMILY: &str = "gmail";
+pub const CHAT_API_FAMILY: &str = "google_chat";
+pub const CALENDAR_API_FAMILY: &str = "google_calendar";
+
+/// Rates learned for the API families of one source, shared by its clients
+/// so that limiters created mid-sync (e.g. for the next user) start from
+/// them too.
+#[derive(Debug, Default)]
+pub struct LearnedRates {
+    rates: RwLock<HashMap<String, u32>>,
+}
+
+impl LearnedRates {
+    /// A limiter for `api_family` that tunes itself between 1 and `max_rps`
+    /// req/sec, starting from the family's learned rate, or `initial_rps`
+    /// when there is none.
+    pub fn limiter(&self, api_family: &str, initial_rps: u32, max_rps: u32) -> Arc<RateLimiter> {
+        let initial_rps = self
+            .rates
+            .read()
+            .ok()
+            .and_then(|rates| rates.get(api_family).copied())
+            .unwrap_or(initial_rps);
+        Arc::new(
+            RateLimiter::new(initial_rps.max(1), google_max_retries())
+                .with_api_family(api_family)
+                .with_adaptive(AdaptiveRateConfig::new(1, max_rps)),
+        )
+    }
+
+    /// Restore rates saved in a checkpoint, seeding `limiters` that have not
+    /// tuned themselves yet as well as ones created later.
+    pub fn restore(&self, saved: &HashMap<String, u32>, limiters: &[Arc<RateLimiter>]) {
+        for limiter in limiters {
+            if let Some(rate) = saved.get(limiter.api_family()) {
+                limiter.seed_rate(*rate);
+            }
+        }
+        if let Ok(mut rates) = self.rates.write() {
+            rates.extend(saved.iter().map(|(family, rate)| (family.clone(), *rate)));
+        }
+    }
+
+    /// The rates to save: for each family, the lowest rate any of its
+    /// `limiters` is at, since per-user limiters of a family share the same
+    /// quota. Families without limiters keep the rate learned before.
+    pub fn learned(&self, limiters: &[Arc<RateLimiter>]) -> HashMap<String, u32> {
+        let mut current: HashMap<String, u32> = HashMap::new();
+        for limiter in limiters {
+            current
+                .entry(limiter.api_family().to_string())
+                .and_modify(|rate| *rate = (*rate).min(limiter.current_rps()))
+                .or_insert_with(|| limiter.current_rps());
+        }
+
+        let Ok(mut rates) = self.rates.write() else {
+            return current;
+        };
+        rates.extend(current);
+        rates.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limiters_start_from_learned_rate() {
+        let learned = LearnedRates::default();
+        assert_eq!(learned.limiter(GMAIL_API_FAMILY, 25, 25).current_rps(), 25);
+
+        learned.restore(&HashMap::from([(GMAIL_API_FAMILY.to_string(), 10)]), &[]);
+        assert_eq!(learned.limiter(GMAIL_API_FAMILY, 25, 25).current_rps(), 10);
+        assert_eq!(learned.limiter(DOCS_API_FAMILY, 5, 5).current_rps(), 5);
+
+        // Clamped to the family's ceiling
+        learned.restore(&HashMap::from([(GMAIL_API_FAMILY.to_string(), 90)]), &[]);
+        assert_eq!(learned.limiter(GMAIL_API_FAMILY, 25, 25).current_rps(), 25);
+    }
+
+    #[test]
+    fn test_restore_seeds_existing_limiters_of_the_family() {
+        let learned = LearnedRates::default();
+        let drive = learned.limiter(DRIVE_API_FAMILY, 50, 200);
+        let gmail = learned.limiter(GMAIL_API_FAMILY, 25, 25);
+
+        learned.restore(
+            &HashMap::from([(DRIVE_API_FAMILY.to_string(), 120)]),
+            &[drive.clone(), gmail.clone()],
+        );
+        assert_eq!(drive.current_rps(), 120);
+        assert_eq!(gmail.current_rps(), 25);
+    }
+
+    #[test]
+    fn test_learned_takes_lowest_rate_per_family_and_keeps_unused_families() {
+        let learned = LearnedRates::default();
+        learned.restore(&HashMap::from([(CHAT_API_FAMILY.to_string(), 7)]), &[]);
+        let alice = learned.limiter(GMAIL_API_FAMILY, 25, 25);
+        let bob = learned.limiter(GMAIL_API_FAMILY, 25, 25);
+        let drive = learned.limiter(DRIVE_API_FAMILY, 50, 200);
+        bob.record_throttled();
+
+        let rates = learned.learned(&[alice, bob, drive]);
+        assert_eq!(
+            rates,
+            HashMap::from([
+                (GMAIL_API_FAMILY.to_string(), 12),
+                (DRIVE_API_FAMILY.to_string(), 50),
+                (CHAT_API_FAMILY.to_string(), 7),
+            ])
+        );
+    }
+}
@@ -15,6 +15,7 @@ const GOOGLE_FILE_CONCURRENCY: usize = 8;
 const DEFAULT_GOOGLE_DRIVE_PARALLEL_USERS: usize = 3;
 const DEFAULT_GOOGLE_DRIVE_MAX_DOWNLOAD_BYTES: usize = 50 * 1024 * 1024;
 const DEFAULT_GOOGLE_WEBHOOK_DEBOUNCE_SECONDS: u64 = 4 * 60 * 60;
+const DEFAULT_GOOGLE_CALENDAR_FUTURE_DAYS: i64 = 365;
 const GOOGLE_MAX_BUFFERED_BYTES: usize = 512 * 1024 * 1024;
 const GOOGLE_BUFFER_PERMIT_UNIT: usize = 64 * 1024;
 const GOOGLE_BUFFER_PERMITS: usize = GOOGLE_MAX_BUFFERED_BYTES / GOOGLE_BUFFER_PERMIT_UNIT;
@@ -84,6 +85,15 @@ fn google_drive_max_download_bytes() -> usize {
         .unwrap_or(DEFAULT_GOOGLE_DRIVE_MAX_DOWNLOAD_BYTES)
 }
 
+/// How far ahead of today upcoming calendar events are indexed.
+fn google_calendar_future_days() -> i64 {
+    std::env::var("GOOGLE_CALENDAR_FUTURE_DAYS")
+        .ok()
+        .and_then(|value| value.parse::<i64>().ok())
+        .filter(|days| *days >= 0)
+        .unwrap_or(DEFAULT_GOOGLE_CALENDAR_FUTURE_DAYS)
+}
+
 fn google_webhook_debounce_duration_ms() -> u64 {
     std::env::var("GOOGLE_WEBHOOK_DEBOUNCE_SECONDS")
         .ok()
@@ -266,20 +276,22 @@ async fn emit_metadata_only_drive_event(
 }
 
 use crate::admin::AdminClient;
-use crate::auth::{GoogleAuth, GoogleOAuthCredentials, OAuthAuth, google_max_retries};
+use crate::auth::{GoogleAuth, GoogleOAuthCredentials, OAuthAuth, google_api_rate_limit_max};
 use crate::cache::LruFolderCache;
+use crate::calendar::{CalendarClient, ListEventsQuery};
 use crate::chat::{
     ChatClient, GoogleChatAttachmentSource, GoogleChatMessage, GoogleChatSpace,
     GoogleChatSpaceEvent, GoogleChatSpaceType,
 };
 use crate::connector::build_attachment_doc_id;
 use crate::drive::{DriveClient, FileContent};
-use crate::gmail::{BatchThreadResult, ExtractedAttachment, GmailClient, MessageFormat};
+use crate::gmail::{BatchThreadResult, GmailClient, MessageFormat};
 use crate::models::{
     AttachmentPointer, GmailThread, GoogleChatSegmentCheckpoint, GoogleChatSpaceCheckpoint,
-    GoogleConnectorState, GoogleSyncCheckpoint, UserFile, WebhookChannel, WebhookChannelResponse,
-    WebhookNotification, mime_type_to_content_type,
+    GoogleConnectorState, GoogleSyncCheckpoint, ThreadAttachment, UserFile, WebhookChannel,
+    WebhookChannelResponse, WebhookNotification,
 };
+use crate::rate_limits::{DRIVE_API_FAMILY, LearnedRates};
 use omni_connector_sdk::RateLimiter;
 use omni_connector_sdk::SdkClient;
 use omni_connector_sdk::{
@@ -846,10 +858,62 @@ pub struct WebhookDebounce {
     pub count: u32,
 }
 
-pub struct SyncManager {
+/// The Google API clients of one source. Each source has its own adaptive
+/// rate limiters, so throttling on one source's quotas doesn't slow down the
+/// others, and the rates they learn are saved in that source's checkpoint.
+#[derive(Clone)]
+struct SourceApis {
+    learned_rates: Arc<LearnedRates>,
     drive_client: DriveClient,
     gmail_client: GmailClient,
     chat_client: ChatClient,
+    calendar_client: CalendarClient,
+}
+
+impl SourceApis {
+    fn new(api_rate_limit: u32) -> Self {
+        // GOOGLE_API_RATE_LIMIT is the starting rate; the limiter backs off on
+        // throttling and probes back up towards GOOGLE_API_RATE_LIMIT_MAX.
+        let learned_rates = Arc::new(LearnedRates::default());
+        let drive_rate_limiter = learned_rates.limiter(
+            DRIVE_API_FAMILY,
+            api_rate_limit,
+            google_api_rate_limit_max(),
+        );
+        Self {
+            drive_client: DriveClient::with_rate_limiter(
+                drive_rate_limiter.clone(),
+                learned_rates.clone(),
+            ),
+            gmail_client: GmailClient::with_rate_limiter(drive_rate_limiter, learned_rates.clone()),
+            chat_client: ChatClient::with_learned_rates(learned_rates.clone()),
+            calendar_client: CalendarClient::with_learned_rates(learned_rates.clone()),
+            learned_rates,
+        }
+    }
+
+    /// The source's Drive limiter and the per-user Docs/Sheets/Gmail/Chat/
+    /// Calendar ones created so far.
+    fn rate_limiters(&self) -> Vec<Arc<RateLimiter>> {
+        let mut limiters = self.drive_client.rate_limiters();
+        limiters.extend(self.gmail_client.user_rate_limiters());
+        limiters.extend(self.chat_client.user_rate_limiters());
+        limiters.extend(self.calendar_client.user_rate_limiters());
+        limiters
+    }
+
+    /// Rates to save in the checkpoint, per API family.
+    fn learned_api_rates(&self) -> HashMap<String, u32> {
+        self.learned_rates.learned(&self.rate_limiters())
+    }
+}
+
+pub struct SyncManager {
+    /// Starting rate of each source's Drive limiter.
+    api_rate_limit: u32,
+    source_apis: DashMap<String, SourceApis>,
+    /// For actions, which aren't tied to a sync of the source.
+    gmail_client: GmailClient,
     admin_client: Arc<AdminClient>,
     // TODO: Remove this one we wire in the webhook codepath to use SyncContext as well
     pub sdk_client: SdkClient,
@@ -877,13 +941,6 @@ impl SyncManager {
             .parse::<u32>()
             .unwrap_or(50);
 
-        let max_retries = google_max_retries();
-
-        let rate_limiter = Arc::new(RateLimiter::new(api_rate_limit, max_retries));
-        let drive_client = DriveClient::with_rate_limiter(rate_limiter.clone());
-        let gmail_client = GmailClient::with_rate_limiter(rate_limiter.clone());
-        let chat_client = ChatClient::with_rate_limiter(rate_limiter);
-
         let debounce_duration_ms = google_webhook_debounce_duration_ms();
         info!(
             "Google webhook debounce duration set to {} seconds",
@@ -891,9 +948,9 @@ impl SyncManager {
         );
 
         Self {
-            drive_client,
-            gmail_client,
-            chat_client,
+            api_rate_limit,
+            source_apis: DashMap::new(),
+            gmail_client: GmailClient::new(),
             admin_client,
             sdk_client,
             folder_cache: LruFolderCache::new(10_000),
@@ -909,6 +966,21 @@ impl SyncManager {
         &self.gmail_client
     }
 
+    fn source_apis(&self, source_id: &str) -> SourceApis {
+        self.source_apis
+            .entry(source_id.to_string())
+            .or_insert_with(|| SourceApis::new(self.api_rate_limit))
+            .clone()
+    }
+
+    /// Every rate limiter a sync of the source may have gone through: the
+    /// source's own and the admin limiter.
+    fn rate_limiters(&self, source_id: &str) -> Vec<Arc<RateLimiter>> {
+        let mut limiters = self.source_apis(source_id).rate_limiters();
+        limiters.extend(self.admin_client.rate_limiter().cloned());
+        limiters
+    }
+
     /// Run a sync driven by the SDK. The SDK passes in the full Source and
     /// optional ServiceCredential, the persisted State, and a `SyncContext`
     /// whose cancellation flag is flipped by the SDK's `/cancel` handler.
@@ -937,8 +1009,17 @@ impl SyncManager {
             ));
         }
 
+        if let Some(rates) = state.as_ref().and_then(|s| s.learned_api_rates.as_ref()) {
+            let apis = self.source_apis(&source_id);
+            apis.learned_rates.restore(rates, &apis.rate_limiters());
+        }
+
         let outcome = self.run_sync_inner(&source, &creds, state, &ctx).await;
 
+        let limiters = self.rate_limiters(&source_id);
+        let limiters: Vec<&RateLimiter> = limiters.iter().map(|l| l.as_ref()).collect();
+        ctx.report_rate_limits(&limiters).await;
+
         match outcome {
             Ok(Some(final_state)) => {
                 // Save the final state explicitly even though run_sync_inner
@@ -1017,6 +1098,17 @@ impl SyncManager {
                 )
                 .await
             }
+            SourceType::GoogleCalendar => {
+                self.sync_google_calendar_source_internal(
+                    source,
+                    service_creds,
+                    sync_type,
+                    existing_state,
+                    known_groups,
+                    ctx,
+                )
+                .await
+            }
             _ => Err(anyhow!("Unsupported source type: {:?}", source.source_type)),
         };
 
@@ -1069,6 +1161,7 @@ impl SyncManager {
         created_after: Option<&str>,
         content_cache: Arc<DriveContentCache>,
     ) -> Result<(usize, usize)> {
+        let apis = self.source_apis(source_id);
         info!("Processing Drive files for user: {}", user_email);
 
         let mut total_scanned = 0;
@@ -1083,7 +1176,7 @@ impl SyncManager {
                 user_email, page_token
             );
 
-            let response = self
+            let response = apis
                 .drive_client
                 .list_files(
                     &service_auth,
@@ -1183,6 +1276,7 @@ impl SyncManager {
         start_page_token: &str,
         content_cache: Arc<DriveContentCache>,
     ) -> Result<(usize, usize)> {
+        let apis = self.source_apis(source_id);
         info!(
             "Processing incremental Drive sync for user {} from pageToken {}",
             user_email, start_page_token
@@ -1194,7 +1288,7 @@ impl SyncManager {
         let mut current_token = start_page_token.to_string();
 
         loop {
-            let response = self
+            let response = apis
                 .drive_client
                 .list_changes(&access_token, &current_token)
                 .await?;
@@ -1299,6 +1393,7 @@ impl SyncManager {
         service_auth: Arc<GoogleAuth>,
         content_cache: Arc<DriveContentCache>,
     ) -> Result<(usize, usize)> {
+        let apis = self.source_apis(source_id);
         info!("Processing batch of {} files", files.len());
 
         // (scanned, updated): scanned counts files we read content from
@@ -1314,7 +1409,7 @@ impl SyncManager {
             let service_auth = service_auth.clone();
             let source_id = source_id_owned.clone();
             let sync_run_id = sync_run_id_owned.clone();
-            let drive_client = self.drive_client.clone();
+            let drive_client = apis.drive_client.clone();
             let memory_budget = self.drive_buffer_memory_budget.clone();
             let content_cache = content_cache.clone();
             let ctx = ctx.clone();
@@ -1343,7 +1438,12 @@ impl SyncManager {
                         content_id, user_file.file.name, user_file.file.id
                     );
                     let file_path = match self
-                        .resolve_file_path(&service_auth, &user_file.user_email, &user_file.file)
+                        .resolve_file_path(
+                            &drive_client,
+                            &service_auth,
+                            &user_file.user_email,
+                            &user_file.file,
+                        )
                         .await
                     {
                         Ok(path) => Some(path),
@@ -1567,6 +1667,7 @@ impl SyncManager {
                                 content_cache.insert_content_id(&user_file.file.id, content_id.clone());
                                 let file_path = match self
                                     .resolve_file_path(
+                                        &drive_client,
                                         &service_auth,
                                         &user_file.user_email,
                                         &user_file.file,
@@ -1676,6 +1777,7 @@ impl SyncManager {
         existing_state: GoogleSyncCheckpoint,
         ctx: &SyncContext,
     ) -> Result<GoogleSyncCheckpoint> {
+        let apis = self.source_apis(&source.id);
         let sync_run_id = ctx.sync_run_id();
 
         let service_auth = Arc::new(self.create_auth(service_creds, source.source_type).await?);
@@ -1720,6 +1822,7 @@ impl SyncManager {
 
         let gmail_history_ids = existing_state.gmail_history_ids.clone();
         let chat_checkpoint = existing_state.chat.clone();
+        let calendar_watermarks = existing_state.calendar_watermarks.clone();
         let old_page_tokens = existing_state.drive_page_tokens.unwrap_or_default();
         let can_resume_full = sync_type == SyncType::Full && ctx.is_resume();
         let mut new_page_tokens: HashMap<String, String> = if can_resume_full {
@@ -1743,6 +1846,7 @@ impl SyncManager {
         let parallel_users = google_drive_parallel_users();
         info!("Processing Drive users with concurrency {}", parallel_users);
 
+        let drive_client = &apis.drive_client;
         let user_tasks = stream::iter(user_emails.iter().cloned()).map(|cur_user_email| {
             let service_auth = service_auth.clone();
             let source_id = source.id.clone();
@@ -1834,8 +1938,7 @@ impl SyncManager {
 
                 match result {
                     Ok((scanned, updated)) => {
-                        let page_token = match self
-                            .drive_client
+                        let page_token = match drive_client
                             .get_start_page_token_for_user(service_auth.as_ref(), &cur_user_email)
                             .await
                         {
@@ -1879,6 +1982,8 @@ impl SyncManager {
                             Some(new_page_tokens.clone())
                         },
                         chat: chat_checkpoint.clone(),
+                        learned_api_rates: Some(apis.learned_api_rates()),
+                        calendar_watermarks: calendar_watermarks.clone(),
                     };
                     ctx.save_checkpoint(serde_json::to_value(&checkpoint_state)?)
                         .await
@@ -1934,6 +2039,8 @@ impl SyncManager {
                 Some(new_page_tokens)
             },
             chat: chat_checkpoint,
+            learned_api_rates: Some(apis.learned_api_rates()),
+            calendar_watermarks,
         })
     }
 
@@ -1946,6 +2053,7 @@ impl SyncManager {
         known_groups: HashSet<String>,
         ctx: &SyncContext,
     ) -> Result<GoogleSyncCheckpoint> {
+        let apis = self.source_apis(&source.id);
         let sync_run_id = ctx.sync_run_id();
 
         let service_auth = Arc::new(self.create_auth(service_creds, source.source_type).await?);
@@ -1989,6 +2097,7 @@ impl SyncManager {
 
         let drive_page_tokens = existing_state.drive_page_tokens.clone();
         let chat_checkpoint = existing_state.chat.clone();
+        let calendar_watermarks = existing_state.calendar_watermarks.clone();
         let old_history_ids = existing_state.gmail_history_ids.unwrap_or_default();
         let can_resume_full = sync_type == SyncType::Full && ctx.is_resume();
         let mut new_history_ids: HashMap<String, String> = if can_resume_full {
@@ -2114,7 +2223,7 @@ impl SyncManager {
                     // and checkpoint immediately. Capturing before processing
                     // would let resume skip past unprocessed history on crash.
                     if user_succeeded {
-                        match self
+                        match apis
                             .gmail_client
                             .get_profile(&service_auth, &cur_user_email)
                             .await
@@ -2138,6 +2247,8 @@ impl SyncManager {
                             },
                             drive_page_tokens: drive_page_tokens.clone(),
                             chat: chat_checkpoint.clone(),
+                            learned_api_rates: Some(apis.learned_api_rates()),
+                            calendar_watermarks: calendar_watermarks.clone(),
                         };
                         ctx.save_checkpoint(serde_json::to_value(&checkpoint_state)?)
                             .await
@@ -2186,9 +2297,294 @@ impl SyncManager {
             },
             drive_page_tokens,
             chat: chat_checkpoint,
+            learned_api_rates: Some(apis.learned_api_rates()),
+            calendar_watermarks,
+        })
+    }
+
+    async fn sync_google_calendar_source_internal(
+        &self,
+        source: &Source,
+        service_creds: &ServiceCredential,
+        sync_type: SyncType,
+        existing_state: GoogleSyncCheckpoint,
+        known_groups: HashSet<String>,
+        ctx: &SyncContext,
+    ) -> Result<GoogleSyncCheckpoint> {
+        let apis = self.source_apis(&source.id);
+        let sync_run_id = ctx.sync_run_id();
+        let service_auth = Arc::new(self.create_auth(service_creds, source.source_type).await?);
+
+        let user_emails: Vec<String> = if service_auth.is_oauth() {
+            let email = service_auth
+                .oauth_user_email()
+                .ok_or_else(|| anyhow!("OAuth auth missing user_email"))?
+                .to_string();
+            info!("OAuth Google Calendar sync for single user: {}", email);
+            vec![email]
+        } else {
+            let domain = crate::auth::get_domain_from_credentials(service_creds)?;
+            let admin_email = ctx.get_user_email_for_source().await.map_err(|e| {
+                anyhow!(
+                    "Failed to get user email for source {}: {}. Make sure the source has a valid creator.",
+                    source.id,
+                    e
+                )
+            })?;
+            let admin_access_token =
+                service_auth
+                    .get_access_token(&admin_email)
+                    .await
+                    .map_err(|e| {
+                        anyhow!(
+                            "Failed to get access token for admin {}: {}",
+                            admin_email,
+                            e
+                        )
+                    })?;
+            let all_users = self
+                .admin_client
+                .list_all_users(&admin_access_token, &domain)
+                .await?;
+            all_users
+                .into_iter()
+                .filter(|user| source.should_index_user(&user.primary_email))
+                .map(|user| user.primary_email)
+                .collect()
+        };
+        let indexed_users: HashSet<String> = user_emails
+            .iter()
+            .map(|email| email.to_lowercase())
+            .collect();
+
+        let (time_min, _gmail_cutoff_date) = self.get_cutoff_date()?;
+        let time_max = (OffsetDateTime::now_utc()
+            + time::Duration::days(google_calendar_future_days()))
+        .format(&time::format_description::well_known::Rfc3339)?;
+        info!(
+            "Syncing Google Calendar events between {} and {} for {} users",
+            time_min,
+            time_max,
+            user_emails.len()
+        );
+
+        let is_incremental = matches!(sync_type, SyncType::Incremental);
+        let can_resume_full = sync_type == SyncType::Full && ctx.is_resume();
+        let old_watermarks = existing_state
+            .calendar_watermarks
+            .clone()
+            .unwrap_or_default();
+        let mut new_watermarks: HashMap<String, String> = if is_incremental || can_resume_full {
+            old_watermarks.clone()
+        } else {
+            HashMap::new()
+        };
+
+        let mut processed_events = HashSet::new();
+        let mut total_processed = 0;
+        let mut total_updated = 0;
+        let mut successful_users = 0;
+        let mut failed_users = 0;
+        let mut last_error: Option<String> = None;
+
+        for cur_user_email in &user_emails {
+            if ctx.is_cancelled() {
+                info!(
+                    "Sync {} cancelled, stopping Google Calendar sync early",
+                    sync_run_id
+                );
+                break;
+            }
+
+            let stored_watermark = old_watermarks.get(cur_user_email.as_str());
+            if can_resume_full && stored_watermark.is_some() {
+                info!(
+                    "Skipping Google Calendar user {} already checkpointed for sync {}",
+                    cur_user_email, sync_run_id
+                );
+                continue;
+            }
+
+            // Taken before listing, so changes made while the user's events
+            // are being listed are picked up again next time
+            let listed_at =
+                OffsetDateTime::now_utc().format(&time::format_description::well_known::Rfc3339)?;
+            let query = ListEventsQuery {
+                time_min: time_min.clone(),
+                time_max: time_max.clone(),
+                updated_min: stored_watermark.filter(|_| is_incremental).cloned(),
+            };
+            match self
+                .sync_calendar_for_user(
+                    cur_user_email,
+                    &service_auth,
+                    ctx,
+                    &query,
+                    &mut processed_events,
+                    &indexed_users,
+                    &known_groups,
+                )
+                .await
+            {
+                Ok((processed, updated)) => {
+                    successful_users += 1;
+                    total_processed += processed;
+                    total_updated += updated;
+                    info!(
+                        "User {} Google Calendar sync completed: {} processed, {} updated",
+                        cur_user_email, processed, updated
+                    );
+                    if ctx.is_cancelled() {
+                        break;
+                    }
+
+                    new_watermarks.insert(cur_user_email.clone(), listed_at);
+                    let checkpoint_state = GoogleSyncCheckpoint {
+                        calendar_watermarks: Some(new_watermarks.clone()),
+                        learned_api_rates: Some(apis.learned_api_rates()),
+                        ..existing_state.clone()
+                    };
+                    ctx.save_checkpoint(serde_json::to_value(&checkpoint_state)?)
+                        .await
+                        .with_context(|| {
+                            format!(
+                                "Failed to checkpoint Google Calendar state after user {}",
+                                cur_user_email
+                            )
+                        })?;
+                }
+                Err(e) => {
+                    if is_google_api_service_disabled_error(&e) {
+                        return Err(google_api_service_disabled_error("Google Calendar", &e));
+                    }
+                    error!(
+                        "Failed to process Google Calendar for user {}: {:#}",
+                        cur_user_email, e
+                    );
+                    failed_users += 1;
+                    last_error = Some(format!("{:#}", e));
+                }
+            }
+        }
+
+        if !ctx.is_cancelled() && successful_users == 0 && failed_users > 0 {
+            return Err(anyhow!(
+                "Google Calendar sync failed for all {} indexed users; last error: {}",
+                failed_users,
+                last_error.unwrap_or_else(|| "unknown error".to_string())
+            ));
+        }
+
+        info!(
+            "Google Calendar sync completed for source {}: {} total processed, {} total updated",
+            source.id, total_processed, total_updated
+        );
+
+        Ok(GoogleSyncCheckpoint {
+            calendar_watermarks: if new_watermarks.is_empty() {
+                None
+            } else {
+                Some(new_watermarks)
+            },
+            learned_api_rates: Some(apis.learned_api_rates()),
+            ..existing_state
         })
     }
 
+    /// Index the events of a user's primary calendar that `query` selects.
+    /// Events already handled through another attendee's calendar in this
+    /// sync are skipped.
+    ///
+    /// A cancelled event is removed when the copy comes from its organizer's
+    /// calendar, or when the organizer is not one of the indexed users. An
+    /// attendee's copy is also marked cancelled when just that attendee is
+    /// removed from the event, so it cannot be trusted on its own.
+    #[allow(clippy::too_many_arguments)]
+    async fn sync_calendar_for_user(
+        &self,
+        user_email: &str,
+        service_auth: &Arc<GoogleAuth>,
+        ctx: &SyncContext,
+        query: &ListEventsQuery,
+        processed_events: &mut HashSet<String>,
+        indexed_users: &HashSet<String>,
+        known_groups: &HashSet<String>,
+    ) -> Result<(usize, usize)> {
+        let apis = self.source_apis(ctx.source_id());
+        let mut page_token: Option<String> = None;
+        let mut processed = 0;
+        let mut updated = 0;
+
+        loop {
+            let response = apis
+                .calendar_client
+                .list_events(
+                    service_auth,
+                    user_email,
+                    "primary",
+                    query,
+                    page_token.as_deref(),
+                )
+                .await
+                .with_context(|| {
+                    format!(
+                        "Failed to list Calendar events for user {} (page_token: {:?})",
+                        user_email, page_token
+                    )
+                })?;
+            ctx.increment_scanned(response.items.len() as i32).await?;
+
+            for event in response.items {
+                if ctx.is_cancelled() {
+                    return Ok((processed, updated));
+                }
+                let document_id = event.document_id();
+                if processed_events.contains(&document_id) {
+                    continue;
+                }
+
+                if event.is_cancelled() {
+                    let organizer_copy = event.organizer.as_ref().is_some_and(|o| o.is_self);
+                    let organizer_indexed = event
+                        .organizer_email()
+                        .is_some_and(|email| indexed_users.contains(&email));
+                    if organizer_copy || !organizer_indexed {
+                        processed_events.insert(document_id.clone());
+                        self.publish_deletion_event(ctx, &document_id).await?;
+                        updated += 1;
+                    }
+                    continue;
+                }
+                if !event.is_indexable() {
+                    continue;
+                }
+
+                processed_events.insert(document_id.clone());
+                processed += 1;
+                let content_id = ctx
+                    .store_content(&event.render_content())
+                    .await
+                    .with_context(|| format!("Failed to store content of {}", document_id))?;
+                let connector_event = event.to_connector_event(
+                    ctx.sync_run_id(),
+                    ctx.source_id(),
+                    &content_id,
+                    known_groups,
+                    user_email,
+                );
+                ctx.emit_event(connector_event).await?;
+                updated += 1;
+            }
+
+            page_token = response.next_page_token;
+            if page_token.is_none() {
+                break;
+            }
+        }
+
+        Ok((processed, updated))
+    }
+
     async fn sync_google_chat_source_internal(
         &self,
         source: &Source,
@@ -2198,6 +2594,7 @@ impl SyncManager {
         _known_groups: HashSet<String>,
         ctx: &SyncContext,
     ) -> Result<GoogleSyncCheckpoint> {
+        let apis = self.source_apis(&source.id);
         let service_auth = Arc::new(
             self.create_auth(service_creds, SourceType::GoogleChat)
                 .await?,
@@ -2341,6 +2738,8 @@ impl SyncManager {
                 gmail_history_ids: existing_state.gmail_history_ids.clone(),
                 drive_page_tokens: existing_state.drive_page_tokens.clone(),
                 chat: Some(chat_checkpoint.clone()),
+                learned_api_rates: Some(apis.learned_api_rates()),
+                calendar_watermarks: existing_state.calendar_watermarks.clone(),
             };
             ctx.save_checkpoint(serde_json::to_value(&checkpoint_state)?)
                 .await?;
@@ -2350,6 +2749,8 @@ impl SyncManager {
             gmail_history_ids: existing_state.gmail_history_ids,
             drive_page_tokens: existing_state.drive_page_tokens,
             chat: Some(chat_checkpoint),
+            learned_api_rates: Some(apis.learned_api_rates()),
+            calendar_watermarks: existing_state.calendar_watermarks,
         })
     }
 
@@ -2360,6 +2761,7 @@ impl SyncManager {
         source: &Source,
         ctx: &SyncContext,
     ) -> Result<HashMap<String, (GoogleChatSpace, String)>> {
+        let apis = self.source_apis(&source.id);
         let mut spaces: HashMap<String, (GoogleChatSpace, String)> = HashMap::new();
         let mut successful_users = 0usize;
         let mut failed_users = 0usize;
@@ -2372,7 +2774,7 @@ impl SyncManager {
             let mut page_token: Option<String> = None;
             let mut user_had_successful_page = false;
             loop {
-                let response = match self
+                let response = match apis
                     .chat_client
                     .list_spaces_for_user(service_auth, user_email, page_token.as_deref())
                     .await
@@ -2446,10 +2848,11 @@ impl SyncManager {
         user_id_to_email: &HashMap<String, String>,
         checkpoint: &mut GoogleChatSpaceCheckpoint,
     ) -> Result<()> {
+        let apis = self.source_apis(&source.id);
         let mut members: HashSet<String> = HashSet::new();
         let mut page_token: Option<String> = None;
         loop {
-            let response = self
+            let response = apis
                 .chat_client
                 .list_members(
                     service_auth,
@@ -2507,6 +2910,7 @@ impl SyncManager {
         space: &GoogleChatSpace,
         checkpoint: &mut GoogleChatSpaceCheckpoint,
     ) -> Result<()> {
+        let apis = self.source_apis(&source.id);
         checkpoint.full_in_progress = true;
         let mut builder = GoogleChatSegmentBuilder::new(space);
         let old_segments: HashSet<String> = checkpoint
@@ -2524,7 +2928,7 @@ impl SyncManager {
             if ctx.is_cancelled() {
                 break;
             }
-            let response = self
+            let response = apis
                 .chat_client
                 .list_messages(
                     service_auth,
@@ -2607,6 +3011,7 @@ impl SyncManager {
         space: &GoogleChatSpace,
         checkpoint: &mut GoogleChatSpaceCheckpoint,
     ) -> Result<()> {
+        let apis = self.source_apis(&source.id);
         checkpoint.incremental_in_progress = true;
         let last_event = checkpoint
             .last_event_time
@@ -2623,7 +3028,7 @@ impl SyncManager {
         };
         let mut page_token = checkpoint.incremental_event_page_token.clone();
         loop {
-            let response = self
+            let response = apis
                 .chat_client
                 .list_space_events(
                     service_auth,
@@ -2704,6 +3109,7 @@ impl SyncManager {
         checkpoint: &mut GoogleChatSpaceCheckpoint,
         affected_times: &[OffsetDateTime],
     ) -> Result<()> {
+        let apis = self.source_apis(&source.id);
         let windows = self.build_chat_rebuild_windows(checkpoint, affected_times)?;
         if windows.len() > GOOGLE_CHAT_MAX_TARGETED_INCREMENTAL_WINDOWS {
             return Err(anyhow!(
@@ -2736,7 +3142,7 @@ impl SyncManager {
                 if ctx.is_cancelled() {
                     break;
                 }
-                let response = self
+                let response = apis
                     .chat_client
                     .list_messages(
                         service_auth,
@@ -3052,8 +3458,9 @@ impl SyncManager {
         reader_email: &str,
         attachment: &GoogleChatSegmentAttachmentRef,
     ) -> Result<GoogleChatAttachmentStoredContent> {
+        let apis = self.source_apis(ctx.source_id());
         if let Some(resource_name) = attachment.resource_name.as_deref() {
-            let data = self
+            let data = apis
                 .chat_client
                 .download_uploaded_attachment(service_auth, reader_email, resource_name)
                 .await
@@ -3087,7 +3494,7 @@ impl SyncManager {
         }
 
         if let Some(drive_file_id) = attachment.drive_file_id.as_deref() {
-            let file = self
+            let file = apis
                 .drive_client
                 .get_file_metadata(drive_auth, reader_email, drive_file_id)
                 .await
@@ -3099,7 +3506,7 @@ impl SyncManager {
                 })?;
             let source_url = file.web_view_link.clone();
             let size = file.size.clone();
-            let content = self
+            let content = apis
                 .drive_client
                 .get_file_content(drive_auth, reader_email, &file)
                 .await
@@ -3396,6 +3803,7 @@ impl SyncManager {
         source_id: &str,
         webhook_url: String,
     ) -> Result<WebhookChannelResponse> {
+        let apis = self.source_apis(source_id);
         // Capture old channel info before registering the new one
         let old_channel = match self.sdk_client.get_connector_state(source_id).await {
             Ok(Some(raw_state)) => {
@@ -3427,14 +3835,14 @@ impl SyncManager {
         };
         let access_token = auth.get_access_token(&user_email).await?;
 
-        let start_page_token = self
+        let start_page_token = apis
             .drive_client
             .get_start_page_token(&access_token)
             .await?;
 
         let webhook_channel = WebhookChannel::new(webhook_url.clone(), source_id);
 
-        let webhook_response = self
+        let webhook_response = apis
             .drive_client
             .register_changes_webhook(&access_token, &webhook_channel, &start_page_token)
             .await?;
@@ -3487,6 +3895,7 @@ impl SyncManager {
         channel_id: &str,
         resource_id: &str,
     ) -> Result<()> {
+        let apis = self.source_apis(source_id);
         let service_creds = self.get_service_credentials(source_id).await?;
         let auth = self
             .create_auth(&service_creds, SourceType::GoogleDrive)
@@ -3499,7 +3908,7 @@ impl SyncManager {
         };
         let access_token = auth.get_access_token(&user_email).await?;
 
-        self.drive_client
+        apis.drive_client
             .stop_webhook_channel(&access_token, channel_id, resource_id)
             .await?;
 
@@ -3512,6 +3921,7 @@ impl SyncManager {
 
     async fn resolve_file_path(
         &self,
+        drive_client: &DriveClient,
         auth: &GoogleAuth,
         user_email: &str,
         file: &crate::models::GoogleDriveFile,
@@ -3519,7 +3929,7 @@ impl SyncManager {
         if let Some(parents) = &file.parents {
             if let Some(parent_id) = parents.first() {
                 return self
-                    .build_full_path(auth, user_email, parent_id, &file.name)
+                    .build_full_path(drive_client, auth, user_email, parent_id, &file.name)
                     .await;
             }
         }
@@ -3530,6 +3940,7 @@ impl SyncManager {
 
     async fn build_full_path(
         &self,
+        drive_client: &DriveClient,
         auth: &GoogleAuth,
         user_email: &str,
         folder_id: &str,
@@ -3578,8 +3989,7 @@ impl SyncManager {
                         "Folder {} not found in cache, fetching metadata.",
                         current_folder_id
                     );
-                    let folder_metadata = self
-                        .drive_client
+                    let folder_metadata = drive_client
                         .get_folder_metadata(&auth, &user_email, &folder_id)
                         .await;
 
@@ -3647,6 +4057,7 @@ impl SyncManager {
         created_after: Option<&str>,
         known_groups: Arc<HashSet<String>>,
     ) -> Result<(usize, usize)> {
+        let apis = self.source_apis(ctx.source_id());
         info!("Processing Gmail for user: {}", user_email);
 
         let mut page_token: Option<String> = None;
@@ -3662,7 +4073,7 @@ impl SyncManager {
                 user_email, page_token
             );
 
-            let response = self
+            let response = apis
                 .gmail_client
                 .list_threads(
                     &service_auth,
@@ -3741,6 +4152,7 @@ impl SyncManager {
         processed_threads: Arc<std::sync::Mutex<HashSet<String>>>,
         known_groups: Arc<HashSet<String>>,
     ) -> Result<(usize, usize)> {
+        let apis = self.source_apis(source_id);
         info!(
             "Processing incremental Gmail sync for user {} from historyId {}",
             user_email, start_history_id
@@ -3750,7 +4162,7 @@ impl SyncManager {
         let mut page_token: Option<String> = None;
 
         loop {
-            let response = self
+            let response = apis
                 .gmail_client
                 .list_history(
                     &service_auth,
@@ -3834,6 +4246,7 @@ impl SyncManager {
         processed_threads: Arc<std::sync::Mutex<HashSet<String>>>,
         known_groups: Arc<HashSet<String>>,
     ) -> Result<(usize, usize)> {
+        let apis = self.source_apis(ctx.source_id());
         let mut total_processed = 0;
         let mut total_updated = 0;
         let mut total_deduped = 0usize;
@@ -3909,7 +4322,7 @@ impl SyncManager {
                     tokio::time::sleep(delay).await;
                 }
 
-                let batch_results = match self
+                let batch_results = match apis
                     .gmail_client
                     .batch_get_threads(
                         &service_auth,
@@ -4011,6 +4424,7 @@ impl SyncManager {
         ctx: &SyncContext,
         known_groups: &HashSet<String>,
     ) -> bool {
+        let apis = self.source_apis(ctx.source_id());
         let mut gmail_thread = GmailThread::new(thread_id.to_string());
         for message in response.messages {
             gmail_thread.add_message(message);
@@ -4021,15 +4435,6 @@ impl SyncManager {
             return false;
         }
 
-        let thread_url = gmail_thread.message_id.as_ref().map(|mid| {
-            let clean_id = mid.trim_start_matches('<').trim_end_matches('>');
-            let encoded = urlencoding::encode(clean_id);
-            format!(
-                "https://mail.google.com/mail/#search/rfc822msgid%3A{}",
-                encoded
-            )
-        });
-
         // Extract attachments and store their content first, so the thread
         // document can carry pointers to its attachments in metadata.extra.
         //
@@ -4040,10 +4445,10 @@ impl SyncManager {
         // We persist the canonical RFC 822 Message-ID (not Gmail's per-mailbox
         // messageId) so the attachment can be fetched from any participating
         // user's mailbox via `messages.list?q=rfc822msgid:<id>`.
-        let mut stored_attachments: Vec<(ExtractedAttachment, String, String)> = Vec::new();
+        let mut stored_attachments: Vec<ThreadAttachment> = Vec::new();
         let mut seen: HashSet<(String, u64)> = HashSet::new();
         for message in &gmail_thread.messages {
-            let rfc822_msgid = match self
+            let rfc822_msgid = match apis
                 .gmail_client
                 .get_header_value(message, "Message-ID")
                 .map(|raw| {
@@ -4065,7 +4470,7 @@ impl SyncManager {
                 }
             };
 
-            let attachments = self
+            let attachments = apis
                 .gmail_client
                 .extract_attachments(
                     message,
@@ -4100,23 +4505,27 @@ impl SyncManager {
                     }
                 };
 
-                stored_attachments.push((att, att_content_id, rfc822_msgid.clone()));
+                stored_attachments.push(ThreadAttachment {
+                    pointer: AttachmentPointer {
+                        id: build_attachment_doc_id(&rfc822_msgid, &att.filename, att.size),
+                        filename: att.filename,
+                        mime_type: att.mime_type,
+                        size: att.size,
+                    },
+                    message_id: att.message_id,
+                    content_id: att_content_id,
+                });
             }
         }
 
         let attachment_pointers: Vec<AttachmentPointer> = stored_attachments
             .iter()
-            .map(|(att, _, rfc822_msgid)| AttachmentPointer {
-                id: build_attachment_doc_id(rfc822_msgid, &att.filename, att.size),
-                filename: att.filename.clone(),
-                mime_type: att.mime_type.clone(),
-                size: att.size,
-            })
+            .map(|att| att.pointer.clone())
             .collect();
 
         let emit_result: Result<bool> = async {
             let content = gmail_thread
-                .aggregate_content(&self.gmail_client, ctx.sdk_client(), ctx.sync_run_id())
+                .aggregate_content(&apis.gmail_client, ctx.sdk_client(), ctx.sync_run_id())
                 .await
                 .context("aggregate content")?;
             if content.trim().is_empty() {
@@ -4151,68 +4560,23 @@ impl SyncManager {
             }
         };
 
-        let mut att_users = Vec::new();
-        let mut att_groups = Vec::new();
-        let mut att_participants = gmail_thread.participants.clone();
-        att_participants.insert(user_email.to_lowercase());
-        for participant in &att_participants {
-            if known_groups.contains(participant) {
-                att_groups.push(participant.clone());
-            } else {
-                att_users.push(participant.clone());
-            }
-        }
-        att_users.sort();
-        att_users.dedup();
-        att_groups.sort();
-        att_groups.dedup();
-        let att_permissions = DocumentPermissions {
-            public: false,
-            users: att_users,
-            groups: att_groups,
-        };
-
-        for (att, att_content_id, rfc822_msgid) in stored_attachments {
-            let att_doc_id = build_attachment_doc_id(&rfc822_msgid, &att.filename, att.size);
-
-            let mut att_extra = HashMap::new();
-            att_extra.insert(
-                "parent_thread_id".to_string(),
-                json!(gmail_thread.canonical_external_id()),
+        for att in &stored_attachments {
+            let att_event = gmail_thread.attachment_to_connector_event(
+                ctx.sync_run_id(),
+                ctx.source_id(),
+                known_groups,
+                user_email,
+                att,
             );
-            att_extra.insert("gmail_thread_id".to_string(), json!(thread_id));
-
-            let att_metadata = DocumentMetadata {
-                title: Some(att.filename.clone()),
-                author: None,
-                created_at: None,
-                updated_at: None,
-                content_type: mime_type_to_content_type(&att.mime_type),
-                mime_type: Some(att.mime_type.clone()),
-                size: Some(att.size.to_string()),
-                url: thread_url.clone(),
-                path: Some(format!("/Gmail/{}/{}", gmail_thread.subject, att.filename)),
-                extra: Some(att_extra),
-            };
-
-            let att_event = ConnectorEvent::DocumentCreated {
-                sync_run_id: ctx.sync_run_id().to_string(),
-                source_id: ctx.source_id().to_string(),
-                document_id: att_doc_id.clone(),
-                content_id: att_content_id,
-                metadata: att_metadata,
-                permissions: att_permissions.clone(),
-                attributes: Some(HashMap::new()),
-            };
 
             match ctx.emit_event(att_event).await {
                 Ok(_) => debug!(
                     "Queued attachment {} for thread {}",
-                    att.filename, thread_id
+                    att.pointer.filename, thread_id
                 ),
                 Err(e) => error!(
                     "Failed to queue attachment {} for thread {}: {}",
-                    att.filename, thread_id, e
+                    att.pointer.filename, thread_id, e
                 ),
             }
         }
@@ -61,6 +61,13 @@ impl GoogleConnectorTestFixture {
             sync_backoff_base_seconds: 30,
             sync_backoff_max_seconds: 3600,
             sync_max_consecutive_failures: 10,
+            export_link_ttl_seconds: 86400,
+            backpressure: Default::default(),
+            source_stats_refresh_interval_seconds: 3600,
+            source_stats_retention_days: 365,
+            connector_error_retention_days: 30,
+            audit_log_retention_days: 365,
+            credential_refresh_margin_seconds: 900,
             extraction_concurrency: 2,
             extraction_retry_after_seconds: 1,
         };
@@ -424,6 +424,7 @@ mod drive_buffer_budget_tests {
             created_at: now,
             updated_at: now,
             created_by: "user-id".to_string(),
+            workspace_id: shared::models::DEFAULT_WORKSPACE_ID.to_string(),
         }
     }
 
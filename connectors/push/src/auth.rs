@@ -0,0 +1,85 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use axum::http::{HeaderMap, header};
+use omni_connector_sdk::{PushKeyIdentity, SdkClient};
+
+/// Pull the API key out of an `Authorization: Bearer <key>` header.
+pub fn bearer_key(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+        .map(str::trim)
+        .filter(|key| !key.is_empty())
+}
+
+/// Resolves API keys to sources through connector-manager, remembering
+/// successful lookups for `ttl` so a busy pusher isn't a database hit per
+/// request. Failed lookups are not cached.
+pub struct KeyAuthenticator {
+    sdk_client: SdkClient,
+    ttl: Duration,
+    cache: Mutex<HashMap<String, (PushKeyIdentity, Instant)>>,
+}
+
+impl KeyAuthenticator {
+    pub fn new(sdk_client: SdkClient, ttl: Duration) -> Self {
+        Self {
+            sdk_client,
+            ttl,
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub async fn authenticate(&self, key: &str) -> Result<Option<PushKeyIdentity>> {
+        {
+            let mut cache = self.cache.lock().unwrap();
+            match cache.get(key) {
+                Some((identity, cached_at)) if cached_at.elapsed() < self.ttl => {
+                    return Ok(Some(identity.clone()));
+                }
+                Some(_) => {
+                    cache.remove(key);
+                }
+                None => {}
+            }
+        }
+
+        let identity = self.sdk_client.authenticate_push_key(key).await?;
+        if let Some(identity) = &identity {
+            self.cache
+                .lock()
+                .unwrap()
+                .insert(key.to_string(), (identity.clone(), Instant::now()));
+        }
+        Ok(identity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    #[test]
+    fn test_bearer_key() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(bearer_key(&headers), None);
+
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_static("Bearer omni_push_abc"),
+        );
+        assert_eq!(bearer_key(&headers), Some("omni_push_abc"));
+
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_static("Basic dXNlcjpwYXNz"),
+        );
+        assert_eq!(bearer_key(&headers), None);
+    }
+}
@@ -0,0 +1,57 @@
+use std::env;
+use std::time::Duration;
+
+fn env_or<T: std::str::FromStr>(key: &str, default: T) -> T {
+    env::var(key)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+#[derive(Debug, Clone)]
+pub struct PushConfig {
+    /// Sustained requests per minute allowed for each API key.
+    pub requests_per_minute: u32,
+    /// Requests a key may send in a burst before being throttled.
+    pub burst: u32,
+    /// Maximum documents (or deletions) in one request.
+    pub max_batch_size: usize,
+    /// Maximum size of a single document's content, in bytes.
+    pub max_content_bytes: usize,
+    /// Maximum size of a request body, in bytes.
+    pub max_request_bytes: usize,
+    /// How long a successfully authenticated key is trusted without asking
+    /// connector-manager again. Bounds how long a revoked key keeps working.
+    pub key_cache_ttl: Duration,
+}
+
+impl Default for PushConfig {
+    fn default() -> Self {
+        Self {
+            requests_per_minute: 120,
+            burst: 20,
+            max_batch_size: 100,
+            max_content_bytes: 10 * 1024 * 1024,
+            max_request_bytes: 32 * 1024 * 1024,
+            key_cache_ttl: Duration::from_secs(60),
+        }
+    }
+}
+
+impl PushConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            requests_per_minute: env_or("PUSH_RATE_LIMIT_PER_MINUTE", defaults.requests_per_minute)
+                .max(1),
+            burst: env_or("PUSH_RATE_LIMIT_BURST", defaults.burst).max(1),
+            max_batch_size: env_or("PUSH_MAX_BATCH_SIZE", defaults.max_batch_size).max(1),
+            max_content_bytes: env_or("PUSH_MAX_CONTENT_BYTES", defaults.max_content_bytes),
+            max_request_bytes: env_or("PUSH_MAX_REQUEST_BYTES", defaults.max_request_bytes),
+            key_cache_ttl: Duration::from_secs(env_or(
+                "PUSH_KEY_CACHE_TTL_SECONDS",
+                defaults.key_cache_ttl.as_secs(),
+            )),
+        }
+    }
+}
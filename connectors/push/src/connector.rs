@@ -0,0 +1,78 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use omni_connector_sdk::{Connector, ServiceCredential, Source, SourceType, SyncContext, SyncType};
+use serde_json::Value as JsonValue;
+use tracing::info;
+
+/// Documents of push sources arrive through the ingestion API (see
+/// [`crate::routes`]); there is nothing to pull, so syncs complete immediately.
+pub struct PushConnector;
+
+impl PushConnector {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for PushConnector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Connector for PushConnector {
+    type Config = JsonValue;
+    type Credentials = JsonValue;
+    type State = JsonValue;
+
+    fn name(&self) -> &'static str {
+        "push"
+    }
+
+    fn version(&self) -> &'static str {
+        env!("CARGO_PKG_VERSION")
+    }
+
+    fn display_name(&self) -> String {
+        "Push API".to_string()
+    }
+
+    fn description(&self) -> Option<String> {
+        Some(
+            "Let internal systems push documents to Omni over an authenticated HTTP API"
+                .to_string(),
+        )
+    }
+
+    fn source_types(&self) -> Vec<SourceType> {
+        vec![SourceType::Push]
+    }
+
+    fn sync_modes(&self) -> Vec<SyncType> {
+        vec![SyncType::Incremental]
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    fn requires_credentials(&self) -> bool {
+        false
+    }
+
+    async fn sync(
+        &self,
+        source: Source,
+        _credentials: Option<ServiceCredential>,
+        _state: Option<Self::State>,
+        ctx: SyncContext,
+    ) -> Result<()> {
+        info!(
+            "Push source {} has nothing to pull; completing sync {}",
+            source.id,
+            ctx.sync_run_id()
+        );
+        ctx.complete().await
+    }
+}
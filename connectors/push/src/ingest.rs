@@ -0,0 +1,174 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::anyhow;
+use axum::http::StatusCode;
+use omni_connector_sdk::{ConnectorEvent, SdkClient, SdkError, SyncType};
+use tokio::sync::Mutex;
+use tracing::{error, info};
+
+use crate::models::PushDocument;
+
+#[derive(Debug, thiserror::Error)]
+pub enum IngestError {
+    /// Another sync of the source holds its sync slot; the caller should retry.
+    #[error("source is busy: {0}")]
+    Busy(String),
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+impl From<SdkError> for IngestError {
+    fn from(err: SdkError) -> Self {
+        match err.status() {
+            Some(StatusCode::CONFLICT) => IngestError::Busy(err.to_string()),
+            _ => IngestError::Other(err.into()),
+        }
+    }
+}
+
+/// Writes pushed batches into the event queue. Each batch is recorded as a
+/// short incremental sync run of the source, so it shows up in sync history
+/// and goes through the same pipeline as pulled documents.
+pub struct Ingestor {
+    sdk_client: SdkClient,
+    // Connector-manager allows one incremental sync per source at a time, so
+    // concurrent pushes to a source queue up here instead of failing.
+    source_locks: Mutex<HashMap<String, Arc<Mutex<()>>>>,
+}
+
+impl Ingestor {
+    pub fn new(sdk_client: SdkClient) -> Self {
+        Self {
+            sdk_client,
+            source_locks: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Store and queue documents. Returns the sync run they were recorded in.
+    pub async fn push_documents(
+        &self,
+        source_id: &str,
+        documents: Vec<PushDocument>,
+    ) -> Result<String, IngestError> {
+        let lock = self.source_lock(source_id).await;
+        let _guard = lock.lock().await;
+
+        let sync_run_id = self
+            .sdk_client
+            .create_sync_run(source_id, SyncType::Incremental)
+            .await?;
+        let count = documents.len();
+
+        let result = async {
+            for document in documents {
+                let content_id = self
+                    .sdk_client
+                    .store_content(&sync_run_id, &document.content)
+                    .await?;
+                let event = document.into_event(&sync_run_id, source_id, content_id);
+                self.sdk_client
+                    .emit_event(&sync_run_id, source_id, event)
+                    .await?;
+            }
+            Ok::<_, SdkError>(())
+        }
+        .await;
+
+        self.finish(&sync_run_id, source_id, count, result).await?;
+        info!(
+            "Queued {} pushed documents for source {} (sync run {})",
+            count, source_id, sync_run_id
+        );
+        Ok(sync_run_id)
+    }
+
+    /// Queue deletions. Unknown document ids are ignored by the indexer.
+    pub async fn push_deletions(
+        &self,
+        source_id: &str,
+        document_ids: Vec<String>,
+    ) -> Result<String, IngestError> {
+        let lock = self.source_lock(source_id).await;
+        let _guard = lock.lock().await;
+
+        let sync_run_id = self
+            .sdk_client
+            .create_sync_run(source_id, SyncType::Incremental)
+            .await?;
+        let count = document_ids.len();
+
+        let result = async {
+            for document_id in document_ids {
+                let event = ConnectorEvent::DocumentDeleted {
+                    sync_run_id: sync_run_id.clone(),
+                    source_id: source_id.to_string(),
+                    document_id,
+                };
+                self.sdk_client
+                    .emit_event(&sync_run_id, source_id, event)
+                    .await?;
+            }
+            Ok::<_, SdkError>(())
+        }
+        .await;
+
+        self.finish(&sync_run_id, source_id, count, result).await?;
+        info!(
+            "Queued {} pushed deletions for source {} (sync run {})",
+            count, source_id, sync_run_id
+        );
+        Ok(sync_run_id)
+    }
+
+    /// Flush and complete the run, or fail it if emitting went wrong.
+    async fn finish(
+        &self,
+        sync_run_id: &str,
+        source_id: &str,
+        count: usize,
+        result: Result<(), SdkError>,
+    ) -> Result<(), IngestError> {
+        let result = match result {
+            Ok(()) => self
+                .sdk_client
+                .flush_events(sync_run_id, source_id)
+                .await
+                .map_err(IngestError::Other),
+            Err(e) => Err(IngestError::Other(e.into())),
+        };
+
+        match result {
+            Ok(()) => {
+                let count = i32::try_from(count).map_err(|e| anyhow!(e))?;
+                self.sdk_client
+                    .increment_scanned(sync_run_id, count)
+                    .await?;
+                self.sdk_client
+                    .increment_updated(sync_run_id, count)
+                    .await?;
+                self.sdk_client.complete(sync_run_id).await?;
+                Ok(())
+            }
+            Err(e) => {
+                error!("Push to source {} failed: {}", source_id, e);
+                if let Err(fail_err) = self.sdk_client.fail(sync_run_id, &e.to_string()).await {
+                    error!(
+                        "Failed to mark sync run {} as failed: {}",
+                        sync_run_id, fail_err
+                    );
+                }
+                Err(e)
+            }
+        }
+    }
+
+    async fn source_lock(&self, source_id: &str) -> Arc<Mutex<()>> {
+        self.source_locks
+            .lock()
+            .await
+            .entry(source_id.to_string())
+            .or_default()
+            .clone()
+    }
+}
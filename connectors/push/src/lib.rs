@@ -0,0 +1,7 @@
+pub mod auth;
+pub mod config;
+pub mod connector;
+pub mod ingest;
+pub mod models;
+pub mod rate_limit;
+pub mod routes;
@@ -0,0 +1,27 @@
+use anyhow::Result;
+use dotenvy::dotenv;
+use omni_connector_sdk::telemetry::{self, TelemetryConfig};
+use omni_connector_sdk::{SdkClient, ServerConfig, serve_with_extra_routes};
+use omni_push_connector::config::PushConfig;
+use omni_push_connector::connector::PushConnector;
+use omni_push_connector::routes;
+use tracing::info;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenv().ok();
+
+    telemetry::init_telemetry(TelemetryConfig::from_env("omni-push-connector"))?;
+
+    info!("Starting Push Connector");
+
+    let config = PushConfig::from_env();
+    let extra_routes = routes::build_router(SdkClient::from_env()?, &config);
+
+    serve_with_extra_routes(
+        PushConnector::new(),
+        ServerConfig::from_env()?,
+        extra_routes,
+    )
+    .await
+}
@@ -0,0 +1,316 @@
+use std::collections::{HashMap, HashSet};
+
+use omni_connector_sdk::{ConnectorEvent, DocumentMetadata, DocumentPermissions};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use time::OffsetDateTime;
+
+const MAX_ID_LEN: usize = 512;
+const MAX_TITLE_LEN: usize = 1000;
+
+/// A document pushed by an external system. `id` is the caller's identifier;
+/// pushing the same id again replaces the document.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PushDocument {
+    pub id: String,
+    pub title: String,
+    pub content: String,
+    pub permissions: PushPermissions,
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub author: Option<String>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub created_at: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub updated_at: Option<OffsetDateTime>,
+    /// Kind of record, e.g. "ticket" or "runbook". Shown as the content type
+    /// facet in search.
+    #[serde(default)]
+    pub content_type: Option<String>,
+    #[serde(default)]
+    pub mime_type: Option<String>,
+    /// Display path for hierarchical context, e.g. "Runbooks / Databases".
+    #[serde(default)]
+    pub path: Option<String>,
+    /// Filterable attributes.
+    #[serde(default)]
+    pub attributes: Option<HashMap<String, JsonValue>>,
+    /// Free-form metadata stored with the document.
+    #[serde(default)]
+    pub metadata: Option<HashMap<String, JsonValue>>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PushPermissions {
+    #[serde(default)]
+    pub public: bool,
+    /// Emails of users who can see the document.
+    #[serde(default)]
+    pub users: Vec<String>,
+    /// Emails of groups whose members can see the document.
+    #[serde(default)]
+    pub groups: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PushDocumentsRequest {
+    pub documents: Vec<PushDocument>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PushDeletionsRequest {
+    pub document_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+}
+
+/// Limits a request is validated against.
+#[derive(Debug, Clone, Copy)]
+pub struct PushLimits {
+    pub max_batch_size: usize,
+    pub max_content_bytes: usize,
+}
+
+fn validate_batch_size(len: usize, field: &str, limits: PushLimits, errors: &mut Vec<FieldError>) {
+    if len == 0 {
+        errors.push(FieldError::new(field, "must not be empty"));
+    } else if len > limits.max_batch_size {
+        errors.push(FieldError::new(
+            field,
+            format!("at most {} items per request", limits.max_batch_size),
+        ));
+    }
+}
+
+fn validate_id(id: &str, field: &str, errors: &mut Vec<FieldError>) {
+    if id.trim().is_empty() {
+        errors.push(FieldError::new(field, "must not be empty"));
+    } else if id.len() > MAX_ID_LEN {
+        errors.push(FieldError::new(
+            field,
+            format!("must be at most {} bytes", MAX_ID_LEN),
+        ));
+    } else if id.chars().any(char::is_control) {
+        errors.push(FieldError::new(
+            field,
+            "must not contain control characters",
+        ));
+    }
+}
+
+impl PushDocumentsRequest {
+    pub fn validate(&self, limits: PushLimits) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+        validate_batch_size(self.documents.len(), "documents", limits, &mut errors);
+
+        let mut seen = HashSet::new();
+        for (i, doc) in self.documents.iter().enumerate() {
+            let field = |name: &str| format!("documents[{}].{}", i, name);
+
+            validate_id(&doc.id, &field("id"), &mut errors);
+            if !seen.insert(doc.id.as_str()) {
+                errors.push(FieldError::new(field("id"), "duplicate id in request"));
+            }
+            if doc.title.trim().is_empty() {
+                errors.push(FieldError::new(field("title"), "must not be empty"));
+            } else if doc.title.chars().count() > MAX_TITLE_LEN {
+                errors.push(FieldError::new(
+                    field("title"),
+                    format!("must be at most {} characters", MAX_TITLE_LEN),
+                ));
+            }
+            if doc.content.trim().is_empty() {
+                errors.push(FieldError::new(field("content"), "must not be empty"));
+            } else if doc.content.len() > limits.max_content_bytes {
+                errors.push(FieldError::new(
+                    field("content"),
+                    format!("must be at most {} bytes", limits.max_content_bytes),
+                ));
+            }
+            if let Some(url) = &doc.url
+                && !(url.starts_with("https://") || url.starts_with("http://"))
+            {
+                errors.push(FieldError::new(field("url"), "must be an http(s) URL"));
+            }
+
+            let permissions = &doc.permissions;
+            if !permissions.public && permissions.users.is_empty() && permissions.groups.is_empty()
+            {
+                errors.push(FieldError::new(
+                    field("permissions"),
+                    "must be public or list at least one user or group",
+                ));
+            }
+            for (j, email) in permissions.users.iter().enumerate() {
+                if !email.contains('@') {
+                    errors.push(FieldError::new(
+                        format!("documents[{}].permissions.users[{}]", i, j),
+                        "must be an email address",
+                    ));
+                }
+            }
+            for (j, email) in permissions.groups.iter().enumerate() {
+                if !email.contains('@') {
+                    errors.push(FieldError::new(
+                        format!("documents[{}].permissions.groups[{}]", i, j),
+                        "must be an email address",
+                    ));
+                }
+            }
+        }
+        errors
+    }
+}
+
+impl PushDeletionsRequest {
+    pub fn validate(&self, limits: PushLimits) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+        validate_batch_size(self.document_ids.len(), "document_ids", limits, &mut errors);
+        for (i, id) in self.document_ids.iter().enumerate() {
+            validate_id(id, &format!("document_ids[{}]", i), &mut errors);
+        }
+        errors
+    }
+}
+
+impl PushDocument {
+    /// Build the event for this document once its content is stored.
+    pub fn into_event(
+        self,
+        sync_run_id: &str,
+        source_id: &str,
+        content_id: String,
+    ) -> ConnectorEvent {
+        let size = self.content.len().to_string();
+        ConnectorEvent::DocumentCreated {
+            sync_run_id: sync_run_id.to_string(),
+            source_id: source_id.to_string(),
+            document_id: self.id,
+            content_id,
+            metadata: DocumentMetadata {
+                title: Some(self.title),
+                author: self.author,
+                created_at: self.created_at,
+                updated_at: self.updated_at,
+                content_type: self.content_type,
+                mime_type: Some(self.mime_type.unwrap_or_else(|| "text/plain".to_string())),
+                size: Some(size),
+                url: self.url,
+                path: self.path,
+                extra: self.metadata,
+            },
+            permissions: DocumentPermissions {
+                public: self.permissions.public,
+                users: self.permissions.users,
+                groups: self.permissions.groups,
+            },
+            attributes: self.attributes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const LIMITS: PushLimits = PushLimits {
+        max_batch_size: 2,
+        max_content_bytes: 16,
+    };
+
+    fn documents(value: JsonValue) -> PushDocumentsRequest {
+        serde_json::from_value(json!({ "documents": value })).unwrap()
+    }
+
+    fn fields(errors: Vec<FieldError>) -> Vec<String> {
+        errors.into_iter().map(|e| e.field).collect()
+    }
+
+    #[test]
+    fn test_valid_document_passes() {
+        let request = documents(json!([{
+            "id": "runbook-1",
+            "title": "Failover",
+            "content": "Promote replica",
+            "permissions": {"groups": ["sre@example.com"]},
+            "updated_at": "2024-05-01T10:00:00Z"
+        }]));
+        assert!(request.validate(LIMITS).is_empty());
+    }
+
+    #[test]
+    fn test_invalid_documents_report_field_paths() {
+        let request = documents(json!([
+            {
+                "id": "a",
+                "title": " ",
+                "content": "this content is too long",
+                "url": "ftp://example.com",
+                "permissions": {}
+            },
+            {
+                "id": "a",
+                "title": "Dup",
+                "content": "x",
+                "permissions": {"users": ["not-an-email"]}
+            }
+        ]));
+        assert_eq!(
+            fields(request.validate(LIMITS)),
+            vec![
+                "documents[0].title",
+                "documents[0].content",
+                "documents[0].url",
+                "documents[0].permissions",
+                "documents[1].id",
+                "documents[1].permissions.users[0]",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_batch_size_limits() {
+        let empty = PushDeletionsRequest {
+            document_ids: vec![],
+        };
+        assert_eq!(fields(empty.validate(LIMITS)), vec!["document_ids"]);
+
+        let too_many = PushDeletionsRequest {
+            document_ids: vec!["a".into(), "b".into(), "c".into()],
+        };
+        assert_eq!(fields(too_many.validate(LIMITS)), vec!["document_ids"]);
+    }
+
+    #[test]
+    fn test_unknown_fields_are_rejected() {
+        let result: Result<PushDocumentsRequest, _> = serde_json::from_value(json!({
+            "documents": [{
+                "id": "a",
+                "title": "t",
+                "content": "c",
+                "permissions": {"public": true},
+                "tittle": "typo"
+            }]
+        }));
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,84 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+/// Token bucket per API key: `burst` requests at once, refilled at
+/// `requests_per_minute`.
+pub struct KeyRateLimiter {
+    capacity: f64,
+    refill_per_sec: f64,
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl KeyRateLimiter {
+    pub fn new(requests_per_minute: u32, burst: u32) -> Self {
+        Self {
+            capacity: f64::from(burst.max(1)),
+            refill_per_sec: f64::from(requests_per_minute.max(1)) / 60.0,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Take a token for `key_id`. On refusal, returns how long until the next
+    /// token is available.
+    pub fn check(&self, key_id: &str) -> Result<(), Duration> {
+        self.check_at(key_id, Instant::now())
+    }
+
+    fn check_at(&self, key_id: &str, now: Instant) -> Result<(), Duration> {
+        let mut buckets = self.buckets.lock().unwrap();
+        let bucket = buckets.entry(key_id.to_string()).or_insert(Bucket {
+            tokens: self.capacity,
+            refilled_at: now,
+        });
+
+        let elapsed = now.saturating_duration_since(bucket.refilled_at);
+        bucket.tokens =
+            (bucket.tokens + elapsed.as_secs_f64() * self.refill_per_sec).min(self.capacity);
+        bucket.refilled_at = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64(
+                (1.0 - bucket.tokens) / self.refill_per_sec,
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_burst_then_refill() {
+        let limiter = KeyRateLimiter::new(60, 2);
+        let start = Instant::now();
+
+        assert!(limiter.check_at("k1", start).is_ok());
+        assert!(limiter.check_at("k1", start).is_ok());
+        let wait = limiter.check_at("k1", start).unwrap_err();
+        assert_eq!(wait.as_secs(), 1);
+
+        // Other keys have their own bucket
+        assert!(limiter.check_at("k2", start).is_ok());
+
+        assert!(
+            limiter
+                .check_at("k1", start + Duration::from_secs(1))
+                .is_ok()
+        );
+        assert!(
+            limiter
+                .check_at("k1", start + Duration::from_secs(1))
+                .is_err()
+        );
+    }
+}
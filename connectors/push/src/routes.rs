@@ -0,0 +1,181 @@
+use std::sync::Arc;
+
+use axum::body::Bytes;
+use axum::extract::{DefaultBodyLimit, State};
+use axum::http::{HeaderMap, HeaderValue, StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::{Json, Router};
+use omni_connector_sdk::{PushKeyIdentity, SdkClient};
+use serde::de::DeserializeOwned;
+use serde_json::json;
+use tracing::{error, warn};
+
+use crate::auth::{KeyAuthenticator, bearer_key};
+use crate::config::PushConfig;
+use crate::ingest::{IngestError, Ingestor};
+use crate::models::{FieldError, PushDeletionsRequest, PushDocumentsRequest, PushLimits};
+use crate::rate_limit::KeyRateLimiter;
+
+/// Seconds a caller should wait when the source is busy with another sync.
+const BUSY_RETRY_AFTER_SECS: u64 = 5;
+
+struct PushState {
+    authenticator: KeyAuthenticator,
+    rate_limiter: KeyRateLimiter,
+    ingestor: Ingestor,
+    limits: PushLimits,
+}
+
+/// Public ingestion API, exposed through the reverse proxy under `/push`.
+pub fn build_router(sdk_client: SdkClient, config: &PushConfig) -> Router {
+    let state = Arc::new(PushState {
+        authenticator: KeyAuthenticator::new(sdk_client.clone(), config.key_cache_ttl),
+        rate_limiter: KeyRateLimiter::new(config.requests_per_minute, config.burst),
+        ingestor: Ingestor::new(sdk_client),
+        limits: PushLimits {
+            max_batch_size: config.max_batch_size,
+            max_content_bytes: config.max_content_bytes,
+        },
+    });
+
+    Router::new()
+        .route("/push/documents", post(push_documents))
+        .route("/push/deletions", post(push_deletions))
+        .layer(DefaultBodyLimit::max(config.max_request_bytes))
+        .with_state(state)
+}
+
+fn error_response(status: StatusCode, message: impl Into<String>) -> Response {
+    (status, Json(json!({ "error": message.into() }))).into_response()
+}
+
+fn retry_after_response(status: StatusCode, message: &str, retry_after_secs: u64) -> Response {
+    let mut response = error_response(status, message);
+    response
+        .headers_mut()
+        .insert(header::RETRY_AFTER, HeaderValue::from(retry_after_secs));
+    response
+}
+
+fn validation_response(errors: Vec<FieldError>) -> Response {
+    (
+        StatusCode::UNPROCESSABLE_ENTITY,
+        Json(json!({ "errors": errors })),
+    )
+        .into_response()
+}
+
+/// Authenticate and rate limit a request, yielding the key's source.
+async fn admit(state: &PushState, headers: &HeaderMap) -> Result<PushKeyIdentity, Response> {
+    let Some(key) = bearer_key(headers) else {
+        return Err(error_response(
+            StatusCode::UNAUTHORIZED,
+            "Missing API key; send it as 'Authorization: Bearer <key>'",
+        ));
+    };
+    let identity = match state.authenticator.authenticate(key).await {
+        Ok(Some(identity)) => identity,
+        Ok(None) => return Err(error_response(StatusCode::UNAUTHORIZED, "Invalid API key")),
+        Err(e) => {
+            error!("Failed to authenticate push API key: {}", e);
+            return Err(error_response(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "Authentication is temporarily unavailable",
+            ));
+        }
+    };
+
+    if let Err(wait) = state.rate_limiter.check(&identity.key_id) {
+        warn!(
+            "Rate limited push API key {} (source {})",
+            identity.key_id, identity.source_id
+        );
+        return Err(retry_after_response(
+            StatusCode::TOO_MANY_REQUESTS,
+            "Rate limit exceeded",
+            wait.as_secs_f64().ceil() as u64,
+        ));
+    }
+    Ok(identity)
+}
+
+/// Decode a JSON body, reporting type errors against the offending field.
+fn parse_body<T: DeserializeOwned>(body: &Bytes) -> Result<T, FieldError> {
+    let deserializer = &mut serde_json::Deserializer::from_slice(body);
+    serde_path_to_error::deserialize(deserializer).map_err(|e| {
+        let field = e.path().to_string();
+        FieldError::new(field, e.into_inner().to_string())
+    })
+}
+
+fn ingest_response(result: Result<String, IngestError>, accepted: usize) -> Response {
+    match result {
+        Ok(sync_run_id) => (
+            StatusCode::ACCEPTED,
+            Json(json!({ "sync_run_id": sync_run_id, "accepted": accepted })),
+        )
+            .into_response(),
+        Err(IngestError::Busy(_)) => retry_after_response(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Source is busy, retry shortly",
+            BUSY_RETRY_AFTER_SECS,
+        ),
+        Err(IngestError::Other(e)) => {
+            error!("Failed to ingest pushed batch: {}", e);
+            error_response(StatusCode::INTERNAL_SERVER_ERROR, "Failed to queue batch")
+        }
+    }
+}
+
+async fn push_documents(
+    State(state): State<Arc<PushState>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let identity = match admit(&state, &headers).await {
+        Ok(identity) => identity,
+        Err(response) => return response,
+    };
+    let request: PushDocumentsRequest = match parse_body(&body) {
+        Ok(request) => request,
+        Err(error) => return validation_response(vec![error]),
+    };
+    let errors = request.validate(state.limits);
+    if !errors.is_empty() {
+        return validation_response(errors);
+    }
+
+    let accepted = request.documents.len();
+    let result = state
+        .ingestor
+        .push_documents(&identity.source_id, request.documents)
+        .await;
+    ingest_response(result, accepted)
+}
+
+async fn push_deletions(
+    State(state): State<Arc<PushState>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let identity = match admit(&state, &headers).await {
+        Ok(identity) => identity,
+        Err(response) => return response,
+    };
+    let request: PushDeletionsRequest = match parse_body(&body) {
+        Ok(request) => request,
+        Err(error) => return validation_response(vec![error]),
+    };
+    let errors = request.validate(state.limits);
+    if !errors.is_empty() {
+        return validation_response(errors);
+    }
+
+    let accepted = request.document_ids.len();
+    let result = state
+        .ingestor
+        .push_deletions(&identity.source_id, request.document_ids)
+        .await;
+    ingest_response(result, accepted)
+}
@@ -0,0 +1,218 @@
+//! Tests for the push ingestion API against a mocked connector-manager.
+
+use axum::http::StatusCode;
+use axum_test::TestServer;
+use omni_connector_sdk::SdkClient;
+use omni_push_connector::config::PushConfig;
+use omni_push_connector::routes::build_router;
+use serde_json::{Value, json};
+use wiremock::matchers::{body_json, method, path};
+use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+const KEY: &str = "omni_push_valid";
+
+async fn mock_manager() -> MockServer {
+    let server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/sdk/push-keys/authenticate"))
+        .and(body_json(json!({ "key": KEY })))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(json!({ "key_id": "key-1", "source_id": "src-1" })),
+        )
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/sdk/push-keys/authenticate"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/sdk/sync/create"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "sync_run_id": "run-1" })))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/sdk/content"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "content_id": "c-1" })))
+        .mount(&server)
+        .await;
+    for endpoint in ["events/batch", "sync/run-1/scanned", "sync/run-1/updated"] {
+        Mock::given(method("POST"))
+            .and(path(format!("/sdk/{}", endpoint)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "status": "ok" })))
+            .mount(&server)
+            .await;
+    }
+    Mock::given(method("POST"))
+        .and(path("/sdk/sync/run-1/complete"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "status": "ok" })))
+        .mount(&server)
+        .await;
+
+    server
+}
+
+fn test_server(manager: &MockServer, config: PushConfig) -> TestServer {
+    TestServer::new(build_router(SdkClient::new(&manager.uri()), &config)).unwrap()
+}
+
+fn document(id: &str) -> Value {
+    json!({
+        "id": id,
+        "title": "Database failover",
+        "content": "Promote the replica, then repoint the writers.",
+        "permissions": { "groups": ["sre@example.com"] },
+        "attributes": { "team": "sre" }
+    })
+}
+
+fn emitted_events(requests: &[Request]) -> Vec<Value> {
+    requests
+        .iter()
+        .filter(|r| r.url.path() == "/sdk/events/batch")
+        .flat_map(|r| {
+            let body: Value = serde_json::from_slice(&r.body).unwrap();
+            body["events"].as_array().unwrap().clone()
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_push_documents_emits_events_for_the_keys_source() {
+    let manager = mock_manager().await;
+    let server = test_server(&manager, PushConfig::default());
+
+    let resp = server
+        .post("/push/documents")
+        .authorization_bearer(KEY)
+        .json(&json!({ "documents": [document("runbook-1"), document("runbook-2")] }))
+        .await;
+
+    resp.assert_status(StatusCode::ACCEPTED);
+    let body: Value = resp.json();
+    assert_eq!(body["accepted"], 2);
+    assert_eq!(body["sync_run_id"], "run-1");
+
+    let requests = manager.received_requests().await.unwrap();
+    let events = emitted_events(&requests);
+    assert_eq!(events.len(), 2);
+    let created = &events[0];
+    assert_eq!(created["type"], "document_created");
+    assert_eq!(created["source_id"], "src-1");
+    assert_eq!(created["document_id"], "runbook-1");
+    assert_eq!(created["content_id"], "c-1");
+    assert_eq!(created["metadata"]["title"], "Database failover");
+    assert_eq!(created["permissions"]["groups"][0], "sre@example.com");
+    assert_eq!(created["attributes"]["team"], "sre");
+    assert!(
+        requests
+            .iter()
+            .any(|r| r.url.path() == "/sdk/sync/run-1/complete")
+    );
+}
+
+#[tokio::test]
+async fn test_push_deletions() {
+    let manager = mock_manager().await;
+    let server = test_server(&manager, PushConfig::default());
+
+    let resp = server
+        .post("/push/deletions")
+        .authorization_bearer(KEY)
+        .json(&json!({ "document_ids": ["runbook-1"] }))
+        .await;
+
+    resp.assert_status(StatusCode::ACCEPTED);
+    let events = emitted_events(&manager.received_requests().await.unwrap());
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0]["type"], "document_deleted");
+    assert_eq!(events[0]["document_id"], "runbook-1");
+}
+
+#[tokio::test]
+async fn test_rejects_missing_and_invalid_keys() {
+    let manager = mock_manager().await;
+    let server = test_server(&manager, PushConfig::default());
+    let payload = json!({ "document_ids": ["runbook-1"] });
+
+    server
+        .post("/push/deletions")
+        .json(&payload)
+        .expect_failure()
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+    server
+        .post("/push/deletions")
+        .authorization_bearer("omni_push_revoked")
+        .json(&payload)
+        .expect_failure()
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_invalid_payload_reports_fields_without_queueing() {
+    let manager = mock_manager().await;
+    let server = test_server(&manager, PushConfig::default());
+
+    let mut bad = document("runbook-1");
+    bad["title"] = json!("");
+    let resp = server
+        .post("/push/documents")
+        .authorization_bearer(KEY)
+        .json(&json!({ "documents": [bad] }))
+        .expect_failure()
+        .await;
+    resp.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+    let body: Value = resp.json();
+    assert_eq!(body["errors"][0]["field"], "documents[0].title");
+
+    // Type errors point at the offending field too
+    let mut bad = document("runbook-1");
+    bad["permissions"]["public"] = json!("yes");
+    let resp = server
+        .post("/push/documents")
+        .authorization_bearer(KEY)
+        .json(&json!({ "documents": [bad] }))
+        .expect_failure()
+        .await;
+    resp.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+    let body: Value = resp.json();
+    assert_eq!(
+        body["errors"][0]["field"],
+        "documents[0].permissions.public"
+    );
+
+    let requests = manager.received_requests().await.unwrap();
+    assert!(requests.iter().all(|r| r.url.path() != "/sdk/sync/create"));
+}
+
+#[tokio::test]
+async fn test_rate_limits_per_key() {
+    let manager = mock_manager().await;
+    let config = PushConfig {
+        requests_per_minute: 1,
+        burst: 1,
+        ..PushConfig::default()
+    };
+    let server = test_server(&manager, config);
+    let payload = json!({ "document_ids": ["runbook-1"] });
+
+    server
+        .post("/push/deletions")
+        .authorization_bearer(KEY)
+        .json(&payload)
+        .await
+        .assert_status(StatusCode::ACCEPTED);
+
+    let resp = server
+        .post("/push/deletions")
+        .authorization_bearer(KEY)
+        .json(&payload)
+        .expect_failure()
+        .await;
+    resp.assert_status(StatusCode::TOO_MANY_REQUESTS);
+    assert!(resp.headers().contains_key("retry-after"));
+}
@@ -0,0 +1,159 @@
+use anyhow::{Context, Result, anyhow};
+use aws_config::{BehaviorVersion, Region};
+use aws_sdk_s3::Client;
+use aws_sdk_s3::config::Credentials;
+use aws_sdk_s3::error::DisplayErrorContext;
+use tracing::debug;
+
+use crate::config::S3Config;
+use crate::connector::S3Credentials;
+use crate::models::S3Object;
+
+/// Keys per `ListObjectsV2` page; 1000 is the S3 maximum.
+const LIST_PAGE_SIZE: i32 = 1000;
+
+/// One page of a bucket listing.
+#[derive(Debug, Default)]
+pub struct ObjectPage {
+    pub objects: Vec<S3Object>,
+    /// Whether more keys follow the last one in this page.
+    pub is_truncated: bool,
+}
+
+/// A downloaded object body with the content type S3 stored for it.
+#[derive(Debug)]
+pub struct ObjectBody {
+    pub data: Vec<u8>,
+    pub content_type: Option<String>,
+}
+
+pub struct S3Client {
+    client: Client,
+    bucket: String,
+}
+
+impl S3Client {
+    pub async fn new(config: &S3Config, credentials: &S3Credentials) -> Self {
+        let sdk_config = aws_config::defaults(BehaviorVersion::latest())
+            .region(Region::new(config.region().to_string()))
+            .credentials_provider(Credentials::new(
+                &credentials.access_key_id,
+                &credentials.secret_access_key,
+                credentials.session_token.clone(),
+                None,
+                "omni-s3-connector",
+            ))
+            .load()
+            .await;
+
+        let mut builder = aws_sdk_s3::config::Builder::from(&sdk_config);
+        if let Some(endpoint_url) = config.endpoint_url.as_deref().filter(|e| !e.is_empty()) {
+            builder = builder.endpoint_url(endpoint_url).force_path_style(true);
+        }
+
+        Self {
+            client: Client::from_conf(builder.build()),
+            bucket: config.bucket.clone(),
+        }
+    }
+
+    /// Check that the bucket exists and the credentials can access it.
+    pub async fn validate_access(&self) -> Result<()> {
+        self.client
+            .head_bucket()
+            .bucket(&self.bucket)
+            .send()
+            .await
+            .map_err(|e| {
+                anyhow!(
+                    "Cannot access bucket {}: {}",
+                    self.bucket,
+                    DisplayErrorContext(e)
+                )
+            })?;
+        Ok(())
+    }
+
+    /// List keys under `prefix` in lexicographic order, starting after
+    /// `start_after` when given.
+    pub async fn list_objects(
+        &self,
+        prefix: &str,
+        start_after: Option<&str>,
+    ) -> Result<ObjectPage> {
+        debug!(
+            "Listing s3://{}/{} after {:?}",
+            self.bucket, prefix, start_after
+        );
+
+        let mut request = self
+            .client
+            .list_objects_v2()
+            .bucket(&self.bucket)
+            .max_keys(LIST_PAGE_SIZE);
+        if !prefix.is_empty() {
+            request = request.prefix(prefix);
+        }
+        if let Some(start_after) = start_after {
+            request = request.start_after(start_after);
+        }
+
+        let response = request.send().await.map_err(|e| {
+            anyhow!(
+                "Failed to list s3://{}/{}: {}",
+                self.bucket,
+                prefix,
+                DisplayErrorContext(e)
+            )
+        })?;
+
+        let objects = response
+            .contents()
+            .iter()
+            .filter_map(|object| {
+                Some(S3Object {
+                    key: object.key()?.to_string(),
+                    size: object.size().unwrap_or(0).max(0) as u64,
+                    etag: object
+                        .e_tag()
+                        .map(|etag| etag.trim_matches('"').to_string()),
+                    last_modified: object.last_modified().map(|t| t.secs()),
+                })
+            })
+            .collect();
+
+        Ok(ObjectPage {
+            objects,
+            is_truncated: response.is_truncated().unwrap_or(false),
+        })
+    }
+
+    pub async fn get_object(&self, key: &str) -> Result<ObjectBody> {
+        let response = self
+            .client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .send()
+            .await
+            .map_err(|e| {
+                anyhow!(
+                    "Failed to download s3://{}/{}: {}",
+                    self.bucket,
+                    key,
+                    DisplayErrorContext(e)
+                )
+            })?;
+
+        let content_type = response.content_type().map(str::to_string);
+        let data = response
+            .body
+            .collect()
+            .await
+            .with_context(|| format!("Failed to read s3://{}/{}", self.bucket, key))?
+            .into_bytes()
+            .to_vec();
+
+        Ok(ObjectBody { data, content_type })
+    }
+}
@@ -0,0 +1,180 @@
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+
+const DEFAULT_REGION: &str = "us-east-1";
+
+/// Per-source S3 configuration stored in `Source.config`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct S3Config {
+    /// Bucket to index.
+    pub bucket: String,
+    /// AWS region of the bucket (default: `us-east-1`).
+    #[serde(default)]
+    pub region: Option<String>,
+    /// Custom endpoint for S3-compatible stores (MinIO, Ceph, R2, ...).
+    /// Requests then use path-style addressing.
+    #[serde(default)]
+    pub endpoint_url: Option<String>,
+    /// Key prefixes to index (default: the whole bucket).
+    #[serde(default = "default_prefixes")]
+    pub prefixes: Vec<String>,
+    /// MIME types to include (empty = all). Entries may end in `/*` to match
+    /// a whole family, e.g. `text/*`.
+    #[serde(default)]
+    pub mime_type_allowlist: Vec<String>,
+    /// MIME types to exclude, same syntax as the allowlist.
+    #[serde(default)]
+    pub mime_type_denylist: Vec<String>,
+    /// Maximum object size in bytes to download and index (0 = unlimited).
+    #[serde(default)]
+    pub max_file_size: u64,
+    /// Whether periodic sync is enabled.
+    #[serde(default = "default_true")]
+    pub sync_enabled: bool,
+}
+
+fn default_prefixes() -> Vec<String> {
+    vec![String::new()]
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl S3Config {
+    pub fn from_source_config(config: &serde_json::Value) -> Result<Self> {
+        let config: Self =
+            serde_json::from_value(config.clone()).context("Failed to parse S3 source config")?;
+        if config.bucket.trim().is_empty() {
+            bail!("S3 source config is missing 'bucket'");
+        }
+        Ok(config)
+    }
+
+    pub fn region(&self) -> &str {
+        self.region
+            .as_deref()
+            .filter(|r| !r.is_empty())
+            .unwrap_or(DEFAULT_REGION)
+    }
+
+    /// Prefixes to list, without duplicates. Leading slashes are dropped
+    /// since S3 keys never start with one; an empty list means the whole
+    /// bucket.
+    pub fn normalized_prefixes(&self) -> Vec<String> {
+        let mut prefixes: Vec<String> = Vec::new();
+        for prefix in &self.prefixes {
+            let prefix = prefix.trim_start_matches('/').to_string();
+            if !prefixes.contains(&prefix) {
+                prefixes.push(prefix);
+            }
+        }
+        if prefixes.is_empty() {
+            prefixes.push(String::new());
+        }
+        prefixes
+    }
+
+    /// Whether an object of the given MIME type should be indexed based on
+    /// the MIME filters. The denylist wins over the allowlist.
+    pub fn should_index_mime_type(&self, mime_type: &str) -> bool {
+        if self
+            .mime_type_denylist
+            .iter()
+            .any(|pattern| mime_matches(pattern, mime_type))
+        {
+            return false;
+        }
+        self.mime_type_allowlist.is_empty()
+            || self
+                .mime_type_allowlist
+                .iter()
+                .any(|pattern| mime_matches(pattern, mime_type))
+    }
+}
+
+fn mime_matches(pattern: &str, mime_type: &str) -> bool {
+    let pattern = pattern.trim();
+    match pattern.strip_suffix("/*") {
+        Some(family) => mime_type
+            .split_once('/')
+            .is_some_and(|(t, _)| t.eq_ignore_ascii_case(family)),
+        None => pattern.eq_ignore_ascii_case(mime_type),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn config_with_filters(allow: &[&str], deny: &[&str]) -> S3Config {
+        let mut cfg = S3Config::from_source_config(&json!({ "bucket": "docs" })).unwrap();
+        cfg.mime_type_allowlist = allow.iter().map(|s| s.to_string()).collect();
+        cfg.mime_type_denylist = deny.iter().map(|s| s.to_string()).collect();
+        cfg
+    }
+
+    #[test]
+    fn test_config_from_json_defaults() {
+        let cfg = S3Config::from_source_config(&json!({ "bucket": "docs" })).unwrap();
+        assert_eq!(cfg.bucket, "docs");
+        assert_eq!(cfg.region(), "us-east-1");
+        assert!(cfg.endpoint_url.is_none());
+        assert_eq!(cfg.normalized_prefixes(), vec![String::new()]);
+        assert!(cfg.mime_type_allowlist.is_empty());
+        assert_eq!(cfg.max_file_size, 0);
+        assert!(cfg.sync_enabled);
+    }
+
+    #[test]
+    fn test_config_requires_bucket() {
+        assert!(S3Config::from_source_config(&json!({})).is_err());
+        assert!(S3Config::from_source_config(&json!({ "bucket": " " })).is_err());
+    }
+
+    #[test]
+    fn test_normalized_prefixes() {
+        let cfg = S3Config::from_source_config(&json!({
+            "bucket": "docs",
+            "prefixes": ["/reports/", "reports/", "contracts/2024/"]
+        }))
+        .unwrap();
+        assert_eq!(
+            cfg.normalized_prefixes(),
+            vec!["reports/".to_string(), "contracts/2024/".to_string()]
+        );
+
+        let empty =
+            S3Config::from_source_config(&json!({ "bucket": "docs", "prefixes": [] })).unwrap();
+        assert_eq!(empty.normalized_prefixes(), vec![String::new()]);
+    }
+
+    #[test]
+    fn test_should_index_mime_type_no_filters() {
+        let cfg = config_with_filters(&[], &[]);
+        assert!(cfg.should_index_mime_type("application/pdf"));
+        assert!(cfg.should_index_mime_type("image/png"));
+    }
+
+    #[test]
+    fn test_should_index_mime_type_allowlist_with_wildcard() {
+        let cfg = config_with_filters(&["application/pdf", "text/*"], &[]);
+        assert!(cfg.should_index_mime_type("application/pdf"));
+        assert!(cfg.should_index_mime_type("text/markdown"));
+        assert!(cfg.should_index_mime_type("Text/Plain"));
+        assert!(!cfg.should_index_mime_type("image/png"));
+        assert!(!cfg.should_index_mime_type("application/zip"));
+    }
+
+    #[test]
+    fn test_should_index_mime_type_denylist_beats_allowlist() {
+        let cfg = config_with_filters(&["text/*"], &["text/csv"]);
+        assert!(cfg.should_index_mime_type("text/plain"));
+        assert!(!cfg.should_index_mime_type("text/csv"));
+
+        let deny_family = config_with_filters(&[], &["image/*"]);
+        assert!(!deny_family.should_index_mime_type("image/jpeg"));
+        assert!(deny_family.should_index_mime_type("application/pdf"));
+    }
+}
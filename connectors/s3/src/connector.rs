@@ -0,0 +1,214 @@
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use axum::response::Response;
+use omni_connector_sdk::{
+    ActionDefinition, ActionResponse, Connector, ServiceCredential, Source, SourceType,
+    SyncContext, SyncType,
+};
+use serde::Deserialize;
+use serde_json::{Value as JsonValue, json};
+
+use crate::client::S3Client;
+use crate::config::S3Config;
+use crate::models::{S3ConnectorState, S3Object};
+use crate::sync::run_sync;
+
+#[derive(Debug, Deserialize)]
+pub struct S3Credentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    #[serde(default)]
+    pub session_token: Option<String>,
+}
+
+impl S3Credentials {
+    fn from_service_credential(credentials: Option<ServiceCredential>) -> Result<Self> {
+        let creds = credentials.ok_or_else(|| anyhow!("S3 credentials are required"))?;
+        Ok(serde_json::from_value(creds.credentials)?)
+    }
+}
+
+pub struct S3Connector;
+
+impl S3Connector {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for S3Connector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Connector for S3Connector {
+    type Config = S3Config;
+    type Credentials = S3Credentials;
+    type State = S3ConnectorState;
+
+    fn name(&self) -> &'static str {
+        "s3"
+    }
+
+    fn version(&self) -> &'static str {
+        env!("CARGO_PKG_VERSION")
+    }
+
+    fn display_name(&self) -> String {
+        "Amazon S3".to_string()
+    }
+
+    fn description(&self) -> Option<String> {
+        Some("Index files from Amazon S3 or S3-compatible object storage".to_string())
+    }
+
+    fn source_types(&self) -> Vec<SourceType> {
+        vec![SourceType::S3]
+    }
+
+    fn sync_modes(&self) -> Vec<SyncType> {
+        vec![SyncType::Full, SyncType::Incremental]
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    fn actions(&self) -> Vec<ActionDefinition> {
+        vec![
+            ActionDefinition {
+                name: "validate_credentials".into(),
+                description: "Verify that the provided credentials can access the bucket".into(),
+                input_schema: json!({}),
+                mode: omni_connector_sdk::ActionMode::Read,
+                source_types: Vec::new(),
+                admin_only: false,
+                hidden: false,
+            },
+            ActionDefinition {
+                name: "fetch_file".into(),
+                description: "Download an object from the bucket by its document ID".into(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "file_id": {
+                            "type": "string",
+                            "description": "External file ID (s3:{source_id}:{key})"
+                        }
+                    },
+                    "required": ["file_id"]
+                }),
+                mode: omni_connector_sdk::ActionMode::Read,
+                source_types: Vec::new(),
+                // Internal action for the read_document binary fetch, same as
+                // Nextcloud: hidden from chat tools and routed to the org
+                // credential, the only kind an S3 source has.
+                hidden: true,
+                admin_only: true,
+            },
+        ]
+    }
+
+    async fn sync(
+        &self,
+        source: Source,
+        credentials: Option<ServiceCredential>,
+        state: Option<Self::State>,
+        ctx: SyncContext,
+    ) -> Result<()> {
+        let config = S3Config::from_source_config(&source.config)?;
+        let creds = S3Credentials::from_service_credential(credentials)?;
+        run_sync(config, creds, state, ctx).await
+    }
+
+    async fn cancel(&self, _sync_run_id: &str) -> bool {
+        // SDK owns the cancellation flag (exposed via SyncContext); just ack.
+        true
+    }
+
+    async fn execute_action(
+        &self,
+        action: &str,
+        params: JsonValue,
+        credentials: Option<ServiceCredential>,
+    ) -> Result<Response> {
+        match action {
+            "validate_credentials" => {
+                let config = S3Config::from_source_config(&params)?;
+                let creds = S3Credentials::from_service_credential(credentials)?;
+                let client = S3Client::new(&config, &creds).await;
+                let authenticated = client.validate_access().await.is_ok();
+                Ok(
+                    ActionResponse::success(json!({ "authenticated": authenticated }))
+                        .into_response(),
+                )
+            }
+            "fetch_file" => {
+                // Source config (bucket etc.) is merged into params by the
+                // connector-manager before dispatch.
+                let config = S3Config::from_source_config(&params)?;
+                let creds = S3Credentials::from_service_credential(credentials)?;
+
+                let file_id = params
+                    .get("file_id")
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| anyhow!("Missing required parameter: file_id"))?;
+                let key = parse_file_id(file_id)?;
+
+                let client = S3Client::new(&config, &creds).await;
+                let body = client.get_object(&key).await?;
+
+                let object = S3Object {
+                    key,
+                    ..Default::default()
+                };
+                let content_type = body
+                    .content_type
+                    .unwrap_or_else(|| object.guessed_mime_type());
+                let encoded_name = urlencoding::encode(object.filename()).into_owned();
+
+                Response::builder()
+                    .status(200)
+                    .header("Content-Type", content_type)
+                    .header("Content-Length", body.data.len())
+                    .header("X-File-Name", encoded_name)
+                    .body(axum::body::Body::from(body.data))
+                    .map_err(|e| anyhow!("Failed to build response: {}", e))
+            }
+            other => Err(anyhow!("Action not supported: {}", other)),
+        }
+    }
+}
+
+/// Extract the object key from an external ID of the form
+/// `s3:{source_id}:{url_encoded_key}`.
+fn parse_file_id(file_id: &str) -> Result<String> {
+    let parts: Vec<&str> = file_id.splitn(3, ':').collect();
+    if parts.len() < 3 || parts[0] != "s3" || parts[2].is_empty() {
+        anyhow::bail!("Invalid file_id format: {}", file_id);
+    }
+    Ok(urlencoding::decode(parts[2])
+        .map_err(|e| anyhow!("Failed to URL-decode object key: {}", e))?
+        .into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::document_id;
+
+    #[test]
+    fn test_parse_file_id_round_trip() {
+        let key = "reports/2024/Q1 summary.pdf";
+        assert_eq!(parse_file_id(&document_id("src-1", key)).unwrap(), key);
+    }
+
+    #[test]
+    fn test_parse_file_id_invalid() {
+        assert!(parse_file_id("nextcloud:src-1:abc").is_err());
+        assert!(parse_file_id("s3:src-1").is_err());
+        assert!(parse_file_id("s3:src-1:").is_err());
+    }
+}
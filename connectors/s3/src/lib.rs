@@ -0,0 +1,5 @@
+pub mod client;
+pub mod config;
+pub mod connector;
+pub mod models;
+pub mod sync;
@@ -0,0 +1,17 @@
+use anyhow::Result;
+use dotenvy::dotenv;
+use omni_connector_sdk::telemetry::{self, TelemetryConfig};
+use omni_connector_sdk::{ServerConfig, serve_with_config};
+use omni_s3_connector::connector::S3Connector;
+use tracing::info;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenv().ok();
+
+    telemetry::init_telemetry(TelemetryConfig::from_env("omni-s3-connector"))?;
+
+    info!("Starting S3 Connector");
+
+    serve_with_config(S3Connector::new(), ServerConfig::from_env()?).await
+}
@@ -0,0 +1,260 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// An object from a `ListObjectsV2` page.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct S3Object {
+    pub key: String,
+    pub size: u64,
+    /// ETag without the surrounding quotes.
+    pub etag: Option<String>,
+    /// Last modified time as a unix timestamp.
+    pub last_modified: Option<i64>,
+}
+
+impl S3Object {
+    /// Last path segment of the key.
+    pub fn filename(&self) -> &str {
+        self.key.rsplit('/').next().unwrap_or(&self.key)
+    }
+
+    /// Zero-byte keys ending in `/` are folder placeholders created by the
+    /// S3 console and other tools, not files.
+    pub fn is_folder_marker(&self) -> bool {
+        self.key.ends_with('/')
+    }
+
+    /// MIME type guessed from the key's extension. `ListObjectsV2` does not
+    /// return content types, and a `HeadObject` per key would double the
+    /// request count.
+    pub fn guessed_mime_type(&self) -> String {
+        mime_guess::from_path(self.filename())
+            .first_raw()
+            .unwrap_or("application/octet-stream")
+            .to_string()
+    }
+
+    /// Build the document ID used within Omni. Deterministic and stable.
+    pub fn document_id(&self, source_id: &str) -> String {
+        document_id(source_id, &self.key)
+    }
+
+    /// Link to the object: the AWS console for AWS buckets, the path-style
+    /// object URL for custom endpoints.
+    pub fn web_url(&self, bucket: &str, region: &str, endpoint_url: Option<&str>) -> String {
+        match endpoint_url {
+            Some(endpoint) => format!(
+                "{}/{}/{}",
+                endpoint.trim_end_matches('/'),
+                bucket,
+                encode_key(&self.key)
+            ),
+            None => format!(
+                "https://s3.console.aws.amazon.com/s3/object/{}?region={}&prefix={}",
+                bucket,
+                region,
+                urlencoding::encode(&self.key)
+            ),
+        }
+    }
+
+    pub fn version(&self) -> ObjectVersion {
+        ObjectVersion {
+            etag: self.etag.clone(),
+            last_modified: self.last_modified,
+        }
+    }
+}
+
+pub fn document_id(source_id: &str, key: &str) -> String {
+    format!("s3:{}:{}", source_id, urlencoding::encode(key))
+}
+
+/// Percent-encode each path segment of a key, keeping the slashes.
+fn encode_key(key: &str) -> String {
+    key.split('/')
+        .map(|segment| urlencoding::encode(segment).into_owned())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// What an object looked like when it was last processed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ObjectVersion {
+    pub etag: Option<String>,
+    pub last_modified: Option<i64>,
+}
+
+impl ObjectVersion {
+    /// An object changed if either its ETag or its LastModified differs.
+    /// With neither available it is always treated as changed.
+    pub fn matches(&self, other: &ObjectVersion) -> bool {
+        (self.etag.is_some() || self.last_modified.is_some()) && self == other
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrackedObject {
+    #[serde(flatten)]
+    pub version: ObjectVersion,
+    /// False when no text could be extracted; the object has no document.
+    pub indexed: bool,
+    /// Sync run that last saw the object in a listing. Objects not seen by
+    /// a completed run were deleted from the bucket.
+    pub seen_in: String,
+}
+
+/// Listing position of an in-progress run, so a resumed run continues
+/// where it stopped instead of listing the bucket again.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ListCursor {
+    pub sync_run_id: String,
+    pub prefix: String,
+    /// Last key fully processed under `prefix`.
+    pub start_after: String,
+}
+
+/// Connector state persisted across sync runs.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct S3ConnectorState {
+    /// Known objects by key.
+    #[serde(default)]
+    pub objects: HashMap<String, TrackedObject>,
+    #[serde(default)]
+    pub cursor: Option<ListCursor>,
+}
+
+impl S3ConnectorState {
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::to_value(self).unwrap_or_default()
+    }
+
+    /// The cursor to continue from, if it belongs to this sync run.
+    pub fn cursor_for_run(&self, sync_run_id: &str) -> Option<&ListCursor> {
+        self.cursor
+            .as_ref()
+            .filter(|cursor| cursor.sync_run_id == sync_run_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_object() -> S3Object {
+        S3Object {
+            key: "reports/2024/Q1 summary.pdf".to_string(),
+            size: 2048,
+            etag: Some("abc123".to_string()),
+            last_modified: Some(1_704_067_200),
+        }
+    }
+
+    #[test]
+    fn test_filename() {
+        assert_eq!(sample_object().filename(), "Q1 summary.pdf");
+        let root = S3Object {
+            key: "readme.txt".into(),
+            ..Default::default()
+        };
+        assert_eq!(root.filename(), "readme.txt");
+    }
+
+    #[test]
+    fn test_folder_marker() {
+        let marker = S3Object {
+            key: "reports/".into(),
+            ..Default::default()
+        };
+        assert!(marker.is_folder_marker());
+        assert!(!sample_object().is_folder_marker());
+    }
+
+    #[test]
+    fn test_guessed_mime_type() {
+        assert_eq!(sample_object().guessed_mime_type(), "application/pdf");
+        let unknown = S3Object {
+            key: "data/blob".into(),
+            ..Default::default()
+        };
+        assert_eq!(unknown.guessed_mime_type(), "application/octet-stream");
+    }
+
+    #[test]
+    fn test_document_id_deterministic() {
+        let id = sample_object().document_id("src-1");
+        assert_eq!(id, sample_object().document_id("src-1"));
+        assert_eq!(id, "s3:src-1:reports%2F2024%2FQ1%20summary.pdf");
+    }
+
+    #[test]
+    fn test_web_url_aws_console() {
+        assert_eq!(
+            sample_object().web_url("docs", "eu-west-1", None),
+            "https://s3.console.aws.amazon.com/s3/object/docs?region=eu-west-1&prefix=reports%2F2024%2FQ1%20summary.pdf"
+        );
+    }
+
+    #[test]
+    fn test_web_url_custom_endpoint() {
+        assert_eq!(
+            sample_object().web_url("docs", "us-east-1", Some("http://minio:9000/")),
+            "http://minio:9000/docs/reports/2024/Q1%20summary.pdf"
+        );
+    }
+
+    #[test]
+    fn test_version_matches() {
+        let version = sample_object().version();
+        assert!(version.matches(&sample_object().version()));
+
+        let mut touched = sample_object();
+        touched.last_modified = Some(1_704_067_201);
+        assert!(!version.matches(&touched.version()));
+
+        let mut rewritten = sample_object();
+        rewritten.etag = Some("def456".into());
+        assert!(!version.matches(&rewritten.version()));
+
+        let unknown = ObjectVersion::default();
+        assert!(!unknown.matches(&ObjectVersion::default()));
+    }
+
+    #[test]
+    fn test_connector_state_round_trip() {
+        let mut state = S3ConnectorState::default();
+        state.objects.insert(
+            "a.pdf".into(),
+            TrackedObject {
+                version: sample_object().version(),
+                indexed: true,
+                seen_in: "run-1".into(),
+            },
+        );
+        state.cursor = Some(ListCursor {
+            sync_run_id: "run-1".into(),
+            prefix: "reports/".into(),
+            start_after: "reports/a.pdf".into(),
+        });
+
+        let restored: S3ConnectorState = serde_json::from_value(state.to_json()).unwrap();
+        let tracked = restored.objects.get("a.pdf").unwrap();
+        assert_eq!(tracked.version, sample_object().version());
+        assert!(tracked.indexed);
+        assert_eq!(restored.cursor, state.cursor);
+    }
+
+    #[test]
+    fn test_cursor_for_run() {
+        let state = S3ConnectorState {
+            cursor: Some(ListCursor {
+                sync_run_id: "run-1".into(),
+                prefix: String::new(),
+                start_after: "b.pdf".into(),
+            }),
+            ..Default::default()
+        };
+        assert!(state.cursor_for_run("run-1").is_some());
+        assert!(state.cursor_for_run("run-2").is_none());
+    }
+}
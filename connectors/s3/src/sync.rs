@@ -0,0 +1,508 @@
+use anyhow::Result;
+use omni_connector_sdk::{
+    ConnectorEvent, DocumentMetadata, DocumentPermissions, SyncContext, SyncType,
+};
+use std::collections::HashMap;
+use tracing::{error, info, warn};
+
+use crate::client::S3Client;
+use crate::config::S3Config;
+use crate::connector::S3Credentials;
+use crate::models::{ListCursor, S3ConnectorState, S3Object, TrackedObject, document_id};
+
+#[derive(Debug, Default)]
+struct SyncStats {
+    scanned: usize,
+    processed: usize,
+}
+
+pub async fn run_sync(
+    config: S3Config,
+    credentials: S3Credentials,
+    state: Option<S3ConnectorState>,
+    ctx: SyncContext,
+) -> Result<()> {
+    let sync_run_id = ctx.sync_run_id().to_string();
+    let source_id = ctx.source_id().to_string();
+
+    info!(
+        "Starting S3 sync for source: {} (bucket: {}, sync_run_id: {})",
+        source_id, config.bucket, sync_run_id
+    );
+
+    if !config.sync_enabled {
+        info!("Sync disabled for source {}, skipping", source_id);
+        ctx.complete().await?;
+        return Ok(());
+    }
+
+    // Full sync keeps the known objects, so objects removed from the bucket
+    // are still detected, but re-indexes everything regardless of ETags.
+    let mut state = state.unwrap_or_default();
+    let force = ctx.sync_mode() == SyncType::Full;
+
+    let client = S3Client::new(&config, &credentials).await;
+    client.validate_access().await?;
+
+    let user_email = ctx.get_user_email_for_source().await.ok();
+
+    let result = execute_sync(
+        &client,
+        &config,
+        &ctx,
+        user_email.as_deref(),
+        force,
+        &mut state,
+    )
+    .await;
+
+    if ctx.is_cancelled() {
+        info!("S3 sync {} was cancelled", sync_run_id);
+        let _ = ctx.save_checkpoint(state.to_json()).await;
+        let _ = ctx.cancel().await;
+        return Ok(());
+    }
+
+    match result {
+        Ok(stats) => {
+            info!(
+                "S3 sync completed for source {}: {} scanned, {} processed",
+                source_id, stats.scanned, stats.processed
+            );
+            ctx.save_checkpoint(state.to_json()).await?;
+            ctx.complete().await?;
+            Ok(())
+        }
+        Err(e) => {
+            let _ = ctx.save_checkpoint(state.to_json()).await;
+            error!("S3 sync failed for source {}: {}", source_id, e);
+            Err(e)
+        }
+    }
+}
+
+async fn execute_sync(
+    client: &S3Client,
+    config: &S3Config,
+    ctx: &SyncContext,
+    user_email: Option<&str>,
+    force: bool,
+    state: &mut S3ConnectorState,
+) -> Result<SyncStats> {
+    let sync_run_id = ctx.sync_run_id().to_string();
+    let prefixes = config.normalized_prefixes();
+    let mut stats = SyncStats::default();
+
+    // A resumed run continues after the last page it checkpointed. If the
+    // cursor's prefix is no longer configured, list everything again.
+    let resume = state.cursor_for_run(&sync_run_id).cloned();
+    let first_prefix = resume
+        .as_ref()
+        .and_then(|cursor| prefixes.iter().position(|p| *p == cursor.prefix))
+        .unwrap_or(0);
+    if let Some(cursor) = &resume {
+        info!(
+            "Resuming listing of prefix '{}' after '{}'",
+            cursor.prefix, cursor.start_after
+        );
+    }
+
+    for (index, prefix) in prefixes.iter().enumerate().skip(first_prefix) {
+        let mut start_after = resume
+            .as_ref()
+            .filter(|cursor| index == first_prefix && cursor.prefix == *prefix)
+            .map(|cursor| cursor.start_after.clone());
+
+        loop {
+            if ctx.is_cancelled() {
+                return Ok(stats);
+            }
+
+            let page = client.list_objects(prefix, start_after.as_deref()).await?;
+            let Some(last_key) = page.objects.last().map(|o| o.key.clone()) else {
+                break;
+            };
+
+            let completed = process_page(
+                &page.objects,
+                client,
+                config,
+                ctx,
+                user_email,
+                force,
+                state,
+                &mut stats,
+            )
+            .await;
+            if !completed {
+                return Ok(stats);
+            }
+
+            // Events for the page are flushed before the checkpoint is saved,
+            // so a resumed run never skips an object that was not emitted.
+            state.cursor = Some(ListCursor {
+                sync_run_id: sync_run_id.clone(),
+                prefix: prefix.clone(),
+                start_after: last_key.clone(),
+            });
+            ctx.save_checkpoint(state.to_json()).await?;
+
+            if !page.is_truncated {
+                break;
+            }
+            start_after = Some(last_key);
+        }
+    }
+
+    emit_deletions(ctx, state).await;
+    state.cursor = None;
+
+    Ok(stats)
+}
+
+/// Process one listing page. Returns false if the sync was cancelled midway.
+#[allow(clippy::too_many_arguments)]
+async fn process_page(
+    objects: &[S3Object],
+    client: &S3Client,
+    config: &S3Config,
+    ctx: &SyncContext,
+    user_email: Option<&str>,
+    force: bool,
+    state: &mut S3ConnectorState,
+    stats: &mut SyncStats,
+) -> bool {
+    let sync_run_id = ctx.sync_run_id();
+
+    for object in objects {
+        if ctx.is_cancelled() {
+            return false;
+        }
+        if object.is_folder_marker() {
+            continue;
+        }
+        stats.scanned += 1;
+
+        // Objects filtered out here are not marked as seen, so documents
+        // indexed before a filter change are deleted at the end of the run.
+        let guessed_mime = object.guessed_mime_type();
+        if !config.should_index_mime_type(&guessed_mime) {
+            continue;
+        }
+        if config.max_file_size > 0 && object.size > config.max_file_size {
+            warn!(
+                "Skipping '{}': size {} exceeds limit {}",
+                object.key, object.size, config.max_file_size
+            );
+            continue;
+        }
+
+        let version = object.version();
+        let previous = state.objects.get_mut(&object.key);
+        // Already processed by this run: a resumed run or overlapping prefixes.
+        let done_this_run = previous
+            .as_ref()
+            .is_some_and(|p| p.seen_in == sync_run_id && p.version.matches(&version));
+        let unchanged = previous
+            .as_ref()
+            .is_some_and(|p| !force && p.version.matches(&version));
+        if done_this_run || unchanged {
+            if let Some(previous) = previous {
+                previous.seen_in = sync_run_id.to_string();
+            }
+            continue;
+        }
+        let was_indexed = previous.as_ref().is_some_and(|p| p.indexed);
+
+        let text = match download_and_extract(client, object, &guessed_mime, ctx).await {
+            Ok(text) => text,
+            Err(e) => {
+                // Transient failure: keep the previous version so the object
+                // is retried next sync and its document is not deleted.
+                warn!("Failed to process '{}': {}", object.key, e);
+                if let Some(previous) = state.objects.get_mut(&object.key) {
+                    previous.seen_in = sync_run_id.to_string();
+                }
+                continue;
+            }
+        };
+
+        if text.trim().is_empty() {
+            warn!(
+                "Skipping '{}': unsupported format, no text content could be extracted",
+                object.key
+            );
+            if was_indexed {
+                emit_deleted(ctx, &object.key).await;
+            }
+            state.objects.insert(
+                object.key.clone(),
+                TrackedObject {
+                    version,
+                    indexed: false,
+                    seen_in: sync_run_id.to_string(),
+                },
+            );
+            continue;
+        }
+
+        let content_id = match ctx.store_content(&text).await {
+            Ok(id) => id,
+            Err(e) => {
+                warn!("Failed to store content for '{}': {}", object.key, e);
+                continue;
+            }
+        };
+
+        let event = build_object_event(
+            object,
+            config,
+            sync_run_id,
+            ctx.source_id(),
+            &content_id,
+            &guessed_mime,
+            user_email,
+            was_indexed,
+        );
+        if let Err(e) = ctx.emit_event(event).await {
+            warn!("Failed to emit event for '{}': {}", object.key, e);
+            continue;
+        }
+
+        state.objects.insert(
+            object.key.clone(),
+            TrackedObject {
+                version,
+                indexed: true,
+                seen_in: sync_run_id.to_string(),
+            },
+        );
+        stats.processed += 1;
+    }
+
+    let _ = ctx.increment_scanned(objects.len() as i32).await;
+    true
+}
+
+/// Emit deletions for objects no longer listed by this run and forget them.
+async fn emit_deletions(ctx: &SyncContext, state: &mut S3ConnectorState) {
+    let sync_run_id = ctx.sync_run_id();
+    let gone: Vec<String> = state
+        .objects
+        .iter()
+        .filter(|(_, tracked)| tracked.seen_in != sync_run_id)
+        .map(|(key, _)| key.clone())
+        .collect();
+
+    if !gone.is_empty() {
+        info!("{} objects were removed from the bucket", gone.len());
+    }
+    for key in gone {
+        if let Some(tracked) = state.objects.remove(&key)
+            && tracked.indexed
+        {
+            emit_deleted(ctx, &key).await;
+        }
+    }
+}
+
+async fn emit_deleted(ctx: &SyncContext, key: &str) {
+    let event = ConnectorEvent::DocumentDeleted {
+        sync_run_id: ctx.sync_run_id().to_string(),
+        source_id: ctx.source_id().to_string(),
+        document_id: document_id(ctx.source_id(), key),
+    };
+    if let Err(e) = ctx.emit_event(event).await {
+        warn!("Failed to emit deletion event for '{}': {}", key, e);
+    }
+}
+
+/// Download an object and extract its text via the connector manager.
+/// Unsupported formats yield an empty string.
+async fn download_and_extract(
+    client: &S3Client,
+    object: &S3Object,
+    guessed_mime: &str,
+    ctx: &SyncContext,
+) -> Result<String> {
+    let body = client.get_object(&object.key).await?;
+
+    // Prefer the stored Content-Type unless it is the generic default most
+    // upload tools fall back to.
+    let mime = body
+        .content_type
+        .as_deref()
+        .filter(|ct| {
+            !ct.is_empty() && *ct != "application/octet-stream" && *ct != "binary/octet-stream"
+        })
+        .unwrap_or(guessed_mime);
+
+    let text = ctx
+        .sdk_client()
+        .extract_text(ctx.sync_run_id(), body.data, mime, Some(object.filename()))
+        .await?;
+
+    Ok(text)
+}
+
+/// Build a ConnectorEvent for an object.
+#[allow(clippy::too_many_arguments)]
+pub fn build_object_event(
+    object: &S3Object,
+    config: &S3Config,
+    sync_run_id: &str,
+    source_id: &str,
+    content_id: &str,
+    mime_type: &str,
+    user_email: Option<&str>,
+    is_update: bool,
+) -> ConnectorEvent {
+    let doc_id = object.document_id(source_id);
+    let filename = object.filename().to_string();
+    let web_url = object.web_url(
+        &config.bucket,
+        config.region(),
+        config.endpoint_url.as_deref().filter(|e| !e.is_empty()),
+    );
+
+    let updated_at = object
+        .last_modified
+        .and_then(|secs| time::OffsetDateTime::from_unix_timestamp(secs).ok());
+
+    let mut extra = HashMap::new();
+    extra.insert("bucket".to_string(), serde_json::json!(config.bucket));
+    if let Some(ref etag) = object.etag {
+        extra.insert("etag".to_string(), serde_json::json!(etag));
+    }
+
+    let metadata = DocumentMetadata {
+        title: Some(filename.clone()),
+        author: None,
+        created_at: None,
+        updated_at,
+        content_type: Some(mime_type.to_string()),
+        mime_type: Some(mime_type.to_string()),
+        size: Some(object.size.to_string()),
+        url: Some(web_url),
+        path: Some(object.key.clone()),
+        extra: Some(extra),
+    };
+
+    let permissions = DocumentPermissions {
+        public: false,
+        users: user_email.map(|e| vec![e.to_string()]).unwrap_or_default(),
+        groups: vec![],
+    };
+
+    let mut attributes = HashMap::new();
+    attributes.insert("bucket".to_string(), serde_json::json!(config.bucket));
+    attributes.insert("path".to_string(), serde_json::json!(object.key));
+    if let Some((_, ext)) = filename.rsplit_once('.')
+        && !ext.is_empty()
+    {
+        attributes.insert(
+            "file_extension".to_string(),
+            serde_json::json!(ext.to_lowercase()),
+        );
+    }
+
+    if is_update {
+        ConnectorEvent::DocumentUpdated {
+            sync_run_id: sync_run_id.to_string(),
+            source_id: source_id.to_string(),
+            document_id: doc_id,
+            content_id: content_id.to_string(),
+            metadata,
+            permissions: Some(permissions),
+            attributes: Some(attributes),
+        }
+    } else {
+        ConnectorEvent::DocumentCreated {
+            sync_run_id: sync_run_id.to_string(),
+            source_id: source_id.to_string(),
+            document_id: doc_id,
+            content_id: content_id.to_string(),
+            metadata,
+            permissions,
+            attributes: Some(attributes),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn config() -> S3Config {
+        S3Config::from_source_config(&json!({ "bucket": "docs", "region": "eu-west-1" })).unwrap()
+    }
+
+    fn object() -> S3Object {
+        S3Object {
+            key: "reports/Q1.PDF".into(),
+            size: 1024,
+            etag: Some("abc".into()),
+            last_modified: Some(1_710_513_000),
+        }
+    }
+
+    #[test]
+    fn test_build_object_event_created() {
+        let event = build_object_event(
+            &object(),
+            &config(),
+            "run-1",
+            "src-1",
+            "cnt-1",
+            "application/pdf",
+            Some("alice@example.com"),
+            false,
+        );
+
+        match event {
+            ConnectorEvent::DocumentCreated {
+                document_id,
+                metadata,
+                permissions,
+                attributes,
+                ..
+            } => {
+                assert_eq!(document_id, "s3:src-1:reports%2FQ1.PDF");
+                assert_eq!(metadata.title.as_deref(), Some("Q1.PDF"));
+                assert_eq!(metadata.mime_type.as_deref(), Some("application/pdf"));
+                assert_eq!(metadata.size.as_deref(), Some("1024"));
+                assert_eq!(metadata.path.as_deref(), Some("reports/Q1.PDF"));
+                assert_eq!(metadata.updated_at.unwrap().year(), 2024);
+                assert!(metadata.url.unwrap().contains("region=eu-west-1"));
+                assert_eq!(permissions.users, vec!["alice@example.com"]);
+                assert!(!permissions.public);
+                let attrs = attributes.unwrap();
+                assert_eq!(attrs.get("file_extension").unwrap(), &json!("pdf"));
+                assert_eq!(attrs.get("bucket").unwrap(), &json!("docs"));
+            }
+            _ => panic!("Expected DocumentCreated"),
+        }
+    }
+
+    #[test]
+    fn test_build_object_event_updated() {
+        let event = build_object_event(
+            &object(),
+            &config(),
+            "run-2",
+            "src-1",
+            "cnt-2",
+            "application/pdf",
+            None,
+            true,
+        );
+
+        match event {
+            ConnectorEvent::DocumentUpdated { permissions, .. } => {
+                assert!(permissions.unwrap().users.is_empty());
+            }
+            _ => panic!("Expected DocumentUpdated"),
+        }
+    }
+}
@@ -0,0 +1,261 @@
+//! Integration tests for the S3 connector.
+//!
+//! These run the S3Client against a mocked S3-compatible endpoint (the same
+//! path-style addressing MinIO uses) and check listing, pagination, download
+//! and event creation together as they would run in a sync.
+
+use omni_connector_sdk::ConnectorEvent;
+use omni_s3_connector::client::S3Client;
+use omni_s3_connector::config::S3Config;
+use omni_s3_connector::connector::S3Credentials;
+use omni_s3_connector::sync::build_object_event;
+use serde_json::json;
+use wiremock::matchers::{method, path, path_regex, query_param, query_param_is_missing};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+// ---------------------------------------------------------------------------
+// Helper builders
+// ---------------------------------------------------------------------------
+
+/// Bucket-level requests are sent to `/docs/` with path-style addressing.
+const BUCKET_PATH: &str = r"^/docs/?$";
+
+fn make_config(endpoint_url: &str, prefixes: &[&str]) -> S3Config {
+    S3Config::from_source_config(&json!({
+        "bucket": "docs",
+        "region": "us-east-1",
+        "endpoint_url": endpoint_url,
+        "prefixes": prefixes,
+    }))
+    .unwrap()
+}
+
+fn credentials() -> S3Credentials {
+    S3Credentials {
+        access_key_id: "AKIDEXAMPLE".to_string(),
+        secret_access_key: "secret".to_string(),
+        session_token: None,
+    }
+}
+
+fn contents_xml(key: &str, etag: &str, size: u64) -> String {
+    format!(
+        r#"<Contents>
+    <Key>{key}</Key>
+    <LastModified>2024-03-15T14:30:00.000Z</LastModified>
+    <ETag>&quot;{etag}&quot;</ETag>
+    <Size>{size}</Size>
+    <StorageClass>STANDARD</StorageClass>
+  </Contents>"#
+    )
+}
+
+fn list_xml(prefix: &str, truncated: bool, contents: &[String]) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<ListBucketResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+  <Name>docs</Name>
+  <Prefix>{prefix}</Prefix>
+  <KeyCount>{count}</KeyCount>
+  <MaxKeys>1000</MaxKeys>
+  <IsTruncated>{truncated}</IsTruncated>
+  {contents}
+</ListBucketResult>"#,
+        count = contents.len(),
+        contents = contents.join("\n  "),
+    )
+}
+
+// ---------------------------------------------------------------------------
+// S3 client integration (wiremock)
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn test_list_objects_parses_page() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path_regex(BUCKET_PATH))
+        .and(query_param("list-type", "2"))
+        .and(query_param("prefix", "reports/"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(list_xml(
+            "reports/",
+            false,
+            &[
+                contents_xml("reports/", "d41d8cd98f00b204e9800998ecf8427e", 0),
+                contents_xml("reports/Q1.pdf", "etag-q1", 51200),
+            ],
+        )))
+        .mount(&server)
+        .await;
+
+    let config = make_config(&server.uri(), &["reports/"]);
+    let client = S3Client::new(&config, &credentials()).await;
+    let page = client.list_objects("reports/", None).await.unwrap();
+
+    assert!(!page.is_truncated);
+    assert_eq!(page.objects.len(), 2);
+    assert!(page.objects[0].is_folder_marker());
+
+    let report = &page.objects[1];
+    assert_eq!(report.key, "reports/Q1.pdf");
+    assert_eq!(report.size, 51200);
+    assert_eq!(report.etag.as_deref(), Some("etag-q1"));
+    assert_eq!(report.last_modified, Some(1_710_513_000));
+}
+
+#[tokio::test]
+async fn test_list_objects_continues_after_last_key() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path_regex(BUCKET_PATH))
+        .and(query_param("list-type", "2"))
+        .and(query_param_is_missing("start-after"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(list_xml(
+            "",
+            true,
+            &[contents_xml("a.pdf", "etag-a", 10)],
+        )))
+        .mount(&server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path_regex(BUCKET_PATH))
+        .and(query_param("list-type", "2"))
+        .and(query_param("start-after", "a.pdf"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(list_xml(
+            "",
+            false,
+            &[contents_xml("b.pdf", "etag-b", 20)],
+        )))
+        .mount(&server)
+        .await;
+
+    let config = make_config(&server.uri(), &[]);
+    let client = S3Client::new(&config, &credentials()).await;
+
+    let first = client.list_objects("", None).await.unwrap();
+    assert!(first.is_truncated);
+    assert_eq!(first.objects[0].key, "a.pdf");
+
+    let second = client.list_objects("", Some("a.pdf")).await.unwrap();
+    assert!(!second.is_truncated);
+    assert_eq!(second.objects[0].key, "b.pdf");
+}
+
+#[tokio::test]
+async fn test_get_object_returns_body_and_content_type() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/docs/notes/hello.txt"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("Content-Type", "text/plain")
+                .insert_header("ETag", "\"etag-hello\"")
+                .set_body_string("Hello, S3!"),
+        )
+        .mount(&server)
+        .await;
+
+    let config = make_config(&server.uri(), &[]);
+    let client = S3Client::new(&config, &credentials()).await;
+    let body = client.get_object("notes/hello.txt").await.unwrap();
+
+    assert_eq!(String::from_utf8(body.data).unwrap(), "Hello, S3!");
+    assert_eq!(body.content_type.as_deref(), Some("text/plain"));
+}
+
+#[tokio::test]
+async fn test_validate_access() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("HEAD"))
+        .and(path_regex(BUCKET_PATH))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+
+    let config = make_config(&server.uri(), &[]);
+    let client = S3Client::new(&config, &credentials()).await;
+    assert!(client.validate_access().await.is_ok());
+}
+
+#[tokio::test]
+async fn test_validate_access_denied() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("HEAD"))
+        .and(path_regex(BUCKET_PATH))
+        .respond_with(ResponseTemplate::new(403))
+        .mount(&server)
+        .await;
+
+    let config = make_config(&server.uri(), &[]);
+    let client = S3Client::new(&config, &credentials()).await;
+    assert!(client.validate_access().await.is_err());
+}
+
+// ---------------------------------------------------------------------------
+// Listing → filtering → events
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn test_listed_objects_filtered_and_mapped_to_events() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path_regex(BUCKET_PATH))
+        .and(query_param("list-type", "2"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(list_xml(
+            "",
+            false,
+            &[
+                contents_xml("contracts/", "folder", 0),
+                contents_xml("contracts/msa.pdf", "etag-msa", 4096),
+                contents_xml("images/logo.png", "etag-logo", 1024),
+            ],
+        )))
+        .mount(&server)
+        .await;
+
+    let mut config = make_config(&server.uri(), &[]);
+    config.mime_type_allowlist = vec!["application/pdf".to_string(), "text/*".to_string()];
+
+    let client = S3Client::new(&config, &credentials()).await;
+    let page = client.list_objects("", None).await.unwrap();
+
+    let indexable: Vec<_> = page
+        .objects
+        .iter()
+        .filter(|o| !o.is_folder_marker() && config.should_index_mime_type(&o.guessed_mime_type()))
+        .collect();
+    assert_eq!(indexable.len(), 1);
+
+    let event = build_object_event(
+        indexable[0],
+        &config,
+        "run-1",
+        "src-1",
+        "cnt-1",
+        &indexable[0].guessed_mime_type(),
+        Some("admin@example.com"),
+        false,
+    );
+    match event {
+        ConnectorEvent::DocumentCreated {
+            document_id,
+            metadata,
+            ..
+        } => {
+            assert_eq!(document_id, "s3:src-1:contracts%2Fmsa.pdf");
+            assert_eq!(metadata.title.as_deref(), Some("msa.pdf"));
+            assert_eq!(
+                metadata.url.as_deref(),
+                Some(format!("{}/docs/contracts/msa.pdf", server.uri()).as_str())
+            );
+        }
+        _ => panic!("Expected DocumentCreated"),
+    }
+}
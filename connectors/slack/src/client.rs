@@ -51,11 +51,15 @@ impl SlackClient {
         Self {
             client: Client::new(),
             // Slack Tier 3 allows ~50 req/min; 1 req/sec keeps us safely under.
-            rate_limiter: RateLimiter::new(1, 5),
+            rate_limiter: RateLimiter::new(1, 5).with_api_family("slack"),
             base_url,
         }
     }
 
+    pub fn rate_limiter(&self) -> &RateLimiter {
+        &self.rate_limiter
+    }
+
     fn extract_retry_after(response: &reqwest::Response) -> Duration {
         response
             .headers()
@@ -210,6 +210,7 @@ mod tests {
             created_at: now,
             updated_at: now,
             created_by: "01JGF7V3E0Y2R1X8P5Q7W9T4N6".to_string(),
+            workspace_id: omni_connector_sdk::DEFAULT_WORKSPACE_ID.to_string(),
         }
     }
 
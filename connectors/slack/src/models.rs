@@ -253,6 +253,17 @@ impl SlackFileAttributes {
     }
 }
 
+/// Document id of the thread started by the message at `thread_ts`.
+pub fn thread_document_id(channel_id: &str, thread_ts: &str) -> String {
+    format!("slack_thread_{}_{}", channel_id, thread_ts)
+}
+
+/// Document id of a channel's top-level messages on `date`, when the day is
+/// small enough not to be split into parts.
+pub fn channel_day_document_id(channel_id: &str, date: NaiveDate) -> String {
+    format!("slack_channel_{}_{}", channel_id, date)
+}
+
 impl MessageGroup {
     pub fn new(
         channel_id: String,
@@ -296,6 +307,17 @@ impl MessageGroup {
         self.message_count() >= 100 || self.content_size() >= 50_000
     }
 
+    pub fn document_id(&self) -> String {
+        if self.is_thread {
+            thread_document_id(&self.channel_id, self.thread_ts.as_ref().unwrap())
+        } else {
+            match self.part {
+                Some(n) => format!("slack_channel_{}_{}_p{}", self.channel_id, self.date, n),
+                None => channel_day_document_id(&self.channel_id, self.date),
+            }
+        }
+    }
+
     pub fn to_document_content(&self) -> String {
         let mut content = String::new();
 
@@ -399,18 +421,7 @@ impl MessageGroup {
         }
         extra.insert("slack".to_string(), serde_json::json!(slack_metadata));
 
-        let document_id = if self.is_thread {
-            format!(
-                "slack_thread_{}_{}",
-                self.channel_id,
-                self.thread_ts.as_ref().unwrap()
-            )
-        } else {
-            match self.part {
-                Some(n) => format!("slack_channel_{}_{}_p{}", self.channel_id, self.date, n),
-                None => format!("slack_channel_{}_{}", self.channel_id, self.date),
-            }
-        };
+        let document_id = self.document_id();
 
         let existing_slack_url =
             format!("slack://channel/{}/archive/{}", self.channel_id, self.date);
@@ -22,6 +22,51 @@ fn slack_ts_date_key(ts: &str) -> Option<String> {
     DateTime::from_timestamp(secs, 0).map(|dt| dt.date_naive().to_string())
 }
 
+/// The message a `message` event affects, and the thread it belongs to.
+#[derive(Debug, PartialEq)]
+struct MessageEventTarget<'a> {
+    channel_id: &'a str,
+    message_ts: &'a str,
+    thread_ts: Option<&'a str>,
+}
+
+/// Resolve which message a `message` event is about. Edits and deletions
+/// carry the affected message under `message` / `previous_message` rather
+/// than at the top level, so their own `ts` is not the message's. Returns
+/// `None` for events that don't change any indexed content.
+fn message_event_target(event: &serde_json::Value) -> Option<MessageEventTarget<'_>> {
+    let str_at = |pointer: &str| event.pointer(pointer).and_then(|v| v.as_str());
+    let channel_id = str_at("/channel")?;
+
+    match str_at("/subtype") {
+        Some("message_changed") => {
+            // Unfurls and reply bookkeeping on a thread parent also arrive as
+            // edits; only the text ends up in the document.
+            if str_at("/message/text") == str_at("/previous_message/text") {
+                return None;
+            }
+            Some(MessageEventTarget {
+                channel_id,
+                message_ts: str_at("/message/ts")?,
+                thread_ts: str_at("/message/thread_ts"),
+            })
+        }
+        Some("message_deleted") => Some(MessageEventTarget {
+            channel_id,
+            message_ts: str_at("/deleted_ts")?,
+            thread_ts: str_at("/previous_message/thread_ts"),
+        }),
+        // Sent for the parent when a reply is posted; the reply's own event
+        // already repairs the thread.
+        Some("message_replied") => None,
+        _ => Some(MessageEventTarget {
+            channel_id,
+            message_ts: str_at("/ts")?,
+            thread_ts: str_at("/thread_ts"),
+        }),
+    }
+}
+
 // ============================================================================
 // Socket Mode protocol types
 // ============================================================================
@@ -359,21 +404,20 @@ async fn handle_event(
         return;
     }
 
-    let channel_id = match payload.pointer("/event/channel").and_then(|v| v.as_str()) {
-        Some(id) => id,
-        None => {
-            warn!(source_id, "Message event missing channel field");
-            return;
-        }
-    };
-    let message_ts = match payload.pointer("/event/ts").and_then(|v| v.as_str()) {
-        Some(ts) => ts,
-        None => {
-            warn!(source_id, channel_id, "Message event missing ts field");
-            return;
-        }
+    let event = &payload["event"];
+    let Some(MessageEventTarget {
+        channel_id,
+        message_ts,
+        thread_ts,
+    }) = message_event_target(event)
+    else {
+        let subtype = event.get("subtype").and_then(|v| v.as_str());
+        debug!(
+            source_id,
+            subtype, "Ignoring message event that changes no indexed message"
+        );
+        return;
     };
-    let thread_ts = payload.pointer("/event/thread_ts").and_then(|v| v.as_str());
 
     let sync_manager = match sync_manager {
         Some(sm) => sm,
@@ -452,3 +496,89 @@ async fn handle_event(
         debug!(source_id, channel_id, "Debounce timer reset for channel");
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_message_event_target_resolves_edits_and_deletions() {
+        let reply = json!({
+            "type": "message",
+            "channel": "C001",
+            "ts": "1700000100.000200",
+            "thread_ts": "1700000000.000100",
+        });
+        assert_eq!(
+            message_event_target(&reply),
+            Some(MessageEventTarget {
+                channel_id: "C001",
+                message_ts: "1700000100.000200",
+                thread_ts: Some("1700000000.000100"),
+            })
+        );
+
+        let edited_reply = json!({
+            "type": "message",
+            "subtype": "message_changed",
+            "channel": "C001",
+            "ts": "1700090000.000900",
+            "message": {
+                "text": "fixed typo",
+                "ts": "1700000100.000200",
+                "thread_ts": "1700000000.000100",
+            },
+            "previous_message": {
+                "text": "fixed tpyo",
+                "ts": "1700000100.000200",
+                "thread_ts": "1700000000.000100",
+            },
+        });
+        assert_eq!(
+            message_event_target(&edited_reply),
+            Some(MessageEventTarget {
+                channel_id: "C001",
+                message_ts: "1700000100.000200",
+                thread_ts: Some("1700000000.000100"),
+            })
+        );
+
+        let deleted = json!({
+            "type": "message",
+            "subtype": "message_deleted",
+            "channel": "C001",
+            "ts": "1700090000.000900",
+            "deleted_ts": "1700000000.000100",
+            "previous_message": { "text": "hello", "ts": "1700000000.000100" },
+        });
+        assert_eq!(
+            message_event_target(&deleted),
+            Some(MessageEventTarget {
+                channel_id: "C001",
+                message_ts: "1700000000.000100",
+                thread_ts: None,
+            })
+        );
+    }
+
+    #[test]
+    fn test_message_event_target_ignores_events_without_content_changes() {
+        let unfurl = json!({
+            "type": "message",
+            "subtype": "message_changed",
+            "channel": "C001",
+            "message": { "text": "see https://example.com", "ts": "1700000000.000100" },
+            "previous_message": { "text": "see https://example.com", "ts": "1700000000.000100" },
+        });
+        assert_eq!(message_event_target(&unfurl), None);
+
+        let replied = json!({
+            "type": "message",
+            "subtype": "message_replied",
+            "channel": "C001",
+            "message": { "text": "parent", "ts": "1700000000.000100" },
+        });
+        assert_eq!(message_event_target(&replied), None);
+    }
+}
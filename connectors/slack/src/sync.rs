@@ -14,6 +14,7 @@ use crate::client::SlackClient;
 use crate::content::ContentProcessor;
 use crate::models::{
     MessageGroup, SlackChannel, SlackConnectorState, SlackCredentials, SlackMessage,
+    channel_day_document_id, thread_document_id,
 };
 
 /// Group identifier for a Slack channel — emitted via `GroupMembershipSync`
@@ -316,6 +317,8 @@ impl SyncManager {
         ctx.flush().await?;
         ctx.save_checkpoint(serde_json::to_value(&connector_state)?)
             .await?;
+        ctx.report_rate_limits(&[self.slack_client.rate_limiter()])
+            .await;
         ctx.complete().await?;
 
         Ok(())
@@ -416,6 +419,8 @@ impl SyncManager {
             if updated > 0 {
                 ctx.increment_updated(updated as i32).await?;
             }
+            ctx.report_rate_limits(&[self.slack_client.rate_limiter()])
+                .await;
             ctx.complete().await?;
 
             info!(
@@ -549,6 +554,8 @@ impl SyncManager {
                 ctx.increment_updated(outcome.emitted_documents as i32)
                     .await?;
             }
+            ctx.report_rate_limits(&[self.slack_client.rate_limiter()])
+                .await;
             ctx.complete().await?;
             Ok(())
         }
@@ -743,6 +750,21 @@ impl SyncManager {
         Ok(())
     }
 
+    async fn emit_document_deleted(
+        &self,
+        ctx: &SyncContext,
+        sync_run_id: &str,
+        source_id: &str,
+        document_id: String,
+    ) -> Result<()> {
+        ctx.emit_event(ConnectorEvent::DocumentDeleted {
+            sync_run_id: sync_run_id.to_string(),
+            source_id: source_id.to_string(),
+            document_id,
+        })
+        .await
+    }
+
     async fn emit_channel_group_membership(
         &self,
         ctx: &SyncContext,
@@ -779,16 +801,49 @@ impl SyncManager {
         thread_ts: &str,
         is_update: bool,
     ) -> Result<RepairOutcome> {
-        let thread_messages = self
+        let thread_messages = match self
             .fetch_thread_replies_all(token, &channel.id, thread_ts)
             .await
-            .with_context(|| {
-                format!(
-                    "Failed to fully fetch Slack thread {} for channel {}",
-                    thread_ts, channel.id
-                )
-            })?;
+        {
+            Ok(messages) => messages,
+            // The parent was deleted and took the thread with it
+            Err(e) if e.to_string().contains("thread_not_found") => Vec::new(),
+            Err(e) => {
+                return Err(e).with_context(|| {
+                    format!(
+                        "Failed to fully fetch Slack thread {} for channel {}",
+                        thread_ts, channel.id
+                    )
+                });
+            }
+        };
         let scanned_items = thread_messages.len();
+
+        // A thread whose replies were all deleted is no longer indexed as a
+        // thread; its parent stays in the channel-day document.
+        if !thread_messages
+            .iter()
+            .any(|message| message.ts != thread_ts)
+        {
+            if !is_update {
+                return Ok(RepairOutcome {
+                    emitted_documents: 0,
+                    scanned_items,
+                });
+            }
+            self.emit_document_deleted(
+                ctx,
+                sync_run_id,
+                source_id,
+                thread_document_id(&channel.id, thread_ts),
+            )
+            .await?;
+            return Ok(RepairOutcome {
+                emitted_documents: 1,
+                scanned_items,
+            });
+        }
+
         let thread_date = slack_ts_date(thread_ts)?;
         let mut thread_group = MessageGroup::new(
             channel.id.clone(),
@@ -858,6 +913,17 @@ impl SyncManager {
             top_level_messages.clone(),
         )?;
         let mut emitted_documents = 0;
+        if message_groups.is_empty() && is_update {
+            // Every message of the day was deleted
+            self.emit_document_deleted(
+                ctx,
+                sync_run_id,
+                source_id,
+                channel_day_document_id(&channel.id, date),
+            )
+            .await?;
+            emitted_documents += 1;
+        }
         for group in message_groups {
             self.emit_message_group(ctx, group, sync_run_id, source_id, group_email, is_update)
                 .await?;
@@ -46,6 +46,13 @@ impl SlackConnectorTestFixture {
             sync_backoff_base_seconds: 30,
             sync_backoff_max_seconds: 3600,
             sync_max_consecutive_failures: 10,
+            export_link_ttl_seconds: 86400,
+            backpressure: Default::default(),
+            source_stats_refresh_interval_seconds: 3600,
+            source_stats_retention_days: 365,
+            connector_error_retention_days: 30,
+            audit_log_retention_days: 365,
+            credential_refresh_margin_seconds: 900,
         };
 
         let redis_client = redis::Client::open(cm_config.redis.redis_url.clone())?;
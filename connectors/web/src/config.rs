@@ -1,5 +1,7 @@
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, anyhow};
+use regex::RegexSet;
 use serde::{Deserialize, Serialize};
+use spider::compact_str::CompactString;
 use spider::website::Website;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -13,10 +15,26 @@ pub struct WebSourceConfig {
     pub respect_robots_txt: bool,
     #[serde(default)]
     pub user_agent: Option<String>,
+    /// Regexes matched against each URL; matching URLs are not crawled.
     #[serde(default)]
     pub blacklist_patterns: Vec<String>,
+    /// Regexes matched against each URL; when set, only URLs matching one of
+    /// them are crawled. The root URL must match.
+    #[serde(default)]
+    pub whitelist_patterns: Vec<String>,
     #[serde(default)]
     pub include_subdomains: bool,
+    /// Also crawl the URLs listed in the site's sitemap, which is how pages
+    /// of sites that build their links client-side get discovered.
+    #[serde(default = "default_use_sitemap")]
+    pub use_sitemap: bool,
+    /// Sitemap location relative to the root URL; `sitemap.xml` when unset.
+    #[serde(default)]
+    pub sitemap_path: Option<String>,
+    /// Index pages as rendered by the headless browser service rather than
+    /// as served, for sites that render their content with JavaScript.
+    #[serde(default)]
+    pub render_javascript: bool,
 }
 
 fn default_max_depth() -> usize {
@@ -31,18 +49,27 @@ fn default_respect_robots() -> bool {
     true
 }
 
+fn default_use_sitemap() -> bool {
+    true
+}
+
 impl WebSourceConfig {
     pub fn from_json(config: &serde_json::Value) -> Result<Self> {
         serde_json::from_value(config.clone()).context("Failed to parse web source configuration")
     }
 
     pub fn build_spider_website(&self) -> Result<Website> {
+        self.validate_patterns()?;
+
         let mut website = Website::new(&self.root_url);
 
         website
             .with_respect_robots_txt(self.respect_robots_txt)
             .with_subdomains(self.include_subdomains)
             .with_depth(self.max_depth)
+            .with_limit(self.max_pages.try_into().unwrap_or(u32::MAX))
+            .with_ignore_sitemap(!self.use_sitemap)
+            .with_sitemap(self.sitemap_path.as_deref())
             .with_delay(300);
 
         if let Some(user_agent) = &self.user_agent {
@@ -50,13 +77,40 @@ impl WebSourceConfig {
         }
 
         if !self.blacklist_patterns.is_empty() {
-            for pattern in &self.blacklist_patterns {
-                website.with_blacklist_url(Some(vec![pattern.as_str().into()]));
-            }
+            website.with_blacklist_url(Some(
+                self.blacklist_patterns
+                    .iter()
+                    .map(|pattern| pattern.as_str().into())
+                    .collect::<Vec<CompactString>>(),
+            ));
+        }
+
+        if !self.whitelist_patterns.is_empty() {
+            website.with_whitelist_url(Some(
+                self.whitelist_patterns
+                    .iter()
+                    .map(|pattern| pattern.as_str().into())
+                    .collect::<Vec<CompactString>>(),
+            ));
         }
 
         Ok(website)
     }
+
+    /// Spider drops a pattern list that fails to compile, which would crawl
+    /// pages the admin meant to exclude, so reject it up front instead.
+    fn validate_patterns(&self) -> Result<()> {
+        RegexSet::new(&self.blacklist_patterns).context("Invalid blacklist pattern")?;
+        let whitelist =
+            RegexSet::new(&self.whitelist_patterns).context("Invalid whitelist pattern")?;
+        if !self.whitelist_patterns.is_empty() && !whitelist.is_match(&self.root_url) {
+            return Err(anyhow!(
+                "Root URL {} does not match any whitelist pattern",
+                self.root_url
+            ));
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -76,6 +130,8 @@ mod tests {
         assert_eq!(web_config.max_pages, 10_000);
         assert!(web_config.respect_robots_txt);
         assert!(!web_config.include_subdomains);
+        assert!(web_config.use_sitemap);
+        assert!(!web_config.render_javascript);
     }
 
     #[test]
@@ -87,7 +143,11 @@ mod tests {
             "respect_robots_txt": false,
             "user_agent": "MyBot/1.0",
             "blacklist_patterns": ["/admin", "/api"],
-            "include_subdomains": true
+            "whitelist_patterns": ["^https://docs\\.example\\.com/"],
+            "include_subdomains": true,
+            "use_sitemap": false,
+            "sitemap_path": "/docs/sitemap.xml",
+            "render_javascript": true
         });
 
         let web_config = WebSourceConfig::from_json(&config).unwrap();
@@ -97,22 +157,59 @@ mod tests {
         assert!(!web_config.respect_robots_txt);
         assert_eq!(web_config.user_agent, Some("MyBot/1.0".to_string()));
         assert_eq!(web_config.blacklist_patterns.len(), 2);
+        assert_eq!(web_config.whitelist_patterns.len(), 1);
         assert!(web_config.include_subdomains);
+        assert!(!web_config.use_sitemap);
+        assert_eq!(
+            web_config.sitemap_path,
+            Some("/docs/sitemap.xml".to_string())
+        );
+        assert!(web_config.render_javascript);
     }
 
-    #[test]
-    fn test_build_spider_website() {
-        let config = WebSourceConfig {
+    fn test_config() -> WebSourceConfig {
+        WebSourceConfig {
             root_url: "https://example.com".to_string(),
             max_depth: 5,
             max_pages: 1000,
             respect_robots_txt: true,
             user_agent: Some("TestBot/1.0".to_string()),
             blacklist_patterns: vec!["/admin".to_string()],
+            whitelist_patterns: vec![],
             include_subdomains: false,
-        };
+            use_sitemap: true,
+            sitemap_path: None,
+            render_javascript: false,
+        }
+    }
 
-        let website = config.build_spider_website();
+    #[test]
+    fn test_build_spider_website() {
+        let website = test_config().build_spider_website();
         assert!(website.is_ok());
     }
+
+    #[test]
+    fn test_build_spider_website_rejects_invalid_patterns() {
+        let config = WebSourceConfig {
+            blacklist_patterns: vec!["/admin(".to_string()],
+            ..test_config()
+        };
+        assert!(config.build_spider_website().is_err());
+
+        let config = WebSourceConfig {
+            whitelist_patterns: vec!["^https://example\\.com/docs/".to_string()],
+            ..test_config()
+        };
+        assert!(
+            config.build_spider_website().is_err(),
+            "Root URL outside the whitelist should be rejected"
+        );
+
+        let config = WebSourceConfig {
+            root_url: "https://example.com/docs/".to_string(),
+            ..config
+        };
+        assert!(config.build_spider_website().is_ok());
+    }
 }
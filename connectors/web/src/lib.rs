@@ -1,4 +1,5 @@
 pub mod config;
 pub mod connector;
 pub mod models;
+pub mod renderer;
 pub mod sync;
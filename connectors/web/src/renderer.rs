@@ -0,0 +1,63 @@
+use anyhow::{Context, Result, anyhow};
+use reqwest::Client;
+use serde_json::json;
+use std::time::Duration;
+
+/// Client for a headless browser service exposing a Browserless-compatible
+/// `/content` endpoint, which loads a URL, runs its JavaScript and returns
+/// the resulting HTML.
+#[derive(Clone)]
+pub struct RenderingClient {
+    client: Client,
+    base_url: String,
+}
+
+impl RenderingClient {
+    pub fn new(base_url: &str) -> Self {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(60))
+            .build()
+            .expect("Failed to build HTTP client");
+        Self {
+            client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// The rendering service configured by `WEB_RENDERER_URL`, if any.
+    pub fn from_env() -> Option<Self> {
+        std::env::var("WEB_RENDERER_URL")
+            .ok()
+            .filter(|url| !url.trim().is_empty())
+            .map(|url| Self::new(url.trim()))
+    }
+
+    pub async fn render(&self, url: &str) -> Result<String> {
+        let response = self
+            .client
+            .post(format!("{}/content", self.base_url))
+            .json(&json!({
+                "url": url,
+                "gotoOptions": { "waitUntil": "networkidle2" },
+            }))
+            .send()
+            .await
+            .with_context(|| format!("Failed to reach rendering service for {}", url))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(anyhow!(
+                "Rendering service returned HTTP {} for {}: {}",
+                status,
+                url,
+                body
+            ));
+        }
+
+        response
+            .text()
+            .await
+            .context("Failed to read rendered HTML")
+    }
+}
@@ -1,15 +1,17 @@
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, anyhow};
 use async_trait::async_trait;
 use omni_connector_sdk::{SdkClient, SyncContext};
 use spider::client::StatusCode;
+use spider::page::Page;
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::time::Instant;
 use tokio::sync::{Mutex, mpsc};
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
 use crate::config::WebSourceConfig;
 use crate::models::{PageSyncState, WebConnectorState, WebPage};
+use crate::renderer::RenderingClient;
 
 /// Result of a crawl operation
 pub struct CrawlResult {
@@ -27,7 +29,15 @@ pub trait PageSource: Send + Sync {
 }
 
 /// Real implementation using spider library
-pub struct SpiderPageSource;
+pub struct SpiderPageSource {
+    renderer: Option<RenderingClient>,
+}
+
+impl SpiderPageSource {
+    pub fn new(renderer: Option<RenderingClient>) -> Self {
+        Self { renderer }
+    }
+}
 
 #[async_trait]
 impl PageSource for SpiderPageSource {
@@ -36,6 +46,17 @@ impl PageSource for SpiderPageSource {
         config: &WebSourceConfig,
         tx: mpsc::Sender<WebPage>,
     ) -> Result<CrawlResult> {
+        let renderer = match (config.render_javascript, &self.renderer) {
+            (false, _) => None,
+            (true, Some(renderer)) => Some(renderer.clone()),
+            (true, None) => {
+                return Err(anyhow!(
+                    "JavaScript rendering is enabled for {} but WEB_RENDERER_URL is not set",
+                    config.root_url
+                ));
+            }
+        };
+
         let mut website = config.build_spider_website()?;
 
         let mut rx = website.subscribe(32);
@@ -46,10 +67,17 @@ impl PageSource for SpiderPageSource {
                     continue;
                 }
 
-                if let Ok(web_page) = WebPage::from_spider_page(&page) {
-                    if tx.send(web_page).await.is_err() {
-                        break;
-                    }
+                // Links are still discovered from the page as served, so
+                // sites that build their navigation client-side rely on the
+                // sitemap to be crawled beyond the root URL.
+                let web_page = match &renderer {
+                    Some(renderer) => Self::render_page(renderer, &page).await,
+                    None => WebPage::from_spider_page(&page),
+                };
+                if let Ok(web_page) = web_page
+                    && tx.send(web_page).await.is_err()
+                {
+                    break;
                 }
             }
         });
@@ -76,6 +104,25 @@ impl PageSource for SpiderPageSource {
     }
 }
 
+impl SpiderPageSource {
+    async fn render_page(renderer: &RenderingClient, page: &Page) -> Result<WebPage> {
+        let served = WebPage::from_spider_page(page);
+        let html = match renderer.render(page.get_url()).await {
+            Ok(html) => html,
+            Err(e) => {
+                warn!("Indexing {} as served: {}", page.get_url(), e);
+                return served;
+            }
+        };
+        let mut web_page = WebPage::from_html(page.get_url().to_string(), &html)?;
+        if let Ok(served) = served {
+            web_page.etag = served.etag;
+            web_page.last_modified = served.last_modified;
+        }
+        Ok(web_page)
+    }
+}
+
 pub struct SyncManager {
     sdk_client: SdkClient,
     page_source: Arc<dyn PageSource>,
@@ -83,7 +130,8 @@ pub struct SyncManager {
 
 impl SyncManager {
     pub fn new(sdk_client: SdkClient) -> Self {
-        Self::with_page_source(sdk_client, Arc::new(SpiderPageSource))
+        let page_source = SpiderPageSource::new(RenderingClient::from_env());
+        Self::with_page_source(sdk_client, Arc::new(page_source))
     }
 
     pub fn with_page_source(sdk_client: SdkClient, page_source: Arc<dyn PageSource>) -> Self {
@@ -0,0 +1,19 @@
+#!/usr/bin/env python3
+"""Zendesk Connector entry point for Omni."""
+
+import logging
+import os
+
+from zendesk_connector import ZendeskConnector
+
+logging.basicConfig(
+    level=logging.INFO,
+    format="%(asctime)s - %(name)s - %(levelname)s - %(message)s",
+)
+
+if __name__ == "__main__":
+    port = os.environ.get("PORT")
+    if not port:
+        raise SystemExit("PORT environment variable is required")
+    port = int(port)
+    ZendeskConnector().serve(port=port)
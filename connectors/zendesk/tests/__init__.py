@@ -0,0 +1 @@
+"""Tests for Zendesk connector."""
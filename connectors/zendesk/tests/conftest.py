@@ -0,0 +1,383 @@
+"""Integration test fixtures for the Zendesk connector.
+
+Session-scoped: harness, mock Zendesk API server, connector server, connector-manager.
+Function-scoped: seed helper, source_id, httpx client.
+"""
+
+from __future__ import annotations
+
+import logging
+import socket
+import threading
+import time
+from typing import Any
+
+import httpx
+import pytest
+import pytest_asyncio
+import uvicorn
+from starlette.applications import Starlette
+from starlette.requests import Request
+from starlette.responses import JSONResponse
+from starlette.routing import Route
+
+from omni_connector.testing import OmniTestHarness, SeedHelper
+
+logger = logging.getLogger(__name__)
+
+
+# ---------------------------------------------------------------------------
+# Mock data helpers
+# ---------------------------------------------------------------------------
+
+
+def _user_payload(user_id: int, name: str, email: str, role: str = "end-user") -> dict[str, Any]:
+    return {"id": user_id, "name": name, "email": email, "role": role}
+
+
+def _ticket_payload(
+    ticket_id: int,
+    subject: str = "Printer on fire",
+    status: str = "open",
+    requester_id: int = 100,
+    assignee_id: int | None = 1,
+    group_id: int | None = 10,
+    updated_at: str = "2024-06-01T14:00:00Z",
+) -> dict[str, Any]:
+    return {
+        "id": ticket_id,
+        "subject": subject,
+        "status": status,
+        "priority": "high",
+        "type": "incident",
+        "requester_id": requester_id,
+        "submitter_id": requester_id,
+        "assignee_id": assignee_id,
+        "group_id": group_id,
+        "collaborator_ids": [],
+        "follower_ids": [],
+        "tags": ["hardware"],
+        "created_at": "2024-06-01T10:00:00Z",
+        "updated_at": updated_at,
+    }
+
+
+def _comment_payload(
+    comment_id: int,
+    body: str,
+    author_id: int,
+    public: bool = True,
+) -> dict[str, Any]:
+    return {
+        "id": comment_id,
+        "body": body,
+        "plain_body": body,
+        "author_id": author_id,
+        "public": public,
+        "created_at": "2024-06-01T10:05:00Z",
+    }
+
+
+def _article_payload(
+    article_id: int,
+    title: str = "Resetting your password",
+    user_segment_id: int | None = None,
+    draft: bool = False,
+) -> dict[str, Any]:
+    return {
+        "id": article_id,
+        "title": title,
+        "body": "<p>Click <b>Forgot password</b> on the sign-in page.</p>",
+        "author_id": 1,
+        "draft": draft,
+        "user_segment_id": user_segment_id,
+        "section_id": 500,
+        "locale": "en-us",
+        "label_names": ["account"],
+        "html_url": f"https://acme.zendesk.com/hc/en-us/articles/{article_id}",
+        "created_at": "2024-05-01T09:00:00Z",
+        "updated_at": "2024-05-02T09:00:00Z",
+        "edited_at": "2024-05-02T09:00:00Z",
+    }
+
+
+# ---------------------------------------------------------------------------
+# Mock Zendesk API
+# ---------------------------------------------------------------------------
+
+
+class MockZendeskAPI:
+    """Controllable mock of the Zendesk Support and Help Center APIs.
+
+    The ticket export cursor is the number of tickets already returned, so
+    tickets added after a sync show up on the next incremental run.
+    """
+
+    def __init__(self) -> None:
+        self.users: dict[int, dict[str, Any]] = {}
+        self.groups: list[dict[str, Any]] = []
+        self.group_memberships: list[dict[str, Any]] = []
+        self.tickets: list[dict[str, Any]] = []
+        self.comments: dict[int, list[dict[str, Any]]] = {}
+        self.articles: list[dict[str, Any]] = []
+        self.should_fail_auth: bool = False
+        self.export_start_times: list[str] = []
+
+    def reset(self) -> None:
+        self.__init__()  # type: ignore[misc]
+
+    def add_user(self, user_id: int, name: str, email: str, role: str = "end-user") -> None:
+        self.users[user_id] = _user_payload(user_id, name, email, role)
+
+    def add_group(self, group_id: int, name: str, member_ids: list[int]) -> None:
+        self.groups.append({"id": group_id, "name": name, "deleted": False})
+        for user_id in member_ids:
+            self.group_memberships.append({"group_id": group_id, "user_id": user_id})
+
+    def add_ticket(self, ticket_id: int, **kwargs: Any) -> None:
+        self.tickets.append(_ticket_payload(ticket_id, **kwargs))
+
+    def add_comment(
+        self, ticket_id: int, comment_id: int, body: str, author_id: int, public: bool = True
+    ) -> None:
+        self.comments.setdefault(ticket_id, []).append(
+            _comment_payload(comment_id, body, author_id, public)
+        )
+
+    def add_article(self, article_id: int, **kwargs: Any) -> None:
+        self.articles.append(_article_payload(article_id, **kwargs))
+
+    def create_app(self) -> Starlette:
+        mock = self
+
+        def unauthorized() -> JSONResponse:
+            return JSONResponse({"error": "Couldn't authenticate you"}, status_code=401)
+
+        def page(key: str, items: list[dict[str, Any]]) -> JSONResponse:
+            return JSONResponse(
+                {key: items, "meta": {"has_more": False}, "links": {"next": None}}
+            )
+
+        async def me(request: Request) -> JSONResponse:
+            if mock.should_fail_auth:
+                return unauthorized()
+            return JSONResponse({"user": _user_payload(1, "Agent", "agent@acme.com", "admin")})
+
+        async def list_users(request: Request) -> JSONResponse:
+            roles = set(request.query_params.getlist("role[]"))
+            users = [u for u in mock.users.values() if not roles or u["role"] in roles]
+            return page("users", users)
+
+        async def show_many(request: Request) -> JSONResponse:
+            ids = {int(i) for i in request.query_params.get("ids", "").split(",") if i}
+            return JSONResponse({"users": [u for i, u in mock.users.items() if i in ids]})
+
+        async def list_groups(request: Request) -> JSONResponse:
+            return page("groups", mock.groups)
+
+        async def list_group_memberships(request: Request) -> JSONResponse:
+            return page("group_memberships", mock.group_memberships)
+
+        async def export_tickets(request: Request) -> JSONResponse:
+            if mock.should_fail_auth:
+                return unauthorized()
+            cursor = request.query_params.get("cursor")
+            if cursor is None:
+                mock.export_start_times.append(request.query_params.get("start_time", ""))
+            start = int(cursor) if cursor else 0
+            return JSONResponse(
+                {
+                    "tickets": mock.tickets[start:],
+                    "after_cursor": str(len(mock.tickets)),
+                    "end_of_stream": True,
+                }
+            )
+
+        async def list_comments(request: Request) -> JSONResponse:
+            ticket_id = int(request.path_params["ticket_id"])
+            return page("comments", mock.comments.get(ticket_id, []))
+
+        async def list_articles(request: Request) -> JSONResponse:
+            return page("articles", mock.articles)
+
+        async def export_articles(request: Request) -> JSONResponse:
+            return JSONResponse(
+                {
+                    "articles": mock.articles,
+                    "next_page": None,
+                    "end_time": int(time.time()),
+                }
+            )
+
+        routes = [
+            Route("/api/v2/users/me.json", me),
+            Route("/api/v2/users.json", list_users),
+            Route("/api/v2/users/show_many.json", show_many),
+            Route("/api/v2/groups.json", list_groups),
+            Route("/api/v2/group_memberships.json", list_group_memberships),
+            Route("/api/v2/incremental/tickets/cursor.json", export_tickets),
+            Route("/api/v2/tickets/{ticket_id}/comments.json", list_comments),
+            Route("/api/v2/help_center/articles.json", list_articles),
+            Route("/api/v2/help_center/incremental/articles.json", export_articles),
+        ]
+        return Starlette(routes=routes)
+
+
+# ---------------------------------------------------------------------------
+# Helpers
+# ---------------------------------------------------------------------------
+
+
+def _free_port() -> int:
+    with socket.socket(socket.AF_INET, socket.SOCK_STREAM) as s:
+        s.bind(("", 0))
+        return s.getsockname()[1]
+
+
+def _wait_for_port(port: int, host: str = "localhost", timeout: float = 10) -> None:
+    deadline = time.monotonic() + timeout
+    while time.monotonic() < deadline:
+        try:
+            with socket.create_connection((host, port), timeout=1):
+                return
+        except OSError:
+            time.sleep(0.1)
+    raise TimeoutError(f"Port {port} not open after {timeout}s")
+
+
+# ---------------------------------------------------------------------------
+# Session-scoped fixtures
+# ---------------------------------------------------------------------------
+
+
+@pytest.fixture(scope="session")
+def mock_zendesk_api() -> MockZendeskAPI:
+    return MockZendeskAPI()
+
+
+@pytest.fixture(scope="session")
+def mock_zendesk_server(mock_zendesk_api: MockZendeskAPI) -> str:
+    """Start mock Zendesk API server in a daemon thread. Returns base URL."""
+    port = _free_port()
+    app = mock_zendesk_api.create_app()
+    config = uvicorn.Config(app, host="0.0.0.0", port=port, log_level="warning")
+    server = uvicorn.Server(config)
+
+    thread = threading.Thread(target=server.run, daemon=True)
+    thread.start()
+
+    _wait_for_port(port)
+    return f"http://localhost:{port}"
+
+
+@pytest.fixture(scope="session")
+def connector_port() -> int:
+    return _free_port()
+
+
+@pytest.fixture(scope="session")
+def connector_server(connector_port: int) -> str:
+    """Start the Zendesk connector as a uvicorn server in a daemon thread. Returns base URL."""
+    import os
+
+    os.environ.setdefault("CONNECTOR_MANAGER_URL", "http://localhost:0")
+
+    from omni_connector.server import create_app
+    from zendesk_connector import ZendeskConnector
+
+    app = create_app(ZendeskConnector())
+    config = uvicorn.Config(app, host="0.0.0.0", port=connector_port, log_level="warning")
+    server = uvicorn.Server(config)
+
+    thread = threading.Thread(target=server.run, daemon=True)
+    thread.start()
+
+    _wait_for_port(connector_port)
+    return f"http://localhost:{connector_port}"
+
+
+@pytest_asyncio.fixture(scope="session")
+async def harness(
+    connector_server: str,
+    connector_port: int,
+) -> OmniTestHarness:
+    """Session-scoped OmniTestHarness with all infrastructure started."""
+    import os
+
+    h = OmniTestHarness()
+    await h.start_infra()
+    await h.start_connector_manager(
+        {
+            "ZENDESK_CONNECTOR_URL": f"http://host.docker.internal:{connector_port}",
+        }
+    )
+
+    os.environ["CONNECTOR_MANAGER_URL"] = h.connector_manager_url
+
+    yield h
+    await h.teardown()
+
+
+# ---------------------------------------------------------------------------
+# Function-scoped fixtures
+# ---------------------------------------------------------------------------
+
+
+@pytest_asyncio.fixture
+async def seed(harness: OmniTestHarness) -> SeedHelper:
+    return harness.seed()
+
+
+@pytest_asyncio.fixture
+async def source_id(
+    seed: SeedHelper,
+    mock_zendesk_server: str,
+    mock_zendesk_api: MockZendeskAPI,
+) -> str:
+    """Create a Zendesk source with credentials pointing to the mock server."""
+    mock_zendesk_api.reset()
+    mock_zendesk_api.add_user(1, "Alice Agent", "alice@acme.com", role="agent")
+    mock_zendesk_api.add_user(2, "Bob Agent", "bob@acme.com", role="admin")
+    mock_zendesk_api.add_user(100, "Carol Customer", "carol@customer.com")
+    mock_zendesk_api.add_group(10, "Support", [1, 2])
+
+    sid = await seed.create_source(
+        source_type="zendesk",
+        config={"subdomain": "acme", "api_url": mock_zendesk_server},
+    )
+    await seed.create_credentials(
+        sid, {"email": "alice@acme.com", "api_token": "test-token"}, provider="zendesk"
+    )
+    return sid
+
+
+@pytest_asyncio.fixture
+async def cm_client(harness: OmniTestHarness) -> httpx.AsyncClient:
+    """Async httpx client pointed at the connector-manager."""
+    async with httpx.AsyncClient(base_url=harness.connector_manager_url, timeout=30) as client:
+        yield client
+
+
+# ---------------------------------------------------------------------------
+# Fixtures for unit tests (test_mappers.py)
+# ---------------------------------------------------------------------------
+
+
+@pytest.fixture
+def zendesk_ticket() -> dict[str, Any]:
+    ticket = _ticket_payload(42)
+    ticket["collaborator_ids"] = [101]
+    ticket["follower_ids"] = [2]
+    return ticket
+
+
+@pytest.fixture
+def zendesk_users():
+    from zendesk_connector.models import ZendeskUser
+
+    return {
+        1: ZendeskUser(1, "Alice Agent", "alice@acme.com", "agent"),
+        2: ZendeskUser(2, "Bob Agent", "bob@acme.com", "admin"),
+        100: ZendeskUser(100, "Carol Customer", "carol@customer.com", "end-user"),
+        101: ZendeskUser(101, "Dan Customer", "dan@customer.com", "end-user"),
+    }
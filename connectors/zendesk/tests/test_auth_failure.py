@@ -0,0 +1,42 @@
+"""Integration tests: authentication failures are handled correctly."""
+
+import httpx
+import pytest
+
+from omni_connector.testing import wait_for_sync
+
+pytestmark = pytest.mark.integration
+
+
+async def test_bad_token_fails_sync(
+    harness, seed, source_id, mock_zendesk_api, cm_client: httpx.AsyncClient
+):
+    mock_zendesk_api.add_ticket(1)
+    mock_zendesk_api.should_fail_auth = True
+
+    try:
+        resp = await cm_client.post("/sync", json={"source_id": source_id, "sync_type": "full"})
+        assert resp.status_code == 200, resp.text
+
+        row = await wait_for_sync(harness.db_pool, resp.json()["sync_run_id"], timeout=30)
+        assert row["status"] == "failed"
+        assert "auth" in (row["error_message"] or "").lower()
+    finally:
+        mock_zendesk_api.should_fail_auth = False
+
+
+async def test_missing_credentials_fail_sync(
+    harness, seed, mock_zendesk_server, cm_client: httpx.AsyncClient
+):
+    sid = await seed.create_source(
+        source_type="zendesk",
+        config={"subdomain": "acme", "api_url": mock_zendesk_server},
+    )
+    await seed.create_credentials(sid, {"email": "alice@acme.com"}, provider="zendesk")
+
+    resp = await cm_client.post("/sync", json={"source_id": sid, "sync_type": "full"})
+    assert resp.status_code == 200, resp.text
+
+    row = await wait_for_sync(harness.db_pool, resp.json()["sync_run_id"], timeout=30)
+    assert row["status"] == "failed"
+    assert "api_token" in (row["error_message"] or "")
@@ -0,0 +1,64 @@
+"""Integration tests: full sync creates ticket and article documents."""
+
+import httpx
+import pytest
+
+from omni_connector.testing import count_events, get_events, wait_for_sync
+
+pytestmark = pytest.mark.integration
+
+
+async def _run_sync(harness, cm_client: httpx.AsyncClient, source_id: str, sync_type: str):
+    resp = await cm_client.post("/sync", json={"source_id": source_id, "sync_type": sync_type})
+    assert resp.status_code == 200, resp.text
+    return await wait_for_sync(harness.db_pool, resp.json()["sync_run_id"], timeout=30)
+
+
+async def test_full_sync_creates_tickets_and_articles(
+    harness, seed, source_id, mock_zendesk_api, cm_client: httpx.AsyncClient
+):
+    mock_zendesk_api.add_ticket(1)
+    mock_zendesk_api.add_comment(1, 11, "My printer is on fire", author_id=100)
+    mock_zendesk_api.add_ticket(2, subject="Cannot log in")
+    mock_zendesk_api.add_article(7)
+
+    row = await _run_sync(harness, cm_client, source_id, "full")
+    assert (
+        row["status"] == "completed"
+    ), f"Sync ended with status={row['status']}, error={row.get('error_message')}"
+    assert row["documents_scanned"] >= 3
+
+    events = await get_events(harness.db_pool, source_id)
+    created = {
+        e["payload"]["document_id"] for e in events if e["event_type"] == "document_created"
+    }
+    assert {"ticket:1", "ticket:2", "article:7"} <= created
+
+
+async def test_internal_notes_are_a_separate_document(
+    harness, seed, source_id, mock_zendesk_api, cm_client: httpx.AsyncClient
+):
+    mock_zendesk_api.add_ticket(3)
+    mock_zendesk_api.add_comment(3, 31, "Customer-visible reply", author_id=1)
+    mock_zendesk_api.add_comment(3, 32, "Escalate to tier 2", author_id=1, public=False)
+
+    row = await _run_sync(harness, cm_client, source_id, "full")
+    assert row["status"] == "completed"
+
+    events = await get_events(harness.db_pool, source_id)
+    created = {
+        e["payload"]["document_id"] for e in events if e["event_type"] == "document_created"
+    }
+    assert {"ticket:3", "ticket:3:internal"} <= created
+
+
+async def test_draft_articles_are_skipped(
+    harness, seed, source_id, mock_zendesk_api, cm_client: httpx.AsyncClient
+):
+    mock_zendesk_api.add_article(8, draft=True)
+
+    row = await _run_sync(harness, cm_client, source_id, "full")
+    assert row["status"] == "completed"
+
+    n_events = await count_events(harness.db_pool, source_id, "document_created")
+    assert n_events == 0, f"Expected no documents for a draft article, got {n_events}"
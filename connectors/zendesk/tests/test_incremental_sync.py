@@ -0,0 +1,62 @@
+"""Integration tests: incremental sync continues the ticket export cursor."""
+
+import httpx
+import pytest
+
+from omni_connector.testing import get_events, wait_for_sync
+
+pytestmark = pytest.mark.integration
+
+
+async def test_incremental_sync_only_exports_new_tickets(
+    harness, seed, source_id, mock_zendesk_api, cm_client: httpx.AsyncClient
+):
+    mock_zendesk_api.add_ticket(1)
+
+    resp = await cm_client.post("/sync", json={"source_id": source_id, "sync_type": "full"})
+    row = await wait_for_sync(harness.db_pool, resp.json()["sync_run_id"], timeout=30)
+    assert row["status"] == "completed"
+
+    mock_zendesk_api.add_ticket(2, subject="A new ticket")
+
+    resp = await cm_client.post(
+        "/sync", json={"source_id": source_id, "sync_type": "incremental"}
+    )
+    sync_run_id = resp.json()["sync_run_id"]
+    row = await wait_for_sync(harness.db_pool, sync_run_id, timeout=30)
+    assert row["status"] == "completed"
+
+    # The incremental run resumed from the cursor instead of restarting the export.
+    assert mock_zendesk_api.export_start_times == ["0"]
+
+    events = await get_events(harness.db_pool, source_id)
+    incremental_docs = {
+        e["payload"]["document_id"]
+        for e in events
+        if e["event_type"] == "document_created" and e["sync_run_id"] == sync_run_id
+    }
+    assert incremental_docs == {"ticket:2"}
+
+
+async def test_deleted_tickets_are_removed(
+    harness, seed, source_id, mock_zendesk_api, cm_client: httpx.AsyncClient
+):
+    mock_zendesk_api.add_ticket(5)
+
+    resp = await cm_client.post("/sync", json={"source_id": source_id, "sync_type": "full"})
+    row = await wait_for_sync(harness.db_pool, resp.json()["sync_run_id"], timeout=30)
+    assert row["status"] == "completed"
+
+    mock_zendesk_api.add_ticket(5, status="deleted")
+
+    resp = await cm_client.post(
+        "/sync", json={"source_id": source_id, "sync_type": "incremental"}
+    )
+    row = await wait_for_sync(harness.db_pool, resp.json()["sync_run_id"], timeout=30)
+    assert row["status"] == "completed"
+
+    events = await get_events(harness.db_pool, source_id)
+    deleted = {
+        e["payload"]["document_id"] for e in events if e["event_type"] == "document_deleted"
+    }
+    assert "ticket:5" in deleted
@@ -0,0 +1,141 @@
+"""Tests for Zendesk ticket and article mapping."""
+
+from zendesk_connector.config import AGENTS_GROUP
+from zendesk_connector.mappers import (
+    generate_article_content,
+    generate_ticket_content,
+    map_article_to_document,
+    map_internal_notes_to_document,
+    map_ticket_to_document,
+    strip_html,
+)
+from zendesk_connector.models import ZendeskSourceConfig, ZendeskSyncCheckpoint
+
+
+class TestTicketMapping:
+    """Tests for ticket documents and their permissions."""
+
+    def test_ticket_mapping(self, zendesk_ticket, zendesk_users):
+        doc = map_ticket_to_document(
+            zendesk_ticket, "content-id-1", zendesk_users, "https://acme.zendesk.com"
+        )
+
+        assert doc.external_id == "ticket:42"
+        assert doc.title == "#42 Printer on fire"
+        assert doc.content_id == "content-id-1"
+        assert doc.metadata.url == "https://acme.zendesk.com/agent/tickets/42"
+        assert doc.metadata.author == "Carol Customer"
+        assert doc.metadata.content_type == "ticket"
+        assert doc.attributes["status"] == "open"
+        assert doc.attributes["tags"] == ["hardware"]
+
+    def test_ticket_permissions(self, zendesk_ticket, zendesk_users):
+        """Requester, CCs, followers, assignee and the ticket group can see it."""
+        doc = map_ticket_to_document(zendesk_ticket, "c", zendesk_users, "https://x")
+
+        assert doc.permissions.public is False
+        assert doc.permissions.users == [
+            "alice@acme.com",
+            "bob@acme.com",
+            "carol@customer.com",
+            "dan@customer.com",
+        ]
+        assert doc.permissions.groups == ["zendesk:group:10"]
+
+    def test_unknown_users_are_skipped(self, zendesk_ticket):
+        doc = map_ticket_to_document(zendesk_ticket, "c", {}, "https://x")
+
+        assert doc.permissions.users == []
+        assert doc.permissions.groups == ["zendesk:group:10"]
+
+    def test_internal_notes_exclude_end_users(self, zendesk_ticket, zendesk_users):
+        doc = map_internal_notes_to_document(zendesk_ticket, "c", zendesk_users, "https://x")
+
+        assert doc.external_id == "ticket:42:internal"
+        assert doc.permissions.users == ["alice@acme.com", "bob@acme.com"]
+        assert doc.permissions.groups == ["zendesk:group:10"]
+
+    def test_internal_notes_without_group_go_to_agents(self, zendesk_ticket, zendesk_users):
+        zendesk_ticket["group_id"] = None
+        doc = map_internal_notes_to_document(zendesk_ticket, "c", zendesk_users, "https://x")
+
+        assert doc.permissions.groups == [AGENTS_GROUP]
+
+    def test_ticket_content(self, zendesk_ticket, zendesk_users):
+        comments = [
+            {"author_id": 100, "plain_body": "It is on fire.", "created_at": "2024-06-01"},
+            {"author_id": 1, "html_body": "<p>Have you tried water?</p>"},
+        ]
+        content = generate_ticket_content(zendesk_ticket, comments, zendesk_users, {10: "Support"})
+
+        assert content.startswith("Ticket #42: Printer on fire")
+        assert "Requester: Carol Customer <carol@customer.com>" in content
+        assert "Group: Support" in content
+        assert "It is on fire." in content
+        assert "Have you tried water?" in content
+
+
+class TestArticleMapping:
+    """Tests for help-center article documents."""
+
+    def _article(self, **overrides):
+        article = {
+            "id": 7,
+            "title": "Resetting your password",
+            "body": "<p>Click <b>Forgot password</b> &amp; follow the link.</p>",
+            "author_id": 1,
+            "user_segment_id": None,
+            "html_url": "https://acme.zendesk.com/hc/en-us/articles/7",
+            "updated_at": "2024-05-02T09:00:00Z",
+        }
+        article.update(overrides)
+        return article
+
+    def test_public_article(self, zendesk_users):
+        doc = map_article_to_document(self._article(), "c", zendesk_users)
+
+        assert doc.external_id == "article:7"
+        assert doc.metadata.author == "Alice Agent"
+        assert doc.metadata.url == "https://acme.zendesk.com/hc/en-us/articles/7"
+        assert doc.permissions.public is True
+
+    def test_restricted_article(self, zendesk_users):
+        doc = map_article_to_document(self._article(user_segment_id=3), "c", zendesk_users)
+
+        assert doc.permissions.public is False
+        assert doc.permissions.groups == [AGENTS_GROUP]
+
+    def test_article_content_strips_html(self):
+        content = generate_article_content(self._article())
+
+        assert content == "Resetting your password\n\nClick Forgot password & follow the link."
+
+    def test_strip_html(self):
+        assert strip_html("<h1>Title</h1>\n\n\n<p>Body</p>") == "Title\n\nBody"
+
+
+class TestModels:
+    """Tests for source config and checkpoint parsing."""
+
+    def test_subdomain_normalization(self):
+        config = ZendeskSourceConfig.from_mapping({"subdomain": "https://Acme.zendesk.com/"})
+
+        assert config.subdomain == "acme"
+        assert config.base_url == "https://acme.zendesk.com"
+
+    def test_api_url_overrides_subdomain(self):
+        config = ZendeskSourceConfig.from_mapping(
+            {"subdomain": "acme", "api_url": "http://localhost:9000/"}
+        )
+
+        assert config.base_url == "http://localhost:9000"
+
+    def test_checkpoint_roundtrip(self):
+        checkpoint = ZendeskSyncCheckpoint(ticket_cursor="abc", articles_start_time=1700000000)
+
+        assert ZendeskSyncCheckpoint.from_mapping(checkpoint.to_json()) == checkpoint
+
+    def test_checkpoint_ignores_unknown_version(self):
+        checkpoint = ZendeskSyncCheckpoint.from_mapping({"ticket_cursor": "abc"})
+
+        assert checkpoint.ticket_cursor is None
@@ -0,0 +1,48 @@
+"""Integration tests: ticket permissions and group membership sync."""
+
+import httpx
+import pytest
+
+from omni_connector.testing import get_events, wait_for_sync
+
+pytestmark = pytest.mark.integration
+
+
+async def test_group_memberships_emitted(
+    harness, seed, source_id, mock_zendesk_api, cm_client: httpx.AsyncClient
+):
+    resp = await cm_client.post("/sync", json={"source_id": source_id, "sync_type": "full"})
+    row = await wait_for_sync(harness.db_pool, resp.json()["sync_run_id"], timeout=30)
+    assert (
+        row["status"] == "completed"
+    ), f"status={row['status']}, error={row.get('error_message')}"
+
+    events = await get_events(harness.db_pool, source_id)
+    groups = {
+        e["payload"]["group_email"]: set(e["payload"]["member_emails"])
+        for e in events
+        if e["event_type"] == "group_membership_sync"
+    }
+    assert groups["zendesk:group:10"] == {"alice@acme.com", "bob@acme.com"}
+    assert groups["zendesk:agents"] == {"alice@acme.com", "bob@acme.com"}
+
+
+async def test_ticket_shared_with_requester_assignee_and_group(
+    harness, seed, source_id, mock_zendesk_api, cm_client: httpx.AsyncClient
+):
+    mock_zendesk_api.add_ticket(1, requester_id=100, assignee_id=1, group_id=10)
+
+    resp = await cm_client.post("/sync", json={"source_id": source_id, "sync_type": "full"})
+    row = await wait_for_sync(harness.db_pool, resp.json()["sync_run_id"], timeout=30)
+    assert row["status"] == "completed"
+
+    events = await get_events(harness.db_pool, source_id)
+    ticket = next(
+        e
+        for e in events
+        if e["event_type"] == "document_created" and e["payload"]["document_id"] == "ticket:1"
+    )
+    permissions = ticket["payload"]["permissions"]
+    assert permissions["public"] is False
+    assert set(permissions["users"]) == {"alice@acme.com", "carol@customer.com"}
+    assert permissions["groups"] == ["zendesk:group:10"]
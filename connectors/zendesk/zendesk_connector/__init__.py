@@ -0,0 +1,6 @@
+"""Zendesk connector for Omni."""
+
+from .connector import ZendeskConnector
+
+__version__ = "1.0.0"
+__all__ = ["ZendeskConnector"]
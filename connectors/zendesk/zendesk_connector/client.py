@@ -0,0 +1,188 @@
+"""Async HTTP client for the Zendesk Support and Help Center APIs."""
+
+import asyncio
+import logging
+from collections.abc import AsyncIterator
+from typing import Any
+
+import httpx
+
+from .config import (
+    DEFAULT_RETRY_AFTER_SECONDS,
+    INITIAL_BACKOFF_SECONDS,
+    MAX_RETRIES,
+    PAGE_SIZE,
+    USERS_PER_LOOKUP,
+)
+
+logger = logging.getLogger(__name__)
+
+
+class ZendeskError(Exception):
+    """Base exception for Zendesk API errors."""
+
+
+class AuthenticationError(ZendeskError):
+    """Invalid or expired credentials (401)."""
+
+
+class ForbiddenError(ZendeskError):
+    """The credentials lack access to the resource (403)."""
+
+
+class ZendeskClient:
+    """Thin async wrapper around the Zendesk REST API.
+
+    Authenticates either with an OAuth access token or with an API token
+    belonging to `email`.
+    """
+
+    def __init__(
+        self,
+        base_url: str,
+        access_token: str | None = None,
+        email: str | None = None,
+        api_token: str | None = None,
+    ):
+        if access_token:
+            headers = {"Authorization": f"Bearer {access_token}"}
+            auth = None
+        elif email and api_token:
+            headers = {}
+            auth = httpx.BasicAuth(f"{email}/token", api_token)
+        else:
+            raise ValueError("Either access_token or email and api_token are required")
+
+        self._client = httpx.AsyncClient(
+            base_url=base_url,
+            headers=headers,
+            auth=auth,
+            timeout=30.0,
+        )
+
+    async def close(self) -> None:
+        await self._client.aclose()
+
+    async def _request(self, method: str, url: str, **kwargs: Any) -> Any:
+        """Make an HTTP request with rate-limit retry and error handling.
+
+        `url` may be a path or an absolute pagination link from a previous
+        response.
+        """
+        backoff = INITIAL_BACKOFF_SECONDS
+        for attempt in range(MAX_RETRIES + 1):
+            resp = await self._client.request(method, url, **kwargs)
+
+            if resp.status_code == 429:
+                retry_after = resp.headers.get("Retry-After")
+                wait = float(retry_after) if retry_after else DEFAULT_RETRY_AFTER_SECONDS
+                logger.warning("Rate limited, waiting %.1fs (attempt %d)", wait, attempt + 1)
+                await asyncio.sleep(wait)
+                continue
+
+            if resp.status_code == 401:
+                raise AuthenticationError("Invalid or expired Zendesk credentials")
+
+            if resp.status_code == 403:
+                raise ForbiddenError(f"Access denied to {url}: {resp.text}")
+
+            if resp.status_code >= 500:
+                if attempt < MAX_RETRIES:
+                    logger.warning("Server error %d, retrying in %.1fs", resp.status_code, backoff)
+                    await asyncio.sleep(backoff)
+                    backoff *= 2
+                    continue
+                raise ZendeskError(f"Server error {resp.status_code}: {resp.text}")
+
+            if resp.status_code >= 400:
+                raise ZendeskError(f"API error {resp.status_code}: {resp.text}")
+
+            return resp.json()
+
+        raise ZendeskError("Max retries exceeded")
+
+    async def _paginate(self, path: str, key: str, **params: Any) -> AsyncIterator[dict[str, Any]]:
+        """Iterate a cursor-paginated list endpoint (`page[size]` / `links.next`)."""
+        url: str | None = path
+        request_params: dict[str, Any] | None = {**params, "page[size]": PAGE_SIZE}
+        while url:
+            data = await self._request("GET", url, params=request_params)
+            for item in data.get(key, []):
+                yield item
+            has_more = data.get("meta", {}).get("has_more", False)
+            url = data.get("links", {}).get("next") if has_more else None
+            # The next link already carries every query parameter.
+            request_params = None
+
+    # ── Account ─────────────────────────────────────────────────────
+
+    async def get_current_user(self) -> dict[str, Any]:
+        """Fetch the authenticated user. Also validates the credentials."""
+        data = await self._request("GET", "/api/v2/users/me.json")
+        return data.get("user", {})
+
+    # ── Users and groups ────────────────────────────────────────────
+
+    def list_agents(self) -> AsyncIterator[dict[str, Any]]:
+        return self._paginate("/api/v2/users.json", "users", **{"role[]": ["agent", "admin"]})
+
+    async def get_users(self, user_ids: list[int]) -> list[dict[str, Any]]:
+        users: list[dict[str, Any]] = []
+        for i in range(0, len(user_ids), USERS_PER_LOOKUP):
+            batch = user_ids[i : i + USERS_PER_LOOKUP]
+            data = await self._request(
+                "GET",
+                "/api/v2/users/show_many.json",
+                params={"ids": ",".join(str(user_id) for user_id in batch)},
+            )
+            users.extend(data.get("users", []))
+        return users
+
+    def list_groups(self) -> AsyncIterator[dict[str, Any]]:
+        return self._paginate("/api/v2/groups.json", "groups")
+
+    def list_group_memberships(self) -> AsyncIterator[dict[str, Any]]:
+        return self._paginate("/api/v2/group_memberships.json", "group_memberships")
+
+    # ── Tickets ─────────────────────────────────────────────────────
+
+    async def export_tickets(
+        self,
+        cursor: str | None = None,
+        start_time: int = 0,
+    ) -> dict[str, Any]:
+        """Fetch one page of the cursor-based incremental ticket export.
+
+        Starts at `start_time` when no cursor is given. The response carries
+        `tickets`, `after_cursor` and `end_of_stream`.
+        """
+        params: dict[str, Any] = {"cursor": cursor} if cursor else {"start_time": start_time}
+        return await self._request("GET", "/api/v2/incremental/tickets/cursor.json", params=params)
+
+    def list_ticket_comments(self, ticket_id: int) -> AsyncIterator[dict[str, Any]]:
+        return self._paginate(f"/api/v2/tickets/{ticket_id}/comments.json", "comments")
+
+    # ── Help Center ─────────────────────────────────────────────────
+
+    def list_articles(self) -> AsyncIterator[dict[str, Any]]:
+        return self._paginate("/api/v2/help_center/articles.json", "articles")
+
+    async def export_articles(self, start_time: int) -> AsyncIterator[dict[str, Any]]:
+        """Iterate pages of the incremental article export since `start_time`.
+
+        Yields whole pages so callers can read each page's `end_time`.
+        """
+        url: str | None = "/api/v2/help_center/incremental/articles.json"
+        params: dict[str, Any] | None = {"start_time": start_time}
+        last_end_time: int | None = None
+        while url:
+            data = await self._request("GET", url, params=params)
+            yield data
+            end_time = data.get("end_time")
+            # The export keeps linking to a next page once caught up; stop
+            # when a page is empty or no longer advances.
+            if not data.get("articles") or end_time == last_end_time:
+                break
+            last_end_time = end_time
+            url = data.get("next_page")
+            params = None
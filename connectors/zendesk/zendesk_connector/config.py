@@ -0,0 +1,12 @@
+"""Configuration constants for Zendesk connector."""
+
+PAGE_SIZE = 100
+USERS_PER_LOOKUP = 100
+MAX_CONTENT_LENGTH = 100_000
+MAX_RETRIES = 3
+INITIAL_BACKOFF_SECONDS = 1.0
+DEFAULT_RETRY_AFTER_SECONDS = 10.0
+
+# Group holding every agent and admin; restricted help-center articles and
+# internal notes are shared with it.
+AGENTS_GROUP = "zendesk:agents"
@@ -0,0 +1,371 @@
+"""Main ZendeskConnector class."""
+
+import logging
+import time
+from collections.abc import Iterable
+from typing import Any
+
+from omni_connector import Connector, SyncContext, SyncMode
+
+from .client import AuthenticationError, ForbiddenError, ZendeskClient, ZendeskError
+from .config import AGENTS_GROUP
+from .mappers import (
+    article_external_id,
+    generate_article_content,
+    generate_internal_notes_content,
+    generate_ticket_content,
+    group_id_for,
+    internal_notes_external_id,
+    map_article_to_document,
+    map_internal_notes_to_document,
+    map_ticket_to_document,
+    ticket_external_id,
+    ticket_user_ids,
+)
+from .models import ZendeskSourceConfig, ZendeskSyncCheckpoint, ZendeskUser
+
+logger = logging.getLogger(__name__)
+
+
+class ZendeskConnector(Connector):
+    """Zendesk Support and Help Center connector for Omni."""
+
+    @property
+    def name(self) -> str:
+        return "zendesk"
+
+    @property
+    def display_name(self) -> str:
+        return "Zendesk"
+
+    @property
+    def version(self) -> str:
+        return "1.0.0"
+
+    @property
+    def source_types(self) -> list[str]:
+        return ["zendesk"]
+
+    @property
+    def description(self) -> str:
+        return "Connect to Zendesk tickets, comments and help center articles"
+
+    @property
+    def sync_modes(self) -> list[str]:
+        return ["full", "incremental"]
+
+    async def sync(
+        self,
+        source_config: dict[str, Any],
+        credentials: dict[str, Any],
+        checkpoint: dict[str, Any] | None,
+        ctx: SyncContext,
+    ) -> None:
+        """
+        Sync tickets with their comments, then help-center articles.
+
+        Tickets come from the cursor-based incremental ticket export: a full
+        sync starts it from the beginning, an incremental sync continues from
+        the checkpointed cursor. Articles are listed in full, or exported
+        incrementally from the time of the last sync.
+
+        Args:
+            source_config: Must contain 'subdomain' (or 'api_url')
+            credentials: 'access_token', or 'email' and 'api_token'
+            checkpoint: Previous ZendeskSyncCheckpoint
+            ctx: Sync context with emit(), complete(), etc.
+        """
+        config = ZendeskSourceConfig.from_mapping(source_config)
+        base_url = config.base_url
+        if not base_url:
+            await ctx.fail("Missing 'subdomain' in source config")
+            return
+
+        access_token = credentials.get("access_token")
+        email = credentials.get("email")
+        api_token = credentials.get("api_token")
+        if not access_token and not (email and api_token):
+            await ctx.fail("Missing 'access_token', or 'email' and 'api_token', in credentials")
+            return
+
+        client = ZendeskClient(
+            base_url=base_url,
+            access_token=access_token,
+            email=email,
+            api_token=api_token,
+        )
+
+        try:
+            await client.get_current_user()
+        except AuthenticationError as e:
+            await client.close()
+            await ctx.fail(f"Authentication failed: {e}")
+            return
+        except ZendeskError as e:
+            await client.close()
+            await ctx.fail(f"Connection test failed: {e}")
+            return
+
+        incremental = ctx.sync_mode == SyncMode.INCREMENTAL
+        if incremental or ctx.is_resume:
+            state = ZendeskSyncCheckpoint.from_mapping(checkpoint)
+        else:
+            state = ZendeskSyncCheckpoint()
+        sync_started_at = int(time.time())
+
+        logger.info(
+            "Starting Zendesk %s sync for %s (ticket cursor: %s)",
+            ctx.sync_mode.value,
+            base_url,
+            "resumed" if state.ticket_cursor else "none",
+        )
+
+        try:
+            # Persist a run-scoped checkpoint immediately so a resumed full
+            # sync does not continue from the previous incremental cursor.
+            await ctx.save_checkpoint(state.to_json())
+
+            users, group_names = await self._sync_groups(client, ctx)
+
+            if not await self._sync_tickets(client, config, state, users, group_names, ctx):
+                await ctx.fail("Cancelled by user")
+                return
+
+            if config.include_articles:
+                if not await self._sync_articles(
+                    client, state, users, incremental, sync_started_at, ctx
+                ):
+                    await ctx.fail("Cancelled by user")
+                    return
+
+            await ctx.complete(checkpoint=state.to_json())
+            logger.info(
+                "Sync completed: %d scanned, %d emitted",
+                ctx.documents_scanned,
+                ctx.documents_emitted,
+            )
+        except AuthenticationError as e:
+            logger.error("Authentication error during sync: %s", e)
+            await ctx.fail(f"Authentication failed: {e}")
+        except Exception as e:
+            logger.exception("Sync failed with unexpected error")
+            await ctx.fail(str(e))
+        finally:
+            await client.close()
+
+    async def _sync_groups(
+        self,
+        client: ZendeskClient,
+        ctx: SyncContext,
+    ) -> tuple[dict[int, ZendeskUser], dict[int, str]]:
+        """Emit memberships of every Zendesk group and of the agents group.
+
+        Returns the agents by ID, to seed the user lookup, and group names.
+        """
+        users: dict[int, ZendeskUser] = {}
+        async for raw in client.list_agents():
+            user = ZendeskUser.from_api(raw)
+            users[user.id] = user
+
+        await ctx.emit_group_membership(
+            group_email=AGENTS_GROUP,
+            member_emails=sorted(user.email for user in users.values() if user.email),
+            group_name="Zendesk agents",
+        )
+
+        group_names: dict[int, str] = {}
+        async for group in client.list_groups():
+            if not group.get("deleted"):
+                group_names[int(group["id"])] = str(group.get("name") or group["id"])
+
+        members: dict[int, set[str]] = {group_id: set() for group_id in group_names}
+        try:
+            async for membership in client.list_group_memberships():
+                group_id = int(membership["group_id"])
+                user = users.get(int(membership["user_id"]))
+                if group_id in members and user and user.email:
+                    members[group_id].add(user.email)
+        except ForbiddenError as e:
+            # Listing memberships needs an admin; tickets then stay visible
+            # to their requester and assignee only.
+            logger.warning("Cannot list group memberships, skipping: %s", e)
+            return users, group_names
+
+        for group_id, emails in members.items():
+            await ctx.emit_group_membership(
+                group_email=group_id_for(group_id),
+                member_emails=sorted(emails),
+                group_name=group_names[group_id],
+            )
+
+        logger.info("Synced %d groups and %d agents", len(group_names), len(users))
+
+        return users, group_names
+
+    async def _sync_tickets(
+        self,
+        client: ZendeskClient,
+        config: ZendeskSourceConfig,
+        state: ZendeskSyncCheckpoint,
+        users: dict[int, ZendeskUser],
+        group_names: dict[int, str],
+        ctx: SyncContext,
+    ) -> bool:
+        """Walk the incremental ticket export, checkpointing after each page.
+
+        Returns False if the sync was cancelled.
+        """
+        agent_url = config.base_url or ""
+        count = 0
+
+        while True:
+            if ctx.is_cancelled():
+                return False
+
+            page = await client.export_tickets(cursor=state.ticket_cursor)
+            tickets = page.get("tickets", [])
+            await self._resolve_users(
+                client, users, (user_id for t in tickets for user_id in ticket_user_ids(t))
+            )
+
+            for ticket in tickets:
+                if ctx.is_cancelled():
+                    return False
+
+                await ctx.increment_scanned()
+                count += 1
+                try:
+                    await self._process_ticket(
+                        client, config, ticket, users, group_names, agent_url, ctx
+                    )
+                except AuthenticationError:
+                    raise
+                except Exception as e:
+                    external_id = ticket_external_id(ticket.get("id", "unknown"))
+                    logger.warning("Error processing %s: %s", external_id, e)
+                    await ctx.emit_error(external_id, str(e))
+
+            if page.get("after_cursor"):
+                state.ticket_cursor = page["after_cursor"]
+            await ctx.save_checkpoint(state.to_json())
+
+            if page.get("end_of_stream") or not tickets:
+                break
+
+        logger.info("Finished syncing tickets: %d processed", count)
+        return True
+
+    async def _process_ticket(
+        self,
+        client: ZendeskClient,
+        config: ZendeskSourceConfig,
+        ticket: dict[str, Any],
+        users: dict[int, ZendeskUser],
+        group_names: dict[int, str],
+        agent_url: str,
+        ctx: SyncContext,
+    ) -> None:
+        ticket_id = ticket["id"]
+        if ticket.get("status") == "deleted":
+            await ctx.emit_deleted(ticket_external_id(ticket_id))
+            await ctx.emit_deleted(internal_notes_external_id(ticket_id))
+            return
+
+        comments = [comment async for comment in client.list_ticket_comments(ticket_id)]
+        await self._resolve_users(
+            client, users, (c["author_id"] for c in comments if c.get("author_id"))
+        )
+        public = [c for c in comments if c.get("public", True)]
+        internal = [c for c in comments if not c.get("public", True)]
+
+        content = generate_ticket_content(ticket, public, users, group_names)
+        content_id = await ctx.content_storage.save(content, "text/plain")
+        await ctx.emit(map_ticket_to_document(ticket, content_id, users, agent_url))
+
+        if config.include_internal_notes and internal:
+            content = generate_internal_notes_content(ticket, internal, users)
+            content_id = await ctx.content_storage.save(content, "text/plain")
+            await ctx.emit(map_internal_notes_to_document(ticket, content_id, users, agent_url))
+
+    async def _sync_articles(
+        self,
+        client: ZendeskClient,
+        state: ZendeskSyncCheckpoint,
+        users: dict[int, ZendeskUser],
+        incremental: bool,
+        sync_started_at: int,
+        ctx: SyncContext,
+    ) -> bool:
+        """Sync help-center articles. Returns False if the sync was cancelled."""
+        count = 0
+        try:
+            if incremental and state.articles_start_time is not None:
+                async for page in client.export_articles(state.articles_start_time):
+                    for article in page.get("articles", []):
+                        if ctx.is_cancelled():
+                            return False
+                        count += 1
+                        await self._process_article(client, article, users, True, ctx)
+                    if page.get("end_time"):
+                        state.articles_start_time = int(page["end_time"])
+            else:
+                async for article in client.list_articles():
+                    if ctx.is_cancelled():
+                        return False
+                    count += 1
+                    await self._process_article(client, article, users, False, ctx)
+                state.articles_start_time = sync_started_at
+        except ForbiddenError as e:
+            # Help Center may be disabled for the account.
+            logger.warning("Cannot access help center articles, skipping: %s", e)
+            await ctx.emit_error("article:*", f"Failed to fetch articles: {e}")
+            return True
+
+        logger.info("Finished syncing articles: %d processed", count)
+        return True
+
+    async def _process_article(
+        self,
+        client: ZendeskClient,
+        article: dict[str, Any],
+        users: dict[int, ZendeskUser],
+        incremental: bool,
+        ctx: SyncContext,
+    ) -> None:
+        await ctx.increment_scanned()
+        external_id = article_external_id(article.get("id", "unknown"))
+        try:
+            if article.get("draft"):
+                # A published article moved back to draft drops out of search.
+                if incremental:
+                    await ctx.emit_deleted(external_id)
+                return
+
+            if article.get("author_id"):
+                await self._resolve_users(client, users, [article["author_id"]])
+            content = generate_article_content(article)
+            content_id = await ctx.content_storage.save(content, "text/plain")
+            await ctx.emit(map_article_to_document(article, content_id, users))
+        except AuthenticationError:
+            raise
+        except Exception as e:
+            logger.warning("Error processing %s: %s", external_id, e)
+            await ctx.emit_error(external_id, str(e))
+
+    async def _resolve_users(
+        self,
+        client: ZendeskClient,
+        users: dict[int, ZendeskUser],
+        user_ids: Iterable[int],
+    ) -> None:
+        """Look up users not seen yet, in batches."""
+        missing = sorted({int(user_id) for user_id in user_ids} - users.keys())
+        if not missing:
+            return
+        for raw in await client.get_users(missing):
+            user = ZendeskUser.from_api(raw)
+            users[user.id] = user
+        # Deleted users are not returned; remember them so they are not
+        # looked up again for every ticket.
+        for user_id in missing:
+            users.setdefault(user_id, ZendeskUser(user_id, str(user_id), None, "end-user"))
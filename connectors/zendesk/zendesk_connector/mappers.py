@@ -0,0 +1,264 @@
+"""Map Zendesk tickets and help-center articles to Omni Documents."""
+
+from __future__ import annotations
+
+import html
+import re
+from datetime import datetime
+from typing import Any
+
+from omni_connector import Document, DocumentMetadata, DocumentPermissions
+
+from .config import AGENTS_GROUP, MAX_CONTENT_LENGTH
+from .models import ZendeskUser
+
+_BLOCK_TAG_RE = re.compile(
+    r"</?(?:p|div|br|li|ul|ol|h[1-6]|tr|table|blockquote|pre)\b[^>]*>", re.IGNORECASE
+)
+_HTML_TAG_RE = re.compile(r"<[^>]+>")
+_WHITESPACE_RE = re.compile(r"\s+")
+_BLANK_LINES_RE = re.compile(r"\n{3,}")
+
+
+def strip_html(value: str) -> str:
+    """Naive HTML to text for article and comment bodies, keeping block
+    elements on their own lines."""
+    text = _BLOCK_TAG_RE.sub("\n", value)
+    text = html.unescape(_HTML_TAG_RE.sub(" ", text))
+    lines = [_WHITESPACE_RE.sub(" ", line).strip() for line in text.splitlines()]
+    return _BLANK_LINES_RE.sub("\n\n", "\n".join(lines)).strip()
+
+
+def group_id_for(zendesk_group_id: int | str) -> str:
+    return f"zendesk:group:{zendesk_group_id}"
+
+
+def ticket_external_id(ticket_id: int | str) -> str:
+    return f"ticket:{ticket_id}"
+
+
+def internal_notes_external_id(ticket_id: int | str) -> str:
+    return f"ticket:{ticket_id}:internal"
+
+
+def article_external_id(article_id: int | str) -> str:
+    return f"article:{article_id}"
+
+
+def ticket_user_ids(ticket: dict[str, Any]) -> set[int]:
+    """IDs of every user a ticket document is shared with or names."""
+    ids = {
+        ticket.get("requester_id"),
+        ticket.get("submitter_id"),
+        ticket.get("assignee_id"),
+        *(ticket.get("collaborator_ids") or []),
+        *(ticket.get("follower_ids") or []),
+    }
+    return {int(user_id) for user_id in ids if user_id is not None}
+
+
+def ticket_permissions(
+    ticket: dict[str, Any],
+    users: dict[int, ZendeskUser],
+) -> DocumentPermissions:
+    """Requester, submitter, CCs, followers and assignee by email, plus the
+    ticket's group."""
+    emails = {
+        user.email
+        for user_id in ticket_user_ids(ticket)
+        if (user := users.get(user_id)) and user.email
+    }
+    groups = [group_id_for(ticket["group_id"])] if ticket.get("group_id") else []
+    return DocumentPermissions(public=False, users=sorted(emails), groups=groups)
+
+
+def internal_notes_permissions(
+    ticket: dict[str, Any],
+    users: dict[int, ZendeskUser],
+) -> DocumentPermissions:
+    """Internal notes are visible to agents only: the assignee, agents among
+    the followers, and the ticket's group."""
+    agent_ids = {ticket.get("assignee_id"), *(ticket.get("follower_ids") or [])}
+    emails = {
+        user.email
+        for user_id in agent_ids
+        if user_id is not None
+        and (user := users.get(int(user_id)))
+        and user.is_agent
+        and user.email
+    }
+    groups = [group_id_for(ticket["group_id"])] if ticket.get("group_id") else [AGENTS_GROUP]
+    return DocumentPermissions(public=False, users=sorted(emails), groups=groups)
+
+
+def article_permissions(article: dict[str, Any]) -> DocumentPermissions:
+    """Articles without a user segment are public on the help center; the
+    rest are restricted to agents."""
+    if article.get("user_segment_id") is None:
+        return DocumentPermissions(public=True)
+    return DocumentPermissions(public=False, groups=[AGENTS_GROUP])
+
+
+def generate_ticket_content(
+    ticket: dict[str, Any],
+    comments: list[dict[str, Any]],
+    users: dict[int, ZendeskUser],
+    group_names: dict[int, str],
+) -> str:
+    lines = [f"Ticket #{ticket['id']}: {ticket.get('subject') or 'No subject'}", ""]
+
+    fields = [
+        ("Status", ticket.get("status")),
+        ("Priority", ticket.get("priority")),
+        ("Type", ticket.get("type")),
+        ("Requester", _user_display(ticket.get("requester_id"), users)),
+        ("Assignee", _user_display(ticket.get("assignee_id"), users)),
+        ("Group", group_names.get(ticket["group_id"]) if ticket.get("group_id") else None),
+        ("Tags", ", ".join(ticket.get("tags") or [])),
+    ]
+    for label, value in fields:
+        if value:
+            lines.append(f"{label}: {value}")
+
+    for comment in comments:
+        lines.append("")
+        author = _user_display(comment.get("author_id"), users) or "Unknown"
+        lines.append(f"--- {author} ({comment.get('created_at', '')})")
+        lines.append(_comment_body(comment))
+
+    return "\n".join(lines)[:MAX_CONTENT_LENGTH]
+
+
+def generate_internal_notes_content(
+    ticket: dict[str, Any],
+    notes: list[dict[str, Any]],
+    users: dict[int, ZendeskUser],
+) -> str:
+    lines = [f"Internal notes on ticket #{ticket['id']}: {ticket.get('subject') or 'No subject'}"]
+    for note in notes:
+        lines.append("")
+        author = _user_display(note.get("author_id"), users) or "Unknown"
+        lines.append(f"--- {author} ({note.get('created_at', '')})")
+        lines.append(_comment_body(note))
+    return "\n".join(lines)[:MAX_CONTENT_LENGTH]
+
+
+def generate_article_content(article: dict[str, Any]) -> str:
+    title = article.get("title") or "Untitled article"
+    body = strip_html(article.get("body") or "")
+    return f"{title}\n\n{body}"[:MAX_CONTENT_LENGTH]
+
+
+def map_ticket_to_document(
+    ticket: dict[str, Any],
+    content_id: str,
+    users: dict[int, ZendeskUser],
+    agent_url: str,
+) -> Document:
+    ticket_id = ticket["id"]
+    requester = users.get(int(ticket["requester_id"])) if ticket.get("requester_id") else None
+
+    return Document(
+        external_id=ticket_external_id(ticket_id),
+        title=f"#{ticket_id} {ticket.get('subject') or 'No subject'}",
+        content_id=content_id,
+        metadata=DocumentMetadata(
+            author=requester.name if requester else None,
+            created_at=_parse_timestamp(ticket.get("created_at")),
+            updated_at=_parse_timestamp(ticket.get("updated_at")),
+            url=f"{agent_url}/agent/tickets/{ticket_id}",
+            content_type="ticket",
+            mime_type="text/plain",
+        ),
+        permissions=ticket_permissions(ticket, users),
+        attributes=_ticket_attributes(ticket),
+    )
+
+
+def map_internal_notes_to_document(
+    ticket: dict[str, Any],
+    content_id: str,
+    users: dict[int, ZendeskUser],
+    agent_url: str,
+) -> Document:
+    ticket_id = ticket["id"]
+
+    return Document(
+        external_id=internal_notes_external_id(ticket_id),
+        title=f"#{ticket_id} {ticket.get('subject') or 'No subject'} (internal notes)",
+        content_id=content_id,
+        metadata=DocumentMetadata(
+            created_at=_parse_timestamp(ticket.get("created_at")),
+            updated_at=_parse_timestamp(ticket.get("updated_at")),
+            url=f"{agent_url}/agent/tickets/{ticket_id}",
+            content_type="ticket_internal_notes",
+            mime_type="text/plain",
+        ),
+        permissions=internal_notes_permissions(ticket, users),
+        attributes=_ticket_attributes(ticket),
+    )
+
+
+def map_article_to_document(
+    article: dict[str, Any],
+    content_id: str,
+    users: dict[int, ZendeskUser],
+) -> Document:
+    author = users.get(int(article["author_id"])) if article.get("author_id") else None
+
+    return Document(
+        external_id=article_external_id(article["id"]),
+        title=article.get("title") or "Untitled article",
+        content_id=content_id,
+        metadata=DocumentMetadata(
+            author=author.name if author else None,
+            created_at=_parse_timestamp(article.get("created_at")),
+            updated_at=_parse_timestamp(article.get("edited_at") or article.get("updated_at")),
+            url=article.get("html_url"),
+            content_type="article",
+            mime_type="text/plain",
+        ),
+        permissions=article_permissions(article),
+        attributes={
+            "source_type": "zendesk",
+            "object_type": "article",
+            "section_id": article.get("section_id"),
+            "locale": article.get("locale"),
+            "labels": article.get("label_names") or [],
+        },
+    )
+
+
+def _ticket_attributes(ticket: dict[str, Any]) -> dict[str, Any]:
+    return {
+        "source_type": "zendesk",
+        "object_type": "ticket",
+        "status": ticket.get("status"),
+        "priority": ticket.get("priority"),
+        "ticket_type": ticket.get("type"),
+        "tags": ticket.get("tags") or [],
+    }
+
+
+def _comment_body(comment: dict[str, Any]) -> str:
+    if plain := comment.get("plain_body"):
+        return str(plain)
+    if html_body := comment.get("html_body"):
+        return strip_html(str(html_body))
+    return str(comment.get("body") or "")
+
+
+def _user_display(user_id: Any, users: dict[int, ZendeskUser]) -> str | None:
+    if user_id is None:
+        return None
+    user = users.get(int(user_id))
+    return user.display() if user else str(user_id)
+
+
+def _parse_timestamp(value: str | None) -> datetime | None:
+    if not value:
+        return None
+    try:
+        return datetime.fromisoformat(value.replace("Z", "+00:00"))
+    except ValueError:
+        return None
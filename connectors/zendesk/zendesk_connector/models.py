@@ -0,0 +1,106 @@
+"""Typed representations of Zendesk source config and connector state."""
+
+from __future__ import annotations
+
+from dataclasses import dataclass
+from typing import Any, Mapping
+
+CHECKPOINT_VERSION = 1
+
+
+@dataclass(frozen=True)
+class ZendeskSourceConfig:
+    subdomain: str | None = None
+    api_url: str | None = None
+    include_articles: bool = True
+    include_internal_notes: bool = True
+
+    @classmethod
+    def from_mapping(cls, raw: Mapping[str, object]) -> "ZendeskSourceConfig":
+        subdomain = raw.get("subdomain")
+        api_url = raw.get("api_url")
+        include_articles = raw.get("include_articles")
+        include_internal_notes = raw.get("include_internal_notes")
+
+        return cls(
+            subdomain=_normalize_subdomain(subdomain) if isinstance(subdomain, str) else None,
+            api_url=api_url if isinstance(api_url, str) and api_url else None,
+            include_articles=include_articles if isinstance(include_articles, bool) else True,
+            include_internal_notes=(
+                include_internal_notes if isinstance(include_internal_notes, bool) else True
+            ),
+        )
+
+    @property
+    def base_url(self) -> str | None:
+        if self.api_url:
+            return self.api_url.rstrip("/")
+        if self.subdomain:
+            return f"https://{self.subdomain}.zendesk.com"
+        return None
+
+
+@dataclass
+class ZendeskSyncCheckpoint:
+    """Progress through the incremental exports.
+
+    `ticket_cursor` is the `after_cursor` of the last ticket export page
+    processed; the next run continues from it. `articles_start_time` is the
+    unix time help-center articles were last exported up to.
+    """
+
+    ticket_cursor: str | None = None
+    articles_start_time: int | None = None
+
+    @classmethod
+    def from_mapping(cls, raw: Mapping[str, Any] | None) -> "ZendeskSyncCheckpoint":
+        if not raw or raw.get("version") != CHECKPOINT_VERSION:
+            return cls()
+        ticket_cursor = raw.get("ticket_cursor")
+        articles_start_time = raw.get("articles_start_time")
+        return cls(
+            ticket_cursor=ticket_cursor if isinstance(ticket_cursor, str) else None,
+            articles_start_time=(
+                articles_start_time if isinstance(articles_start_time, int) else None
+            ),
+        )
+
+    def to_json(self) -> dict[str, Any]:
+        return {
+            "version": CHECKPOINT_VERSION,
+            "ticket_cursor": self.ticket_cursor,
+            "articles_start_time": self.articles_start_time,
+        }
+
+
+@dataclass(frozen=True)
+class ZendeskUser:
+    id: int
+    name: str
+    email: str | None
+    role: str
+
+    @classmethod
+    def from_api(cls, raw: Mapping[str, Any]) -> "ZendeskUser":
+        email = raw.get("email")
+        return cls(
+            id=int(raw["id"]),
+            name=str(raw.get("name") or raw["id"]),
+            email=email.lower() if isinstance(email, str) and email else None,
+            role=str(raw.get("role") or "end-user"),
+        )
+
+    @property
+    def is_agent(self) -> bool:
+        return self.role in ("agent", "admin")
+
+    def display(self) -> str:
+        return f"{self.name} <{self.email}>" if self.email else self.name
+
+
+def _normalize_subdomain(value: str) -> str | None:
+    """Accept `acme`, `acme.zendesk.com` or `https://acme.zendesk.com`."""
+    value = value.strip().lower()
+    value = value.removeprefix("https://").removeprefix("http://")
+    value = value.split("/", 1)[0].removesuffix(".zendesk.com")
+    return value or None
@@ -197,6 +197,25 @@ resource "aws_service_discovery_service" "hubspot_connector" {
   }
 }
 
+resource "aws_service_discovery_service" "zendesk_connector" {
+  count = contains(var.enabled_connectors, "zendesk") ? 1 : 0
+
+  name = "zendesk-connector"
+
+  dns_config {
+    namespace_id = var.service_discovery_namespace_id
+
+    dns_records {
+      ttl  = 300
+      type = "A"
+    }
+  }
+
+  health_check_custom_config {
+    failure_threshold = 1
+  }
+}
+
 resource "aws_service_discovery_service" "google_ads_connector" {
   count = contains(var.enabled_connectors, "google_ads") ? 1 : 0
 
@@ -387,6 +406,44 @@ resource "aws_service_discovery_service" "nextcloud_connector" {
   }
 }
 
+resource "aws_service_discovery_service" "s3_connector" {
+  count = contains(var.enabled_connectors, "s3") ? 1 : 0
+
+  name = "s3-connector"
+
+  dns_config {
+    namespace_id = var.service_discovery_namespace_id
+
+    dns_records {
+      ttl  = 300
+      type = "A"
+    }
+  }
+
+  health_check_custom_config {
+    failure_threshold = 1
+  }
+}
+
+resource "aws_service_discovery_service" "push_connector" {
+  count = contains(var.enabled_connectors, "push") ? 1 : 0
+
+  name = "push-connector"
+
+  dns_config {
+    namespace_id = var.service_discovery_namespace_id
+
+    dns_records {
+      ttl  = 300
+      type = "A"
+    }
+  }
+
+  health_check_custom_config {
+    failure_threshold = 1
+  }
+}
+
 resource "aws_service_discovery_service" "paperless_connector" {
   count = contains(var.enabled_connectors, "paperless") ? 1 : 0
 
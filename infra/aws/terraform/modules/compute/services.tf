@@ -291,6 +291,33 @@ resource "aws_ecs_service" "hubspot_connector" {
   })
 }
 
+# Zendesk Connector Service
+resource "aws_ecs_service" "zendesk_connector" {
+  count = contains(var.enabled_connectors, "zendesk") ? 1 : 0
+
+  name            = "omni-${var.customer_name}-zendesk-connector"
+  cluster         = var.cluster_arn
+  task_definition = aws_ecs_task_definition.zendesk_connector[0].arn
+  launch_type     = "FARGATE"
+  desired_count   = var.desired_count
+
+  enable_execute_command = true
+
+  network_configuration {
+    security_groups  = [var.security_group_id]
+    subnets          = var.subnet_ids
+    assign_public_ip = false
+  }
+
+  service_registries {
+    registry_arn = aws_service_discovery_service.zendesk_connector[0].arn
+  }
+
+  tags = merge(local.common_tags, {
+    Name = "omni-${var.customer_name}-zendesk-connector"
+  })
+}
+
 # Google Ads Connector Service
 resource "aws_ecs_service" "google_ads_connector" {
   count = contains(var.enabled_connectors, "google_ads") ? 1 : 0
@@ -561,6 +588,60 @@ resource "aws_ecs_service" "nextcloud_connector" {
   })
 }
 
+# S3 Connector Service
+resource "aws_ecs_service" "s3_connector" {
+  count = contains(var.enabled_connectors, "s3") ? 1 : 0
+
+  name            = "omni-${var.customer_name}-s3-connector"
+  cluster         = var.cluster_arn
+  task_definition = aws_ecs_task_definition.s3_connector[0].arn
+  launch_type     = "FARGATE"
+  desired_count   = var.desired_count
+
+  enable_execute_command = true
+
+  network_configuration {
+    security_groups  = [var.security_group_id]
+    subnets          = var.subnet_ids
+    assign_public_ip = false
+  }
+
+  service_registries {
+    registry_arn = aws_service_discovery_service.s3_connector[0].arn
+  }
+
+  tags = merge(local.common_tags, {
+    Name = "omni-${var.customer_name}-s3-connector"
+  })
+}
+
+# Push Connector Service
+resource "aws_ecs_service" "push_connector" {
+  count = contains(var.enabled_connectors, "push") ? 1 : 0
+
+  name            = "omni-${var.customer_name}-push-connector"
+  cluster         = var.cluster_arn
+  task_definition = aws_ecs_task_definition.push_connector[0].arn
+  launch_type     = "FARGATE"
+  desired_count   = var.desired_count
+
+  enable_execute_command = true
+
+  network_configuration {
+    security_groups  = [var.security_group_id]
+    subnets          = var.subnet_ids
+    assign_public_ip = false
+  }
+
+  service_registries {
+    registry_arn = aws_service_discovery_service.push_connector[0].arn
+  }
+
+  tags = merge(local.common_tags, {
+    Name = "omni-${var.customer_name}-push-connector"
+  })
+}
+
 # Paperless Connector Service
 resource "aws_ecs_service" "paperless_connector" {
   count = contains(var.enabled_connectors, "paperless") ? 1 : 0
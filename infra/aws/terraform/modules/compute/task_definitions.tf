@@ -612,6 +612,50 @@ resource "aws_ecs_task_definition" "hubspot_connector" {
   })
 }
 
+# Zendesk Connector Task Definition
+resource "aws_ecs_task_definition" "zendesk_connector" {
+  count = contains(var.enabled_connectors, "zendesk") ? 1 : 0
+
+  family                   = "omni-${var.customer_name}-zendesk-connector"
+  network_mode             = "awsvpc"
+  requires_compatibilities = ["FARGATE"]
+  cpu                      = var.task_cpu
+  memory                   = var.task_memory
+  execution_role_arn       = aws_iam_role.ecs_task_execution.arn
+  task_role_arn            = aws_iam_role.ecs_task.arn
+
+  container_definitions = jsonencode([{
+    name      = "omni-zendesk-connector"
+    image     = "ghcr.io/${var.github_org}/omni/omni-zendesk-connector:latest"
+    essential = true
+
+    portMappings = [{
+      containerPort = 4018
+      protocol      = "tcp"
+    }]
+
+    logConfiguration = {
+      logDriver = "awslogs"
+      options = {
+        "awslogs-group"         = var.log_group_name
+        "awslogs-region"        = var.region
+        "awslogs-stream-prefix" = "zendesk-connector"
+      }
+    }
+
+    environment = concat(local.connector_base_environment, [
+      { name = "PORT", value = "4018" },
+      { name = "CONNECTOR_HOST_NAME", value = "zendesk-connector" }
+    ])
+
+    secrets = []
+  }])
+
+  tags = merge(local.common_tags, {
+    Name = "omni-${var.customer_name}-zendesk-connector"
+  })
+}
+
 # Google Ads Connector Task Definition
 resource "aws_ecs_task_definition" "google_ads_connector" {
   count = contains(var.enabled_connectors, "google_ads") ? 1 : 0
@@ -1051,6 +1095,94 @@ resource "aws_ecs_task_definition" "nextcloud_connector" {
   })
 }
 
+# S3 Connector Task Definition
+resource "aws_ecs_task_definition" "s3_connector" {
+  count = contains(var.enabled_connectors, "s3") ? 1 : 0
+
+  family                   = "omni-${var.customer_name}-s3-connector"
+  network_mode             = "awsvpc"
+  requires_compatibilities = ["FARGATE"]
+  cpu                      = var.task_cpu
+  memory                   = var.task_memory
+  execution_role_arn       = aws_iam_role.ecs_task_execution.arn
+  task_role_arn            = aws_iam_role.ecs_task.arn
+
+  container_definitions = jsonencode([{
+    name      = "omni-s3-connector"
+    image     = "ghcr.io/${var.github_org}/omni/omni-s3-connector:latest"
+    essential = true
+
+    portMappings = [{
+      containerPort = 4019
+      protocol      = "tcp"
+    }]
+
+    logConfiguration = {
+      logDriver = "awslogs"
+      options = {
+        "awslogs-group"         = var.log_group_name
+        "awslogs-region"        = var.region
+        "awslogs-stream-prefix" = "s3-connector"
+      }
+    }
+
+    environment = concat(local.connector_base_environment, [
+      { name = "PORT", value = "4019" },
+      { name = "CONNECTOR_HOST_NAME", value = "s3-connector" }
+    ])
+
+    secrets = []
+  }])
+
+  tags = merge(local.common_tags, {
+    Name = "omni-${var.customer_name}-s3-connector"
+  })
+}
+
+# Push Connector Task Definition
+resource "aws_ecs_task_definition" "push_connector" {
+  count = contains(var.enabled_connectors, "push") ? 1 : 0
+
+  family                   = "omni-${var.customer_name}-push-connector"
+  network_mode             = "awsvpc"
+  requires_compatibilities = ["FARGATE"]
+  cpu                      = var.task_cpu
+  memory                   = var.task_memory
+  execution_role_arn       = aws_iam_role.ecs_task_execution.arn
+  task_role_arn            = aws_iam_role.ecs_task.arn
+
+  container_definitions = jsonencode([{
+    name      = "omni-push-connector"
+    image     = "ghcr.io/${var.github_org}/omni/omni-push-connector:latest"
+    essential = true
+
+    portMappings = [{
+      containerPort = 4020
+      protocol      = "tcp"
+    }]
+
+    logConfiguration = {
+      logDriver = "awslogs"
+      options = {
+        "awslogs-group"         = var.log_group_name
+        "awslogs-region"        = var.region
+        "awslogs-stream-prefix" = "push-connector"
+      }
+    }
+
+    environment = concat(local.connector_base_environment, [
+      { name = "PORT", value = "4020" },
+      { name = "CONNECTOR_HOST_NAME", value = "push-connector" }
+    ])
+
+    secrets = []
+  }])
+
+  tags = merge(local.common_tags, {
+    Name = "omni-${var.customer_name}-push-connector"
+  })
+}
+
 # Paperless Connector Task Definition
 resource "aws_ecs_task_definition" "paperless_connector" {
   count = contains(var.enabled_connectors, "paperless") ? 1 : 0
@@ -10,6 +10,7 @@ locals {
     "google-conn", "slack-conn", "atlassian-conn", "web-conn",
     "github-conn", "hubspot-conn", "google-ads-conn", "microsoft-conn", "notion-conn", "fireflies-conn",
     "imap-conn", "clickup-conn", "linear-conn", "filesystem-conn", "nextcloud-conn", "paperless-conn",
+    "zendesk-conn", "s3-conn", "push-conn",
   ] : name => "https://omni-${var.customer_name}-${name}-${local.project_number}.${var.region}.run.app" }
 
   db_env = {
@@ -53,6 +54,7 @@ locals {
     hubspot    = { port = 4006, image = "omni-hubspot-connector", extra_env = {} }
     google_ads = { port = 4016, image = "omni-google_ads-connector", extra_env = {} }
     darwinbox  = { port = 4017, image = "omni-darwinbox-connector", extra_env = {} }
+    zendesk    = { port = 4018, image = "omni-zendesk-connector", extra_env = {} }
     microsoft  = { port = 4007, image = "omni-microsoft-connector", extra_env = {} }
     notion     = { port = 4008, image = "omni-notion-connector", extra_env = {} }
     fireflies  = { port = 4009, image = "omni-fireflies-connector", extra_env = {} }
@@ -62,6 +64,8 @@ locals {
     filesystem = { port = 4013, image = "omni-filesystem-connector", extra_env = {} }
     nextcloud  = { port = 4014, image = "omni-nextcloud-connector", extra_env = {} }
     paperless  = { port = 4015, image = "omni-paperless-connector", extra_env = {} }
+    s3         = { port = 4019, image = "omni-s3-connector", extra_env = {} }
+    push       = { port = 4020, image = "omni-push-connector", extra_env = {} }
   }
 
   simple_connectors = { for k, v in local.all_simple_connectors : k => v if contains(var.enabled_connectors, k) }
@@ -0,0 +1,40 @@
+#!/usr/bin/env sh
+set -eu
+
+ENV_FILE="${1:-.env}"
+
+usage() {
+  cat <<'USAGE'
+Generate the key Omni's services authenticate to each other with and write it
+to INTERNAL_AUTH_KEY in an env file (default: .env). A key already set there
+is kept.
+
+Usage: generate-internal-auth-key.sh [ENV_FILE]
+USAGE
+}
+
+case "$ENV_FILE" in
+  -h|--help)
+    usage
+    exit 0
+    ;;
+esac
+
+if [ ! -f "$ENV_FILE" ]; then
+  echo "error: $ENV_FILE not found; copy .env.example to it first" >&2
+  exit 1
+fi
+
+if grep -Eq '^INTERNAL_AUTH_KEY=.+' "$ENV_FILE"; then
+  echo "INTERNAL_AUTH_KEY is already set in $ENV_FILE"
+  exit 0
+fi
+
+KEY="$(openssl rand -hex 32)"
+if grep -q '^INTERNAL_AUTH_KEY=' "$ENV_FILE"; then
+  sed "s/^INTERNAL_AUTH_KEY=.*/INTERNAL_AUTH_KEY=$KEY/" "$ENV_FILE" > "$ENV_FILE.tmp"
+  mv "$ENV_FILE.tmp" "$ENV_FILE"
+else
+  printf '\nINTERNAL_AUTH_KEY=%s\n' "$KEY" >> "$ENV_FILE"
+fi
+echo "Wrote a new INTERNAL_AUTH_KEY to $ENV_FILE"
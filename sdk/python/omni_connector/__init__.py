@@ -14,6 +14,7 @@ from .models import (
     ActionResponse,
     CancelRequest,
     CancelResponse,
+    ConnectorErrorCategory,
     ConnectorEvent,
     ConnectorManifest,
     ConnectorSkillDefinition,
@@ -29,6 +30,7 @@ from .models import (
     OAuthCredentialReadyRequest,
     OAuthManifestConfig,
     OAuthScopeSet,
+    PermissionsChangedEvent,
     SdkSourceSyncData,
     SearchOperator,
     SkillRequest,
@@ -53,8 +55,10 @@ __all__ = [
     "DocumentMetadata",
     "DocumentPermissions",
     "ConnectorEvent",
+    "ConnectorErrorCategory",
     "DocumentEvent",
     "GroupMembershipSyncEvent",
+    "PermissionsChangedEvent",
     "EventType",
     "ActionDefinition",
     "ActionRequest",
@@ -7,10 +7,21 @@ import httpx
 from pydantic import ValidationError
 
 from .exceptions import SdkClientError, ServiceOverloadedError
-from .models import ConnectorEvent, SdkSourceSyncData
+from .models import ConnectorErrorCategory, ConnectorEvent, SdkSourceSyncData
 
 logger = logging.getLogger(__name__)
 
+SERVICE_KEY_HEADER = "x-omni-service-key"
+
+
+def service_key_headers() -> dict[str, str]:
+    """Headers authenticating this connector to the connector-manager when it
+    has internal auth enabled."""
+    key = os.environ.get("CONNECTOR_INTERNAL_AUTH_KEY") or os.environ.get(
+        "INTERNAL_AUTH_KEY"
+    )
+    return {SERVICE_KEY_HEADER: key.strip()} if key and key.strip() else {}
+
 
 class SdkClient:
     """HTTP client for communicating with connector-manager SDK endpoints."""
@@ -30,7 +41,9 @@ class SdkClient:
 
     async def _get_client(self) -> httpx.AsyncClient:
         if self._client is None:
-            self._client = httpx.AsyncClient(timeout=self._timeout)
+            self._client = httpx.AsyncClient(
+                timeout=self._timeout, headers=service_key_headers()
+            )
         return self._client
 
     async def fetch_source_sync_data(self, source_id: str) -> SdkSourceSyncData:
@@ -336,14 +349,24 @@ class SdkClient:
                 f"Failed to complete: {response.status_code} - {response.text}"
             )
 
-    async def fail(self, sync_run_id: str, error: str) -> None:
-        """Mark sync as failed."""
+    async def fail(
+        self,
+        sync_run_id: str,
+        error: str,
+        category: ConnectorErrorCategory | None = None,
+    ) -> None:
+        """Mark sync as failed. Without a category, the connector manager
+        infers one from the error message."""
         logger.info("SDK: Failing sync_run=%s: %s", sync_run_id, error)
 
+        payload: dict[str, Any] = {"error": error}
+        if category is not None:
+            payload["category"] = category.value
+
         client = await self._get_client()
         response = await client.post(
             f"{self.base_url}/sdk/sync/{sync_run_id}/fail",
-            json={"error": error},
+            json=payload,
         )
 
         if not response.is_success:
@@ -351,6 +374,39 @@ class SdkClient:
                 f"Failed to mark as failed: {response.status_code} - {response.text}"
             )
 
+    async def report_error(
+        self,
+        source_id: str,
+        category: ConnectorErrorCategory,
+        message: str,
+        sync_run_id: str | None = None,
+        details: dict[str, Any] | None = None,
+    ) -> None:
+        """Report an error for a source that admins should see in its health,
+        such as expired credentials, without failing a sync."""
+        logger.debug(
+            "SDK: Reporting %s error for source=%s: %s",
+            category.value,
+            source_id,
+            message,
+        )
+
+        client = await self._get_client()
+        response = await client.post(
+            f"{self.base_url}/sdk/source/{source_id}/errors",
+            json={
+                "sync_run_id": sync_run_id,
+                "category": category.value,
+                "message": message,
+                "details": details,
+            },
+        )
+
+        if not response.is_success:
+            raise SdkClientError(
+                f"Failed to report error: {response.status_code} - {response.text}"
+            )
+
     async def register(self, manifest: dict) -> None:
         """Register this connector with the connector manager."""
         logger.debug("SDK: Registering connector")
@@ -5,11 +5,14 @@ from typing import Any
 
 from .client import SdkClient
 from .models import (
+    ConnectorErrorCategory,
     ConnectorEvent,
     DocumentEvent,
     Document,
+    DocumentPermissions,
     EventType,
     GroupMembershipSyncEvent,
+    PermissionsChangedEvent,
     SyncMode,
     UserFilterMode,
 )
@@ -225,6 +228,26 @@ class SyncContext:
         )
         await self._buffer_event(event)
 
+    async def emit_permissions_changed(
+        self,
+        external_id: str,
+        permissions: DocumentPermissions,
+        descendants_path: str | None = None,
+    ) -> None:
+        """Update who can see a document without re-indexing its content.
+
+        Pass `descendants_path` (e.g. a folder's path) to apply the same
+        permissions to every document stored under that path.
+        """
+        event = PermissionsChangedEvent(
+            sync_run_id=self._sync_run_id,
+            source_id=self._source_id,
+            document_id=external_id,
+            permissions=permissions,
+            descendants_path=descendants_path,
+        )
+        await self._buffer_event(event)
+
     async def emit_error(self, external_id: str, error: str) -> None:
         """Report non-fatal error for a specific document. Sync continues."""
         logger.warning("Document error for %s: %s", external_id, error)
@@ -265,8 +288,11 @@ class SyncContext:
             None,
         )
 
-    async def fail(self, error: str) -> None:
-        """Mark sync as failed with error message.
+    async def fail(
+        self, error: str, category: ConnectorErrorCategory | None = None
+    ) -> None:
+        """Mark sync as failed with error message, and optionally the category
+        of the failure, such as rejected credentials.
 
         Best-effort flush of buffered events first — a flush failure is
         logged and swallowed so we always mark the sync as failed.
@@ -279,7 +305,29 @@ class SyncContext:
                 self._sync_run_id,
                 e,
             )
-        await self._client.fail(self._sync_run_id, error)
+        await self._client.fail(self._sync_run_id, error, category)
+
+    async def report_error(
+        self, category: ConnectorErrorCategory, message: str
+    ) -> None:
+        """Report an error that doesn't fail the sync, such as a document that
+        couldn't be read, so it shows in the source's health.
+
+        Best-effort: a failed report is logged and dropped.
+        """
+        try:
+            await self._client.report_error(
+                self._source_id,
+                category,
+                message,
+                sync_run_id=self._sync_run_id,
+            )
+        except Exception as e:
+            logger.warning(
+                "Failed to report error for sync_run=%s: %s",
+                self._sync_run_id,
+                e,
+            )
 
     def is_cancelled(self) -> bool:
         """Check if sync was cancelled. Connector should poll this periodically."""
@@ -12,6 +12,18 @@ class SyncMode(str, Enum):
     REALTIME = "realtime"
 
 
+class ConnectorErrorCategory(str, Enum):
+    """Broad cause of a connector error, shown in the source's health."""
+
+    AUTHENTICATION = "authentication"
+    PERMISSION = "permission"
+    CONFIGURATION = "configuration"
+    RATE_LIMIT = "rate_limit"
+    NETWORK = "network"
+    UPSTREAM = "upstream"
+    UNKNOWN = "unknown"
+
+
 class UserFilterMode(str, Enum):
     ALL = "all"
     WHITELIST = "whitelist"
@@ -23,6 +35,7 @@ class EventType(str, Enum):
     DOCUMENT_UPDATED = "document_updated"
     DOCUMENT_DELETED = "document_deleted"
     GROUP_MEMBERSHIP_SYNC = "group_membership_sync"
+    PERMISSIONS_CHANGED = "permissions_changed"
 
 
 class DocumentMetadata(BaseModel):
@@ -114,10 +127,41 @@ class GroupMembershipSyncEvent(BaseModel):
         return result
 
 
+class PermissionsChangedEvent(BaseModel):
+    """Permissions-only update — mirrors Rust ConnectorEvent::PermissionsChanged.
+
+    Replaces the stored permissions without re-processing content. With
+    `descendants_path`, every document of the source whose path lies under it
+    gets the same permissions.
+    """
+
+    type: Literal["permissions_changed"] = "permissions_changed"
+    sync_run_id: str
+    source_id: str
+    document_id: str
+    permissions: DocumentPermissions
+    descendants_path: str | None = None
+
+    def to_dict(self) -> dict[str, Any]:
+        """Convert to dict format matching Rust tagged enum serialization."""
+        result: dict[str, Any] = {
+            "type": self.type,
+            "sync_run_id": self.sync_run_id,
+            "source_id": self.source_id,
+            "document_id": self.document_id,
+            "permissions": self.permissions.model_dump(),
+        }
+        if self.descendants_path:
+            result["descendants_path"] = self.descendants_path
+        return result
+
+
 def _event_discriminator(v: Any) -> str:
     raw_type = v.get("type", "") if isinstance(v, dict) else getattr(v, "type", "")
     if raw_type == "group_membership_sync":
         return "group"
+    if raw_type == "permissions_changed":
+        return "permissions"
     return "document"
 
 
@@ -125,6 +169,7 @@ ConnectorEvent = Annotated[
     Union[
         Annotated[DocumentEvent, Tag("document")],
         Annotated[GroupMembershipSyncEvent, Tag("group")],
+        Annotated[PermissionsChangedEvent, Tag("permissions")],
     ],
     Discriminator(_event_discriminator),
 ]
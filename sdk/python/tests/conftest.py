@@ -33,6 +33,10 @@ def mock_connector_manager():
             return_value=Response(200, json={"status": "ok"})
         )
 
+        respx_mock.post(path__regex=r"/sdk/source/.*/errors").mock(
+            return_value=Response(200, json={"status": "ok"})
+        )
+
         respx_mock.put(path__regex=r"/sdk/source/.*/connector-state").mock(
             return_value=Response(200, json={"status": "ok"})
         )
@@ -4,6 +4,7 @@ import pytest
 from httpx import Response
 
 from omni_connector import (
+    ConnectorErrorCategory,
     ConnectorEvent,
     DocumentMetadata,
     DocumentPermissions,
@@ -152,6 +153,25 @@ async def test_fail_sends_error_message(sdk_client, mock_connector_manager):
     assert payload["error"] == "Connection timeout after 30s"
 
 
+@pytest.mark.asyncio
+async def test_report_error_sends_category(sdk_client, mock_connector_manager):
+    """Verify reported errors carry their category to the source endpoint."""
+    await sdk_client.report_error(
+        "source-1",
+        ConnectorErrorCategory.AUTHENTICATION,
+        "Token expired",
+        sync_run_id="sync-123",
+    )
+
+    call = mock_connector_manager.calls[0]
+    assert "/sdk/source/source-1/errors" in str(call.request.url)
+
+    payload = json.loads(call.request.content)
+    assert payload["category"] == "authentication"
+    assert payload["message"] == "Token expired"
+    assert payload["sync_run_id"] == "sync-123"
+
+
 @pytest.mark.asyncio
 async def test_heartbeat_uses_correct_url(sdk_client, mock_connector_manager):
     """Verify heartbeat hits the right endpoint."""
@@ -343,3 +363,17 @@ def test_client_strips_trailing_slash(monkeypatch):
     client = SdkClient.from_env()
 
     assert client.base_url == "http://localhost:9000"
+
+
+@pytest.mark.asyncio
+async def test_client_sends_service_key(monkeypatch, mock_connector_manager):
+    """Verify the internal auth key is sent when one is configured."""
+    monkeypatch.setenv("INTERNAL_AUTH_KEY", "connector-key")
+
+    from omni_connector import SdkClient
+
+    client = SdkClient(base_url="http://localhost:9000")
+    await client.heartbeat("sync-123")
+
+    call = mock_connector_manager.calls[0]
+    assert call.request.headers["x-omni-service-key"] == "connector-key"
@@ -105,6 +105,34 @@ async def test_emit_deleted_creates_document_deleted_event(
     assert "metadata" not in event
 
 
+@pytest.mark.asyncio
+async def test_emit_permissions_changed_creates_permissions_event(
+    sdk_client, mock_connector_manager
+):
+    """Verify emit_permissions_changed() carries only permissions, not content."""
+    ctx = SyncContext(
+        sdk_client=sdk_client,
+        sync_run_id="sync-123",
+        source_id="source-456",
+    )
+
+    await ctx.emit_permissions_changed(
+        "folder-1",
+        DocumentPermissions(public=True),
+        descendants_path="/Shared/Handbook",
+    )
+
+    payload = json.loads(mock_connector_manager.calls[0].request.content)
+    event = payload["event"]
+
+    assert event["type"] == "permissions_changed"
+    assert event["document_id"] == "folder-1"
+    assert event["permissions"] == {"public": True, "users": [], "groups": []}
+    assert event["descendants_path"] == "/Shared/Handbook"
+    assert "content_id" not in event
+    assert ctx.documents_emitted == 0
+
+
 @pytest.mark.asyncio
 async def test_complete_sends_correct_counts(sdk_client, mock_connector_manager):
     """Verify complete() sends accurate document counts."""
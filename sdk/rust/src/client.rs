@@ -1,5 +1,6 @@
 use anyhow::Result;
 use reqwest::{Client, Response, StatusCode};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -7,7 +8,13 @@ use std::time::{Duration, Instant};
 use tokio::sync::Mutex;
 use tracing::{debug, warn};
 
-use shared::models::{ConnectorEvent, ConnectorManifest, ServiceCredential, Source, SyncType};
+use crate::incremental::{ChangeMarker, ChangeTracker, Versioned};
+use crate::models::PushKeyIdentity;
+use shared::RateLimitStats;
+use shared::models::{
+    ConnectorErrorCategory, ConnectorEvent, ConnectorManifest, ServiceCredential, Source, SyncType,
+};
+use shared::service_auth::internal::InternalAuthConfig;
 
 /// Errors produced by [`SdkClient`]. Callers that use `anyhow::Result` can
 /// still bubble these up via `?` because `anyhow::Error: From<E>` for any
@@ -31,6 +38,11 @@ impl SdkError {
         matches!(self, SdkError::Http { status, .. } if *status == StatusCode::NOT_FOUND)
     }
 
+    /// A compare-and-swap lost to another writer.
+    pub fn is_conflict(&self) -> bool {
+        matches!(self, SdkError::Http { status, .. } if *status == StatusCode::CONFLICT)
+    }
+
     pub fn status(&self) -> Option<StatusCode> {
         match self {
             SdkError::Http { status, .. } => Some(*status),
@@ -89,7 +101,8 @@ fn thresholds_for(sync_type: SyncType) -> (usize, Option<Duration>) {
 /// The SDK learns each sync's type from `create_sync_run` (auto-registered) or
 /// from an explicit `register_sync` call (used by connectors whose sync was
 /// created by connector-manager, e.g. scheduled or webhook-triggered syncs).
-/// Unknown sync_run_ids default to `Incremental` — safe middle ground.
+/// Unknown sync_run_ids default to `Incremental` — safe middle ground. The
+/// registration is dropped once the sync completes or fails.
 ///
 /// **Invariant**: any operation that persists a checkpoint or terminates a sync
 /// (`save_checkpoint`, `complete`, `fail`) must flush the relevant buffered
@@ -130,6 +143,8 @@ struct SyncConfigResponse {
 #[derive(Debug, Serialize)]
 struct FailRequest {
     error: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    category: Option<ConnectorErrorCategory>,
 }
 
 #[derive(Debug, Serialize)]
@@ -164,6 +179,28 @@ struct WebhookNotificationResponse {
     sync_run_id: String,
 }
 
+#[derive(Debug, Serialize)]
+struct AuthenticatePushKeyRequest<'a> {
+    key: &'a str,
+}
+
+#[derive(Debug, Deserialize)]
+struct SyncStateResponse<T> {
+    value: T,
+    version: i64,
+}
+
+#[derive(Debug, Serialize)]
+struct SetSyncStateRequest<'a, T> {
+    value: &'a T,
+}
+
+#[derive(Debug, Serialize)]
+struct CompareAndSwapSyncStateRequest<'a, T> {
+    expected_version: Option<i64>,
+    value: &'a T,
+}
+
 #[derive(Debug, Deserialize)]
 struct ExtractTextResponse {
     text: String,
@@ -172,7 +209,10 @@ struct ExtractTextResponse {
 impl SdkClient {
     pub fn new(connector_manager_url: &str) -> Self {
         Self {
-            client: Client::new(),
+            client: Client::builder()
+                .default_headers(InternalAuthConfig::from_env("CONNECTOR").outgoing_headers())
+                .build()
+                .expect("failed to build HTTP client"),
             base_url: connector_manager_url.trim_end_matches('/').to_string(),
             event_buffer: Arc::new(Mutex::new(HashMap::new())),
             sync_types: Arc::new(Mutex::new(HashMap::new())),
@@ -501,6 +541,36 @@ impl SdkClient {
         Ok(())
     }
 
+    /// Heartbeat that also reports what the sync is doing and how many
+    /// documents it expects to scan, for the live progress stream. `None`
+    /// keeps the previously reported value.
+    pub async fn report_progress(
+        &self,
+        sync_run_id: &str,
+        phase: Option<&str>,
+        documents_total: Option<i32>,
+    ) -> SdkResult<()> {
+        debug!(
+            "SDK: Reporting progress for sync_run={}: phase={:?}, total={:?}",
+            sync_run_id, phase, documents_total
+        );
+
+        let response = self
+            .client
+            .post(format!(
+                "{}/sdk/sync/{}/heartbeat",
+                self.base_url, sync_run_id
+            ))
+            .json(&serde_json::json!({
+                "phase": phase,
+                "documents_total": documents_total,
+            }))
+            .send()
+            .await?;
+        ensure_ok(response, "report_progress").await?;
+        Ok(())
+    }
+
     /// Increment scanned count and update heartbeat
     pub async fn increment_scanned(&self, sync_run_id: &str, count: i32) -> SdkResult<()> {
         debug!(
@@ -543,6 +613,32 @@ impl SdkClient {
         Ok(())
     }
 
+    /// Report upstream rate limit usage for a sync run. Usage is additive:
+    /// each report should cover only what happened since the previous one.
+    pub async fn report_rate_limits(
+        &self,
+        sync_run_id: &str,
+        stats: &[RateLimitStats],
+    ) -> SdkResult<()> {
+        debug!(
+            "SDK: Reporting rate limits for sync_run={} ({} API families)",
+            sync_run_id,
+            stats.len()
+        );
+
+        let response = self
+            .client
+            .post(format!(
+                "{}/sdk/sync/{}/rate-limits",
+                self.base_url, sync_run_id
+            ))
+            .json(&serde_json::json!({ "stats": stats }))
+            .send()
+            .await?;
+        ensure_ok(response, "report_rate_limits").await?;
+        Ok(())
+    }
+
     /// Mark sync as completed. Flushes any buffered events first so the
     /// completion never races ahead of the final events for this sync.
     pub async fn complete(&self, sync_run_id: &str) -> SdkResult<()> {
@@ -559,6 +655,7 @@ impl SdkClient {
             .send()
             .await?;
         ensure_ok(response, "complete").await?;
+        self.sync_types.lock().await.remove(sync_run_id);
         Ok(())
     }
 
@@ -566,6 +663,26 @@ impl SdkClient {
     /// the flush itself fails we log and proceed, because marking the sync as
     /// failed is more important than preserving partial progress.
     pub async fn fail(&self, sync_run_id: &str, error: &str) -> SdkResult<()> {
+        self.send_fail(sync_run_id, error, None).await
+    }
+
+    /// Mark sync as failed for a known reason. Without a category, the
+    /// connector manager infers one from the error message.
+    pub async fn fail_with_category(
+        &self,
+        sync_run_id: &str,
+        category: ConnectorErrorCategory,
+        error: &str,
+    ) -> SdkResult<()> {
+        self.send_fail(sync_run_id, error, Some(category)).await
+    }
+
+    async fn send_fail(
+        &self,
+        sync_run_id: &str,
+        error: &str,
+        category: Option<ConnectorErrorCategory>,
+    ) -> SdkResult<()> {
         debug!("SDK: Failing sync_run={}: {}", sync_run_id, error);
 
         if let Err(e) = self.flush_all().await {
@@ -577,6 +694,7 @@ impl SdkClient {
 
         let request = FailRequest {
             error: error.to_string(),
+            category,
         };
 
         let response = self
@@ -586,6 +704,37 @@ impl SdkClient {
             .send()
             .await?;
         ensure_ok(response, "fail").await?;
+        self.sync_types.lock().await.remove(sync_run_id);
+        Ok(())
+    }
+
+    /// Report an error for a source that admins should see in its health,
+    /// such as expired credentials, without failing a sync.
+    pub async fn report_error(
+        &self,
+        source_id: &str,
+        sync_run_id: Option<&str>,
+        category: ConnectorErrorCategory,
+        message: &str,
+        details: Option<&serde_json::Value>,
+    ) -> SdkResult<()> {
+        debug!(
+            "SDK: Reporting {:?} error for source={}: {}",
+            category, source_id, message
+        );
+
+        let response = self
+            .client
+            .post(format!("{}/sdk/source/{}/errors", self.base_url, source_id))
+            .json(&serde_json::json!({
+                "sync_run_id": sync_run_id,
+                "category": category,
+                "message": message,
+                "details": details,
+            }))
+            .send()
+            .await?;
+        ensure_ok(response, "report_error").await?;
         Ok(())
     }
 
@@ -654,6 +803,32 @@ impl SdkClient {
         Ok(credentials)
     }
 
+    /// Write refreshed credentials back to the source's owner credential row.
+    /// `expires_at` is the new access-token expiry in unix seconds. Used by
+    /// connectors whose OAuth provider rotates refresh tokens, where losing
+    /// the new token would break the next refresh.
+    pub async fn update_credentials(
+        &self,
+        source_id: &str,
+        credentials: serde_json::Value,
+        expires_at: Option<i64>,
+    ) -> SdkResult<()> {
+        debug!("SDK: Updating credentials for source_id={}", source_id);
+
+        let response = self
+            .client
+            .put(format!("{}/sdk/credentials/{}", self.base_url, source_id))
+            .json(&serde_json::json!({
+                "credentials": credentials,
+                "expires_at": expires_at,
+            }))
+            .send()
+            .await?;
+        ensure_ok(response, "update_credentials").await?;
+
+        Ok(())
+    }
+
     /// Create a new sync run for a source.
     ///
     /// Under normal circumstances, the connector-manager is responsible for
@@ -823,6 +998,164 @@ impl SdkClient {
         Ok(())
     }
 
+    /// Resolve a push API key to the source it was issued for. Returns `None`
+    /// for unknown or revoked keys and keys of inactive sources.
+    pub async fn authenticate_push_key(&self, key: &str) -> SdkResult<Option<PushKeyIdentity>> {
+        let response = self
+            .client
+            .post(format!("{}/sdk/push-keys/authenticate", self.base_url))
+            .json(&AuthenticatePushKeyRequest { key })
+            .send()
+            .await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let response = ensure_ok(response, "authenticate_push_key").await?;
+        Ok(Some(response.json().await?))
+    }
+
+    /// Read a value from the source's sync state. Keys may contain ASCII
+    /// letters, digits and `_ - . :`.
+    pub async fn get_state<T: DeserializeOwned>(
+        &self,
+        source_id: &str,
+        key: &str,
+    ) -> SdkResult<Option<Versioned<T>>> {
+        debug!(
+            "SDK: Getting sync state {} for source_id={}",
+            key, source_id
+        );
+
+        let response = self
+            .client
+            .get(format!(
+                "{}/sdk/source/{}/state/{}",
+                self.base_url, source_id, key
+            ))
+            .send()
+            .await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let response = ensure_ok(response, "get_state").await?;
+        let state: SyncStateResponse<T> = response.json().await?;
+        Ok(Some(Versioned {
+            value: state.value,
+            version: state.version,
+        }))
+    }
+
+    /// Overwrite a value in the source's sync state. Returns its new version.
+    pub async fn set_state<T: Serialize>(
+        &self,
+        source_id: &str,
+        key: &str,
+        value: &T,
+    ) -> SdkResult<i64> {
+        debug!(
+            "SDK: Setting sync state {} for source_id={}",
+            key, source_id
+        );
+
+        let response = self
+            .client
+            .put(format!(
+                "{}/sdk/source/{}/state/{}",
+                self.base_url, source_id, key
+            ))
+            .json(&SetSyncStateRequest { value })
+            .send()
+            .await?;
+        let response = ensure_ok(response, "set_state").await?;
+        let state: SyncStateResponse<serde_json::Value> = response.json().await?;
+        Ok(state.version)
+    }
+
+    /// Write a value only if the stored version is still `expected_version`
+    /// (`None`: only if the key doesn't exist). Returns the new version, or an
+    /// error for which [`SdkError::is_conflict`] holds if someone else wrote
+    /// first — re-read and retry.
+    pub async fn compare_and_swap_state<T: Serialize>(
+        &self,
+        source_id: &str,
+        key: &str,
+        expected_version: Option<i64>,
+        value: &T,
+    ) -> SdkResult<i64> {
+        debug!(
+            "SDK: Compare-and-swap sync state {} for source_id={} (expected version {:?})",
+            key, source_id, expected_version
+        );
+
+        let response = self
+            .client
+            .post(format!(
+                "{}/sdk/source/{}/state/{}/cas",
+                self.base_url, source_id, key
+            ))
+            .json(&CompareAndSwapSyncStateRequest {
+                expected_version,
+                value,
+            })
+            .send()
+            .await?;
+        let response = ensure_ok(response, "compare_and_swap_state").await?;
+        let state: SyncStateResponse<serde_json::Value> = response.json().await?;
+        Ok(state.version)
+    }
+
+    pub async fn delete_state(&self, source_id: &str, key: &str) -> SdkResult<()> {
+        debug!(
+            "SDK: Deleting sync state {} for source_id={}",
+            key, source_id
+        );
+
+        let response = self
+            .client
+            .delete(format!(
+                "{}/sdk/source/{}/state/{}",
+                self.base_url, source_id, key
+            ))
+            .send()
+            .await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(());
+        }
+        ensure_ok(response, "delete_state").await?;
+        Ok(())
+    }
+
+    /// Load the per-item change markers stored under `key`, or an empty
+    /// tracker on the first run.
+    pub async fn load_change_tracker(
+        &self,
+        source_id: &str,
+        key: &str,
+    ) -> SdkResult<ChangeTracker> {
+        let state = self
+            .get_state::<HashMap<String, ChangeMarker>>(source_id, key)
+            .await?;
+        Ok(ChangeTracker::from_state(key, state))
+    }
+
+    /// Save a tracker if it has unsaved changes. Fails with a conflict if the
+    /// key was written since the tracker was loaded.
+    pub async fn save_change_tracker(
+        &self,
+        source_id: &str,
+        tracker: &mut ChangeTracker,
+    ) -> SdkResult<()> {
+        if !tracker.dirty {
+            return Ok(());
+        }
+        let version = self
+            .compare_and_swap_state(source_id, &tracker.key, tracker.version, &tracker.markers)
+            .await?;
+        tracker.version = Some(version);
+        tracker.dirty = false;
+        Ok(())
+    }
+
     /// Get all active sources of a given type
     pub async fn get_sources_by_type(&self, source_type: &str) -> SdkResult<Vec<Source>> {
         debug!("SDK: Getting sources by type={}", source_type);
@@ -1,9 +1,11 @@
 use crate::client::SdkClient;
+use crate::incremental::ChangeTracker;
 use anyhow::Result;
-use shared::models::{ConnectorEvent, SourceType, SyncType};
+use shared::models::{ConnectorErrorCategory, ConnectorEvent, SourceType, SyncType};
+use shared::{RateLimitStats, RateLimiter};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use tracing::warn;
+use tracing::{info, warn};
 
 #[derive(Clone)]
 pub struct SyncContext {
@@ -99,6 +101,25 @@ impl SyncContext {
         Ok(())
     }
 
+    /// Report the full member list of a group. Documents shared with
+    /// `group_email` become visible to its members, and to the members of any
+    /// group listed among them.
+    pub async fn emit_group_membership(
+        &self,
+        group_email: &str,
+        member_emails: Vec<String>,
+        group_name: Option<String>,
+    ) -> Result<()> {
+        self.emit_event(ConnectorEvent::GroupMembershipSync {
+            sync_run_id: self.sync_run_id.clone(),
+            source_id: self.source_id.clone(),
+            group_email: group_email.to_string(),
+            group_name,
+            member_emails,
+        })
+        .await
+    }
+
     /// Flush all buffered events for this (sync_run_id, source_id) pair.
     pub async fn flush(&self) -> Result<()> {
         self.sdk_client
@@ -146,6 +167,45 @@ impl SyncContext {
         Ok(())
     }
 
+    /// Report the usage of `limiters` since they were last reported, merged
+    /// per API family, against this sync run. Call before `complete` or
+    /// `fail`, and periodically during long syncs. Best-effort: a failed
+    /// report is logged and the usage is dropped.
+    pub async fn report_rate_limits(&self, limiters: &[&RateLimiter]) {
+        let taken: Vec<RateLimitStats> = limiters.iter().map(|l| l.take_stats()).collect();
+        let stats: Vec<RateLimitStats> = RateLimitStats::merge_by_family(&taken)
+            .into_iter()
+            .filter(|s| !s.is_empty())
+            .collect();
+        if stats.is_empty() {
+            return;
+        }
+        for s in &stats {
+            info!(
+                sync_run_id = %self.sync_run_id,
+                source_id = %self.source_id,
+                api_family = %s.api_family,
+                requests = s.requests,
+                wait_ms_total = s.wait_ms_total,
+                wait_ms_max = s.wait_ms_max,
+                throttled = s.throttled,
+                retries = s.retries,
+                current_rps = s.current_rps,
+                "Rate limit usage"
+            );
+        }
+        if let Err(e) = self
+            .sdk_client
+            .report_rate_limits(&self.sync_run_id, &stats)
+            .await
+        {
+            warn!(
+                "SDK: Failed to report rate limits for sync_run={}: {}",
+                self.sync_run_id, e
+            );
+        }
+    }
+
     /// Mark sync as completed. Flushes any buffered events first so the
     /// completion never races ahead of the final events for this sync.
     /// Status flip only — counts come from `increment_scanned`/`updated`,
@@ -169,11 +229,65 @@ impl SyncContext {
         Ok(())
     }
 
+    /// Like `fail`, for a known reason such as rejected credentials.
+    pub async fn fail_with_category(
+        &self,
+        category: ConnectorErrorCategory,
+        error: &str,
+    ) -> Result<()> {
+        if let Err(e) = self.flush_all().await {
+            warn!(
+                "SDK: flush before fail() failed (continuing): sync_run={}: {}",
+                self.sync_run_id, e
+            );
+        }
+        self.sdk_client
+            .fail_with_category(&self.sync_run_id, category, error)
+            .await?;
+        Ok(())
+    }
+
+    /// Report an error that doesn't fail the sync, such as a document or
+    /// channel that couldn't be read, so it shows in the source's health.
+    /// Best-effort: a failed report is logged and dropped.
+    pub async fn report_error(&self, category: ConnectorErrorCategory, message: &str) {
+        if let Err(e) = self
+            .sdk_client
+            .report_error(
+                &self.source_id,
+                Some(&self.sync_run_id),
+                category,
+                message,
+                None,
+            )
+            .await
+        {
+            warn!(
+                "SDK: Failed to report error for sync_run={}: {}",
+                self.sync_run_id, e
+            );
+        }
+    }
+
     pub async fn heartbeat(&self) -> Result<()> {
         self.sdk_client.heartbeat(&self.sync_run_id).await?;
         Ok(())
     }
 
+    /// Heartbeat with the current phase (e.g. "listing files") and, once
+    /// known, the number of documents the sync expects to scan, which lets
+    /// the UI show live progress and an ETA.
+    pub async fn report_progress(
+        &self,
+        phase: Option<&str>,
+        documents_total: Option<i32>,
+    ) -> Result<()> {
+        self.sdk_client
+            .report_progress(&self.sync_run_id, phase, documents_total)
+            .await?;
+        Ok(())
+    }
+
     pub async fn cancel(&self) -> Result<()> {
         self.sdk_client.cancel(&self.sync_run_id).await?;
         Ok(())
@@ -189,6 +303,25 @@ impl SyncContext {
         Ok(())
     }
 
+    /// Load the source's change tracker stored under `key`.
+    pub async fn load_change_tracker(&self, key: &str) -> Result<ChangeTracker> {
+        Ok(self
+            .sdk_client
+            .load_change_tracker(&self.source_id, key)
+            .await?)
+    }
+
+    /// Save a change tracker. Flushes buffered events first, for the same
+    /// reason as [`Self::save_checkpoint`]: items recorded as synced must not
+    /// have their events still sitting in the buffer.
+    pub async fn save_change_tracker(&self, tracker: &mut ChangeTracker) -> Result<()> {
+        self.flush().await?;
+        self.sdk_client
+            .save_change_tracker(&self.source_id, tracker)
+            .await?;
+        Ok(())
+    }
+
     #[deprecated(note = "use save_checkpoint")]
     pub async fn save_connector_state(&self, state: serde_json::Value) -> Result<()> {
         self.save_checkpoint(state).await
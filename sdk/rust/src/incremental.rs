@@ -0,0 +1,145 @@
+//! Helpers for incremental syncs: remember each item's ETag or modification
+//! time in the source's sync state, and skip items that haven't changed since
+//! the last run.
+//!
+//! ```ignore
+//! let mut tracker = client.load_change_tracker(&source_id, "files").await?;
+//! for file in files {
+//!     let marker = ChangeMarker::ETag(file.etag.clone());
+//!     if tracker.is_unchanged(&file.id, &marker) {
+//!         continue;
+//!     }
+//!     // ... emit the document ...
+//!     tracker.record(&file.id, marker);
+//! }
+//! client.save_change_tracker(&source_id, &mut tracker).await?;
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use time::OffsetDateTime;
+
+/// A value from a source's sync state, with the version to pass to
+/// [`crate::SdkClient::compare_and_swap_state`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Versioned<T> {
+    pub value: T,
+    pub version: i64,
+}
+
+/// What a connector knows about the revision of an item.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "value", rename_all = "snake_case")]
+pub enum ChangeMarker {
+    /// Opaque revision id; the item changed if it differs.
+    #[serde(rename = "etag")]
+    ETag(String),
+    /// Last modification time; the item changed if it moved forward.
+    ModifiedAt(#[serde(with = "time::serde::rfc3339")] OffsetDateTime),
+}
+
+impl ChangeMarker {
+    fn is_unchanged_from(&self, previous: &ChangeMarker) -> bool {
+        match (previous, self) {
+            (ChangeMarker::ETag(previous), ChangeMarker::ETag(current)) => previous == current,
+            (ChangeMarker::ModifiedAt(previous), ChangeMarker::ModifiedAt(current)) => {
+                current <= previous
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Per-item change markers stored under one sync state key. Load it with
+/// [`crate::SdkClient::load_change_tracker`] and save it back with
+/// [`crate::SdkClient::save_change_tracker`], which fails with a conflict if
+/// another run saved the same key in between.
+#[derive(Debug, Clone)]
+pub struct ChangeTracker {
+    pub(crate) key: String,
+    pub(crate) version: Option<i64>,
+    pub(crate) markers: HashMap<String, ChangeMarker>,
+    pub(crate) dirty: bool,
+}
+
+impl ChangeTracker {
+    /// An empty tracker that hasn't been stored yet.
+    pub fn new(key: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            version: None,
+            markers: HashMap::new(),
+            dirty: false,
+        }
+    }
+
+    pub(crate) fn from_state(
+        key: impl Into<String>,
+        state: Option<Versioned<HashMap<String, ChangeMarker>>>,
+    ) -> Self {
+        let mut tracker = Self::new(key);
+        if let Some(state) = state {
+            tracker.version = Some(state.version);
+            tracker.markers = state.value;
+        }
+        tracker
+    }
+
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// True if the item was recorded before with a marker showing it hasn't
+    /// changed since. Unknown items and markers of a different kind count as
+    /// changed.
+    pub fn is_unchanged(&self, item_id: &str, marker: &ChangeMarker) -> bool {
+        self.markers
+            .get(item_id)
+            .is_some_and(|previous| marker.is_unchanged_from(previous))
+    }
+
+    /// Remember an item's marker once it has been synced.
+    pub fn record(&mut self, item_id: impl Into<String>, marker: ChangeMarker) {
+        let item_id = item_id.into();
+        if self.markers.get(&item_id) != Some(&marker) {
+            self.markers.insert(item_id, marker);
+            self.dirty = true;
+        }
+    }
+
+    /// Forget an item, e.g. after emitting its deletion.
+    pub fn remove(&mut self, item_id: &str) -> bool {
+        let removed = self.markers.remove(item_id).is_some();
+        self.dirty |= removed;
+        removed
+    }
+
+    /// After a full listing, drop every item that wasn't seen and return
+    /// their ids — they were deleted upstream.
+    pub fn retain_seen(&mut self, seen: &HashSet<String>) -> Vec<String> {
+        let gone: Vec<String> = self
+            .markers
+            .keys()
+            .filter(|id| !seen.contains(*id))
+            .cloned()
+            .collect();
+        for id in &gone {
+            self.markers.remove(id);
+        }
+        self.dirty |= !gone.is_empty();
+        gone
+    }
+
+    pub fn len(&self) -> usize {
+        self.markers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.markers.is_empty()
+    }
+
+    /// Whether there are changes that haven't been saved.
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+}
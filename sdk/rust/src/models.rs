@@ -86,3 +86,10 @@ fn default_email_field() -> String {
 fn default_scope_separator() -> String {
     " ".to_string()
 }
+
+/// Source a push API key belongs to, as resolved by connector-manager.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PushKeyIdentity {
+    pub key_id: String,
+    pub source_id: String,
+}
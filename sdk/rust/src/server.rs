@@ -18,6 +18,7 @@ use axum::{
 use dashmap::mapref::entry::Entry;
 use dashmap::DashMap;
 use serde::de::DeserializeOwned;
+use shared::http_security::HttpSecurityConfig;
 use shared::models::{ConnectorSkillDefinition, SyncSlotClass, SyncType};
 use shared::telemetry;
 use std::collections::HashMap;
@@ -25,7 +26,6 @@ use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, OnceLock};
 use tokio::time::{interval, Duration};
 use tower::ServiceBuilder;
-use tower_http::cors::CorsLayer;
 use tracing::{error, info, warn};
 
 #[derive(Clone)]
@@ -187,7 +187,7 @@ where
         .layer(
             ServiceBuilder::new()
                 .layer(middleware::from_fn(telemetry::middleware::trace_layer))
-                .layer(CorsLayer::permissive()),
+                .layer(HttpSecurityConfig::from_env("CONNECTOR").layer()),
         )
         .with_state(state)
 }
@@ -0,0 +1,61 @@
+use omni_connector_sdk::{ChangeMarker, ChangeTracker};
+use serde_json::json;
+use std::collections::HashSet;
+use time::OffsetDateTime;
+
+/// 2024-03-01T12:00:00Z plus `offset` seconds.
+fn at(offset: i64) -> ChangeMarker {
+    ChangeMarker::ModifiedAt(OffsetDateTime::from_unix_timestamp(1_709_294_400 + offset).unwrap())
+}
+
+#[test]
+fn test_etag_markers_skip_only_identical_revisions() {
+    let mut tracker = ChangeTracker::new("files");
+    let marker = ChangeMarker::ETag("\"v1\"".to_string());
+
+    assert!(!tracker.is_unchanged("file-1", &marker));
+    tracker.record("file-1", marker.clone());
+    assert!(tracker.is_dirty());
+
+    assert!(tracker.is_unchanged("file-1", &marker));
+    assert!(!tracker.is_unchanged("file-1", &ChangeMarker::ETag("\"v2\"".to_string())));
+    assert!(!tracker.is_unchanged("file-2", &marker));
+}
+
+#[test]
+fn test_timestamp_markers_skip_until_modified_moves_forward() {
+    let mut tracker = ChangeTracker::new("pages");
+    tracker.record("page-1", at(0));
+
+    assert!(tracker.is_unchanged("page-1", &at(0)));
+    assert!(tracker.is_unchanged("page-1", &at(-86_400)));
+    assert!(!tracker.is_unchanged("page-1", &at(1)));
+    // Switching marker kinds always counts as a change
+    assert!(!tracker.is_unchanged("page-1", &ChangeMarker::ETag("x".to_string())));
+}
+
+#[test]
+fn test_retain_seen_reports_items_gone_upstream() {
+    let mut tracker = ChangeTracker::new("files");
+    for id in ["a", "b", "c"] {
+        tracker.record(id, ChangeMarker::ETag(id.to_string()));
+    }
+
+    let seen: HashSet<String> = ["a", "c"].iter().map(|s| s.to_string()).collect();
+    assert_eq!(tracker.retain_seen(&seen), vec!["b".to_string()]);
+    assert_eq!(tracker.len(), 2);
+    assert!(!tracker.remove("b"));
+    assert!(tracker.remove("a"));
+}
+
+#[test]
+fn test_change_marker_serialization() {
+    assert_eq!(
+        serde_json::to_value(ChangeMarker::ETag("abc".to_string())).unwrap(),
+        json!({"kind": "etag", "value": "abc"})
+    );
+    assert_eq!(
+        serde_json::to_value(at(0)).unwrap(),
+        json!({"kind": "modified_at", "value": "2024-03-01T12:00:00Z"})
+    );
+}
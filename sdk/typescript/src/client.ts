@@ -2,13 +2,26 @@ import { SdkClientError, ConfigurationError } from './errors.js';
 import {
   SdkSourceSyncDataSchema,
   serializeConnectorEvent,
+  type ConnectorErrorCategory,
   type ConnectorEventPayload,
   type SdkSourceSyncData,
 } from './models.js';
 
+export const SERVICE_KEY_HEADER = 'x-omni-service-key';
+
+/**
+ * Headers authenticating this connector to the connector-manager when it has
+ * internal auth enabled.
+ */
+function serviceKeyHeaders(): Record<string, string> {
+  const key = (process.env.CONNECTOR_INTERNAL_AUTH_KEY || process.env.INTERNAL_AUTH_KEY)?.trim();
+  return key ? { [SERVICE_KEY_HEADER]: key } : {};
+}
+
 export class SdkClient {
   private readonly baseUrl: string;
   private readonly timeout: number;
+  private readonly serviceKeyHeaders: Record<string, string>;
 
   constructor(baseUrl?: string, timeout = 30000) {
     const url = baseUrl ?? process.env.CONNECTOR_MANAGER_URL;
@@ -17,6 +30,7 @@ export class SdkClient {
     }
     this.baseUrl = url.replace(/\/$/, '');
     this.timeout = timeout;
+    this.serviceKeyHeaders = serviceKeyHeaders();
   }
 
   static fromEnv(): SdkClient {
@@ -86,6 +100,7 @@ export class SdkClient {
     const url = `${this.baseUrl}/sdk/extract-content`;
     const response = await fetch(url, {
       method: 'POST',
+      headers: this.serviceKeyHeaders,
       body: formData,
       signal: AbortSignal.timeout(this.timeout),
     });
@@ -119,6 +134,7 @@ export class SdkClient {
     const url = `${this.baseUrl}/sdk/extract-text`;
     const response = await fetch(url, {
       method: 'POST',
+      headers: this.serviceKeyHeaders,
       body: formData,
       signal: AbortSignal.timeout(this.timeout),
     });
@@ -239,8 +255,13 @@ export class SdkClient {
     }
   }
 
-  async fail(syncRunId: string, error: string): Promise<void> {
-    const response = await this.post(`/sdk/sync/${syncRunId}/fail`, { error });
+  async fail(
+    syncRunId: string,
+    error: string,
+    category?: ConnectorErrorCategory
+  ): Promise<void> {
+    const body = category === undefined ? { error } : { error, category };
+    const response = await this.post(`/sdk/sync/${syncRunId}/fail`, body);
     if (!response.ok) {
       const text = await response.text();
       throw new SdkClientError(
@@ -250,6 +271,27 @@ export class SdkClient {
     }
   }
 
+  async reportError(
+    sourceId: string,
+    category: ConnectorErrorCategory,
+    message: string,
+    options: { syncRunId?: string; details?: Record<string, unknown> } = {}
+  ): Promise<void> {
+    const response = await this.post(`/sdk/source/${sourceId}/errors`, {
+      sync_run_id: options.syncRunId,
+      category,
+      message,
+      details: options.details,
+    });
+    if (!response.ok) {
+      const text = await response.text();
+      throw new SdkClientError(
+        `Failed to report error: ${response.status} - ${text}`,
+        response.status
+      );
+    }
+  }
+
   async register(manifest: Record<string, unknown>): Promise<void> {
     const response = await this.post('/sdk/register', manifest);
     if (!response.ok) {
@@ -278,6 +320,7 @@ export class SdkClient {
     const url = `${this.baseUrl}${path}`;
     return fetch(url, {
       method: 'GET',
+      headers: this.serviceKeyHeaders,
       signal: AbortSignal.timeout(this.timeout),
     });
   }
@@ -288,6 +331,7 @@ export class SdkClient {
       method: 'PUT',
       headers: {
         'Content-Type': 'application/json',
+        ...this.serviceKeyHeaders,
       },
       signal: AbortSignal.timeout(this.timeout),
     };
@@ -303,6 +347,7 @@ export class SdkClient {
       method: 'POST',
       headers: {
         'Content-Type': 'application/json',
+        ...this.serviceKeyHeaders,
       },
       signal: AbortSignal.timeout(this.timeout),
     };
@@ -3,11 +3,13 @@ import {
   EventType,
   SyncMode,
   UserFilterMode,
+  type ConnectorErrorCategory,
   type Document,
   type DocumentMetadata,
   type DocumentPermissions,
   type ConnectorEventPayload,
   type GroupMembershipEventPayload,
+  type PermissionsChangedEventPayload,
 } from './models.js';
 import { ContentStorage } from './storage.js';
 import { getLogger } from './logger.js';
@@ -222,6 +224,27 @@ export class SyncContext {
     await this.bufferEvent(event);
   }
 
+  /**
+   * Replace a document's permissions without re-sending its content. With
+   * `descendantsPath`, documents whose `metadata.path` is under that path get
+   * the same permissions (e.g. everything inside a re-shared folder).
+   */
+  async emitPermissionsChanged(
+    externalId: string,
+    permissions: DocumentPermissions,
+    descendantsPath?: string,
+  ): Promise<void> {
+    const event: PermissionsChangedEventPayload = {
+      type: EventType.PERMISSIONS_CHANGED,
+      sync_run_id: this._syncRunId,
+      source_id: this._sourceId,
+      document_id: externalId,
+      permissions,
+      descendants_path: descendantsPath,
+    };
+    await this.bufferEvent(event);
+  }
+
   emitError(externalId: string, error: string): void {
     logger.warn(`Document error for ${externalId}: ${error}`);
   }
@@ -298,7 +321,7 @@ export class SyncContext {
     await this.client.complete(this._syncRunId);
   }
 
-  async fail(error: string): Promise<void> {
+  async fail(error: string, category?: ConnectorErrorCategory): Promise<void> {
     try {
       await this.flush();
     } catch (e) {
@@ -306,7 +329,26 @@ export class SyncContext {
         `flush before fail() failed (continuing): sync_run=${this._syncRunId}: ${e}`
       );
     }
-    await this.client.fail(this._syncRunId, error);
+    await this.client.fail(this._syncRunId, error, category);
+  }
+
+  /**
+   * Report an error that doesn't fail the sync, e.g. a rate limit the
+   * connector is backing off from. Best-effort: failures are only logged.
+   */
+  async reportError(
+    category: ConnectorErrorCategory,
+    message: string
+  ): Promise<void> {
+    try {
+      await this.client.reportError(this._sourceId, category, message, {
+        syncRunId: this._syncRunId,
+      });
+    } catch (e) {
+      logger.warn(
+        `Failed to report ${category} error: sync_run=${this._syncRunId}: ${e}`
+      );
+    }
   }
 
   isCancelled(): boolean {
@@ -38,6 +38,7 @@ export {
   ActionResponse,
   serializeConnectorEvent,
   UserFilterMode,
+  ConnectorErrorCategory,
   SdkSourceSyncDataSchema,
   type DocumentMetadata,
   type DocumentPermissions,
@@ -55,6 +56,7 @@ export {
   type CancelResponse,
   type ActionRequest,
   type ConnectorEventPayload,
+  type PermissionsChangedEventPayload,
   type SdkSourceSyncData,
 } from './models.js';
 
@@ -12,6 +12,7 @@ export const EventType = {
   DOCUMENT_UPDATED: 'document_updated',
   DOCUMENT_DELETED: 'document_deleted',
   GROUP_MEMBERSHIP_SYNC: 'group_membership_sync',
+  PERMISSIONS_CHANGED: 'permissions_changed',
 } as const;
 export type EventType = (typeof EventType)[keyof typeof EventType];
 
@@ -296,7 +297,19 @@ export interface GroupMembershipEventPayload {
   member_emails: string[];
 }
 
-export type ConnectorEventPayload = DocumentEventPayload | GroupMembershipEventPayload;
+export interface PermissionsChangedEventPayload {
+  type: typeof EventType.PERMISSIONS_CHANGED;
+  sync_run_id: string;
+  source_id: string;
+  document_id: string;
+  permissions: DocumentPermissions;
+  descendants_path?: string;
+}
+
+export type ConnectorEventPayload =
+  | DocumentEventPayload
+  | GroupMembershipEventPayload
+  | PermissionsChangedEventPayload;
 
 export function serializeConnectorEvent(event: ConnectorEventPayload): Record<string, unknown> {
   if (event.type === EventType.GROUP_MEMBERSHIP_SYNC) {
@@ -310,6 +323,20 @@ export function serializeConnectorEvent(event: ConnectorEventPayload): Record<st
     };
   }
 
+  if (event.type === EventType.PERMISSIONS_CHANGED) {
+    const serialized: Record<string, unknown> = {
+      type: event.type,
+      sync_run_id: event.sync_run_id,
+      source_id: event.source_id,
+      document_id: event.document_id,
+      permissions: event.permissions,
+    };
+    if (event.descendants_path) {
+      serialized.descendants_path = event.descendants_path;
+    }
+    return serialized;
+  }
+
   const base: Record<string, unknown> = {
     type: event.type,
     sync_run_id: event.sync_run_id,
@@ -338,6 +365,22 @@ export const UserFilterMode = {
 } as const;
 export type UserFilterMode = (typeof UserFilterMode)[keyof typeof UserFilterMode];
 
+/**
+ * Category of an error reported to connector-manager. Authentication,
+ * permission and configuration errors mark the source as needing attention.
+ */
+export const ConnectorErrorCategory = {
+  AUTHENTICATION: 'authentication',
+  PERMISSION: 'permission',
+  CONFIGURATION: 'configuration',
+  RATE_LIMIT: 'rate_limit',
+  NETWORK: 'network',
+  UPSTREAM: 'upstream',
+  UNKNOWN: 'unknown',
+} as const;
+export type ConnectorErrorCategory =
+  (typeof ConnectorErrorCategory)[keyof typeof ConnectorErrorCategory];
+
 /**
  * Wire shape of GET /sdk/source/:source_id/sync-config from connector-manager.
  *
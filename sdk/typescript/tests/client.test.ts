@@ -231,6 +231,36 @@ describe('SdkClient', () => {
     });
   });
 
+  describe('reportError', () => {
+    it('sends category and message for the source', async () => {
+      let capturedBody: unknown;
+      let capturedSourceId: string | undefined;
+
+      server.use(
+        http.post(
+          `${BASE_URL}/sdk/source/:id/errors`,
+          async ({ request, params }) => {
+            capturedSourceId = params.id as string;
+            capturedBody = await request.json();
+            return HttpResponse.json({ success: true });
+          }
+        )
+      );
+
+      const client = new SdkClient(BASE_URL);
+      await client.reportError('src-1', 'rate_limit', 'Too many requests', {
+        syncRunId: 'sync-123',
+      });
+
+      expect(capturedSourceId).toBe('src-1');
+      expect(capturedBody).toEqual({
+        sync_run_id: 'sync-123',
+        category: 'rate_limit',
+        message: 'Too many requests',
+      });
+    });
+  });
+
   describe('fetchSourceConfig', () => {
     it('sends correct request and parses response', async () => {
       const mockData = {
@@ -359,4 +389,23 @@ describe('SdkClient', () => {
       ).rejects.toThrow('Failed to emit event: 500');
     });
   });
+
+  describe('internal auth', () => {
+    it('sends the service key when one is configured', async () => {
+      let serviceKey: string | null = null;
+
+      server.use(
+        http.post(`${BASE_URL}/sdk/sync/sync-123/heartbeat`, ({ request }) => {
+          serviceKey = request.headers.get('x-omni-service-key');
+          return HttpResponse.json({ success: true });
+        })
+      );
+
+      vi.stubEnv('INTERNAL_AUTH_KEY', 'connector-key');
+      const client = new SdkClient(BASE_URL);
+      await client.heartbeat('sync-123');
+
+      expect(serviceKey).toBe('connector-key');
+    });
+  });
 });
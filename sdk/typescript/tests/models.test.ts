@@ -223,6 +223,26 @@ describe('serializeConnectorEvent', () => {
     expect(serialized.metadata).toBeUndefined();
   });
 
+  it('serializes permissions_changed event without content fields', () => {
+    const event: ConnectorEventPayload = {
+      type: EventType.PERMISSIONS_CHANGED,
+      sync_run_id: 'sync-123',
+      source_id: 'source-456',
+      document_id: 'folder-1',
+      permissions: { public: true, users: [], groups: [] },
+      descendants_path: '/Shared/Handbook',
+    };
+
+    const serialized = serializeConnectorEvent(event);
+
+    expect(serialized.type).toBe('permissions_changed');
+    expect(serialized.document_id).toBe('folder-1');
+    expect(serialized.permissions).toEqual({ public: true, users: [], groups: [] });
+    expect(serialized.descendants_path).toBe('/Shared/Handbook');
+    expect(serialized.content_id).toBeUndefined();
+    expect(serialized.metadata).toBeUndefined();
+  });
+
   it('provides default permissions when not specified', () => {
     const event: ConnectorEventPayload = {
       type: EventType.DOCUMENT_CREATED,
@@ -49,6 +49,7 @@ from db.documents import DocumentsRepository
 from db.models import Source, UserConfiguration
 from db.usage import UsageRepository
 from db.users import UsersRepository
+from internal_auth import internal_auth_headers
 from memory import MemoryMode, agent_key, resolve_memory_mode
 from prompts import build_agent_system_prompt
 from providers import LLMProvider, ProviderError
@@ -128,7 +129,9 @@ def _resolve_llm_provider(state: AppState, agent: Agent) -> LLMProvider:
 async def _fetch_sources() -> list[Source] | None:
     """Fetch all sources from the connector manager."""
     try:
-        async with httpx.AsyncClient(timeout=10.0) as client:
+        async with httpx.AsyncClient(
+            timeout=10.0, headers=internal_auth_headers()
+        ) as client:
             resp = await client.get(f"{CONNECTOR_MANAGER_URL.rstrip('/')}/sources")
             resp.raise_for_status()
             return sources_from_sync_overview_response(resp.json())
@@ -25,6 +25,7 @@ import httpx
 from anthropic.types import ContentBlockParam, MessageParam, TextBlockParam
 
 from db.uploads import UploadsRepository
+from internal_auth import internal_auth_headers
 from storage import ContentStorage
 
 
@@ -77,7 +78,9 @@ async def _stage_in_sandbox(
 ) -> None:
     """Write `content` to the sandbox at `path`, skipping if a file already exists there."""
     base = sandbox_url.rstrip("/")
-    async with httpx.AsyncClient(timeout=60.0) as client:
+    async with httpx.AsyncClient(
+        timeout=60.0, headers=internal_auth_headers()
+    ) as client:
         stat = await client.post(
             f"{base}/files/stat",
             json={"path": path, "chat_id": chat_id},
@@ -57,6 +57,29 @@ DATABASE_URL = (
 # Embedding configuration (provider config is in DB; only window size remains here)
 EMBEDDING_MAX_MODEL_LEN = int(get_optional_env("EMBEDDING_MAX_MODEL_LEN", "8192"))
 
+# Embedding queue throughput. The processor runs at the base batch size and
+# concurrency, and scales towards the max values while the backlog is above
+# the threshold (or its oldest item is older than the age threshold).
+EMBEDDING_BATCH_SIZE = int(get_optional_env("EMBEDDING_BATCH_SIZE", "10"))
+EMBEDDING_MAX_BATCH_SIZE = int(get_optional_env("EMBEDDING_MAX_BATCH_SIZE", "50"))
+EMBEDDING_CONCURRENCY = int(get_optional_env("EMBEDDING_CONCURRENCY", "1"))
+EMBEDDING_MAX_CONCURRENCY = int(get_optional_env("EMBEDDING_MAX_CONCURRENCY", "4"))
+EMBEDDING_BACKLOG_SCALE_THRESHOLD = int(
+    get_optional_env("EMBEDDING_BACKLOG_SCALE_THRESHOLD", "500")
+)
+EMBEDDING_BACKLOG_SCALE_AGE_SECONDS = float(
+    get_optional_env("EMBEDDING_BACKLOG_SCALE_AGE_SECONDS", "900")
+)
+
+# Document summary embeddings: one extra vector per document from its title and
+# opening text, which the searcher can use to narrow chunk search.
+SUMMARY_EMBEDDINGS_ENABLED = (
+    get_optional_env("SUMMARY_EMBEDDINGS_ENABLED", "false").lower() == "true"
+)
+SUMMARY_EMBEDDING_MAX_CHARS = int(
+    get_optional_env("SUMMARY_EMBEDDING_MAX_CHARS", "2000")
+)
+
 DEFAULT_MAX_TOKENS = int(get_optional_env("DEFAULT_MAX_TOKENS", "8192"))
 DEFAULT_TEMPERATURE = float(get_optional_env("DEFAULT_TEMPERATURE", "0.0"))
 DEFAULT_TOP_P = float(get_optional_env("DEFAULT_TOP_P", "1.0"))
@@ -3,9 +3,25 @@ from .compactions import AgentRunCompaction, ChatCompaction, CompactionsReposito
 from .configuration import ConfigurationRepository
 from .connection import close_db_pool, get_db_pool
 from .documents import ContentBlob, Document, DocumentsRepository
+from .embedding_experiments import (
+    ChunkingConfig,
+    EmbeddingExperiment,
+    EmbeddingExperimentsRepository,
+    ExperimentExistsError,
+    ExperimentNotRunningError,
+    ExperimentProgress,
+    PromotionResult,
+)
+from .embedding_migrations import EmbeddingMigration, EmbeddingMigrationsRepository
 from .embedding_providers import EmbeddingProviderRecord, EmbeddingProvidersRepository
-from .embedding_queue import EmbeddingQueueItem, EmbeddingQueueRepository, QueueStatus
-from .embeddings import Embedding, EmbeddingsRepository
+from .embedding_queue import (
+    BacklogStats,
+    ChunkFailure,
+    EmbeddingQueueItem,
+    EmbeddingQueueRepository,
+    QueueStatus,
+)
+from .embeddings import Embedding, EmbeddingsRepository, SummaryEmbedding
 from .messages import MessagesRepository
 from .model_providers import ModelProviderRecord, ModelProvidersRepository, ModelsRepository
 from .models import Chat, ChatMessage, ModelRecord, Source, User
@@ -36,10 +52,22 @@ __all__ = [
     "Document",
     "ContentBlob",
     "EmbeddingQueueRepository",
+    "BacklogStats",
+    "ChunkFailure",
     "EmbeddingQueueItem",
     "QueueStatus",
     "EmbeddingsRepository",
     "Embedding",
+    "SummaryEmbedding",
+    "EmbeddingExperimentsRepository",
+    "EmbeddingExperiment",
+    "ExperimentProgress",
+    "PromotionResult",
+    "ChunkingConfig",
+    "ExperimentExistsError",
+    "ExperimentNotRunningError",
+    "EmbeddingMigrationsRepository",
+    "EmbeddingMigration",
     "ModelProvidersRepository",
     "ModelProviderRecord",
     "ModelsRepository",
@@ -10,7 +10,10 @@ from .connection import get_db_pool
 
 logger = logging.getLogger(__name__)
 
-_COLUMNS = "id, content_id, source_id, external_id, title, content_type"
+_COLUMNS = (
+    "id, content_id, source_id, external_id, title, content_type, "
+    "attributes->>'content_kind' AS content_kind"
+)
 
 
 def _permission_filter(user_email: str) -> str:
@@ -33,6 +36,7 @@ class Document:
     external_id: Optional[str] = None
     title: Optional[str] = None
     content_type: Optional[str] = None
+    content_kind: Optional[str] = None  # "code" for source files
 
 
 @dataclass
@@ -76,6 +80,7 @@ class DocumentsRepository:
                 external_id=row["external_id"],
                 title=row["title"],
                 content_type=row["content_type"],
+                content_kind=row["content_kind"],
             )
             for row in rows
         }
@@ -108,6 +113,7 @@ class DocumentsRepository:
                 external_id=row["external_id"],
                 title=row["title"],
                 content_type=row["content_type"],
+                content_kind=row["content_kind"],
             )
         return None
 
@@ -138,6 +144,7 @@ class DocumentsRepository:
                 external_id=row["external_id"],
                 title=row["title"],
                 content_type=row["content_type"],
+                content_kind=row["content_kind"],
             )
         return None
 
@@ -163,7 +170,7 @@ class DocumentsRepository:
             WITH embedded_documents AS (
                 SELECT document_id, max(created_at) AS latest_embedding_at
                 FROM embeddings
-                WHERE model_name = $3
+                WHERE model_name = $3 AND namespace IS NULL
                 GROUP BY document_id
             )
             SELECT DISTINCT ON (d.content_id) d.content_id, d.id
@@ -175,13 +182,15 @@ class DocumentsRepository:
                   SELECT 1
                   FROM embedding_queue q
                   WHERE q.document_id = d.id
+                    AND q.namespace IS NULL
                     AND q.status IN ('pending', 'processing')
               )
               AND NOT EXISTS (
                   SELECT 1
                   FROM embedding_queue q
                   WHERE q.document_id = d.id
-                    AND q.status = 'failed'
+                    AND q.namespace IS NULL
+                    AND q.status IN ('failed', 'quarantined')
                     AND GREATEST(q.created_at, q.updated_at, COALESCE(q.processed_at, q.updated_at))
                         > ed.latest_embedding_at
               )
@@ -211,7 +220,10 @@ class DocumentsRepository:
             FROM documents d
             WHERE d.external_id = $1
               AND d.id != $2
-              AND EXISTS (SELECT 1 FROM embeddings e WHERE e.document_id = d.id)
+              AND EXISTS (
+                  SELECT 1 FROM embeddings e
+                  WHERE e.document_id = d.id AND e.namespace IS NULL
+              )
             LIMIT 1
             """,
             external_id,
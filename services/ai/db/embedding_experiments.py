@@ -0,0 +1,386 @@
+"""Repository for chunking experiments that embed into a shadow namespace."""
+
+import logging
+from dataclasses import dataclass
+from datetime import datetime
+from typing import Optional
+
+from asyncpg import Pool
+from ulid import ULID
+
+from .connection import get_db_pool
+
+logger = logging.getLogger(__name__)
+
+# Experiment work yields to production items, which are queued at priority 0
+EXPERIMENT_QUEUE_PRIORITY = -1
+
+
+@dataclass(frozen=True)
+class ChunkingConfig:
+    """How documents are split before embedding."""
+
+    chunk_size: int
+    chunking_mode: str
+
+
+# Production chunking until an experiment is promoted
+DEFAULT_CHUNKING = ChunkingConfig(chunk_size=512, chunking_mode="sentence")
+
+
+@dataclass
+class EmbeddingExperiment:
+    """Represents an embedding_experiments row."""
+
+    name: str
+    chunk_size: int
+    chunking_mode: str
+    status: str
+    created_at: datetime
+    promoted_at: Optional[datetime]
+
+    @property
+    def chunking(self) -> ChunkingConfig:
+        return ChunkingConfig(self.chunk_size, self.chunking_mode)
+
+
+@dataclass
+class ExperimentProgress:
+    """An experiment with how much of its namespace has been embedded."""
+
+    experiment: EmbeddingExperiment
+    embedded_documents: int
+    chunks: int
+    pending: int
+    quarantined: int
+
+
+@dataclass
+class PromotionResult:
+    """Outcome of swapping an experiment's rows in as production."""
+
+    promoted_documents: int
+    requeued_documents: int
+
+
+class ExperimentExistsError(Exception):
+    pass
+
+
+class ExperimentNotRunningError(Exception):
+    pass
+
+
+_EXPERIMENT_COLUMNS = "name, chunk_size, chunking_mode, status, created_at, promoted_at"
+
+
+class EmbeddingExperimentsRepository:
+    """Repository for embedding_experiments and their shadow embeddings.
+
+    Production embeddings have namespace NULL; an experiment's embeddings
+    carry its name.
+    """
+
+    def __init__(self, pool: Optional[Pool] = None):
+        self.pool = pool
+
+    async def _get_pool(self) -> Pool:
+        """Get database pool"""
+        if self.pool:
+            return self.pool
+        return await get_db_pool()
+
+    async def create(
+        self,
+        name: str,
+        chunking: ChunkingConfig,
+        source_ids: Optional[list[str]] = None,
+    ) -> tuple[EmbeddingExperiment, int]:
+        """Create an experiment and queue every document that has production
+        embeddings (optionally only from `source_ids`) for re-embedding into
+        its namespace. Returns the experiment and the number of queued
+        documents."""
+        pool = await self._get_pool()
+
+        async with pool.acquire() as conn:
+            async with conn.transaction():
+                row = await conn.fetchrow(
+                    f"""
+                    INSERT INTO embedding_experiments (name, chunk_size, chunking_mode)
+                    VALUES ($1, $2, $3)
+                    ON CONFLICT (name) DO NOTHING
+                    RETURNING {_EXPERIMENT_COLUMNS}
+                    """,
+                    name,
+                    chunking.chunk_size,
+                    chunking.chunking_mode,
+                )
+                if row is None:
+                    raise ExperimentExistsError(name)
+
+                document_ids = await conn.fetch(
+                    """
+                    SELECT DISTINCT e.document_id
+                    FROM embeddings e
+                    JOIN documents d ON d.id = e.document_id
+                    WHERE e.namespace IS NULL
+                      AND ($1::text[] IS NULL OR d.source_id = ANY($1))
+                    """,
+                    source_ids,
+                )
+                await conn.execute(
+                    """
+                    INSERT INTO embedding_queue (id, document_id, namespace, priority)
+                    SELECT q.id, q.document_id, $3, $4
+                    FROM UNNEST($1::text[], $2::text[]) AS q(id, document_id)
+                    """,
+                    [str(ULID()) for _ in document_ids],
+                    [r["document_id"] for r in document_ids],
+                    name,
+                    EXPERIMENT_QUEUE_PRIORITY,
+                )
+
+        logger.info(
+            f"Created embedding experiment {name} ({chunking.chunking_mode}, "
+            f"chunk_size={chunking.chunk_size}), queued {len(document_ids)} documents"
+        )
+        return EmbeddingExperiment(**dict(row)), len(document_ids)
+
+    async def get(self, name: str) -> Optional[EmbeddingExperiment]:
+        pool = await self._get_pool()
+
+        row = await pool.fetchrow(
+            f"SELECT {_EXPERIMENT_COLUMNS} FROM embedding_experiments WHERE name = $1",
+            name,
+        )
+        return EmbeddingExperiment(**dict(row)) if row else None
+
+    async def list_with_progress(self) -> list[ExperimentProgress]:
+        """All experiments, newest first, with embedded and outstanding counts."""
+        pool = await self._get_pool()
+
+        rows = await pool.fetch(
+            """
+            SELECT x.name, x.chunk_size, x.chunking_mode, x.status,
+                   x.created_at, x.promoted_at,
+                   COALESCE(e.documents, 0) AS embedded_documents,
+                   COALESCE(e.chunks, 0) AS chunks,
+                   COALESCE(q.pending, 0) AS pending,
+                   COALESCE(q.quarantined, 0) AS quarantined
+            FROM embedding_experiments x
+            LEFT JOIN (
+                SELECT namespace,
+                       COUNT(DISTINCT document_id) AS documents,
+                       COUNT(*) AS chunks
+                FROM embeddings
+                WHERE namespace IS NOT NULL
+                GROUP BY namespace
+            ) e ON e.namespace = x.name
+            LEFT JOIN (
+                SELECT namespace,
+                       COUNT(*) FILTER (
+                           WHERE status IN ('pending', 'processing', 'failed')
+                       ) AS pending,
+                       COUNT(*) FILTER (WHERE status = 'quarantined') AS quarantined
+                FROM embedding_queue
+                WHERE namespace IS NOT NULL
+                GROUP BY namespace
+            ) q ON q.namespace = x.name
+            ORDER BY x.created_at DESC
+            """
+        )
+        return [
+            ExperimentProgress(
+                experiment=EmbeddingExperiment(
+                    name=row["name"],
+                    chunk_size=row["chunk_size"],
+                    chunking_mode=row["chunking_mode"],
+                    status=row["status"],
+                    created_at=row["created_at"],
+                    promoted_at=row["promoted_at"],
+                ),
+                embedded_documents=int(row["embedded_documents"]),
+                chunks=int(row["chunks"]),
+                pending=int(row["pending"]),
+                quarantined=int(row["quarantined"]),
+            )
+            for row in rows
+        ]
+
+    async def get_production_chunking(self) -> ChunkingConfig:
+        """Chunking of the most recently promoted experiment, or the default."""
+        pool = await self._get_pool()
+
+        row = await pool.fetchrow(
+            """
+            SELECT chunk_size, chunking_mode
+            FROM embedding_experiments
+            WHERE status = 'promoted'
+            ORDER BY promoted_at DESC
+            LIMIT 1
+            """
+        )
+        if row is None:
+            return DEFAULT_CHUNKING
+        return ChunkingConfig(row["chunk_size"], row["chunking_mode"])
+
+    async def get_running_chunking(self, names: list[str]) -> dict[str, ChunkingConfig]:
+        """Chunking of the named experiments that are still running."""
+        if not names:
+            return {}
+
+        pool = await self._get_pool()
+
+        rows = await pool.fetch(
+            """
+            SELECT name, chunk_size, chunking_mode
+            FROM embedding_experiments
+            WHERE name = ANY($1) AND status = 'running'
+            """,
+            names,
+        )
+        return {
+            row["name"]: ChunkingConfig(row["chunk_size"], row["chunking_mode"])
+            for row in rows
+        }
+
+    async def enqueue_for_running(self, document_id: str) -> int:
+        """Queue a re-embedded document into every running experiment, so
+        shadow rows follow content changes. Returns the number queued."""
+        pool = await self._get_pool()
+
+        rows = await pool.fetch(
+            """
+            SELECT x.name
+            FROM embedding_experiments x
+            WHERE x.status = 'running'
+              AND NOT EXISTS (
+                  SELECT 1 FROM embedding_queue q
+                  WHERE q.document_id = $1
+                    AND q.namespace = x.name
+                    AND q.status IN ('pending', 'processing')
+              )
+            """,
+            document_id,
+        )
+        if not rows:
+            return 0
+
+        await pool.execute(
+            """
+            INSERT INTO embedding_queue (id, document_id, namespace, priority)
+            SELECT q.id, $2, q.namespace, $4
+            FROM UNNEST($1::text[], $3::text[]) AS q(id, namespace)
+            """,
+            [str(ULID()) for _ in rows],
+            document_id,
+            [row["name"] for row in rows],
+            EXPERIMENT_QUEUE_PRIORITY,
+        )
+        return len(rows)
+
+    async def promote(self, name: str) -> PromotionResult:
+        """Make the experiment's embeddings and chunking the production ones.
+
+        Documents embedded in the namespace have their production rows for
+        the same model replaced. Documents it never reached are queued for
+        production re-embedding, which picks up the promoted chunking.
+        Outstanding experiment work is dropped.
+        """
+        pool = await self._get_pool()
+
+        async with pool.acquire() as conn:
+            async with conn.transaction():
+                status = await conn.fetchval(
+                    "SELECT status FROM embedding_experiments WHERE name = $1 FOR UPDATE",
+                    name,
+                )
+                if status != "running":
+                    raise ExperimentNotRunningError(name)
+
+                uncovered = await conn.fetch(
+                    """
+                    SELECT DISTINCT e.document_id
+                    FROM embeddings e
+                    WHERE e.namespace IS NULL
+                      AND NOT EXISTS (
+                          SELECT 1 FROM embeddings s
+                          WHERE s.namespace = $1 AND s.document_id = e.document_id
+                      )
+                    """,
+                    name,
+                )
+
+                await conn.execute(
+                    """
+                    DELETE FROM embeddings e
+                    WHERE e.namespace IS NULL
+                      AND EXISTS (
+                          SELECT 1 FROM embeddings s
+                          WHERE s.namespace = $1
+                            AND s.document_id = e.document_id
+                            AND s.model_name = e.model_name
+                      )
+                    """,
+                    name,
+                )
+                promoted_documents = await conn.fetchval(
+                    """
+                    WITH promoted AS (
+                        UPDATE embeddings SET namespace = NULL
+                        WHERE namespace = $1
+                        RETURNING document_id
+                    )
+                    SELECT COUNT(DISTINCT document_id) FROM promoted
+                    """,
+                    name,
+                )
+
+                await conn.execute(
+                    "DELETE FROM embedding_queue WHERE namespace = $1", name
+                )
+                requeued = await conn.fetch(
+                    """
+                    INSERT INTO embedding_queue (id, document_id)
+                    SELECT q.id, q.document_id
+                    FROM UNNEST($1::text[], $2::text[]) AS q(id, document_id)
+                    WHERE NOT EXISTS (
+                        SELECT 1 FROM embedding_queue p
+                        WHERE p.document_id = q.document_id
+                          AND p.namespace IS NULL
+                          AND p.migration_id IS NULL
+                          AND p.status IN ('pending', 'processing')
+                    )
+                    RETURNING id
+                    """,
+                    [str(ULID()) for _ in uncovered],
+                    [row["document_id"] for row in uncovered],
+                )
+
+                await conn.execute(
+                    """
+                    UPDATE embedding_experiments
+                    SET status = 'promoted', promoted_at = NOW()
+                    WHERE name = $1
+                    """,
+                    name,
+                )
+
+        result = PromotionResult(
+            promoted_documents=int(promoted_documents),
+            requeued_documents=len(requeued),
+        )
+        logger.info(
+            f"Promoted embedding experiment {name}: {result.promoted_documents} "
+            f"documents swapped in, {result.requeued_documents} queued for re-embedding"
+        )
+        return result
+
+    async def delete(self, name: str) -> bool:
+        """Delete an experiment along with its shadow rows and queued work."""
+        pool = await self._get_pool()
+
+        result = await pool.execute(
+            "DELETE FROM embedding_experiments WHERE name = $1", name
+        )
+        return int(result.split()[-1]) > 0
@@ -0,0 +1,91 @@
+"""Repository for the running embedding model migration.
+
+Migrations are started, monitored and cut over by the indexer; the embedding
+processor only needs to know which one is running so it can embed its queue
+items with the target provider and keep it in step with production writes.
+"""
+
+import logging
+from dataclasses import dataclass
+from typing import Optional
+
+from asyncpg import Pool
+from ulid import ULID
+
+from .connection import get_db_pool
+
+logger = logging.getLogger(__name__)
+
+# Migration work yields to production items, which are queued at priority 0
+MIGRATION_QUEUE_PRIORITY = -1
+
+
+@dataclass(frozen=True)
+class EmbeddingMigration:
+    """The parts of an embedding_migrations row the processor needs."""
+
+    id: str
+    to_provider_id: str
+    from_model: str
+    to_model: str
+
+
+class EmbeddingMigrationsRepository:
+    def __init__(self, pool: Optional[Pool] = None):
+        self.pool = pool
+
+    async def _get_pool(self) -> Pool:
+        """Get database pool"""
+        if self.pool:
+            return self.pool
+        return await get_db_pool()
+
+    async def get_running(self) -> Optional[EmbeddingMigration]:
+        pool = await self._get_pool()
+
+        row = await pool.fetchrow(
+            """
+            SELECT id, to_provider_id, from_model, to_model
+            FROM embedding_migrations
+            WHERE status = 'running'
+            """
+        )
+        if row is None:
+            return None
+        return EmbeddingMigration(
+            id=row["id"].strip(),
+            to_provider_id=row["to_provider_id"].strip(),
+            from_model=row["from_model"],
+            to_model=row["to_model"],
+        )
+
+    async def enqueue_for_running(self, document_ids: list[str]) -> int:
+        """Queue documents being re-embedded in production for the running
+        migration too, so its target vectors follow content changes. Returns
+        the number queued."""
+        if not document_ids:
+            return 0
+
+        pool = await self._get_pool()
+
+        rows = await pool.fetch(
+            """
+            INSERT INTO embedding_queue (id, document_id, migration_id, priority)
+            SELECT q.id, q.document_id, m.id, $3
+            FROM UNNEST($1::text[], $2::text[]) AS q(id, document_id)
+            CROSS JOIN embedding_migrations m
+            WHERE m.status = 'running'
+              AND NOT EXISTS (
+                  SELECT 1 FROM embedding_queue p
+                  WHERE p.document_id = q.document_id
+                    AND p.migration_id = m.id
+                    -- One in progress may be embedding the old content
+                    AND p.status = 'pending'
+              )
+            RETURNING id
+            """,
+            [str(ULID()) for _ in document_ids],
+            document_ids,
+            MIGRATION_QUEUE_PRIORITY,
+        )
+        return len(rows)
@@ -64,6 +64,19 @@ class EmbeddingProvidersRepository:
             return EmbeddingProviderRecord.from_row(dict(row))
         return None
 
+    async def get_by_id(self, provider_id: str) -> Optional[EmbeddingProviderRecord]:
+        pool = await self._get_pool()
+        query = """
+            SELECT id, name, provider_type, config, is_current, is_deleted, created_at, updated_at
+            FROM embedding_providers
+            WHERE id = $1 AND is_deleted = FALSE
+        """
+        async with pool.acquire() as conn:
+            row = await conn.fetchrow(query, provider_id)
+        if row:
+            return EmbeddingProviderRecord.from_row(dict(row))
+        return None
+
     async def get_current_fingerprint(self) -> Optional[tuple[str, datetime]]:
         """Return (id, updated_at) for the current provider, or None if no provider is set."""
         pool = await self._get_pool()
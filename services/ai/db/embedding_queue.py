@@ -6,6 +6,7 @@ from typing import Optional, List
 from dataclasses import dataclass
 from datetime import datetime
 from asyncpg import Pool
+from ulid import ULID
 
 from .connection import get_db_pool
 
@@ -15,6 +16,7 @@ class QueueStatus(StrEnum):
     PROCESSING = "processing"
     COMPLETED = "completed"
     FAILED = "failed"
+    QUARANTINED = "quarantined"
 
 
 logger = logging.getLogger(__name__)
@@ -30,6 +32,27 @@ class EmbeddingQueueItem:
     error_message: Optional[str]
     retry_count: int
     created_at: datetime
+    # Experiment the item embeds for; None for production
+    namespace: Optional[str] = None
+    # Embedding migration the item embeds for with the target provider
+    migration_id: Optional[str] = None
+
+
+@dataclass
+class ChunkFailure:
+    """A sliding-window chunk of a document that failed to embed"""
+
+    start_offset: int
+    end_offset: int
+    error: str
+
+
+@dataclass
+class BacklogStats:
+    """Size and age of the embeddable backlog."""
+
+    pending: int
+    oldest_age_seconds: Optional[float]
 
 
 class EmbeddingQueueRepository:
@@ -50,7 +73,8 @@ class EmbeddingQueueRepository:
 
         row = await pool.fetchrow(
             """
-            SELECT id, document_id, status, error_message, retry_count, created_at
+            SELECT id, document_id, status, error_message, retry_count, created_at,
+                   namespace, migration_id
             FROM embedding_queue
             WHERE id = $1
             """,
@@ -85,10 +109,31 @@ class EmbeddingQueueRepository:
 
         return int(res)
 
+    async def get_backlog_stats(self, max_retries: int) -> BacklogStats:
+        """Count items still eligible for processing and the age of the oldest."""
+        pool = await self._get_pool()
+
+        row = await pool.fetchrow(
+            """
+            SELECT COUNT(*) AS pending,
+                   EXTRACT(EPOCH FROM (NOW() - MIN(created_at))) AS oldest_age_seconds
+            FROM embedding_queue
+            WHERE retry_count < $1
+              AND status IN ('pending', 'failed')
+            """,
+            max_retries,
+        )
+
+        oldest = row["oldest_age_seconds"]
+        return BacklogStats(
+            pending=int(row["pending"]),
+            oldest_age_seconds=float(oldest) if oldest is not None else None,
+        )
+
     async def get_pending_items(
         self, limit: int, max_retries: int
     ) -> List[EmbeddingQueueItem]:
-        """Atomically fetch and claim pending items.
+        """Atomically fetch and claim pending items, highest priority first.
 
         Uses FOR UPDATE SKIP LOCKED so each item is only claimed by one worker.
         """
@@ -103,19 +148,23 @@ class EmbeddingQueueRepository:
                 FROM embedding_queue
                 WHERE retry_count < $1
                   AND status IN ('pending', 'failed')
-                ORDER BY created_at ASC
+                ORDER BY priority DESC, created_at ASC
                 LIMIT $2
                 FOR UPDATE SKIP LOCKED
             )
-            RETURNING id, document_id, status, error_message, retry_count, created_at
+            RETURNING id, document_id, status, error_message, retry_count, created_at,
+                      namespace, migration_id
             """,
             max_retries,
             limit,
         )
         return [EmbeddingQueueItem(**dict(row)) for row in rows]
 
-    async def mark_completed(self, item_ids: List[str]) -> None:
-        """Mark queue items as completed"""
+    async def mark_completed(
+        self, item_ids: List[str], quarantined_chunks: int = 0
+    ) -> None:
+        """Mark queue items as completed, recording how many quarantined chunks
+        were skipped"""
         if not item_ids:
             return
 
@@ -124,28 +173,114 @@ class EmbeddingQueueRepository:
         await pool.execute(
             """
             UPDATE embedding_queue
-            SET status = 'completed', processed_at = CURRENT_TIMESTAMP
+            SET status = 'completed', processed_at = CURRENT_TIMESTAMP,
+                quarantined_chunks = $2
             WHERE id = ANY($1)
             """,
             item_ids,
+            quarantined_chunks,
         )
         logger.info(f"Marked {len(item_ids)} queue items as completed")
 
-    async def mark_failed(self, item_ids: List[str], error: str) -> None:
-        """Mark queue items as failed"""
+    async def mark_failed(
+        self, item_ids: List[str], error: str, max_retries: Optional[int] = None
+    ) -> None:
+        """Mark queue items as failed. Items that reach `max_retries` failures
+        are quarantined instead, keeping the error for inspection."""
         if not item_ids:
             return
 
         pool = await self._get_pool()
 
-        await pool.execute(
+        rows = await pool.fetch(
             """
             UPDATE embedding_queue
-            SET status = 'failed', error_message = $2, processed_at = CURRENT_TIMESTAMP,
+            SET status = CASE
+                    WHEN $3::int IS NOT NULL AND retry_count + 1 >= $3 THEN 'quarantined'
+                    ELSE 'failed'
+                END,
+                quarantined_at = CASE
+                    WHEN $3::int IS NOT NULL AND retry_count + 1 >= $3 THEN CURRENT_TIMESTAMP
+                    ELSE quarantined_at
+                END,
+                error_message = $2, processed_at = CURRENT_TIMESTAMP,
                 retry_count = retry_count + 1, updated_at = CURRENT_TIMESTAMP
             WHERE id = ANY($1)
+            RETURNING id, status
             """,
             item_ids,
             error,
+            max_retries,
         )
+        quarantined = [row["id"] for row in rows if row["status"] == "quarantined"]
         logger.error(f"Marked {len(item_ids)} queue items as failed: {error}")
+        if quarantined:
+            logger.warning(
+                f"Quarantined {len(quarantined)} queue items after {max_retries} "
+                f"failed attempts: {quarantined}"
+            )
+
+    async def get_quarantined_chunk_offsets(
+        self, document_id: str, content_id: str
+    ) -> set[int]:
+        """Start offsets of the document's quarantined chunks for its current
+        content."""
+        pool = await self._get_pool()
+
+        rows = await pool.fetch(
+            """
+            SELECT chunk_start_offset
+            FROM embedding_chunk_failures
+            WHERE document_id = $1 AND content_id = $2 AND status = 'quarantined'
+            """,
+            document_id,
+            content_id,
+        )
+        return {row["chunk_start_offset"] for row in rows}
+
+    async def record_chunk_failures(
+        self,
+        document_id: str,
+        content_id: str,
+        failures: List[ChunkFailure],
+        max_attempts: int,
+    ) -> set[int]:
+        """Count a failed attempt for each chunk, quarantining chunks that
+        reach `max_attempts`. Returns the start offsets now quarantined."""
+        if not failures:
+            return set()
+
+        pool = await self._get_pool()
+
+        rows = await pool.fetch(
+            """
+            INSERT INTO embedding_chunk_failures (
+                id, document_id, content_id, chunk_start_offset, chunk_end_offset,
+                status, error_message
+            )
+            SELECT f.id, $1, $2, f.start_offset, f.end_offset,
+                   CASE WHEN $7 <= 1 THEN 'quarantined' ELSE 'failing' END, f.error
+            FROM UNNEST($3::text[], $4::int[], $5::int[], $6::text[])
+                AS f(id, start_offset, end_offset, error)
+            ON CONFLICT (document_id, content_id, chunk_start_offset) DO UPDATE
+            SET attempts = embedding_chunk_failures.attempts + 1,
+                status = CASE
+                    WHEN embedding_chunk_failures.attempts + 1 >= $7 THEN 'quarantined'
+                    ELSE 'failing'
+                END,
+                chunk_end_offset = EXCLUDED.chunk_end_offset,
+                error_message = EXCLUDED.error_message,
+                updated_at = NOW()
+            RETURNING chunk_start_offset, status
+            """,
+            document_id,
+            content_id,
+            [str(ULID()) for _ in failures],
+            [f.start_offset for f in failures],
+            [f.end_offset for f in failures],
+            [f.error for f in failures],
+            max_attempts,
+        )
+        return {
+            row["chunk_start_offset"] for row in rows if row["status"] == "quarantined"
+        }
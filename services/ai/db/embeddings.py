@@ -24,6 +24,19 @@ class Embedding:
     embedding: list
     model_name: str
     dimensions: int
+    namespace: Optional[str] = None
+    page_number: Optional[int] = None
+    heading_path: Optional[str] = None
+
+
+@dataclass
+class SummaryEmbedding:
+    """Represents a document_summary_embeddings row."""
+
+    document_id: str
+    embedding: list
+    model_name: str
+    dimensions: int
 
 
 class EmbeddingsRepository:
@@ -38,24 +51,35 @@ class EmbeddingsRepository:
             return self.pool
         return await get_db_pool()
 
-    async def get_for_document(self, document_id: str) -> List[Embedding]:
-        """Get all embeddings for a document, ordered by chunk_index."""
+    async def get_for_document(
+        self, document_id: str, namespace: Optional[str] = None
+    ) -> List[Embedding]:
+        """Get a document's embeddings in `namespace` (production when None),
+        ordered by chunk_index."""
         pool = await self._get_pool()
 
         rows = await pool.fetch(
             """
             SELECT id, document_id, chunk_index, chunk_start_offset, chunk_end_offset,
-                   embedding, model_name, dimensions
+                   embedding, model_name, dimensions, namespace, page_number,
+                   heading_path
             FROM embeddings
-            WHERE document_id = $1
+            WHERE document_id = $1 AND namespace IS NOT DISTINCT FROM $2
             ORDER BY chunk_index
             """,
             document_id,
+            namespace,
         )
         return [Embedding(**dict(row)) for row in rows]
 
-    async def delete_for_documents(self, document_ids: List[str]) -> None:
-        """Delete existing embeddings for documents"""
+    async def delete_for_documents(
+        self,
+        document_ids: List[str],
+        namespace: Optional[str] = None,
+        model_name: Optional[str] = None,
+    ) -> None:
+        """Delete existing embeddings for documents in `namespace` (production
+        when None), only those of `model_name` if given"""
         if not document_ids:
             return
 
@@ -64,9 +88,12 @@ class EmbeddingsRepository:
         await pool.execute(
             """
             DELETE FROM embeddings
-            WHERE document_id = ANY($1)
+            WHERE document_id = ANY($1) AND namespace IS NOT DISTINCT FROM $2
+              AND ($3::text IS NULL OR model_name = $3)
             """,
             document_ids,
+            namespace,
+            model_name,
         )
         logger.info(f"Deleted existing embeddings for {len(document_ids)} documents")
 
@@ -82,6 +109,9 @@ class EmbeddingsRepository:
         - embedding: List[float]
         - model_name: str
         - dimensions: int
+        - namespace: str (optional, experiment name; production when absent)
+        - page_number: int (optional, 1-based page the chunk starts on)
+        - heading_path: str (optional, headings enclosing the chunk start)
         - created_at: datetime (optional, defaults to now)
         """
         if not embeddings:
@@ -100,6 +130,9 @@ class EmbeddingsRepository:
                 emb["embedding"],
                 emb["model_name"],
                 emb["dimensions"],
+                emb.get("namespace"),
+                emb.get("page_number"),
+                emb.get("heading_path"),
                 emb.get("created_at", datetime.utcnow()),
             )
             for emb in embeddings
@@ -118,11 +151,56 @@ class EmbeddingsRepository:
                 "embedding",
                 "model_name",
                 "dimensions",
+                "namespace",
+                "page_number",
+                "heading_path",
                 "created_at",
             ],
         )
         logger.info(f"Bulk inserted {len(embeddings)} embeddings")
 
+    async def upsert_summary_embedding(
+        self,
+        document_id: str,
+        embedding: List[float],
+        model_name: str,
+    ) -> None:
+        """Store a document's summary embedding, replacing any previous one."""
+        pool = await self._get_pool()
+
+        await pool.execute(
+            """
+            INSERT INTO document_summary_embeddings
+                (document_id, embedding, model_name, dimensions)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (document_id) DO UPDATE
+            SET embedding = EXCLUDED.embedding,
+                model_name = EXCLUDED.model_name,
+                dimensions = EXCLUDED.dimensions,
+                updated_at = NOW()
+            """,
+            document_id,
+            embedding,
+            model_name,
+            len(embedding),
+        )
+
+    async def get_summary_embedding(
+        self, document_id: str
+    ) -> Optional[SummaryEmbedding]:
+        """Get a document's summary embedding, or None if it has none."""
+        pool = await self._get_pool()
+
+        row = await pool.fetchrow(
+            """
+            SELECT document_id, embedding, model_name, dimensions
+            FROM document_summary_embeddings
+            WHERE document_id = $1
+            """,
+            document_id,
+        )
+        return SummaryEmbedding(**dict(row)) if row else None
+
     async def bulk_clone_for_documents(
         self, clone_requests: list[tuple[str, str, str]], model_name: str
     ) -> dict[str, int]:
@@ -163,6 +241,7 @@ class EmbeddingsRepository:
                     JOIN embeddings e
                       ON e.document_id = p.source_document_id
                      AND e.model_name = $4
+                     AND e.namespace IS NULL
                     GROUP BY p.target_document_id, p.queue_item_id
                     """,
                     source_document_ids,
@@ -180,7 +259,7 @@ class EmbeddingsRepository:
                 await conn.execute(
                     """
                     DELETE FROM embeddings
-                    WHERE document_id = ANY($1)
+                    WHERE document_id = ANY($1) AND namespace IS NULL
                     """,
                     list(clone_counts.keys()),
                 )
@@ -207,7 +286,9 @@ class EmbeddingsRepository:
                         chunk_end_offset,
                         embedding,
                         model_name,
-                        dimensions
+                        dimensions,
+                        page_number,
+                        heading_path
                     )
                     SELECT
                         substring(
@@ -224,11 +305,14 @@ class EmbeddingsRepository:
                         e.chunk_end_offset,
                         e.embedding,
                         e.model_name,
-                        e.dimensions
+                        e.dimensions,
+                        e.page_number,
+                        e.heading_path
                     FROM clone_pairs p
                     JOIN embeddings e
                       ON e.document_id = p.source_document_id
                      AND e.model_name = $3
+                     AND e.namespace IS NULL
                     """,
                     source_document_ids,
                     target_document_ids,
@@ -256,7 +340,7 @@ class EmbeddingsRepository:
     async def clone_for_document(
         self, source_document_id: str, target_document_id: str
     ) -> int:
-        """Clone all embeddings from one document to another.
+        """Clone all production embeddings from one document to another.
 
         Creates new embedding rows with fresh IDs pointing to target_document_id,
         copying all chunk data and vectors from source_document_id.
@@ -279,6 +363,8 @@ class EmbeddingsRepository:
                 "embedding": emb.embedding,
                 "model_name": emb.model_name,
                 "dimensions": emb.dimensions,
+                "page_number": emb.page_number,
+                "heading_path": emb.heading_path,
             }
             for emb in existing
         ]
@@ -7,7 +7,7 @@ import time
 from typing import Optional
 from dataclasses import dataclass
 
-from db import EmbeddingProvidersRepository
+from db import EmbeddingProviderRecord, EmbeddingProvidersRepository
 
 
 # =============================================================================
@@ -24,6 +24,18 @@ class EmbeddingConfig:
     dimensions: Optional[int] = None
     max_model_len: Optional[int] = None
 
+    @classmethod
+    def from_record(cls, record: EmbeddingProviderRecord) -> "EmbeddingConfig":
+        config = record.config
+        return cls(
+            provider=record.provider_type,
+            api_key=config.get("apiKey"),
+            model=config.get("model", ""),
+            api_url=config.get("apiUrl"),
+            dimensions=config.get("dimensions"),
+            max_model_len=config.get("maxModelLen"),
+        )
+
 
 class EmbeddingConfigCache:
     """Cached embedding configuration reader with PostgreSQL backend."""
@@ -46,15 +58,7 @@ class EmbeddingConfigCache:
         if record is None:
             return None
 
-        config = record.config
-        return EmbeddingConfig(
-            provider=record.provider_type,
-            api_key=config.get("apiKey"),
-            model=config.get("model", ""),
-            api_url=config.get("apiUrl"),
-            dimensions=config.get("dimensions"),
-            max_model_len=config.get("maxModelLen"),
-        )
+        return EmbeddingConfig.from_record(record)
 
     async def get_config(self) -> Optional[EmbeddingConfig]:
         if self._is_cache_valid():
@@ -4,6 +4,12 @@ Embedding Provider abstraction layer for supporting multiple embedding providers
 
 from abc import ABC, abstractmethod
 from dataclasses import dataclass
+from typing import TYPE_CHECKING
+
+from config import AWS_REGION
+
+if TYPE_CHECKING:
+    from db_config import EmbeddingConfig
 
 
 @dataclass
@@ -168,3 +174,65 @@ __all__ = [
     "CohereEmbeddingProvider",
     "create_embedding_provider",
 ]
+
+
+def build_embedding_provider(embedding_config: "EmbeddingConfig") -> EmbeddingProvider:
+    """Create the embedding provider described by a provider's stored config."""
+    provider = embedding_config.provider
+
+    max_model_len = embedding_config.max_model_len or 8192
+
+    if provider == "jina":
+        if not embedding_config.api_key:
+            raise ValueError("Embedding API key is required when using Jina provider")
+        return create_embedding_provider(
+            "jina",
+            api_key=embedding_config.api_key,
+            model=embedding_config.model,
+            api_url=embedding_config.api_url,
+            max_model_len=max_model_len,
+        )
+
+    elif provider == "bedrock":
+        region_name = AWS_REGION if AWS_REGION else None
+        return create_embedding_provider(
+            "bedrock",
+            model_id=embedding_config.model,
+            region_name=region_name,
+            max_model_len=max_model_len,
+        )
+
+    elif provider == "openai":
+        if not embedding_config.api_key:
+            raise ValueError("Embedding API key is required when using OpenAI provider")
+        return create_embedding_provider(
+            "openai",
+            api_key=embedding_config.api_key,
+            model=embedding_config.model,
+            api_url=embedding_config.api_url,
+            dimensions=embedding_config.dimensions,
+            max_model_len=max_model_len,
+        )
+
+    elif provider == "cohere":
+        if not embedding_config.api_key:
+            raise ValueError("Embedding API key is required when using Cohere provider")
+        return create_embedding_provider(
+            "cohere",
+            api_key=embedding_config.api_key,
+            model=embedding_config.model,
+            api_url=embedding_config.api_url,
+            max_model_len=max_model_len,
+            dimensions=embedding_config.dimensions,
+        )
+
+    elif provider == "local":
+        return create_embedding_provider(
+            "local",
+            base_url=embedding_config.api_url or "",
+            model=embedding_config.model,
+            max_model_len=max_model_len,
+        )
+
+    else:
+        raise ValueError(f"Unknown embedding provider: {provider}")
@@ -0,0 +1,100 @@
+"""
+Backlog-aware throughput scaling for the embedding processor.
+
+While the embedding queue backlog is large (or its oldest item is stale) the
+processor steps its batch size and concurrency up towards configured ceilings,
+one step per backlog check. Once the backlog has drained below half the
+threshold it returns straight to baseline.
+"""
+
+from dataclasses import dataclass
+from typing import Optional
+
+from config import (
+    EMBEDDING_BACKLOG_SCALE_AGE_SECONDS,
+    EMBEDDING_BACKLOG_SCALE_THRESHOLD,
+    EMBEDDING_BATCH_SIZE,
+    EMBEDDING_CONCURRENCY,
+    EMBEDDING_MAX_BATCH_SIZE,
+    EMBEDDING_MAX_CONCURRENCY,
+)
+
+SCALE_STEPS = 3
+
+
+@dataclass(frozen=True)
+class BacklogScalingConfig:
+    base_batch_size: int = EMBEDDING_BATCH_SIZE
+    max_batch_size: int = EMBEDDING_MAX_BATCH_SIZE
+    base_concurrency: int = EMBEDDING_CONCURRENCY
+    max_concurrency: int = EMBEDDING_MAX_CONCURRENCY
+    backlog_threshold: int = EMBEDDING_BACKLOG_SCALE_THRESHOLD
+    age_threshold_seconds: float = EMBEDDING_BACKLOG_SCALE_AGE_SECONDS
+
+
+@dataclass
+class BacklogStatus:
+    """Latest backlog observation and the throughput chosen for it."""
+
+    pending: int
+    oldest_age_seconds: Optional[float]
+    level: int
+    max_level: int
+    batch_size: int
+    concurrency: int
+
+
+class BacklogScaler:
+    def __init__(self, config: Optional[BacklogScalingConfig] = None):
+        self.config = config or BacklogScalingConfig()
+        self.level = 0
+
+    def _interpolate(self, base: int, ceiling: int) -> int:
+        base = max(1, base)
+        ceiling = max(base, ceiling)
+        return base + round((ceiling - base) * self.level / SCALE_STEPS)
+
+    @property
+    def batch_size(self) -> int:
+        return self._interpolate(
+            self.config.base_batch_size, self.config.max_batch_size
+        )
+
+    @property
+    def concurrency(self) -> int:
+        return self._interpolate(
+            self.config.base_concurrency, self.config.max_concurrency
+        )
+
+    def update(self, pending: int, oldest_age_seconds: Optional[float]) -> bool:
+        """Adjust the scale level for the observed backlog.
+
+        Returns True if the level changed.
+        """
+        age = oldest_age_seconds or 0.0
+        behind = pending >= self.config.backlog_threshold or (
+            pending > 0 and age >= self.config.age_threshold_seconds
+        )
+        drained = (
+            pending < self.config.backlog_threshold // 2
+            and age < self.config.age_threshold_seconds / 2
+        )
+
+        previous = self.level
+        if behind:
+            self.level = min(SCALE_STEPS, self.level + 1)
+        elif drained:
+            self.level = 0
+        return self.level != previous
+
+    def status(
+        self, pending: int, oldest_age_seconds: Optional[float]
+    ) -> BacklogStatus:
+        return BacklogStatus(
+            pending=pending,
+            oldest_age_seconds=oldest_age_seconds,
+            level=self.level,
+            max_level=SCALE_STEPS,
+            batch_size=self.batch_size,
+            concurrency=self.concurrency,
+        )
@@ -3,38 +3,132 @@ Embedding processor for document indexing.
 
 Drains the embedding_queue table by chunking each document and calling the
 configured embedding provider.
+
+Queue items with a namespace belong to a chunking experiment: they are chunked
+with the experiment's settings and written as shadow rows flagged with its
+name, leaving the production embeddings untouched.
+
+With summary embeddings enabled, production items also get one vector of the
+document's title and opening text, the searcher's coarse retrieval signal.
+
+Queue items with a migration id belong to an embedding model migration: they
+are embedded with the migration's target provider and written next to the
+current model's rows. While a migration runs, every production item is also
+queued for it, so documents that change mid-migration are current in both.
 """
 
 import asyncio
+import bisect
 import logging
+import re
 import time
 from typing import Optional
 
 import ulid
 
-from config import EMBEDDING_MAX_MODEL_LEN
+from config import (
+    EMBEDDING_MAX_MODEL_LEN,
+    SUMMARY_EMBEDDING_MAX_CHARS,
+    SUMMARY_EMBEDDINGS_ENABLED,
+)
 from db import (
+    ChunkFailure,
+    ChunkingConfig,
     Document,
     DocumentsRepository,
+    EmbeddingExperimentsRepository,
+    EmbeddingMigration,
+    EmbeddingMigrationsRepository,
+    EmbeddingProvidersRepository,
     EmbeddingQueueItem,
     EmbeddingQueueRepository,
     EmbeddingsRepository,
     QueueStatus,
     get_db_pool,
 )
+from db_config import EmbeddingConfig
 from state import AppState
 
-from . import Chunk
+from . import Chunk, EmbeddingProvider, build_embedding_provider
+from .backlog_scaling import BacklogScaler
 
 logger = logging.getLogger(__name__)
 
 
-# Configuration for online processing
-ONLINE_BATCH_SIZE = 10
+# Configuration for online processing. Batch size and concurrency come from
+# the backlog scaler.
 ONLINE_POLL_INTERVAL = 5  # Seconds to wait when queue is empty
 ONLINE_BATCH_DELAY = 0.1  # Seconds to yield between batches when queue has items
 PROGRESS_LOG_INTERVAL = 30  # Seconds between progress log lines
+BACKLOG_CHECK_INTERVAL = 15  # Seconds between backlog scaling decisions
 MAX_EMBEDDING_RETRIES = 5
+# Failed attempts after which a chunk is quarantined and skipped. Must stay
+# below MAX_EMBEDDING_RETRIES so the rest of the document still gets indexed.
+MAX_CHUNK_ATTEMPTS = 3
+# Separates pages in extracted PDF text (see shared::content_extractor).
+PAGE_BREAK = "\f"
+
+
+def page_starts(content_text: str) -> list[int] | None:
+    """Offsets at which each page of the content begins, or None when the
+    content has no page breaks."""
+    if PAGE_BREAK not in content_text:
+        return None
+    return [0] + [i + 1 for i, c in enumerate(content_text) if c == PAGE_BREAK]
+
+
+def page_number_at(starts: list[int] | None, offset: int) -> int | None:
+    """1-based page containing `offset`."""
+    if starts is None:
+        return None
+    return bisect.bisect_right(starts, offset)
+
+
+# Joins the headings of a chunk's heading path, outermost first.
+HEADING_PATH_SEPARATOR = " > "
+# A markdown ATX heading line, e.g. "## Incident Response ##"
+HEADING_RE = re.compile(r"^ {0,3}(#{1,6})[ \t]+(.+?)(?:[ \t]+#+)?[ \t]*$")
+CODE_FENCES = ("```", "~~~")
+
+
+def heading_sections(content_text: str) -> tuple[list[int], list[str]] | None:
+    """Offsets at which each markdown section of the content begins, with the
+    heading path in effect from there, or None when it has no headings.
+
+    Headings inside fenced code blocks are ignored."""
+    starts: list[int] = []
+    paths: list[str] = []
+    stack: list[tuple[int, str]] = []
+    in_fence = False
+    offset = 0
+    for line in content_text.split("\n"):
+        # PDF pages begin with a form feed (see PAGE_BREAK)
+        text = line.strip("\r").lstrip(PAGE_BREAK)
+        if text.lstrip().startswith(CODE_FENCES):
+            in_fence = not in_fence
+        elif not in_fence and (match := HEADING_RE.match(text)):
+            level = len(match.group(1))
+            while stack and stack[-1][0] >= level:
+                stack.pop()
+            stack.append((level, match.group(2)))
+            starts.append(offset)
+            paths.append(HEADING_PATH_SEPARATOR.join(title for _, title in stack))
+        offset += len(line) + 1
+    if not starts:
+        return None
+    return starts, paths
+
+
+def heading_path_at(
+    sections: tuple[list[int], list[str]] | None, offset: int
+) -> str | None:
+    """Heading path of the section containing `offset`; None before the
+    first heading."""
+    if sections is None:
+        return None
+    starts, paths = sections
+    index = bisect.bisect_right(starts, offset)
+    return paths[index - 1] if index else None
 
 
 class EmbeddingBatchProcessor:
@@ -46,13 +140,27 @@ class EmbeddingBatchProcessor:
         queue_repo: EmbeddingQueueRepository,
         embeddings_repo: EmbeddingsRepository,
         app_state: AppState,
+        experiments_repo: Optional[EmbeddingExperimentsRepository] = None,
+        summary_embeddings_enabled: bool = SUMMARY_EMBEDDINGS_ENABLED,
+        migrations_repo: Optional[EmbeddingMigrationsRepository] = None,
     ):
         self.documents_repo = documents_repo
         self.queue_repo = queue_repo
         self.embeddings_repo = embeddings_repo
+        self.experiments_repo = experiments_repo or EmbeddingExperimentsRepository(
+            embeddings_repo.pool
+        )
+        self.migrations_repo = migrations_repo or EmbeddingMigrationsRepository(
+            embeddings_repo.pool
+        )
         self.app_state = app_state
+        self.summary_embeddings_enabled = summary_embeddings_enabled
+        # Target provider of the running migration, keyed by migration id
+        self._migration_provider: Optional[tuple[str, EmbeddingProvider]] = None
 
-        self._embedding_semaphore = asyncio.Semaphore(1)
+        self.scaler = BacklogScaler()
+        self._embedding_semaphore = asyncio.Semaphore(self.scaler.concurrency)
+        self._last_backlog_check: Optional[float] = None
 
         # Progress tracking (populated at online loop start)
         self._progress_start_time: Optional[float] = None
@@ -111,8 +219,10 @@ class EmbeddingBatchProcessor:
         Returns:
             True if any items were processed, False if queue was empty.
         """
+        await self._maybe_rescale()
+
         items = await self.queue_repo.get_pending_items(
-            limit=ONLINE_BATCH_SIZE, max_retries=MAX_EMBEDDING_RETRIES
+            limit=self.scaler.batch_size, max_retries=MAX_EMBEDDING_RETRIES
         )
 
         if not items:
@@ -121,38 +231,181 @@ class EmbeddingBatchProcessor:
 
         logger.info(f"Processing {len(items)} documents via online embedding API")
 
+        migration = await self.migrations_repo.get_running()
+        migration_provider = None
+        if migration is not None:
+            # Queued before production rows are replaced, so the migration
+            # cannot finish in between and miss the new content
+            await self.migrations_repo.enqueue_for_running(
+                list(
+                    {
+                        item.document_id
+                        for item in items
+                        if item.namespace is None and item.migration_id is None
+                    }
+                )
+            )
+            migration_provider = await self._resolve_migration_provider(migration)
+
         documents_by_id = await self.documents_repo.get_by_ids(
             [item.document_id for item in items]
         )
         items_to_process = await self._clone_same_content_embeddings(
             items, documents_by_id
         )
+        chunking_by_namespace = await self._resolve_chunking(items_to_process)
+
+        # Concurrency is bounded by _embedding_semaphore inside
+        # _process_single_document.
+        await asyncio.gather(
+            *(
+                self._process_queue_item(
+                    item,
+                    documents_by_id.get(item.document_id),
+                    chunking_by_namespace.get(item.namespace),
+                    self._provider_for(item, migration, migration_provider),
+                )
+                for item in items_to_process
+            )
+        )
 
-        for item in items_to_process:
-            try:
-                await self._process_single_document(
-                    item, documents_by_id.get(item.document_id)
+        return True
+
+    async def _resolve_chunking(
+        self, items: list[EmbeddingQueueItem]
+    ) -> dict[Optional[str], ChunkingConfig]:
+        """Chunking for production (key None) and each running experiment
+        among the items. Experiments that were promoted or deleted since the
+        items were queued are missing."""
+        namespaces = list({item.namespace for item in items if item.namespace})
+        chunking: dict[Optional[str], ChunkingConfig] = dict(
+            await self.experiments_repo.get_running_chunking(namespaces)
+        )
+        chunking[None] = await self.experiments_repo.get_production_chunking()
+        return chunking
+
+    async def _resolve_migration_provider(
+        self, migration: EmbeddingMigration
+    ) -> Optional[EmbeddingProvider]:
+        """The running migration's target provider, built once per migration.
+        None if the target provider was deleted, which fails the migration
+        at cutover."""
+        if self._migration_provider and self._migration_provider[0] == migration.id:
+            return self._migration_provider[1]
+
+        providers_repo = EmbeddingProvidersRepository(self.embeddings_repo.pool)
+        record = await providers_repo.get_by_id(migration.to_provider_id)
+        if record is None:
+            logger.warning(
+                f"Target provider {migration.to_provider_id} of embedding "
+                f"migration {migration.id} no longer exists"
+            )
+            return None
+
+        provider = build_embedding_provider(EmbeddingConfig.from_record(record))
+        self._migration_provider = (migration.id, provider)
+        logger.info(
+            f"Embedding migration {migration.id}: embedding with "
+            f"{record.provider_type} model {provider.get_model_name()}"
+        )
+        return provider
+
+    def _provider_for(
+        self,
+        item: EmbeddingQueueItem,
+        migration: Optional[EmbeddingMigration],
+        migration_provider: Optional[EmbeddingProvider],
+    ) -> Optional[EmbeddingProvider]:
+        """Provider to embed the item with; None for migration items whose
+        migration is no longer running."""
+        if item.migration_id is None:
+            return self.embedding_provider
+        if migration is None or migration.id != item.migration_id:
+            return None
+        return migration_provider
+
+    async def _process_queue_item(
+        self,
+        item: EmbeddingQueueItem,
+        doc: Document | None,
+        chunking: ChunkingConfig | None,
+        provider: Optional[EmbeddingProvider],
+    ) -> None:
+        try:
+            if chunking is None:
+                logger.info(
+                    f"Skipping document {item.document_id}: embedding experiment "
+                    f"{item.namespace} is no longer running"
                 )
-            except Exception as e:
-                logger.error(
-                    f"Failed to process document {item.document_id}: {e}", exc_info=True
+                await self.queue_repo.mark_completed([item.id])
+                return
+            if provider is None:
+                logger.info(
+                    f"Skipping document {item.document_id}: embedding migration "
+                    f"{item.migration_id} is no longer running"
                 )
-                await self.queue_repo.mark_failed([item.id], str(e))
-                self._docs_failed += 1
-            finally:
-                # Yield to allow higher-priority tasks (stream requests) to run
-                await asyncio.sleep(0)
-                await self._maybe_log_progress()
+                await self.queue_repo.mark_completed([item.id])
+                return
+            await self._process_single_document(item, doc, chunking, provider)
+        except Exception as e:
+            logger.error(
+                f"Failed to process document {item.document_id}: {e}", exc_info=True
+            )
+            await self.queue_repo.mark_failed(
+                [item.id], str(e), max_retries=MAX_EMBEDDING_RETRIES
+            )
+            self._docs_failed += 1
+        finally:
+            # Yield to allow higher-priority tasks (stream requests) to run
+            await asyncio.sleep(0)
+            await self._maybe_log_progress()
 
-        return True
+    async def _maybe_rescale(self) -> None:
+        """Re-evaluate batch size and concurrency against the current backlog.
+
+        Runs between batches, so no document holds the semaphore when it is
+        replaced.
+        """
+        now = time.time()
+        if (
+            self._last_backlog_check is not None
+            and now - self._last_backlog_check < BACKLOG_CHECK_INTERVAL
+        ):
+            return
+        self._last_backlog_check = now
+
+        stats = await self.queue_repo.get_backlog_stats(
+            max_retries=MAX_EMBEDDING_RETRIES
+        )
+        if self.scaler.update(stats.pending, stats.oldest_age_seconds):
+            self._embedding_semaphore = asyncio.Semaphore(self.scaler.concurrency)
+            logger.info(
+                f"Embedding backlog {stats.pending} items "
+                f"(oldest {stats.oldest_age_seconds or 0:.0f}s): scaling to level "
+                f"{self.scaler.level}, batch_size={self.scaler.batch_size}, "
+                f"concurrency={self.scaler.concurrency}"
+            )
+        self.app_state.embedding_backlog = self.scaler.status(
+            stats.pending, stats.oldest_age_seconds
+        )
 
     async def _clone_same_content_embeddings(
         self,
         items: list[EmbeddingQueueItem],
         documents_by_id: dict[str, Document],
     ) -> list[EmbeddingQueueItem]:
+        # Experiment items always embed with their own chunking, and
+        # migration items with their own model
+        production_items = [
+            item
+            for item in items
+            if item.namespace is None and item.migration_id is None
+        ]
+        production_document_ids = {item.document_id for item in production_items}
         docs_with_content = [
-            doc for doc in documents_by_id.values() if doc.content_id is not None
+            doc
+            for doc in documents_by_id.values()
+            if doc.id in production_document_ids and doc.content_id is not None
         ]
         if not docs_with_content:
             return items
@@ -173,7 +426,7 @@ class EmbeddingBatchProcessor:
             return items
 
         clone_requests: list[tuple[str, str, str]] = []
-        item_by_document_id = {item.document_id: item for item in items}
+        item_by_document_id = {item.document_id: item for item in production_items}
         for doc in docs_with_content:
             donor_id = donor_by_content_id.get(doc.content_id)
             item = item_by_document_id.get(doc.id)
@@ -198,12 +451,22 @@ class EmbeddingBatchProcessor:
         )
 
         cloned_document_ids = set(clone_counts.keys())
-        return [item for item in items if item.document_id not in cloned_document_ids]
+        return [
+            item
+            for item in items
+            if item.namespace is not None
+            or item.migration_id is not None
+            or item.document_id not in cloned_document_ids
+        ]
 
     async def _process_single_document(
-        self, item: EmbeddingQueueItem, doc: Document | None = None
+        self,
+        item: EmbeddingQueueItem,
+        doc: Document | None,
+        chunking: ChunkingConfig,
+        provider: EmbeddingProvider,
     ):
-        """Process a single document using the embedding provider"""
+        """Process a single document using `provider`"""
         if item.retry_count > 0:
             logger.debug(
                 f"Retrying document {item.document_id} (attempt {item.retry_count + 1})"
@@ -219,7 +482,9 @@ class EmbeddingBatchProcessor:
                     f"Document {item.document_id} has no content_id, skipping"
                 )
                 await self.queue_repo.mark_failed(
-                    [item.id], "Document has no content_id"
+                    [item.id],
+                    "Document has no content_id",
+                    max_retries=MAX_EMBEDDING_RETRIES,
                 )
                 self._docs_failed += 1
                 return
@@ -228,7 +493,12 @@ class EmbeddingBatchProcessor:
             # external_id already has embeddings, clone them instead of
             # regenerating.  This avoids duplicate vectors in the HNSW index
             # for IMAP threads ingested from multiple accounts.
-            if doc.external_id and doc.external_id.startswith("imap-thread:"):
+            if (
+                item.namespace is None
+                and item.migration_id is None
+                and doc.external_id
+                and doc.external_id.startswith("imap-thread:")
+            ):
                 donor_id = await self.documents_repo.find_embedded_duplicate(
                     doc.external_id, item.document_id
                 )
@@ -253,7 +523,9 @@ class EmbeddingBatchProcessor:
                     f"Document {item.document_id} has empty content, skipping"
                 )
                 await self.queue_repo.mark_failed(
-                    [item.id], "Document has empty content"
+                    [item.id],
+                    "Document has empty content",
+                    max_retries=MAX_EMBEDDING_RETRIES,
                 )
                 self._docs_failed += 1
                 return
@@ -265,18 +537,47 @@ class EmbeddingBatchProcessor:
                 )  # TODO: address 3 chars per token assumption here
                 overlap = window_size // 4
                 stride = window_size - overlap
+                # Source files are chunked at function and class boundaries
+                chunking_mode = (
+                    "code" if doc.content_kind == "code" else chunking.chunking_mode
+                )
+
+                # Chunks that failed repeatedly for this content are skipped
+                quarantined_offsets = (
+                    await self.queue_repo.get_quarantined_chunk_offsets(
+                        item.document_id, doc.content_id
+                    )
+                )
 
                 all_chunks = []
+                failures: list[ChunkFailure] = []
+                last_error: Exception | None = None
                 offset = 0
                 while offset < len(content_text):
                     piece = content_text[offset : offset + window_size]
+                    if offset in quarantined_offsets:
+                        offset += stride
+                        continue
+
                     t0 = time.monotonic()
-                    chunk_results = await self.embedding_provider.generate_embeddings(
-                        text=piece,
-                        task="passage",
-                        chunk_size=512,
-                        chunking_mode="sentence",
-                    )
+                    try:
+                        chunk_results = await provider.generate_embeddings(
+                            text=piece,
+                            task="passage",
+                            chunk_size=chunking.chunk_size,
+                            chunking_mode=chunking_mode,
+                        )
+                    except Exception as e:
+                        logger.warning(
+                            f"Embedding chunk at offset {offset} of document "
+                            f"{item.document_id} failed: {e}"
+                        )
+                        failures.append(
+                            ChunkFailure(offset, offset + len(piece), str(e))
+                        )
+                        last_error = e
+                        offset += stride
+                        continue
                     elapsed_ms = (time.monotonic() - t0) * 1000
                     n_chunks = len(chunk_results) if chunk_results else 0
                     logger.debug(
@@ -295,6 +596,36 @@ class EmbeddingBatchProcessor:
 
                     offset += stride
 
+                if failures:
+                    if (
+                        not all_chunks
+                        or item.namespace is not None
+                        or item.migration_id is not None
+                    ):
+                        # Nothing embedded, which points at the provider rather
+                        # than the content: retry the whole document. Experiments
+                        # and migrations don't count towards production chunk
+                        # quarantine.
+                        raise last_error or RuntimeError("Embedding failed")
+
+                    # The rest of the document embeds fine, so these chunks are
+                    # likely poison. Retry them until they are quarantined.
+                    newly_quarantined = await self.queue_repo.record_chunk_failures(
+                        item.document_id,
+                        doc.content_id,
+                        failures,
+                        max_attempts=MAX_CHUNK_ATTEMPTS,
+                    )
+                    still_failing = [
+                        f for f in failures if f.start_offset not in newly_quarantined
+                    ]
+                    if still_failing:
+                        raise RuntimeError(
+                            f"{len(still_failing)} chunk(s) failed to embed: "
+                            f"{still_failing[0].error}"
+                        )
+                    quarantined_offsets |= newly_quarantined
+
                 chunks = all_chunks
 
                 if not chunks:
@@ -302,13 +633,24 @@ class EmbeddingBatchProcessor:
                         f"No embeddings generated for document {item.document_id}"
                     )
                     await self.queue_repo.mark_failed(
-                        [item.id], "No embeddings generated"
+                        [item.id],
+                        "No embeddings generated",
+                        max_retries=MAX_EMBEDDING_RETRIES,
                     )
                     self._docs_failed += 1
                     return
 
-                await self.embeddings_repo.delete_for_documents([item.document_id])
+                # Migration rows sit next to the current model's, so only
+                # the target model's are replaced
+                model_name = provider.get_model_name()
+                await self.embeddings_repo.delete_for_documents(
+                    [item.document_id],
+                    namespace=item.namespace,
+                    model_name=model_name if item.migration_id else None,
+                )
 
+                starts = page_starts(content_text)
+                sections = heading_sections(content_text)
                 embeddings_to_insert = []
                 for chunk_idx, chunk in enumerate(chunks):
                     embeddings_to_insert.append(
@@ -319,29 +661,76 @@ class EmbeddingBatchProcessor:
                             "chunk_start_offset": chunk.span[0],
                             "chunk_end_offset": chunk.span[1],
                             "embedding": chunk.embedding,
-                            "model_name": self.embedding_provider.get_model_name(),
+                            "model_name": model_name,
                             "dimensions": len(chunk.embedding),
+                            "namespace": item.namespace,
+                            "page_number": page_number_at(starts, chunk.span[0]),
+                            "heading_path": heading_path_at(sections, chunk.span[0]),
                         }
                     )
 
                 await self.embeddings_repo.bulk_insert(embeddings_to_insert)
 
-                await self.queue_repo.mark_completed([item.id])
+                await self.queue_repo.mark_completed(
+                    [item.id], quarantined_chunks=len(quarantined_offsets)
+                )
+                if item.namespace is None and item.migration_id is None:
+                    if self.summary_embeddings_enabled:
+                        await self._embed_summary(doc, content_text)
+                    # Keep running experiments in step with the new content
+                    await self.experiments_repo.enqueue_for_running(item.document_id)
 
                 self._docs_completed += 1
                 self._embeddings_written += len(chunks)
-                logger.info(
-                    f"Processed document {item.document_id}: {len(chunks)} chunks embedded"
-                )
+                if quarantined_offsets:
+                    skipped = len(quarantined_offsets)
+                    logger.warning(
+                        f"Processed document {item.document_id}: {len(chunks)} chunks "
+                        f"embedded, {skipped} quarantined chunks skipped"
+                    )
+                else:
+                    logger.info(
+                        f"Processed document {item.document_id}: {len(chunks)} chunks embedded"
+                    )
 
             except Exception as e:
                 logger.error(
                     f"Embedding generation failed for {item.document_id}: {e}",
                     exc_info=True,
                 )
-                await self.queue_repo.mark_failed([item.id], str(e))
+                await self.queue_repo.mark_failed(
+                    [item.id], str(e), max_retries=MAX_EMBEDDING_RETRIES
+                )
                 self._docs_failed += 1
 
+    async def _embed_summary(self, doc: Document, content_text: str) -> None:
+        """Embed the document's title and opening text as a single vector.
+
+        The summary only narrows searches, so a failure is logged and leaves
+        the chunk embeddings in place; the document is still found by the
+        full chunk search.
+        """
+        summary_text = content_text[:SUMMARY_EMBEDDING_MAX_CHARS]
+        if doc.title:
+            summary_text = f"{doc.title}\n\n{summary_text}"
+
+        try:
+            chunks = await self.embedding_provider.generate_embeddings(
+                text=summary_text,
+                task="passage",
+                chunk_size=None,
+                chunking_mode="none",
+            )
+            if not chunks:
+                return
+            await self.embeddings_repo.upsert_summary_embedding(
+                doc.id,
+                chunks[0].embedding,
+                self.embedding_provider.get_model_name(),
+            )
+        except Exception as e:
+            logger.warning(f"Summary embedding failed for document {doc.id}: {e}")
+
     async def _maybe_log_progress(self):
         """Log embedding progress periodically."""
         if self._last_progress_log_time is None:
@@ -352,9 +741,10 @@ class EmbeddingBatchProcessor:
             return
 
         self._last_progress_log_time = now
-        pending = await self.queue_repo.get_pending_count(
+        backlog = await self.queue_repo.get_backlog_stats(
             max_retries=MAX_EMBEDDING_RETRIES
         )
+        pending = backlog.pending
         total_completed = self._baseline_completed + self._docs_completed
         total_failed = self._baseline_failed + self._docs_failed
 
@@ -372,10 +762,13 @@ class EmbeddingBatchProcessor:
         )
 
         logger.info(
-            f"Embedding progress: {pending} pending | "
+            f"Embedding progress: {pending} pending "
+            f"(oldest {backlog.oldest_age_seconds or 0:.0f}s) | "
             f"{total_completed} completed, {total_failed} failed | "
             f"Throughput: {docs_per_min:.1f} docs/min, {chunks_per_min:.0f} chunks/min | "
             f"Avg embed time: {avg_embed_ms:.0f}ms/doc | "
+            f"Scale: level {self.scaler.level}, batch {self.scaler.batch_size}, "
+            f"concurrency {self.scaler.concurrency} | "
             f"ETA: {eta}"
         )
 
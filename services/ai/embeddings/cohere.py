@@ -121,6 +121,20 @@ class CohereEmbeddingProvider(EmbeddingProvider):
                     for span, embedding in zip(char_spans, embeddings)
                 ]
 
+            elif chunking_mode == "code":
+                max_chars = (
+                    resolve_chunk_size(chunk_size, self.max_model_len)
+                    * self.CHARS_PER_TOKEN
+                )
+                char_spans = Chunker.chunk_code_by_chars(text, max_chars)
+                chunk_texts = [text[start:end] for start, end in char_spans]
+
+                embeddings = await self._embed_texts(chunk_texts, cohere_task)
+                chunks = [
+                    Chunk(span, embedding)
+                    for span, embedding in zip(char_spans, embeddings)
+                ]
+
             else:
                 logger.warning(
                     f"Unsupported chunking mode: {chunking_mode}, using no chunking"
@@ -23,6 +23,8 @@ class JinaEmbeddingProvider(EmbeddingProvider):
     JINA_MAX_BATCH_SIZE = 2048
     JINA_MAX_RETRIES = 3
     JINA_RETRY_DELAY = 1.0
+    # Code chunks are cut by characters; source code tokenizes densely
+    CODE_CHARS_PER_TOKEN = 3
 
     def __init__(self, api_key: str, model: str, api_url: str, max_model_len: int):
         self.api_key = api_key
@@ -110,6 +112,23 @@ class JinaEmbeddingProvider(EmbeddingProvider):
                     for span, embedding in zip(char_spans, embeddings)
                 ]
 
+            elif chunking_mode == "code":
+                max_chars = (
+                    resolve_chunk_size(chunk_size, self.max_model_len)
+                    * self.CODE_CHARS_PER_TOKEN
+                )
+                char_spans = Chunker.chunk_code_by_chars(text, max_chars)
+
+                chunk_texts = [text[start:end] for start, end in char_spans]
+
+                embeddings = await self.client.generate_embeddings(
+                    chunk_texts, api_task
+                )
+                chunks = [
+                    Chunk(span, embedding)
+                    for span, embedding in zip(char_spans, embeddings)
+                ]
+
             else:
                 logger.warning(
                     f"Unsupported chunking mode: {chunking_mode}, using no chunking"
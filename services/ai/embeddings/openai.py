@@ -119,6 +119,24 @@ class OpenAIEmbeddingProvider(EmbeddingProvider):
                     for span, embedding in zip(char_spans, embeddings)
                 ]
 
+            elif chunking_mode == "code":
+                max_chars = (
+                    resolve_chunk_size(chunk_size, self.max_model_len)
+                    * CHARS_PER_TOKEN
+                )
+                char_spans = Chunker.chunk_code_by_chars(text, max_chars)
+                chunk_texts = [text[start:end] for start, end in char_spans]
+
+                t0 = time.monotonic()
+                embeddings = await self.client.generate_embeddings(chunk_texts)
+                logger.debug(
+                    f"Embedding API call: {len(chunk_texts)} texts in {(time.monotonic() - t0) * 1000:.0f}ms"
+                )
+                chunks = [
+                    Chunk(span, embedding)
+                    for span, embedding in zip(char_spans, embeddings)
+                ]
+
             else:
                 logger.warning(
                     f"Unsupported chunking mode: {chunking_mode}, using no chunking"
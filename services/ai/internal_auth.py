@@ -0,0 +1,17 @@
+"""Credentials for calls to the other Omni services.
+
+The searcher, indexer, connector-manager and sandbox reject requests without a
+valid service key once they have internal auth enabled.
+"""
+
+import os
+
+SERVICE_KEY_HEADER = "x-omni-service-key"
+
+
+def internal_auth_headers() -> dict[str, str]:
+    """Headers to send with every request to another Omni service."""
+    key = os.getenv("AI_INTERNAL_AUTH_KEY") or os.getenv("INTERNAL_AUTH_KEY")
+    if not key or key.strip() == "":
+        return {}
+    return {SERVICE_KEY_HEADER: key.strip()}
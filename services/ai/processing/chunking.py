@@ -14,6 +14,15 @@ _chunking_executor = ThreadPoolExecutor(
     max_workers=_chunking_max_workers, thread_name_prefix="chunker"
 )
 
+# An unindented line opening a definition, after any modifiers
+_CODE_DEFINITION_PATTERN = re.compile(
+    r"(?:(?:pub(?:\([^)]*\))?|export|default|async|public|private|protected"
+    r"|internal|static|abstract|final|unsafe|open|data|sealed)\s+)*"
+    r"(?:def|class|fn|func|fun|function|impl|struct|enum|trait|interface"
+    r"|type|module|object|record)\b"
+)
+_CODE_PREAMBLE_PREFIXES = ("@", "#", "//", "/*", "*", "--")
+
 
 class Chunker:
 
@@ -81,6 +90,57 @@ class Chunker:
 
         return chunks if chunks else [(0, len(text))]
 
+    @staticmethod
+    def chunk_code_by_chars(text: str, max_chars: int) -> list[tuple[int, int]]:
+        """Chunk source code at top-level blocks, keeping chunks under
+        max_chars (character-based). A block starts at an unindented line that
+        follows a blank line or opens a definition, so functions and classes
+        stay whole when they fit. Longer blocks are split at line ends."""
+        if not text or max_chars < 1:
+            return []
+
+        block_starts = [0]
+        pos = 0
+        previous = ""
+        for line in text.splitlines(keepends=True):
+            # Comments, decorators and attributes stay with the definition
+            # that follows them
+            attached = previous.lstrip().startswith(_CODE_PREAMBLE_PREFIXES)
+            if (
+                pos > 0
+                and line.strip()
+                and not line[0].isspace()
+                and (
+                    not previous.strip()
+                    or (_CODE_DEFINITION_PATTERN.match(line) and not attached)
+                )
+            ):
+                block_starts.append(pos)
+            previous = line
+            pos += len(line)
+
+        chunks = []
+        chunk_start = 0
+        chunk_end = 0
+        for block_end in block_starts[1:] + [len(text)]:
+            if block_end - chunk_start > max_chars and chunk_end > chunk_start:
+                chunks.append((chunk_start, chunk_end))
+                chunk_start = chunk_end
+            chunk_end = block_end
+        chunks.append((chunk_start, chunk_end))
+
+        final_chunks = []
+        for start, end in chunks:
+            while end - start > max_chars:
+                split = text.rfind("\n", start, start + max_chars) + 1
+                if split <= start:
+                    split = start + max_chars
+                final_chunks.append((start, split))
+                start = split
+            final_chunks.append((start, end))
+
+        return final_chunks
+
     @staticmethod
     def _check_text_length(text: str, tokenizer: AutoTokenizer):
         max_len = getattr(tokenizer, "model_max_length", None)
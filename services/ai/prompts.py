@@ -16,6 +16,9 @@ SOURCE_DISPLAY_NAMES = {
     "jira": "Jira",
     "slack": "Slack",
     "hubspot": "HubSpot",
+    "zendesk": "Zendesk",
+    "s3": "Amazon S3",
+    "push": "Push API",
     "fireflies": "Fireflies",
     "web": "Web",
     "local_files": "Files",
@@ -35,6 +38,7 @@ SOURCE_DISPLAY_NAMES = {
     "google_calendar": "Google Calendar",
     "microsoft_teams": "Microsoft Teams",
     "google_ads": "Google Ads",
+    "chat_upload": "Chat uploads",
 }
 
 _SKILLS_DIR = Path(__file__).resolve().parent / "skills"
@@ -279,10 +283,14 @@ def _build_toolsets_section(
         source_type = ts["source_type"]
         display = SOURCE_DISPLAY_NAMES.get(source_type, source_type)
         sample = ", ".join(ts.get("sample_tool_names") or []) or "—"
-        lines.append(
+        line = (
             f"- {source_type} (source_id={ts['source_id']}): {display} · "
             f"{ts['source_name']} · {ts['tool_count']} tools (e.g. {sample})"
         )
+        description = ts.get("description")
+        if description:
+            line += f" — {description}"
+        lines.append(line)
     return "\n".join(lines)
 
 
@@ -364,7 +372,8 @@ def build_chat_system_prompt(
     Args:
         sources: list of Source dataclass instances (from db.models)
         toolsets: list of dicts produced by ConnectorToolHandler.list_toolsets().
-            Each entry: source_id, source_type, source_name, tool_count, sample_tool_names.
+            Each entry: source_id, source_type, source_name, tool_count,
+            sample_tool_names, description.
         loaded_source_ids: source_ids whose tools are already loaded into this chat.
         user_name: display name of the current user
         user_email: email of the current user
@@ -50,6 +50,7 @@ from db.tool_approvals import (
 from db.uploads import UploadsRepository
 from db.usage import UsageRepository
 from db.users import UsersRepository
+from internal_auth import internal_auth_headers
 from memory import (
     MemoryMode,
     agent_key,
@@ -227,7 +228,9 @@ def _loaded_source_ids(
 
 async def _fetch_sources_from_connector_manager() -> list[Source] | None:
     try:
-        async with httpx.AsyncClient(timeout=10.0) as client:
+        async with httpx.AsyncClient(
+            timeout=10.0, headers=internal_auth_headers()
+        ) as client:
             resp = await client.get(f"{CONNECTOR_MANAGER_URL.rstrip('/')}/sources")
             resp.raise_for_status()
             return sources_from_sync_overview_response(resp.json())
@@ -1197,7 +1200,9 @@ async def download_artifact(
     path: str = Path(..., description="Relative file path in the sandbox"),
 ):
     try:
-        async with httpx.AsyncClient(timeout=30.0) as client:
+        async with httpx.AsyncClient(
+            timeout=30.0, headers=internal_auth_headers()
+        ) as client:
             resp = await client.get(
                 f"{SANDBOX_URL}/files/download",
                 params={"chat_id": chat_id, "path": path},
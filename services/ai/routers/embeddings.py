@@ -2,10 +2,17 @@
 
 import logging
 import uuid
+from dataclasses import asdict
 
 from fastapi import APIRouter, HTTPException, Request
 
-from schemas import EmbeddingRequest, EmbeddingResponse
+from db import (
+    ChunkingConfig,
+    EmbeddingExperimentsRepository,
+    ExperimentExistsError,
+    ExperimentNotRunningError,
+)
+from schemas import EmbeddingExperimentRequest, EmbeddingRequest, EmbeddingResponse
 
 logger = logging.getLogger(__name__)
 router = APIRouter(tags=["embeddings"])
@@ -18,6 +25,7 @@ async def generate_embeddings(request: Request, body: EmbeddingRequest):
     Chunking behavior:
     - fixed mode: chunk_size sets the number of tokens per chunk
     - sentence mode: groups sentences until chunk_size tokens limit
+    - code mode: groups top-level code blocks until chunk_size tokens limit
     - none mode: embed entire text without chunking
     """
     logger.info(
@@ -25,7 +33,7 @@ async def generate_embeddings(request: Request, body: EmbeddingRequest):
     )
 
     # Validate chunking method
-    valid_chunking_modes = ["sentence", "fixed", "none"]
+    valid_chunking_modes = ["sentence", "fixed", "code", "none"]
     if body.chunking_mode not in valid_chunking_modes:
         raise HTTPException(
             status_code=422,
@@ -48,3 +56,60 @@ async def generate_embeddings(request: Request, body: EmbeddingRequest):
     except Exception as e:
         logger.error(f"Failed to generate embeddings: {str(e)}")
         raise HTTPException(status_code=500, detail=str(e))
+
+
+@router.get("/embeddings/backlog")
+async def embedding_backlog(request: Request):
+    """Embedding queue backlog size and age, with the processor's current
+    throughput scaling."""
+    status = getattr(request.app.state, "embedding_backlog", None)
+    if status is None:
+        return {"status": "idle"}
+    return {"status": "active", **asdict(status)}
+
+
+@router.post("/embeddings/experiments", status_code=201)
+async def create_embedding_experiment(body: EmbeddingExperimentRequest):
+    """Start re-embedding already-embedded documents with alternative chunking
+    into a shadow namespace named after the experiment. Search it by passing
+    `embedding_namespace` to the searcher."""
+    repo = EmbeddingExperimentsRepository()
+    try:
+        experiment, queued = await repo.create(
+            body.name,
+            ChunkingConfig(body.chunk_size, body.chunking_mode),
+            source_ids=body.source_ids,
+        )
+    except ExperimentExistsError:
+        raise HTTPException(
+            status_code=409, detail=f"Experiment {body.name} already exists"
+        )
+    return {"experiment": asdict(experiment), "queued_documents": queued}
+
+
+@router.get("/embeddings/experiments")
+async def list_embedding_experiments():
+    """Experiments with how many documents each has embedded so far."""
+    repo = EmbeddingExperimentsRepository()
+    progress = await repo.list_with_progress()
+    return {"experiments": [asdict(p) for p in progress]}
+
+
+@router.post("/embeddings/experiments/{name}/promote")
+async def promote_embedding_experiment(name: str):
+    """Swap the experiment's embeddings in as production and make its chunking
+    the production chunking."""
+    repo = EmbeddingExperimentsRepository()
+    try:
+        result = await repo.promote(name)
+    except ExperimentNotRunningError:
+        raise HTTPException(status_code=409, detail=f"Experiment {name} is not running")
+    return asdict(result)
+
+
+@router.delete("/embeddings/experiments/{name}", status_code=204)
+async def delete_embedding_experiment(name: str):
+    """Drop the experiment with its shadow embeddings and queued work."""
+    repo = EmbeddingExperimentsRepository()
+    if not await repo.delete(name):
+        raise HTTPException(status_code=404, detail=f"Experiment {name} not found")
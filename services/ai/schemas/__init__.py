@@ -1,6 +1,7 @@
 from .api import (
     Priority,
     PrioritizedRequest,
+    EmbeddingExperimentRequest,
     EmbeddingRequest,
     EmbeddingResponse,
     PromptRequest,
@@ -10,6 +11,7 @@ from .api import (
 __all__ = [
     "Priority",
     "PrioritizedRequest",
+    "EmbeddingExperimentRequest",
     "EmbeddingRequest",
     "EmbeddingResponse",
     "PromptRequest",
@@ -6,7 +6,7 @@ from dataclasses import dataclass, field
 from enum import IntEnum
 from typing import Literal
 
-from pydantic import BaseModel
+from pydantic import BaseModel, Field
 
 
 class Priority(IntEnum):
@@ -34,10 +34,19 @@ class EmbeddingRequest(BaseModel):
     texts: list[str]
     task: str | None = "passage"
     chunk_size: int | None = 512  # Chunk size in tokens
-    chunking_mode: str | None = "sentence"  # "sentence", "fixed", or "none"
+    chunking_mode: str | None = "sentence"  # "sentence", "fixed", "code" or "none"
     priority: Literal["high", "normal", "low"] | None = "normal"
 
 
+class EmbeddingExperimentRequest(BaseModel):
+    """Request to start a chunking experiment in a shadow namespace."""
+
+    name: str = Field(pattern=r"^[a-z0-9][a-z0-9_-]{0,62}$")
+    chunk_size: int = Field(ge=16, le=8192)  # Chunk size in tokens
+    chunking_mode: Literal["sentence", "fixed"] = "sentence"
+    source_ids: list[str] | None = None  # Limit the backfill to these sources
+
+
 class EmbeddingResponse(BaseModel):
     """Response containing generated embeddings."""
 
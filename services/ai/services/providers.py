@@ -22,7 +22,7 @@ from db import (
 )
 from db.listener import start_db_listener
 from providers import create_llm_provider, LLMProvider
-from embeddings import create_embedding_provider
+from embeddings import build_embedding_provider
 from tools import SearcherTool
 from storage import create_content_storage
 from embeddings.batch_processor import start_batch_processing
@@ -160,63 +160,7 @@ async def _init_embedding_provider(app_state: AppState) -> None:
     provider = embedding_config.provider
     logger.info(f"Loaded embedding configuration (provider: {provider})")
 
-    max_model_len = embedding_config.max_model_len or 8192
-
-    if provider == "jina":
-        if not embedding_config.api_key:
-            raise ValueError("Embedding API key is required when using Jina provider")
-        app_state.embedding_provider = create_embedding_provider(
-            "jina",
-            api_key=embedding_config.api_key,
-            model=embedding_config.model,
-            api_url=embedding_config.api_url,
-            max_model_len=max_model_len,
-        )
-
-    elif provider == "bedrock":
-        region_name = AWS_REGION if AWS_REGION else None
-        app_state.embedding_provider = create_embedding_provider(
-            "bedrock",
-            model_id=embedding_config.model,
-            region_name=region_name,
-            max_model_len=max_model_len,
-        )
-
-    elif provider == "openai":
-        if not embedding_config.api_key:
-            raise ValueError("Embedding API key is required when using OpenAI provider")
-        app_state.embedding_provider = create_embedding_provider(
-            "openai",
-            api_key=embedding_config.api_key,
-            model=embedding_config.model,
-            api_url=embedding_config.api_url,
-            dimensions=embedding_config.dimensions,
-            max_model_len=max_model_len,
-        )
-
-    elif provider == "cohere":
-        if not embedding_config.api_key:
-            raise ValueError("Embedding API key is required when using Cohere provider")
-        app_state.embedding_provider = create_embedding_provider(
-            "cohere",
-            api_key=embedding_config.api_key,
-            model=embedding_config.model,
-            api_url=embedding_config.api_url,
-            max_model_len=max_model_len,
-            dimensions=embedding_config.dimensions,
-        )
-
-    elif provider == "local":
-        app_state.embedding_provider = create_embedding_provider(
-            "local",
-            base_url=embedding_config.api_url or "",
-            model=embedding_config.model,
-            max_model_len=max_model_len,
-        )
-
-    else:
-        raise ValueError(f"Unknown embedding provider: {provider}")
-
+    app_state.embedding_provider = build_embedding_provider(embedding_config)
     app_state.embedding_provider_type = provider
     logger.info(
         f"Initialized {provider} embedding provider with model: {app_state.embedding_provider.get_model_name()}"
@@ -7,6 +7,7 @@ from datetime import datetime
 import redis.asyncio as aioredis
 
 from embeddings import EmbeddingProvider
+from embeddings.backlog_scaling import BacklogStatus
 from providers import LLMProvider
 from tools import SearcherTool
 from storage import ContentStorage
@@ -26,6 +27,7 @@ class AppState:
     embedding_provider_type: str | None = None
     embedding_provider_id: str | None = None
     embedding_provider_updated_at: datetime | None = None
+    embedding_backlog: BacklogStatus | None = None
     models: dict[str, LLMProvider] = field(default_factory=dict)
     default_model_id: str | None = None
     secondary_model_id: str | None = None
@@ -97,6 +97,7 @@ async def test_online_processes_document_end_to_end(
     embeddings = await embeddings_repo.get_for_document(doc_id)
     assert len(embeddings) >= 1
     assert len(embeddings[0].embedding) == 1024
+    assert embeddings[0].page_number is None
 
     queue_item = await queue_repo.get_by_id(queue_id)
     assert queue_item.status == "completed"
@@ -335,6 +336,89 @@ async def test_online_processes_large_document_with_sliding_window(
     assert provider.generate_embeddings.call_count == 7
 
 
+@pytest.mark.integration
+async def test_chunks_record_page_of_paged_content(
+    db_pool,
+    online_processor,
+    embeddings_repo,
+    mock_embedding_provider,
+):
+    """Chunks of extracted PDF text record the page they start on."""
+
+    def chunk_per_page(text, **kwargs):
+        chunks = []
+        start = 0
+        for page in text.split("\f"):
+            chunk = MagicMock()
+            chunk.span = (start, start + len(page))
+            chunk.embedding = [0.1] * 1024
+            chunks.append(chunk)
+            start += len(page) + 1
+        return chunks
+
+    mock_embedding_provider.generate_embeddings.side_effect = chunk_per_page
+
+    user_id = await create_test_user(db_pool)
+    source_id = await create_test_source(db_pool, user_id)
+    doc_id = await create_test_document(
+        db_pool, source_id, "Cover\fIntroduction\f\fAppendix"
+    )
+    await enqueue_document(db_pool, doc_id)
+
+    await online_processor._process_online_batch()
+
+    embeddings = await embeddings_repo.get_for_document(doc_id)
+    assert [e.page_number for e in embeddings] == [1, 2, 3, 4]
+
+
+@pytest.mark.integration
+async def test_chunks_record_heading_path(
+    db_pool,
+    online_processor,
+    embeddings_repo,
+    mock_embedding_provider,
+):
+    """Chunks record the markdown headings enclosing their start."""
+
+    def chunk_per_paragraph(text, **kwargs):
+        chunks = []
+        start = 0
+        for paragraph in text.split("\n\n"):
+            chunk = MagicMock()
+            chunk.span = (start, start + len(paragraph))
+            chunk.embedding = [0.1] * 1024
+            chunks.append(chunk)
+            start += len(paragraph) + 2
+        return chunks
+
+    mock_embedding_provider.generate_embeddings.side_effect = chunk_per_paragraph
+
+    user_id = await create_test_user(db_pool)
+    source_id = await create_test_source(db_pool, user_id)
+    content = (
+        "Owned by the platform team.\n\n"
+        "# Runbook\n\n"
+        "## Incident Response\n\n"
+        "```\n# restart the pager\n```\n\n"
+        "### Paging\n\n"
+        "## Escalation"
+    )
+    doc_id = await create_test_document(db_pool, source_id, content)
+    await enqueue_document(db_pool, doc_id)
+
+    await online_processor._process_online_batch()
+
+    embeddings = await embeddings_repo.get_for_document(doc_id)
+    assert [e.heading_path for e in embeddings] == [
+        None,
+        "Runbook",
+        "Runbook > Incident Response",
+        "Runbook > Incident Response",
+        "Runbook > Incident Response > Paging",
+        "Runbook > Escalation",
+    ]
+
+
 # =============================================================================
 # Retry Behavior Tests
 # =============================================================================
@@ -419,3 +503,256 @@ async def test_max_retries_exhausted_items_are_not_retried(
     item = await queue_repo.get_by_id(queue_id)
     assert item.status == "failed"
     assert item.retry_count == 5
+
+
+@pytest.mark.integration
+async def test_items_are_quarantined_when_retries_run_out(
+    db_pool,
+    online_processor,
+    queue_repo,
+    mock_embedding_provider,
+):
+    """The final failed attempt moves the item to quarantine with its error."""
+    user_id = await create_test_user(db_pool)
+    source_id = await create_test_source(db_pool, user_id)
+    doc_id = await create_test_document(
+        db_pool, source_id, "Content that never embeds."
+    )
+
+    queue_id = str(ulid.ULID())
+    async with db_pool.acquire() as conn:
+        await conn.execute(
+            """INSERT INTO embedding_queue (id, document_id, status, retry_count)
+               VALUES ($1, $2, 'failed', 4)""",
+            queue_id,
+            doc_id,
+        )
+
+    mock_embedding_provider.generate_embeddings.side_effect = RuntimeError(
+        "Permanent API error"
+    )
+
+    await online_processor._process_online_batch()
+
+    item = await queue_repo.get_by_id(queue_id)
+    assert item.status == "quarantined"
+    assert item.retry_count == 5
+    assert "Permanent API error" in item.error_message
+
+    async with db_pool.acquire() as conn:
+        quarantined_at = await conn.fetchval(
+            "SELECT quarantined_at FROM embedding_queue WHERE id = $1", queue_id
+        )
+    assert quarantined_at is not None
+
+
+# =============================================================================
+# Chunk Quarantine Tests
+# =============================================================================
+
+
+@pytest.mark.integration
+async def test_poison_chunk_is_quarantined_and_rest_of_document_indexed(
+    db_pool,
+    online_processor_with_sliding_window,
+    queue_repo,
+    embeddings_repo,
+    monkeypatch,
+):
+    """A chunk that keeps failing is quarantined after MAX_CHUNK_ATTEMPTS and
+    the other windows of the document are still embedded."""
+    import embeddings.batch_processor as bp
+
+    monkeypatch.setattr(bp, "EMBEDDING_MAX_MODEL_LEN", 33)
+    monkeypatch.setattr(bp, "ONLINE_POLL_INTERVAL", 0)
+
+    provider = online_processor_with_sliding_window.embedding_provider
+    embed_window = provider.generate_embeddings.side_effect
+
+    async def fail_on_poison(text, **kwargs):
+        if "Poisoned" in text:
+            raise RuntimeError("Input rejected by provider")
+        return await embed_window(text, **kwargs)
+
+    provider.generate_embeddings.side_effect = fail_on_poison
+
+    user_id = await create_test_user(db_pool)
+    source_id = await create_test_source(db_pool, user_id)
+
+    # Only the first window (offset 0) contains the poison text
+    content = "Poisoned chunk text. " + "This is a test sentence. " * 19
+    doc_id = await create_test_document(db_pool, source_id, content)
+    queue_id = await enqueue_document(db_pool, doc_id)
+
+    for attempt in range(1, bp.MAX_CHUNK_ATTEMPTS):
+        await online_processor_with_sliding_window._process_online_batch()
+        item = await queue_repo.get_by_id(queue_id)
+        assert item.status == "failed"
+        assert item.retry_count == attempt
+
+    await online_processor_with_sliding_window._process_online_batch()
+
+    item = await queue_repo.get_by_id(queue_id)
+    assert item.status == "completed"
+
+    embeddings = await embeddings_repo.get_for_document(doc_id)
+    assert len(embeddings) == 6
+    assert all(e.chunk_start_offset != 0 for e in embeddings)
+
+    async with db_pool.acquire() as conn:
+        quarantined_chunks = await conn.fetchval(
+            "SELECT quarantined_chunks FROM embedding_queue WHERE id = $1",
+            queue_id,
+        )
+        failure = await conn.fetchrow(
+            """SELECT chunk_start_offset, status, attempts, error_message
+               FROM embedding_chunk_failures WHERE document_id = $1""",
+            doc_id,
+        )
+    assert quarantined_chunks == 1
+    assert failure["chunk_start_offset"] == 0
+    assert failure["status"] == "quarantined"
+    assert failure["attempts"] == bp.MAX_CHUNK_ATTEMPTS
+    assert "Input rejected" in failure["error_message"]
+
+
+@pytest.mark.integration
+async def test_summary_embedding_written_when_enabled(
+    db_pool,
+    online_processor,
+    queue_repo,
+    embeddings_repo,
+    mock_embedding_provider,
+):
+    """Production items get one summary vector from the title and opening text."""
+    online_processor.summary_embeddings_enabled = True
+    user_id = await create_test_user(db_pool)
+    source_id = await create_test_source(db_pool, user_id)
+    doc_id = await create_test_document(
+        db_pool, source_id, "Quarterly planning notes for the platform team."
+    )
+    queue_id = await enqueue_document(db_pool, doc_id)
+
+    await online_processor._process_online_batch()
+
+    queue_item = await queue_repo.get_by_id(queue_id)
+    assert queue_item.status == "completed"
+
+    summary = await embeddings_repo.get_summary_embedding(doc_id)
+    assert summary is not None
+    assert summary.dimensions == 1024
+    assert summary.model_name == "test-embedding-model"
+
+    summary_call = mock_embedding_provider.generate_embeddings.call_args_list[-1]
+    assert summary_call.kwargs["chunking_mode"] == "none"
+    assert "Quarterly planning notes" in summary_call.kwargs["text"]
+
+
+@pytest.mark.integration
+async def test_summary_embedding_failure_does_not_fail_document(
+    db_pool,
+    online_processor,
+    queue_repo,
+    embeddings_repo,
+    mock_embedding_provider,
+):
+    """The summary is advisory: chunk embeddings are kept if it fails."""
+    online_processor.summary_embeddings_enabled = True
+    chunk_results = mock_embedding_provider.generate_embeddings.return_value
+
+    async def fail_unchunked(text, **kwargs):
+        if kwargs["chunking_mode"] == "none":
+            raise RuntimeError("Provider unavailable")
+        return chunk_results
+
+    mock_embedding_provider.generate_embeddings.side_effect = fail_unchunked
+
+    user_id = await create_test_user(db_pool)
+    source_id = await create_test_source(db_pool, user_id)
+    doc_id = await create_test_document(db_pool, source_id, "Content to embed.")
+    queue_id = await enqueue_document(db_pool, doc_id)
+
+    await online_processor._process_online_batch()
+
+    queue_item = await queue_repo.get_by_id(queue_id)
+    assert queue_item.status == "completed"
+    assert len(await embeddings_repo.get_for_document(doc_id)) == 1
+    assert await embeddings_repo.get_summary_embedding(doc_id) is None
+
+
+async def _start_embedding_migration(db_pool, to_model: str) -> str:
+    """Insert a target provider and a running migration to it."""
+    provider_id = str(ulid.ULID())
+    migration_id = str(ulid.ULID())
+    async with db_pool.acquire() as conn:
+        await conn.execute(
+            """INSERT INTO embedding_providers (id, name, provider_type, config)
+               VALUES ($1, 'Target', 'openai', $2)""",
+            provider_id,
+            f'{{"model": "{to_model}", "apiKey": "test-key"}}',
+        )
+        await conn.execute(
+            """INSERT INTO embedding_migrations (id, to_provider_id, from_model, to_model)
+               VALUES ($1, $2, 'test-embedding-model', $3)""",
+            migration_id,
+            provider_id,
+            to_model,
+        )
+    return migration_id
+
+
+@pytest.mark.integration
+async def test_migration_items_embed_next_to_current_model(
+    db_pool,
+    online_processor,
+    queue_repo,
+    embeddings_repo,
+    mock_embedding_provider,
+    monkeypatch,
+):
+    """Migration items write target-model rows and leave the current ones, and
+    production items queued mid-migration are queued for the target too."""
+    user_id = await create_test_user(db_pool)
+    source_id = await create_test_source(db_pool, user_id)
+    doc_id = await create_test_document(db_pool, source_id, "Content to migrate.")
+    await enqueue_document(db_pool, doc_id)
+    await online_processor._process_online_batch()
+
+    target_provider = AsyncMock()
+    target_provider.get_model_name = MagicMock(return_value="new-embedding-model")
+    target_provider.generate_embeddings.return_value = (
+        mock_embedding_provider.generate_embeddings.return_value
+    )
+    monkeypatch.setattr(
+        "embeddings.batch_processor.build_embedding_provider",
+        lambda config: target_provider,
+    )
+    migration_id = await _start_embedding_migration(db_pool, "new-embedding-model")
+    async with db_pool.acquire() as conn:
+        await conn.execute(
+            """INSERT INTO embedding_queue (id, document_id, migration_id, priority)
+               VALUES ($1, $2, $3, -1)""",
+            str(ulid.ULID()),
+            doc_id,
+            migration_id,
+        )
+
+    await online_processor._process_online_batch()
+
+    embeddings = await embeddings_repo.get_for_document(doc_id)
+    assert sorted(e.model_name for e in embeddings) == [
+        "new-embedding-model",
+        "test-embedding-model",
+    ]
+
+    await enqueue_document(db_pool, doc_id)
+    await online_processor._process_online_batch()
+
+    async with db_pool.acquire() as conn:
+        pending = await conn.fetchval(
+            """SELECT COUNT(*) FROM embedding_queue
+               WHERE document_id = $1 AND migration_id = $2 AND status = 'pending'""",
+            doc_id,
+            migration_id,
+        )
+    assert pending == 1
@@ -760,6 +760,7 @@ async def test_agent_registry_applies_user_agent_source_filter(
                 "source_name": "Work Gmail",
                 "tool_count": 1,
                 "sample_tool_names": ["list_threads"],
+                "description": "",
             }
         ]
     finally:
@@ -0,0 +1,266 @@
+"""Integration tests for chunking experiments embedding into shadow namespaces."""
+
+from unittest.mock import AsyncMock, MagicMock
+
+import pytest
+import ulid
+
+from db import (
+    ChunkingConfig,
+    EmbeddingExperimentsRepository,
+    ExperimentExistsError,
+    ExperimentNotRunningError,
+)
+from embeddings.batch_processor import EmbeddingBatchProcessor
+from state import AppState
+from tests.helpers import (
+    create_test_document_with_content as create_test_document,
+)
+from tests.helpers import (
+    create_test_source,
+    enqueue_document,
+)
+from tests.helpers import (
+    create_test_user as _create_test_user_full,
+)
+
+
+@pytest.fixture
+def experiments_repo(db_pool):
+    return EmbeddingExperimentsRepository(db_pool)
+
+
+@pytest.fixture
+async def name(experiments_repo):
+    """A fresh experiment name; the experiment is deleted afterwards so it
+    doesn't pick up documents embedded by other tests."""
+    experiment_name = f"exp-{str(ulid.ULID()).lower()}"
+    yield experiment_name
+    await experiments_repo.delete(experiment_name)
+
+
+@pytest.fixture
+def recording_provider():
+    """Provider returning one chunk per call and recording the chunking used."""
+    provider = AsyncMock()
+    provider.get_model_name = MagicMock(return_value="test-embedding-model")
+
+    async def generate(text, **kwargs):
+        chunk = MagicMock()
+        chunk.span = (0, len(text))
+        chunk.embedding = [0.2] * 1024
+        return [chunk]
+
+    provider.generate_embeddings.side_effect = generate
+    return provider
+
+
+@pytest.fixture
+def processor(
+    db_pool, documents_repo, queue_repo, embeddings_repo, recording_provider
+):
+    content_storage = AsyncMock()
+
+    async def get_text_from_db(content_id):
+        async with db_pool.acquire() as conn:
+            row = await conn.fetchrow(
+                "SELECT content FROM content_blobs WHERE id = $1", content_id
+            )
+            return row["content"].decode() if row else None
+
+    content_storage.get_text = get_text_from_db
+
+    state = AppState()
+    state.embedding_provider = recording_provider
+    state.embedding_provider_type = "jina"
+    state.content_storage = content_storage
+    return EmbeddingBatchProcessor(
+        documents_repo=documents_repo,
+        queue_repo=queue_repo,
+        embeddings_repo=embeddings_repo,
+        app_state=state,
+    )
+
+
+async def _embedded_document(db_pool, embeddings_repo) -> tuple[str, str]:
+    """A document with one production embedding. Returns (source_id, doc_id)."""
+    user_id, _ = await _create_test_user_full(db_pool)
+    source_id = await create_test_source(db_pool, user_id)
+    doc_id = await create_test_document(
+        db_pool, source_id, "Experiment content. Second sentence."
+    )
+    await embeddings_repo.bulk_insert(
+        [
+            {
+                "id": str(ulid.ULID()),
+                "document_id": doc_id,
+                "chunk_index": 0,
+                "chunk_start_offset": 0,
+                "chunk_end_offset": 20,
+                "embedding": [0.1] * 1024,
+                "model_name": "test-embedding-model",
+                "dimensions": 1024,
+            }
+        ]
+    )
+    return source_id, doc_id
+
+
+async def _drain_until_done(processor, queue_repo, item_id: str):
+    for _ in range(20):
+        item = await queue_repo.get_by_id(item_id)
+        if item.status == "completed":
+            return item
+        await processor._process_online_batch()
+    return await queue_repo.get_by_id(item_id)
+
+
+async def _queue_item_id(db_pool, doc_id: str, namespace: str | None) -> str:
+    async with db_pool.acquire() as conn:
+        return await conn.fetchval(
+            """
+            SELECT id FROM embedding_queue
+            WHERE document_id = $1 AND namespace IS NOT DISTINCT FROM $2
+            ORDER BY created_at DESC
+            LIMIT 1
+            """,
+            doc_id,
+            namespace,
+        )
+
+
+@pytest.mark.integration
+async def test_create_queues_embedded_documents_of_selected_sources(
+    db_pool, name, experiments_repo, embeddings_repo
+):
+    source_id, doc_id = await _embedded_document(db_pool, embeddings_repo)
+
+    experiment, queued = await experiments_repo.create(
+        name, ChunkingConfig(256, "fixed"), source_ids=[source_id]
+    )
+
+    assert experiment.status == "running"
+    assert experiment.chunking == ChunkingConfig(256, "fixed")
+    assert queued == 1
+    assert await _queue_item_id(db_pool, doc_id, name) is not None
+
+    with pytest.raises(ExperimentExistsError):
+        await experiments_repo.create(name, ChunkingConfig(128, "sentence"))
+
+
+@pytest.mark.integration
+async def test_experiment_item_writes_shadow_rows_with_its_chunking(
+    db_pool,
+    name,
+    processor,
+    experiments_repo,
+    queue_repo,
+    embeddings_repo,
+    recording_provider,
+):
+    source_id, doc_id = await _embedded_document(db_pool, embeddings_repo)
+    await experiments_repo.create(
+        name, ChunkingConfig(128, "fixed"), source_ids=[source_id]
+    )
+
+    item = await _drain_until_done(
+        processor, queue_repo, await _queue_item_id(db_pool, doc_id, name)
+    )
+
+    assert item.status == "completed"
+    shadow = await embeddings_repo.get_for_document(doc_id, namespace=name)
+    assert len(shadow) == 1
+    assert shadow[0].namespace == name
+    production = await embeddings_repo.get_for_document(doc_id)
+    assert [e.embedding[0] for e in production] == pytest.approx([0.1])
+
+    _, kwargs = recording_provider.generate_embeddings.call_args
+    assert kwargs["chunk_size"] == 128
+    assert kwargs["chunking_mode"] == "fixed"
+
+    [progress] = [
+        p
+        for p in await experiments_repo.list_with_progress()
+        if p.experiment.name == name
+    ]
+    assert progress.embedded_documents == 1
+    assert progress.chunks == 1
+    assert progress.pending == 0
+
+
+@pytest.mark.integration
+async def test_production_reembedding_queues_running_experiments(
+    db_pool, name, processor, experiments_repo, queue_repo, embeddings_repo
+):
+    source_id, doc_id = await _embedded_document(db_pool, embeddings_repo)
+    await experiments_repo.create(
+        name, ChunkingConfig(128, "fixed"), source_ids=[source_id]
+    )
+    await _drain_until_done(
+        processor, queue_repo, await _queue_item_id(db_pool, doc_id, name)
+    )
+
+    production_item = await enqueue_document(db_pool, doc_id)
+    await _drain_until_done(processor, queue_repo, production_item)
+
+    async with db_pool.acquire() as conn:
+        experiment_items = await conn.fetchval(
+            "SELECT COUNT(*) FROM embedding_queue WHERE document_id = $1 AND namespace = $2",
+            doc_id,
+            name,
+        )
+    assert experiment_items == 2
+    # The production re-embed left the shadow rows alone
+    assert len(await embeddings_repo.get_for_document(doc_id, namespace=name)) == 1
+
+
+@pytest.mark.integration
+async def test_promote_swaps_shadow_rows_into_production(
+    db_pool, name, processor, experiments_repo, queue_repo, embeddings_repo
+):
+    source_id, doc_id = await _embedded_document(db_pool, embeddings_repo)
+    _, uncovered_doc_id = await _embedded_document(db_pool, embeddings_repo)
+    await experiments_repo.create(
+        name, ChunkingConfig(1024, "fixed"), source_ids=[source_id]
+    )
+    await _drain_until_done(
+        processor, queue_repo, await _queue_item_id(db_pool, doc_id, name)
+    )
+
+    result = await experiments_repo.promote(name)
+
+    assert result.promoted_documents == 1
+    assert result.requeued_documents >= 1
+    production = await embeddings_repo.get_for_document(doc_id)
+    assert len(production) == 1
+    assert production[0].embedding[0] == pytest.approx(0.2)
+    assert await embeddings_repo.get_for_document(doc_id, namespace=name) == []
+    # Documents the experiment never reached are re-embedded in production
+    assert await _queue_item_id(db_pool, uncovered_doc_id, None) is not None
+
+    assert await experiments_repo.get_production_chunking() == ChunkingConfig(
+        1024, "fixed"
+    )
+    assert (await experiments_repo.get(name)).status == "promoted"
+    with pytest.raises(ExperimentNotRunningError):
+        await experiments_repo.promote(name)
+
+
+@pytest.mark.integration
+async def test_delete_drops_shadow_rows_and_queued_work(
+    db_pool, name, processor, experiments_repo, queue_repo, embeddings_repo
+):
+    source_id, doc_id = await _embedded_document(db_pool, embeddings_repo)
+    await experiments_repo.create(
+        name, ChunkingConfig(128, "sentence"), source_ids=[source_id]
+    )
+    await _drain_until_done(
+        processor, queue_repo, await _queue_item_id(db_pool, doc_id, name)
+    )
+
+    assert await experiments_repo.delete(name)
+
+    assert await embeddings_repo.get_for_document(doc_id, namespace=name) == []
+    assert await _queue_item_id(db_pool, doc_id, name) is None
+    assert len(await embeddings_repo.get_for_document(doc_id)) == 1
+    assert not await experiments_repo.delete(name)
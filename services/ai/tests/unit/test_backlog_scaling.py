@@ -0,0 +1,71 @@
+"""Unit tests for backlog-aware embedding throughput scaling."""
+
+from embeddings.backlog_scaling import (
+    SCALE_STEPS,
+    BacklogScaler,
+    BacklogScalingConfig,
+)
+
+CONFIG = BacklogScalingConfig(
+    base_batch_size=10,
+    max_batch_size=40,
+    base_concurrency=1,
+    max_concurrency=4,
+    backlog_threshold=100,
+    age_threshold_seconds=600,
+)
+
+
+def test_starts_at_baseline():
+    scaler = BacklogScaler(CONFIG)
+    assert scaler.level == 0
+    assert scaler.batch_size == 10
+    assert scaler.concurrency == 1
+
+
+def test_steps_up_to_ceiling_while_backlog_is_large():
+    scaler = BacklogScaler(CONFIG)
+
+    assert scaler.update(pending=500, oldest_age_seconds=30)
+    assert scaler.level == 1
+    assert scaler.batch_size == 20
+    assert scaler.concurrency == 2
+
+    for _ in range(SCALE_STEPS + 2):
+        scaler.update(pending=500, oldest_age_seconds=30)
+    assert scaler.level == SCALE_STEPS
+    assert scaler.batch_size == 40
+    assert scaler.concurrency == 4
+
+
+def test_stale_backlog_scales_up_even_when_small():
+    scaler = BacklogScaler(CONFIG)
+    assert scaler.update(pending=5, oldest_age_seconds=3600)
+    assert scaler.level == 1
+
+
+def test_holds_level_until_backlog_drains_then_returns_to_baseline():
+    scaler = BacklogScaler(CONFIG)
+    scaler.update(pending=500, oldest_age_seconds=30)
+    scaler.update(pending=500, oldest_age_seconds=30)
+    assert scaler.level == 2
+
+    # Below the threshold but not yet drained: hold.
+    assert not scaler.update(pending=70, oldest_age_seconds=30)
+    assert scaler.level == 2
+
+    assert scaler.update(pending=10, oldest_age_seconds=5)
+    assert scaler.level == 0
+    assert scaler.batch_size == 10
+    assert scaler.concurrency == 1
+
+
+def test_status_reports_current_scaling():
+    scaler = BacklogScaler(CONFIG)
+    scaler.update(pending=250, oldest_age_seconds=120.0)
+    status = scaler.status(250, 120.0)
+    assert status.pending == 250
+    assert status.oldest_age_seconds == 120.0
+    assert status.level == 1
+    assert status.max_level == SCALE_STEPS
+    assert (status.batch_size, status.concurrency) == (20, 2)
@@ -241,6 +241,37 @@ class TestCharacterBasedChunking:
         reconstructed = "".join(chunks)
         assert reconstructed == text
 
+    def test_chunk_code_by_chars_keeps_definitions_whole(self):
+        """Code chunks end at top-level definitions, with decorators attached."""
+        text = (
+            "import os\n\n\n"
+            "def first(a):\n    return a + 1\n\n\n"
+            "@decorator\ndef second(b):\n    x = 1\n    return b\n"
+            "class Third:\n    def method(self):\n        pass\n"
+        )
+        spans = Chunker.chunk_code_by_chars(text, 60)
+
+        chunks = [text[start:end] for start, end in spans]
+
+        assert chunks[1].startswith("@decorator\ndef second")
+        assert chunks[1].endswith("return b\n")
+        assert chunks[2].startswith("class Third:")
+        assert "".join(chunks) == text
+
+    def test_chunk_code_by_chars_splits_long_definition_at_lines(self):
+        """A definition longer than max_chars is split at line ends."""
+        text = "fn long() {\n" + "    step();\n" * 100 + "}\n"
+        max_chars = 100
+        spans = Chunker.chunk_code_by_chars(text, max_chars)
+
+        chunks = [text[start:end] for start, end in spans]
+
+        assert len(chunks) >= 2
+        for chunk in chunks:
+            assert len(chunk) <= max_chars
+            assert chunk.endswith("\n")
+        assert "".join(chunks) == text
+
 
 if __name__ == "__main__":
     pytest.main([__file__, "-v"])
@@ -0,0 +1,17 @@
+import pytest
+
+from internal_auth import SERVICE_KEY_HEADER, internal_auth_headers
+
+
+@pytest.mark.unit
+def test_no_headers_without_a_key(monkeypatch):
+    monkeypatch.delenv("AI_INTERNAL_AUTH_KEY", raising=False)
+    monkeypatch.setenv("INTERNAL_AUTH_KEY", "  ")
+    assert internal_auth_headers() == {}
+
+
+@pytest.mark.unit
+def test_service_specific_key_wins(monkeypatch):
+    monkeypatch.setenv("INTERNAL_AUTH_KEY", "shared-key")
+    monkeypatch.setenv("AI_INTERNAL_AUTH_KEY", "ai-key")
+    assert internal_auth_headers() == {SERVICE_KEY_HEADER: "ai-key"}
@@ -2,6 +2,8 @@
 
 from __future__ import annotations
 
+from dataclasses import replace
+
 import pytest
 
 from tools.connector_handler import ConnectorAction, ConnectorToolHandler
@@ -380,6 +382,29 @@ def test_list_toolsets_groups_by_source(actions):
     assert "send_email" in by_source["src-gmail-1"]["sample_tool_names"]
 
 
+def test_list_toolsets_describes_capabilities():
+    actions = [
+        replace(
+            _make_action("src-gmail", "gmail", "send_email"),
+            connector_description="Search, read and send Gmail messages.\nMore detail.",
+        ),
+        replace(
+            _make_action("src-linear", "linear", "create_issue"),
+            connector_description="x" * 500,
+        ),
+        _make_action("src-drive", "google_drive", "list_files"),
+    ]
+    handler = _make_handler(actions)
+
+    by_source = {ts["source_id"]: ts for ts in handler.list_toolsets()}
+    assert by_source["src-gmail"]["description"] == (
+        "Search, read and send Gmail messages."
+    )
+    assert len(by_source["src-linear"]["description"]) == 160
+    assert by_source["src-linear"]["description"].endswith("…")
+    assert by_source["src-drive"]["description"] == ""
+
+
 def test_duplicate_source_type_actions_are_not_dropped():
     actions = [
         _make_action(
@@ -16,6 +16,7 @@ from anthropic.types import ToolParam
 from db.connection import get_db_pool
 from db.documents import DocumentsRepository
 from db.models import Source
+from internal_auth import internal_auth_headers
 from tools.omni_tool_result import OAuthRequiredPayload, encode_oauth_required
 from tools.registry import ToolContext, ToolResult
 from tools.sandbox import (
@@ -28,6 +29,7 @@ logger = logging.getLogger(__name__)
 
 ACTIONS_CACHE_TTL = 60  # seconds
 _TOOL_NAME_SAFE_RE = re.compile(r"[^a-zA-Z0-9_]")
+_TOOLSET_DESCRIPTION_MAX_CHARS = 160
 
 SourceMode = Literal["read", "write"]
 # Maps source_id -> list of modes allowed for that source.
@@ -57,6 +59,9 @@ class ToolsetSummary(TypedDict):
     source_name: str
     tool_count: int
     sample_tool_names: list[str]
+    # One-line capability blurb from the connector manifest; empty when the
+    # connector does not describe itself.
+    description: str
 
 
 @dataclass
@@ -83,6 +88,7 @@ class ConnectorAction:
     mode: SourceMode
     admin_only: bool = False
     hidden: bool = False
+    connector_description: str = ""
 
 
 class ConnectorToolHandler:
@@ -158,7 +164,9 @@ class ConnectorToolHandler:
         to map source_id.
         """
         try:
-            async with httpx.AsyncClient(timeout=10.0) as client:
+            async with httpx.AsyncClient(
+                timeout=10.0, headers=internal_auth_headers()
+            ) as client:
                 # Fetch connector info (includes manifests)
                 connectors_resp = await client.get(
                     f"{self._connector_manager_url}/connectors"
@@ -220,6 +228,7 @@ class ConnectorToolHandler:
             if not manifest or not connector.get("healthy"):
                 continue
 
+            connector_description = manifest.get("description") or ""
             for action_def in manifest.get("actions", []):
                 action_source_types = action_def.get("source_types") or []
                 if action_source_types and source_type not in action_source_types:
@@ -239,6 +248,7 @@ class ConnectorToolHandler:
                             mode=action_def.get("mode", "write"),
                             admin_only=action_def.get("admin_only", False),
                             hidden=action_def.get("hidden", False),
+                            connector_description=connector_description,
                         )
                     )
 
@@ -322,7 +332,8 @@ class ConnectorToolHandler:
         """One entry per source for prompt rendering and tool_search.
 
         Returns dicts with: source_id, source_type, source_name, tool_count,
-        sample_tool_names (up to 3 for the LLM to skim).
+        sample_tool_names (up to 3 for the LLM to skim) and a one-line
+        description of what the source's tools can do.
         """
         by_source: dict[str, list[ConnectorAction]] = {}
         for tool_name, action in self._actions.items():
@@ -339,6 +350,7 @@ class ConnectorToolHandler:
                     "source_name": first.source_name,
                     "tool_count": len(actions),
                     "sample_tool_names": sample,
+                    "description": _toolset_description(first.connector_description),
                 }
             )
         toolsets.sort(key=lambda t: (t["source_type"], t["source_name"]))
@@ -442,7 +454,9 @@ class ConnectorToolHandler:
                 )
 
         try:
-            async with httpx.AsyncClient(timeout=120.0) as client:
+            async with httpx.AsyncClient(
+                timeout=120.0, headers=internal_auth_headers()
+            ) as client:
                 response = await client.post(
                     f"{self._connector_manager_url}/action",
                     json={
@@ -572,6 +586,15 @@ class ConnectorToolHandler:
         )
 
 
+def _toolset_description(connector_description: str) -> str:
+    """First line of a connector description, trimmed for the system prompt."""
+    lines = connector_description.strip().splitlines()
+    line = lines[0].strip() if lines else ""
+    if len(line) <= _TOOLSET_DESCRIPTION_MAX_CHARS:
+        return line
+    return line[: _TOOLSET_DESCRIPTION_MAX_CHARS - 1].rstrip() + "…"
+
+
 def _action_result_file_name(action_name: str, *, extension: str) -> str:
     safe_name = "".join(
         char if char.isalnum() or char in ("-", "_") else "_" for char in action_name
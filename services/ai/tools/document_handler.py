@@ -11,6 +11,7 @@ import httpx
 from anthropic.types import ToolParam
 
 from db.documents import DocumentsRepository
+from internal_auth import internal_auth_headers
 from storage import ContentStorage, PostgresContentStorage
 from tools.registry import ToolContext, ToolResult
 from tools.sandbox import write_binary_to_sandbox, write_text_to_sandbox
@@ -206,7 +207,9 @@ class DocumentToolHandler:
             f"Fetching binary file '{document_name}' (id={doc.id}) from source {doc.source_id}"
         )
 
-        async with httpx.AsyncClient(timeout=120.0) as client:
+        async with httpx.AsyncClient(
+            timeout=120.0, headers=internal_auth_headers()
+        ) as client:
             resp = await client.post(
                 f"{self._connector_manager_url}/action",
                 json={
@@ -15,6 +15,7 @@ from anthropic.types import ToolParam
 from pydantic import BaseModel, ConfigDict, Field, TypeAdapter
 
 from db.models import Source
+from internal_auth import internal_auth_headers
 from tools.connector_handler import SourceFilter, sources_from_sync_overview_response
 from tools.registry import ToolContext, ToolResult
 from tools.searcher_client import (
@@ -142,7 +143,9 @@ class McpCapabilityHandler:
             return
 
         try:
-            async with httpx.AsyncClient(timeout=10.0) as client:
+            async with httpx.AsyncClient(
+                timeout=10.0, headers=internal_auth_headers()
+            ) as client:
                 connectors_resp = await client.get(
                     f"{self._connector_manager_url}/connectors"
                 )
@@ -502,7 +505,9 @@ class McpCapabilityHandler:
             return ToolResult(content=[{"type": "text", "text": line_error}], is_error=True)
 
         try:
-            async with httpx.AsyncClient(timeout=60.0) as client:
+            async with httpx.AsyncClient(
+                timeout=60.0, headers=internal_auth_headers()
+            ) as client:
                 response = await client.post(
                     f"{self._connector_manager_url}/resource",
                     json={"source_id": record.source_id, "uri": read_uri},
@@ -556,7 +561,9 @@ class McpCapabilityHandler:
             )
 
         try:
-            async with httpx.AsyncClient(timeout=60.0) as client:
+            async with httpx.AsyncClient(
+                timeout=60.0, headers=internal_auth_headers()
+            ) as client:
                 response = await client.post(
                     f"{self._connector_manager_url}/prompt",
                     json={
@@ -63,7 +63,9 @@ class PeopleSearchHandler:
             )
 
         limit = tool_input.get("limit", 10)
-        request = PeopleSearchRequest(query=query, limit=limit)
+        request = PeopleSearchRequest(
+            query=query, limit=limit, user_email=context.user_email
+        )
 
         try:
             response: PeopleSearchResponse = await self._searcher.client.search_people(
@@ -5,6 +5,7 @@ import logging
 
 import httpx
 
+from internal_auth import internal_auth_headers
 from tools.registry import ToolResult
 
 logger = logging.getLogger(__name__)
@@ -109,7 +110,9 @@ async def write_text_to_sandbox(
     """Write text data to the sandbox and return a ToolResult for the LLM."""
     size_kb = len(text.encode("utf-8")) / 1024
 
-    async with httpx.AsyncClient(timeout=60.0) as client:
+    async with httpx.AsyncClient(
+        timeout=60.0, headers=internal_auth_headers()
+    ) as client:
         resp = await client.post(
             f"{sandbox_url.rstrip('/')}/files/write",
             json={
@@ -140,7 +143,9 @@ async def write_binary_to_sandbox(
     encoded = base64.b64encode(binary_data).decode("ascii")
     size_kb = len(binary_data) / 1024
 
-    async with httpx.AsyncClient(timeout=60.0) as client:
+    async with httpx.AsyncClient(
+        timeout=60.0, headers=internal_auth_headers()
+    ) as client:
         resp = await client.post(
             f"{sandbox_url.rstrip('/')}/files/write_binary",
             json={
@@ -9,6 +9,7 @@ import re
 import httpx
 from anthropic.types import ToolParam
 
+from internal_auth import internal_auth_headers
 from tools.registry import ToolContext, ToolResult
 
 logger = logging.getLogger(__name__)
@@ -135,7 +136,9 @@ class SandboxToolHandler:
     ) -> ToolResult:
 
         try:
-            async with httpx.AsyncClient(timeout=60.0) as client:
+            async with httpx.AsyncClient(
+                timeout=60.0, headers=internal_auth_headers()
+            ) as client:
                 if tool_name == "write_file":
                     resp = await client.post(
                         f"{self._sandbox_url}/files/write",
@@ -45,9 +45,13 @@ TYPE_VALID_VALUES = [
     "page",
     "email",
     "meeting",
+    "code",
 ]
 
 _MAX_DISPLAYED_VALUES = 20
+# Heading path prefix of chunks from extracted tables (see
+# shared::content_extractor::TABLES_HEADING)
+_TABLE_SECTION_PREFIX = "Tables > "
 _OPERATOR_VALUES_CACHE_KEY = "search:operator_values"
 _OPERATOR_VALUES_CACHE_TTL = 300  # 5 minutes
 
@@ -366,6 +370,22 @@ class SearchToolHandler:
                     TextBlockParam(type="text", text=f"[Date: {date_str}]")
                 )
 
+            if result.page:
+                metadata_blocks.append(
+                    TextBlockParam(type="text", text=f"[Page: {result.page}]")
+                )
+
+            if result.heading_path:
+                # Extracted tables sit under a top-level "Tables" heading,
+                # one "Column: value | ..." row per line
+                table = result.heading_path.removeprefix(_TABLE_SECTION_PREFIX)
+                label = (
+                    f"[Table: {table}]"
+                    if table != result.heading_path
+                    else f"[Section: {result.heading_path}]"
+                )
+                metadata_blocks.append(TextBlockParam(type="text", text=label))
+
             if doc.attributes:
                 attrs_str = ", ".join(f"{k}: {v}" for k, v in doc.attributes.items())
                 metadata_blocks.append(
@@ -383,6 +403,9 @@ class SearchToolHandler:
             # This is the value shown in Anthropic 【source】 citation markers and
             # serialised as [title](source) for OpenAI.
             doc_source = doc.url or attr_doc_source or "<unknown>"
+            # A PDF open parameter lets the viewer jump to the cited page.
+            if doc.url and result.page and "#" not in doc.url:
+                doc_source = f"{doc.url}#page={result.page}"
 
             content_blocks.append(
                 SearchResultBlockParam(
@@ -12,6 +12,7 @@ import httpx
 from pydantic import BaseModel
 
 from db.models import UserConfiguration
+from internal_auth import internal_auth_headers
 
 logger = logging.getLogger(__name__)
 
@@ -52,6 +53,8 @@ class SearchResult(BaseModel):
     document: Document
     highlights: list[str]
     source_type: str | None = None
+    page: int | None = None
+    heading_path: str | None = None
 
 
 class SearchResponse(BaseModel):
@@ -69,6 +72,8 @@ class SearcherError(httpx.HTTPStatusError):
 class PeopleSearchRequest(BaseModel):
     query: str
     limit: int = 10
+    # People are limited to this user's workspace
+    user_email: str | None = None
 
 
 class PersonResult(BaseModel):
@@ -159,7 +164,7 @@ class SearcherClient:
             sys.exit(1)
 
         self.searcher_url = searcher_url.rstrip("/")
-        self.client = httpx.AsyncClient(timeout=30.0)
+        self.client = httpx.AsyncClient(timeout=30.0, headers=internal_auth_headers())
 
     async def search_documents(self, request: SearchRequest) -> SearchResponse:
         """
@@ -173,8 +178,12 @@ class SearcherClient:
 
             logger.info(f"Calling searcher service with query: {request.query}...")
 
+            # Retrieval on behalf of the model yields to interactive user
+            # search when the searcher is under load.
             response = await self.client.post(
-                f"{self.searcher_url}/search", json=search_payload
+                f"{self.searcher_url}/search",
+                json=search_payload,
+                headers={"X-Omni-Priority": "rag"},
             )
 
             if response.status_code == 200:
@@ -198,9 +207,12 @@ class SearcherClient:
         """Search the people directory using omni-searcher service."""
         try:
             logger.info(f"People search with query: {request.query}...")
+            params = {"q": request.query, "limit": request.limit}
+            if request.user_email:
+                params["user_email"] = request.user_email
             response = await self.client.get(
                 f"{self.searcher_url}/people/search",
-                params={"q": request.query, "limit": request.limit},
+                params=params,
             )
 
             if response.status_code == 200:
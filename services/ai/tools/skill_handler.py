@@ -14,6 +14,7 @@ import httpx
 from anthropic.types import ToolParam
 
 from db.skills import Skill, SkillsRepository
+from internal_auth import internal_auth_headers
 from tools.registry import ToolContext, ToolResult
 from tools.searcher_client import (
     CapabilitiesSyncRequest,
@@ -102,7 +103,9 @@ class SkillHandler:
         if self._connector_skills_loaded or not self._connector_manager_url:
             return
         try:
-            async with httpx.AsyncClient(timeout=10.0) as client:
+            async with httpx.AsyncClient(
+                timeout=10.0, headers=internal_auth_headers()
+            ) as client:
                 response = await client.get(f"{self._connector_manager_url}/skills")
                 response.raise_for_status()
                 payload = response.json()
@@ -268,7 +271,9 @@ class SkillHandler:
                 is_error=True,
             )
         try:
-            async with httpx.AsyncClient(timeout=10.0) as client:
+            async with httpx.AsyncClient(
+                timeout=10.0, headers=internal_auth_headers()
+            ) as client:
                 response = await client.post(
                     f"{self._connector_manager_url}/skill",
                     json=self._connector_skill_request(skill_id),
@@ -303,7 +308,9 @@ class SkillHandler:
         if not self._connector_manager_url:
             return None
         try:
-            async with httpx.AsyncClient(timeout=10.0) as client:
+            async with httpx.AsyncClient(
+                timeout=10.0, headers=internal_auth_headers()
+            ) as client:
                 response = await client.post(
                     f"{self._connector_manager_url}/skill",
                     json=self._connector_skill_request(skill_id),
@@ -0,0 +1,587 @@
+//! Data subject access and erasure requests.
+//!
+//! A request names a person by email. Their identifiers are the email, the
+//! display name from the people directory and any aliases given with the
+//! request; documents match when their author, title, metadata, attributes or
+//! content contain one of them. Access requests write the matches and the
+//! directory record to a gzipped tar like a source export, downloadable with a
+//! secret token until the link expires. Erasure requests either delete the
+//! matches or redact the identifiers out of them, then remove the person from
+//! the directory. Content blobs nothing references any more are deleted from
+//! storage straight away rather than left for GC, and cached searches over the
+//! affected sources are invalidated batch by batch, so erased text stops being
+//! returned as soon as it is gone.
+//!
+//! Every document a request touches is recorded against it, so the requests
+//! table doubles as the audit log. Erasure covers Omni's copy only: a document
+//! that changes in its source system is synced again as it is there.
+
+use crate::source_export::{
+    ARCHIVE_CONTENT_TYPE, file_stem, generate_download_token, render_markdown,
+};
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use redis::Client as RedisClient;
+use serde_json::{Value as JsonValue, json};
+use shared::db::repositories::{
+    ComplianceDocumentAction, ComplianceRequest, ComplianceRequestKind,
+    ComplianceRequestRepository, ErasureAction, RedactedDocument, SubjectDocument,
+    identifier_patterns,
+};
+use shared::search_cache;
+use shared::{EmbeddingQueue, ObjectStorage, PersonRepository};
+use sqlx::PgPool;
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use time::format_description::well_known::Iso8601;
+use time::{Duration as TimeDuration, OffsetDateTime};
+use tracing::{error, info, warn};
+
+pub const REDACTION_MARKER: &str = "[redacted]";
+
+const BATCH_SIZE: i64 = 100;
+/// Compressed bytes buffered before an access export part is written.
+const ARCHIVE_PART_SIZE: usize = 16 * 1024 * 1024;
+const MAX_RUNNING_REQUESTS: i64 = 1;
+/// Running requests without a heartbeat for this long are considered orphaned.
+const REQUEST_HEARTBEAT_TIMEOUT_SECONDS: i64 = 600;
+const STORAGE_PREFIX: &str = "compliance";
+/// Display names shorter than this are too likely to match unrelated text.
+const MIN_IDENTIFIER_CHARS: usize = 4;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ComplianceError {
+    #[error("Database error: {0}")]
+    Database(String),
+
+    #[error("Storage error: {0}")]
+    Storage(String),
+
+    #[error("Archive error: {0}")]
+    Archive(#[from] std::io::Error),
+}
+
+impl From<shared::DatabaseError> for ComplianceError {
+    fn from(e: shared::DatabaseError) -> Self {
+        ComplianceError::Database(e.to_string())
+    }
+}
+
+impl From<shared::storage::StorageError> for ComplianceError {
+    fn from(e: shared::storage::StorageError) -> Self {
+        ComplianceError::Storage(e.to_string())
+    }
+}
+
+impl From<anyhow::Error> for ComplianceError {
+    fn from(e: anyhow::Error) -> Self {
+        ComplianceError::Database(e.to_string())
+    }
+}
+
+/// The strings documents are matched on: the email, the directory display
+/// name and the aliases, without duplicates or ones too short to be
+/// distinctive.
+pub fn subject_identifiers(
+    email: &str,
+    display_name: Option<&str>,
+    aliases: &[String],
+) -> Vec<String> {
+    let mut identifiers: Vec<String> = Vec::new();
+    let candidates = std::iter::once(email)
+        .chain(display_name)
+        .chain(aliases.iter().map(String::as_str));
+    for candidate in candidates {
+        let candidate = candidate.trim();
+        if candidate.chars().count() < MIN_IDENTIFIER_CHARS {
+            continue;
+        }
+        if !identifiers
+            .iter()
+            .any(|existing| existing.to_lowercase() == candidate.to_lowercase())
+        {
+            identifiers.push(candidate.to_string());
+        }
+    }
+    identifiers
+}
+
+/// Replace every case-insensitive occurrence of the identifiers in `text`
+/// with [`REDACTION_MARKER`]. Returns None if nothing was replaced.
+pub fn redact_text(text: &str, identifiers: &[String]) -> Option<String> {
+    let needles: Vec<Vec<char>> = identifiers
+        .iter()
+        .filter(|identifier| !identifier.is_empty())
+        .map(|identifier| identifier.chars().collect())
+        .collect();
+    let chars: Vec<char> = text.chars().collect();
+    let matches_at = |start: usize, needle: &[char]| {
+        chars.len() - start >= needle.len()
+            && needle
+                .iter()
+                .zip(&chars[start..])
+                .all(|(a, b)| a.to_lowercase().eq(b.to_lowercase()))
+    };
+
+    let mut redacted = String::with_capacity(text.len());
+    let mut replaced = false;
+    let mut i = 0;
+    while i < chars.len() {
+        // Longest first, so a name inside an email never leaves a fragment
+        if let Some(needle) = needles
+            .iter()
+            .filter(|needle| matches_at(i, needle))
+            .max_by_key(|needle| needle.len())
+        {
+            redacted.push_str(REDACTION_MARKER);
+            i += needle.len();
+            replaced = true;
+        } else {
+            redacted.push(chars[i]);
+            i += 1;
+        }
+    }
+
+    replaced.then_some(redacted)
+}
+
+/// Redact every string in a JSON value. Object keys are left alone.
+pub fn redact_json(value: &JsonValue, identifiers: &[String]) -> JsonValue {
+    match value {
+        JsonValue::String(s) => match redact_text(s, identifiers) {
+            Some(redacted) => JsonValue::String(redacted),
+            None => value.clone(),
+        },
+        JsonValue::Array(items) => JsonValue::Array(
+            items
+                .iter()
+                .map(|item| redact_json(item, identifiers))
+                .collect(),
+        ),
+        JsonValue::Object(map) => JsonValue::Object(
+            map.iter()
+                .map(|(key, item)| (key.clone(), redact_json(item, identifiers)))
+                .collect(),
+        ),
+        _ => value.clone(),
+    }
+}
+
+#[derive(Clone)]
+pub struct ComplianceProcessor {
+    pool: PgPool,
+    redis_client: RedisClient,
+    storage: Arc<dyn ObjectStorage>,
+    link_ttl: TimeDuration,
+}
+
+impl ComplianceProcessor {
+    pub fn new(
+        pool: PgPool,
+        redis_client: RedisClient,
+        storage: Arc<dyn ObjectStorage>,
+        link_ttl_seconds: i64,
+    ) -> Self {
+        Self {
+            pool,
+            redis_client,
+            storage,
+            link_ttl: TimeDuration::seconds(link_ttl_seconds),
+        }
+    }
+
+    fn repo(&self) -> ComplianceRequestRepository {
+        ComplianceRequestRepository::new(&self.pool)
+    }
+
+    /// Expire lapsed access exports, fail orphaned requests and start pending
+    /// ones in the background. Returns the number of requests started.
+    pub async fn process(&self) -> Result<usize, ComplianceError> {
+        let repo = self.repo();
+
+        let expired_parts = repo.expire_completed().await?;
+        self.delete_blobs(&expired_parts).await;
+
+        let orphaned_parts = repo
+            .fail_orphaned(REQUEST_HEARTBEAT_TIMEOUT_SECONDS)
+            .await?;
+        self.delete_blobs(&orphaned_parts).await;
+
+        let capacity = MAX_RUNNING_REQUESTS - repo.count_running().await?;
+        if capacity <= 0 {
+            return Ok(0);
+        }
+
+        let claimed = repo.claim_pending(capacity).await?;
+        let started = claimed.len();
+        for request in claimed {
+            let processor = self.clone();
+            tokio::spawn(async move { processor.run(request).await });
+        }
+
+        Ok(started)
+    }
+
+    /// Run a claimed request to completion, recording failure on error.
+    pub async fn run(&self, request: ComplianceRequest) {
+        info!(
+            "Starting {:?} request {} for {}",
+            request.kind, request.id, request.subject_email
+        );
+        let mut parts = Vec::new();
+        let result = match (request.kind, request.erasure_action) {
+            (ComplianceRequestKind::Access, _) => self.export(&request, &mut parts).await,
+            (ComplianceRequestKind::Erasure, Some(action)) => self.erase(&request, action).await,
+            (ComplianceRequestKind::Erasure, None) => Err(ComplianceError::Database(
+                "Erasure request has no action".to_string(),
+            )),
+        };
+
+        match result {
+            Ok(()) => info!("Compliance request {} completed", request.id),
+            Err(e) => {
+                error!("Compliance request {} failed: {}", request.id, e);
+                if let Err(e) = self.repo().fail(&request.id, &e.to_string()).await {
+                    error!(
+                        "Failed to mark compliance request {} as failed: {}",
+                        request.id, e
+                    );
+                }
+                self.delete_blobs(&parts).await;
+            }
+        }
+    }
+
+    /// Write the subject's directory record and every matching document to
+    /// an archive, with a manifest of why each document matched.
+    async fn export(
+        &self,
+        request: &ComplianceRequest,
+        parts: &mut Vec<String>,
+    ) -> Result<(), ComplianceError> {
+        let repo = self.repo();
+        let patterns = identifier_patterns(&request.identifiers);
+        let root = format!("subject-access-{}", request.id.to_lowercase());
+
+        let mut archive = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+        let mut manifest_documents = Vec::new();
+        let mut size_bytes = 0i64;
+        let mut after_id = String::new();
+
+        loop {
+            let documents = repo
+                .find_subject_documents(&patterns, &after_id, BATCH_SIZE)
+                .await?;
+            let Some(last) = documents.last() else {
+                break;
+            };
+            after_id = last.document.id.clone();
+
+            let content_ids: Vec<String> = documents
+                .iter()
+                .filter_map(|d| d.document.content_id.clone())
+                .collect();
+            let contents = self.storage.batch_get_text(content_ids).await?;
+
+            for SubjectDocument {
+                document,
+                match_reasons,
+            } in &documents
+            {
+                let content = document
+                    .content_id
+                    .as_ref()
+                    .and_then(|id| contents.get(id))
+                    .map(String::as_str)
+                    .unwrap_or_default();
+                let path = format!(
+                    "{}/documents/{}.md",
+                    root,
+                    file_stem(&document.title, &document.id)
+                );
+                append_file(
+                    &mut archive,
+                    &path,
+                    render_markdown(document, content).as_bytes(),
+                    document.updated_at,
+                )?;
+                manifest_documents.push(json!({
+                    "id": document.id,
+                    "source_id": document.source_id,
+                    "external_id": document.external_id,
+                    "title": document.title,
+                    "url": document.url,
+                    "match_reasons": match_reasons,
+                    "path": path,
+                }));
+            }
+            repo.record_documents(&request.id, &documents, ComplianceDocumentAction::Exported)
+                .await?;
+
+            let buffered = archive.get_mut().get_mut();
+            if buffered.len() >= ARCHIVE_PART_SIZE {
+                let part = std::mem::take(buffered);
+                size_bytes += part.len() as i64;
+                parts.push(self.store_part(&part).await?);
+            }
+            repo.record_progress(
+                &request.id,
+                manifest_documents.len() as i32,
+                size_bytes,
+                parts.as_slice(),
+            )
+            .await?;
+        }
+
+        let person = PersonRepository::new(&self.pool)
+            .fetch_person_by_email(&request.subject_email)
+            .await?;
+        let now = OffsetDateTime::now_utc();
+        let manifest = json!({
+            "request_id": request.id,
+            "subject_email": request.subject_email,
+            "identifiers": request.identifiers,
+            "generated_at": now.format(&Iso8601::DEFAULT).ok(),
+            "person": person,
+            "documents": manifest_documents,
+        });
+        let manifest = serde_json::to_vec_pretty(&manifest)
+            .map_err(|e| ComplianceError::Archive(std::io::Error::other(e)))?;
+        append_file(
+            &mut archive,
+            &format!("{}/manifest.json", root),
+            &manifest,
+            now,
+        )?;
+
+        let last_part = archive.into_inner()?.finish()?;
+        if !last_part.is_empty() {
+            size_bytes += last_part.len() as i64;
+            parts.push(self.store_part(&last_part).await?);
+        }
+
+        let token = generate_download_token();
+        repo.complete(
+            &request.id,
+            manifest_documents.len() as i32,
+            size_bytes,
+            parts.as_slice(),
+            Some((&token, now + self.link_ttl)),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Delete or redact every matching document, then remove the subject
+    /// from the people directory.
+    async fn erase(
+        &self,
+        request: &ComplianceRequest,
+        action: ErasureAction,
+    ) -> Result<(), ComplianceError> {
+        let repo = self.repo();
+        let patterns = identifier_patterns(&request.identifiers);
+        let mut document_count = 0i32;
+        let mut after_id = String::new();
+
+        loop {
+            let documents = repo
+                .find_subject_documents(&patterns, &after_id, BATCH_SIZE)
+                .await?;
+            let Some(last) = documents.last() else {
+                break;
+            };
+            after_id = last.document.id.clone();
+
+            let released = match action {
+                ErasureAction::Delete => repo.delete_documents(&request.id, &documents).await?,
+                ErasureAction::Redact => self.redact(request, &documents).await?,
+            };
+            self.delete_unreferenced(&released).await?;
+            let source_ids: BTreeSet<&str> = documents
+                .iter()
+                .map(|d| d.document.source_id.as_str())
+                .collect();
+            search_cache::invalidate_sources(&self.redis_client, source_ids).await;
+
+            document_count += documents.len() as i32;
+            repo.record_progress(&request.id, document_count, 0, &[])
+                .await?;
+        }
+
+        if repo.delete_person(&request.subject_email).await? {
+            info!(
+                "Removed {} from the people directory for request {}",
+                request.subject_email, request.id
+            );
+        }
+        repo.complete(&request.id, document_count, 0, &[], None)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Redact a batch of documents and queue them for re-embedding, since
+    /// their vectors were computed from the original text. Returns the
+    /// content ids they no longer reference.
+    async fn redact(
+        &self,
+        request: &ComplianceRequest,
+        documents: &[SubjectDocument],
+    ) -> Result<Vec<String>, ComplianceError> {
+        let repo = self.repo();
+        let identifiers = &request.identifiers;
+        let today = OffsetDateTime::now_utc();
+        let prefix = format!(
+            "{:04}-{:02}-{:02}/{}-{}",
+            today.year(),
+            today.month() as u8,
+            today.day(),
+            STORAGE_PREFIX,
+            request.id
+        );
+
+        let mut released = Vec::new();
+        let mut reembed = Vec::new();
+        for subject_document in documents {
+            let document = &subject_document.document;
+            let redacted_content = match &document.content_id {
+                Some(content_id) => {
+                    let content = self.storage.get_text(content_id).await?;
+                    redact_text(&content, identifiers)
+                }
+                None => None,
+            };
+            let redacted_content_id = match &redacted_content {
+                Some(content) => Some(self.storage.store_text(content, Some(&prefix)).await?),
+                None => None,
+            };
+            let title = redact_text(&document.title, identifiers);
+            let metadata = redact_json(&document.metadata, identifiers);
+            let attributes = redact_json(&document.attributes, identifiers);
+
+            released.extend(
+                repo.redact_document(
+                    &request.id,
+                    subject_document,
+                    &RedactedDocument {
+                        title: title.as_deref().unwrap_or(&document.title),
+                        content_id: redacted_content_id.as_deref(),
+                        content: redacted_content.as_deref(),
+                        metadata: &metadata,
+                        attributes: &attributes,
+                    },
+                )
+                .await?,
+            );
+            if redacted_content.is_some() || title.is_some() {
+                reembed.push(document.id.clone());
+            }
+        }
+
+        if !reembed.is_empty() {
+            EmbeddingQueue::new(self.pool.clone())
+                .enqueue_batch(reembed)
+                .await?;
+        }
+        Ok(released)
+    }
+
+    async fn delete_unreferenced(&self, content_ids: &[String]) -> Result<(), ComplianceError> {
+        if content_ids.is_empty() {
+            return Ok(());
+        }
+        let unreferenced = self.repo().unreferenced_content_ids(content_ids).await?;
+        self.delete_blobs(&unreferenced).await;
+        Ok(())
+    }
+
+    async fn store_part(&self, bytes: &[u8]) -> Result<String, ComplianceError> {
+        Ok(self
+            .storage
+            .store_content_with_type(bytes, Some(ARCHIVE_CONTENT_TYPE), Some(STORAGE_PREFIX))
+            .await?)
+    }
+
+    async fn delete_blobs(&self, content_ids: &[String]) {
+        for content_id in content_ids {
+            if let Err(e) = self.storage.delete_content(content_id).await {
+                warn!("Failed to delete content blob {}: {}", content_id, e);
+            }
+        }
+    }
+}
+
+fn append_file(
+    archive: &mut tar::Builder<GzEncoder<Vec<u8>>>,
+    path: &str,
+    data: &[u8],
+    modified_at: OffsetDateTime,
+) -> std::io::Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(modified_at.unix_timestamp().max(0) as u64);
+    archive.append_data(&mut header, path, data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn identifiers() -> Vec<String> {
+        subject_identifiers("jane.doe@example.com", Some("Jane Doe"), &[])
+    }
+
+    #[test]
+    fn test_subject_identifiers_skip_short_and_duplicate_names() {
+        assert_eq!(
+            subject_identifiers(
+                "jane@example.com",
+                Some("Jo"),
+                &["JANE@example.com".to_string(), " J. Doe ".to_string()]
+            ),
+            vec!["jane@example.com".to_string(), "J. Doe".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_redact_text_is_case_insensitive() {
+        assert_eq!(
+            redact_text(
+                "Owner: JANE DOE <Jane.Doe@example.com>, cc jane doe",
+                &identifiers()
+            )
+            .unwrap(),
+            "Owner: [redacted] <[redacted]>, cc [redacted]"
+        );
+        assert_eq!(redact_text("Owner: John Smith", &identifiers()), None);
+    }
+
+    #[test]
+    fn test_redact_text_handles_non_ascii() {
+        let identifiers = vec!["José Álvarez".to_string()];
+        assert_eq!(
+            redact_text("Met JOSÉ ÁLVAREZ in Málaga", &identifiers).unwrap(),
+            "Met [redacted] in Málaga"
+        );
+    }
+
+    #[test]
+    fn test_redact_json_keeps_structure() {
+        let value = json!({
+            "author": "Jane Doe",
+            "reviewers": ["jane.doe@example.com", "sam@example.com"],
+            "jane doe": 3,
+        });
+        assert_eq!(
+            redact_json(&value, &identifiers()),
+            json!({
+                "author": "[redacted]",
+                "reviewers": ["[redacted]", "sam@example.com"],
+                "jane doe": 3,
+            })
+        );
+    }
+}
@@ -1,6 +1,7 @@
+use shared::config_loader::ConfigLoader;
+use shared::models::SourceType;
 use shared::{DatabaseConfig, RedisConfig};
-use std::env;
-use std::process;
+use std::collections::HashMap;
 
 #[derive(Debug, Clone)]
 pub struct ConnectorManagerConfig {
@@ -16,64 +17,231 @@ pub struct ConnectorManagerConfig {
     pub sync_backoff_base_seconds: i64,
     pub sync_backoff_max_seconds: i64,
     pub sync_max_consecutive_failures: i32,
+    pub export_link_ttl_seconds: i64,
+    pub backpressure: BackpressureConfig,
+    pub source_stats_refresh_interval_seconds: u64,
+    pub source_stats_retention_days: i64,
+    pub connector_error_retention_days: i64,
+    pub audit_log_retention_days: i64,
+    pub credential_refresh_margin_seconds: i64,
+}
+
+/// Queue depths above which the scheduler stops starting new scheduled
+/// syncs. A limit of 0 disables the check for that queue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BackpressureThresholds {
+    pub max_pending_events: i64,
+    pub max_pending_embeddings: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackpressureConfig {
+    pub defaults: BackpressureThresholds,
+    pub max_pending_events_by_source_type: HashMap<SourceType, i64>,
+    pub max_pending_embeddings_by_source_type: HashMap<SourceType, i64>,
+}
+
+impl Default for BackpressureConfig {
+    fn default() -> Self {
+        Self {
+            defaults: BackpressureThresholds {
+                max_pending_events: 50_000,
+                max_pending_embeddings: 200_000,
+            },
+            max_pending_events_by_source_type: HashMap::new(),
+            max_pending_embeddings_by_source_type: HashMap::new(),
+        }
+    }
+}
+
+impl BackpressureConfig {
+    /// The thresholds for a source type, with per-type overrides applied.
+    pub fn thresholds_for(&self, source_type: SourceType) -> BackpressureThresholds {
+        BackpressureThresholds {
+            max_pending_events: self
+                .max_pending_events_by_source_type
+                .get(&source_type)
+                .copied()
+                .unwrap_or(self.defaults.max_pending_events),
+            max_pending_embeddings: self
+                .max_pending_embeddings_by_source_type
+                .get(&source_type)
+                .copied()
+                .unwrap_or(self.defaults.max_pending_embeddings),
+        }
+    }
+
+    fn load(loader: &mut ConfigLoader) -> Self {
+        let max_pending_events: i64 = loader.optional("BACKPRESSURE_MAX_PENDING_EVENTS", "50000");
+        loader.check(
+            "BACKPRESSURE_MAX_PENDING_EVENTS",
+            max_pending_events >= 0,
+            "non-negative",
+        );
+        let max_pending_embeddings: i64 =
+            loader.optional("BACKPRESSURE_MAX_PENDING_EMBEDDINGS", "200000");
+        loader.check(
+            "BACKPRESSURE_MAX_PENDING_EMBEDDINGS",
+            max_pending_embeddings >= 0,
+            "non-negative",
+        );
+        let max_pending_events_by_source_type = loader.optional_with(
+            "BACKPRESSURE_MAX_PENDING_EVENTS_BY_SOURCE_TYPE",
+            "",
+            parse_limits_by_source_type,
+        );
+        let max_pending_embeddings_by_source_type = loader.optional_with(
+            "BACKPRESSURE_MAX_PENDING_EMBEDDINGS_BY_SOURCE_TYPE",
+            "",
+            parse_limits_by_source_type,
+        );
+
+        Self {
+            defaults: BackpressureThresholds {
+                max_pending_events,
+                max_pending_embeddings,
+            },
+            max_pending_events_by_source_type,
+            max_pending_embeddings_by_source_type,
+        }
+    }
+}
+
+/// Parse `slack=5000,google_drive=20000` into limits per source type.
+fn parse_limits_by_source_type(value: &str) -> Result<HashMap<SourceType, i64>, String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (source_type, limit) = pair
+                .split_once('=')
+                .ok_or_else(|| format!("'{}' is not a source_type=limit pair", pair))?;
+            let source_type: SourceType =
+                serde_json::from_value(serde_json::Value::String(source_type.trim().to_string()))
+                    .map_err(|_| format!("unknown source type '{}'", source_type.trim()))?;
+            let limit = limit
+                .trim()
+                .parse::<i64>()
+                .ok()
+                .filter(|l| *l >= 0)
+                .ok_or_else(|| format!("limit for '{}' must be a non-negative integer", pair))?;
+            Ok((source_type, limit))
+        })
+        .collect()
 }
 
 impl ConnectorManagerConfig {
     pub fn from_env() -> Self {
-        let database = DatabaseConfig::from_env();
-        let redis = RedisConfig::from_env();
-
-        let port_str = env::var("PORT").unwrap_or_else(|_| "8090".to_string());
-        let port = port_str.parse::<u16>().unwrap_or_else(|_| {
-            eprintln!("ERROR: Invalid port number in 'PORT': '{}'", port_str);
-            process::exit(1);
-        });
-
-        let max_concurrent_syncs = env::var("MAX_CONCURRENT_SYNCS")
-            .unwrap_or_else(|_| "10".to_string())
-            .parse::<usize>()
-            .unwrap_or(10);
-
-        let max_concurrent_syncs_per_type = env::var("MAX_CONCURRENT_SYNCS_PER_TYPE")
-            .unwrap_or_else(|_| "3".to_string())
-            .parse::<usize>()
-            .unwrap_or(3);
-
-        let scheduler_interval_seconds = env::var("SCHEDULER_INTERVAL_SECONDS")
-            .unwrap_or_else(|_| "30".to_string())
-            .parse::<u64>()
-            .unwrap_or(30);
-
-        let stale_sync_timeout_minutes = env::var("STALE_SYNC_TIMEOUT_MINUTES")
-            .unwrap_or_else(|_| "60".to_string())
-            .parse::<u64>()
-            .unwrap_or(10);
-
-        let extraction_concurrency = env::var("EXTRACTION_CONCURRENCY")
-            .unwrap_or_else(|_| "2".to_string())
-            .parse::<usize>()
-            .unwrap_or(2)
-            .max(1);
-
-        let extraction_retry_after_seconds = env::var("EXTRACTION_RETRY_AFTER_SECONDS")
-            .unwrap_or_else(|_| "30".to_string())
-            .parse::<u64>()
-            .unwrap_or(30);
-
-        let sync_backoff_base_seconds = env::var("SYNC_BACKOFF_BASE_SECONDS")
-            .unwrap_or_else(|_| "30".to_string())
-            .parse::<i64>()
-            .unwrap_or(30);
-
-        let sync_backoff_max_seconds = env::var("SYNC_BACKOFF_MAX_SECONDS")
-            .unwrap_or_else(|_| "3600".to_string())
-            .parse::<i64>()
-            .unwrap_or(3600);
-
-        let sync_max_consecutive_failures = env::var("SYNC_MAX_CONSECUTIVE_FAILURES")
-            .unwrap_or_else(|_| "10".to_string())
-            .parse::<i32>()
-            .unwrap_or(10);
+        ConfigLoader::load_or_exit(Self::load)
+    }
+
+    pub fn load(loader: &mut ConfigLoader) -> Self {
+        let database = DatabaseConfig::load(loader);
+        let redis = RedisConfig::load(loader);
+
+        let port: u16 = loader.optional("PORT", "8090");
+        loader.check("PORT", port > 0, "a port between 1 and 65535");
+
+        let max_concurrent_syncs: usize = loader.optional("MAX_CONCURRENT_SYNCS", "10");
+        loader.check(
+            "MAX_CONCURRENT_SYNCS",
+            max_concurrent_syncs >= 1,
+            "at least 1",
+        );
+        let max_concurrent_syncs_per_type: usize =
+            loader.optional("MAX_CONCURRENT_SYNCS_PER_TYPE", "3");
+        loader.check(
+            "MAX_CONCURRENT_SYNCS_PER_TYPE",
+            max_concurrent_syncs_per_type >= 1,
+            "at least 1",
+        );
+
+        let scheduler_interval_seconds: u64 = loader.optional("SCHEDULER_INTERVAL_SECONDS", "30");
+        loader.check(
+            "SCHEDULER_INTERVAL_SECONDS",
+            scheduler_interval_seconds >= 1,
+            "at least 1",
+        );
+        let stale_sync_timeout_minutes: u64 = loader.optional("STALE_SYNC_TIMEOUT_MINUTES", "60");
+        loader.check(
+            "STALE_SYNC_TIMEOUT_MINUTES",
+            stale_sync_timeout_minutes >= 1,
+            "at least 1",
+        );
+
+        let extraction_concurrency: usize = loader.optional("EXTRACTION_CONCURRENCY", "2");
+        loader.check(
+            "EXTRACTION_CONCURRENCY",
+            extraction_concurrency >= 1,
+            "at least 1",
+        );
+        let extraction_retry_after_seconds: u64 =
+            loader.optional("EXTRACTION_RETRY_AFTER_SECONDS", "30");
+
+        let sync_backoff_base_seconds: i64 = loader.optional("SYNC_BACKOFF_BASE_SECONDS", "30");
+        loader.check(
+            "SYNC_BACKOFF_BASE_SECONDS",
+            sync_backoff_base_seconds >= 0,
+            "non-negative",
+        );
+        let sync_backoff_max_seconds: i64 = loader.optional("SYNC_BACKOFF_MAX_SECONDS", "3600");
+        loader.check(
+            "SYNC_BACKOFF_MAX_SECONDS",
+            sync_backoff_max_seconds >= sync_backoff_base_seconds,
+            "at least SYNC_BACKOFF_BASE_SECONDS",
+        );
+        let sync_max_consecutive_failures: i32 =
+            loader.optional("SYNC_MAX_CONSECUTIVE_FAILURES", "10");
+        loader.check(
+            "SYNC_MAX_CONSECUTIVE_FAILURES",
+            sync_max_consecutive_failures >= 1,
+            "at least 1",
+        );
+
+        let export_link_ttl_seconds: i64 = loader.optional("EXPORT_LINK_TTL_SECONDS", "86400");
+        loader.check(
+            "EXPORT_LINK_TTL_SECONDS",
+            export_link_ttl_seconds >= 60,
+            "at least 60",
+        );
+
+        let backpressure = BackpressureConfig::load(loader);
+
+        let source_stats_refresh_interval_seconds: u64 =
+            loader.optional("SOURCE_STATS_REFRESH_INTERVAL_SECONDS", "3600");
+        loader.check(
+            "SOURCE_STATS_REFRESH_INTERVAL_SECONDS",
+            source_stats_refresh_interval_seconds >= 60,
+            "at least 60",
+        );
+        let source_stats_retention_days: i64 =
+            loader.optional("SOURCE_STATS_RETENTION_DAYS", "365");
+        loader.check(
+            "SOURCE_STATS_RETENTION_DAYS",
+            source_stats_retention_days >= 1,
+            "at least 1",
+        );
+        let connector_error_retention_days: i64 =
+            loader.optional("CONNECTOR_ERROR_RETENTION_DAYS", "30");
+        loader.check(
+            "CONNECTOR_ERROR_RETENTION_DAYS",
+            connector_error_retention_days >= 1,
+            "at least 1",
+        );
+        let audit_log_retention_days: i64 = loader.optional("AUDIT_LOG_RETENTION_DAYS", "365");
+        loader.check(
+            "AUDIT_LOG_RETENTION_DAYS",
+            audit_log_retention_days >= 1,
+            "at least 1",
+        );
+        let credential_refresh_margin_seconds: i64 =
+            loader.optional("CREDENTIAL_REFRESH_MARGIN_SECONDS", "900");
+        loader.check(
+            "CREDENTIAL_REFRESH_MARGIN_SECONDS",
+            credential_refresh_margin_seconds >= 60,
+            "at least 60",
+        );
 
         Self {
             database,
@@ -88,6 +256,13 @@ impl ConnectorManagerConfig {
             sync_backoff_base_seconds,
             sync_backoff_max_seconds,
             sync_max_consecutive_failures,
+            export_link_ttl_seconds,
+            backpressure,
+            source_stats_refresh_interval_seconds,
+            source_stats_retention_days,
+            connector_error_retention_days,
+            audit_log_retention_days,
+            credential_refresh_margin_seconds,
         }
     }
 }
@@ -0,0 +1,113 @@
+use crate::models::ConnectorHealthStatus;
+use shared::db::repositories::{ConnectorErrorRecord, SourceSyncHealthSummary};
+
+/// Failing once as many syncs failed in a row as the scheduler tolerates
+/// before backing off, or as soon as an error that won't resolve on its own
+/// (expired credentials, revoked access, bad configuration) is reported after
+/// the last successful sync. Degraded while anything failed since then.
+pub(crate) fn evaluate_health(
+    summary: &SourceSyncHealthSummary,
+    last_error: Option<&ConnectorErrorRecord>,
+    max_consecutive_failures: i32,
+) -> ConnectorHealthStatus {
+    let error_since_success = last_error.filter(|error| {
+        summary
+            .last_successful_sync_at
+            .is_none_or(|succeeded_at| error.created_at > succeeded_at)
+    });
+
+    if summary.consecutive_failures >= i64::from(max_consecutive_failures.max(1))
+        || error_since_success.is_some_and(|error| error.category.requires_action())
+    {
+        ConnectorHealthStatus::Failing
+    } else if summary.consecutive_failures > 0 || error_since_success.is_some() {
+        ConnectorHealthStatus::Degraded
+    } else {
+        ConnectorHealthStatus::Healthy
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::models::ConnectorErrorCategory;
+    use time::{Duration, OffsetDateTime};
+
+    fn summary(
+        consecutive_failures: i64,
+        last_successful_sync_at: Option<OffsetDateTime>,
+    ) -> SourceSyncHealthSummary {
+        SourceSyncHealthSummary {
+            source_id: "src-1".to_string(),
+            last_sync_at: last_successful_sync_at,
+            last_successful_sync_at,
+            consecutive_failures,
+        }
+    }
+
+    fn error(category: ConnectorErrorCategory, created_at: OffsetDateTime) -> ConnectorErrorRecord {
+        ConnectorErrorRecord {
+            id: "err-1".to_string(),
+            source_id: "src-1".to_string(),
+            sync_run_id: None,
+            category,
+            message: "error".to_string(),
+            details: None,
+            created_at,
+        }
+    }
+
+    #[test]
+    fn test_healthy_without_failures_since_last_success() {
+        let now = OffsetDateTime::now_utc();
+        let old_error = error(
+            ConnectorErrorCategory::Authentication,
+            now - Duration::hours(2),
+        );
+
+        assert_eq!(
+            evaluate_health(&summary(0, Some(now)), Some(&old_error), 3),
+            ConnectorHealthStatus::Healthy
+        );
+        assert_eq!(
+            evaluate_health(&summary(0, None), None, 3),
+            ConnectorHealthStatus::Healthy
+        );
+    }
+
+    #[test]
+    fn test_failure_streak_degrades_then_fails() {
+        let now = OffsetDateTime::now_utc();
+
+        assert_eq!(
+            evaluate_health(&summary(1, Some(now)), None, 3),
+            ConnectorHealthStatus::Degraded
+        );
+        assert_eq!(
+            evaluate_health(&summary(3, Some(now)), None, 3),
+            ConnectorHealthStatus::Failing
+        );
+    }
+
+    #[test]
+    fn test_error_category_decides_severity() {
+        let succeeded_at = OffsetDateTime::now_utc() - Duration::hours(1);
+        let recent = succeeded_at + Duration::minutes(30);
+
+        let rate_limited = error(ConnectorErrorCategory::RateLimit, recent);
+        assert_eq!(
+            evaluate_health(&summary(0, Some(succeeded_at)), Some(&rate_limited), 3),
+            ConnectorHealthStatus::Degraded
+        );
+
+        let expired = error(ConnectorErrorCategory::Authentication, recent);
+        assert_eq!(
+            evaluate_health(&summary(1, Some(succeeded_at)), Some(&expired), 3),
+            ConnectorHealthStatus::Failing
+        );
+        assert_eq!(
+            evaluate_health(&summary(1, None), Some(&expired), 3),
+            ConnectorHealthStatus::Failing
+        );
+    }
+}
@@ -1,10 +1,22 @@
+use crate::compliance::subject_identifiers;
 use crate::connector_client::ConnectorClient;
+use crate::connector_health::evaluate_health;
 use crate::models::{
-    ActionContext, ActionRequest, ConnectorInfo, ExecuteActionRequest, ExecutePromptRequest,
-    ExecuteResourceRequest, ExecuteSkillRequest, McpCredentials, OAuthCredentialReadyRequest,
-    PromptRequest, ResourceRequest, ScheduleInfo, SourceHealth, SourceSyncOverview, SyncProgress,
-    TriggerSyncRequest, TriggerSyncResponse, TriggerType,
+    ActionContext, ActionRequest, AddCoOwnerRequest, AuditLogListQuery, AuditLogListResponse,
+    ComplianceRequestDetailResponse, ComplianceRequestListQuery, ComplianceRequestResponse,
+    ConnectorInfo, CreateComplianceRequest, CreatePushApiKeyRequest, CreatePushApiKeyResponse,
+    CreateSourceExportRequest, CreateWorkspaceRequest, ExecuteActionRequest, ExecutePromptRequest,
+    ExecuteResourceRequest, ExecuteSkillRequest, ExportDownloadQuery, McpCredentials,
+    OAuthCredentialReadyRequest, PromptRequest, ReassignOrphanedSourcesRequest,
+    ReassignOrphanedSourcesResponse, ReassignSourceDocumentsRequest, ResourceRequest, ScheduleInfo,
+    SdkCompareAndSwapSyncStateRequest, SdkRateLimitsRequest, SdkReportErrorRequest,
+    SdkSetSyncStateRequest, SetServicePrincipalRequest, SourceConnectorHealth,
+    SourceExportResponse, SourceHealth, SourceStatsPoint, SourceStatsQuery, SourceStatsResponse,
+    SourceSyncOverview, StartMaintenanceRequest, SyncProgress, SyncRunListQuery,
+    SyncRunListResponse, TransferOwnershipRequest, TriggerSyncRequest, TriggerSyncResponse,
+    TriggerType,
 };
+use crate::source_export::ARCHIVE_CONTENT_TYPE;
 use crate::sync_circuit_breaker::has_failure_streak;
 use crate::sync_manager::SyncError;
 use crate::AppState;
@@ -20,21 +32,36 @@ use axum::{
 use futures::stream::Stream;
 use redis::AsyncCommands;
 use serde_json::{json, Value};
+use shared::audit::{self, Actor, ActorType, AuditAction, AuditEvent};
 use shared::clients::docling::{DoclingClient, DoclingError};
-use shared::db::repositories::{ConfigurationRepository, SyncRunRepository};
+use shared::db::error::DatabaseError;
+use shared::db::repositories::{
+    AuditLogFilter, AuditLogRepository, ComplianceRequest, ComplianceRequestKind,
+    ComplianceRequestRepository, ConfigurationRepository, ConnectorErrorRecord,
+    ConnectorErrorRepository, OrphanedSource, PushApiKey, PushApiKeyRepository, SourceCoOwner,
+    SourceDailyStats, SourceDocumentReassignment, SourceExport, SourceExportRepository,
+    SourceMaintenance, SourceMaintenanceRepository, SourceMigrationRepository, SourceOwnership,
+    SourceOwnershipRepository, SourceRateLimitSummary, SourceStatsRepository,
+    SourceSyncHealthSummary, SyncRunFilter, SyncRunRepository, SyncStateEntry, SyncStateRepository,
+    WorkspaceRepository,
+};
 use shared::models::{
-    ActionMode, ConnectorManifest, GlobalConfiguration, SearchOperator, ServiceCredential,
-    ServiceProvider, Source, SourceType, SyncRun, SyncType,
+    ActionMode, ConnectorErrorCategory, ConnectorManifest, GlobalConfiguration, SearchOperator,
+    ServiceCredential, ServiceProvider, Source, SourceType, SyncRun, SyncType, Workspace,
+    DEFAULT_WORKSPACE_ID,
 };
 use shared::queue::EventQueue;
+use shared::search_cache;
+use shared::service_auth::TokenLifecycleManager;
 use shared::utils;
 use shared::{
-    DocumentRepository, Repository, ServiceCredentialsRepo, SourceRepository, UserRepository,
+    DocumentRepository, PersonRepository, Repository, ServiceCredentialsRepo, SourceRepository,
+    UserRepository,
 };
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::convert::Infallible;
 use std::time::Duration;
-use tokio::sync::OwnedSemaphorePermit;
+use tokio::sync::{broadcast, OwnedSemaphorePermit};
 use tracing::{debug, error, info, warn};
 
 pub async fn health_check() -> impl IntoResponse {
@@ -94,6 +121,12 @@ pub async fn cancel_sync(
     Ok(Json(json!({ "status": "cancelled" })))
 }
 
+/// How often a progress stream re-reads its sync run without notifications,
+/// to pick up changes made elsewhere (the indexer, other replicas).
+const SYNC_PROGRESS_POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// Minimum spacing of progress events, however often the connector reports.
+const SYNC_PROGRESS_MIN_INTERVAL: Duration = Duration::from_millis(500);
+
 pub async fn get_sync_progress(
     State(state): State<AppState>,
     Path(sync_run_id): Path<String>,
@@ -101,15 +134,16 @@ pub async fn get_sync_progress(
     debug!("SSE connection for sync progress: {}", sync_run_id);
 
     let pool = state.db_pool.pool().clone();
-    let sync_run_id_clone = sync_run_id.clone();
+    // Subscribe before the first read so no update in between is missed
+    let mut updates = state.sync_manager.subscribe_progress();
 
     let stream = async_stream::stream! {
-        let mut interval = tokio::time::interval(Duration::from_secs(1));
+        let mut poll = tokio::time::interval(SYNC_PROGRESS_POLL_INTERVAL);
+        poll.tick().await;
 
         loop {
-            interval.tick().await;
-
-            let progress = match get_progress_from_db(&pool, &sync_run_id_clone).await {
+            let sent_at = tokio::time::Instant::now();
+            let progress = match get_progress_from_db(&pool, &sync_run_id).await {
                 Ok(p) => p,
                 Err(e) => {
                     error!("Failed to get progress: {}", e);
@@ -127,30 +161,54 @@ pub async fn get_sync_progress(
             if progress.status != "running" {
                 break;
             }
+
+            // Wait for the connector to report progress on this run, or for
+            // the next poll
+            poll.reset();
+            loop {
+                tokio::select! {
+                    _ = poll.tick() => break,
+                    update = updates.recv() => match update {
+                        Ok(id) if id != sync_run_id => continue,
+                        // Our run, or missed notifications that may include it
+                        Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => break,
+                        Err(broadcast::error::RecvError::Closed) => {
+                            poll.tick().await;
+                            break;
+                        }
+                    },
+                }
+            }
+            tokio::time::sleep_until(sent_at + SYNC_PROGRESS_MIN_INTERVAL).await;
         }
     };
 
     Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
 }
 
+#[derive(sqlx::FromRow)]
+struct SyncProgressRow {
+    id: String,
+    source_id: String,
+    status: String,
+    documents_scanned: i32,
+    documents_processed: i32,
+    documents_updated: i32,
+    phase: Option<String>,
+    documents_total: Option<i32>,
+    error_message: Option<String>,
+    started_at: Option<time::OffsetDateTime>,
+    completed_at: Option<time::OffsetDateTime>,
+}
+
 async fn get_progress_from_db(
     pool: &sqlx::PgPool,
     sync_run_id: &str,
 ) -> Result<SyncProgress, sqlx::Error> {
-    let row: (
-        String,
-        String,
-        String,
-        i32,
-        i32,
-        i32,
-        Option<String>,
-        Option<time::OffsetDateTime>,
-        Option<time::OffsetDateTime>,
-    ) = sqlx::query_as(
+    let row: SyncProgressRow = sqlx::query_as(
         r#"
         SELECT id, source_id, status, documents_scanned, documents_processed, documents_updated,
-               error_message, started_at, completed_at
+               phase, documents_total, error_message, started_at, completed_at
         FROM sync_runs
         WHERE id = $1
         "#,
@@ -159,19 +217,41 @@ async fn get_progress_from_db(
     .fetch_one(pool)
     .await?;
 
+    let eta_seconds = match (row.status.as_str(), row.documents_total, row.started_at) {
+        ("running", Some(total), Some(started_at)) => estimate_eta_seconds(
+            row.documents_scanned,
+            total,
+            (time::OffsetDateTime::now_utc() - started_at).as_seconds_f64(),
+        ),
+        _ => None,
+    };
+
     Ok(SyncProgress {
-        sync_run_id: row.0,
-        source_id: row.1,
-        status: row.2,
-        documents_scanned: row.3,
-        documents_processed: row.4,
-        documents_updated: row.5,
-        error_message: row.6,
-        started_at: row.7.map(|t| t.to_string()),
-        completed_at: row.8.map(|t| t.to_string()),
+        sync_run_id: row.id,
+        source_id: row.source_id,
+        status: row.status,
+        documents_scanned: row.documents_scanned,
+        documents_processed: row.documents_processed,
+        documents_updated: row.documents_updated,
+        phase: row.phase,
+        documents_total: row.documents_total,
+        eta_seconds,
+        error_message: row.error_message,
+        started_at: row.started_at.map(|t| t.to_string()),
+        completed_at: row.completed_at.map(|t| t.to_string()),
     })
 }
 
+/// Seconds until `total` documents are scanned at the rate seen so far, or
+/// `None` before anything was scanned.
+fn estimate_eta_seconds(scanned: i32, total: i32, elapsed_seconds: f64) -> Option<i64> {
+    if scanned <= 0 || elapsed_seconds <= 0.0 {
+        return None;
+    }
+    let remaining = (total - scanned).max(0) as f64;
+    Some((remaining * elapsed_seconds / scanned as f64).ceil() as i64)
+}
+
 pub async fn list_schedules(
     State(state): State<AppState>,
 ) -> Result<Json<Vec<ScheduleInfo>>, ApiError> {
@@ -229,15 +309,175 @@ pub async fn list_schedules(
     Ok(Json(schedules))
 }
 
-pub async fn list_sources(
+const DEFAULT_SYNC_RUN_PAGE_SIZE: u32 = 50;
+const MAX_SYNC_RUN_PAGE_SIZE: u32 = 200;
+
+/// Sync run history across sources, newest first, with per-period totals
+/// of every matching run.
+pub async fn list_sync_runs(
     State(state): State<AppState>,
-) -> Result<Json<Vec<SourceSyncOverview>>, ApiError> {
-    let source_repo = SourceRepository::new(state.db_pool.pool());
-    let sources = source_repo
-        .find_all_sources_without_state()
+    Query(query): Query<SyncRunListQuery>,
+) -> Result<Json<SyncRunListResponse>, ApiError> {
+    let filter = SyncRunFilter {
+        source_id: query.source_id.filter(|id| !id.is_empty()),
+        status: query.status,
+        from: query
+            .from
+            .as_deref()
+            .map(|v| parse_time_bound("from", v))
+            .transpose()?,
+        to: query
+            .to
+            .as_deref()
+            .map(|v| parse_time_bound("to", v))
+            .transpose()?,
+    };
+    if matches!((filter.from, filter.to), (Some(from), Some(to)) if from >= to) {
+        return Err(ApiError::BadRequest(
+            "'from' must be earlier than 'to'".to_string(),
+        ));
+    }
+
+    let page = query.page.unwrap_or(1).max(1);
+    let page_size = query
+        .page_size
+        .unwrap_or(DEFAULT_SYNC_RUN_PAGE_SIZE)
+        .clamp(1, MAX_SYNC_RUN_PAGE_SIZE);
+    let offset = i64::from(page - 1) * i64::from(page_size);
+
+    let sync_run_repo = SyncRunRepository::new(state.db_pool.pool());
+    let (sync_runs, total) = sync_run_repo
+        .search(&filter, i64::from(page_size), offset)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    let stats = sync_run_repo
+        .stats_by_period(&filter, query.period)
         .await
         .map_err(|e| ApiError::Internal(e.to_string()))?;
 
+    let total_pages =
+        u32::try_from((total.max(0) as u64).div_ceil(u64::from(page_size))).unwrap_or(u32::MAX);
+
+    Ok(Json(SyncRunListResponse {
+        sync_runs,
+        total,
+        page,
+        page_size,
+        total_pages,
+        period: query.period,
+        stats,
+    }))
+}
+
+const DEFAULT_AUDIT_LOG_PAGE_SIZE: u32 = 50;
+const MAX_AUDIT_LOG_PAGE_SIZE: u32 = 500;
+
+/// Audit log entries, newest first.
+pub async fn list_audit_log(
+    State(state): State<AppState>,
+    Query(query): Query<AuditLogListQuery>,
+) -> Result<Json<AuditLogListResponse>, ApiError> {
+    let filter = AuditLogFilter {
+        actor: query
+            .actor
+            .map(|actor| actor.trim().to_string())
+            .filter(|actor| !actor.is_empty()),
+        action: query.action,
+        from: query
+            .from
+            .as_deref()
+            .map(|v| parse_time_bound("from", v))
+            .transpose()?,
+        to: query
+            .to
+            .as_deref()
+            .map(|v| parse_time_bound("to", v))
+            .transpose()?,
+    };
+    if matches!((filter.from, filter.to), (Some(from), Some(to)) if from >= to) {
+        return Err(ApiError::BadRequest(
+            "'from' must be earlier than 'to'".to_string(),
+        ));
+    }
+    let page = query.page.unwrap_or(1).max(1);
+    let page_size = query
+        .page_size
+        .unwrap_or(DEFAULT_AUDIT_LOG_PAGE_SIZE)
+        .clamp(1, MAX_AUDIT_LOG_PAGE_SIZE);
+    let offset = i64::from(page - 1) * i64::from(page_size);
+
+    let (entries, total) = AuditLogRepository::new(state.db_pool.pool())
+        .search(&filter, i64::from(page_size), offset)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    let total_pages =
+        u32::try_from((total.max(0) as u64).div_ceil(u64::from(page_size))).unwrap_or(u32::MAX);
+
+    Ok(Json(AuditLogListResponse {
+        entries,
+        total,
+        page,
+        page_size,
+        total_pages,
+    }))
+}
+
+/// Accepts an RFC 3339 timestamp, or a date meaning midnight UTC.
+fn parse_time_bound(name: &str, value: &str) -> Result<time::OffsetDateTime, ApiError> {
+    time::OffsetDateTime::parse(value, &time::format_description::well_known::Rfc3339)
+        .or_else(|_| {
+            time::Date::parse(
+                value,
+                time::macros::format_description!("[year]-[month]-[day]"),
+            )
+            .map(|date| date.midnight().assume_utc())
+        })
+        .map_err(|_| {
+            ApiError::BadRequest(format!(
+                "Invalid '{}': expected an RFC 3339 timestamp or YYYY-MM-DD date",
+                name
+            ))
+        })
+}
+
+/// Workspace whose sources a listing shows: the acting user's own. Services
+/// and background jobs, acting for no user, see every workspace.
+async fn actor_workspace(state: &AppState, actor: &Actor) -> Result<Option<String>, ApiError> {
+    if actor.actor_type != ActorType::User {
+        return Ok(None);
+    }
+    let workspaces = WorkspaceRepository::new(state.db_pool.pool());
+    let workspace_id = match (&actor.id, &actor.email) {
+        (Some(user_id), _) => workspaces.workspace_id_for_user(user_id).await,
+        (None, Some(email)) => workspaces.workspace_id_for_email(email).await,
+        (None, None) => Ok(DEFAULT_WORKSPACE_ID.to_string()),
+    }
+    .map_err(|e| ApiError::Internal(e.to_string()))?;
+    Ok(Some(workspace_id))
+}
+
+/// Every source the actor may see, per `actor_workspace`.
+async fn find_actor_sources(state: &AppState, actor: &Actor) -> Result<Vec<Source>, ApiError> {
+    let workspace_id = actor_workspace(state, actor).await?;
+    Ok(SourceRepository::new(state.db_pool.pool())
+        .find_all_sources_without_state()
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .into_iter()
+        .filter(|source| {
+            workspace_id
+                .as_deref()
+                .is_none_or(|id| source.workspace_id == id)
+        })
+        .collect())
+}
+
+pub async fn list_sources(
+    State(state): State<AppState>,
+    actor: Actor,
+) -> Result<Json<Vec<SourceSyncOverview>>, ApiError> {
+    let sources = find_actor_sources(&state, &actor).await?;
+
     Ok(Json(build_source_sync_overviews(&state, sources).await?))
 }
 
@@ -249,18 +489,999 @@ pub async fn get_source(
     let source = source_repo
         .find_by_id(source_id.clone())
         .await
-        .map_err(|e| ApiError::Internal(e.to_string()))?
-        .filter(|source| !source.is_deleted)
-        .ok_or_else(|| ApiError::NotFound(format!("Source not found: {}", source_id)))?;
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .filter(|source| !source.is_deleted)
+        .ok_or_else(|| ApiError::NotFound(format!("Source not found: {}", source_id)))?;
+
+    let mut overviews = build_source_sync_overviews(&state, vec![source]).await?;
+    let overview = overviews
+        .pop()
+        .ok_or_else(|| ApiError::NotFound(format!("Source not found: {}", source_id)))?;
+
+    Ok(Json(overview))
+}
+
+pub async fn get_source_maintenance(
+    State(state): State<AppState>,
+    Path(source_id): Path<String>,
+) -> Result<Json<SourceMaintenance>, ApiError> {
+    SourceMaintenanceRepository::new(state.db_pool.pool())
+        .get(&source_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .map(Json)
+        .ok_or_else(|| {
+            ApiError::NotFound(format!("Source is not in maintenance mode: {}", source_id))
+        })
+}
+
+/// Put a source into maintenance mode, or update an ongoing maintenance
+/// window. Syncs already running are left to finish.
+pub async fn start_source_maintenance(
+    State(state): State<AppState>,
+    actor: Actor,
+    Path(source_id): Path<String>,
+    Json(request): Json<StartMaintenanceRequest>,
+) -> Result<Json<SourceMaintenance>, ApiError> {
+    let source_repo = SourceRepository::new(state.db_pool.pool());
+    source_repo
+        .find_by_id(source_id.clone())
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .filter(|source| !source.is_deleted)
+        .ok_or_else(|| ApiError::NotFound(format!("Source not found: {}", source_id)))?;
+
+    let reason = request
+        .reason
+        .as_deref()
+        .map(str::trim)
+        .filter(|r| !r.is_empty());
+    let maintenance = SourceMaintenanceRepository::new(state.db_pool.pool())
+        .start(
+            &source_id,
+            request.search_visibility,
+            reason,
+            request.started_by.as_deref(),
+        )
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    // Search visibility of the source's documents may have changed
+    search_cache::invalidate_sources(&state.redis_client, [source_id.as_str()]).await;
+
+    info!(
+        "Source {} entered maintenance mode (search visibility: {:?})",
+        source_id, maintenance.search_visibility
+    );
+    audit_source_change(
+        &state,
+        actor,
+        &source_id,
+        json!({
+            "change": "maintenance_started",
+            "search_visibility": maintenance.search_visibility,
+            "reason": maintenance.reason,
+        }),
+    )
+    .await;
+    Ok(Json(maintenance))
+}
+
+pub async fn end_source_maintenance(
+    State(state): State<AppState>,
+    actor: Actor,
+    Path(source_id): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    let ended = SourceMaintenanceRepository::new(state.db_pool.pool())
+        .end(&source_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    if !ended {
+        return Err(ApiError::NotFound(format!(
+            "Source is not in maintenance mode: {}",
+            source_id
+        )));
+    }
+    search_cache::invalidate_sources(&state.redis_client, [source_id.as_str()]).await;
+
+    info!("Source {} left maintenance mode", source_id);
+    audit_source_change(
+        &state,
+        actor,
+        &source_id,
+        json!({ "change": "maintenance_ended" }),
+    )
+    .await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+const DEFAULT_SOURCE_STATS_DAYS: u32 = 30;
+const MAX_SOURCE_STATS_DAYS: u32 = 365;
+
+/// A source's footprint from the daily rollups, with what the last
+/// completed sync changed.
+pub async fn get_source_stats(
+    State(state): State<AppState>,
+    Path(source_id): Path<String>,
+    Query(query): Query<SourceStatsQuery>,
+) -> Result<Json<SourceStatsResponse>, ApiError> {
+    SourceRepository::new(state.db_pool.pool())
+        .find_by_id(source_id.clone())
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .filter(|source| !source.is_deleted)
+        .ok_or_else(|| ApiError::NotFound(format!("Source not found: {}", source_id)))?;
+
+    let days = query
+        .days
+        .unwrap_or(DEFAULT_SOURCE_STATS_DAYS)
+        .clamp(1, MAX_SOURCE_STATS_DAYS);
+    let today = time::OffsetDateTime::now_utc().replace_time(time::Time::MIDNIGHT);
+    let since = today - time::Duration::days(i64::from(days) - 1);
+
+    let history = SourceStatsRepository::new(state.db_pool.pool())
+        .get_daily_stats(&source_id, since)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    let last_sync = SyncRunRepository::new(state.db_pool.pool())
+        .get_last_completed_for_source(&source_id, None)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+
+    Ok(Json(SourceStatsResponse {
+        source_id,
+        current: history.last().cloned(),
+        history: with_document_count_changes(history),
+        last_sync: last_sync.map(Into::into),
+    }))
+}
+
+fn with_document_count_changes(history: Vec<SourceDailyStats>) -> Vec<SourceStatsPoint> {
+    let mut previous_count = None;
+    history
+        .into_iter()
+        .map(|stats| {
+            let document_count_change = previous_count.map(|count| stats.document_count - count);
+            previous_count = Some(stats.document_count);
+            SourceStatsPoint {
+                stats,
+                document_count_change,
+            }
+        })
+        .collect()
+}
+
+pub async fn list_workspaces(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<Workspace>>, ApiError> {
+    WorkspaceRepository::new(state.db_pool.pool())
+        .list()
+        .await
+        .map(Json)
+        .map_err(|e| ApiError::Internal(e.to_string()))
+}
+
+fn is_valid_workspace_slug(slug: &str) -> bool {
+    !slug.is_empty()
+        && slug.len() <= 100
+        && slug
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+}
+
+pub async fn create_workspace(
+    State(state): State<AppState>,
+    actor: Actor,
+    Json(request): Json<CreateWorkspaceRequest>,
+) -> Result<(StatusCode, Json<Workspace>), ApiError> {
+    let name = request.name.trim();
+    if name.is_empty() || name.len() > 255 {
+        return Err(ApiError::BadRequest(
+            "Workspace name must be 1 to 255 characters".to_string(),
+        ));
+    }
+    if !is_valid_workspace_slug(&request.slug) {
+        return Err(ApiError::BadRequest(
+            "Workspace slug must be lowercase letters, digits and dashes".to_string(),
+        ));
+    }
+
+    let workspace = WorkspaceRepository::new(state.db_pool.pool())
+        .create(name, &request.slug)
+        .await
+        .map_err(|e| match e {
+            DatabaseError::ConstraintViolation(message) => ApiError::Conflict(message),
+            other => ApiError::Internal(other.to_string()),
+        })?;
+
+    info!("Created workspace {} ({})", workspace.slug, workspace.id);
+    audit::record(
+        state.db_pool.pool(),
+        AuditEvent::new(actor, AuditAction::ConfigurationChanged)
+            .resource("workspace", &workspace.id)
+            .details(json!({ "change": "workspace_created", "slug": workspace.slug })),
+    )
+    .await;
+    Ok((StatusCode::CREATED, Json(workspace)))
+}
+
+/// Move a user into a workspace; they then only see its sources, documents
+/// and people.
+pub async fn assign_workspace_user(
+    State(state): State<AppState>,
+    actor: Actor,
+    Path((workspace_id, user_id)): Path<(String, String)>,
+) -> Result<StatusCode, ApiError> {
+    let workspaces = WorkspaceRepository::new(state.db_pool.pool());
+    workspaces
+        .find_by_id(&workspace_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .ok_or_else(|| ApiError::NotFound(format!("Workspace not found: {}", workspace_id)))?;
+    let assigned = workspaces
+        .assign_user(&user_id, &workspace_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    if !assigned {
+        return Err(ApiError::NotFound(format!("User not found: {}", user_id)));
+    }
+
+    info!("Moved user {} to workspace {}", user_id, workspace_id);
+    audit::record(
+        state.db_pool.pool(),
+        AuditEvent::new(actor, AuditAction::ConfigurationChanged)
+            .resource("workspace", &workspace_id)
+            .details(json!({ "change": "user_assigned", "user_id": user_id })),
+    )
+    .await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Fails unless `user_id` names an active user.
+async fn require_active_user(state: &AppState, user_id: &str) -> Result<(), ApiError> {
+    let user = UserRepository::new(state.db_pool.pool())
+        .find_by_id(user_id.to_string())
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .ok_or_else(|| ApiError::NotFound(format!("User not found: {}", user_id)))?;
+    if !user.is_active {
+        return Err(ApiError::BadRequest(format!(
+            "User is not active: {}",
+            user_id
+        )));
+    }
+    Ok(())
+}
+
+fn source_change_event(actor: Actor, source_id: &str, details: Value) -> AuditEvent {
+    AuditEvent::new(actor, AuditAction::ConfigurationChanged)
+        .resource("source", source_id)
+        .details(details)
+}
+
+/// Record an admin change to a source; `details.change` names it.
+async fn audit_source_change(state: &AppState, actor: Actor, source_id: &str, details: Value) {
+    audit::record(
+        state.db_pool.pool(),
+        source_change_event(actor, source_id, details),
+    )
+    .await;
+}
+
+pub async fn get_source_ownership(
+    State(state): State<AppState>,
+    Path(source_id): Path<String>,
+) -> Result<Json<SourceOwnership>, ApiError> {
+    SourceOwnershipRepository::new(state.db_pool.pool())
+        .get(&source_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .map(Json)
+        .ok_or_else(|| ApiError::NotFound(format!("Source not found: {}", source_id)))
+}
+
+/// Hand a source over to another user.
+pub async fn transfer_source_ownership(
+    State(state): State<AppState>,
+    actor: Actor,
+    Path(source_id): Path<String>,
+    Json(request): Json<TransferOwnershipRequest>,
+) -> Result<Json<SourceOwnership>, ApiError> {
+    require_active_user(&state, &request.new_owner_id).await?;
+
+    let ownership_repo = SourceOwnershipRepository::new(state.db_pool.pool());
+    let transferred = ownership_repo
+        .transfer_ownership(
+            &source_id,
+            &request.new_owner_id,
+            request.keep_previous_as_co_owner,
+        )
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    if !transferred {
+        return Err(ApiError::NotFound(format!(
+            "Source not found: {}",
+            source_id
+        )));
+    }
+
+    info!(
+        "Transferred ownership of source {} to user {}",
+        source_id, request.new_owner_id
+    );
+    audit_source_change(
+        &state,
+        actor,
+        &source_id,
+        json!({
+            "change": "ownership_transferred",
+            "new_owner_id": request.new_owner_id,
+            "keep_previous_as_co_owner": request.keep_previous_as_co_owner,
+        }),
+    )
+    .await;
+    ownership_repo
+        .get(&source_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .map(Json)
+        .ok_or_else(|| ApiError::NotFound(format!("Source not found: {}", source_id)))
+}
+
+pub async fn add_source_co_owner(
+    State(state): State<AppState>,
+    actor: Actor,
+    Path(source_id): Path<String>,
+    Json(request): Json<AddCoOwnerRequest>,
+) -> Result<Json<SourceCoOwner>, ApiError> {
+    let ownership = SourceOwnershipRepository::new(state.db_pool.pool());
+    let current = ownership
+        .get(&source_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .ok_or_else(|| ApiError::NotFound(format!("Source not found: {}", source_id)))?;
+    if current.owner_id == request.user_id {
+        return Err(ApiError::BadRequest(format!(
+            "User {} already owns source {}",
+            request.user_id, source_id
+        )));
+    }
+    require_active_user(&state, &request.user_id).await?;
+
+    let co_owner = ownership
+        .add_co_owner(&source_id, &request.user_id, request.added_by.as_deref())
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+
+    info!(
+        "Added user {} as co-owner of source {}",
+        request.user_id, source_id
+    );
+    audit_source_change(
+        &state,
+        actor,
+        &source_id,
+        json!({ "change": "co_owner_added", "user_id": request.user_id }),
+    )
+    .await;
+    Ok(Json(co_owner))
+}
+
+pub async fn remove_source_co_owner(
+    State(state): State<AppState>,
+    actor: Actor,
+    Path((source_id, user_id)): Path<(String, String)>,
+) -> Result<StatusCode, ApiError> {
+    let removed = SourceOwnershipRepository::new(state.db_pool.pool())
+        .remove_co_owner(&source_id, &user_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    if !removed {
+        return Err(ApiError::NotFound(format!(
+            "User {} is not a co-owner of source {}",
+            user_id, source_id
+        )));
+    }
+
+    info!(
+        "Removed user {} as co-owner of source {}",
+        user_id, source_id
+    );
+    audit_source_change(
+        &state,
+        actor,
+        &source_id,
+        json!({ "change": "co_owner_removed", "user_id": user_id }),
+    )
+    .await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Set or clear the user whose email connectors act as for the source, in
+/// place of its owner's.
+pub async fn set_source_service_principal(
+    State(state): State<AppState>,
+    actor: Actor,
+    Path(source_id): Path<String>,
+    Json(request): Json<SetServicePrincipalRequest>,
+) -> Result<Json<SourceOwnership>, ApiError> {
+    if let Some(user_id) = &request.user_id {
+        require_active_user(&state, user_id).await?;
+    }
+
+    let ownership_repo = SourceOwnershipRepository::new(state.db_pool.pool());
+    let updated = ownership_repo
+        .set_service_principal(&source_id, request.user_id.as_deref())
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    if !updated {
+        return Err(ApiError::NotFound(format!(
+            "Source not found: {}",
+            source_id
+        )));
+    }
+
+    info!(
+        "Service principal of source {} set to {:?}",
+        source_id, request.user_id
+    );
+    audit_source_change(
+        &state,
+        actor,
+        &source_id,
+        json!({ "change": "service_principal_set", "user_id": request.user_id }),
+    )
+    .await;
+    ownership_repo
+        .get(&source_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .map(Json)
+        .ok_or_else(|| ApiError::NotFound(format!("Source not found: {}", source_id)))
+}
+
+pub async fn list_orphaned_sources(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<OrphanedSource>>, ApiError> {
+    let sources = SourceOwnershipRepository::new(state.db_pool.pool())
+        .find_orphaned()
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    Ok(Json(sources))
+}
+
+/// Transfer orphaned sources, all or the given ones, to a new owner.
+pub async fn reassign_orphaned_sources(
+    State(state): State<AppState>,
+    actor: Actor,
+    Json(request): Json<ReassignOrphanedSourcesRequest>,
+) -> Result<Json<ReassignOrphanedSourcesResponse>, ApiError> {
+    require_active_user(&state, &request.new_owner_id).await?;
+
+    let ownership_repo = SourceOwnershipRepository::new(state.db_pool.pool());
+    let orphaned = ownership_repo
+        .find_orphaned()
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+
+    let requested = |source: &OrphanedSource| {
+        request
+            .source_ids
+            .as_ref()
+            .is_none_or(|ids| ids.contains(&source.source_id))
+    };
+    let mut reassigned = Vec::new();
+    for source in orphaned.into_iter().filter(requested) {
+        let transferred = ownership_repo
+            .transfer_ownership(&source.source_id, &request.new_owner_id, false)
+            .await
+            .map_err(|e| ApiError::Internal(e.to_string()))?;
+        if transferred {
+            reassigned.push(source.source_id);
+        }
+    }
+
+    info!(
+        "Reassigned {} orphaned sources to user {}",
+        reassigned.len(),
+        request.new_owner_id
+    );
+    let events = reassigned
+        .iter()
+        .map(|source_id| {
+            source_change_event(
+                actor.clone(),
+                source_id,
+                json!({
+                    "change": "ownership_transferred",
+                    "new_owner_id": request.new_owner_id,
+                    "orphaned": true,
+                }),
+            )
+        })
+        .collect();
+    audit::record_all(state.db_pool.pool(), events).await;
+    Ok(Json(ReassignOrphanedSourcesResponse { reassigned }))
+}
+
+/// Move a source's documents, and by default its sync state, to another
+/// source of the same connector type, e.g. one recreated to re-authenticate
+/// with a new account.
+pub async fn reassign_source_documents(
+    State(state): State<AppState>,
+    actor: Actor,
+    Path(source_id): Path<String>,
+    Json(request): Json<ReassignSourceDocumentsRequest>,
+) -> Result<Json<SourceDocumentReassignment>, ApiError> {
+    let reassignment = SourceMigrationRepository::new(state.db_pool.pool())
+        .reassign_documents(
+            &source_id,
+            &request.to_source_id,
+            request.include_sync_state,
+        )
+        .await
+        .map_err(|e| match e {
+            DatabaseError::NotFound => ApiError::NotFound(format!(
+                "Source not found: {} or {}",
+                source_id, request.to_source_id
+            )),
+            DatabaseError::InvalidInput(message) => ApiError::BadRequest(message),
+            DatabaseError::ConstraintViolation(message) => ApiError::Conflict(message),
+            other => ApiError::Internal(other.to_string()),
+        })?;
+    search_cache::invalidate_sources(
+        &state.redis_client,
+        [source_id.as_str(), request.to_source_id.as_str()],
+    )
+    .await;
+
+    info!(
+        "Reassigned {} documents from source {} to source {} ({} skipped, sync state moved: {})",
+        reassignment.moved_documents,
+        source_id,
+        request.to_source_id,
+        reassignment.skipped_documents,
+        reassignment.sync_state_moved
+    );
+    audit_source_change(
+        &state,
+        actor,
+        &source_id,
+        json!({
+            "change": "documents_reassigned",
+            "to_source_id": request.to_source_id,
+            "moved_documents": reassignment.moved_documents,
+            "sync_state_moved": reassignment.sync_state_moved,
+        }),
+    )
+    .await;
+    Ok(Json(reassignment))
+}
+
+const SOURCE_EXPORT_LIST_LIMIT: i64 = 20;
+
+fn export_response(export: SourceExport) -> SourceExportResponse {
+    let download_url = if export.is_downloadable(time::OffsetDateTime::now_utc()) {
+        export
+            .download_token
+            .as_ref()
+            .map(|token| format!("/exports/{}/download?token={}", export.id, token))
+    } else {
+        None
+    };
+    SourceExportResponse {
+        export,
+        download_url,
+    }
+}
+
+/// Queue an export of the source's documents. The scheduler picks it up and
+/// writes the archive in the background; poll `GET /exports/:id` for status.
+pub async fn create_source_export(
+    State(state): State<AppState>,
+    Path(source_id): Path<String>,
+    Json(request): Json<CreateSourceExportRequest>,
+) -> Result<(StatusCode, Json<SourceExportResponse>), ApiError> {
+    SourceRepository::new(state.db_pool.pool())
+        .find_by_id(source_id.clone())
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .filter(|source| !source.is_deleted)
+        .ok_or_else(|| ApiError::NotFound(format!("Source not found: {}", source_id)))?;
+
+    let export_repo = SourceExportRepository::new(state.db_pool.pool());
+    if export_repo
+        .has_active_export(&source_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+    {
+        return Err(ApiError::Conflict(format!(
+            "An export is already in progress for source: {}",
+            source_id
+        )));
+    }
+
+    let export = export_repo
+        .create(&source_id, request.requested_by.as_deref())
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    info!("Export {} queued for source {}", export.id, source_id);
+
+    Ok((StatusCode::ACCEPTED, Json(export_response(export))))
+}
+
+pub async fn list_source_exports(
+    State(state): State<AppState>,
+    Path(source_id): Path<String>,
+) -> Result<Json<Vec<SourceExportResponse>>, ApiError> {
+    let exports = SourceExportRepository::new(state.db_pool.pool())
+        .list_for_source(&source_id, SOURCE_EXPORT_LIST_LIMIT)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+
+    Ok(Json(exports.into_iter().map(export_response).collect()))
+}
+
+pub async fn get_source_export(
+    State(state): State<AppState>,
+    Path(export_id): Path<String>,
+) -> Result<Json<SourceExportResponse>, ApiError> {
+    let export = SourceExportRepository::new(state.db_pool.pool())
+        .get(&export_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .ok_or_else(|| ApiError::NotFound(format!("Export not found: {}", export_id)))?;
+
+    Ok(Json(export_response(export)))
+}
+
+/// Stream the archive by concatenating its parts. Requires the export's
+/// token and fails once the link has expired.
+pub async fn download_source_export(
+    State(state): State<AppState>,
+    Path(export_id): Path<String>,
+    Query(query): Query<ExportDownloadQuery>,
+) -> Result<axum::response::Response, ApiError> {
+    let export = SourceExportRepository::new(state.db_pool.pool())
+        .get(&export_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .ok_or_else(|| ApiError::NotFound(format!("Export not found: {}", export_id)))?;
+
+    if export.download_token.as_deref() != Some(query.token.as_str()) {
+        return Err(ApiError::Forbidden("Invalid download token".to_string()));
+    }
+    if !export.is_downloadable(time::OffsetDateTime::now_utc()) {
+        return Err(ApiError::Gone(format!(
+            "Export download link has expired: {}",
+            export_id
+        )));
+    }
+
+    let storage = state.content_storage.clone();
+    let parts = export.part_content_ids.clone();
+    let body = async_stream::stream! {
+        for part in parts {
+            yield storage
+                .get_content(&part)
+                .await
+                .map(axum::body::Bytes::from)
+                .map_err(|e| std::io::Error::other(e.to_string()));
+        }
+    };
+
+    Ok(axum::response::Response::builder()
+        .header(header::CONTENT_TYPE, ARCHIVE_CONTENT_TYPE)
+        .header(header::CONTENT_LENGTH, export.size_bytes)
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!(
+                "attachment; filename=\"source-{}-export-{}.tar.gz\"",
+                export.source_id, export.id
+            ),
+        )
+        .body(axum::body::Body::from_stream(body))
+        .unwrap())
+}
+
+const COMPLIANCE_REQUEST_LIST_LIMIT: i64 = 50;
+
+fn compliance_request_response(request: ComplianceRequest) -> ComplianceRequestResponse {
+    let download_url = if request.is_downloadable(time::OffsetDateTime::now_utc()) {
+        request.download_token.as_ref().map(|token| {
+            format!(
+                "/compliance/requests/{}/download?token={}",
+                request.id, token
+            )
+        })
+    } else {
+        None
+    };
+    ComplianceRequestResponse {
+        request,
+        download_url,
+    }
+}
+
+/// Queue a data subject access or erasure request. The scheduler picks it
+/// up and runs it in the background; poll `GET /compliance/requests/:id`
+/// for status and the documents it touched.
+pub async fn create_compliance_request(
+    State(state): State<AppState>,
+    actor: Actor,
+    Json(request): Json<CreateComplianceRequest>,
+) -> Result<(StatusCode, Json<ComplianceRequestResponse>), ApiError> {
+    let subject_email = request.subject_email.trim();
+    if !subject_email.contains('@') {
+        return Err(ApiError::BadRequest(format!(
+            "subject_email is not an email address: {}",
+            subject_email
+        )));
+    }
+    match (request.kind, request.erasure_action) {
+        (ComplianceRequestKind::Erasure, None) => {
+            return Err(ApiError::BadRequest(
+                "erasure_action is required for erasure requests".to_string(),
+            ));
+        }
+        (ComplianceRequestKind::Access, Some(_)) => {
+            return Err(ApiError::BadRequest(
+                "erasure_action is only valid for erasure requests".to_string(),
+            ));
+        }
+        _ => {}
+    }
+
+    let repo = ComplianceRequestRepository::new(state.db_pool.pool());
+    if repo
+        .has_active_request(subject_email)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+    {
+        return Err(ApiError::Conflict(format!(
+            "A request is already in progress for subject: {}",
+            subject_email
+        )));
+    }
+
+    let person = PersonRepository::new(state.db_pool.pool())
+        .fetch_person_by_email(subject_email)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    let identifiers = subject_identifiers(
+        subject_email,
+        person.as_ref().and_then(|p| p.display_name.as_deref()),
+        &request.aliases,
+    );
+
+    let created = repo
+        .create(
+            request.kind,
+            request.erasure_action,
+            subject_email,
+            &identifiers,
+            request.requested_by.as_deref(),
+        )
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    info!(
+        "Compliance request {} ({:?}) queued for {}",
+        created.id, created.kind, subject_email
+    );
+    audit::record(
+        state.db_pool.pool(),
+        AuditEvent::new(actor, AuditAction::ConfigurationChanged)
+            .resource("compliance_request", &created.id)
+            .details(json!({
+                "change": "compliance_request_created",
+                "kind": created.kind,
+                "erasure_action": created.erasure_action,
+                "subject_email": subject_email,
+            })),
+    )
+    .await;
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(compliance_request_response(created)),
+    ))
+}
+
+pub async fn list_compliance_requests(
+    State(state): State<AppState>,
+    Query(query): Query<ComplianceRequestListQuery>,
+) -> Result<Json<Vec<ComplianceRequestResponse>>, ApiError> {
+    let requests = ComplianceRequestRepository::new(state.db_pool.pool())
+        .list_recent(
+            query.subject_email.as_deref().map(str::trim),
+            COMPLIANCE_REQUEST_LIST_LIMIT,
+        )
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+
+    Ok(Json(
+        requests
+            .into_iter()
+            .map(compliance_request_response)
+            .collect(),
+    ))
+}
+
+pub async fn get_compliance_request(
+    State(state): State<AppState>,
+    Path(request_id): Path<String>,
+) -> Result<Json<ComplianceRequestDetailResponse>, ApiError> {
+    let repo = ComplianceRequestRepository::new(state.db_pool.pool());
+    let request = repo
+        .get(&request_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .ok_or_else(|| {
+            ApiError::NotFound(format!("Compliance request not found: {}", request_id))
+        })?;
+    let documents = repo
+        .list_documents(&request_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+
+    Ok(Json(ComplianceRequestDetailResponse {
+        request: compliance_request_response(request),
+        documents,
+    }))
+}
+
+/// Stream an access request's archive by concatenating its parts. Requires
+/// the request's token and fails once the link has expired.
+pub async fn download_compliance_export(
+    State(state): State<AppState>,
+    Path(request_id): Path<String>,
+    Query(query): Query<ExportDownloadQuery>,
+) -> Result<axum::response::Response, ApiError> {
+    let request = ComplianceRequestRepository::new(state.db_pool.pool())
+        .get(&request_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .ok_or_else(|| {
+            ApiError::NotFound(format!("Compliance request not found: {}", request_id))
+        })?;
+
+    if request.download_token.as_deref() != Some(query.token.as_str()) {
+        return Err(ApiError::Forbidden("Invalid download token".to_string()));
+    }
+    if !request.is_downloadable(time::OffsetDateTime::now_utc()) {
+        return Err(ApiError::Gone(format!(
+            "Export download link has expired: {}",
+            request_id
+        )));
+    }
+
+    let storage = state.content_storage.clone();
+    let parts = request.part_content_ids.clone();
+    let body = async_stream::stream! {
+        for part in parts {
+            yield storage
+                .get_content(&part)
+                .await
+                .map(axum::body::Bytes::from)
+                .map_err(|e| std::io::Error::other(e.to_string()));
+        }
+    };
+
+    Ok(axum::response::Response::builder()
+        .header(header::CONTENT_TYPE, ARCHIVE_CONTENT_TYPE)
+        .header(header::CONTENT_LENGTH, request.size_bytes)
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!(
+                "attachment; filename=\"subject-access-{}.tar.gz\"",
+                request.id
+            ),
+        )
+        .body(axum::body::Body::from_stream(body))
+        .unwrap())
+}
+
+/// Create an API key the push connector accepts for a push source.
+pub async fn create_push_api_key(
+    State(state): State<AppState>,
+    actor: Actor,
+    Path(source_id): Path<String>,
+    Json(request): Json<CreatePushApiKeyRequest>,
+) -> Result<(StatusCode, Json<CreatePushApiKeyResponse>), ApiError> {
+    let source = SourceRepository::new(state.db_pool.pool())
+        .find_by_id(source_id.clone())
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .filter(|source| !source.is_deleted)
+        .ok_or_else(|| ApiError::NotFound(format!("Source not found: {}", source_id)))?;
+    if source.source_type != SourceType::Push {
+        return Err(ApiError::BadRequest(format!(
+            "API keys can only be created for push sources: {}",
+            source_id
+        )));
+    }
+
+    let name = request.name.trim();
+    if name.is_empty() {
+        return Err(ApiError::BadRequest("name must not be empty".to_string()));
+    }
+
+    let (api_key, key) = PushApiKeyRepository::new(state.db_pool.pool())
+        .create(&source_id, name, request.created_by.as_deref())
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    info!(
+        "Push API key {} created for source {}",
+        api_key.id, source_id
+    );
+    audit_source_change(
+        &state,
+        actor,
+        &source_id,
+        json!({ "change": "push_api_key_created", "key_id": api_key.id, "name": name }),
+    )
+    .await;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(CreatePushApiKeyResponse { api_key, key }),
+    ))
+}
+
+pub async fn list_push_api_keys(
+    State(state): State<AppState>,
+    Path(source_id): Path<String>,
+) -> Result<Json<Vec<PushApiKey>>, ApiError> {
+    let keys = PushApiKeyRepository::new(state.db_pool.pool())
+        .list_for_source(&source_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+
+    Ok(Json(keys))
+}
+
+pub async fn revoke_push_api_key(
+    State(state): State<AppState>,
+    actor: Actor,
+    Path((source_id, key_id)): Path<(String, String)>,
+) -> Result<StatusCode, ApiError> {
+    let revoked = PushApiKeyRepository::new(state.db_pool.pool())
+        .revoke(&source_id, &key_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    if !revoked {
+        return Err(ApiError::NotFound(format!(
+            "Active API key not found: {}",
+            key_id
+        )));
+    }
 
-    let mut overviews = build_source_sync_overviews(&state, vec![source]).await?;
-    let overview = overviews
-        .pop()
-        .ok_or_else(|| ApiError::NotFound(format!("Source not found: {}", source_id)))?;
+    info!("Push API key {} revoked for source {}", key_id, source_id);
+    audit_source_change(
+        &state,
+        actor,
+        &source_id,
+        json!({ "change": "push_api_key_revoked", "key_id": key_id }),
+    )
+    .await;
+    Ok(StatusCode::NO_CONTENT)
+}
 
-    Ok(Json(overview))
+fn maintenance_message(source_id: &str, reason: Option<&str>) -> String {
+    match reason {
+        Some(reason) => format!(
+            "Source is in maintenance mode and cannot be synced: {} ({})",
+            source_id, reason
+        ),
+        None => format!(
+            "Source is in maintenance mode and cannot be synced: {}",
+            source_id
+        ),
+    }
 }
 
+/// How far back source health looks when summarizing rate limit usage.
+const RATE_LIMIT_SUMMARY_WINDOW: time::Duration = time::Duration::days(7);
+
 async fn build_source_sync_overviews(
     state: &AppState,
     sources: Vec<Source>,
@@ -291,11 +1512,35 @@ async fn build_source_sync_overviews(
             .or_default()
             .push(run);
     }
+    let mut rate_limits_by_source: HashMap<String, Vec<SourceRateLimitSummary>> = HashMap::new();
+    let rate_limits = sync_run_repo
+        .rate_limit_summary(
+            &source_ids,
+            time::OffsetDateTime::now_utc() - RATE_LIMIT_SUMMARY_WINDOW,
+        )
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    for summary in rate_limits {
+        rate_limits_by_source
+            .entry(summary.source_id.clone())
+            .or_default()
+            .push(summary);
+    }
+    let mut maintenance_by_source: HashMap<String, SourceMaintenance> =
+        SourceMaintenanceRepository::new(state.db_pool.pool())
+            .list()
+            .await
+            .map_err(|e| ApiError::Internal(e.to_string()))?
+            .into_iter()
+            .map(|m| (m.source_id.clone(), m))
+            .collect();
 
     Ok(sources
         .into_iter()
         .map(|source| {
             let sync_runs = runs_by_source.remove(&source.id).unwrap_or_default();
+            let maintenance = maintenance_by_source.remove(&source.id);
+            let rate_limits = rate_limits_by_source.remove(&source.id).unwrap_or_default();
             let health =
                 if has_failure_streak(&sync_runs, state.config.sync_max_consecutive_failures) {
                     SourceHealth::Unhealthy
@@ -319,6 +1564,8 @@ async fn build_source_sync_overviews(
                     ..source
                 },
                 health,
+                maintenance,
+                rate_limits,
             }
         })
         .collect())
@@ -352,6 +1599,82 @@ pub async fn list_connectors(
     Ok(Json(connectors))
 }
 
+/// Source types served by a registered connector that answers health checks.
+async fn online_source_types(state: &AppState) -> HashSet<SourceType> {
+    let client = ConnectorClient::new();
+    let mut online = HashSet::new();
+    for manifest in get_registered_manifests(&state.redis_client).await {
+        if !manifest.connector_url.is_empty() && client.health_check(&manifest.connector_url).await
+        {
+            online.extend(manifest.source_types.iter().copied());
+        }
+    }
+    online
+}
+
+/// Health of every source's connector: how its recent syncs went and the
+/// last error it reported, so failures needing an admin show in one place.
+pub async fn get_connectors_health(
+    State(state): State<AppState>,
+    actor: Actor,
+) -> Result<Json<Vec<SourceConnectorHealth>>, ApiError> {
+    let sources = find_actor_sources(&state, &actor).await?;
+    let source_ids: Vec<String> = sources.iter().map(|s| s.id.clone()).collect();
+
+    let mut summaries: HashMap<String, SourceSyncHealthSummary> =
+        SyncRunRepository::new(state.db_pool.pool())
+            .health_summary(&source_ids, &[SyncType::Full, SyncType::Incremental])
+            .await
+            .map_err(|e| ApiError::Internal(e.to_string()))?
+            .into_iter()
+            .map(|summary| (summary.source_id.clone(), summary))
+            .collect();
+    let mut last_errors: HashMap<String, ConnectorErrorRecord> =
+        ConnectorErrorRepository::new(state.db_pool.pool())
+            .latest_for_sources(&source_ids)
+            .await
+            .map_err(|e| ApiError::Internal(e.to_string()))?
+            .into_iter()
+            .map(|error| (error.source_id.clone(), error))
+            .collect();
+    let online = online_source_types(&state).await;
+
+    Ok(Json(
+        sources
+            .into_iter()
+            .map(|source| {
+                let summary =
+                    summaries
+                        .remove(&source.id)
+                        .unwrap_or_else(|| SourceSyncHealthSummary {
+                            source_id: source.id.clone(),
+                            last_sync_at: None,
+                            last_successful_sync_at: None,
+                            consecutive_failures: 0,
+                        });
+                let last_error = last_errors.remove(&source.id);
+                SourceConnectorHealth {
+                    status: evaluate_health(
+                        &summary,
+                        last_error.as_ref(),
+                        state.config.sync_max_consecutive_failures,
+                    ),
+                    connector_online: online.contains(&source.source_type),
+                    source_id: source.id,
+                    source_name: source.name,
+                    source_type: source.source_type,
+                    workspace_id: source.workspace_id,
+                    is_active: source.is_active,
+                    last_sync_at: summary.last_sync_at,
+                    last_successful_sync_at: summary.last_successful_sync_at,
+                    consecutive_failures: summary.consecutive_failures,
+                    last_error,
+                }
+            })
+            .collect(),
+    ))
+}
+
 pub async fn execute_action(
     State(state): State<AppState>,
     Json(request): Json<ExecuteActionRequest>,
@@ -1181,6 +2504,12 @@ pub enum ApiError {
     #[error("Conflict: {0}")]
     Conflict(String),
 
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+
+    #[error("Gone: {0}")]
+    Gone(String),
+
     #[error("Internal error: {0}")]
     Internal(String),
 
@@ -1209,6 +2538,9 @@ impl From<SyncError> for ApiError {
             SyncError::SourceInactive(id) => {
                 ApiError::BadRequest(format!("Source is inactive: {}", id))
             }
+            SyncError::SourceInMaintenance { source_id, reason } => {
+                ApiError::Conflict(maintenance_message(&source_id, reason.as_deref()))
+            }
             SyncError::SyncAlreadyRunning(id) => {
                 ApiError::Conflict(format!("Sync already running for source: {}", id))
             }
@@ -1255,6 +2587,8 @@ impl IntoResponse for ApiError {
             ApiError::NotFound(msg) => (StatusCode::NOT_FOUND, msg.clone()),
             ApiError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg.clone()),
             ApiError::Conflict(msg) => (StatusCode::CONFLICT, msg.clone()),
+            ApiError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg.clone()),
+            ApiError::Gone(msg) => (StatusCode::GONE, msg.clone()),
             ApiError::Internal(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg.clone()),
             ApiError::PayloadTooLarge(msg) => (StatusCode::PAYLOAD_TOO_LARGE, msg.clone()),
             ApiError::TooManyRequests { .. } => unreachable!(),
@@ -1778,11 +3112,13 @@ fn maybe_filter_xlsx_extracted_text(
 // ============================================================================
 
 use crate::models::{
-    SdkCancelSyncRequest, SdkCancelSyncResponse, SdkCreateSyncRequest, SdkCreateSyncResponse,
-    SdkEmitBatchRequest, SdkEmitEventRequest, SdkExtractContentResponse, SdkExtractTextResponse,
-    SdkFailRequest, SdkIncrementScannedRequest, SdkIncrementUpdatedRequest,
+    SdkAuthenticatePushKeyRequest, SdkAuthenticatePushKeyResponse, SdkCancelSyncRequest,
+    SdkCancelSyncResponse, SdkCreateSyncRequest, SdkCreateSyncResponse, SdkEmitBatchRequest,
+    SdkEmitEventRequest, SdkExtractContentResponse, SdkExtractTextResponse, SdkFailRequest,
+    SdkHeartbeatRequest, SdkIncrementScannedRequest, SdkIncrementUpdatedRequest,
     SdkSourceSyncConfigResponse, SdkStatusResponse, SdkStoreContentRequest,
-    SdkStoreContentResponse, SdkUserEmailResponse, SdkWebhookNotification, SdkWebhookResponse,
+    SdkStoreContentResponse, SdkUpdateCredentialsRequest, SdkUserEmailResponse,
+    SdkWebhookNotification, SdkWebhookResponse,
 };
 
 pub async fn sdk_emit_event(
@@ -1888,6 +3224,42 @@ async fn extract_content_blocking(
     .map_err(|e| ApiError::Internal(format!("Content extraction failed: {}", e)))
 }
 
+/// Tables of spreadsheets and Word documents with their headers, so cells
+/// are indexed in context. A file whose tables cannot be read is indexed
+/// from its text alone.
+async fn extract_tables_blocking(
+    data: &[u8],
+    mime_type: &str,
+    filename: Option<&str>,
+) -> Vec<shared::content_extractor::ExtractedTable> {
+    if !shared::content_extractor::has_extractable_tables(mime_type, filename) {
+        return Vec::new();
+    }
+
+    let max_rows = spreadsheet_max_indexed_rows();
+    let (data, mime_type, filename) = (
+        data.to_vec(),
+        mime_type.to_string(),
+        filename.map(str::to_string),
+    );
+    let tables = tokio::task::spawn_blocking(move || {
+        shared::content_extractor::extract_tables(&data, &mime_type, filename.as_deref(), max_rows)
+    })
+    .await;
+
+    match tables {
+        Ok(Ok(tables)) => tables,
+        Ok(Err(e)) => {
+            warn!("Table extraction failed, indexing text only: {}", e);
+            Vec::new()
+        }
+        Err(e) => {
+            warn!("Table extraction task failed, indexing text only: {}", e);
+            Vec::new()
+        }
+    }
+}
+
 fn is_pdf_extraction_target(mime_type: &str, filename: Option<&str>) -> bool {
     matches!(mime_type, "application/pdf" | "application/x-pdf")
         || (mime_type == "application/octet-stream" && has_extension(filename, "pdf"))
@@ -2000,6 +3372,7 @@ async fn do_extract_text(
     data: Vec<u8>,
 ) -> Result<String, ApiError> {
     let is_spreadsheet = is_spreadsheet_extraction_target(&mime_type, filename.as_deref());
+    let tables = extract_tables_blocking(&data, &mime_type, filename.as_deref()).await;
     let docling_candidate = is_docling_supported_mime(&mime_type)
         || (mime_type == "application/octet-stream"
             && is_docling_supported_extension(filename.as_deref()));
@@ -2083,6 +3456,12 @@ async fn do_extract_text(
     } else {
         extracted_text
     };
+    let processed_text = shared::content_extractor::with_structured_tables(
+        processed_text,
+        &tables,
+        &mime_type,
+        filename.as_deref(),
+    );
 
     let max_bytes = max_extracted_text_bytes();
     if processed_text.len() > max_bytes {
@@ -2243,14 +3622,21 @@ pub async fn sdk_store_content(
 pub async fn sdk_heartbeat(
     State(state): State<AppState>,
     Path(sync_run_id): Path<String>,
+    request: Option<Json<SdkHeartbeatRequest>>,
 ) -> Result<Json<SdkStatusResponse>, ApiError> {
     debug!("SDK: Heartbeat for sync_run={}", sync_run_id);
 
+    let request = request.map(|Json(request)| request).unwrap_or_default();
     let sync_run_repo = SyncRunRepository::new(state.db_pool.pool());
     sync_run_repo
-        .update_activity(&sync_run_id)
+        .update_progress(
+            &sync_run_id,
+            request.phase.as_deref(),
+            request.documents_total,
+        )
         .await
-        .map_err(|e| ApiError::Internal(format!("Failed to update activity: {}", e)))?;
+        .map_err(|e| ApiError::Internal(format!("Failed to update progress: {}", e)))?;
+    state.sync_manager.notify_progress(&sync_run_id);
 
     Ok(Json(SdkStatusResponse {
         status: "ok".to_string(),
@@ -2276,6 +3662,7 @@ pub async fn sdk_complete(
             sync_run_id
         );
     }
+    state.sync_manager.notify_progress(&sync_run_id);
 
     Ok(Json(SdkStatusResponse {
         status: "ok".to_string(),
@@ -2296,12 +3683,117 @@ pub async fn sdk_fail(
         .mark_failed(&sync_run_id, &request.error)
         .await
         .map_err(|e| ApiError::Internal(format!("Failed to mark failed: {}", e)))?;
-    if !updated {
+    if updated {
+        let category = request
+            .category
+            .unwrap_or_else(|| ConnectorErrorCategory::classify(&request.error));
+        record_sync_failure(&state, &sync_run_id, category, &request.error).await;
+    } else {
         warn!(
             "SDK: Ignoring stale fail for non-running sync_run={}",
             sync_run_id
         );
     }
+    state.sync_manager.notify_progress(&sync_run_id);
+
+    Ok(Json(SdkStatusResponse {
+        status: "ok".to_string(),
+    }))
+}
+
+/// Longest connector error message kept, in bytes.
+const MAX_CONNECTOR_ERROR_MESSAGE_BYTES: usize = 4096;
+
+fn truncate_error_message(message: &str) -> &str {
+    if message.len() <= MAX_CONNECTOR_ERROR_MESSAGE_BYTES {
+        return message;
+    }
+    utils::safe_str_slice(message, 0, MAX_CONNECTOR_ERROR_MESSAGE_BYTES)
+}
+
+/// Record why a sync run failed as an error of its source. Best-effort: the
+/// run is already marked failed either way.
+async fn record_sync_failure(
+    state: &AppState,
+    sync_run_id: &str,
+    category: ConnectorErrorCategory,
+    message: &str,
+) {
+    let sync_run = match SyncRunRepository::new(state.db_pool.pool())
+        .find_by_id(sync_run_id)
+        .await
+    {
+        Ok(Some(sync_run)) => sync_run,
+        Ok(None) => return,
+        Err(e) => {
+            warn!(
+                "Failed to look up sync_run={} to record its failure: {}",
+                sync_run_id, e
+            );
+            return;
+        }
+    };
+
+    if let Err(e) = ConnectorErrorRepository::new(state.db_pool.pool())
+        .record(
+            &sync_run.source_id,
+            Some(sync_run_id),
+            category,
+            truncate_error_message(message),
+            None,
+        )
+        .await
+    {
+        warn!(
+            "Failed to record failure of sync_run={}: {}",
+            sync_run_id, e
+        );
+    }
+}
+
+pub async fn sdk_report_error(
+    State(state): State<AppState>,
+    Path(source_id): Path<String>,
+    Json(request): Json<SdkReportErrorRequest>,
+) -> Result<Json<SdkStatusResponse>, ApiError> {
+    if request.message.trim().is_empty() {
+        return Err(ApiError::BadRequest(
+            "Error message must not be empty".to_string(),
+        ));
+    }
+    require_source_exists(&state, &source_id).await?;
+
+    if let Some(sync_run_id) = &request.sync_run_id {
+        let sync_run = SyncRunRepository::new(state.db_pool.pool())
+            .find_by_id(sync_run_id)
+            .await
+            .map_err(|e| ApiError::Internal(e.to_string()))?
+            .ok_or_else(|| ApiError::NotFound(format!("Sync run not found: {}", sync_run_id)))?;
+        if sync_run.source_id != source_id {
+            return Err(ApiError::BadRequest(format!(
+                "Sync run {} does not belong to source {}",
+                sync_run_id, source_id
+            )));
+        }
+    }
+
+    warn!(
+        source_id = %source_id,
+        sync_run_id = ?request.sync_run_id,
+        category = ?request.category,
+        "SDK: Connector error: {}",
+        request.message
+    );
+    ConnectorErrorRepository::new(state.db_pool.pool())
+        .record(
+            &source_id,
+            request.sync_run_id.as_deref(),
+            request.category,
+            truncate_error_message(&request.message),
+            request.details.as_ref(),
+        )
+        .await
+        .map_err(|e| ApiError::Internal(format!("Failed to record connector error: {}", e)))?;
 
     Ok(Json(SdkStatusResponse {
         status: "ok".to_string(),
@@ -2329,6 +3821,7 @@ pub async fn sdk_increment_scanned(
             sync_run_id
         );
     }
+    state.sync_manager.notify_progress(&sync_run_id);
 
     Ok(Json(SdkStatusResponse {
         status: "ok".to_string(),
@@ -2356,6 +3849,49 @@ pub async fn sdk_increment_updated(
             sync_run_id
         );
     }
+    state.sync_manager.notify_progress(&sync_run_id);
+
+    Ok(Json(SdkStatusResponse {
+        status: "ok".to_string(),
+    }))
+}
+
+pub async fn sdk_report_rate_limits(
+    State(state): State<AppState>,
+    Path(sync_run_id): Path<String>,
+    Json(request): Json<SdkRateLimitsRequest>,
+) -> Result<Json<SdkStatusResponse>, ApiError> {
+    let sync_run_repo = SyncRunRepository::new(state.db_pool.pool());
+    let sync_run = sync_run_repo
+        .find_by_id(&sync_run_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .ok_or_else(|| ApiError::NotFound(format!("Sync run not found: {}", sync_run_id)))?;
+
+    let stats: Vec<_> = request
+        .stats
+        .into_iter()
+        .filter(|s| !s.is_empty())
+        .collect();
+    for s in &stats {
+        info!(
+            source_id = %sync_run.source_id,
+            sync_run_id = %sync_run_id,
+            api_family = %s.api_family,
+            requests = s.requests,
+            wait_ms_total = s.wait_ms_total,
+            wait_ms_max = s.wait_ms_max,
+            backoff_ms_total = s.backoff_ms_total,
+            throttled = s.throttled,
+            retries = s.retries,
+            current_rps = s.current_rps,
+            "SDK: Rate limit usage"
+        );
+    }
+    sync_run_repo
+        .record_rate_limits(&sync_run_id, &stats)
+        .await
+        .map_err(|e| ApiError::Internal(format!("Failed to record rate limits: {}", e)))?;
 
     Ok(Json(SdkStatusResponse {
         status: "ok".to_string(),
@@ -2402,9 +3938,71 @@ pub async fn sdk_get_credentials(
             ApiError::NotFound(format!("Credentials not found for source: {}", source_id))
         })?;
 
+    // Hand out a token that outlives the sync's start. If it can't be
+    // refreshed, the connector gets the stored one and reports the failure.
+    let creds = match TokenLifecycleManager::new(state.db_pool.pool().clone())
+        .with_margin(time::Duration::seconds(
+            state.config.credential_refresh_margin_seconds,
+        ))
+        .ensure_fresh(&source, creds.clone())
+        .await
+    {
+        Ok(fresh) => fresh,
+        Err(e) => {
+            warn!(
+                "SDK: Could not refresh credentials of source {}: {}",
+                source_id, e
+            );
+            creds
+        }
+    };
+
     Ok(Json(creds))
 }
 
+pub async fn sdk_update_credentials(
+    State(state): State<AppState>,
+    Path(source_id): Path<String>,
+    Json(request): Json<SdkUpdateCredentialsRequest>,
+) -> Result<Json<SdkStatusResponse>, ApiError> {
+    debug!("SDK: Updating credentials for source_id={}", source_id);
+
+    let source_repo = SourceRepository::new(state.db_pool.pool());
+    let source = source_repo
+        .find_by_id(source_id.clone())
+        .await
+        .map_err(|e| ApiError::Internal(format!("Database error: {}", e)))?
+        .ok_or_else(|| ApiError::NotFound(format!("Source not found: {}", source_id)))?;
+
+    let creds_repo = ServiceCredentialsRepo::new(state.db_pool.pool().clone())
+        .map_err(|e| ApiError::Internal(format!("Failed to create credentials repo: {}", e)))?;
+
+    let mut creds = creds_repo
+        .find_owner_credential(&source)
+        .await
+        .map_err(|e| ApiError::Internal(format!("Database error: {}", e)))?
+        .ok_or_else(|| {
+            ApiError::NotFound(format!("Credentials not found for source: {}", source_id))
+        })?;
+
+    creds.credentials = request.credentials;
+    if let Some(expires_at) = request.expires_at {
+        creds.expires_at = Some(
+            time::OffsetDateTime::from_unix_timestamp(expires_at)
+                .map_err(|e| ApiError::BadRequest(format!("Invalid expires_at: {}", e)))?,
+        );
+    }
+
+    creds_repo
+        .update_credentials(&creds)
+        .await
+        .map_err(|e| ApiError::Internal(format!("Failed to update credentials: {}", e)))?;
+
+    Ok(Json(SdkStatusResponse {
+        status: "ok".to_string(),
+    }))
+}
+
 // TODO: drop this endpoint once the Python SDK is updated to fetch source +
 // credentials separately (matching the Rust SDK). Today the Rust SDK passes
 // full Source/ServiceCredential directly to Connector::sync, so it has no
@@ -2468,6 +4066,16 @@ pub async fn sdk_create_sync(
             request.source_id
         )));
     }
+    if let Some(maintenance) = SourceMaintenanceRepository::new(state.db_pool.pool())
+        .get(&request.source_id)
+        .await
+        .map_err(|e| ApiError::Internal(format!("Database error: {}", e)))?
+    {
+        return Err(ApiError::Conflict(maintenance_message(
+            &request.source_id,
+            maintenance.reason.as_deref(),
+        )));
+    }
     if state
         .sync_manager
         .is_sync_class_running(&request.source_id, request.sync_type.slot_class())
@@ -2539,17 +4147,39 @@ pub async fn sdk_get_user_email(
 ) -> Result<Json<SdkUserEmailResponse>, ApiError> {
     debug!("SDK: Getting user email for source_id={}", source_id);
 
-    let email = sqlx::query_scalar::<_, String>(
-        "SELECT u.email FROM sources s JOIN users u ON s.created_by = u.id WHERE s.id = $1",
-    )
-    .bind(&source_id)
-    .fetch_one(state.db_pool.pool())
-    .await
-    .map_err(|e| ApiError::Internal(format!("Failed to get user email: {}", e)))?;
+    let email = SourceOwnershipRepository::new(state.db_pool.pool())
+        .resolve_principal_email(&source_id)
+        .await
+        .map_err(|e| ApiError::Internal(format!("Failed to get user email: {}", e)))?
+        .ok_or_else(|| {
+            warn!(
+                "SDK: Source {} has no active owner, co-owner or service principal",
+                source_id
+            );
+            ApiError::NotFound(format!("No active owner for source: {}", source_id))
+        })?;
 
     Ok(Json(SdkUserEmailResponse { email }))
 }
 
+/// Resolve a push API key to its source. Unknown, revoked and inactive-source
+/// keys are all reported as not found.
+pub async fn sdk_authenticate_push_key(
+    State(state): State<AppState>,
+    Json(request): Json<SdkAuthenticatePushKeyRequest>,
+) -> Result<Json<SdkAuthenticatePushKeyResponse>, ApiError> {
+    let api_key = PushApiKeyRepository::new(state.db_pool.pool())
+        .authenticate(&request.key)
+        .await
+        .map_err(|e| ApiError::Internal(format!("Database error: {}", e)))?
+        .ok_or_else(|| ApiError::NotFound("Invalid push API key".to_string()))?;
+
+    Ok(Json(SdkAuthenticatePushKeyResponse {
+        key_id: api_key.id,
+        source_id: api_key.source_id,
+    }))
+}
+
 pub async fn sdk_notify_webhook(
     State(state): State<AppState>,
     Json(request): Json<SdkWebhookNotification>,
@@ -2619,6 +4249,116 @@ pub async fn sdk_update_connector_state(
     }))
 }
 
+// ============================================================================
+// SDK Sync State
+// ============================================================================
+
+const MAX_SYNC_STATE_KEY_LEN: usize = 200;
+
+fn validate_sync_state_key(key: &str) -> Result<(), ApiError> {
+    let valid = !key.is_empty()
+        && key.len() <= MAX_SYNC_STATE_KEY_LEN
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | ':'));
+    if !valid {
+        return Err(ApiError::BadRequest(format!(
+            "Invalid sync state key '{}': use up to {} ASCII letters, digits or _ - . :",
+            key, MAX_SYNC_STATE_KEY_LEN
+        )));
+    }
+    Ok(())
+}
+
+async fn require_source_exists(state: &AppState, source_id: &str) -> Result<(), ApiError> {
+    SourceRepository::new(state.db_pool.pool())
+        .find_by_id(source_id.to_string())
+        .await
+        .map_err(|e| ApiError::Internal(format!("Database error: {}", e)))?
+        .ok_or_else(|| ApiError::NotFound(format!("Source not found: {}", source_id)))?;
+    Ok(())
+}
+
+pub async fn sdk_get_sync_state(
+    State(state): State<AppState>,
+    Path((source_id, key)): Path<(String, String)>,
+) -> Result<Json<SyncStateEntry>, ApiError> {
+    validate_sync_state_key(&key)?;
+
+    let entry = SyncStateRepository::new(state.db_pool.pool())
+        .get(&source_id, &key)
+        .await
+        .map_err(|e| ApiError::Internal(format!("Database error: {}", e)))?
+        .ok_or_else(|| ApiError::NotFound(format!("No sync state '{}'", key)))?;
+
+    Ok(Json(entry))
+}
+
+pub async fn sdk_set_sync_state(
+    State(state): State<AppState>,
+    Path((source_id, key)): Path<(String, String)>,
+    Json(request): Json<SdkSetSyncStateRequest>,
+) -> Result<Json<SyncStateEntry>, ApiError> {
+    validate_sync_state_key(&key)?;
+    require_source_exists(&state, &source_id).await?;
+    debug!(
+        "SDK: Setting sync state {} for source_id={}",
+        key, source_id
+    );
+
+    let entry = SyncStateRepository::new(state.db_pool.pool())
+        .set(&source_id, &key, &request.value)
+        .await
+        .map_err(|e| ApiError::Internal(format!("Failed to set sync state: {}", e)))?;
+
+    Ok(Json(entry))
+}
+
+/// Write sync state only if it is still at the version the caller read.
+/// Responds 409 when another writer got there first.
+pub async fn sdk_compare_and_swap_sync_state(
+    State(state): State<AppState>,
+    Path((source_id, key)): Path<(String, String)>,
+    Json(request): Json<SdkCompareAndSwapSyncStateRequest>,
+) -> Result<Json<SyncStateEntry>, ApiError> {
+    validate_sync_state_key(&key)?;
+    require_source_exists(&state, &source_id).await?;
+    debug!(
+        "SDK: Compare-and-swap sync state {} for source_id={} (expected version {:?})",
+        key, source_id, request.expected_version
+    );
+
+    let entry = SyncStateRepository::new(state.db_pool.pool())
+        .compare_and_swap(&source_id, &key, request.expected_version, &request.value)
+        .await
+        .map_err(|e| ApiError::Internal(format!("Failed to set sync state: {}", e)))?
+        .ok_or_else(|| {
+            ApiError::Conflict(format!(
+                "Sync state '{}' is no longer at version {:?}",
+                key, request.expected_version
+            ))
+        })?;
+
+    Ok(Json(entry))
+}
+
+pub async fn sdk_delete_sync_state(
+    State(state): State<AppState>,
+    Path((source_id, key)): Path<(String, String)>,
+) -> Result<StatusCode, ApiError> {
+    validate_sync_state_key(&key)?;
+
+    let deleted = SyncStateRepository::new(state.db_pool.pool())
+        .delete(&source_id, &key)
+        .await
+        .map_err(|e| ApiError::Internal(format!("Failed to delete sync state: {}", e)))?;
+    if !deleted {
+        return Err(ApiError::NotFound(format!("No sync state '{}'", key)));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
 // ============================================================================
 // SDK Sources by Type
 // ============================================================================
@@ -2669,6 +4409,51 @@ pub async fn sdk_get_connector_config(
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_with_document_count_changes() {
+        let snapshot = |day: i64, document_count: i64| SourceDailyStats {
+            day: time::macros::datetime!(2026-03-01 00:00 UTC) + time::Duration::days(day),
+            document_count,
+            content_bytes: 0,
+            embedded_document_count: 0,
+            chunk_count: 0,
+            avg_document_age_seconds: None,
+            refreshed_at: time::macros::datetime!(2026-03-05 00:00 UTC),
+        };
+
+        let changes: Vec<Option<i64>> =
+            with_document_count_changes(vec![snapshot(0, 100), snapshot(1, 130), snapshot(3, 120)])
+                .into_iter()
+                .map(|point| point.document_count_change)
+                .collect();
+
+        assert_eq!(changes, vec![None, Some(30), Some(-10)]);
+    }
+
+    #[test]
+    fn test_estimate_eta_seconds() {
+        // 250 of 1000 in 60s leaves 750 at the same rate
+        assert_eq!(estimate_eta_seconds(250, 1000, 60.0), Some(180));
+        assert_eq!(estimate_eta_seconds(1200, 1000, 60.0), Some(0));
+        assert_eq!(estimate_eta_seconds(0, 1000, 60.0), None);
+    }
+
+    #[test]
+    fn test_parse_time_bound() {
+        assert_eq!(
+            parse_time_bound("from", "2026-03-01").unwrap(),
+            time::macros::datetime!(2026-03-01 00:00 UTC)
+        );
+        assert_eq!(
+            parse_time_bound("from", "2026-03-01T12:30:00+02:00").unwrap(),
+            time::macros::datetime!(2026-03-01 10:30 UTC)
+        );
+        assert!(matches!(
+            parse_time_bound("to", "yesterday"),
+            Err(ApiError::BadRequest(_))
+        ));
+    }
+
     fn manifest_with_action_schema(input_schema: serde_json::Value) -> ConnectorManifest {
         ConnectorManifest {
             name: "test_connector".to_string(),
@@ -1,9 +1,12 @@
+pub mod compliance;
 pub mod config;
 pub mod connector_client;
+pub mod connector_health;
 pub mod handlers;
 pub mod models;
 pub mod scheduler;
 pub mod source_cleanup;
+pub mod source_export;
 pub mod sync_circuit_breaker;
 pub mod sync_manager;
 
@@ -11,12 +14,14 @@ use anyhow::Result as AnyhowResult;
 use axum::{
     extract::DefaultBodyLimit,
     middleware,
-    routing::{get, post, put},
+    routing::{delete, get, post, put},
     Router,
 };
 use config::ConnectorManagerConfig;
 use redis::Client as RedisClient;
 use shared::{
+    http_security::HttpSecurityConfig,
+    service_auth::internal::InternalAuthConfig,
     telemetry::{self, TelemetryConfig},
     DatabasePool, ObjectStorage,
 };
@@ -25,7 +30,6 @@ use std::sync::Arc;
 use sync_manager::SyncManager;
 use tokio::sync::Semaphore;
 use tower::ServiceBuilder;
-use tower_http::cors::CorsLayer;
 use tracing::{info, warn};
 
 #[derive(Clone)]
@@ -47,9 +51,85 @@ pub fn create_app(state: AppState) -> Router {
         .route("/sync/:id/cancel", post(handlers::cancel_sync))
         .route("/sync/:id/progress", get(handlers::get_sync_progress))
         .route("/schedules", get(handlers::list_schedules))
+        .route("/sync-runs", get(handlers::list_sync_runs))
+        .route("/audit-log", get(handlers::list_audit_log))
+        .route(
+            "/workspaces",
+            get(handlers::list_workspaces).post(handlers::create_workspace),
+        )
+        .route(
+            "/workspaces/:workspace_id/users/:user_id",
+            put(handlers::assign_workspace_user),
+        )
         .route("/sources", get(handlers::list_sources))
+        .route("/sources/orphaned", get(handlers::list_orphaned_sources))
+        .route(
+            "/sources/orphaned/reassign",
+            post(handlers::reassign_orphaned_sources),
+        )
         .route("/sources/:source_id", get(handlers::get_source))
+        .route(
+            "/sources/:source_id/ownership",
+            get(handlers::get_source_ownership),
+        )
+        .route(
+            "/sources/:source_id/owner",
+            put(handlers::transfer_source_ownership),
+        )
+        .route(
+            "/sources/:source_id/co-owners",
+            post(handlers::add_source_co_owner),
+        )
+        .route(
+            "/sources/:source_id/co-owners/:user_id",
+            delete(handlers::remove_source_co_owner),
+        )
+        .route(
+            "/sources/:source_id/service-principal",
+            put(handlers::set_source_service_principal),
+        )
+        .route(
+            "/sources/:source_id/maintenance",
+            get(handlers::get_source_maintenance)
+                .put(handlers::start_source_maintenance)
+                .delete(handlers::end_source_maintenance),
+        )
+        .route(
+            "/sources/:source_id/documents/reassign",
+            post(handlers::reassign_source_documents),
+        )
+        .route("/sources/:source_id/stats", get(handlers::get_source_stats))
+        .route(
+            "/sources/:source_id/exports",
+            get(handlers::list_source_exports).post(handlers::create_source_export),
+        )
+        .route(
+            "/sources/:source_id/push-keys",
+            get(handlers::list_push_api_keys).post(handlers::create_push_api_key),
+        )
+        .route(
+            "/sources/:source_id/push-keys/:key_id",
+            delete(handlers::revoke_push_api_key),
+        )
+        .route("/exports/:export_id", get(handlers::get_source_export))
+        .route(
+            "/compliance/requests",
+            get(handlers::list_compliance_requests).post(handlers::create_compliance_request),
+        )
+        .route(
+            "/compliance/requests/:request_id",
+            get(handlers::get_compliance_request),
+        )
+        .route(
+            "/compliance/requests/:request_id/download",
+            get(handlers::download_compliance_export),
+        )
+        .route(
+            "/exports/:export_id/download",
+            get(handlers::download_source_export),
+        )
         .route("/connectors", get(handlers::list_connectors))
+        .route("/connectors/health", get(handlers::get_connectors_health))
         .route("/action", post(handlers::execute_action))
         .route("/actions", get(handlers::list_actions))
         .route("/resource", post(handlers::read_resource))
@@ -84,10 +164,18 @@ pub fn create_app(state: AppState) -> Router {
             "/sdk/sync/:id/updated",
             post(handlers::sdk_increment_updated),
         )
+        .route(
+            "/sdk/sync/:id/rate-limits",
+            post(handlers::sdk_report_rate_limits),
+        )
+        .route(
+            "/sdk/source/:source_id/errors",
+            post(handlers::sdk_report_error),
+        )
         .route("/sdk/source/:source_id", get(handlers::sdk_get_source))
         .route(
             "/sdk/credentials/:source_id",
-            get(handlers::sdk_get_credentials),
+            get(handlers::sdk_get_credentials).put(handlers::sdk_update_credentials),
         )
         .route(
             "/sdk/source/:source_id/sync-config",
@@ -100,6 +188,10 @@ pub fn create_app(state: AppState) -> Router {
             "/sdk/source/:source_id/user-email",
             get(handlers::sdk_get_user_email),
         )
+        .route(
+            "/sdk/push-keys/authenticate",
+            post(handlers::sdk_authenticate_push_key),
+        )
         // Webhook notification endpoint
         .route("/sdk/webhook/notify", post(handlers::sdk_notify_webhook))
         // Connector state management
@@ -107,6 +199,17 @@ pub fn create_app(state: AppState) -> Router {
             "/sdk/source/:source_id/connector-state",
             put(handlers::sdk_update_connector_state),
         )
+        // Incremental sync state
+        .route(
+            "/sdk/source/:source_id/state/:key",
+            get(handlers::sdk_get_sync_state)
+                .put(handlers::sdk_set_sync_state)
+                .delete(handlers::sdk_delete_sync_state),
+        )
+        .route(
+            "/sdk/source/:source_id/state/:key/cas",
+            post(handlers::sdk_compare_and_swap_sync_state),
+        )
         // Sources by type
         .route(
             "/sdk/sources/by-type/:source_type",
@@ -121,13 +224,14 @@ pub fn create_app(state: AppState) -> Router {
         .layer(
             ServiceBuilder::new()
                 .layer(middleware::from_fn(telemetry::middleware::trace_layer))
-                .layer(CorsLayer::permissive()),
+                .layer(HttpSecurityConfig::from_env("CONNECTOR_MANAGER").layer())
+                .layer(InternalAuthConfig::from_env("CONNECTOR_MANAGER").layer()),
         )
         .with_state(state)
 }
 
 pub async fn run_server() -> AnyhowResult<()> {
-    dotenvy::dotenv().ok();
+    shared::config_loader::load_env_files();
 
     let telemetry_config = TelemetryConfig::from_env("omni-connector-manager");
     telemetry::init_telemetry(telemetry_config)?;
@@ -161,7 +265,7 @@ pub async fn run_server() -> AnyhowResult<()> {
         redis_client: redis_client.clone(),
         config: config.clone(),
         sync_manager: sync_manager.clone(),
-        content_storage,
+        content_storage: content_storage.clone(),
         extraction_semaphore: Arc::new(Semaphore::new(config.extraction_concurrency)),
     };
 
@@ -176,6 +280,7 @@ pub async fn run_server() -> AnyhowResult<()> {
         redis_client,
         config.clone(),
         sync_manager,
+        content_storage,
     ));
     info!("Scheduler started");
 
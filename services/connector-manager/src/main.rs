@@ -1,6 +1,15 @@
 use anyhow::Result;
+use omni_connector_manager::config::ConnectorManagerConfig;
+use shared::config_loader;
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    if config_loader::print_config_requested() {
+        config_loader::load_env_files();
+        std::process::exit(config_loader::print_config(
+            "omni-connector-manager",
+            ConnectorManagerConfig::load,
+        ));
+    }
     omni_connector_manager::run_server().await
 }
@@ -1,6 +1,14 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
-use shared::models::{Source, SourceType, SyncRun, SyncType};
+use shared::RateLimitStats;
+use shared::audit::AuditAction;
+use shared::db::repositories::{
+    AuditLogEntry, ComplianceRequest, ComplianceRequestDocument, ComplianceRequestKind,
+    ConnectorErrorRecord, ErasureAction, MaintenanceSearchVisibility, PushApiKey, SourceDailyStats,
+    SourceExport, SourceMaintenance, SourceRateLimitSummary, SyncRunPeriodStats,
+    SyncRunStatsPeriod,
+};
+use shared::models::{ConnectorErrorCategory, Source, SourceType, SyncRun, SyncStatus, SyncType};
 
 pub use shared::models::{
     ActionContext, ActionDefinition, ActionRequest, ActionResponse, CancelRequest,
@@ -35,6 +43,15 @@ pub struct SyncProgress {
     pub documents_scanned: i32,
     pub documents_processed: i32,
     pub documents_updated: i32,
+    /// What the connector last reported it was doing, e.g. "listing files".
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub phase: Option<String>,
+    /// How many documents the connector expects to scan, if it knows.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub documents_total: Option<i32>,
+    /// Estimated time left, from the scan rate so far and `documents_total`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub eta_seconds: Option<i64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error_message: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -72,11 +89,292 @@ pub enum SourceHealth {
     Unhealthy,
 }
 
+/// Overall state of a source's connector, from its recent syncs and errors.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ConnectorHealthStatus {
+    Healthy,
+    /// Some syncs or operations failed, but not enough to stop syncing.
+    Degraded,
+    /// Syncs keep failing, or failed for a reason that needs an admin.
+    Failing,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceConnectorHealth {
+    pub source_id: String,
+    pub source_name: String,
+    pub source_type: SourceType,
+    pub workspace_id: String,
+    pub is_active: bool,
+    pub status: ConnectorHealthStatus,
+    /// Whether a connector serving the source's type is registered and
+    /// answering health checks.
+    pub connector_online: bool,
+    #[serde(with = "time::serde::iso8601::option")]
+    pub last_sync_at: Option<time::OffsetDateTime>,
+    #[serde(with = "time::serde::iso8601::option")]
+    pub last_successful_sync_at: Option<time::OffsetDateTime>,
+    pub consecutive_failures: i64,
+    pub last_error: Option<ConnectorErrorRecord>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SourceSyncOverview {
     pub source: Source,
     pub health: SourceHealth,
     pub sync_runs: Vec<SyncRun>,
+    /// Present while the source is in maintenance mode.
+    pub maintenance: Option<SourceMaintenance>,
+    /// Upstream rate limit usage of the source's recent syncs, per API family.
+    #[serde(default)]
+    pub rate_limits: Vec<SourceRateLimitSummary>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StartMaintenanceRequest {
+    #[serde(default)]
+    pub search_visibility: MaintenanceSearchVisibility,
+    #[serde(default)]
+    pub reason: Option<String>,
+    /// Admin user starting maintenance.
+    #[serde(default)]
+    pub started_by: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransferOwnershipRequest {
+    pub new_owner_id: String,
+    /// Keep the previous owner on as a co-owner.
+    #[serde(default)]
+    pub keep_previous_as_co_owner: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddCoOwnerRequest {
+    pub user_id: String,
+    /// Admin user adding the co-owner.
+    #[serde(default)]
+    pub added_by: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SetServicePrincipalRequest {
+    /// `None` clears the service principal.
+    #[serde(default)]
+    pub user_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReassignOrphanedSourcesRequest {
+    pub new_owner_id: String,
+    /// Limit the reassignment to these sources. All orphaned sources when
+    /// omitted.
+    #[serde(default)]
+    pub source_ids: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReassignOrphanedSourcesResponse {
+    pub reassigned: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReassignSourceDocumentsRequest {
+    pub to_source_id: String,
+    /// Also carry over the connector state, checkpoint and sync state keys,
+    /// so the target's next sync continues from the old source's.
+    #[serde(default = "default_include_sync_state")]
+    pub include_sync_state: bool,
+}
+
+fn default_include_sync_state() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CreateSourceExportRequest {
+    /// Admin user requesting the export.
+    #[serde(default)]
+    pub requested_by: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SourceExportResponse {
+    #[serde(flatten)]
+    pub export: SourceExport,
+    /// Relative download path carrying the export's secret token. Present
+    /// only while the archive is downloadable.
+    pub download_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ExportDownloadQuery {
+    pub token: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateComplianceRequest {
+    pub kind: ComplianceRequestKind,
+    /// Required for erasure requests.
+    #[serde(default)]
+    pub erasure_action: Option<ErasureAction>,
+    pub subject_email: String,
+    /// Other names or addresses the subject appears under, matched in
+    /// addition to the email and their people directory display name.
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    /// Admin user making the request.
+    #[serde(default)]
+    pub requested_by: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ComplianceRequestListQuery {
+    #[serde(default)]
+    pub subject_email: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ComplianceRequestResponse {
+    #[serde(flatten)]
+    pub request: ComplianceRequest,
+    /// Relative download path carrying the access export's secret token.
+    /// Present only while the archive is downloadable.
+    pub download_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ComplianceRequestDetailResponse {
+    #[serde(flatten)]
+    pub request: ComplianceRequestResponse,
+    /// Every document the request exported, deleted or redacted.
+    pub documents: Vec<ComplianceRequestDocument>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreatePushApiKeyRequest {
+    pub name: String,
+    /// Admin user creating the key.
+    #[serde(default)]
+    pub created_by: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CreatePushApiKeyResponse {
+    #[serde(flatten)]
+    pub api_key: PushApiKey,
+    /// The plaintext key. Only returned here; it cannot be retrieved later.
+    pub key: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateWorkspaceRequest {
+    pub name: String,
+    /// Lowercase letters, digits and dashes.
+    pub slug: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AuditLogListQuery {
+    /// An actor id, or an actor email matched case-insensitively.
+    pub actor: Option<String>,
+    pub action: Option<AuditAction>,
+    /// Earliest entry time, inclusive: an RFC 3339 timestamp or a date.
+    pub from: Option<String>,
+    /// Latest entry time, exclusive: an RFC 3339 timestamp or a date.
+    pub to: Option<String>,
+    /// 1-based page number.
+    pub page: Option<u32>,
+    pub page_size: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditLogListResponse {
+    pub entries: Vec<AuditLogEntry>,
+    /// Entries matching the filters across all pages.
+    pub total: i64,
+    pub page: u32,
+    pub page_size: u32,
+    pub total_pages: u32,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SyncRunListQuery {
+    pub source_id: Option<String>,
+    pub status: Option<SyncStatus>,
+    /// Earliest start time, inclusive: an RFC 3339 timestamp or a date.
+    pub from: Option<String>,
+    /// Latest start time, exclusive: an RFC 3339 timestamp or a date.
+    pub to: Option<String>,
+    /// 1-based page number.
+    pub page: Option<u32>,
+    pub page_size: Option<u32>,
+    /// Bucket size for `stats`.
+    #[serde(default)]
+    pub period: SyncRunStatsPeriod,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncRunListResponse {
+    pub sync_runs: Vec<SyncRun>,
+    /// Runs matching the filters across all pages.
+    pub total: i64,
+    pub page: u32,
+    pub page_size: u32,
+    pub total_pages: u32,
+    pub period: SyncRunStatsPeriod,
+    /// Aggregates of every matching run, newest period first.
+    pub stats: Vec<SyncRunPeriodStats>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SourceStatsQuery {
+    /// How many days of history to return, including today.
+    pub days: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SourceStatsResponse {
+    pub source_id: String,
+    /// The latest snapshot, if the source has been rolled up yet.
+    pub current: Option<SourceDailyStats>,
+    /// One snapshot per day, oldest first.
+    pub history: Vec<SourceStatsPoint>,
+    pub last_sync: Option<LastSyncStats>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SourceStatsPoint {
+    #[serde(flatten)]
+    pub stats: SourceDailyStats,
+    /// Change in document count since the previous snapshot in `history`.
+    pub document_count_change: Option<i64>,
+}
+
+/// What the most recent completed sync changed.
+#[derive(Debug, Clone, Serialize)]
+pub struct LastSyncStats {
+    pub sync_run_id: String,
+    pub sync_type: SyncType,
+    #[serde(with = "time::serde::iso8601::option")]
+    pub completed_at: Option<time::OffsetDateTime>,
+    pub documents_scanned: i32,
+    pub documents_processed: i32,
+    pub documents_updated: i32,
+}
+
+impl From<SyncRun> for LastSyncStats {
+    fn from(run: SyncRun) -> Self {
+        Self {
+            sync_run_id: run.id,
+            sync_type: run.sync_type,
+            completed_at: run.completed_at,
+            documents_scanned: run.documents_scanned,
+            documents_processed: run.documents_processed,
+            documents_updated: run.documents_updated,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -164,6 +462,29 @@ pub struct SdkStoreContentResponse {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SdkFailRequest {
     pub error: String,
+    /// Inferred from `error` when the connector doesn't say.
+    #[serde(default)]
+    pub category: Option<ConnectorErrorCategory>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SdkReportErrorRequest {
+    #[serde(default)]
+    pub sync_run_id: Option<String>,
+    pub category: ConnectorErrorCategory,
+    pub message: String,
+    #[serde(default)]
+    pub details: Option<JsonValue>,
+}
+
+/// Optional body of an SDK heartbeat. Connectors that send no body only
+/// refresh the sync's activity time.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SdkHeartbeatRequest {
+    #[serde(default)]
+    pub phase: Option<String>,
+    #[serde(default)]
+    pub documents_total: Option<i32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -178,6 +499,11 @@ pub struct SdkIncrementUpdatedRequest {
     pub count: i32,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SdkRateLimitsRequest {
+    pub stats: Vec<RateLimitStats>,
+}
+
 fn default_count() -> i32 {
     1
 }
@@ -198,6 +524,45 @@ pub struct SdkCreateSyncResponse {
     pub sync_run_id: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SdkAuthenticatePushKeyRequest {
+    pub key: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SdkAuthenticatePushKeyResponse {
+    pub key_id: String,
+    pub source_id: String,
+}
+
+// ============================================================================
+// SDK Sync State
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SdkSetSyncStateRequest {
+    pub value: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SdkCompareAndSwapSyncStateRequest {
+    /// Version the caller read; `None` if it expects the key not to exist.
+    pub expected_version: Option<i64>,
+    pub value: serde_json::Value,
+}
+
+// ============================================================================
+// SDK Credentials Update
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SdkUpdateCredentialsRequest {
+    pub credentials: serde_json::Value,
+    /// New access-token expiry, unix seconds.
+    #[serde(default)]
+    pub expires_at: Option<i64>,
+}
+
 // ============================================================================
 // SDK Extract Content
 // ============================================================================
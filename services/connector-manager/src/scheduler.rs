@@ -1,15 +1,22 @@
-use crate::config::ConnectorManagerConfig;
+use crate::compliance::ComplianceProcessor;
+use crate::config::{BackpressureThresholds, ConnectorManagerConfig};
 use crate::handlers::get_sync_modes_for_source;
 use crate::models::TriggerType;
 use crate::source_cleanup::SourceCleanup;
+use crate::source_export::SourceExporter;
 use crate::sync_circuit_breaker::current_unsuccessful_streak;
 use crate::sync_manager::{SyncError, SyncManager};
 use futures::FutureExt;
 use redis::Client as RedisClient;
-use shared::db::repositories::{SourceRepository, SyncRunRepository};
+use shared::db::repositories::{
+    AuditLogRepository, ConnectorErrorRepository, GroupRepository, SourceMaintenanceRepository,
+    SourceRepository, SourceStatsRepository, SyncRunRepository,
+};
 use shared::models::{Source, SyncRun, SyncSlotClass, SyncStatus, SyncType};
+use shared::service_auth::{TokenLifecycleManager, TokenRefreshSummary};
+use shared::{EmbeddingQueue, EventQueue, ObjectStorage};
 use sqlx::PgPool;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt::Display;
 use std::future::Future;
 use std::panic::AssertUnwindSafe;
@@ -21,13 +28,19 @@ use tracing::{debug, error, info, warn};
 
 const SCHEDULER_PHASE_TIMEOUT: Duration = Duration::from_secs(300);
 const SCHEDULER_RESTART_DELAY: Duration = Duration::from_secs(5);
+/// Fraction of a backpressure threshold at which scheduled syncs are
+/// throttled to one new sync per tick, before being deferred outright.
+const BACKPRESSURE_THROTTLE_FRACTION: f64 = 0.5;
 
 pub struct Scheduler {
     pool: PgPool,
     redis_client: RedisClient,
     config: ConnectorManagerConfig,
     sync_manager: Arc<SyncManager>,
+    source_exporter: SourceExporter,
+    compliance_processor: ComplianceProcessor,
     slot_health: Arc<Mutex<HashMap<SlotHealthKey, SlotHealth>>>,
+    source_stats_refreshed_at: Mutex<Option<Instant>>,
 }
 
 impl Scheduler {
@@ -36,6 +49,7 @@ impl Scheduler {
         redis_client: RedisClient,
         config: ConnectorManagerConfig,
         sync_manager: Arc<SyncManager>,
+        content_storage: Arc<dyn ObjectStorage>,
     ) {
         loop {
             let scheduler = Self::new(
@@ -43,6 +57,7 @@ impl Scheduler {
                 redis_client.clone(),
                 config.clone(),
                 sync_manager.clone(),
+                content_storage.clone(),
             );
 
             match AssertUnwindSafe(scheduler.run_internal())
@@ -66,13 +81,28 @@ impl Scheduler {
         redis_client: RedisClient,
         config: ConnectorManagerConfig,
         sync_manager: Arc<SyncManager>,
+        content_storage: Arc<dyn ObjectStorage>,
     ) -> Self {
+        let source_exporter = SourceExporter::new(
+            pool.clone(),
+            content_storage.clone(),
+            config.export_link_ttl_seconds,
+        );
+        let compliance_processor = ComplianceProcessor::new(
+            pool.clone(),
+            redis_client.clone(),
+            content_storage,
+            config.export_link_ttl_seconds,
+        );
         Self {
             pool,
             redis_client,
             config,
             sync_manager,
+            source_exporter,
+            compliance_processor,
             slot_health: Arc::new(Mutex::new(HashMap::new())),
+            source_stats_refreshed_at: Mutex::new(None),
         }
     }
 
@@ -134,6 +164,119 @@ impl Scheduler {
             Ok::<(), SchedulerError>(())
         })
         .await;
+
+        self.run_phase("process_source_exports", self.source_exporter.process())
+            .await
+            .inspect(|started| {
+                if *started > 0 {
+                    info!("Started {} source export(s)", started);
+                }
+            });
+
+        self.run_phase(
+            "process_compliance_requests",
+            self.compliance_processor.process(),
+        )
+        .await
+        .inspect(|started| {
+            if *started > 0 {
+                info!("Started {} compliance request(s)", started);
+            }
+        });
+
+        self.run_phase("refresh_source_stats", self.refresh_source_stats())
+            .await;
+
+        self.run_phase(
+            "refresh_expiring_credentials",
+            self.refresh_expiring_credentials(),
+        )
+        .await;
+
+        self.run_phase("prune_stale_groups", async {
+            GroupRepository::new(&self.pool).delete_stale().await
+        })
+        .await
+        .inspect(|deleted| {
+            if *deleted > 0 {
+                info!(
+                    "Deleted {} group(s) no longer reported by their source",
+                    deleted
+                );
+            }
+        });
+    }
+
+    /// Refresh OAuth tokens that expire within the refresh margin, so syncs
+    /// don't start with a token that lapses halfway through.
+    async fn refresh_expiring_credentials(&self) -> Result<(), SchedulerError> {
+        let summary = TokenLifecycleManager::new(self.pool.clone())
+            .with_margin(TimeDuration::seconds(
+                self.config.credential_refresh_margin_seconds,
+            ))
+            .refresh_expiring()
+            .await
+            .map_err(|e| SchedulerError::DatabaseError(e.to_string()))?;
+
+        if summary != TokenRefreshSummary::default() {
+            info!(
+                "Refreshed {} OAuth token(s); {} need re-authorization, {} failed",
+                summary.refreshed, summary.reauthorization_required, summary.failed
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Rewrite today's per-source stats snapshot, and drop connector errors
+    /// and audit log entries past their retention, once the refresh interval
+    /// has passed since the last refresh.
+    async fn refresh_source_stats(&self) -> Result<(), SchedulerError> {
+        let refresh_interval =
+            Duration::from_secs(self.config.source_stats_refresh_interval_seconds);
+        {
+            let refreshed_at = self
+                .source_stats_refreshed_at
+                .lock()
+                .expect("source stats lock poisoned");
+            if refreshed_at.is_some_and(|at| at.elapsed() < refresh_interval) {
+                return Ok(());
+            }
+        }
+
+        let rows = SourceStatsRepository::new(&self.pool)
+            .refresh_daily_stats(self.config.source_stats_retention_days)
+            .await
+            .map_err(|e| SchedulerError::DatabaseError(e.to_string()))?;
+        *self
+            .source_stats_refreshed_at
+            .lock()
+            .expect("source stats lock poisoned") = Some(Instant::now());
+        debug!("Refreshed daily stats for {} source(s)", rows);
+
+        let pruned = ConnectorErrorRepository::new(&self.pool)
+            .delete_older_than(
+                OffsetDateTime::now_utc()
+                    - TimeDuration::days(self.config.connector_error_retention_days),
+            )
+            .await
+            .map_err(|e| SchedulerError::DatabaseError(e.to_string()))?;
+        if pruned > 0 {
+            debug!("Deleted {} expired connector error(s)", pruned);
+        }
+
+        let pruned = AuditLogRepository::new(&self.pool)
+            .delete_older_than(
+                OffsetDateTime::now_utc()
+                    - TimeDuration::days(self.config.audit_log_retention_days),
+            )
+            .await
+            .map_err(|e| SchedulerError::DatabaseError(e.to_string()))?;
+        if pruned > 0 {
+            debug!("Deleted {} expired audit log entries", pruned);
+        }
+
+        Ok(())
     }
 
     async fn run_phase<T, E, F>(&self, phase: &'static str, future: F) -> Option<T>
@@ -183,12 +326,7 @@ impl Scheduler {
     /// if one isn't already in flight, otherwise no-op. Runs in parallel with
     /// scheduled Full / Incremental syncs because they occupy a separate slot.
     async fn ensure_realtime_running(&self) -> Result<(), SchedulerError> {
-        let source_repo = SourceRepository::new(&self.pool);
-
-        let active_sources = source_repo
-            .find_active_sources()
-            .await
-            .map_err(|e| SchedulerError::DatabaseError(e.to_string()))?;
+        let active_sources = self.schedulable_sources().await?;
         let now = OffsetDateTime::now_utc();
 
         for source in active_sources {
@@ -327,15 +465,37 @@ impl Scheduler {
         true
     }
 
+    /// Active sources, minus those an admin has put in maintenance mode.
+    async fn schedulable_sources(&self) -> Result<Vec<Source>, SchedulerError> {
+        let sources = SourceRepository::new(&self.pool)
+            .find_active_sources()
+            .await
+            .map_err(|e| SchedulerError::DatabaseError(e.to_string()))?;
+        let in_maintenance: HashSet<String> = SourceMaintenanceRepository::new(&self.pool)
+            .list()
+            .await
+            .map_err(|e| SchedulerError::DatabaseError(e.to_string()))?
+            .into_iter()
+            .map(|m| m.source_id)
+            .collect();
+
+        Ok(sources
+            .into_iter()
+            .filter(|source| {
+                let skip = in_maintenance.contains(&source.id);
+                if skip {
+                    debug!("Source {} is in maintenance mode, skipping", source.id);
+                }
+                !skip
+            })
+            .collect())
+    }
+
     async fn process_due_sources(&self) -> Result<(), SchedulerError> {
         let now = OffsetDateTime::now_utc();
-        let source_repo = SourceRepository::new(&self.pool);
         let sync_run_repo = SyncRunRepository::new(&self.pool);
 
-        let sources = source_repo
-            .find_active_sources()
-            .await
-            .map_err(|e| SchedulerError::DatabaseError(e.to_string()))?;
+        let sources = self.schedulable_sources().await?;
         let source_ids: Vec<String> = sources.iter().map(|source| source.id.clone()).collect();
         let recent_run_limit = self
             .config
@@ -366,11 +526,47 @@ impl Scheduler {
 
         info!("Found {} sources due for sync", due_sources.len());
 
+        let queue_depths = self.queue_depths().await;
+        let mut throttled_started = false;
+
         for source in due_sources {
             if self.slot_backoff_active(&source, SyncSlotClass::Scheduled, now) {
                 continue;
             }
 
+            let pressure = queue_depths
+                .as_ref()
+                .map(|depths| {
+                    backpressure_for(
+                        depths,
+                        self.config.backpressure.thresholds_for(source.source_type),
+                    )
+                })
+                .unwrap_or(Backpressure::Clear);
+            match pressure {
+                Backpressure::Clear => {}
+                Backpressure::Throttle(queue) if !throttled_started => {
+                    debug!(
+                        "Throttling scheduled syncs: {} has {} pending (limit {}); allowing source {}",
+                        queue.name, queue.pending, queue.limit, source.id
+                    );
+                }
+                Backpressure::Throttle(queue) => {
+                    info!(
+                        "Deferring scheduled sync for source {} ({:?}): {} has {} pending (limit {}) and a throttled sync already started this tick",
+                        source.id, source.source_type, queue.name, queue.pending, queue.limit
+                    );
+                    continue;
+                }
+                Backpressure::Defer(queue) => {
+                    info!(
+                        "Deferring scheduled sync for source {} ({:?}): {} has {} pending (limit {})",
+                        source.id, source.source_type, queue.name, queue.pending, queue.limit
+                    );
+                    continue;
+                }
+            }
+
             if self
                 .sync_manager
                 .is_sync_class_running(&source.id, SyncSlotClass::Scheduled)
@@ -395,6 +591,7 @@ impl Scheduler {
             {
                 Ok(sync_run_id) => {
                     self.mark_slot_healthy(&source.id, SyncSlotClass::Scheduled);
+                    throttled_started |= matches!(pressure, Backpressure::Throttle(_));
                     info!(
                         "Scheduled sync {} triggered for source {} ({:?})",
                         sync_run_id, source.name, source.source_type
@@ -420,6 +617,82 @@ impl Scheduler {
 
         Ok(())
     }
+
+    /// Pending items in the indexing queues, or `None` if they couldn't be
+    /// read, in which case scheduling goes ahead without backpressure.
+    async fn queue_depths(&self) -> Option<QueueDepths> {
+        let events = EventQueue::new(self.pool.clone()).get_queue_stats().await;
+        let embeddings = EmbeddingQueue::new(self.pool.clone())
+            .get_queue_stats()
+            .await;
+
+        match (events, embeddings) {
+            (Ok(events), Ok(embeddings)) => Some(QueueDepths {
+                pending_events: events.pending,
+                pending_embeddings: embeddings.pending,
+            }),
+            (Err(e), _) | (_, Err(e)) => {
+                warn!(
+                    "Failed to read queue depths, skipping backpressure checks: {}",
+                    e
+                );
+                None
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct QueueDepths {
+    pending_events: i64,
+    pending_embeddings: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct QueuePressure {
+    name: &'static str,
+    pending: i64,
+    limit: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Backpressure {
+    Clear,
+    /// Start at most one more scheduled sync this tick.
+    Throttle(QueuePressure),
+    /// Don't start scheduled syncs until the queue drains.
+    Defer(QueuePressure),
+}
+
+/// How much the most loaded indexing queue should hold back scheduled syncs
+/// of a source with these thresholds.
+fn backpressure_for(depths: &QueueDepths, thresholds: BackpressureThresholds) -> Backpressure {
+    let queues = [
+        QueuePressure {
+            name: "connector_events_queue",
+            pending: depths.pending_events,
+            limit: thresholds.max_pending_events,
+        },
+        QueuePressure {
+            name: "embedding_queue",
+            pending: depths.pending_embeddings,
+            limit: thresholds.max_pending_embeddings,
+        },
+    ];
+
+    let fullest = queues
+        .into_iter()
+        .filter(|queue| queue.limit > 0)
+        .map(|queue| (queue.pending as f64 / queue.limit as f64, queue))
+        .max_by(|(a, _), (b, _)| a.total_cmp(b));
+
+    match fullest {
+        Some((load, queue)) if load >= 1.0 => Backpressure::Defer(queue),
+        Some((load, queue)) if load >= BACKPRESSURE_THROTTLE_FRACTION => {
+            Backpressure::Throttle(queue)
+        }
+        _ => Backpressure::Clear,
+    }
 }
 
 fn sources_due_for_sync(
@@ -622,6 +895,7 @@ struct SlotHealth {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::BackpressureConfig;
     use serde_json::json;
     use shared::models::{SourceScope, SourceType, UserFilterMode};
 
@@ -644,6 +918,7 @@ mod tests {
             created_at: now,
             updated_at: now,
             created_by: "01JGF7V3E0Y2R1X8P5Q7W9T4N6".to_string(),
+            workspace_id: shared::models::DEFAULT_WORKSPACE_ID.to_string(),
         }
     }
 
@@ -740,6 +1015,70 @@ mod tests {
         assert!(active_backoff(&health, now, 30, 3600).is_none());
     }
 
+    fn thresholds(max_pending_events: i64, max_pending_embeddings: i64) -> BackpressureThresholds {
+        BackpressureThresholds {
+            max_pending_events,
+            max_pending_embeddings,
+        }
+    }
+
+    #[test]
+    fn backpressure_clear_below_throttle_fraction() {
+        let depths = QueueDepths {
+            pending_events: 400,
+            pending_embeddings: 100,
+        };
+        assert_eq!(
+            backpressure_for(&depths, thresholds(1000, 1000)),
+            Backpressure::Clear
+        );
+    }
+
+    #[test]
+    fn backpressure_reports_the_fullest_queue() {
+        let depths = QueueDepths {
+            pending_events: 600,
+            pending_embeddings: 1500,
+        };
+        assert_eq!(
+            backpressure_for(&depths, thresholds(1000, 1000)),
+            Backpressure::Defer(QueuePressure {
+                name: "embedding_queue",
+                pending: 1500,
+                limit: 1000,
+            })
+        );
+        assert_eq!(
+            backpressure_for(&depths, thresholds(1000, 0)),
+            Backpressure::Throttle(QueuePressure {
+                name: "connector_events_queue",
+                pending: 600,
+                limit: 1000,
+            })
+        );
+    }
+
+    #[test]
+    fn backpressure_uses_per_source_type_thresholds() {
+        let mut config = BackpressureConfig::default();
+        config
+            .max_pending_events_by_source_type
+            .insert(SourceType::Slack, 100);
+        let depths = QueueDepths {
+            pending_events: 150,
+            pending_embeddings: 0,
+        };
+
+        assert!(matches!(
+            backpressure_for(&depths, config.thresholds_for(SourceType::Slack)),
+            Backpressure::Defer(_)
+        ));
+        assert_eq!(
+            backpressure_for(&depths, config.thresholds_for(SourceType::LocalFiles)),
+            Backpressure::Clear
+        );
+    }
+
     #[test]
     fn due_sources_include_elapsed_successes() {
         let now = OffsetDateTime::now_utc();
@@ -0,0 +1,359 @@
+//! Per-source export of stored documents as a gzipped tar of Markdown files.
+//!
+//! Each document becomes one Markdown file whose front matter mirrors the
+//! stored document row. Documents are read in batches and the compressed
+//! archive is written to object storage in fixed-size parts, so memory use is
+//! bounded regardless of source size. The scheduler claims pending exports and
+//! runs them in the background; once complete, the archive is downloadable
+//! with a secret token until the link expires, after which the parts are
+//! deleted.
+
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use rand::RngCore;
+use serde_json::Value as JsonValue;
+use shared::db::repositories::{SourceExport, SourceExportRepository};
+use shared::models::Document;
+use shared::{ObjectStorage, Repository, SourceRepository};
+use sqlx::PgPool;
+use std::sync::Arc;
+use time::format_description::well_known::Iso8601;
+use time::{Duration as TimeDuration, OffsetDateTime};
+use tracing::{error, info, warn};
+
+pub const ARCHIVE_CONTENT_TYPE: &str = "application/gzip";
+
+const EXPORT_BATCH_SIZE: i64 = 100;
+/// Compressed bytes buffered before a part is written to storage.
+const ARCHIVE_PART_SIZE: usize = 16 * 1024 * 1024;
+const MAX_RUNNING_EXPORTS: i64 = 2;
+/// Running exports without a heartbeat for this long are considered orphaned.
+const EXPORT_HEARTBEAT_TIMEOUT_SECONDS: i64 = 600;
+const STORAGE_PREFIX: &str = "exports";
+const MAX_FILE_STEM_CHARS: usize = 80;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ExportError {
+    #[error("Source not found: {0}")]
+    SourceNotFound(String),
+
+    #[error("Database error: {0}")]
+    Database(String),
+
+    #[error("Storage error: {0}")]
+    Storage(String),
+
+    #[error("Archive error: {0}")]
+    Archive(#[from] std::io::Error),
+}
+
+impl From<shared::DatabaseError> for ExportError {
+    fn from(e: shared::DatabaseError) -> Self {
+        ExportError::Database(e.to_string())
+    }
+}
+
+impl From<sqlx::Error> for ExportError {
+    fn from(e: sqlx::Error) -> Self {
+        ExportError::Database(e.to_string())
+    }
+}
+
+impl From<shared::storage::StorageError> for ExportError {
+    fn from(e: shared::storage::StorageError) -> Self {
+        ExportError::Storage(e.to_string())
+    }
+}
+
+#[derive(Clone)]
+pub struct SourceExporter {
+    pool: PgPool,
+    storage: Arc<dyn ObjectStorage>,
+    link_ttl: TimeDuration,
+}
+
+impl SourceExporter {
+    pub fn new(pool: PgPool, storage: Arc<dyn ObjectStorage>, link_ttl_seconds: i64) -> Self {
+        Self {
+            pool,
+            storage,
+            link_ttl: TimeDuration::seconds(link_ttl_seconds),
+        }
+    }
+
+    /// Expire lapsed archives, fail orphaned exports and start pending ones
+    /// in the background. Returns the number of exports started.
+    pub async fn process(&self) -> Result<usize, ExportError> {
+        let repo = SourceExportRepository::new(&self.pool);
+
+        let expired_parts = repo.expire_completed().await?;
+        if !expired_parts.is_empty() {
+            info!("Deleting {} expired export part(s)", expired_parts.len());
+            self.delete_parts(&expired_parts).await;
+        }
+
+        let orphaned_parts = repo.fail_orphaned(EXPORT_HEARTBEAT_TIMEOUT_SECONDS).await?;
+        self.delete_parts(&orphaned_parts).await;
+
+        let capacity = MAX_RUNNING_EXPORTS - repo.count_running().await?;
+        if capacity <= 0 {
+            return Ok(0);
+        }
+
+        let claimed = repo.claim_pending(capacity).await?;
+        let started = claimed.len();
+        for export in claimed {
+            let exporter = self.clone();
+            tokio::spawn(async move { exporter.run(export).await });
+        }
+
+        Ok(started)
+    }
+
+    /// Run a claimed export to completion, recording failure on error.
+    pub async fn run(&self, export: SourceExport) {
+        info!(
+            "Starting export {} for source {}",
+            export.id, export.source_id
+        );
+        let mut parts = Vec::new();
+        match self.write_archive(&export, &mut parts).await {
+            Ok(()) => info!("Export {} completed", export.id),
+            Err(e) => {
+                error!("Export {} failed: {}", export.id, e);
+                let repo = SourceExportRepository::new(&self.pool);
+                if let Err(e) = repo.fail(&export.id, &e.to_string()).await {
+                    error!("Failed to mark export {} as failed: {}", export.id, e);
+                }
+                self.delete_parts(&parts).await;
+            }
+        }
+    }
+
+    async fn write_archive(
+        &self,
+        export: &SourceExport,
+        parts: &mut Vec<String>,
+    ) -> Result<(), ExportError> {
+        let repo = SourceExportRepository::new(&self.pool);
+        let source = SourceRepository::new(&self.pool)
+            .find_by_id(export.source_id.clone())
+            .await?
+            .ok_or_else(|| ExportError::SourceNotFound(export.source_id.clone()))?;
+        let root = file_stem(&source.name, &source.id);
+
+        let mut archive = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+        let mut document_count = 0i32;
+        let mut size_bytes = 0i64;
+        let mut after_id = String::new();
+
+        loop {
+            let documents = sqlx::query_as::<_, Document>(
+                r#"
+                SELECT id, source_id, external_id, title, content_id, content_type,
+                       file_size, file_extension, url,
+                       metadata, permissions, attributes, created_at, updated_at, last_indexed_at
+                FROM documents
+                WHERE source_id = $1 AND id > $2
+                ORDER BY id
+                LIMIT $3
+                "#,
+            )
+            .bind(&export.source_id)
+            .bind(&after_id)
+            .bind(EXPORT_BATCH_SIZE)
+            .fetch_all(&self.pool)
+            .await?;
+            let Some(last) = documents.last() else {
+                break;
+            };
+            after_id = last.id.clone();
+
+            let content_ids: Vec<String> = documents
+                .iter()
+                .filter_map(|d| d.content_id.clone())
+                .collect();
+            let contents = self.storage.batch_get_text(content_ids).await?;
+
+            for document in &documents {
+                let content = document
+                    .content_id
+                    .as_ref()
+                    .and_then(|id| contents.get(id))
+                    .map(String::as_str)
+                    .unwrap_or_default();
+                let markdown = render_markdown(document, content);
+                let mut header = tar::Header::new_gnu();
+                header.set_size(markdown.len() as u64);
+                header.set_mode(0o644);
+                header.set_mtime(document.updated_at.unix_timestamp().max(0) as u64);
+                let path = format!("{}/{}.md", root, file_stem(&document.title, &document.id));
+                archive.append_data(&mut header, path, markdown.as_bytes())?;
+                document_count += 1;
+            }
+
+            let buffered = archive.get_mut().get_mut();
+            if buffered.len() >= ARCHIVE_PART_SIZE {
+                let part = std::mem::take(buffered);
+                size_bytes += part.len() as i64;
+                parts.push(self.store_part(&part).await?);
+            }
+            repo.record_progress(&export.id, document_count, size_bytes, parts.as_slice())
+                .await?;
+        }
+
+        let last_part = archive.into_inner()?.finish()?;
+        if !last_part.is_empty() {
+            size_bytes += last_part.len() as i64;
+            parts.push(self.store_part(&last_part).await?);
+        }
+
+        let expires_at = OffsetDateTime::now_utc() + self.link_ttl;
+        repo.complete(
+            &export.id,
+            document_count,
+            size_bytes,
+            parts.as_slice(),
+            &generate_download_token(),
+            expires_at,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn store_part(&self, bytes: &[u8]) -> Result<String, ExportError> {
+        Ok(self
+            .storage
+            .store_content_with_type(bytes, Some(ARCHIVE_CONTENT_TYPE), Some(STORAGE_PREFIX))
+            .await?)
+    }
+
+    async fn delete_parts(&self, parts: &[String]) {
+        for part in parts {
+            if let Err(e) = self.storage.delete_content(part).await {
+                warn!("Failed to delete export part {}: {}", part, e);
+            }
+        }
+    }
+}
+
+/// Render a document as Markdown with YAML front matter. Values are written
+/// as JSON, which is valid YAML and keeps arbitrary strings safely quoted.
+pub fn render_markdown(document: &Document, content: &str) -> String {
+    let timestamp = |t: OffsetDateTime| JsonValue::from(t.format(&Iso8601::DEFAULT).ok());
+    let fields = [
+        ("id", JsonValue::from(document.id.as_str())),
+        ("source_id", JsonValue::from(document.source_id.as_str())),
+        (
+            "external_id",
+            JsonValue::from(document.external_id.as_str()),
+        ),
+        ("title", JsonValue::from(document.title.as_str())),
+        ("url", JsonValue::from(document.url.clone())),
+        (
+            "content_type",
+            JsonValue::from(document.content_type.clone()),
+        ),
+        ("file_size", JsonValue::from(document.file_size)),
+        (
+            "file_extension",
+            JsonValue::from(document.file_extension.clone()),
+        ),
+        ("created_at", timestamp(document.created_at)),
+        ("updated_at", timestamp(document.updated_at)),
+        ("last_indexed_at", timestamp(document.last_indexed_at)),
+        ("metadata", document.metadata.clone()),
+        ("attributes", document.attributes.clone()),
+        ("permissions", document.permissions.clone()),
+    ];
+
+    let mut markdown = String::from("---\n");
+    for (key, value) in fields {
+        markdown.push_str(key);
+        markdown.push_str(": ");
+        markdown.push_str(&value.to_string());
+        markdown.push('\n');
+    }
+    markdown.push_str("---\n\n");
+    markdown.push_str(content);
+    if !content.ends_with('\n') {
+        markdown.push('\n');
+    }
+    markdown
+}
+
+/// A filesystem-safe file name from `name`, suffixed with `id` so names never
+/// collide.
+pub(crate) fn file_stem(name: &str, id: &str) -> String {
+    let mut stem = String::new();
+    for c in name.chars() {
+        if stem.chars().count() >= MAX_FILE_STEM_CHARS {
+            break;
+        }
+        if c.is_alphanumeric() {
+            stem.extend(c.to_lowercase());
+        } else if !stem.is_empty() && !stem.ends_with('-') {
+            stem.push('-');
+        }
+    }
+    let stem = stem.trim_end_matches('-');
+    if stem.is_empty() {
+        id.to_lowercase()
+    } else {
+        format!("{}-{}", stem, id.to_lowercase())
+    }
+}
+
+pub(crate) fn generate_download_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_file_stem() {
+        assert_eq!(
+            file_stem("Q3 Planning: Notes/Draft", "01ABC"),
+            "q3-planning-notes-draft-01abc"
+        );
+        assert_eq!(file_stem("???", "01ABC"), "01abc");
+    }
+
+    #[test]
+    fn test_render_markdown_front_matter() {
+        let now = OffsetDateTime::now_utc();
+        let document = Document {
+            id: "01JDOC".to_string(),
+            source_id: "01JSRC".to_string(),
+            external_id: "ext-1".to_string(),
+            title: "Title with \"quotes\"\nand newline".to_string(),
+            content_id: None,
+            content_type: Some("text/markdown".to_string()),
+            file_size: None,
+            file_extension: None,
+            url: None,
+            metadata: json!({"author": "alice"}),
+            permissions: json!({"public": true}),
+            attributes: json!({}),
+            created_at: now,
+            updated_at: now,
+            last_indexed_at: now,
+        };
+
+        let markdown = render_markdown(&document, "Body");
+        let (front_matter, body) = markdown
+            .strip_prefix("---\n")
+            .and_then(|rest| rest.split_once("---\n\n"))
+            .unwrap();
+        assert_eq!(body, "Body\n");
+        assert!(front_matter.contains("title: \"Title with \\\"quotes\\\"\\nand newline\"\n"));
+        assert!(front_matter.contains("metadata: {\"author\":\"alice\"}\n"));
+        assert!(front_matter.contains("url: null\n"));
+    }
+}
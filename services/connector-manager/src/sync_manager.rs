@@ -5,7 +5,7 @@ use crate::models::{SyncRequest, TriggerType};
 use dashmap::DashMap;
 use redis::Client as RedisClient;
 use shared::db::error::DatabaseError;
-use shared::db::repositories::SyncRunRepository;
+use shared::db::repositories::{SourceMaintenanceRepository, SyncRunRepository};
 use shared::models::{SourceType, SyncSlotClass, SyncStatus, SyncType};
 use shared::{DatabasePool, Repository, SourceRepository};
 use sqlx::PgPool;
@@ -14,12 +14,16 @@ use std::sync::Arc;
 use std::time::Duration;
 use time::format_description::well_known::Rfc3339;
 use time::OffsetDateTime;
+use tokio::sync::broadcast;
 use tokio::time::timeout;
 use tracing::{debug, error, info, warn};
 
 const MAX_RESUME_ATTEMPTS: usize = 3;
 const MISSING_MANIFEST_GRACE_OBSERVATIONS: usize = 2;
 const CONNECTOR_TRIGGER_TIMEOUT: Duration = Duration::from_secs(150);
+/// Progress notifications buffered per stream; a stream that falls further
+/// behind just re-reads the sync run.
+const PROGRESS_CHANNEL_CAPACITY: usize = 1024;
 
 #[derive(Clone)]
 pub struct SyncManager {
@@ -36,6 +40,8 @@ pub struct SyncManager {
     /// connector manifest. A short grace window prevents a transient heartbeat
     /// miss from immediately being counted as a lost sync.
     missing_manifest_observations: Arc<DashMap<String, usize>>,
+    /// Ids of sync runs whose progress just changed, for the progress stream.
+    progress_tx: broadcast::Sender<String>,
 }
 
 impl SyncManager {
@@ -52,9 +58,20 @@ impl SyncManager {
             sync_run_repo: SyncRunRepository::new(db_pool.pool()),
             resume_attempts: Arc::new(DashMap::new()),
             missing_manifest_observations: Arc::new(DashMap::new()),
+            progress_tx: broadcast::channel(PROGRESS_CHANNEL_CAPACITY).0,
         }
     }
 
+    /// Wake progress streams watching `sync_run_id`.
+    pub fn notify_progress(&self, sync_run_id: &str) {
+        // Fails only when nobody is watching
+        let _ = self.progress_tx.send(sync_run_id.to_string());
+    }
+
+    pub fn subscribe_progress(&self) -> broadcast::Receiver<String> {
+        self.progress_tx.subscribe()
+    }
+
     pub async fn trigger_sync(
         &self,
         source_id: &str,
@@ -91,6 +108,17 @@ impl SyncManager {
             return Err(SyncError::SourceInactive(source_id.to_string()));
         }
 
+        if let Some(maintenance) = SourceMaintenanceRepository::new(&self.pool)
+            .get(source_id)
+            .await
+            .map_err(|e| SyncError::DatabaseError(e.to_string()))?
+        {
+            return Err(SyncError::SourceInMaintenance {
+                source_id: source_id.to_string(),
+                reason: maintenance.reason,
+            });
+        }
+
         // Get connector URL from registry
         let connector_url = get_connector_url_for_source(&self.redis_client, source.source_type)
             .await
@@ -704,6 +732,12 @@ pub enum SyncError {
     #[error("Source is inactive: {0}")]
     SourceInactive(String),
 
+    #[error("Source is in maintenance mode: {source_id}")]
+    SourceInMaintenance {
+        source_id: String,
+        reason: Option<String>,
+    },
+
     #[error("Sync already running for source: {0}")]
     SyncAlreadyRunning(String),
 
@@ -1,7 +1,4 @@
-pub mod mock_connector;
-
 use anyhow::Result;
-use mock_connector::MockConnector;
 use omni_connector_manager::{
     config::ConnectorManagerConfig, create_app, sync_manager::SyncManager, AppState,
 };
@@ -9,6 +6,7 @@ use redis::{AsyncCommands, Client as RedisClient};
 use shared::models::{ConnectorManifest, SourceType, SyncType};
 use shared::storage::postgres::PostgresStorage;
 use shared::test_environment::TestEnvironment;
+use shared::test_utils::mock_connector::MockConnector;
 use shared::ObjectStorage;
 use std::sync::Arc;
 use tokio::sync::Semaphore;
@@ -50,6 +48,13 @@ pub async fn setup_test_fixture() -> Result<TestFixture> {
         sync_backoff_base_seconds: 30,
         sync_backoff_max_seconds: 3600,
         sync_max_consecutive_failures: 10,
+        export_link_ttl_seconds: 86400,
+        backpressure: Default::default(),
+        source_stats_refresh_interval_seconds: 3600,
+        source_stats_retention_days: 365,
+        connector_error_retention_days: 30,
+        audit_log_retention_days: 365,
+        credential_refresh_margin_seconds: 900,
     };
 
     let redis_client = RedisClient::open(config.redis.redis_url.clone())?;
@@ -109,3 +114,14 @@ pub async fn setup_test_fixture() -> Result<TestFixture> {
         test_env,
     })
 }
+
+/// Serve the manager app on an ephemeral local port so the mock connector can
+/// make SDK callbacks to it. Returns the base URL.
+pub async fn serve_manager(app: axum::Router) -> Result<String> {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    tokio::spawn(async move {
+        let _ = axum::serve(listener, app).await;
+    });
+    Ok(format!("http://{}", addr))
+}
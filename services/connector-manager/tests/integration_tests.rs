@@ -4,11 +4,17 @@ use axum::http::StatusCode;
 use axum_test::{TestServer, TestServerConfig};
 use common::TEST_SOURCE_ID;
 use omni_connector_manager::source_cleanup::SourceCleanup;
+use omni_connector_manager::source_export::SourceExporter;
 use redis::AsyncCommands;
 use serde_json::json;
-use shared::db::repositories::SyncRunRepository;
-use shared::models::{ConnectorEvent, DocumentMetadata, DocumentPermissions, SyncStatus, SyncType};
+use shared::audit::ACTOR_ID_HEADER;
+use shared::db::repositories::{SourceExportRepository, SourceStatsRepository, SyncRunRepository};
+use shared::models::{
+    ConnectorEvent, DEFAULT_WORKSPACE_ID, DocumentMetadata, DocumentPermissions, SyncStatus,
+    SyncType,
+};
 use shared::queue::EventQueue;
+use shared::test_utils::mock_connector::SyncBehavior;
 
 fn test_server(fixture: &common::TestFixture) -> TestServer {
     let config = TestServerConfig::builder()
@@ -237,6 +243,50 @@ async fn test_sync_trigger_guards() {
     assert_eq!(requests.len(), 1);
 }
 
+#[tokio::test]
+async fn test_source_maintenance_blocks_syncs() {
+    let fixture = common::setup_test_fixture().await.unwrap();
+    let server = test_server_no_expect(&fixture);
+    let maintenance_path = format!("/sources/{}/maintenance", TEST_SOURCE_ID);
+
+    let resp = server
+        .put(&maintenance_path)
+        .json(&json!({"search_visibility": "hidden", "reason": "Rotating credentials"}))
+        .await;
+    resp.assert_status(StatusCode::OK);
+    let body: serde_json::Value = resp.json();
+    assert_eq!(body["search_visibility"], "hidden");
+
+    // Manual trigger → 409 with the reason
+    let resp = server
+        .post("/sync")
+        .json(&json!({"source_id": TEST_SOURCE_ID}))
+        .await;
+    resp.assert_status(StatusCode::CONFLICT);
+    let body: serde_json::Value = resp.json();
+    let error = body["error"].as_str().unwrap();
+    assert!(error.contains("maintenance mode"));
+    assert!(error.contains("Rotating credentials"));
+
+    let resp = server.get(&format!("/sources/{}", TEST_SOURCE_ID)).await;
+    resp.assert_status(StatusCode::OK);
+    let body: serde_json::Value = resp.json();
+    assert_eq!(body["maintenance"]["reason"], "Rotating credentials");
+
+    server
+        .delete(&maintenance_path)
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    server
+        .delete(&maintenance_path)
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+
+    // Back to normal
+    trigger_sync(&server).await;
+    assert_eq!(fixture.mock_connector.get_sync_requests().len(), 1);
+}
+
 // ============================================================================
 // 3. test_sync_connector_failure — connector /sync returns 500
 // ============================================================================
@@ -1041,3 +1091,797 @@ async fn test_incremental_after_failed_sync_starts_from_previous_successful_chec
         Some("successful")
     );
 }
+
+// ============================================================================
+// Scripted mock connector behaviors
+// ============================================================================
+
+async fn wait_for_terminal_run(
+    sync_run_repo: &SyncRunRepository,
+    sync_run_id: &str,
+) -> shared::models::SyncRun {
+    for _ in 0..100 {
+        let run = sync_run_repo
+            .find_by_id(sync_run_id)
+            .await
+            .unwrap()
+            .unwrap();
+        if run.status != SyncStatus::Running {
+            return run;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    }
+    panic!("sync run {} did not finish in time", sync_run_id);
+}
+
+#[tokio::test]
+async fn test_mock_connector_scripted_sync_completes() {
+    let fixture = common::setup_test_fixture().await.unwrap();
+    let server = test_server(&fixture);
+    let pool = fixture.state.db_pool.pool();
+    let sync_run_repo = SyncRunRepository::new(pool);
+
+    let manager_url = common::serve_manager(fixture.app.clone()).await.unwrap();
+    fixture.mock_connector.set_manager_url(manager_url);
+    fixture.mock_connector.set_behavior(SyncBehavior::Huge {
+        documents: 5,
+        content_bytes: 4096,
+        batch_size: 2,
+    });
+
+    let sync_run_id = trigger_sync(&server).await;
+    let run = wait_for_terminal_run(&sync_run_repo, &sync_run_id).await;
+    assert_eq!(run.status, SyncStatus::Completed);
+    assert!(!fixture.mock_connector.is_sync_active(TEST_SOURCE_ID));
+
+    let stats = EventQueue::new(pool.clone())
+        .get_queue_stats()
+        .await
+        .unwrap();
+    assert_eq!(stats.pending, 5);
+}
+
+#[tokio::test]
+async fn test_mock_connector_scripted_failure_and_flaky_trigger() {
+    let fixture = common::setup_test_fixture().await.unwrap();
+    let server = test_server(&fixture);
+    let sync_run_repo = SyncRunRepository::new(fixture.state.db_pool.pool());
+
+    let manager_url = common::serve_manager(fixture.app.clone()).await.unwrap();
+    fixture.mock_connector.set_manager_url(manager_url);
+    fixture.mock_connector.set_behavior(SyncBehavior::Fail {
+        message: "mock exploded".to_string(),
+        after: std::time::Duration::from_millis(50),
+    });
+    fixture.mock_connector.fail_next_triggers(1);
+
+    // The flaky first trigger fails the run up front; a manual retry goes through.
+    test_server_no_expect(&fixture)
+        .post("/sync")
+        .json(&json!({"source_id": TEST_SOURCE_ID}))
+        .await
+        .assert_status(StatusCode::INTERNAL_SERVER_ERROR);
+    assert!(fixture.mock_connector.get_sync_requests().is_empty());
+
+    let sync_run_id = trigger_sync(&server).await;
+    let run = wait_for_terminal_run(&sync_run_repo, &sync_run_id).await;
+    assert_eq!(run.status, SyncStatus::Failed);
+    assert_eq!(run.error_message.as_deref(), Some("mock exploded"));
+    assert_eq!(fixture.mock_connector.get_sync_requests().len(), 1);
+}
+
+#[tokio::test]
+async fn test_source_export_archive_and_download_link() {
+    use std::io::Read;
+
+    let fixture = common::setup_test_fixture().await.unwrap();
+    let server = test_server_no_expect(&fixture);
+    let pool = fixture.state.db_pool.pool();
+    let storage = fixture.state.content_storage.clone();
+
+    let content_id = storage
+        .store_text("Quarterly roadmap body", None)
+        .await
+        .unwrap();
+    sqlx::query(
+        r#"
+        INSERT INTO documents (id, source_id, external_id, title, content_id, content_type,
+                               metadata, permissions, attributes)
+        VALUES ($1, $2, 'ext-1', 'Q3 Roadmap', $3, 'text/plain',
+                '{"author": "alice"}', '{"public": true}', '{}')
+        "#,
+    )
+    .bind(shared::utils::generate_ulid())
+    .bind(TEST_SOURCE_ID)
+    .bind(&content_id)
+    .execute(pool)
+    .await
+    .unwrap();
+
+    let exports_path = format!("/sources/{}/exports", TEST_SOURCE_ID);
+    let resp = server.post(&exports_path).json(&json!({})).await;
+    resp.assert_status(StatusCode::ACCEPTED);
+    let body: serde_json::Value = resp.json();
+    assert_eq!(body["status"], "pending");
+    assert!(body["download_url"].is_null());
+    let export_id = body["id"].as_str().unwrap().to_string();
+
+    // One export per source at a time
+    server
+        .post(&exports_path)
+        .json(&json!({}))
+        .await
+        .assert_status(StatusCode::CONFLICT);
+
+    let exporter = SourceExporter::new(pool.clone(), storage.clone(), 3600);
+    let export_repo = SourceExportRepository::new(pool);
+    let claimed = export_repo.claim_pending(1).await.unwrap();
+    assert_eq!(claimed.len(), 1);
+    exporter.run(claimed.into_iter().next().unwrap()).await;
+
+    let resp = server.get(&format!("/exports/{}", export_id)).await;
+    resp.assert_status(StatusCode::OK);
+    let body: serde_json::Value = resp.json();
+    assert_eq!(body["status"], "completed");
+    assert_eq!(body["document_count"], 1);
+    let download_url = body["download_url"].as_str().unwrap().to_string();
+
+    server
+        .get(&format!("/exports/{}/download?token=wrong", export_id))
+        .await
+        .assert_status(StatusCode::FORBIDDEN);
+
+    let resp = server.get(&download_url).await;
+    resp.assert_status(StatusCode::OK);
+    let archive_bytes = resp.as_bytes().to_vec();
+    let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(archive_bytes.as_slice()));
+    let mut files = Vec::new();
+    for entry in archive.entries().unwrap() {
+        let mut entry = entry.unwrap();
+        let path = entry.path().unwrap().to_string_lossy().to_string();
+        let mut markdown = String::new();
+        entry.read_to_string(&mut markdown).unwrap();
+        files.push((path, markdown));
+    }
+    assert_eq!(files.len(), 1);
+    let (path, markdown) = &files[0];
+    assert!(path.ends_with(".md") && path.contains("/q3-roadmap-"));
+    assert!(markdown.starts_with("---\n"));
+    assert!(markdown.contains("title: \"Q3 Roadmap\"\n"));
+    assert!(markdown.contains("metadata: {\"author\":\"alice\"}\n"));
+    assert!(markdown.ends_with("Quarterly roadmap body\n"));
+
+    // Once the link lapses the archive is deleted and downloads are refused
+    sqlx::query("UPDATE source_exports SET expires_at = NOW() - INTERVAL '1 minute' WHERE id = $1")
+        .bind(&export_id)
+        .execute(pool)
+        .await
+        .unwrap();
+    server
+        .get(&download_url)
+        .await
+        .assert_status(StatusCode::GONE);
+    exporter.process().await.unwrap();
+    let export = export_repo.get(&export_id).await.unwrap().unwrap();
+    assert!(export.part_content_ids.is_empty());
+    let resp = server.get(&format!("/exports/{}", export_id)).await;
+    let body: serde_json::Value = resp.json();
+    assert_eq!(body["status"], "expired");
+}
+
+#[tokio::test]
+async fn test_list_sync_runs_filters_pages_and_aggregates() {
+    let fixture = common::setup_test_fixture().await.unwrap();
+    let server = test_server_no_expect(&fixture);
+    let pool = fixture.state.db_pool.pool();
+    let other_source = seed_source(pool, "local_files", true).await;
+
+    for (source_id, status, started_at, scanned) in [
+        (TEST_SOURCE_ID, "completed", "2026-03-01T10:00:00Z", 10),
+        (TEST_SOURCE_ID, "failed", "2026-03-01T12:00:00Z", 3),
+        (TEST_SOURCE_ID, "completed", "2026-03-02T09:00:00Z", 5),
+        (
+            other_source.as_str(),
+            "completed",
+            "2026-03-02T11:00:00Z",
+            7,
+        ),
+    ] {
+        sqlx::query(
+            r#"
+            INSERT INTO sync_runs (id, source_id, sync_type, status, trigger_type,
+                                   started_at, completed_at, documents_scanned)
+            VALUES ($1, $2, 'full', $3, 'manual',
+                    $4::timestamptz, $4::timestamptz + INTERVAL '1 minute', $5)
+            "#,
+        )
+        .bind(shared::utils::generate_ulid())
+        .bind(source_id)
+        .bind(status)
+        .bind(started_at)
+        .bind(scanned)
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    let resp = server.get("/sync-runs?page_size=3").await;
+    resp.assert_status(StatusCode::OK);
+    let body: serde_json::Value = resp.json();
+    assert_eq!(body["total"], 4);
+    assert_eq!(body["total_pages"], 2);
+    assert_eq!(body["page"], 1);
+    let runs = body["sync_runs"].as_array().unwrap();
+    assert_eq!(runs.len(), 3);
+    assert_eq!(runs[0]["source_id"], other_source.as_str());
+
+    let stats = body["stats"].as_array().unwrap();
+    assert_eq!(stats.len(), 2);
+    assert_eq!(stats[0]["total_runs"], 2);
+    assert_eq!(stats[0]["documents_scanned"], 12);
+    assert_eq!(stats[1]["total_runs"], 2);
+    assert_eq!(stats[1]["failed_runs"], 1);
+    assert_eq!(stats[1]["avg_duration_seconds"], 60.0);
+
+    let body: serde_json::Value = server.get("/sync-runs?page_size=3&page=2").await.json();
+    assert_eq!(body["sync_runs"].as_array().unwrap().len(), 1);
+
+    let body: serde_json::Value = server
+        .get(&format!(
+            "/sync-runs?source_id={}&status=completed",
+            TEST_SOURCE_ID
+        ))
+        .await
+        .json();
+    assert_eq!(body["total"], 2);
+
+    let body: serde_json::Value = server
+        .get("/sync-runs?from=2026-03-02&period=month")
+        .await
+        .json();
+    assert_eq!(body["total"], 2);
+    assert_eq!(body["stats"].as_array().unwrap().len(), 1);
+    assert_eq!(body["stats"][0]["completed_runs"], 2);
+
+    let resp = server.get("/sync-runs?from=2026-03-02&to=2026-03-01").await;
+    resp.assert_status(StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_source_ownership_transfer_and_orphan_reassignment() {
+    let fixture = common::setup_test_fixture().await.unwrap();
+    let server = test_server(&fixture);
+    let pool = fixture.state.db_pool.pool();
+
+    let mut users = Vec::new();
+    for email in [
+        "leaver@example.com",
+        "co-owner@example.com",
+        "admin2@example.com",
+    ] {
+        let id = shared::utils::generate_ulid();
+        sqlx::query(
+            r#"
+            INSERT INTO users (id, email, password_hash, created_at, updated_at)
+            VALUES ($1, $2, 'x', NOW(), NOW())
+            "#,
+        )
+        .bind(&id)
+        .bind(email)
+        .execute(pool)
+        .await
+        .unwrap();
+        users.push(id);
+    }
+    let (leaver, co_owner, admin) = (&users[0], &users[1], &users[2]);
+
+    server
+        .put(&format!("/sources/{}/owner", TEST_SOURCE_ID))
+        .json(&json!({"new_owner_id": leaver}))
+        .await;
+    server
+        .post(&format!("/sources/{}/co-owners", TEST_SOURCE_ID))
+        .json(&json!({"user_id": co_owner}))
+        .await;
+
+    let email: serde_json::Value = server
+        .get(&format!("/sdk/source/{}/user-email", TEST_SOURCE_ID))
+        .await
+        .json();
+    assert_eq!(email["email"], "leaver@example.com");
+
+    // Once the owner is deactivated, connectors act as the co-owner.
+    sqlx::query("UPDATE users SET is_active = false WHERE id = $1")
+        .bind(leaver)
+        .execute(pool)
+        .await
+        .unwrap();
+    let email: serde_json::Value = server
+        .get(&format!("/sdk/source/{}/user-email", TEST_SOURCE_ID))
+        .await
+        .json();
+    assert_eq!(email["email"], "co-owner@example.com");
+
+    // A service principal takes precedence over owner and co-owners.
+    server
+        .put(&format!("/sources/{}/service-principal", TEST_SOURCE_ID))
+        .json(&json!({"user_id": admin}))
+        .await;
+    let email: serde_json::Value = server
+        .get(&format!("/sdk/source/{}/user-email", TEST_SOURCE_ID))
+        .await
+        .json();
+    assert_eq!(email["email"], "admin2@example.com");
+
+    let orphaned: serde_json::Value = server.get("/sources/orphaned").await.json();
+    let orphaned = orphaned.as_array().unwrap();
+    assert_eq!(orphaned.len(), 1);
+    assert_eq!(orphaned[0]["source_id"], TEST_SOURCE_ID);
+    assert_eq!(orphaned[0]["has_active_principal"], true);
+
+    // Deactivated users cannot take ownership.
+    test_server_no_expect(&fixture)
+        .put(&format!("/sources/{}/owner", TEST_SOURCE_ID))
+        .json(&json!({"new_owner_id": leaver}))
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+
+    let body: serde_json::Value = server
+        .post("/sources/orphaned/reassign")
+        .json(&json!({"new_owner_id": co_owner}))
+        .await
+        .json();
+    assert_eq!(body["reassigned"], json!([TEST_SOURCE_ID]));
+
+    let ownership: serde_json::Value = server
+        .get(&format!("/sources/{}/ownership", TEST_SOURCE_ID))
+        .await
+        .json();
+    assert_eq!(ownership["owner_id"], co_owner.as_str());
+    assert_eq!(ownership["owner_active"], true);
+    assert_eq!(ownership["co_owners"], json!([]));
+
+    let orphaned: serde_json::Value = server.get("/sources/orphaned").await.json();
+    assert_eq!(orphaned, json!([]));
+}
+
+#[tokio::test]
+async fn test_reassign_source_documents_to_recreated_source() {
+    let fixture = common::setup_test_fixture().await.unwrap();
+    let server = test_server_no_expect(&fixture);
+    let pool = fixture.state.db_pool.pool();
+
+    let (old_source, doc_ids) = seed_deleted_source_with_documents(pool, 3).await;
+    sqlx::query("UPDATE sources SET checkpoint = $1 WHERE id = $2")
+        .bind(json!({"page_token": "abc"}))
+        .bind(&old_source)
+        .execute(pool)
+        .await
+        .unwrap();
+    sqlx::query(
+        "INSERT INTO source_sync_state (source_id, key, value) VALUES ($1, 'cursor', '42')",
+    )
+    .bind(&old_source)
+    .execute(pool)
+    .await
+    .unwrap();
+    // Already synced into the new source, so it stays behind
+    sqlx::query(
+        r#"
+        INSERT INTO documents (id, source_id, external_id, title, metadata, permissions)
+        VALUES ($1, $2, 'ext_2', 'Doc 2', '{}', '[]')
+        "#,
+    )
+    .bind(shared::utils::generate_ulid())
+    .bind(TEST_SOURCE_ID)
+    .execute(pool)
+    .await
+    .unwrap();
+
+    let reassign_path = format!("/sources/{}/documents/reassign", old_source);
+    let other_type = seed_source(pool, "google_drive", true).await;
+    server
+        .post(&reassign_path)
+        .json(&json!({"to_source_id": other_type}))
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+
+    let sync_run_id = create_running_sync(pool, TEST_SOURCE_ID).await;
+    server
+        .post(&reassign_path)
+        .json(&json!({"to_source_id": TEST_SOURCE_ID}))
+        .await
+        .assert_status(StatusCode::CONFLICT);
+    SyncRunRepository::new(pool)
+        .mark_completed(&sync_run_id)
+        .await
+        .unwrap();
+
+    let resp = server
+        .post(&reassign_path)
+        .json(&json!({"to_source_id": TEST_SOURCE_ID}))
+        .await;
+    resp.assert_status(StatusCode::OK);
+    let body: serde_json::Value = resp.json();
+    assert_eq!(body["moved_documents"], 2);
+    assert_eq!(body["skipped_documents"], 1);
+    assert_eq!(body["moved_sync_state_keys"], 1);
+
+    let moved: Vec<String> =
+        sqlx::query_scalar("SELECT id FROM documents WHERE source_id = $1 AND id = ANY($2)")
+            .bind(TEST_SOURCE_ID)
+            .bind(&doc_ids)
+            .fetch_all(pool)
+            .await
+            .unwrap();
+    assert_eq!(moved.len(), 2);
+    assert!(!moved.contains(&doc_ids[2]));
+    assert_eq!(
+        get_source_checkpoint(pool).await,
+        Some(json!({"page_token": "abc"}))
+    );
+    let cursor: serde_json::Value = sqlx::query_scalar(
+        "SELECT value FROM source_sync_state WHERE source_id = $1 AND key = 'cursor'",
+    )
+    .bind(TEST_SOURCE_ID)
+    .fetch_one(pool)
+    .await
+    .unwrap();
+    assert_eq!(cursor, json!(42));
+}
+
+#[tokio::test]
+async fn test_push_api_keys_authenticate_until_revoked() {
+    let fixture = common::setup_test_fixture().await.unwrap();
+    let server = test_server_no_expect(&fixture);
+    let pool = fixture.state.db_pool.pool();
+    let push_source = seed_source(pool, "push", true).await;
+    let keys_path = format!("/sources/{}/push-keys", push_source);
+
+    // Only push sources get keys
+    server
+        .post(&format!("/sources/{}/push-keys", TEST_SOURCE_ID))
+        .json(&json!({"name": "ci"}))
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+
+    let resp = server
+        .post(&keys_path)
+        .json(&json!({"name": "Ticketing export"}))
+        .await;
+    resp.assert_status(StatusCode::CREATED);
+    let created: serde_json::Value = resp.json();
+    let key = created["key"].as_str().unwrap().to_string();
+    let key_id = created["id"].as_str().unwrap().to_string();
+    assert!(key.starts_with(created["key_prefix"].as_str().unwrap()));
+
+    let resp = server
+        .post("/sdk/push-keys/authenticate")
+        .json(&json!({"key": key}))
+        .await;
+    resp.assert_status(StatusCode::OK);
+    let identity: serde_json::Value = resp.json();
+    assert_eq!(identity["source_id"], push_source.as_str());
+    assert_eq!(identity["key_id"], key_id.as_str());
+
+    let listed: serde_json::Value = server.get(&keys_path).await.json();
+    assert_eq!(listed.as_array().unwrap().len(), 1);
+    assert!(listed[0]["last_used_at"].is_string());
+    assert!(listed[0].get("key_hash").is_none());
+
+    server
+        .post("/sdk/push-keys/authenticate")
+        .json(&json!({"key": format!("{}x", key)}))
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+
+    server
+        .delete(&format!("{}/{}", keys_path, key_id))
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    server
+        .delete(&format!("{}/{}", keys_path, key_id))
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+    server
+        .post("/sdk/push-keys/authenticate")
+        .json(&json!({"key": key}))
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_sdk_sync_state_compare_and_swap() {
+    let fixture = common::setup_test_fixture().await.unwrap();
+    let server = test_server_no_expect(&fixture);
+    let state_path = format!("/sdk/source/{}/state/drive:files", TEST_SOURCE_ID);
+    let cas_path = format!("{}/cas", state_path);
+
+    server
+        .get(&state_path)
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+
+    // Creating with expected_version = null only succeeds once
+    let created: serde_json::Value = server
+        .post(&cas_path)
+        .json(&json!({"expected_version": null, "value": {"a": "etag-1"}}))
+        .await
+        .json();
+    assert_eq!(created["version"], 1);
+    server
+        .post(&cas_path)
+        .json(&json!({"expected_version": null, "value": {"a": "etag-2"}}))
+        .await
+        .assert_status(StatusCode::CONFLICT);
+
+    let updated: serde_json::Value = server
+        .post(&cas_path)
+        .json(&json!({"expected_version": 1, "value": {"a": "etag-2"}}))
+        .await
+        .json();
+    assert_eq!(updated["version"], 2);
+
+    // A writer still holding version 1 loses
+    server
+        .post(&cas_path)
+        .json(&json!({"expected_version": 1, "value": {"a": "stale"}}))
+        .await
+        .assert_status(StatusCode::CONFLICT);
+
+    let overwritten: serde_json::Value = server
+        .put(&state_path)
+        .json(&json!({"value": {"a": "etag-3"}}))
+        .await
+        .json();
+    assert_eq!(overwritten["version"], 3);
+
+    let stored: serde_json::Value = server.get(&state_path).await.json();
+    assert_eq!(stored["value"], json!({"a": "etag-3"}));
+    assert_eq!(stored["version"], 3);
+
+    server
+        .put(&format!("/sdk/source/{}/state/bad%20key", TEST_SOURCE_ID))
+        .json(&json!({"value": 1}))
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+    server
+        .put("/sdk/source/no-such-source/state/cursor")
+        .json(&json!({"value": 1}))
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+
+    server
+        .delete(&state_path)
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    server
+        .get(&state_path)
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_source_stats_from_daily_rollups() {
+    let fixture = common::setup_test_fixture().await.unwrap();
+    let server = test_server_no_expect(&fixture);
+    let pool = fixture.state.db_pool.pool();
+    let stats_path = format!("/sources/{}/stats", TEST_SOURCE_ID);
+
+    // Nothing rolled up yet
+    let body: serde_json::Value = server.get(&stats_path).await.json();
+    assert!(body["current"].is_null());
+    assert_eq!(body["history"], json!([]));
+
+    // Yesterday's snapshot, then two documents today
+    sqlx::query(
+        r#"
+        INSERT INTO source_daily_stats (source_id, day, document_count, content_bytes,
+                                        embedded_document_count, chunk_count)
+        VALUES ($1, date_trunc('day', NOW() AT TIME ZONE 'UTC') AT TIME ZONE 'UTC' - INTERVAL '1 day',
+                1, 10, 0, 0)
+        "#,
+    )
+    .bind(TEST_SOURCE_ID)
+    .execute(pool)
+    .await
+    .unwrap();
+    for external_id in ["ext-1", "ext-2"] {
+        let content_id = fixture
+            .state
+            .content_storage
+            .store_text("twelve bytes", None)
+            .await
+            .unwrap();
+        sqlx::query(
+            r#"
+            INSERT INTO documents (id, source_id, external_id, title, content_id, metadata,
+                                   permissions, attributes)
+            VALUES ($1, $2, $3, 'Doc', $4, '{}', '{"public": true}', '{}')
+            "#,
+        )
+        .bind(shared::utils::generate_ulid())
+        .bind(TEST_SOURCE_ID)
+        .bind(external_id)
+        .bind(&content_id)
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    SourceStatsRepository::new(pool)
+        .refresh_daily_stats(365)
+        .await
+        .unwrap();
+
+    let body: serde_json::Value = server.get(&stats_path).await.json();
+    assert_eq!(body["current"]["document_count"], 2);
+    assert_eq!(body["current"]["content_bytes"], 24);
+    assert_eq!(body["current"]["embedded_document_count"], 0);
+    let history = body["history"].as_array().unwrap();
+    assert_eq!(history.len(), 2);
+    assert!(history[0]["document_count_change"].is_null());
+    assert_eq!(history[1]["document_count_change"], 1);
+
+    // Only today's snapshot within a one-day window
+    let body: serde_json::Value = server.get(&format!("{}?days=1", stats_path)).await.json();
+    assert_eq!(body["history"].as_array().unwrap().len(), 1);
+
+    server
+        .get("/sources/01JGF7V3E0Y2R1X8P5Q7W9T4N0/stats")
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_sync_progress_stream_reports_heartbeat_progress() {
+    let fixture = common::setup_test_fixture().await.unwrap();
+    let server = test_server(&fixture);
+
+    let sync_run_id = trigger_sync(&server).await;
+
+    server
+        .post(&format!("/sdk/sync/{}/heartbeat", sync_run_id))
+        .json(&json!({"phase": "listing files", "documents_total": 20}))
+        .await
+        .assert_status(StatusCode::OK);
+    // A bare heartbeat keeps the reported progress
+    server
+        .post(&format!("/sdk/sync/{}/heartbeat", sync_run_id))
+        .await
+        .assert_status(StatusCode::OK);
+    server
+        .post(&format!("/sdk/sync/{}/scanned", sync_run_id))
+        .json(&json!({"count": 5}))
+        .await
+        .assert_status(StatusCode::OK);
+    server
+        .post(&format!("/sdk/sync/{}/complete", sync_run_id))
+        .await
+        .assert_status(StatusCode::OK);
+
+    // The stream ends after the first event once the run is finished
+    let response = server.get(&format!("/sync/{}/progress", sync_run_id)).await;
+    response.assert_status(StatusCode::OK);
+    let body = response.text();
+    let data = body
+        .lines()
+        .find_map(|line| line.strip_prefix("data:"))
+        .expect("progress event");
+    let progress: serde_json::Value = serde_json::from_str(data.trim()).unwrap();
+
+    assert_eq!(progress["status"], "completed");
+    assert_eq!(progress["phase"], "listing files");
+    assert_eq!(progress["documents_total"], 20);
+    assert_eq!(progress["documents_scanned"], 5);
+    assert!(progress.get("eta_seconds").is_none());
+}
+
+#[tokio::test]
+async fn test_workspace_admin_api_scopes_source_listings() {
+    let fixture = common::setup_test_fixture().await.unwrap();
+    let server = test_server_no_expect(&fixture);
+    let pool = fixture.state.db_pool.pool();
+
+    let response = server
+        .post("/workspaces")
+        .json(&json!({"name": "Research", "slug": "research"}))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    let workspace: serde_json::Value = response.json();
+    let workspace_id = workspace["id"].as_str().unwrap().to_string();
+
+    server
+        .post("/workspaces")
+        .json(&json!({"name": "Research again", "slug": "research"}))
+        .await
+        .assert_status(StatusCode::CONFLICT);
+    server
+        .post("/workspaces")
+        .json(&json!({"name": "Research", "slug": "Research Team"}))
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+
+    let workspaces: Vec<serde_json::Value> = server.get("/workspaces").await.json();
+    assert!(workspaces.iter().any(|w| w["id"] == DEFAULT_WORKSPACE_ID));
+    assert!(workspaces.iter().any(|w| w["id"] == workspace_id));
+
+    let user_id = shared::utils::generate_ulid();
+    sqlx::query(
+        r#"
+        INSERT INTO users (id, email, password_hash, created_at, updated_at)
+        VALUES ($1, 'researcher@example.com', 'hash', NOW(), NOW())
+        "#,
+    )
+    .bind(&user_id)
+    .execute(pool)
+    .await
+    .unwrap();
+
+    // Listings follow the acting user's workspace; services see every source
+    let listed_source_ids = |path: &'static str, acting_user: Option<String>| {
+        let server = &server;
+        async move {
+            let mut request = server.get(path);
+            if let Some(user_id) = acting_user {
+                request = request.add_header(ACTOR_ID_HEADER, user_id);
+            }
+            let response = request.await;
+            response.assert_status(StatusCode::OK);
+            let body: Vec<serde_json::Value> = response.json();
+            body.iter()
+                .map(|entry| {
+                    entry["source"]["id"]
+                        .as_str()
+                        .or(entry["source_id"].as_str())
+                        .unwrap()
+                        .to_string()
+                })
+                .collect::<Vec<_>>()
+        }
+    };
+    for path in ["/sources", "/connectors/health"] {
+        assert!(
+            listed_source_ids(path, Some(user_id.clone()))
+                .await
+                .contains(&TEST_SOURCE_ID.to_string())
+        );
+    }
+
+    server
+        .put(&format!("/workspaces/{}/users/{}", workspace_id, user_id))
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    let moved_to: String = sqlx::query_scalar("SELECT workspace_id FROM users WHERE id = $1")
+        .bind(&user_id)
+        .fetch_one(pool)
+        .await
+        .unwrap();
+    assert_eq!(moved_to, workspace_id);
+
+    for path in ["/sources", "/connectors/health"] {
+        assert!(
+            listed_source_ids(path, Some(user_id.clone()))
+                .await
+                .is_empty()
+        );
+        assert!(
+            listed_source_ids(path, None)
+                .await
+                .contains(&TEST_SOURCE_ID.to_string())
+        );
+    }
+
+    server
+        .put(&format!("/workspaces/{}/users/{}", "0".repeat(26), user_id))
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+    server
+        .put(&format!(
+            "/workspaces/{}/users/{}",
+            workspace_id,
+            shared::utils::generate_ulid()
+        ))
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,323 @@
+//! Admin-configured ingestion blocklists.
+//!
+//! Rules name material that must never be indexed: a folder, a file name
+//! glob or a regular expression on the extracted text, optionally limited to
+//! one source. The queue processor checks each created or updated document
+//! against the enabled rules before writing it; a match is recorded as a
+//! quarantined event instead of being indexed.
+
+use regex::{Regex, RegexBuilder};
+use serde::Deserialize;
+use shared::db::repositories::{BlockRuleKind, IngestionBlockRule};
+use shared::models::Document;
+use tracing::warn;
+
+/// Compiled regexes are capped so one rule cannot slow every batch down.
+const REGEX_SIZE_LIMIT: usize = 1024 * 1024;
+/// Longest excerpt of matching content kept on a quarantined event.
+const MAX_MATCHED_VALUE_CHARS: usize = 200;
+/// Longest quarantined event list returned at once.
+pub const MAX_QUARANTINE_LIST_LIMIT: i64 = 500;
+const DEFAULT_QUARANTINE_LIST_LIMIT: i64 = 100;
+
+enum Matcher {
+    /// Lower-cased path without surrounding slashes.
+    Folder(String),
+    FileName(Regex),
+    Content(Regex),
+}
+
+struct CompiledRule {
+    id: String,
+    name: String,
+    source_id: Option<String>,
+    matcher: Matcher,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateBlockRuleRequest {
+    pub name: String,
+    pub kind: BlockRuleKind,
+    pub pattern: String,
+    /// Limit the rule to one source; it applies to all sources when unset.
+    pub source_id: Option<String>,
+    pub created_by: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateBlockRuleRequest {
+    pub name: Option<String>,
+    pub pattern: Option<String>,
+    pub enabled: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct QuarantineQuery {
+    pub rule_id: Option<String>,
+    pub source_id: Option<String>,
+    pub limit: Option<i64>,
+}
+
+impl QuarantineQuery {
+    pub fn limit(&self) -> i64 {
+        self.limit
+            .unwrap_or(DEFAULT_QUARANTINE_LIST_LIMIT)
+            .clamp(1, MAX_QUARANTINE_LIST_LIMIT)
+    }
+}
+
+/// Why a document was blocked.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockMatch {
+    pub rule_id: String,
+    pub rule_name: String,
+    pub matched_value: String,
+}
+
+fn normalize_folder(path: &str) -> String {
+    path.trim().trim_matches('/').to_lowercase()
+}
+
+fn glob_to_regex(glob: &str) -> String {
+    let mut pattern = String::from("^");
+    for c in glob.chars() {
+        match c {
+            '*' => pattern.push_str(".*"),
+            '?' => pattern.push('.'),
+            _ => pattern.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    pattern.push('$');
+    pattern
+}
+
+fn build_regex(pattern: &str, case_insensitive: bool) -> Result<Regex, String> {
+    RegexBuilder::new(pattern)
+        .case_insensitive(case_insensitive)
+        .size_limit(REGEX_SIZE_LIMIT)
+        .build()
+        .map_err(|e| e.to_string())
+}
+
+fn compile_matcher(kind: BlockRuleKind, pattern: &str) -> Result<Matcher, String> {
+    if pattern.trim().is_empty() {
+        return Err("Pattern must not be empty".to_string());
+    }
+    match kind {
+        BlockRuleKind::Folder => {
+            let folder = normalize_folder(pattern);
+            if folder.is_empty() {
+                return Err("Folder pattern must name a folder".to_string());
+            }
+            Ok(Matcher::Folder(folder))
+        }
+        BlockRuleKind::FileName => {
+            build_regex(&glob_to_regex(pattern.trim()), true).map(Matcher::FileName)
+        }
+        BlockRuleKind::Content => build_regex(pattern, false).map(Matcher::Content),
+    }
+}
+
+/// Check that a pattern is usable for its kind, so bad rules are rejected
+/// when they are saved rather than skipped at ingestion.
+pub fn validate_pattern(kind: BlockRuleKind, pattern: &str) -> Result<(), String> {
+    compile_matcher(kind, pattern).map(|_| ())
+}
+
+/// The last segment of a path or URL, without any query or fragment.
+fn last_segment(value: &str) -> Option<&str> {
+    let value = value.split(['?', '#']).next().unwrap_or(value);
+    value
+        .trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .filter(|segment| !segment.is_empty())
+}
+
+fn excerpt(text: &str) -> String {
+    let mut excerpt: String = text.chars().take(MAX_MATCHED_VALUE_CHARS).collect();
+    if excerpt.len() < text.len() {
+        excerpt.push('…');
+    }
+    excerpt
+}
+
+impl CompiledRule {
+    fn matches(&self, document: &Document, content: &str) -> Option<String> {
+        if self
+            .source_id
+            .as_deref()
+            .is_some_and(|source_id| source_id != document.source_id)
+        {
+            return None;
+        }
+
+        let path = document.metadata.get("path").and_then(|p| p.as_str());
+        match &self.matcher {
+            Matcher::Folder(folder) => {
+                let path = path?;
+                let normalized = normalize_folder(path);
+                let inside = normalized == *folder
+                    || normalized
+                        .strip_prefix(folder.as_str())
+                        .is_some_and(|rest| rest.starts_with('/'));
+                inside.then(|| path.to_string())
+            }
+            Matcher::FileName(glob) => [
+                path.and_then(last_segment),
+                document.url.as_deref().and_then(last_segment),
+                Some(document.title.as_str()),
+            ]
+            .into_iter()
+            .flatten()
+            .find(|name| glob.is_match(name))
+            .map(str::to_string),
+            Matcher::Content(regex) => regex.find(content).map(|m| excerpt(m.as_str())),
+        }
+    }
+}
+
+/// The enabled rules, compiled once per batch.
+pub struct Blocklist {
+    rules: Vec<CompiledRule>,
+}
+
+impl Blocklist {
+    /// Compile the rules. A rule whose pattern no longer compiles is skipped
+    /// with a warning rather than failing ingestion.
+    pub fn new(rules: &[IngestionBlockRule]) -> Self {
+        let rules = rules
+            .iter()
+            .filter(|rule| rule.enabled)
+            .filter_map(|rule| match compile_matcher(rule.kind, &rule.pattern) {
+                Ok(matcher) => Some(CompiledRule {
+                    id: rule.id.clone(),
+                    name: rule.name.clone(),
+                    source_id: rule.source_id.clone(),
+                    matcher,
+                }),
+                Err(e) => {
+                    warn!("Skipping invalid ingestion block rule {}: {}", rule.id, e);
+                    None
+                }
+            })
+            .collect();
+        Self { rules }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// The first rule the document matches, if any.
+    pub fn check(&self, document: &Document, content: &str) -> Option<BlockMatch> {
+        self.rules.iter().find_map(|rule| {
+            rule.matches(document, content)
+                .map(|matched_value| BlockMatch {
+                    rule_id: rule.id.clone(),
+                    rule_name: rule.name.clone(),
+                    matched_value,
+                })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use sqlx::types::time::OffsetDateTime;
+
+    fn rule(kind: BlockRuleKind, pattern: &str, source_id: Option<&str>) -> IngestionBlockRule {
+        let now = OffsetDateTime::now_utc();
+        IngestionBlockRule {
+            id: "rule-1".to_string(),
+            name: "Blocked".to_string(),
+            kind,
+            pattern: pattern.to_string(),
+            source_id: source_id.map(str::to_string),
+            enabled: true,
+            match_count: 0,
+            last_matched_at: None,
+            created_by: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    fn document(title: &str, path: Option<&str>, url: Option<&str>) -> Document {
+        let now = OffsetDateTime::now_utc();
+        Document {
+            id: "doc-1".to_string(),
+            source_id: "source-1".to_string(),
+            external_id: "ext-1".to_string(),
+            title: title.to_string(),
+            content_id: None,
+            content_type: None,
+            file_size: None,
+            file_extension: None,
+            url: url.map(str::to_string),
+            metadata: json!({ "path": path }),
+            permissions: json!({}),
+            attributes: json!({}),
+            created_at: now,
+            updated_at: now,
+            last_indexed_at: now,
+        }
+    }
+
+    #[test]
+    fn test_folder_rule_matches_folder_and_descendants_only() {
+        let blocklist = Blocklist::new(&[rule(BlockRuleKind::Folder, "/HR/Payroll/", None)]);
+
+        let inside = document("Salaries", Some("hr/payroll/2024/salaries.xlsx"), None);
+        let same = document("Payroll", Some("/HR/Payroll"), None);
+        let sibling = document("Payroll notes", Some("HR/Payroll-archive/notes"), None);
+
+        assert!(blocklist.check(&inside, "").is_some());
+        assert!(blocklist.check(&same, "").is_some());
+        assert!(blocklist.check(&sibling, "").is_none());
+    }
+
+    #[test]
+    fn test_file_name_rule_globs_path_url_and_title() {
+        let blocklist = Blocklist::new(&[rule(BlockRuleKind::FileName, "*.key", None)]);
+
+        let by_url = document(
+            "Deploy key",
+            None,
+            Some("https://files.example.com/ops/id_rsa.KEY?dl=1"),
+        );
+        let by_title = document("server.key", None, None);
+        let neither = document("keynote.pdf", Some("talks/keynote.pdf"), None);
+
+        assert_eq!(
+            blocklist.check(&by_url, "").map(|m| m.matched_value),
+            Some("id_rsa.KEY".to_string())
+        );
+        assert!(blocklist.check(&by_title, "").is_some());
+        assert!(blocklist.check(&neither, "").is_none());
+    }
+
+    #[test]
+    fn test_content_rule_records_matching_excerpt() {
+        let blocklist =
+            Blocklist::new(&[rule(BlockRuleKind::Content, r"\b\d{3}-\d{2}-\d{4}\b", None)]);
+        let doc = document("Onboarding", None, None);
+
+        let matched = blocklist.check(&doc, "SSN: 123-45-6789 on file").unwrap();
+        assert_eq!(matched.matched_value, "123-45-6789");
+        assert!(blocklist.check(&doc, "No numbers here").is_none());
+    }
+
+    #[test]
+    fn test_source_scoped_and_invalid_rules() {
+        let scoped = Blocklist::new(&[rule(BlockRuleKind::FileName, "*", Some("source-2"))]);
+        assert!(scoped.check(&document("a.txt", None, None), "").is_none());
+
+        let invalid = Blocklist::new(&[rule(BlockRuleKind::Content, "(unclosed", None)]);
+        assert!(invalid.is_empty());
+        assert!(validate_pattern(BlockRuleKind::Content, "(unclosed").is_err());
+        assert!(validate_pattern(BlockRuleKind::Folder, "///").is_err());
+    }
+}
@@ -0,0 +1,435 @@
+//! Code-aware indexing of source files.
+//!
+//! Documents whose file extension names a programming language are marked
+//! with `content_kind: "code"` and their `language`, and the names of the
+//! functions and classes they define are extracted into attributes so they
+//! can be filtered and matched by name. The embedding service reads the
+//! content kind to chunk code at function boundaries instead of sentences.
+
+use regex::Regex;
+use serde_json::{Value as JsonValue, json};
+use shared::models::Document;
+use std::collections::HashSet;
+use std::sync::LazyLock;
+
+/// `content_kind` attribute value of source files.
+pub const CODE_CONTENT_KIND: &str = "code";
+/// Most symbols of each kind kept per document.
+const MAX_SYMBOLS: usize = 200;
+/// Only this much of a file is scanned for symbols, in bytes.
+const MAX_SCANNED_BYTES: usize = 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CodeLanguage {
+    C,
+    Cpp,
+    CSharp,
+    Go,
+    Java,
+    JavaScript,
+    Kotlin,
+    Php,
+    Python,
+    Ruby,
+    Rust,
+    Scala,
+    Shell,
+    Sql,
+    Swift,
+    TypeScript,
+}
+
+impl CodeLanguage {
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        let language = match extension.to_ascii_lowercase().as_str() {
+            "c" | "h" => Self::C,
+            "cc" | "cpp" | "cxx" | "hh" | "hpp" | "hxx" => Self::Cpp,
+            "cs" => Self::CSharp,
+            "go" => Self::Go,
+            "java" => Self::Java,
+            "js" | "jsx" | "mjs" | "cjs" => Self::JavaScript,
+            "kt" | "kts" => Self::Kotlin,
+            "php" => Self::Php,
+            "py" | "pyi" => Self::Python,
+            "rb" => Self::Ruby,
+            "rs" => Self::Rust,
+            "scala" | "sc" => Self::Scala,
+            "sh" | "bash" | "zsh" => Self::Shell,
+            "sql" => Self::Sql,
+            "swift" => Self::Swift,
+            "ts" | "tsx" | "mts" | "cts" => Self::TypeScript,
+            _ => return None,
+        };
+        Some(language)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::C => "c",
+            Self::Cpp => "cpp",
+            Self::CSharp => "csharp",
+            Self::Go => "go",
+            Self::Java => "java",
+            Self::JavaScript => "javascript",
+            Self::Kotlin => "kotlin",
+            Self::Php => "php",
+            Self::Python => "python",
+            Self::Ruby => "ruby",
+            Self::Rust => "rust",
+            Self::Scala => "scala",
+            Self::Shell => "shell",
+            Self::Sql => "sql",
+            Self::Swift => "swift",
+            Self::TypeScript => "typescript",
+        }
+    }
+
+    /// Patterns whose first group captures a function name, then patterns
+    /// capturing a class or type name.
+    fn symbol_patterns(self) -> (&'static [&'static str], &'static [&'static str]) {
+        const JVM_CLASSES: &[&str] = &[
+            r"(?m)^\s*(?:@\w+\s+)*(?:(?:public|private|protected|internal|abstract|final|static|sealed|open|data|partial|case|readonly)\s+)*(?:class|interface|enum|record|struct|object|trait|protocol)\s+(\w+)",
+        ];
+        match self {
+            Self::C | Self::Cpp => (
+                &[
+                    r"(?m)^[A-Za-z_][\w\s\*&:<>,]*?[\s\*&]\**(\w+)\s*\([^;{]*\)\s*(?:const\s*)?\{?\s*$",
+                ],
+                &[r"(?m)^\s*(?:typedef\s+)?(?:class|struct|enum)\s+(\w+)\s*(?::[^;{]*)?\{?\s*$"],
+            ),
+            Self::CSharp | Self::Java => (
+                &[
+                    r"(?m)^\s*(?:(?:public|private|protected|internal|static|final|abstract|synchronized|override|virtual|async)\s+)+[\w<>\[\],.?]+\s+(\w+)\s*\(",
+                ],
+                JVM_CLASSES,
+            ),
+            Self::Kotlin | Self::Scala | Self::Swift => (
+                &[
+                    r"(?m)^\s*(?:(?:public|private|protected|internal|override|open|static|final|suspend|inline)\s+)*(?:fun|def|func)\s+(?:<[^>]*>\s*)?(?:\w+\.)?(\w+)",
+                ],
+                JVM_CLASSES,
+            ),
+            Self::Go => (
+                &[r"(?m)^func\s+(?:\([^)]*\)\s*)?(\w+)"],
+                &[r"(?m)^type\s+(\w+)\s+(?:struct|interface)\b"],
+            ),
+            Self::JavaScript | Self::TypeScript => (
+                &[
+                    r"(?m)^\s*(?:export\s+)?(?:default\s+)?(?:async\s+)?function\s*\*?\s*(\w+)",
+                    r"(?m)^\s*(?:export\s+)?(?:const|let|var)\s+(\w+)\s*=\s*(?:async\s+)?(?:function\b|\([^)]*\)\s*(?::[^=]+)?=>|\w+\s*=>)",
+                ],
+                &[
+                    r"(?m)^\s*(?:export\s+)?(?:default\s+)?(?:declare\s+)?(?:abstract\s+)?(?:class|interface|enum)\s+(\w+)",
+                ],
+            ),
+            Self::Php => (
+                &[
+                    r"(?m)^\s*(?:(?:public|private|protected|static|abstract|final)\s+)*function\s+&?(\w+)",
+                ],
+                &[
+                    r"(?m)^\s*(?:(?:abstract|final|readonly)\s+)*(?:class|interface|trait|enum)\s+(\w+)",
+                ],
+            ),
+            Self::Python => (
+                &[r"(?m)^\s*(?:async\s+)?def\s+(\w+)"],
+                &[r"(?m)^\s*class\s+(\w+)"],
+            ),
+            Self::Ruby => (
+                &[r"(?m)^\s*def\s+(?:self\.)?(\w+[?!=]?)"],
+                &[r"(?m)^\s*(?:class|module)\s+([A-Z]\w*(?:::\w+)*)"],
+            ),
+            Self::Rust => (
+                &[
+                    r#"(?m)^\s*(?:pub(?:\([^)]*\))?\s+)?(?:const\s+)?(?:async\s+)?(?:unsafe\s+)?(?:extern\s+"[^"]*"\s+)?fn\s+(\w+)"#,
+                ],
+                &[r"(?m)^\s*(?:pub(?:\([^)]*\))?\s+)?(?:struct|enum|trait|union)\s+(\w+)"],
+            ),
+            Self::Shell => (
+                &[
+                    r"(?m)^\s*function\s+([A-Za-z_][\w-]*)",
+                    r"(?m)^\s*([A-Za-z_][\w-]*)\s*\(\s*\)\s*\{?",
+                ],
+                &[],
+            ),
+            Self::Sql => (
+                &[r"(?im)^\s*create\s+(?:or\s+replace\s+)?(?:function|procedure)\s+([\w.]+)"],
+                &[],
+            ),
+        }
+    }
+}
+
+/// Compiled symbol patterns of one language.
+struct SymbolPatterns {
+    functions: Vec<Regex>,
+    classes: Vec<Regex>,
+}
+
+const ALL_LANGUAGES: &[CodeLanguage] = &[
+    CodeLanguage::C,
+    CodeLanguage::Cpp,
+    CodeLanguage::CSharp,
+    CodeLanguage::Go,
+    CodeLanguage::Java,
+    CodeLanguage::JavaScript,
+    CodeLanguage::Kotlin,
+    CodeLanguage::Php,
+    CodeLanguage::Python,
+    CodeLanguage::Ruby,
+    CodeLanguage::Rust,
+    CodeLanguage::Scala,
+    CodeLanguage::Shell,
+    CodeLanguage::Sql,
+    CodeLanguage::Swift,
+    CodeLanguage::TypeScript,
+];
+
+static SYMBOL_PATTERNS: LazyLock<Vec<(CodeLanguage, SymbolPatterns)>> = LazyLock::new(|| {
+    let compile = |patterns: &[&str]| -> Vec<Regex> {
+        patterns
+            .iter()
+            .map(|p| Regex::new(p).expect("valid symbol pattern"))
+            .collect()
+    };
+    ALL_LANGUAGES
+        .iter()
+        .map(|&language| {
+            let (functions, classes) = language.symbol_patterns();
+            (
+                language,
+                SymbolPatterns {
+                    functions: compile(functions),
+                    classes: compile(classes),
+                },
+            )
+        })
+        .collect()
+});
+
+/// Names C-style function patterns can capture that are control flow, not
+/// definitions.
+const NON_SYMBOL_NAMES: &[&str] = &[
+    "if", "for", "while", "switch", "catch", "return", "sizeof", "elif", "else",
+];
+
+/// Functions and classes defined in a source file, in order of appearance.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct CodeSymbols {
+    pub functions: Vec<String>,
+    pub classes: Vec<String>,
+}
+
+fn collect_symbols(patterns: &[Regex], content: &str) -> Vec<String> {
+    let mut found: Vec<(usize, &str)> = patterns
+        .iter()
+        .flat_map(|regex| regex.captures_iter(content))
+        .filter_map(|captures| captures.get(1))
+        .map(|m| (m.start(), m.as_str()))
+        .filter(|(_, name)| !NON_SYMBOL_NAMES.contains(name))
+        .collect();
+    found.sort_by_key(|(start, _)| *start);
+
+    let mut seen = HashSet::new();
+    found
+        .into_iter()
+        .filter(|(_, name)| seen.insert(*name))
+        .take(MAX_SYMBOLS)
+        .map(|(_, name)| name.to_string())
+        .collect()
+}
+
+/// Extract the functions and classes a source file defines.
+pub fn extract_symbols(language: CodeLanguage, content: &str) -> CodeSymbols {
+    let mut end = content.len().min(MAX_SCANNED_BYTES);
+    while !content.is_char_boundary(end) {
+        end -= 1;
+    }
+    let content = &content[..end];
+
+    let Some((_, patterns)) = SYMBOL_PATTERNS.iter().find(|(l, _)| *l == language) else {
+        return CodeSymbols::default();
+    };
+    CodeSymbols {
+        functions: collect_symbols(&patterns.functions, content),
+        classes: collect_symbols(&patterns.classes, content),
+    }
+}
+
+/// The extension of the last segment of a path, file name or URL.
+fn extension_of(value: &str) -> Option<&str> {
+    let value = value.split(['?', '#']).next().unwrap_or(value);
+    let name = value.rsplit('/').next()?;
+    name.rsplit_once('.')
+        .map(|(_, extension)| extension)
+        .filter(|extension| !extension.is_empty())
+}
+
+/// The programming language of a document, judged by its file extension,
+/// path or title.
+pub fn detect_code_language(document: &Document) -> Option<CodeLanguage> {
+    document
+        .file_extension
+        .as_deref()
+        .and_then(CodeLanguage::from_extension)
+        .or_else(|| {
+            document
+                .metadata
+                .get("path")
+                .and_then(|p| p.as_str())
+                .and_then(extension_of)
+                .and_then(CodeLanguage::from_extension)
+        })
+        .or_else(|| extension_of(&document.title).and_then(CodeLanguage::from_extension))
+}
+
+/// Mark a source file as code and record its language and symbols in its
+/// attributes. Attributes the connector already set are kept. Returns
+/// whether the document is code.
+pub fn annotate_code_document(document: &mut Document, content: &str) -> bool {
+    let Some(language) = detect_code_language(document) else {
+        return false;
+    };
+    let symbols = extract_symbols(language, content);
+
+    let Some(attributes) = document.attributes.as_object_mut() else {
+        return false;
+    };
+    attributes
+        .entry("content_kind")
+        .or_insert_with(|| json!(CODE_CONTENT_KIND));
+    attributes
+        .entry("language")
+        .or_insert_with(|| json!(language.name()));
+    for (key, names) in [
+        ("functions", symbols.functions),
+        ("classes", symbols.classes),
+    ] {
+        if !names.is_empty() {
+            attributes
+                .entry(key)
+                .or_insert_with(|| JsonValue::from(names));
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::types::time::OffsetDateTime;
+
+    fn document(title: &str, path: Option<&str>, file_extension: Option<&str>) -> Document {
+        let now = OffsetDateTime::now_utc();
+        Document {
+            id: "doc-1".to_string(),
+            source_id: "source-1".to_string(),
+            external_id: "ext-1".to_string(),
+            title: title.to_string(),
+            content_id: None,
+            content_type: None,
+            file_size: None,
+            file_extension: file_extension.map(str::to_string),
+            url: None,
+            metadata: json!({ "path": path }),
+            permissions: json!({}),
+            attributes: json!({}),
+            created_at: now,
+            updated_at: now,
+            last_indexed_at: now,
+        }
+    }
+
+    #[test]
+    fn test_detects_language_from_extension_path_or_title() {
+        let by_extension = document("main", None, Some("RS"));
+        let by_path = document("handler", Some("services/api/handler.py"), None);
+        let by_title = document("index.tsx", None, None);
+        let prose = document("notes.md", Some("docs/notes.md"), Some("md"));
+
+        assert_eq!(
+            detect_code_language(&by_extension),
+            Some(CodeLanguage::Rust)
+        );
+        assert_eq!(detect_code_language(&by_path), Some(CodeLanguage::Python));
+        assert_eq!(
+            detect_code_language(&by_title),
+            Some(CodeLanguage::TypeScript)
+        );
+        assert_eq!(detect_code_language(&prose), None);
+    }
+
+    #[test]
+    fn test_extracts_python_and_rust_symbols() {
+        let python = "import os\n\nclass Loader:\n    def load(self):\n        pass\n\n\
+                      async def fetch(url):\n    return url\n\ndef load():\n    pass\n";
+        assert_eq!(
+            extract_symbols(CodeLanguage::Python, python),
+            CodeSymbols {
+                functions: vec!["load".to_string(), "fetch".to_string()],
+                classes: vec!["Loader".to_string()],
+            }
+        );
+
+        let rust = "pub struct Index;\n\nimpl Index {\n    pub async fn search(&self) {}\n}\n\n\
+                    pub(crate) enum Mode { A }\n\nfn helper() {}\n";
+        assert_eq!(
+            extract_symbols(CodeLanguage::Rust, rust),
+            CodeSymbols {
+                functions: vec!["search".to_string(), "helper".to_string()],
+                classes: vec!["Index".to_string(), "Mode".to_string()],
+            }
+        );
+    }
+
+    #[test]
+    fn test_extracts_go_typescript_and_c_symbols() {
+        let go = "package main\n\ntype Server struct {}\n\nfunc (s *Server) Start() error {\n}\n\n\
+                  func main() {\n}\n";
+        assert_eq!(
+            extract_symbols(CodeLanguage::Go, go),
+            CodeSymbols {
+                functions: vec!["Start".to_string(), "main".to_string()],
+                classes: vec!["Server".to_string()],
+            }
+        );
+
+        let typescript = "export interface Props {}\n\nexport const render = async (props: Props) => {};\n\n\
+                          export default function App() {}\n";
+        assert_eq!(
+            extract_symbols(CodeLanguage::TypeScript, typescript),
+            CodeSymbols {
+                functions: vec!["render".to_string(), "App".to_string()],
+                classes: vec!["Props".to_string()],
+            }
+        );
+
+        let c = "struct point {\n    int x;\n};\n\nstatic int add(int a, int b)\n{\n    \
+                 if (a) {\n        return a + b;\n    }\n}\n";
+        assert_eq!(
+            extract_symbols(CodeLanguage::C, c),
+            CodeSymbols {
+                functions: vec!["add".to_string()],
+                classes: vec!["point".to_string()],
+            }
+        );
+    }
+
+    #[test]
+    fn test_annotates_code_documents_and_keeps_connector_attributes() {
+        let mut code = document("lib.rs", Some("src/lib.rs"), Some("rs"));
+        code.attributes = json!({ "language": "Rust 2024" });
+        assert!(annotate_code_document(&mut code, "pub fn run() {}\n"));
+        assert_eq!(
+            code.attributes,
+            json!({
+                "content_kind": "code",
+                "language": "Rust 2024",
+                "functions": ["run"],
+            })
+        );
+
+        let mut prose = document("Roadmap", None, Some("pdf"));
+        assert!(!annotate_code_document(&mut prose, "fn run() {}"));
+        assert_eq!(prose.attributes, json!({}));
+    }
+}
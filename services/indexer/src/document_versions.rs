@@ -0,0 +1,137 @@
+//! Document version history: retention of the versions recorded by the
+//! `documents_record_version` trigger, and line diffs between versions.
+
+use serde::{Deserialize, Serialize};
+use shared::db::repositories::DocumentVersion;
+
+const DEFAULT_MAX_VERSIONS: i64 = 20;
+const DEFAULT_RETENTION_DAYS: i64 = 90;
+
+#[derive(Debug, Clone)]
+pub struct VersionRetentionConfig {
+    /// Versions kept per document, including the current one.
+    pub max_versions: i64,
+    /// Older versions are pruned after this many days; 0 keeps them until
+    /// `max_versions` pushes them out.
+    pub retention_days: i64,
+}
+
+impl Default for VersionRetentionConfig {
+    fn default() -> Self {
+        Self {
+            max_versions: DEFAULT_MAX_VERSIONS,
+            retention_days: DEFAULT_RETENTION_DAYS,
+        }
+    }
+}
+
+impl VersionRetentionConfig {
+    pub fn from_env() -> Self {
+        let env_or = |key: &str, default: i64| {
+            std::env::var(key)
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default)
+        };
+
+        Self {
+            max_versions: env_or("INDEXER_DOCUMENT_VERSIONS_MAX", DEFAULT_MAX_VERSIONS).max(1),
+            retention_days: env_or(
+                "INDEXER_DOCUMENT_VERSIONS_RETENTION_DAYS",
+                DEFAULT_RETENTION_DAYS,
+            )
+            .max(0),
+        }
+    }
+
+    pub fn max_age_days(&self) -> Option<i64> {
+        (self.retention_days > 0).then_some(self.retention_days)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct VersionQuery {
+    /// Include a line diff from this version to the requested one.
+    pub diff_from: Option<i32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiffOp {
+    Equal,
+    Insert,
+    Delete,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DiffLine {
+    pub op: DiffOp,
+    pub text: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DocumentVersionResponse {
+    #[serde(flatten)]
+    pub version: DocumentVersion,
+    /// None once the content has been garbage collected.
+    pub content: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diff_from: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diff: Option<Vec<DiffLine>>,
+}
+
+/// Line-by-line diff turning `old` into `new`.
+pub fn line_diff(old: &str, new: &str) -> Vec<DiffLine> {
+    diff::lines(old, new)
+        .into_iter()
+        .map(|line| match line {
+            diff::Result::Both(text, _) => DiffLine {
+                op: DiffOp::Equal,
+                text: text.to_string(),
+            },
+            diff::Result::Right(text) => DiffLine {
+                op: DiffOp::Insert,
+                text: text.to_string(),
+            },
+            diff::Result::Left(text) => DiffLine {
+                op: DiffOp::Delete,
+                text: text.to_string(),
+            },
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_diff() {
+        let diff = line_diff(
+            "title\nold line\nfooter",
+            "title\nnew line\nfooter\nappendix",
+        );
+        let ops: Vec<(DiffOp, &str)> = diff.iter().map(|l| (l.op, l.text.as_str())).collect();
+        assert_eq!(
+            ops,
+            vec![
+                (DiffOp::Equal, "title"),
+                (DiffOp::Delete, "old line"),
+                (DiffOp::Insert, "new line"),
+                (DiffOp::Equal, "footer"),
+                (DiffOp::Insert, "appendix"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_retention_days_zero_disables_age_limit() {
+        let config = VersionRetentionConfig {
+            max_versions: 5,
+            retention_days: 0,
+        };
+        assert_eq!(config.max_age_days(), None);
+        assert_eq!(VersionRetentionConfig::default().max_age_days(), Some(90));
+    }
+}
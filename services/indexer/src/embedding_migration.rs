@@ -0,0 +1,161 @@
+//! Managed re-embedding of the corpus when the embedding model changes.
+//!
+//! Vectors from different models cannot be compared, so switching the current
+//! provider outright would leave searches with nothing to match until every
+//! document is re-embedded. A migration instead queues every document for the
+//! target provider while the current one keeps serving. The AI service embeds
+//! those items with the target model next to the existing vectors, and queues
+//! any document it re-embeds in production for the target as well, so content
+//! that changes mid-migration ends up in both models. Once the migration's
+//! queue drains, the target provider is made current and the old model's
+//! vectors are deleted in one transaction.
+
+use serde::{Deserialize, Serialize};
+use shared::DatabaseError;
+use shared::db::repositories::{
+    EmbeddingMigration, EmbeddingMigrationProgress, EmbeddingMigrationRepository,
+};
+use sqlx::PgPool;
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+/// How often the running migration is checked for completion.
+const MONITOR_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct EmbeddingMigrationRequest {
+    /// Embedding provider to migrate to. It becomes current once every
+    /// document has been embedded with its model.
+    pub provider_id: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EmbeddingMigrationStatusResponse {
+    #[serde(flatten)]
+    pub migration: EmbeddingMigration,
+    /// Present while the migration is running.
+    pub progress: Option<EmbeddingMigrationProgressResponse>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EmbeddingMigrationProgressResponse {
+    #[serde(flatten)]
+    pub progress: EmbeddingMigrationProgress,
+    pub percent: f64,
+}
+
+#[derive(Clone)]
+pub struct EmbeddingMigrator {
+    pool: PgPool,
+}
+
+impl EmbeddingMigrator {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    fn repo(&self) -> EmbeddingMigrationRepository {
+        EmbeddingMigrationRepository::new(&self.pool)
+    }
+
+    /// Record a migration and queue every document for its target provider.
+    pub async fn start(
+        &self,
+        request: &EmbeddingMigrationRequest,
+    ) -> Result<EmbeddingMigration, DatabaseError> {
+        let migration = self.repo().create(request.provider_id.trim()).await?;
+
+        info!(
+            "Started embedding migration {} from {} to {} ({} documents queued)",
+            migration.id, migration.from_model, migration.to_model, migration.total_documents
+        );
+
+        Ok(migration)
+    }
+
+    pub async fn status(
+        &self,
+        id: &str,
+    ) -> Result<Option<EmbeddingMigrationStatusResponse>, DatabaseError> {
+        let repo = self.repo();
+        let Some(migration) = repo.get(id).await? else {
+            return Ok(None);
+        };
+
+        let progress = if migration.status.is_active() {
+            let progress = repo.progress(&migration).await?;
+            Some(EmbeddingMigrationProgressResponse {
+                percent: progress.percent(migration.total_documents),
+                progress,
+            })
+        } else {
+            None
+        };
+
+        Ok(Some(EmbeddingMigrationStatusResponse {
+            migration,
+            progress,
+        }))
+    }
+
+    pub async fn list(&self, limit: i64) -> Result<Vec<EmbeddingMigration>, DatabaseError> {
+        self.repo().list_recent(limit).await
+    }
+
+    /// Stop a running migration, keeping the current provider. Returns false
+    /// if the migration was not running.
+    pub async fn cancel(&self, id: &str) -> Result<bool, DatabaseError> {
+        let cancelled = self.repo().cancel(id).await?;
+        if cancelled {
+            info!("Cancelled embedding migration {}", id);
+        }
+        Ok(cancelled)
+    }
+
+    /// Cut the running migration over once its queue has drained. Unlike
+    /// index builds, migrations live in the queue rather than in this
+    /// process, so a restarted indexer simply picks the check back up.
+    pub async fn check_running(&self) -> Result<(), DatabaseError> {
+        let repo = self.repo();
+        let Some(migration) = repo.get_running().await? else {
+            return Ok(());
+        };
+
+        let progress = repo.progress(&migration).await?;
+        if progress.pending_documents > 0 {
+            return Ok(());
+        }
+
+        match repo.cut_over(&migration.id).await {
+            Ok(cutover) => {
+                info!(
+                    "Embedding migration {} completed: {} is now current, {} old embeddings retired, {} documents queued for re-embedding",
+                    migration.id,
+                    migration.to_model,
+                    cutover.retired_embeddings,
+                    cutover.requeued_documents
+                );
+                Ok(())
+            }
+            Err(DatabaseError::InvalidInput(reason)) => {
+                warn!("Embedding migration {} failed: {}", migration.id, reason);
+                repo.fail(&migration.id, &reason).await?;
+                Ok(())
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn spawn_monitor(&self) {
+        let migrator = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(MONITOR_INTERVAL);
+            loop {
+                interval.tick().await;
+                if let Err(e) = migrator.check_running().await {
+                    error!("Failed to check embedding migration progress: {}", e);
+                }
+            }
+        });
+    }
+}
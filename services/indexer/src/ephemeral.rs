@@ -0,0 +1,124 @@
+//! Files uploaded into a chat. They are indexed like any other document, but
+//! belong to a hidden per-user source, are visible only to the uploader, and
+//! are deleted once they expire or their chat is deleted.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use shared::db::repositories::EphemeralDocument;
+
+const DEFAULT_TTL_SECONDS: i64 = 24 * 3600;
+const DEFAULT_MAX_TTL_SECONDS: i64 = 7 * 24 * 3600;
+const DEFAULT_MAX_UPLOAD_BYTES: i64 = 50 * 1024 * 1024;
+
+/// Embedding queue priority for uploads, so they are searchable within the
+/// chat that uploaded them without waiting behind connector syncs.
+pub const EMBEDDING_PRIORITY: i16 = 10;
+/// Expired uploads deleted per cleanup pass.
+pub const CLEANUP_BATCH_SIZE: i64 = 500;
+
+#[derive(Debug, Clone)]
+pub struct EphemeralUploadConfig {
+    pub default_ttl_seconds: i64,
+    pub max_ttl_seconds: i64,
+    pub max_upload_bytes: usize,
+}
+
+impl Default for EphemeralUploadConfig {
+    fn default() -> Self {
+        Self {
+            default_ttl_seconds: DEFAULT_TTL_SECONDS,
+            max_ttl_seconds: DEFAULT_MAX_TTL_SECONDS,
+            max_upload_bytes: DEFAULT_MAX_UPLOAD_BYTES as usize,
+        }
+    }
+}
+
+impl EphemeralUploadConfig {
+    pub fn from_env() -> Self {
+        let env_or = |key: &str, default: i64| {
+            std::env::var(key)
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default)
+        };
+
+        let max_ttl_seconds =
+            env_or("INDEXER_EPHEMERAL_MAX_TTL_SECONDS", DEFAULT_MAX_TTL_SECONDS).max(60);
+        Self {
+            default_ttl_seconds: env_or("INDEXER_EPHEMERAL_TTL_SECONDS", DEFAULT_TTL_SECONDS)
+                .clamp(60, max_ttl_seconds),
+            max_ttl_seconds,
+            max_upload_bytes: env_or(
+                "INDEXER_EPHEMERAL_MAX_UPLOAD_BYTES",
+                DEFAULT_MAX_UPLOAD_BYTES,
+            )
+            .max(1) as usize,
+        }
+    }
+
+    /// The TTL to apply to an upload: the requested one if given, capped at
+    /// `max_ttl_seconds`.
+    pub fn ttl_seconds(&self, requested: Option<i64>) -> Result<i64, String> {
+        match requested {
+            None => Ok(self.default_ttl_seconds),
+            Some(ttl) if ttl <= 0 => Err("ttl_seconds must be positive".to_string()),
+            Some(ttl) => Ok(ttl.min(self.max_ttl_seconds)),
+        }
+    }
+}
+
+/// Permissions that make a document visible to its uploader only.
+pub fn uploader_permissions(email: &str) -> Value {
+    json!({
+        "public": false,
+        "users": [email],
+        "groups": [],
+    })
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EphemeralListQuery {
+    pub user_id: String,
+    pub chat_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EphemeralDeleteQuery {
+    pub user_id: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EphemeralUploadResponse {
+    #[serde(flatten)]
+    pub document: EphemeralDocument,
+    pub source_id: String,
+    pub content_type: String,
+    pub file_size: i64,
+    pub extracted_chars: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ttl_defaults_and_caps() {
+        let config = EphemeralUploadConfig {
+            default_ttl_seconds: 3600,
+            max_ttl_seconds: 7200,
+            max_upload_bytes: 1024,
+        };
+        assert_eq!(config.ttl_seconds(None), Ok(3600));
+        assert_eq!(config.ttl_seconds(Some(600)), Ok(600));
+        assert_eq!(config.ttl_seconds(Some(86_400)), Ok(7200));
+        assert!(config.ttl_seconds(Some(0)).is_err());
+    }
+
+    #[test]
+    fn test_uploader_permissions_are_private() {
+        let permissions = uploader_permissions("alice@example.com");
+        assert_eq!(permissions["public"], json!(false));
+        assert_eq!(permissions["users"], json!(["alice@example.com"]));
+        assert_eq!(permissions["groups"], json!([]));
+    }
+}
@@ -14,6 +14,7 @@ pub enum IndexerError {
     Serialization(serde_json::Error),
     NotFound(String),
     BadRequest(String),
+    PreconditionFailed(String),
     Internal(String),
 }
 
@@ -25,6 +26,7 @@ impl std::fmt::Display for IndexerError {
             IndexerError::Serialization(e) => write!(f, "Serialization error: {}", e),
             IndexerError::NotFound(msg) => write!(f, "Not found: {}", msg),
             IndexerError::BadRequest(msg) => write!(f, "Bad request: {}", msg),
+            IndexerError::PreconditionFailed(msg) => write!(f, "Precondition failed: {}", msg),
             IndexerError::Internal(msg) => write!(f, "Internal error: {}", msg),
         }
     }
@@ -80,6 +82,7 @@ impl IntoResponse for IndexerError {
             }
             IndexerError::NotFound(msg) => (StatusCode::NOT_FOUND, msg.clone()),
             IndexerError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg.clone()),
+            IndexerError::PreconditionFailed(msg) => (StatusCode::PRECONDITION_FAILED, msg.clone()),
             IndexerError::Internal(msg) => {
                 error!("Internal error: {}", msg);
                 (StatusCode::INTERNAL_SERVER_ERROR, msg.clone())
@@ -0,0 +1,547 @@
+//! Extraction of text from binary content.
+//!
+//! Connectors may store a file as it is instead of extracting its text.
+//! Before such a document is indexed, its content is converted to Markdown:
+//! PDF text layers and Word, Excel and PowerPoint files through the shared
+//! extractors, and images through an OCR backend when one is configured. The
+//! Markdown is stored as new content and indexed in place of the file, whose
+//! blob is then garbage collected like any replaced content.
+
+use crate::AppState;
+use crate::error::IndexerError;
+use async_trait::async_trait;
+use futures::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use shared::ObjectStorage;
+use shared::content_extractor::{
+    PAGE_BREAK, extract_content, extract_tables, has_extractable_tables, with_structured_tables,
+};
+use shared::db::repositories::ContentBlobRepository;
+use shared::models::Document;
+use shared::storage::StorageError;
+use std::collections::HashMap;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tracing::{debug, warn};
+
+/// Document metadata recording how its content was extracted.
+pub const EXTRACTION_METADATA_KEY: &str = "extraction";
+
+const DEFAULT_MAX_BYTES: i64 = 50 * 1024 * 1024;
+const DEFAULT_CONCURRENCY: usize = 2;
+const MAX_TABLE_ROWS: usize = 1000;
+const OCR_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Types read by the shared extractors.
+const DOCUMENT_MIME_TYPES: &[&str] = &[
+    "application/pdf",
+    "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+    "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+    "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+    "application/vnd.ms-excel",
+    "application/vnd.ms-outlook",
+];
+
+/// Types read by OCR.
+const IMAGE_MIME_TYPES: &[&str] = &[
+    "image/png",
+    "image/jpeg",
+    "image/tiff",
+    "image/bmp",
+    "image/gif",
+    "image/webp",
+];
+
+#[derive(Debug, Clone)]
+pub enum OcrConfig {
+    Disabled,
+    /// Run the `tesseract` command line tool.
+    Tesseract {
+        binary: String,
+        languages: String,
+    },
+    /// POST images to a service that responds with their text.
+    Http {
+        url: String,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct ExtractionConfig {
+    /// Extract binary content during indexing.
+    pub enabled: bool,
+    /// Larger files are indexed by their title alone.
+    pub max_bytes: i64,
+    /// Documents of a batch extracted at once.
+    pub concurrency: usize,
+    pub ocr: OcrConfig,
+}
+
+impl Default for ExtractionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_bytes: DEFAULT_MAX_BYTES,
+            concurrency: DEFAULT_CONCURRENCY,
+            ocr: OcrConfig::Disabled,
+        }
+    }
+}
+
+impl ExtractionConfig {
+    pub fn from_env() -> Self {
+        fn env_or<T: std::str::FromStr>(key: &str, default: T) -> T {
+            std::env::var(key)
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default)
+        }
+
+        let ocr = match env_or("INDEXER_OCR_BACKEND", "none".to_string()).as_str() {
+            "tesseract" => OcrConfig::Tesseract {
+                binary: env_or("INDEXER_OCR_TESSERACT_PATH", "tesseract".to_string()),
+                languages: env_or("INDEXER_OCR_LANGUAGES", "eng".to_string()),
+            },
+            "http" => match std::env::var("INDEXER_OCR_URL") {
+                Ok(url) if !url.trim().is_empty() => OcrConfig::Http { url },
+                _ => {
+                    warn!("INDEXER_OCR_BACKEND is http but INDEXER_OCR_URL is not set");
+                    OcrConfig::Disabled
+                }
+            },
+            "none" => OcrConfig::Disabled,
+            other => {
+                warn!("Unknown INDEXER_OCR_BACKEND '{}', OCR disabled", other);
+                OcrConfig::Disabled
+            }
+        };
+
+        Self {
+            enabled: env_or("INDEXER_EXTRACTION_ENABLED", true),
+            max_bytes: env_or("INDEXER_EXTRACTION_MAX_BYTES", DEFAULT_MAX_BYTES),
+            concurrency: env_or("INDEXER_EXTRACTION_CONCURRENCY", DEFAULT_CONCURRENCY).max(1),
+            ocr,
+        }
+    }
+}
+
+/// Recognizes the text of images.
+#[async_trait]
+pub trait OcrBackend: Send + Sync {
+    async fn recognize(&self, image: &[u8], mime_type: &str) -> anyhow::Result<String>;
+}
+
+pub struct TesseractOcr {
+    binary: String,
+    languages: String,
+}
+
+#[async_trait]
+impl OcrBackend for TesseractOcr {
+    async fn recognize(&self, image: &[u8], _mime_type: &str) -> anyhow::Result<String> {
+        let mut child = tokio::process::Command::new(&self.binary)
+            .args(["stdin", "stdout", "-l", &self.languages])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()?;
+
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        let image = image.to_vec();
+        let write = tokio::spawn(async move { stdin.write_all(&image).await });
+
+        let output = tokio::time::timeout(OCR_TIMEOUT, child.wait_with_output()).await??;
+        write.await??;
+        if !output.status.success() {
+            anyhow::bail!(
+                "tesseract exited with {}: {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+pub struct HttpOcr {
+    client: reqwest::Client,
+    url: String,
+}
+
+#[async_trait]
+impl OcrBackend for HttpOcr {
+    async fn recognize(&self, image: &[u8], mime_type: &str) -> anyhow::Result<String> {
+        let response = self
+            .client
+            .post(&self.url)
+            .header(reqwest::header::CONTENT_TYPE, mime_type)
+            .body(image.to_vec())
+            .timeout(OCR_TIMEOUT)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(response.text().await?)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExtractionMethod {
+    /// Read from the file's text and structure.
+    Parsed,
+    Ocr,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExtractedContent {
+    pub markdown: String,
+    pub mime_type: String,
+    pub method: ExtractionMethod,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExtractRequest {
+    pub content_id: String,
+    pub mime_type: String,
+}
+
+#[derive(Debug)]
+pub enum ExtractionError {
+    NotFound(String),
+    Unsupported(String),
+    Failed(String),
+}
+
+impl From<ExtractionError> for IndexerError {
+    fn from(err: ExtractionError) -> Self {
+        match err {
+            ExtractionError::NotFound(msg) => IndexerError::NotFound(msg),
+            ExtractionError::Unsupported(msg) => IndexerError::BadRequest(msg),
+            ExtractionError::Failed(msg) => IndexerError::Internal(msg),
+        }
+    }
+}
+
+impl std::fmt::Display for ExtractionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExtractionError::NotFound(msg)
+            | ExtractionError::Unsupported(msg)
+            | ExtractionError::Failed(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ContentExtractor {
+    storage: Arc<dyn ObjectStorage>,
+    blobs: Arc<ContentBlobRepository>,
+    config: ExtractionConfig,
+    ocr: Option<Arc<dyn OcrBackend>>,
+}
+
+impl ContentExtractor {
+    pub fn new(state: &AppState, config: ExtractionConfig) -> Self {
+        let ocr: Option<Arc<dyn OcrBackend>> = match &config.ocr {
+            OcrConfig::Disabled => None,
+            OcrConfig::Tesseract { binary, languages } => Some(Arc::new(TesseractOcr {
+                binary: binary.clone(),
+                languages: languages.clone(),
+            })),
+            OcrConfig::Http { url } => Some(Arc::new(HttpOcr {
+                client: reqwest::Client::new(),
+                url: url.clone(),
+            })),
+        };
+        Self {
+            storage: state.content_storage.clone(),
+            blobs: Arc::new(ContentBlobRepository::new(state.db_pool.pool())),
+            config,
+            ocr,
+        }
+    }
+
+    pub fn with_ocr_backend(mut self, ocr: Arc<dyn OcrBackend>) -> Self {
+        self.ocr = Some(ocr);
+        self
+    }
+
+    /// Whether content of this type is extracted rather than indexed as text.
+    pub fn is_extractable(&self, mime_type: &str) -> bool {
+        DOCUMENT_MIME_TYPES.contains(&mime_type)
+            || (self.ocr.is_some() && IMAGE_MIME_TYPES.contains(&mime_type))
+    }
+
+    /// Extract stored content as Markdown.
+    pub async fn extract(
+        &self,
+        content_id: &str,
+        mime_type: &str,
+    ) -> Result<ExtractedContent, ExtractionError> {
+        let mime_type = mime_type.trim().to_lowercase();
+        if !self.is_extractable(&mime_type) {
+            return Err(ExtractionError::Unsupported(format!(
+                "Content of type {} cannot be extracted",
+                mime_type
+            )));
+        }
+
+        let size = self
+            .storage
+            .get_content_size(content_id)
+            .await
+            .map_err(|e| storage_error(content_id, e))?;
+        if size > self.config.max_bytes {
+            return Err(ExtractionError::Unsupported(format!(
+                "Content {} is {} bytes, over the {} byte extraction limit",
+                content_id, size, self.config.max_bytes
+            )));
+        }
+        let data = self
+            .storage
+            .get_content(content_id)
+            .await
+            .map_err(|e| storage_error(content_id, e))?;
+
+        self.extract_bytes(data, mime_type).await.map_err(|e| {
+            ExtractionError::Failed(format!("Failed to extract {}: {}", content_id, e))
+        })
+    }
+
+    async fn extract_bytes(
+        &self,
+        data: Vec<u8>,
+        mime_type: String,
+    ) -> anyhow::Result<ExtractedContent> {
+        let (text, method) = if IMAGE_MIME_TYPES.contains(&mime_type.as_str()) {
+            let ocr = self
+                .ocr
+                .as_ref()
+                .ok_or_else(|| anyhow::anyhow!("No OCR backend is configured"))?;
+            (
+                ocr.recognize(&data, &mime_type).await?,
+                ExtractionMethod::Ocr,
+            )
+        } else {
+            let mime = mime_type.clone();
+            let text = tokio::task::spawn_blocking(move || {
+                let text = extract_content(&data, &mime, None)?;
+                // A file whose tables cannot be read is indexed from its text
+                let tables = if has_extractable_tables(&mime, None) {
+                    extract_tables(&data, &mime, None, MAX_TABLE_ROWS).unwrap_or_else(|e| {
+                        warn!("Table extraction failed, indexing text only: {}", e);
+                        Vec::new()
+                    })
+                } else {
+                    Vec::new()
+                };
+                anyhow::Ok(with_structured_tables(text, &tables, &mime, None))
+            })
+            .await??;
+            (text, ExtractionMethod::Parsed)
+        };
+
+        Ok(ExtractedContent {
+            markdown: normalize_markdown(&text),
+            mime_type,
+            method,
+        })
+    }
+
+    /// Replace the binary content of documents with its extracted Markdown.
+    /// A document whose content cannot be extracted is indexed by its title
+    /// alone, rather than by the raw bytes of the file.
+    pub(crate) async fn extract_documents(&self, documents: Vec<Document>) -> Vec<Document> {
+        if !self.config.enabled {
+            return documents;
+        }
+
+        let content_ids: Vec<String> = documents
+            .iter()
+            .filter_map(|doc| doc.content_id.clone())
+            .collect();
+        let blobs: HashMap<String, (Option<String>, i64)> =
+            match self.blobs.find_info(&content_ids).await {
+                Ok(blobs) => blobs
+                    .into_iter()
+                    .map(|blob| (blob.id, (blob.content_type, blob.size_bytes)))
+                    .collect(),
+                Err(e) => {
+                    warn!(
+                        "Failed to look up content types, indexing content as is: {}",
+                        e
+                    );
+                    return documents;
+                }
+            };
+
+        stream::iter(documents)
+            .map(|doc| {
+                let blob = doc.content_id.as_ref().and_then(|id| blobs.get(id));
+                let mime_type = blob.and_then(|(content_type, _)| {
+                    binary_mime_type(content_type.as_deref(), &doc.metadata)
+                });
+                let size = blob.map_or(0, |(_, size)| *size);
+                async move {
+                    match mime_type.filter(|mime| self.is_extractable(mime)) {
+                        Some(mime_type) => self.extract_document(doc, mime_type, size).await,
+                        None => doc,
+                    }
+                }
+            })
+            .buffered(self.config.concurrency)
+            .collect()
+            .await
+    }
+
+    async fn extract_document(&self, mut doc: Document, mime_type: String, size: i64) -> Document {
+        let Some(source_content_id) = doc.content_id.clone() else {
+            return doc;
+        };
+
+        let extracted = if size > self.config.max_bytes {
+            Err(format!(
+                "{} bytes is over the {} byte extraction limit",
+                size, self.config.max_bytes
+            ))
+        } else {
+            match self.storage.get_content(&source_content_id).await {
+                Ok(data) => self
+                    .extract_bytes(data, mime_type.clone())
+                    .await
+                    .map_err(|e| e.to_string()),
+                Err(e) => Err(e.to_string()),
+            }
+        };
+
+        let (markdown, extraction) = match extracted {
+            Ok(extracted) => {
+                debug!(
+                    "Extracted {} characters of {} from document {}",
+                    extracted.markdown.len(),
+                    mime_type,
+                    doc.external_id
+                );
+                let extraction = json!({
+                    "source_content_id": source_content_id,
+                    "mime_type": mime_type,
+                    "method": extracted.method,
+                });
+                (extracted.markdown, extraction)
+            }
+            Err(e) => {
+                warn!(
+                    "Failed to extract {} content of document {}, indexing its title only: {}",
+                    mime_type, doc.external_id, e
+                );
+                let extraction = json!({
+                    "source_content_id": source_content_id,
+                    "mime_type": mime_type,
+                    "error": e,
+                });
+                (String::new(), extraction)
+            }
+        };
+
+        match self
+            .storage
+            .store_content_with_type(markdown.as_bytes(), Some("text/markdown"), None)
+            .await
+        {
+            Ok(content_id) => {
+                doc.content_id = Some(content_id);
+                if let Some(metadata) = doc.metadata.as_object_mut() {
+                    metadata.insert(EXTRACTION_METADATA_KEY.to_string(), extraction);
+                }
+            }
+            Err(e) => warn!(
+                "Failed to store extracted content of document {}: {}",
+                doc.external_id, e
+            ),
+        }
+        doc
+    }
+}
+
+fn storage_error(content_id: &str, err: StorageError) -> ExtractionError {
+    match err {
+        StorageError::NotFound(_) => ExtractionError::NotFound(format!("Content {}", content_id)),
+        other => ExtractionError::Failed(other.to_string()),
+    }
+}
+
+/// The type of a document's stored content when it is a file rather than
+/// text. Untyped content is text; generic binary content takes the type the
+/// connector reported for the document.
+fn binary_mime_type(content_type: Option<&str>, metadata: &serde_json::Value) -> Option<String> {
+    let content_type = content_type?.split(';').next()?.trim().to_lowercase();
+    if content_type.starts_with("text/") {
+        return None;
+    }
+    if content_type == "application/octet-stream" {
+        return metadata
+            .get("mime_type")
+            .and_then(|mime| mime.as_str())
+            .map(|mime| mime.trim().to_lowercase());
+    }
+    Some(content_type)
+}
+
+/// Normalize extracted text to tidy Markdown: Unix line endings, no
+/// trailing whitespace, and at most one blank line in a row. Page breaks are
+/// kept, since chunks record the page they start on.
+pub fn normalize_markdown(text: &str) -> String {
+    let text = text.replace("\r\n", "\n").replace('\r', "\n");
+    let mut normalized = String::with_capacity(text.len());
+    let mut blank_lines = 0;
+    for line in text.split('\n') {
+        let line = line.trim_end_matches(|c: char| c.is_whitespace() && c != PAGE_BREAK);
+        if line.is_empty() {
+            blank_lines += 1;
+            if blank_lines > 1 {
+                continue;
+            }
+        } else {
+            blank_lines = 0;
+        }
+        normalized.push_str(line);
+        normalized.push('\n');
+    }
+    normalized.trim_matches('\n').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_markdown_keeps_page_breaks() {
+        let text = "# Title  \r\n\r\n\r\n\r\nFirst page\t\n\x0cSecond page\n\n";
+        assert_eq!(
+            normalize_markdown(text),
+            "# Title\n\nFirst page\n\x0cSecond page"
+        );
+    }
+
+    #[test]
+    fn test_binary_mime_type_prefers_the_stored_type() {
+        let metadata = json!({"mime_type": "application/pdf"});
+        assert_eq!(binary_mime_type(None, &metadata), None);
+        assert_eq!(
+            binary_mime_type(Some("text/plain; charset=utf-8"), &metadata),
+            None
+        );
+        assert_eq!(
+            binary_mime_type(Some("application/octet-stream"), &metadata).as_deref(),
+            Some("application/pdf")
+        );
+        assert_eq!(
+            binary_mime_type(Some("Image/PNG"), &metadata).as_deref(),
+            Some("image/png")
+        );
+    }
+}
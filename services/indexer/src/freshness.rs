@@ -0,0 +1,464 @@
+//! Indexing freshness: how long a connector's change takes to become
+//! searchable, measured from the event being enqueued to the document's
+//! embeddings completing. Each source is held to an SLO, e.g. 95% of changes
+//! searchable within 5 minutes, and an alert is logged when a source falls
+//! below it. The same figures are exported as Prometheus gauges.
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use shared::db::repositories::{FreshnessRepository, SourceFreshness};
+use sqlx::PgPool;
+use sqlx::types::time::OffsetDateTime;
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+use time::Duration as TimeDuration;
+use tracing::{error, info};
+
+const DEFAULT_SLO_SECS: i64 = 300;
+const DEFAULT_SLO_TARGET: f64 = 0.95;
+const DEFAULT_WINDOW_HOURS: i64 = 24;
+
+#[derive(Debug, Clone)]
+pub struct FreshnessConfig {
+    /// Seconds within which a change should become searchable.
+    pub slo_secs: i64,
+    /// Fraction of changes that must meet `slo_secs`.
+    pub target: f64,
+    /// Hours of changes the figures cover.
+    pub window_hours: i64,
+}
+
+impl Default for FreshnessConfig {
+    fn default() -> Self {
+        Self {
+            slo_secs: DEFAULT_SLO_SECS,
+            target: DEFAULT_SLO_TARGET,
+            window_hours: DEFAULT_WINDOW_HOURS,
+        }
+    }
+}
+
+impl FreshnessConfig {
+    pub fn from_env() -> Self {
+        fn env_or<T: std::str::FromStr>(key: &str, default: T) -> T {
+            std::env::var(key)
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default)
+        }
+
+        Self {
+            slo_secs: env_or("INDEXER_FRESHNESS_SLO_SECS", DEFAULT_SLO_SECS).max(1),
+            target: env_or("INDEXER_FRESHNESS_SLO_TARGET", DEFAULT_SLO_TARGET).clamp(0.0, 1.0),
+            window_hours: env_or("INDEXER_FRESHNESS_WINDOW_HOURS", DEFAULT_WINDOW_HOURS).max(1),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SourceFreshnessReport {
+    #[serde(flatten)]
+    pub stats: SourceFreshness,
+    /// Fraction of changes that met the SLO. Pending changes already older
+    /// than the SLO count as misses; younger ones are not counted yet. Unset
+    /// without any counted change.
+    pub slo_attainment: Option<f64>,
+    pub slo_violated: bool,
+}
+
+impl SourceFreshnessReport {
+    fn new(stats: SourceFreshness, config: &FreshnessConfig) -> Self {
+        let counted = stats.samples + stats.pending_over_slo;
+        let slo_attainment = (counted > 0).then(|| stats.within_slo as f64 / counted as f64);
+        Self {
+            slo_violated: slo_attainment.is_some_and(|attainment| attainment < config.target),
+            slo_attainment,
+            stats,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FreshnessReport {
+    pub generated_at: DateTime<Utc>,
+    pub slo_secs: i64,
+    pub slo_target: f64,
+    pub window_hours: i64,
+    /// All sources together.
+    pub overall: SourceFreshnessReport,
+    /// Sources with changes in the window, by name.
+    pub sources: Vec<SourceFreshnessReport>,
+}
+
+/// Split the repository's rows into the per-source reports and the overall
+/// one.
+pub fn build_report(rows: Vec<SourceFreshness>, config: &FreshnessConfig) -> FreshnessReport {
+    let mut overall = None;
+    let mut sources = Vec::new();
+    for row in rows {
+        let report = SourceFreshnessReport::new(row, config);
+        if report.stats.source_id.is_some() {
+            sources.push(report);
+        } else {
+            overall = Some(report);
+        }
+    }
+    let overall = overall.unwrap_or_else(|| {
+        SourceFreshnessReport::new(
+            SourceFreshness {
+                source_id: None,
+                source_name: None,
+                samples: 0,
+                within_slo: 0,
+                pending: 0,
+                pending_over_slo: 0,
+                p50_secs: None,
+                p95_secs: None,
+                p99_secs: None,
+                max_secs: None,
+            },
+            config,
+        )
+    });
+
+    FreshnessReport {
+        generated_at: Utc::now(),
+        slo_secs: config.slo_secs,
+        slo_target: config.target,
+        window_hours: config.window_hours,
+        overall,
+        sources,
+    }
+}
+
+/// Reports freshness and alerts when a source starts or stops violating the
+/// SLO. Clones share which sources are currently violating, so each
+/// violation is alerted once rather than on every check.
+#[derive(Clone)]
+pub struct FreshnessMonitor {
+    repo: Arc<FreshnessRepository>,
+    config: FreshnessConfig,
+    violations: SloViolations,
+}
+
+impl FreshnessMonitor {
+    pub fn new(pool: &PgPool, config: FreshnessConfig) -> Self {
+        Self {
+            repo: Arc::new(FreshnessRepository::new(pool)),
+            config,
+            violations: SloViolations::default(),
+        }
+    }
+
+    pub async fn report(&self) -> Result<FreshnessReport> {
+        let since = OffsetDateTime::now_utc() - TimeDuration::hours(self.config.window_hours);
+        let rows = self
+            .repo
+            .source_freshness(since, self.config.slo_secs)
+            .await?;
+        Ok(build_report(rows, &self.config))
+    }
+
+    /// Build the report and log an alert for each source that started
+    /// violating the SLO since the last check, and a notice for each that
+    /// recovered.
+    pub async fn check(&self) -> Result<FreshnessReport> {
+        let report = self.report().await?;
+        let (started, recovered) = self.violations.update(&report);
+
+        for source in started {
+            error!(
+                source_id = source.stats.source_id.as_deref().unwrap_or_default(),
+                "Indexing freshness SLO violated for source {}: {:.1}% of changes searchable within {}s (target {:.1}%), p95 {}, {} pending past the SLO",
+                source.stats.source_name.as_deref().unwrap_or_default(),
+                source.slo_attainment.unwrap_or_default() * 100.0,
+                self.config.slo_secs,
+                self.config.target * 100.0,
+                format_secs(source.stats.p95_secs),
+                source.stats.pending_over_slo
+            );
+        }
+        for source_id in recovered {
+            info!(
+                source_id = source_id.as_str(),
+                "Indexing freshness SLO recovered for source {}", source_id
+            );
+        }
+
+        Ok(report)
+    }
+}
+
+/// Ids of the sources violating the SLO as of the last check.
+#[derive(Clone, Default)]
+struct SloViolations(Arc<Mutex<HashSet<String>>>);
+
+impl SloViolations {
+    /// Record which sources violate the SLO now. Returns those that started
+    /// violating and the ids of those that stopped. A source without changes
+    /// in the window is no longer violating.
+    fn update<'a>(
+        &self,
+        report: &'a FreshnessReport,
+    ) -> (Vec<&'a SourceFreshnessReport>, Vec<String>) {
+        let now_violating: Vec<&SourceFreshnessReport> = report
+            .sources
+            .iter()
+            .filter(|source| source.slo_violated)
+            .collect();
+        let ids: HashSet<String> = now_violating
+            .iter()
+            .filter_map(|source| source.stats.source_id.clone())
+            .collect();
+
+        let mut violating = self.0.lock().unwrap();
+        let started = now_violating
+            .into_iter()
+            .filter(|source| {
+                source
+                    .stats
+                    .source_id
+                    .as_ref()
+                    .is_some_and(|id| !violating.contains(id))
+            })
+            .collect();
+        let mut recovered: Vec<String> = violating.difference(&ids).cloned().collect();
+        recovered.sort();
+        *violating = ids;
+
+        (started, recovered)
+    }
+}
+
+fn format_secs(secs: Option<f64>) -> String {
+    secs.map(|secs| format!("{:.0}s", secs))
+        .unwrap_or_else(|| "n/a".to_string())
+}
+
+/// Reads one gauge's value for a source, if it has one.
+type GaugeValue = fn(&SourceFreshnessReport) -> Option<f64>;
+
+/// The report as Prometheus text exposition format.
+pub fn render_metrics(report: &FreshnessReport) -> String {
+    let mut out = String::new();
+    let rows: Vec<(String, &SourceFreshnessReport)> = std::iter::once(&report.overall)
+        .chain(&report.sources)
+        .map(|source| (metric_labels(source), source))
+        .collect();
+
+    let _ = writeln!(
+        out,
+        "# HELP omni_indexer_freshness_seconds Seconds from a change being enqueued to it becoming searchable, over the last {} hours.",
+        report.window_hours
+    );
+    let _ = writeln!(out, "# TYPE omni_indexer_freshness_seconds gauge");
+    for (labels, source) in &rows {
+        for (quantile, value) in [
+            ("0.5", source.stats.p50_secs),
+            ("0.95", source.stats.p95_secs),
+            ("0.99", source.stats.p99_secs),
+            ("1", source.stats.max_secs),
+        ] {
+            if let Some(value) = value {
+                let _ = writeln!(
+                    out,
+                    "omni_indexer_freshness_seconds{{{},quantile=\"{}\"}} {}",
+                    labels, quantile, value
+                );
+            }
+        }
+    }
+
+    let gauges: [(&str, &str, GaugeValue); 5] = [
+        (
+            "omni_indexer_freshness_samples",
+            "Changes in the window that became searchable.",
+            |source| Some(source.stats.samples as f64),
+        ),
+        (
+            "omni_indexer_freshness_pending",
+            "Changes in the window not yet searchable.",
+            |source| Some(source.stats.pending as f64),
+        ),
+        (
+            "omni_indexer_freshness_pending_over_slo",
+            "Changes not yet searchable and older than the SLO.",
+            |source| Some(source.stats.pending_over_slo as f64),
+        ),
+        (
+            "omni_indexer_freshness_slo_attainment",
+            "Fraction of changes that became searchable within the SLO.",
+            |source| source.slo_attainment,
+        ),
+        (
+            "omni_indexer_freshness_slo_violation",
+            "1 when attainment is below the SLO target.",
+            |source| Some(if source.slo_violated { 1.0 } else { 0.0 }),
+        ),
+    ];
+    for (name, help, value) in gauges {
+        let _ = writeln!(out, "# HELP {} {}", name, help);
+        let _ = writeln!(out, "# TYPE {} gauge", name);
+        for (labels, source) in &rows {
+            if let Some(value) = value(source) {
+                let _ = writeln!(out, "{}{{{}}} {}", name, labels, value);
+            }
+        }
+    }
+
+    let _ = writeln!(
+        out,
+        "# HELP omni_indexer_freshness_slo_seconds Seconds within which a change should become searchable."
+    );
+    let _ = writeln!(out, "# TYPE omni_indexer_freshness_slo_seconds gauge");
+    let _ = writeln!(
+        out,
+        "omni_indexer_freshness_slo_seconds {}",
+        report.slo_secs
+    );
+    let _ = writeln!(
+        out,
+        "# HELP omni_indexer_freshness_slo_target Fraction of changes that must meet the SLO."
+    );
+    let _ = writeln!(out, "# TYPE omni_indexer_freshness_slo_target gauge");
+    let _ = writeln!(
+        out,
+        "omni_indexer_freshness_slo_target {}",
+        report.slo_target
+    );
+
+    out
+}
+
+/// Labels identifying a source; the overall row is `source_id="all"`.
+fn metric_labels(source: &SourceFreshnessReport) -> String {
+    match &source.stats.source_id {
+        Some(id) => format!(
+            "source_id=\"{}\",source_name=\"{}\"",
+            escape_label(id),
+            escape_label(source.stats.source_name.as_deref().unwrap_or_default())
+        ),
+        None => "source_id=\"all\"".to_string(),
+    }
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(source_id: Option<&str>, samples: i64, within_slo: i64) -> SourceFreshness {
+        SourceFreshness {
+            source_id: source_id.map(str::to_string),
+            source_name: source_id.map(|id| format!("Source \"{}\"", id)),
+            samples,
+            within_slo,
+            pending: 2,
+            pending_over_slo: 1,
+            p50_secs: Some(40.0),
+            p95_secs: Some(250.5),
+            p99_secs: Some(400.0),
+            max_secs: Some(900.0),
+        }
+    }
+
+    #[test]
+    fn test_attainment_counts_overdue_pending_changes_as_misses() {
+        let config = FreshnessConfig::default();
+
+        let report = SourceFreshnessReport::new(stats(Some("a"), 99, 99), &config);
+        assert_eq!(report.slo_attainment, Some(0.99));
+        assert!(!report.slo_violated);
+
+        let report = SourceFreshnessReport::new(stats(Some("a"), 19, 18), &config);
+        assert_eq!(report.slo_attainment, Some(0.9));
+        assert!(report.slo_violated);
+
+        let mut idle = stats(Some("a"), 0, 0);
+        idle.pending_over_slo = 0;
+        let report = SourceFreshnessReport::new(idle, &config);
+        assert_eq!(report.slo_attainment, None);
+        assert!(!report.slo_violated);
+    }
+
+    #[test]
+    fn test_build_report_separates_overall_row() {
+        let report = build_report(
+            vec![
+                stats(Some("a"), 10, 10),
+                stats(Some("b"), 10, 5),
+                stats(None, 20, 15),
+            ],
+            &FreshnessConfig::default(),
+        );
+
+        assert_eq!(report.overall.stats.samples, 20);
+        let ids: Vec<_> = report
+            .sources
+            .iter()
+            .map(|s| s.stats.source_id.as_deref().unwrap())
+            .collect();
+        assert_eq!(ids, vec!["a", "b"]);
+
+        let empty = build_report(vec![], &FreshnessConfig::default());
+        assert_eq!(empty.overall.stats.samples, 0);
+        assert!(empty.sources.is_empty());
+    }
+
+    #[test]
+    fn test_violations_alert_once_until_recovered() {
+        let violations = SloViolations::default();
+        let config = FreshnessConfig::default();
+
+        let report = build_report(vec![stats(Some("a"), 10, 5)], &config);
+        let (started, recovered) = violations.update(&report);
+        assert_eq!(started.len(), 1);
+        assert!(recovered.is_empty());
+
+        let (started, recovered) = violations.update(&report);
+        assert!(started.is_empty());
+        assert!(recovered.is_empty());
+
+        let report = build_report(vec![stats(Some("a"), 100, 100)], &config);
+        let (started, recovered) = violations.update(&report);
+        assert!(started.is_empty());
+        assert_eq!(recovered, vec!["a".to_string()]);
+    }
+
+    #[test]
+    fn test_render_metrics_labels_sources_and_escapes_names() {
+        let report = build_report(
+            vec![stats(Some("a"), 10, 5), stats(None, 10, 5)],
+            &FreshnessConfig::default(),
+        );
+
+        let metrics = render_metrics(&report);
+
+        assert!(metrics.contains(
+            "omni_indexer_freshness_seconds{source_id=\"a\",source_name=\"Source \\\"a\\\"\",quantile=\"0.95\"} 250.5"
+        ));
+        assert!(
+            metrics
+                .contains("omni_indexer_freshness_seconds{source_id=\"all\",quantile=\"1\"} 900")
+        );
+        assert!(metrics.contains(
+            "omni_indexer_freshness_slo_violation{source_id=\"a\",source_name=\"Source \\\"a\\\"\"} 1"
+        ));
+        assert!(metrics.contains("omni_indexer_freshness_slo_seconds 300"));
+        assert_eq!(
+            metrics
+                .lines()
+                .filter(|line| line.starts_with("# TYPE"))
+                .count(),
+            8
+        );
+    }
+}
@@ -0,0 +1,248 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use shared::db::repositories::{EmbeddingProviderRepository, IntegrityRepository, IntegritySample};
+use shared::embedding_queue::EmbeddingQueue;
+use sqlx::PgPool;
+use tracing::{info, warn};
+
+const DEFAULT_EMBEDDING_SLA_HOURS: i64 = 24;
+// Blobs are stored before their event is enqueued; give that window plenty
+// of slack before calling a blob unreferenced.
+const DEFAULT_BLOB_MIN_AGE_SECS: i64 = 3600;
+const DEFAULT_SAMPLE_SIZE: i64 = 20;
+const DEFAULT_REPAIR_BATCH_SIZE: i64 = 10_000;
+
+#[derive(Debug, Clone)]
+pub struct IntegrityConfig {
+    /// How long a document may go without embeddings before it is reported.
+    pub embedding_sla_hours: i64,
+    pub blob_min_age_secs: i64,
+    /// Ids included per finding in the report.
+    pub sample_size: i64,
+    /// Upper bound on rows touched per finding by a single repair run.
+    pub repair_batch_size: i64,
+}
+
+impl Default for IntegrityConfig {
+    fn default() -> Self {
+        Self {
+            embedding_sla_hours: DEFAULT_EMBEDDING_SLA_HOURS,
+            blob_min_age_secs: DEFAULT_BLOB_MIN_AGE_SECS,
+            sample_size: DEFAULT_SAMPLE_SIZE,
+            repair_batch_size: DEFAULT_REPAIR_BATCH_SIZE,
+        }
+    }
+}
+
+impl IntegrityConfig {
+    pub fn from_env() -> Self {
+        let env_or = |key: &str, default: i64| {
+            std::env::var(key)
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default)
+        };
+
+        Self {
+            embedding_sla_hours: env_or(
+                "INDEXER_INTEGRITY_EMBEDDING_SLA_HOURS",
+                DEFAULT_EMBEDDING_SLA_HOURS,
+            ),
+            blob_min_age_secs: env_or(
+                "INDEXER_INTEGRITY_BLOB_MIN_AGE_SECS",
+                DEFAULT_BLOB_MIN_AGE_SECS,
+            ),
+            sample_size: env_or("INDEXER_INTEGRITY_SAMPLE_SIZE", DEFAULT_SAMPLE_SIZE),
+            repair_batch_size: env_or(
+                "INDEXER_INTEGRITY_REPAIR_BATCH_SIZE",
+                DEFAULT_REPAIR_BATCH_SIZE,
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FindingKind {
+    DocumentMissingContent,
+    UnreferencedContentBlob,
+    DocumentMissingEmbeddings,
+    DanglingEmbedding,
+}
+
+/// What `POST /admin/integrity/repair` does about a finding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RepairAction {
+    /// Content is lost; only a re-sync of the owning source can restore it.
+    ResyncSource,
+    /// Mark orphaned so content blob GC deletes it after the retention period.
+    MarkForGc,
+    EnqueueEmbedding,
+    DeleteEmbedding,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IntegrityFinding {
+    pub kind: FindingKind,
+    pub count: i64,
+    pub sample_ids: Vec<String>,
+    pub repair: RepairAction,
+    /// Whether the repair endpoint applies `repair` itself.
+    pub auto_repairable: bool,
+}
+
+impl IntegrityFinding {
+    fn new(kind: FindingKind, sample: IntegritySample, repair: RepairAction) -> Self {
+        Self {
+            kind,
+            count: sample.count,
+            sample_ids: sample.sample_ids,
+            repair,
+            auto_repairable: repair != RepairAction::ResyncSource,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IntegrityReport {
+    pub checked_at: DateTime<Utc>,
+    pub embedding_sla_hours: i64,
+    /// One entry per check, including checks with nothing to report.
+    pub findings: Vec<IntegrityFinding>,
+}
+
+impl IntegrityReport {
+    pub fn total_issues(&self) -> i64 {
+        self.findings.iter().map(|f| f.count).sum()
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct RepairResult {
+    pub blobs_marked_for_gc: u64,
+    pub embeddings_enqueued: u64,
+    pub embeddings_deleted: u64,
+    /// Documents whose content can only be restored by re-syncing.
+    pub documents_needing_resync: i64,
+}
+
+/// Cross-checks documents, content blobs, embeddings and the embedding queue
+/// for states the pipeline should never leave behind.
+pub struct IntegrityChecker {
+    repo: IntegrityRepository,
+    provider_repo: EmbeddingProviderRepository,
+    embedding_queue: EmbeddingQueue,
+    config: IntegrityConfig,
+}
+
+impl IntegrityChecker {
+    pub fn new(pool: &PgPool, embedding_queue: EmbeddingQueue, config: IntegrityConfig) -> Self {
+        Self {
+            repo: IntegrityRepository::new(pool),
+            provider_repo: EmbeddingProviderRepository::new(pool),
+            embedding_queue,
+            config,
+        }
+    }
+
+    pub async fn check(&self) -> Result<IntegrityReport> {
+        let sample_size = self.config.sample_size;
+
+        let missing_embeddings = if self.provider_repo.has_active_provider().await? {
+            self.repo
+                .documents_missing_embeddings(self.config.embedding_sla_hours, sample_size)
+                .await?
+        } else {
+            // Without a provider nothing gets embedded; that is configuration,
+            // not corruption.
+            IntegritySample::default()
+        };
+
+        let findings = vec![
+            IntegrityFinding::new(
+                FindingKind::DocumentMissingContent,
+                self.repo.documents_missing_content(sample_size).await?,
+                RepairAction::ResyncSource,
+            ),
+            IntegrityFinding::new(
+                FindingKind::UnreferencedContentBlob,
+                self.repo
+                    .unreferenced_blobs(self.config.blob_min_age_secs, sample_size)
+                    .await?,
+                RepairAction::MarkForGc,
+            ),
+            IntegrityFinding::new(
+                FindingKind::DocumentMissingEmbeddings,
+                missing_embeddings,
+                RepairAction::EnqueueEmbedding,
+            ),
+            IntegrityFinding::new(
+                FindingKind::DanglingEmbedding,
+                self.repo.dangling_embeddings(sample_size).await?,
+                RepairAction::DeleteEmbedding,
+            ),
+        ];
+
+        Ok(IntegrityReport {
+            checked_at: Utc::now(),
+            embedding_sla_hours: self.config.embedding_sla_hours,
+            findings,
+        })
+    }
+
+    /// Apply every auto-repairable action, up to `repair_batch_size` rows each.
+    pub async fn repair(&self) -> Result<RepairResult> {
+        let batch = self.config.repair_batch_size;
+        let mut result = RepairResult {
+            blobs_marked_for_gc: self
+                .repo
+                .mark_unreferenced_blobs(self.config.blob_min_age_secs, batch)
+                .await?,
+            embeddings_deleted: self.repo.delete_dangling_embeddings(batch).await?,
+            documents_needing_resync: self.repo.documents_missing_content(1).await?.count,
+            ..Default::default()
+        };
+
+        if self.provider_repo.has_active_provider().await? {
+            let document_ids = self
+                .repo
+                .documents_missing_embeddings(self.config.embedding_sla_hours, batch)
+                .await?
+                .sample_ids;
+            if !document_ids.is_empty() {
+                result.embeddings_enqueued = self
+                    .embedding_queue
+                    .enqueue_batch(document_ids)
+                    .await?
+                    .len() as u64;
+            }
+        }
+
+        info!(
+            "Integrity repair: blobs_marked_for_gc={}, embeddings_enqueued={}, embeddings_deleted={}, documents_needing_resync={}",
+            result.blobs_marked_for_gc,
+            result.embeddings_enqueued,
+            result.embeddings_deleted,
+            result.documents_needing_resync
+        );
+
+        Ok(result)
+    }
+
+    /// Scheduled entry point: check and log, leaving repairs to an operator.
+    pub async fn run_scheduled(&self) -> Result<IntegrityReport> {
+        let report = self.check().await?;
+        for finding in report.findings.iter().filter(|f| f.count > 0) {
+            warn!(
+                "Integrity check: {} {:?} (repair: {:?}, e.g. {:?})",
+                finding.count, finding.kind, finding.repair, finding.sample_ids
+            );
+        }
+        if report.total_issues() == 0 {
+            info!("Integrity check found no issues");
+        }
+        Ok(report)
+    }
+}
@@ -0,0 +1,73 @@
+use serde_json::Value as JsonValue;
+
+/// Code recorded when no language can be detected reliably.
+pub const UNDETERMINED_LANGUAGE: &str = "und";
+
+/// Metadata key holding a document's detected language. The search index
+/// stems the content of documents in supported languages by this key.
+pub const LANGUAGE_METADATA_KEY: &str = "language";
+
+/// Only a prefix of each document is inspected: detection cost grows with
+/// input length while accuracy plateaus after a few thousand characters.
+const DETECTION_SAMPLE_CHARS: usize = 4096;
+
+/// Below this many characters trigram detection is mostly noise.
+const MIN_DETECTION_CHARS: usize = 40;
+
+/// Detect the primary language of `content`, returning an ISO 639-3 code or
+/// [`UNDETERMINED_LANGUAGE`] when the text is too short or ambiguous.
+pub fn detect_primary_language(content: &str) -> &'static str {
+    let sample_end = content
+        .char_indices()
+        .nth(DETECTION_SAMPLE_CHARS)
+        .map(|(idx, _)| idx)
+        .unwrap_or(content.len());
+    let sample = content[..sample_end].trim();
+
+    if sample.chars().filter(|c| c.is_alphabetic()).count() < MIN_DETECTION_CHARS {
+        return UNDETERMINED_LANGUAGE;
+    }
+
+    match whatlang::detect(sample) {
+        Some(info) if info.is_reliable() => info.lang().code(),
+        _ => UNDETERMINED_LANGUAGE,
+    }
+}
+
+/// The language recorded in a document's metadata, if any.
+pub fn metadata_language(metadata: &JsonValue) -> Option<&str> {
+    metadata.get(LANGUAGE_METADATA_KEY).and_then(|l| l.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detects_english_and_german() {
+        assert_eq!(
+            detect_primary_language(
+                "The quarterly report summarises revenue growth across all regions and \
+                 outlines the hiring plan for the engineering organisation next year."
+            ),
+            "eng"
+        );
+        assert_eq!(
+            detect_primary_language(
+                "Der Quartalsbericht fasst das Umsatzwachstum in allen Regionen zusammen \
+                 und beschreibt die Einstellungsplanung für das kommende Jahr."
+            ),
+            "deu"
+        );
+    }
+
+    #[test]
+    fn test_short_or_non_textual_content_is_undetermined() {
+        assert_eq!(detect_primary_language(""), UNDETERMINED_LANGUAGE);
+        assert_eq!(detect_primary_language("Q3 roadmap"), UNDETERMINED_LANGUAGE);
+        assert_eq!(
+            detect_primary_language("1234 5678 9012 3456 7890 1234 5678 9012 3456 7890"),
+            UNDETERMINED_LANGUAGE
+        );
+    }
+}
@@ -1,6 +1,24 @@
+pub mod blocklist;
+pub mod code;
+pub mod document_versions;
+pub mod embedding_migration;
+pub mod ephemeral;
 pub mod error;
+pub mod extraction;
+pub mod freshness;
+pub mod integrity;
+pub mod language;
+pub mod link_checker;
+pub mod normalization;
 pub mod people_extractor;
+pub mod pii;
 pub mod queue_processor;
+pub mod ranking_training;
+pub mod retention;
+pub mod source_reindex;
+pub mod storage_report;
+pub mod term_dictionary;
+pub mod vector_index;
 
 pub use error::{IndexerError, Result};
 pub use queue_processor::QueueProcessor;
@@ -15,26 +33,65 @@ pub use shared::db::pool::DatabasePool;
 use std::sync::Arc;
 
 use axum::{
-    extract::{Path, State},
+    extract::{DefaultBodyLimit, Multipart, Path, Query, State},
+    http::{StatusCode, header},
     middleware,
     response::Json,
     routing::{delete, get, post, put},
 };
+use blocklist::{CreateBlockRuleRequest, QuarantineQuery, UpdateBlockRuleRequest};
+use document_versions::{DocumentVersionResponse, VersionQuery, VersionRetentionConfig, line_diff};
+use embedding_migration::{
+    EmbeddingMigrationRequest, EmbeddingMigrationStatusResponse, EmbeddingMigrator,
+};
+use ephemeral::{
+    EphemeralDeleteQuery, EphemeralListQuery, EphemeralUploadConfig, EphemeralUploadResponse,
+};
 use error::Result as IndexerResult;
+use extraction::{ContentExtractor, ExtractRequest, ExtractedContent, ExtractionConfig};
+use freshness::{FreshnessConfig, FreshnessMonitor, FreshnessReport};
+use integrity::{IntegrityChecker, IntegrityConfig, IntegrityReport, RepairResult};
+use link_checker::{LinkCheckConfig, LinkCheckRunResult, LinkChecker, LinkReport};
+use pii::UpdatePiiPolicyRequest;
+use ranking_training::{RankingTrainer, RankingTrainingConfig, RankingTrainingResult};
+use retention::{
+    PurgedDocumentsQuery, RetentionConfig, RetentionEnforcer, RetentionRunResult,
+    UpdateRetentionPolicyRequest,
+};
 use serde_json::json;
 use shared::{
-    IndexerConfig,
-    db::repositories::{DocumentRepository, OrphanStats},
+    DatabaseError, EmbeddingQueueItem, IndexerConfig, QuarantinedChunk,
+    audit::{self, Actor, AuditAction, AuditEvent},
+    db::repositories::{
+        BlockRuleUpdate, CorpusStatsRepository, DocumentPipelineTrace, DocumentRepository,
+        DocumentUpsertOutcome, DocumentVersion, DocumentVersionRepository, EmbeddingMigration,
+        EphemeralDocument, EphemeralDocumentRepository, IngestionBlockRule,
+        IngestionBlockRuleRepository, OrphanStats, PiiDetector, PipelineTraceRepository,
+        QuarantinedEvent, ReclaimedStorageStats, RetentionPurge, RetentionPurgedDocument,
+        SourceLanguageStats, SourcePiiPolicy, SourcePiiPolicyRepository, SourceReindexJob,
+        SourceRetentionPolicy, SourceRetentionRepository, SourceRetentionSummary,
+        StoredRankingProfile, TermDictionaryRun, UserRepository, VectorIndexBuild,
+    },
+    db::row_level_security,
+    http_security::HttpSecurityConfig,
     models::Document,
+    search_cache,
+    service_auth::internal::InternalAuthConfig,
     storage::gc::{ContentBlobGC, GCConfig, GCResult},
     telemetry::{self, TelemetryConfig},
+    traits::Repository,
+    typeahead_updates::{self, TitleUpdate},
 };
+use source_reindex::{SourceReindexStatusResponse, SourceReindexer};
 use sqlx::types::time::OffsetDateTime;
+use std::collections::HashMap;
 use std::net::SocketAddr;
+use storage_report::{StorageAnalysisResult, StorageAnalyzer, StorageReport, StorageReportConfig};
+use term_dictionary::{TermDictionaryConfig, TermDictionaryRefreshQuery, TermDictionaryStatus};
 use tower::ServiceBuilder;
-use tower_http::cors::CorsLayer;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 use ulid::Ulid;
+use vector_index::{VectorIndexBuildRequest, VectorIndexBuildStatusResponse, VectorIndexBuilder};
 
 #[derive(Clone)]
 pub struct AppState {
@@ -43,6 +100,8 @@ pub struct AppState {
     pub ai_client: AIClient,
     pub content_storage: Arc<dyn shared::ObjectStorage>,
     pub embedding_queue: shared::embedding_queue::EmbeddingQueue,
+    /// Read documents on behalf of a user as the document reader role.
+    pub row_level_security: bool,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -55,6 +114,17 @@ pub struct CreateDocumentRequest {
     pub permissions: Value,
 }
 
+/// Body of `PUT /documents`: create or replace the document keyed on
+/// (source_id, external_id). With `expected_updated_at` the write only goes
+/// through if the stored document still has that `updated_at`.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct UpsertDocumentRequest {
+    #[serde(flatten)]
+    pub document: CreateDocumentRequest,
+    #[serde(default, with = "time::serde::iso8601::option")]
+    pub expected_updated_at: Option<OffsetDateTime>,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct UpdateDocumentRequest {
     pub title: Option<String>,
@@ -86,19 +156,135 @@ pub struct BulkDocumentResponse {
 pub fn create_app(state: AppState) -> Router {
     Router::new()
         .route("/health", get(health_check))
+        .route("/metrics", get(metrics))
         .route("/debug", post(debug_create_document))
-        .route("/documents", post(create_document))
+        .route("/documents", post(create_document).put(upsert_document))
         .route("/documents/bulk", post(bulk_documents))
         .route("/documents/:id", get(get_document))
         .route("/documents/:id", put(update_document))
         .route("/documents/:id", delete(delete_document))
+        .route("/documents/:id/versions", get(list_document_versions))
+        .route("/extract", post(extract_content))
+        .route(
+            "/documents/:id/pipeline-trace",
+            get(get_document_pipeline_trace),
+        )
+        .route(
+            "/documents/:id/versions/:version",
+            get(get_document_version),
+        )
+        .route(
+            "/ephemeral-documents",
+            get(list_ephemeral_documents)
+                .post(upload_ephemeral_document)
+                .layer(DefaultBodyLimit::max(
+                    EphemeralUploadConfig::from_env().max_upload_bytes + 64 * 1024,
+                )),
+        )
+        .route(
+            "/ephemeral-documents/:id",
+            delete(delete_ephemeral_document),
+        )
+        .route(
+            "/sources/:id/reindex",
+            get(list_source_reindex_jobs).post(start_source_reindex),
+        )
+        .route("/sources/:id/reindex/:job_id", get(get_source_reindex_job))
+        .route(
+            "/sources/:id/pii-policy",
+            get(get_source_pii_policy)
+                .put(update_source_pii_policy)
+                .delete(delete_source_pii_policy),
+        )
+        .route(
+            "/sources/:id/retention-policy",
+            get(get_source_retention_policy)
+                .put(update_source_retention_policy)
+                .delete(delete_source_retention_policy),
+        )
+        .route("/sources/:id/retention-purges", get(list_retention_purges))
+        .route(
+            "/sources/:id/retention-purges/:purge_id/documents",
+            get(list_retention_purged_documents),
+        )
         .route("/admin/gc/run", post(run_gc))
         .route("/admin/gc/stats", get(gc_stats))
+        .route("/admin/gc/reclaimed", get(gc_reclaimed))
+        .route(
+            "/admin/document-versions/prune",
+            post(prune_document_versions),
+        )
+        .route("/admin/language-stats", get(language_stats))
+        .route(
+            "/admin/language-stats/refresh",
+            post(refresh_language_stats),
+        )
+        .route("/admin/storage-report", get(storage_report))
+        .route("/admin/storage-report/run", post(run_storage_analysis))
+        .route("/admin/freshness", get(freshness_report))
+        .route("/admin/ranking-profile", get(active_ranking_profile))
+        .route("/admin/ranking-profile/train", post(train_ranking_profile))
+        .route(
+            "/admin/term-dictionary/refresh",
+            post(refresh_term_dictionary),
+        )
+        .route("/admin/term-dictionary/status", get(term_dictionary_status))
         .route("/admin/reindex-embeddings", post(reindex_embeddings))
+        .route(
+            "/admin/embeddings/quarantine",
+            get(list_embedding_quarantine),
+        )
+        .route(
+            "/admin/embeddings/quarantine/items/:id/retry",
+            post(retry_quarantined_item),
+        )
+        .route(
+            "/admin/embeddings/quarantine/chunks/:id/retry",
+            post(retry_quarantined_chunk),
+        )
+        .route(
+            "/admin/vector-indexes/builds",
+            get(list_vector_index_builds).post(start_vector_index_build),
+        )
+        .route(
+            "/admin/vector-indexes/builds/:id",
+            get(get_vector_index_build),
+        )
+        .route(
+            "/admin/embedding-migrations",
+            get(list_embedding_migrations).post(start_embedding_migration),
+        )
+        .route(
+            "/admin/embedding-migrations/:id",
+            get(get_embedding_migration),
+        )
+        .route(
+            "/admin/embedding-migrations/:id/cancel",
+            post(cancel_embedding_migration),
+        )
+        .route("/admin/integrity", get(integrity_check))
+        .route("/admin/integrity/repair", post(integrity_repair))
+        .route("/admin/link-report", get(link_report))
+        .route("/admin/link-check/run", post(run_link_check))
+        .route("/admin/retention", get(retention_report))
+        .route("/admin/retention/run", post(run_retention))
+        .route(
+            "/admin/ingestion-blocklist/rules",
+            get(list_block_rules).post(create_block_rule),
+        )
+        .route(
+            "/admin/ingestion-blocklist/rules/:id",
+            put(update_block_rule).delete(delete_block_rule),
+        )
+        .route(
+            "/admin/ingestion-blocklist/quarantine",
+            get(list_quarantined_events),
+        )
         .layer(
             ServiceBuilder::new()
                 .layer(middleware::from_fn(telemetry::middleware::trace_layer))
-                .layer(CorsLayer::permissive()),
+                .layer(HttpSecurityConfig::from_env("INDEXER").layer())
+                .layer(InternalAuthConfig::from_env("INDEXER").layer()),
         )
         .with_state(state)
 }
@@ -127,6 +313,7 @@ async fn health_check(State(state): State<AppState>) -> IndexerResult<Json<Value
 
 async fn create_document(
     State(state): State<AppState>,
+    actor: Actor,
     Json(request): Json<CreateDocumentRequest>,
 ) -> IndexerResult<Json<Document>> {
     let document_id = Ulid::new().to_string();
@@ -158,11 +345,77 @@ async fn create_document(
 
     let repo = DocumentRepository::new(state.db_pool.pool());
     let document = repo.create(doc).await?;
+    document_changed(&state, &document).await;
+    audit_document(&state, actor, AuditAction::DocumentIndexed, &document).await;
 
     info!("Created document: {}", document_id);
     Ok(Json(document))
 }
 
+async fn upsert_document(
+    State(state): State<AppState>,
+    actor: Actor,
+    Json(request): Json<UpsertDocumentRequest>,
+) -> IndexerResult<(StatusCode, Json<Document>)> {
+    let UpsertDocumentRequest {
+        document: request,
+        expected_updated_at,
+    } = request;
+    let now = OffsetDateTime::now_utc();
+
+    let content_id = state
+        .content_storage
+        .store_content_with_type(request.content.as_bytes(), Some("text/plain"), None)
+        .await
+        .map_err(|e| error::IndexerError::Internal(format!("Failed to store content: {}", e)))?;
+
+    let doc = Document {
+        id: Ulid::new().to_string(),
+        source_id: request.source_id,
+        external_id: request.external_id,
+        title: request.title,
+        content_id: Some(content_id),
+        content_type: Some("text/plain".to_string()),
+        file_size: None,
+        file_extension: None,
+        url: None,
+        metadata: request.metadata,
+        permissions: request.permissions,
+        attributes: serde_json::json!({}),
+        created_at: now,
+        updated_at: now,
+        last_indexed_at: now,
+    };
+
+    let repo = DocumentRepository::new(state.db_pool.pool());
+    match repo
+        .upsert_if_unmodified(doc, &request.content, expected_updated_at)
+        .await?
+    {
+        DocumentUpsertOutcome::Created(document) => {
+            document_changed(&state, &document).await;
+            audit_document(&state, actor, AuditAction::DocumentIndexed, &document).await;
+            info!("Created document: {}", document.id);
+            Ok((StatusCode::CREATED, Json(document)))
+        }
+        DocumentUpsertOutcome::Updated(document) => {
+            document_changed(&state, &document).await;
+            audit_document(&state, actor, AuditAction::DocumentIndexed, &document).await;
+            info!("Updated document: {}", document.id);
+            Ok((StatusCode::OK, Json(document)))
+        }
+        DocumentUpsertOutcome::PreconditionFailed(Some(current)) => {
+            Err(IndexerError::PreconditionFailed(format!(
+                "Document {} was modified at {}",
+                current.id, current.updated_at
+            )))
+        }
+        DocumentUpsertOutcome::PreconditionFailed(None) => Err(IndexerError::PreconditionFailed(
+            "Document does not exist".to_string(),
+        )),
+    }
+}
+
 async fn get_document(
     State(state): State<AppState>,
     Path(id): Path<String>,
@@ -179,6 +432,7 @@ async fn get_document(
 
 async fn update_document(
     State(state): State<AppState>,
+    actor: Actor,
     Path(id): Path<String>,
     Json(request): Json<UpdateDocumentRequest>,
 ) -> IndexerResult<Json<Document>> {
@@ -208,6 +462,8 @@ async fn update_document(
 
     match updated_doc {
         Some(doc) => {
+            document_changed(&state, &doc).await;
+            audit_document(&state, actor, AuditAction::DocumentIndexed, &doc).await;
             info!("Updated document: {}", id);
             Ok(Json(doc))
         }
@@ -218,19 +474,100 @@ async fn update_document(
     }
 }
 
+async fn list_document_versions(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> IndexerResult<Json<Vec<DocumentVersion>>> {
+    if DocumentRepository::new(state.db_pool.pool())
+        .find_by_id(&id)
+        .await?
+        .is_none()
+    {
+        return Err(IndexerError::NotFound(format!("Document {} not found", id)));
+    }
+
+    let versions = DocumentVersionRepository::new(state.db_pool.pool())
+        .list(&id)
+        .await?;
+    Ok(Json(versions))
+}
+
+/// The document's path through ingestion: connector events, content blob,
+/// embedding queue items and stored chunks.
+async fn get_document_pipeline_trace(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> IndexerResult<Json<DocumentPipelineTrace>> {
+    PipelineTraceRepository::new(state.db_pool.pool())
+        .trace_document(&id)
+        .await?
+        .map(Json)
+        .ok_or_else(|| IndexerError::NotFound(format!("Document {} not found", id)))
+}
+
+async fn get_document_version(
+    State(state): State<AppState>,
+    Path((id, version)): Path<(String, i32)>,
+    Query(query): Query<VersionQuery>,
+) -> IndexerResult<Json<DocumentVersionResponse>> {
+    let repo = DocumentVersionRepository::new(state.db_pool.pool());
+    let document_version = repo.find(&id, version).await?.ok_or_else(|| {
+        IndexerError::NotFound(format!("Version {} of document {} not found", version, id))
+    })?;
+    let content = version_content(&state, &document_version).await?;
+
+    let diff = match query.diff_from {
+        Some(from) => {
+            let base = repo.find(&id, from).await?.ok_or_else(|| {
+                IndexerError::NotFound(format!("Version {} of document {} not found", from, id))
+            })?;
+            let base_content = version_content(&state, &base).await?;
+            Some(line_diff(
+                base_content.as_deref().unwrap_or_default(),
+                content.as_deref().unwrap_or_default(),
+            ))
+        }
+        None => None,
+    };
+
+    Ok(Json(DocumentVersionResponse {
+        version: document_version,
+        content,
+        diff_from: query.diff_from,
+        diff,
+    }))
+}
+
+async fn version_content(
+    state: &AppState,
+    version: &DocumentVersion,
+) -> IndexerResult<Option<String>> {
+    let Some(content_id) = &version.content_id else {
+        return Ok(None);
+    };
+    let content = state
+        .content_storage
+        .get_text(content_id)
+        .await
+        .map_err(|e| IndexerError::Internal(format!("Failed to load content: {}", e)))?;
+    Ok(Some(content))
+}
+
 async fn delete_document(
     State(state): State<AppState>,
+    actor: Actor,
     Path(id): Path<String>,
 ) -> IndexerResult<Json<Value>> {
     let repo = DocumentRepository::new(state.db_pool.pool());
+    let not_found = || error::IndexerError::NotFound(format!("Document {} not found", id));
+    let document = repo.find_by_id(&id).await?.ok_or_else(not_found)?;
     let deleted = repo.delete(&id).await?;
 
     if !deleted {
-        return Err(error::IndexerError::NotFound(format!(
-            "Document {} not found",
-            id
-        )));
+        return Err(not_found());
     }
+    document_changed(&state, &document).await;
+    audit_document(&state, actor, AuditAction::DocumentDeleted, &document).await;
 
     info!("Deleted document: {}", id);
     Ok(Json(json!({
@@ -239,8 +576,263 @@ async fn delete_document(
     })))
 }
 
+struct EphemeralUpload {
+    user_id: String,
+    chat_id: Option<String>,
+    ttl_seconds: Option<i64>,
+    filename: String,
+    mime_type: String,
+    data: Vec<u8>,
+}
+
+async fn parse_ephemeral_upload(
+    mut multipart: Multipart,
+    max_upload_bytes: usize,
+) -> IndexerResult<EphemeralUpload> {
+    let mut user_id = None;
+    let mut chat_id = None;
+    let mut ttl_seconds = None;
+    let mut file = None;
+
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| IndexerError::BadRequest(format!("Failed to read multipart field: {}", e)))?
+    {
+        let name = field.name().unwrap_or_default().to_string();
+        match name.as_str() {
+            "user_id" | "chat_id" | "ttl_seconds" => {
+                let value = field
+                    .text()
+                    .await
+                    .map_err(|e| IndexerError::BadRequest(format!("Invalid {}: {}", name, e)))?;
+                let value = value.trim().to_string();
+                if value.is_empty() {
+                    continue;
+                }
+                match name.as_str() {
+                    "user_id" => user_id = Some(value),
+                    "chat_id" => chat_id = Some(value),
+                    _ => {
+                        ttl_seconds = Some(value.parse::<i64>().map_err(|_| {
+                            IndexerError::BadRequest(format!("Invalid ttl_seconds: {}", value))
+                        })?)
+                    }
+                }
+            }
+            "file" => {
+                let filename = field.file_name().unwrap_or("upload").to_string();
+                let mime_type = field
+                    .content_type()
+                    .unwrap_or("application/octet-stream")
+                    .to_string();
+                let data = field
+                    .bytes()
+                    .await
+                    .map_err(|e| IndexerError::BadRequest(format!("Failed to read file: {}", e)))?;
+                if data.len() > max_upload_bytes {
+                    return Err(IndexerError::BadRequest(format!(
+                        "Upload too large: {} bytes exceeds {} byte limit",
+                        data.len(),
+                        max_upload_bytes
+                    )));
+                }
+                file = Some((filename, mime_type, data.to_vec()));
+            }
+            _ => {}
+        }
+    }
+
+    let (filename, mime_type, data) =
+        file.ok_or_else(|| IndexerError::BadRequest("Missing file".to_string()))?;
+    Ok(EphemeralUpload {
+        user_id: user_id.ok_or_else(|| IndexerError::BadRequest("Missing user_id".to_string()))?,
+        chat_id,
+        ttl_seconds,
+        filename,
+        mime_type,
+        data,
+    })
+}
+
+async fn upload_ephemeral_document(
+    State(state): State<AppState>,
+    multipart: Multipart,
+) -> IndexerResult<Json<EphemeralUploadResponse>> {
+    let config = EphemeralUploadConfig::from_env();
+    let upload = parse_ephemeral_upload(multipart, config.max_upload_bytes).await?;
+    let ttl_seconds = config
+        .ttl_seconds(upload.ttl_seconds)
+        .map_err(IndexerError::BadRequest)?;
+
+    let pool = state.db_pool.pool();
+    let user = UserRepository::new(pool)
+        .find_by_id(upload.user_id.clone())
+        .await?
+        .ok_or_else(|| IndexerError::NotFound(format!("User {} not found", upload.user_id)))?;
+
+    let ephemeral_repo = EphemeralDocumentRepository::new(pool);
+    if let Some(chat_id) = &upload.chat_id
+        && !ephemeral_repo
+            .chat_belongs_to_user(chat_id, &upload.user_id)
+            .await?
+    {
+        return Err(IndexerError::NotFound(format!(
+            "Chat {} not found",
+            chat_id
+        )));
+    }
+
+    // Matches the built-in extractor's default spreadsheet row limit
+    const MAX_UPLOAD_TABLE_ROWS: usize = 1000;
+
+    let file_size = upload.data.len() as i64;
+    let text = {
+        let (data, mime_type, filename) = (
+            upload.data,
+            upload.mime_type.clone(),
+            upload.filename.clone(),
+        );
+        tokio::task::spawn_blocking(move || {
+            let text =
+                shared::content_extractor::extract_content(&data, &mime_type, Some(&filename))?;
+            let tables = shared::content_extractor::extract_tables(
+                &data,
+                &mime_type,
+                Some(&filename),
+                MAX_UPLOAD_TABLE_ROWS,
+            )
+            .unwrap_or_else(|e| {
+                warn!("Table extraction failed for {}: {}", filename, e);
+                Vec::new()
+            });
+            anyhow::Ok(shared::content_extractor::with_structured_tables(
+                text,
+                &tables,
+                &mime_type,
+                Some(&filename),
+            ))
+        })
+        .await
+        .map_err(|e| IndexerError::Internal(format!("Content extraction task failed: {}", e)))?
+        .map_err(|e| IndexerError::BadRequest(format!("Could not extract text: {}", e)))?
+    };
+    if text.trim().is_empty() {
+        return Err(IndexerError::BadRequest(format!(
+            "No text could be extracted from {}",
+            upload.filename
+        )));
+    }
+
+    let source_id = ephemeral_repo.ensure_upload_source(&upload.user_id).await?;
+    let content_id = state
+        .content_storage
+        .store_content_with_type(text.as_bytes(), Some("text/plain"), None)
+        .await
+        .map_err(|e| IndexerError::Internal(format!("Failed to store content: {}", e)))?;
+
+    let document_id = Ulid::new().to_string();
+    let now = OffsetDateTime::now_utc();
+    let doc = Document {
+        id: document_id.clone(),
+        source_id: source_id.clone(),
+        external_id: document_id.clone(),
+        title: upload.filename.clone(),
+        content_id: Some(content_id),
+        content_type: Some(upload.mime_type.clone()),
+        file_size: Some(file_size),
+        file_extension: std::path::Path::new(&upload.filename)
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase()),
+        url: None,
+        metadata: json!({
+            "mime_type": upload.mime_type,
+            "size": file_size.to_string(),
+            (language::LANGUAGE_METADATA_KEY): language::detect_primary_language(&text),
+        }),
+        permissions: ephemeral::uploader_permissions(&user.email),
+        attributes: json!({}),
+        created_at: now,
+        updated_at: now,
+        last_indexed_at: now,
+    };
+    // Written with its text so full-text search finds the upload right away,
+    // before its embeddings are ready
+    let document_repo = DocumentRepository::new(pool);
+    let doc = document_repo.create(doc).await?;
+    document_repo.update(&document_id, doc, &text).await?;
+
+    let document = ephemeral_repo
+        .create(
+            &document_id,
+            &upload.user_id,
+            upload.chat_id.as_deref(),
+            &upload.filename,
+            now + std::time::Duration::from_secs(ttl_seconds as u64),
+        )
+        .await?;
+    state
+        .embedding_queue
+        .enqueue_with_priority(document_id.clone(), ephemeral::EMBEDDING_PRIORITY)
+        .await
+        .map_err(|e| IndexerError::Internal(format!("Failed to queue embeddings: {}", e)))?;
+
+    info!(
+        "Indexed ephemeral upload {} ({}) for user {}, expires in {}s",
+        document_id, upload.filename, upload.user_id, ttl_seconds
+    );
+    Ok(Json(EphemeralUploadResponse {
+        document,
+        source_id,
+        content_type: upload.mime_type,
+        file_size,
+        extracted_chars: text.chars().count(),
+    }))
+}
+
+async fn list_ephemeral_documents(
+    State(state): State<AppState>,
+    Query(query): Query<EphemeralListQuery>,
+) -> IndexerResult<Json<Vec<EphemeralDocument>>> {
+    let pool = state.db_pool.pool();
+    let user = UserRepository::new(pool)
+        .find_by_id(query.user_id.clone())
+        .await?
+        .ok_or_else(|| IndexerError::NotFound(format!("User {} not found", query.user_id)))?;
+    let documents = EphemeralDocumentRepository::new(pool)
+        .with_row_level_security(state.row_level_security)
+        .list(&user.id, &user.email, query.chat_id.as_deref())
+        .await?;
+    Ok(Json(documents))
+}
+
+async fn delete_ephemeral_document(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(query): Query<EphemeralDeleteQuery>,
+) -> IndexerResult<Json<Value>> {
+    let pool = state.db_pool.pool();
+    let not_found = || IndexerError::NotFound(format!("Ephemeral document {} not found", id));
+    let document = EphemeralDocumentRepository::new(pool)
+        .find(&id)
+        .await?
+        .ok_or_else(not_found)?;
+    if document.user_id != query.user_id {
+        return Err(not_found());
+    }
+
+    DocumentRepository::new(pool).delete(&id).await?;
+
+    info!("Deleted ephemeral document: {}", id);
+    Ok(Json(json!({
+        "message": "Document deleted successfully",
+        "id": id
+    })))
+}
+
 async fn bulk_documents(
     State(state): State<AppState>,
+    actor: Actor,
     Json(request): Json<BulkDocumentRequest>,
 ) -> IndexerResult<Json<BulkDocumentResponse>> {
     let mut success_count = 0;
@@ -251,14 +843,14 @@ async fn bulk_documents(
         let result = match operation.operation.as_str() {
             "create" => {
                 if let Some(document) = operation.document {
-                    process_create_operation(&state, document).await
+                    process_create_operation(&state, &actor, document).await
                 } else {
                     Err(anyhow::anyhow!("Create operation missing document data"))
                 }
             }
             "update" => {
                 if let (Some(doc_id), Some(updates)) = (operation.document_id, operation.updates) {
-                    process_update_operation(&state, doc_id, updates).await
+                    process_update_operation(&state, &actor, doc_id, updates).await
                 } else {
                     Err(anyhow::anyhow!(
                         "Update operation missing document_id or updates"
@@ -267,7 +859,7 @@ async fn bulk_documents(
             }
             "delete" => {
                 if let Some(doc_id) = operation.document_id {
-                    process_delete_operation(&state, doc_id).await
+                    process_delete_operation(&state, &actor, doc_id).await
                 } else {
                     Err(anyhow::anyhow!("Delete operation missing document_id"))
                 }
@@ -301,6 +893,7 @@ async fn bulk_documents(
 
 async fn process_create_operation(
     state: &AppState,
+    actor: &Actor,
     request: CreateDocumentRequest,
 ) -> anyhow::Result<()> {
     let document_id = Ulid::new().to_string();
@@ -331,13 +924,22 @@ async fn process_create_operation(
     };
 
     let repo = DocumentRepository::new(state.db_pool.pool());
-    repo.create(doc).await?;
+    let document = repo.create(doc).await?;
+    document_changed(state, &document).await;
+    audit_document(
+        state,
+        actor.clone(),
+        AuditAction::DocumentIndexed,
+        &document,
+    )
+    .await;
 
     Ok(())
 }
 
 async fn process_update_operation(
     state: &AppState,
+    actor: &Actor,
     id: String,
     request: UpdateDocumentRequest,
 ) -> anyhow::Result<()> {
@@ -363,24 +965,70 @@ async fn process_update_operation(
         )
         .await?;
 
-    if updated.is_none() {
+    let Some(document) = updated else {
         return Err(anyhow::anyhow!("Document {} not found", id));
-    }
+    };
+    document_changed(state, &document).await;
+    audit_document(
+        state,
+        actor.clone(),
+        AuditAction::DocumentIndexed,
+        &document,
+    )
+    .await;
 
     Ok(())
 }
 
-async fn process_delete_operation(state: &AppState, id: String) -> anyhow::Result<()> {
+async fn process_delete_operation(
+    state: &AppState,
+    actor: &Actor,
+    id: String,
+) -> anyhow::Result<()> {
     let repo = DocumentRepository::new(state.db_pool.pool());
+    let not_found = || anyhow::anyhow!("Document {} not found", id);
+    let document = repo.find_by_id(&id).await?.ok_or_else(not_found)?;
     let deleted = repo.delete(&id).await?;
 
     if !deleted {
-        return Err(anyhow::anyhow!("Document {} not found", id));
+        return Err(not_found());
     }
+    document_changed(state, &document).await;
+    audit_document(
+        state,
+        actor.clone(),
+        AuditAction::DocumentDeleted,
+        &document,
+    )
+    .await;
 
     Ok(())
 }
 
+/// Drop cached searches over the document's source and reload its title
+/// into the searcher's typeahead index.
+async fn document_changed(state: &AppState, document: &Document) {
+    search_cache::invalidate_sources(&state.redis_client, [document.source_id.as_str()]).await;
+    typeahead_updates::publish(
+        &state.redis_client,
+        [TitleUpdate::documents(
+            &document.source_id,
+            vec![document.external_id.clone()],
+        )],
+    )
+    .await;
+}
+
+async fn audit_document(state: &AppState, actor: Actor, action: AuditAction, document: &Document) {
+    let event = AuditEvent::new(actor, action)
+        .resource("document", &document.id)
+        .details(json!({
+            "source_id": document.source_id,
+            "external_id": document.external_id,
+        }));
+    audit::record(state.db_pool.pool(), event).await;
+}
+
 async fn reindex_embeddings(State(state): State<AppState>) -> IndexerResult<Json<Value>> {
     let repo = DocumentRepository::new(state.db_pool.pool());
 
@@ -405,6 +1053,73 @@ async fn reindex_embeddings(State(state): State<AppState>) -> IndexerResult<Json
     })))
 }
 
+#[derive(Debug, Serialize)]
+pub struct EmbeddingQuarantine {
+    /// Queue items that exhausted their retries.
+    pub items: Vec<EmbeddingQueueItem>,
+    /// Chunks skipped so the rest of their document could be indexed.
+    pub chunks: Vec<QuarantinedChunk>,
+}
+
+async fn list_embedding_quarantine(
+    State(state): State<AppState>,
+) -> IndexerResult<Json<EmbeddingQuarantine>> {
+    const QUARANTINE_LIST_LIMIT: i64 = 100;
+
+    let items = state
+        .embedding_queue
+        .list_quarantined_items(QUARANTINE_LIST_LIMIT)
+        .await
+        .map_err(|e| IndexerError::Internal(format!("Failed to list quarantine: {}", e)))?;
+    let chunks = state
+        .embedding_queue
+        .list_quarantined_chunks(QUARANTINE_LIST_LIMIT)
+        .await
+        .map_err(|e| IndexerError::Internal(format!("Failed to list quarantine: {}", e)))?;
+
+    Ok(Json(EmbeddingQuarantine { items, chunks }))
+}
+
+async fn retry_quarantined_item(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> IndexerResult<Json<Value>> {
+    let retried = state
+        .embedding_queue
+        .retry_quarantined_item(&id)
+        .await
+        .map_err(|e| IndexerError::Internal(format!("Failed to retry item: {}", e)))?;
+    if !retried {
+        return Err(IndexerError::NotFound(format!(
+            "Quarantined queue item {}",
+            id
+        )));
+    }
+
+    info!("Released quarantined embedding queue item {}", id);
+    Ok(Json(json!({ "status": "ok", "id": id })))
+}
+
+async fn retry_quarantined_chunk(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> IndexerResult<Json<Value>> {
+    let document_id = state
+        .embedding_queue
+        .retry_quarantined_chunk(&id)
+        .await
+        .map_err(|e| IndexerError::Internal(format!("Failed to retry chunk: {}", e)))?
+        .ok_or_else(|| IndexerError::NotFound(format!("Quarantined chunk {}", id)))?;
+
+    info!(
+        "Released quarantined chunk {} and re-queued document {}",
+        id, document_id
+    );
+    Ok(Json(
+        json!({ "status": "ok", "id": id, "document_id": document_id }),
+    ))
+}
+
 async fn run_gc(State(state): State<AppState>) -> IndexerResult<Json<GCResult>> {
     let gc = ContentBlobGC::new(
         state.db_pool.pool().clone(),
@@ -435,8 +1150,704 @@ async fn gc_stats(State(state): State<AppState>) -> IndexerResult<Json<OrphanSta
     Ok(Json(stats))
 }
 
+async fn gc_reclaimed(State(state): State<AppState>) -> IndexerResult<Json<ReclaimedStorageStats>> {
+    const RECENT_GC_RUNS: i64 = 20;
+
+    let gc = ContentBlobGC::new(
+        state.db_pool.pool().clone(),
+        state.content_storage.clone(),
+        GCConfig::from_env(),
+    );
+
+    let stats = gc
+        .get_reclaimed_storage_stats(RECENT_GC_RUNS)
+        .await
+        .map_err(|e| IndexerError::Internal(format!("Failed to get GC history: {}", e)))?;
+
+    Ok(Json(stats))
+}
+
+async fn prune_document_versions(State(state): State<AppState>) -> IndexerResult<Json<Value>> {
+    let retention = VersionRetentionConfig::from_env();
+    let deleted = DocumentVersionRepository::new(state.db_pool.pool())
+        .prune(None, retention.max_versions, retention.max_age_days())
+        .await?;
+
+    info!("Pruned {} document versions", deleted);
+    Ok(Json(json!({
+        "status": "ok",
+        "deleted": deleted
+    })))
+}
+
+#[derive(Debug, Serialize)]
+pub struct LanguageTotals {
+    pub language: String,
+    pub document_count: i64,
+    pub chunk_count: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LanguageStatsResponse {
+    /// Corpus-wide totals, largest language first.
+    pub languages: Vec<LanguageTotals>,
+    /// Per-source breakdown from the last rollup.
+    pub sources: Vec<SourceLanguageStats>,
+}
+
+async fn language_stats(
+    State(state): State<AppState>,
+) -> IndexerResult<Json<LanguageStatsResponse>> {
+    let sources = CorpusStatsRepository::new(state.db_pool.pool())
+        .get_language_stats()
+        .await?;
+
+    let mut totals: HashMap<&str, (i64, i64)> = HashMap::new();
+    for row in &sources {
+        let entry = totals.entry(row.language.as_str()).or_default();
+        entry.0 += row.document_count;
+        entry.1 += row.chunk_count;
+    }
+    let mut languages: Vec<LanguageTotals> = totals
+        .into_iter()
+        .map(|(language, (document_count, chunk_count))| LanguageTotals {
+            language: language.to_string(),
+            document_count,
+            chunk_count,
+        })
+        .collect();
+    languages.sort_by(|a, b| {
+        b.document_count
+            .cmp(&a.document_count)
+            .then_with(|| a.language.cmp(&b.language))
+    });
+
+    Ok(Json(LanguageStatsResponse { languages, sources }))
+}
+
+async fn refresh_language_stats(State(state): State<AppState>) -> IndexerResult<Json<Value>> {
+    let rows = CorpusStatsRepository::new(state.db_pool.pool())
+        .refresh_language_stats()
+        .await?;
+
+    Ok(Json(json!({
+        "status": "ok",
+        "rows": rows
+    })))
+}
+
+async fn storage_report(State(state): State<AppState>) -> IndexerResult<Json<StorageReport>> {
+    let report = StorageAnalyzer::new(state.db_pool.pool(), StorageReportConfig::from_env())
+        .report()
+        .await
+        .map_err(|e| IndexerError::Internal(format!("Storage report failed: {}", e)))?;
+
+    Ok(Json(report))
+}
+
+async fn run_storage_analysis(
+    State(state): State<AppState>,
+) -> IndexerResult<Json<StorageAnalysisResult>> {
+    let result = StorageAnalyzer::new(state.db_pool.pool(), StorageReportConfig::from_env())
+        .run()
+        .await
+        .map_err(|e| IndexerError::Internal(format!("Storage analysis failed: {}", e)))?;
+
+    Ok(Json(result))
+}
+
+async fn freshness_report(State(state): State<AppState>) -> IndexerResult<Json<FreshnessReport>> {
+    let report = FreshnessMonitor::new(state.db_pool.pool(), FreshnessConfig::from_env())
+        .report()
+        .await
+        .map_err(|e| IndexerError::Internal(format!("Freshness report failed: {}", e)))?;
+
+    Ok(Json(report))
+}
+
+async fn active_ranking_profile(
+    State(state): State<AppState>,
+) -> IndexerResult<Json<Option<StoredRankingProfile>>> {
+    let profile = RankingTrainer::new(state.db_pool.pool(), RankingTrainingConfig::from_env())
+        .active()
+        .await
+        .map_err(|e| IndexerError::Internal(format!("Failed to load ranking profile: {}", e)))?;
+
+    Ok(Json(profile))
+}
+
+async fn train_ranking_profile(
+    State(state): State<AppState>,
+) -> IndexerResult<Json<RankingTrainingResult>> {
+    let result = RankingTrainer::new(state.db_pool.pool(), RankingTrainingConfig::from_env())
+        .run()
+        .await
+        .map_err(|e| IndexerError::Internal(format!("Ranking training failed: {}", e)))?;
+
+    Ok(Json(result))
+}
+
+async fn metrics(
+    State(state): State<AppState>,
+) -> IndexerResult<([(header::HeaderName, &'static str); 1], String)> {
+    let report = FreshnessMonitor::new(state.db_pool.pool(), FreshnessConfig::from_env())
+        .report()
+        .await
+        .map_err(|e| IndexerError::Internal(format!("Freshness report failed: {}", e)))?;
+
+    Ok((
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        freshness::render_metrics(&report),
+    ))
+}
+
+async fn refresh_term_dictionary(
+    State(state): State<AppState>,
+    Query(query): Query<TermDictionaryRefreshQuery>,
+) -> IndexerResult<Json<TermDictionaryRun>> {
+    let run = term_dictionary::refresh(
+        state.db_pool.pool(),
+        &TermDictionaryConfig::from_env(),
+        query.mode,
+    )
+    .await?;
+
+    Ok(Json(run))
+}
+
+async fn term_dictionary_status(
+    State(state): State<AppState>,
+) -> IndexerResult<Json<TermDictionaryStatus>> {
+    Ok(Json(term_dictionary::status(state.db_pool.pool()).await?))
+}
+
+async fn start_vector_index_build(
+    State(state): State<AppState>,
+    Json(request): Json<VectorIndexBuildRequest>,
+) -> IndexerResult<(StatusCode, Json<VectorIndexBuild>)> {
+    let params = request.validate().map_err(IndexerError::BadRequest)?;
+    let build = VectorIndexBuilder::new(state.db_pool.pool().clone())
+        .start(params)
+        .await?;
+
+    Ok((StatusCode::ACCEPTED, Json(build)))
+}
+
+async fn list_vector_index_builds(
+    State(state): State<AppState>,
+) -> IndexerResult<Json<Vec<VectorIndexBuild>>> {
+    const BUILD_LIST_LIMIT: i64 = 50;
+
+    let builds = VectorIndexBuilder::new(state.db_pool.pool().clone())
+        .list(BUILD_LIST_LIMIT)
+        .await?;
+
+    Ok(Json(builds))
+}
+
+async fn get_vector_index_build(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> IndexerResult<Json<VectorIndexBuildStatusResponse>> {
+    let status = VectorIndexBuilder::new(state.db_pool.pool().clone())
+        .status(&id)
+        .await?
+        .ok_or_else(|| IndexerError::NotFound(format!("Vector index build {}", id)))?;
+
+    Ok(Json(status))
+}
+
+async fn start_source_reindex(
+    State(state): State<AppState>,
+    Path(source_id): Path<String>,
+) -> IndexerResult<(StatusCode, Json<SourceReindexJob>)> {
+    let job = SourceReindexer::new(state)
+        .start(&source_id)
+        .await
+        .map_err(|e| match e {
+            DatabaseError::NotFound => IndexerError::NotFound(format!("Source {}", source_id)),
+            e => e.into(),
+        })?;
+
+    Ok((StatusCode::ACCEPTED, Json(job)))
+}
+
+async fn get_source_pii_policy(
+    State(state): State<AppState>,
+    Path(source_id): Path<String>,
+) -> IndexerResult<Json<SourcePiiPolicy>> {
+    SourcePiiPolicyRepository::new(state.db_pool.pool())
+        .get(&source_id)
+        .await?
+        .map(Json)
+        .ok_or_else(|| IndexerError::NotFound(format!("PII policy of source {}", source_id)))
+}
+
+/// Policies apply to documents ingested after the change; reindex the source
+/// to apply them to those already indexed.
+async fn update_source_pii_policy(
+    State(state): State<AppState>,
+    actor: Actor,
+    Path(source_id): Path<String>,
+    Json(request): Json<UpdatePiiPolicyRequest>,
+) -> IndexerResult<Json<SourcePiiPolicy>> {
+    let detectors = request
+        .detectors
+        .unwrap_or_else(|| PiiDetector::ALL.to_vec());
+    if detectors.is_empty() && request.custom_patterns.is_empty() {
+        return Err(IndexerError::BadRequest(
+            "A PII policy needs at least one detector or custom pattern".to_string(),
+        ));
+    }
+    pii::validate_custom_patterns(&request.custom_patterns).map_err(IndexerError::BadRequest)?;
+
+    let policy = SourcePiiPolicyRepository::new(state.db_pool.pool())
+        .upsert(
+            &source_id,
+            request.action,
+            &detectors,
+            &request.custom_patterns,
+            request.updated_by.as_deref(),
+        )
+        .await
+        .map_err(|e| match e {
+            DatabaseError::NotFound => {
+                IndexerError::NotFound(format!("Source {} or updating user", source_id))
+            }
+            e => e.into(),
+        })?;
+    info!(
+        "Set PII policy of source {} ({:?}, {} custom patterns)",
+        source_id,
+        policy.action,
+        policy.custom_patterns.len()
+    );
+    audit_pii_policy(&state, actor, &source_id, json!(policy)).await;
+
+    Ok(Json(policy))
+}
+
+async fn delete_source_pii_policy(
+    State(state): State<AppState>,
+    actor: Actor,
+    Path(source_id): Path<String>,
+) -> IndexerResult<StatusCode> {
+    let deleted = SourcePiiPolicyRepository::new(state.db_pool.pool())
+        .delete(&source_id)
+        .await?;
+    if !deleted {
+        return Err(IndexerError::NotFound(format!(
+            "PII policy of source {}",
+            source_id
+        )));
+    }
+    info!("Removed PII policy of source {}", source_id);
+    audit_pii_policy(&state, actor, &source_id, Value::Null).await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn audit_pii_policy(state: &AppState, actor: Actor, source_id: &str, policy: Value) {
+    let event = AuditEvent::new(actor, AuditAction::ConfigurationChanged)
+        .resource("source", source_id)
+        .details(json!({ "change": "pii_policy", "policy": policy }));
+    audit::record(state.db_pool.pool(), event).await;
+}
+
+async fn get_source_retention_policy(
+    State(state): State<AppState>,
+    Path(source_id): Path<String>,
+) -> IndexerResult<Json<SourceRetentionPolicy>> {
+    SourceRetentionRepository::new(state.db_pool.pool())
+        .get_policy(&source_id)
+        .await?
+        .map(Json)
+        .ok_or_else(|| IndexerError::NotFound(format!("Retention policy of source {}", source_id)))
+}
+
+/// The new limit takes effect on the next retention run.
+async fn update_source_retention_policy(
+    State(state): State<AppState>,
+    actor: Actor,
+    Path(source_id): Path<String>,
+    Json(request): Json<UpdateRetentionPolicyRequest>,
+) -> IndexerResult<Json<SourceRetentionPolicy>> {
+    request.validate().map_err(IndexerError::BadRequest)?;
+
+    let policy = SourceRetentionRepository::new(state.db_pool.pool())
+        .upsert_policy(
+            &source_id,
+            request.retention_days,
+            request.updated_by.as_deref(),
+        )
+        .await
+        .map_err(|e| match e {
+            DatabaseError::NotFound => {
+                IndexerError::NotFound(format!("Source {} or updating user", source_id))
+            }
+            e => e.into(),
+        })?;
+    info!(
+        "Set retention policy of source {} to {} days",
+        source_id, policy.retention_days
+    );
+    audit_retention_policy(&state, actor, &source_id, json!(policy)).await;
+
+    Ok(Json(policy))
+}
+
+async fn delete_source_retention_policy(
+    State(state): State<AppState>,
+    actor: Actor,
+    Path(source_id): Path<String>,
+) -> IndexerResult<StatusCode> {
+    let deleted = SourceRetentionRepository::new(state.db_pool.pool())
+        .delete_policy(&source_id)
+        .await?;
+    if !deleted {
+        return Err(IndexerError::NotFound(format!(
+            "Retention policy of source {}",
+            source_id
+        )));
+    }
+    info!("Removed retention policy of source {}", source_id);
+    audit_retention_policy(&state, actor, &source_id, Value::Null).await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn audit_retention_policy(state: &AppState, actor: Actor, source_id: &str, policy: Value) {
+    let event = AuditEvent::new(actor, AuditAction::ConfigurationChanged)
+        .resource("source", source_id)
+        .details(json!({ "change": "retention_policy", "policy": policy }));
+    audit::record(state.db_pool.pool(), event).await;
+}
+
+async fn list_retention_purges(
+    State(state): State<AppState>,
+    Path(source_id): Path<String>,
+) -> IndexerResult<Json<Vec<RetentionPurge>>> {
+    const PURGE_LIST_LIMIT: i64 = 50;
+
+    let purges = SourceRetentionRepository::new(state.db_pool.pool())
+        .list_purges(&source_id, PURGE_LIST_LIMIT)
+        .await?;
+
+    Ok(Json(purges))
+}
+
+async fn list_retention_purged_documents(
+    State(state): State<AppState>,
+    Path((source_id, purge_id)): Path<(String, String)>,
+    Query(query): Query<PurgedDocumentsQuery>,
+) -> IndexerResult<Json<Vec<RetentionPurgedDocument>>> {
+    let repo = SourceRetentionRepository::new(state.db_pool.pool());
+    repo.get_purge(&purge_id)
+        .await?
+        .filter(|purge| purge.source_id == source_id)
+        .ok_or_else(|| IndexerError::NotFound(format!("Retention purge {}", purge_id)))?;
+
+    let documents = repo
+        .list_purged_documents(&purge_id, query.limit(), query.offset())
+        .await?;
+
+    Ok(Json(documents))
+}
+
+async fn retention_report(
+    State(state): State<AppState>,
+) -> IndexerResult<Json<Vec<SourceRetentionSummary>>> {
+    let summaries = SourceRetentionRepository::new(state.db_pool.pool())
+        .summaries()
+        .await?;
+
+    Ok(Json(summaries))
+}
+
+async fn run_retention(State(state): State<AppState>) -> IndexerResult<Json<RetentionRunResult>> {
+    let result = RetentionEnforcer::new(state, RetentionConfig::from_env())
+        .run()
+        .await
+        .map_err(|e| IndexerError::Internal(format!("Retention run failed: {}", e)))?;
+
+    Ok(Json(result))
+}
+
+async fn list_source_reindex_jobs(
+    State(state): State<AppState>,
+    Path(source_id): Path<String>,
+) -> IndexerResult<Json<Vec<SourceReindexJob>>> {
+    const REINDEX_LIST_LIMIT: i64 = 20;
+
+    let jobs = SourceReindexer::new(state)
+        .list(&source_id, REINDEX_LIST_LIMIT)
+        .await?;
+
+    Ok(Json(jobs))
+}
+
+async fn get_source_reindex_job(
+    State(state): State<AppState>,
+    Path((source_id, job_id)): Path<(String, String)>,
+) -> IndexerResult<Json<SourceReindexStatusResponse>> {
+    let status = SourceReindexer::new(state)
+        .status(&job_id)
+        .await?
+        .filter(|status| status.job.source_id == source_id)
+        .ok_or_else(|| IndexerError::NotFound(format!("Reindex {}", job_id)))?;
+
+    Ok(Json(status))
+}
+
+async fn extract_content(
+    State(state): State<AppState>,
+    Json(request): Json<ExtractRequest>,
+) -> IndexerResult<Json<ExtractedContent>> {
+    let extracted = ContentExtractor::new(&state, ExtractionConfig::from_env())
+        .extract(&request.content_id, &request.mime_type)
+        .await?;
+
+    Ok(Json(extracted))
+}
+
+async fn start_embedding_migration(
+    State(state): State<AppState>,
+    Json(request): Json<EmbeddingMigrationRequest>,
+) -> IndexerResult<(StatusCode, Json<EmbeddingMigration>)> {
+    let migration = EmbeddingMigrator::new(state.db_pool.pool().clone())
+        .start(&request)
+        .await?;
+
+    Ok((StatusCode::ACCEPTED, Json(migration)))
+}
+
+async fn list_embedding_migrations(
+    State(state): State<AppState>,
+) -> IndexerResult<Json<Vec<EmbeddingMigration>>> {
+    const MIGRATION_LIST_LIMIT: i64 = 50;
+
+    let migrations = EmbeddingMigrator::new(state.db_pool.pool().clone())
+        .list(MIGRATION_LIST_LIMIT)
+        .await?;
+
+    Ok(Json(migrations))
+}
+
+async fn get_embedding_migration(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> IndexerResult<Json<EmbeddingMigrationStatusResponse>> {
+    let status = EmbeddingMigrator::new(state.db_pool.pool().clone())
+        .status(&id)
+        .await?
+        .ok_or_else(|| IndexerError::NotFound(format!("Embedding migration {}", id)))?;
+
+    Ok(Json(status))
+}
+
+async fn cancel_embedding_migration(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> IndexerResult<Json<EmbeddingMigrationStatusResponse>> {
+    let migrator = EmbeddingMigrator::new(state.db_pool.pool().clone());
+    let cancelled = migrator.cancel(&id).await?;
+    let status = migrator
+        .status(&id)
+        .await?
+        .ok_or_else(|| IndexerError::NotFound(format!("Embedding migration {}", id)))?;
+    if !cancelled {
+        return Err(IndexerError::BadRequest(format!(
+            "Embedding migration {} is not running",
+            id
+        )));
+    }
+
+    Ok(Json(status))
+}
+
+fn integrity_checker(state: &AppState) -> IntegrityChecker {
+    IntegrityChecker::new(
+        state.db_pool.pool(),
+        state.embedding_queue.clone(),
+        IntegrityConfig::from_env(),
+    )
+}
+
+async fn integrity_check(State(state): State<AppState>) -> IndexerResult<Json<IntegrityReport>> {
+    let report = integrity_checker(&state)
+        .check()
+        .await
+        .map_err(|e| IndexerError::Internal(format!("Integrity check failed: {}", e)))?;
+
+    Ok(Json(report))
+}
+
+async fn integrity_repair(State(state): State<AppState>) -> IndexerResult<Json<RepairResult>> {
+    let result = integrity_checker(&state)
+        .repair()
+        .await
+        .map_err(|e| IndexerError::Internal(format!("Integrity repair failed: {}", e)))?;
+
+    Ok(Json(result))
+}
+
+fn link_checker(state: &AppState) -> IndexerResult<LinkChecker> {
+    LinkChecker::new(state.db_pool.pool(), LinkCheckConfig::from_env())
+        .map_err(|e| IndexerError::Internal(format!("Failed to create link checker: {}", e)))
+}
+
+async fn link_report(State(state): State<AppState>) -> IndexerResult<Json<LinkReport>> {
+    let report = link_checker(&state)?
+        .report()
+        .await
+        .map_err(|e| IndexerError::Internal(format!("Link report failed: {}", e)))?;
+
+    Ok(Json(report))
+}
+
+async fn run_link_check(State(state): State<AppState>) -> IndexerResult<Json<LinkCheckRunResult>> {
+    let result = link_checker(&state)?
+        .run()
+        .await
+        .map_err(|e| IndexerError::Internal(format!("Link check failed: {}", e)))?;
+
+    Ok(Json(result))
+}
+
+async fn list_block_rules(
+    State(state): State<AppState>,
+) -> IndexerResult<Json<Vec<IngestionBlockRule>>> {
+    let rules = IngestionBlockRuleRepository::new(state.db_pool.pool())
+        .list()
+        .await?;
+
+    Ok(Json(rules))
+}
+
+async fn create_block_rule(
+    State(state): State<AppState>,
+    actor: Actor,
+    Json(request): Json<CreateBlockRuleRequest>,
+) -> IndexerResult<(StatusCode, Json<IngestionBlockRule>)> {
+    let name = request.name.trim();
+    if name.is_empty() {
+        return Err(IndexerError::BadRequest(
+            "Rule name must not be empty".to_string(),
+        ));
+    }
+    blocklist::validate_pattern(request.kind, &request.pattern)
+        .map_err(|e| IndexerError::BadRequest(format!("Invalid pattern: {}", e)))?;
+
+    let rule = IngestionBlockRuleRepository::new(state.db_pool.pool())
+        .create(
+            name,
+            request.kind,
+            &request.pattern,
+            request.source_id.as_deref(),
+            request.created_by.as_deref(),
+        )
+        .await?;
+    info!(
+        "Created ingestion block rule {} ({:?} {:?})",
+        rule.id, rule.kind, rule.pattern
+    );
+    audit_block_rule(&state, actor, &rule.id, "created", json!(rule)).await;
+
+    Ok((StatusCode::CREATED, Json(rule)))
+}
+
+/// Rules take effect on documents ingested after the change; documents
+/// already indexed are removed when they are next synced.
+async fn update_block_rule(
+    State(state): State<AppState>,
+    actor: Actor,
+    Path(id): Path<String>,
+    Json(request): Json<UpdateBlockRuleRequest>,
+) -> IndexerResult<Json<IngestionBlockRule>> {
+    let repo = IngestionBlockRuleRepository::new(state.db_pool.pool());
+    let rule = repo
+        .get(&id)
+        .await?
+        .ok_or_else(|| IndexerError::NotFound(format!("Ingestion block rule {}", id)))?;
+
+    let name = request.name.as_deref().map(str::trim);
+    if name.is_some_and(str::is_empty) {
+        return Err(IndexerError::BadRequest(
+            "Rule name must not be empty".to_string(),
+        ));
+    }
+    if let Some(pattern) = &request.pattern {
+        blocklist::validate_pattern(rule.kind, pattern)
+            .map_err(|e| IndexerError::BadRequest(format!("Invalid pattern: {}", e)))?;
+    }
+
+    let update = BlockRuleUpdate {
+        name: name.map(str::to_string),
+        pattern: request.pattern,
+        enabled: request.enabled,
+    };
+    let rule = repo
+        .update(&id, &update)
+        .await?
+        .ok_or_else(|| IndexerError::NotFound(format!("Ingestion block rule {}", id)))?;
+    audit_block_rule(&state, actor, &rule.id, "updated", json!(rule)).await;
+
+    Ok(Json(rule))
+}
+
+async fn delete_block_rule(
+    State(state): State<AppState>,
+    actor: Actor,
+    Path(id): Path<String>,
+) -> IndexerResult<StatusCode> {
+    let deleted = IngestionBlockRuleRepository::new(state.db_pool.pool())
+        .delete(&id)
+        .await?;
+    if !deleted {
+        return Err(IndexerError::NotFound(format!(
+            "Ingestion block rule {}",
+            id
+        )));
+    }
+    audit_block_rule(&state, actor, &id, "deleted", Value::Null).await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn audit_block_rule(
+    state: &AppState,
+    actor: Actor,
+    rule_id: &str,
+    operation: &str,
+    rule: Value,
+) {
+    let event = AuditEvent::new(actor, AuditAction::ConfigurationChanged)
+        .resource("ingestion_block_rule", rule_id)
+        .details(json!({
+            "change": "ingestion_block_rule",
+            "operation": operation,
+            "rule": rule,
+        }));
+    audit::record(state.db_pool.pool(), event).await;
+}
+
+async fn list_quarantined_events(
+    State(state): State<AppState>,
+    Query(query): Query<QuarantineQuery>,
+) -> IndexerResult<Json<Vec<QuarantinedEvent>>> {
+    let events = IngestionBlockRuleRepository::new(state.db_pool.pool())
+        .list_quarantined(
+            query.rule_id.as_deref(),
+            query.source_id.as_deref(),
+            query.limit(),
+        )
+        .await?;
+
+    Ok(Json(events))
+}
+
 pub async fn run_server() -> anyhow::Result<()> {
-    dotenvy::dotenv().ok();
+    shared::config_loader::load_env_files();
 
     let telemetry_config = TelemetryConfig::from_env("omni-indexer");
     telemetry::init_telemetry(telemetry_config)?;
@@ -452,6 +1863,16 @@ pub async fn run_server() -> anyhow::Result<()> {
     // Migrations are now handled by a separate migrator container
     info!("Database migrations handled by migrator container");
 
+    if config.row_level_security {
+        if !row_level_security::is_available(db_pool.pool()).await? {
+            anyhow::bail!(
+                "SEARCH_ROW_LEVEL_SECURITY is enabled but the database user can't assume the {} role",
+                row_level_security::DOCUMENT_READER_ROLE
+            );
+        }
+        info!("Row-level security enabled for user reads");
+    }
+
     let redis_client = RedisClient::open(config.redis.redis_url)?;
     info!("Redis client initialized");
 
@@ -464,14 +1885,47 @@ pub async fn run_server() -> anyhow::Result<()> {
     let content_storage = shared::StorageFactory::from_env(db_pool.pool().clone()).await?;
     info!("Content storage initialized");
 
+    let orphaned_builds = VectorIndexBuilder::new(db_pool.pool().clone())
+        .recover_orphaned()
+        .await?;
+    if orphaned_builds > 0 {
+        info!(
+            "Rolled back {} interrupted vector index builds",
+            orphaned_builds
+        );
+    }
+
+    EmbeddingMigrator::new(db_pool.pool().clone()).spawn_monitor();
+
     let app_state = AppState {
         db_pool,
         redis_client,
         ai_client,
         content_storage,
         embedding_queue,
+        row_level_security: config.row_level_security,
     };
 
+    let source_reindexer = SourceReindexer::new(app_state.clone());
+    let orphaned_reindexes = source_reindexer.recover_orphaned().await?;
+    if orphaned_reindexes > 0 {
+        info!(
+            "Failed {} source reindexes interrupted by a restart",
+            orphaned_reindexes
+        );
+    }
+    source_reindexer.spawn_monitor();
+
+    let orphaned_purges = RetentionEnforcer::new(app_state.clone(), RetentionConfig::from_env())
+        .recover_orphaned()
+        .await?;
+    if orphaned_purges > 0 {
+        info!(
+            "Failed {} retention purges interrupted by a restart",
+            orphaned_purges
+        );
+    }
+
     let app = create_app(app_state.clone());
 
     let queue_processor = queue_processor::QueueProcessor::new(app_state.clone());
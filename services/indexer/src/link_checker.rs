@@ -0,0 +1,384 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use futures::stream::{self, StreamExt};
+use reqwest::{Client, Method, StatusCode, Url, header, redirect};
+use serde::Serialize;
+use shared::db::repositories::{
+    DeadLink, LinkCheckCandidate, LinkCheckRecord, LinkCheckRepository, SourceLinkSummary,
+};
+use sqlx::PgPool;
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+const DEFAULT_SAMPLE_PER_SOURCE: i64 = 50;
+const DEFAULT_RECHECK_AFTER_HOURS: i64 = 24 * 7;
+const DEFAULT_CONCURRENCY: usize = 8;
+const DEFAULT_TIMEOUT_SECS: u64 = 10;
+const MAX_REDIRECTS: usize = 5;
+const DEAD_LINKS_PER_SOURCE: i64 = 20;
+
+#[derive(Debug, Clone)]
+pub struct LinkCheckConfig {
+    /// Documents sampled from each source per run.
+    pub sample_per_source: i64,
+    /// Documents checked more recently than this are not sampled again.
+    pub recheck_after_hours: i64,
+    pub concurrency: usize,
+    pub timeout_secs: u64,
+}
+
+impl Default for LinkCheckConfig {
+    fn default() -> Self {
+        Self {
+            sample_per_source: DEFAULT_SAMPLE_PER_SOURCE,
+            recheck_after_hours: DEFAULT_RECHECK_AFTER_HOURS,
+            concurrency: DEFAULT_CONCURRENCY,
+            timeout_secs: DEFAULT_TIMEOUT_SECS,
+        }
+    }
+}
+
+impl LinkCheckConfig {
+    pub fn from_env() -> Self {
+        fn env_or<T: std::str::FromStr>(key: &str, default: T) -> T {
+            std::env::var(key)
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default)
+        }
+
+        Self {
+            sample_per_source: env_or("LINK_CHECK_SAMPLE_PER_SOURCE", DEFAULT_SAMPLE_PER_SOURCE),
+            recheck_after_hours: env_or("LINK_CHECK_RECHECK_HOURS", DEFAULT_RECHECK_AFTER_HOURS),
+            concurrency: env_or("LINK_CHECK_CONCURRENCY", DEFAULT_CONCURRENCY).max(1),
+            timeout_secs: env_or("LINK_CHECK_TIMEOUT_SECS", DEFAULT_TIMEOUT_SECS),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkStatus {
+    Ok,
+    /// Permanently moved; the stored URL is updated to the final location.
+    Redirected,
+    /// 404 or 410.
+    Dead,
+    /// Behind authentication, so the checker cannot tell whether it resolves.
+    Unverifiable,
+    /// Timeouts, connection failures, 5xx and other unexpected responses.
+    Error,
+}
+
+impl LinkStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LinkStatus::Ok => "ok",
+            LinkStatus::Redirected => "redirected",
+            LinkStatus::Dead => "dead",
+            LinkStatus::Unverifiable => "unverifiable",
+            LinkStatus::Error => "error",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinkCheckOutcome {
+    pub status: LinkStatus,
+    pub http_status: Option<u16>,
+    /// Where a permanent redirect chain ended up.
+    pub final_url: Option<String>,
+    pub error: Option<String>,
+}
+
+impl LinkCheckOutcome {
+    fn error(message: impl Into<String>) -> Self {
+        Self {
+            status: LinkStatus::Error,
+            http_status: None,
+            final_url: None,
+            error: Some(message.into()),
+        }
+    }
+}
+
+/// Classify the response at the end of a redirect chain. `permanent_target`
+/// is set when every hop was a permanent redirect on the original host;
+/// temporary and cross-host redirects are usually login pages or load
+/// balancers, not a moved document, so they never rewrite the stored URL.
+pub fn classify(status: StatusCode, permanent_target: Option<&Url>) -> LinkCheckOutcome {
+    let (link_status, final_url) = match status.as_u16() {
+        200..=299 => match permanent_target {
+            Some(url) => (LinkStatus::Redirected, Some(url.to_string())),
+            None => (LinkStatus::Ok, None),
+        },
+        401 | 403 => (LinkStatus::Unverifiable, None),
+        404 | 410 => (LinkStatus::Dead, None),
+        _ => (LinkStatus::Error, None),
+    };
+
+    LinkCheckOutcome {
+        status: link_status,
+        http_status: Some(status.as_u16()),
+        final_url,
+        error: None,
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct LinkCheckRunResult {
+    pub checked: usize,
+    pub ok: usize,
+    pub redirected: usize,
+    pub dead: usize,
+    pub unverifiable: usize,
+    pub errors: usize,
+    pub urls_updated: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SourceLinkReport {
+    #[serde(flatten)]
+    pub summary: SourceLinkSummary,
+    /// Most recently found dead links, newest first.
+    pub dead_links: Vec<DeadLink>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LinkReport {
+    pub generated_at: DateTime<Utc>,
+    pub sources: Vec<SourceLinkReport>,
+}
+
+/// Samples document URLs per source and checks that they still resolve.
+/// Permanent redirects update the document's stored URL; dead links are
+/// recorded for the per-source report.
+pub struct LinkChecker {
+    repo: LinkCheckRepository,
+    client: Client,
+    config: LinkCheckConfig,
+}
+
+impl LinkChecker {
+    pub fn new(pool: &PgPool, config: LinkCheckConfig) -> Result<Self> {
+        // Redirects are followed by hand so each hop can be inspected.
+        let client = Client::builder()
+            .redirect(redirect::Policy::none())
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .user_agent("omni-link-checker")
+            .build()?;
+
+        Ok(Self {
+            repo: LinkCheckRepository::new(pool),
+            client,
+            config,
+        })
+    }
+
+    pub async fn run(&self) -> Result<LinkCheckRunResult> {
+        let candidates = self
+            .repo
+            .sample_candidates(
+                self.config.sample_per_source,
+                self.config.recheck_after_hours,
+            )
+            .await?;
+        if candidates.is_empty() {
+            debug!("Link check: no documents due for checking");
+            return Ok(LinkCheckRunResult::default());
+        }
+
+        let checked: Vec<(LinkCheckCandidate, LinkCheckOutcome)> = stream::iter(candidates)
+            .map(|candidate| async move {
+                let outcome = self.check_url(&candidate.url).await;
+                (candidate, outcome)
+            })
+            .buffer_unordered(self.config.concurrency)
+            .collect()
+            .await;
+
+        let mut result = LinkCheckRunResult {
+            checked: checked.len(),
+            ..Default::default()
+        };
+        let mut records = Vec::with_capacity(checked.len());
+
+        for (candidate, outcome) in checked {
+            match outcome.status {
+                LinkStatus::Ok => result.ok += 1,
+                LinkStatus::Redirected => result.redirected += 1,
+                LinkStatus::Dead => result.dead += 1,
+                LinkStatus::Unverifiable => result.unverifiable += 1,
+                LinkStatus::Error => result.errors += 1,
+            }
+
+            if let Some(final_url) = &outcome.final_url {
+                match self
+                    .repo
+                    .update_document_url(&candidate.document_id, &candidate.url, final_url)
+                    .await
+                {
+                    Ok(true) => result.urls_updated += 1,
+                    Ok(false) => {}
+                    Err(e) => warn!(
+                        "Failed to update URL for document {}: {}",
+                        candidate.document_id, e
+                    ),
+                }
+            }
+
+            records.push(LinkCheckRecord {
+                document_id: candidate.document_id,
+                source_id: candidate.source_id,
+                url: candidate.url,
+                status: outcome.status.as_str().to_string(),
+                http_status: outcome.http_status.map(i32::from),
+                final_url: outcome.final_url,
+                error_message: outcome.error,
+            });
+        }
+
+        self.repo.record_results(&records).await?;
+
+        info!(
+            "Link check: checked={}, ok={}, redirected={}, dead={}, unverifiable={}, errors={}, urls_updated={}",
+            result.checked,
+            result.ok,
+            result.redirected,
+            result.dead,
+            result.unverifiable,
+            result.errors,
+            result.urls_updated
+        );
+
+        Ok(result)
+    }
+
+    pub async fn report(&self) -> Result<LinkReport> {
+        let mut sources = Vec::new();
+        for summary in self.repo.get_source_summaries().await? {
+            let dead_links = if summary.dead > 0 {
+                self.repo
+                    .get_dead_links(&summary.source_id, DEAD_LINKS_PER_SOURCE)
+                    .await?
+            } else {
+                Vec::new()
+            };
+            sources.push(SourceLinkReport {
+                summary,
+                dead_links,
+            });
+        }
+
+        Ok(LinkReport {
+            generated_at: Utc::now(),
+            sources,
+        })
+    }
+
+    pub async fn check_url(&self, url: &str) -> LinkCheckOutcome {
+        let original = match Url::parse(url) {
+            Ok(url) => url,
+            Err(e) => return LinkCheckOutcome::error(format!("Invalid URL: {}", e)),
+        };
+
+        let mut current = original.clone();
+        let mut all_permanent = true;
+
+        for _ in 0..=MAX_REDIRECTS {
+            let (status, location) = match self.fetch_status(&current).await {
+                Ok(response) => response,
+                Err(e) => return LinkCheckOutcome::error(e.to_string()),
+            };
+
+            if !status.is_redirection() {
+                let permanent_target = (current != original
+                    && all_permanent
+                    && current.host_str() == original.host_str())
+                .then_some(&current);
+                return classify(status, permanent_target);
+            }
+
+            let Some(next) = location.and_then(|location| current.join(&location).ok()) else {
+                return LinkCheckOutcome {
+                    http_status: Some(status.as_u16()),
+                    ..LinkCheckOutcome::error("Redirect without a valid Location header")
+                };
+            };
+            all_permanent &= matches!(
+                status,
+                StatusCode::MOVED_PERMANENTLY | StatusCode::PERMANENT_REDIRECT
+            );
+            current = next;
+        }
+
+        LinkCheckOutcome::error(format!("More than {} redirects", MAX_REDIRECTS))
+    }
+
+    /// HEAD the URL, falling back to GET for servers that do not support it.
+    /// Returns the status and the `Location` header, if any.
+    async fn fetch_status(&self, url: &Url) -> reqwest::Result<(StatusCode, Option<String>)> {
+        let mut response = self
+            .client
+            .request(Method::HEAD, url.clone())
+            .send()
+            .await?;
+        if matches!(
+            response.status(),
+            StatusCode::METHOD_NOT_ALLOWED | StatusCode::NOT_IMPLEMENTED
+        ) {
+            response = self.client.get(url.clone()).send().await?;
+        }
+
+        let location = response
+            .headers()
+            .get(header::LOCATION)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        Ok((response.status(), location))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_without_redirect() {
+        assert_eq!(classify(StatusCode::OK, None).status, LinkStatus::Ok);
+        assert_eq!(
+            classify(StatusCode::NOT_FOUND, None).status,
+            LinkStatus::Dead
+        );
+        assert_eq!(classify(StatusCode::GONE, None).status, LinkStatus::Dead);
+        assert_eq!(
+            classify(StatusCode::UNAUTHORIZED, None).status,
+            LinkStatus::Unverifiable
+        );
+        assert_eq!(
+            classify(StatusCode::FORBIDDEN, None).status,
+            LinkStatus::Unverifiable
+        );
+        assert_eq!(
+            classify(StatusCode::INTERNAL_SERVER_ERROR, None).status,
+            LinkStatus::Error
+        );
+    }
+
+    #[test]
+    fn test_classify_permanent_redirect() {
+        let target = Url::parse("https://wiki.example.com/new").unwrap();
+
+        let outcome = classify(StatusCode::OK, Some(&target));
+        assert_eq!(outcome.status, LinkStatus::Redirected);
+        assert_eq!(
+            outcome.final_url.as_deref(),
+            Some("https://wiki.example.com/new")
+        );
+
+        // A redirect to a page that no longer exists is still a dead link.
+        let outcome = classify(StatusCode::NOT_FOUND, Some(&target));
+        assert_eq!(outcome.status, LinkStatus::Dead);
+        assert_eq!(outcome.final_url, None);
+    }
+}
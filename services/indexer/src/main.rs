@@ -1,6 +1,15 @@
 use anyhow::Result;
+use shared::IndexerConfig;
+use shared::config_loader;
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    if config_loader::print_config_requested() {
+        config_loader::load_env_files();
+        std::process::exit(config_loader::print_config(
+            "omni-indexer",
+            IndexerConfig::load,
+        ));
+    }
     omni_indexer::run_server().await
 }
@@ -0,0 +1,335 @@
+//! Rewrite localized dates and numbers in document attributes into ISO dates
+//! and JSON numbers, so range filters work on values such as `31.12.2024` or
+//! `1.234,56` that connectors pass through as display strings.
+
+use serde_json::{Number, Value};
+use time::{Date, Month};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateOrder {
+    /// `31.12.2024`, `31/12/2024`
+    DayFirst,
+    /// `12/31/2024`
+    MonthFirst,
+}
+
+/// The conventions for writing dates and numbers in one locale.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Locale {
+    pub decimal_separator: char,
+    pub date_order: DateOrder,
+}
+
+const DECIMAL_COMMA: Locale = Locale {
+    decimal_separator: ',',
+    date_order: DateOrder::DayFirst,
+};
+const DECIMAL_POINT_DAY_FIRST: Locale = Locale {
+    decimal_separator: '.',
+    date_order: DateOrder::DayFirst,
+};
+const DECIMAL_POINT_MONTH_FIRST: Locale = Locale {
+    decimal_separator: '.',
+    date_order: DateOrder::MonthFirst,
+};
+
+/// Languages that write `1.234,56` and put the day first, as ISO 639-1 and
+/// ISO 639-3 codes.
+const DECIMAL_COMMA_LANGUAGES: &[(&str, &str)] = &[
+    ("ces", "cs"),
+    ("dan", "da"),
+    ("deu", "de"),
+    ("ell", "el"),
+    ("fin", "fi"),
+    ("fra", "fr"),
+    ("ind", "id"),
+    ("ita", "it"),
+    ("nld", "nl"),
+    ("nob", "nb"),
+    ("pol", "pl"),
+    ("por", "pt"),
+    ("ron", "ro"),
+    ("rus", "ru"),
+    ("spa", "es"),
+    ("swe", "sv"),
+    ("tur", "tr"),
+    ("ukr", "uk"),
+    ("vie", "vi"),
+];
+
+/// English-speaking regions that put the day first.
+const DAY_FIRST_ENGLISH_REGIONS: &[&str] = &["AU", "GB", "IE", "IN", "NZ", "ZA"];
+
+impl Locale {
+    /// The locale for a BCP 47 tag such as `de-DE`, `en_GB` or `fr`. Only the
+    /// language and region are considered.
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        let mut parts = tag.trim().split(['-', '_']);
+        let language = parts.next()?.to_ascii_lowercase();
+        let region = parts.next().map(str::to_ascii_uppercase);
+
+        if language == "en" {
+            return Some(match region {
+                Some(region) if DAY_FIRST_ENGLISH_REGIONS.contains(&region.as_str()) => {
+                    DECIMAL_POINT_DAY_FIRST
+                }
+                _ => DECIMAL_POINT_MONTH_FIRST,
+            });
+        }
+        DECIMAL_COMMA_LANGUAGES
+            .iter()
+            .any(|(_, code)| *code == language)
+            .then_some(DECIMAL_COMMA)
+    }
+
+    /// The locale for an ISO 639-3 code from language detection. English is
+    /// assumed to be US English.
+    pub fn from_language(code: &str) -> Option<Self> {
+        if code == "eng" {
+            return Some(DECIMAL_POINT_MONTH_FIRST);
+        }
+        DECIMAL_COMMA_LANGUAGES
+            .iter()
+            .any(|(iso3, _)| *iso3 == code)
+            .then_some(DECIMAL_COMMA)
+    }
+
+    fn group_separators(&self) -> &'static [char] {
+        if self.decimal_separator == ',' {
+            &['.', ' ', '\u{a0}', '\u{202f}']
+        } else {
+            &[',', ' ', '\u{a0}', '\u{202f}']
+        }
+    }
+}
+
+/// Whether any top-level attribute holds a string that normalization could
+/// rewrite.
+pub fn has_string_attributes(attributes: &Value) -> bool {
+    attributes.as_object().is_some_and(|attributes| {
+        attributes.values().any(|value| match value {
+            Value::String(_) => true,
+            Value::Array(items) => items.iter().any(Value::is_string),
+            _ => false,
+        })
+    })
+}
+
+/// Rewrite top-level string attributes, and strings inside top-level arrays,
+/// that are localized dates or numbers. Returns how many values changed.
+pub fn normalize_attributes(attributes: &mut Value, locale: Locale) -> usize {
+    let Some(attributes) = attributes.as_object_mut() else {
+        return 0;
+    };
+
+    let mut changed = 0;
+    for value in attributes.values_mut() {
+        match value {
+            Value::String(_) => changed += normalize_value(value, locale) as usize,
+            Value::Array(items) => {
+                for item in items {
+                    changed += normalize_value(item, locale) as usize;
+                }
+            }
+            _ => {}
+        }
+    }
+    changed
+}
+
+fn normalize_value(value: &mut Value, locale: Locale) -> bool {
+    let Value::String(text) = value else {
+        return false;
+    };
+
+    let normalized = if let Some(date) = parse_localized_date(text, locale) {
+        Value::String(date.to_string())
+    } else if let Some(number) = parse_localized_number(text, locale) {
+        Value::Number(number)
+    } else {
+        return false;
+    };
+
+    if *value == normalized {
+        return false;
+    }
+    *value = normalized;
+    true
+}
+
+/// Parse `31.12.2024`, `12/31/2024` or `2024/12/31`. A first component
+/// above 12 is always the day, whatever the locale's order.
+pub fn parse_localized_date(text: &str, locale: Locale) -> Option<Date> {
+    let text = text.trim();
+    let separator = text.chars().find(|c| matches!(c, '.' | '/' | '-'))?;
+    let parts: Vec<&str> = text.split(separator).collect();
+    let [a, b, c] = parts.as_slice() else {
+        return None;
+    };
+    if ![a, b, c]
+        .iter()
+        .all(|part| !part.is_empty() && part.chars().all(|ch| ch.is_ascii_digit()))
+    {
+        return None;
+    }
+
+    let (year, month, day) = if a.len() == 4 {
+        (a, b, c)
+    } else if c.len() == 4 && a.len() <= 2 && b.len() <= 2 {
+        let first: u8 = a.parse().ok()?;
+        match locale.date_order {
+            DateOrder::MonthFirst if first <= 12 => (c, a, b),
+            _ => (c, b, a),
+        }
+    } else {
+        return None;
+    };
+
+    let month = Month::try_from(month.parse::<u8>().ok()?).ok()?;
+    Date::from_calendar_date(year.parse().ok()?, month, day.parse().ok()?).ok()
+}
+
+/// Parse a number written with the locale's grouping and decimal separators,
+/// e.g. `1.234,56` or `1 234,56` for German and `1,234.56` for English.
+/// Plain numbers such as `1234` or `1.5` are left to the caller: they are
+/// already machine readable, and reading `1.10` as a number would mangle
+/// version strings.
+pub fn parse_localized_number(text: &str, locale: Locale) -> Option<Number> {
+    let text = text.trim();
+    if text.parse::<f64>().is_ok() {
+        return None;
+    }
+
+    let (sign, unsigned) = match text.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", text),
+    };
+    let (integer, fraction) = match unsigned.split_once(locale.decimal_separator) {
+        Some((integer, fraction)) => (integer, Some(fraction)),
+        None => (unsigned, None),
+    };
+
+    let groups: Vec<&str> = integer.split(locale.group_separators()).collect();
+    let grouping_ok = groups.iter().enumerate().all(|(i, group)| {
+        let len_ok = if i == 0 {
+            (1..=3).contains(&group.len()) || groups.len() == 1
+        } else {
+            group.len() == 3
+        };
+        len_ok && group.chars().all(|c| c.is_ascii_digit())
+    });
+    let fraction_ok =
+        fraction.is_none_or(|f| !f.is_empty() && f.chars().all(|c| c.is_ascii_digit()));
+    if integer.is_empty() || !grouping_ok || !fraction_ok {
+        return None;
+    }
+
+    let digits = groups.concat();
+    match fraction {
+        None => format!("{sign}{digits}")
+            .parse::<i64>()
+            .ok()
+            .map(Number::from),
+        Some(fraction) => format!("{sign}{digits}.{fraction}")
+            .parse::<f64>()
+            .ok()
+            .and_then(Number::from_f64),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_locale_from_tag_and_language() {
+        assert_eq!(Locale::from_tag("de-DE"), Some(DECIMAL_COMMA));
+        assert_eq!(Locale::from_tag("en_GB"), Some(DECIMAL_POINT_DAY_FIRST));
+        assert_eq!(Locale::from_tag("en"), Some(DECIMAL_POINT_MONTH_FIRST));
+        assert_eq!(Locale::from_tag("ja-JP"), None);
+        assert_eq!(Locale::from_language("fra"), Some(DECIMAL_COMMA));
+        assert_eq!(Locale::from_language("und"), None);
+    }
+
+    #[test]
+    fn test_parse_localized_dates() {
+        let date = |y, m, d| Date::from_calendar_date(y, Month::try_from(m).unwrap(), d).unwrap();
+
+        assert_eq!(
+            parse_localized_date("31.12.2024", DECIMAL_COMMA),
+            Some(date(2024, 12, 31))
+        );
+        assert_eq!(
+            parse_localized_date("03/04/2024", DECIMAL_COMMA),
+            Some(date(2024, 4, 3))
+        );
+        assert_eq!(
+            parse_localized_date("03/04/2024", DECIMAL_POINT_MONTH_FIRST),
+            Some(date(2024, 3, 4))
+        );
+        // 31 can't be a month, so it's the day even for US English
+        assert_eq!(
+            parse_localized_date("31/12/2024", DECIMAL_POINT_MONTH_FIRST),
+            Some(date(2024, 12, 31))
+        );
+        assert_eq!(
+            parse_localized_date("2024/12/31", DECIMAL_COMMA),
+            Some(date(2024, 12, 31))
+        );
+        assert_eq!(parse_localized_date("31.02.2024", DECIMAL_COMMA), None);
+        assert_eq!(parse_localized_date("1.2.3", DECIMAL_COMMA), None);
+    }
+
+    #[test]
+    fn test_parse_localized_numbers() {
+        assert_eq!(
+            parse_localized_number("1.234,56", DECIMAL_COMMA),
+            Number::from_f64(1234.56)
+        );
+        assert_eq!(
+            parse_localized_number("-1 234 567", DECIMAL_COMMA),
+            Some(Number::from(-1_234_567))
+        );
+        assert_eq!(
+            parse_localized_number("1,234.5", DECIMAL_POINT_MONTH_FIRST),
+            Number::from_f64(1234.5)
+        );
+        // Already machine readable, or not a grouped number
+        assert_eq!(
+            parse_localized_number("1.10", DECIMAL_POINT_MONTH_FIRST),
+            None
+        );
+        assert_eq!(parse_localized_number("1.10", DECIMAL_COMMA), None);
+        assert_eq!(
+            parse_localized_number("12,34", DECIMAL_POINT_MONTH_FIRST),
+            None
+        );
+        assert_eq!(parse_localized_number("v1,5", DECIMAL_COMMA), None);
+    }
+
+    #[test]
+    fn test_normalize_attributes() {
+        let mut attributes = json!({
+            "due_date": "31.12.2024",
+            "amount": "1.234,56",
+            "dates": ["01.02.2024", "already 2024"],
+            "version": "1.10",
+            "count": 3
+        });
+
+        assert_eq!(normalize_attributes(&mut attributes, DECIMAL_COMMA), 3);
+        assert_eq!(
+            attributes,
+            json!({
+                "due_date": "2024-12-31",
+                "amount": 1234.56,
+                "dates": ["2024-02-01", "already 2024"],
+                "version": "1.10",
+                "count": 3
+            })
+        );
+        // ISO values are left alone
+        assert_eq!(normalize_attributes(&mut attributes, DECIMAL_COMMA), 0);
+    }
+}
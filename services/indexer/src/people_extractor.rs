@@ -110,6 +110,7 @@ pub fn extract_people(
             attributes,
             ..
         } => (Some(metadata), permissions.as_ref(), attributes.as_ref()),
+        ConnectorEvent::PermissionsChanged { permissions, .. } => (None, Some(permissions), None),
         ConnectorEvent::DocumentDeleted { .. } | ConnectorEvent::GroupMembershipSync { .. } => {
             return vec![];
         }
@@ -0,0 +1,404 @@
+//! Detection and scrubbing of personal data under per-source policies.
+//!
+//! Sources with a PII policy have the extracted text of each created or
+//! updated document scanned for email addresses, phone numbers, credit card
+//! numbers and admin-defined patterns before it is stored or embedded. What
+//! is found is recorded on the document: the entity types in the filterable
+//! `pii_types` attribute and counts per type under `metadata.pii`. The policy
+//! then decides the document's fate:
+//!
+//! - `redact` indexes a copy of the content with each match replaced by a
+//!   `[TYPE]` placeholder; the connector's original blob is left for the
+//!   content GC to reclaim.
+//! - `tag_only` indexes the content as it is.
+//! - `block` quarantines the document, like an ingestion block rule.
+
+use regex::{Regex, RegexBuilder};
+use serde::Deserialize;
+use serde_json::json;
+use shared::db::repositories::{CustomPiiPattern, PiiAction, PiiDetector, SourcePiiPolicy};
+use shared::models::Document;
+use std::collections::BTreeMap;
+use std::sync::LazyLock;
+use tracing::warn;
+
+/// Attribute listing the entity types found in a document.
+pub const PII_TYPES_ATTRIBUTE: &str = "pii_types";
+/// Metadata key of the policy action and per-type match counts.
+pub const PII_METADATA_KEY: &str = "pii";
+/// Rule name of quarantined events held back by a PII policy.
+pub const PII_QUARANTINE_RULE_NAME: &str = "PII policy";
+/// Compiled custom patterns are capped so one cannot slow every batch down.
+const REGEX_SIZE_LIMIT: usize = 1024 * 1024;
+const MAX_CUSTOM_PATTERNS: usize = 50;
+
+static EMAIL: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\b[a-z0-9._%+-]+@[a-z0-9-]+(?:\.[a-z0-9-]+)*\.[a-z]{2,}\b").unwrap()
+});
+static PHONE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?:\+\d{1,3}[ .-]?)?(?:\(\d{1,4}\)[ .-]?|\b\d{2,4}[ .-])\d{3,4}[ .-]\d{3,4}\b|\+\d{8,15}\b",
+    )
+    .unwrap()
+});
+static CARD_NUMBER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\b(?:\d[ -]?){12,18}\d\b").unwrap());
+
+#[derive(Debug, Deserialize)]
+pub struct UpdatePiiPolicyRequest {
+    pub action: PiiAction,
+    /// Built-in detectors to run; all of them when unset.
+    pub detectors: Option<Vec<PiiDetector>>,
+    #[serde(default)]
+    pub custom_patterns: Vec<CustomPiiPattern>,
+    pub updated_by: Option<String>,
+}
+
+/// A detected entity, as a byte range of the scanned text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PiiMatch {
+    pub entity: String,
+    pub start: usize,
+    pub end: usize,
+}
+
+enum Detector {
+    Builtin(PiiDetector),
+    Custom { name: String, regex: Regex },
+}
+
+impl Detector {
+    fn entity(&self) -> &str {
+        match self {
+            Detector::Builtin(detector) => detector.name(),
+            Detector::Custom { name, .. } => name,
+        }
+    }
+
+    fn find(&self, content: &str) -> Vec<(usize, usize)> {
+        let (regex, valid): (&Regex, fn(&str) -> bool) = match self {
+            Detector::Builtin(PiiDetector::Email) => (&*EMAIL, |_| true),
+            Detector::Builtin(PiiDetector::Phone) => (&*PHONE, is_phone_number),
+            Detector::Builtin(PiiDetector::CreditCard) => (&*CARD_NUMBER, is_card_number),
+            Detector::Custom { regex, .. } => (regex, |_| true),
+        };
+        regex
+            .find_iter(content)
+            .filter(|m| !m.is_empty() && valid(m.as_str()))
+            .map(|m| (m.start(), m.end()))
+            .collect()
+    }
+}
+
+fn digits(value: &str) -> Vec<u32> {
+    value.chars().filter_map(|c| c.to_digit(10)).collect()
+}
+
+fn is_phone_number(value: &str) -> bool {
+    (7..=15).contains(&digits(value).len())
+}
+
+/// Card numbers are 13 to 19 digits and pass the Luhn check, which rules out
+/// most other long numbers.
+fn is_card_number(value: &str) -> bool {
+    let digits = digits(value);
+    if !(13..=19).contains(&digits.len()) {
+        return false;
+    }
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &digit)| match (i % 2, digit * 2) {
+            (0, _) => digit,
+            (_, doubled) if doubled > 9 => doubled - 9,
+            (_, doubled) => doubled,
+        })
+        .sum();
+    sum.is_multiple_of(10)
+}
+
+fn build_custom_regex(pattern: &CustomPiiPattern) -> Result<Regex, String> {
+    let name = pattern.name.trim();
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+    {
+        return Err(format!(
+            "Pattern name '{}' must be lowercase letters, digits and underscores",
+            pattern.name
+        ));
+    }
+    if PiiDetector::ALL.iter().any(|d| d.name() == name) {
+        return Err(format!("Pattern name '{}' is a built-in detector", name));
+    }
+    if pattern.pattern.trim().is_empty() {
+        return Err(format!("Pattern '{}' must not be empty", name));
+    }
+    RegexBuilder::new(&pattern.pattern)
+        .size_limit(REGEX_SIZE_LIMIT)
+        .build()
+        .map_err(|e| format!("Invalid pattern '{}': {}", name, e))
+}
+
+/// Check a policy's custom patterns, so bad ones are rejected when the
+/// policy is saved rather than skipped at ingestion.
+pub fn validate_custom_patterns(patterns: &[CustomPiiPattern]) -> Result<(), String> {
+    if patterns.len() > MAX_CUSTOM_PATTERNS {
+        return Err(format!(
+            "At most {} custom patterns are allowed",
+            MAX_CUSTOM_PATTERNS
+        ));
+    }
+    for (i, pattern) in patterns.iter().enumerate() {
+        build_custom_regex(pattern)?;
+        if patterns[..i].iter().any(|other| other.name == pattern.name) {
+            return Err(format!("Pattern name '{}' is used twice", pattern.name));
+        }
+    }
+    Ok(())
+}
+
+/// A source's policy, compiled once per batch.
+pub struct PiiScanner {
+    action: PiiAction,
+    detectors: Vec<Detector>,
+}
+
+impl PiiScanner {
+    /// Compile the policy. A custom pattern that no longer compiles is
+    /// skipped with a warning rather than failing ingestion.
+    pub fn new(policy: &SourcePiiPolicy) -> Self {
+        let builtins = policy.detectors.iter().copied().map(Detector::Builtin);
+        let custom =
+            policy
+                .custom_patterns
+                .iter()
+                .filter_map(|pattern| match build_custom_regex(pattern) {
+                    Ok(regex) => Some(Detector::Custom {
+                        name: pattern.name.trim().to_string(),
+                        regex,
+                    }),
+                    Err(e) => {
+                        warn!(
+                            "Skipping custom PII pattern of source {}: {}",
+                            policy.source_id, e
+                        );
+                        None
+                    }
+                });
+        Self {
+            action: policy.action,
+            detectors: builtins.chain(custom).collect(),
+        }
+    }
+
+    pub fn action(&self) -> PiiAction {
+        self.action
+    }
+
+    /// Entities found in `content`, in order and without overlaps. Where
+    /// matches overlap the earliest, then the longest, is kept.
+    pub fn scan(&self, content: &str) -> Vec<PiiMatch> {
+        let mut matches: Vec<PiiMatch> = self
+            .detectors
+            .iter()
+            .flat_map(|detector| {
+                detector
+                    .find(content)
+                    .into_iter()
+                    .map(|(start, end)| PiiMatch {
+                        entity: detector.entity().to_string(),
+                        start,
+                        end,
+                    })
+            })
+            .collect();
+        matches.sort_by(|a, b| a.start.cmp(&b.start).then(b.end.cmp(&a.end)));
+
+        let mut kept: Vec<PiiMatch> = Vec::with_capacity(matches.len());
+        for m in matches {
+            if kept.last().is_none_or(|last| m.start >= last.end) {
+                kept.push(m);
+            }
+        }
+        kept
+    }
+}
+
+/// `content` with each match replaced by its upper-cased entity type in
+/// brackets, e.g. `[EMAIL]`. `matches` must be ordered and not overlap, as
+/// [`PiiScanner::scan`] returns them.
+pub fn redact(content: &str, matches: &[PiiMatch]) -> String {
+    let mut redacted = String::with_capacity(content.len());
+    let mut position = 0;
+    for m in matches {
+        redacted.push_str(&content[position..m.start]);
+        redacted.push('[');
+        redacted.push_str(&m.entity.to_uppercase());
+        redacted.push(']');
+        position = m.end;
+    }
+    redacted.push_str(&content[position..]);
+    redacted
+}
+
+/// Number of matches of each entity type.
+pub fn entity_counts(matches: &[PiiMatch]) -> BTreeMap<&str, usize> {
+    let mut counts = BTreeMap::new();
+    for m in matches {
+        *counts.entry(m.entity.as_str()).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Record what was found in the document. Documents without matches are
+/// left as they are.
+pub fn annotate_document(document: &mut Document, action: PiiAction, matches: &[PiiMatch]) {
+    if matches.is_empty() {
+        return;
+    }
+    let counts = entity_counts(matches);
+    let types: Vec<&str> = counts.keys().copied().collect();
+    if let Some(attributes) = document.attributes.as_object_mut() {
+        attributes.insert(PII_TYPES_ATTRIBUTE.to_string(), json!(types));
+    }
+    if let Some(metadata) = document.metadata.as_object_mut() {
+        metadata.insert(
+            PII_METADATA_KEY.to_string(),
+            json!({ "action": action, "counts": counts }),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::types::Json;
+    use sqlx::types::time::OffsetDateTime;
+
+    fn policy(detectors: &[PiiDetector], custom: &[(&str, &str)]) -> SourcePiiPolicy {
+        let now = OffsetDateTime::now_utc();
+        SourcePiiPolicy {
+            source_id: "source-1".to_string(),
+            action: PiiAction::Redact,
+            detectors: detectors.to_vec(),
+            custom_patterns: Json(
+                custom
+                    .iter()
+                    .map(|(name, pattern)| CustomPiiPattern {
+                        name: name.to_string(),
+                        pattern: pattern.to_string(),
+                    })
+                    .collect(),
+            ),
+            updated_by: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    fn entities(scanner: &PiiScanner, content: &str) -> Vec<String> {
+        scanner
+            .scan(content)
+            .into_iter()
+            .map(|m| content[m.start..m.end].to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_builtin_detectors_find_emails_phones_and_valid_cards() {
+        let scanner = PiiScanner::new(&policy(&PiiDetector::ALL, &[]));
+        let content = "Mail Ada.Lovelace@example.co.uk or call +1 (555) 123-4567. \
+                       Card 4111 1111 1111 1111, build 2024-01-15.";
+
+        assert_eq!(
+            entities(&scanner, content),
+            vec![
+                "Ada.Lovelace@example.co.uk",
+                "+1 (555) 123-4567",
+                "4111 1111 1111 1111"
+            ]
+        );
+        assert!(!is_card_number("4111 1111 1111 1112"));
+        assert!(!is_phone_number("12-345"));
+    }
+
+    #[test]
+    fn test_only_enabled_and_custom_detectors_run() {
+        let scanner = PiiScanner::new(&policy(
+            &[PiiDetector::Email],
+            &[("employee_id", r"EMP-\d{6}"), ("bad", "(unclosed")],
+        ));
+        let matches = scanner.scan("EMP-004211 at ops@example.com, 555-123-4567");
+
+        let counts = entity_counts(&matches);
+        assert_eq!(counts.get("employee_id"), Some(&1));
+        assert_eq!(counts.get("email"), Some(&1));
+        assert_eq!(counts.get("phone"), None);
+    }
+
+    #[test]
+    fn test_redact_replaces_each_match_with_its_type() {
+        let scanner = PiiScanner::new(&policy(&PiiDetector::ALL, &[]));
+        let content = "Contact ada@example.com or 555-123-4567 today";
+
+        assert_eq!(
+            redact(content, &scanner.scan(content)),
+            "Contact [EMAIL] or [PHONE] today"
+        );
+    }
+
+    #[test]
+    fn test_annotate_records_types_and_counts() {
+        let now = OffsetDateTime::now_utc();
+        let mut document = Document {
+            id: "doc-1".to_string(),
+            source_id: "source-1".to_string(),
+            external_id: "ext-1".to_string(),
+            title: "Contacts".to_string(),
+            content_id: None,
+            content_type: None,
+            file_size: None,
+            file_extension: None,
+            url: None,
+            metadata: json!({}),
+            permissions: json!({}),
+            attributes: json!({}),
+            created_at: now,
+            updated_at: now,
+            last_indexed_at: now,
+        };
+        let scanner = PiiScanner::new(&policy(&PiiDetector::ALL, &[]));
+        let matches = scanner.scan("a@example.com, b@example.com, 555-123-4567");
+
+        annotate_document(&mut document, PiiAction::TagOnly, &matches);
+
+        assert_eq!(
+            document.attributes[PII_TYPES_ATTRIBUTE],
+            json!(["email", "phone"])
+        );
+        assert_eq!(
+            document.metadata[PII_METADATA_KEY],
+            json!({ "action": "tag_only", "counts": { "email": 2, "phone": 1 } })
+        );
+    }
+
+    #[test]
+    fn test_validate_custom_patterns() {
+        let pattern = |name: &str, pattern: &str| CustomPiiPattern {
+            name: name.to_string(),
+            pattern: pattern.to_string(),
+        };
+
+        assert!(validate_custom_patterns(&[pattern("badge_id", r"B\d{5}")]).is_ok());
+        assert!(validate_custom_patterns(&[pattern("Badge ID", r"B\d{5}")]).is_err());
+        assert!(validate_custom_patterns(&[pattern("email", r"x")]).is_err());
+        assert!(validate_custom_patterns(&[pattern("badge_id", "(unclosed")]).is_err());
+        assert!(
+            validate_custom_patterns(&[pattern("badge_id", "a"), pattern("badge_id", "b")])
+                .is_err()
+        );
+    }
+}
@@ -1,8 +1,27 @@
 use crate::AppState;
+use crate::blocklist::Blocklist;
+use crate::code;
+use crate::document_versions::VersionRetentionConfig;
+use crate::ephemeral;
+use crate::extraction::{ContentExtractor, ExtractionConfig};
+use crate::freshness::{FreshnessConfig, FreshnessMonitor};
+use crate::integrity::{IntegrityChecker, IntegrityConfig};
+use crate::language::{LANGUAGE_METADATA_KEY, detect_primary_language, metadata_language};
+use crate::link_checker::{LinkCheckConfig, LinkChecker};
+use crate::normalization::{self, Locale};
 use crate::people_extractor;
+use crate::pii::{self, PiiScanner};
+use crate::ranking_training::{RankingTrainer, RankingTrainingConfig};
+use crate::retention::{self, RetentionConfig, RetentionEnforcer};
+use crate::storage_report::{StorageAnalyzer, StorageReportConfig};
+use crate::term_dictionary::{self, TermDictionaryConfig, TermDictionaryMode};
 use anyhow::{Context, Result};
+use shared::audit::{self, Actor, AuditAction, AuditEvent};
 use shared::db::repositories::{
-    DocumentRepository, GroupRepository, PersonRepository, SyncRunRepository,
+    CorpusStatsRepository, DocumentRepository, DocumentVersionRepository,
+    EphemeralDocumentRepository, FreshnessRepository, GroupRepository,
+    IngestionBlockRuleRepository, NewQuarantinedEvent, PersonRepository, PiiAction,
+    SourcePiiPolicyRepository, SourceRepository, SourceRetentionRepository, SyncRunRepository,
 };
 use shared::embedding_queue::EmbeddingQueue;
 use shared::models::{
@@ -11,6 +30,8 @@ use shared::models::{
 };
 use shared::queue::EventQueue;
 use shared::storage::gc::{ContentBlobGC, GCConfig};
+use shared::typeahead_updates::{self, TitleUpdate};
+use sqlx::types::time::OffsetDateTime;
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::{Mutex, Semaphore};
@@ -272,12 +293,22 @@ struct GroupSyncEvent {
     event_ids: Vec<String>,
 }
 
+#[derive(Debug)]
+struct PermissionsChange {
+    source_id: String,
+    document_id: String,
+    permissions: serde_json::Value,
+    descendants_path: Option<String>,
+    event_ids: Vec<String>,
+}
+
 #[derive(Debug)]
 struct EventBatch {
     sync_run_id: String,
     documents_upsert: Vec<(Document, Vec<String>)>, // (document, event_ids) — both creates and updates
     documents_deleted: Vec<(String, String, Vec<String>)>, // (source_id, document_id, event_ids)
     group_syncs: Vec<GroupSyncEvent>,
+    permission_changes: Vec<PermissionsChange>, // in arrival order, applied after upserts
 }
 
 impl EventBatch {
@@ -287,6 +318,7 @@ impl EventBatch {
             documents_upsert: Vec::new(),
             documents_deleted: Vec::new(),
             group_syncs: Vec::new(),
+            permission_changes: Vec::new(),
         }
     }
 
@@ -294,6 +326,7 @@ impl EventBatch {
         self.documents_upsert.is_empty()
             && self.documents_deleted.is_empty()
             && self.group_syncs.is_empty()
+            && self.permission_changes.is_empty()
     }
 }
 
@@ -302,6 +335,7 @@ struct BatchProcessingResult {
     successful_event_ids: Vec<String>,
     successful_documents_count: usize,
     failed_events: Vec<(String, String)>, // (event_id, error_message)
+    title_updates: Vec<TitleUpdate>,
 }
 
 impl BatchProcessingResult {
@@ -310,6 +344,7 @@ impl BatchProcessingResult {
             successful_event_ids: Vec::new(),
             successful_documents_count: 0,
             failed_events: Vec::new(),
+            title_updates: Vec::new(),
         }
     }
 }
@@ -325,6 +360,9 @@ pub struct QueueProcessor {
     processing_mutex: Arc<Mutex<()>>,
     poll_interval: Duration,
     batching_config: BatchingConfig,
+    version_retention: VersionRetentionConfig,
+    freshness: FreshnessMonitor,
+    extractor: ContentExtractor,
 }
 
 impl QueueProcessor {
@@ -336,6 +374,8 @@ impl QueueProcessor {
         let batch_size = env_or("INDEXER_BATCH_SIZE", 2000);
         let batch_max_bytes = env_byte_size_or("INDEXER_BATCH_MAX_BYTES", DEFAULT_BATCH_MAX_BYTES);
         let poll_interval_secs = env_or("INDEXER_POLL_INTERVAL_SECS", DEFAULT_POLL_INTERVAL_SECS);
+        let freshness = FreshnessMonitor::new(state.db_pool.pool(), FreshnessConfig::from_env());
+        let extractor = ContentExtractor::new(&state, ExtractionConfig::from_env());
         Self {
             state,
             event_queue,
@@ -346,6 +386,9 @@ impl QueueProcessor {
             processing_mutex,
             poll_interval: Duration::from_secs(poll_interval_secs),
             batching_config: BatchingConfig::from_env(),
+            version_retention: VersionRetentionConfig::from_env(),
+            freshness,
+            extractor,
         }
     }
 
@@ -416,12 +459,41 @@ impl QueueProcessor {
         let mut retry_interval = interval(Duration::from_secs(300)); // 5 minutes
         let mut cleanup_interval = interval(Duration::from_secs(3600)); // 1 hour
         let mut recovery_interval = interval(Duration::from_secs(300)); // 5 minutes
+        let mut ephemeral_cleanup_interval = interval(Duration::from_secs(300)); // 5 minutes
         let mut gc_interval = interval(Duration::from_secs(3600 * 6)); // 6 hours
+        let mut language_stats_interval = interval(Duration::from_secs(3600)); // 1 hour
+        let term_dictionary_config = TermDictionaryConfig::from_env();
+        let mut term_dictionary_interval =
+            interval(Duration::from_secs(term_dictionary_config.interval_secs));
+        let mut integrity_interval = interval(Duration::from_secs(3600 * 24)); // 24 hours
+        // The first tick fires immediately; skip it so startup is not slowed by
+        // a full-table integrity scan.
+        integrity_interval.reset();
+        let mut link_check_interval = interval(Duration::from_secs(3600 * 24)); // 24 hours
+        link_check_interval.reset();
+        let mut storage_analysis_interval = interval(Duration::from_secs(3600 * 24)); // 24 hours
+        storage_analysis_interval.reset();
+        let mut freshness_interval = interval(Duration::from_secs(300)); // 5 minutes
+        freshness_interval.reset();
+        let ranking_training_config = RankingTrainingConfig::from_env();
+        let mut ranking_training_interval = interval(Duration::from_secs(
+            3600 * ranking_training_config.interval_hours,
+        ));
+        ranking_training_interval.reset();
+        let mut retention_interval = interval(Duration::from_secs(3600)); // 1 hour
+        retention_interval.reset();
 
         // GC runs off the main select as its own task so a long sweep cannot stall
         // event processing. The semaphore bounds concurrent runs to 1; overlapping
         // ticks are skipped.
         let gc_semaphore = Arc::new(Semaphore::new(1));
+        let integrity_semaphore = Arc::new(Semaphore::new(1));
+        let link_check_semaphore = Arc::new(Semaphore::new(1));
+        let term_dictionary_semaphore = Arc::new(Semaphore::new(1));
+        let storage_analysis_semaphore = Arc::new(Semaphore::new(1));
+        let freshness_semaphore = Arc::new(Semaphore::new(1));
+        let ranking_training_semaphore = Arc::new(Semaphore::new(1));
+        let retention_semaphore = Arc::new(Semaphore::new(1));
 
         info!(
             "Queue processor poll interval: {:?}, batch_size: {}, batch_max_bytes: {}, batching: full={}/{}s incremental={}/{}s realtime={}/{}s global_age={}s",
@@ -479,6 +551,15 @@ impl QueueProcessor {
                             info!("Cleaned up {} old failed embedding queue items", deleted);
                         }
                     }
+                    // Prune document versions past the retention period
+                    match DocumentVersionRepository::new(self.state.db_pool.pool())
+                        .prune(None, self.version_retention.max_versions, self.version_retention.max_age_days())
+                        .await
+                    {
+                        Ok(deleted) if deleted > 0 => info!("Pruned {} old document versions", deleted),
+                        Ok(_) => {}
+                        Err(e) => error!("Failed to prune document versions: {}", e),
+                    }
                 }
                 _ = recovery_interval.tick() => {
                     // Periodic recovery of stale processing items
@@ -494,6 +575,17 @@ impl QueueProcessor {
                         }
                     }
                 }
+                _ = ephemeral_cleanup_interval.tick() => {
+                    // Delete chat uploads that expired or whose chat is gone
+                    match EphemeralDocumentRepository::new(self.state.db_pool.pool())
+                        .delete_expired(ephemeral::CLEANUP_BATCH_SIZE)
+                        .await
+                    {
+                        Ok(deleted) if !deleted.is_empty() => info!("Deleted {} expired ephemeral documents", deleted.len()),
+                        Ok(_) => {}
+                        Err(e) => error!("Failed to delete expired ephemeral documents: {}", e),
+                    }
+                }
                 _ = gc_interval.tick() => {
                     match gc_semaphore.clone().try_acquire_owned() {
                         Ok(permit) => {
@@ -522,6 +614,147 @@ impl QueueProcessor {
                         }
                     }
                 }
+                _ = language_stats_interval.tick() => {
+                    let repo = CorpusStatsRepository::new(self.state.db_pool.pool());
+                    match repo.refresh_language_stats().await {
+                        Ok(rows) => debug!("Refreshed corpus language stats ({} rows)", rows),
+                        Err(e) => error!("Failed to refresh corpus language stats: {}", e),
+                    }
+                }
+                _ = term_dictionary_interval.tick() => {
+                    match term_dictionary_semaphore.clone().try_acquire_owned() {
+                        Ok(permit) => {
+                            let pool = self.state.db_pool.pool().clone();
+                            tokio::spawn(async move {
+                                let _permit = permit;
+                                let config = TermDictionaryConfig::from_env();
+                                if let Err(e) = term_dictionary::refresh(
+                                    &pool,
+                                    &config,
+                                    TermDictionaryMode::Incremental,
+                                )
+                                .await
+                                {
+                                    error!("Failed to refresh term dictionary: {}", e);
+                                }
+                            });
+                        }
+                        Err(_) => {
+                            debug!("Skipping term dictionary tick: previous refresh still in progress");
+                        }
+                    }
+                }
+                _ = integrity_interval.tick() => {
+                    match integrity_semaphore.clone().try_acquire_owned() {
+                        Ok(permit) => {
+                            let checker = IntegrityChecker::new(
+                                self.state.db_pool.pool(),
+                                self.embedding_queue.clone(),
+                                IntegrityConfig::from_env(),
+                            );
+                            tokio::spawn(async move {
+                                let _permit = permit;
+                                if let Err(e) = checker.run_scheduled().await {
+                                    error!("Integrity check failed: {}", e);
+                                }
+                            });
+                        }
+                        Err(_) => {
+                            debug!("Skipping integrity check tick: previous run still in progress");
+                        }
+                    }
+                }
+                _ = link_check_interval.tick() => {
+                    match link_check_semaphore.clone().try_acquire_owned() {
+                        Ok(permit) => {
+                            match LinkChecker::new(self.state.db_pool.pool(), LinkCheckConfig::from_env()) {
+                                Ok(checker) => {
+                                    tokio::spawn(async move {
+                                        let _permit = permit;
+                                        if let Err(e) = checker.run().await {
+                                            error!("Link check failed: {}", e);
+                                        }
+                                    });
+                                }
+                                Err(e) => error!("Failed to create link checker: {}", e),
+                            }
+                        }
+                        Err(_) => {
+                            debug!("Skipping link check tick: previous run still in progress");
+                        }
+                    }
+                }
+                _ = storage_analysis_interval.tick() => {
+                    match storage_analysis_semaphore.clone().try_acquire_owned() {
+                        Ok(permit) => {
+                            let analyzer = StorageAnalyzer::new(
+                                self.state.db_pool.pool(),
+                                StorageReportConfig::from_env(),
+                            );
+                            tokio::spawn(async move {
+                                let _permit = permit;
+                                if let Err(e) = analyzer.run().await {
+                                    error!("Storage analysis failed: {}", e);
+                                }
+                            });
+                        }
+                        Err(_) => {
+                            debug!("Skipping storage analysis tick: previous run still in progress");
+                        }
+                    }
+                }
+                _ = freshness_interval.tick() => {
+                    match freshness_semaphore.clone().try_acquire_owned() {
+                        Ok(permit) => {
+                            let monitor = self.freshness.clone();
+                            tokio::spawn(async move {
+                                let _permit = permit;
+                                if let Err(e) = monitor.check().await {
+                                    error!("Freshness check failed: {}", e);
+                                }
+                            });
+                        }
+                        Err(_) => {
+                            debug!("Skipping freshness check tick: previous check still in progress");
+                        }
+                    }
+                }
+                _ = ranking_training_interval.tick() => {
+                    match ranking_training_semaphore.clone().try_acquire_owned() {
+                        Ok(permit) => {
+                            let trainer = RankingTrainer::new(
+                                self.state.db_pool.pool(),
+                                ranking_training_config.clone(),
+                            );
+                            tokio::spawn(async move {
+                                let _permit = permit;
+                                if let Err(e) = trainer.run().await {
+                                    error!("Ranking training failed: {}", e);
+                                }
+                            });
+                        }
+                        Err(_) => {
+                            debug!("Skipping ranking training tick: previous run still in progress");
+                        }
+                    }
+                }
+                _ = retention_interval.tick() => {
+                    match retention_semaphore.clone().try_acquire_owned() {
+                        Ok(permit) => {
+                            let enforcer =
+                                RetentionEnforcer::new(self.state.clone(), RetentionConfig::from_env());
+                            tokio::spawn(async move {
+                                let _permit = permit;
+                                if let Err(e) = enforcer.run().await {
+                                    error!("Retention run failed: {}", e);
+                                }
+                            });
+                        }
+                        Err(_) => {
+                            debug!("Skipping retention tick: previous run still in progress");
+                        }
+                    }
+                }
             }
         }
     }
@@ -715,7 +948,19 @@ impl QueueProcessor {
                                 batch_sync_run_id, e
                             );
                         }
+
+                        // Drop cached searches that may not include these changes
+                        shared::search_cache::invalidate_sources(
+                            &self.state.redis_client,
+                            events_clone.iter().map(|event| event.source_id.as_str()),
+                        )
+                        .await;
                     }
+                    typeahead_updates::publish(
+                        &self.state.redis_client,
+                        batch_result.title_updates,
+                    )
+                    .await;
 
                     self.extract_and_upsert_people(&events_clone).await;
 
@@ -797,6 +1042,11 @@ impl QueueProcessor {
                         .map(|(_, _, event_ids)| event_ids)
                         .or_else(|| upsert_docs.remove(&key).map(|(_, event_ids)| event_ids))
                         .unwrap_or_default();
+                    event_ids.extend(Self::take_superseded_permission_changes(
+                        &mut batch.permission_changes,
+                        &source_id,
+                        &document_id,
+                    ));
                     event_ids.push(event_id);
                     upsert_docs.insert(key, (document, event_ids));
                 }
@@ -839,6 +1089,13 @@ impl QueueProcessor {
                         .map(|(_, _, event_ids)| event_ids)
                         .or_else(|| upsert_docs.remove(&key).map(|(_, event_ids)| event_ids))
                         .unwrap_or_default();
+                    if has_permissions {
+                        event_ids.extend(Self::take_superseded_permission_changes(
+                            &mut batch.permission_changes,
+                            &source_id,
+                            &document_id,
+                        ));
+                    }
                     event_ids.push(event_id);
                     upsert_docs.insert(key, (document, event_ids));
                 }
@@ -853,6 +1110,11 @@ impl QueueProcessor {
                         .map(|(_, event_ids)| event_ids)
                         .or_else(|| deleted_docs.remove(&key).map(|(_, _, event_ids)| event_ids))
                         .unwrap_or_default();
+                    event_ids.extend(Self::take_superseded_permission_changes(
+                        &mut batch.permission_changes,
+                        &source_id,
+                        &document_id,
+                    ));
                     event_ids.push(event_id);
                     deleted_docs.insert(key, (source_id, document_id, event_ids));
                 }
@@ -882,6 +1144,21 @@ impl QueueProcessor {
                         });
                     }
                 }
+                ConnectorEvent::PermissionsChanged {
+                    source_id,
+                    document_id,
+                    permissions,
+                    descendants_path,
+                    ..
+                } => {
+                    batch.permission_changes.push(PermissionsChange {
+                        source_id,
+                        document_id,
+                        permissions: serde_json::to_value(&permissions)?,
+                        descendants_path,
+                        event_ids: vec![event_id],
+                    });
+                }
             }
         }
 
@@ -891,6 +1168,27 @@ impl QueueProcessor {
         Ok(batch)
     }
 
+    /// Drops pending permission changes that a later create, update or delete
+    /// of the same document makes moot, returning their event ids. Changes
+    /// that propagate to descendants are kept since they still apply to them.
+    fn take_superseded_permission_changes(
+        changes: &mut Vec<PermissionsChange>,
+        source_id: &str,
+        document_id: &str,
+    ) -> Vec<String> {
+        let mut event_ids = Vec::new();
+        changes.retain_mut(|change| {
+            let superseded = change.descendants_path.is_none()
+                && change.source_id == source_id
+                && change.document_id == document_id;
+            if superseded {
+                event_ids.append(&mut change.event_ids);
+            }
+            !superseded
+        });
+        event_ids
+    }
+
     async fn process_event_batch(&self, batch: EventBatch) -> Result<BatchProcessingResult> {
         let mut result = BatchProcessingResult::new();
 
@@ -898,12 +1196,17 @@ impl QueueProcessor {
         if !batch.documents_upsert.is_empty() {
             let docs_count = batch.documents_upsert.len();
             match self
-                .process_documents_upsert_batch(&batch.documents_upsert)
+                .process_documents_upsert_batch(&batch.sync_run_id, &batch.documents_upsert)
                 .await
             {
                 Ok(successful_ids) => {
                     result.successful_event_ids.extend(successful_ids);
                     result.successful_documents_count += docs_count;
+                    result
+                        .title_updates
+                        .extend(batch.documents_upsert.iter().map(|(doc, _)| {
+                            TitleUpdate::documents(&doc.source_id, vec![doc.external_id.clone()])
+                        }));
                 }
                 Err(e) => {
                     error!("Batch document upsert failed: {}", e);
@@ -920,12 +1223,19 @@ impl QueueProcessor {
         if !batch.documents_deleted.is_empty() {
             let docs_count = batch.documents_deleted.len();
             match self
-                .process_documents_deleted_batch(&batch.documents_deleted)
+                .process_documents_deleted_batch(&batch.sync_run_id, &batch.documents_deleted)
                 .await
             {
                 Ok(successful_ids) => {
                     result.successful_event_ids.extend(successful_ids);
                     result.successful_documents_count += docs_count;
+                    result
+                        .title_updates
+                        .extend(batch.documents_deleted.iter().map(
+                            |(source_id, document_id, _)| {
+                                TitleUpdate::documents(source_id, vec![document_id.clone()])
+                            },
+                        ));
                 }
                 Err(e) => {
                     error!("Batch document deletion failed: {}", e);
@@ -966,6 +1276,51 @@ impl QueueProcessor {
             }
         }
 
+        // Permission changes go last so they apply on top of any upserts in
+        // this batch. They only touch the permissions column: no content
+        // fetch, no re-embedding and no new document version.
+        if !batch.permission_changes.is_empty() {
+            let repo = DocumentRepository::new(self.state.db_pool.pool());
+
+            for change in batch.permission_changes {
+                match repo
+                    .update_permissions(
+                        &change.source_id,
+                        &change.document_id,
+                        &change.permissions,
+                        change.descendants_path.as_deref(),
+                    )
+                    .await
+                {
+                    Ok(updated) => {
+                        debug!(
+                            "Updated permissions of {} documents for {}:{} (descendants of {:?})",
+                            updated, change.source_id, change.document_id, change.descendants_path
+                        );
+                        result.successful_event_ids.extend(change.event_ids);
+                        result.successful_documents_count += 1;
+                        // Descendants are not known by external id
+                        result.title_updates.push(match change.descendants_path {
+                            Some(_) => TitleUpdate::source(&change.source_id),
+                            None => TitleUpdate::documents(
+                                &change.source_id,
+                                vec![change.document_id.clone()],
+                            ),
+                        });
+                    }
+                    Err(e) => {
+                        error!(
+                            "Permission update failed for {}:{}: {}",
+                            change.source_id, change.document_id, e
+                        );
+                        for event_id in change.event_ids {
+                            result.failed_events.push((event_id, e.to_string()));
+                        }
+                    }
+                }
+            }
+        }
+
         Ok(result)
     }
 
@@ -1001,7 +1356,9 @@ impl QueueProcessor {
         let person_repo = PersonRepository::new(self.state.db_pool.pool());
 
         let mut manifest_cache: HashMap<String, shared::models::ConnectorManifest> = HashMap::new();
-        let mut seen: HashMap<String, shared::PersonUpsert> = HashMap::new();
+        let mut workspace_cache: HashMap<String, String> = HashMap::new();
+        // People are listed per workspace, so one seen in two is upserted twice
+        let mut seen: HashMap<(String, String), shared::PersonUpsert> = HashMap::new();
 
         for event_item in events {
             let event: ConnectorEvent = match serde_json::from_value(event_item.payload.clone()) {
@@ -1019,6 +1376,16 @@ impl QueueProcessor {
             }
             let manifest = manifest_cache.get(&source_id);
 
+            if !workspace_cache.contains_key(&source_id) {
+                match self.load_workspace_for_source(&source_id).await {
+                    Some(workspace_id) => {
+                        workspace_cache.insert(source_id.clone(), workspace_id);
+                    }
+                    None => continue,
+                }
+            }
+            let workspace_id = &workspace_cache[&source_id];
+
             let (extra_schema, attributes_schema, search_operators) = match manifest {
                 Some(m) => (
                     m.extra_schema.as_ref(),
@@ -1036,10 +1403,11 @@ impl QueueProcessor {
             );
 
             for person in people {
-                seen.entry(person.email.clone())
+                seen.entry((workspace_id.clone(), person.email.clone()))
                     .or_insert_with(|| shared::PersonUpsert {
                         email: person.email,
                         display_name: person.display_name,
+                        workspace_id: workspace_id.clone(),
                     });
             }
         }
@@ -1061,6 +1429,14 @@ impl QueueProcessor {
         }
     }
 
+    async fn load_workspace_for_source(&self, source_id: &str) -> Option<String> {
+        sqlx::query_scalar("SELECT workspace_id FROM sources WHERE id = $1")
+            .bind(source_id)
+            .fetch_optional(self.state.db_pool.pool())
+            .await
+            .ok()?
+    }
+
     async fn load_manifest_for_source(
         &self,
         source_id: &str,
@@ -1121,7 +1497,11 @@ impl QueueProcessor {
             .transpose()?
             .unwrap_or(serde_json::json!({}));
 
-        let file_extension = metadata.url.as_deref().and_then(infer_file_extension);
+        let file_extension = metadata
+            .url
+            .as_deref()
+            .and_then(infer_file_extension)
+            .or_else(|| metadata.path.as_deref().and_then(infer_file_extension));
 
         // Parse file size from string to i64
         let file_size = metadata
@@ -1151,8 +1531,76 @@ impl QueueProcessor {
         })
     }
 
+    /// Rewrite localized dates and numbers in attributes to ISO dates and
+    /// JSON numbers. The locale comes from the source's `locale` config, or
+    /// else from the document's detected language.
+    async fn normalize_localized_attributes(
+        &self,
+        documents: &mut [Document],
+        contents: &[String],
+        languages_by_key: &HashMap<(String, String), &'static str>,
+    ) {
+        if !documents
+            .iter()
+            .any(|doc| normalization::has_string_attributes(&doc.attributes))
+        {
+            return;
+        }
+
+        let mut source_ids: Vec<String> = documents.iter().map(|d| d.source_id.clone()).collect();
+        source_ids.sort();
+        source_ids.dedup();
+        let source_locales = SourceRepository::new(self.state.db_pool.pool())
+            .fetch_source_locale_map(&source_ids)
+            .await
+            .unwrap_or_else(|e| {
+                warn!("Failed to load source locales: {}", e);
+                HashMap::new()
+            });
+
+        for (doc, content) in documents.iter_mut().zip(contents) {
+            if !normalization::has_string_attributes(&doc.attributes) {
+                continue;
+            }
+            let locale = match source_locales.get(&doc.source_id) {
+                Some(tag) => Locale::from_tag(tag),
+                None => {
+                    let language = languages_by_key
+                        .get(&(doc.source_id.clone(), doc.external_id.clone()))
+                        .copied()
+                        .unwrap_or_else(|| detect_primary_language(content));
+                    Locale::from_language(language)
+                }
+            };
+            if let Some(locale) = locale {
+                let changed = normalization::normalize_attributes(&mut doc.attributes, locale);
+                if changed > 0 {
+                    debug!(
+                        "Normalized {} localized attribute value(s) of document {}",
+                        changed, doc.external_id
+                    );
+                }
+            }
+        }
+    }
+
+    /// Run stored documents through the upsert pipeline again with their
+    /// content from storage, as if their connector had sent them anew.
+    pub(crate) async fn reindex_documents(
+        &self,
+        job_id: &str,
+        documents: Vec<Document>,
+    ) -> Result<()> {
+        let documents_with_event_ids: Vec<(Document, Vec<String>)> =
+            documents.into_iter().map(|doc| (doc, Vec::new())).collect();
+        self.process_documents_upsert_batch(job_id, &documents_with_event_ids)
+            .await?;
+        Ok(())
+    }
+
     async fn process_documents_upsert_batch(
         &self,
+        sync_run_id: &str,
         documents_with_event_ids: &[(Document, Vec<String>)],
     ) -> Result<Vec<String>> {
         let start_time = std::time::Instant::now();
@@ -1160,6 +1608,10 @@ impl QueueProcessor {
             .iter()
             .map(|(doc, _)| doc.clone())
             .collect();
+        let documents = self.drop_expired(documents).await?;
+
+        // Files stored as they are get indexed by their extracted text
+        let documents = self.extractor.extract_documents(documents).await;
 
         // Batch fetch content from storage
         let content_fetch_start = std::time::Instant::now();
@@ -1191,17 +1643,87 @@ impl QueueProcessor {
             content_fetch_start.elapsed()
         );
 
+        // Blocked documents count as processed: their events succeed without
+        // anything being indexed.
+        let (documents, contents) = self
+            .quarantine_blocked(sync_run_id, documents, contents)
+            .await?;
+        let (mut documents, contents) = self
+            .apply_pii_policies(sync_run_id, documents, contents)
+            .await?;
+        if documents.is_empty() {
+            return Ok(documents_with_event_ids
+                .iter()
+                .flat_map(|(_, event_ids)| event_ids.clone())
+                .collect());
+        }
+
         let repo = DocumentRepository::new(self.state.db_pool.pool());
         let document_keys: Vec<(String, String)> = documents
             .iter()
             .map(|doc| (doc.source_id.clone(), doc.external_id.clone()))
             .collect();
         let existing_documents = repo.find_by_external_ids(&document_keys).await?;
+        let existing_languages_by_key: HashMap<(String, String), String> = existing_documents
+            .iter()
+            .filter_map(|doc| {
+                metadata_language(&doc.metadata).map(|language| {
+                    (
+                        (doc.source_id.clone(), doc.external_id.clone()),
+                        language.to_string(),
+                    )
+                })
+            })
+            .collect();
         let existing_content_by_key: HashMap<(String, String), Option<String>> = existing_documents
             .into_iter()
             .map(|doc| ((doc.source_id, doc.external_id), doc.content_id))
             .collect();
 
+        // Detect the primary language of new and content-changed documents while
+        // the text is in hand; unchanged content keeps its earlier detection.
+        let languages_by_key: HashMap<(String, String), &'static str> = documents
+            .iter()
+            .zip(contents.iter())
+            .filter(|(doc, _)| {
+                existing_content_by_key
+                    .get(&(doc.source_id.clone(), doc.external_id.clone()))
+                    .is_none_or(|existing_content_id| existing_content_id != &doc.content_id)
+            })
+            .map(|(doc, content)| {
+                (
+                    (doc.source_id.clone(), doc.external_id.clone()),
+                    detect_primary_language(content),
+                )
+            })
+            .collect();
+
+        // Record the language on the document itself so the search index can
+        // stem its content for that language.
+        for doc in documents.iter_mut() {
+            let key = (doc.source_id.clone(), doc.external_id.clone());
+            let language = languages_by_key
+                .get(&key)
+                .copied()
+                .or_else(|| existing_languages_by_key.get(&key).map(String::as_str));
+            if let (Some(language), Some(metadata)) = (language, doc.metadata.as_object_mut()) {
+                metadata.insert(LANGUAGE_METADATA_KEY.to_string(), language.into());
+            }
+        }
+
+        self.normalize_localized_attributes(&mut documents, &contents, &languages_by_key)
+            .await;
+
+        let code_documents = documents
+            .iter_mut()
+            .zip(contents.iter())
+            .map(|(doc, content)| code::annotate_code_document(doc, content))
+            .filter(|is_code| *is_code)
+            .count();
+        if code_documents > 0 {
+            debug!("Annotated {} source file(s) as code", code_documents);
+        }
+
         // Batch upsert documents with content
         let upsert_start = std::time::Instant::now();
         let upserted_documents = repo.batch_upsert(documents, contents).await?;
@@ -1210,6 +1732,31 @@ impl QueueProcessor {
             upserted_documents.len(),
             upsert_start.elapsed()
         );
+        let events = upserted_documents
+            .iter()
+            .map(|doc| document_audit_event(AuditAction::DocumentIndexed, doc, sync_run_id))
+            .collect();
+        audit::record_all(self.state.db_pool.pool(), events).await;
+
+        let document_languages: Vec<(String, String)> = upserted_documents
+            .iter()
+            .filter_map(|doc| {
+                languages_by_key
+                    .get(&(doc.source_id.clone(), doc.external_id.clone()))
+                    .map(|language| (doc.id.clone(), language.to_string()))
+            })
+            .collect();
+        // Language stats are advisory; a failure here must not fail the batch.
+        if let Err(e) = CorpusStatsRepository::new(self.state.db_pool.pool())
+            .upsert_document_languages(&document_languages)
+            .await
+        {
+            warn!(
+                "Failed to record languages for {} documents: {}",
+                document_languages.len(),
+                e
+            );
+        }
 
         let changed_content_doc_ids: Vec<String> = upserted_documents
             .iter()
@@ -1221,6 +1768,24 @@ impl QueueProcessor {
             .map(|doc| doc.id.clone())
             .collect();
 
+        // New content adds a version; drop the ones now beyond retention.
+        // Like language stats, this must not fail the batch.
+        if !changed_content_doc_ids.is_empty()
+            && let Err(e) = DocumentVersionRepository::new(self.state.db_pool.pool())
+                .prune(
+                    Some(&changed_content_doc_ids),
+                    self.version_retention.max_versions,
+                    self.version_retention.max_age_days(),
+                )
+                .await
+        {
+            warn!(
+                "Failed to prune versions of {} documents: {}",
+                changed_content_doc_ids.len(),
+                e
+            );
+        }
+
         let changed_content_doc_id_set: std::collections::HashSet<String> =
             changed_content_doc_ids.iter().cloned().collect();
 
@@ -1276,6 +1841,36 @@ impl QueueProcessor {
             embedding_start.elapsed()
         );
 
+        // Freshness tracking is advisory; a failure here must not fail the batch.
+        let event_ids_by_key: HashMap<(String, String), &Vec<String>> = documents_with_event_ids
+            .iter()
+            .map(|(doc, event_ids)| ((doc.source_id.clone(), doc.external_id.clone()), event_ids))
+            .collect();
+        let (freshness_doc_ids, freshness_event_ids): (Vec<String>, Vec<String>) =
+            upserted_documents
+                .iter()
+                .filter_map(|doc| {
+                    event_ids_by_key
+                        .get(&(doc.source_id.clone(), doc.external_id.clone()))
+                        .map(|event_ids| (doc, *event_ids))
+                })
+                .flat_map(|(doc, event_ids)| {
+                    event_ids
+                        .iter()
+                        .map(|event_id| (doc.id.clone(), event_id.clone()))
+                })
+                .unzip();
+        if let Err(e) = FreshnessRepository::new(self.state.db_pool.pool())
+            .record_indexed(&freshness_doc_ids, &freshness_event_ids)
+            .await
+        {
+            warn!(
+                "Failed to record freshness of {} documents: {}",
+                upserted_documents.len(),
+                e
+            );
+        }
+
         let total_duration = start_time.elapsed();
         info!(
             "Batch processed {} documents successfully (took {:?}, {:.1} docs/sec)",
@@ -1291,8 +1886,228 @@ impl QueueProcessor {
             .collect())
     }
 
+    /// Drop documents their source's retention policy has already expired,
+    /// so a connector sending them again does not bring back what a purge
+    /// removed. Their events succeed without anything being indexed.
+    async fn drop_expired(&self, documents: Vec<Document>) -> Result<Vec<Document>> {
+        let retention_repo = SourceRetentionRepository::new(self.state.db_pool.pool());
+        let now = OffsetDateTime::now_utc();
+        let mut cutoffs: HashMap<String, Option<OffsetDateTime>> = HashMap::new();
+        for document in &documents {
+            if !cutoffs.contains_key(&document.source_id) {
+                let cutoff = retention_repo
+                    .get_policy(&document.source_id)
+                    .await?
+                    .map(|policy| retention::cutoff(now, policy.retention_days));
+                cutoffs.insert(document.source_id.clone(), cutoff);
+            }
+        }
+
+        let total = documents.len();
+        let kept: Vec<Document> = documents
+            .into_iter()
+            .filter(|document| {
+                cutoffs[&document.source_id]
+                    .is_none_or(|cutoff| retention::source_updated_at(document, now) >= cutoff)
+            })
+            .collect();
+        if kept.len() < total {
+            info!(
+                "Dropped {} documents past their source's retention limit",
+                total - kept.len()
+            );
+        }
+
+        Ok(kept)
+    }
+
+    /// Drop documents matching an ingestion block rule. Each is recorded as a
+    /// quarantined event, and a copy indexed before the rule existed is
+    /// deleted. Returns the remaining documents with their contents.
+    async fn quarantine_blocked(
+        &self,
+        sync_run_id: &str,
+        documents: Vec<Document>,
+        contents: Vec<String>,
+    ) -> Result<(Vec<Document>, Vec<String>)> {
+        let rule_repo = IngestionBlockRuleRepository::new(self.state.db_pool.pool());
+        let blocklist = Blocklist::new(&rule_repo.list_enabled().await?);
+        if blocklist.is_empty() {
+            return Ok((documents, contents));
+        }
+
+        let mut kept_documents = Vec::with_capacity(documents.len());
+        let mut kept_contents = Vec::with_capacity(contents.len());
+        let mut quarantined = Vec::new();
+        for (document, content) in documents.into_iter().zip(contents) {
+            match blocklist.check(&document, &content) {
+                Some(block) => quarantined.push(NewQuarantinedEvent {
+                    rule_id: Some(block.rule_id),
+                    rule_name: block.rule_name,
+                    path: document
+                        .metadata
+                        .get("path")
+                        .and_then(|p| p.as_str())
+                        .map(str::to_string),
+                    source_id: document.source_id,
+                    external_id: document.external_id,
+                    title: document.title,
+                    url: document.url,
+                    matched_value: block.matched_value,
+                    sync_run_id: Some(sync_run_id.to_string()),
+                }),
+                None => {
+                    kept_documents.push(document);
+                    kept_contents.push(content);
+                }
+            }
+        }
+        if quarantined.is_empty() {
+            return Ok((kept_documents, kept_contents));
+        }
+
+        self.record_quarantined(&quarantined).await?;
+        info!(
+            "Quarantined {} documents matching ingestion block rules",
+            quarantined.len()
+        );
+
+        Ok((kept_documents, kept_contents))
+    }
+
+    /// Scan documents of sources with a PII policy and record what is found
+    /// on them. Matches are redacted from the content, or the document is
+    /// quarantined, as the policy says. Returns the remaining documents with
+    /// their contents.
+    async fn apply_pii_policies(
+        &self,
+        sync_run_id: &str,
+        documents: Vec<Document>,
+        contents: Vec<String>,
+    ) -> Result<(Vec<Document>, Vec<String>)> {
+        let mut source_ids: Vec<String> = documents.iter().map(|d| d.source_id.clone()).collect();
+        source_ids.sort();
+        source_ids.dedup();
+        let scanners: HashMap<String, PiiScanner> =
+            SourcePiiPolicyRepository::new(self.state.db_pool.pool())
+                .list_for_sources(&source_ids)
+                .await?
+                .iter()
+                .map(|policy| (policy.source_id.clone(), PiiScanner::new(policy)))
+                .collect();
+        if scanners.is_empty() {
+            return Ok((documents, contents));
+        }
+
+        let mut kept_documents = Vec::with_capacity(documents.len());
+        let mut kept_contents = Vec::with_capacity(contents.len());
+        let mut quarantined = Vec::new();
+        let mut redacted = 0;
+        for (mut document, content) in documents.into_iter().zip(contents) {
+            let Some(scanner) = scanners.get(&document.source_id) else {
+                kept_documents.push(document);
+                kept_contents.push(content);
+                continue;
+            };
+            let matches = scanner.scan(&content);
+            if matches.is_empty() {
+                kept_documents.push(document);
+                kept_contents.push(content);
+                continue;
+            }
+
+            match scanner.action() {
+                PiiAction::Block => {
+                    let types: Vec<&str> = pii::entity_counts(&matches).into_keys().collect();
+                    quarantined.push(NewQuarantinedEvent {
+                        rule_id: None,
+                        rule_name: pii::PII_QUARANTINE_RULE_NAME.to_string(),
+                        path: document
+                            .metadata
+                            .get("path")
+                            .and_then(|p| p.as_str())
+                            .map(str::to_string),
+                        source_id: document.source_id,
+                        external_id: document.external_id,
+                        title: document.title,
+                        url: document.url,
+                        // The entity types only, never the personal data itself
+                        matched_value: types.join(", "),
+                        sync_run_id: Some(sync_run_id.to_string()),
+                    });
+                }
+                PiiAction::Redact => {
+                    let content = pii::redact(&content, &matches);
+                    let content_id = self
+                        .state
+                        .content_storage
+                        .store_content_with_type(content.as_bytes(), Some("text/plain"), None)
+                        .await
+                        .with_context(|| {
+                            format!(
+                                "Failed to store redacted content of document {}",
+                                document.external_id
+                            )
+                        })?;
+                    document.content_id = Some(content_id);
+                    pii::annotate_document(&mut document, PiiAction::Redact, &matches);
+                    kept_documents.push(document);
+                    kept_contents.push(content);
+                    redacted += 1;
+                }
+                PiiAction::TagOnly => {
+                    pii::annotate_document(&mut document, PiiAction::TagOnly, &matches);
+                    kept_documents.push(document);
+                    kept_contents.push(content);
+                }
+            }
+        }
+
+        if redacted > 0 {
+            info!("Redacted personal data from {} documents", redacted);
+        }
+        if !quarantined.is_empty() {
+            self.record_quarantined(&quarantined).await?;
+            info!(
+                "Quarantined {} documents containing personal data",
+                quarantined.len()
+            );
+        }
+
+        Ok((kept_documents, kept_contents))
+    }
+
+    /// Record documents held back from indexing, deleting any copy of them
+    /// indexed earlier.
+    async fn record_quarantined(&self, quarantined: &[NewQuarantinedEvent]) -> Result<()> {
+        let repo = DocumentRepository::new(self.state.db_pool.pool());
+        let keys: Vec<(String, String)> = quarantined
+            .iter()
+            .map(|event| (event.source_id.clone(), event.external_id.clone()))
+            .collect();
+        let indexed_ids: Vec<String> = repo
+            .find_by_external_ids(&keys)
+            .await?
+            .into_iter()
+            .map(|doc| doc.id)
+            .collect();
+        if !indexed_ids.is_empty() {
+            let deleted = repo.batch_delete(indexed_ids).await?;
+            info!(
+                "Deleted {} indexed documents now blocked from ingestion",
+                deleted
+            );
+        }
+
+        IngestionBlockRuleRepository::new(self.state.db_pool.pool())
+            .record_quarantined(quarantined)
+            .await?;
+        Ok(())
+    }
+
     async fn process_documents_deleted_batch(
         &self,
+        sync_run_id: &str,
         deletions: &[(String, String, Vec<String>)], // (source_id, document_id, event_ids)
     ) -> Result<Vec<String>> {
         let start_time = std::time::Instant::now();
@@ -1334,11 +2149,28 @@ impl QueueProcessor {
                 "Batch deleted {} documents and their embeddings (took {:?})",
                 deleted_count, total_duration
             );
+
+            let events = found_documents
+                .iter()
+                .map(|doc| document_audit_event(AuditAction::DocumentDeleted, doc, sync_run_id))
+                .collect();
+            audit::record_all(self.state.db_pool.pool(), events).await;
         }
 
         Ok(successful_event_ids)
     }
 }
+/// Audit entry for a connector indexing or deleting `document` during a
+/// sync run, or for a reindex job, whose id then stands in for the run's.
+fn document_audit_event(action: AuditAction, document: &Document, sync_run_id: &str) -> AuditEvent {
+    AuditEvent::new(Actor::connector(&document.source_id), action)
+        .resource("document", &document.id)
+        .details(serde_json::json!({
+            "external_id": document.external_id,
+            "sync_run_id": sync_run_id,
+        }))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1354,6 +2186,34 @@ mod tests {
         assert_eq!(parse_byte_size("not-bytes"), None);
     }
 
+    #[test]
+    fn test_later_document_event_supersedes_pending_permission_change() {
+        let change =
+            |document_id: &str, descendants_path: Option<&str>, event_id: &str| PermissionsChange {
+                source_id: "src".to_string(),
+                document_id: document_id.to_string(),
+                permissions: serde_json::json!({"public": true}),
+                descendants_path: descendants_path.map(str::to_string),
+                event_ids: vec![event_id.to_string()],
+            };
+        let mut changes = vec![
+            change("doc-1", None, "e1"),
+            change("doc-2", None, "e2"),
+            change("doc-1", Some("/Shared/doc-1"), "e3"),
+        ];
+
+        let taken =
+            QueueProcessor::take_superseded_permission_changes(&mut changes, "src", "doc-1");
+
+        // The propagating change still applies to descendants, so it stays
+        assert_eq!(taken, vec!["e1".to_string()]);
+        let remaining: Vec<&str> = changes
+            .iter()
+            .flat_map(|c| c.event_ids.iter().map(String::as_str))
+            .collect();
+        assert_eq!(remaining, vec!["e2", "e3"]);
+    }
+
     #[test]
     fn test_summarize_pending_tracks_size_bytes_and_ready_by_bytes() {
         let summary = QueueSummary {
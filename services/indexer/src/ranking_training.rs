@@ -0,0 +1,216 @@
+//! Offline training of the hybrid search ranking profile.
+//!
+//! Periodically turns the clicks on logged searches into pairwise preferences
+//! and fits new feature weights to them (see `shared::ranking`). Every fifth
+//! search is held out, and the new profile only replaces the active one when
+//! it orders the held-out preferences better than the baseline weights do.
+//! The searcher picks up the active profile on its next refresh.
+
+use anyhow::Result;
+use serde::Serialize;
+use shared::db::repositories::{
+    ClickedSearch, NewRankingProfile, RankingProfileRepository, StoredRankingProfile,
+};
+use shared::ranking::{
+    PreferencePair, RankingProfile, TrainingOptions, pairwise_accuracy, preference_pairs, train,
+};
+use sqlx::PgPool;
+use sqlx::types::time::OffsetDateTime;
+use time::Duration as TimeDuration;
+use tracing::info;
+
+const DEFAULT_INTERVAL_HOURS: u64 = 24;
+const DEFAULT_WINDOW_DAYS: i64 = 30;
+const DEFAULT_MIN_PAIRS: usize = 200;
+/// One search in this many is held out for evaluation.
+const HOLDOUT_EVERY: usize = 5;
+
+#[derive(Debug, Clone)]
+pub struct RankingTrainingConfig {
+    pub interval_hours: u64,
+    /// Days of searches to train on.
+    pub window_days: i64,
+    /// Fewer training preferences than this leave the active profile as is.
+    pub min_pairs: usize,
+}
+
+impl Default for RankingTrainingConfig {
+    fn default() -> Self {
+        Self {
+            interval_hours: DEFAULT_INTERVAL_HOURS,
+            window_days: DEFAULT_WINDOW_DAYS,
+            min_pairs: DEFAULT_MIN_PAIRS,
+        }
+    }
+}
+
+impl RankingTrainingConfig {
+    pub fn from_env() -> Self {
+        fn env_or<T: std::str::FromStr>(key: &str, default: T) -> T {
+            std::env::var(key)
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default)
+        }
+
+        Self {
+            interval_hours: env_or(
+                "INDEXER_RANKING_TRAINING_INTERVAL_HOURS",
+                DEFAULT_INTERVAL_HOURS,
+            )
+            .max(1),
+            window_days: env_or("INDEXER_RANKING_TRAINING_WINDOW_DAYS", DEFAULT_WINDOW_DAYS).max(1),
+            min_pairs: env_or("INDEXER_RANKING_TRAINING_MIN_PAIRS", DEFAULT_MIN_PAIRS).max(1),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RankingTrainingResult {
+    /// Searches with clicks in the window.
+    pub searches: usize,
+    pub training_pairs: usize,
+    pub holdout_pairs: usize,
+    pub baseline_accuracy: Option<f64>,
+    pub accuracy: Option<f64>,
+    /// The stored profile; unset when there were too few preferences to train.
+    pub profile: Option<StoredRankingProfile>,
+    pub activated: bool,
+}
+
+/// Preferences from the searches, split into training and held-out sets.
+pub fn split_pairs(searches: &[ClickedSearch]) -> (Vec<PreferencePair>, Vec<PreferencePair>) {
+    let mut training = Vec::new();
+    let mut holdout = Vec::new();
+    for (i, search) in searches.iter().enumerate() {
+        let pairs = preference_pairs(&search.impressions, &search.clicked_document_ids);
+        if i % HOLDOUT_EVERY == HOLDOUT_EVERY - 1 {
+            holdout.extend(pairs);
+        } else {
+            training.extend(pairs);
+        }
+    }
+    (training, holdout)
+}
+
+/// Whether a profile with held-out `accuracy` should replace the active one.
+pub fn beats_baseline(accuracy: Option<f64>, baseline_accuracy: Option<f64>) -> bool {
+    matches!((accuracy, baseline_accuracy), (Some(a), Some(b)) if a > b)
+}
+
+pub struct RankingTrainer {
+    repo: RankingProfileRepository,
+    config: RankingTrainingConfig,
+}
+
+impl RankingTrainer {
+    pub fn new(pool: &PgPool, config: RankingTrainingConfig) -> Self {
+        Self {
+            repo: RankingProfileRepository::new(pool),
+            config,
+        }
+    }
+
+    pub async fn active(&self) -> Result<Option<StoredRankingProfile>> {
+        Ok(self.repo.active().await?)
+    }
+
+    pub async fn run(&self) -> Result<RankingTrainingResult> {
+        let since = OffsetDateTime::now_utc() - TimeDuration::days(self.config.window_days);
+        let searches = self.repo.clicked_searches(since).await?;
+        let (training, holdout) = split_pairs(&searches);
+
+        let mut result = RankingTrainingResult {
+            searches: searches.len(),
+            training_pairs: training.len(),
+            holdout_pairs: holdout.len(),
+            baseline_accuracy: None,
+            accuracy: None,
+            profile: None,
+            activated: false,
+        };
+        if training.len() < self.config.min_pairs {
+            info!(
+                "Ranking training skipped: {} preferences from {} searches, need {}",
+                training.len(),
+                searches.len(),
+                self.config.min_pairs
+            );
+            return Ok(result);
+        }
+
+        let weights = train(&training, &TrainingOptions::default());
+        result.baseline_accuracy = pairwise_accuracy(&RankingProfile::baseline(), &holdout);
+        result.accuracy = pairwise_accuracy(&weights, &holdout);
+        result.activated = beats_baseline(result.accuracy, result.baseline_accuracy);
+
+        let profile = self
+            .repo
+            .create(
+                &NewRankingProfile {
+                    weights,
+                    training_searches: searches.len() as i32,
+                    training_pairs: training.len() as i32,
+                    baseline_accuracy: result.baseline_accuracy,
+                    accuracy: result.accuracy,
+                },
+                result.activated,
+            )
+            .await?;
+        info!(
+            "Ranking profile {} trained on {} preferences from {} searches: held-out accuracy {:?} vs baseline {:?}, {}",
+            profile.id,
+            training.len(),
+            searches.len(),
+            result.accuracy,
+            result.baseline_accuracy,
+            if result.activated {
+                "activated"
+            } else {
+                "not activated"
+            }
+        );
+        result.profile = Some(profile);
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::ranking::{RankingFeatures, RankingImpression};
+    use sqlx::types::Json;
+
+    fn search(clicked: &str) -> ClickedSearch {
+        let impressions = ["a", "b"]
+            .into_iter()
+            .map(|id| RankingImpression {
+                document_id: id.to_string(),
+                features: RankingFeatures::default(),
+            })
+            .collect();
+        ClickedSearch {
+            impressions: Json(impressions),
+            clicked_document_ids: vec![clicked.to_string()],
+        }
+    }
+
+    #[test]
+    fn test_split_holds_out_every_fifth_search() {
+        let searches: Vec<ClickedSearch> = (0..10).map(|_| search("b")).collect();
+
+        let (training, holdout) = split_pairs(&searches);
+
+        assert_eq!(training.len(), 8);
+        assert_eq!(holdout.len(), 2);
+    }
+
+    #[test]
+    fn test_activates_only_when_better_than_baseline() {
+        assert!(beats_baseline(Some(0.8), Some(0.6)));
+        assert!(!beats_baseline(Some(0.6), Some(0.6)));
+        assert!(!beats_baseline(Some(0.5), Some(0.6)));
+        assert!(!beats_baseline(None, None));
+    }
+}
@@ -0,0 +1,301 @@
+//! Per-source document retention.
+//!
+//! A source's retention policy caps how long its documents stay searchable,
+//! measured from when the source last updated them. A periodic run purges
+//! every document past its source's limit together with its embeddings,
+//! chunks and versions, and keeps a tombstone of each as the report of what
+//! was removed. An expired document a connector sends again is dropped on
+//! arrival, so what a purge removed does not come back.
+
+use crate::AppState;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use shared::audit::{self, Actor, AuditAction, AuditEvent};
+use shared::db::repositories::{RetentionPurge, SourceRetentionPolicy, SourceRetentionRepository};
+use shared::models::Document;
+use shared::search_cache;
+use shared::typeahead_updates::{self, TitleUpdate};
+use sqlx::types::time::OffsetDateTime;
+use time::Duration as TimeDuration;
+use time::format_description::well_known::Rfc3339;
+use tracing::{error, info};
+
+const DEFAULT_BATCH_SIZE: i64 = 500;
+/// A hundred years; longer limits are almost certainly a unit mistake.
+pub const MAX_RETENTION_DAYS: i32 = 36_500;
+const DEFAULT_PURGED_DOCUMENTS_LIMIT: i64 = 100;
+const MAX_PURGED_DOCUMENTS_LIMIT: i64 = 1000;
+
+#[derive(Debug, Clone)]
+pub struct RetentionConfig {
+    /// Documents purged per transaction.
+    pub batch_size: i64,
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            batch_size: DEFAULT_BATCH_SIZE,
+        }
+    }
+}
+
+impl RetentionConfig {
+    pub fn from_env() -> Self {
+        fn env_or<T: std::str::FromStr>(key: &str, default: T) -> T {
+            std::env::var(key)
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default)
+        }
+
+        Self {
+            batch_size: env_or("INDEXER_RETENTION_BATCH_SIZE", DEFAULT_BATCH_SIZE).max(1),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateRetentionPolicyRequest {
+    pub retention_days: i32,
+    pub updated_by: Option<String>,
+}
+
+impl UpdateRetentionPolicyRequest {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(1..=MAX_RETENTION_DAYS).contains(&self.retention_days) {
+            return Err(format!(
+                "retention_days must be between 1 and {}",
+                MAX_RETENTION_DAYS
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PurgedDocumentsQuery {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+impl PurgedDocumentsQuery {
+    pub fn limit(&self) -> i64 {
+        self.limit
+            .unwrap_or(DEFAULT_PURGED_DOCUMENTS_LIMIT)
+            .clamp(1, MAX_PURGED_DOCUMENTS_LIMIT)
+    }
+
+    pub fn offset(&self) -> i64 {
+        self.offset.unwrap_or(0).max(0)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct RetentionRunResult {
+    pub policies_checked: usize,
+    /// Runs that purged documents or failed; sources with nothing expired
+    /// are not recorded.
+    pub purges: Vec<RetentionPurge>,
+}
+
+/// Documents of a source with `retention_days` last updated before the
+/// returned time are expired.
+pub fn cutoff(now: OffsetDateTime, retention_days: i32) -> OffsetDateTime {
+    now - TimeDuration::days(retention_days as i64)
+}
+
+/// When the source last updated an incoming document: the `updated_at` it
+/// reports when valid, else `now`, when it is indexed. Retention measures an
+/// indexed document's age the same way.
+pub fn source_updated_at(document: &Document, now: OffsetDateTime) -> OffsetDateTime {
+    document
+        .metadata
+        .get("updated_at")
+        .and_then(|updated_at| updated_at.as_str())
+        .and_then(|updated_at| OffsetDateTime::parse(updated_at, &Rfc3339).ok())
+        .unwrap_or(now)
+}
+
+pub struct RetentionEnforcer {
+    state: AppState,
+    config: RetentionConfig,
+}
+
+impl RetentionEnforcer {
+    pub fn new(state: AppState, config: RetentionConfig) -> Self {
+        Self { state, config }
+    }
+
+    fn repo(&self) -> SourceRetentionRepository {
+        SourceRetentionRepository::new(self.state.db_pool.pool())
+    }
+
+    /// Fail purges a previous process was still running.
+    pub async fn recover_orphaned(&self) -> Result<u64> {
+        Ok(self.repo().fail_orphaned().await?)
+    }
+
+    /// Enforce every source's policy. A failing source is recorded and does
+    /// not stop the others.
+    pub async fn run(&self) -> Result<RetentionRunResult> {
+        let policies = self.repo().list_policies().await?;
+        let now = OffsetDateTime::now_utc();
+
+        let mut purges = Vec::new();
+        for policy in &policies {
+            match self.enforce(policy, now).await {
+                Ok(Some(purge)) => purges.push(purge),
+                Ok(None) => {}
+                Err(e) => error!(
+                    "Failed to enforce the retention policy of source {}: {}",
+                    policy.source_id, e
+                ),
+            }
+        }
+
+        Ok(RetentionRunResult {
+            policies_checked: policies.len(),
+            purges,
+        })
+    }
+
+    async fn enforce(
+        &self,
+        policy: &SourceRetentionPolicy,
+        now: OffsetDateTime,
+    ) -> Result<Option<RetentionPurge>> {
+        let repo = self.repo();
+        let cutoff = cutoff(now, policy.retention_days);
+        if !repo.has_expired(&policy.source_id, cutoff).await? {
+            return Ok(None);
+        }
+
+        let purge = repo.start_purge(policy, cutoff).await?;
+        let result = self.purge_expired(&purge).await;
+        search_cache::invalidate_sources(&self.state.redis_client, [purge.source_id.as_str()])
+            .await;
+        typeahead_updates::publish(
+            &self.state.redis_client,
+            [TitleUpdate::source(&purge.source_id)],
+        )
+        .await;
+
+        match result {
+            Ok(()) => {
+                let purge = repo.complete_purge(&purge.id).await?;
+                info!(
+                    "Retention purge {} of source {} removed {} documents and {} embeddings older than {} days",
+                    purge.id,
+                    purge.source_id,
+                    purge.documents_purged,
+                    purge.embeddings_purged,
+                    purge.retention_days
+                );
+                Ok(Some(purge))
+            }
+            Err(e) => {
+                error!(
+                    "Retention purge {} of source {} failed: {}",
+                    purge.id, purge.source_id, e
+                );
+                repo.fail_purge(&purge.id, &e.to_string()).await?;
+                Ok(repo.get_purge(&purge.id).await?)
+            }
+        }
+    }
+
+    async fn purge_expired(&self, purge: &RetentionPurge) -> Result<()> {
+        let repo = self.repo();
+        loop {
+            let batch = repo.purge_batch(purge, self.config.batch_size).await?;
+            if batch.documents.is_empty() {
+                return Ok(());
+            }
+
+            let events = batch
+                .documents
+                .iter()
+                .map(|doc| {
+                    AuditEvent::new(Actor::system(), AuditAction::DocumentDeleted)
+                        .resource("document", &doc.document_id)
+                        .details(serde_json::json!({
+                            "source_id": purge.source_id,
+                            "external_id": doc.external_id,
+                            "retention_purge_id": purge.id,
+                        }))
+                })
+                .collect();
+            audit::record_all(self.state.db_pool.pool(), events).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cutoff_is_retention_days_before_now() {
+        let now = OffsetDateTime::from_unix_timestamp(1_700_000_000).unwrap();
+        assert_eq!(cutoff(now, 1), now - TimeDuration::days(1));
+        assert_eq!(
+            cutoff(now, 548).unix_timestamp(),
+            1_700_000_000 - 548 * 86_400
+        );
+    }
+
+    #[test]
+    fn test_source_updated_at_falls_back_to_now() {
+        let now = OffsetDateTime::from_unix_timestamp(1_700_000_000).unwrap();
+        let document = |metadata| Document {
+            id: "doc".to_string(),
+            source_id: "source".to_string(),
+            external_id: "ext".to_string(),
+            title: "Doc".to_string(),
+            content_id: None,
+            content_type: None,
+            file_size: None,
+            file_extension: None,
+            url: None,
+            metadata,
+            permissions: serde_json::json!({}),
+            attributes: serde_json::json!({}),
+            created_at: now,
+            updated_at: now,
+            last_indexed_at: now,
+        };
+
+        assert_eq!(
+            source_updated_at(
+                &document(serde_json::json!({"updated_at": "2023-01-02T03:04:05Z"})),
+                now
+            ),
+            OffsetDateTime::parse("2023-01-02T03:04:05Z", &Rfc3339).unwrap()
+        );
+        assert_eq!(
+            source_updated_at(
+                &document(serde_json::json!({"updated_at": "yesterday"})),
+                now
+            ),
+            now
+        );
+        assert_eq!(
+            source_updated_at(&document(serde_json::json!({})), now),
+            now
+        );
+    }
+
+    #[test]
+    fn test_validate_bounds_retention_days() {
+        let request = |retention_days| UpdateRetentionPolicyRequest {
+            retention_days,
+            updated_by: None,
+        };
+        assert!(request(548).validate().is_ok());
+        assert!(request(MAX_RETENTION_DAYS).validate().is_ok());
+        assert!(request(0).validate().is_err());
+        assert!(request(-30).validate().is_err());
+        assert!(request(MAX_RETENTION_DAYS + 1).validate().is_err());
+    }
+}
@@ -0,0 +1,198 @@
+//! Forced reindexing of a single source.
+//!
+//! After a fix to how documents are processed, a source's existing documents
+//! keep what the old code derived from them until their connector happens to
+//! re-send them. A reindex job walks the source's documents in batches,
+//! deletes their embeddings, and runs each batch through the indexing
+//! pipeline again with the content already in storage, which queues the
+//! documents to be embedded anew. Searches keep working throughout: fulltext
+//! matches are unaffected, and a document is only missing from semantic
+//! results until it is embedded again. The job completes once the source's
+//! embedding work has drained.
+
+use crate::AppState;
+use crate::queue_processor::QueueProcessor;
+use serde::Serialize;
+use shared::DatabaseError;
+use shared::db::repositories::{SourceReindexJob, SourceReindexProgress, SourceReindexRepository};
+use shared::search_cache;
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+/// Documents cleared and reindexed at a time.
+const REINDEX_BATCH_SIZE: i64 = 200;
+/// How often jobs waiting on embeddings are checked for completion.
+const MONITOR_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Serialize)]
+pub struct SourceReindexStatusResponse {
+    #[serde(flatten)]
+    pub job: SourceReindexJob,
+    /// Present while the job is in flight.
+    pub progress: Option<SourceReindexProgressResponse>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SourceReindexProgressResponse {
+    #[serde(flatten)]
+    pub progress: SourceReindexProgress,
+    pub percent: f64,
+}
+
+#[derive(Clone)]
+pub struct SourceReindexer {
+    state: AppState,
+}
+
+impl SourceReindexer {
+    pub fn new(state: AppState) -> Self {
+        Self { state }
+    }
+
+    fn repo(&self) -> SourceReindexRepository {
+        SourceReindexRepository::new(self.state.db_pool.pool())
+    }
+
+    /// Record a reindex of the source and run it in the background.
+    pub async fn start(&self, source_id: &str) -> Result<SourceReindexJob, DatabaseError> {
+        let job = self.repo().create(source_id).await?;
+
+        info!(
+            "Starting reindex {} of source {} ({} documents)",
+            job.id, job.source_id, job.total_documents
+        );
+
+        let reindexer = self.clone();
+        let queued = job.clone();
+        tokio::spawn(async move {
+            reindexer.run(queued).await;
+        });
+
+        Ok(job)
+    }
+
+    pub async fn status(
+        &self,
+        id: &str,
+    ) -> Result<Option<SourceReindexStatusResponse>, DatabaseError> {
+        let repo = self.repo();
+        let Some(job) = repo.get(id).await? else {
+            return Ok(None);
+        };
+
+        let progress = if job.status.is_active() {
+            let progress = repo.progress(&job).await?;
+            Some(SourceReindexProgressResponse {
+                percent: progress.percent(&job),
+                progress,
+            })
+        } else {
+            None
+        };
+
+        Ok(Some(SourceReindexStatusResponse { job, progress }))
+    }
+
+    pub async fn list(
+        &self,
+        source_id: &str,
+        limit: i64,
+    ) -> Result<Vec<SourceReindexJob>, DatabaseError> {
+        self.repo().list_for_source(source_id, limit).await
+    }
+
+    /// Fail jobs a previous process was still reindexing. Like index builds,
+    /// they are driven by the indexer that started them, so a restart
+    /// orphans them; the source can simply be reindexed again.
+    pub async fn recover_orphaned(&self) -> Result<usize, DatabaseError> {
+        let orphaned = self.repo().fail_orphaned().await?;
+        for job in &orphaned {
+            warn!(
+                "Reindex {} of source {} was interrupted after {} of {} documents",
+                job.id, job.source_id, job.reindexed_documents, job.total_documents
+            );
+        }
+        Ok(orphaned.len())
+    }
+
+    async fn run(&self, job: SourceReindexJob) {
+        let repo = self.repo();
+
+        match self.reindex_documents(&job).await {
+            Ok(()) => {
+                if let Err(e) = repo.mark_embedding(&job.id).await {
+                    error!("Failed to update reindex {}: {}", job.id, e);
+                }
+                info!(
+                    "Reindexed the documents of source {} (reindex {}); waiting for embeddings",
+                    job.source_id, job.id
+                );
+            }
+            Err(e) => {
+                error!(
+                    "Reindex {} of source {} failed: {}",
+                    job.id, job.source_id, e
+                );
+                if let Err(e) = repo.fail(&job.id, &e.to_string()).await {
+                    error!("Failed to mark reindex {} failed: {}", job.id, e);
+                }
+            }
+        }
+        search_cache::invalidate_sources(&self.state.redis_client, [job.source_id.as_str()]).await;
+    }
+
+    async fn reindex_documents(&self, job: &SourceReindexJob) -> anyhow::Result<()> {
+        let repo = self.repo();
+        let processor = QueueProcessor::new(self.state.clone());
+
+        let mut after_id = String::new();
+        loop {
+            let documents = repo
+                .documents_after(&job.source_id, &after_id, REINDEX_BATCH_SIZE)
+                .await?;
+            let Some(last) = documents.last() else {
+                break;
+            };
+            after_id = last.id.clone();
+
+            let document_ids: Vec<String> = documents.iter().map(|doc| doc.id.clone()).collect();
+            let cleared = repo.clear_embeddings(&document_ids).await?;
+            let count = documents.len() as i32;
+            processor.reindex_documents(&job.id, documents).await?;
+            repo.record_progress(&job.id, count, cleared as i64).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Complete jobs whose documents have all been embedded again.
+    pub async fn check_embedding(&self) -> Result<(), DatabaseError> {
+        let repo = self.repo();
+        for job in repo.list_embedding().await? {
+            let progress = repo.progress(&job).await?;
+            if progress.pending_documents > 0 {
+                continue;
+            }
+
+            repo.complete(&job.id).await?;
+            info!(
+                "Reindex {} of source {} completed: {} documents embedded, {} quarantined",
+                job.id, job.source_id, progress.embedded_documents, progress.quarantined_documents
+            );
+        }
+        Ok(())
+    }
+
+    pub fn spawn_monitor(&self) {
+        let reindexer = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(MONITOR_INTERVAL);
+            loop {
+                interval.tick().await;
+                if let Err(e) = reindexer.check_embedding().await {
+                    error!("Failed to check source reindex progress: {}", e);
+                }
+            }
+        });
+    }
+}
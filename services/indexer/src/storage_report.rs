@@ -0,0 +1,533 @@
+use crate::document_versions::VersionRetentionConfig;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use shared::db::repositories::{SourceStorageSnapshot, StorageSnapshotRepository};
+use sqlx::PgPool;
+use sqlx::types::time::OffsetDateTime;
+use std::collections::BTreeMap;
+use time::Duration as TimeDuration;
+use tracing::info;
+
+const DEFAULT_GROWTH_WINDOW_DAYS: i64 = 30;
+const DEFAULT_PROJECTION_DAYS: i64 = 90;
+const DEFAULT_SNAPSHOT_RETENTION_DAYS: i64 = 400;
+/// Reclaimable storage below this is not worth recommending an action for.
+const MIN_RECLAIMABLE_BYTES: i64 = 1024 * 1024;
+
+#[derive(Debug, Clone)]
+pub struct StorageReportConfig {
+    /// Total storage the corpus may use. Unset disables budget tracking.
+    pub budget_bytes: Option<i64>,
+    /// Days of snapshots growth is estimated from.
+    pub growth_window_days: i64,
+    /// How far ahead storage is projected.
+    pub projection_days: i64,
+    pub snapshot_retention_days: i64,
+}
+
+impl Default for StorageReportConfig {
+    fn default() -> Self {
+        Self {
+            budget_bytes: None,
+            growth_window_days: DEFAULT_GROWTH_WINDOW_DAYS,
+            projection_days: DEFAULT_PROJECTION_DAYS,
+            snapshot_retention_days: DEFAULT_SNAPSHOT_RETENTION_DAYS,
+        }
+    }
+}
+
+impl StorageReportConfig {
+    pub fn from_env() -> Self {
+        fn env_or<T: std::str::FromStr>(key: &str, default: T) -> T {
+            std::env::var(key)
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default)
+        }
+
+        Self {
+            budget_bytes: Some(env_or("INDEXER_STORAGE_BUDGET_BYTES", 0i64)).filter(|b| *b > 0),
+            growth_window_days: env_or(
+                "INDEXER_STORAGE_GROWTH_WINDOW_DAYS",
+                DEFAULT_GROWTH_WINDOW_DAYS,
+            )
+            .max(1),
+            projection_days: env_or("INDEXER_STORAGE_PROJECTION_DAYS", DEFAULT_PROJECTION_DAYS)
+                .max(1),
+            snapshot_retention_days: env_or(
+                "INDEXER_STORAGE_SNAPSHOT_RETENTION_DAYS",
+                DEFAULT_SNAPSHOT_RETENTION_DAYS,
+            )
+            .max(1),
+        }
+    }
+}
+
+/// Bytes stored, by where they are stored.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct StorageBreakdown {
+    /// Document rows, including their inline content.
+    pub document_bytes: i64,
+    /// Content blobs of current documents.
+    pub content_bytes: i64,
+    pub embedding_bytes: i64,
+    /// Content blobs kept alive by superseded document versions.
+    pub version_bytes: i64,
+    pub total_bytes: i64,
+}
+
+impl StorageBreakdown {
+    fn of(snapshot: &SourceStorageSnapshot) -> Self {
+        Self {
+            document_bytes: snapshot.document_bytes,
+            content_bytes: snapshot.content_bytes,
+            embedding_bytes: snapshot.embedding_bytes,
+            version_bytes: snapshot.version_bytes,
+            total_bytes: snapshot.document_bytes
+                + snapshot.content_bytes
+                + snapshot.embedding_bytes
+                + snapshot.version_bytes,
+        }
+    }
+
+    fn add(&mut self, other: &StorageBreakdown) {
+        self.document_bytes += other.document_bytes;
+        self.content_bytes += other.content_bytes;
+        self.embedding_bytes += other.embedding_bytes;
+        self.version_bytes += other.version_bytes;
+        self.total_bytes += other.total_bytes;
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SourceStorageReport {
+    pub source_id: String,
+    pub source_name: String,
+    pub document_count: i64,
+    pub embedding_count: i64,
+    pub version_count: i64,
+    #[serde(flatten)]
+    pub storage: StorageBreakdown,
+    /// Part of `version_bytes` the version retention policy would prune.
+    pub prunable_version_bytes: i64,
+    /// Estimated from the snapshots in the growth window; negative when the
+    /// source shrinks.
+    pub daily_growth_bytes: i64,
+    pub projected_total_bytes: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetentionAction {
+    /// Run `POST /admin/gc/run`; nothing references the blobs any more.
+    RunContentGc,
+    /// Run `POST /admin/document-versions/prune`.
+    PruneDocumentVersions,
+    /// Version history outweighs the current content even after pruning;
+    /// lower `INDEXER_DOCUMENT_VERSIONS_MAX` or the retention days.
+    TightenVersionRetention,
+    /// The source drives most of the growth that takes the corpus over
+    /// budget; narrow what it syncs or move it to its own budget.
+    ReviewSourceGrowth,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RetentionRecommendation {
+    pub action: RetentionAction,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_id: Option<String>,
+    /// Storage the action would free, when it can be estimated.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reclaimable_bytes: Option<i64>,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StorageBudget {
+    pub budget_bytes: i64,
+    pub used_fraction: f64,
+    pub projected_fraction: f64,
+    /// At the current growth rate; unset when storage is not growing or
+    /// already over budget.
+    pub days_until_exhausted: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StorageReport {
+    pub generated_at: DateTime<Utc>,
+    /// When the snapshots the report is built from were taken.
+    #[serde(with = "time::serde::iso8601::option")]
+    pub snapshot_at: Option<OffsetDateTime>,
+    pub totals: StorageBreakdown,
+    /// Blobs nothing references, which GC has yet to reclaim.
+    pub unreferenced_content_bytes: i64,
+    pub daily_growth_bytes: i64,
+    pub projection_days: i64,
+    pub projected_total_bytes: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub budget: Option<StorageBudget>,
+    /// Largest source first.
+    pub sources: Vec<SourceStorageReport>,
+    /// Largest reclaimable storage first.
+    pub recommendations: Vec<RetentionRecommendation>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StorageAnalysisResult {
+    pub snapshots: u64,
+}
+
+/// Snapshots what each source stores, and reports the breakdown with
+/// projected growth and the retention actions that would free the most.
+pub struct StorageAnalyzer {
+    repo: StorageSnapshotRepository,
+    config: StorageReportConfig,
+    retention: VersionRetentionConfig,
+}
+
+impl StorageAnalyzer {
+    pub fn new(pool: &PgPool, config: StorageReportConfig) -> Self {
+        Self {
+            repo: StorageSnapshotRepository::new(pool),
+            config,
+            retention: VersionRetentionConfig::from_env(),
+        }
+    }
+
+    pub async fn run(&self) -> Result<StorageAnalysisResult> {
+        let snapshots = self
+            .repo
+            .refresh_snapshots(
+                self.retention.max_versions,
+                self.retention.max_age_days(),
+                self.config.snapshot_retention_days,
+            )
+            .await?;
+        info!("Storage analysis: refreshed {} source snapshots", snapshots);
+        Ok(StorageAnalysisResult { snapshots })
+    }
+
+    pub async fn report(&self) -> Result<StorageReport> {
+        let since = OffsetDateTime::now_utc() - TimeDuration::days(self.config.growth_window_days);
+        let snapshots = self.repo.get_snapshots(since).await?;
+        let unreferenced_content_bytes = self.repo.unreferenced_content_bytes().await?;
+        Ok(build_report(
+            &snapshots,
+            unreferenced_content_bytes,
+            &self.config,
+        ))
+    }
+}
+
+/// Assemble the report from snapshots ordered oldest first. Each source is
+/// reported from its newest snapshot.
+pub fn build_report(
+    snapshots: &[SourceStorageSnapshot],
+    unreferenced_content_bytes: i64,
+    config: &StorageReportConfig,
+) -> StorageReport {
+    let mut by_source: BTreeMap<&str, Vec<&SourceStorageSnapshot>> = BTreeMap::new();
+    for snapshot in snapshots {
+        by_source
+            .entry(snapshot.source_id.as_str())
+            .or_default()
+            .push(snapshot);
+    }
+
+    let mut sources: Vec<SourceStorageReport> = by_source
+        .values()
+        .filter_map(|history| {
+            let latest = *history.last()?;
+            let storage = StorageBreakdown::of(latest);
+            let points: Vec<(OffsetDateTime, i64)> = history
+                .iter()
+                .map(|s| (s.day, StorageBreakdown::of(s).total_bytes))
+                .collect();
+            let daily_growth_bytes = daily_growth(&points).round() as i64;
+            Some(SourceStorageReport {
+                source_id: latest.source_id.clone(),
+                source_name: latest.source_name.clone(),
+                document_count: latest.document_count,
+                embedding_count: latest.embedding_count,
+                version_count: latest.version_count,
+                projected_total_bytes: (storage.total_bytes
+                    + daily_growth_bytes * config.projection_days)
+                    .max(0),
+                storage,
+                prunable_version_bytes: latest.prunable_version_bytes,
+                daily_growth_bytes,
+            })
+        })
+        .collect();
+    sources.sort_by(|a, b| {
+        b.storage
+            .total_bytes
+            .cmp(&a.storage.total_bytes)
+            .then_with(|| a.source_id.cmp(&b.source_id))
+    });
+
+    let mut totals = StorageBreakdown::default();
+    for source in &sources {
+        totals.add(&source.storage);
+    }
+    let daily_growth_bytes: i64 = sources.iter().map(|s| s.daily_growth_bytes).sum();
+    let projected_total_bytes =
+        (totals.total_bytes + daily_growth_bytes * config.projection_days).max(0);
+
+    let budget = config.budget_bytes.map(|budget_bytes| StorageBudget {
+        budget_bytes,
+        used_fraction: totals.total_bytes as f64 / budget_bytes as f64,
+        projected_fraction: projected_total_bytes as f64 / budget_bytes as f64,
+        days_until_exhausted: (daily_growth_bytes > 0 && totals.total_bytes < budget_bytes)
+            .then(|| (budget_bytes - totals.total_bytes) / daily_growth_bytes),
+    });
+    let over_budget = config
+        .budget_bytes
+        .is_some_and(|budget_bytes| projected_total_bytes > budget_bytes);
+
+    StorageReport {
+        generated_at: Utc::now(),
+        snapshot_at: snapshots.iter().map(|s| s.refreshed_at).max(),
+        recommendations: recommend(&sources, unreferenced_content_bytes, over_budget),
+        totals,
+        unreferenced_content_bytes,
+        daily_growth_bytes,
+        projection_days: config.projection_days,
+        projected_total_bytes,
+        budget,
+        sources,
+    }
+}
+
+/// Least-squares slope of `(day, bytes)` points, in bytes per day. Zero with
+/// fewer than two distinct days.
+pub fn daily_growth(points: &[(OffsetDateTime, i64)]) -> f64 {
+    let Some(&(first_day, _)) = points.first() else {
+        return 0.0;
+    };
+    let xy: Vec<(f64, f64)> = points
+        .iter()
+        .map(|(day, bytes)| {
+            let days = (*day - first_day).whole_seconds() as f64 / 86_400.0;
+            (days, *bytes as f64)
+        })
+        .collect();
+    let n = xy.len() as f64;
+    let mean_x = xy.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = xy.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let variance: f64 = xy.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+    if variance == 0.0 {
+        return 0.0;
+    }
+    let covariance: f64 = xy.iter().map(|(x, y)| (x - mean_x) * (y - mean_y)).sum();
+    covariance / variance
+}
+
+/// Retention actions worth taking, largest reclaimable storage first. When the
+/// corpus is projected over budget, the fastest-growing sources that together
+/// account for at least half the growth are flagged for review.
+pub fn recommend(
+    sources: &[SourceStorageReport],
+    unreferenced_content_bytes: i64,
+    over_budget: bool,
+) -> Vec<RetentionRecommendation> {
+    let mut recommendations = Vec::new();
+
+    if unreferenced_content_bytes >= MIN_RECLAIMABLE_BYTES {
+        recommendations.push(RetentionRecommendation {
+            action: RetentionAction::RunContentGc,
+            source_id: None,
+            reclaimable_bytes: Some(unreferenced_content_bytes),
+            reason:
+                "Content blobs are no longer referenced by any document, version, upload or export"
+                    .to_string(),
+        });
+    }
+
+    for source in sources {
+        if source.prunable_version_bytes >= MIN_RECLAIMABLE_BYTES {
+            recommendations.push(RetentionRecommendation {
+                action: RetentionAction::PruneDocumentVersions,
+                source_id: Some(source.source_id.clone()),
+                reclaimable_bytes: Some(source.prunable_version_bytes),
+                reason: format!(
+                    "{} has versions past the retention policy",
+                    source.source_name
+                ),
+            });
+        }
+        let retained_version_bytes = source.storage.version_bytes - source.prunable_version_bytes;
+        if retained_version_bytes >= MIN_RECLAIMABLE_BYTES
+            && retained_version_bytes > source.storage.content_bytes
+        {
+            recommendations.push(RetentionRecommendation {
+                action: RetentionAction::TightenVersionRetention,
+                source_id: Some(source.source_id.clone()),
+                reclaimable_bytes: None,
+                reason: format!(
+                    "{} keeps more bytes of version history than of current content",
+                    source.source_name
+                ),
+            });
+        }
+    }
+    recommendations.sort_by(|a, b| {
+        b.reclaimable_bytes
+            .unwrap_or(0)
+            .cmp(&a.reclaimable_bytes.unwrap_or(0))
+    });
+
+    if over_budget {
+        let mut growing: Vec<&SourceStorageReport> = sources
+            .iter()
+            .filter(|s| s.daily_growth_bytes > 0)
+            .collect();
+        growing.sort_by_key(|s| std::cmp::Reverse(s.daily_growth_bytes));
+        let total_growth: i64 = growing.iter().map(|s| s.daily_growth_bytes).sum();
+        let mut covered = 0;
+        for source in growing {
+            if covered * 2 >= total_growth {
+                break;
+            }
+            covered += source.daily_growth_bytes;
+            recommendations.push(RetentionRecommendation {
+                action: RetentionAction::ReviewSourceGrowth,
+                source_id: Some(source.source_id.clone()),
+                reclaimable_bytes: None,
+                reason: format!(
+                    "{} grows by {} bytes a day and takes the corpus over budget",
+                    source.source_name, source.daily_growth_bytes
+                ),
+            });
+        }
+    }
+
+    recommendations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MIB: i64 = 1024 * 1024;
+
+    fn day(n: i64) -> OffsetDateTime {
+        OffsetDateTime::UNIX_EPOCH + TimeDuration::days(n)
+    }
+
+    fn snapshot(source_id: &str, n: i64, content_bytes: i64) -> SourceStorageSnapshot {
+        SourceStorageSnapshot {
+            source_id: source_id.to_string(),
+            source_name: format!("Source {}", source_id),
+            day: day(n),
+            document_count: 10,
+            document_bytes: MIB,
+            content_bytes,
+            embedding_count: 40,
+            embedding_bytes: 2 * MIB,
+            version_count: 5,
+            version_bytes: 3 * MIB,
+            prunable_version_bytes: 2 * MIB,
+            refreshed_at: day(n),
+        }
+    }
+
+    #[test]
+    fn test_daily_growth_is_least_squares_slope() {
+        assert_eq!(daily_growth(&[]), 0.0);
+        assert_eq!(daily_growth(&[(day(0), 100)]), 0.0);
+        assert_eq!(daily_growth(&[(day(0), 100), (day(2), 300)]), 100.0);
+        assert_eq!(
+            daily_growth(&[(day(0), 100), (day(1), 250), (day(2), 250)]),
+            75.0
+        );
+    }
+
+    #[test]
+    fn test_report_projects_growth_per_source_and_against_budget() {
+        let snapshots = vec![
+            snapshot("a", 0, 10 * MIB),
+            snapshot("b", 0, 20 * MIB),
+            snapshot("a", 10, 20 * MIB),
+            snapshot("b", 10, 20 * MIB),
+        ];
+        let config = StorageReportConfig {
+            budget_bytes: Some(100 * MIB),
+            projection_days: 30,
+            ..Default::default()
+        };
+
+        let report = build_report(&snapshots, 0, &config);
+
+        // Both sources store 26 MiB; ties are ordered by id.
+        assert_eq!(report.sources[0].source_id, "a");
+        assert_eq!(report.sources[0].storage.total_bytes, 26 * MIB);
+        assert_eq!(report.sources[0].daily_growth_bytes, MIB);
+        assert_eq!(report.sources[0].projected_total_bytes, 56 * MIB);
+        assert_eq!(report.sources[1].daily_growth_bytes, 0);
+        assert_eq!(report.totals.content_bytes, 40 * MIB);
+        assert_eq!(report.totals.total_bytes, 52 * MIB);
+        assert_eq!(report.projected_total_bytes, 82 * MIB);
+        assert_eq!(report.snapshot_at, Some(day(10)));
+
+        let budget = report.budget.unwrap();
+        assert_eq!(budget.used_fraction, 0.52);
+        assert_eq!(budget.days_until_exhausted, Some(48));
+        assert!(
+            !report
+                .recommendations
+                .iter()
+                .any(|r| r.action == RetentionAction::ReviewSourceGrowth)
+        );
+    }
+
+    #[test]
+    fn test_recommends_gc_and_pruning_largest_first_and_growth_review_over_budget() {
+        let snapshots = vec![
+            snapshot("a", 0, 10 * MIB),
+            snapshot("b", 0, 20 * MIB),
+            snapshot("a", 10, 110 * MIB),
+            snapshot("b", 10, 20 * MIB),
+        ];
+        let config = StorageReportConfig {
+            budget_bytes: Some(200 * MIB),
+            projection_days: 30,
+            ..Default::default()
+        };
+
+        let report = build_report(&snapshots, 5 * MIB, &config);
+        let actions: Vec<(RetentionAction, Option<&str>)> = report
+            .recommendations
+            .iter()
+            .map(|r| (r.action, r.source_id.as_deref()))
+            .collect();
+
+        assert_eq!(
+            actions,
+            vec![
+                (RetentionAction::RunContentGc, None),
+                (RetentionAction::PruneDocumentVersions, Some("a")),
+                (RetentionAction::PruneDocumentVersions, Some("b")),
+                (RetentionAction::ReviewSourceGrowth, Some("a")),
+            ]
+        );
+        assert_eq!(report.recommendations[0].reclaimable_bytes, Some(5 * MIB));
+    }
+
+    #[test]
+    fn test_recommends_tighter_retention_when_history_outweighs_content() {
+        let mut history_heavy = snapshot("a", 0, MIB);
+        history_heavy.version_bytes = 8 * MIB;
+        history_heavy.prunable_version_bytes = 0;
+
+        let report = build_report(&[history_heavy], 0, &StorageReportConfig::default());
+
+        assert_eq!(report.recommendations.len(), 1);
+        assert_eq!(
+            report.recommendations[0].action,
+            RetentionAction::TightenVersionRetention
+        );
+        assert!(report.budget.is_none());
+    }
+}
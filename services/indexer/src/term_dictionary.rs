@@ -0,0 +1,210 @@
+//! Refresh of the term dictionary the searcher uses for spell correction.
+//!
+//! Each document's terms are kept alongside corpus-wide document counts per
+//! term. An incremental refresh re-tokenizes only the documents indexed since
+//! the last completed refresh started, adjusts the counts by the difference
+//! from their previous terms, subtracts the terms of deleted documents, and
+//! rebuilds the dictionary from the counts. A full refresh re-tokenizes every
+//! document, e.g. after changing how much content is scanned.
+
+use futures::future::try_join_all;
+use serde::{Deserialize, Serialize};
+use shared::db::error::DatabaseError;
+use shared::db::repositories::{CorpusStatsRepository, TermDictionaryRun, TermDictionaryUpdate};
+use sqlx::PgPool;
+use sqlx::types::time::OffsetDateTime;
+use tracing::{error, info};
+
+// Terms past the first few thousand words of a document rarely add new
+// vocabulary, but make the rebuild scan much more text.
+const DEFAULT_CONTENT_CHARS: i32 = 10_000;
+// Terms seen in a single document are often typos themselves.
+const DEFAULT_MIN_DOCUMENT_COUNT: i64 = 2;
+const DEFAULT_MAX_TERMS: i64 = 200_000;
+const DEFAULT_INTERVAL_SECS: u64 = 3600 * 6;
+const DEFAULT_BATCH_SIZE: i64 = 1000;
+const DEFAULT_CONCURRENCY: usize = 2;
+const STATUS_RECENT_RUNS: i64 = 10;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TermDictionaryMode {
+    /// Only documents indexed since the last completed refresh started.
+    #[default]
+    Incremental,
+    Full,
+}
+
+impl TermDictionaryMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TermDictionaryMode::Incremental => "incremental",
+            TermDictionaryMode::Full => "full",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TermDictionaryConfig {
+    /// Characters of each document's content scanned for terms.
+    pub content_chars: i32,
+    pub min_document_count: i64,
+    pub max_terms: i64,
+    /// Seconds between scheduled incremental refreshes.
+    pub interval_secs: u64,
+    /// Documents tokenized per query.
+    pub batch_size: i64,
+    /// Batches tokenized at once.
+    pub concurrency: usize,
+}
+
+impl Default for TermDictionaryConfig {
+    fn default() -> Self {
+        Self {
+            content_chars: DEFAULT_CONTENT_CHARS,
+            min_document_count: DEFAULT_MIN_DOCUMENT_COUNT,
+            max_terms: DEFAULT_MAX_TERMS,
+            interval_secs: DEFAULT_INTERVAL_SECS,
+            batch_size: DEFAULT_BATCH_SIZE,
+            concurrency: DEFAULT_CONCURRENCY,
+        }
+    }
+}
+
+impl TermDictionaryConfig {
+    pub fn from_env() -> Self {
+        fn env_or<T: std::str::FromStr>(key: &str, default: T) -> T {
+            std::env::var(key)
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default)
+        }
+
+        Self {
+            content_chars: env_or(
+                "INDEXER_TERM_DICTIONARY_CONTENT_CHARS",
+                DEFAULT_CONTENT_CHARS,
+            ),
+            min_document_count: env_or(
+                "INDEXER_TERM_DICTIONARY_MIN_DOCUMENT_COUNT",
+                DEFAULT_MIN_DOCUMENT_COUNT,
+            ),
+            max_terms: env_or("INDEXER_TERM_DICTIONARY_MAX_TERMS", DEFAULT_MAX_TERMS),
+            interval_secs: env_or(
+                "INDEXER_TERM_DICTIONARY_INTERVAL_SECS",
+                DEFAULT_INTERVAL_SECS,
+            )
+            .max(60),
+            batch_size: env_or("INDEXER_TERM_DICTIONARY_BATCH_SIZE", DEFAULT_BATCH_SIZE).max(1),
+            concurrency: env_or("INDEXER_TERM_DICTIONARY_CONCURRENCY", DEFAULT_CONCURRENCY).max(1),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TermDictionaryRefreshQuery {
+    #[serde(default)]
+    pub mode: TermDictionaryMode,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TermDictionaryStatus {
+    pub last_run: Option<TermDictionaryRun>,
+    pub last_completed_run: Option<TermDictionaryRun>,
+    pub recent_runs: Vec<TermDictionaryRun>,
+}
+
+/// Refresh the term dictionary, recording the run.
+pub async fn refresh(
+    pool: &PgPool,
+    config: &TermDictionaryConfig,
+    mode: TermDictionaryMode,
+) -> Result<TermDictionaryRun, DatabaseError> {
+    let repo = CorpusStatsRepository::new(pool);
+    let (run_id, changed_since) = repo
+        .start_term_dictionary_run(mode.as_str(), mode == TermDictionaryMode::Incremental)
+        .await?;
+
+    match apply(&repo, config, changed_since).await {
+        Ok(update) => {
+            let run = repo.complete_term_dictionary_run(run_id, &update).await?;
+            info!(
+                "Refreshed spell-correction term dictionary ({} mode): {} documents refreshed, {} removed, {} terms",
+                run.mode, run.documents_refreshed, run.documents_removed, run.terms
+            );
+            Ok(run)
+        }
+        Err(e) => {
+            if let Err(fail_err) = repo.fail_term_dictionary_run(run_id, &e.to_string()).await {
+                error!(
+                    "Failed to record term dictionary run {} as failed: {}",
+                    run_id, fail_err
+                );
+            }
+            Err(e)
+        }
+    }
+}
+
+async fn apply(
+    repo: &CorpusStatsRepository,
+    config: &TermDictionaryConfig,
+    changed_since: Option<OffsetDateTime>,
+) -> Result<TermDictionaryUpdate, DatabaseError> {
+    let mut after_id = String::new();
+    let mut exhausted = false;
+    while !exhausted {
+        // Page through the changed documents one wave of batches at a time,
+        // tokenizing each wave's batches concurrently
+        let mut wave = Vec::with_capacity(config.concurrency);
+        while wave.len() < config.concurrency {
+            let ids = repo
+                .documents_indexed_since(changed_since, &after_id, config.batch_size)
+                .await?;
+            exhausted = (ids.len() as i64) < config.batch_size;
+            if let Some(last) = ids.last() {
+                after_id = last.clone();
+                wave.push(ids);
+            }
+            if exhausted {
+                break;
+            }
+        }
+        try_join_all(
+            wave.iter()
+                .map(|ids| repo.stage_document_terms(ids, config.content_chars)),
+        )
+        .await?;
+    }
+
+    repo.apply_pending_terms(config.min_document_count, config.max_terms)
+        .await
+}
+
+pub async fn status(pool: &PgPool) -> Result<TermDictionaryStatus, DatabaseError> {
+    let repo = CorpusStatsRepository::new(pool);
+    let recent_runs = repo.recent_term_dictionary_runs(STATUS_RECENT_RUNS).await?;
+    let last_completed_run = repo.last_completed_term_dictionary_run().await?;
+
+    Ok(TermDictionaryStatus {
+        last_run: recent_runs.first().cloned(),
+        last_completed_run,
+        recent_runs,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_refresh_mode_defaults_to_incremental() {
+        let query: TermDictionaryRefreshQuery = serde_json::from_str("{}").unwrap();
+        assert_eq!(query.mode, TermDictionaryMode::Incremental);
+
+        let query: TermDictionaryRefreshQuery =
+            serde_json::from_str(r#"{"mode": "full"}"#).unwrap();
+        assert_eq!(query.mode, TermDictionaryMode::Full);
+        assert_eq!(query.mode.as_str(), "full");
+    }
+}
@@ -0,0 +1,401 @@
+//! Managed rebuilds of the per-dimension embedding vector indexes.
+//!
+//! Searches use the partial index `idx_embeddings_vector_<dimensions>`, so
+//! rebuilding it in place would either lock the embeddings table or leave
+//! searches without an index. Instead the replacement is built CONCURRENTLY
+//! under a temporary name while the old index keeps serving, then swapped in
+//! by renaming both in one short transaction, and the old index is dropped
+//! CONCURRENTLY. If anything fails before the swap commits, the partial
+//! replacement is dropped and the old index is left untouched.
+
+use serde::{Deserialize, Serialize};
+use shared::DatabaseError;
+use shared::db::repositories::{
+    VectorIndexBuild, VectorIndexBuildProgress, VectorIndexBuildRepository, VectorIndexMethod,
+};
+use sqlx::PgPool;
+use tracing::{error, info, warn};
+
+/// pgvector cannot index `vector` columns with more dimensions than this.
+const MAX_INDEXED_DIMENSIONS: i32 = 2000;
+const DEFAULT_HNSW_M: i32 = 32;
+const DEFAULT_HNSW_EF_CONSTRUCTION: i32 = 200;
+const DEFAULT_IVFFLAT_LISTS: i32 = 100;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct VectorIndexBuildRequest {
+    pub dimensions: i32,
+    #[serde(default = "default_method")]
+    pub method: VectorIndexMethod,
+    /// hnsw only.
+    pub m: Option<i32>,
+    /// hnsw only.
+    pub ef_construction: Option<i32>,
+    /// ivfflat only.
+    pub lists: Option<i32>,
+}
+
+fn default_method() -> VectorIndexMethod {
+    VectorIndexMethod::Hnsw
+}
+
+/// Index parameters after defaults are applied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VectorIndexParams {
+    pub dimensions: i32,
+    pub method: VectorIndexMethod,
+    pub m: Option<i32>,
+    pub ef_construction: Option<i32>,
+    pub lists: Option<i32>,
+}
+
+impl VectorIndexBuildRequest {
+    pub fn validate(&self) -> Result<VectorIndexParams, String> {
+        if !(1..=MAX_INDEXED_DIMENSIONS).contains(&self.dimensions) {
+            return Err(format!(
+                "dimensions must be between 1 and {}",
+                MAX_INDEXED_DIMENSIONS
+            ));
+        }
+
+        match self.method {
+            VectorIndexMethod::Hnsw => {
+                if self.lists.is_some() {
+                    return Err("lists only applies to ivfflat indexes".to_string());
+                }
+                let m = self.m.unwrap_or(DEFAULT_HNSW_M);
+                let ef_construction = self.ef_construction.unwrap_or(DEFAULT_HNSW_EF_CONSTRUCTION);
+                if !(2..=100).contains(&m) {
+                    return Err("m must be between 2 and 100".to_string());
+                }
+                if !(4..=1000).contains(&ef_construction) || ef_construction < 2 * m {
+                    return Err(
+                        "ef_construction must be between 4 and 1000 and at least twice m"
+                            .to_string(),
+                    );
+                }
+                Ok(VectorIndexParams {
+                    dimensions: self.dimensions,
+                    method: self.method,
+                    m: Some(m),
+                    ef_construction: Some(ef_construction),
+                    lists: None,
+                })
+            }
+            VectorIndexMethod::Ivfflat => {
+                if self.m.is_some() || self.ef_construction.is_some() {
+                    return Err("m and ef_construction only apply to hnsw indexes".to_string());
+                }
+                let lists = self.lists.unwrap_or(DEFAULT_IVFFLAT_LISTS);
+                if !(1..=32768).contains(&lists) {
+                    return Err("lists must be between 1 and 32768".to_string());
+                }
+                Ok(VectorIndexParams {
+                    dimensions: self.dimensions,
+                    method: self.method,
+                    m: None,
+                    ef_construction: None,
+                    lists: Some(lists),
+                })
+            }
+        }
+    }
+}
+
+impl VectorIndexParams {
+    fn from_build(build: &VectorIndexBuild) -> Self {
+        Self {
+            dimensions: build.dimensions,
+            method: build.method,
+            m: build.m,
+            ef_construction: build.ef_construction,
+            lists: build.lists,
+        }
+    }
+
+    /// `CREATE INDEX CONCURRENTLY` for this index under `name`. Every value
+    /// interpolated here is a validated integer.
+    fn create_statement(&self, name: &str) -> String {
+        let with = match self.method {
+            VectorIndexMethod::Hnsw => format!(
+                "m = {}, ef_construction = {}",
+                self.m.unwrap_or(DEFAULT_HNSW_M),
+                self.ef_construction.unwrap_or(DEFAULT_HNSW_EF_CONSTRUCTION)
+            ),
+            VectorIndexMethod::Ivfflat => {
+                format!("lists = {}", self.lists.unwrap_or(DEFAULT_IVFFLAT_LISTS))
+            }
+        };
+        format!(
+            "CREATE INDEX CONCURRENTLY {name} ON embeddings \
+             USING {method} ((embedding::vector({dims})) vector_cosine_ops) \
+             WITH ({with}) WHERE dimensions = {dims}",
+            method = self.method.as_str(),
+            dims = self.dimensions,
+        )
+    }
+}
+
+/// Name searches rely on.
+pub fn live_index_name(dimensions: i32) -> String {
+    format!("idx_embeddings_vector_{}", dimensions)
+}
+
+fn building_index_name(dimensions: i32) -> String {
+    format!("idx_embeddings_vector_{}_build", dimensions)
+}
+
+fn retired_index_name(dimensions: i32) -> String {
+    format!("idx_embeddings_vector_{}_old", dimensions)
+}
+
+#[derive(Debug, Serialize)]
+pub struct VectorIndexBuildStatusResponse {
+    #[serde(flatten)]
+    pub build: VectorIndexBuild,
+    /// Present while CREATE INDEX is running.
+    pub progress: Option<VectorIndexBuildProgressResponse>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VectorIndexBuildProgressResponse {
+    #[serde(flatten)]
+    pub progress: VectorIndexBuildProgress,
+    pub phase_percent: Option<f64>,
+}
+
+#[derive(Clone)]
+pub struct VectorIndexBuilder {
+    pool: PgPool,
+}
+
+impl VectorIndexBuilder {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    fn repo(&self) -> VectorIndexBuildRepository {
+        VectorIndexBuildRepository::new(&self.pool)
+    }
+
+    /// Record a build and run it in the background.
+    pub async fn start(
+        &self,
+        params: VectorIndexParams,
+    ) -> Result<VectorIndexBuild, DatabaseError> {
+        let build = self
+            .repo()
+            .create(
+                params.dimensions,
+                params.method,
+                params.m,
+                params.ef_construction,
+                params.lists,
+            )
+            .await?;
+
+        info!(
+            "Starting {} rebuild of {} (build {})",
+            params.method.as_str(),
+            live_index_name(params.dimensions),
+            build.id
+        );
+
+        let builder = self.clone();
+        let queued = build.clone();
+        tokio::spawn(async move {
+            builder.run(queued).await;
+        });
+
+        Ok(build)
+    }
+
+    pub async fn status(
+        &self,
+        id: &str,
+    ) -> Result<Option<VectorIndexBuildStatusResponse>, DatabaseError> {
+        let repo = self.repo();
+        let Some(build) = repo.get(id).await? else {
+            return Ok(None);
+        };
+
+        let progress = match build.backend_pid {
+            Some(pid) if build.status.is_active() => repo.progress(pid).await?,
+            _ => None,
+        };
+
+        Ok(Some(VectorIndexBuildStatusResponse {
+            build,
+            progress: progress.map(|progress| VectorIndexBuildProgressResponse {
+                phase_percent: progress.phase_percent(),
+                progress,
+            }),
+        }))
+    }
+
+    pub async fn list(&self, limit: i64) -> Result<Vec<VectorIndexBuild>, DatabaseError> {
+        self.repo().list_recent(limit).await
+    }
+
+    /// Fail builds left in flight by a previous process and drop their
+    /// partial indexes. Builds are driven by the indexer that started them,
+    /// so any still marked in flight at startup were orphaned by a restart.
+    pub async fn recover_orphaned(&self) -> Result<usize, DatabaseError> {
+        let orphaned = self.repo().fail_orphaned().await?;
+        for build in &orphaned {
+            warn!(
+                "Vector index build {} was interrupted; rolling back",
+                build.id
+            );
+            self.drop_index(&building_index_name(build.dimensions))
+                .await;
+        }
+        Ok(orphaned.len())
+    }
+
+    async fn run(&self, build: VectorIndexBuild) {
+        let repo = self.repo();
+        let live = live_index_name(build.dimensions);
+
+        match self.build_and_swap(&build).await {
+            Ok(()) => {
+                if let Err(e) = repo.complete(&build.id).await {
+                    error!(
+                        "Failed to mark vector index build {} completed: {}",
+                        build.id, e
+                    );
+                }
+                info!("Rebuilt {} (build {})", live, build.id);
+            }
+            Err(e) => {
+                error!("Vector index build {} failed: {}", build.id, e);
+                // The swap is transactional, so on any failure the live index
+                // is still the old one and only the replacement needs dropping
+                self.drop_index(&building_index_name(build.dimensions))
+                    .await;
+                if let Err(e) = repo.fail(&build.id, &e.to_string()).await {
+                    error!(
+                        "Failed to mark vector index build {} failed: {}",
+                        build.id, e
+                    );
+                }
+            }
+        }
+    }
+
+    async fn build_and_swap(&self, build: &VectorIndexBuild) -> Result<(), DatabaseError> {
+        let repo = self.repo();
+        let params = VectorIndexParams::from_build(build);
+        let building = building_index_name(build.dimensions);
+        let live = live_index_name(build.dimensions);
+        let retired = retired_index_name(build.dimensions);
+
+        // Leftovers of an earlier interrupted build would make CREATE INDEX fail
+        self.drop_index_checked(&building).await?;
+        self.drop_index_checked(&retired).await?;
+
+        // CREATE INDEX CONCURRENTLY cannot run inside a transaction, and its
+        // progress is reported per backend, so run it on a dedicated connection
+        let mut conn = self.pool.acquire().await?;
+        let pid: i32 = sqlx::query_scalar("SELECT pg_backend_pid()")
+            .fetch_one(&mut *conn)
+            .await?;
+        repo.mark_building(&build.id, pid).await?;
+
+        let create = params.create_statement(&building);
+        sqlx::query(&create).execute(&mut *conn).await?;
+        drop(conn);
+
+        repo.mark_swapping(&build.id).await?;
+
+        // Renaming only takes a brief lock, so searches switch over atomically
+        let mut tx = self.pool.begin().await?;
+        let retire = format!("ALTER INDEX IF EXISTS {live} RENAME TO {retired}");
+        let promote = format!("ALTER INDEX {building} RENAME TO {live}");
+        sqlx::query(&retire).execute(&mut *tx).await?;
+        sqlx::query(&promote).execute(&mut *tx).await?;
+        tx.commit().await?;
+
+        // The new index is live; a leftover old index only costs disk space
+        // and is dropped by the next build for these dimensions
+        if let Err(e) = self.drop_index_checked(&retired).await {
+            warn!("Failed to drop retired vector index {}: {}", retired, e);
+        }
+
+        Ok(())
+    }
+
+    async fn drop_index_checked(&self, name: &str) -> Result<(), DatabaseError> {
+        let statement = format!("DROP INDEX CONCURRENTLY IF EXISTS {}", name);
+        sqlx::query(&statement).execute(&self.pool).await?;
+        Ok(())
+    }
+
+    async fn drop_index(&self, name: &str) {
+        if let Err(e) = self.drop_index_checked(name).await {
+            error!("Failed to drop vector index {}: {}", name, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(method: VectorIndexMethod) -> VectorIndexBuildRequest {
+        VectorIndexBuildRequest {
+            dimensions: 1024,
+            method,
+            m: None,
+            ef_construction: None,
+            lists: None,
+        }
+    }
+
+    #[test]
+    fn test_validate_applies_method_defaults() {
+        let hnsw = request(VectorIndexMethod::Hnsw).validate().unwrap();
+        assert_eq!(hnsw.m, Some(DEFAULT_HNSW_M));
+        assert_eq!(hnsw.ef_construction, Some(DEFAULT_HNSW_EF_CONSTRUCTION));
+        assert_eq!(hnsw.lists, None);
+
+        let ivfflat = request(VectorIndexMethod::Ivfflat).validate().unwrap();
+        assert_eq!(ivfflat.lists, Some(DEFAULT_IVFFLAT_LISTS));
+        assert_eq!(ivfflat.m, None);
+    }
+
+    #[test]
+    fn test_validate_rejects_out_of_range_and_mismatched_params() {
+        let mut req = request(VectorIndexMethod::Hnsw);
+        req.dimensions = 4096;
+        assert!(req.validate().is_err());
+
+        let mut req = request(VectorIndexMethod::Hnsw);
+        req.lists = Some(10);
+        assert!(req.validate().is_err());
+
+        let mut req = request(VectorIndexMethod::Hnsw);
+        req.m = Some(64);
+        req.ef_construction = Some(100);
+        assert!(req.validate().is_err());
+
+        let mut req = request(VectorIndexMethod::Ivfflat);
+        req.m = Some(16);
+        assert!(req.validate().is_err());
+
+        let mut req = request(VectorIndexMethod::Ivfflat);
+        req.lists = Some(0);
+        assert!(req.validate().is_err());
+    }
+
+    #[test]
+    fn test_create_statement_matches_live_index_definition() {
+        let params = request(VectorIndexMethod::Hnsw).validate().unwrap();
+        assert_eq!(
+            params.create_statement("idx_embeddings_vector_1024_build"),
+            "CREATE INDEX CONCURRENTLY idx_embeddings_vector_1024_build ON embeddings \
+             USING hnsw ((embedding::vector(1024)) vector_cosine_ops) \
+             WITH (m = 32, ef_construction = 200) WHERE dimensions = 1024"
+        );
+    }
+}
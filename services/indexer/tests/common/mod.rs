@@ -54,6 +54,7 @@ pub async fn setup_test_fixture() -> Result<TestFixture> {
         ai_client,
         embedding_queue,
         content_storage,
+        row_level_security: true,
     };
 
     let app = create_app(app_state.clone());
@@ -2,15 +2,25 @@ mod common;
 
 use axum::http::StatusCode;
 use axum_test::TestServer;
+use axum_test::multipart::{MultipartForm, Part};
 use common::TEST_SOURCE_ID;
 use common::fixtures::{create_document_request, update_document_request};
+use omni_indexer::extraction::{ContentExtractor, ExtractionConfig, ExtractionMethod, OcrBackend};
+use omni_indexer::language;
+use omni_indexer::source_reindex::SourceReindexer;
 use omni_indexer::{BulkDocumentOperation, BulkDocumentRequest, QueueProcessor};
 use serde_json::{Value, json};
-use shared::db::repositories::{DocumentRepository, GroupRepository, PersonRepository};
-use shared::models::{ConnectorEvent, Document, DocumentMetadata, DocumentPermissions};
+use shared::db::repositories::{
+    DocumentRepository, EphemeralDocumentRepository, GroupRepository, PersonRepository,
+    SourceRetentionRepository, WorkspaceRepository, document,
+};
+use shared::models::{
+    ConnectorEvent, DEFAULT_WORKSPACE_ID, Document, DocumentMetadata, DocumentPermissions,
+};
 use shared::queue::EventQueue;
 use sqlx::types::time::OffsetDateTime;
 use std::collections::HashMap;
+use std::sync::Arc;
 use tokio::time::Duration;
 
 #[tokio::test]
@@ -1102,6 +1112,74 @@ async fn test_api_document_operations() {
     assert_eq!(get_deleted.status_code(), StatusCode::NOT_FOUND);
 }
 
+#[tokio::test]
+async fn test_api_document_upsert_with_precondition() {
+    let fixture = common::setup_test_fixture().await.unwrap();
+    let server = TestServer::new(fixture.app().clone()).unwrap();
+
+    let mut request = serde_json::to_value(create_document_request()).unwrap();
+
+    // A precondition on a document that does not exist yet fails
+    request["expected_updated_at"] = json!("2024-01-01T00:00:00Z");
+    let response = server.put("/documents").json(&request).await;
+    assert_eq!(response.status_code(), StatusCode::PRECONDITION_FAILED);
+
+    // First upsert creates
+    request
+        .as_object_mut()
+        .unwrap()
+        .remove("expected_updated_at");
+    let response = server.put("/documents").json(&request).await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+    let created: Document = response.json();
+
+    // Repeating it updates the same document instead of failing
+    request["title"] = json!("Upserted Title");
+    let response = server.put("/documents").json(&request).await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let updated: Document = response.json();
+    assert_eq!(updated.id, created.id);
+    assert_eq!(updated.title, "Upserted Title");
+    assert!(updated.updated_at > created.updated_at);
+
+    // A stale precondition is rejected and leaves the document untouched
+    request["title"] = json!("Stale Write");
+    request["expected_updated_at"] = json!(
+        created
+            .updated_at
+            .format(&time::format_description::well_known::Iso8601::DEFAULT)
+            .unwrap()
+    );
+    let response = server.put("/documents").json(&request).await;
+    assert_eq!(response.status_code(), StatusCode::PRECONDITION_FAILED);
+
+    // A current precondition goes through
+    request["title"] = json!("Fresh Write");
+    request["expected_updated_at"] = json!(
+        updated
+            .updated_at
+            .format(&time::format_description::well_known::Iso8601::DEFAULT)
+            .unwrap()
+    );
+    let response = server.put("/documents").json(&request).await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+
+    let repo = DocumentRepository::new(fixture.state.db_pool.pool());
+    let stored = repo
+        .find_by_external_id(&created.source_id, &created.external_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored.id, created.id);
+    assert_eq!(stored.title, "Fresh Write");
+
+    let count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM documents")
+        .fetch_one(fixture.state.db_pool.pool())
+        .await
+        .unwrap();
+    assert_eq!(count.0, 1);
+}
+
 #[tokio::test]
 async fn test_people_extraction_from_events() {
     let fixture = common::setup_test_fixture().await.unwrap();
@@ -1279,6 +1357,105 @@ async fn test_people_extraction_from_events() {
     processor_handle.abort();
 }
 
+#[tokio::test]
+async fn test_people_are_linked_to_their_source_workspace() {
+    let fixture = common::setup_test_fixture().await.unwrap();
+    let pool = fixture.state.db_pool.pool();
+    let event_queue = EventQueue::new(pool.clone());
+    let repo = DocumentRepository::new(pool);
+    let person_repo = PersonRepository::new(pool);
+
+    let workspace = WorkspaceRepository::new(pool)
+        .create("Research", "research")
+        .await
+        .unwrap();
+    sqlx::query("UPDATE sources SET workspace_id = $1 WHERE id = $2")
+        .bind(&workspace.id)
+        .bind(TEST_SOURCE_ID)
+        .execute(pool)
+        .await
+        .unwrap();
+
+    let processor =
+        QueueProcessor::new(fixture.state.clone()).with_poll_interval(Duration::from_millis(200));
+    let processor_handle = tokio::spawn(async move {
+        let _ = processor.start().await;
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let content_id = fixture
+        .state
+        .content_storage
+        .store_content("Workspace people test".as_bytes(), None)
+        .await
+        .unwrap();
+    let create_event = ConnectorEvent::DocumentCreated {
+        sync_run_id: "sync_people".to_string(),
+        source_id: TEST_SOURCE_ID.to_string(),
+        document_id: "people_workspace_doc".to_string(),
+        content_id,
+        metadata: DocumentMetadata {
+            title: Some("Research Notes".to_string()),
+            author: Some("erin@example.com".to_string()),
+            ..Default::default()
+        },
+        permissions: DocumentPermissions {
+            public: false,
+            users: vec!["erin@example.com".to_string()],
+            groups: vec![],
+        },
+        attributes: None,
+    };
+    event_queue
+        .enqueue(TEST_SOURCE_ID, &create_event)
+        .await
+        .unwrap();
+
+    common::wait_for_document_exists(
+        &repo,
+        TEST_SOURCE_ID,
+        "people_workspace_doc",
+        Duration::from_secs(5),
+    )
+    .await
+    .expect("Document should be created");
+    tokio::time::sleep(Duration::from_millis(500)).await;
+
+    let erin = person_repo
+        .fetch_person_by_email("erin@example.com")
+        .await
+        .unwrap()
+        .expect("erin@example.com should be in people table");
+    let linked: Vec<String> =
+        sqlx::query_scalar("SELECT workspace_id FROM person_workspaces WHERE person_id = $1")
+            .bind(&erin.id)
+            .fetch_all(pool)
+            .await
+            .unwrap();
+    assert_eq!(
+        linked,
+        vec![workspace.id.clone()],
+        "erin should only be listed in the workspace of the source"
+    );
+
+    // People search only finds erin from inside that workspace
+    let found = person_repo
+        .search_people("erin", &workspace.id, 10)
+        .await
+        .unwrap();
+    assert_eq!(found.len(), 1);
+    let found = person_repo
+        .search_people("erin", DEFAULT_WORKSPACE_ID, 10)
+        .await
+        .unwrap();
+    assert!(
+        found.is_empty(),
+        "erin should not leak into other workspaces"
+    );
+
+    processor_handle.abort();
+}
+
 #[tokio::test]
 async fn test_group_membership_sync_event() {
     let fixture = common::setup_test_fixture().await.unwrap();
@@ -1338,3 +1515,1471 @@ async fn test_group_membership_sync_event() {
 
     processor_handle.abort();
 }
+
+#[tokio::test]
+async fn test_nested_group_membership_is_expanded() {
+    let fixture = common::setup_test_fixture().await.unwrap();
+    let pool = fixture.state.db_pool.pool();
+    let group_repo = GroupRepository::new(pool);
+
+    // alice -> backend -> engineering -> everyone, with a cycle back to backend
+    for (group_email, members) in [
+        (
+            "backend@test.com",
+            vec!["alice@test.com", "everyone@test.com"],
+        ),
+        ("engineering@test.com", vec!["Backend@test.com"]),
+        ("everyone@test.com", vec!["engineering@test.com"]),
+    ] {
+        let group = group_repo
+            .upsert_group(TEST_SOURCE_ID, group_email, None, None)
+            .await
+            .unwrap();
+        let members: Vec<String> = members.into_iter().map(String::from).collect();
+        group_repo
+            .sync_group_members(&group.id, &members)
+            .await
+            .unwrap();
+    }
+
+    let mut alice_groups = group_repo
+        .find_groups_for_user("alice@test.com")
+        .await
+        .unwrap();
+    alice_groups.sort();
+    assert_eq!(
+        alice_groups,
+        vec![
+            "backend@test.com",
+            "engineering@test.com",
+            "everyone@test.com"
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_permissions_changed_propagates_to_folder_descendants() {
+    let fixture = common::setup_test_fixture().await.unwrap();
+    let event_queue = EventQueue::new(fixture.state.db_pool.pool().clone());
+    let repo = DocumentRepository::new(fixture.state.db_pool.pool());
+    let pool = fixture.state.db_pool.pool();
+
+    let processor =
+        QueueProcessor::new(fixture.state.clone()).with_poll_interval(Duration::from_millis(200));
+    let processor_handle = tokio::spawn(async move {
+        let _ = processor.start().await;
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let private = DocumentPermissions {
+        public: false,
+        users: vec!["owner@example.com".to_string()],
+        groups: vec![],
+    };
+    let docs = [
+        ("handbook_intro", "/Shared/Handbook/intro.md"),
+        ("handbook_nested", "/Shared/Handbook/Onboarding/week-1.md"),
+        ("sibling_folder_doc", "/Shared/Handbook Archive/old.md"),
+    ];
+    for (doc_id, path) in docs {
+        let content_id = fixture
+            .state
+            .content_storage
+            .store_content(format!("content of {}", doc_id).as_bytes(), None)
+            .await
+            .unwrap();
+        let event = ConnectorEvent::DocumentCreated {
+            sync_run_id: "sync_permissions".to_string(),
+            source_id: TEST_SOURCE_ID.to_string(),
+            document_id: doc_id.to_string(),
+            content_id,
+            metadata: DocumentMetadata {
+                title: Some(doc_id.to_string()),
+                author: None,
+                created_at: None,
+                updated_at: Some(OffsetDateTime::now_utc()),
+                content_type: None,
+                mime_type: Some("text/markdown".to_string()),
+                size: None,
+                url: None,
+                path: Some(path.to_string()),
+                extra: None,
+            },
+            permissions: private.clone(),
+            attributes: None,
+        };
+        event_queue.enqueue(TEST_SOURCE_ID, &event).await.unwrap();
+    }
+    common::wait_for_completed(pool, 3, Duration::from_secs(10)).await;
+
+    let before = repo
+        .find_by_external_id(TEST_SOURCE_ID, "handbook_intro")
+        .await
+        .unwrap()
+        .unwrap();
+    let (embedding_entries_before,): (i64,) =
+        sqlx::query_as("SELECT COUNT(*) FROM embedding_queue")
+            .fetch_one(pool)
+            .await
+            .unwrap();
+
+    // The folder itself isn't indexed; only its descendants are.
+    let event = ConnectorEvent::PermissionsChanged {
+        sync_run_id: "sync_permissions".to_string(),
+        source_id: TEST_SOURCE_ID.to_string(),
+        document_id: "handbook_folder".to_string(),
+        permissions: DocumentPermissions {
+            public: true,
+            users: vec![],
+            groups: vec![],
+        },
+        descendants_path: Some("/Shared/Handbook".to_string()),
+    };
+    event_queue.enqueue(TEST_SOURCE_ID, &event).await.unwrap();
+    common::wait_for_completed(pool, 4, Duration::from_secs(10)).await;
+
+    for doc_id in ["handbook_intro", "handbook_nested"] {
+        let doc = repo
+            .find_by_external_id(TEST_SOURCE_ID, doc_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            doc.permissions["public"],
+            json!(true),
+            "{} should be public",
+            doc_id
+        );
+    }
+    let sibling = repo
+        .find_by_external_id(TEST_SOURCE_ID, "sibling_folder_doc")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(sibling.permissions["public"], json!(false));
+    assert_eq!(sibling.permissions["users"], json!(["owner@example.com"]));
+
+    // Metadata-only: content and embeddings are untouched
+    let after = repo
+        .find_by_external_id(TEST_SOURCE_ID, "handbook_intro")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(after.content_id, before.content_id);
+    let (embedding_entries_after,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM embedding_queue")
+        .fetch_one(pool)
+        .await
+        .unwrap();
+    assert_eq!(embedding_entries_after, embedding_entries_before);
+
+    processor_handle.abort();
+}
+
+#[tokio::test]
+async fn test_integrity_check_and_repair() {
+    let fixture = common::setup_test_fixture().await.unwrap();
+    let server = TestServer::new(fixture.app().clone()).unwrap();
+    let pool = fixture.state.db_pool.pool();
+
+    let mut created = Vec::new();
+    for external_id in ["lost_content", "never_embedded", "healthy"] {
+        let mut request = create_document_request();
+        request.external_id = external_id.to_string();
+        let response = server.post("/documents").json(&request).await;
+        assert_eq!(response.status_code(), StatusCode::OK);
+        created.push(response.json::<Document>());
+    }
+    let (lost_content, never_embedded) = (&created[0], &created[1]);
+
+    // Blob deleted out from under a document: the FK nulls content_id.
+    sqlx::query("DELETE FROM content_blobs WHERE id = $1")
+        .bind(lost_content.content_id.as_ref().unwrap())
+        .execute(pool)
+        .await
+        .unwrap();
+
+    // Indexed two days ago with nothing queued to embed it.
+    sqlx::query("UPDATE documents SET last_indexed_at = NOW() - INTERVAL '48 hours' WHERE id = $1")
+        .bind(&never_embedded.id)
+        .execute(pool)
+        .await
+        .unwrap();
+    sqlx::query("DELETE FROM embedding_queue")
+        .execute(pool)
+        .await
+        .unwrap();
+
+    // A blob nothing ever referenced, older than the in-flight grace period.
+    let stray_blob = fixture
+        .state
+        .content_storage
+        .store_content(b"stray content", None)
+        .await
+        .unwrap();
+    sqlx::query("UPDATE content_blobs SET created_at = NOW() - INTERVAL '1 day' WHERE id = $1")
+        .bind(&stray_blob)
+        .execute(pool)
+        .await
+        .unwrap();
+
+    let finding = |report: &Value, kind: &str| -> Value {
+        report["findings"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|f| f["kind"] == kind)
+            .cloned()
+            .unwrap_or_else(|| panic!("missing finding {}", kind))
+    };
+
+    let response = server.get("/admin/integrity").await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let report: Value = response.json();
+
+    let missing_content = finding(&report, "document_missing_content");
+    assert_eq!(missing_content["count"], 1);
+    assert_eq!(missing_content["sample_ids"], json!([lost_content.id]));
+    assert_eq!(missing_content["repair"], "resync_source");
+    assert_eq!(missing_content["auto_repairable"], false);
+
+    let missing_embeddings = finding(&report, "document_missing_embeddings");
+    assert_eq!(missing_embeddings["count"], 1);
+    assert_eq!(missing_embeddings["sample_ids"], json!([never_embedded.id]));
+
+    let unreferenced = finding(&report, "unreferenced_content_blob");
+    assert_eq!(unreferenced["count"], 1);
+    assert_eq!(unreferenced["sample_ids"], json!([stray_blob]));
+
+    assert_eq!(finding(&report, "dangling_embedding")["count"], 0);
+
+    let response = server.post("/admin/integrity/repair").await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let result: Value = response.json();
+    assert_eq!(result["blobs_marked_for_gc"], 1);
+    assert_eq!(result["embeddings_enqueued"], 1);
+    assert_eq!(result["embeddings_deleted"], 0);
+    assert_eq!(result["documents_needing_resync"], 1);
+
+    let queued: (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM embedding_queue WHERE document_id = $1 AND status = 'pending'",
+    )
+    .bind(&never_embedded.id)
+    .fetch_one(pool)
+    .await
+    .unwrap();
+    assert_eq!(queued.0, 1);
+
+    // Repaired findings clear; lost content still needs a re-sync.
+    let report: Value = server.get("/admin/integrity").await.json();
+    assert_eq!(finding(&report, "document_missing_embeddings")["count"], 0);
+    assert_eq!(finding(&report, "unreferenced_content_blob")["count"], 0);
+    assert_eq!(finding(&report, "document_missing_content")["count"], 1);
+}
+
+#[tokio::test]
+async fn test_gc_reclaims_superseded_content() {
+    let fixture = common::setup_test_fixture().await.unwrap();
+    let server = TestServer::new(fixture.app().clone()).unwrap();
+    let pool = fixture.state.db_pool.pool();
+
+    let ref_count = |content_id: String| async move {
+        sqlx::query_scalar::<_, i32>("SELECT ref_count FROM content_blobs WHERE id = $1")
+            .bind(content_id)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    };
+
+    let created: Document = server
+        .post("/documents")
+        .json(&create_document_request())
+        .await
+        .json();
+    let old_content = created.content_id.clone().unwrap();
+    // Referenced by the document and by its first version
+    assert_eq!(ref_count(old_content.clone()).await, 2);
+
+    // A later sync replaces the content; only the old version still holds it.
+    let updated: Document = server
+        .put(&format!("/documents/{}", created.id))
+        .json(&update_document_request())
+        .await
+        .json();
+    let new_content = updated.content_id.clone().unwrap();
+    assert_ne!(new_content, old_content);
+    assert_eq!(ref_count(old_content.clone()).await, 1);
+    assert_eq!(ref_count(new_content.clone()).await, 2);
+
+    // Once retention prunes that version, the old blob is unreferenced.
+    sqlx::query("DELETE FROM document_versions WHERE document_id = $1 AND version = 1")
+        .bind(&created.id)
+        .execute(pool)
+        .await
+        .unwrap();
+    assert_eq!(ref_count(old_content.clone()).await, 0);
+
+    let result: Value = server.post("/admin/gc/run").await.json();
+    assert_eq!(result["orphans_marked"], 1);
+    assert_eq!(result["blobs_deleted"], 0);
+
+    sqlx::query("UPDATE content_blobs SET orphaned_at = NOW() - INTERVAL '30 days' WHERE id = $1")
+        .bind(&old_content)
+        .execute(pool)
+        .await
+        .unwrap();
+    let old_size: i64 = sqlx::query_scalar("SELECT size_bytes FROM content_blobs WHERE id = $1")
+        .bind(&old_content)
+        .fetch_one(pool)
+        .await
+        .unwrap();
+
+    let result: Value = server.post("/admin/gc/run").await.json();
+    assert_eq!(result["blobs_deleted"], 1);
+    assert_eq!(result["bytes_reclaimed"], old_size);
+
+    let remaining: Vec<String> = sqlx::query_scalar("SELECT id FROM content_blobs")
+        .fetch_all(pool)
+        .await
+        .unwrap();
+    assert_eq!(remaining, vec![new_content]);
+
+    let response = server.get("/admin/gc/reclaimed").await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let stats: Value = response.json();
+    assert_eq!(stats["total_runs"], 2);
+    assert_eq!(stats["blobs_deleted"], 1);
+    assert_eq!(stats["bytes_reclaimed"], old_size);
+    assert_eq!(stats["bytes_reclaimed_last_30_days"], old_size);
+    assert_eq!(stats["recent_runs"].as_array().unwrap().len(), 2);
+    assert_eq!(stats["recent_runs"][0]["blobs_deleted"], 1);
+}
+
+#[tokio::test]
+async fn test_storage_report_breaks_down_source_storage() {
+    let fixture = common::setup_test_fixture().await.unwrap();
+    let server = TestServer::new(fixture.app().clone()).unwrap();
+    let pool = fixture.state.db_pool.pool();
+
+    let blob_size = |content_id: String| async move {
+        sqlx::query_scalar::<_, i64>("SELECT size_bytes FROM content_blobs WHERE id = $1")
+            .bind(content_id)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    };
+
+    let created: Document = server
+        .post("/documents")
+        .json(&create_document_request())
+        .await
+        .json();
+    let updated: Document = server
+        .put(&format!("/documents/{}", created.id))
+        .json(&update_document_request())
+        .await
+        .json();
+    let old_size = blob_size(created.content_id.clone().unwrap()).await;
+    let new_size = blob_size(updated.content_id.clone().unwrap()).await;
+
+    let response = server.post("/admin/storage-report/run").await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    assert!(response.json::<Value>()["snapshots"].as_u64().unwrap() >= 1);
+
+    let response = server.get("/admin/storage-report").await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let report: Value = response.json();
+    let source = report["sources"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|s| s["source_id"] == created.source_id.as_str())
+        .expect("the document's source is reported");
+    assert_eq!(source["document_count"], 1);
+    assert_eq!(source["content_bytes"], new_size);
+    assert!(source["document_bytes"].as_i64().unwrap() > 0);
+    // The superseded first version keeps the old content alive.
+    assert_eq!(source["version_count"], 1);
+    assert_eq!(source["version_bytes"], old_size);
+    assert_eq!(source["daily_growth_bytes"], 0);
+    assert!(report["totals"]["total_bytes"].as_i64().unwrap() >= old_size + new_size);
+    assert!(report["snapshot_at"].is_string());
+}
+
+#[tokio::test]
+async fn test_document_versions_history_and_diff() {
+    let fixture = common::setup_test_fixture().await.unwrap();
+    let server = TestServer::new(fixture.app().clone()).unwrap();
+
+    let created: Document = server
+        .post("/documents")
+        .json(&create_document_request())
+        .await
+        .json();
+    server
+        .put(&format!("/documents/{}", created.id))
+        .json(&update_document_request())
+        .await
+        .assert_status_ok();
+
+    let versions: Vec<Value> = server
+        .get(&format!("/documents/{}/versions", created.id))
+        .await
+        .json();
+    assert_eq!(versions.len(), 2);
+    assert_eq!(versions[0]["version"], 2);
+    assert_eq!(versions[0]["title"], "Updated Test Document");
+    assert_eq!(versions[1]["version"], 1);
+    assert_eq!(versions[1]["title"], "Test Document");
+
+    let first: Value = server
+        .get(&format!("/documents/{}/versions/1", created.id))
+        .await
+        .json();
+    assert_eq!(
+        first["content"],
+        "This is test content for integration testing."
+    );
+    assert!(first.get("diff").is_none());
+
+    let second: Value = server
+        .get(&format!("/documents/{}/versions/2", created.id))
+        .add_query_param("diff_from", 1)
+        .await
+        .json();
+    assert_eq!(second["content"], "This is updated content.");
+    assert_eq!(second["diff_from"], 1);
+    assert_eq!(
+        second["diff"],
+        json!([
+            {"op": "delete", "text": "This is test content for integration testing."},
+            {"op": "insert", "text": "This is updated content."},
+        ])
+    );
+
+    let response = server
+        .get(&format!("/documents/{}/versions/3", created.id))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+
+    let response = server.get("/documents/missing/versions").await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_embedding_quarantine_list_and_retry() {
+    let fixture = common::setup_test_fixture().await.unwrap();
+    let server = TestServer::new(fixture.app().clone()).unwrap();
+    let pool = fixture.state.db_pool.pool();
+
+    let created: Document = server
+        .post("/documents")
+        .json(&create_document_request())
+        .await
+        .json();
+    let content_id = created.content_id.clone().unwrap();
+
+    let queue_id = ulid::Ulid::new().to_string();
+    sqlx::query(
+        "INSERT INTO embedding_queue
+            (id, document_id, status, retry_count, quarantined_at, error_message)
+         VALUES ($1, $2, 'quarantined', 5, NOW(), 'Permanent API error')",
+    )
+    .bind(&queue_id)
+    .bind(&created.id)
+    .execute(pool)
+    .await
+    .unwrap();
+
+    let chunk_id = ulid::Ulid::new().to_string();
+    sqlx::query(
+        "INSERT INTO embedding_chunk_failures
+            (id, document_id, content_id, chunk_start_offset, chunk_end_offset,
+             status, attempts, error_message)
+         VALUES ($1, $2, $3, 0, 100, 'quarantined', 3, 'Input rejected')",
+    )
+    .bind(&chunk_id)
+    .bind(&created.id)
+    .bind(&content_id)
+    .execute(pool)
+    .await
+    .unwrap();
+
+    let response = server.get("/admin/embeddings/quarantine").await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let quarantine: Value = response.json();
+    assert_eq!(quarantine["items"].as_array().unwrap().len(), 1);
+    assert_eq!(quarantine["items"][0]["id"], queue_id.as_str());
+    assert_eq!(
+        quarantine["items"][0]["error_message"],
+        "Permanent API error"
+    );
+    assert_eq!(quarantine["chunks"].as_array().unwrap().len(), 1);
+    assert_eq!(quarantine["chunks"][0]["id"], chunk_id.as_str());
+    assert_eq!(quarantine["chunks"][0]["attempts"], 3);
+
+    let response = server
+        .post(&format!(
+            "/admin/embeddings/quarantine/items/{}/retry",
+            queue_id
+        ))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let (status, retry_count): (String, i32) =
+        sqlx::query_as("SELECT status, retry_count FROM embedding_queue WHERE id = $1")
+            .bind(&queue_id)
+            .fetch_one(pool)
+            .await
+            .unwrap();
+    assert_eq!(status, "pending");
+    assert_eq!(retry_count, 0);
+
+    // Releasing a chunk clears it so the next attempt embeds it again.
+    let response = server
+        .post(&format!(
+            "/admin/embeddings/quarantine/chunks/{}/retry",
+            chunk_id
+        ))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let body: Value = response.json();
+    assert_eq!(body["document_id"], created.id.as_str());
+    let (remaining,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM embedding_chunk_failures")
+        .fetch_one(pool)
+        .await
+        .unwrap();
+    assert_eq!(remaining, 0);
+
+    let quarantine: Value = server.get("/admin/embeddings/quarantine").await.json();
+    assert!(quarantine["items"].as_array().unwrap().is_empty());
+    assert!(quarantine["chunks"].as_array().unwrap().is_empty());
+
+    let response = server
+        .post(&format!(
+            "/admin/embeddings/quarantine/chunks/{}/retry",
+            chunk_id
+        ))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+}
+
+/// Serves a fixed set of pages standing in for a connector's web UI.
+async fn spawn_link_target_server() -> String {
+    use axum::{Router, response::Redirect, routing::get};
+
+    let app = Router::new()
+        .route("/live", get(|| async { "ok" }))
+        .route("/moved", get(|| async { Redirect::permanent("/new-home") }))
+        .route("/new-home", get(|| async { "ok" }))
+        .route(
+            "/login-wall",
+            get(|| async { Redirect::temporary("/live") }),
+        )
+        .route("/private", get(|| async { StatusCode::UNAUTHORIZED }))
+        .route("/deleted", get(|| async { StatusCode::NOT_FOUND }));
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+    format!("http://{}", addr)
+}
+
+#[tokio::test]
+async fn test_link_check_updates_redirects_and_reports_dead_links() {
+    let fixture = common::setup_test_fixture().await.unwrap();
+    let server = TestServer::new(fixture.app().clone()).unwrap();
+    let pool = fixture.state.db_pool.pool();
+    let base_url = spawn_link_target_server().await;
+
+    let mut documents = HashMap::new();
+    for path in ["live", "moved", "login-wall", "private", "deleted"] {
+        let mut request = create_document_request();
+        request.external_id = format!("link_{}", path);
+        let response = server.post("/documents").json(&request).await;
+        assert_eq!(response.status_code(), StatusCode::OK);
+        let document = response.json::<Document>();
+
+        sqlx::query("UPDATE documents SET url = $2 WHERE id = $1")
+            .bind(&document.id)
+            .bind(format!("{}/{}", base_url, path))
+            .execute(pool)
+            .await
+            .unwrap();
+        documents.insert(path, document.id);
+    }
+
+    let response = server.post("/admin/link-check/run").await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let result: Value = response.json();
+    assert_eq!(result["checked"], 5);
+    assert_eq!(result["ok"], 2);
+    assert_eq!(result["redirected"], 1);
+    assert_eq!(result["dead"], 1);
+    assert_eq!(result["unverifiable"], 1);
+    assert_eq!(result["urls_updated"], 1);
+
+    let repo = DocumentRepository::new(pool);
+    let moved = repo.find_by_id(&documents["moved"]).await.unwrap().unwrap();
+    assert_eq!(moved.url, Some(format!("{}/new-home", base_url)));
+    // Temporary redirects leave the stored URL alone.
+    let login_wall = repo
+        .find_by_id(&documents["login-wall"])
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(login_wall.url, Some(format!("{}/login-wall", base_url)));
+
+    let report: Value = server.get("/admin/link-report").await.json();
+    let source = report["sources"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|s| s["source_id"] == TEST_SOURCE_ID)
+        .cloned()
+        .expect("test source missing from link report");
+    assert_eq!(source["checked"], 5);
+    assert_eq!(source["dead"], 1);
+    assert_eq!(source["dead_links"][0]["document_id"], documents["deleted"]);
+    assert_eq!(source["dead_links"][0]["http_status"], 404);
+
+    // Everything was just checked, so nothing is due again.
+    let result: Value = server.post("/admin/link-check/run").await.json();
+    assert_eq!(result["checked"], 0);
+}
+
+async fn wait_for_vector_index_build(server: &TestServer, id: &str) -> Value {
+    for _ in 0..100 {
+        let build: Value = server
+            .get(&format!("/admin/vector-indexes/builds/{}", id))
+            .await
+            .json();
+        if build["status"] == "completed" || build["status"] == "failed" {
+            return build;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    panic!("Vector index build {} did not finish", id);
+}
+
+async fn vector_index_names(pool: &sqlx::PgPool, dimensions: i32) -> Vec<String> {
+    sqlx::query_scalar(
+        "SELECT indexname::text FROM pg_indexes
+         WHERE tablename = 'embeddings' AND indexname LIKE $1
+         ORDER BY indexname",
+    )
+    .bind(format!("idx_embeddings_vector_{}%", dimensions))
+    .fetch_all(pool)
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_vector_index_rebuild_swaps_in_new_index() {
+    let fixture = common::setup_test_fixture().await.unwrap();
+    let server = TestServer::new(fixture.app().clone()).unwrap();
+    let pool = fixture.state.db_pool.pool();
+
+    let response = server
+        .post("/admin/vector-indexes/builds")
+        .json(&json!({ "dimensions": 512, "method": "ivfflat", "lists": 10 }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::ACCEPTED);
+    let build: Value = response.json();
+    let build_id = build["id"].as_str().unwrap().to_string();
+
+    let build = wait_for_vector_index_build(&server, &build_id).await;
+    assert_eq!(build["status"], "completed", "{:?}", build);
+    assert!(build["progress"].is_null());
+
+    assert_eq!(
+        vector_index_names(pool, 512).await,
+        vec!["idx_embeddings_vector_512".to_string()]
+    );
+    let definition: String = sqlx::query_scalar(
+        "SELECT indexdef FROM pg_indexes WHERE indexname = 'idx_embeddings_vector_512'",
+    )
+    .fetch_one(pool)
+    .await
+    .unwrap();
+    assert!(definition.contains("USING ivfflat"), "{}", definition);
+    assert!(definition.contains("lists='10'"), "{}", definition);
+
+    let builds: Value = server.get("/admin/vector-indexes/builds").await.json();
+    assert_eq!(builds[0]["id"], build_id.as_str());
+
+    let response = server
+        .post("/admin/vector-indexes/builds")
+        .json(&json!({ "dimensions": 512, "method": "hnsw", "lists": 10 }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+
+    // Only one build per index may be in flight
+    sqlx::query(
+        "INSERT INTO vector_index_builds (id, dimensions, method, m, ef_construction, status)
+         VALUES ($1, 384, 'hnsw', 32, 200, 'building')",
+    )
+    .bind(ulid::Ulid::new().to_string())
+    .execute(pool)
+    .await
+    .unwrap();
+    let response = server
+        .post("/admin/vector-indexes/builds")
+        .json(&json!({ "dimensions": 384 }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_failed_vector_index_build_rolls_back() {
+    let fixture = common::setup_test_fixture().await.unwrap();
+    let server = TestServer::new(fixture.app().clone()).unwrap();
+    let pool = fixture.state.db_pool.pool();
+
+    let created: Document = server
+        .post("/documents")
+        .json(&create_document_request())
+        .await
+        .json();
+
+    // Labelled 768 but only 3 long, so casting it for the index fails
+    sqlx::query("DROP INDEX idx_embeddings_vector_768")
+        .execute(pool)
+        .await
+        .unwrap();
+    sqlx::query(
+        "INSERT INTO embeddings
+            (id, document_id, chunk_index, chunk_start_offset, chunk_end_offset,
+             embedding, model_name, dimensions)
+         VALUES ($1, $2, 0, 0, 10, '[0.1,0.2,0.3]'::vector, 'test-model', 768)",
+    )
+    .bind(ulid::Ulid::new().to_string())
+    .bind(&created.id)
+    .execute(pool)
+    .await
+    .unwrap();
+
+    let response = server
+        .post("/admin/vector-indexes/builds")
+        .json(&json!({ "dimensions": 768 }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::ACCEPTED);
+    let build: Value = response.json();
+
+    let build = wait_for_vector_index_build(&server, build["id"].as_str().unwrap()).await;
+    assert_eq!(build["status"], "failed");
+    assert!(
+        build["error_message"]
+            .as_str()
+            .unwrap()
+            .contains("dimensions")
+    );
+    assert!(vector_index_names(pool, 768).await.is_empty());
+
+    // The 1024 index is unaffected and can still be rebuilt
+    let response = server
+        .post("/admin/vector-indexes/builds")
+        .json(&json!({ "dimensions": 1024 }))
+        .await;
+    let build: Value = response.json();
+    let build = wait_for_vector_index_build(&server, build["id"].as_str().unwrap()).await;
+    assert_eq!(build["status"], "completed", "{:?}", build);
+    assert_eq!(
+        vector_index_names(pool, 1024).await,
+        vec!["idx_embeddings_vector_1024".to_string()]
+    );
+}
+
+#[tokio::test]
+async fn test_ephemeral_upload_is_private_and_expires() {
+    let fixture = common::setup_test_fixture().await.unwrap();
+    let server = TestServer::new(fixture.app().clone()).unwrap();
+    let pool = fixture.state.db_pool.pool();
+    let user_id = "01JGF7V3E0Y2R1X8P5Q7W9T4N6";
+    let chat_id = "01JGF7V3E0Y2R1X8P5Q7W9CHAT";
+
+    sqlx::query("INSERT INTO chats (id, user_id, title) VALUES ($1, $2, 'Uploads')")
+        .bind(chat_id)
+        .bind(user_id)
+        .execute(pool)
+        .await
+        .unwrap();
+
+    let upload = |chat_id: &str| {
+        MultipartForm::new()
+            .add_text("user_id", user_id)
+            .add_text("chat_id", chat_id)
+            .add_part(
+                "file",
+                Part::bytes(b"Quarterly notes\nRevenue grew 12%".as_slice())
+                    .file_name("notes.txt")
+                    .mime_type("text/plain"),
+            )
+    };
+
+    let response = server
+        .post("/ephemeral-documents")
+        .multipart(upload("01JGF7V3E0Y2R1X8P5Q7W9NONE"))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+
+    let uploaded: Value = server
+        .post("/ephemeral-documents")
+        .multipart(upload(chat_id))
+        .await
+        .json();
+    let document_id = uploaded["document_id"].as_str().unwrap().to_string();
+    assert_eq!(uploaded["chat_id"], chat_id);
+    assert_eq!(uploaded["filename"], "notes.txt");
+
+    let document = DocumentRepository::new(pool)
+        .find_by_id(&document_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(document.source_id, uploaded["source_id"].as_str().unwrap());
+    assert_eq!(
+        document.permissions,
+        json!({"public": false, "users": ["test@example.com"], "groups": []})
+    );
+    let priority: i16 =
+        sqlx::query_scalar("SELECT priority FROM embedding_queue WHERE document_id = $1")
+            .bind(&document_id)
+            .fetch_one(pool)
+            .await
+            .unwrap();
+    assert!(priority > 0);
+
+    let listed: Vec<Value> = server
+        .get("/ephemeral-documents")
+        .add_query_param("user_id", user_id)
+        .add_query_param("chat_id", chat_id)
+        .await
+        .json();
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0]["document_id"], document_id.as_str());
+
+    // Listing reads as the uploader: an upload they lost access to is hidden
+    sqlx::query(
+        r#"UPDATE documents SET permissions = '{"users": [], "groups": []}' WHERE id = $1"#,
+    )
+    .bind(&document_id)
+    .execute(pool)
+    .await
+    .unwrap();
+    let listed: Vec<Value> = server
+        .get("/ephemeral-documents")
+        .add_query_param("user_id", user_id)
+        .await
+        .json();
+    assert!(listed.is_empty());
+    let response = server
+        .get("/ephemeral-documents")
+        .add_query_param("user_id", "01JGF7V3E0Y2R1X8P5Q7W9OTHR")
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+
+    let response = server
+        .delete(&format!("/ephemeral-documents/{}", document_id))
+        .add_query_param("user_id", "01JGF7V3E0Y2R1X8P5Q7W9OTHR")
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+
+    sqlx::query(
+        "UPDATE ephemeral_documents SET expires_at = NOW() - INTERVAL '1 minute' WHERE document_id = $1",
+    )
+    .bind(&document_id)
+    .execute(pool)
+    .await
+    .unwrap();
+    let deleted = EphemeralDocumentRepository::new(pool)
+        .delete_expired(100)
+        .await
+        .unwrap();
+    assert_eq!(deleted, vec![document_id.clone()]);
+    assert!(
+        DocumentRepository::new(pool)
+            .find_by_id(&document_id)
+            .await
+            .unwrap()
+            .is_none()
+    );
+}
+
+#[tokio::test]
+async fn test_ephemeral_upload_is_full_text_searchable_by_uploader_only() {
+    let fixture = common::setup_test_fixture().await.unwrap();
+    let server = TestServer::new(fixture.app().clone()).unwrap();
+    let pool = fixture.state.db_pool.pool();
+    let user_id = "01JGF7V3E0Y2R1X8P5Q7W9T4N6";
+    let chat_id = "01JGF7V3E0Y2R1X8P5Q7W9CHAT";
+
+    sqlx::query("INSERT INTO chats (id, user_id, title) VALUES ($1, $2, 'Uploads')")
+        .bind(chat_id)
+        .bind(user_id)
+        .execute(pool)
+        .await
+        .unwrap();
+
+    let uploaded: Value = server
+        .post("/ephemeral-documents")
+        .multipart(
+            MultipartForm::new()
+                .add_text("user_id", user_id)
+                .add_text("chat_id", chat_id)
+                .add_part(
+                    "file",
+                    Part::bytes(b"Quarterly notes\nRevenue grew 12%".as_slice())
+                        .file_name("notes.txt")
+                        .mime_type("text/plain"),
+                ),
+        )
+        .await
+        .json();
+    let document_id = uploaded["document_id"].as_str().unwrap().to_string();
+
+    let document = DocumentRepository::new(pool)
+        .find_by_id(&document_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        document.metadata[language::LANGUAGE_METADATA_KEY],
+        language::detect_primary_language("Quarterly notes\nRevenue grew 12%")
+    );
+
+    let search_as = |email: &'static str| async move {
+        sqlx::query_scalar::<_, String>(&format!(
+            "SELECT id FROM documents WHERE id @@@ pdb.parse('revenue', lenient => true) AND {}",
+            document::generate_permission_filter(email, &[])
+        ))
+        .fetch_all(pool)
+        .await
+        .unwrap()
+    };
+    assert_eq!(search_as("test@example.com").await, vec![document_id]);
+    assert!(search_as("other@example.com").await.is_empty());
+}
+
+#[tokio::test]
+async fn test_documents_past_retention_are_not_reindexed() {
+    let fixture = common::setup_test_fixture().await.unwrap();
+    let event_queue = EventQueue::new(fixture.state.db_pool.pool().clone());
+    let repo = DocumentRepository::new(fixture.state.db_pool.pool());
+    SourceRetentionRepository::new(fixture.state.db_pool.pool())
+        .upsert_policy(TEST_SOURCE_ID, 30, None)
+        .await
+        .unwrap();
+
+    let processor =
+        QueueProcessor::new(fixture.state.clone()).with_poll_interval(Duration::from_millis(200));
+    let processor_handle = tokio::spawn(async move {
+        let _ = processor.start().await;
+    });
+
+    // A purged document sent again ahead of a current one
+    for (doc_id, updated_at) in [
+        (
+            "expired_doc",
+            OffsetDateTime::now_utc() - time::Duration::days(90),
+        ),
+        ("current_doc", OffsetDateTime::now_utc()),
+    ] {
+        let content_id = fixture
+            .state
+            .content_storage
+            .store_content(b"Retention test content", None)
+            .await
+            .unwrap();
+        let event = ConnectorEvent::DocumentCreated {
+            sync_run_id: "sync_retention".to_string(),
+            source_id: TEST_SOURCE_ID.to_string(),
+            document_id: doc_id.to_string(),
+            content_id,
+            metadata: DocumentMetadata {
+                title: Some(doc_id.to_string()),
+                updated_at: Some(updated_at),
+                ..Default::default()
+            },
+            permissions: DocumentPermissions {
+                public: true,
+                users: vec![],
+                groups: vec![],
+            },
+            attributes: None,
+        };
+        event_queue.enqueue(TEST_SOURCE_ID, &event).await.unwrap();
+    }
+
+    common::wait_for_document_exists(&repo, TEST_SOURCE_ID, "current_doc", Duration::from_secs(5))
+        .await
+        .expect("Current document should be indexed");
+    assert!(
+        repo.find_by_external_id(TEST_SOURCE_ID, "expired_doc")
+            .await
+            .unwrap()
+            .is_none()
+    );
+
+    processor_handle.abort();
+}
+
+#[tokio::test]
+async fn test_document_pipeline_trace() {
+    let fixture = common::setup_test_fixture().await.unwrap();
+    let server = TestServer::new(fixture.app().clone()).unwrap();
+    let event_queue = EventQueue::new(fixture.state.db_pool.pool().clone());
+    let repo = DocumentRepository::new(fixture.state.db_pool.pool());
+
+    let processor =
+        QueueProcessor::new(fixture.state.clone()).with_poll_interval(Duration::from_millis(200));
+    let processor_handle = tokio::spawn(async move {
+        let _ = processor.start().await;
+    });
+
+    let doc_id = "trace_doc_1";
+    let content_id = fixture
+        .state
+        .content_storage
+        .store_content(b"Content to trace through the pipeline", None)
+        .await
+        .unwrap();
+    let create_event = ConnectorEvent::DocumentCreated {
+        sync_run_id: "sync_trace".to_string(),
+        source_id: TEST_SOURCE_ID.to_string(),
+        document_id: doc_id.to_string(),
+        content_id: content_id.clone(),
+        metadata: DocumentMetadata {
+            title: Some("Traced Document".to_string()),
+            ..Default::default()
+        },
+        permissions: DocumentPermissions {
+            public: true,
+            users: vec![],
+            groups: vec![],
+        },
+        attributes: None,
+    };
+    event_queue
+        .enqueue(TEST_SOURCE_ID, &create_event)
+        .await
+        .unwrap();
+
+    let document =
+        common::wait_for_document_exists(&repo, TEST_SOURCE_ID, doc_id, Duration::from_secs(5))
+            .await
+            .expect("Document should be created");
+    common::wait_for_embedding_queue_entry(
+        fixture.state.db_pool.pool(),
+        &document.id,
+        Duration::from_secs(5),
+    )
+    .await
+    .expect("Embedding queue entry should exist");
+    common::wait_for_completed(fixture.state.db_pool.pool(), 1, Duration::from_secs(5)).await;
+    processor_handle.abort();
+
+    let trace: Value = server
+        .get(&format!("/documents/{}/pipeline-trace", document.id))
+        .await
+        .json();
+    assert_eq!(trace["external_id"], doc_id);
+
+    let events = trace["connector_events"].as_array().unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0]["event_type"], "document_created");
+    assert_eq!(events[0]["status"], "completed");
+    assert_eq!(events[0]["content_id"], content_id.as_str());
+    let transitions: Vec<&str> = events[0]["transitions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| t["status"].as_str().unwrap())
+        .collect();
+    assert_eq!(transitions.first(), Some(&"pending"));
+    assert_eq!(transitions.last(), Some(&"completed"));
+
+    assert_eq!(trace["content_blob"]["id"], content_id.as_str());
+    assert_eq!(trace["embedding_queue"].as_array().unwrap().len(), 1);
+    assert_eq!(trace["quarantined_chunks"], 0);
+
+    let response = server.get("/documents/missing/pipeline-trace").await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_freshness_tracks_time_until_embeddings_complete() {
+    let fixture = common::setup_test_fixture().await.unwrap();
+    let server = TestServer::new(fixture.app().clone()).unwrap();
+    let event_queue = EventQueue::new(fixture.state.db_pool.pool().clone());
+    let repo = DocumentRepository::new(fixture.state.db_pool.pool());
+    let pool = fixture.state.db_pool.pool();
+
+    let processor =
+        QueueProcessor::new(fixture.state.clone()).with_poll_interval(Duration::from_millis(200));
+    let processor_handle = tokio::spawn(async move {
+        let _ = processor.start().await;
+    });
+
+    let doc_id = "freshness_doc_1";
+    let content_id = fixture
+        .state
+        .content_storage
+        .store_content(b"Content that should be searchable quickly", None)
+        .await
+        .unwrap();
+    let create_event = ConnectorEvent::DocumentCreated {
+        sync_run_id: "sync_freshness".to_string(),
+        source_id: TEST_SOURCE_ID.to_string(),
+        document_id: doc_id.to_string(),
+        content_id,
+        metadata: DocumentMetadata {
+            title: Some("Fresh Document".to_string()),
+            ..Default::default()
+        },
+        permissions: DocumentPermissions {
+            public: true,
+            users: vec![],
+            groups: vec![],
+        },
+        attributes: None,
+    };
+    event_queue
+        .enqueue(TEST_SOURCE_ID, &create_event)
+        .await
+        .unwrap();
+
+    let document =
+        common::wait_for_document_exists(&repo, TEST_SOURCE_ID, doc_id, Duration::from_secs(5))
+            .await
+            .expect("Document should be created");
+    common::wait_for_completed(pool, 1, Duration::from_secs(5)).await;
+    processor_handle.abort();
+
+    // Indexed, but not searchable until its embeddings complete.
+    let source_report = |report: Value| {
+        report["sources"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|s| s["source_id"] == TEST_SOURCE_ID)
+            .cloned()
+            .expect("the document's source is reported")
+    };
+    let report: Value = server.get("/admin/freshness").await.json();
+    let source = source_report(report);
+    assert_eq!(source["pending"], 1);
+    assert_eq!(source["samples"], 0);
+    assert!(source["p50_secs"].is_null());
+
+    sqlx::query(
+        "UPDATE embedding_queue SET status = 'completed', processed_at = NOW() WHERE document_id = $1",
+    )
+    .bind(&document.id)
+    .execute(pool)
+    .await
+    .unwrap();
+
+    let report: Value = server.get("/admin/freshness").await.json();
+    assert_eq!(report["slo_secs"], 300);
+    let source = source_report(report.clone());
+    assert_eq!(source["pending"], 0);
+    assert_eq!(source["samples"], 1);
+    assert_eq!(source["within_slo"], 1);
+    assert_eq!(source["slo_attainment"], 1.0);
+    assert_eq!(source["slo_violated"], false);
+    assert!(source["p95_secs"].as_f64().unwrap() < 300.0);
+    assert_eq!(report["overall"]["samples"], 1);
+
+    let response = server.get("/metrics").await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let metrics = response.text();
+    assert!(metrics.contains(&format!(
+        "omni_indexer_freshness_samples{{source_id=\"{}\"",
+        TEST_SOURCE_ID
+    )));
+    assert!(metrics.contains("omni_indexer_freshness_slo_violation{source_id=\"all\"} 0"));
+}
+
+#[tokio::test]
+async fn test_term_dictionary_refreshes_only_changed_documents() {
+    let fixture = common::setup_test_fixture().await.unwrap();
+    let server = TestServer::new(fixture.app().clone()).unwrap();
+    let pool = fixture.state.db_pool.pool();
+
+    let mut doc_ids = Vec::new();
+    for (external_id, title, content) in [
+        ("terms_1", "Glacier orchard", "glacier orchard lantern"),
+        ("terms_2", "Glacier harbor", "glacier harbor lantern"),
+    ] {
+        let id = ulid::Ulid::new().to_string();
+        sqlx::query(
+            r#"
+            INSERT INTO documents (id, source_id, external_id, title, content, metadata, permissions, attributes)
+            VALUES ($1, $2, $3, $4, $5, '{}', '{"public": true, "users": [], "groups": []}', '{}')
+            "#,
+        )
+        .bind(&id)
+        .bind(TEST_SOURCE_ID)
+        .bind(external_id)
+        .bind(title)
+        .bind(content)
+        .execute(pool)
+        .await
+        .unwrap();
+        doc_ids.push(id);
+    }
+    let term_counts = || async {
+        sqlx::query_as::<_, (String, i64)>(
+            "SELECT term, document_count FROM corpus_term_counts ORDER BY term",
+        )
+        .fetch_all(pool)
+        .await
+        .unwrap()
+    };
+    let counts = |pairs: &[(&str, i64)]| -> Vec<(String, i64)> {
+        pairs.iter().map(|(t, c)| (t.to_string(), *c)).collect()
+    };
+
+    let response = server
+        .post("/admin/term-dictionary/refresh?mode=full")
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let run: Value = response.json();
+    assert_eq!(run["mode"], "full");
+    assert_eq!(run["status"], "completed");
+    assert_eq!(run["documents_refreshed"], 2);
+    assert!(run["changed_since"].is_null());
+    // Only terms in at least two documents make the dictionary
+    assert_eq!(run["terms"], 2);
+    assert_eq!(
+        term_counts().await,
+        counts(&[
+            ("glacier", 2),
+            ("harbor", 1),
+            ("lantern", 2),
+            ("orchard", 1)
+        ])
+    );
+
+    // Nothing changed since: nothing is re-tokenized
+    let run: Value = server.post("/admin/term-dictionary/refresh").await.json();
+    assert_eq!(run["mode"], "incremental");
+    assert_eq!(run["documents_refreshed"], 0);
+    assert!(run["changed_since"].is_string());
+    assert_eq!(run["terms"], 2);
+
+    sqlx::query(
+        "UPDATE documents SET content = 'glacier meadow', last_indexed_at = NOW() WHERE id = $1",
+    )
+    .bind(&doc_ids[0])
+    .execute(pool)
+    .await
+    .unwrap();
+    sqlx::query("DELETE FROM documents WHERE id = $1")
+        .bind(&doc_ids[1])
+        .execute(pool)
+        .await
+        .unwrap();
+
+    let run: Value = server.post("/admin/term-dictionary/refresh").await.json();
+    assert_eq!(run["status"], "completed");
+    assert_eq!(run["documents_refreshed"], 1);
+    assert_eq!(run["documents_removed"], 1);
+    assert_eq!(run["terms"], 0);
+    assert_eq!(
+        term_counts().await,
+        counts(&[("glacier", 1), ("meadow", 1), ("orchard", 1)])
+    );
+
+    let response = server.get("/admin/term-dictionary/status").await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let status: Value = response.json();
+    assert_eq!(status["last_run"]["id"], run["id"]);
+    assert_eq!(status["last_completed_run"]["id"], run["id"]);
+    assert_eq!(status["recent_runs"].as_array().unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn test_source_reindex_clears_embeddings_and_reports_progress() {
+    let fixture = common::setup_test_fixture().await.unwrap();
+    let server = TestServer::new(fixture.app().clone()).unwrap();
+    let event_queue = EventQueue::new(fixture.state.db_pool.pool().clone());
+    let repo = DocumentRepository::new(fixture.state.db_pool.pool());
+    let pool = fixture.state.db_pool.pool();
+
+    let processor =
+        QueueProcessor::new(fixture.state.clone()).with_poll_interval(Duration::from_millis(200));
+    let processor_handle = tokio::spawn(async move {
+        let _ = processor.start().await;
+    });
+
+    let doc_id = "reindex_doc_1";
+    let content_id = fixture
+        .state
+        .content_storage
+        .store_content(b"Content indexed before the fix", None)
+        .await
+        .unwrap();
+    let create_event = ConnectorEvent::DocumentCreated {
+        sync_run_id: "sync_reindex".to_string(),
+        source_id: TEST_SOURCE_ID.to_string(),
+        document_id: doc_id.to_string(),
+        content_id,
+        metadata: DocumentMetadata {
+            title: Some("Reindexed Document".to_string()),
+            ..Default::default()
+        },
+        permissions: DocumentPermissions {
+            public: true,
+            users: vec![],
+            groups: vec![],
+        },
+        attributes: None,
+    };
+    event_queue
+        .enqueue(TEST_SOURCE_ID, &create_event)
+        .await
+        .unwrap();
+
+    let document =
+        common::wait_for_document_exists(&repo, TEST_SOURCE_ID, doc_id, Duration::from_secs(5))
+            .await
+            .expect("Document should be created");
+    common::wait_for_completed(pool, 1, Duration::from_secs(5)).await;
+    processor_handle.abort();
+
+    sqlx::query("UPDATE embedding_queue SET status = 'completed' WHERE document_id = $1")
+        .bind(&document.id)
+        .execute(pool)
+        .await
+        .unwrap();
+    sqlx::query(
+        r#"
+        INSERT INTO embeddings (id, document_id, chunk_index, chunk_start_offset, chunk_end_offset, embedding, model_name, dimensions)
+        VALUES ('emb_reindex_stale', $1, 0, 0, 30, '[0.1,0.2,0.3]'::vector, 'test-model', 3)
+        "#,
+    )
+    .bind(&document.id)
+    .execute(pool)
+    .await
+    .unwrap();
+
+    let response = server.post("/sources/unknown_source_id/reindex").await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+
+    let response = server
+        .post(&format!("/sources/{}/reindex", TEST_SOURCE_ID))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::ACCEPTED);
+    let job: Value = response.json();
+    assert_eq!(job["total_documents"], 1);
+    let job_url = format!(
+        "/sources/{}/reindex/{}",
+        TEST_SOURCE_ID,
+        job["id"].as_str().unwrap()
+    );
+
+    let mut status = Value::Null;
+    for _ in 0..50 {
+        status = server.get(&job_url).await.json();
+        if status["status"] != "reindexing" {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    assert_eq!(status["status"], "embedding");
+    assert_eq!(status["reindexed_documents"], 1);
+    assert_eq!(status["embeddings_cleared"], 1);
+    assert_eq!(status["progress"]["pending_documents"], 1);
+    assert_eq!(status["progress"]["percent"], 0.0);
+
+    let stale: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM embeddings WHERE document_id = $1")
+        .bind(&document.id)
+        .fetch_one(pool)
+        .await
+        .unwrap();
+    assert_eq!(stale, 0);
+
+    // Only one reindex of a source at a time
+    let response = server
+        .post(&format!("/sources/{}/reindex", TEST_SOURCE_ID))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+
+    // Embedding the document again completes the job
+    sqlx::query("UPDATE embedding_queue SET status = 'completed' WHERE document_id = $1")
+        .bind(&document.id)
+        .execute(pool)
+        .await
+        .unwrap();
+    sqlx::query(
+        r#"
+        INSERT INTO embeddings (id, document_id, chunk_index, chunk_start_offset, chunk_end_offset, embedding, model_name, dimensions)
+        VALUES ('emb_reindex_fresh', $1, 0, 0, 30, '[0.1,0.2,0.3]'::vector, 'test-model', 3)
+        "#,
+    )
+    .bind(&document.id)
+    .execute(pool)
+    .await
+    .unwrap();
+    SourceReindexer::new(fixture.state.clone())
+        .check_embedding()
+        .await
+        .unwrap();
+
+    let status: Value = server.get(&job_url).await.json();
+    assert_eq!(status["status"], "completed");
+    assert!(status["progress"].is_null());
+
+    let jobs: Value = server
+        .get(&format!("/sources/{}/reindex", TEST_SOURCE_ID))
+        .await
+        .json();
+    assert_eq!(jobs.as_array().unwrap().len(), 1);
+}
+
+struct FixedOcr;
+
+#[async_trait::async_trait]
+impl OcrBackend for FixedOcr {
+    async fn recognize(&self, image: &[u8], mime_type: &str) -> anyhow::Result<String> {
+        assert_eq!(mime_type, "image/png");
+        Ok(format!(
+            "Scanned receipt  \r\n\r\n\r\nTotal: {} bytes\n",
+            image.len()
+        ))
+    }
+}
+
+#[tokio::test]
+async fn test_extract_content_ocr_and_unsupported_types() {
+    let fixture = common::setup_test_fixture().await.unwrap();
+    let server = TestServer::new(fixture.app().clone()).unwrap();
+
+    let content_id = fixture
+        .state
+        .content_storage
+        .store_content_with_type(b"not really a png", Some("image/png"), None)
+        .await
+        .unwrap();
+
+    let extractor = ContentExtractor::new(&fixture.state, ExtractionConfig::default())
+        .with_ocr_backend(Arc::new(FixedOcr));
+    let extracted = extractor.extract(&content_id, "image/png").await.unwrap();
+    assert_eq!(extracted.markdown, "Scanned receipt\n\nTotal: 16 bytes");
+    assert_eq!(extracted.method, ExtractionMethod::Ocr);
+
+    // Without an OCR backend, images cannot be extracted
+    let response = server
+        .post("/extract")
+        .json(&json!({"content_id": content_id, "mime_type": "image/png"}))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+
+    let response = server
+        .post("/extract")
+        .json(&json!({"content_id": "missing", "mime_type": "application/pdf"}))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+}
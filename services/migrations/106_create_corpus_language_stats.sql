@@ -0,0 +1,26 @@
+-- Primary-language detection and per-source corpus rollups for the admin
+-- language stats endpoint.
+--
+-- Detected languages live in a side table rather than a column on documents:
+-- every UPDATE on documents rewrites the row in the bm25 index, and detection
+-- runs after the upsert, so a column would double index churn on every sync.
+
+CREATE TABLE IF NOT EXISTS document_languages (
+    document_id VARCHAR(26) PRIMARY KEY REFERENCES documents(id) ON DELETE CASCADE,
+    -- ISO 639-3 code, or 'und' when the content is too short or ambiguous
+    language TEXT NOT NULL,
+    detected_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_document_languages_language ON document_languages(language);
+
+-- Rebuilt wholesale by the indexer's periodic rollup job. Documents without a
+-- detection yet are counted under 'und'.
+CREATE TABLE IF NOT EXISTS corpus_language_stats (
+    source_id CHAR(26) NOT NULL REFERENCES sources(id) ON DELETE CASCADE,
+    language TEXT NOT NULL,
+    document_count BIGINT NOT NULL,
+    chunk_count BIGINT NOT NULL,
+    refreshed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (source_id, language)
+);
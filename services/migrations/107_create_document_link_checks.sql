@@ -0,0 +1,21 @@
+-- Results of the indexer's link verification job, one row per checked
+-- document. Kept out of the documents table so recording a check does not
+-- rewrite the document's bm25 index entry.
+
+CREATE TABLE IF NOT EXISTS document_link_checks (
+    document_id VARCHAR(26) PRIMARY KEY REFERENCES documents(id) ON DELETE CASCADE,
+    source_id CHAR(26) NOT NULL REFERENCES sources(id) ON DELETE CASCADE,
+    -- URL as checked, before any redirect was applied to the document
+    url TEXT NOT NULL,
+    -- ok | redirected | dead | unverifiable | error
+    status TEXT NOT NULL,
+    http_status INT,
+    final_url TEXT,
+    error_message TEXT,
+    checked_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_document_link_checks_source_status
+    ON document_link_checks(source_id, status);
+CREATE INDEX IF NOT EXISTS idx_document_link_checks_checked_at
+    ON document_link_checks(checked_at);
@@ -0,0 +1,18 @@
+-- Search result clicks, used to train the searcher's source router. The
+-- source type is captured at click time so the signal survives the clicked
+-- document being deleted or re-synced under a new id.
+
+CREATE TABLE IF NOT EXISTS search_clicks (
+    id BIGSERIAL PRIMARY KEY,
+    user_id CHAR(26) REFERENCES users(id) ON DELETE SET NULL,
+    -- Query as typed by the user, operators included
+    query TEXT NOT NULL,
+    document_id VARCHAR(26) NOT NULL,
+    source_id CHAR(26) NOT NULL REFERENCES sources(id) ON DELETE CASCADE,
+    source_type VARCHAR(50) NOT NULL,
+    -- Zero-based rank of the clicked result, when known
+    position INT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_search_clicks_created_at ON search_clicks(created_at);
@@ -0,0 +1,22 @@
+-- One row per RAG invocation: the chunks placed in the prompt, the document
+-- versions and scores they came from, and the identity and groups the
+-- permission filter was evaluated against. User columns are plain values
+-- rather than foreign keys so the audit trail outlives the user.
+
+CREATE TABLE IF NOT EXISTS rag_provenance (
+    id CHAR(26) PRIMARY KEY,
+    user_id CHAR(26),
+    user_email TEXT,
+    query TEXT NOT NULL,
+    -- User email, resolved groups and source type filter at answer time
+    permission_snapshot JSONB NOT NULL,
+    -- Ordered context entries: document id/version, score, chunk ids and the
+    -- document's permissions as they were when the answer was generated
+    context JSONB NOT NULL,
+    document_ids TEXT[] NOT NULL DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_rag_provenance_created_at ON rag_provenance(created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_rag_provenance_user_id ON rag_provenance(user_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_rag_provenance_document_ids ON rag_provenance USING GIN(document_ids);
@@ -0,0 +1,40 @@
+-- User-curated document sets. Search and chat can be scoped to a collection
+-- via `collection_id`; document permissions still apply on top of the
+-- collection's own visibility.
+
+CREATE TABLE IF NOT EXISTS collections (
+    id CHAR(26) PRIMARY KEY,
+    owner_id CHAR(26) NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    description TEXT,
+    -- private: owner only; team: members of `team_groups`; public: everyone
+    visibility TEXT NOT NULL DEFAULT 'private',
+    -- Lowercased group emails a team collection is shared with
+    team_groups TEXT[] NOT NULL DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT collections_name_not_blank CHECK (btrim(name) <> ''),
+    CONSTRAINT collections_visibility_check CHECK (visibility IN ('private', 'team', 'public')),
+    CONSTRAINT collections_team_has_groups CHECK (visibility <> 'team' OR cardinality(team_groups) > 0)
+);
+
+CREATE INDEX IF NOT EXISTS idx_collections_owner_updated
+    ON collections (owner_id, updated_at DESC);
+
+CREATE INDEX IF NOT EXISTS idx_collections_team_groups
+    ON collections USING GIN (team_groups)
+    WHERE visibility = 'team';
+
+CREATE TRIGGER update_collections_updated_at BEFORE UPDATE ON collections
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+CREATE TABLE IF NOT EXISTS collection_documents (
+    collection_id CHAR(26) NOT NULL REFERENCES collections(id) ON DELETE CASCADE,
+    document_id VARCHAR(26) NOT NULL REFERENCES documents(id) ON DELETE CASCADE,
+    added_by CHAR(26) REFERENCES users(id) ON DELETE SET NULL,
+    added_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (collection_id, document_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_collection_documents_document_id
+    ON collection_documents (document_id);
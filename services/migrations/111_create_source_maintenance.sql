@@ -0,0 +1,14 @@
+-- Sources an admin has taken offline, e.g. for credential rotation or cleanup.
+-- While a row exists, scheduled syncs are skipped and manual triggers are
+-- rejected. `search_visibility` decides whether the source's documents are
+-- hidden from search or returned labelled as possibly stale.
+
+CREATE TABLE IF NOT EXISTS source_maintenance (
+    source_id CHAR(26) PRIMARY KEY REFERENCES sources(id) ON DELETE CASCADE,
+    -- hidden | stale
+    search_visibility TEXT NOT NULL DEFAULT 'stale',
+    reason TEXT,
+    started_by CHAR(26) REFERENCES users(id) ON DELETE SET NULL,
+    started_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT source_maintenance_visibility_check CHECK (search_visibility IN ('hidden', 'stale'))
+);
@@ -0,0 +1,33 @@
+-- Per-source exports of stored documents as a gzipped tar of Markdown files.
+-- The archive is written to object storage in consecutive parts so it never
+-- has to be held in memory; downloads concatenate the parts in order.
+
+CREATE TABLE IF NOT EXISTS source_exports (
+    id CHAR(26) PRIMARY KEY,
+    source_id CHAR(26) NOT NULL REFERENCES sources(id) ON DELETE CASCADE,
+    requested_by CHAR(26) REFERENCES users(id) ON DELETE SET NULL,
+    -- pending | running | completed | failed | expired
+    status TEXT NOT NULL DEFAULT 'pending',
+    document_count INT NOT NULL DEFAULT 0,
+    size_bytes BIGINT NOT NULL DEFAULT 0,
+    -- Content ids of the archive parts, in order
+    part_content_ids TEXT[] NOT NULL DEFAULT '{}',
+    -- Secret required by the download link; set on completion
+    download_token TEXT,
+    expires_at TIMESTAMPTZ,
+    error_message TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    started_at TIMESTAMPTZ,
+    -- Bumped after every batch so exports orphaned by a restart can be failed
+    heartbeat_at TIMESTAMPTZ,
+    completed_at TIMESTAMPTZ,
+    CONSTRAINT source_exports_status_check
+        CHECK (status IN ('pending', 'running', 'completed', 'failed', 'expired'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_source_exports_source_created
+    ON source_exports (source_id, created_at DESC);
+
+CREATE INDEX IF NOT EXISTS idx_source_exports_active_status
+    ON source_exports (status, created_at)
+    WHERE status IN ('pending', 'running', 'completed');
@@ -0,0 +1,114 @@
+-- Reference counting for content blobs, plus a history of GC runs.
+--
+-- ref_count tracks how many documents, uploads and export archives point at a
+-- blob. It is maintained by triggers, so content superseded by a later sync
+-- (the document moves to a new content_id) drops to zero immediately and the
+-- GC can find unreferenced blobs from a partial index instead of anti-joining
+-- every referencing table.
+
+ALTER TABLE content_blobs
+ADD COLUMN IF NOT EXISTS ref_count INTEGER NOT NULL DEFAULT 0;
+
+CREATE OR REPLACE FUNCTION adjust_content_blob_ref_counts(
+    removed TEXT[],
+    added TEXT[]
+) RETURNS VOID AS $$
+BEGIN
+    IF removed IS NOT NULL AND cardinality(removed) > 0 THEN
+        UPDATE content_blobs cb
+        SET ref_count = GREATEST(cb.ref_count - r.n, 0)
+        FROM (SELECT id, COUNT(*)::INT AS n FROM unnest(removed) AS id GROUP BY id) r
+        WHERE cb.id = r.id;
+    END IF;
+
+    -- A blob that gains a reference is no longer an orphan
+    IF added IS NOT NULL AND cardinality(added) > 0 THEN
+        UPDATE content_blobs cb
+        SET ref_count = cb.ref_count + a.n, orphaned_at = NULL
+        FROM (SELECT id, COUNT(*)::INT AS n FROM unnest(added) AS id GROUP BY id) a
+        WHERE cb.id = a.id;
+    END IF;
+END;
+$$ LANGUAGE plpgsql;
+
+-- documents.content_id and uploads.content_id
+CREATE OR REPLACE FUNCTION track_content_id_ref_count() RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        PERFORM adjust_content_blob_ref_counts(NULL, ARRAY[NEW.content_id::TEXT]);
+    ELSIF TG_OP = 'DELETE' THEN
+        PERFORM adjust_content_blob_ref_counts(ARRAY[OLD.content_id::TEXT], NULL);
+    ELSIF NEW.content_id IS DISTINCT FROM OLD.content_id THEN
+        PERFORM adjust_content_blob_ref_counts(
+            ARRAY[OLD.content_id::TEXT],
+            ARRAY[NEW.content_id::TEXT]
+        );
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+-- source_exports.part_content_ids
+CREATE OR REPLACE FUNCTION track_export_parts_ref_count() RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        PERFORM adjust_content_blob_ref_counts(NULL, NEW.part_content_ids);
+    ELSIF TG_OP = 'DELETE' THEN
+        PERFORM adjust_content_blob_ref_counts(OLD.part_content_ids, NULL);
+    ELSIF NEW.part_content_ids IS DISTINCT FROM OLD.part_content_ids THEN
+        PERFORM adjust_content_blob_ref_counts(OLD.part_content_ids, NEW.part_content_ids);
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS documents_content_ref_count ON documents;
+CREATE TRIGGER documents_content_ref_count
+    AFTER INSERT OR DELETE OR UPDATE OF content_id ON documents
+    FOR EACH ROW EXECUTE FUNCTION track_content_id_ref_count();
+
+DROP TRIGGER IF EXISTS uploads_content_ref_count ON uploads;
+CREATE TRIGGER uploads_content_ref_count
+    AFTER INSERT OR DELETE OR UPDATE OF content_id ON uploads
+    FOR EACH ROW EXECUTE FUNCTION track_content_id_ref_count();
+
+DROP TRIGGER IF EXISTS source_exports_parts_ref_count ON source_exports;
+CREATE TRIGGER source_exports_parts_ref_count
+    AFTER INSERT OR DELETE OR UPDATE OF part_content_ids ON source_exports
+    FOR EACH ROW EXECUTE FUNCTION track_export_parts_ref_count();
+
+-- Backfill from existing references
+UPDATE content_blobs cb
+SET ref_count = refs.n
+FROM (
+    SELECT id, COUNT(*)::INT AS n
+    FROM (
+        SELECT content_id::TEXT AS id FROM documents WHERE content_id IS NOT NULL
+        UNION ALL
+        SELECT content_id::TEXT FROM uploads
+        UNION ALL
+        SELECT unnest(part_content_ids) FROM source_exports
+    ) r
+    GROUP BY id
+) refs
+WHERE cb.id = refs.id;
+
+UPDATE content_blobs SET orphaned_at = NULL WHERE ref_count > 0 AND orphaned_at IS NOT NULL;
+
+CREATE INDEX IF NOT EXISTS idx_content_blobs_unreferenced
+    ON content_blobs (created_at)
+    WHERE ref_count = 0 AND orphaned_at IS NULL;
+
+CREATE TABLE IF NOT EXISTS content_gc_runs (
+    id CHAR(26) PRIMARY KEY,
+    started_at TIMESTAMPTZ NOT NULL,
+    completed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    dry_run BOOLEAN NOT NULL DEFAULT FALSE,
+    orphans_marked BIGINT NOT NULL DEFAULT 0,
+    orphans_unmarked BIGINT NOT NULL DEFAULT 0,
+    blobs_deleted BIGINT NOT NULL DEFAULT 0,
+    bytes_reclaimed BIGINT NOT NULL DEFAULT 0,
+    error_count INT NOT NULL DEFAULT 0
+);
+
+CREATE INDEX IF NOT EXISTS idx_content_gc_runs_started_at ON content_gc_runs (started_at DESC);
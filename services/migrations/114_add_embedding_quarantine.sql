@@ -0,0 +1,43 @@
+-- Quarantine for embedding work that keeps failing.
+--
+-- Queue items that exhaust their retries move to 'quarantined' instead of
+-- lingering as 'failed'. Within a document, a sliding-window chunk that fails
+-- while the rest of the document embeds fine is tracked here; once it has
+-- failed repeatedly it is quarantined and skipped, so the remainder of the
+-- document still gets indexed.
+
+ALTER TABLE embedding_queue DROP CONSTRAINT IF EXISTS embedding_queue_status_check;
+ALTER TABLE embedding_queue ADD CONSTRAINT embedding_queue_status_check
+    CHECK (status IN ('pending', 'processing', 'completed', 'failed', 'quarantined'));
+
+ALTER TABLE embedding_queue ADD COLUMN IF NOT EXISTS quarantined_at TIMESTAMPTZ;
+-- Chunks skipped when the item completed
+ALTER TABLE embedding_queue ADD COLUMN IF NOT EXISTS quarantined_chunks INTEGER NOT NULL DEFAULT 0;
+
+CREATE TABLE IF NOT EXISTS embedding_chunk_failures (
+    id CHAR(26) PRIMARY KEY,
+    document_id TEXT NOT NULL REFERENCES documents(id) ON DELETE CASCADE,
+    -- Offsets only identify a chunk within this content; a re-sync with new
+    -- content starts over
+    content_id CHAR(26) NOT NULL,
+    chunk_start_offset INTEGER NOT NULL,
+    chunk_end_offset INTEGER NOT NULL,
+    -- failing | quarantined
+    status TEXT NOT NULL DEFAULT 'failing',
+    attempts INTEGER NOT NULL DEFAULT 1,
+    error_message TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT embedding_chunk_failures_status_check
+        CHECK (status IN ('failing', 'quarantined')),
+    CONSTRAINT embedding_chunk_failures_chunk_unique
+        UNIQUE (document_id, content_id, chunk_start_offset)
+);
+
+CREATE INDEX IF NOT EXISTS idx_embedding_chunk_failures_quarantined
+    ON embedding_chunk_failures (updated_at DESC)
+    WHERE status = 'quarantined';
+
+CREATE INDEX IF NOT EXISTS idx_embedding_queue_quarantined
+    ON embedding_queue (quarantined_at DESC)
+    WHERE status = 'quarantined';
@@ -0,0 +1,38 @@
+-- Managed (re)builds of the per-dimension embedding vector indexes.
+--
+-- A build creates the replacement index CONCURRENTLY under a temporary name,
+-- then swaps it in place of idx_embeddings_vector_<dimensions> by renaming,
+-- so searches keep using the old index until the new one is valid. A failed
+-- build drops the partial replacement and leaves the old index untouched.
+
+CREATE TABLE IF NOT EXISTS vector_index_builds (
+    id CHAR(26) PRIMARY KEY,
+    dimensions INT NOT NULL,
+    -- hnsw | ivfflat
+    method TEXT NOT NULL,
+    -- hnsw parameters
+    m INT,
+    ef_construction INT,
+    -- ivfflat parameter
+    lists INT,
+    -- pending | building | swapping | completed | failed
+    status TEXT NOT NULL DEFAULT 'pending',
+    -- Backend running CREATE INDEX, for pg_stat_progress_create_index
+    backend_pid INT,
+    error_message TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    started_at TIMESTAMPTZ,
+    completed_at TIMESTAMPTZ,
+    CONSTRAINT vector_index_builds_method_check
+        CHECK (method IN ('hnsw', 'ivfflat')),
+    CONSTRAINT vector_index_builds_status_check
+        CHECK (status IN ('pending', 'building', 'swapping', 'completed', 'failed'))
+);
+
+-- At most one build in flight per index
+CREATE UNIQUE INDEX IF NOT EXISTS idx_vector_index_builds_one_active
+    ON vector_index_builds (dimensions)
+    WHERE status IN ('pending', 'building', 'swapping');
+
+CREATE INDEX IF NOT EXISTS idx_vector_index_builds_created_at
+    ON vector_index_builds (created_at DESC);
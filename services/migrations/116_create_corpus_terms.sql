@@ -0,0 +1,12 @@
+-- Term dictionary for the searcher's spell correction ("did you mean").
+--
+-- Rebuilt wholesale by the indexer's periodic refresh from unstemmed
+-- ('simple' configuration) lexemes of document titles and content, so
+-- suggestions are real words from the corpus rather than stems.
+
+CREATE TABLE IF NOT EXISTS corpus_terms (
+    term TEXT PRIMARY KEY,
+    -- Documents containing the term; used to rank correction candidates
+    document_count BIGINT NOT NULL,
+    refreshed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
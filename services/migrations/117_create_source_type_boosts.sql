@@ -0,0 +1,10 @@
+-- Deployment-wide ranking multipliers per source type, applied to hybrid
+-- search scores after rank fusion (e.g. Confluence 1.5, Slack 0.8). Source
+-- types without a row rank unboosted; searches can override individual
+-- types with `source_boosts`.
+
+CREATE TABLE IF NOT EXISTS source_type_boosts (
+    source_type VARCHAR(50) PRIMARY KEY,
+    boost REAL NOT NULL CHECK (boost > 0 AND boost <= 10),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
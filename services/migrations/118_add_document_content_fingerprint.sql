@@ -0,0 +1,7 @@
+-- Fingerprint of a document's normalized text, written by the indexer, so
+-- search can collapse the same file synced through several connectors (e.g.
+-- a Drive file also attached in Slack) into one result. NULL for documents
+-- too short to fingerprint, and for existing documents until their next sync.
+
+ALTER TABLE documents
+ADD COLUMN IF NOT EXISTS content_fingerprint TEXT;
@@ -0,0 +1,61 @@
+-- History of the content each document was indexed with. A trigger records a
+-- version whenever a document gets new content, whichever path wrote it, so
+-- the indexer's upserts keep overwriting documents in place. Versions hold a
+-- reference on their content blob, keeping superseded content out of GC until
+-- the indexer's retention policy prunes the version.
+
+CREATE TABLE IF NOT EXISTS document_versions (
+    document_id CHAR(26) NOT NULL REFERENCES documents(id) ON DELETE CASCADE,
+    version INTEGER NOT NULL,
+    content_id CHAR(26) REFERENCES content_blobs(id) ON DELETE SET NULL,
+    title TEXT NOT NULL,
+    indexed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (document_id, version)
+);
+
+CREATE INDEX IF NOT EXISTS idx_document_versions_indexed_at
+    ON document_versions (indexed_at);
+
+CREATE OR REPLACE FUNCTION record_document_version() RETURNS TRIGGER AS $$
+BEGIN
+    IF NEW.content_id IS NULL
+        OR (TG_OP = 'UPDATE' AND NEW.content_id IS NOT DISTINCT FROM OLD.content_id)
+    THEN
+        RETURN NULL;
+    END IF;
+
+    INSERT INTO document_versions (document_id, version, content_id, title)
+    SELECT NEW.id, COALESCE(MAX(version), 0) + 1, NEW.content_id, NEW.title
+    FROM document_versions
+    WHERE document_id = NEW.id;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS documents_record_version ON documents;
+CREATE TRIGGER documents_record_version
+    AFTER INSERT OR UPDATE OF content_id ON documents
+    FOR EACH ROW EXECUTE FUNCTION record_document_version();
+
+-- Current content becomes version 1. Reference counts are backfilled in bulk
+-- before the per-row trigger exists.
+INSERT INTO document_versions (document_id, version, content_id, title, indexed_at)
+SELECT id, 1, content_id, title, last_indexed_at
+FROM documents
+WHERE content_id IS NOT NULL
+ON CONFLICT DO NOTHING;
+
+UPDATE content_blobs cb
+SET ref_count = cb.ref_count + refs.n, orphaned_at = NULL
+FROM (
+    SELECT content_id::TEXT AS id, COUNT(*)::INT AS n
+    FROM document_versions
+    WHERE content_id IS NOT NULL
+    GROUP BY content_id
+) refs
+WHERE cb.id = refs.id;
+
+DROP TRIGGER IF EXISTS document_versions_content_ref_count ON document_versions;
+CREATE TRIGGER document_versions_content_ref_count
+    AFTER INSERT OR DELETE OR UPDATE OF content_id ON document_versions
+    FOR EACH ROW EXECUTE FUNCTION track_content_id_ref_count();
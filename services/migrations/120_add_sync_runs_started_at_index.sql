@@ -0,0 +1,3 @@
+-- Backs the sync run history listing, which pages through runs across all
+-- sources newest first and buckets them by when they started.
+CREATE INDEX IF NOT EXISTS idx_sync_runs_started_at ON sync_runs(started_at DESC);
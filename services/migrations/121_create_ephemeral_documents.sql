@@ -0,0 +1,62 @@
+-- Files dropped into a chat, indexed for immediate search without a
+-- connector. Each user's uploads live in a hidden, inactive 'chat_upload'
+-- source so they flow through the regular document, embedding and search
+-- paths; permissions limit them to the uploading user. The indexer deletes
+-- the document once it expires or its chat is deleted.
+
+ALTER TABLE sources DROP CONSTRAINT IF EXISTS sources_source_type_check;
+ALTER TABLE sources ADD CONSTRAINT sources_source_type_check
+CHECK (source_type IN (
+  'google_drive',
+  'gmail',
+  'google_chat',
+  'confluence',
+  'jira',
+  'slack',
+  'notion',
+  'web',
+  'github',
+  'local_files',
+  'file_system',
+  'fireflies',
+  'hubspot',
+  'one_drive',
+  'share_point',
+  'outlook',
+  'outlook_calendar',
+  'imap',
+  'clickup',
+  'linear',
+  'ms_teams',
+  'paperless_ngx',
+  'nextcloud',
+  'google_ads',
+  'darwinbox',
+  'chat_upload'
+));
+
+-- One upload source per user.
+CREATE UNIQUE INDEX IF NOT EXISTS idx_sources_chat_upload_per_user
+    ON sources (created_by)
+    WHERE source_type = 'chat_upload' AND NOT is_deleted;
+
+CREATE TABLE IF NOT EXISTS ephemeral_documents (
+    document_id CHAR(26) PRIMARY KEY REFERENCES documents(id) ON DELETE CASCADE,
+    user_id CHAR(26) NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    -- Not a foreign key: deleting the chat leaves the row for the indexer's
+    -- cleanup, which then deletes the document as well.
+    chat_id CHAR(26),
+    filename TEXT NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_ephemeral_documents_user_chat
+    ON ephemeral_documents (user_id, chat_id);
+CREATE INDEX IF NOT EXISTS idx_ephemeral_documents_expires_at
+    ON ephemeral_documents (expires_at);
+
+-- Embedding work for chat uploads jumps the backlog of synced documents.
+ALTER TABLE embedding_queue ADD COLUMN IF NOT EXISTS priority SMALLINT NOT NULL DEFAULT 0;
+CREATE INDEX IF NOT EXISTS idx_embedding_queue_status_priority
+    ON embedding_queue (status, priority DESC, created_at);
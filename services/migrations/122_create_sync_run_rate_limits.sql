@@ -0,0 +1,15 @@
+-- Upstream rate limit usage reported by connectors, per sync run and API
+-- family. Reports are additive: each one carries the usage since the last.
+CREATE TABLE IF NOT EXISTS sync_run_rate_limits (
+    sync_run_id CHAR(26) NOT NULL REFERENCES sync_runs(id) ON DELETE CASCADE,
+    api_family TEXT NOT NULL,
+    requests BIGINT NOT NULL DEFAULT 0,
+    wait_ms_total BIGINT NOT NULL DEFAULT 0,
+    wait_ms_max BIGINT NOT NULL DEFAULT 0,
+    backoff_ms_total BIGINT NOT NULL DEFAULT 0,
+    throttled BIGINT NOT NULL DEFAULT 0,
+    retries BIGINT NOT NULL DEFAULT 0,
+    current_rps INTEGER NOT NULL DEFAULT 0,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (sync_run_id, api_family)
+);
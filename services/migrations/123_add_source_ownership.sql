@@ -0,0 +1,18 @@
+-- Sources were bound to `created_by`: once that user is deactivated, lookups
+-- of the source's owner email fail and syncs break. Sources can now have
+-- co-owners and a designated service principal, whose email connectors use
+-- in place of the creator's. Ownership itself is transferred by updating
+-- `created_by`.
+
+ALTER TABLE sources
+    ADD COLUMN IF NOT EXISTS service_principal_id CHAR(26) REFERENCES users(id) ON DELETE SET NULL;
+
+CREATE TABLE IF NOT EXISTS source_co_owners (
+    source_id CHAR(26) NOT NULL REFERENCES sources(id) ON DELETE CASCADE,
+    user_id CHAR(26) NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    added_by CHAR(26) REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (source_id, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_source_co_owners_user_id ON source_co_owners(user_id);
@@ -0,0 +1,49 @@
+-- Chunking experiments that embed into a shadow namespace.
+--
+-- An experiment re-embeds documents with alternative chunking settings into
+-- embedding rows flagged with the experiment's name, alongside the production
+-- rows (namespace IS NULL). Searches can target a namespace to compare results
+-- side by side. Promoting an experiment swaps its rows in as production and
+-- makes its settings the production chunking config.
+
+CREATE TABLE IF NOT EXISTS embedding_experiments (
+    name TEXT PRIMARY KEY,
+    chunk_size INTEGER NOT NULL,
+    -- sentence | fixed
+    chunking_mode TEXT NOT NULL,
+    -- running | promoted
+    status TEXT NOT NULL DEFAULT 'running',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    promoted_at TIMESTAMPTZ,
+    CONSTRAINT embedding_experiments_name_check
+        CHECK (name ~ '^[a-z0-9][a-z0-9_-]{0,62}$'),
+    CONSTRAINT embedding_experiments_chunk_size_check
+        CHECK (chunk_size BETWEEN 16 AND 8192),
+    CONSTRAINT embedding_experiments_chunking_mode_check
+        CHECK (chunking_mode IN ('sentence', 'fixed')),
+    CONSTRAINT embedding_experiments_status_check
+        CHECK (status IN ('running', 'promoted'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_embedding_experiments_promoted
+    ON embedding_experiments (promoted_at DESC)
+    WHERE status = 'promoted';
+
+-- Deleting an experiment drops its shadow rows and pending work
+ALTER TABLE embeddings ADD COLUMN IF NOT EXISTS namespace TEXT
+    REFERENCES embedding_experiments(name) ON DELETE CASCADE;
+
+ALTER TABLE embeddings DROP CONSTRAINT IF EXISTS embeddings_document_id_chunk_index_model_name_key;
+ALTER TABLE embeddings ADD CONSTRAINT embeddings_document_chunk_model_namespace_key
+    UNIQUE NULLS NOT DISTINCT (document_id, chunk_index, model_name, namespace);
+
+CREATE INDEX IF NOT EXISTS idx_embeddings_namespace
+    ON embeddings (namespace, document_id)
+    WHERE namespace IS NOT NULL;
+
+ALTER TABLE embedding_queue ADD COLUMN IF NOT EXISTS namespace TEXT
+    REFERENCES embedding_experiments(name) ON DELETE CASCADE;
+
+CREATE INDEX IF NOT EXISTS idx_embedding_queue_namespace
+    ON embedding_queue (namespace, status)
+    WHERE namespace IS NOT NULL;
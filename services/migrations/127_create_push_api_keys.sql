@@ -0,0 +1,50 @@
+-- Push sources: external systems POST documents and deletions directly to the
+-- push connector, authenticating with per-source API keys.
+ALTER TABLE sources DROP CONSTRAINT IF EXISTS sources_source_type_check;
+ALTER TABLE sources ADD CONSTRAINT sources_source_type_check
+CHECK (source_type IN (
+  'google_drive',
+  'gmail',
+  'google_chat',
+  'confluence',
+  'jira',
+  'slack',
+  'notion',
+  'web',
+  'github',
+  'local_files',
+  'file_system',
+  'fireflies',
+  'hubspot',
+  'one_drive',
+  'share_point',
+  'outlook',
+  'outlook_calendar',
+  'imap',
+  'clickup',
+  'linear',
+  'ms_teams',
+  'paperless_ngx',
+  'nextcloud',
+  'google_ads',
+  'darwinbox',
+  'chat_upload',
+  'zendesk',
+  's3',
+  'push'
+));
+
+CREATE TABLE IF NOT EXISTS push_api_keys (
+    id CHAR(26) PRIMARY KEY,
+    source_id TEXT NOT NULL REFERENCES sources(id) ON DELETE CASCADE,
+    key_hash CHAR(64) NOT NULL,
+    key_prefix VARCHAR(16) NOT NULL,
+    name TEXT NOT NULL,
+    created_by TEXT REFERENCES users(id) ON DELETE SET NULL,
+    last_used_at TIMESTAMPTZ,
+    revoked_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_push_api_keys_hash ON push_api_keys(key_hash);
+CREATE INDEX IF NOT EXISTS idx_push_api_keys_source_id ON push_api_keys(source_id);
@@ -0,0 +1,11 @@
+-- Versioned key/value state connectors keep between syncs of a source (change
+-- cursors, per-item ETags, ...). `version` increases on every write so
+-- concurrent writers can compare-and-swap instead of overwriting each other.
+CREATE TABLE IF NOT EXISTS source_sync_state (
+    source_id TEXT NOT NULL REFERENCES sources(id) ON DELETE CASCADE,
+    key TEXT NOT NULL,
+    value JSONB NOT NULL,
+    version BIGINT NOT NULL DEFAULT 1,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (source_id, key)
+);
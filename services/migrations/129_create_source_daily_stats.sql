@@ -0,0 +1,17 @@
+-- Daily snapshot of each source's footprint for the source stats endpoint.
+-- The connector-manager scheduler rewrites the current day's row
+-- periodically, so each day keeps its last snapshot and the history shows
+-- how a source grew over time.
+CREATE TABLE IF NOT EXISTS source_daily_stats (
+    source_id CHAR(26) NOT NULL REFERENCES sources(id) ON DELETE CASCADE,
+    -- Midnight UTC of the day the snapshot belongs to
+    day TIMESTAMPTZ NOT NULL,
+    document_count BIGINT NOT NULL,
+    -- Size of the stored content blobs of the source's documents
+    content_bytes BIGINT NOT NULL,
+    embedded_document_count BIGINT NOT NULL,
+    chunk_count BIGINT NOT NULL,
+    avg_document_age_seconds DOUBLE PRECISION,
+    refreshed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (source_id, day)
+);
@@ -0,0 +1,25 @@
+-- One embedding per document of its title and opening text, used as a coarse
+-- first stage that narrows chunk-level semantic search to likely documents.
+CREATE TABLE IF NOT EXISTS document_summary_embeddings (
+    document_id CHAR(26) PRIMARY KEY REFERENCES documents(id) ON DELETE CASCADE,
+    embedding vector NOT NULL,
+    model_name TEXT NOT NULL,
+    dimensions SMALLINT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_document_summary_embeddings_vector_1024 ON document_summary_embeddings
+    USING hnsw ((embedding::vector(1024)) vector_cosine_ops)
+    WITH (m = 32, ef_construction = 200)
+    WHERE dimensions = 1024;
+
+CREATE INDEX IF NOT EXISTS idx_document_summary_embeddings_vector_768 ON document_summary_embeddings
+    USING hnsw ((embedding::vector(768)) vector_cosine_ops)
+    WITH (m = 32, ef_construction = 200)
+    WHERE dimensions = 768;
+
+CREATE INDEX IF NOT EXISTS idx_document_summary_embeddings_vector_512 ON document_summary_embeddings
+    USING hnsw ((embedding::vector(512)) vector_cosine_ops)
+    WITH (m = 32, ef_construction = 200)
+    WHERE dimensions = 512;
@@ -0,0 +1,5 @@
+-- Progress a connector reports with its heartbeats: what it is doing right
+-- now and how many documents it expects to scan, for live progress and ETAs.
+ALTER TABLE sync_runs
+    ADD COLUMN IF NOT EXISTS phase TEXT,
+    ADD COLUMN IF NOT EXISTS documents_total INTEGER;
@@ -0,0 +1,3 @@
+-- 1-based page a chunk starts on, for documents with pages (PDFs). Lets
+-- search hits and RAG citations point at the page.
+ALTER TABLE embeddings ADD COLUMN IF NOT EXISTS page_number INTEGER;
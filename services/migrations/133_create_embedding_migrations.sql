@@ -0,0 +1,40 @@
+-- Managed re-embedding of the corpus with a new embedding provider.
+--
+-- A migration queues every document for embedding with the target provider
+-- while the current provider keeps serving searches. Production work queued
+-- during the migration is also queued for the target, so both models stay
+-- complete. Once the migration's queue drains, the target provider becomes
+-- current and the old model's vectors are deleted.
+
+CREATE TABLE IF NOT EXISTS embedding_migrations (
+    id CHAR(26) PRIMARY KEY,
+    to_provider_id CHAR(26) NOT NULL REFERENCES embedding_providers(id),
+    from_model TEXT NOT NULL,
+    to_model TEXT NOT NULL,
+    -- running | completed | cancelled | failed
+    status TEXT NOT NULL DEFAULT 'running',
+    total_documents INTEGER NOT NULL DEFAULT 0,
+    error_message TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMPTZ,
+    CONSTRAINT embedding_migrations_status_check
+        CHECK (status IN ('running', 'completed', 'cancelled', 'failed')),
+    CONSTRAINT embedding_migrations_models_check
+        CHECK (from_model <> to_model)
+);
+
+-- Only one migration may run at a time
+CREATE UNIQUE INDEX IF NOT EXISTS idx_embedding_migrations_running
+    ON embedding_migrations ((TRUE))
+    WHERE status = 'running';
+
+CREATE INDEX IF NOT EXISTS idx_embedding_migrations_created_at
+    ON embedding_migrations (created_at DESC);
+
+-- Queue items that embed for a migration rather than with the current provider
+ALTER TABLE embedding_queue ADD COLUMN IF NOT EXISTS migration_id CHAR(26)
+    REFERENCES embedding_migrations(id) ON DELETE CASCADE;
+
+CREATE INDEX IF NOT EXISTS idx_embedding_queue_migration
+    ON embedding_queue (migration_id, status)
+    WHERE migration_id IS NOT NULL;
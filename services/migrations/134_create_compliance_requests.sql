@@ -0,0 +1,74 @@
+-- Data subject requests: access (export everything held about a person) and
+-- erasure (delete or redact it).
+--
+-- A request matches documents the subject authored or that mention them, via
+-- the people directory and the identifiers given with the request. Each
+-- request and every document it touched is kept as the audit record; the
+-- documents table is not referenced because erasure deletes rows from it.
+-- Access exports are written to object storage in parts, like source exports.
+
+CREATE TABLE IF NOT EXISTS compliance_requests (
+    id CHAR(26) PRIMARY KEY,
+    -- access | erasure
+    kind TEXT NOT NULL,
+    -- delete | redact; set for erasure requests only
+    erasure_action TEXT,
+    subject_email TEXT NOT NULL,
+    -- Strings documents were matched on: the email, plus the directory
+    -- display name and any aliases given with the request
+    identifiers TEXT[] NOT NULL DEFAULT '{}',
+    requested_by CHAR(26) REFERENCES users(id) ON DELETE SET NULL,
+    -- pending | running | completed | failed | expired
+    status TEXT NOT NULL DEFAULT 'pending',
+    document_count INT NOT NULL DEFAULT 0,
+    size_bytes BIGINT NOT NULL DEFAULT 0,
+    -- Content ids of the access export parts, in order
+    part_content_ids TEXT[] NOT NULL DEFAULT '{}',
+    download_token TEXT,
+    expires_at TIMESTAMPTZ,
+    error_message TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    started_at TIMESTAMPTZ,
+    heartbeat_at TIMESTAMPTZ,
+    completed_at TIMESTAMPTZ,
+    CONSTRAINT compliance_requests_kind_check
+        CHECK (kind IN ('access', 'erasure')),
+    CONSTRAINT compliance_requests_erasure_action_check
+        CHECK (
+            (kind = 'erasure' AND erasure_action IN ('delete', 'redact'))
+            OR (kind = 'access' AND erasure_action IS NULL)
+        ),
+    CONSTRAINT compliance_requests_status_check
+        CHECK (status IN ('pending', 'running', 'completed', 'failed', 'expired'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_compliance_requests_created_at
+    ON compliance_requests (created_at DESC);
+
+CREATE INDEX IF NOT EXISTS idx_compliance_requests_subject
+    ON compliance_requests (lower(subject_email));
+
+CREATE INDEX IF NOT EXISTS idx_compliance_requests_active_status
+    ON compliance_requests (status, created_at)
+    WHERE status IN ('pending', 'running', 'completed');
+
+DROP TRIGGER IF EXISTS compliance_requests_parts_ref_count ON compliance_requests;
+CREATE TRIGGER compliance_requests_parts_ref_count
+    AFTER INSERT OR DELETE OR UPDATE OF part_content_ids ON compliance_requests
+    FOR EACH ROW EXECUTE FUNCTION track_export_parts_ref_count();
+
+-- Every document a request exported, deleted or redacted
+CREATE TABLE IF NOT EXISTS compliance_request_documents (
+    request_id CHAR(26) NOT NULL REFERENCES compliance_requests(id) ON DELETE CASCADE,
+    document_id CHAR(26) NOT NULL,
+    source_id CHAR(26) NOT NULL,
+    external_id TEXT NOT NULL,
+    -- author | title | metadata | attributes | content
+    match_reasons TEXT[] NOT NULL,
+    -- exported | deleted | redacted
+    action TEXT NOT NULL,
+    processed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (request_id, document_id),
+    CONSTRAINT compliance_request_documents_action_check
+        CHECK (action IN ('exported', 'deleted', 'redacted'))
+);
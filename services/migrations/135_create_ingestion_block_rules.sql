@@ -0,0 +1,55 @@
+-- Admin-configured patterns for material that must never be indexed.
+--
+-- The indexer checks every created or updated document against the enabled
+-- rules before writing it. A matching document is not indexed (and an already
+-- indexed copy is removed); the event is recorded in
+-- ingestion_quarantined_events instead so admins can see what was held back.
+
+CREATE TABLE IF NOT EXISTS ingestion_block_rules (
+    id CHAR(26) PRIMARY KEY,
+    name TEXT NOT NULL,
+    -- folder: the document path is the pattern or lies beneath it
+    -- file_name: glob (* and ?) on the file name, case-insensitive
+    -- content: regular expression on the extracted text
+    kind TEXT NOT NULL,
+    pattern TEXT NOT NULL,
+    -- Applies to every source when NULL
+    source_id CHAR(26) REFERENCES sources(id) ON DELETE CASCADE,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    match_count BIGINT NOT NULL DEFAULT 0,
+    last_matched_at TIMESTAMPTZ,
+    created_by CHAR(26) REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT ingestion_block_rules_kind_check
+        CHECK (kind IN ('folder', 'file_name', 'content'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_ingestion_block_rules_enabled
+    ON ingestion_block_rules (created_at)
+    WHERE enabled = TRUE;
+
+CREATE TABLE IF NOT EXISTS ingestion_quarantined_events (
+    id CHAR(26) PRIMARY KEY,
+    -- Kept when the rule is deleted so the history stays readable
+    rule_id CHAR(26) REFERENCES ingestion_block_rules(id) ON DELETE SET NULL,
+    rule_name TEXT NOT NULL,
+    source_id CHAR(26) NOT NULL REFERENCES sources(id) ON DELETE CASCADE,
+    external_id TEXT NOT NULL,
+    title TEXT NOT NULL,
+    url TEXT,
+    path TEXT,
+    -- The folder, file name or content excerpt that matched
+    matched_value TEXT NOT NULL,
+    sync_run_id CHAR(26),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_ingestion_quarantined_events_created_at
+    ON ingestion_quarantined_events (created_at DESC);
+
+CREATE INDEX IF NOT EXISTS idx_ingestion_quarantined_events_rule
+    ON ingestion_quarantined_events (rule_id, created_at DESC);
+
+CREATE INDEX IF NOT EXISTS idx_ingestion_quarantined_events_source
+    ON ingestion_quarantined_events (source_id, created_at DESC);
@@ -0,0 +1,4 @@
+-- Markdown headings enclosing the start of a chunk, outermost first and
+-- joined with " > " (e.g. "Runbook > Incident Response > Paging"). Lets search
+-- snippets and RAG citations name the section a chunk came from.
+ALTER TABLE embeddings ADD COLUMN IF NOT EXISTS heading_path TEXT;
@@ -0,0 +1,61 @@
+-- Per-language stemmed aliases for the document BM25 index.
+--
+-- Migration 077 stems every document with the English stemmer only, so a
+-- German query such as "Quartalsberichte" cannot match "Quartalsbericht".
+-- The indexer now records each document's detected language (ISO 639-3) in
+-- metadata.language. Each supported language gets title and content aliases
+-- that only index documents in that language, with its Snowball stemmer; the
+-- searcher adds clauses on the aliases of the query's detected language.
+--
+-- Only documents in a language are indexed under its aliases, so the index
+-- grows by about one stemmed copy per document rather than one per language.
+-- The unstemmed and English paths are unchanged and always searched.
+
+DROP INDEX IF EXISTS document_search_idx;
+
+-- Documents detected before this migration carry their language in the
+-- document_languages side table only. Backfilled while the index is dropped
+-- so the rows are indexed once.
+UPDATE documents d
+SET metadata = jsonb_set(d.metadata, '{language}', to_jsonb(dl.language))
+FROM document_languages dl
+WHERE dl.document_id = d.id
+  AND d.metadata->>'language' IS DISTINCT FROM dl.language;
+
+CREATE INDEX document_search_idx ON documents
+USING bm25 (
+    id,
+    (source_id::pdb.literal),
+    (external_id::pdb.literal),
+    (title::pdb.simple('ascii_folding=true')),
+    (title::pdb.source_code('alias=title_secondary', 'ascii_folding=true')),
+    (title::pdb.simple('alias=title_en', 'stemmer=english', 'ascii_folding=true')),
+    (content::pdb.icu('ascii_folding=true')),
+    (content::pdb.icu('alias=content_en', 'stemmer=english', 'ascii_folding=true')),
+    ((CASE WHEN metadata->>'language' = 'deu' THEN title END)::pdb.simple('alias=title_de', 'stemmer=german', 'ascii_folding=true')),
+    ((CASE WHEN metadata->>'language' = 'deu' THEN content END)::pdb.icu('alias=content_de', 'stemmer=german', 'ascii_folding=true')),
+    ((CASE WHEN metadata->>'language' = 'fra' THEN title END)::pdb.simple('alias=title_fr', 'stemmer=french', 'ascii_folding=true')),
+    ((CASE WHEN metadata->>'language' = 'fra' THEN content END)::pdb.icu('alias=content_fr', 'stemmer=french', 'ascii_folding=true')),
+    ((CASE WHEN metadata->>'language' = 'spa' THEN title END)::pdb.simple('alias=title_es', 'stemmer=spanish', 'ascii_folding=true')),
+    ((CASE WHEN metadata->>'language' = 'spa' THEN content END)::pdb.icu('alias=content_es', 'stemmer=spanish', 'ascii_folding=true')),
+    ((CASE WHEN metadata->>'language' = 'ita' THEN title END)::pdb.simple('alias=title_it', 'stemmer=italian', 'ascii_folding=true')),
+    ((CASE WHEN metadata->>'language' = 'ita' THEN content END)::pdb.icu('alias=content_it', 'stemmer=italian', 'ascii_folding=true')),
+    ((CASE WHEN metadata->>'language' = 'por' THEN title END)::pdb.simple('alias=title_pt', 'stemmer=portuguese', 'ascii_folding=true')),
+    ((CASE WHEN metadata->>'language' = 'por' THEN content END)::pdb.icu('alias=content_pt', 'stemmer=portuguese', 'ascii_folding=true')),
+    ((CASE WHEN metadata->>'language' = 'nld' THEN title END)::pdb.simple('alias=title_nl', 'stemmer=dutch', 'ascii_folding=true')),
+    ((CASE WHEN metadata->>'language' = 'nld' THEN content END)::pdb.icu('alias=content_nl', 'stemmer=dutch', 'ascii_folding=true')),
+    (content_type::pdb.literal),
+    file_size,
+    (file_extension::pdb.literal),
+    metadata,
+    (permissions::pdb.literal),
+    attributes,
+    created_at,
+    updated_at
+)
+WITH (
+    key_field = id,
+    background_layer_sizes = '100KB, 1MB, 10MB, 100MB, 1GB, 10GB',
+    target_segment_count = 2,
+    mutable_segment_rows = 100
+);
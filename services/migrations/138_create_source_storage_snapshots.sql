@@ -0,0 +1,26 @@
+-- Daily snapshot of what each source stores, for the indexer's storage
+-- report. The indexer rewrites the current day's row when the storage
+-- analysis runs, so each day keeps its last snapshot and growth can be
+-- projected from the history.
+CREATE TABLE IF NOT EXISTS source_storage_snapshots (
+    source_id CHAR(26) NOT NULL REFERENCES sources(id) ON DELETE CASCADE,
+    -- Midnight UTC of the day the snapshot belongs to
+    day TIMESTAMPTZ NOT NULL,
+    document_count BIGINT NOT NULL,
+    -- On-disk size of the source's document rows, including inline content
+    document_bytes BIGINT NOT NULL,
+    -- Size of the content blobs of the source's current documents
+    content_bytes BIGINT NOT NULL,
+    embedding_count BIGINT NOT NULL,
+    embedding_bytes BIGINT NOT NULL,
+    -- Superseded document versions and the content blobs they keep alive
+    version_count BIGINT NOT NULL,
+    version_bytes BIGINT NOT NULL,
+    -- Part of version_bytes the version retention policy would prune
+    prunable_version_bytes BIGINT NOT NULL,
+    refreshed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (source_id, day)
+);
+
+CREATE INDEX IF NOT EXISTS idx_source_storage_snapshots_day
+    ON source_storage_snapshots (day);
@@ -0,0 +1,44 @@
+-- Expiring links that let people without Omni access read one document.
+-- Only the SHA-256 of a link's token is stored; the token is returned once,
+-- when the link is created.
+CREATE TABLE IF NOT EXISTS document_share_links (
+    id CHAR(26) PRIMARY KEY,
+    document_id VARCHAR(26) NOT NULL REFERENCES documents(id) ON DELETE CASCADE,
+    created_by CHAR(26) NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    token_hash TEXT NOT NULL UNIQUE,
+    -- Start of the token, kept for display
+    token_prefix TEXT NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    -- Successful fetches allowed; NULL for no limit
+    max_accesses INTEGER CHECK (max_accesses > 0),
+    access_count BIGINT NOT NULL DEFAULT 0,
+    last_accessed_at TIMESTAMPTZ,
+    revoked_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_document_share_links_document_id
+    ON document_share_links (document_id);
+
+CREATE INDEX IF NOT EXISTS idx_document_share_links_created_by
+    ON document_share_links (created_by);
+
+-- Deployment-wide rules for share links. Always exactly one row; sharing is
+-- off until an admin enables it.
+CREATE TABLE IF NOT EXISTS share_link_policy (
+    id BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+    enabled BOOLEAN NOT NULL DEFAULT FALSE,
+    default_ttl_hours INTEGER NOT NULL DEFAULT 72,
+    max_ttl_hours INTEGER NOT NULL DEFAULT 720,
+    -- Upper bound on a link's max_accesses; NULL for no limit
+    max_accesses INTEGER CHECK (max_accesses > 0),
+    -- Fetches per minute a single link serves
+    rate_limit_per_minute INTEGER NOT NULL DEFAULT 30,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT share_link_policy_ttls CHECK (
+        default_ttl_hours > 0 AND max_ttl_hours >= default_ttl_hours
+    ),
+    CONSTRAINT share_link_policy_rate_limit CHECK (rate_limit_per_minute > 0)
+);
+
+INSERT INTO share_link_policy (id) VALUES (TRUE) ON CONFLICT (id) DO NOTHING;
@@ -0,0 +1,42 @@
+-- How long a document's latest change took to become searchable: from the
+-- connector enqueuing the event to its embeddings completing. The indexer
+-- writes a row when it indexes the document; documents with embeddings still
+-- queued stay pending (searchable_at NULL) until the trigger below sees the
+-- last of them complete. Re-embedding for an embedding migration or experiment
+-- does not count.
+CREATE TABLE IF NOT EXISTS document_freshness (
+    document_id VARCHAR(26) PRIMARY KEY REFERENCES documents(id) ON DELETE CASCADE,
+    source_id CHAR(26) NOT NULL REFERENCES sources(id) ON DELETE CASCADE,
+    -- When the earliest not-yet-searchable change was enqueued
+    enqueued_at TIMESTAMPTZ NOT NULL,
+    indexed_at TIMESTAMPTZ NOT NULL,
+    searchable_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_document_freshness_source_enqueued
+    ON document_freshness (source_id, enqueued_at);
+
+CREATE OR REPLACE FUNCTION record_document_searchable() RETURNS trigger AS $$
+BEGIN
+    UPDATE document_freshness
+    SET searchable_at = COALESCE(NEW.processed_at, NOW())
+    WHERE document_id = NEW.document_id
+      AND searchable_at IS NULL
+      AND NOT EXISTS (
+          SELECT 1 FROM embedding_queue q
+          WHERE q.document_id = NEW.document_id
+            AND q.id <> NEW.id
+            AND q.namespace IS NULL
+            AND q.migration_id IS NULL
+            AND q.status IN ('pending', 'processing', 'failed')
+      );
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER embedding_queue_record_searchable
+    AFTER UPDATE OF status ON embedding_queue
+    FOR EACH ROW
+    WHEN (NEW.status = 'completed' AND OLD.status IS DISTINCT FROM 'completed'
+          AND NEW.namespace IS NULL AND NEW.migration_id IS NULL)
+    EXECUTE FUNCTION record_document_searchable();
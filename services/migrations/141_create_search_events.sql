@@ -0,0 +1,26 @@
+-- One row per search served: what was asked, how many results it found and
+-- how long it took. Clicks reference the search they came from, so queries
+-- can be judged by whether people opened anything.
+
+CREATE TABLE IF NOT EXISTS search_events (
+    id CHAR(26) PRIMARY KEY,
+    user_id CHAR(26) REFERENCES users(id) ON DELETE SET NULL,
+    -- Query as typed by the user, operators included
+    query TEXT NOT NULL,
+    -- Lowercased with whitespace collapsed, for grouping
+    normalized_query TEXT NOT NULL,
+    search_mode VARCHAR(20) NOT NULL,
+    -- Issued by the assistant rather than typed by a user
+    is_generated BOOLEAN NOT NULL DEFAULT FALSE,
+    result_count BIGINT NOT NULL,
+    latency_ms INTEGER NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_search_events_created_at ON search_events(created_at);
+
+ALTER TABLE search_clicks
+    ADD COLUMN IF NOT EXISTS search_event_id CHAR(26) REFERENCES search_events(id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS idx_search_clicks_search_event_id
+    ON search_clicks(search_event_id) WHERE search_event_id IS NOT NULL;
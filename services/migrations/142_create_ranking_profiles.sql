@@ -0,0 +1,22 @@
+-- Ranking features of the results each search showed, in the order shown, so
+-- clicks can be turned into training preferences: [{document_id, features}].
+ALTER TABLE search_events ADD COLUMN IF NOT EXISTS impressions JSONB;
+
+-- Feature weights learned from clicks. The searcher fuses hybrid results with
+-- the active profile, if any, instead of the static retriever weights.
+CREATE TABLE IF NOT EXISTS ranking_profiles (
+    id CHAR(26) PRIMARY KEY,
+    weights JSONB NOT NULL,
+    -- Searches with clicks the profile was trained on
+    training_searches INTEGER NOT NULL,
+    training_pairs INTEGER NOT NULL,
+    -- Fraction of held-out preferences ordered correctly, by the baseline
+    -- weights and by this profile
+    baseline_accuracy DOUBLE PRECISION,
+    accuracy DOUBLE PRECISION,
+    is_active BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_ranking_profiles_active
+    ON ranking_profiles(is_active) WHERE is_active;
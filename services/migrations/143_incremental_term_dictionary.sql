@@ -0,0 +1,46 @@
+-- Incremental term dictionary refresh. Each document's dictionary terms are
+-- kept so a refresh only re-tokenizes documents indexed since the last run,
+-- adjusting the corpus-wide counts by the difference; `corpus_terms` is then
+-- rebuilt from the counts.
+
+CREATE TABLE IF NOT EXISTS document_lexemes (
+    -- No foreign key: rows of deleted documents stay until a refresh has
+    -- subtracted their terms from the counts
+    document_id VARCHAR(26) PRIMARY KEY,
+    -- Terms included in corpus_term_counts
+    terms TEXT[] NOT NULL,
+    -- Terms from the latest tokenization, not yet applied to the counts
+    pending_terms TEXT[],
+    refreshed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_document_lexemes_pending
+    ON document_lexemes(document_id) WHERE pending_terms IS NOT NULL;
+
+-- Documents containing each term, without the dictionary's minimum count or
+-- size limit
+CREATE TABLE IF NOT EXISTS corpus_term_counts (
+    term TEXT PRIMARY KEY,
+    document_count BIGINT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS term_dictionary_runs (
+    id BIGSERIAL PRIMARY KEY,
+    -- 'incremental' or 'full'
+    mode VARCHAR(20) NOT NULL,
+    -- 'running', 'completed' or 'failed'
+    status VARCHAR(20) NOT NULL DEFAULT 'running',
+    -- Documents indexed since this time were refreshed; NULL for all documents
+    changed_since TIMESTAMPTZ,
+    documents_refreshed BIGINT NOT NULL DEFAULT 0,
+    documents_removed BIGINT NOT NULL DEFAULT 0,
+    terms BIGINT NOT NULL DEFAULT 0,
+    error TEXT,
+    started_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    finished_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_term_dictionary_runs_completed
+    ON term_dictionary_runs(started_at) WHERE status = 'completed';
+
+CREATE INDEX IF NOT EXISTS idx_documents_last_indexed_at ON documents(last_indexed_at);
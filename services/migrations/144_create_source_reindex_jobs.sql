@@ -0,0 +1,30 @@
+-- Forced reindexing of a single source from content storage.
+--
+-- A job clears the embeddings of the source's documents and runs each
+-- document through the indexing pipeline again with its stored content, which
+-- re-queues it for embedding. The job completes once that embedding work has
+-- drained.
+
+CREATE TABLE IF NOT EXISTS source_reindex_jobs (
+    id CHAR(26) PRIMARY KEY,
+    source_id CHAR(26) NOT NULL REFERENCES sources(id) ON DELETE CASCADE,
+    -- reindexing | embedding | completed | failed
+    status TEXT NOT NULL DEFAULT 'reindexing',
+    -- Documents with content when the job started
+    total_documents INTEGER NOT NULL DEFAULT 0,
+    reindexed_documents INTEGER NOT NULL DEFAULT 0,
+    embeddings_cleared BIGINT NOT NULL DEFAULT 0,
+    error_message TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMPTZ,
+    CONSTRAINT source_reindex_jobs_status_check
+        CHECK (status IN ('reindexing', 'embedding', 'completed', 'failed'))
+);
+
+-- Only one reindex per source may be in flight
+CREATE UNIQUE INDEX IF NOT EXISTS idx_source_reindex_jobs_active
+    ON source_reindex_jobs (source_id)
+    WHERE status IN ('reindexing', 'embedding');
+
+CREATE INDEX IF NOT EXISTS idx_source_reindex_jobs_source_created_at
+    ON source_reindex_jobs (source_id, created_at DESC);
@@ -0,0 +1,18 @@
+-- Structured errors reported by connectors, including the reasons they failed
+-- syncs with. The most recent error of each source is shown in its health.
+CREATE TABLE IF NOT EXISTS connector_errors (
+    id CHAR(26) PRIMARY KEY,
+    source_id CHAR(26) NOT NULL REFERENCES sources(id) ON DELETE CASCADE,
+    sync_run_id CHAR(26) REFERENCES sync_runs(id) ON DELETE SET NULL,
+    -- authentication | permission | configuration | rate_limit | network | upstream | unknown
+    category TEXT NOT NULL,
+    message TEXT NOT NULL,
+    details JSONB,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT connector_errors_category_check
+        CHECK (category IN ('authentication', 'permission', 'configuration', 'rate_limit',
+                            'network', 'upstream', 'unknown'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_connector_errors_source_created_at
+    ON connector_errors (source_id, created_at DESC);
@@ -0,0 +1,9 @@
+-- When an OAuth token could not be refreshed and the credential has to be
+-- re-authorized (revoked grant, deleted client). Stays set until new tokens are
+-- written to the row.
+ALTER TABLE service_credentials ADD COLUMN IF NOT EXISTS refresh_failed_at TIMESTAMPTZ;
+ALTER TABLE service_credentials ADD COLUMN IF NOT EXISTS refresh_error TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_service_credentials_oauth_expires_at
+    ON service_credentials (expires_at)
+    WHERE auth_type = 'oauth' AND expires_at IS NOT NULL;
@@ -0,0 +1,47 @@
+-- Workspaces isolate the users, sources and indexed content of separate
+-- tenants sharing one deployment. Documents, embeddings and groups belong to
+-- the workspace of their source; users only see documents of their own
+-- workspace.
+--
+-- Existing installations keep working as a single workspace: every user and
+-- source is assigned to the default workspace created here.
+
+CREATE TABLE IF NOT EXISTS workspaces (
+    id CHAR(26) PRIMARY KEY,
+    name VARCHAR(255) NOT NULL,
+    slug VARCHAR(100) NOT NULL UNIQUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+INSERT INTO workspaces (id, name, slug)
+VALUES ('00000000000000000000000000', 'Default', 'default')
+ON CONFLICT (id) DO NOTHING;
+
+ALTER TABLE users ADD COLUMN IF NOT EXISTS workspace_id CHAR(26)
+    NOT NULL DEFAULT '00000000000000000000000000' REFERENCES workspaces(id);
+ALTER TABLE sources ADD COLUMN IF NOT EXISTS workspace_id CHAR(26)
+    NOT NULL DEFAULT '00000000000000000000000000' REFERENCES workspaces(id);
+
+CREATE INDEX IF NOT EXISTS idx_users_workspace_id ON users(workspace_id);
+CREATE INDEX IF NOT EXISTS idx_sources_workspace_id ON sources(workspace_id);
+
+-- Sources inserted without a workspace, which then get the default, are
+-- created in their creator's workspace
+CREATE OR REPLACE FUNCTION assign_source_workspace() RETURNS trigger AS $$
+BEGIN
+    IF NEW.workspace_id IS NULL OR NEW.workspace_id = '00000000000000000000000000' THEN
+        NEW.workspace_id := COALESCE(
+            (SELECT workspace_id FROM users WHERE id = NEW.created_by),
+            '00000000000000000000000000'
+        );
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS sources_assign_workspace ON sources;
+CREATE TRIGGER sources_assign_workspace
+    BEFORE INSERT ON sources
+    FOR EACH ROW
+    EXECUTE FUNCTION assign_source_workspace();
@@ -0,0 +1,75 @@
+-- Row-level security for document reads made on behalf of a user.
+--
+-- Permission filtering normally happens in the SQL the services generate. As a
+-- second line of defense, the searcher can run a user's queries as the
+-- `omni_document_reader` role (SET LOCAL ROLE inside a transaction), with the
+-- user's identity in the `omni.user_email` and `omni.user_groups` settings.
+-- Postgres then hides every document the user may not see, whatever the query.
+--
+-- Other connections keep their own role, to which the policy doesn't apply, so
+-- services that read documents for no particular user (indexer, connector
+-- manager) are unaffected.
+
+-- Whether a document with `permissions` from `source_id` is visible to the user
+-- in `omni.user_email`. Mirrors `generate_permission_filter`: public documents,
+-- the user's email, their domain or one of their groups (`omni.user_groups`, a
+-- JSON array that includes the domain), within the user's workspace.
+CREATE OR REPLACE FUNCTION omni_document_visible(permissions JSONB, doc_source_id TEXT)
+RETURNS BOOLEAN AS $$
+    SELECT NULLIF(current_setting('omni.user_email', true), '') IS NOT NULL
+       AND (
+           COALESCE((permissions->>'public')::boolean, false)
+           OR COALESCE(permissions->'users', '[]'::jsonb) ? current_setting('omni.user_email', true)
+           OR COALESCE(permissions->'groups', '[]'::jsonb) ?| ARRAY(
+               SELECT jsonb_array_elements_text(
+                   COALESCE(NULLIF(current_setting('omni.user_groups', true), ''), '[]')::jsonb
+               )
+           )
+       )
+       AND EXISTS (
+           SELECT 1 FROM sources s
+           WHERE s.id = doc_source_id
+             AND s.workspace_id = COALESCE(
+                 (SELECT u.workspace_id FROM users u
+                  WHERE lower(u.email) = lower(current_setting('omni.user_email', true))
+                  LIMIT 1),
+                 '00000000000000000000000000'
+             )
+       )
+$$ LANGUAGE sql STABLE;
+
+-- Creating the role needs CREATEROLE. Deployments whose migration user lacks it
+-- can create the role and grants themselves; the searcher refuses to start
+-- with row-level security enabled while its user can't assume the role.
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_roles WHERE rolname = 'omni_document_reader') THEN
+        CREATE ROLE omni_document_reader NOLOGIN;
+    END IF;
+    EXECUTE format('GRANT omni_document_reader TO %I', current_user);
+    GRANT USAGE ON SCHEMA public TO omni_document_reader;
+    GRANT SELECT ON ALL TABLES IN SCHEMA public TO omni_document_reader;
+    ALTER DEFAULT PRIVILEGES IN SCHEMA public GRANT SELECT ON TABLES TO omni_document_reader;
+    IF EXISTS (SELECT 1 FROM pg_namespace WHERE nspname = 'pdb') THEN
+        GRANT USAGE ON SCHEMA pdb TO omni_document_reader;
+    END IF;
+    IF EXISTS (SELECT 1 FROM pg_namespace WHERE nspname = 'paradedb') THEN
+        GRANT USAGE ON SCHEMA paradedb TO omni_document_reader;
+    END IF;
+EXCEPTION
+    WHEN insufficient_privilege THEN
+        RAISE NOTICE 'Could not set up the omni_document_reader role: %', SQLERRM;
+END;
+$$;
+
+-- The table owner, which the services normally connect as, bypasses RLS. Any
+-- other role except the reader keeps seeing and writing every document.
+ALTER TABLE documents ENABLE ROW LEVEL SECURITY;
+
+DROP POLICY IF EXISTS documents_user_visibility ON documents;
+CREATE POLICY documents_user_visibility ON documents
+    USING (
+        current_user <> 'omni_document_reader'
+        OR omni_document_visible(permissions, source_id)
+    )
+    WITH CHECK (true);
@@ -0,0 +1,49 @@
+-- Audit trail of searches, document changes and admin actions. Actor columns
+-- are plain values rather than foreign keys so entries outlive the user or
+-- source they name.
+--
+-- The table is append-only: entries can't be updated, and can only be deleted
+-- by the retention job, which sets `omni.audit_log_retention` for its
+-- transaction.
+
+CREATE TABLE IF NOT EXISTS audit_log (
+    id CHAR(26) PRIMARY KEY,
+    -- user, service, connector or system
+    actor_type TEXT NOT NULL,
+    -- User id, service name or source id, depending on actor_type
+    actor_id TEXT,
+    actor_email TEXT,
+    -- search, document_read, document_indexed, document_deleted or
+    -- configuration_changed
+    action TEXT NOT NULL,
+    resource_type TEXT,
+    resource_id TEXT,
+    details JSONB NOT NULL DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_log_created_at ON audit_log(created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_audit_log_actor_id ON audit_log(actor_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_audit_log_actor_email ON audit_log(lower(actor_email), created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_audit_log_action ON audit_log(action, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_audit_log_resource ON audit_log(resource_type, resource_id);
+
+CREATE OR REPLACE FUNCTION audit_log_append_only()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'DELETE' AND current_setting('omni.audit_log_retention', true) = 'on' THEN
+        RETURN OLD;
+    END IF;
+    RAISE EXCEPTION 'audit_log is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS audit_log_append_only ON audit_log;
+CREATE TRIGGER audit_log_append_only
+    BEFORE UPDATE OR DELETE ON audit_log
+    FOR EACH ROW EXECUTE FUNCTION audit_log_append_only();
+
+DROP TRIGGER IF EXISTS audit_log_no_truncate ON audit_log;
+CREATE TRIGGER audit_log_no_truncate
+    BEFORE TRUNCATE ON audit_log
+    FOR EACH STATEMENT EXECUTE FUNCTION audit_log_append_only();
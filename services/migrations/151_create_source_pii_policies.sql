@@ -0,0 +1,24 @@
+-- Per-source scrubbing of personal data before documents are stored and
+-- embedded. Sources without a policy are indexed as they are.
+--
+-- The indexer scans the extracted text of every created or updated document
+-- of a source with a policy, and records what it found on the document: the
+-- detected entity types in the `pii_types` attribute and counts per type in
+-- `metadata.pii`.
+
+CREATE TABLE IF NOT EXISTS source_pii_policies (
+    source_id CHAR(26) PRIMARY KEY REFERENCES sources(id) ON DELETE CASCADE,
+    -- redact: index the content with matches replaced by a [TYPE] placeholder
+    -- tag_only: index the content as it is
+    -- block: quarantine documents with any match instead of indexing them
+    action TEXT NOT NULL,
+    -- Built-in detectors to run: email, phone, credit_card
+    detectors TEXT[] NOT NULL DEFAULT ARRAY['email', 'phone', 'credit_card'],
+    -- Extra detectors as [{"name": "employee_id", "pattern": "EMP-\\d{6}"}]
+    custom_patterns JSONB NOT NULL DEFAULT '[]',
+    updated_by CHAR(26) REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT source_pii_policies_action_check
+        CHECK (action IN ('redact', 'tag_only', 'block'))
+);
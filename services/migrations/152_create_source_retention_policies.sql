@@ -0,0 +1,49 @@
+-- Per-source document retention.
+--
+-- The indexer periodically purges documents of a source with a policy that
+-- were last updated, according to their source, more than `retention_days`
+-- ago. Purging deletes the document with its embeddings, chunks and versions
+-- (all cascade from documents). Each run that purged anything is kept with a
+-- tombstone per document as the record of what was removed. Tombstones keep
+-- identifiers only, not titles or content, and do not reference documents
+-- because the rows are gone.
+
+CREATE TABLE IF NOT EXISTS source_retention_policies (
+    source_id CHAR(26) PRIMARY KEY REFERENCES sources(id) ON DELETE CASCADE,
+    retention_days INTEGER NOT NULL,
+    updated_by CHAR(26) REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT source_retention_policies_days_check CHECK (retention_days > 0)
+);
+
+CREATE TABLE IF NOT EXISTS retention_purges (
+    id CHAR(26) PRIMARY KEY,
+    source_id CHAR(26) NOT NULL REFERENCES sources(id) ON DELETE CASCADE,
+    -- The policy the run enforced
+    retention_days INTEGER NOT NULL,
+    -- Documents last updated before this were purged
+    cutoff TIMESTAMPTZ NOT NULL,
+    -- running | completed | failed
+    status TEXT NOT NULL DEFAULT 'running',
+    documents_purged INTEGER NOT NULL DEFAULT 0,
+    embeddings_purged BIGINT NOT NULL DEFAULT 0,
+    error_message TEXT,
+    started_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMPTZ,
+    CONSTRAINT retention_purges_status_check
+        CHECK (status IN ('running', 'completed', 'failed'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_retention_purges_source_started_at
+    ON retention_purges (source_id, started_at DESC);
+
+CREATE TABLE IF NOT EXISTS retention_purged_documents (
+    purge_id CHAR(26) NOT NULL REFERENCES retention_purges(id) ON DELETE CASCADE,
+    document_id CHAR(26) NOT NULL,
+    external_id TEXT NOT NULL,
+    -- When the source last updated the document
+    document_updated_at TIMESTAMPTZ NOT NULL,
+    purged_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (purge_id, document_id)
+);
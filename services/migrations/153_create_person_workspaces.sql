@@ -0,0 +1,20 @@
+-- People are shared across workspaces by email, but only show up in the
+-- people search of workspaces with a source that mentioned them. The indexer
+-- links each person it extracts to the workspace of the event's source.
+--
+-- People indexed before workspaces could be created all came from sources of
+-- the default workspace.
+
+CREATE TABLE IF NOT EXISTS person_workspaces (
+    person_id VARCHAR(26) NOT NULL REFERENCES people(id) ON DELETE CASCADE,
+    workspace_id CHAR(26) NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (person_id, workspace_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_person_workspaces_workspace_id
+    ON person_workspaces(workspace_id);
+
+INSERT INTO person_workspaces (person_id, workspace_id)
+SELECT id, '00000000000000000000000000' FROM people
+ON CONFLICT DO NOTHING;
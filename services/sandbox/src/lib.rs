@@ -10,6 +10,7 @@ use axum::http::StatusCode;
 use axum::response::{IntoResponse, Json};
 use axum::routing::{get, post};
 use axum::Router;
+use shared::service_auth::internal::InternalAuthConfig;
 use tower_http::trace::TraceLayer;
 
 #[derive(Debug, Clone)]
@@ -93,6 +94,7 @@ pub fn create_app(state: Arc<AppState>) -> Router {
         .route("/files/read", post(handlers::read_file))
         .route("/files/stat", post(handlers::file_stat))
         .route("/files/download", get(handlers::download_file))
+        .layer(InternalAuthConfig::from_env("SANDBOX").layer())
         .layer(TraceLayer::new_for_http())
         .with_state(state)
 }
@@ -0,0 +1,424 @@
+//! Priority-aware admission control for search traffic.
+//!
+//! Requests are classified into priority classes, each with its own
+//! concurrency limit. While interactive P95 latency is within target every
+//! class simply waits for a permit in its own pool. Once interactive P95
+//! exceeds the target, lower classes back off: RAG retrieval queues until
+//! latency recovers, and analytics/export traffic is shed outright.
+
+use axum::extract::{Request, State};
+use axum::http::HeaderMap;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tracing::warn;
+
+use crate::SearcherError;
+
+/// Header callers use to lower the priority of a request, e.g. the AI service
+/// marks its retrieval calls to `/search` as `rag`.
+pub const PRIORITY_HEADER: &str = "x-omni-priority";
+
+const DEFAULT_INTERACTIVE_MAX_CONCURRENT: usize = 64;
+const DEFAULT_RAG_MAX_CONCURRENT: usize = 16;
+const DEFAULT_ANALYTICS_MAX_CONCURRENT: usize = 4;
+const DEFAULT_INTERACTIVE_QUEUE_TIMEOUT_MS: u64 = 2000;
+const DEFAULT_RAG_QUEUE_TIMEOUT_MS: u64 = 5000;
+const DEFAULT_ANALYTICS_QUEUE_TIMEOUT_MS: u64 = 1000;
+const DEFAULT_P95_TARGET_MS: u64 = 1500;
+
+/// Interactive latencies older than this no longer influence the overload
+/// signal, so a past spike cannot keep lower classes throttled.
+const LATENCY_WINDOW: Duration = Duration::from_secs(60);
+const LATENCY_WINDOW_MAX_SAMPLES: usize = 512;
+/// P95 over fewer samples than this is too noisy to act on.
+pub(crate) const MIN_LATENCY_SAMPLES: usize = 20;
+const OVERLOAD_RECHECK_INTERVAL: Duration = Duration::from_millis(50);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PriorityClass {
+    /// User-facing search; never shed for the sake of other classes.
+    Interactive,
+    /// Retrieval performed on behalf of AI answers and agents.
+    Rag,
+    /// Analytics, exports and other bulk callers.
+    Analytics,
+}
+
+impl PriorityClass {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PriorityClass::Interactive => "interactive",
+            PriorityClass::Rag => "rag",
+            PriorityClass::Analytics => "analytics",
+        }
+    }
+
+    /// Default class for a route, before any caller-supplied downgrade.
+    fn for_path(path: &str) -> Self {
+        match path {
+            "/search/ai-answer" | "/answer" | "/answer/stream" | "/suggested-questions" => {
+                PriorityClass::Rag
+            }
+            _ => PriorityClass::Interactive,
+        }
+    }
+
+    /// Resolve the class for a request. The priority header may only lower a
+    /// request's class, never raise it above the route default.
+    pub fn classify(path: &str, headers: &HeaderMap) -> Self {
+        let route_class = Self::for_path(path);
+        headers
+            .get(PRIORITY_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<PriorityClass>().ok())
+            .map(|requested| requested.max(route_class))
+            .unwrap_or(route_class)
+    }
+
+    fn index(&self) -> usize {
+        match self {
+            PriorityClass::Interactive => 0,
+            PriorityClass::Rag => 1,
+            PriorityClass::Analytics => 2,
+        }
+    }
+}
+
+impl FromStr for PriorityClass {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "interactive" => Ok(PriorityClass::Interactive),
+            "rag" => Ok(PriorityClass::Rag),
+            "analytics" | "export" => Ok(PriorityClass::Analytics),
+            other => Err(format!("unknown priority class '{}'", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ClassLimits {
+    pub max_concurrent: usize,
+    pub queue_timeout: Duration,
+}
+
+#[derive(Debug, Clone)]
+pub struct AdmissionConfig {
+    pub interactive: ClassLimits,
+    pub rag: ClassLimits,
+    pub analytics: ClassLimits,
+    /// Interactive P95 above which lower classes are queued or shed.
+    pub p95_target: Duration,
+}
+
+impl Default for AdmissionConfig {
+    fn default() -> Self {
+        Self {
+            interactive: ClassLimits {
+                max_concurrent: DEFAULT_INTERACTIVE_MAX_CONCURRENT,
+                queue_timeout: Duration::from_millis(DEFAULT_INTERACTIVE_QUEUE_TIMEOUT_MS),
+            },
+            rag: ClassLimits {
+                max_concurrent: DEFAULT_RAG_MAX_CONCURRENT,
+                queue_timeout: Duration::from_millis(DEFAULT_RAG_QUEUE_TIMEOUT_MS),
+            },
+            analytics: ClassLimits {
+                max_concurrent: DEFAULT_ANALYTICS_MAX_CONCURRENT,
+                queue_timeout: Duration::from_millis(DEFAULT_ANALYTICS_QUEUE_TIMEOUT_MS),
+            },
+            p95_target: Duration::from_millis(DEFAULT_P95_TARGET_MS),
+        }
+    }
+}
+
+impl AdmissionConfig {
+    pub fn from_env() -> Self {
+        let class_limits =
+            |prefix: &str, max_concurrent: usize, queue_timeout_ms: u64| ClassLimits {
+                max_concurrent: env_or(&format!("{}_MAX_CONCURRENT", prefix), max_concurrent)
+                    .max(1),
+                queue_timeout: Duration::from_millis(env_or(
+                    &format!("{}_QUEUE_TIMEOUT_MS", prefix),
+                    queue_timeout_ms,
+                )),
+            };
+
+        Self {
+            interactive: class_limits(
+                "SEARCHER_INTERACTIVE",
+                DEFAULT_INTERACTIVE_MAX_CONCURRENT,
+                DEFAULT_INTERACTIVE_QUEUE_TIMEOUT_MS,
+            ),
+            rag: class_limits(
+                "SEARCHER_RAG",
+                DEFAULT_RAG_MAX_CONCURRENT,
+                DEFAULT_RAG_QUEUE_TIMEOUT_MS,
+            ),
+            analytics: class_limits(
+                "SEARCHER_ANALYTICS",
+                DEFAULT_ANALYTICS_MAX_CONCURRENT,
+                DEFAULT_ANALYTICS_QUEUE_TIMEOUT_MS,
+            ),
+            p95_target: Duration::from_millis(env_or(
+                "SEARCHER_P95_TARGET_MS",
+                DEFAULT_P95_TARGET_MS,
+            )),
+        }
+    }
+
+    fn limits(&self, class: PriorityClass) -> ClassLimits {
+        match class {
+            PriorityClass::Interactive => self.interactive,
+            PriorityClass::Rag => self.rag,
+            PriorityClass::Analytics => self.analytics,
+        }
+    }
+}
+
+fn env_or<T: FromStr>(key: &str, default: T) -> T {
+    std::env::var(key)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+/// Rolling window of recent request latencies.
+#[derive(Default)]
+pub(crate) struct LatencyWindow {
+    samples: VecDeque<(Instant, Duration)>,
+}
+
+impl LatencyWindow {
+    pub(crate) fn record(&mut self, now: Instant, latency: Duration) {
+        self.evict(now);
+        if self.samples.len() >= LATENCY_WINDOW_MAX_SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back((now, latency));
+    }
+
+    pub(crate) fn p95(&mut self, now: Instant) -> Option<Duration> {
+        self.evict(now);
+        if self.samples.len() < MIN_LATENCY_SAMPLES {
+            return None;
+        }
+        let mut latencies: Vec<Duration> = self.samples.iter().map(|(_, l)| *l).collect();
+        latencies.sort_unstable();
+        let rank = (latencies.len() * 95).div_ceil(100);
+        Some(latencies[rank.saturating_sub(1)])
+    }
+
+    fn evict(&mut self, now: Instant) {
+        while let Some((at, _)) = self.samples.front() {
+            if now.duration_since(*at) <= LATENCY_WINDOW {
+                break;
+            }
+            self.samples.pop_front();
+        }
+    }
+}
+
+pub struct AdmissionController {
+    config: AdmissionConfig,
+    permits: [Arc<Semaphore>; 3],
+    interactive_latency: Mutex<LatencyWindow>,
+}
+
+impl AdmissionController {
+    pub fn new(config: AdmissionConfig) -> Self {
+        let permits = [
+            PriorityClass::Interactive,
+            PriorityClass::Rag,
+            PriorityClass::Analytics,
+        ]
+        .map(|class| Arc::new(Semaphore::new(config.limits(class).max_concurrent)));
+
+        Self {
+            config,
+            permits,
+            interactive_latency: Mutex::new(LatencyWindow::default()),
+        }
+    }
+
+    /// Current interactive P95, or `None` until enough samples are in.
+    pub fn interactive_p95(&self) -> Option<Duration> {
+        self.interactive_latency.lock().unwrap().p95(Instant::now())
+    }
+
+    pub fn is_overloaded(&self) -> bool {
+        self.interactive_p95()
+            .is_some_and(|p95| p95 > self.config.p95_target)
+    }
+
+    pub fn record_latency(&self, class: PriorityClass, latency: Duration) {
+        if class == PriorityClass::Interactive {
+            self.interactive_latency
+                .lock()
+                .unwrap()
+                .record(Instant::now(), latency);
+        }
+    }
+
+    /// Wait for a permit for `class`, or fail with `Overloaded` if the request
+    /// should be shed.
+    pub async fn admit(&self, class: PriorityClass) -> crate::Result<OwnedSemaphorePermit> {
+        let limits = self.config.limits(class);
+        let deadline = tokio::time::Instant::now() + limits.queue_timeout;
+
+        match class {
+            PriorityClass::Interactive => {}
+            PriorityClass::Analytics => {
+                if self.is_overloaded() {
+                    return Err(self.shed(class, "interactive latency above target"));
+                }
+            }
+            PriorityClass::Rag => {
+                while self.is_overloaded() {
+                    if tokio::time::Instant::now() >= deadline {
+                        return Err(self.shed(class, "interactive latency above target"));
+                    }
+                    tokio::time::sleep(OVERLOAD_RECHECK_INTERVAL).await;
+                }
+            }
+        }
+
+        let semaphore = self.permits[class.index()].clone();
+        match tokio::time::timeout_at(deadline, semaphore.acquire_owned()).await {
+            Ok(Ok(permit)) => Ok(permit),
+            Ok(Err(_)) | Err(_) => Err(self.shed(class, "concurrency limit reached")),
+        }
+    }
+
+    fn shed(&self, class: PriorityClass, reason: &str) -> SearcherError {
+        warn!(
+            "Shedding {} request: {} (interactive p95: {:?}, target: {:?})",
+            class.as_str(),
+            reason,
+            self.interactive_p95(),
+            self.config.p95_target
+        );
+        SearcherError::Overloaded(class)
+    }
+}
+
+/// Middleware applying admission control to the wrapped routes. Interactive
+/// latencies are recorded here, so they include queueing time. For streamed
+/// responses the permit is released once headers are sent.
+pub async fn admit(
+    State(controller): State<Arc<AdmissionController>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let class = PriorityClass::classify(request.uri().path(), request.headers());
+    let started = Instant::now();
+
+    let _permit = match controller.admit(class).await {
+        Ok(permit) => permit,
+        Err(e) => return e.into_response(),
+    };
+
+    let response = next.run(request).await;
+    controller.record_latency(class, started.elapsed());
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn controller_with_latency(latency: Duration, queue_timeout: Duration) -> AdmissionController {
+        let limits = ClassLimits {
+            max_concurrent: 1,
+            queue_timeout,
+        };
+        let controller = AdmissionController::new(AdmissionConfig {
+            interactive: limits,
+            rag: limits,
+            analytics: limits,
+            p95_target: Duration::from_millis(100),
+        });
+        for _ in 0..MIN_LATENCY_SAMPLES {
+            controller.record_latency(PriorityClass::Interactive, latency);
+        }
+        controller
+    }
+
+    #[test]
+    fn test_priority_header_can_only_lower_class() {
+        let mut headers = HeaderMap::new();
+        headers.insert(PRIORITY_HEADER, HeaderValue::from_static("analytics"));
+        assert_eq!(
+            PriorityClass::classify("/search", &headers),
+            PriorityClass::Analytics
+        );
+
+        headers.insert(PRIORITY_HEADER, HeaderValue::from_static("interactive"));
+        assert_eq!(
+            PriorityClass::classify("/search/ai-answer", &headers),
+            PriorityClass::Rag
+        );
+
+        headers.insert(PRIORITY_HEADER, HeaderValue::from_static("bogus"));
+        assert_eq!(
+            PriorityClass::classify("/search", &headers),
+            PriorityClass::Interactive
+        );
+    }
+
+    #[test]
+    fn test_p95_requires_minimum_samples() {
+        let mut window = LatencyWindow::default();
+        let now = Instant::now();
+        for ms in 1..MIN_LATENCY_SAMPLES as u64 {
+            window.record(now, Duration::from_millis(ms));
+        }
+        assert_eq!(window.p95(now), None);
+
+        for ms in 0..80 {
+            window.record(now, Duration::from_millis(ms));
+        }
+        let p95 = window.p95(now).unwrap();
+        assert!(p95 >= Duration::from_millis(70), "p95 was {:?}", p95);
+    }
+
+    #[tokio::test]
+    async fn test_low_priority_classes_back_off_when_overloaded() {
+        let controller =
+            controller_with_latency(Duration::from_millis(500), Duration::from_millis(100));
+        assert!(controller.is_overloaded());
+
+        assert!(matches!(
+            controller.admit(PriorityClass::Analytics).await,
+            Err(SearcherError::Overloaded(PriorityClass::Analytics))
+        ));
+        assert!(matches!(
+            controller.admit(PriorityClass::Rag).await,
+            Err(SearcherError::Overloaded(PriorityClass::Rag))
+        ));
+        assert!(controller.admit(PriorityClass::Interactive).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_class_concurrency_limit_queues_then_sheds() {
+        let controller =
+            controller_with_latency(Duration::from_millis(10), Duration::from_millis(50));
+        assert!(!controller.is_overloaded());
+
+        let held = controller.admit(PriorityClass::Rag).await.unwrap();
+        // Another class has its own pool.
+        assert!(controller.admit(PriorityClass::Analytics).await.is_ok());
+        assert!(controller.admit(PriorityClass::Rag).await.is_err());
+
+        drop(held);
+        assert!(controller.admit(PriorityClass::Rag).await.is_ok());
+    }
+}
@@ -0,0 +1,174 @@
+//! Generated answers with citations.
+//!
+//! `/answer` retrieves context the same way `/search/ai-answer` does (hybrid
+//! search, with semantic hits expanded by `rag_context_window` neighbouring
+//! chunks), but asks the model to cite context entries by number, e.g. `[2]`.
+//! The markers in the answer are resolved against the context the prompt was
+//! built from, so each citation points at a document and the chunk ranges
+//! that were placed in the prompt.
+
+use crate::models::SearchResult;
+use crate::rag_provenance::{ContextChunk, ContextEntry};
+use crate::search::push_rag_context;
+use regex::Regex;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::sync::LazyLock;
+
+/// `[2]` or `[1, 3]`.
+static CITATION_MARKER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\[(\d+(?:\s*,\s*\d+)*)\]").unwrap());
+
+/// A context entry the answer cites.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Citation {
+    /// The number used for the entry in the answer text.
+    pub marker: usize,
+    pub document_id: String,
+    pub title: String,
+    pub url: Option<String>,
+    /// Chunks of the document that were in the prompt, with their offsets in
+    /// the document's content. Empty for fulltext matches, which contribute
+    /// highlights rather than embedded chunks.
+    pub chunks: Vec<ContextChunk>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AnswerResponse {
+    pub answer: String,
+    /// Cited context entries, in order of first citation.
+    pub citations: Vec<Citation>,
+    /// Id of the answer's provenance record.
+    pub provenance_id: String,
+}
+
+/// One event of a streamed answer.
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AnswerStreamEvent {
+    /// The next piece of the answer text.
+    Delta { text: String },
+    /// The complete answer with its citations. Always the last event of a
+    /// stream that did not fail.
+    Final(AnswerResponse),
+}
+
+impl AnswerStreamEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            AnswerStreamEvent::Delta { .. } => "delta",
+            AnswerStreamEvent::Final(_) => "final",
+        }
+    }
+}
+
+/// Prompt asking for an answer to `query` from `context`, citing entries by
+/// their context number.
+pub fn build_answer_prompt(query: &str, context: &[SearchResult]) -> String {
+    let mut prompt = String::new();
+
+    prompt.push_str("You are Omni - an AI assistant that assists users with their queries. ");
+    prompt.push_str(
+        "Answer the user's question/instruction using only the information from the provided context. ",
+    );
+    prompt.push_str(
+        "Cite every statement that relies on the context with the number of the context it comes from in square brackets, e.g. [1] or [2, 3]. Do not cite anything else and do not add a list of sources. If the context does not answer the question, say so. Return your response in markdown format. ",
+    );
+
+    push_rag_context(&mut prompt, context);
+    prompt.push_str(&format!("Question: {}\n\n", query));
+
+    prompt
+}
+
+/// The context entries cited in `answer`, in order of first citation.
+/// Markers that do not name a context entry are ignored.
+pub fn resolve_citations(answer: &str, context: &[ContextEntry]) -> Vec<Citation> {
+    let mut seen = HashSet::new();
+    let mut citations = Vec::new();
+    for marker in CITATION_MARKER.captures_iter(answer).flat_map(|captures| {
+        captures[1]
+            .split(',')
+            .filter_map(|n| n.trim().parse::<usize>().ok())
+            .collect::<Vec<_>>()
+    }) {
+        let Some(entry) = context.iter().find(|entry| entry.rank == marker) else {
+            continue;
+        };
+        if seen.insert(marker) {
+            citations.push(Citation {
+                marker,
+                document_id: entry.document_id.clone(),
+                title: entry.title.clone(),
+                url: entry.url.clone(),
+                chunks: entry.chunks.clone(),
+            });
+        }
+    }
+    citations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::OffsetDateTime;
+
+    fn entry(rank: usize, document_id: &str, chunks: Vec<ContextChunk>) -> ContextEntry {
+        ContextEntry {
+            rank,
+            document_id: document_id.to_string(),
+            source_id: "source".to_string(),
+            title: format!("Document {}", document_id),
+            url: None,
+            match_type: "semantic".to_string(),
+            score: 1.0,
+            document_updated_at: OffsetDateTime::UNIX_EPOCH,
+            document_indexed_at: OffsetDateTime::UNIX_EPOCH,
+            chunks,
+            permissions: serde_json::Value::Null,
+        }
+    }
+
+    fn chunk(chunk_index: i32, start_offset: i32, end_offset: i32) -> ContextChunk {
+        ContextChunk {
+            chunk_id: format!("chunk-{}", chunk_index),
+            chunk_index,
+            start_offset,
+            end_offset,
+            page_number: None,
+            heading_path: None,
+        }
+    }
+
+    #[test]
+    fn test_resolves_citations_in_order_of_first_use() {
+        let context = vec![
+            entry(1, "doc-a", vec![chunk(0, 0, 512), chunk(1, 512, 1024)]),
+            entry(2, "doc-b", vec![]),
+            entry(3, "doc-c", vec![chunk(4, 2048, 2560)]),
+        ];
+        let answer = "Deploys run on Fridays [3]. Rollbacks need approval [1, 3][2].";
+
+        let citations = resolve_citations(answer, &context);
+
+        let markers: Vec<usize> = citations.iter().map(|c| c.marker).collect();
+        assert_eq!(markers, vec![3, 1, 2]);
+        assert_eq!(citations[0].document_id, "doc-c");
+        assert_eq!(
+            citations[1].chunks,
+            vec![chunk(0, 0, 512), chunk(1, 512, 1024)]
+        );
+        assert!(citations[2].chunks.is_empty());
+    }
+
+    #[test]
+    fn test_ignores_unknown_markers_and_links() {
+        let context = vec![entry(1, "doc-a", vec![])];
+        let answer = "See [the runbook](https://example.com) [7] and [x] [1].";
+
+        let citations = resolve_citations(answer, &context);
+
+        assert_eq!(citations.len(), 1);
+        assert_eq!(citations[0].document_id, "doc-a");
+    }
+}
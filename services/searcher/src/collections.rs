@@ -0,0 +1,271 @@
+//! User-curated document collections.
+//!
+//! A collection is visible to its owner, to members of its `team_groups` when
+//! shared with a team, or to everyone when public. Team and public
+//! collections are only shared within the owner's workspace. Only the owner
+//! may change it. Membership never widens access: searching a collection
+//! still applies each document's own permissions.
+
+use serde::{Deserialize, Serialize};
+use sqlx::types::time::OffsetDateTime;
+use sqlx::{FromRow, PgPool};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+pub enum CollectionVisibility {
+    #[default]
+    Private,
+    Team,
+    Public,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Collection {
+    pub id: String,
+    pub owner_id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub visibility: CollectionVisibility,
+    pub team_groups: Vec<String>,
+    pub document_count: i64,
+    #[serde(with = "time::serde::iso8601")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::iso8601")]
+    pub updated_at: OffsetDateTime,
+}
+
+/// Who is looking at collections: their user id, if signed in, the groups
+/// they belong to and their workspace.
+#[derive(Debug, Clone, Default)]
+pub struct CollectionViewer {
+    pub user_id: Option<String>,
+    pub groups: Vec<String>,
+    pub workspace_id: String,
+}
+
+impl CollectionViewer {
+    pub fn new(user_id: Option<String>, groups: Vec<String>, workspace_id: String) -> Self {
+        Self {
+            user_id,
+            groups: normalize_groups(&groups),
+            workspace_id,
+        }
+    }
+
+    pub fn owns(&self, collection: &Collection) -> bool {
+        self.user_id.as_deref() == Some(collection.owner_id.as_str())
+    }
+}
+
+pub fn normalize_groups(groups: &[String]) -> Vec<String> {
+    let mut groups: Vec<String> = groups
+        .iter()
+        .map(|g| g.trim().to_lowercase())
+        .filter(|g| !g.is_empty())
+        .collect();
+    groups.sort();
+    groups.dedup();
+    groups
+}
+
+const COLLECTION_COLUMNS: &str = r#"
+    c.id, c.owner_id, c.name, c.description, c.visibility, c.team_groups,
+    c.created_at, c.updated_at,
+    (SELECT COUNT(*) FROM collection_documents cd WHERE cd.collection_id = c.id) AS document_count
+"#;
+
+const VISIBLE_TO_VIEWER: &str = r#"
+    (c.owner_id = $1
+     OR ((c.visibility = 'public'
+          OR (c.visibility = 'team' AND c.team_groups && $2))
+         AND EXISTS (SELECT 1 FROM users cu
+                     WHERE cu.id = c.owner_id AND cu.workspace_id = $3)))
+"#;
+
+pub struct CollectionRepository {
+    pool: PgPool,
+}
+
+impl CollectionRepository {
+    pub fn new(pool: &PgPool) -> Self {
+        Self { pool: pool.clone() }
+    }
+
+    /// Collections the viewer can see, most recently updated first.
+    pub async fn list_visible(
+        &self,
+        viewer: &CollectionViewer,
+    ) -> Result<Vec<Collection>, sqlx::Error> {
+        let query = format!(
+            "SELECT {COLLECTION_COLUMNS} FROM collections c WHERE {VISIBLE_TO_VIEWER} \
+             ORDER BY c.updated_at DESC"
+        );
+        sqlx::query_as::<_, Collection>(&query)
+            .bind(viewer.user_id.as_deref())
+            .bind(&viewer.groups)
+            .bind(&viewer.workspace_id)
+            .fetch_all(&self.pool)
+            .await
+    }
+
+    pub async fn get_visible(
+        &self,
+        id: &str,
+        viewer: &CollectionViewer,
+    ) -> Result<Option<Collection>, sqlx::Error> {
+        let query = format!(
+            "SELECT {COLLECTION_COLUMNS} FROM collections c WHERE {VISIBLE_TO_VIEWER} AND c.id = $4"
+        );
+        sqlx::query_as::<_, Collection>(&query)
+            .bind(viewer.user_id.as_deref())
+            .bind(&viewer.groups)
+            .bind(&viewer.workspace_id)
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+    }
+
+    pub async fn create(
+        &self,
+        owner_id: &str,
+        name: &str,
+        description: Option<&str>,
+        visibility: CollectionVisibility,
+        team_groups: &[String],
+    ) -> Result<Collection, sqlx::Error> {
+        let query = format!(
+            r#"
+            WITH c AS (
+                INSERT INTO collections (id, owner_id, name, description, visibility, team_groups)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                RETURNING *
+            )
+            SELECT {COLLECTION_COLUMNS} FROM c
+            "#
+        );
+        sqlx::query_as::<_, Collection>(&query)
+            .bind(ulid::Ulid::new().to_string())
+            .bind(owner_id)
+            .bind(name)
+            .bind(description)
+            .bind(visibility)
+            .bind(team_groups)
+            .fetch_one(&self.pool)
+            .await
+    }
+
+    /// Update fields that are `Some`. Returns `None` unless `owner_id` owns
+    /// the collection.
+    pub async fn update(
+        &self,
+        id: &str,
+        owner_id: &str,
+        name: Option<&str>,
+        description: Option<&str>,
+        visibility: Option<CollectionVisibility>,
+        team_groups: Option<&[String]>,
+    ) -> Result<Option<Collection>, sqlx::Error> {
+        let query = format!(
+            r#"
+            WITH c AS (
+                UPDATE collections
+                SET name = COALESCE($3, name),
+                    description = COALESCE($4, description),
+                    visibility = COALESCE($5, visibility),
+                    team_groups = COALESCE($6, team_groups)
+                WHERE id = $1 AND owner_id = $2
+                RETURNING *
+            )
+            SELECT {COLLECTION_COLUMNS} FROM c
+            "#
+        );
+        sqlx::query_as::<_, Collection>(&query)
+            .bind(id)
+            .bind(owner_id)
+            .bind(name)
+            .bind(description)
+            .bind(visibility)
+            .bind(team_groups)
+            .fetch_optional(&self.pool)
+            .await
+    }
+
+    pub async fn delete(&self, id: &str, owner_id: &str) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query("DELETE FROM collections WHERE id = $1 AND owner_id = $2")
+            .bind(id)
+            .bind(owner_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Add documents to a collection. Callers pass only documents the adder
+    /// can read. Returns the number of documents newly added.
+    pub async fn add_documents(
+        &self,
+        id: &str,
+        added_by: &str,
+        document_ids: &[String],
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO collection_documents (collection_id, document_id, added_by)
+            SELECT $1, document_id, $3
+            FROM unnest($2::text[]) AS document_id
+            ON CONFLICT (collection_id, document_id) DO NOTHING
+            "#,
+        )
+        .bind(id)
+        .bind(document_ids)
+        .bind(added_by)
+        .execute(&self.pool)
+        .await?;
+        sqlx::query("UPDATE collections SET updated_at = NOW() WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+
+    pub async fn remove_document(&self, id: &str, document_id: &str) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            "DELETE FROM collection_documents WHERE collection_id = $1 AND document_id = $2",
+        )
+        .bind(id)
+        .bind(document_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Member document ids, most recently added first.
+    pub async fn document_ids(&self, id: &str) -> Result<Vec<String>, sqlx::Error> {
+        sqlx::query_scalar(
+            "SELECT document_id FROM collection_documents WHERE collection_id = $1 \
+             ORDER BY added_at DESC",
+        )
+        .bind(id)
+        .fetch_all(&self.pool)
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_groups() {
+        let groups = vec![
+            " Eng@Example.com ".to_string(),
+            "eng@example.com".to_string(),
+            "".to_string(),
+            "design@example.com".to_string(),
+        ];
+        assert_eq!(
+            normalize_groups(&groups),
+            vec!["design@example.com", "eng@example.com"]
+        );
+    }
+}
@@ -0,0 +1,195 @@
+//! Conditional requests for document reads.
+//!
+//! A read's ETag is derived from the SHA-256 of the document's content blob,
+//! which storage already computes for deduplication, combined with when the
+//! document last changed and the request parameters that shape the response
+//! (query, line range, field selection). Clients that send a matching
+//! `If-None-Match`, or an `If-Modified-Since` no older than the document, get
+//! a 304 without the content being loaded again.
+
+use axum::http::{HeaderMap, HeaderValue, StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use shared::db::repositories::ContentVersion;
+
+use crate::models::SearchRequest;
+
+const HTTP_DATE_FORMAT: &str = "%a, %d %b %Y %H:%M:%S GMT";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContentValidators {
+    pub etag: String,
+    /// Truncated to whole seconds, the resolution of HTTP dates.
+    pub last_modified: DateTime<Utc>,
+}
+
+impl ContentValidators {
+    pub fn new(version: &ContentVersion, request: &SearchRequest) -> Self {
+        let updated_at = version.updated_at.unix_timestamp_nanos();
+
+        let mut hasher = Sha256::new();
+        hasher.update(version.content_hash.as_deref().unwrap_or("").as_bytes());
+        hasher.update(updated_at.to_be_bytes());
+        hasher.update(request.query.as_bytes());
+        hasher.update([0]);
+        for line in [
+            request.document_content_start_line,
+            request.document_content_end_line,
+        ] {
+            hasher.update(line.map_or(-1, i64::from).to_be_bytes());
+        }
+        if let Some(fields) = &request.fields {
+            for field in fields {
+                hasher.update(field.as_bytes());
+                hasher.update([0]);
+            }
+        }
+        let digest = hasher.finalize();
+        let etag = format!(
+            "\"{}\"",
+            digest[..16]
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect::<String>()
+        );
+
+        let last_modified =
+            DateTime::from_timestamp(version.updated_at.unix_timestamp(), 0).unwrap_or_default();
+
+        Self {
+            etag,
+            last_modified,
+        }
+    }
+
+    /// Whether the client's cached copy is current. `If-None-Match` takes
+    /// precedence; `If-Modified-Since` is only consulted without it.
+    pub fn is_not_modified(&self, headers: &HeaderMap) -> bool {
+        if let Some(if_none_match) = headers.get(header::IF_NONE_MATCH) {
+            let Ok(if_none_match) = if_none_match.to_str() else {
+                return false;
+            };
+            return if_none_match
+                .split(',')
+                .map(str::trim)
+                .any(|tag| tag == "*" || tag.strip_prefix("W/").unwrap_or(tag) == self.etag);
+        }
+
+        headers
+            .get(header::IF_MODIFIED_SINCE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| DateTime::parse_from_rfc2822(value).ok())
+            .is_some_and(|since| self.last_modified <= since)
+    }
+
+    pub fn apply(&self, headers: &mut HeaderMap) {
+        if let Ok(etag) = HeaderValue::from_str(&self.etag) {
+            headers.insert(header::ETAG, etag);
+        }
+        if let Ok(last_modified) =
+            HeaderValue::from_str(&self.last_modified.format(HTTP_DATE_FORMAT).to_string())
+        {
+            headers.insert(header::LAST_MODIFIED, last_modified);
+        }
+        // Cacheable per user only, and always revalidated.
+        headers.insert(
+            header::CACHE_CONTROL,
+            HeaderValue::from_static("private, no-cache"),
+        );
+    }
+
+    pub fn not_modified(&self) -> Response {
+        let mut response = StatusCode::NOT_MODIFIED.into_response();
+        self.apply(response.headers_mut());
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::datetime;
+
+    fn version() -> ContentVersion {
+        ContentVersion {
+            content_hash: Some("ab12".to_string()),
+            updated_at: datetime!(2026-10-12 09:30:15.250 UTC),
+        }
+    }
+
+    fn request() -> SearchRequest {
+        SearchRequest {
+            query: "content".to_string(),
+            document_id: Some("doc1".to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn headers(name: header::HeaderName, value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(name, HeaderValue::from_str(value).unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_etag_varies_with_content_and_line_range() {
+        let base = ContentValidators::new(&version(), &request());
+        assert_eq!(base, ContentValidators::new(&version(), &request()));
+
+        let mut changed = version();
+        changed.content_hash = Some("cd34".to_string());
+        assert_ne!(base.etag, ContentValidators::new(&changed, &request()).etag);
+
+        let mut ranged = request();
+        ranged.document_content_start_line = Some(1);
+        ranged.document_content_end_line = Some(50);
+        assert_ne!(base.etag, ContentValidators::new(&version(), &ranged).etag);
+    }
+
+    #[test]
+    fn test_if_none_match() {
+        let validators = ContentValidators::new(&version(), &request());
+
+        assert!(validators.is_not_modified(&headers(header::IF_NONE_MATCH, &validators.etag)));
+        assert!(validators.is_not_modified(&headers(
+            header::IF_NONE_MATCH,
+            &format!("\"other\", W/{}", validators.etag)
+        )));
+        assert!(!validators.is_not_modified(&headers(header::IF_NONE_MATCH, "\"other\"")));
+        // If-None-Match wins over a matching If-Modified-Since
+        let mut both = headers(header::IF_NONE_MATCH, "\"other\"");
+        both.insert(
+            header::IF_MODIFIED_SINCE,
+            HeaderValue::from_static("Mon, 12 Oct 2026 09:30:15 GMT"),
+        );
+        assert!(!validators.is_not_modified(&both));
+    }
+
+    #[test]
+    fn test_if_modified_since() {
+        let validators = ContentValidators::new(&version(), &request());
+
+        for (since, not_modified) in [
+            ("Mon, 12 Oct 2026 09:30:15 GMT", true),
+            ("Tue, 13 Oct 2026 00:00:00 GMT", true),
+            ("Mon, 12 Oct 2026 09:30:14 GMT", false),
+            ("not a date", false),
+        ] {
+            assert_eq!(
+                validators.is_not_modified(&headers(header::IF_MODIFIED_SINCE, since)),
+                not_modified,
+                "{}",
+                since
+            );
+        }
+
+        let mut response_headers = HeaderMap::new();
+        validators.apply(&mut response_headers);
+        assert_eq!(
+            response_headers[header::LAST_MODIFIED],
+            "Mon, 12 Oct 2026 09:30:15 GMT"
+        );
+        assert_eq!(response_headers[header::ETAG], validators.etag.as_str());
+    }
+}
@@ -0,0 +1,90 @@
+//! Collapsing of duplicate results across connectors.
+//!
+//! The same file often reaches Omni more than once, e.g. synced from Google
+//! Drive and also attached in a Slack thread. The indexer stores a
+//! fingerprint of each document's normalized text
+//! (`shared::utils::content_fingerprint`); results sharing one are collapsed
+//! into the highest-ranked of them, which lists the rest under `duplicates`.
+
+use crate::models::{DuplicateHit, SearchResult};
+use std::collections::HashMap;
+
+/// Collapse results with the same fingerprint into the first (highest-ranked)
+/// of them. Results without a fingerprint are kept as they are. Expects
+/// `results` sorted by rank.
+pub fn collapse_duplicates(
+    results: Vec<SearchResult>,
+    fingerprints: &HashMap<String, String>,
+) -> Vec<SearchResult> {
+    let mut collapsed: Vec<SearchResult> = Vec::with_capacity(results.len());
+    let mut kept_by_fingerprint: HashMap<&str, usize> = HashMap::new();
+
+    for result in results {
+        let Some(fingerprint) = fingerprints.get(&result.document.id) else {
+            collapsed.push(result);
+            continue;
+        };
+        match kept_by_fingerprint.get(fingerprint.as_str()) {
+            Some(&index) => collapsed[index].duplicates.push(DuplicateHit {
+                document_id: result.document.id,
+                source_id: result.document.source_id,
+                source_type: result.source_type,
+                title: result.document.title,
+                url: result.document.url,
+                score: result.score,
+            }),
+            None => {
+                kept_by_fingerprint.insert(fingerprint, collapsed.len());
+                collapsed.push(result);
+            }
+        }
+    }
+
+    collapsed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(id: &str, source_type: &str, score: f32) -> SearchResult {
+        let mut result = SearchResult::for_test(id);
+        result.document.source_id = format!("{}-source", source_type);
+        result.source_type = Some(source_type.to_string());
+        result.score = score;
+        result
+    }
+
+    #[test]
+    fn test_collapses_into_highest_ranked() {
+        let results = vec![
+            result("drive-doc", "google_drive", 0.9),
+            result("other", "confluence", 0.8),
+            result("slack-file", "slack", 0.7),
+            result("unfingerprinted", "slack", 0.6),
+        ];
+        let fingerprints = HashMap::from([
+            ("drive-doc".to_string(), "abc".to_string()),
+            ("other".to_string(), "def".to_string()),
+            ("slack-file".to_string(), "abc".to_string()),
+        ]);
+
+        let collapsed = collapse_duplicates(results, &fingerprints);
+        let ids: Vec<&str> = collapsed.iter().map(|r| r.document.id.as_str()).collect();
+        assert_eq!(ids, vec!["drive-doc", "other", "unfingerprinted"]);
+
+        let duplicates = &collapsed[0].duplicates;
+        assert_eq!(duplicates.len(), 1);
+        assert_eq!(duplicates[0].document_id, "slack-file");
+        assert_eq!(duplicates[0].source_type.as_deref(), Some("slack"));
+        assert!(collapsed[1].duplicates.is_empty());
+    }
+
+    #[test]
+    fn test_without_fingerprints_results_are_unchanged() {
+        let results = vec![result("a", "slack", 0.9), result("b", "slack", 0.8)];
+        let collapsed = collapse_duplicates(results, &HashMap::new());
+        assert_eq!(collapsed.len(), 2);
+        assert!(collapsed.iter().all(|r| r.duplicates.is_empty()));
+    }
+}
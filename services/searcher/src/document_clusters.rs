@@ -0,0 +1,164 @@
+//! Grouping documents into topics by their summary embeddings.
+//!
+//! Suggested questions are drawn from one document per topic rather than
+//! from random documents, so a user's suggestions cover the different things
+//! recently indexed for them. Clustering is spherical k-means: embeddings are
+//! normalized and compared by cosine similarity. Seeds are picked farthest
+//! first, starting from the first embedding, so the same input always gives
+//! the same clusters.
+
+/// Stop early once assignments settle; this is only a bound.
+const MAX_ITERATIONS: usize = 20;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cluster {
+    /// Indices into the clustered embeddings, most central first.
+    pub members: Vec<usize>,
+}
+
+/// Split `embeddings` into at most `k` clusters, largest first. All
+/// embeddings must have the same dimensions.
+pub fn cluster(embeddings: &[Vec<f32>], k: usize) -> Vec<Cluster> {
+    let points: Vec<Vec<f32>> = embeddings.iter().map(|e| normalized(e.clone())).collect();
+    let k = k.min(points.len());
+    if k == 0 {
+        return Vec::new();
+    }
+
+    let mut centroids = farthest_first_seeds(&points, k);
+    let mut assignments = vec![usize::MAX; points.len()];
+    for _ in 0..MAX_ITERATIONS {
+        let mut changed = false;
+        for (i, point) in points.iter().enumerate() {
+            let nearest = nearest_centroid(point, &centroids);
+            if assignments[i] != nearest {
+                assignments[i] = nearest;
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+
+        for (c, centroid) in centroids.iter_mut().enumerate() {
+            let mut sum = vec![0.0; centroid.len()];
+            for (point, _) in points.iter().zip(&assignments).filter(|(_, a)| **a == c) {
+                for (s, x) in sum.iter_mut().zip(point) {
+                    *s += x;
+                }
+            }
+            // A centroid that lost all its points keeps its place
+            if sum.iter().any(|s| *s != 0.0) {
+                *centroid = normalized(sum);
+            }
+        }
+    }
+
+    let mut clusters: Vec<Cluster> = centroids
+        .iter()
+        .enumerate()
+        .map(|(c, centroid)| {
+            let mut members: Vec<(f32, usize)> = assignments
+                .iter()
+                .enumerate()
+                .filter(|(_, a)| **a == c)
+                .map(|(i, _)| (dot(&points[i], centroid), i))
+                .collect();
+            members.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.cmp(&b.1)));
+            Cluster {
+                members: members.into_iter().map(|(_, i)| i).collect(),
+            }
+        })
+        .filter(|cluster| !cluster.members.is_empty())
+        .collect();
+    clusters.sort_by(|a, b| {
+        b.members
+            .len()
+            .cmp(&a.members.len())
+            .then(a.members[0].cmp(&b.members[0]))
+    });
+    clusters
+}
+
+fn farthest_first_seeds(points: &[Vec<f32>], k: usize) -> Vec<Vec<f32>> {
+    let mut seeds = vec![points[0].clone()];
+    let mut closest: Vec<f32> = points.iter().map(|p| dot(p, &points[0])).collect();
+    while seeds.len() < k {
+        let (next, _) = closest
+            .iter()
+            .enumerate()
+            .min_by(|a, b| a.1.total_cmp(b.1))
+            .expect("points is not empty");
+        seeds.push(points[next].clone());
+        for (similarity, point) in closest.iter_mut().zip(points) {
+            *similarity = similarity.max(dot(point, &points[next]));
+        }
+    }
+    seeds
+}
+
+fn nearest_centroid(point: &[f32], centroids: &[Vec<f32>]) -> usize {
+    centroids
+        .iter()
+        .enumerate()
+        .map(|(c, centroid)| (c, dot(point, centroid)))
+        .max_by(|a, b| a.1.total_cmp(&b.1).then(b.0.cmp(&a.0)))
+        .map(|(c, _)| c)
+        .unwrap_or(0)
+}
+
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+fn normalized(mut v: Vec<f32>) -> Vec<f32> {
+    let norm = dot(&v, &v).sqrt();
+    if norm > 0.0 {
+        for x in &mut v {
+            *x /= norm;
+        }
+    }
+    v
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cluster_separates_topics_largest_first() {
+        let embeddings = vec![
+            vec![1.0, 0.1, 0.0],
+            vec![0.0, 1.0, 0.1],
+            vec![0.9, 0.0, 0.1],
+            vec![0.1, 0.9, 0.0],
+            vec![1.0, 0.0, 0.0],
+        ];
+        let clusters = cluster(&embeddings, 2);
+        assert_eq!(clusters.len(), 2);
+
+        let mut first = clusters[0].members.clone();
+        first.sort();
+        assert_eq!(first, vec![0, 2, 4]);
+        assert_eq!(clusters[0].members[0], 4, "most central member first");
+        let mut second = clusters[1].members.clone();
+        second.sort();
+        assert_eq!(second, vec![1, 3]);
+    }
+
+    #[test]
+    fn test_cluster_with_fewer_points_than_clusters() {
+        assert!(cluster(&[], 3).is_empty());
+
+        let clusters = cluster(&[vec![1.0, 0.0], vec![0.0, 1.0]], 5);
+        assert_eq!(clusters.len(), 2);
+        assert!(clusters.iter().all(|c| c.members.len() == 1));
+    }
+
+    #[test]
+    fn test_cluster_merges_identical_embeddings() {
+        let clusters = cluster(&[vec![1.0, 1.0], vec![2.0, 2.0], vec![0.5, 0.5]], 3);
+        assert_eq!(clusters.len(), 1);
+        assert_eq!(clusters[0].members.len(), 3);
+    }
+}
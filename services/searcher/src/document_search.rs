@@ -0,0 +1,287 @@
+//! Search within a single document.
+//!
+//! Matches are located by line: fulltext matches are the lines containing
+//! query terms, semantic matches are the lines spanned by the document's
+//! chunks closest to the query embedding. Each match is widened to a window
+//! of surrounding lines, overlapping windows are merged, and the windows are
+//! returned in document order.
+
+use crate::models::MatchLocation;
+use shared::content_extractor::{PAGE_BREAK, page_at_offset};
+use shared::models::ChunkResult;
+use std::collections::HashSet;
+
+/// Lines of context kept on each side of a match.
+pub const CONTEXT_LINES: usize = 2;
+/// Upper bound on the text of one window, in characters.
+const MAX_WINDOW_CHARS: usize = 4_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchSource {
+    Fulltext,
+    Semantic,
+    Hybrid,
+}
+
+impl MatchSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MatchSource::Fulltext => "fulltext",
+            MatchSource::Semantic => "semantic",
+            MatchSource::Hybrid => "hybrid",
+        }
+    }
+
+    fn merge(self, other: MatchSource) -> MatchSource {
+        if self == other {
+            self
+        } else {
+            MatchSource::Hybrid
+        }
+    }
+}
+
+/// Lines matched by the query, 0-based and inclusive.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineMatch {
+    pub start_line: usize,
+    pub end_line: usize,
+    pub score: f32,
+    pub source: MatchSource,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DocumentMatch {
+    pub location: MatchLocation,
+    pub score: f32,
+    pub source: MatchSource,
+    /// Page the match starts on, for documents with pages.
+    pub page: Option<i32>,
+    /// The window's lines, each prefixed with its line number.
+    pub text: String,
+}
+
+fn words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+}
+
+/// Lines containing words that start with a query term, scored by the
+/// fraction of distinct terms they contain.
+pub fn fulltext_matches(content: &str, query: &str) -> Vec<LineMatch> {
+    let mut seen = HashSet::new();
+    let terms: Vec<String> = words(query).filter(|t| seen.insert(t.clone())).collect();
+    if terms.is_empty() {
+        return Vec::new();
+    }
+
+    content
+        .lines()
+        .enumerate()
+        .filter_map(|(index, line)| {
+            let line_words: Vec<String> = words(line).collect();
+            let matched = terms
+                .iter()
+                .filter(|term| {
+                    line_words
+                        .iter()
+                        .any(|word| word.starts_with(term.as_str()))
+                })
+                .count();
+            (matched > 0).then(|| LineMatch {
+                start_line: index,
+                end_line: index,
+                score: matched as f32 / terms.len() as f32,
+                source: MatchSource::Fulltext,
+            })
+        })
+        .collect()
+}
+
+/// Byte offset at which each line of `content` starts.
+fn line_starts(content: &str) -> Vec<usize> {
+    std::iter::once(0)
+        .chain(content.match_indices('\n').map(|(i, _)| i + 1))
+        .filter(|&start| start < content.len())
+        .collect()
+}
+
+/// The lines spanned by each chunk, scored by the chunk's similarity.
+pub fn semantic_matches(content: &str, chunks: &[ChunkResult]) -> Vec<LineMatch> {
+    let starts = line_starts(content);
+    if starts.is_empty() {
+        return Vec::new();
+    }
+    let line_of = |offset: usize| starts.partition_point(|&start| start <= offset).max(1) - 1;
+
+    chunks
+        .iter()
+        .filter(|chunk| chunk.chunk_end_offset > chunk.chunk_start_offset)
+        .map(|chunk| {
+            let start = chunk.chunk_start_offset.max(0) as usize;
+            let end = (chunk.chunk_end_offset as usize).min(content.len());
+            LineMatch {
+                start_line: line_of(start),
+                end_line: line_of(end.saturating_sub(1)),
+                score: chunk.similarity_score,
+                source: MatchSource::Semantic,
+            }
+        })
+        .collect()
+}
+
+/// Widen each match by `context_lines` on either side and merge windows that
+/// overlap or touch. Windows keep their best score, and are returned in
+/// document order.
+pub fn build_windows(
+    content: &str,
+    mut matches: Vec<LineMatch>,
+    context_lines: usize,
+) -> Vec<DocumentMatch> {
+    let lines: Vec<&str> = content.lines().collect();
+    if lines.is_empty() {
+        return Vec::new();
+    }
+    let last_line = lines.len() - 1;
+    let starts = line_starts(content);
+    matches.retain(|m| m.start_line <= last_line);
+    matches.sort_by_key(|m| (m.start_line, m.end_line));
+
+    // (window start, window end, match start, match end, score, source)
+    let mut windows: Vec<(usize, usize, usize, usize, f32, MatchSource)> = Vec::new();
+    for m in matches {
+        let end_line = m.end_line.min(last_line);
+        let start = m.start_line.saturating_sub(context_lines);
+        let end = (end_line + context_lines).min(last_line);
+        match windows.last_mut() {
+            Some(window) if start <= window.1 + 1 => {
+                window.1 = window.1.max(end);
+                window.3 = window.3.max(end_line);
+                window.4 = window.4.max(m.score);
+                window.5 = window.5.merge(m.source);
+            }
+            _ => windows.push((start, end, m.start_line, end_line, m.score, m.source)),
+        }
+    }
+
+    windows
+        .into_iter()
+        .map(
+            |(start, end, match_start, match_end, score, source)| DocumentMatch {
+                location: MatchLocation {
+                    start_line: start as u32 + 1,
+                    end_line: end as u32 + 1,
+                    match_start_line: match_start as u32 + 1,
+                    match_end_line: match_end as u32 + 1,
+                },
+                score,
+                source,
+                // A line can begin with the break that starts its page.
+                page: starts.get(match_start).and_then(|&offset| {
+                    let breaks = lines[match_start].len()
+                        - lines[match_start].trim_start_matches(PAGE_BREAK).len();
+                    page_at_offset(content, offset + breaks)
+                }),
+                text: lines[start..=end]
+                    .iter()
+                    .enumerate()
+                    .map(|(i, line)| format!("{} | {}", start + i + 1, line))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+                    .chars()
+                    .take(MAX_WINDOW_CHARS)
+                    .collect(),
+            },
+        )
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONTENT: &str = "Intro\nThe deploy checklist\nStep one\nStep two\nStep three\nStep four\nStep five\nRollback plan for deploys\nOutro";
+
+    #[test]
+    fn test_fulltext_matches_terms_by_word_prefix() {
+        let matches = fulltext_matches(CONTENT, "deploy rollback");
+        let lines: Vec<(usize, f32)> = matches.iter().map(|m| (m.start_line, m.score)).collect();
+        assert_eq!(lines, vec![(1, 0.5), (7, 1.0)]);
+        assert!(fulltext_matches(CONTENT, "  ").is_empty());
+    }
+
+    #[test]
+    fn test_semantic_matches_map_offsets_to_lines() {
+        let start = CONTENT.find("Step two").unwrap() as i32;
+        let end = (CONTENT.find("Step four").unwrap() + "Step four".len()) as i32;
+        let chunk = ChunkResult {
+            document_id: "doc".to_string(),
+            similarity_score: 0.8,
+            chunk_start_offset: start,
+            chunk_end_offset: end,
+            chunk_index: 1,
+            page_number: None,
+            heading_path: None,
+        };
+        let matches = semantic_matches(CONTENT, &[chunk]);
+        assert_eq!(matches.len(), 1);
+        assert_eq!((matches[0].start_line, matches[0].end_line), (3, 5));
+    }
+
+    #[test]
+    fn test_build_windows_merges_overlaps_in_document_order() {
+        let matches = vec![
+            LineMatch {
+                start_line: 7,
+                end_line: 7,
+                score: 1.0,
+                source: MatchSource::Fulltext,
+            },
+            LineMatch {
+                start_line: 1,
+                end_line: 1,
+                score: 0.5,
+                source: MatchSource::Fulltext,
+            },
+            LineMatch {
+                start_line: 3,
+                end_line: 3,
+                score: 0.7,
+                source: MatchSource::Semantic,
+            },
+        ];
+        let windows = build_windows(CONTENT, matches, 1);
+        assert_eq!(windows.len(), 2);
+
+        assert_eq!(
+            windows[0].location,
+            MatchLocation {
+                start_line: 1,
+                end_line: 5,
+                match_start_line: 2,
+                match_end_line: 4,
+            }
+        );
+        assert_eq!(windows[0].score, 0.7);
+        assert_eq!(windows[0].source, MatchSource::Hybrid);
+        assert!(
+            windows[0]
+                .text
+                .starts_with("1 | Intro\n2 | The deploy checklist")
+        );
+
+        assert_eq!(windows[1].location.start_line, 7);
+        assert_eq!(windows[1].location.end_line, 9);
+        assert_eq!(windows[1].source, MatchSource::Fulltext);
+        assert_eq!(windows[1].page, None);
+    }
+
+    #[test]
+    fn test_build_windows_reports_page_of_match() {
+        let content = "Cover\n\x0cContents\nBudget overview\n\x0cBudget details\n";
+        let windows = build_windows(content, fulltext_matches(content, "details"), 0);
+        assert_eq!(windows.len(), 1);
+        assert_eq!(windows[0].page, Some(3));
+    }
+}
@@ -0,0 +1,71 @@
+use crate::SearcherError;
+use crate::models::{FieldError, SearchRequest};
+use crate::share_links::validate_share_link_policy;
+use crate::source_boosts::{SourceBoostsSettings, validate_source_boosts};
+use async_trait::async_trait;
+use axum::body::Bytes;
+use axum::extract::{FromRequest, Request};
+use serde::de::DeserializeOwned;
+use shared::db::repositories::ShareLinkPolicy;
+
+/// Request bodies that can report field-level problems beyond what
+/// deserialization already checks.
+pub trait Validate {
+    fn validate(&self) -> Vec<FieldError>;
+}
+
+impl Validate for SearchRequest {
+    fn validate(&self) -> Vec<FieldError> {
+        SearchRequest::validate(self)
+    }
+}
+
+impl Validate for SourceBoostsSettings {
+    fn validate(&self) -> Vec<FieldError> {
+        validate_source_boosts("boosts", &self.boosts)
+    }
+}
+
+impl Validate for ShareLinkPolicy {
+    fn validate(&self) -> Vec<FieldError> {
+        validate_share_link_policy(self)
+    }
+}
+
+/// JSON body extractor that rejects with a 422 listing per-field errors,
+/// instead of axum's plain-text rejection. Deserialization failures carry the
+/// offending path (e.g. `source_types[1]`).
+pub struct ValidatedJson<T>(pub T);
+
+#[async_trait]
+impl<S, T> FromRequest<S> for ValidatedJson<T>
+where
+    S: Send + Sync,
+    T: DeserializeOwned + Validate,
+{
+    type Rejection = SearcherError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let body = Bytes::from_request(req, state)
+            .await
+            .map_err(|e| SearcherError::BadRequest(e.body_text()))?;
+
+        let deserializer = &mut serde_json::Deserializer::from_slice(&body);
+        let value: T = serde_path_to_error::deserialize(deserializer).map_err(|e| {
+            let path = e.path().to_string();
+            let field = if path == "." {
+                "body".to_string()
+            } else {
+                path
+            };
+            SearcherError::Validation(vec![FieldError::new(field, e.into_inner().to_string())])
+        })?;
+
+        let errors = value.validate();
+        if !errors.is_empty() {
+            return Err(SearcherError::Validation(errors));
+        }
+
+        Ok(Self(value))
+    }
+}
@@ -1,35 +1,74 @@
+use crate::answer::{build_answer_prompt, resolve_citations, AnswerResponse, AnswerStreamEvent};
 use crate::capabilities_repository::AgentCapabilitiesRepository;
+use crate::collections::{
+    normalize_groups, Collection, CollectionRepository, CollectionViewer, CollectionVisibility,
+};
+use crate::conditional::ContentValidators;
+use crate::extract::ValidatedJson;
 use crate::models::{
-    AttributeValuesResponse, CapabilitiesSyncRequest, CapabilitiesSyncResponse,
-    CapabilitiesUpsertRequest, CapabilitiesUpsertResponse, CapabilitySearchRequest,
-    CapabilitySearchResponse, PeopleSearchResponse, PersonResult, RecentSearchesRequest,
-    SearchRequest, SuggestedQuestionsRequest, SuggestedQuestionsResponse, TypeaheadQuery,
-    TypeaheadResponse,
+    AddCollectionDocumentsRequest, AddCollectionDocumentsResponse, AttributeValuesResponse,
+    CapabilitiesSyncRequest, CapabilitiesSyncResponse, CapabilitiesUpsertRequest,
+    CapabilitiesUpsertResponse, CapabilitySearchRequest, CapabilitySearchResponse,
+    CollectionResponse, CollectionUserQuery, CreateCollectionRequest, CreateShareLinkRequest,
+    CreateShareLinkResponse, FieldSelection, PeopleSearchResponse, PersonResult,
+    RecentSearchesRequest, SearchRequest, SearchResponse, SearchStreamEnvelope, SearchStreamStage,
+    ShareLinkUserQuery, SharedDocumentResponse, SuggestedQuestionsQuery,
+    SuggestedQuestionsResponse, TypeaheadGroup, TypeaheadGroupType, TypeaheadQuery,
+    TypeaheadResponse, TypeaheadResult, TypeaheadSuggestion, UpdateCollectionRequest,
+};
+use crate::rag_provenance::{
+    ContextEntry, RagProvenance, RagProvenanceQuery, RagProvenanceRepository,
+};
+use crate::search::{search_history_key, SearchEngine};
+use crate::search_analytics::{
+    SearchAnalyticsQuery, SearchAnalyticsReport, SearchAnalyticsRepository, SearchEvent,
 };
-use crate::search::SearchEngine;
 use crate::search_repository::SearchDocumentRepository;
+use crate::share_links::{allow_fetch, resolve_link_limits};
+use crate::sla::SlaStatus;
+use crate::source_boosts::{SourceBoostRepository, SourceBoostsSettings};
+use crate::source_router::SearchClick;
+use crate::typeahead::Viewer;
 use crate::{AppState, Result as SearcherResult, SearcherError};
 use anyhow::anyhow;
 use axum::body::Body;
 use axum::{
-    extract::{Query, State},
-    http::StatusCode,
-    response::Json,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::sse::{Event, KeepAlive, Sse},
+    response::{IntoResponse, Json, Response},
 };
-use futures_util::Stream;
+use futures_util::{Stream, StreamExt};
 use redis::AsyncCommands;
 use serde_json::{json, Value};
 use shared::{
-    models::UserConfiguration, ConfigurationRepository, PersonRepository, Repository,
-    UserRepository,
+    audit::{self, Actor, AuditAction, AuditEvent},
+    db::repositories::{
+        DocumentShareLink, DocumentShareLinkRepository, ShareLinkPolicy, WorkspaceRepository,
+    },
+    models::{Document, UserConfiguration, DEFAULT_WORKSPACE_ID},
+    ConfigurationRepository, DocumentRepository, GroupRepository, PersonRepository, Repository,
+    SourceRepository, UserRepository,
 };
 use sqlx::types::time::OffsetDateTime;
+use sqlx::PgPool;
+use std::collections::HashSet;
+use std::convert::Infallible;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
-use tokio::sync::Mutex;
+use std::time::Instant;
+use tokio::sync::{mpsc, Mutex};
+use tokio_stream::wrappers::UnboundedReceiverStream;
 use tracing::{debug, error, info};
 
+/// Title-index candidates fetched per requested document suggestion, to leave
+/// room for results the user cannot see.
+const TYPEAHEAD_DOCUMENT_OVERFETCH: usize = 4;
+
+/// Response header carrying the id of the answer's provenance record.
+pub const RAG_PROVENANCE_HEADER: &str = "X-Rag-Provenance-Id";
+
 /// A stream wrapper that collects chunks for caching while forwarding them to the client
 struct CachingStream<S> {
     inner: S,
@@ -125,6 +164,66 @@ async fn hydrate_user_configuration(
     Ok(())
 }
 
+async fn collection_viewer(
+    state: &AppState,
+    user_id: Option<&str>,
+    user_email: Option<&str>,
+) -> SearcherResult<CollectionViewer> {
+    let groups = match user_email {
+        Some(email) => GroupRepository::new(state.db_pool.pool())
+            .find_groups_for_user(email)
+            .await
+            .map_err(|error| SearcherError::Internal(anyhow!(error)))?,
+        None => Vec::new(),
+    };
+    let workspace_id = match user_id {
+        Some(user_id) => WorkspaceRepository::new(state.db_pool.pool())
+            .workspace_id_for_user(user_id)
+            .await
+            .map_err(|error| SearcherError::Internal(anyhow!(error)))?,
+        None => DEFAULT_WORKSPACE_ID.to_string(),
+    };
+    Ok(CollectionViewer::new(
+        user_id.map(str::to_string),
+        groups,
+        workspace_id,
+    ))
+}
+
+/// Workspace of the user with `user_email`; the default workspace for
+/// callers without one, like the documents they can read.
+async fn workspace_of(state: &AppState, user_email: Option<&str>) -> SearcherResult<String> {
+    match user_email {
+        Some(email) => WorkspaceRepository::new(state.db_pool.pool())
+            .workspace_id_for_email(email)
+            .await
+            .map_err(|error| SearcherError::Internal(anyhow!(error))),
+        None => Ok(DEFAULT_WORKSPACE_ID.to_string()),
+    }
+}
+
+/// Reject searches scoped to a collection the user cannot see.
+async fn authorize_collection_scope(
+    state: &AppState,
+    request: &SearchRequest,
+) -> SearcherResult<()> {
+    let Some(collection_id) = request.collection_id.as_deref() else {
+        return Ok(());
+    };
+
+    let viewer = collection_viewer(
+        state,
+        request.user_id.as_deref(),
+        request.user_email().map(|e| e.as_str()),
+    )
+    .await?;
+    CollectionRepository::new(state.db_pool.pool())
+        .get_visible(collection_id, &viewer)
+        .await?
+        .map(|_| ())
+        .ok_or_else(|| SearcherError::NotFound(format!("Collection {}", collection_id)))
+}
+
 pub async fn health_check(State(state): State<AppState>) -> SearcherResult<Json<Value>> {
     sqlx::query("SELECT 1")
         .execute(state.db_pool.pool())
@@ -147,23 +246,144 @@ pub async fn health_check(State(state): State<AppState>) -> SearcherResult<Json<
     })))
 }
 
+/// Store search history if user_id is provided. Failures are logged, never
+/// surfaced to the caller.
+async fn store_search_history(search_engine: &SearchEngine, request: &SearchRequest) {
+    if let Some(user_id) = &request.user_id {
+        let is_generated = request.is_generated_query.unwrap_or(false);
+
+        let query_to_store = if is_generated {
+            // For AI-generated queries, only cache if original_user_query is provided
+            request.original_user_query.as_ref()
+        } else {
+            // For user queries, cache the query itself
+            Some(&request.query)
+        };
+
+        if let Some(query) = query_to_store
+            && let Err(e) = search_engine.store_search_history(user_id, query).await
+        {
+            // Log the error but don't fail the search request
+            error!("Failed to store search history: {}", e);
+        }
+    }
+}
+
+/// Log a search for analytics and return its event id. Document reads are
+/// not searches and are not logged; failures are logged and swallowed.
+async fn record_search_event(
+    analytics: &SearchAnalyticsRepository,
+    request: &SearchRequest,
+    response: &SearchResponse,
+    started_at: Instant,
+) -> Option<String> {
+    if request
+        .document_id
+        .as_deref()
+        .is_some_and(|id| !id.trim().is_empty())
+    {
+        return None;
+    }
+
+    let event = SearchEvent {
+        user_id: request.user_id.as_deref(),
+        query: &request.query,
+        search_mode: request.search_mode(),
+        is_generated: request.is_generated_query.unwrap_or(false),
+        result_count: response.total_count,
+        latency_ms: started_at.elapsed().as_millis() as u64,
+        impressions: &response.ranking_impressions,
+    };
+    match analytics.record(&event).await {
+        Ok(id) => Some(id),
+        Err(e) => {
+            error!("Failed to record search event: {}", e);
+            None
+        }
+    }
+}
+
+/// Add a search or document read to the audit log. The user the request is
+/// made for is the actor when it names one.
+async fn record_search_audit(
+    pool: &PgPool,
+    actor: Actor,
+    request: &SearchRequest,
+    response: &SearchResponse,
+) {
+    let actor = if request.user_id.is_some() || request.user_email.is_some() {
+        Actor::user(request.user_id.as_deref(), request.user_email.as_deref())
+    } else {
+        actor
+    };
+    let event = match request
+        .document_id
+        .as_deref()
+        .filter(|id| !id.trim().is_empty())
+    {
+        Some(document_id) => {
+            AuditEvent::new(actor, AuditAction::DocumentRead).resource("document", document_id)
+        }
+        None => AuditEvent::new(actor, AuditAction::Search).details(json!({
+            "query": request.query,
+            "mode": request.search_mode().as_str(),
+            "result_count": response.total_count,
+        })),
+    };
+    audit::record(pool, event).await;
+}
+
+/// Search, or read a document when `document_id` is set. Document reads carry
+/// `ETag` and `Last-Modified` and answer conditional requests with 304.
 pub async fn search(
     State(state): State<AppState>,
-    Json(mut request): Json<SearchRequest>,
-) -> SearcherResult<Json<Value>> {
+    actor: Actor,
+    headers: HeaderMap,
+    ValidatedJson(mut request): ValidatedJson<SearchRequest>,
+) -> SearcherResult<Response> {
     info!("Received search request: {:?}", request);
     hydrate_user_configuration(&state, &mut request).await?;
+    authorize_collection_scope(&state, &request).await?;
+
+    let validators = match request
+        .document_id
+        .as_deref()
+        .filter(|id| !id.trim().is_empty())
+    {
+        Some(document_id) => DocumentRepository::new(state.db_pool.pool())
+            .find_content_version(document_id)
+            .await
+            .map_err(|e| SearcherError::Internal(anyhow!("Document lookup failed: {}", e)))?
+            .map(|version| ContentValidators::new(&version, &request)),
+        None => None,
+    };
+    if let Some(validators) = &validators
+        && validators.is_not_modified(&headers)
+    {
+        debug!("Document {:?} not modified", request.document_id);
+        return Ok(validators.not_modified());
+    }
 
+    let pool = state.db_pool.pool().clone();
+    let analytics = SearchAnalyticsRepository::new(&pool);
     let search_engine = SearchEngine::new(
         state.db_pool,
         state.redis_client,
         state.ai_client,
         state.config,
         state.operator_registry,
+        state.source_router,
+        state.query_intent,
+        state.query_language,
+        state.query_understanding,
+        state.sla_monitor,
+        state.spell_checker,
+        state.learned_ranker,
     )
     .await?;
 
-    let response = match search_engine.search(request.clone()).await {
+    let started_at = Instant::now();
+    let mut response = match search_engine.search(request.clone()).await {
         Ok(response) => response,
         Err(e) => {
             error!("Search engine error: {}", e);
@@ -171,27 +391,152 @@ pub async fn search(
         }
     };
 
-    // Store search history if user_id is provided
-    if let Some(user_id) = &request.user_id {
-        let is_generated = request.is_generated_query.unwrap_or(false);
+    store_search_history(&search_engine, &request).await;
+    response.search_event_id =
+        record_search_event(&analytics, &request, &response, started_at).await;
+    record_search_audit(&pool, actor, &request, &response).await;
 
-        let query_to_store = if is_generated {
-            // For AI-generated queries, only cache if original_user_query is provided
-            request.original_user_query.as_ref()
-        } else {
-            // For user queries, cache the query itself
-            Some(&request.query)
+    let selection = request
+        .field_selection()
+        .map_err(SearcherError::Validation)?;
+    let mut response = Json(response.to_value(selection.as_ref())?).into_response();
+    if let Some(validators) = &validators {
+        validators.apply(response.headers_mut());
+    }
+    Ok(response)
+}
+
+/// Search as server-sent events. Hybrid searches first send a `partial`
+/// event with the fulltext hits, then a `final` event with the fully ranked
+/// response; other modes and cached responses send only the `final` event.
+/// Failures after the stream has started are sent as an `error` event.
+pub async fn search_stream(
+    State(state): State<AppState>,
+    actor: Actor,
+    ValidatedJson(mut request): ValidatedJson<SearchRequest>,
+) -> SearcherResult<Sse<impl Stream<Item = Result<Event, Infallible>>>> {
+    info!("Received streaming search request: {:?}", request);
+    hydrate_user_configuration(&state, &mut request).await?;
+    authorize_collection_scope(&state, &request).await?;
+    let selection = request
+        .field_selection()
+        .map_err(SearcherError::Validation)?;
+
+    let pool = state.db_pool.pool().clone();
+    let analytics = SearchAnalyticsRepository::new(&pool);
+    let search_engine = SearchEngine::new(
+        state.db_pool,
+        state.redis_client,
+        state.ai_client,
+        state.config,
+        state.operator_registry,
+        state.source_router,
+        state.query_intent,
+        state.query_language,
+        state.query_understanding,
+        state.sla_monitor,
+        state.spell_checker,
+        state.learned_ranker,
+    )
+    .await?;
+
+    let (events_tx, events_rx) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        let start_time = Instant::now();
+        let (partial_tx, mut partial_rx) = mpsc::unbounded_channel();
+        let search = search_engine.search_streaming(request.clone(), partial_tx);
+        tokio::pin!(search);
+
+        let result = loop {
+            tokio::select! {
+                biased;
+                Some(partial) = partial_rx.recv() => {
+                    let response = SearchResponse {
+                        has_more: request.offset() + request.limit() < partial.total_count,
+                        results: partial.results,
+                        total_count: partial.total_count,
+                        query_time_ms: start_time.elapsed().as_millis() as u64,
+                        query: request
+                            .original_user_query
+                            .clone()
+                            .unwrap_or(request.query.clone()),
+                        facets: None,
+                        active_filters: None,
+                        did_you_mean: None,
+                        corrected_query: None,
+                        source_routing: None,
+                        personalization: None,
+                        intent: None,
+                        query_understanding: None,
+                        search_event_id: None,
+                        ranking_impressions: Vec::new(),
+                    };
+                    let event =
+                        search_stream_event(SearchStreamStage::Partial, &response, &selection);
+                    let _ = events_tx.send(event);
+                }
+                result = &mut search => break result,
+            }
         };
 
-        if let Some(query) = query_to_store {
-            if let Err(e) = search_engine.store_search_history(user_id, query).await {
-                // Log the error but don't fail the search request
-                error!("Failed to store search history: {}", e);
+        let event = match result {
+            Ok(mut response) => {
+                store_search_history(&search_engine, &request).await;
+                response.search_event_id =
+                    record_search_event(&analytics, &request, &response, start_time).await;
+                record_search_audit(&pool, actor, &request, &response).await;
+                search_stream_event(SearchStreamStage::Final, &response, &selection)
             }
-        }
+            Err(e) => {
+                error!("Search engine error: {}", e);
+                search_stream_error(&e.to_string())
+            }
+        };
+        let _ = events_tx.send(event);
+    });
+
+    Ok(Sse::new(UnboundedReceiverStream::new(events_rx).map(Ok)).keep_alive(KeepAlive::default()))
+}
+
+fn search_stream_event(
+    stage: SearchStreamStage,
+    response: &SearchResponse,
+    selection: &Option<FieldSelection>,
+) -> Event {
+    let envelope = match response.to_value(selection.as_ref()) {
+        Ok(response) => SearchStreamEnvelope { stage, response },
+        Err(e) => return search_stream_error(&e.to_string()),
+    };
+    Event::default()
+        .event(stage.as_str())
+        .json_data(envelope)
+        .unwrap_or_else(|e| search_stream_error(&e.to_string()))
+}
+
+fn search_stream_error(message: &str) -> Event {
+    Event::default()
+        .event("error")
+        .data(json!({ "error": message }).to_string())
+}
+
+pub async fn record_click(
+    State(state): State<AppState>,
+    Json(click): Json<SearchClick>,
+) -> SearcherResult<StatusCode> {
+    if click.query.trim().is_empty() {
+        return Err(SearcherError::BadRequest(
+            "query must not be empty".to_string(),
+        ));
     }
 
-    Ok(Json(serde_json::to_value(response)?))
+    if !state.source_router.record_click(&click).await? {
+        return Err(SearcherError::NotFound(format!(
+            "Document {}",
+            click.document_id
+        )));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
 }
 
 pub async fn recent_searches(
@@ -209,6 +554,13 @@ pub async fn recent_searches(
         state.ai_client,
         state.config,
         state.operator_registry,
+        state.source_router,
+        state.query_intent,
+        state.query_language,
+        state.query_understanding,
+        state.sla_monitor,
+        state.spell_checker,
+        state.learned_ranker,
     )
     .await?;
 
@@ -219,12 +571,18 @@ pub async fn recent_searches(
 
 pub async fn ai_answer(
     State(state): State<AppState>,
-    Json(mut request): Json<SearchRequest>,
+    ValidatedJson(mut request): ValidatedJson<SearchRequest>,
 ) -> Result<axum::response::Response<Body>, axum::http::StatusCode> {
     info!("Received AI answer request: {:?}", request);
     hydrate_user_configuration(&state, &mut request)
         .await
         .map_err(|_| axum::http::StatusCode::BAD_REQUEST)?;
+    authorize_collection_scope(&state, &request)
+        .await
+        .map_err(|e| match e {
+            SearcherError::NotFound(_) => StatusCode::NOT_FOUND,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        })?;
 
     let search_engine = SearchEngine::new(
         state.db_pool.clone(),
@@ -232,6 +590,13 @@ pub async fn ai_answer(
         state.ai_client.clone(),
         state.config.clone(),
         state.operator_registry.clone(),
+        state.source_router.clone(),
+        state.query_intent.clone(),
+        state.query_language.clone(),
+        state.query_understanding.clone(),
+        state.sla_monitor.clone(),
+        state.spell_checker.clone(),
+        state.learned_ranker.clone(),
     )
     .await
     .map_err(|_| axum::http::StatusCode::INTERNAL_SERVER_ERROR)?;
@@ -265,8 +630,25 @@ pub async fn ai_answer(
         }
     };
 
+    // Answers are only generated once their provenance is on record
+    let provenance_id = match RagProvenanceRepository::new(state.db_pool.pool())
+        .record(
+            request.user_id.as_deref(),
+            &request.query,
+            &context.permissions,
+            &context.provenance,
+        )
+        .await
+    {
+        Ok(id) => id,
+        Err(e) => {
+            error!("Failed to record RAG provenance: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
     // Build RAG prompt with context and citation instructions
-    let prompt = search_engine.build_rag_prompt(&request.query, &context);
+    let prompt = search_engine.build_rag_prompt(&request.query, &context.results);
     info!("Built RAG prompt of length: {}", prompt.len());
     debug!("RAG prompt: {}", prompt);
 
@@ -288,28 +670,307 @@ pub async fn ai_answer(
         .header("Content-Type", "text/plain; charset=utf-8")
         .header("Cache-Control", "no-cache")
         .header("Connection", "keep-alive")
+        .header(RAG_PROVENANCE_HEADER, provenance_id)
         .body(Body::from_stream(caching_stream))
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
     Ok(response)
 }
 
+/// A prompt ready to send for `/answer`, with the context it cites.
+struct PreparedAnswer {
+    prompt: String,
+    context: Vec<ContextEntry>,
+    provenance_id: String,
+}
+
+/// Retrieve context for an answer and record its provenance.
+async fn prepare_answer(
+    state: &AppState,
+    mut request: SearchRequest,
+) -> SearcherResult<PreparedAnswer> {
+    hydrate_user_configuration(state, &mut request).await?;
+    authorize_collection_scope(state, &request).await?;
+
+    let search_engine = SearchEngine::new(
+        state.db_pool.clone(),
+        state.redis_client.clone(),
+        state.ai_client.clone(),
+        state.config.clone(),
+        state.operator_registry.clone(),
+        state.source_router.clone(),
+        state.query_intent.clone(),
+        state.query_language.clone(),
+        state.query_understanding.clone(),
+        state.sla_monitor.clone(),
+        state.spell_checker.clone(),
+        state.learned_ranker.clone(),
+    )
+    .await?;
+
+    let context = search_engine.get_rag_context(&request).await?;
+    // Answers are only generated once their provenance is on record
+    let provenance_id = RagProvenanceRepository::new(state.db_pool.pool())
+        .record(
+            request.user_id.as_deref(),
+            &request.query,
+            &context.permissions,
+            &context.provenance,
+        )
+        .await?;
+
+    let prompt = build_answer_prompt(&request.query, &context.results);
+    debug!("Answer prompt: {}", prompt);
+    Ok(PreparedAnswer {
+        prompt,
+        context: context.provenance,
+        provenance_id,
+    })
+}
+
+/// Answer a query from retrieved context, with citations resolved to the
+/// documents and chunk ranges the answer relies on.
+pub async fn answer(
+    State(state): State<AppState>,
+    ValidatedJson(request): ValidatedJson<SearchRequest>,
+) -> SearcherResult<Response> {
+    info!("Received answer request: {:?}", request);
+    let prepared = prepare_answer(&state, request).await?;
+
+    let mut ai_stream = state.ai_client.stream_prompt(&prepared.prompt).await?;
+    let mut answer = String::new();
+    while let Some(chunk) = ai_stream.next().await {
+        answer.push_str(&chunk?);
+    }
+
+    let response = AnswerResponse {
+        citations: resolve_citations(&answer, &prepared.context),
+        answer,
+        provenance_id: prepared.provenance_id.clone(),
+    };
+    Ok((
+        [(RAG_PROVENANCE_HEADER, prepared.provenance_id)],
+        Json(response),
+    )
+        .into_response())
+}
+
+/// `/answer` as server-sent events: `delta` events with the answer text as
+/// the model produces it, then a `final` event with the whole answer and its
+/// citations. Failures after the stream has started are sent as an `error`
+/// event.
+pub async fn answer_stream(
+    State(state): State<AppState>,
+    ValidatedJson(request): ValidatedJson<SearchRequest>,
+) -> SearcherResult<Response> {
+    info!("Received streaming answer request: {:?}", request);
+    let prepared = prepare_answer(&state, request).await?;
+    let mut ai_stream = state.ai_client.stream_prompt(&prepared.prompt).await?;
+
+    let provenance_id = prepared.provenance_id.clone();
+    let (events_tx, events_rx) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        let mut answer = String::new();
+        while let Some(chunk) = ai_stream.next().await {
+            match chunk {
+                Ok(text) => {
+                    answer.push_str(&text);
+                    let _ = events_tx.send(answer_stream_event(AnswerStreamEvent::Delta { text }));
+                }
+                Err(e) => {
+                    error!("AI stream error: {}", e);
+                    let _ = events_tx.send(search_stream_error(&e.to_string()));
+                    return;
+                }
+            }
+        }
+
+        let response = AnswerResponse {
+            citations: resolve_citations(&answer, &prepared.context),
+            answer,
+            provenance_id: prepared.provenance_id,
+        };
+        let _ = events_tx.send(answer_stream_event(AnswerStreamEvent::Final(response)));
+    });
+
+    let sse = Sse::new(UnboundedReceiverStream::new(events_rx).map(Ok::<_, Infallible>))
+        .keep_alive(KeepAlive::default());
+    Ok(([(RAG_PROVENANCE_HEADER, provenance_id)], sse).into_response())
+}
+
+fn answer_stream_event(event: AnswerStreamEvent) -> Event {
+    Event::default()
+        .event(event.name())
+        .json_data(&event)
+        .unwrap_or_else(|e| search_stream_error(&e.to_string()))
+}
+
 pub async fn typeahead(
     State(state): State<AppState>,
     Query(query): Query<TypeaheadQuery>,
 ) -> SearcherResult<Json<Value>> {
-    let results = state.title_index.search(&query.q, query.limit()).await;
+    let mut results = Vec::new();
+    let mut groups = Vec::new();
+
+    for group_type in query.groups() {
+        let limit = query.group_limit(group_type);
+        let suggestions = match group_type {
+            TypeaheadGroupType::Documents => {
+                results = typeahead_documents(&state, &query, limit).await?;
+                results
+                    .iter()
+                    .cloned()
+                    .map(TypeaheadSuggestion::Document)
+                    .collect()
+            }
+            TypeaheadGroupType::People => typeahead_people(&state, &query, limit).await?,
+            TypeaheadGroupType::Sources => typeahead_sources(&state, &query, limit).await?,
+            TypeaheadGroupType::Queries => typeahead_queries(&state, &query, limit).await?,
+        };
+        groups.push(TypeaheadGroup {
+            group_type,
+            suggestions,
+        });
+    }
+
     let response = TypeaheadResponse {
         results,
+        groups,
         query: query.q,
     };
     Ok(Json(serde_json::to_value(response)?))
 }
 
-// TODO: Make this a GET request, this should not be POST
+/// Title matches from the in-memory index, which only returns titles the
+/// user's principals grant. The database then confirms them, which also
+/// applies workspace boundaries the index does not know about; over-fetch
+/// so that trimming still leaves enough.
+async fn typeahead_documents(
+    state: &AppState,
+    query: &TypeaheadQuery,
+    limit: usize,
+) -> SearcherResult<Vec<TypeaheadResult>> {
+    let user_email = query.user_email.as_deref();
+    let user_groups = match user_email {
+        Some(email) => GroupRepository::new(state.db_pool.pool())
+            .find_groups_for_user(email)
+            .await
+            .map_err(|error| SearcherError::Internal(anyhow!(error)))?,
+        None => vec![],
+    };
+    let viewer = match user_email {
+        Some(email) => Viewer::user(email, &user_groups),
+        None => Viewer::anonymous(),
+    };
+
+    let candidates = state
+        .title_index
+        .search(&query.q, &viewer, limit * TYPEAHEAD_DOCUMENT_OVERFETCH)
+        .await;
+    if candidates.is_empty() {
+        return Ok(candidates);
+    }
+
+    let candidate_ids: Vec<String> = candidates.iter().map(|r| r.document_id.clone()).collect();
+    let accessible: HashSet<String> = user_document_repository(state)
+        .filter_accessible_ids(&candidate_ids, user_email, &user_groups)
+        .await
+        .map_err(|e| SearcherError::Internal(anyhow!("Typeahead permission check failed: {}", e)))?
+        .into_iter()
+        .collect();
+
+    Ok(candidates
+        .into_iter()
+        .filter(|r| accessible.contains(&r.document_id))
+        .take(limit)
+        .collect())
+}
+
+async fn typeahead_people(
+    state: &AppState,
+    query: &TypeaheadQuery,
+    limit: usize,
+) -> SearcherResult<Vec<TypeaheadSuggestion>> {
+    let Some(user_email) = query.user_email.as_deref() else {
+        return Ok(vec![]);
+    };
+    if query.q.trim().is_empty() {
+        return Ok(vec![]);
+    }
+
+    let workspace_id = workspace_of(state, Some(user_email)).await?;
+    let people = PersonRepository::new(state.db_pool.pool())
+        .search_people(&query.q, &workspace_id, limit as i64)
+        .await
+        .map_err(|e| SearcherError::Internal(anyhow!("People search failed: {}", e)))?;
+
+    Ok(people
+        .into_iter()
+        .map(|p| TypeaheadSuggestion::Person {
+            id: p.id,
+            email: p.email,
+            display_name: p.display_name,
+            job_title: p.job_title,
+        })
+        .collect())
+}
+
+async fn typeahead_sources(
+    state: &AppState,
+    query: &TypeaheadQuery,
+    limit: usize,
+) -> SearcherResult<Vec<TypeaheadSuggestion>> {
+    let Some(user_id) = query.user_id.as_deref() else {
+        return Ok(vec![]);
+    };
+    if query.q.trim().is_empty() {
+        return Ok(vec![]);
+    }
+
+    let sources = SourceRepository::new(state.db_pool.pool())
+        .search_visible_sources(query.q.trim(), user_id, limit as i64)
+        .await
+        .map_err(|e| SearcherError::Internal(anyhow!("Source search failed: {}", e)))?;
+
+    Ok(sources
+        .into_iter()
+        .map(|s| TypeaheadSuggestion::Source {
+            source_id: s.id,
+            name: s.name,
+            source_type: s.source_type,
+        })
+        .collect())
+}
+
+/// The user's own previous searches that contain the typed text.
+async fn typeahead_queries(
+    state: &AppState,
+    query: &TypeaheadQuery,
+    limit: usize,
+) -> SearcherResult<Vec<TypeaheadSuggestion>> {
+    let Some(user_id) = query.user_id.as_deref() else {
+        return Ok(vec![]);
+    };
+
+    let key = search_history_key(&state.db_pool, user_id).await?;
+    let mut conn = state
+        .redis_client
+        .get_multiplexed_async_connection()
+        .await?;
+    let searches: Vec<String> = conn.lrange(&key, 0, -1).await.unwrap_or_default();
+
+    let needle = query.q.trim().to_lowercase();
+    Ok(searches
+        .into_iter()
+        .filter(|s| s.to_lowercase().contains(&needle) && !s.eq_ignore_ascii_case(query.q.trim()))
+        .take(limit)
+        .map(|query| TypeaheadSuggestion::Query { query })
+        .collect())
+}
+
 pub async fn suggested_questions(
     State(state): State<AppState>,
-    Json(request): Json<SuggestedQuestionsRequest>,
+    Query(request): Query<SuggestedQuestionsQuery>,
 ) -> SearcherResult<Json<SuggestedQuestionsResponse>> {
     info!("Received suggested questions request");
 
@@ -339,7 +1000,13 @@ pub async fn suggested_questions(
 
     let response = state
         .suggested_questions_generator
-        .get_suggested_questions(&user.email)
+        .get_suggested_questions(
+            &user.id,
+            &user.email,
+            &user.workspace_id,
+            request.limit(),
+            request.offset(),
+        )
         .await?;
 
     Ok(Json(response))
@@ -349,6 +1016,8 @@ pub async fn suggested_questions(
 pub struct PeopleSearchQuery {
     pub q: String,
     pub limit: Option<i64>,
+    /// Only people of this user's workspace are returned.
+    pub user_email: Option<String>,
 }
 
 pub async fn people_search(
@@ -357,9 +1026,10 @@ pub async fn people_search(
 ) -> SearcherResult<Json<PeopleSearchResponse>> {
     let person_repo = PersonRepository::new(state.db_pool.pool());
     let limit = query.limit.unwrap_or(10).min(50);
+    let workspace_id = workspace_of(&state, query.user_email.as_deref()).await?;
 
     let results = person_repo
-        .search_people(&query.q, limit)
+        .search_people(&query.q, &workspace_id, limit)
         .await
         .map_err(|e| SearcherError::Internal(anyhow!("People search failed: {}", e)))?;
 
@@ -513,3 +1183,491 @@ pub async fn capabilities_search(
 
     Ok(Json(CapabilitySearchResponse { results }))
 }
+
+pub async fn search_sla_status(State(state): State<AppState>) -> Json<SlaStatus> {
+    Json(state.sla_monitor.status())
+}
+
+pub async fn get_source_boosts(
+    State(state): State<AppState>,
+) -> SearcherResult<Json<SourceBoostsSettings>> {
+    let boosts = SourceBoostRepository::new(state.db_pool.pool())
+        .get_all()
+        .await?;
+    Ok(Json(SourceBoostsSettings { boosts }))
+}
+
+/// Replace the configured boosts. Source types left out rank unboosted.
+pub async fn update_source_boosts(
+    State(state): State<AppState>,
+    actor: Actor,
+    ValidatedJson(settings): ValidatedJson<SourceBoostsSettings>,
+) -> SearcherResult<Json<SourceBoostsSettings>> {
+    SourceBoostRepository::new(state.db_pool.pool())
+        .replace_all(&settings.boosts)
+        .await?;
+    info!("Updated source boosts: {:?}", settings.boosts);
+    audit::record(
+        state.db_pool.pool(),
+        AuditEvent::new(actor, AuditAction::ConfigurationChanged).details(json!({
+            "change": "source_boosts",
+            "boosts": settings.boosts,
+        })),
+    )
+    .await;
+    Ok(Json(settings))
+}
+
+pub async fn search_analytics(
+    State(state): State<AppState>,
+    Query(query): Query<SearchAnalyticsQuery>,
+) -> SearcherResult<Json<SearchAnalyticsReport>> {
+    let report = SearchAnalyticsRepository::new(state.db_pool.pool())
+        .report(&query)
+        .await?;
+    Ok(Json(report))
+}
+
+pub async fn list_rag_provenance(
+    State(state): State<AppState>,
+    Query(query): Query<RagProvenanceQuery>,
+) -> SearcherResult<Json<Vec<RagProvenance>>> {
+    let records = RagProvenanceRepository::new(state.db_pool.pool())
+        .list(&query)
+        .await?;
+    Ok(Json(records))
+}
+
+pub async fn get_rag_provenance(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> SearcherResult<Json<RagProvenance>> {
+    RagProvenanceRepository::new(state.db_pool.pool())
+        .get(&id)
+        .await?
+        .map(Json)
+        .ok_or_else(|| SearcherError::NotFound(format!("RAG provenance {}", id)))
+}
+
+/// Load a collection the caller owns: 404 if they cannot see it, 403 if they
+/// can but it belongs to someone else.
+async fn owned_collection(
+    state: &AppState,
+    id: &str,
+    user_id: &str,
+    user_email: Option<&str>,
+) -> SearcherResult<(Collection, CollectionViewer)> {
+    let viewer = collection_viewer(state, Some(user_id), user_email).await?;
+    let collection = CollectionRepository::new(state.db_pool.pool())
+        .get_visible(id, &viewer)
+        .await?
+        .ok_or_else(|| SearcherError::NotFound(format!("Collection {}", id)))?;
+    if !viewer.owns(&collection) {
+        return Err(SearcherError::Forbidden(
+            "Only the owner can modify this collection".to_string(),
+        ));
+    }
+    Ok((collection, viewer))
+}
+
+fn validate_collection_sharing(
+    visibility: CollectionVisibility,
+    team_groups: &[String],
+) -> SearcherResult<()> {
+    if visibility == CollectionVisibility::Team && team_groups.is_empty() {
+        return Err(SearcherError::BadRequest(
+            "team collections must be shared with at least one group".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+pub async fn list_collections(
+    State(state): State<AppState>,
+    Query(query): Query<CollectionUserQuery>,
+) -> SearcherResult<Json<Vec<Collection>>> {
+    let viewer = collection_viewer(
+        &state,
+        query.user_id.as_deref(),
+        query.user_email.as_deref(),
+    )
+    .await?;
+    let collections = CollectionRepository::new(state.db_pool.pool())
+        .list_visible(&viewer)
+        .await?;
+    Ok(Json(collections))
+}
+
+pub async fn create_collection(
+    State(state): State<AppState>,
+    Json(request): Json<CreateCollectionRequest>,
+) -> SearcherResult<(StatusCode, Json<Collection>)> {
+    let name = request.name.trim();
+    if name.is_empty() {
+        return Err(SearcherError::BadRequest(
+            "name must not be empty".to_string(),
+        ));
+    }
+    let team_groups = normalize_groups(&request.team_groups);
+    validate_collection_sharing(request.visibility, &team_groups)?;
+
+    let collection = CollectionRepository::new(state.db_pool.pool())
+        .create(
+            &request.user_id,
+            name,
+            request.description.as_deref(),
+            request.visibility,
+            &team_groups,
+        )
+        .await?;
+    Ok((StatusCode::CREATED, Json(collection)))
+}
+
+pub async fn get_collection(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(query): Query<CollectionUserQuery>,
+) -> SearcherResult<Json<CollectionResponse>> {
+    let viewer = collection_viewer(
+        &state,
+        query.user_id.as_deref(),
+        query.user_email.as_deref(),
+    )
+    .await?;
+    let repo = CollectionRepository::new(state.db_pool.pool());
+    let collection = repo
+        .get_visible(&id, &viewer)
+        .await?
+        .ok_or_else(|| SearcherError::NotFound(format!("Collection {}", id)))?;
+    let document_ids = repo.document_ids(&id).await?;
+
+    Ok(Json(CollectionResponse {
+        collection,
+        document_ids,
+    }))
+}
+
+pub async fn update_collection(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(request): Json<UpdateCollectionRequest>,
+) -> SearcherResult<Json<Collection>> {
+    let (existing, _) =
+        owned_collection(&state, &id, &request.user_id, request.user_email.as_deref()).await?;
+
+    let name = request.name.as_deref().map(str::trim);
+    if name.is_some_and(str::is_empty) {
+        return Err(SearcherError::BadRequest(
+            "name must not be empty".to_string(),
+        ));
+    }
+    let team_groups = request.team_groups.as_deref().map(normalize_groups);
+    validate_collection_sharing(
+        request.visibility.unwrap_or(existing.visibility),
+        team_groups.as_deref().unwrap_or(&existing.team_groups),
+    )?;
+
+    CollectionRepository::new(state.db_pool.pool())
+        .update(
+            &id,
+            &request.user_id,
+            name,
+            request.description.as_deref(),
+            request.visibility,
+            team_groups.as_deref(),
+        )
+        .await?
+        .map(Json)
+        .ok_or_else(|| SearcherError::NotFound(format!("Collection {}", id)))
+}
+
+pub async fn delete_collection(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(query): Query<CollectionUserQuery>,
+) -> SearcherResult<StatusCode> {
+    let user_id = query
+        .user_id
+        .as_deref()
+        .ok_or_else(|| SearcherError::BadRequest("user_id is required".to_string()))?;
+    owned_collection(&state, &id, user_id, query.user_email.as_deref()).await?;
+
+    CollectionRepository::new(state.db_pool.pool())
+        .delete(&id, user_id)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Add documents the owner can read; documents they cannot see are skipped.
+pub async fn add_collection_documents(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(request): Json<AddCollectionDocumentsRequest>,
+) -> SearcherResult<Json<AddCollectionDocumentsResponse>> {
+    if request.document_ids.is_empty() {
+        return Err(SearcherError::BadRequest(
+            "document_ids must not be empty".to_string(),
+        ));
+    }
+    let (_, viewer) = owned_collection(
+        &state,
+        &id,
+        &request.user_id,
+        Some(request.user_email.as_str()),
+    )
+    .await?;
+
+    let readable = user_document_repository(&state)
+        .filter_accessible_ids(
+            &request.document_ids,
+            Some(request.user_email.as_str()),
+            &viewer.groups,
+        )
+        .await
+        .map_err(|e| SearcherError::Internal(anyhow!("Permission check failed: {}", e)))?;
+    let added = CollectionRepository::new(state.db_pool.pool())
+        .add_documents(&id, &request.user_id, &readable)
+        .await?;
+    Ok(Json(AddCollectionDocumentsResponse { added }))
+}
+
+pub async fn remove_collection_document(
+    State(state): State<AppState>,
+    Path((id, document_id)): Path<(String, String)>,
+    Query(query): Query<CollectionUserQuery>,
+) -> SearcherResult<StatusCode> {
+    let user_id = query
+        .user_id
+        .as_deref()
+        .ok_or_else(|| SearcherError::BadRequest("user_id is required".to_string()))?;
+    owned_collection(&state, &id, user_id, query.user_email.as_deref()).await?;
+
+    if !CollectionRepository::new(state.db_pool.pool())
+        .remove_document(&id, &document_id)
+        .await?
+    {
+        return Err(SearcherError::NotFound(format!(
+            "Document {} in collection {}",
+            document_id, id
+        )));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Documents read on behalf of a user, under row-level security when the
+/// searcher enables it.
+fn user_document_repository(state: &AppState) -> DocumentRepository {
+    DocumentRepository::new(state.db_pool.pool())
+        .with_row_level_security(state.config.row_level_security)
+}
+
+/// The document, if a user can read it under its permissions.
+async fn find_readable_document(
+    state: &AppState,
+    document_id: &str,
+    user_email: &str,
+) -> SearcherResult<Option<Document>> {
+    let groups = GroupRepository::new(state.db_pool.pool())
+        .find_groups_for_user(user_email)
+        .await
+        .map_err(|error| SearcherError::Internal(anyhow!(error)))?;
+    user_document_repository(state)
+        .find_by_id_for_user(document_id, user_email, &groups)
+        .await
+        .map_err(|e| SearcherError::Internal(anyhow!("Permission check failed: {}", e)))
+}
+
+/// Create a link to a document the caller can read, if the admin policy
+/// allows sharing.
+pub async fn create_share_link(
+    State(state): State<AppState>,
+    Path(document_id): Path<String>,
+    Json(request): Json<CreateShareLinkRequest>,
+) -> SearcherResult<(StatusCode, Json<CreateShareLinkResponse>)> {
+    let repo = DocumentShareLinkRepository::new(state.db_pool.pool());
+    let policy = repo
+        .get_policy()
+        .await
+        .map_err(|error| SearcherError::Internal(anyhow!(error)))?;
+    if !policy.enabled {
+        return Err(SearcherError::Forbidden(
+            "Document sharing is disabled".to_string(),
+        ));
+    }
+    let user = UserRepository::new(state.db_pool.pool())
+        .find_by_id(request.user_id.clone())
+        .await
+        .map_err(|error| SearcherError::Internal(anyhow!(error)))?
+        .ok_or_else(|| SearcherError::NotFound(format!("User {}", request.user_id)))?;
+    if find_readable_document(&state, &document_id, &user.email)
+        .await?
+        .is_none()
+    {
+        return Err(SearcherError::NotFound(format!("Document {}", document_id)));
+    }
+
+    let (expires_at, max_accesses) = resolve_link_limits(
+        &policy,
+        OffsetDateTime::now_utc(),
+        request.ttl_hours,
+        request.max_accesses,
+    )
+    .map_err(SearcherError::Validation)?;
+    let (link, token) = repo
+        .create(&document_id, &request.user_id, expires_at, max_accesses)
+        .await
+        .map_err(|error| SearcherError::Internal(anyhow!(error)))?;
+    info!(
+        "User {} shared document {} until {}",
+        request.user_id, document_id, expires_at
+    );
+    Ok((
+        StatusCode::CREATED,
+        Json(CreateShareLinkResponse { link, token }),
+    ))
+}
+
+/// The caller's links to a document, including revoked and expired ones.
+pub async fn list_share_links(
+    State(state): State<AppState>,
+    Path(document_id): Path<String>,
+    Query(query): Query<ShareLinkUserQuery>,
+) -> SearcherResult<Json<Vec<DocumentShareLink>>> {
+    let links = DocumentShareLinkRepository::new(state.db_pool.pool())
+        .list_for_document(&document_id, &query.user_id)
+        .await
+        .map_err(|error| SearcherError::Internal(anyhow!(error)))?;
+    Ok(Json(links))
+}
+
+pub async fn revoke_share_link(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(query): Query<ShareLinkUserQuery>,
+) -> SearcherResult<StatusCode> {
+    revoke_link(&state, &id, Some(&query.user_id)).await
+}
+
+pub async fn admin_revoke_share_link(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> SearcherResult<StatusCode> {
+    revoke_link(&state, &id, None).await
+}
+
+async fn revoke_link(
+    state: &AppState,
+    id: &str,
+    created_by: Option<&str>,
+) -> SearcherResult<StatusCode> {
+    if !DocumentShareLinkRepository::new(state.db_pool.pool())
+        .revoke(id, created_by)
+        .await
+        .map_err(|error| SearcherError::Internal(anyhow!(error)))?
+    {
+        return Err(SearcherError::NotFound(format!("Share link {}", id)));
+    }
+    info!("Revoked share link {}", id);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn list_active_share_links(
+    State(state): State<AppState>,
+) -> SearcherResult<Json<Vec<DocumentShareLink>>> {
+    let links = DocumentShareLinkRepository::new(state.db_pool.pool())
+        .list_active()
+        .await
+        .map_err(|error| SearcherError::Internal(anyhow!(error)))?;
+    Ok(Json(links))
+}
+
+pub async fn get_share_link_policy(
+    State(state): State<AppState>,
+) -> SearcherResult<Json<ShareLinkPolicy>> {
+    let policy = DocumentShareLinkRepository::new(state.db_pool.pool())
+        .get_policy()
+        .await
+        .map_err(|error| SearcherError::Internal(anyhow!(error)))?;
+    Ok(Json(policy))
+}
+
+pub async fn update_share_link_policy(
+    State(state): State<AppState>,
+    actor: Actor,
+    ValidatedJson(policy): ValidatedJson<ShareLinkPolicy>,
+) -> SearcherResult<Json<ShareLinkPolicy>> {
+    DocumentShareLinkRepository::new(state.db_pool.pool())
+        .update_policy(&policy)
+        .await
+        .map_err(|error| SearcherError::Internal(anyhow!(error)))?;
+    info!("Updated share link policy: {:?}", policy);
+    audit::record(
+        state.db_pool.pool(),
+        AuditEvent::new(actor, AuditAction::ConfigurationChanged).details(json!({
+            "change": "share_link_policy",
+            "policy": policy,
+        })),
+    )
+    .await;
+    Ok(Json(policy))
+}
+
+/// Fetch a document through a share link, without an Omni account. Every
+/// reason a link does not work answers 404, so tokens cannot be probed.
+pub async fn get_shared_document(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+) -> SearcherResult<Json<SharedDocumentResponse>> {
+    let not_found = || SearcherError::NotFound("Share link not found or expired".to_string());
+    let repo = DocumentShareLinkRepository::new(state.db_pool.pool());
+    let policy = repo
+        .get_policy()
+        .await
+        .map_err(|error| SearcherError::Internal(anyhow!(error)))?;
+    if !policy.enabled {
+        return Err(not_found());
+    }
+    let link = repo
+        .find_active_by_token(&token)
+        .await
+        .map_err(|error| SearcherError::Internal(anyhow!(error)))?
+        .ok_or_else(not_found)?;
+
+    // The link only lasts as long as its creator's own access
+    let creator = UserRepository::new(state.db_pool.pool())
+        .find_by_id(link.created_by.clone())
+        .await
+        .map_err(|error| SearcherError::Internal(anyhow!(error)))?
+        .ok_or_else(not_found)?;
+    let document = find_readable_document(&state, &link.document_id, &creator.email)
+        .await?
+        .ok_or_else(not_found)?;
+
+    if !allow_fetch(&state.redis_client, &link.id, policy.rate_limit_per_minute).await {
+        return Err(SearcherError::TooManyRequests(
+            "Share link rate limit exceeded".to_string(),
+        ));
+    }
+    let link = repo
+        .record_access(&link.id)
+        .await
+        .map_err(|error| SearcherError::Internal(anyhow!(error)))?
+        .ok_or_else(not_found)?;
+
+    let content = match &document.content_id {
+        Some(content_id) => state
+            .content_storage
+            .get_text(content_id)
+            .await
+            .map_err(|e| SearcherError::Internal(anyhow!("Failed to load content: {}", e)))?,
+        None => String::new(),
+    };
+
+    Ok(Json(SharedDocumentResponse {
+        title: document.title,
+        content_type: document.content_type,
+        content,
+        updated_at: document.updated_at,
+        expires_at: link.expires_at,
+    }))
+}
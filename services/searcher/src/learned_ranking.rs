@@ -0,0 +1,264 @@
+//! Learned fusion weights for hybrid search.
+//!
+//! Hybrid search describes each fused candidate by its ranking features (see
+//! `shared::ranking`) and logs them with the search, so clicks can be turned
+//! into training data. When the indexer's training job has activated a
+//! ranking profile, fused scores come from the profile's weights instead of
+//! the static per-retriever RRF weights. The active profile and the
+//! per-document click counts behind the click-through prior are reloaded
+//! periodically.
+
+use crate::models::SearchResult;
+use crate::recency::last_updated;
+use shared::DatabasePool;
+use shared::db::repositories::RankingProfileRepository;
+use shared::ranking::{RankingFeatures, RankingProfile, click_prior_feature, recency_feature};
+use std::collections::HashMap;
+use std::sync::Arc;
+use time::{Duration, OffsetDateTime};
+use tokio::sync::RwLock;
+use tracing::{error, info};
+
+const SECONDS_PER_DAY: f64 = 86_400.0;
+
+#[derive(Debug, Clone)]
+pub struct LearnedRankingConfig {
+    /// Off: no features are logged and the static weights always apply.
+    pub enabled: bool,
+    /// Days of clicks behind the click-through prior.
+    pub click_window_days: i64,
+    /// Documents with a click-through prior; the rest get 0.
+    pub max_prior_documents: i64,
+}
+
+impl Default for LearnedRankingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            click_window_days: 90,
+            max_prior_documents: 50_000,
+        }
+    }
+}
+
+impl LearnedRankingConfig {
+    pub fn from_env() -> Self {
+        fn env_or<T: std::str::FromStr>(key: &str, default: T) -> T {
+            std::env::var(key)
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default)
+        }
+
+        let defaults = Self::default();
+        Self {
+            enabled: env_or("SEARCHER_LEARNED_RANKING_ENABLED", defaults.enabled),
+            click_window_days: env_or(
+                "SEARCHER_LEARNED_RANKING_CLICK_WINDOW_DAYS",
+                defaults.click_window_days,
+            )
+            .max(1),
+            max_prior_documents: env_or(
+                "SEARCHER_LEARNED_RANKING_MAX_PRIOR_DOCUMENTS",
+                defaults.max_prior_documents,
+            )
+            .max(0),
+        }
+    }
+}
+
+/// Scores from the retrievers that found a candidate.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RetrieverScores {
+    pub bm25: Option<f32>,
+    /// Best BM25 score among the query's fulltext hits.
+    pub max_bm25: f32,
+    pub semantic: Option<f32>,
+}
+
+#[derive(Debug, Default)]
+pub struct RankingModel {
+    /// Id and weights of the active profile.
+    profile: Option<(String, RankingProfile)>,
+    document_clicks: HashMap<String, u64>,
+    max_clicks: u64,
+}
+
+impl RankingModel {
+    pub fn new(
+        profile: Option<(String, RankingProfile)>,
+        document_clicks: HashMap<String, u64>,
+    ) -> Self {
+        let max_clicks = document_clicks.values().copied().max().unwrap_or_default();
+        Self {
+            profile,
+            document_clicks,
+            max_clicks,
+        }
+    }
+
+    pub fn profile(&self) -> Option<&RankingProfile> {
+        self.profile.as_ref().map(|(_, profile)| profile)
+    }
+
+    pub fn profile_id(&self) -> Option<&str> {
+        self.profile.as_ref().map(|(id, _)| id.as_str())
+    }
+
+    /// Features of a fused candidate. Its source type must be populated for
+    /// per-type weights to apply.
+    pub fn features(
+        &self,
+        result: &SearchResult,
+        scores: RetrieverScores,
+        now: OffsetDateTime,
+    ) -> RankingFeatures {
+        let bm25 = match scores.bm25 {
+            Some(bm25) if scores.max_bm25 > 0.0 => (bm25 / scores.max_bm25).clamp(0.0, 1.0),
+            _ => 0.0,
+        };
+        let age_days = (now - last_updated(&result.document)).as_seconds_f64() / SECONDS_PER_DAY;
+        let clicks = self
+            .document_clicks
+            .get(&result.document.id)
+            .copied()
+            .unwrap_or_default();
+
+        RankingFeatures {
+            bm25,
+            semantic: scores.semantic.unwrap_or_default().max(0.0),
+            recency: recency_feature(age_days),
+            click_prior: click_prior_feature(clicks, self.max_clicks),
+            source_type: result.source_type.clone(),
+        }
+    }
+}
+
+pub struct LearnedRanker {
+    model: RwLock<Arc<RankingModel>>,
+    db_pool: DatabasePool,
+    config: LearnedRankingConfig,
+}
+
+impl LearnedRanker {
+    pub fn new(db_pool: DatabasePool, config: LearnedRankingConfig) -> Self {
+        Self {
+            model: RwLock::new(Arc::new(RankingModel::default())),
+            db_pool,
+            config,
+        }
+    }
+
+    pub fn config(&self) -> &LearnedRankingConfig {
+        &self.config
+    }
+
+    pub async fn refresh(&self) -> anyhow::Result<()> {
+        if !self.config.enabled {
+            return Ok(());
+        }
+
+        let repo = RankingProfileRepository::new(self.db_pool.pool());
+        let profile = repo
+            .active()
+            .await?
+            .map(|profile| (profile.id, profile.weights.0));
+        let since = OffsetDateTime::now_utc() - Duration::days(self.config.click_window_days);
+        let document_clicks: HashMap<String, u64> = repo
+            .document_clicks(since, self.config.max_prior_documents)
+            .await?
+            .into_iter()
+            .map(|(document_id, clicks)| (document_id, clicks.max(0) as u64))
+            .collect();
+
+        info!(
+            "Learned ranking loaded profile {} and click priors for {} documents",
+            profile.as_ref().map_or("none", |(id, _)| id.as_str()),
+            document_clicks.len()
+        );
+        *self.model.write().await = Arc::new(RankingModel::new(profile, document_clicks));
+        Ok(())
+    }
+
+    pub fn start_background_refresh(self: &Arc<Self>, interval_secs: u64) {
+        let ranker = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(interval_secs));
+            loop {
+                interval.tick().await;
+                if let Err(e) = ranker.refresh().await {
+                    error!("Failed to refresh learned ranking: {}", e);
+                }
+            }
+        });
+    }
+
+    /// The current model, or `None` when learned ranking is turned off.
+    pub async fn model(&self) -> Option<Arc<RankingModel>> {
+        if !self.config.enabled {
+            return None;
+        }
+        Some(self.model.read().await.clone())
+    }
+
+    /// Id of the profile fused scores currently come from.
+    pub async fn profile_id(&self) -> Option<String> {
+        let model = self.model().await?;
+        model.profile_id().map(str::to_string)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::datetime;
+
+    fn result(id: &str, updated_at: OffsetDateTime) -> SearchResult {
+        let mut result = SearchResult::for_test(id);
+        result.document.created_at = updated_at;
+        result.document.updated_at = updated_at;
+        result.document.last_indexed_at = updated_at;
+        result.score = 0.0;
+        result.source_type = Some("confluence".to_string());
+        result
+    }
+
+    #[test]
+    fn test_features_of_fused_candidate() {
+        let now = datetime!(2026-03-01 00:00 UTC);
+        let model = RankingModel::new(
+            None,
+            HashMap::from([("a".to_string(), 9), ("b".to_string(), 99)]),
+        );
+
+        let features = model.features(
+            &result("a", now - Duration::days(30)),
+            RetrieverScores {
+                bm25: Some(4.0),
+                max_bm25: 8.0,
+                semantic: Some(0.7),
+            },
+            now,
+        );
+
+        assert_eq!(features.bm25, 0.5);
+        assert_eq!(features.semantic, 0.7);
+        assert!((features.recency - 0.5).abs() < 1e-6);
+        assert!((features.click_prior - 0.5).abs() < 1e-6);
+        assert_eq!(features.source_type.as_deref(), Some("confluence"));
+
+        // Found by semantic search only, never clicked
+        let features = model.features(
+            &result("c", now),
+            RetrieverScores {
+                bm25: None,
+                max_bm25: 8.0,
+                semantic: Some(0.4),
+            },
+            now,
+        );
+        assert_eq!(features.bm25, 0.0);
+        assert_eq!(features.click_prior, 0.0);
+        assert_eq!(features.recency, 1.0);
+    }
+}
@@ -1,32 +1,71 @@
+pub mod admission;
+pub mod answer;
 pub mod capabilities_repository;
+pub mod collections;
+pub mod conditional;
+pub mod dedupe;
+pub mod document_clusters;
+pub mod document_search;
+pub mod extract;
 pub mod handlers;
+pub mod learned_ranking;
+pub mod metadata_filter;
 pub mod models;
 pub mod operator_registry;
+pub mod personalization;
+pub mod query_ast;
+pub mod query_embedding_cache;
+pub mod query_intent;
+pub mod query_language;
 pub mod query_parser;
+pub mod query_understanding;
+pub mod rag_provenance;
+pub mod recency;
+pub mod rerank;
 pub mod search;
+pub mod search_analytics;
+pub mod search_cache;
 pub mod search_repository;
+pub mod share_links;
+pub mod sla;
+pub mod snippets;
+pub mod source_boosts;
+pub mod source_router;
+pub mod spelling;
 pub mod suggested_questions;
+pub mod suggestion_prefetch;
 pub mod typeahead;
 
 use anyhow::Result as AnyhowResult;
 use axum::{
     middleware,
-    routing::{get, post},
+    routing::{delete, get, post},
     Router,
 };
 use redis::Client as RedisClient;
 use shared::{
+    db::row_level_security,
+    http_security::HttpSecurityConfig,
+    service_auth::internal::InternalAuthConfig,
     telemetry::{self, TelemetryConfig},
     AIClient, DatabasePool, ObjectStorage, SearcherConfig, StorageFactory,
 };
 use std::net::SocketAddr;
 use std::sync::Arc;
 use tower::ServiceBuilder;
-use tower_http::cors::CorsLayer;
 use tracing::{error, info};
 
+use crate::admission::{AdmissionConfig, AdmissionController};
+use crate::learned_ranking::{LearnedRanker, LearnedRankingConfig};
 use crate::operator_registry::OperatorRegistry;
-use crate::suggested_questions::SuggestedQuestionsGenerator;
+use crate::query_intent::{QueryIntentClassifier, QueryIntentConfig};
+use crate::query_language::{QueryLanguage, QueryLanguageConfig};
+use crate::query_understanding::{QueryUnderstanding, QueryUnderstandingConfig};
+use crate::sla::{SlaConfig, SlaMonitor};
+use crate::source_router::{SourceRouter, SourceRouterConfig};
+use crate::spelling::{SpellChecker, SpellingConfig};
+use crate::suggested_questions::{SuggestedQuestionsConfig, SuggestedQuestionsGenerator};
+use crate::suggestion_prefetch::SuggestionPrefetchConfig;
 use crate::typeahead::TitleIndex;
 
 pub type Result<T> = std::result::Result<T, SearcherError>;
@@ -45,6 +84,14 @@ pub enum SearcherError {
     NotFound(String),
     #[error("Bad request: {0}")]
     BadRequest(String),
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+    #[error("Too many requests: {0}")]
+    TooManyRequests(String),
+    #[error("Validation failed for {} field(s)", .0.len())]
+    Validation(Vec<models::FieldError>),
+    #[error("Overloaded: shed {} request", .0.as_str())]
+    Overloaded(admission::PriorityClass),
 }
 
 impl axum::response::IntoResponse for SearcherError {
@@ -68,6 +115,41 @@ impl axum::response::IntoResponse for SearcherError {
             ),
             SearcherError::NotFound(msg) => (axum::http::StatusCode::NOT_FOUND, msg),
             SearcherError::BadRequest(msg) => (axum::http::StatusCode::BAD_REQUEST, msg),
+            SearcherError::Forbidden(msg) => (axum::http::StatusCode::FORBIDDEN, msg),
+            SearcherError::TooManyRequests(msg) => {
+                let body = serde_json::json!({
+                    "error": msg,
+                });
+                return (
+                    axum::http::StatusCode::TOO_MANY_REQUESTS,
+                    [(axum::http::header::RETRY_AFTER, "60")],
+                    axum::Json(body),
+                )
+                    .into_response();
+            }
+            SearcherError::Validation(fields) => {
+                let body = serde_json::json!({
+                    "error": "Invalid request",
+                    "fields": fields,
+                });
+                return (
+                    axum::http::StatusCode::UNPROCESSABLE_ENTITY,
+                    axum::Json(body),
+                )
+                    .into_response();
+            }
+            SearcherError::Overloaded(class) => {
+                let body = serde_json::json!({
+                    "error": "Searcher is overloaded, retry later",
+                    "priority": class,
+                });
+                return (
+                    axum::http::StatusCode::SERVICE_UNAVAILABLE,
+                    [(axum::http::header::RETRY_AFTER, "1")],
+                    axum::Json(body),
+                )
+                    .into_response();
+            }
         };
 
         let body = serde_json::json!({
@@ -88,31 +170,93 @@ pub struct AppState {
     pub suggested_questions_generator: Arc<SuggestedQuestionsGenerator>,
     pub title_index: Arc<TitleIndex>,
     pub operator_registry: Arc<OperatorRegistry>,
+    pub admission: Arc<AdmissionController>,
+    pub source_router: Arc<SourceRouter>,
+    pub query_intent: Arc<QueryIntentClassifier>,
+    pub query_language: Arc<QueryLanguage>,
+    pub query_understanding: Arc<QueryUnderstanding>,
+    pub sla_monitor: Arc<SlaMonitor>,
+    pub spell_checker: Arc<SpellChecker>,
+    pub learned_ranker: Arc<LearnedRanker>,
 }
 
 pub fn create_app(state: AppState) -> Router {
     Router::new()
-        .route("/health", get(handlers::health_check))
         .route("/search", post(handlers::search))
+        .route("/search/stream", post(handlers::search_stream))
         .route("/search/ai-answer", post(handlers::ai_answer))
+        .route("/answer", post(handlers::answer))
+        .route("/answer/stream", post(handlers::answer_stream))
+        .route("/suggested-questions", get(handlers::suggested_questions))
+        .route_layer(middleware::from_fn_with_state(
+            state.admission.clone(),
+            admission::admit,
+        ))
+        .route("/health", get(handlers::health_check))
+        .route("/search/clicks", post(handlers::record_click))
+        .route("/feedback/click", post(handlers::record_click))
         .route("/recent-searches", get(handlers::recent_searches))
         .route("/typeahead", get(handlers::typeahead))
         .route("/people/search", get(handlers::people_search))
         .route("/capabilities/upsert", post(handlers::capabilities_upsert))
         .route("/capabilities/sync", post(handlers::capabilities_sync))
         .route("/capabilities/search", post(handlers::capabilities_search))
-        .route("/suggested-questions", post(handlers::suggested_questions))
         .route("/attributes/values", get(handlers::attribute_values))
+        .route(
+            "/collections",
+            get(handlers::list_collections).post(handlers::create_collection),
+        )
+        .route(
+            "/collections/:id",
+            get(handlers::get_collection)
+                .put(handlers::update_collection)
+                .delete(handlers::delete_collection),
+        )
+        .route(
+            "/collections/:id/documents",
+            post(handlers::add_collection_documents),
+        )
+        .route(
+            "/collections/:id/documents/:document_id",
+            delete(handlers::remove_collection_document),
+        )
+        .route(
+            "/documents/:id/share-links",
+            get(handlers::list_share_links).post(handlers::create_share_link),
+        )
+        .route("/share-links/:id", delete(handlers::revoke_share_link))
+        .route("/shared/:token", get(handlers::get_shared_document))
+        .route("/admin/search-sla", get(handlers::search_sla_status))
+        .route("/admin/search-analytics", get(handlers::search_analytics))
+        .route(
+            "/admin/source-boosts",
+            get(handlers::get_source_boosts).put(handlers::update_source_boosts),
+        )
+        .route(
+            "/admin/share-link-policy",
+            get(handlers::get_share_link_policy).put(handlers::update_share_link_policy),
+        )
+        .route("/admin/share-links", get(handlers::list_active_share_links))
+        .route(
+            "/admin/share-links/:id",
+            delete(handlers::admin_revoke_share_link),
+        )
+        .route("/admin/rag-provenance", get(handlers::list_rag_provenance))
+        .route(
+            "/admin/rag-provenance/:id",
+            get(handlers::get_rag_provenance),
+        )
         .layer(
             ServiceBuilder::new()
                 .layer(middleware::from_fn(telemetry::middleware::trace_layer))
-                .layer(CorsLayer::permissive()),
+                .layer(HttpSecurityConfig::from_env("SEARCHER").layer())
+                .layer(InternalAuthConfig::from_env("SEARCHER").layer()),
         )
         .with_state(state)
 }
 
 pub async fn run_server() -> AnyhowResult<()> {
-    dotenvy::dotenv().ok();
+    shared::config_loader::load_env_files();
 
     let telemetry_config = TelemetryConfig::from_env("omni-searcher");
     telemetry::init_telemetry(telemetry_config)?;
@@ -125,6 +269,16 @@ pub async fn run_server() -> AnyhowResult<()> {
         .await
         .map_err(|e| anyhow::anyhow!("Failed to create database pool: {}", e))?;
 
+    if config.row_level_security {
+        if !row_level_security::is_available(db_pool.pool()).await? {
+            anyhow::bail!(
+                "SEARCH_ROW_LEVEL_SECURITY is enabled but the database user can't assume the {} role",
+                row_level_security::DOCUMENT_READER_ROLE
+            );
+        }
+        info!("Row-level security enabled for user searches");
+    }
+
     let redis_client = RedisClient::open(config.redis.redis_url.clone())?;
     info!("Redis client initialized");
 
@@ -134,18 +288,27 @@ pub async fn run_server() -> AnyhowResult<()> {
     let content_storage = StorageFactory::from_env(db_pool.pool().clone()).await?;
     info!("Storage initialized");
 
-    let suggested_questions_generator = Arc::new(SuggestedQuestionsGenerator::new(
-        redis_client.clone(),
-        db_pool.clone(),
-        content_storage.clone(),
-        ai_client.clone(),
-    ));
+    let (prefetch_tx, prefetch_rx) = tokio::sync::mpsc::unbounded_channel();
+    let suggested_questions_generator = Arc::new(
+        SuggestedQuestionsGenerator::new(
+            redis_client.clone(),
+            db_pool.clone(),
+            content_storage.clone(),
+            ai_client.clone(),
+        )
+        .with_config(SuggestedQuestionsConfig::from_env())
+        .with_prefetch(prefetch_tx),
+    );
+    suggested_questions_generator.start_scheduler();
 
     let title_index = Arc::new(TitleIndex::new(db_pool.clone()));
     if let Err(e) = title_index.refresh().await {
         error!("Failed initial typeahead index load: {}", e);
     }
-    title_index.start_background_refresh(300);
+    // Indexer updates keep the index current; full refreshes only catch
+    // what they missed
+    title_index.start_background_refresh(3600);
+    title_index.start_update_listener(redis_client.clone());
     info!("Typeahead index initialized");
 
     let operator_registry = Arc::new(OperatorRegistry::new(redis_client.clone()));
@@ -155,6 +318,52 @@ pub async fn run_server() -> AnyhowResult<()> {
     operator_registry.start_background_refresh(60);
     info!("Operator registry initialized");
 
+    let admission = Arc::new(AdmissionController::new(AdmissionConfig::from_env()));
+    let sla_monitor = Arc::new(SlaMonitor::new(SlaConfig::from_env()));
+
+    let source_router = Arc::new(SourceRouter::new(
+        db_pool.clone(),
+        SourceRouterConfig::from_env(),
+    ));
+    if let Err(e) = source_router.refresh().await {
+        error!("Failed initial source router training: {}", e);
+    }
+    source_router.start_background_refresh(3600);
+    info!("Source router initialized");
+
+    let query_intent = Arc::new(QueryIntentClassifier::new(
+        ai_client.clone(),
+        QueryIntentConfig::from_env(),
+    ));
+    let query_language = Arc::new(QueryLanguage::new(
+        ai_client.clone(),
+        QueryLanguageConfig::from_env(),
+    ));
+    let query_understanding = Arc::new(QueryUnderstanding::new(
+        ai_client.clone(),
+        QueryUnderstandingConfig::from_env(),
+    ));
+
+    let spell_checker = Arc::new(SpellChecker::new(
+        db_pool.clone(),
+        SpellingConfig::from_env(),
+    ));
+    if let Err(e) = spell_checker.refresh().await {
+        error!("Failed initial spell checker load: {}", e);
+    }
+    spell_checker.start_background_refresh(3600);
+    info!("Spell checker initialized");
+
+    let learned_ranker = Arc::new(LearnedRanker::new(
+        db_pool.clone(),
+        LearnedRankingConfig::from_env(),
+    ));
+    if let Err(e) = learned_ranker.refresh().await {
+        error!("Failed initial learned ranking load: {}", e);
+    }
+    learned_ranker.start_background_refresh(3600);
+    info!("Learned ranking initialized");
+
     let app_state = AppState {
         db_pool,
         redis_client,
@@ -164,8 +373,22 @@ pub async fn run_server() -> AnyhowResult<()> {
         suggested_questions_generator,
         title_index,
         operator_registry,
+        admission,
+        source_router,
+        query_intent,
+        query_language,
+        query_understanding,
+        sla_monitor,
+        spell_checker,
+        learned_ranker,
     };
 
+    suggestion_prefetch::spawn_worker(
+        app_state.clone(),
+        SuggestionPrefetchConfig::from_env(),
+        prefetch_rx,
+    );
+
     let app = create_app(app_state);
 
     let addr = SocketAddr::from(([0, 0, 0, 0], config.port));
@@ -1,6 +1,15 @@
 use anyhow::Result;
+use shared::SearcherConfig;
+use shared::config_loader;
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    if config_loader::print_config_requested() {
+        config_loader::load_env_files();
+        std::process::exit(config_loader::print_config(
+            "omni-searcher",
+            SearcherConfig::load,
+        ));
+    }
     omni_searcher::run_server().await
 }
@@ -0,0 +1,360 @@
+//! Filter expressions over document fields, e.g. `author = alice AND
+//! updated_at > 2024-01-01 AND path LIKE /runbooks/%`, given with a search
+//! request as a tree of conditions.
+//!
+//! An expression compiles to a single SQL predicate on the `d` documents
+//! alias, which every search mode adds to its BM25 or vector query like the
+//! other request filters. Values are inlined as escaped literals; field
+//! names are resolved against a fixed set of columns and metadata keys, so
+//! nothing the caller sends reaches the SQL unescaped.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use shared::db::repositories::document::DOCUMENT_UPDATED_AT;
+use time::format_description::well_known::Rfc3339;
+use time::macros::format_description;
+use time::{Date, OffsetDateTime};
+
+/// Deepest nesting of `and`, `or` and `not`.
+pub const MAX_DEPTH: usize = 8;
+/// Most conditions an expression may hold.
+pub const MAX_CONDITIONS: usize = 50;
+/// Most values an `in` condition may list.
+pub const MAX_IN_VALUES: usize = 100;
+
+/// When a document was created, as reported by its source when valid.
+const DOCUMENT_CREATED_AT: &str = "COALESCE(\
+     CASE WHEN d.metadata->>'created_at' IS NOT NULL \
+          AND pg_input_is_valid(d.metadata->>'created_at', 'timestamptz') \
+     THEN (d.metadata->>'created_at')::timestamptz END, \
+     d.created_at)";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MetadataFilter {
+    /// Documents matching every filter.
+    And { filters: Vec<MetadataFilter> },
+    /// Documents matching any of the filters.
+    Or { filters: Vec<MetadataFilter> },
+    /// Documents not matching the filter.
+    Not { filter: Box<MetadataFilter> },
+    /// A comparison of one field with a value.
+    Condition {
+        /// `title`, `url`, `content_type`, `author`, `path`, `mime_type`,
+        /// `size`, `created_at`, `updated_at`, `extra.<key>[.<key>...]` for
+        /// connector-specific metadata, or `attributes.<key>`.
+        field: String,
+        op: FilterOp,
+        value: JsonValue,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterOp {
+    #[serde(alias = "=")]
+    Eq,
+    /// Also matches documents without the field.
+    #[serde(alias = "!=")]
+    Ne,
+    #[serde(alias = ">")]
+    Gt,
+    #[serde(alias = ">=")]
+    Gte,
+    #[serde(alias = "<")]
+    Lt,
+    #[serde(alias = "<=")]
+    Lte,
+    /// SQL `LIKE`: `%` matches any run of characters, `_` any one.
+    Like,
+    /// Equal to any of an array of values.
+    In,
+    /// With `true`, documents that have the field; with `false`, those
+    /// that don't.
+    Exists,
+}
+
+impl FilterOp {
+    fn comparison(&self) -> Option<&'static str> {
+        match self {
+            FilterOp::Eq => Some("="),
+            FilterOp::Ne => Some("IS DISTINCT FROM"),
+            FilterOp::Gt => Some(">"),
+            FilterOp::Gte => Some(">="),
+            FilterOp::Lt => Some("<"),
+            FilterOp::Lte => Some("<="),
+            FilterOp::Like | FilterOp::In | FilterOp::Exists => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FieldKind {
+    Text,
+    Timestamp,
+}
+
+/// Compile an expression to a SQL predicate.
+pub fn compile(filter: &MetadataFilter) -> Result<String, String> {
+    let mut conditions = 0;
+    compile_node(filter, 1, &mut conditions)
+}
+
+fn compile_node(
+    filter: &MetadataFilter,
+    depth: usize,
+    conditions: &mut usize,
+) -> Result<String, String> {
+    if depth > MAX_DEPTH {
+        return Err(format!("must not nest deeper than {}", MAX_DEPTH));
+    }
+    match filter {
+        MetadataFilter::And { filters } | MetadataFilter::Or { filters } => {
+            let (name, joiner) = match filter {
+                MetadataFilter::And { .. } => ("and", " AND "),
+                _ => ("or", " OR "),
+            };
+            if filters.is_empty() {
+                return Err(format!("{} must have at least one filter", name));
+            }
+            let parts = filters
+                .iter()
+                .map(|filter| compile_node(filter, depth + 1, conditions))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(format!("({})", parts.join(joiner)))
+        }
+        // A missing field compares as NULL, which NOT alone would keep out
+        MetadataFilter::Not { filter } => Ok(format!(
+            "(({}) IS NOT TRUE)",
+            compile_node(filter, depth + 1, conditions)?
+        )),
+        MetadataFilter::Condition { field, op, value } => {
+            *conditions += 1;
+            if *conditions > MAX_CONDITIONS {
+                return Err(format!(
+                    "must not have more than {} conditions",
+                    MAX_CONDITIONS
+                ));
+            }
+            compile_condition(field, *op, value).map_err(|e| format!("{}: {}", field, e))
+        }
+    }
+}
+
+fn compile_condition(field: &str, op: FilterOp, value: &JsonValue) -> Result<String, String> {
+    let (expr, kind) = field_expr(field)?;
+
+    if op == FilterOp::Exists {
+        let JsonValue::Bool(exists) = value else {
+            return Err("exists takes true or false".to_string());
+        };
+        let test = if *exists { "IS NOT NULL" } else { "IS NULL" };
+        return Ok(format!("({} {})", expr, test));
+    }
+
+    match kind {
+        FieldKind::Timestamp => {
+            let Some(comparison) = op.comparison() else {
+                return Err("dates can only be compared".to_string());
+            };
+            let JsonValue::String(value) = value else {
+                return Err("must be compared with a date".to_string());
+            };
+            let timestamp = parse_timestamp(value)
+                .ok_or_else(|| format!("'{}' is not a date or timestamp", value))?;
+            let timestamp = timestamp.format(&Rfc3339).map_err(|e| e.to_string())?;
+            Ok(format!(
+                "({} {} '{}'::timestamptz)",
+                expr, comparison, timestamp
+            ))
+        }
+        FieldKind::Text => match op {
+            FilterOp::Like => {
+                let JsonValue::String(pattern) = value else {
+                    return Err("like takes a string pattern".to_string());
+                };
+                Ok(format!("({} LIKE {})", expr, text_literal(pattern)?))
+            }
+            FilterOp::In => {
+                let JsonValue::Array(values) = value else {
+                    return Err("in takes an array of values".to_string());
+                };
+                if values.is_empty() || values.len() > MAX_IN_VALUES {
+                    return Err(format!("in takes between 1 and {} values", MAX_IN_VALUES));
+                }
+                let literals = values
+                    .iter()
+                    .map(|value| text_literal(&scalar_text(value)?))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(format!(
+                    "({} = ANY(ARRAY[{}]::text[]))",
+                    expr,
+                    literals.join(", ")
+                ))
+            }
+            op => {
+                let comparison = op.comparison().unwrap_or("=");
+                match value {
+                    // Numbers compare numerically, skipping values that
+                    // aren't numbers
+                    JsonValue::Number(number) => Ok(format!(
+                        "(CASE WHEN pg_input_is_valid({expr}, 'numeric') \
+                         THEN ({expr})::numeric END {comparison} {number})"
+                    )),
+                    value => Ok(format!(
+                        "({} {} {})",
+                        expr,
+                        comparison,
+                        text_literal(&scalar_text(value)?)?
+                    )),
+                }
+            }
+        },
+    }
+}
+
+/// The SQL expression holding a field's value, and how to compare it.
+fn field_expr(field: &str) -> Result<(String, FieldKind), String> {
+    let segments: Vec<&str> = field.split('.').collect();
+    if segments.iter().any(|segment| !is_valid_segment(segment)) {
+        return Err(
+            "field names may only contain letters, digits, '_' and '-', separated by '.'"
+                .to_string(),
+        );
+    }
+
+    match segments.as_slice() {
+        ["title"] => Ok(("d.title".to_string(), FieldKind::Text)),
+        ["url"] => Ok(("d.url".to_string(), FieldKind::Text)),
+        ["content_type"] => Ok(("d.content_type".to_string(), FieldKind::Text)),
+        ["created_at"] => Ok((DOCUMENT_CREATED_AT.to_string(), FieldKind::Timestamp)),
+        ["updated_at"] => Ok((DOCUMENT_UPDATED_AT.to_string(), FieldKind::Timestamp)),
+        [key @ ("author" | "path" | "mime_type" | "size")] => {
+            Ok((format!("(d.metadata->>'{}')", key), FieldKind::Text))
+        }
+        ["extra", keys @ ..] if !keys.is_empty() => Ok((
+            format!("(d.metadata #>> '{{extra,{}}}')", keys.join(",")),
+            FieldKind::Text,
+        )),
+        ["attributes", key] => Ok((format!("(d.attributes->>'{}')", key), FieldKind::Text)),
+        _ => Err("is not a filterable field".to_string()),
+    }
+}
+
+fn is_valid_segment(segment: &str) -> bool {
+    !segment.is_empty()
+        && segment
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Dates are midnight UTC; timestamps need an offset.
+fn parse_timestamp(value: &str) -> Option<OffsetDateTime> {
+    if let Ok(timestamp) = OffsetDateTime::parse(value, &Rfc3339) {
+        return Some(timestamp);
+    }
+    Date::parse(value, format_description!("[year]-[month]-[day]"))
+        .ok()
+        .map(|date| date.midnight().assume_utc())
+}
+
+fn scalar_text(value: &JsonValue) -> Result<String, String> {
+    match value {
+        JsonValue::String(s) => Ok(s.clone()),
+        JsonValue::Number(n) => Ok(n.to_string()),
+        JsonValue::Bool(b) => Ok(b.to_string()),
+        _ => Err("values must be strings, numbers or booleans".to_string()),
+    }
+}
+
+fn text_literal(value: &str) -> Result<String, String> {
+    if value.contains('\0') {
+        return Err("values must not contain NUL characters".to_string());
+    }
+    Ok(format!("'{}'", value.replace('\'', "''")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn filter(value: JsonValue) -> MetadataFilter {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_compiles_nested_conditions() {
+        let sql = compile(&filter(json!({
+            "type": "and",
+            "filters": [
+                {"type": "condition", "field": "author", "op": "=", "value": "alice"},
+                {"type": "condition", "field": "updated_at", "op": "gt", "value": "2024-01-01"},
+                {"type": "or", "filters": [
+                    {"type": "condition", "field": "path", "op": "like", "value": "/runbooks/%"},
+                    {"type": "condition", "field": "extra.channel.name", "op": "in", "value": ["ops", 7]},
+                ]},
+            ],
+        })))
+        .unwrap();
+
+        assert_eq!(
+            sql,
+            format!(
+                "(((d.metadata->>'author') = 'alice') AND \
+                 ({DOCUMENT_UPDATED_AT} > '2024-01-01T00:00:00Z'::timestamptz) AND \
+                 (((d.metadata->>'path') LIKE '/runbooks/%') OR \
+                 ((d.metadata #>> '{{extra,channel,name}}') = ANY(ARRAY['ops', '7']::text[]))))"
+            )
+        );
+    }
+
+    #[test]
+    fn test_escapes_values_and_compares_numbers_numerically() {
+        let sql = compile(&filter(json!({
+            "type": "condition", "field": "title", "op": "ne", "value": "it's"
+        })))
+        .unwrap();
+        assert_eq!(sql, "(d.title IS DISTINCT FROM 'it''s')");
+
+        let sql = compile(&filter(json!({
+            "type": "condition", "field": "attributes.priority", "op": "gte", "value": 2
+        })))
+        .unwrap();
+        assert!(sql.contains("pg_input_is_valid((d.attributes->>'priority'), 'numeric')"));
+        assert!(sql.ends_with(">= 2)"));
+
+        let sql = compile(&filter(json!({
+            "type": "not",
+            "filter": {"type": "condition", "field": "author", "op": "exists", "value": true}
+        })))
+        .unwrap();
+        assert_eq!(sql, "((((d.metadata->>'author') IS NOT NULL)) IS NOT TRUE)");
+    }
+
+    #[test]
+    fn test_rejects_invalid_filters() {
+        let invalid = [
+            json!({"type": "condition", "field": "metadata'; DROP TABLE documents; --", "op": "eq", "value": "x"}),
+            json!({"type": "condition", "field": "owner", "op": "eq", "value": "x"}),
+            json!({"type": "condition", "field": "updated_at", "op": "like", "value": "2024%"}),
+            json!({"type": "condition", "field": "updated_at", "op": "gt", "value": "last week"}),
+            json!({"type": "condition", "field": "author", "op": "in", "value": []}),
+            json!({"type": "condition", "field": "author", "op": "eq", "value": {"a": 1}}),
+            json!({"type": "condition", "field": "author", "op": "exists", "value": "yes"}),
+            json!({"type": "and", "filters": []}),
+        ];
+        for value in invalid {
+            assert!(compile(&filter(value.clone())).is_err(), "{}", value);
+        }
+
+        let mut nested =
+            filter(json!({"type": "condition", "field": "author", "op": "eq", "value": "a"}));
+        for _ in 0..MAX_DEPTH {
+            nested = MetadataFilter::Not {
+                filter: Box::new(nested),
+            };
+        }
+        assert!(compile(&nested).is_err());
+    }
+}
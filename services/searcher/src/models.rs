@@ -1,9 +1,24 @@
+use crate::collections::{Collection, CollectionVisibility};
+use crate::metadata_filter::{self, MetadataFilter};
+use crate::personalization::PersonalizationDebug;
+use crate::query_ast::{QueryNode, TextQuery};
+use crate::query_intent::{IntentClassification, QueryIntent};
+use crate::query_understanding::UnderstoodQuery;
+use crate::recency::RecencyDecayParams;
+use crate::rerank::MAX_RERANK_TOP_N;
+use crate::source_boosts::{validate_source_boosts, SourceBoosts};
+use crate::snippets::Snippet;
+use crate::source_router::SourceRouting;
 use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{Map, Value as JsonValue};
 use shared::{
-    models::{AttributeFilter, DateFilter, Document, Facet, UserConfiguration},
     SourceType,
+    db::repositories::DocumentShareLink,
+    models::{AttributeFilter, DateFilter, Document, Facet, UserConfiguration},
+    ranking::RankingImpression,
 };
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use time::format_description::well_known::Iso8601;
 
 #[derive(Debug, Clone, Deserialize, Serialize, Hash, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
@@ -13,6 +28,67 @@ pub enum SearchMode {
     Hybrid,
 }
 
+impl SearchMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SearchMode::Fulltext => "fulltext",
+            SearchMode::Semantic => "semantic",
+            SearchMode::Hybrid => "hybrid",
+        }
+    }
+}
+
+/// A dimension search results can be faceted and filtered on.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum FacetDimension {
+    SourceType,
+    /// `metadata.author`
+    Author,
+    /// MIME type
+    ContentType,
+    /// Week the document was last updated, as the Monday it starts on
+    /// (`YYYY-MM-DD`).
+    Week,
+    /// Month the document was last updated (`YYYY-MM`).
+    Month,
+}
+
+impl FacetDimension {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FacetDimension::SourceType => "source_type",
+            FacetDimension::Author => "author",
+            FacetDimension::ContentType => "content_type",
+            FacetDimension::Week => "week",
+            FacetDimension::Month => "month",
+        }
+    }
+
+    /// Whether `value` is a well-formed bucket for this dimension.
+    fn is_valid_value(&self, value: &str) -> bool {
+        match self {
+            FacetDimension::Week => time::Date::parse(
+                value,
+                time::macros::format_description!("[year]-[month]-[day]"),
+            )
+            .is_ok_and(|date| date.weekday() == time::Weekday::Monday),
+            FacetDimension::Month => time::Date::parse(
+                &format!("{}-01", value),
+                time::macros::format_description!("[year]-[month]-[day]"),
+            )
+            .is_ok(),
+            _ => !value.is_empty(),
+        }
+    }
+}
+
+/// Selected values per facet dimension.
+pub type FacetFilters = BTreeMap<FacetDimension, Vec<String>>;
+
+/// Facets computed when the request does not name any.
+pub const DEFAULT_FACETS: &[FacetDimension] = &[FacetDimension::SourceType];
+
 fn deserialize_user_configuration<'de, D>(deserializer: D) -> Result<UserConfiguration, D::Error>
 where
     D: Deserializer<'de>,
@@ -22,7 +98,11 @@ where
 
 #[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct SearchRequest {
+    #[serde(default)]
     pub query: String,
+    /// A structured query, e.g. from an advanced search form, searched
+    /// instead of `query`.
+    pub query_ast: Option<QueryNode>,
     pub source_types: Option<Vec<SourceType>>,
     pub content_types: Option<Vec<String>>,
     /// Attribute filters for filtering by document attributes.
@@ -32,10 +112,21 @@ pub struct SearchRequest {
     /// - `{"labels": ["bug", "urgent"]}` - match any of these values
     /// - `{"date": {"gte": "2024-01-01", "lte": "2024-12-31"}}` - date range
     pub attribute_filters: Option<HashMap<String, AttributeFilter>>,
+    /// Conditions on document fields, nested with `and`, `or` and `not`,
+    /// e.g. `{"type": "and", "filters": [{"type": "condition", "field":
+    /// "author", "op": "eq", "value": "alice"}, {"type": "condition",
+    /// "field": "updated_at", "op": "gt", "value": "2024-01-01"}]}`.
+    pub filter: Option<MetadataFilter>,
     pub limit: Option<i64>,
     pub offset: Option<i64>,
     pub mode: Option<SearchMode>,
     pub include_facets: Option<bool>,
+    /// Facet dimensions to compute; defaults to `DEFAULT_FACETS`.
+    pub facets: Option<Vec<FacetDimension>>,
+    /// Selected facet values. Values within a dimension are OR'ed and
+    /// dimensions are AND'ed. Each facet's counts ignore its own selection,
+    /// so the other values stay selectable.
+    pub facet_filters: Option<FacetFilters>,
     pub user_email: Option<String>,
     pub user_id: Option<String>,
     #[serde(default, deserialize_with = "deserialize_user_configuration")]
@@ -43,21 +134,75 @@ pub struct SearchRequest {
     pub is_generated_query: Option<bool>,
     pub original_user_query: Option<String>,
     pub document_id: Option<String>,
+    /// Restrict results to a collection visible to the user.
+    pub collection_id: Option<String>,
     // This SearchRequest doubles as a ReadDocumentRequest (because we want to support searching
     // through a single doc, to handle large documents).
     // So these next two fields allow us to read a specific set of lines from the document.
     // Both inclusive.
     pub document_content_start_line: Option<u32>,
     pub document_content_end_line: Option<u32>,
+    /// Hit fields to return, e.g. `["title", "url", "snippet", "metadata.author"]`.
+    /// The document id is always included. When absent, hits carry every field.
+    pub fields: Option<Vec<String>>,
+    /// Include diagnostics, such as source routing predictions and
+    /// personalization boosts, in the response.
+    pub debug: Option<bool>,
+    /// When the query finds few results and a spelling correction is
+    /// available, search the corrected query instead if it finds more.
+    pub auto_correct: Option<bool>,
+    /// Per-source-type score multipliers for hybrid search, e.g.
+    /// `{"confluence": 1.5, "slack": 0.8}`. Override the admin-configured
+    /// boosts for the types they name.
+    pub source_boosts: Option<SourceBoosts>,
+    /// Overrides of the hybrid time-decay parameters, for experimenting with
+    /// how strongly recent documents are favored.
+    pub recency_decay: Option<RecencyDecayParams>,
+    /// Collapse results with the same content, e.g. a file synced from Drive
+    /// and also attached in Slack, into one result listing the others under
+    /// `duplicates`. Defaults to true.
+    pub dedupe: Option<bool>,
+    /// Match semantically against the shadow embeddings of a chunking
+    /// experiment instead of production, to compare results before the
+    /// experiment is promoted.
+    pub embedding_namespace: Option<String>,
+    /// Reorder the top hybrid candidates with the cross-encoder reranker.
+    /// Defaults to the searcher's `RERANK_ENABLED` setting.
+    pub rerank: Option<bool>,
+    /// Hybrid candidates to rerank; defaults to `RERANK_TOP_N`.
+    pub rerank_top_n: Option<usize>,
+    /// Restrict results to one kind of content, e.g. `"code"` for source
+    /// files. Matches the `content_kind` attribute set at indexing.
+    pub content_kind: Option<String>,
+    /// ISO 639-3 code of the query's language, e.g. `"deu"`. Detected from
+    /// the query when unset.
+    pub language: Option<String>,
+    /// Take filters written in words out of the query, e.g. "slides last
+    /// quarter" becomes presentations updated last quarter. What was taken
+    /// out is returned as `query_understanding`.
+    pub understand_query: Option<bool>,
     #[serde(skip)]
     pub date_filter: Option<DateFilter>,
     #[serde(skip)]
     pub person_filters: Option<Vec<String>>,
+    /// Weights hybrid fusion toward fulltext or semantic matches.
+    #[serde(skip)]
+    pub intent: Option<QueryIntent>,
+    /// The query translated for semantic retrieval.
+    #[serde(skip)]
+    pub translated_query: Option<String>,
+    /// The words of `query_ast`, matched by fulltext search in place of
+    /// `query`'s terms.
+    #[serde(skip)]
+    pub text_query: Option<TextQuery>,
+    /// `filter` compiled to a SQL predicate.
+    #[serde(skip)]
+    pub filter_sql: Option<String>,
 }
 
 impl SearchRequest {
     pub fn limit(&self) -> i64 {
-        self.limit.unwrap_or(20).min(100)
+        self.limit.unwrap_or(20).min(MAX_SEARCH_LIMIT)
     }
 
     pub fn offset(&self) -> i64 {
@@ -68,13 +213,372 @@ impl SearchRequest {
         self.mode.as_ref().unwrap_or(&SearchMode::Fulltext)
     }
 
+    /// The text embedded for semantic matching: the translated query when
+    /// there is one.
+    pub fn semantic_query(&self) -> &str {
+        self.translated_query.as_deref().unwrap_or(&self.query)
+    }
+
     pub fn include_facets(&self) -> bool {
         self.include_facets.unwrap_or(true)
     }
 
+    pub fn facet_dimensions(&self) -> &[FacetDimension] {
+        self.facets.as_deref().unwrap_or(DEFAULT_FACETS)
+    }
+
+    pub fn debug(&self) -> bool {
+        self.debug.unwrap_or(false)
+    }
+
+    pub fn auto_correct(&self) -> bool {
+        self.auto_correct.unwrap_or(false)
+    }
+
+    pub fn understand_query(&self) -> bool {
+        self.understand_query.unwrap_or(false)
+    }
+
+    pub fn dedupe(&self) -> bool {
+        self.dedupe.unwrap_or(true)
+    }
+
     pub fn user_email(&self) -> Option<&String> {
         self.user_email.as_ref()
     }
+
+    /// Field-level problems that make the request unserviceable. Unknown enum
+    /// values (e.g. `source_types`, `mode`) are rejected earlier, during
+    /// deserialization.
+    pub fn validate(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+
+        if let Some(limit) = self.limit
+            && !(1..=MAX_SEARCH_LIMIT).contains(&limit)
+        {
+            errors.push(FieldError::new(
+                "limit",
+                format!("must be between 1 and {}", MAX_SEARCH_LIMIT),
+            ));
+        }
+        if self.offset.is_some_and(|offset| offset < 0) {
+            errors.push(FieldError::new("offset", "must not be negative"));
+        }
+        if let Some(query_ast) = &self.query_ast {
+            if !self.query.trim().is_empty() {
+                errors.push(FieldError::new(
+                    "query",
+                    "cannot be combined with query_ast",
+                ));
+            }
+            match crate::query_ast::compile(query_ast, &self.user_configuration) {
+                Ok(compiled) => {
+                    let constrained = compiled.text.is_some_and(|text| text.has_constraints());
+                    if constrained && matches!(self.mode, Some(SearchMode::Semantic)) {
+                        errors.push(FieldError::new(
+                            "mode",
+                            "semantic search cannot require or exclude words",
+                        ));
+                    }
+                }
+                Err(message) => errors.push(FieldError::new("query_ast", message)),
+            }
+        }
+
+        if let Some(filter) = &self.filter
+            && let Err(message) = metadata_filter::compile(filter)
+        {
+            errors.push(FieldError::new("filter", message));
+        }
+
+        match self.document_id.as_deref() {
+            Some(document_id) => {
+                if document_id.trim().is_empty() {
+                    errors.push(FieldError::new("document_id", "must not be empty"));
+                }
+                if self.include_facets == Some(true) {
+                    errors.push(FieldError::new(
+                        "include_facets",
+                        "cannot be combined with document_id",
+                    ));
+                }
+            }
+            None => {
+                if self.document_content_start_line.is_some() {
+                    errors.push(FieldError::new(
+                        "document_content_start_line",
+                        "requires document_id",
+                    ));
+                }
+                if self.document_content_end_line.is_some() {
+                    errors.push(FieldError::new(
+                        "document_content_end_line",
+                        "requires document_id",
+                    ));
+                }
+            }
+        }
+        if let (Some(start), Some(end)) = (
+            self.document_content_start_line,
+            self.document_content_end_line,
+        ) && end < start
+        {
+            errors.push(FieldError::new(
+                "document_content_end_line",
+                "must not be before document_content_start_line",
+            ));
+        }
+        if self
+            .collection_id
+            .as_deref()
+            .is_some_and(|id| id.trim().is_empty())
+        {
+            errors.push(FieldError::new("collection_id", "must not be empty"));
+        }
+        if self
+            .embedding_namespace
+            .as_deref()
+            .is_some_and(|namespace| namespace.trim().is_empty())
+        {
+            errors.push(FieldError::new("embedding_namespace", "must not be empty"));
+        }
+        if self
+            .rerank_top_n
+            .is_some_and(|top_n| !(1..=MAX_RERANK_TOP_N).contains(&top_n))
+        {
+            errors.push(FieldError::new(
+                "rerank_top_n",
+                format!("must be between 1 and {}", MAX_RERANK_TOP_N),
+            ));
+        }
+        if self.facets.as_ref().is_some_and(|facets| facets.is_empty()) {
+            errors.push(FieldError::new("facets", "must not be empty"));
+        }
+        for (dimension, values) in self.facet_filters.iter().flatten() {
+            let field = format!("facet_filters.{}", dimension.as_str());
+            if values.is_empty() {
+                errors.push(FieldError::new(field, "must select at least one value"));
+            } else if let Some(value) = values.iter().find(|v| !dimension.is_valid_value(v)) {
+                errors.push(FieldError::new(
+                    field,
+                    format!("'{}' is not a valid {} value", value, dimension.as_str()),
+                ));
+            }
+        }
+        if let Some(source_boosts) = &self.source_boosts {
+            errors.extend(validate_source_boosts("source_boosts", source_boosts));
+        }
+        if let Some(recency_decay) = &self.recency_decay {
+            errors.extend(recency_decay.validate());
+        }
+        if let Err(field_errors) = self.field_selection() {
+            errors.extend(field_errors);
+        }
+
+        errors
+    }
+
+    /// Parsed `fields` parameter; `None` when every field should be returned.
+    pub fn field_selection(&self) -> Result<Option<FieldSelection>, Vec<FieldError>> {
+        self.fields
+            .as_deref()
+            .map(FieldSelection::parse)
+            .transpose()
+    }
+}
+
+/// Document fields selectable by name through `SearchRequest::fields`.
+const SELECTABLE_DOCUMENT_FIELDS: &[&str] = &[
+    "id",
+    "source_id",
+    "external_id",
+    "title",
+    "url",
+    "content_type",
+    "file_size",
+    "file_extension",
+    "created_at",
+    "updated_at",
+];
+
+/// Hit-level fields selectable by name. `snippet` selects `highlights`.
+const SELECTABLE_HIT_FIELDS: &[&str] = &[
+    "score",
+    "snippet",
+    "snippets",
+    "match_type",
+    "content",
+    "source_type",
+    "also_in",
+    "duplicates",
+    "possibly_stale",
+    "location",
+    "page",
+    "heading_path",
+];
+
+/// Which keys of a JSON object field (`metadata`, `attributes`) to return.
+#[derive(Debug, Clone, Default, PartialEq)]
+enum ObjectSelection {
+    #[default]
+    None,
+    All,
+    Keys(BTreeSet<String>),
+}
+
+impl ObjectSelection {
+    fn add_key(&mut self, key: &str) {
+        match self {
+            ObjectSelection::All => {}
+            ObjectSelection::Keys(keys) => {
+                keys.insert(key.to_string());
+            }
+            ObjectSelection::None => {
+                *self = ObjectSelection::Keys(BTreeSet::from([key.to_string()]));
+            }
+        }
+    }
+
+    fn project(&self, value: &JsonValue) -> Option<JsonValue> {
+        match self {
+            ObjectSelection::None => None,
+            ObjectSelection::All => Some(value.clone()),
+            ObjectSelection::Keys(keys) => {
+                let subset: Map<String, JsonValue> = keys
+                    .iter()
+                    .filter_map(|key| value.get(key).map(|v| (key.clone(), v.clone())))
+                    .collect();
+                Some(JsonValue::Object(subset))
+            }
+        }
+    }
+}
+
+/// Subset of hit fields to serialize, parsed from `SearchRequest::fields`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FieldSelection {
+    document: BTreeSet<&'static str>,
+    hit: BTreeSet<&'static str>,
+    metadata: ObjectSelection,
+    attributes: ObjectSelection,
+}
+
+impl FieldSelection {
+    pub fn parse(fields: &[String]) -> Result<Self, Vec<FieldError>> {
+        let mut selection = FieldSelection::default();
+        let mut errors = Vec::new();
+
+        for (idx, field) in fields.iter().enumerate() {
+            let field = field.trim();
+            if let Some(name) = SELECTABLE_DOCUMENT_FIELDS.iter().find(|f| **f == field) {
+                selection.document.insert(name);
+            } else if let Some(name) = SELECTABLE_HIT_FIELDS.iter().find(|f| **f == field) {
+                selection.hit.insert(name);
+            } else if field == "metadata" {
+                selection.metadata = ObjectSelection::All;
+            } else if field == "attributes" {
+                selection.attributes = ObjectSelection::All;
+            } else if let Some(key) = field.strip_prefix("metadata.").filter(|k| !k.is_empty()) {
+                selection.metadata.add_key(key);
+            } else if let Some(key) = field.strip_prefix("attributes.").filter(|k| !k.is_empty()) {
+                selection.attributes.add_key(key);
+            } else {
+                errors.push(FieldError::new(
+                    format!("fields[{}]", idx),
+                    format!("unknown field '{}'", field),
+                ));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(selection)
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Serialize only the selected fields of `result`, keeping the usual hit
+    /// shape. Builds the JSON directly so unselected fields are never
+    /// serialized.
+    pub fn project(&self, result: &SearchResult) -> JsonValue {
+        let doc = &result.document;
+        let mut document = Map::new();
+        document.insert("id".to_string(), JsonValue::from(doc.id.as_str()));
+        for field in &self.document {
+            let value = match *field {
+                "source_id" => JsonValue::from(doc.source_id.as_str()),
+                "external_id" => JsonValue::from(doc.external_id.as_str()),
+                "title" => JsonValue::from(doc.title.as_str()),
+                "url" => JsonValue::from(doc.url.clone()),
+                "content_type" => JsonValue::from(doc.content_type.clone()),
+                "file_size" => JsonValue::from(doc.file_size),
+                "file_extension" => JsonValue::from(doc.file_extension.clone()),
+                "created_at" => JsonValue::from(doc.created_at.format(&Iso8601::DEFAULT).ok()),
+                "updated_at" => JsonValue::from(doc.updated_at.format(&Iso8601::DEFAULT).ok()),
+                _ => continue,
+            };
+            document.insert(field.to_string(), value);
+        }
+        if let Some(metadata) = self.metadata.project(&doc.metadata) {
+            document.insert("metadata".to_string(), metadata);
+        }
+        if let Some(attributes) = self.attributes.project(&doc.attributes) {
+            document.insert("attributes".to_string(), attributes);
+        }
+
+        let mut hit = Map::new();
+        hit.insert("document".to_string(), JsonValue::Object(document));
+        for field in &self.hit {
+            let (key, value) = match *field {
+                "score" => ("score", JsonValue::from(result.score)),
+                "snippet" => ("highlights", JsonValue::from(result.highlights.clone())),
+                "snippets" => (
+                    "snippets",
+                    serde_json::to_value(&result.snippets).unwrap_or_default(),
+                ),
+                "match_type" => ("match_type", JsonValue::from(result.match_type.as_str())),
+                "content" => ("content", JsonValue::from(result.content.clone())),
+                "source_type" => ("source_type", JsonValue::from(result.source_type.clone())),
+                "also_in" => (
+                    "also_in",
+                    serde_json::to_value(&result.also_in).unwrap_or_default(),
+                ),
+                "duplicates" => (
+                    "duplicates",
+                    serde_json::to_value(&result.duplicates).unwrap_or_default(),
+                ),
+                "possibly_stale" => ("possibly_stale", JsonValue::from(result.possibly_stale)),
+                "location" => (
+                    "location",
+                    serde_json::to_value(result.location).unwrap_or_default(),
+                ),
+                "page" => ("page", JsonValue::from(result.page)),
+                "heading_path" => ("heading_path", JsonValue::from(result.heading_path.clone())),
+                _ => continue,
+            };
+            hit.insert(key.to_string(), value);
+        }
+        JsonValue::Object(hit)
+    }
+}
+
+/// Upper bound on `SearchRequest::limit`; larger values are rejected.
+pub const MAX_SEARCH_LIMIT: i64 = 100;
+
+/// One field-level validation failure, reported in 422 responses.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            message: message.into(),
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -88,6 +592,120 @@ pub struct SearchResponse {
     pub facets: Option<Vec<Facet>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub active_filters: Option<Vec<Facet>>,
+    /// Spelling corrections of the query, best first, when it found few
+    /// results.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub did_you_mean: Option<Vec<String>>,
+    /// The corrected query the results are for, when `auto_correct` replaced
+    /// the query. `query` is still the query as typed.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub corrected_query: Option<String>,
+    /// Present only when the request set `debug`.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub source_routing: Option<SourceRouting>,
+    /// Present only when the request set `debug`.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub personalization: Option<PersonalizationDebug>,
+    /// What the query is after, e.g. to decide whether to offer an AI
+    /// answer. Absent when intent classification is disabled.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub intent: Option<IntentClassification>,
+    /// Filters taken out of the query, when the request set
+    /// `understand_query` and any were found.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub query_understanding: Option<UnderstoodQuery>,
+    /// Identifies this search in the analytics log; send it with clicks on
+    /// its results.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub search_event_id: Option<String>,
+    /// Ranking features of the results in the order returned, logged with the
+    /// search for training the ranking profile.
+    #[serde(skip)]
+    pub ranking_impressions: Vec<RankingImpression>,
+}
+
+impl SearchResponse {
+    /// Serialize the response, trimming each hit to `selection` when given.
+    pub fn to_value(&self, selection: Option<&FieldSelection>) -> serde_json::Result<JsonValue> {
+        let Some(selection) = selection else {
+            return serde_json::to_value(self);
+        };
+
+        serde_json::to_value(ProjectedSearchResponse {
+            results: self
+                .results
+                .iter()
+                .map(|result| selection.project(result))
+                .collect(),
+            total_count: self.total_count,
+            query_time_ms: self.query_time_ms,
+            has_more: self.has_more,
+            query: &self.query,
+            facets: self.facets.as_ref(),
+            active_filters: self.active_filters.as_ref(),
+            did_you_mean: self.did_you_mean.as_ref(),
+            corrected_query: self.corrected_query.as_deref(),
+            source_routing: self.source_routing.as_ref(),
+            personalization: self.personalization.as_ref(),
+            intent: self.intent.as_ref(),
+            query_understanding: self.query_understanding.as_ref(),
+            search_event_id: self.search_event_id.as_deref(),
+        })
+    }
+}
+
+#[derive(Serialize)]
+struct ProjectedSearchResponse<'a> {
+    results: Vec<JsonValue>,
+    total_count: i64,
+    query_time_ms: u64,
+    has_more: bool,
+    query: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    facets: Option<&'a Vec<Facet>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    active_filters: Option<&'a Vec<Facet>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    did_you_mean: Option<&'a Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    corrected_query: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source_routing: Option<&'a SourceRouting>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    personalization: Option<&'a PersonalizationDebug>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    intent: Option<&'a IntentClassification>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    query_understanding: Option<&'a UnderstoodQuery>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    search_event_id: Option<&'a str>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchStreamStage {
+    /// Fulltext hits, sent while semantic ranking is still running. Superseded
+    /// by the final response.
+    Partial,
+    /// The fully ranked response. Always the last event of a stream.
+    Final,
+}
+
+impl SearchStreamStage {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SearchStreamStage::Partial => "partial",
+            SearchStreamStage::Final => "final",
+        }
+    }
+}
+
+/// One event of a streamed search. `response` has the same shape as the
+/// `/search` response.
+#[derive(Debug, Serialize)]
+pub struct SearchStreamEnvelope {
+    pub stage: SearchStreamStage,
+    pub response: JsonValue,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -95,6 +713,10 @@ pub struct SearchResult {
     pub document: Document,
     pub score: f32,
     pub highlights: Vec<String>,
+    /// The passages of `highlights` with matched words as offsets, for
+    /// clients that render emphasis themselves.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub snippets: Vec<Snippet>,
     pub match_type: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub content: Option<String>,
@@ -102,6 +724,74 @@ pub struct SearchResult {
     pub source_type: Option<String>,
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
     pub also_in: Vec<AlsoIn>,
+    /// Other results with the same content, collapsed into this one.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub duplicates: Vec<DuplicateHit>,
+    /// Set when the document's source is in maintenance and its content may
+    /// be out of date.
+    #[serde(skip_serializing_if = "std::ops::Not::not", default)]
+    pub possibly_stale: bool,
+    /// Where the match is within the document, for searches scoped to one
+    /// document with `document_id`.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub location: Option<MatchLocation>,
+    /// 1-based page of the match, for documents with pages such as PDFs.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub page: Option<i32>,
+    /// Section of the match, as the headings enclosing it joined with " > ",
+    /// for semantic matches in documents with headings.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub heading_path: Option<String>,
+}
+
+#[cfg(test)]
+impl SearchResult {
+    /// A hybrid hit with score 1.0 on an otherwise empty document whose id,
+    /// external id and title are `id`. Tests override what they look at.
+    pub(crate) fn for_test(id: &str) -> Self {
+        let now = time::OffsetDateTime::now_utc();
+        SearchResult {
+            document: Document {
+                id: id.to_string(),
+                source_id: "source".to_string(),
+                external_id: id.to_string(),
+                title: id.to_string(),
+                content_id: None,
+                content_type: None,
+                file_size: None,
+                file_extension: None,
+                url: None,
+                metadata: serde_json::json!({}),
+                permissions: serde_json::json!({}),
+                attributes: serde_json::json!({}),
+                created_at: now,
+                updated_at: now,
+                last_indexed_at: now,
+            },
+            score: 1.0,
+            highlights: Vec::new(),
+            snippets: Vec::new(),
+            match_type: "hybrid".to_string(),
+            content: None,
+            source_type: None,
+            also_in: Vec::new(),
+            duplicates: Vec::new(),
+            location: None,
+            page: None,
+            heading_path: None,
+            possibly_stale: false,
+        }
+    }
+}
+
+/// Lines of a match within its document, 1-based and inclusive. The window
+/// is the match plus its surrounding context lines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MatchLocation {
+    pub start_line: u32,
+    pub end_line: u32,
+    pub match_start_line: u32,
+    pub match_end_line: u32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -111,6 +801,19 @@ pub struct AlsoIn {
     pub score: f32,
 }
 
+/// A result collapsed into a higher-ranked one with the same content.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateHit {
+    pub document_id: String,
+    pub source_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_type: Option<String>,
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    pub score: f32,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct RecentSearchesRequest {
     pub user_id: String,
@@ -127,34 +830,150 @@ pub struct SuggestedQuestion {
     pub document_id: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct SuggestedQuestionsRequest {
+#[derive(Debug, Clone, Deserialize)]
+pub struct SuggestedQuestionsQuery {
     pub user_id: String,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+}
+
+impl SuggestedQuestionsQuery {
+    pub fn limit(&self) -> usize {
+        self.limit.unwrap_or(9).clamp(1, 50)
+    }
+
+    pub fn offset(&self) -> usize {
+        self.offset.unwrap_or(0)
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SuggestedQuestionsResponse {
     pub questions: Vec<SuggestedQuestion>,
+    pub total_count: usize,
+    pub has_more: bool,
+}
+
+impl SuggestedQuestionsResponse {
+    pub fn page(questions: Vec<SuggestedQuestion>, limit: usize, offset: usize) -> Self {
+        let total_count = questions.len();
+        let questions: Vec<SuggestedQuestion> =
+            questions.into_iter().skip(offset).take(limit).collect();
+        let has_more = offset + questions.len() < total_count;
+        Self {
+            questions,
+            total_count,
+            has_more,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
 pub struct TypeaheadQuery {
     pub q: String,
     pub limit: Option<usize>,
+    /// Comma-separated suggestion groups to return: `documents`, `people`,
+    /// `sources`, `queries`. Defaults to `documents`.
+    pub types: Option<String>,
+    pub people_limit: Option<usize>,
+    pub sources_limit: Option<usize>,
+    pub queries_limit: Option<usize>,
+    /// Requesting user. Documents are trimmed to what this email can read
+    /// (public only when absent); people, sources and previous queries are
+    /// only returned for an identified user.
+    pub user_email: Option<String>,
+    pub user_id: Option<String>,
 }
 
 impl TypeaheadQuery {
     pub fn limit(&self) -> usize {
         self.limit.unwrap_or(5).min(20)
     }
+
+    pub fn group_limit(&self, group: TypeaheadGroupType) -> usize {
+        let requested = match group {
+            TypeaheadGroupType::Documents => return self.limit(),
+            TypeaheadGroupType::People => self.people_limit,
+            TypeaheadGroupType::Sources => self.sources_limit,
+            TypeaheadGroupType::Queries => self.queries_limit,
+        };
+        requested.unwrap_or(3).min(10)
+    }
+
+    /// Requested groups in response order, deduplicated. Unknown names are
+    /// ignored.
+    pub fn groups(&self) -> Vec<TypeaheadGroupType> {
+        let Some(types) = self.types.as_deref() else {
+            return vec![TypeaheadGroupType::Documents];
+        };
+        let mut groups: Vec<TypeaheadGroupType> = types
+            .split(',')
+            .filter_map(|t| TypeaheadGroupType::parse(t.trim()))
+            .collect();
+        groups.sort();
+        groups.dedup();
+        groups
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum TypeaheadGroupType {
+    Documents,
+    People,
+    Sources,
+    Queries,
+}
+
+impl TypeaheadGroupType {
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "documents" => Some(Self::Documents),
+            "people" => Some(Self::People),
+            "sources" => Some(Self::Sources),
+            "queries" => Some(Self::Queries),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Serialize)]
 pub struct TypeaheadResponse {
+    /// Document suggestions; kept alongside `groups` for existing clients.
     pub results: Vec<TypeaheadResult>,
+    pub groups: Vec<TypeaheadGroup>,
     pub query: String,
 }
 
+#[derive(Debug, Serialize)]
+pub struct TypeaheadGroup {
+    #[serde(rename = "type")]
+    pub group_type: TypeaheadGroupType,
+    pub suggestions: Vec<TypeaheadSuggestion>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TypeaheadSuggestion {
+    Document(TypeaheadResult),
+    Person {
+        id: String,
+        email: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        display_name: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        job_title: Option<String>,
+    },
+    Source {
+        source_id: String,
+        name: String,
+        source_type: SourceType,
+    },
+    Query {
+        query: String,
+    },
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct TypeaheadResult {
     pub document_id: String,
@@ -269,6 +1088,87 @@ pub struct CapabilitySearchResponse {
     pub results: Vec<CapabilitySearchResult>,
 }
 
+/// Identifies the caller of the collections API.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct CollectionUserQuery {
+    pub user_id: Option<String>,
+    pub user_email: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateCollectionRequest {
+    pub user_id: String,
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub visibility: CollectionVisibility,
+    #[serde(default)]
+    pub team_groups: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct UpdateCollectionRequest {
+    pub user_id: String,
+    pub user_email: Option<String>,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub visibility: Option<CollectionVisibility>,
+    pub team_groups: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AddCollectionDocumentsRequest {
+    pub user_id: String,
+    pub user_email: String,
+    pub document_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AddCollectionDocumentsResponse {
+    pub added: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CollectionResponse {
+    #[serde(flatten)]
+    pub collection: Collection,
+    pub document_ids: Vec<String>,
+}
+
+/// Identifies the caller of the share links API.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ShareLinkUserQuery {
+    pub user_id: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateShareLinkRequest {
+    pub user_id: String,
+    /// Hours until the link expires; the policy's default when omitted.
+    pub ttl_hours: Option<i32>,
+    /// Fetches the link allows; the policy's limit when omitted.
+    pub max_accesses: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CreateShareLinkResponse {
+    pub link: DocumentShareLink,
+    /// Shown once; only its hash is stored.
+    pub token: String,
+}
+
+/// A document fetched through a share link.
+#[derive(Debug, Clone, Serialize)]
+pub struct SharedDocumentResponse {
+    pub title: String,
+    pub content_type: Option<String>,
+    pub content: String,
+    #[serde(with = "time::serde::iso8601")]
+    pub updated_at: time::OffsetDateTime,
+    #[serde(with = "time::serde::iso8601")]
+    pub expires_at: time::OffsetDateTime,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -298,6 +1198,248 @@ mod tests {
         assert_eq!(request.offset(), 0); // Negative offset should become 0
     }
 
+    #[test]
+    fn test_facet_filter_validation() {
+        let request: SearchRequest = serde_json::from_value(serde_json::json!({
+            "query": "test",
+            "facets": ["author", "week"],
+            "facet_filters": {
+                "source_type": ["slack", "jira"],
+                "week": ["2026-10-12"],
+                "month": ["2026-10"]
+            }
+        }))
+        .unwrap();
+        assert_eq!(
+            request.facet_dimensions(),
+            &[FacetDimension::Author, FacetDimension::Week]
+        );
+        assert!(request.validate().is_empty());
+
+        let invalid: SearchRequest = serde_json::from_value(serde_json::json!({
+            "query": "test",
+            "facets": [],
+            "facet_filters": {
+                "author": [],
+                // Not a Monday
+                "week": ["2026-10-14"],
+                "month": ["October"]
+            }
+        }))
+        .unwrap();
+        let fields: Vec<String> = invalid.validate().into_iter().map(|e| e.field).collect();
+        assert_eq!(
+            fields,
+            vec![
+                "facets",
+                "facet_filters.author",
+                "facet_filters.week",
+                "facet_filters.month",
+            ]
+        );
+        assert_eq!(
+            SearchRequest::default().facet_dimensions(),
+            &[FacetDimension::SourceType]
+        );
+    }
+
+    #[test]
+    fn test_search_request_validation() {
+        let valid = SearchRequest {
+            query: "test".to_string(),
+            limit: Some(100),
+            document_id: Some("doc1".to_string()),
+            document_content_start_line: Some(10),
+            document_content_end_line: Some(10),
+            include_facets: Some(false),
+            ..Default::default()
+        };
+        assert!(valid.validate().is_empty());
+
+        let invalid = SearchRequest {
+            query: "test".to_string(),
+            limit: Some(0),
+            offset: Some(-1),
+            document_content_start_line: Some(20),
+            document_content_end_line: Some(10),
+            ..Default::default()
+        };
+        let fields: Vec<String> = invalid.validate().into_iter().map(|e| e.field).collect();
+        assert_eq!(
+            fields,
+            vec![
+                "limit",
+                "offset",
+                "document_content_start_line",
+                "document_content_end_line",
+                "document_content_end_line",
+            ]
+        );
+
+        let conflicting = SearchRequest {
+            query: "test".to_string(),
+            document_id: Some("doc1".to_string()),
+            include_facets: Some(true),
+            ..Default::default()
+        };
+        assert_eq!(
+            conflicting.validate(),
+            vec![FieldError::new(
+                "include_facets",
+                "cannot be combined with document_id"
+            )]
+        );
+
+        let blank_namespace = SearchRequest {
+            query: "test".to_string(),
+            embedding_namespace: Some(" ".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            blank_namespace.validate(),
+            vec![FieldError::new("embedding_namespace", "must not be empty")]
+        );
+
+        let too_many_to_rerank = SearchRequest {
+            query: "test".to_string(),
+            rerank_top_n: Some(MAX_RERANK_TOP_N + 1),
+            ..Default::default()
+        };
+        assert_eq!(
+            too_many_to_rerank.validate(),
+            vec![FieldError::new(
+                "rerank_top_n",
+                format!("must be between 1 and {}", MAX_RERANK_TOP_N)
+            )]
+        );
+    }
+
+    #[test]
+    fn test_search_request_query_ast_validation() {
+        let request: SearchRequest = serde_json::from_value(serde_json::json!({
+            "query_ast": {"type": "and", "nodes": [
+                {"type": "term", "value": "roadmap"},
+                {"type": "not", "node": {"type": "term", "value": "draft"}}
+            ]}
+        }))
+        .unwrap();
+        assert!(request.validate().is_empty());
+
+        let semantic = SearchRequest {
+            query: "roadmap".to_string(),
+            mode: Some(SearchMode::Semantic),
+            ..request.clone()
+        };
+        let fields: Vec<String> = semantic.validate().into_iter().map(|e| e.field).collect();
+        assert_eq!(fields, vec!["query", "mode"]);
+
+        let only_excluded = SearchRequest {
+            query_ast: Some(QueryNode::Not {
+                node: Box::new(QueryNode::Term {
+                    value: "draft".to_string(),
+                }),
+            }),
+            ..Default::default()
+        };
+        assert_eq!(
+            only_excluded.validate(),
+            vec![FieldError::new(
+                "query_ast",
+                "must include words to match besides excluded ones"
+            )]
+        );
+    }
+
+    #[test]
+    fn test_search_request_filter_validation() {
+        let request: SearchRequest = serde_json::from_value(serde_json::json!({
+            "filter": {"type": "and", "filters": [
+                {"type": "condition", "field": "author", "op": "=", "value": "alice"},
+                {"type": "condition", "field": "updated_at", "op": ">", "value": "2024-01-01"},
+                {"type": "condition", "field": "path", "op": "like", "value": "/runbooks/%"}
+            ]}
+        }))
+        .unwrap();
+        assert!(request.validate().is_empty());
+
+        let unknown_field: SearchRequest = serde_json::from_value(serde_json::json!({
+            "query": "roadmap",
+            "filter": {"type": "condition", "field": "owner", "op": "eq", "value": "alice"}
+        }))
+        .unwrap();
+        assert_eq!(
+            unknown_field.validate(),
+            vec![FieldError::new("filter", "owner: is not a filterable field")]
+        );
+    }
+
+    #[test]
+    fn test_field_selection_projects_hits() {
+        let fields: Vec<String> = ["title", "snippet", "metadata.author", "attributes"]
+            .iter()
+            .map(|f| f.to_string())
+            .collect();
+        let selection = FieldSelection::parse(&fields).unwrap();
+
+        let mut result = SearchResult::for_test("doc1");
+        result.document.title = "Roadmap".to_string();
+        result.document.url = Some("https://example.com/roadmap".to_string());
+        result.document.metadata = serde_json::json!({"author": "alice", "size": 10});
+        result.document.permissions = serde_json::json!({"public": true});
+        result.document.attributes = serde_json::json!({"status": "done"});
+        result.score = 1.5;
+        result.highlights = vec!["the <b>roadmap</b>".to_string()];
+        result.match_type = "fulltext".to_string();
+
+        assert_eq!(
+            selection.project(&result),
+            serde_json::json!({
+                "document": {
+                    "id": "doc1",
+                    "title": "Roadmap",
+                    "metadata": {"author": "alice"},
+                    "attributes": {"status": "done"}
+                },
+                "highlights": ["the <b>roadmap</b>"]
+            })
+        );
+
+        let errors =
+            FieldSelection::parse(&["title".to_string(), "permissions".to_string()]).unwrap_err();
+        assert_eq!(
+            errors,
+            vec![FieldError::new("fields[1]", "unknown field 'permissions'")]
+        );
+    }
+
+    #[test]
+    fn test_field_selection_projects_page() {
+        let selection = FieldSelection::parse(&["page".to_string()]).unwrap();
+        let mut result = SearchResult::for_test("doc1");
+        result.page = Some(3);
+
+        assert_eq!(
+            selection.project(&result),
+            serde_json::json!({"document": {"id": "doc1"}, "page": 3})
+        );
+    }
+
+    #[test]
+    fn test_field_selection_projects_heading_path() {
+        let fields = ["title".to_string(), "heading_path".to_string()];
+        let selection = FieldSelection::parse(&fields).unwrap();
+        let mut result = SearchResult::for_test("doc1");
+        result.heading_path = Some("Runbook > Rollbacks".to_string());
+
+        assert_eq!(
+            selection.project(&result),
+            serde_json::json!({
+                "document": {"id": "doc1", "title": "doc1"},
+                "heading_path": "Runbook > Rollbacks"
+            })
+        );
+    }
+
     #[test]
     fn test_search_modes() {
         let modes = vec![
@@ -359,4 +1501,66 @@ mod tests {
 
         assert_eq!(request.user_configuration, UserConfiguration::default());
     }
+
+    #[test]
+    fn test_suggested_questions_page() {
+        let questions: Vec<SuggestedQuestion> = (0..5)
+            .map(|i| SuggestedQuestion {
+                question: format!("Question {}?", i),
+                document_id: format!("doc{}", i),
+            })
+            .collect();
+
+        let page = SuggestedQuestionsResponse::page(questions.clone(), 2, 2);
+        assert_eq!(page.questions[0].question, "Question 2?");
+        assert_eq!(page.questions.len(), 2);
+        assert_eq!(page.total_count, 5);
+        assert!(page.has_more);
+
+        let last = SuggestedQuestionsResponse::page(questions.clone(), 2, 4);
+        assert_eq!(last.questions.len(), 1);
+        assert!(!last.has_more);
+
+        let past_end = SuggestedQuestionsResponse::page(questions, 2, 10);
+        assert!(past_end.questions.is_empty());
+        assert!(!past_end.has_more);
+    }
+
+    #[test]
+    fn test_typeahead_groups_parsing() {
+        let query: TypeaheadQuery = serde_json::from_value(serde_json::json!({
+            "q": "road",
+            "types": "queries, people,bogus,documents,people",
+            "people_limit": 50
+        }))
+        .unwrap();
+
+        assert_eq!(
+            query.groups(),
+            vec![
+                TypeaheadGroupType::Documents,
+                TypeaheadGroupType::People,
+                TypeaheadGroupType::Queries,
+            ]
+        );
+        assert_eq!(query.group_limit(TypeaheadGroupType::People), 10);
+        assert_eq!(query.group_limit(TypeaheadGroupType::Queries), 3);
+        assert_eq!(query.group_limit(TypeaheadGroupType::Documents), 5);
+
+        let default: TypeaheadQuery =
+            serde_json::from_value(serde_json::json!({ "q": "road" })).unwrap();
+        assert_eq!(default.groups(), vec![TypeaheadGroupType::Documents]);
+    }
+
+    #[test]
+    fn test_typeahead_suggestion_is_type_tagged() {
+        let value = serde_json::to_value(TypeaheadSuggestion::Query {
+            query: "roadmap".to_string(),
+        })
+        .unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({ "type": "query", "query": "roadmap" })
+        );
+    }
 }
@@ -0,0 +1,241 @@
+//! Per-user ranking signals.
+//!
+//! Results the searching user wrote, opened recently, or that are shared with
+//! teams they actively work with get a multiplicative boost after retrieval,
+//! scaled by `SearcherConfig::personalization_weight`. Signals come from the
+//! people directory (identity), group memberships and recorded search clicks.
+
+use crate::models::SearchResult;
+use serde::{Deserialize, Serialize};
+use shared::db::repositories::PersonRepository;
+use sqlx::PgPool;
+use std::collections::HashSet;
+
+/// How far back clicks count as "recently viewed" and as team interaction.
+const INTERACTION_WINDOW_DAYS: i32 = 30;
+const MAX_VIEWED_DOCUMENTS: i64 = 500;
+
+const AUTHORED_WEIGHT: f32 = 1.0;
+const VIEWED_WEIGHT: f32 = 0.6;
+const TEAM_WEIGHT: f32 = 0.3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PersonalSignal {
+    Authored,
+    RecentlyViewed,
+    Team,
+}
+
+impl PersonalSignal {
+    fn weight(&self) -> f32 {
+        match self {
+            PersonalSignal::Authored => AUTHORED_WEIGHT,
+            PersonalSignal::RecentlyViewed => VIEWED_WEIGHT,
+            PersonalSignal::Team => TEAM_WEIGHT,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersonalizedResult {
+    pub document_id: String,
+    pub signals: Vec<PersonalSignal>,
+    pub multiplier: f32,
+}
+
+/// Which results were boosted and why; returned when the request sets `debug`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersonalizationDebug {
+    pub weight: f32,
+    pub boosted: Vec<PersonalizedResult>,
+}
+
+#[derive(Debug, Default)]
+pub struct UserSignals {
+    /// Lowercased strings an `author` field may use for this user: their
+    /// email and, from the people directory, their display name.
+    identities: Vec<String>,
+    viewed_document_ids: HashSet<String>,
+    /// Groups the user belongs to and has opened documents shared with.
+    team_groups: HashSet<String>,
+}
+
+impl UserSignals {
+    pub async fn load(
+        pool: &PgPool,
+        user_id: Option<&str>,
+        user_email: &str,
+        user_groups: &[String],
+    ) -> anyhow::Result<Self> {
+        let mut identities = vec![user_email.to_lowercase()];
+        if let Some(person) = PersonRepository::new(pool)
+            .fetch_person_by_email(user_email)
+            .await?
+        {
+            identities.extend(
+                person
+                    .display_name
+                    .map(|name| name.trim().to_lowercase())
+                    .filter(|name| !name.is_empty()),
+            );
+        }
+
+        let Some(user_id) = user_id else {
+            return Ok(Self {
+                identities,
+                ..Default::default()
+            });
+        };
+
+        let viewed_document_ids: Vec<String> = sqlx::query_scalar(
+            r#"
+            SELECT document_id
+            FROM search_clicks
+            WHERE user_id = $1
+              AND created_at > NOW() - make_interval(days => $2)
+            GROUP BY document_id
+            ORDER BY MAX(created_at) DESC
+            LIMIT $3
+            "#,
+        )
+        .bind(user_id)
+        .bind(INTERACTION_WINDOW_DAYS)
+        .bind(MAX_VIEWED_DOCUMENTS)
+        .fetch_all(pool)
+        .await?;
+
+        let team_groups: Vec<String> = if user_groups.is_empty() {
+            Vec::new()
+        } else {
+            let groups: Vec<String> = user_groups.iter().map(|g| g.to_lowercase()).collect();
+            sqlx::query_scalar(
+                r#"
+                SELECT DISTINCT lower(g.value)
+                FROM search_clicks c
+                JOIN documents d ON d.id = c.document_id
+                CROSS JOIN LATERAL jsonb_array_elements_text(d.permissions->'groups') AS g(value)
+                WHERE c.user_id = $1
+                  AND c.created_at > NOW() - make_interval(days => $2)
+                  AND lower(g.value) = ANY($3)
+                "#,
+            )
+            .bind(user_id)
+            .bind(INTERACTION_WINDOW_DAYS)
+            .bind(&groups)
+            .fetch_all(pool)
+            .await?
+        };
+
+        Ok(Self {
+            identities,
+            viewed_document_ids: viewed_document_ids.into_iter().collect(),
+            team_groups: team_groups.into_iter().collect(),
+        })
+    }
+
+    fn signals_for(&self, result: &SearchResult) -> Vec<PersonalSignal> {
+        let document = &result.document;
+        let mut signals = Vec::new();
+
+        if let Some(author) = document.metadata.get("author").and_then(|a| a.as_str()) {
+            let author = author.to_lowercase();
+            if self.identities.iter().any(|id| author.contains(id)) {
+                signals.push(PersonalSignal::Authored);
+            }
+        }
+        if self.viewed_document_ids.contains(&document.id) {
+            signals.push(PersonalSignal::RecentlyViewed);
+        }
+        if !self.team_groups.is_empty() {
+            let shared_with_team = document
+                .permissions
+                .get("groups")
+                .and_then(|g| g.as_array())
+                .is_some_and(|groups| {
+                    groups
+                        .iter()
+                        .filter_map(|g| g.as_str())
+                        .any(|g| self.team_groups.contains(&g.to_lowercase()))
+                });
+            if shared_with_team {
+                signals.push(PersonalSignal::Team);
+            }
+        }
+
+        signals
+    }
+
+    /// Boost matching results in place. Returns the boosted results; the
+    /// caller re-sorts when the list is non-empty.
+    pub fn apply(&self, results: &mut [SearchResult], weight: f32) -> Vec<PersonalizedResult> {
+        let mut boosted = Vec::new();
+        for result in results.iter_mut() {
+            let signals = self.signals_for(result);
+            if signals.is_empty() {
+                continue;
+            }
+            let multiplier = 1.0 + weight * signals.iter().map(|s| s.weight()).sum::<f32>();
+            result.score *= multiplier;
+            boosted.push(PersonalizedResult {
+                document_id: result.document.id.clone(),
+                signals,
+                multiplier,
+            });
+        }
+        boosted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn result(id: &str, author: &str, groups: &[&str]) -> SearchResult {
+        let mut result = SearchResult::for_test(id);
+        result.document.metadata = json!({ "author": author });
+        result.document.permissions = json!({ "users": [], "groups": groups });
+        result
+    }
+
+    fn signals() -> UserSignals {
+        UserSignals {
+            identities: vec!["ada@example.com".to_string(), "ada lovelace".to_string()],
+            viewed_document_ids: HashSet::from(["viewed".to_string()]),
+            team_groups: HashSet::from(["eng@example.com".to_string()]),
+        }
+    }
+
+    #[test]
+    fn test_signals_match_authorship_views_and_teams() {
+        let signals = signals();
+        assert_eq!(
+            signals.signals_for(&result("doc", "Ada Lovelace", &[])),
+            vec![PersonalSignal::Authored]
+        );
+        assert_eq!(
+            signals.signals_for(&result("viewed", "Someone Else", &["ENG@example.com"])),
+            vec![PersonalSignal::RecentlyViewed, PersonalSignal::Team]
+        );
+        assert!(
+            signals
+                .signals_for(&result("doc", "Someone Else", &["sales@example.com"]))
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_apply_scales_scores_by_weight() {
+        let mut results = vec![
+            result("doc", "ada@example.com", &[]),
+            result("other", "Someone Else", &[]),
+        ];
+        let boosted = signals().apply(&mut results, 0.5);
+
+        assert_eq!(boosted.len(), 1);
+        assert_eq!(boosted[0].document_id, "doc");
+        assert!((results[0].score - 1.5).abs() < f32::EPSILON);
+        assert_eq!(results[1].score, 1.0);
+    }
+}
@@ -0,0 +1,410 @@
+//! Structured queries, for search forms that build a query out of typed
+//! parts (all/any/none of these words, source, author, date) instead of
+//! composing a query string with operators.
+//!
+//! A `QueryNode` tree compiles to what parsing a free-text query produces:
+//! the words to search for, used for semantic matching and highlighting,
+//! the same filters as the `in:`, `by:`, `type:`, `before:` and `after:`
+//! operators, and a boolean `TextQuery` that fulltext search matches in place
+//! of the OR of the query's terms. Filters can only be AND'ed with the rest
+//! of the query, or OR'ed with filters of the same kind, since that is all
+//! the request filters can express.
+
+use crate::query_parser::{self, ParsedQuery};
+use serde::{Deserialize, Serialize};
+use shared::SourceType;
+use shared::models::UserConfiguration;
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum QueryNode {
+    /// Documents matching every node.
+    And {
+        nodes: Vec<QueryNode>,
+    },
+    /// Documents matching any of the nodes.
+    Or {
+        nodes: Vec<QueryNode>,
+    },
+    /// Documents not matching the node.
+    Not {
+        node: Box<QueryNode>,
+    },
+    /// A single word.
+    Term {
+        value: String,
+    },
+    /// Words appearing in this order.
+    Phrase {
+        value: String,
+    },
+    Source {
+        source_type: SourceType,
+    },
+    /// Documents authored by a person, like `by:`.
+    Author {
+        value: String,
+    },
+    /// A content type or `code`, like `type:`.
+    ContentType {
+        value: String,
+    },
+    /// Documents updated within a range, with dates like `before:` and
+    /// `after:` take, e.g. `2024-06-01`, `2024-06` or `2024`.
+    Date {
+        after: Option<String>,
+        before: Option<String>,
+    },
+}
+
+/// The words of a structured query, matched by fulltext search.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TextQuery {
+    All(Vec<TextQuery>),
+    Any(Vec<TextQuery>),
+    Not(Box<TextQuery>),
+    Term(String),
+    Phrase(String),
+}
+
+impl TextQuery {
+    /// Whether matching requires more than any one of the words. Semantic
+    /// matches cannot be checked against such constraints.
+    pub fn has_constraints(&self) -> bool {
+        match self {
+            TextQuery::All(_) | TextQuery::Not(_) => true,
+            TextQuery::Any(queries) => queries.iter().any(TextQuery::has_constraints),
+            TextQuery::Term(_) | TextQuery::Phrase(_) => false,
+        }
+    }
+
+    /// Whether the query matches documents by their words rather than by
+    /// lacking words, which fulltext search cannot do on its own.
+    fn is_positive(&self) -> bool {
+        match self {
+            TextQuery::All(queries) => queries.iter().any(TextQuery::is_positive),
+            TextQuery::Any(queries) => queries.iter().all(TextQuery::is_positive),
+            TextQuery::Not(_) => false,
+            TextQuery::Term(_) | TextQuery::Phrase(_) => true,
+        }
+    }
+
+    /// The words searched for, leaving out excluded ones.
+    fn collect_words<'a>(&'a self, words: &mut Vec<&'a str>) {
+        match self {
+            TextQuery::All(queries) | TextQuery::Any(queries) => {
+                for query in queries {
+                    query.collect_words(words);
+                }
+            }
+            TextQuery::Not(_) => {}
+            TextQuery::Term(word) | TextQuery::Phrase(word) => words.push(word),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CompiledQuery {
+    /// Filters, with the searched-for words as the cleaned query.
+    pub parsed: ParsedQuery,
+    pub text: Option<TextQuery>,
+}
+
+/// Compile a structured query, resolving dates in the user's timezone.
+pub fn compile(
+    node: &QueryNode,
+    user_configuration: &UserConfiguration,
+) -> Result<CompiledQuery, String> {
+    let mut compiler = Compiler {
+        timezone: query_parser::resolve_timezone(user_configuration),
+        parsed: ParsedQuery::default(),
+        text: Vec::new(),
+        filter_kinds: HashSet::new(),
+    };
+    compiler.conjunct(node)?;
+
+    let mut text = compiler.text;
+    let text = match text.len() {
+        0 => None,
+        1 => text.pop(),
+        _ => Some(TextQuery::All(text)),
+    };
+    let mut parsed = compiler.parsed;
+    if let Some(text) = &text {
+        if !text.is_positive() {
+            return Err("must include words to match besides excluded ones".to_string());
+        }
+        let mut words = Vec::new();
+        text.collect_words(&mut words);
+        parsed.cleaned_query = words.join(" ");
+    }
+
+    Ok(CompiledQuery { parsed, text })
+}
+
+struct Compiler {
+    timezone: chrono_tz::Tz,
+    parsed: ParsedQuery,
+    /// Word constraints AND'ed at the top level.
+    text: Vec<TextQuery>,
+    /// Filter kinds applied so far; each may be AND'ed only once, since
+    /// values of the same kind are OR'ed.
+    filter_kinds: HashSet<&'static str>,
+}
+
+impl Compiler {
+    /// Compile a node AND'ed at the top level of the query.
+    fn conjunct(&mut self, node: &QueryNode) -> Result<(), String> {
+        match node {
+            QueryNode::And { nodes } => {
+                if nodes.is_empty() {
+                    return Err("and must have at least one node".to_string());
+                }
+                for node in nodes {
+                    self.conjunct(node)?;
+                }
+            }
+            QueryNode::Or { nodes } if !nodes.is_empty() && nodes.iter().all(is_filter) => {
+                self.filters(nodes)?;
+            }
+            QueryNode::Date { after, before } => {
+                if after.is_none() && before.is_none() {
+                    return Err("date must set after or before".to_string());
+                }
+                for (value, is_before) in [(after, false), (before, true)] {
+                    let Some(value) = value else {
+                        continue;
+                    };
+                    if !query_parser::apply_date_operator(
+                        value,
+                        is_before,
+                        self.timezone,
+                        &mut self.parsed,
+                    ) {
+                        return Err(format!("'{}' is not a date", value));
+                    }
+                }
+            }
+            node if is_filter(node) => self.filters(std::slice::from_ref(node))?,
+            node => {
+                let text = self.text(node)?;
+                self.text.push(text);
+            }
+        }
+        Ok(())
+    }
+
+    /// Apply filters of one kind, OR'ed together.
+    fn filters(&mut self, nodes: &[QueryNode]) -> Result<(), String> {
+        let kind = filter_kind(&nodes[0]);
+        if nodes.iter().any(|node| filter_kind(node) != kind) {
+            return Err("or can only combine filters of the same kind".to_string());
+        }
+        if !self.filter_kinds.insert(kind) {
+            return Err(format!("{} filters must be combined with or", kind));
+        }
+
+        for node in nodes {
+            match node {
+                QueryNode::Source { source_type } => {
+                    if !self.parsed.source_types.contains(source_type) {
+                        self.parsed.source_types.push(*source_type);
+                    }
+                }
+                QueryNode::Author { value } => {
+                    self.parsed
+                        .person_filters
+                        .push(non_empty(value, "author")?.to_string());
+                }
+                QueryNode::ContentType { value } => {
+                    query_parser::apply_type_operator(
+                        non_empty(value, "content_type")?,
+                        &mut self.parsed,
+                    );
+                }
+                _ => unreachable!("not a filter"),
+            }
+        }
+        Ok(())
+    }
+
+    fn text(&self, node: &QueryNode) -> Result<TextQuery, String> {
+        let text = match node {
+            QueryNode::And { nodes } => TextQuery::All(self.text_nodes(nodes, "and")?),
+            QueryNode::Or { nodes } => TextQuery::Any(self.text_nodes(nodes, "or")?),
+            QueryNode::Not { node } => match self.text(node)? {
+                TextQuery::Not(text) => *text,
+                text => TextQuery::Not(Box::new(text)),
+            },
+            QueryNode::Term { value } => {
+                let value = non_empty(value, "term")?;
+                if value.contains(char::is_whitespace) {
+                    return Err("term must be a single word; use a phrase".to_string());
+                }
+                TextQuery::Term(value.to_string())
+            }
+            QueryNode::Phrase { value } => {
+                let words: Vec<&str> = non_empty(value, "phrase")?.split_whitespace().collect();
+                TextQuery::Phrase(words.join(" "))
+            }
+            QueryNode::Source { .. }
+            | QueryNode::Author { .. }
+            | QueryNode::ContentType { .. }
+            | QueryNode::Date { .. } => {
+                return Err(
+                    "filters can only be combined with and at the top of the query".to_string(),
+                );
+            }
+        };
+
+        match text {
+            TextQuery::Any(queries) if !queries.iter().all(TextQuery::is_positive) => {
+                Err("or cannot match documents by excluded words alone".to_string())
+            }
+            TextQuery::All(mut queries) | TextQuery::Any(mut queries) if queries.len() == 1 => {
+                Ok(queries.pop().unwrap())
+            }
+            text => Ok(text),
+        }
+    }
+
+    fn text_nodes(&self, nodes: &[QueryNode], operator: &str) -> Result<Vec<TextQuery>, String> {
+        if nodes.is_empty() {
+            return Err(format!("{} must have at least one node", operator));
+        }
+        nodes.iter().map(|node| self.text(node)).collect()
+    }
+}
+
+fn is_filter(node: &QueryNode) -> bool {
+    matches!(
+        node,
+        QueryNode::Source { .. } | QueryNode::Author { .. } | QueryNode::ContentType { .. }
+    )
+}
+
+fn filter_kind(node: &QueryNode) -> &'static str {
+    match node {
+        QueryNode::Source { .. } => "source",
+        QueryNode::Author { .. } => "author",
+        _ => "content_type",
+    }
+}
+
+fn non_empty<'a>(value: &'a str, node: &str) -> Result<&'a str, String> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Err(format!("{} must not be empty", node));
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn compile_json(value: serde_json::Value) -> Result<CompiledQuery, String> {
+        let node: QueryNode = serde_json::from_value(value).unwrap();
+        compile(&node, &UserConfiguration::default())
+    }
+
+    fn term(word: &str) -> TextQuery {
+        TextQuery::Term(word.to_string())
+    }
+
+    #[test]
+    fn test_compiles_advanced_search_form() {
+        let compiled = compile_json(json!({
+            "type": "and",
+            "nodes": [
+                {"type": "term", "value": "roadmap"},
+                {"type": "phrase", "value": "  q3   planning "},
+                {"type": "or", "nodes": [
+                    {"type": "term", "value": "draft"},
+                    {"type": "term", "value": "final"}
+                ]},
+                {"type": "not", "node": {"type": "term", "value": "archived"}},
+                {"type": "or", "nodes": [
+                    {"type": "source", "source_type": "slack"},
+                    {"type": "source", "source_type": "confluence"}
+                ]},
+                {"type": "author", "value": "alice"},
+                {"type": "date", "after": "2024-01-01"}
+            ]
+        }))
+        .unwrap();
+
+        assert_eq!(
+            compiled.text,
+            Some(TextQuery::All(vec![
+                term("roadmap"),
+                TextQuery::Phrase("q3 planning".to_string()),
+                TextQuery::Any(vec![term("draft"), term("final")]),
+                TextQuery::Not(Box::new(term("archived"))),
+            ]))
+        );
+        assert_eq!(
+            compiled.parsed.cleaned_query,
+            "roadmap q3 planning draft final"
+        );
+        assert_eq!(
+            compiled.parsed.source_types,
+            vec![SourceType::Slack, SourceType::Confluence]
+        );
+        assert_eq!(compiled.parsed.person_filters, vec!["alice"]);
+        let date_filter = compiled.parsed.date_filter.unwrap();
+        assert!(date_filter.after.is_some());
+        assert!(date_filter.before.is_none());
+    }
+
+    #[test]
+    fn test_any_of_words_has_no_constraints() {
+        let compiled = compile_json(json!({
+            "type": "or",
+            "nodes": [
+                {"type": "term", "value": "invoice"},
+                {"type": "term", "value": "receipt"}
+            ]
+        }))
+        .unwrap();
+        let text = compiled.text.unwrap();
+        assert!(!text.has_constraints());
+
+        let compiled = compile_json(json!({"type": "content_type", "value": "pdf"})).unwrap();
+        assert!(compiled.text.is_none());
+        assert_eq!(compiled.parsed.content_types, vec!["pdf"]);
+        assert_eq!(compiled.parsed.cleaned_query, "");
+    }
+
+    #[test]
+    fn test_rejects_queries_filters_cannot_express() {
+        let cases = [
+            // Only excluded words
+            json!({"type": "not", "node": {"type": "term", "value": "draft"}}),
+            // Filters under or/not
+            json!({"type": "or", "nodes": [
+                {"type": "term", "value": "draft"},
+                {"type": "author", "value": "alice"}
+            ]}),
+            json!({"type": "not", "node": {"type": "source", "source_type": "slack"}}),
+            // Sources AND'ed together
+            json!({"type": "and", "nodes": [
+                {"type": "source", "source_type": "slack"},
+                {"type": "source", "source_type": "jira"}
+            ]}),
+            json!({"type": "or", "nodes": [
+                {"type": "source", "source_type": "slack"},
+                {"type": "author", "value": "alice"}
+            ]}),
+            json!({"type": "and", "nodes": []}),
+            json!({"type": "term", "value": "two words"}),
+            json!({"type": "date", "after": "last tuesday"}),
+        ];
+
+        for case in cases {
+            assert!(compile_json(case.clone()).is_err(), "{case} should fail");
+        }
+    }
+}
@@ -0,0 +1,54 @@
+//! Cached query embeddings.
+//!
+//! Embedding a query is the slowest step of a semantic search before the
+//! vector lookup itself. Queries we expect to be searched soon, such as the
+//! suggested questions on the home page, have their embeddings computed ahead
+//! of time and stored here. Entries are keyed by the embedding model as well
+//! as the normalized query, so switching providers never serves vectors from
+//! another model's space.
+
+use crate::search_cache::normalize_query;
+use redis::AsyncCommands;
+use redis::aio::MultiplexedConnection;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+const KEY_PREFIX: &str = "query_embedding:v1";
+
+pub fn cache_key(model: &str, query: &str) -> String {
+    let mut hasher = DefaultHasher::new();
+    normalize_query(query).hash(&mut hasher);
+    format!("{}:{}:{:x}", KEY_PREFIX, model, hasher.finish())
+}
+
+pub async fn get(conn: &mut MultiplexedConnection, key: &str) -> Option<Vec<f32>> {
+    let json: Option<String> = conn.get(key).await.ok()?;
+    serde_json::from_str(&json?).ok()
+}
+
+pub async fn put(
+    conn: &mut MultiplexedConnection,
+    key: &str,
+    embedding: &[f32],
+    ttl_secs: u64,
+) -> redis::RedisResult<()> {
+    let json = serde_json::to_string(embedding).unwrap_or_default();
+    conn.set_ex(key, json, ttl_secs).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_key_normalizes_query_and_separates_models() {
+        assert_eq!(
+            cache_key("model-a", "How do we  handle refunds?"),
+            cache_key("model-a", "how do we handle REFUNDS?")
+        );
+        assert_ne!(
+            cache_key("model-a", "How do we handle refunds?"),
+            cache_key("model-b", "How do we handle refunds?")
+        );
+    }
+}
@@ -0,0 +1,444 @@
+//! Classifies what a query is after, so short navigational queries ("expense
+//! policy") and questions ("how do I submit expenses?") are searched
+//! differently.
+//!
+//! Heuristics decide most queries instantly: a trailing question mark or
+//! leading question word makes a question, while a short query naming a kind
+//! of document, a quoted phrase or an identifier is navigational. When the
+//! heuristics are unsure and the model is enabled, the AI service is asked to
+//! label the query under a tight timeout; its answers are cached in memory.
+//!
+//! The intent picks the search mode for requests that leave it unset, weights
+//! fulltext against semantic matches in hybrid fusion, and tells clients
+//! whether an AI answer is worth offering.
+
+use crate::models::SearchMode;
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use shared::AIClient;
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tracing::{debug, warn};
+
+const QUESTION_WORDS: &[&str] = &[
+    "how", "what", "why", "when", "where", "who", "whom", "whose", "which", "can", "could",
+    "should", "would", "will", "is", "are", "was", "were", "do", "does", "did", "has", "have",
+];
+/// Requests for an explanation rather than a document.
+const INSTRUCTION_WORDS: &[&str] = &["explain", "summarize", "summarise", "describe", "compare"];
+/// Words that name a kind of document, as in "expense policy" or "q3 roadmap".
+const DOCUMENT_NOUNS: &[&str] = &[
+    "agenda",
+    "checklist",
+    "deck",
+    "doc",
+    "docs",
+    "faq",
+    "form",
+    "guide",
+    "guidelines",
+    "handbook",
+    "notes",
+    "plan",
+    "playbook",
+    "policy",
+    "presentation",
+    "report",
+    "roadmap",
+    "runbook",
+    "slides",
+    "spec",
+    "template",
+    "wiki",
+];
+/// Queries of at most this many words may be looking for a known document.
+const MAX_NAVIGATIONAL_WORDS: usize = 3;
+/// Question words only signal a question in queries at least this long;
+/// "who's who" or "what's new" read as titles.
+const MIN_QUESTION_WORDS: usize = 3;
+/// Confidence reported for model labels, which come without a score.
+const MODEL_CONFIDENCE: f32 = 0.8;
+/// Model answers cached before the cache is cleared.
+const MAX_CACHED_INTENTS: usize = 10_000;
+const MODEL_PROMPT_TEMPLATE: &str = r#"Classify this workplace search query into exactly one category:
+- navigational: looking for a specific known document, page or item by name
+- search: exploring a topic, expecting a list of relevant documents
+- question: asking for an answer or explanation
+
+Query: {query}
+
+Respond with only the category name."#;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryIntent {
+    /// Looking for a specific document by name.
+    Navigational,
+    /// Exploring a topic.
+    Search,
+    /// Asking for an answer.
+    Question,
+}
+
+impl QueryIntent {
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim().trim_end_matches('.').to_lowercase().as_str() {
+            "navigational" => Some(Self::Navigational),
+            "search" => Some(Self::Search),
+            "question" => Some(Self::Question),
+            _ => None,
+        }
+    }
+
+    /// Mode for requests that do not choose one. Questions rarely share
+    /// their wording with the documents that answer them, so they need
+    /// semantic matching; other queries keep the fulltext default.
+    pub fn default_mode(&self) -> SearchMode {
+        match self {
+            Self::Question => SearchMode::Hybrid,
+            Self::Navigational | Self::Search => SearchMode::Fulltext,
+        }
+    }
+
+    /// Multipliers for the fulltext and semantic rank contributions in
+    /// hybrid fusion. Names match literally; questions match by meaning.
+    pub fn fusion_weights(&self) -> (f32, f32) {
+        match self {
+            Self::Navigational => (1.5, 0.5),
+            Self::Search => (1.0, 1.0),
+            Self::Question => (0.5, 1.5),
+        }
+    }
+
+    /// Whether an AI answer is worth generating. Someone looking for a
+    /// document by name wants the document, not a summary of it.
+    pub fn suggests_answer(&self) -> bool {
+        !matches!(self, Self::Navigational)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IntentSource {
+    Heuristic,
+    Model,
+}
+
+/// The intent of a query; returned in search responses as a hint for
+/// clients.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IntentClassification {
+    pub intent: QueryIntent,
+    pub confidence: f32,
+    pub source: IntentSource,
+    /// Whether an AI answer is likely to help, e.g. to show one above the
+    /// results.
+    pub suggest_answer: bool,
+}
+
+impl IntentClassification {
+    fn new(intent: QueryIntent, confidence: f32, source: IntentSource) -> Self {
+        Self {
+            intent,
+            confidence,
+            source,
+            suggest_answer: intent.suggests_answer(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct QueryIntentConfig {
+    pub enabled: bool,
+    /// Ask the AI service about queries the heuristics are unsure of.
+    pub model_enabled: bool,
+    /// Heuristic confidence below which the model is asked.
+    pub model_min_confidence: f32,
+    pub model_timeout_ms: u64,
+}
+
+impl Default for QueryIntentConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            model_enabled: false,
+            model_min_confidence: 0.7,
+            model_timeout_ms: 300,
+        }
+    }
+}
+
+impl QueryIntentConfig {
+    pub fn from_env() -> Self {
+        fn env_or<T: std::str::FromStr>(key: &str, default: T) -> T {
+            std::env::var(key)
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default)
+        }
+
+        let defaults = Self::default();
+        Self {
+            enabled: env_or("SEARCHER_QUERY_INTENT_ENABLED", defaults.enabled),
+            model_enabled: env_or(
+                "SEARCHER_QUERY_INTENT_MODEL_ENABLED",
+                defaults.model_enabled,
+            ),
+            model_min_confidence: env_or(
+                "SEARCHER_QUERY_INTENT_MODEL_MIN_CONFIDENCE",
+                defaults.model_min_confidence,
+            ),
+            model_timeout_ms: env_or(
+                "SEARCHER_QUERY_INTENT_MODEL_TIMEOUT_MS",
+                defaults.model_timeout_ms,
+            ),
+        }
+    }
+}
+
+/// Classify a query from its wording alone.
+pub fn classify_heuristic(query: &str) -> IntentClassification {
+    let heuristic =
+        |intent, confidence| IntentClassification::new(intent, confidence, IntentSource::Heuristic);
+
+    let query = query.trim();
+    if query.ends_with('?') {
+        return heuristic(QueryIntent::Question, 0.95);
+    }
+
+    let words: Vec<String> = query
+        .split_whitespace()
+        .map(|word| {
+            word.trim_matches(|c: char| !c.is_alphanumeric() && c != '\'')
+                .to_lowercase()
+        })
+        .filter(|word| !word.is_empty())
+        .collect();
+    let Some(first) = words.first() else {
+        return heuristic(QueryIntent::Search, 0.5);
+    };
+    // "what's", "how'd"
+    let first = first.split('\'').next().unwrap_or(first);
+
+    if words.len() >= MIN_QUESTION_WORDS {
+        if QUESTION_WORDS.contains(&first) {
+            return heuristic(QueryIntent::Question, 0.85);
+        }
+        if INSTRUCTION_WORDS.contains(&first) {
+            return heuristic(QueryIntent::Question, 0.75);
+        }
+    }
+
+    let is_quoted = query.len() > 1 && query.starts_with('"') && query.ends_with('"');
+    if is_quoted || words.iter().any(|word| is_identifier(word)) {
+        return heuristic(QueryIntent::Navigational, 0.9);
+    }
+
+    if words.len() <= MAX_NAVIGATIONAL_WORDS {
+        if words
+            .iter()
+            .any(|word| DOCUMENT_NOUNS.contains(&word.as_str()))
+        {
+            return heuristic(QueryIntent::Navigational, 0.85);
+        }
+        // Could be a title or a topic; let the model decide when enabled.
+        return heuristic(QueryIntent::Navigational, 0.55);
+    }
+
+    heuristic(QueryIntent::Search, 0.6)
+}
+
+/// Ticket keys like "eng-1234", URLs and file names point at one item.
+fn is_identifier(word: &str) -> bool {
+    if word.contains("://") || word.starts_with("www.") {
+        return true;
+    }
+    if let Some((prefix, number)) = word.split_once('-')
+        && !prefix.is_empty()
+        && prefix.chars().all(|c| c.is_ascii_alphabetic())
+        && !number.is_empty()
+        && number.chars().all(|c| c.is_ascii_digit())
+    {
+        return true;
+    }
+    word.rsplit_once('.').is_some_and(|(name, extension)| {
+        !name.is_empty()
+            && matches!(
+                extension,
+                "pdf" | "doc" | "docx" | "xls" | "xlsx" | "ppt" | "pptx" | "csv" | "md" | "txt"
+            )
+    })
+}
+
+pub struct QueryIntentClassifier {
+    ai_client: AIClient,
+    config: QueryIntentConfig,
+    model_cache: RwLock<HashMap<String, QueryIntent>>,
+}
+
+impl QueryIntentClassifier {
+    pub fn new(ai_client: AIClient, config: QueryIntentConfig) -> Self {
+        Self {
+            ai_client,
+            config,
+            model_cache: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn config(&self) -> &QueryIntentConfig {
+        &self.config
+    }
+
+    /// Classify a query, or None when classification is disabled or the
+    /// query is empty.
+    pub async fn classify(&self, query: &str) -> Option<IntentClassification> {
+        if !self.config.enabled || query.trim().is_empty() {
+            return None;
+        }
+
+        let heuristic = classify_heuristic(query);
+        if !self.config.model_enabled || heuristic.confidence >= self.config.model_min_confidence {
+            return Some(heuristic);
+        }
+
+        match self.classify_with_model(query).await {
+            Some(intent) => Some(IntentClassification::new(
+                intent,
+                MODEL_CONFIDENCE,
+                IntentSource::Model,
+            )),
+            None => Some(heuristic),
+        }
+    }
+
+    async fn classify_with_model(&self, query: &str) -> Option<QueryIntent> {
+        let key = query.trim().to_lowercase();
+        if let Some(intent) = self.model_cache.read().await.get(&key) {
+            return Some(*intent);
+        }
+
+        let timeout = Duration::from_millis(self.config.model_timeout_ms);
+        let intent = match tokio::time::timeout(timeout, self.prompt_model(&key)).await {
+            Ok(Ok(Some(intent))) => intent,
+            Ok(Ok(None)) => {
+                debug!("Intent model gave no usable label for query '{}'", key);
+                return None;
+            }
+            Ok(Err(e)) => {
+                warn!("Intent model failed for query '{}': {}", key, e);
+                return None;
+            }
+            Err(_) => {
+                debug!(
+                    "Intent model timed out after {}ms for query '{}'",
+                    self.config.model_timeout_ms, key
+                );
+                return None;
+            }
+        };
+
+        let mut cache = self.model_cache.write().await;
+        if cache.len() >= MAX_CACHED_INTENTS {
+            cache.clear();
+        }
+        cache.insert(key, intent);
+        Some(intent)
+    }
+
+    async fn prompt_model(&self, query: &str) -> anyhow::Result<Option<QueryIntent>> {
+        let prompt = MODEL_PROMPT_TEMPLATE.replace("{query}", query);
+        let mut stream = self.ai_client.stream_prompt(&prompt).await?;
+        let mut answer = String::new();
+        while let Some(chunk) = stream.next().await {
+            answer.push_str(&chunk?);
+        }
+        Ok(answer
+            .split_whitespace()
+            .next()
+            .and_then(QueryIntent::parse))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn intent(query: &str) -> QueryIntent {
+        classify_heuristic(query).intent
+    }
+
+    #[test]
+    fn test_questions() {
+        assert_eq!(intent("how do I submit expenses?"), QueryIntent::Question);
+        assert_eq!(intent("how do I submit expenses"), QueryIntent::Question);
+        assert_eq!(
+            intent("What's the vacation carryover limit"),
+            QueryIntent::Question
+        );
+        assert_eq!(intent("explain the release process"), QueryIntent::Question);
+        assert_eq!(intent("vacation carryover?"), QueryIntent::Question);
+        assert!(classify_heuristic("why is the build failing?").suggest_answer);
+    }
+
+    #[test]
+    fn test_navigational_queries() {
+        let expense_policy = classify_heuristic("expense policy");
+        assert_eq!(expense_policy.intent, QueryIntent::Navigational);
+        assert!(expense_policy.confidence >= 0.7);
+        assert!(!expense_policy.suggest_answer);
+
+        assert_eq!(
+            intent("\"quarterly business review\""),
+            QueryIntent::Navigational
+        );
+        assert_eq!(
+            intent("status of ENG-1234 rollout"),
+            QueryIntent::Navigational
+        );
+        assert_eq!(intent("board-deck-final.pptx"), QueryIntent::Navigational);
+        assert_eq!(intent("what's new"), QueryIntent::Navigational);
+    }
+
+    #[test]
+    fn test_short_topics_are_uncertain() {
+        let classification = classify_heuristic("budget");
+        assert_eq!(classification.intent, QueryIntent::Navigational);
+        assert!(classification.confidence < QueryIntentConfig::default().model_min_confidence);
+    }
+
+    #[test]
+    fn test_longer_keyword_queries_are_search() {
+        assert_eq!(
+            intent("customer churn analysis enterprise accounts 2024"),
+            QueryIntent::Search
+        );
+    }
+
+    #[test]
+    fn test_intent_effects() {
+        assert!(matches!(
+            QueryIntent::Question.default_mode(),
+            SearchMode::Hybrid
+        ));
+        assert!(matches!(
+            QueryIntent::Navigational.default_mode(),
+            SearchMode::Fulltext
+        ));
+
+        let (fulltext, semantic) = QueryIntent::Navigational.fusion_weights();
+        assert!(fulltext > semantic);
+        let (fulltext, semantic) = QueryIntent::Question.fusion_weights();
+        assert!(semantic > fulltext);
+    }
+
+    #[test]
+    fn test_parse_model_label() {
+        assert_eq!(QueryIntent::parse("Question."), Some(QueryIntent::Question));
+        assert_eq!(
+            QueryIntent::parse(" navigational"),
+            Some(QueryIntent::Navigational)
+        );
+        assert_eq!(QueryIntent::parse("unsure"), None);
+    }
+}
@@ -0,0 +1,298 @@
+//! Detects the language a query is written in, so fulltext search can match
+//! it against documents stemmed for that language, and optionally
+//! translates it for cross-lingual semantic retrieval.
+//!
+//! The indexer records each document's language in `metadata.language`, and
+//! the BM25 index stems the titles and content of documents in the languages
+//! below with their own analyzer under a per-language alias (for example
+//! `content_de`). A query detected as German is also matched against those
+//! aliases, so "Quartalsberichte" finds "Quartalsbericht". English keeps the
+//! `_en` aliases that every document is indexed under.
+//!
+//! When translation is enabled, a query in another language than the
+//! configured target is translated by the AI service under a tight timeout,
+//! and the semantic leg embeds the translation. Translations are cached in
+//! memory.
+
+use futures_util::StreamExt;
+use shared::AIClient;
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tracing::{debug, warn};
+use whatlang::{Detector, Lang};
+
+/// ISO 639-3 code of English, whose stemmed aliases are always searched.
+const ENGLISH: &str = "eng";
+/// Languages with stemmed index aliases, by ISO 639-3 code, with the suffix
+/// of their `title_*` and `content_*` aliases (see migration 137).
+const ANALYZER_LANGUAGES: &[(&str, &str)] = &[
+    ("deu", "de"),
+    ("fra", "fr"),
+    ("spa", "es"),
+    ("ita", "it"),
+    ("por", "pt"),
+    ("nld", "nl"),
+];
+/// Below this many letters a query's language is mostly guesswork.
+const MIN_DETECTION_LETTERS: usize = 8;
+/// Translations cached before the cache is cleared.
+const MAX_CACHED_TRANSLATIONS: usize = 10_000;
+const TRANSLATION_PROMPT_TEMPLATE: &str = r#"Translate this workplace search query from {from} to {to}. Keep names, identifiers and quoted phrases unchanged.
+
+Query: {query}
+
+Respond with only the translated query."#;
+
+/// The suffix of the stemmed index aliases of `language`, an ISO 639-3
+/// code, when it has its own analyzer.
+pub fn analyzer_suffix(language: &str) -> Option<&'static str> {
+    ANALYZER_LANGUAGES
+        .iter()
+        .find(|(code, _)| *code == language)
+        .map(|(_, suffix)| *suffix)
+}
+
+#[derive(Debug, Clone)]
+pub struct QueryLanguageConfig {
+    pub enabled: bool,
+    /// Detection confidence, from 0 to 1, below which the query's language
+    /// is left undetermined.
+    pub min_confidence: f64,
+    /// Translate queries for the semantic leg of search.
+    pub translate_enabled: bool,
+    /// ISO 639-3 code of the language queries are translated to, usually
+    /// the language most of the corpus is written in.
+    pub translate_target: String,
+    pub translate_timeout_ms: u64,
+}
+
+impl Default for QueryLanguageConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            min_confidence: 0.5,
+            translate_enabled: false,
+            translate_target: ENGLISH.to_string(),
+            translate_timeout_ms: 500,
+        }
+    }
+}
+
+impl QueryLanguageConfig {
+    pub fn from_env() -> Self {
+        fn env_or<T: std::str::FromStr>(key: &str, default: T) -> T {
+            std::env::var(key)
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default)
+        }
+
+        let defaults = Self::default();
+        Self {
+            enabled: env_or("SEARCHER_QUERY_LANGUAGE_ENABLED", defaults.enabled),
+            min_confidence: env_or(
+                "SEARCHER_QUERY_LANGUAGE_MIN_CONFIDENCE",
+                defaults.min_confidence,
+            ),
+            translate_enabled: env_or(
+                "SEARCHER_QUERY_TRANSLATION_ENABLED",
+                defaults.translate_enabled,
+            ),
+            translate_target: env_or(
+                "SEARCHER_QUERY_TRANSLATION_TARGET",
+                defaults.translate_target,
+            ),
+            translate_timeout_ms: env_or(
+                "SEARCHER_QUERY_TRANSLATION_TIMEOUT_MS",
+                defaults.translate_timeout_ms,
+            ),
+        }
+    }
+}
+
+pub struct QueryLanguage {
+    ai_client: AIClient,
+    config: QueryLanguageConfig,
+    detector: Detector,
+    translation_cache: RwLock<HashMap<(String, String), String>>,
+}
+
+impl QueryLanguage {
+    pub fn new(ai_client: AIClient, config: QueryLanguageConfig) -> Self {
+        let allowlist = std::iter::once(ENGLISH)
+            .chain(ANALYZER_LANGUAGES.iter().map(|(code, _)| *code))
+            .filter_map(Lang::from_code)
+            .collect();
+        Self {
+            ai_client,
+            config,
+            detector: Detector::with_allowlist(allowlist),
+            translation_cache: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// The ISO 639-3 code of the query's language, among English and the
+    /// languages with their own analyzer, when it can be told confidently.
+    pub fn detect(&self, query: &str) -> Option<&'static str> {
+        if !self.config.enabled
+            || query.chars().filter(|c| c.is_alphabetic()).count() < MIN_DETECTION_LETTERS
+        {
+            return None;
+        }
+        self.detector
+            .detect(query)
+            .filter(|info| info.confidence() >= self.config.min_confidence)
+            .map(|info| info.lang().code())
+    }
+
+    /// The query translated to the target language, when translation is
+    /// enabled and `language` is another language. `None` leaves the query
+    /// as is, including when the AI service fails or is too slow.
+    pub async fn translate(&self, query: &str, language: &str) -> Option<String> {
+        if !self.config.translate_enabled
+            || query.trim().is_empty()
+            || language == self.config.translate_target
+        {
+            return None;
+        }
+
+        let key = (language.to_string(), query.trim().to_lowercase());
+        if let Some(translation) = self.translation_cache.read().await.get(&key) {
+            return Some(translation.clone());
+        }
+
+        let timeout = Duration::from_millis(self.config.translate_timeout_ms);
+        let translation =
+            match tokio::time::timeout(timeout, self.prompt_model(query.trim(), language)).await {
+                Ok(Ok(Some(translation))) => translation,
+                Ok(Ok(None)) => {
+                    debug!("Translation model gave no usable answer for '{}'", query);
+                    return None;
+                }
+                Ok(Err(e)) => {
+                    warn!("Translation model failed for query '{}': {}", query, e);
+                    return None;
+                }
+                Err(_) => {
+                    debug!(
+                        "Translation model timed out after {}ms for query '{}'",
+                        self.config.translate_timeout_ms, query
+                    );
+                    return None;
+                }
+            };
+
+        let mut cache = self.translation_cache.write().await;
+        if cache.len() >= MAX_CACHED_TRANSLATIONS {
+            cache.clear();
+        }
+        cache.insert(key, translation.clone());
+        Some(translation)
+    }
+
+    async fn prompt_model(&self, query: &str, language: &str) -> anyhow::Result<Option<String>> {
+        let language_name = |code: &str| {
+            Lang::from_code(code)
+                .map(|lang| lang.eng_name().to_string())
+                .unwrap_or_else(|| code.to_string())
+        };
+        let prompt = TRANSLATION_PROMPT_TEMPLATE
+            .replace("{from}", &language_name(language))
+            .replace("{to}", &language_name(&self.config.translate_target))
+            .replace("{query}", query);
+        let mut stream = self.ai_client.stream_prompt(&prompt).await?;
+        let mut answer = String::new();
+        while let Some(chunk) = stream.next().await {
+            answer.push_str(&chunk?);
+        }
+        Ok(clean_translation(&answer, query))
+    }
+}
+
+/// The model's answer as a query: its first line without surrounding
+/// quotes, or `None` when it is empty or just repeats the query.
+fn clean_translation(answer: &str, query: &str) -> Option<String> {
+    let translation = answer
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())?
+        .trim_matches(|c| c == '"' || c == '\'' || c == '`')
+        .trim();
+    (!translation.is_empty() && !translation.eq_ignore_ascii_case(query))
+        .then(|| translation.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query_language(config: QueryLanguageConfig) -> QueryLanguage {
+        QueryLanguage::new(AIClient::new("http://localhost:0".to_string()), config)
+    }
+
+    #[test]
+    fn test_detects_supported_query_languages() {
+        let detector = query_language(QueryLanguageConfig::default());
+
+        assert_eq!(
+            detector.detect("Quartalsberichte für das Vertriebsteam"),
+            Some("deu")
+        );
+        assert_eq!(
+            detector.detect("où se trouvent les rapports trimestriels de l'équipe commerciale"),
+            Some("fra")
+        );
+        assert_eq!(
+            detector.detect("quarterly reports for the sales team"),
+            Some("eng")
+        );
+    }
+
+    #[test]
+    fn test_short_queries_and_disabled_detection_are_undetermined() {
+        let detector = query_language(QueryLanguageConfig::default());
+        assert_eq!(detector.detect("Q3 OKRs"), None);
+
+        let disabled = query_language(QueryLanguageConfig {
+            enabled: false,
+            ..Default::default()
+        });
+        assert_eq!(
+            disabled.detect("Quartalsberichte für das Vertriebsteam"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_analyzer_suffix() {
+        assert_eq!(analyzer_suffix("deu"), Some("de"));
+        assert_eq!(analyzer_suffix("eng"), None);
+        assert_eq!(analyzer_suffix("und"), None);
+    }
+
+    #[test]
+    fn test_clean_translation() {
+        assert_eq!(
+            clean_translation("\n\"quarterly reports\"\n", "Quartalsberichte"),
+            Some("quarterly reports".to_string())
+        );
+        assert_eq!(clean_translation("  ", "Quartalsberichte"), None);
+        assert_eq!(
+            clean_translation("Quartalsberichte", "quartalsberichte"),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_no_translation_when_disabled_or_already_in_target() {
+        let disabled = query_language(QueryLanguageConfig::default());
+        assert_eq!(disabled.translate("Quartalsberichte", "deu").await, None);
+
+        let enabled = query_language(QueryLanguageConfig {
+            translate_enabled: true,
+            ..Default::default()
+        });
+        assert_eq!(enabled.translate("quarterly reports", "eng").await, None);
+    }
+}
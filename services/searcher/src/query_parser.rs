@@ -11,6 +11,11 @@ use shared::SourceType;
 use std::collections::HashMap;
 use time::OffsetDateTime;
 
+/// Attribute the indexer sets to the kind of content a document holds.
+pub const CONTENT_KIND_ATTRIBUTE: &str = "content_kind";
+/// `content_kind` of source files.
+const CODE_CONTENT_KIND: &str = "code";
+
 #[async_trait]
 pub trait PersonLookup: Send + Sync {
     async fn is_known_person(&self, term: &str) -> bool;
@@ -123,29 +128,13 @@ async fn extract_operators(
                 }
             }
             "type" => {
-                apply_type_filter(&value, &mut result.content_types);
+                apply_type_operator(&value, result);
             }
             "before" => {
-                if let Some(dt) = parse_date_value(&value, true, timezone) {
-                    result
-                        .date_filter
-                        .get_or_insert(DateFilter {
-                            after: None,
-                            before: None,
-                        })
-                        .before = Some(dt);
-                }
+                apply_date_operator(&value, true, timezone, result);
             }
             "after" => {
-                if let Some(dt) = parse_date_value(&value, false, timezone) {
-                    result
-                        .date_filter
-                        .get_or_insert(DateFilter {
-                            after: None,
-                            before: None,
-                        })
-                        .after = Some(dt);
-                }
+                apply_date_operator(&value, false, timezone, result);
             }
             // Dynamic operators — looked up from the registry
             _ => {
@@ -163,7 +152,45 @@ async fn extract_operators(
     remaining
 }
 
-fn resolve_timezone(user_configuration: &UserConfiguration) -> Tz {
+/// Apply a `type:` value: a content type, or the content kind of source
+/// files.
+pub(crate) fn apply_type_operator(value: &str, result: &mut ParsedQuery) {
+    // Source files are told apart by their content kind, not their MIME type.
+    if value.eq_ignore_ascii_case(CODE_CONTENT_KIND) {
+        merge_attribute_filter(
+            &mut result.attribute_filters,
+            CONTENT_KIND_ATTRIBUTE,
+            CODE_CONTENT_KIND,
+        );
+    } else {
+        apply_type_filter(value, &mut result.content_types);
+    }
+}
+
+/// Apply a `before:` or `after:` value, returning false when it is not a
+/// date.
+pub(crate) fn apply_date_operator(
+    value: &str,
+    is_before: bool,
+    timezone: Tz,
+    result: &mut ParsedQuery,
+) -> bool {
+    let Some(dt) = parse_date_value(value, is_before, timezone) else {
+        return false;
+    };
+    let date_filter = result.date_filter.get_or_insert(DateFilter {
+        after: None,
+        before: None,
+    });
+    if is_before {
+        date_filter.before = Some(dt);
+    } else {
+        date_filter.after = Some(dt);
+    }
+    true
+}
+
+pub(crate) fn resolve_timezone(user_configuration: &UserConfiguration) -> Tz {
     user_configuration
         .timezone()
         .and_then(|tz| tz.parse::<Tz>().ok())
@@ -174,7 +201,9 @@ fn chrono_to_offset_datetime(dt: chrono::DateTime<chrono::Utc>) -> Option<Offset
     OffsetDateTime::from_unix_timestamp(dt.timestamp()).ok()
 }
 
-fn offset_datetime_to_chrono_utc(dt: OffsetDateTime) -> Option<chrono::DateTime<chrono::Utc>> {
+pub(crate) fn offset_datetime_to_chrono_utc(
+    dt: OffsetDateTime,
+) -> Option<chrono::DateTime<chrono::Utc>> {
     chrono::DateTime::from_timestamp(dt.unix_timestamp(), 0)
 }
 
@@ -194,7 +223,7 @@ fn local_datetime_to_utc(
     chrono_to_offset_datetime(local_dt.with_timezone(&chrono::Utc))
 }
 
-fn local_midnight_to_utc(date: NaiveDate, timezone: Tz) -> Option<OffsetDateTime> {
+pub(crate) fn local_midnight_to_utc(date: NaiveDate, timezone: Tz) -> Option<OffsetDateTime> {
     local_datetime_to_utc(date, 0, 0, 0, timezone)
 }
 
@@ -336,6 +365,7 @@ fn resolve_source_alias(alias: &str) -> Option<SourceType> {
         "drive" | "gdrive" | "google_drive" => Some(SourceType::GoogleDrive),
         "gmail" | "email" | "mail" => Some(SourceType::Gmail),
         "google_chat" | "gchat" | "chat" => Some(SourceType::GoogleChat),
+        "google_calendar" | "gcal" => Some(SourceType::GoogleCalendar),
         "google_ads" | "googleads" | "ads" => Some(SourceType::GoogleAds),
         "slack" => Some(SourceType::Slack),
         "confluence" | "wiki" => Some(SourceType::Confluence),
@@ -658,6 +688,17 @@ mod tests {
         assert_eq!(parsed.content_types, vec!["meeting_transcript".to_string()]);
     }
 
+    #[test]
+    fn test_type_operator_code_filters_content_kind() {
+        let parsed = test_parse("type:Code retry backoff");
+        assert_eq!(parsed.cleaned_query, "retry backoff");
+        assert!(parsed.content_types.is_empty());
+        assert_eq!(
+            parsed.attribute_filters.get(CONTENT_KIND_ATTRIBUTE),
+            Some(&AttributeFilter::Exact(JsonValue::String("code".to_string())))
+        );
+    }
+
     #[test]
     fn test_channel_operator() {
         let parsed = test_parse("channel:eng standup");
@@ -904,6 +945,8 @@ mod tests {
             ("gmail", SourceType::Gmail),
             ("email", SourceType::Gmail),
             ("mail", SourceType::Gmail),
+            ("google_calendar", SourceType::GoogleCalendar),
+            ("gcal", SourceType::GoogleCalendar),
             ("google_ads", SourceType::GoogleAds),
             ("googleads", SourceType::GoogleAds),
             ("ads", SourceType::GoogleAds),
@@ -0,0 +1,686 @@
+//! Turns natural language in a query into the filters operators would give,
+//! so "slides from marketing last quarter" searches presentations in Google
+//! Drive updated last quarter for "marketing".
+//!
+//! Runs after operator parsing, for requests that set `understand_query`.
+//! Rules recognise kinds of document ("slides", "spreadsheets") and relative
+//! periods ("last quarter", "past 3 weeks"). A kind of document held by only
+//! some kinds of source also restricts the search to the connected sources
+//! of those kinds. When the model is enabled, the AI service is asked about
+//! what the rules leave of the query under a tight timeout; its answers are
+//! cached in memory. Filters set by the request or by operators always win
+//! over understood ones, and the model may only drop words from the query,
+//! not rewrite them.
+
+use crate::query_parser::{
+    self, ParsedQuery, local_midnight_to_utc, offset_datetime_to_chrono_utc,
+};
+use chrono::{Datelike, NaiveDate};
+use chrono_tz::Tz;
+use futures_util::StreamExt;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use shared::AIClient;
+use shared::SourceType;
+use shared::models::{DateFilter, UserConfiguration};
+use std::collections::HashMap;
+use std::sync::LazyLock;
+use std::time::Duration;
+use time::OffsetDateTime;
+use tokio::sync::RwLock;
+use tracing::{debug, warn};
+
+/// Words for kinds of document, with the `type:` value each stands for.
+static DOCUMENT_KINDS: LazyLock<Vec<(Regex, &str)>> = LazyLock::new(|| {
+    [
+        (
+            r"slide\s+decks?|slides?|decks?|presentations?",
+            "presentation",
+        ),
+        (r"spreadsheets?", "spreadsheet"),
+        (r"pdfs?", "pdf"),
+        (r"e-?mails", "email"),
+        (
+            r"meeting\s+(?:notes|transcripts|recordings)|transcripts",
+            "meeting",
+        ),
+        (r"pull\s+requests|prs", "pr"),
+        (r"tickets|issues", "issue"),
+    ]
+    .into_iter()
+    .map(|(pattern, kind)| {
+        (
+            Regex::new(&format!(r"(?i)\b(?:{})\b", pattern)).unwrap(),
+            kind,
+        )
+    })
+    .collect()
+});
+/// Relative periods, optionally introduced by a preposition.
+static PERIOD_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)\b(?:(?:from|in|during|over|within|since)\s+)?(?:the\s+)?(?:(?P<which>last|this)\s+(?P<unit>quarter|year|month)|(?:past|last)\s+(?P<count>\d{1,3})\s+(?P<counted>day|week|month)s?)\b",
+    )
+    .unwrap()
+});
+/// Words left dangling at either end of a query once filters are taken out
+/// of it, as in "show me slides from marketing".
+const FILLER_WORDS: &[&str] = &[
+    "about",
+    "all",
+    "any",
+    "by",
+    "during",
+    "find",
+    "for",
+    "from",
+    "get",
+    "in",
+    "me",
+    "my",
+    "of",
+    "on",
+    "our",
+    "over",
+    "regarding",
+    "search",
+    "show",
+    "since",
+    "some",
+    "the",
+    "with",
+    "within",
+];
+/// Content types only some kinds of source hold.
+const CONTENT_TYPE_SOURCES: &[(&str, &[SourceType])] = &[
+    (
+        "presentation",
+        &[
+            SourceType::GoogleDrive,
+            SourceType::OneDrive,
+            SourceType::SharePoint,
+        ],
+    ),
+    (
+        "spreadsheet",
+        &[
+            SourceType::GoogleDrive,
+            SourceType::OneDrive,
+            SourceType::SharePoint,
+        ],
+    ),
+    (
+        "email_thread",
+        &[SourceType::Gmail, SourceType::Outlook, SourceType::Imap],
+    ),
+    (
+        "email",
+        &[SourceType::Gmail, SourceType::Outlook, SourceType::Imap],
+    ),
+    ("meeting_transcript", &[SourceType::Fireflies]),
+    ("pull_request", &[SourceType::Github]),
+    (
+        "issue",
+        &[
+            SourceType::Jira,
+            SourceType::Github,
+            SourceType::Linear,
+            SourceType::Clickup,
+        ],
+    ),
+];
+/// Content types the model may filter on, as `type:` values.
+const MODEL_CONTENT_TYPES: &[&str] = &[
+    "document",
+    "spreadsheet",
+    "presentation",
+    "pdf",
+    "email",
+    "meeting",
+    "issue",
+    "pr",
+    "page",
+];
+/// Queries with fewer words left are not worth asking the model about.
+const MIN_MODEL_WORDS: usize = 2;
+/// Model answers cached before the cache is cleared.
+const MAX_CACHED_ANSWERS: usize = 10_000;
+const MODEL_PROMPT_TEMPLATE: &str = r#"Extract search filters from this workplace search query. Today is {today}.
+
+Query: {query}
+
+Respond with only a JSON object with these keys, leaving out filters the query does not ask for:
+- "source_types": apps the documents must come from, among: {source_types}
+- "content_types": kinds of document, among: {content_types}
+- "after": the first day documents may be from, as YYYY-MM-DD
+- "before": the last day documents may be from, as YYYY-MM-DD
+- "query": the words of the query that are not filters"#;
+
+#[derive(Debug, Clone)]
+pub struct QueryUnderstandingConfig {
+    pub enabled: bool,
+    /// Ask the AI service about what the rules leave of a query.
+    pub model_enabled: bool,
+    pub model_timeout_ms: u64,
+}
+
+impl Default for QueryUnderstandingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            model_enabled: true,
+            model_timeout_ms: 1000,
+        }
+    }
+}
+
+impl QueryUnderstandingConfig {
+    pub fn from_env() -> Self {
+        fn env_or<T: std::str::FromStr>(key: &str, default: T) -> T {
+            std::env::var(key)
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default)
+        }
+
+        let defaults = Self::default();
+        Self {
+            enabled: env_or("SEARCHER_QUERY_UNDERSTANDING_ENABLED", defaults.enabled),
+            model_enabled: env_or(
+                "SEARCHER_QUERY_UNDERSTANDING_MODEL_ENABLED",
+                defaults.model_enabled,
+            ),
+            model_timeout_ms: env_or(
+                "SEARCHER_QUERY_UNDERSTANDING_MODEL_TIMEOUT_MS",
+                defaults.model_timeout_ms,
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnderstandingSource {
+    Rules,
+    Model,
+}
+
+/// The filters understood from a query; returned in search responses so
+/// clients can show them, e.g. as removable chips.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UnderstoodQuery {
+    /// What is left of the query to match documents against.
+    pub query: String,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub source_types: Vec<SourceType>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub content_types: Vec<String>,
+    #[serde(
+        with = "time::serde::iso8601::option",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub after: Option<OffsetDateTime>,
+    #[serde(
+        with = "time::serde::iso8601::option",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub before: Option<OffsetDateTime>,
+    /// `model` when the AI service contributed any of the above.
+    pub source: UnderstandingSource,
+}
+
+/// What the model extracted from a query, before validation.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+struct ModelFilters {
+    source_types: Vec<String>,
+    content_types: Vec<String>,
+    after: Option<String>,
+    before: Option<String>,
+    query: Option<String>,
+}
+
+/// Take kinds of document and relative periods out of `parsed`'s query
+/// into its filters, unless it already has filters of that kind.
+pub fn apply_rules(parsed: &mut ParsedQuery, timezone: Tz, now: OffsetDateTime) {
+    let mut remaining = parsed.cleaned_query.clone();
+
+    if parsed.content_types.is_empty() {
+        for (re, kind) in DOCUMENT_KINDS.iter() {
+            if re.is_match(&remaining) {
+                query_parser::apply_type_operator(kind, parsed);
+                remaining = re.replace_all(&remaining, " ").into_owned();
+                break;
+            }
+        }
+    }
+
+    if parsed.date_filter.is_none()
+        && let Some(cap) = PERIOD_RE.captures(&remaining)
+        && let Some(date_filter) = period_filter(&cap, timezone, now)
+    {
+        parsed.date_filter = Some(date_filter);
+        let period = cap.get(0).unwrap().range();
+        remaining.replace_range(period, " ");
+    }
+
+    if remaining != parsed.cleaned_query {
+        parsed.cleaned_query = trim_filler_words(&remaining);
+    }
+}
+
+fn period_filter(cap: &regex::Captures, timezone: Tz, now: OffsetDateTime) -> Option<DateFilter> {
+    if let Some(count) = cap.name("count") {
+        let count: i64 = count.as_str().parse().ok()?;
+        let days = match cap["counted"].to_lowercase().as_str() {
+            "week" => count * 7,
+            "month" => count * 30,
+            _ => count,
+        };
+        return Some(DateFilter {
+            after: Some(now - time::Duration::days(days)),
+            before: None,
+        });
+    }
+
+    let today = offset_datetime_to_chrono_utc(now)?
+        .with_timezone(&timezone)
+        .date_naive();
+    let (start, previous_start) = match cap["unit"].to_lowercase().as_str() {
+        "quarter" => {
+            let month = (today.month0() / 3) * 3 + 1;
+            let start = NaiveDate::from_ymd_opt(today.year(), month, 1)?;
+            (start, start.checked_sub_months(chrono::Months::new(3))?)
+        }
+        "year" => {
+            let start = NaiveDate::from_ymd_opt(today.year(), 1, 1)?;
+            (start, NaiveDate::from_ymd_opt(today.year() - 1, 1, 1)?)
+        }
+        _ => {
+            let start = NaiveDate::from_ymd_opt(today.year(), today.month(), 1)?;
+            (start, start.checked_sub_months(chrono::Months::new(1))?)
+        }
+    };
+    let start = local_midnight_to_utc(start, timezone)?;
+    Some(if cap["which"].eq_ignore_ascii_case("last") {
+        DateFilter {
+            after: Some(local_midnight_to_utc(previous_start, timezone)?),
+            before: Some(start),
+        }
+    } else {
+        DateFilter {
+            after: Some(start),
+            before: None,
+        }
+    })
+}
+
+fn trim_filler_words(query: &str) -> String {
+    let is_filler = |word: &str| FILLER_WORDS.contains(&word.to_lowercase().as_str());
+    let mut words: Vec<&str> = query.split_whitespace().collect();
+    while words.last().is_some_and(|word| is_filler(word)) {
+        words.pop();
+    }
+    let leading = words.iter().take_while(|word| is_filler(word)).count();
+    words[leading..].join(" ")
+}
+
+/// The connected kinds of source that hold documents of `content_types`,
+/// or none when any of them can be held by every kind of source.
+fn sources_holding(content_types: &[String], connected: &[SourceType]) -> Vec<SourceType> {
+    let mut holding = Vec::new();
+    for content_type in content_types {
+        let Some((_, sources)) = CONTENT_TYPE_SOURCES
+            .iter()
+            .find(|(holds, _)| holds == content_type)
+        else {
+            return Vec::new();
+        };
+        for source in sources.iter() {
+            if connected.contains(source) && !holding.contains(source) {
+                holding.push(*source);
+            }
+        }
+    }
+    holding
+}
+
+pub struct QueryUnderstanding {
+    ai_client: AIClient,
+    config: QueryUnderstandingConfig,
+    model_cache: RwLock<HashMap<(String, NaiveDate), ModelFilters>>,
+}
+
+impl QueryUnderstanding {
+    pub fn new(ai_client: AIClient, config: QueryUnderstandingConfig) -> Self {
+        Self {
+            ai_client,
+            config,
+            model_cache: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn config(&self) -> &QueryUnderstandingConfig {
+        &self.config
+    }
+
+    /// Move filters expressed in words out of `parsed`'s query into its
+    /// filters. Filters `parsed` already has, including those the request
+    /// sets itself, are kept. Returns what was understood, or None when
+    /// nothing was.
+    pub async fn understand(
+        &self,
+        parsed: &mut ParsedQuery,
+        connected_source_types: &[SourceType],
+        user_configuration: &UserConfiguration,
+    ) -> Option<UnderstoodQuery> {
+        if !self.config.enabled || parsed.cleaned_query.trim().is_empty() {
+            return None;
+        }
+        let timezone = query_parser::resolve_timezone(user_configuration);
+        let now = OffsetDateTime::now_utc();
+        let original = parsed.clone();
+
+        apply_rules(parsed, timezone, now);
+        let mut source = UnderstandingSource::Rules;
+        if self.config.model_enabled
+            && parsed.cleaned_query.split_whitespace().count() >= MIN_MODEL_WORDS
+            && let Some(today) = offset_datetime_to_chrono_utc(now)
+        {
+            let today = today.with_timezone(&timezone).date_naive();
+            if let Some(filters) = self
+                .extract_with_model(&parsed.cleaned_query, today, connected_source_types)
+                .await
+                && apply_model_filters(parsed, filters, connected_source_types, timezone)
+            {
+                source = UnderstandingSource::Model;
+            }
+        }
+
+        let content_types = parsed.content_types[original.content_types.len()..].to_vec();
+        if parsed.source_types.is_empty() {
+            parsed.source_types = sources_holding(&content_types, connected_source_types);
+        }
+        let source_types = parsed.source_types[original.source_types.len()..].to_vec();
+        let date_filter = parsed
+            .date_filter
+            .as_ref()
+            .filter(|_| original.date_filter.is_none());
+        if parsed.cleaned_query == original.cleaned_query
+            && content_types.is_empty()
+            && source_types.is_empty()
+            && date_filter.is_none()
+        {
+            return None;
+        }
+
+        Some(UnderstoodQuery {
+            query: parsed.cleaned_query.clone(),
+            source_types,
+            content_types,
+            after: date_filter.and_then(|filter| filter.after),
+            before: date_filter.and_then(|filter| filter.before),
+            source,
+        })
+    }
+
+    async fn extract_with_model(
+        &self,
+        query: &str,
+        today: NaiveDate,
+        connected_source_types: &[SourceType],
+    ) -> Option<ModelFilters> {
+        let key = (query.trim().to_lowercase(), today);
+        if let Some(filters) = self.model_cache.read().await.get(&key) {
+            return Some(filters.clone());
+        }
+
+        let timeout = Duration::from_millis(self.config.model_timeout_ms);
+        let prompt = self.prompt_model(&key.0, today, connected_source_types);
+        let filters = match tokio::time::timeout(timeout, prompt).await {
+            Ok(Ok(Some(filters))) => filters,
+            Ok(Ok(None)) => {
+                debug!(
+                    "Query understanding model gave no usable answer for '{}'",
+                    key.0
+                );
+                return None;
+            }
+            Ok(Err(e)) => {
+                warn!(
+                    "Query understanding model failed for query '{}': {}",
+                    key.0, e
+                );
+                return None;
+            }
+            Err(_) => {
+                debug!(
+                    "Query understanding model timed out after {}ms for query '{}'",
+                    self.config.model_timeout_ms, key.0
+                );
+                return None;
+            }
+        };
+
+        let mut cache = self.model_cache.write().await;
+        if cache.len() >= MAX_CACHED_ANSWERS {
+            cache.clear();
+        }
+        cache.insert(key, filters.clone());
+        Some(filters)
+    }
+
+    async fn prompt_model(
+        &self,
+        query: &str,
+        today: NaiveDate,
+        connected_source_types: &[SourceType],
+    ) -> anyhow::Result<Option<ModelFilters>> {
+        let source_types: Vec<String> = connected_source_types
+            .iter()
+            .filter_map(|source| serde_json::to_value(source).ok())
+            .filter_map(|value| value.as_str().map(str::to_string))
+            .collect();
+        let prompt = MODEL_PROMPT_TEMPLATE
+            .replace("{today}", &today.format("%Y-%m-%d").to_string())
+            .replace("{source_types}", &source_types.join(", "))
+            .replace("{content_types}", &MODEL_CONTENT_TYPES.join(", "))
+            .replace("{query}", query);
+        let mut stream = self.ai_client.stream_prompt(&prompt).await?;
+        let mut answer = String::new();
+        while let Some(chunk) = stream.next().await {
+            answer.push_str(&chunk?);
+        }
+        Ok(parse_model_answer(&answer))
+    }
+}
+
+/// The JSON object in the model's answer, which may be wrapped in prose or
+/// a code fence.
+fn parse_model_answer(answer: &str) -> Option<ModelFilters> {
+    let start = answer.find('{')?;
+    let end = answer.rfind('}')?;
+    serde_json::from_str(answer.get(start..=end)?).ok()
+}
+
+/// Apply the filters the model found that `parsed` does not have yet,
+/// returning whether any were.
+fn apply_model_filters(
+    parsed: &mut ParsedQuery,
+    filters: ModelFilters,
+    connected_source_types: &[SourceType],
+    timezone: Tz,
+) -> bool {
+    let mut applied = false;
+
+    if parsed.source_types.is_empty() {
+        for source in &filters.source_types {
+            if let Ok(source) =
+                serde_json::from_value::<SourceType>(serde_json::Value::from(source.as_str()))
+                && connected_source_types.contains(&source)
+                && !parsed.source_types.contains(&source)
+            {
+                parsed.source_types.push(source);
+                applied = true;
+            }
+        }
+    }
+    if parsed.content_types.is_empty() {
+        for content_type in &filters.content_types {
+            if MODEL_CONTENT_TYPES.contains(&content_type.as_str()) {
+                query_parser::apply_type_operator(content_type, parsed);
+                applied = true;
+            }
+        }
+    }
+    if parsed.date_filter.is_none() {
+        for (value, is_before) in [(&filters.after, false), (&filters.before, true)] {
+            if let Some(value) = value {
+                applied |= query_parser::apply_date_operator(value, is_before, timezone, parsed);
+            }
+        }
+    }
+
+    // The model may leave words out, but not add or reword them
+    if let Some(query) = filters.query.filter(|_| applied) {
+        let words: Vec<String> = parsed
+            .cleaned_query
+            .split_whitespace()
+            .map(str::to_lowercase)
+            .collect();
+        if query
+            .split_whitespace()
+            .all(|word| words.contains(&word.to_lowercase()))
+        {
+            parsed.cleaned_query = trim_filler_words(&query);
+        }
+    }
+    applied
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::datetime;
+
+    const NOW: OffsetDateTime = datetime!(2024-08-14 15:00 UTC);
+
+    fn rules(query: &str) -> ParsedQuery {
+        let mut parsed = ParsedQuery {
+            cleaned_query: query.to_string(),
+            ..Default::default()
+        };
+        apply_rules(&mut parsed, chrono_tz::UTC, NOW);
+        parsed
+    }
+
+    #[test]
+    fn test_rules_extract_kind_and_period() {
+        let parsed = rules("slides from marketing last quarter");
+        assert_eq!(parsed.cleaned_query, "marketing");
+        assert_eq!(parsed.content_types, vec!["presentation"]);
+        let date_filter = parsed.date_filter.unwrap();
+        assert_eq!(date_filter.after, Some(datetime!(2024-04-01 0:00 UTC)));
+        assert_eq!(date_filter.before, Some(datetime!(2024-07-01 0:00 UTC)));
+    }
+
+    #[test]
+    fn test_rules_periods() {
+        let this_year = rules("budget spreadsheets this year");
+        assert_eq!(this_year.cleaned_query, "budget");
+        assert_eq!(this_year.content_types, vec!["spreadsheet"]);
+        let date_filter = this_year.date_filter.unwrap();
+        assert_eq!(date_filter.after, Some(datetime!(2024-01-01 0:00 UTC)));
+        assert_eq!(date_filter.before, None);
+
+        let past_weeks = rules("show me incident reports from the past 2 weeks");
+        assert_eq!(past_weeks.cleaned_query, "incident reports");
+        assert_eq!(
+            past_weeks.date_filter.unwrap().after,
+            Some(NOW - time::Duration::days(14))
+        );
+    }
+
+    #[test]
+    fn test_rules_leave_other_queries_alone() {
+        let parsed = rules("from zero to one");
+        assert_eq!(parsed.cleaned_query, "from zero to one");
+        assert!(parsed.content_types.is_empty());
+        assert!(parsed.date_filter.is_none());
+
+        let mut with_type = ParsedQuery {
+            cleaned_query: "pricing slides".to_string(),
+            content_types: vec!["pdf".to_string()],
+            ..Default::default()
+        };
+        apply_rules(&mut with_type, chrono_tz::UTC, NOW);
+        assert_eq!(with_type.cleaned_query, "pricing slides");
+        assert_eq!(with_type.content_types, vec!["pdf"]);
+    }
+
+    #[test]
+    fn test_sources_holding() {
+        let connected = [
+            SourceType::GoogleDrive,
+            SourceType::Slack,
+            SourceType::Gmail,
+        ];
+        assert_eq!(
+            sources_holding(&["presentation".to_string()], &connected),
+            vec![SourceType::GoogleDrive]
+        );
+        assert_eq!(
+            sources_holding(
+                &["email_thread".to_string(), "email".to_string()],
+                &connected
+            ),
+            vec![SourceType::Gmail]
+        );
+        assert!(sources_holding(&["pdf".to_string()], &connected).is_empty());
+    }
+
+    #[test]
+    fn test_model_filters_only_fill_gaps_and_drop_words() {
+        let connected = [SourceType::GoogleDrive, SourceType::Slack];
+        let answer = r#"Sure: ```json
+{"source_types": ["slack", "jira"], "content_types": ["video"], "after": "2024-06-01", "query": "launch plan"}
+```"#;
+        let mut parsed = ParsedQuery {
+            cleaned_query: "launch plan discussed in june".to_string(),
+            ..Default::default()
+        };
+        let filters = parse_model_answer(answer).unwrap();
+        assert!(apply_model_filters(
+            &mut parsed,
+            filters,
+            &connected,
+            chrono_tz::UTC
+        ));
+        assert_eq!(parsed.source_types, vec![SourceType::Slack]);
+        assert!(parsed.content_types.is_empty());
+        assert_eq!(
+            parsed.date_filter.unwrap().after,
+            Some(datetime!(2024-06-01 0:00 UTC))
+        );
+        assert_eq!(parsed.cleaned_query, "launch plan");
+
+        let mut reworded = ParsedQuery {
+            cleaned_query: "launch plan discussed in june".to_string(),
+            ..Default::default()
+        };
+        let filters =
+            parse_model_answer(r#"{"source_types": ["slack"], "query": "product launch roadmap"}"#)
+                .unwrap();
+        assert!(apply_model_filters(
+            &mut reworded,
+            filters,
+            &connected,
+            chrono_tz::UTC
+        ));
+        assert_eq!(reworded.cleaned_query, "launch plan discussed in june");
+
+        assert!(parse_model_answer("no filters here").is_none());
+    }
+}
@@ -0,0 +1,186 @@
+//! Audit records for generated answers.
+//!
+//! Every RAG invocation stores exactly what was placed in the prompt: the
+//! chunks (by embedding id and offsets), the version of each document they
+//! came from, their scores, and the identity and groups the permission
+//! filter was evaluated against. Records are append-only and read through the
+//! admin API.
+
+use crate::models::SearchResult;
+use serde::{Deserialize, Serialize};
+use shared::models::SourceType;
+use sqlx::types::Json;
+use sqlx::types::time::OffsetDateTime;
+use sqlx::{FromRow, PgPool};
+
+pub const DEFAULT_LIST_LIMIT: i64 = 50;
+pub const MAX_LIST_LIMIT: i64 = 500;
+
+/// An embedded chunk that contributed text to the prompt.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContextChunk {
+    /// `embeddings.id`
+    pub chunk_id: String,
+    pub chunk_index: i32,
+    pub start_offset: i32,
+    pub end_offset: i32,
+    /// 1-based page the chunk starts on, for documents with pages.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page_number: Option<i32>,
+    /// Headings enclosing the chunk start, e.g. "Runbook > Paging".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub heading_path: Option<String>,
+}
+
+/// One context entry of the prompt, in prompt order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContextEntry {
+    pub rank: usize,
+    pub document_id: String,
+    pub source_id: String,
+    pub title: String,
+    pub url: Option<String>,
+    pub match_type: String,
+    pub score: f32,
+    /// Document version the chunks were read from.
+    #[serde(with = "time::serde::iso8601")]
+    pub document_updated_at: OffsetDateTime,
+    #[serde(with = "time::serde::iso8601")]
+    pub document_indexed_at: OffsetDateTime,
+    /// Empty for fulltext matches, which contribute highlights rather than
+    /// embedded chunks.
+    pub chunks: Vec<ContextChunk>,
+    /// The document's permissions at answer time.
+    pub permissions: serde_json::Value,
+}
+
+impl ContextEntry {
+    pub fn new(rank: usize, result: &SearchResult, chunks: Vec<ContextChunk>) -> Self {
+        let document = &result.document;
+        Self {
+            rank,
+            document_id: document.id.clone(),
+            source_id: document.source_id.clone(),
+            title: document.title.clone(),
+            url: document.url.clone(),
+            match_type: result.match_type.clone(),
+            score: result.score,
+            document_updated_at: document.updated_at,
+            document_indexed_at: document.last_indexed_at,
+            chunks,
+            permissions: document.permissions.clone(),
+        }
+    }
+}
+
+/// The inputs the permission filter was evaluated with.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PermissionSnapshot {
+    pub user_email: Option<String>,
+    pub user_groups: Vec<String>,
+    pub source_types: Option<Vec<SourceType>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct RagProvenance {
+    pub id: String,
+    pub user_id: Option<String>,
+    pub user_email: Option<String>,
+    pub query: String,
+    pub permission_snapshot: Json<PermissionSnapshot>,
+    pub context: Json<Vec<ContextEntry>>,
+    #[serde(with = "time::serde::iso8601")]
+    pub created_at: OffsetDateTime,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct RagProvenanceQuery {
+    pub user_id: Option<String>,
+    pub document_id: Option<String>,
+    pub limit: Option<i64>,
+}
+
+impl RagProvenanceQuery {
+    pub fn limit(&self) -> i64 {
+        self.limit
+            .unwrap_or(DEFAULT_LIST_LIMIT)
+            .clamp(1, MAX_LIST_LIMIT)
+    }
+}
+
+pub struct RagProvenanceRepository {
+    pool: PgPool,
+}
+
+impl RagProvenanceRepository {
+    pub fn new(pool: &PgPool) -> Self {
+        Self { pool: pool.clone() }
+    }
+
+    /// Persist a record and return its id.
+    pub async fn record(
+        &self,
+        user_id: Option<&str>,
+        query: &str,
+        permission_snapshot: &PermissionSnapshot,
+        context: &[ContextEntry],
+    ) -> Result<String, sqlx::Error> {
+        let id = ulid::Ulid::new().to_string();
+        let document_ids: Vec<&str> = context.iter().map(|e| e.document_id.as_str()).collect();
+
+        sqlx::query(
+            r#"
+            INSERT INTO rag_provenance
+                (id, user_id, user_email, query, permission_snapshot, context, document_ids)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+        )
+        .bind(&id)
+        .bind(user_id)
+        .bind(permission_snapshot.user_email.as_deref())
+        .bind(query)
+        .bind(Json(permission_snapshot))
+        .bind(Json(context))
+        .bind(&document_ids)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(id)
+    }
+
+    pub async fn get(&self, id: &str) -> Result<Option<RagProvenance>, sqlx::Error> {
+        sqlx::query_as::<_, RagProvenance>(
+            r#"
+            SELECT id, user_id, user_email, query, permission_snapshot, context, created_at
+            FROM rag_provenance
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    /// Most recent records first, optionally narrowed to a user or to answers
+    /// that used a given document.
+    pub async fn list(
+        &self,
+        query: &RagProvenanceQuery,
+    ) -> Result<Vec<RagProvenance>, sqlx::Error> {
+        sqlx::query_as::<_, RagProvenance>(
+            r#"
+            SELECT id, user_id, user_email, query, permission_snapshot, context, created_at
+            FROM rag_provenance
+            WHERE ($1::text IS NULL OR user_id = $1)
+              AND ($2::text IS NULL OR $2 = ANY(document_ids))
+            ORDER BY created_at DESC
+            LIMIT $3
+            "#,
+        )
+        .bind(query.user_id.as_deref())
+        .bind(query.document_id.as_deref())
+        .bind(query.limit())
+        .fetch_all(&self.pool)
+        .await
+    }
+}
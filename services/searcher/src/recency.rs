@@ -0,0 +1,243 @@
+//! Time decay for fused hybrid scores.
+//!
+//! Rank fusion only sees each retriever's ranks, so two documents that rank
+//! alike score alike however old they are. After fusion each score is
+//! multiplied by `1 + weight * 0.5^(age / half_life)`: a document updated
+//! today gets the full boost, one a half-life old gets half of it. Half-lives
+//! can differ per source type, since a week-old Slack thread is stale long
+//! before a week-old design doc.
+
+use crate::models::{FieldError, SearchResult};
+use crate::search::source_type_to_string;
+use serde::{Deserialize, Serialize};
+use shared::SearcherConfig;
+use shared::models::{Document, SourceType};
+use std::collections::HashMap;
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+
+const SECONDS_PER_DAY: f64 = 86_400.0;
+const MAX_RECENCY_WEIGHT: f32 = 10.0;
+
+/// Per-request overrides of the configured decay, for experimentation.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct RecencyDecayParams {
+    /// Zero turns the decay off.
+    pub weight: Option<f32>,
+    pub half_life_days: Option<f32>,
+    /// Merged over the configured per-source-type half-lives.
+    pub half_life_days_by_source_type: Option<HashMap<SourceType, f32>>,
+}
+
+impl RecencyDecayParams {
+    pub fn validate(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+        if self
+            .weight
+            .is_some_and(|w| !(w.is_finite() && (0.0..=MAX_RECENCY_WEIGHT).contains(&w)))
+        {
+            errors.push(FieldError::new(
+                "recency_decay.weight",
+                format!("must be between 0 and {}", MAX_RECENCY_WEIGHT),
+            ));
+        }
+        if self.half_life_days.is_some_and(|h| !is_valid_half_life(h)) {
+            errors.push(FieldError::new(
+                "recency_decay.half_life_days",
+                "must be greater than 0",
+            ));
+        }
+        let mut invalid_types: Vec<String> = self
+            .half_life_days_by_source_type
+            .iter()
+            .flatten()
+            .filter(|(_, h)| !is_valid_half_life(**h))
+            .map(|(st, _)| source_type_to_string(st))
+            .collect();
+        invalid_types.sort();
+        for source_type in invalid_types {
+            errors.push(FieldError::new(
+                format!(
+                    "recency_decay.half_life_days_by_source_type.{}",
+                    source_type
+                ),
+                "must be greater than 0",
+            ));
+        }
+        errors
+    }
+}
+
+fn is_valid_half_life(days: f32) -> bool {
+    days.is_finite() && days > 0.0
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecencyDecay {
+    pub weight: f32,
+    pub half_life_days: f32,
+    /// Keyed by source type name, as carried on search results.
+    pub half_life_days_by_source_type: HashMap<String, f32>,
+}
+
+impl RecencyDecay {
+    pub fn new(config: &SearcherConfig, overrides: Option<&RecencyDecayParams>) -> Self {
+        Self {
+            weight: config.hybrid_recency_weight,
+            half_life_days: config.recency_half_life_days,
+            half_life_days_by_source_type: by_source_type_name(
+                &config.recency_half_life_days_by_source_type,
+            ),
+        }
+        .with_overrides(overrides)
+    }
+
+    fn with_overrides(mut self, overrides: Option<&RecencyDecayParams>) -> Self {
+        let Some(overrides) = overrides else {
+            return self;
+        };
+        if let Some(weight) = overrides.weight {
+            self.weight = weight;
+        }
+        if let Some(half_life_days) = overrides.half_life_days {
+            self.half_life_days = half_life_days;
+        }
+        if let Some(by_type) = &overrides.half_life_days_by_source_type {
+            self.half_life_days_by_source_type
+                .extend(by_source_type_name(by_type));
+        }
+        self
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.weight > 0.0
+    }
+
+    /// Score multiplier for a document `age_days` old.
+    pub fn factor(&self, source_type: Option<&str>, age_days: f64) -> f32 {
+        let half_life = source_type
+            .and_then(|st| self.half_life_days_by_source_type.get(st))
+            .copied()
+            .unwrap_or(self.half_life_days);
+        let decay = 0.5_f64.powf(age_days.max(0.0) / f64::from(half_life));
+        (1.0 + f64::from(self.weight) * decay) as f32
+    }
+
+    /// Multiply each score by its document's decay factor. Results must have
+    /// their source types populated for per-type half-lives to apply.
+    pub fn apply(&self, results: &mut [SearchResult], now: OffsetDateTime) {
+        for result in results {
+            let age_days =
+                (now - last_updated(&result.document)).as_seconds_f64() / SECONDS_PER_DAY;
+            result.score *= self.factor(result.source_type.as_deref(), age_days);
+        }
+    }
+}
+
+fn by_source_type_name(half_lives: &HashMap<SourceType, f32>) -> HashMap<String, f32> {
+    half_lives
+        .iter()
+        .map(|(st, days)| (source_type_to_string(st), *days))
+        .collect()
+}
+
+/// When the document last changed at its source, falling back to when Omni
+/// last updated it. Matches the timestamp the SQL recency boosts use.
+pub(crate) fn last_updated(document: &Document) -> OffsetDateTime {
+    document
+        .metadata
+        .get("updated_at")
+        .and_then(|v| v.as_str())
+        .and_then(|v| OffsetDateTime::parse(v, &Rfc3339).ok())
+        .unwrap_or(document.updated_at)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use time::macros::datetime;
+
+    fn decay() -> RecencyDecay {
+        RecencyDecay {
+            weight: 1.0,
+            half_life_days: 30.0,
+            half_life_days_by_source_type: HashMap::from([("slack".to_string(), 7.0)]),
+        }
+    }
+
+    fn result(id: &str, source_type: &str, metadata_updated_at: Option<&str>) -> SearchResult {
+        let updated_at = datetime!(2026-01-01 00:00 UTC);
+        let mut result = SearchResult::for_test(id);
+        result.document.metadata = json!({ "updated_at": metadata_updated_at });
+        result.document.created_at = updated_at;
+        result.document.updated_at = updated_at;
+        result.document.last_indexed_at = updated_at;
+        result.source_type = Some(source_type.to_string());
+        result
+    }
+
+    #[test]
+    fn test_factor_halves_boost_each_half_life() {
+        let decay = decay();
+        assert!((decay.factor(Some("confluence"), 0.0) - 2.0).abs() < 1e-6);
+        assert!((decay.factor(Some("confluence"), 30.0) - 1.5).abs() < 1e-6);
+        assert!((decay.factor(None, 60.0) - 1.25).abs() < 1e-6);
+        // Slack decays four times as fast
+        assert!((decay.factor(Some("slack"), 7.0) - 1.5).abs() < 1e-6);
+        // Future timestamps are treated as brand new
+        assert!((decay.factor(Some("slack"), -3.0) - 2.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_apply_prefers_source_timestamp() {
+        let mut results = vec![
+            result("fresh", "confluence", Some("2026-01-31T00:00:00Z")),
+            result("fallback", "confluence", None),
+            result("unparseable", "confluence", Some("last tuesday")),
+        ];
+        decay().apply(&mut results, datetime!(2026-01-31 00:00 UTC));
+
+        assert!((results[0].score - 2.0).abs() < 1e-6);
+        assert!((results[1].score - 1.5).abs() < 1e-6);
+        assert!((results[2].score - 1.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_request_overrides_merge_over_config() {
+        let overrides = RecencyDecayParams {
+            weight: Some(0.5),
+            half_life_days: None,
+            half_life_days_by_source_type: Some(HashMap::from([(SourceType::Jira, 14.0)])),
+        };
+        assert!(overrides.validate().is_empty());
+
+        assert_eq!(
+            decay().with_overrides(Some(&overrides)),
+            RecencyDecay {
+                weight: 0.5,
+                half_life_days: 30.0,
+                half_life_days_by_source_type: HashMap::from([
+                    ("slack".to_string(), 7.0),
+                    ("jira".to_string(), 14.0),
+                ]),
+            }
+        );
+        assert_eq!(decay().with_overrides(None), decay());
+
+        let invalid = RecencyDecayParams {
+            weight: Some(-1.0),
+            half_life_days: Some(0.0),
+            half_life_days_by_source_type: Some(HashMap::from([(SourceType::Jira, f32::NAN)])),
+        };
+        let fields: Vec<String> = invalid.validate().into_iter().map(|e| e.field).collect();
+        assert_eq!(
+            fields,
+            vec![
+                "recency_decay.weight",
+                "recency_decay.half_life_days",
+                "recency_decay.half_life_days_by_source_type.jira"
+            ]
+        );
+    }
+}
@@ -0,0 +1,129 @@
+//! Cross-encoder reranking of fused hybrid results.
+//!
+//! Rank fusion only combines each retriever's ranks. A cross-encoder reads
+//! the query and a candidate together, so it judges relevance far better, but
+//! is too slow to run over more than the head of the list. The top fused
+//! candidates are sent to the rerank endpoint and reordered by its scores;
+//! the rest keep their fused order below them.
+
+use crate::models::SearchResult;
+use shared::clients::ai::RerankResult;
+
+/// Most candidates a request may ask to rerank.
+pub const MAX_RERANK_TOP_N: usize = 200;
+/// Longest text sent to the reranker per candidate, in bytes.
+const MAX_RERANK_TEXT_LEN: usize = 2000;
+
+/// The text the reranker scores a result by: its title and matched passages.
+pub fn rerank_text(result: &SearchResult) -> String {
+    let mut text = result.document.title.clone();
+    let passages = if result.highlights.is_empty() {
+        result.content.iter().cloned().collect()
+    } else {
+        result.highlights.clone()
+    };
+    for passage in passages {
+        text.push('\n');
+        text.push_str(&passage);
+    }
+    if text.len() > MAX_RERANK_TEXT_LEN {
+        let mut end = MAX_RERANK_TEXT_LEN;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        text.truncate(end);
+    }
+    text
+}
+
+/// Reorder the first `top_n` results, which were sent to the reranker in
+/// order, by its ranking. Results the reranker left out follow in their fused
+/// order. The fused scores are handed out again in the new order, so later
+/// boosts and re-sorts start from the reranked order without the head
+/// overtaking the tail.
+pub fn apply_rerank_order(results: &mut Vec<SearchResult>, top_n: usize, ranked: &[RerankResult]) {
+    let top_n = top_n.min(results.len());
+    let mut scores: Vec<f32> = results[..top_n].iter().map(|r| r.score).collect();
+    scores.sort_by(|a, b| b.total_cmp(a));
+
+    let mut head: Vec<Option<SearchResult>> = results.drain(..top_n).map(Some).collect();
+    let mut reordered: Vec<SearchResult> = ranked
+        .iter()
+        .map(|r| r.index)
+        .chain(0..top_n)
+        .filter_map(|index| head.get_mut(index).and_then(Option::take))
+        .collect();
+    for (result, score) in reordered.iter_mut().zip(scores) {
+        result.score = score;
+    }
+    results.splice(0..0, reordered);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(id: &str, score: f32) -> SearchResult {
+        let mut result = SearchResult::for_test(id);
+        result.document.title = format!("Title {}", id);
+        result.score = score;
+        result
+    }
+
+    fn ranked(index: usize, relevance_score: f32) -> RerankResult {
+        RerankResult {
+            index,
+            relevance_score,
+        }
+    }
+
+    fn ids(results: &[SearchResult]) -> Vec<&str> {
+        results.iter().map(|r| r.document.id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_reorders_head_and_keeps_fused_scores() {
+        let mut results = vec![
+            result("a", 0.04),
+            result("b", 0.03),
+            result("c", 0.02),
+            result("d", 0.01),
+        ];
+
+        apply_rerank_order(
+            &mut results,
+            3,
+            &[ranked(2, 0.9), ranked(0, 0.5), ranked(1, 0.1)],
+        );
+
+        assert_eq!(ids(&results), vec!["c", "a", "b", "d"]);
+        let scores: Vec<f32> = results.iter().map(|r| r.score).collect();
+        assert_eq!(scores, vec![0.04, 0.03, 0.02, 0.01]);
+    }
+
+    #[test]
+    fn test_unranked_and_invalid_indices_keep_fused_order() {
+        let mut results = vec![result("a", 0.3), result("b", 0.2), result("c", 0.1)];
+
+        apply_rerank_order(
+            &mut results,
+            10,
+            &[ranked(2, 0.8), ranked(7, 0.7), ranked(2, 0.6)],
+        );
+
+        assert_eq!(ids(&results), vec!["c", "a", "b"]);
+    }
+
+    #[test]
+    fn test_rerank_text_prefers_highlights_over_content() {
+        let mut with_content = result("a", 1.0);
+        with_content.content = Some("chunk text".to_string());
+        assert_eq!(rerank_text(&with_content), "Title a\nchunk text");
+
+        with_content.highlights = vec!["first".to_string(), "second".to_string()];
+        assert_eq!(rerank_text(&with_content), "Title a\nfirst\nsecond");
+
+        with_content.highlights = vec!["é".repeat(MAX_RERANK_TEXT_LEN)];
+        assert!(rerank_text(&with_content).len() <= MAX_RERANK_TEXT_LEN);
+    }
+}
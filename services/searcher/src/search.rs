@@ -1,16 +1,43 @@
+use crate::dedupe::collapse_duplicates;
+use crate::document_search;
+use crate::learned_ranking::{LearnedRanker, RetrieverScores};
+use crate::metadata_filter;
 use crate::models::{
     RecentSearchesResponse, SearchMode, SearchRequest, SearchResponse, SearchResult,
 };
 use crate::operator_registry::OperatorRegistry;
-use crate::query_parser;
-use crate::search_repository::SearchDocumentRepository;
+use crate::personalization::{PersonalizationDebug, UserSignals};
+use crate::query_ast;
+use crate::query_embedding_cache;
+use crate::query_intent::QueryIntentClassifier;
+use crate::query_language::QueryLanguage;
+use crate::query_parser::{self, CONTENT_KIND_ATTRIBUTE};
+use crate::query_understanding::QueryUnderstanding;
+use crate::rag_provenance::{ContextChunk, ContextEntry, PermissionSnapshot};
+use crate::recency::RecencyDecay;
+use crate::rerank::{apply_rerank_order, rerank_text};
+use crate::search_cache;
+use crate::search_repository::{SearchDocumentRepository, build_structured_query_text};
+use crate::sla::SlaMonitor;
+use crate::snippets::{self, Snippet};
+use crate::source_boosts::{SourceBoostRepository, apply_source_boosts, effective_source_boosts};
+use crate::source_router::{RoutingDecision, SourceRouter};
+use crate::spelling::SpellChecker;
 use anyhow::Result;
 use redis::{AsyncCommands, Client as RedisClient};
 use shared::SourceType;
+use shared::clients::embeddings::{
+    EmbeddingProvider, EmbeddingProviderResolver, EmbeddingTask, embed_texts,
+};
+use shared::content_extractor::TABLES_HEADING;
 use shared::db::repositories::{
-    DocumentRepository, EmbeddingRepository, GroupRepository, PersonRepository, SourceRepository,
+    DocumentRepository, EmbeddingRepository, GroupRepository, PersonRepository,
+    SourceMaintenanceRepository, SourceRepository, WorkspaceRepository,
+};
+use shared::models::{
+    AttributeFilter, ChunkResult, DEFAULT_WORKSPACE_ID, Document, Facet, FacetValue,
 };
-use shared::models::{ChunkResult, Document, Facet, FacetValue};
+use shared::ranking::{RankingFeatures, RankingImpression};
 use shared::utils::safe_str_slice;
 use shared::{
     AIClient, DatabasePool, ObjectStorage, Repository, SearcherConfig, StorageFactory,
@@ -22,16 +49,52 @@ use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
-use tracing::{debug, error, info};
+use time::OffsetDateTime;
+use tokio::sync::mpsc::UnboundedSender;
+use tracing::{debug, error, info, warn};
+
+/// Stands in for the model name in cached query embeddings when queries are
+/// embedded by the AI service, which does not report its model.
+const AI_SERVICE_QUERY_MODEL: &str = "ai-service";
+
+/// Context for a generated answer, along with what the provenance record
+/// needs to reproduce how it was assembled.
+pub struct RagContext {
+    pub results: Vec<SearchResult>,
+    pub provenance: Vec<ContextEntry>,
+    pub permissions: PermissionSnapshot,
+}
+
+/// Fulltext hits of a hybrid search, available before semantic search and
+/// rank fusion finish. Query-level boosts are not yet applied.
+#[derive(Debug)]
+pub struct PartialResults {
+    pub results: Vec<SearchResult>,
+    pub total_count: i64,
+}
+
+pub type PartialResultsSender = UnboundedSender<PartialResults>;
+
+/// Results, their total count, and the ranking features of the results by
+/// document id when learned ranking is on.
+type HybridResults = (Vec<SearchResult>, i64, HashMap<String, RankingFeatures>);
 
 pub struct SearchEngine {
     db_pool: DatabasePool,
     redis_client: RedisClient,
     ai_client: AIClient,
+    embedding_resolver: EmbeddingProviderResolver,
     content_storage: Arc<dyn ObjectStorage>,
     config: SearcherConfig,
     person_repo: PersonRepository,
     operator_registry: Arc<OperatorRegistry>,
+    source_router: Arc<SourceRouter>,
+    query_intent: Arc<QueryIntentClassifier>,
+    query_language: Arc<QueryLanguage>,
+    query_understanding: Arc<QueryUnderstanding>,
+    sla_monitor: Arc<SlaMonitor>,
+    spell_checker: Arc<SpellChecker>,
+    learned_ranker: Arc<LearnedRanker>,
 }
 
 impl SearchEngine {
@@ -43,21 +106,42 @@ impl SearchEngine {
         ai_client: AIClient,
         config: SearcherConfig,
         operator_registry: Arc<OperatorRegistry>,
+        source_router: Arc<SourceRouter>,
+        query_intent: Arc<QueryIntentClassifier>,
+        query_language: Arc<QueryLanguage>,
+        query_understanding: Arc<QueryUnderstanding>,
+        sla_monitor: Arc<SlaMonitor>,
+        spell_checker: Arc<SpellChecker>,
+        learned_ranker: Arc<LearnedRanker>,
     ) -> Result<Self> {
         let content_storage = StorageFactory::from_env(db_pool.pool().clone()).await?;
         let person_repo = PersonRepository::new(db_pool.pool());
+        let embedding_resolver = EmbeddingProviderResolver::new(db_pool.pool(), ai_client.clone());
 
         Ok(Self {
             db_pool,
             redis_client,
             ai_client,
+            embedding_resolver,
             content_storage,
             config,
             person_repo,
             operator_registry,
+            source_router,
+            query_intent,
+            query_language,
+            query_understanding,
+            sla_monitor,
+            spell_checker,
+            learned_ranker,
         })
     }
 
+    fn search_repo(&self) -> SearchDocumentRepository {
+        SearchDocumentRepository::new(self.db_pool.pool())
+            .with_row_level_security(self.config.row_level_security)
+    }
+
     async fn populate_source_types(&self, results: &mut [SearchResult]) -> Result<()> {
         let source_ids: Vec<String> = results
             .iter()
@@ -84,6 +168,46 @@ impl SearchEngine {
         Ok(())
     }
 
+    /// Flag results from sources in maintenance that are still searchable.
+    async fn label_possibly_stale(&self, results: &mut [SearchResult]) -> Result<()> {
+        if results.is_empty() {
+            return Ok(());
+        }
+        let source_ids: Vec<String> = results
+            .iter()
+            .map(|r| r.document.source_id.clone())
+            .collect::<std::collections::HashSet<_>>()
+            .into_iter()
+            .collect();
+
+        let maintenance_repo = SourceMaintenanceRepository::new(self.db_pool.pool());
+        let stale_source_ids: std::collections::HashSet<String> = maintenance_repo
+            .find_stale_source_ids(&source_ids)
+            .await?
+            .into_iter()
+            .collect();
+        for result in results.iter_mut() {
+            result.possibly_stale = stale_source_ids.contains(&result.document.source_id);
+        }
+
+        Ok(())
+    }
+
+    /// Collapse results with the same content fingerprint into one.
+    async fn collapse_duplicate_results(
+        &self,
+        results: Vec<SearchResult>,
+    ) -> Result<Vec<SearchResult>> {
+        if results.len() < 2 {
+            return Ok(results);
+        }
+        let ids: Vec<String> = results.iter().map(|r| r.document.id.clone()).collect();
+        let fingerprints = DocumentRepository::new(self.db_pool.pool())
+            .find_content_fingerprints(&ids)
+            .await?;
+        Ok(collapse_duplicates(results, &fingerprints))
+    }
+
     fn prepare_document_for_response(&self, mut doc: Document) -> Document {
         doc.content_id = None;
 
@@ -112,6 +236,65 @@ impl SearchEngine {
     }
 
     pub async fn search(&self, request: SearchRequest) -> Result<SearchResponse> {
+        self.run_search_with_correction(request, None).await
+    }
+
+    /// Like `search`, but hybrid searches also send their fulltext hits to
+    /// `partial` as soon as they are ready. Cached responses and other search
+    /// modes only produce the final response.
+    pub async fn search_streaming(
+        &self,
+        request: SearchRequest,
+        partial: PartialResultsSender,
+    ) -> Result<SearchResponse> {
+        self.run_search_with_correction(request, Some(&partial))
+            .await
+    }
+
+    /// Run the search and, when the request sets `auto_correct` and the
+    /// query's best spelling correction finds more, return the corrected
+    /// query's results instead.
+    async fn run_search_with_correction(
+        &self,
+        request: SearchRequest,
+        partial: Option<&PartialResultsSender>,
+    ) -> Result<SearchResponse> {
+        // Structured queries are searched as given
+        let retry_request =
+            (request.auto_correct() && request.query_ast.is_none()).then(|| request.clone());
+        let response = self.run_search(request, partial).await?;
+
+        let Some(mut retry_request) = retry_request else {
+            return Ok(response);
+        };
+        let Some(corrected) = response
+            .did_you_mean
+            .as_ref()
+            .and_then(|suggestions| suggestions.first())
+        else {
+            return Ok(response);
+        };
+
+        info!("Retrying query '{}' as '{}'", response.query, corrected);
+        retry_request.query = corrected.clone();
+        retry_request.original_user_query = None;
+        retry_request.auto_correct = Some(false);
+        let mut corrected_response = self.run_search(retry_request, partial).await?;
+        if corrected_response.total_count <= response.total_count {
+            return Ok(response);
+        }
+
+        corrected_response.corrected_query = Some(corrected_response.query);
+        corrected_response.query = response.query;
+        corrected_response.did_you_mean = None;
+        Ok(corrected_response)
+    }
+
+    async fn run_search(
+        &self,
+        request: SearchRequest,
+        partial: Option<&PartialResultsSender>,
+    ) -> Result<SearchResponse> {
         let start_time = Instant::now();
 
         info!(
@@ -168,15 +351,68 @@ impl SearchEngine {
             return self.read_document_by_id(document_id, &request).await;
         }
 
-        // Parse query for structured operators (from:, in:, before:, etc.)
-        let parsed = query_parser::parse(
-            &request.query,
-            &self.person_repo as &dyn query_parser::PersonLookup,
-            &self.operator_registry,
-            &request.user_configuration,
-        )
-        .await;
+        // Parse query for structured operators (from:, in:, before:, etc.),
+        // or compile the structured query to the same filters
+        let mut parsed = match &request.query_ast {
+            Some(query_ast) => {
+                let compiled = query_ast::compile(query_ast, &request.user_configuration)
+                    .map_err(|e| anyhow::anyhow!("Invalid query_ast: {}", e))?;
+                request.text_query = compiled.text;
+                compiled.parsed
+            }
+            None => {
+                query_parser::parse(
+                    &request.query,
+                    &self.person_repo as &dyn query_parser::PersonLookup,
+                    &self.operator_registry,
+                    &request.user_configuration,
+                )
+                .await
+            }
+        };
         info!("Parsed query: {:?}", parsed);
+
+        // Take filters the user wrote in words out of the query. Filters the
+        // request sets itself are kept in place of understood ones.
+        let query_understanding = if request.understand_query()
+            && request.query_ast.is_none()
+            && !request.is_generated_query.unwrap_or(false)
+        {
+            for source in request.source_types.iter().flatten() {
+                if !parsed.source_types.contains(source) {
+                    parsed.source_types.push(*source);
+                }
+            }
+            for content_type in request.content_types.iter().flatten() {
+                if !parsed.content_types.contains(content_type) {
+                    parsed.content_types.push(content_type.clone());
+                }
+            }
+            let connected_source_types: Vec<SourceType> =
+                DocumentRepository::new(self.db_pool.pool())
+                    .fetch_active_sources()
+                    .await?
+                    .into_iter()
+                    .map(|(_, source_type)| source_type)
+                    .collect();
+            self.query_understanding
+                .understand(
+                    &mut parsed,
+                    &connected_source_types,
+                    &request.user_configuration,
+                )
+                .await
+        } else {
+            None
+        };
+        if let Some(understood) = &query_understanding {
+            debug!("Understood query: {:?}", understood);
+        }
+        if let Some(filter) = &request.filter {
+            let filter_sql = metadata_filter::compile(filter)
+                .map_err(|e| anyhow::anyhow!("Invalid filter: {}", e))?;
+            request.filter_sql = Some(filter_sql);
+        }
         let has_parsed_filters = !parsed.attribute_filters.is_empty()
             || !parsed.source_types.is_empty()
             || !parsed.boosted_source_types.is_empty()
@@ -191,6 +427,30 @@ impl SearchEngine {
             .get_or_insert(request.query.clone());
         request.query = parsed.cleaned_query;
 
+        // Classify what the user typed; generated queries are phrased for
+        // retrieval already and choose their own mode.
+        let intent = if request.is_generated_query.unwrap_or(false) {
+            None
+        } else {
+            self.query_intent.classify(&request.query).await
+        };
+        if let Some(intent) = &intent {
+            debug!("Query intent: {:?}", intent);
+            if request.mode.is_none() {
+                request.mode = Some(intent.intent.default_mode());
+            }
+            request.intent = Some(intent.intent);
+        }
+
+        // Fulltext search also matches the query against documents stemmed
+        // for its language.
+        if request.language.is_none() {
+            request.language = self
+                .query_language
+                .detect(&request.query)
+                .map(str::to_string);
+        }
+
         // Merge parsed attribute filters
         if !parsed.attribute_filters.is_empty() {
             let filters = request.attribute_filters.get_or_insert_with(HashMap::new);
@@ -199,6 +459,16 @@ impl SearchEngine {
             }
         }
 
+        if let Some(content_kind) = request.content_kind.take() {
+            request
+                .attribute_filters
+                .get_or_insert_with(HashMap::new)
+                .insert(
+                    CONTENT_KIND_ATTRIBUTE.to_string(),
+                    AttributeFilter::Exact(content_kind.to_lowercase().into()),
+                );
+        }
+
         // Merge parsed content types
         if !parsed.content_types.is_empty() {
             let cts = request.content_types.get_or_insert_with(Vec::new);
@@ -226,31 +496,66 @@ impl SearchEngine {
             request.person_filters = Some(parsed.person_filters);
         }
 
-        // Generate cache key based on request parameters
-        let cache_key = self.generate_cache_key(&request);
+        // Hybrid ranking uses the configured source boosts, overridden by any
+        // the request names. Resolve them before the cache key so changing
+        // the configuration takes effect immediately.
+        if matches!(request.search_mode(), SearchMode::Hybrid) {
+            let configured_boosts = SourceBoostRepository::new(self.db_pool.pool())
+                .get_all()
+                .await?;
+            request.source_boosts = Some(effective_source_boosts(
+                configured_boosts,
+                request.source_boosts.as_ref(),
+            ));
+        }
+
+        // Generate cache key based on request parameters, the user's access and
+        // the ranking profile in use
+        let ranking_profile_id = self.learned_ranker.profile_id().await;
+        let cache_key =
+            self.generate_cache_key(&request, &user_groups, ranking_profile_id.as_deref());
+        let mut cache_conn = if self.config.search_cache_ttl_secs > 0 {
+            self.redis_client
+                .get_multiplexed_async_connection()
+                .await
+                .ok()
+        } else {
+            None
+        };
 
         // Try to get from cache first
-        if let Ok(mut conn) = self.redis_client.get_multiplexed_async_connection().await {
-            if let Ok(cached_response) = conn.get::<_, String>(&cache_key).await {
-                if let Ok(response) = serde_json::from_str::<SearchResponse>(&cached_response) {
-                    info!("Cache hit for request: {:?}", request);
-                    return Ok(response);
-                }
-            }
+        let cached = match cache_conn.as_mut() {
+            Some(conn) => search_cache::get(conn, &cache_key).await,
+            None => None,
+        };
+        if let Some(response) = cached {
+            info!("Cache hit for request: {:?}", request);
+            return Ok(response);
+        }
+
+        // Lighter configuration while this mode is breaching its latency SLA
+        let degradation = self.sla_monitor.level(request.search_mode());
+        if degradation.is_degraded() {
+            debug!(
+                "Running {:?} search degraded: {}",
+                request.search_mode(),
+                degradation.as_str()
+            );
         }
 
         let repo = DocumentRepository::new(self.db_pool.pool());
-        let search_repo = SearchDocumentRepository::new(self.db_pool.pool());
+        let search_repo = self.search_repo();
 
         // Empty query is allowed ONLY if some narrowing filter will scope the
         // result set. Otherwise `filter_only_search` would scan the entire
         // corpus. Accept either parser-extracted filters (from query operators)
-        // OR body-provided filters (attribute_filters, source_types, content_types)
-        // that were merged into `request` above.
+        // OR body-provided filters (attribute_filters, filter, source_types,
+        // content_types) that were merged into `request` above.
         let has_body_filters = request
             .attribute_filters
             .as_ref()
             .map_or(false, |m| !m.is_empty())
+            || request.filter.is_some()
             || request
                 .source_types
                 .as_ref()
@@ -265,6 +570,37 @@ impl SearchEngine {
 
         let all_sources = repo.fetch_active_sources().await?;
         let all_source_ids: Vec<String> = all_sources.iter().map(|(id, _)| id.clone()).collect();
+
+        // Active filters reflect what the user asked for, so build them before
+        // source routing narrows the request.
+        let active_filters = build_active_filters(&request);
+
+        // Route only queries the user has not already scoped to sources.
+        let source_routing = if request.source_types.is_none()
+            && parsed.boosted_source_types.is_empty()
+            && !request.query.trim().is_empty()
+        {
+            let active_types: Vec<SourceType> = all_sources.iter().map(|(_, st)| *st).collect();
+            self.source_router
+                .route(&request.query, &active_types)
+                .await
+        } else {
+            None
+        };
+        let mut boosted_source_types = parsed.boosted_source_types.clone();
+        if let Some(routing) = &source_routing {
+            debug!("Source routing: {:?}", routing);
+            match routing.decision {
+                RoutingDecision::Restrict => {
+                    request.source_types = Some(routing.source_types.clone());
+                }
+                RoutingDecision::Boost => {
+                    boosted_source_types.extend(routing.source_types.iter().copied());
+                }
+                RoutingDecision::All => {}
+            }
+        }
+
         let filtered_source_ids: Vec<String> = if let Some(ref st) = request.source_types {
             all_sources
                 .iter()
@@ -275,13 +611,40 @@ impl SearchEngine {
             all_source_ids.clone()
         };
 
-        let tantivy_query = search_repo.build_query_text(&request.query).await?;
+        // Read the source generations before searching, so an update indexed
+        // while the search runs invalidates the entry written below.
+        let source_generations = match cache_conn.as_mut() {
+            Some(conn) => search_cache::snapshot_generations(conn, &filtered_source_ids)
+                .await
+                .ok(),
+            None => None,
+        };
+
+        let tantivy_query = match &request.text_query {
+            Some(text_query) => Some(build_structured_query_text(
+                text_query,
+                request.language.as_deref(),
+            )),
+            None => {
+                search_repo
+                    .build_query_text(&request.query, request.language.as_deref())
+                    .await?
+            }
+        };
+        // Semantic matching can embed a translation of the query instead.
+        let has_semantic_leg = !matches!(request.search_mode(), SearchMode::Fulltext);
+        if let Some(language) = request.language.as_deref().filter(|_| has_semantic_leg) {
+            request.translated_query = self
+                .query_language
+                .translate(&request.query, language)
+                .await;
+        }
 
         let search_future = async {
             let start_ts = Instant::now();
             let res = match request.search_mode() {
-                SearchMode::Fulltext => {
-                    self.fulltext_search(
+                SearchMode::Fulltext => self
+                    .fulltext_search(
                         &search_repo,
                         &request,
                         &filtered_source_ids,
@@ -291,16 +654,16 @@ impl SearchEngine {
                         request.offset(),
                     )
                     .await
-                }
+                    .map(|(results, total_count)| (results, total_count, HashMap::new())),
                 SearchMode::Semantic => {
                     let results = self
                         .semantic_search(&request, &user_groups, request.limit(), request.offset())
                         .await?;
                     let total_count = results.len() as i64;
-                    Ok((results, total_count))
+                    Ok((results, total_count, HashMap::new()))
                 }
                 SearchMode::Hybrid => {
-                    self.hybrid_search(&request, &user_groups, tantivy_query.as_deref())
+                    self.hybrid_search(&request, &user_groups, tantivy_query.as_deref(), partial)
                         .await
                 }
             };
@@ -309,9 +672,10 @@ impl SearchEngine {
             res
         };
 
-        // Unfiltered facets: query + permissions only, no source_type/date/person/content filters
+        // Facets: query, permissions and other selected facet values only, no
+        // source_type/date/person/content filters
         let unfiltered_facets_future = async {
-            if request.include_facets() {
+            if request.include_facets() && degradation.include_facets() {
                 let start_ts = Instant::now();
                 let facets = search_repo
                     .get_facet_counts(
@@ -320,10 +684,14 @@ impl SearchEngine {
                         &all_source_ids,
                         None,
                         None,
+                        None,
                         request.user_email().map(|e| e.as_str()),
                         &user_groups,
+                        request.collection_id.as_deref(),
                         None,
                         None,
+                        request.facet_dimensions(),
+                        request.facet_filters.as_ref(),
                     )
                     .await
                     .unwrap_or_else(|e| {
@@ -339,13 +707,14 @@ impl SearchEngine {
         };
 
         let (search_result, facets) = tokio::join!(search_future, unfiltered_facets_future);
-        let (mut results, total_count) = search_result?;
+        let (mut results, total_count, mut ranking_features) = search_result?;
 
         // Apply source boost for implicit source words (e.g. "standup slack")
-        if !parsed.boosted_source_types.is_empty() {
+        // and for source types the router predicted
+        if !boosted_source_types.is_empty() {
             let boosted_source_ids: Vec<String> = all_sources
                 .iter()
-                .filter(|(_, st)| parsed.boosted_source_types.contains(st))
+                .filter(|(_, st)| boosted_source_types.contains(st))
                 .map(|(id, _)| id.clone())
                 .collect();
             if !boosted_source_ids.is_empty() {
@@ -381,12 +750,41 @@ impl SearchEngine {
             }
         }
 
+        // Apply per-user signals: documents the user wrote, recently opened, or
+        // that are shared with teams they work with
+        let mut personalization = None;
+        if self.config.personalization_enabled
+            && self.config.personalization_weight > 0.0
+            && !degradation.skip_rerank()
+            && let Some(email) = request.user_email()
+        {
+            match UserSignals::load(
+                self.db_pool.pool(),
+                request.user_id.as_deref(),
+                email,
+                &user_groups,
+            )
+            .await
+            {
+                Ok(signals) => {
+                    personalization = Some(PersonalizationDebug {
+                        weight: self.config.personalization_weight,
+                        boosted: signals.apply(&mut results, self.config.personalization_weight),
+                    });
+                }
+                Err(e) => error!("Failed to load personalization signals: {}", e),
+            }
+        }
+        let personalized = personalization
+            .as_ref()
+            .is_some_and(|p| !p.boosted.is_empty());
+
         // Re-sort if any boosts were applied
-        if !parsed.boosted_source_types.is_empty() || !parsed.person_boosts.is_empty() {
+        if !boosted_source_types.is_empty() || !parsed.person_boosts.is_empty() || personalized {
             results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal));
         }
 
-        self.populate_fulltext_highlights(&search_repo, &request.query, &mut results)
+        self.populate_fulltext_snippets(&search_repo, &request.query, &mut results)
             .await?;
 
         let has_more = request.offset() + request.limit() < total_count;
@@ -397,16 +795,44 @@ impl SearchEngine {
         {
             self.populate_source_types(&mut results).await?;
         }
+        self.label_possibly_stale(&mut results).await?;
+        if request.dedupe() {
+            results = self.collapse_duplicate_results(results).await?;
+        }
 
-        // Build active_filters from merged request state
-        let active_filters = build_active_filters(&request);
+        // Generated queries are not what the user typed, so corrections to
+        // them would be meaningless to show.
+        let did_you_mean = if request.is_generated_query.unwrap_or(false) {
+            None
+        } else {
+            let typed_query = request
+                .original_user_query
+                .as_deref()
+                .unwrap_or(&request.query);
+            self.spell_checker
+                .did_you_mean(typed_query, total_count)
+                .await
+        };
 
         info!(
             "Search completed in {}ms, found {} results",
             query_time,
             results.len()
         );
+        self.sla_monitor
+            .record(request.search_mode(), start_time.elapsed());
 
+        let ranking_impressions = results
+            .iter()
+            .filter_map(|result| {
+                ranking_features
+                    .remove(&result.document.id)
+                    .map(|features| RankingImpression {
+                        document_id: result.document.id.clone(),
+                        features,
+                    })
+            })
+            .collect();
         let response = SearchResponse {
             results,
             total_count,
@@ -426,19 +852,37 @@ impl SearchEngine {
             } else {
                 Some(active_filters)
             },
+            did_you_mean,
+            corrected_query: None,
+            source_routing: source_routing.filter(|_| request.debug()),
+            personalization: personalization.filter(|_| request.debug()),
+            intent,
+            query_understanding,
+            search_event_id: None,
+            ranking_impressions,
         };
 
-        // Cache the response for 5 minutes
-        if let Ok(mut conn) = self.redis_client.get_multiplexed_async_connection().await {
-            if let Ok(response_json) = serde_json::to_string(&response) {
-                let _: Result<(), _> = conn.set_ex(&cache_key, response_json, 300).await;
-            }
+        // Cache the response until its sources are updated or the TTL passes.
+        // Degraded responses are not cached so full results come back as soon
+        // as the mode recovers.
+        if degradation.is_degraded() {
+            return Ok(response);
+        }
+        if let (Some(conn), Some(generations)) = (cache_conn.as_mut(), source_generations) {
+            search_cache::put(
+                conn,
+                &cache_key,
+                &generations,
+                &response,
+                self.config.search_cache_ttl_secs,
+            )
+            .await;
         }
 
         Ok(response)
     }
 
-    async fn populate_fulltext_highlights(
+    async fn populate_fulltext_snippets(
         &self,
         repo: &SearchDocumentRepository,
         query: &str,
@@ -458,11 +902,22 @@ impl SearchEngine {
             return Ok(());
         }
 
-        let highlights = repo.fetch_highlights(&document_ids, query).await?;
+        let fulltext_snippets = repo.fetch_snippets(&document_ids, query).await?;
         for result in results.iter_mut() {
-            if let Some(snippets) = highlights.get(&result.document.id) {
-                result.highlights = snippets.clone();
+            let Some(snippets) = fulltext_snippets.get(&result.document.id) else {
+                continue;
+            };
+            // Without matched words a headline is just the start of the
+            // document, so a semantic snippet of a hybrid match is kept.
+            let matched = snippets
+                .iter()
+                .any(|snippet| !snippet.highlights.is_empty());
+            if !matched && !result.snippets.is_empty() {
+                continue;
             }
+            let fragments: Vec<String> = snippets.iter().map(Snippet::to_markdown).collect();
+            result.highlights = vec![fragments.join(" ... ")];
+            result.snippets = snippets.clone();
         }
 
         Ok(())
@@ -496,13 +951,16 @@ impl SearchEngine {
                 source_ids,
                 content_types,
                 attribute_filters,
+                request.filter_sql.as_deref(),
                 fetch_limit,
                 fetch_offset,
                 request.user_email().map(|e| e.as_str()),
                 user_groups,
                 request.document_id.as_deref(),
+                request.collection_id.as_deref(),
                 request.date_filter.as_ref(),
                 request.person_filters.as_deref(),
+                request.facet_filters.as_ref(),
                 self.config.recency_boost_weight,
                 self.config.recency_half_life_days,
             )
@@ -530,10 +988,16 @@ impl SearchEngine {
                 document: prepared_doc,
                 score: search_hit.score as f32,
                 highlights,
+                snippets: Vec::new(),
                 match_type: "fulltext".to_string(),
                 content: None,
                 source_type: search_hit.source_type,
                 also_in: Vec::new(),
+                duplicates: Vec::new(),
+                location: None,
+                page: None,
+                heading_path: None,
+                possibly_stale: false,
             });
         }
 
@@ -554,29 +1018,23 @@ impl SearchEngine {
         let start_time = Instant::now();
         info!("Performing semantic search for query: '{}'", request.query);
 
-        let query_embedding = self.generate_query_embedding(&request.query).await?;
+        let query_embedding = self
+            .generate_query_embedding(request.semantic_query())
+            .await?;
 
-        let search_repo = SearchDocumentRepository::new(self.db_pool.pool());
         let doc_repo = DocumentRepository::new(self.db_pool.pool());
 
-        let sources = request.source_types.as_deref();
-        let content_types = request.content_types.as_deref();
-
         // Recency boost is applied in SQL (inside find_similar_with_filters)
         // by over-fetching candidates and re-ranking with an exponential decay
         // factor, consistent with how FTS handles recency in search_repository.
-        let chunk_results = search_repo
-            .find_similar_with_filters(
+        let chunk_results = self
+            .find_similar_chunks(
+                request,
+                user_groups,
                 query_embedding,
-                sources,
-                content_types,
                 limit,
                 offset,
-                request.user_email().map(|e| e.as_str()),
-                user_groups,
                 request.document_id.as_deref(),
-                self.config.recency_boost_weight,
-                self.config.recency_half_life_days,
             )
             .await?;
 
@@ -612,11 +1070,23 @@ impl SearchEngine {
                     .iter()
                     .map(|chunk| chunk.similarity_score)
                     .fold(f32::NEG_INFINITY, f32::max);
+                let best_chunk = Self::best_chunk(&chunks);
+                let page = best_chunk.and_then(|chunk| chunk.page_number);
+                let heading_path = best_chunk.and_then(|chunk| chunk.heading_path.clone());
 
                 // Fetch document content and extract chunk text using offsets
                 let mut chunk_highlights: Vec<(f32, String)> = Vec::new();
+                let mut snippets = Vec::new();
                 if let Some(content_id) = &doc.content_id {
                     if let Ok(content) = self.content_storage.get_text(content_id).await {
+                        snippets.extend(best_chunk.and_then(|chunk| {
+                            snippets::semantic_snippet(
+                                &content,
+                                chunk.chunk_start_offset as usize,
+                                chunk.chunk_end_offset as usize,
+                                &request.query,
+                            )
+                        }));
                         for chunk in chunks {
                             let chunk_text = self.extract_chunk_from_content(
                                 &content,
@@ -644,10 +1114,16 @@ impl SearchEngine {
                     document: prepared_doc,
                     score: max_score,
                     highlights: all_highlights,
+                    snippets,
                     match_type: "semantic".to_string(),
                     content: None,
                     source_type: None,
                     also_in: Vec::new(),
+                    duplicates: Vec::new(),
+                    location: None,
+                    page,
+                    heading_path,
+                    possibly_stale: false,
                 });
             }
         }
@@ -662,6 +1138,18 @@ impl SearchEngine {
         Ok(results)
     }
 
+    /// The highest scoring chunk, whose page and section locate the match.
+    fn best_chunk<'a>(chunks: &[&'a ChunkResult]) -> Option<&'a ChunkResult> {
+        chunks
+            .iter()
+            .max_by(|a, b| {
+                a.similarity_score
+                    .partial_cmp(&b.similarity_score)
+                    .unwrap_or(Ordering::Equal)
+            })
+            .copied()
+    }
+
     fn extract_chunk_from_content(
         &self,
         content: &str,
@@ -694,12 +1182,31 @@ impl SearchEngine {
             .await?
             .ok_or_else(|| anyhow::anyhow!("Document not found: {}", document_id))?;
 
+        let scoped_search = !request.query.trim().is_empty()
+            && request.document_content_start_line.is_none()
+            && request.document_content_end_line.is_none();
+        let mut total_count = None;
+
         // Get actual content size (extracted text, not original file)
         let mut results = if let Some(content_id) = &doc.content_id {
             match self.content_storage.get_text(content_id).await {
                 Ok(content) => {
                     let content_size = content.len();
-                    if content_size < Self::CONTENT_SIZE_THRESHOLD {
+                    let (matches, total) = if scoped_search {
+                        self.search_within_document(&doc, &content, request).await?
+                    } else {
+                        (Vec::new(), 0)
+                    };
+                    if !matches.is_empty() {
+                        info!(
+                            "Found {} matches within document {}, returning {}",
+                            total,
+                            document_id,
+                            matches.len()
+                        );
+                        total_count = Some(total);
+                        matches
+                    } else if content_size < Self::CONTENT_SIZE_THRESHOLD {
                         // Small document: return full content
                         info!(
                             "Document content is small ({}B), returning full content",
@@ -709,10 +1216,16 @@ impl SearchEngine {
                             document: self.prepare_document_for_response(doc.clone()),
                             score: 1.0,
                             highlights: vec![content],
+                            snippets: Vec::new(),
                             match_type: "full_content".to_string(),
                             content: None,
                             source_type: None,
                             also_in: Vec::new(),
+                            duplicates: Vec::new(),
+                            location: None,
+                            page: None,
+                            heading_path: None,
+                            possibly_stale: false,
                         }]
                     } else {
                         // Check if specific line range is requested
@@ -775,10 +1288,16 @@ impl SearchEngine {
                                     document: self.prepare_document_for_response(doc.clone()),
                                     score: 1.0,
                                     highlights: vec![selected_content],
+                                    snippets: Vec::new(),
                                     match_type: "line_range".to_string(),
                                     content: None,
                                     source_type: None,
                                     also_in: Vec::new(),
+                                    duplicates: Vec::new(),
+                                    location: None,
+                                    page: None,
+                                    heading_path: None,
+                                    possibly_stale: false,
                                 }]
                             }
                             _ => {
@@ -808,7 +1327,8 @@ impl SearchEngine {
 
         self.populate_source_types(&mut results).await?;
 
-        let total_count = results.len() as i64;
+        let total_count = total_count.unwrap_or(results.len() as i64);
+        let has_more = request.offset() + (results.len() as i64) < total_count;
         let query_time = start_time.elapsed().as_millis() as u64;
 
         info!(
@@ -821,13 +1341,109 @@ impl SearchEngine {
             results,
             total_count,
             query_time_ms: query_time,
-            has_more: false,
+            has_more,
             query: request.query.clone(),
             facets: None,
             active_filters: None,
+            did_you_mean: None,
+            corrected_query: None,
+            source_routing: None,
+            personalization: None,
+            intent: None,
+            query_understanding: None,
+            search_event_id: None,
+            ranking_impressions: Vec::new(),
         })
     }
 
+    /// Search one document for the query: fulltext matches lines containing
+    /// query terms, semantic matches the document's chunks closest to the
+    /// query, and hybrid does both. Returns a page of match windows in
+    /// document order, along with the total number of windows.
+    async fn search_within_document(
+        &self,
+        doc: &shared::models::Document,
+        content: &str,
+        request: &SearchRequest,
+    ) -> Result<(Vec<SearchResult>, i64)> {
+        let user_groups = if let Some(email) = request.user_email() {
+            GroupRepository::new(self.db_pool.pool())
+                .find_groups_for_user(email.as_str())
+                .await
+                .unwrap_or_default()
+        } else {
+            vec![]
+        };
+        let accessible = DocumentRepository::new(self.db_pool.pool())
+            .with_row_level_security(self.config.row_level_security)
+            .filter_accessible_ids(
+                std::slice::from_ref(&doc.id),
+                request.user_email().map(|e| e.as_str()),
+                &user_groups,
+            )
+            .await?;
+        if accessible.is_empty() {
+            return Err(anyhow::anyhow!("Document not found: {}", doc.id));
+        }
+
+        let mode = request.search_mode();
+        let mut matches = Vec::new();
+        if matches!(mode, SearchMode::Fulltext | SearchMode::Hybrid) {
+            matches.extend(document_search::fulltext_matches(content, &request.query));
+        }
+        if matches!(mode, SearchMode::Semantic | SearchMode::Hybrid) {
+            // Semantic matching is best-effort; fulltext matches still count.
+            match self
+                .generate_query_embedding(request.semantic_query())
+                .await
+            {
+                Ok(embedding) => {
+                    let chunks = self
+                        .search_repo()
+                        .find_similar_chunks_in_document(
+                            &doc.id,
+                            embedding,
+                            request.offset() + request.limit(),
+                            request.embedding_namespace.as_deref(),
+                        )
+                        .await?;
+                    matches.extend(document_search::semantic_matches(content, &chunks));
+                }
+                Err(e) => warn!(
+                    "Semantic matching within document {} unavailable: {}",
+                    doc.id, e
+                ),
+            }
+        }
+
+        let windows =
+            document_search::build_windows(content, matches, document_search::CONTEXT_LINES);
+        let total = windows.len() as i64;
+        let prepared_doc = self.prepare_document_for_response(doc.clone());
+        let results = windows
+            .into_iter()
+            .skip(request.offset() as usize)
+            .take(request.limit() as usize)
+            .map(|window| SearchResult {
+                document: prepared_doc.clone(),
+                score: window.score,
+                highlights: vec![window.text],
+                snippets: Vec::new(),
+                match_type: window.source.as_str().to_string(),
+                content: None,
+                source_type: None,
+                also_in: Vec::new(),
+                duplicates: Vec::new(),
+                location: Some(window.location),
+                page: window.page,
+                heading_path: None,
+                possibly_stale: false,
+            })
+            .collect();
+
+        Ok((results, total))
+    }
+
     /// Read document chunks, optionally filtered by query for semantic search
     async fn read_document_chunks(
         &self,
@@ -848,10 +1464,12 @@ impl SearchEngine {
         let results = if !request.query.trim().is_empty() {
             // Query provided: do hybrid search within document
             info!("Query provided, hybrid search within document");
-            let search_repo = SearchDocumentRepository::new(self.db_pool.pool());
-            let tantivy_query = search_repo.build_query_text(&request.query).await?;
-            let (results, _total_count) = self
-                .hybrid_search(request, &user_groups, tantivy_query.as_deref())
+            let search_repo = self.search_repo();
+            let tantivy_query = search_repo
+                .build_query_text(&request.query, request.language.as_deref())
+                .await?;
+            let (results, _total_count, _ranking_features) = self
+                .hybrid_search(request, &user_groups, tantivy_query.as_deref(), None)
                 .await?;
             results
         } else {
@@ -881,10 +1499,16 @@ impl SearchEngine {
                     document: doc.clone(),
                     score: 1.0,
                     highlights: vec![truncated],
+                    snippets: Vec::new(),
                     match_type: "fulltext".to_string(),
                     content: None,
                     source_type: None,
                     also_in: Vec::new(),
+                    duplicates: Vec::new(),
+                    location: None,
+                    page: None,
+                    heading_path: None,
+                    possibly_stale: false,
                 }]
             } else {
                 error!(
@@ -898,8 +1522,146 @@ impl SearchEngine {
         Ok(results)
     }
 
+    /// Chunk-level semantic search. With coarse retrieval enabled, the search
+    /// is first narrowed to the documents whose summary embeddings match the
+    /// query best; if that yields fewer chunks than asked for, e.g. because
+    /// summaries are still being backfilled or filters exclude most
+    /// candidates, all chunks are searched instead.
+    async fn find_similar_chunks(
+        &self,
+        request: &SearchRequest,
+        user_groups: &[String],
+        query_embedding: Vec<f32>,
+        limit: i64,
+        offset: i64,
+        document_id: Option<&str>,
+    ) -> Result<Vec<ChunkResult>> {
+        let search_repo = self.search_repo();
+        let sources = request.source_types.as_deref();
+        let content_types = request.content_types.as_deref();
+        let user_email = request.user_email().map(|e| e.as_str());
+
+        // Summaries only exist for production embeddings, and a single
+        // document needs no narrowing
+        let coarse_limit = self.config.coarse_retrieval_documents;
+        if coarse_limit > 0 && document_id.is_none() && request.embedding_namespace.is_none() {
+            let candidates = search_repo
+                .find_coarse_candidates(&query_embedding, coarse_limit.max(limit + offset))
+                .await?;
+            if candidates.len() as i64 >= limit + offset {
+                let chunk_results = search_repo
+                    .find_similar_with_filters(
+                        query_embedding.clone(),
+                        sources,
+                        content_types,
+                        request.filter_sql.as_deref(),
+                        limit,
+                        offset,
+                        user_email,
+                        user_groups,
+                        None,
+                        Some(&candidates),
+                        request.collection_id.as_deref(),
+                        request.facet_filters.as_ref(),
+                        self.config.recency_boost_weight,
+                        self.config.recency_half_life_days,
+                        None,
+                    )
+                    .await?;
+                if chunk_results.len() as i64 >= limit {
+                    debug!(
+                        "Coarse retrieval narrowed semantic search to {} documents",
+                        candidates.len()
+                    );
+                    return Ok(chunk_results);
+                }
+            }
+            debug!("Coarse retrieval came up short, searching all chunks");
+        }
+
+        let chunk_results = search_repo
+            .find_similar_with_filters(
+                query_embedding,
+                sources,
+                content_types,
+                request.filter_sql.as_deref(),
+                limit,
+                offset,
+                user_email,
+                user_groups,
+                document_id,
+                None,
+                request.collection_id.as_deref(),
+                request.facet_filters.as_ref(),
+                self.config.recency_boost_weight,
+                self.config.recency_half_life_days,
+                request.embedding_namespace.as_deref(),
+            )
+            .await?;
+
+        Ok(chunk_results)
+    }
+
     async fn generate_query_embedding(&self, query: &str) -> Result<Vec<f32>> {
+        let provider = self.query_embedding_provider().await?;
+        let key = query_embedding_cache::cache_key(
+            provider
+                .as_ref()
+                .map_or(AI_SERVICE_QUERY_MODEL, |p| p.model_name()),
+            query,
+        );
+        if let Ok(mut conn) = self.redis_client.get_multiplexed_async_connection().await
+            && let Some(embedding) = query_embedding_cache::get(&mut conn, &key).await
+        {
+            debug!("Using prefetched embedding for query '{}'", query);
+            return Ok(embedding);
+        }
+
+        self.embed_query(query, provider.as_deref()).await
+    }
+
+    /// Compute the query's embedding now and cache it for `ttl_secs`, so a
+    /// search for it soon after skips the embedding call.
+    pub async fn prefetch_query_embedding(&self, query: &str, ttl_secs: u64) -> Result<()> {
+        let provider = self.query_embedding_provider().await?;
+        let key = query_embedding_cache::cache_key(
+            provider
+                .as_ref()
+                .map_or(AI_SERVICE_QUERY_MODEL, |p| p.model_name()),
+            query,
+        );
+        let embedding = self.embed_query(query, provider.as_deref()).await?;
+
+        let mut conn = self.redis_client.get_multiplexed_async_connection().await?;
+        query_embedding_cache::put(&mut conn, &key, &embedding, ttl_secs).await?;
+        Ok(())
+    }
+
+    /// The provider queries are embedded with directly, or `None` when they
+    /// go through the AI service.
+    async fn query_embedding_provider(&self) -> Result<Option<Arc<dyn EmbeddingProvider>>> {
+        if self.config.direct_query_embeddings {
+            Ok(Some(self.embedding_resolver.current().await?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    async fn embed_query(
+        &self,
+        query: &str,
+        provider: Option<&dyn EmbeddingProvider>,
+    ) -> Result<Vec<f32>> {
         debug!("Generating query embeddings for query '{}'", query);
+        if let Some(provider) = provider {
+            let embeddings =
+                embed_texts(provider, &[query.to_string()], EmbeddingTask::Query).await?;
+            return embeddings
+                .vectors
+                .into_iter()
+                .next()
+                .ok_or_else(|| anyhow::anyhow!("Failed to generate embedding for query"));
+        }
         let embeddings = self
             .ai_client
             .generate_embeddings_with_options(
@@ -923,34 +1685,28 @@ impl SearchEngine {
         &self,
         request: &SearchRequest,
         user_groups: &[String],
-    ) -> Result<Vec<SearchResult>> {
+    ) -> Result<Vec<(SearchResult, Vec<ContextChunk>)>> {
         let start_time = Instant::now();
         info!(
             "Generating enhanced semantic search results for RAG query: '{}'",
             request.query
         );
 
-        let query_embedding = self.generate_query_embedding(&request.query).await?;
-        let search_repo = SearchDocumentRepository::new(self.db_pool.pool());
+        let query_embedding = self
+            .generate_query_embedding(request.semantic_query())
+            .await?;
         let embedding_repo = EmbeddingRepository::new(self.db_pool.pool());
         let doc_repo = DocumentRepository::new(self.db_pool.pool());
 
-        let sources = request.source_types.as_deref();
-        let content_types = request.content_types.as_deref();
-
         // Recency boost is applied in SQL (see find_similar_with_filters).
-        let chunk_results = search_repo
-            .find_similar_with_filters(
+        let chunk_results = self
+            .find_similar_chunks(
+                request,
+                user_groups,
                 query_embedding,
-                sources,
-                content_types,
                 request.limit(),
                 request.offset(),
-                request.user_email().map(|e| e.as_str()),
-                user_groups,
                 None,
-                self.config.recency_boost_weight,
-                self.config.recency_half_life_days,
             )
             .await?;
 
@@ -979,6 +1735,9 @@ impl SearchEngine {
                     .iter()
                     .map(|chunk| chunk.similarity_score)
                     .fold(f32::NEG_INFINITY, f32::max);
+                let best_chunk = Self::best_chunk(&chunks);
+                let page = best_chunk.and_then(|chunk| chunk.page_number);
+                let heading_path = best_chunk.and_then(|chunk| chunk.heading_path.clone());
 
                 // Extract chunk indices for this document
                 let chunk_indices: Vec<i32> = chunks.iter().map(|c| c.chunk_index).collect();
@@ -989,10 +1748,13 @@ impl SearchEngine {
                         &document_id,
                         &chunk_indices,
                         self.config.rag_context_window,
+                        request.embedding_namespace.as_deref(),
                     )
                     .await?;
 
-                // Combine expanded chunks into continuous text
+                // Combine expanded chunks into continuous text, remembering
+                // which chunks contributed for the provenance record
+                let mut used_chunks = Vec::new();
                 let expanded_context = if let Some(content_id) = &doc.content_id {
                     if let Ok(content) = self.content_storage.get_text(content_id).await {
                         let mut chunk_texts = Vec::new();
@@ -1004,6 +1766,14 @@ impl SearchEngine {
                             );
                             if !chunk_text.trim().is_empty() {
                                 chunk_texts.push(chunk_text.trim().to_string());
+                                used_chunks.push(ContextChunk {
+                                    chunk_id: chunk.id.clone(),
+                                    chunk_index: chunk.chunk_index,
+                                    start_offset: chunk.chunk_start_offset,
+                                    end_offset: chunk.chunk_end_offset,
+                                    page_number: chunk.page_number,
+                                    heading_path: chunk.heading_path.clone(),
+                                });
                             }
                         }
                         chunk_texts.join(" ")
@@ -1015,24 +1785,33 @@ impl SearchEngine {
                 };
 
                 let prepared_doc = self.prepare_document_for_response(doc.clone());
-                results.push(SearchResult {
-                    document: prepared_doc,
-                    score: max_score,
-                    highlights: if expanded_context.trim().is_empty() {
-                        vec![]
-                    } else {
-                        vec![expanded_context]
+                results.push((
+                    SearchResult {
+                        document: prepared_doc,
+                        score: max_score,
+                        highlights: if expanded_context.trim().is_empty() {
+                            vec![]
+                        } else {
+                            vec![expanded_context]
+                        },
+                        snippets: Vec::new(),
+                        match_type: "semantic".to_string(),
+                        content: None,
+                        source_type: None,
+                        also_in: Vec::new(),
+                        duplicates: Vec::new(),
+                        location: None,
+                        page,
+                        heading_path,
+                        possibly_stale: false,
                     },
-                    match_type: "semantic".to_string(),
-                    content: None,
-                    source_type: None,
-                    also_in: Vec::new(),
-                });
+                    used_chunks,
+                ));
             }
         }
 
         // Sort results by score in descending order
-        results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal));
+        results.sort_by(|a, b| b.0.score.partial_cmp(&a.0.score).unwrap_or(Ordering::Equal));
 
         info!(
             "Enhanced semantic search for RAG completed in {}ms",
@@ -1046,30 +1825,50 @@ impl SearchEngine {
         request: &SearchRequest,
         user_groups: &[String],
         tantivy_query: Option<&str>,
-    ) -> Result<(Vec<SearchResult>, i64)> {
+        partial: Option<&PartialResultsSender>,
+    ) -> Result<HybridResults> {
         info!("Performing hybrid search for query: '{}'", request.query);
         let start_time = Instant::now();
 
         let doc_repo = DocumentRepository::new(self.db_pool.pool());
-        let search_repo = SearchDocumentRepository::new(self.db_pool.pool());
+        let search_repo = self.search_repo();
         let source_ids = doc_repo
             .fetch_active_source_ids(request.source_types.as_deref())
             .await?;
-        let candidate_limit = request.offset() + request.limit();
-        let fts_future = self.fulltext_search(
-            &search_repo,
-            request,
-            &source_ids,
-            user_groups,
-            tantivy_query,
-            candidate_limit,
-            0,
-        );
+        // Reranking needs the whole top-N fused, not just the requested page
+        let rerank_top_n = self
+            .rerank_top_n(request)
+            .filter(|_| !self.sla_monitor.level(&SearchMode::Hybrid).skip_rerank());
+        let candidate_limit =
+            (request.offset() + request.limit()).max(rerank_top_n.unwrap_or_default() as i64);
+        let fts_future = async {
+            let fts_results = self
+                .fulltext_search(
+                    &search_repo,
+                    request,
+                    &source_ids,
+                    user_groups,
+                    tantivy_query,
+                    candidate_limit,
+                    0,
+                )
+                .await;
+            if let (Some(partial), Ok((results, total_count))) = (partial, &fts_results) {
+                self.send_partial_results(partial, request, results, *total_count)
+                    .await;
+            }
+            fts_results
+        };
+
+        let semantic_k = self
+            .sla_monitor
+            .level(&SearchMode::Hybrid)
+            .semantic_k(candidate_limit, request.limit());
 
         // Apply timeout to semantic search
         let semantic_future = tokio::time::timeout(
             Duration::from_millis(self.config.semantic_search_timeout_ms),
-            self.semantic_search(request, user_groups, candidate_limit, 0),
+            self.semantic_search(request, user_groups, semantic_k, 0),
         );
 
         let (fts_results, semantic_results) = tokio::join!(fts_future, semantic_future);
@@ -1096,14 +1895,20 @@ impl SearchEngine {
             semantic_results.len()
         );
 
-        // Reciprocal Rank Fusion: score by rank position, not raw scores
+        // Reciprocal Rank Fusion: score by rank position, not raw scores,
+        // weighted toward the retriever that suits the query's intent
         let k = self.config.rrf_k;
+        let (fts_weight, semantic_weight) = request
+            .intent
+            .map_or((1.0, 1.0), |intent| intent.fusion_weights());
         let mut combined_results: HashMap<String, SearchResult> = HashMap::new();
         let mut rrf_scores: HashMap<String, f32> = HashMap::new();
+        let mut fts_scores: HashMap<String, f32> = HashMap::new();
+        let mut semantic_scores: HashMap<String, f32> = HashMap::new();
 
         for (rank, result) in fts_results.into_iter().enumerate() {
             let doc_id = result.document.id.clone();
-            let rrf_contrib = 1.0 / (k + (rank + 1) as f32);
+            let rrf_contrib = fts_weight / (k + (rank + 1) as f32);
             debug!(
                 "FTS result document {} [id={}], rank={}, rrf_contrib={:.6}",
                 result.document.title,
@@ -1112,6 +1917,7 @@ impl SearchEngine {
                 rrf_contrib
             );
             *rrf_scores.entry(doc_id.clone()).or_insert(0.0) += rrf_contrib;
+            fts_scores.insert(doc_id.clone(), result.score);
             let prepared_doc = self.prepare_document_for_response(result.document);
             combined_results.insert(
                 doc_id,
@@ -1119,17 +1925,32 @@ impl SearchEngine {
                     document: prepared_doc,
                     score: 0.0,
                     highlights: result.highlights,
+                    snippets: result.snippets,
                     match_type: "fulltext".to_string(),
                     content: result.content,
                     source_type: result.source_type,
                     also_in: Vec::new(),
+                    duplicates: Vec::new(),
+                    location: None,
+                    page: None,
+                    heading_path: None,
+                    possibly_stale: false,
                 },
             );
         }
 
+        // Word constraints of a structured query only apply to fulltext
+        // matches, so documents matched just semantically may violate them.
+        let fulltext_only = request
+            .text_query
+            .as_ref()
+            .is_some_and(|text_query| text_query.has_constraints());
         for (rank, result) in semantic_results.into_iter().enumerate() {
             let doc_id = result.document.id.clone();
-            let rrf_contrib = 1.0 / (k + (rank + 1) as f32);
+            if fulltext_only && !combined_results.contains_key(&doc_id) {
+                continue;
+            }
+            let rrf_contrib = semantic_weight / (k + (rank + 1) as f32);
             debug!(
                 "Semantic result document {} [id={}], rank={}, rrf_contrib={:.6}",
                 result.document.title,
@@ -1138,10 +1959,18 @@ impl SearchEngine {
                 rrf_contrib
             );
             *rrf_scores.entry(doc_id.clone()).or_insert(0.0) += rrf_contrib;
+            semantic_scores.insert(doc_id.clone(), result.score);
             combined_results
                 .entry(doc_id)
                 .and_modify(|existing| {
                     existing.match_type = "hybrid".to_string();
+                    existing.page = existing.page.or(result.page);
+                    if existing.snippets.is_empty() {
+                        existing.snippets = result.snippets.clone();
+                    }
+                    if existing.heading_path.is_none() {
+                        existing.heading_path = result.heading_path.clone();
+                    }
                 })
                 .or_insert_with(|| {
                     let prepared_doc = self.prepare_document_for_response(result.document);
@@ -1149,15 +1978,21 @@ impl SearchEngine {
                         document: prepared_doc,
                         score: 0.0,
                         highlights: result.highlights,
+                        snippets: result.snippets,
                         match_type: "semantic".to_string(),
                         content: result.content,
                         source_type: None,
                         also_in: Vec::new(),
+                        duplicates: Vec::new(),
+                        location: None,
+                        page: result.page,
+                        heading_path: result.heading_path,
+                        possibly_stale: false,
                     }
                 });
         }
 
-        // Apply RRF scores and sort
+        // Apply RRF scores, boosted per source type and decayed by age, and sort
         let mut final_results: Vec<SearchResult> = combined_results
             .into_iter()
             .map(|(doc_id, mut result)| {
@@ -1165,21 +2000,145 @@ impl SearchEngine {
                 result
             })
             .collect();
-        final_results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal));
         self.populate_source_types(&mut final_results).await?;
+
+        // With an active ranking profile, scores come from the candidates'
+        // features rather than the static retriever weights
+        let mut ranking_features: HashMap<String, RankingFeatures> = HashMap::new();
+        let mut learned_profile_applied = false;
+        if let Some(model) = self.learned_ranker.model().await {
+            let max_bm25 = fts_scores.values().copied().fold(0.0, f32::max);
+            let now = OffsetDateTime::now_utc();
+            for result in &mut final_results {
+                let doc_id = result.document.id.clone();
+                let scores = RetrieverScores {
+                    bm25: fts_scores.get(&doc_id).copied(),
+                    max_bm25,
+                    semantic: semantic_scores.get(&doc_id).copied(),
+                };
+                let features = model.features(result, scores, now);
+                if let Some(profile) = model.profile() {
+                    result.score = profile.score(&features);
+                }
+                ranking_features.insert(doc_id, features);
+            }
+            learned_profile_applied = model.profile().is_some();
+        }
+
+        if let Some(source_boosts) = &request.source_boosts
+            && apply_source_boosts(&mut final_results, source_boosts)
+        {
+            debug!("Applied source boosts: {:?}", source_boosts);
+        }
+        // A profile weighs recency itself, so only a decay the request asks
+        // for applies on top of it
+        let recency_decay = RecencyDecay::new(&self.config, request.recency_decay.as_ref());
+        if recency_decay.is_enabled()
+            && (!learned_profile_applied || request.recency_decay.is_some())
+        {
+            recency_decay.apply(&mut final_results, OffsetDateTime::now_utc());
+        }
+        final_results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal));
         final_results = Self::deduplicate_ranked_results_by_external_id(final_results);
+        if let Some(top_n) = rerank_top_n {
+            self.rerank_results(&request.query, &mut final_results, top_n)
+                .await;
+        }
 
         final_results = final_results
             .into_iter()
             .skip(request.offset() as usize)
             .take(request.limit() as usize)
             .collect();
+        ranking_features.retain(|doc_id, _| {
+            final_results
+                .iter()
+                .any(|result| &result.document.id == doc_id)
+        });
 
         info!(
             "Hybrid search completed in {}ms",
             start_time.elapsed().as_millis()
         );
-        Ok((final_results, fts_total_count))
+        Ok((final_results, fts_total_count, ranking_features))
+    }
+
+    /// Fused candidates to rerank, or `None` when the search is not reranked.
+    fn rerank_top_n(&self, request: &SearchRequest) -> Option<usize> {
+        let enabled = request.rerank.unwrap_or(self.config.rerank_enabled)
+            && self.config.rerank_url.is_some()
+            && matches!(request.search_mode(), SearchMode::Hybrid)
+            && !request.query.trim().is_empty();
+        enabled.then(|| request.rerank_top_n.unwrap_or(self.config.rerank_top_n))
+    }
+
+    /// Reorder the leading `top_n` results by cross-encoder relevance. The
+    /// fused order is kept if the reranker fails or does not answer in time.
+    async fn rerank_results(&self, query: &str, results: &mut Vec<SearchResult>, top_n: usize) {
+        let Some(rerank_url) = self.config.rerank_url.as_deref() else {
+            return;
+        };
+        let top_n = top_n.min(results.len());
+        if top_n < 2 {
+            return;
+        }
+
+        let start_time = Instant::now();
+        let documents: Vec<String> = results[..top_n].iter().map(rerank_text).collect();
+        let reranked = tokio::time::timeout(
+            Duration::from_millis(self.config.rerank_timeout_ms),
+            self.ai_client.rerank(
+                rerank_url,
+                self.config.rerank_model.as_deref(),
+                query,
+                &documents,
+            ),
+        )
+        .await;
+
+        match reranked {
+            Ok(Ok(ranked)) => {
+                apply_rerank_order(results, top_n, &ranked);
+                debug!(
+                    "Reranked {} candidates in {}ms",
+                    top_n,
+                    start_time.elapsed().as_millis()
+                );
+            }
+            Ok(Err(e)) => {
+                warn!("Rerank failed: {}, keeping fused order", e);
+            }
+            Err(_) => {
+                info!(
+                    "Rerank timed out after {}ms, keeping fused order",
+                    self.config.rerank_timeout_ms
+                );
+            }
+        }
+    }
+
+    /// Send the requested page of fulltext hits while semantic search is still
+    /// running. A closed channel just means the client went away.
+    async fn send_partial_results(
+        &self,
+        partial: &PartialResultsSender,
+        request: &SearchRequest,
+        fts_results: &[SearchResult],
+        total_count: i64,
+    ) {
+        let mut results: Vec<SearchResult> = fts_results
+            .iter()
+            .skip(request.offset() as usize)
+            .take(request.limit() as usize)
+            .cloned()
+            .collect();
+        if let Err(e) = self.label_possibly_stale(&mut results).await {
+            debug!("Failed to label partial results: {}", e);
+        }
+        let _ = partial.send(PartialResults {
+            results,
+            total_count,
+        });
     }
 
     /// Collapse an already-ranked result list by the same generic dedupe key
@@ -1200,10 +2159,18 @@ impl SearchEngine {
             .collect()
     }
 
-    fn generate_cache_key(&self, request: &SearchRequest) -> String {
+    fn generate_cache_key(
+        &self,
+        request: &SearchRequest,
+        user_groups: &[String],
+        ranking_profile_id: Option<&str>,
+    ) -> String {
         let mut hasher = DefaultHasher::new();
-        request.query.hash(&mut hasher);
+        search_cache::normalize_query(&request.query).hash(&mut hasher);
+        request.text_query.hash(&mut hasher);
         request.search_mode().hash(&mut hasher);
+        request.intent.hash(&mut hasher);
+        request.understand_query().hash(&mut hasher);
         request.limit().hash(&mut hasher);
         request.offset().hash(&mut hasher);
 
@@ -1220,14 +2187,32 @@ impl SearchEngine {
         }
 
         request.include_facets().hash(&mut hasher);
+        request.facet_dimensions().hash(&mut hasher);
+        request.facet_filters.hash(&mut hasher);
+        request.debug().hash(&mut hasher);
+        request.dedupe().hash(&mut hasher);
+        request.embedding_namespace.hash(&mut hasher);
+        self.rerank_top_n(request).hash(&mut hasher);
+        request.language.hash(&mut hasher);
 
         if let Some(attribute_filters) = &request.attribute_filters {
             let json = serde_json::to_string(attribute_filters).unwrap_or_default();
             json.hash(&mut hasher);
         }
+        if let Some(filter) = &request.filter {
+            let json = serde_json::to_string(filter).unwrap_or_default();
+            json.hash(&mut hasher);
+        }
 
-        if let Some(user_email) = &request.user_email {
-            user_email.hash(&mut hasher);
+        search_cache::permission_fingerprint(request.user_email.as_deref(), user_groups)
+            .hash(&mut hasher);
+        // Personalized rankings depend on the user's own activity
+        if self.config.personalization_enabled {
+            request.user_id.hash(&mut hasher);
+        }
+
+        if let Some(collection_id) = &request.collection_id {
+            collection_id.hash(&mut hasher);
         }
 
         if let Some(date_filter) = &request.date_filter {
@@ -1245,6 +2230,22 @@ impl SearchEngine {
             }
         }
 
+        if let Some(recency_decay) = &request.recency_decay {
+            let json = serde_json::to_string(recency_decay).unwrap_or_default();
+            json.hash(&mut hasher);
+        }
+
+        if let Some(source_boosts) = &request.source_boosts {
+            let mut boosts: Vec<(String, u32)> = source_boosts
+                .iter()
+                .map(|(st, boost)| (source_type_to_string(st), boost.to_bits()))
+                .collect();
+            boosts.sort();
+            boosts.hash(&mut hasher);
+        }
+
+        ranking_profile_id.hash(&mut hasher);
+
         format!("search:{:x}", hasher.finish())
     }
 
@@ -1255,7 +2256,7 @@ impl SearchEngine {
             return Ok(());
         }
 
-        let key = format!("search_history:{}", user_id);
+        let key = search_history_key(&self.db_pool, user_id).await?;
         let mut conn = self.redis_client.get_multiplexed_async_connection().await?;
 
         // Get all existing searches
@@ -1294,7 +2295,7 @@ impl SearchEngine {
 
     /// Get recent searches for a user from Redis
     pub async fn get_recent_searches(&self, user_id: &str) -> Result<RecentSearchesResponse> {
-        let key = format!("search_history:{}", user_id);
+        let key = search_history_key(&self.db_pool, user_id).await?;
         let mut conn = self.redis_client.get_multiplexed_async_connection().await?;
 
         // Get all searches (up to 5 as we maintain that limit)
@@ -1310,7 +2311,7 @@ impl SearchEngine {
     }
 
     /// Generate RAG context from search request using chunk-based approach with expanded context
-    pub async fn get_rag_context(&self, request: &SearchRequest) -> Result<Vec<SearchResult>> {
+    pub async fn get_rag_context(&self, request: &SearchRequest) -> Result<RagContext> {
         info!("Generating RAG context for query: '{}'", request.query);
 
         let user_groups = if let Some(email) = request.user_email() {
@@ -1324,11 +2325,13 @@ impl SearchEngine {
         };
 
         let doc_repo = DocumentRepository::new(self.db_pool.pool());
-        let search_repo = SearchDocumentRepository::new(self.db_pool.pool());
+        let search_repo = self.search_repo();
         let source_ids = doc_repo
             .fetch_active_source_ids(request.source_types.as_deref())
             .await?;
-        let tantivy_query = search_repo.build_query_text(&request.query).await?;
+        let tantivy_query = search_repo
+            .build_query_text(&request.query, request.language.as_deref())
+            .await?;
         let (fts_results, _fts_total_count) = self
             .fulltext_search(
                 &search_repo,
@@ -1357,18 +2360,36 @@ impl SearchEngine {
         // Add context around fulltext matches
         for fts_result in fts_results.into_iter().take(5) {
             // For fulltext matches, we already have highlights generated
-            combined_results.push(fts_result);
+            combined_results.push((fts_result, Vec::new()));
         }
 
         // Sort by score and take top results
-        combined_results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal));
+        combined_results
+            .sort_by(|a, b| b.0.score.partial_cmp(&a.0.score).unwrap_or(Ordering::Equal));
         combined_results.truncate(10);
 
         info!(
             "Generated RAG context with {} chunks",
             combined_results.len()
         );
-        Ok(combined_results)
+
+        let provenance = combined_results
+            .iter()
+            .enumerate()
+            .map(|(i, (result, chunks))| ContextEntry::new(i + 1, result, chunks.clone()))
+            .collect();
+        Ok(RagContext {
+            results: combined_results
+                .into_iter()
+                .map(|(result, _)| result)
+                .collect(),
+            provenance,
+            permissions: PermissionSnapshot {
+                user_email: request.user_email().cloned(),
+                user_groups,
+                source_types: request.source_types.clone(),
+            },
+        })
     }
 
     /// Generate cache key for AI answers based on query and timezone-sensitive context.
@@ -1376,6 +2397,7 @@ impl SearchEngine {
         let mut hasher = DefaultHasher::new();
         request.query.trim().to_lowercase().hash(&mut hasher);
         request.user_configuration.hash(&mut hasher);
+        request.collection_id.hash(&mut hasher);
         format!("ai_answer:{:x}", hasher.finish())
     }
 
@@ -1388,50 +2410,96 @@ impl SearchEngine {
             "Please provide a response to the user's question/instruction using the information from the provided context. ",
         );
         prompt.push_str(
-            "When referencing information, cite it using the format [<Document Title>](<Document URL>). Return your response in markdown format. Only reference documents provided as context below, do not cite anything else. ",
+            "When referencing information, cite it using the format [<Document Title>](<Document URL>), naming the section when the context gives one. Return your response in markdown format. Only reference documents provided as context below, do not cite anything else. ",
         );
 
-        prompt.push_str("Context Information:\n");
-        for (i, result) in context.iter().enumerate() {
-            prompt.push_str(&format!(
-                "Context {}: \nTitle: \"{}\"\nURL: {}\nMatch Type: {}\n",
-                i + 1,
-                result.document.title,
-                result.document.url.as_deref().unwrap_or("<unknown>"),
-                result.match_type,
-            ));
+        push_rag_context(&mut prompt, context);
+        prompt.push_str(&format!("Question: {}\n\n", query));
 
-            match result.match_type.as_str() {
-                "semantic" => {
-                    // For semantic chunks, use the highlights if available
-                    if !result.highlights.is_empty() {
-                        prompt.push_str(&format!("Content: {}\n", result.highlights[0]));
-                    }
+        prompt
+    }
+}
+
+/// Redis key of a user's search history in their current workspace, so the
+/// searches they made in a workspace they left don't follow them. The default
+/// workspace keeps the key histories had before workspaces existed.
+pub(crate) async fn search_history_key(db_pool: &DatabasePool, user_id: &str) -> Result<String> {
+    let workspace_id = WorkspaceRepository::new(db_pool.pool())
+        .workspace_id_for_user(user_id)
+        .await?;
+    Ok(if workspace_id == DEFAULT_WORKSPACE_ID {
+        format!("search_history:{}", user_id)
+    } else {
+        format!("search_history:{}:{}", workspace_id, user_id)
+    })
+}
+
+/// Append the numbered context entries of a RAG prompt, starting with any
+/// instructions the kind of context calls for.
+pub(crate) fn push_rag_context(prompt: &mut String, context: &[SearchResult]) {
+    if context.iter().any(|result| {
+        result
+            .heading_path
+            .as_deref()
+            .and_then(table_name)
+            .is_some()
+    }) {
+        prompt.push_str(
+            "Table context lists one row per line as `Column: value` pairs. For questions about specific values, answer from the row whose values match the question and quote the cells you used. ",
+        );
+    }
+
+    prompt.push_str("Context Information:\n");
+    for (i, result) in context.iter().enumerate() {
+        prompt.push_str(&format!(
+            "Context {}: \nTitle: \"{}\"\nURL: {}\nMatch Type: {}\n",
+            i + 1,
+            result.document.title,
+            result.document.url.as_deref().unwrap_or("<unknown>"),
+            result.match_type,
+        ));
+        if let Some(heading_path) = &result.heading_path {
+            match table_name(heading_path) {
+                Some(table) => prompt.push_str(&format!("Table: {}\n", table)),
+                None => prompt.push_str(&format!("Section: {}\n", heading_path)),
+            }
+        }
+
+        match result.match_type.as_str() {
+            "semantic" => {
+                // For semantic chunks, use the highlights if available
+                if !result.highlights.is_empty() {
+                    prompt.push_str(&format!("Content: {}\n", result.highlights[0]));
                 }
-                "fulltext" => {
-                    // For fulltext matches, use the highlights which contain context around matches
-                    if !result.highlights.is_empty() {
-                        prompt.push_str(&format!("Relevant excerpt: {}\n", result.highlights[0]));
-                    }
+            }
+            "fulltext" => {
+                // For fulltext matches, use the highlights which contain context around matches
+                if !result.highlights.is_empty() {
+                    prompt.push_str(&format!("Relevant excerpt: {}\n", result.highlights[0]));
                 }
-                _ => {
-                    if let Some(_content_id) = &result.document.content_id {
-                        if !result.highlights.is_empty() {
-                            prompt.push_str(&format!("Content: {}\n", result.highlights[0]));
-                        }
+            }
+            _ => {
+                if let Some(_content_id) = &result.document.content_id {
+                    if !result.highlights.is_empty() {
+                        prompt.push_str(&format!("Content: {}\n", result.highlights[0]));
                     }
                 }
             }
-            prompt.push_str("\n");
         }
-
-        prompt.push_str(&format!("Question: {}\n\n", query));
-
-        prompt
+        prompt.push_str("\n");
     }
 }
 
-fn source_type_to_string(st: &SourceType) -> String {
+/// Name of the extracted table a chunk's heading path points into, if any
+/// (see `shared::content_extractor::render_tables`).
+fn table_name(heading_path: &str) -> Option<&str> {
+    heading_path
+        .strip_prefix(TABLES_HEADING)?
+        .strip_prefix(" > ")
+        .filter(|name| !name.is_empty())
+}
+
+pub(crate) fn source_type_to_string(st: &SourceType) -> String {
     serde_json::to_value(st)
         .ok()
         .and_then(|v| v.as_str().map(String::from))
@@ -1508,6 +2576,19 @@ fn build_active_filters(request: &SearchRequest) -> Vec<Facet> {
         }
     }
 
+    for (dimension, values) in request.facet_filters.iter().flatten() {
+        filters.push(Facet {
+            name: format!("facet:{}", dimension.as_str()),
+            values: values
+                .iter()
+                .map(|value| FacetValue {
+                    value: value.clone(),
+                    count: None,
+                })
+                .collect(),
+        });
+    }
+
     if let Some(ref attribute_filters) = request.attribute_filters {
         for (key, filter) in attribute_filters {
             let value = serde_json::to_string(filter).unwrap_or_default();
@@ -1518,5 +2599,13 @@ fn build_active_filters(request: &SearchRequest) -> Vec<Facet> {
         }
     }
 
+    if let Some(ref filter) = request.filter {
+        let value = serde_json::to_string(filter).unwrap_or_default();
+        filters.push(Facet {
+            name: "filter".to_string(),
+            values: vec![FacetValue { value, count: None }],
+        });
+    }
+
     filters
 }
@@ -0,0 +1,239 @@
+//! Search analytics.
+//!
+//! Every search served is logged with its result count and latency, and
+//! returns the id of its log entry so the client can attach result clicks to
+//! it. The admin report aggregates the log into the most frequent queries and
+//! the queries that find nothing, with how often their results get opened,
+//! to show where relevance tuning or missing content hurts most.
+
+use crate::models::SearchMode;
+use crate::search_cache::normalize_query;
+use serde::{Deserialize, Serialize};
+use shared::ranking::RankingImpression;
+use sqlx::types::Json;
+use sqlx::types::time::OffsetDateTime;
+use sqlx::{FromRow, PgPool};
+use time::Duration;
+
+pub const DEFAULT_REPORT_DAYS: i64 = 30;
+pub const MAX_REPORT_DAYS: i64 = 365;
+pub const DEFAULT_REPORT_LIMIT: i64 = 20;
+pub const MAX_REPORT_LIMIT: i64 = 200;
+
+/// A search to log.
+#[derive(Debug, Clone)]
+pub struct SearchEvent<'a> {
+    pub user_id: Option<&'a str>,
+    pub query: &'a str,
+    pub search_mode: &'a SearchMode,
+    pub is_generated: bool,
+    pub result_count: i64,
+    pub latency_ms: u64,
+    /// Ranking features of the results returned, if learned ranking is on.
+    pub impressions: &'a [RankingImpression],
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct SearchAnalyticsQuery {
+    /// Days of searches to cover, counting back from now.
+    pub days: Option<i64>,
+    /// Queries listed per ranking.
+    pub limit: Option<i64>,
+    /// Include queries the assistant issued; by default only typed searches
+    /// are reported.
+    #[serde(default)]
+    pub include_generated: bool,
+}
+
+impl SearchAnalyticsQuery {
+    pub fn days(&self) -> i64 {
+        self.days
+            .unwrap_or(DEFAULT_REPORT_DAYS)
+            .clamp(1, MAX_REPORT_DAYS)
+    }
+
+    pub fn limit(&self) -> i64 {
+        self.limit
+            .unwrap_or(DEFAULT_REPORT_LIMIT)
+            .clamp(1, MAX_REPORT_LIMIT)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct SearchTotals {
+    pub searches: i64,
+    pub unique_queries: i64,
+    pub zero_result_searches: i64,
+    /// Searches with at least one result opened.
+    pub clicked_searches: i64,
+    pub latency_p50_ms: Option<f64>,
+    pub latency_p95_ms: Option<f64>,
+}
+
+/// Searches for one normalized query.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct QueryStats {
+    pub query: String,
+    pub searches: i64,
+    pub users: i64,
+    pub avg_result_count: f64,
+    pub clicked_searches: i64,
+    /// Fraction of searches with at least one result opened.
+    pub click_through_rate: f64,
+    pub avg_latency_ms: f64,
+    #[serde(with = "time::serde::iso8601")]
+    pub last_searched_at: OffsetDateTime,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchAnalyticsReport {
+    #[serde(with = "time::serde::iso8601")]
+    pub since: OffsetDateTime,
+    #[serde(flatten)]
+    pub totals: SearchTotals,
+    /// Most searched first.
+    pub top_queries: Vec<QueryStats>,
+    /// Queries by how often they found nothing, most frequent first. Their
+    /// `searches` counts only the searches without results.
+    pub zero_result_queries: Vec<QueryStats>,
+}
+
+/// Searches in the window, each with whether any of its results was opened.
+const EVENTS_IN_WINDOW: &str = r#"
+    WITH events AS (
+        SELECT e.*,
+               EXISTS (SELECT 1 FROM search_clicks c WHERE c.search_event_id = e.id) AS clicked
+        FROM search_events e
+        WHERE e.created_at >= $1 AND ($2 OR NOT e.is_generated)
+    )
+"#;
+
+const QUERY_STATS_COLUMNS: &str = r#"
+    normalized_query AS query,
+    COUNT(*) AS searches,
+    COUNT(DISTINCT user_id) AS users,
+    AVG(result_count)::float8 AS avg_result_count,
+    COUNT(*) FILTER (WHERE clicked) AS clicked_searches,
+    (COUNT(*) FILTER (WHERE clicked))::float8 / COUNT(*) AS click_through_rate,
+    AVG(latency_ms)::float8 AS avg_latency_ms,
+    MAX(created_at) AS last_searched_at
+"#;
+
+pub struct SearchAnalyticsRepository {
+    pool: PgPool,
+}
+
+impl SearchAnalyticsRepository {
+    pub fn new(pool: &PgPool) -> Self {
+        Self { pool: pool.clone() }
+    }
+
+    /// Log a search and return the id clicks on its results can refer to.
+    pub async fn record(&self, event: &SearchEvent<'_>) -> Result<String, sqlx::Error> {
+        let id = ulid::Ulid::new().to_string();
+
+        sqlx::query(
+            r#"
+            INSERT INTO search_events
+                (id, user_id, query, normalized_query, search_mode, is_generated, result_count,
+                 latency_ms, impressions)
+            VALUES ($1, (SELECT id FROM users WHERE id = $2), $3, $4, $5, $6, $7, $8, $9)
+            "#,
+        )
+        .bind(&id)
+        .bind(event.user_id)
+        .bind(event.query)
+        .bind(normalize_query(event.query))
+        .bind(event.search_mode.as_str())
+        .bind(event.is_generated)
+        .bind(event.result_count)
+        .bind(event.latency_ms.min(i32::MAX as u64) as i32)
+        .bind((!event.impressions.is_empty()).then_some(Json(event.impressions)))
+        .execute(&self.pool)
+        .await?;
+
+        Ok(id)
+    }
+
+    pub async fn report(
+        &self,
+        query: &SearchAnalyticsQuery,
+    ) -> Result<SearchAnalyticsReport, sqlx::Error> {
+        let since = OffsetDateTime::now_utc() - Duration::days(query.days());
+
+        let totals = sqlx::query_as::<_, SearchTotals>(&format!(
+            r#"{EVENTS_IN_WINDOW}
+            SELECT COUNT(*) AS searches,
+                   COUNT(DISTINCT normalized_query) AS unique_queries,
+                   COUNT(*) FILTER (WHERE result_count = 0) AS zero_result_searches,
+                   COUNT(*) FILTER (WHERE clicked) AS clicked_searches,
+                   percentile_cont(0.5) WITHIN GROUP (ORDER BY latency_ms) AS latency_p50_ms,
+                   percentile_cont(0.95) WITHIN GROUP (ORDER BY latency_ms) AS latency_p95_ms
+            FROM events
+            "#
+        ))
+        .bind(since)
+        .bind(query.include_generated)
+        .fetch_one(&self.pool)
+        .await?;
+
+        let top_queries = sqlx::query_as::<_, QueryStats>(&format!(
+            r#"{EVENTS_IN_WINDOW}
+            SELECT {QUERY_STATS_COLUMNS}
+            FROM events
+            GROUP BY normalized_query
+            ORDER BY searches DESC, query
+            LIMIT $3
+            "#
+        ))
+        .bind(since)
+        .bind(query.include_generated)
+        .bind(query.limit())
+        .fetch_all(&self.pool)
+        .await?;
+
+        let zero_result_queries = sqlx::query_as::<_, QueryStats>(&format!(
+            r#"{EVENTS_IN_WINDOW}
+            SELECT {QUERY_STATS_COLUMNS}
+            FROM events
+            WHERE result_count = 0
+            GROUP BY normalized_query
+            ORDER BY searches DESC, query
+            LIMIT $3
+            "#
+        ))
+        .bind(since)
+        .bind(query.include_generated)
+        .bind(query.limit())
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(SearchAnalyticsReport {
+            since,
+            totals,
+            top_queries,
+            zero_result_queries,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_query_bounds_window_and_limit() {
+        let query = SearchAnalyticsQuery::default();
+        assert_eq!(query.days(), DEFAULT_REPORT_DAYS);
+        assert_eq!(query.limit(), DEFAULT_REPORT_LIMIT);
+        assert!(!query.include_generated);
+
+        let query = SearchAnalyticsQuery {
+            days: Some(10_000),
+            limit: Some(0),
+            include_generated: true,
+        };
+        assert_eq!(query.days(), MAX_REPORT_DAYS);
+        assert_eq!(query.limit(), 1);
+    }
+}
@@ -0,0 +1,138 @@
+//! Cached search responses.
+//!
+//! Responses are keyed by the normalized query, the filters, and a
+//! fingerprint of what the user is permitted to see (their email and group
+//! memberships), so permission-trimmed results are never served to a user
+//! with different access. Each entry records the generations of the sources
+//! it searched (see `shared::search_cache`); once the indexer bumps one of
+//! them the entry is treated as a miss and overwritten.
+
+use crate::models::SearchResponse;
+use redis::AsyncCommands;
+use redis::aio::MultiplexedConnection;
+use serde::{Deserialize, Serialize};
+use shared::search_cache::source_generations;
+use std::collections::BTreeSet;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use tracing::debug;
+
+/// Lowercases the query and collapses whitespace, so queries differing only
+/// in case or spacing share a cache entry.
+pub fn normalize_query(query: &str) -> String {
+    query
+        .split_whitespace()
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Identifies the documents a user may see: their email and the groups they
+/// belong to, independent of group order.
+pub fn permission_fingerprint(user_email: Option<&str>, user_groups: &[String]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    user_email.hash(&mut hasher);
+    user_groups
+        .iter()
+        .collect::<BTreeSet<_>>()
+        .hash(&mut hasher);
+    hasher.finish()
+}
+
+#[derive(Deserialize)]
+struct CachedSearch {
+    /// Source ids searched, with their generations before the search ran.
+    generations: Vec<(String, u64)>,
+    response: SearchResponse,
+}
+
+/// Serialized form of `CachedSearch`.
+#[derive(Serialize)]
+struct CachedSearchRef<'a> {
+    generations: &'a [(String, u64)],
+    response: &'a SearchResponse,
+}
+
+/// Generations of `source_ids`, to be stored with the response computed
+/// after this call.
+pub async fn snapshot_generations(
+    conn: &mut MultiplexedConnection,
+    source_ids: &[String],
+) -> redis::RedisResult<Vec<(String, u64)>> {
+    let generations = source_generations(conn, source_ids).await?;
+    Ok(source_ids.iter().cloned().zip(generations).collect())
+}
+
+/// The cached response under `key`, unless any source it searched has been
+/// invalidated since.
+pub async fn get(conn: &mut MultiplexedConnection, key: &str) -> Option<SearchResponse> {
+    let json: Option<String> = conn.get(key).await.ok()?;
+    let cached: CachedSearch = serde_json::from_str(&json?).ok()?;
+
+    let source_ids: Vec<String> = cached
+        .generations
+        .iter()
+        .map(|(id, _)| id.clone())
+        .collect();
+    let current = source_generations(conn, &source_ids).await.ok()?;
+    let unchanged = cached
+        .generations
+        .iter()
+        .zip(&current)
+        .all(|((_, cached), current)| cached == current);
+    if !unchanged {
+        debug!("Search cache entry {} is stale", key);
+        return None;
+    }
+
+    Some(cached.response)
+}
+
+pub async fn put(
+    conn: &mut MultiplexedConnection,
+    key: &str,
+    generations: &[(String, u64)],
+    response: &SearchResponse,
+    ttl_secs: u64,
+) {
+    let cached = CachedSearchRef {
+        generations,
+        response,
+    };
+    if let Ok(json) = serde_json::to_string(&cached) {
+        let _: Result<(), _> = conn.set_ex(key, json, ttl_secs).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_query_ignores_case_and_spacing() {
+        assert_eq!(
+            normalize_query("  Quarterly   REPORT\tdraft "),
+            "quarterly report draft"
+        );
+        assert_eq!(normalize_query(""), "");
+    }
+
+    #[test]
+    fn test_permission_fingerprint_ignores_group_order() {
+        let groups = vec!["eng@example.com".to_string(), "all@example.com".to_string()];
+        let reordered = vec!["all@example.com".to_string(), "eng@example.com".to_string()];
+
+        assert_eq!(
+            permission_fingerprint(Some("alice@example.com"), &groups),
+            permission_fingerprint(Some("alice@example.com"), &reordered)
+        );
+        assert_ne!(
+            permission_fingerprint(Some("alice@example.com"), &groups),
+            permission_fingerprint(Some("alice@example.com"), &groups[..1])
+        );
+        assert_ne!(
+            permission_fingerprint(Some("alice@example.com"), &groups),
+            permission_fingerprint(Some("bob@example.com"), &groups)
+        );
+    }
+}
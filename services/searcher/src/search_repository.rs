@@ -1,12 +1,17 @@
+use crate::models::{FacetDimension, FacetFilters};
+use crate::query_ast::TextQuery;
+use crate::query_language::analyzer_suffix;
+use crate::snippets::{self, Snippet};
 use pgvector::Vector;
 use serde_json::Value as JsonValue;
 use shared::{
     SourceType,
     db::error::DatabaseError,
-    db::repositories::document,
+    db::repositories::document::{self, DOCUMENT_UPDATED_AT},
+    db::row_level_security,
     models::{AttributeFilter, ChunkResult, DateFilter, Document, Facet, FacetValue},
 };
-use sqlx::{FromRow, PgPool, Row, postgres::PgRow};
+use sqlx::{Execute, Executor, FromRow, PgPool, Postgres, Row, postgres::PgRow};
 use std::collections::{HashMap, HashSet};
 use tracing::debug;
 
@@ -18,11 +23,20 @@ const CANDIDATE_PADDING: i64 = 200;
 /// the Tantivy index scan, avoiding full result-set materialisation.
 const FACET_CANDIDATE_LIMIT: i64 = 10_000;
 
+/// Values returned per facet.
+const FACET_VALUE_LIMIT: i64 = 20;
+
 /// Drop weak fulltext matches relative to the strongest recency-adjusted score.
 /// Keep this in SQL so `total_count` and pagination use the same row universe
 /// as displayed fulltext hits.
 const MIN_SCORE_RATIO: f32 = 0.15;
 
+/// Joins `sources s` to `documents d`, dropping deleted sources and sources
+/// in maintenance whose documents are hidden from search.
+const SEARCHABLE_SOURCE_JOIN: &str = "JOIN sources s ON s.id = d.source_id AND NOT s.is_deleted \
+     AND NOT EXISTS (SELECT 1 FROM source_maintenance m \
+     WHERE m.source_id = s.id AND m.search_visibility = 'hidden')";
+
 #[derive(FromRow)]
 pub struct SearchHit {
     #[sqlx(flatten)]
@@ -61,14 +75,55 @@ impl SearchHitWithTotalRow {
 
 pub struct SearchDocumentRepository {
     pool: PgPool,
+    row_level_security: bool,
 }
 
 impl SearchDocumentRepository {
     pub fn new(pool: &PgPool) -> Self {
-        Self { pool: pool.clone() }
+        Self {
+            pool: pool.clone(),
+            row_level_security: false,
+        }
     }
 
-    pub async fn build_query_text(&self, query: &str) -> Result<Option<String>, DatabaseError> {
+    /// Also have Postgres enforce the permissions of the searching user, by
+    /// running their queries as the document reader role.
+    pub fn with_row_level_security(mut self, enabled: bool) -> Self {
+        self.row_level_security = enabled;
+        self
+    }
+
+    /// Run a query reading documents for `user_email`, inside a row-level
+    /// security transaction when enabled.
+    async fn fetch_all_for_user<'q, E>(
+        &self,
+        query: E,
+        user_email: Option<&str>,
+        user_groups: &[String],
+    ) -> Result<Vec<PgRow>, DatabaseError>
+    where
+        E: 'q + Execute<'q, Postgres>,
+    {
+        match user_email {
+            Some(email) if self.row_level_security => {
+                let mut tx =
+                    row_level_security::begin_as_user(&self.pool, email, user_groups).await?;
+                let rows = tx.fetch_all(query).await?;
+                tx.commit().await?;
+                Ok(rows)
+            }
+            _ => Ok(self.pool.fetch_all(query).await?),
+        }
+    }
+
+    /// Build the Tantivy query string for `query`. When `language`, an ISO
+    /// 639-3 code, has its own analyzer, the query is also matched against
+    /// the fields stemmed for that language.
+    pub async fn build_query_text(
+        &self,
+        query: &str,
+        language: Option<&str>,
+    ) -> Result<Option<String>, DatabaseError> {
         if query.trim().is_empty() {
             return Ok(None);
         }
@@ -92,7 +147,11 @@ impl SearchDocumentRepository {
             .take(12)
             .collect();
 
-        Ok(Some(build_tantivy_query(&terms, query)))
+        Ok(Some(build_tantivy_query(
+            &terms,
+            query,
+            language.and_then(analyzer_suffix),
+        )))
     }
 
     pub async fn search(
@@ -102,13 +161,16 @@ impl SearchDocumentRepository {
         source_ids: &[String],
         content_types: Option<&[String]>,
         attribute_filters: Option<&HashMap<String, AttributeFilter>>,
+        filter_sql: Option<&str>,
         limit: i64,
         offset: i64,
         user_email: Option<&str>,
         user_groups: &[String],
         document_id: Option<&str>,
+        collection_id: Option<&str>,
         date_filter: Option<&DateFilter>,
         person_filters: Option<&[String]>,
+        facet_filters: Option<&FacetFilters>,
         recency_boost_weight: f32,
         recency_half_life_days: f32,
     ) -> Result<(Vec<SearchHit>, i64), DatabaseError> {
@@ -122,12 +184,15 @@ impl SearchDocumentRepository {
                     source_ids,
                     content_types,
                     attribute_filters,
+                    filter_sql,
                     limit,
                     offset,
                     user_email,
                     user_groups,
+                    collection_id,
                     date_filter,
                     person_filters,
+                    facet_filters,
                 )
                 .await;
         }
@@ -136,7 +201,10 @@ impl SearchDocumentRepository {
         let tantivy_query = if let Some(tq) = tantivy_query {
             tq
         } else {
-            owned_tantivy_query = self.build_query_text(query).await?.unwrap_or_default();
+            owned_tantivy_query = self
+                .build_query_text(query, None)
+                .await?
+                .unwrap_or_default();
             &owned_tantivy_query
         };
 
@@ -150,9 +218,11 @@ impl SearchDocumentRepository {
             source_ids,
             content_types,
             attribute_filters,
+            filter_sql,
             user_email,
             user_groups,
             date_filter,
+            collection_id,
         );
 
         if document_id.is_some() {
@@ -174,6 +244,8 @@ impl SearchDocumentRepository {
             }
         }
 
+        filters.extend(facet_filter_conditions(facet_filters, None, facet_expr));
+
         let filter_where = if filters.is_empty() {
             String::new()
         } else {
@@ -204,7 +276,7 @@ impl SearchDocumentRepository {
             WITH filtered_candidates AS MATERIALIZED (
                 SELECT d.id, d.source_id, pdb.score(d.id) as bm25_score
                 FROM documents d
-                JOIN sources s ON s.id = d.source_id AND NOT s.is_deleted
+                {SEARCHABLE_SOURCE_JOIN}
                 WHERE d.id @@@ pdb.parse($1, lenient => true){filter_where}
                 ORDER BY bm25_score DESC
                 LIMIT ${candidate_limit_idx}
@@ -243,7 +315,7 @@ impl SearchDocumentRepository {
                            ) AS dedupe_rank
                     FROM relevant_candidates rc
                     JOIN documents d ON d.id = rc.id
-                    JOIN sources s ON s.id = d.source_id AND NOT s.is_deleted
+                    {SEARCHABLE_SOURCE_JOIN}
                 ) ranked_candidates
                 WHERE dedupe_rank = 1
             ),
@@ -265,7 +337,7 @@ impl SearchDocumentRepository {
                        s.source_type::text as source_type
                 FROM ranked r
                 JOIN documents d ON d.id = r.id
-                JOIN sources s ON s.id = d.source_id AND NOT s.is_deleted
+                {SEARCHABLE_SOURCE_JOIN}
             )
             SELECT h.id, h.score,
                    h.source_id, h.external_id, h.title, h.content_id, h.content_type,
@@ -309,7 +381,12 @@ impl SearchDocumentRepository {
             .bind(recency_half_life_days as f64)
             .bind(MIN_SCORE_RATIO);
 
-        let rows = query_builder.fetch_all(&self.pool).await?;
+        let rows = self
+            .fetch_all_for_user(query_builder, user_email, user_groups)
+            .await?
+            .iter()
+            .map(SearchHitWithTotalRow::from_row)
+            .collect::<Result<Vec<_>, _>>()?;
         let total_count = rows.first().map_or(0, |row| row.total_count);
         let results = rows
             .into_iter()
@@ -324,12 +401,15 @@ impl SearchDocumentRepository {
         source_ids: &[String],
         content_types: Option<&[String]>,
         attribute_filters: Option<&HashMap<String, AttributeFilter>>,
+        filter_sql: Option<&str>,
         limit: i64,
         offset: i64,
         user_email: Option<&str>,
         user_groups: &[String],
+        collection_id: Option<&str>,
         date_filter: Option<&DateFilter>,
         person_filters: Option<&[String]>,
+        facet_filters: Option<&FacetFilters>,
     ) -> Result<(Vec<SearchHit>, i64), DatabaseError> {
         let mut param_idx = 1;
         let mut filters = Vec::new();
@@ -339,9 +419,11 @@ impl SearchDocumentRepository {
             source_ids,
             content_types,
             attribute_filters,
+            filter_sql,
             user_email,
             user_groups,
             date_filter,
+            collection_id,
         );
 
         // Apply person filters (from `by:Name` operators) here too — without
@@ -365,6 +447,8 @@ impl SearchDocumentRepository {
             }
         }
 
+        filters.extend(facet_filter_conditions(facet_filters, None, facet_expr));
+
         let filter_where = if filters.is_empty() {
             String::new()
         } else {
@@ -376,7 +460,7 @@ impl SearchDocumentRepository {
             WITH filtered_scope AS MATERIALIZED (
                 SELECT d.id, d.source_id
                 FROM documents d
-                JOIN sources s ON s.id = d.source_id AND NOT s.is_deleted
+                {SEARCHABLE_SOURCE_JOIN}
                 {filter_where}
             ),
             -- Dedupe by (source_type, external_id), not source_id. Connectors may emit
@@ -398,7 +482,7 @@ impl SearchDocumentRepository {
                            ) AS dedupe_rank
                     FROM filtered_scope fs
                     JOIN documents d ON d.id = fs.id
-                    JOIN sources s ON s.id = d.source_id AND NOT s.is_deleted
+                    {SEARCHABLE_SOURCE_JOIN}
                 ) ranked_scope
                 WHERE dedupe_rank = 1
             ),
@@ -424,7 +508,7 @@ impl SearchDocumentRepository {
                        s.source_type::text as source_type
                 FROM ranked r
                 JOIN documents d ON d.id = r.id
-                JOIN sources s ON s.id = d.source_id AND NOT s.is_deleted
+                {SEARCHABLE_SOURCE_JOIN}
             )
             SELECT h.id, h.score, h.source_id, h.external_id, h.title, h.content_id, h.content_type,
                    h.file_size, h.file_extension, h.url,
@@ -452,7 +536,12 @@ impl SearchDocumentRepository {
 
         query_builder = query_builder.bind(limit).bind(offset);
 
-        let rows = query_builder.fetch_all(&self.pool).await?;
+        let rows = self
+            .fetch_all_for_user(query_builder, user_email, user_groups)
+            .await?
+            .iter()
+            .map(SearchHitWithTotalRow::from_row)
+            .collect::<Result<Vec<_>, _>>()?;
         let total_count = rows.first().map_or(0, |row| row.total_count);
         let results = rows
             .into_iter()
@@ -461,38 +550,34 @@ impl SearchDocumentRepository {
         Ok((results, total_count))
     }
 
-    pub async fn fetch_highlights(
+    /// Fulltext snippets of documents, by document id.
+    pub async fn fetch_snippets(
         &self,
         document_ids: &[String],
         query: &str,
-    ) -> Result<HashMap<String, Vec<String>>, DatabaseError> {
+    ) -> Result<HashMap<String, Vec<Snippet>>, DatabaseError> {
         if document_ids.is_empty() || query.trim().is_empty() {
             return Ok(HashMap::new());
         }
 
-        let rows: Vec<(String, Option<Vec<String>>)> = sqlx::query_as(
+        let rows: Vec<(String, Option<String>)> = sqlx::query_as(
             r#"
             SELECT id,
-                   ARRAY[ts_headline('english', content,
-                       plainto_tsquery('english', $2),
-                       'StartSel=**, StopSel=**, MaxFragments=3, MaxWords=30, MinWords=10'
-                   )] as content_snippets
+                   ts_headline('english', content, plainto_tsquery('english', $2), $3) as headline
             FROM documents
             WHERE id = ANY($1)
             "#,
         )
         .bind(document_ids)
         .bind(query)
+        .bind(snippets::HEADLINE_OPTIONS)
         .fetch_all(&self.pool)
         .await?;
 
         Ok(rows
             .into_iter()
-            .filter_map(|(id, snippets)| {
-                let snippets = snippets?
-                    .into_iter()
-                    .filter(|snippet| !snippet.is_empty())
-                    .collect::<Vec<String>>();
+            .filter_map(|(id, headline)| {
+                let snippets = snippets::parse_headline(&headline?);
                 if snippets.is_empty() {
                     None
                 } else {
@@ -507,13 +592,18 @@ impl SearchDocumentRepository {
         embedding: Vec<f32>,
         source_types: Option<&[SourceType]>,
         content_types: Option<&[String]>,
+        filter_sql: Option<&str>,
         limit: i64,
         offset: i64,
         user_email: Option<&str>,
         user_groups: &[String],
         document_id: Option<&str>,
+        candidate_document_ids: Option<&[String]>,
+        collection_id: Option<&str>,
+        facet_filters: Option<&FacetFilters>,
         recency_boost_weight: f32,
         recency_half_life_days: f32,
+        embedding_namespace: Option<&str>,
     ) -> Result<Vec<ChunkResult>, DatabaseError> {
         let dims = embedding.len() as i16;
         let vector = Vector::from(embedding);
@@ -525,7 +615,8 @@ impl SearchDocumentRepository {
 
         // Fixed bind slots: $1=vector, $2=limit, $3=offset, $4=dims,
         // $5=recency_boost_weight, $6=recency_half_life_days.
-        // Dynamic filters (document_id, source_types, content_types) start at $7.
+        // Dynamic filters (embedding_namespace, document_id,
+        // candidate_document_ids, source_types, content_types) start at $7.
         let mut bind_index = 7;
 
         // Filter by the current active embedding model via subquery
@@ -534,11 +625,25 @@ impl SearchDocumentRepository {
                 .to_string(),
         );
 
+        // Production embeddings, or the shadow rows of a chunking experiment
+        if embedding_namespace.is_some() {
+            where_conditions.push(format!("e.namespace = ${}", bind_index));
+            bind_index += 1;
+        } else {
+            where_conditions.push("e.namespace IS NULL".to_string());
+        }
+
         if document_id.is_some() {
             where_conditions.push(format!("e.document_id = ${}", bind_index));
             bind_index += 1;
         }
 
+        // Documents preselected by the summary-embedding stage
+        if candidate_document_ids.is_some() {
+            where_conditions.push(format!("e.document_id = ANY(${})", bind_index));
+            bind_index += 1;
+        }
+
         if let Some(src) = source_types {
             if !src.is_empty() {
                 where_conditions.push(format!(
@@ -555,6 +660,16 @@ impl SearchDocumentRepository {
             }
         }
 
+        if let Some(filter_sql) = filter_sql {
+            where_conditions.push(filter_sql.to_string());
+        }
+
+        if let Some(collection_id) = collection_id {
+            where_conditions.push(collection_filter(collection_id));
+        }
+
+        where_conditions.extend(facet_filter_conditions(facet_filters, None, facet_expr));
+
         if let Some(email) = user_email {
             where_conditions.push(generate_permission_filter(email, user_groups));
         }
@@ -583,13 +698,15 @@ impl SearchDocumentRepository {
                     e.chunk_start_offset,
                     e.chunk_end_offset,
                     e.chunk_index,
+                    e.page_number,
+                    e.heading_path,
                     d.external_id,
                     d.updated_at as doc_updated_at,
                     d.metadata as doc_metadata,
                     s.source_type
                 FROM embeddings e
                 JOIN documents d ON e.document_id = d.id
-                JOIN sources s ON s.id = d.source_id AND NOT s.is_deleted
+                {SEARCHABLE_SOURCE_JOIN}
                 {where_clause}
                 ORDER BY e.embedding <=> $1
                 LIMIT ($2 + $3) * 3
@@ -601,13 +718,15 @@ impl SearchDocumentRepository {
                     c.chunk_start_offset,
                     c.chunk_end_offset,
                     c.chunk_index,
+                    c.page_number,
+                    c.heading_path,
                     c.external_id,
                     c.doc_updated_at,
                     c.source_type
                 FROM candidates c
             ),
             deduped_candidates AS (
-                SELECT document_id, distance, chunk_start_offset, chunk_end_offset, chunk_index
+                SELECT document_id, distance, chunk_start_offset, chunk_end_offset, chunk_index, page_number, heading_path
                 FROM (
                     SELECT sc.*,
                            ROW_NUMBER() OVER (
@@ -623,7 +742,9 @@ impl SearchDocumentRepository {
                 dc.distance,
                 dc.chunk_start_offset,
                 dc.chunk_end_offset,
-                dc.chunk_index
+                dc.chunk_index,
+                dc.page_number,
+                dc.heading_path
             FROM deduped_candidates dc
             ORDER BY distance
             LIMIT $2 OFFSET $3
@@ -640,10 +761,18 @@ impl SearchDocumentRepository {
             .bind(recency_boost_weight as f64)
             .bind(recency_half_life_days as f64);
 
+        if let Some(namespace) = embedding_namespace {
+            query = query.bind(namespace);
+        }
+
         if let Some(doc_id) = document_id {
             query = query.bind(doc_id);
         }
 
+        if let Some(candidates) = candidate_document_ids {
+            query = query.bind(candidates);
+        }
+
         if let Some(src) = source_types {
             if !src.is_empty() {
                 query = query.bind(src);
@@ -656,7 +785,9 @@ impl SearchDocumentRepository {
             }
         }
 
-        let results = query.fetch_all(&self.pool).await?;
+        let results = self
+            .fetch_all_for_user(query, user_email, user_groups)
+            .await?;
         let chunk_results = results
             .into_iter()
             .map(|row| {
@@ -668,6 +799,8 @@ impl SearchDocumentRepository {
                     chunk_start_offset: row.get("chunk_start_offset"),
                     chunk_end_offset: row.get("chunk_end_offset"),
                     chunk_index: row.get("chunk_index"),
+                    page_number: row.get("page_number"),
+                    heading_path: row.get("heading_path"),
                 }
             })
             .collect();
@@ -675,6 +808,94 @@ impl SearchDocumentRepository {
         Ok(chunk_results)
     }
 
+    /// The documents whose summary embeddings are closest to `embedding`,
+    /// best first, for narrowing chunk search to likely documents. Filters
+    /// and permissions are left to the chunk search that follows.
+    pub async fn find_coarse_candidates(
+        &self,
+        embedding: &[f32],
+        limit: i64,
+    ) -> Result<Vec<String>, DatabaseError> {
+        let vector = Vector::from(embedding.to_vec());
+
+        let document_ids = sqlx::query_scalar::<_, String>(
+            r#"
+            SELECT document_id
+            FROM document_summary_embeddings
+            WHERE dimensions = $3
+              AND model_name = (SELECT config->>'model' FROM embedding_providers WHERE is_current = TRUE AND is_deleted = FALSE LIMIT 1)
+            ORDER BY embedding <=> $1
+            LIMIT $2
+            "#,
+        )
+        .bind(&vector)
+        .bind(limit)
+        .bind(embedding.len() as i16)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(document_ids)
+    }
+
+    /// The chunks of one document closest to `embedding`, best first. Unlike
+    /// `find_similar_with_filters`, which keeps one chunk per document, every
+    /// matching chunk is returned.
+    pub async fn find_similar_chunks_in_document(
+        &self,
+        document_id: &str,
+        embedding: Vec<f32>,
+        limit: i64,
+        embedding_namespace: Option<&str>,
+    ) -> Result<Vec<ChunkResult>, DatabaseError> {
+        let dims = embedding.len() as i16;
+        let vector = Vector::from(embedding);
+
+        let rows = sqlx::query(
+            r#"
+            SELECT e.document_id,
+                   e.embedding <=> $1 AS distance,
+                   e.chunk_start_offset,
+                   e.chunk_end_offset,
+                   e.chunk_index,
+                   e.page_number,
+                   e.heading_path
+            FROM embeddings e
+            WHERE e.document_id = $2
+              AND e.dimensions = $3
+              AND e.model_name = (SELECT config->>'model' FROM embedding_providers WHERE is_current = TRUE AND is_deleted = FALSE LIMIT 1)
+              AND e.namespace IS NOT DISTINCT FROM $5
+            ORDER BY e.embedding <=> $1
+            LIMIT $4
+            "#,
+        )
+        .bind(&vector)
+        .bind(document_id)
+        .bind(dims)
+        .bind(limit)
+        .bind(embedding_namespace)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                let distance: Option<f64> = row.get("distance");
+                ChunkResult {
+                    document_id: row.get("document_id"),
+                    similarity_score: (1.0 - distance.unwrap_or(1.0)) as f32,
+                    chunk_start_offset: row.get("chunk_start_offset"),
+                    chunk_end_offset: row.get("chunk_end_offset"),
+                    chunk_index: row.get("chunk_index"),
+                    page_number: row.get("page_number"),
+                    heading_path: row.get("heading_path"),
+                }
+            })
+            .collect())
+    }
+
+    /// Counts per value for each of `dimensions`, over the documents matching
+    /// the query and filters. Facet filters narrow every facet except their
+    /// own dimension, so other values of a selected facet keep their counts.
     pub async fn get_facet_counts(
         &self,
         query: &str,
@@ -682,60 +903,22 @@ impl SearchDocumentRepository {
         source_ids: &[String],
         content_types: Option<&[String]>,
         attribute_filters: Option<&HashMap<String, AttributeFilter>>,
+        filter_sql: Option<&str>,
         user_email: Option<&str>,
         user_groups: &[String],
+        collection_id: Option<&str>,
         date_filter: Option<&DateFilter>,
         person_filters: Option<&[String]>,
+        dimensions: &[FacetDimension],
+        facet_filters: Option<&FacetFilters>,
     ) -> Result<Vec<Facet>, DatabaseError> {
-        if source_ids.is_empty() {
+        if source_ids.is_empty() || dimensions.is_empty() {
             return Ok(vec![]);
         }
 
-        if query.trim().is_empty() {
-            // No BM25 scoring possible — count all docs matching filters
-            let mut param_idx = 1;
-            let mut filters = Vec::new();
-            build_common_filters(
-                &mut filters,
-                &mut param_idx,
-                source_ids,
-                content_types,
-                attribute_filters,
-                user_email,
-                user_groups,
-                date_filter,
-            );
-            let where_clause = if filters.is_empty() {
-                String::new()
-            } else {
-                format!("WHERE {}", filters.join(" AND "))
-            };
-            let query_str = format!(
-                r#"
-                SELECT 'source_type' as facet, s.source_type as value, count(*) as count
-                FROM documents d
-                JOIN sources s ON d.source_id = s.id
-                {where_clause}
-                GROUP BY s.source_type
-                ORDER BY count DESC
-                "#,
-            );
-            let mut qb = sqlx::query_as::<_, (String, String, i64)>(&query_str).bind(source_ids);
-            if let Some(ct) = content_types {
-                if !ct.is_empty() {
-                    qb = qb.bind(ct);
-                }
-            }
-            let rows = qb.fetch_all(&self.pool).await?;
-            return Ok(rows_to_facets(rows));
-        }
-
-        let tantivy_query = tantivy_query.ok_or_else(|| {
-            DatabaseError::InvalidInput("tantivy query is required for facet counts".to_string())
-        })?;
-
-        // Bind params: $1 = tantivy query string, then filters
-        let mut param_idx = 2;
+        let has_query = !query.trim().is_empty();
+        // Bind params: $1 = tantivy query string when there is one, then filters
+        let mut param_idx = if has_query { 2 } else { 1 };
 
         let mut filters = Vec::new();
         build_common_filters(
@@ -744,54 +927,114 @@ impl SearchDocumentRepository {
             source_ids,
             content_types,
             attribute_filters,
+            filter_sql,
             user_email,
             user_groups,
             date_filter,
+            collection_id,
         );
 
-        if let Some(persons) = person_filters {
-            let conditions: Vec<String> = persons
-                .iter()
-                .map(|p| {
-                    let escaped = p.replace('\'', "''");
-                    format!("metadata ||| 'author:{escaped}'")
-                })
-                .collect();
-            if !conditions.is_empty() {
-                filters.push(format!("({})", conditions.join(" OR ")));
+        let candidates = if has_query {
+            if let Some(persons) = person_filters {
+                let conditions: Vec<String> = persons
+                    .iter()
+                    .map(|p| {
+                        let escaped = p.replace('\'', "''");
+                        format!("d.metadata ||| 'author:{escaped}'")
+                    })
+                    .collect();
+                if !conditions.is_empty() {
+                    filters.push(format!("({})", conditions.join(" OR ")));
+                }
             }
-        }
 
-        let filter_where = if filters.is_empty() {
-            String::new()
+            let filter_where = if filters.is_empty() {
+                String::new()
+            } else {
+                format!(" AND {}", filters.join(" AND "))
+            };
+            format!(
+                r#"
+                SELECT d.id, pdb.score(d.id) as score
+                FROM documents d
+                WHERE d.id @@@ pdb.parse($1, lenient => true){filter_where}
+                ORDER BY score DESC
+                LIMIT ${param_idx}
+                "#
+            )
         } else {
-            format!(" AND {}", filters.join(" AND "))
+            // No BM25 scoring possible — count all docs matching filters
+            let filter_where = if filters.is_empty() {
+                String::new()
+            } else {
+                format!("WHERE {}", filters.join(" AND "))
+            };
+            format!(
+                r#"
+                SELECT d.id
+                FROM documents d
+                {filter_where}
+                "#
+            )
         };
 
-        let facet_limit_idx = param_idx;
+        let facet_columns: Vec<String> = ALL_FACET_DIMENSIONS
+            .iter()
+            .map(|dimension| format!("{} AS {}", facet_expr(*dimension), dimension.as_str()))
+            .collect();
+        let facet_queries: Vec<String> = dimensions
+            .iter()
+            .map(|dimension| {
+                let column = dimension.as_str();
+                let mut conditions = vec![format!("{column} IS NOT NULL")];
+                conditions.extend(facet_filter_conditions(
+                    facet_filters,
+                    Some(*dimension),
+                    |other| other.as_str().to_string(),
+                ));
+                // Time buckets read best in order; other facets by popularity
+                let order_by = match dimension {
+                    FacetDimension::Week | FacetDimension::Month => "value DESC",
+                    _ => "count DESC, value",
+                };
+                format!(
+                    r#"
+                    (SELECT '{column}' as facet, {column} as value, count(*) as count
+                    FROM facet_rows
+                    WHERE {conditions}
+                    GROUP BY {column}
+                    ORDER BY {order_by}
+                    LIMIT {FACET_VALUE_LIMIT})
+                    "#,
+                    conditions = conditions.join(" AND "),
+                )
+            })
+            .collect();
 
         let query_str = format!(
             r#"
-            WITH candidates AS (
-                SELECT id, pdb.score(id) as score
-                FROM documents
-                WHERE id @@@ pdb.parse($1, lenient => true){filter_where}
-                ORDER BY score DESC
-                LIMIT ${facet_limit_idx}
+            WITH candidates AS ({candidates}),
+            facet_rows AS MATERIALIZED (
+                SELECT {facet_columns}
+                FROM candidates c
+                JOIN documents d ON d.id = c.id
+                {SEARCHABLE_SOURCE_JOIN}
             )
-            SELECT 'source_type' as facet, s.source_type as value, count(*) as count
-            FROM candidates c
-            JOIN documents d ON d.id = c.id
-            JOIN sources s ON d.source_id = s.id
-            GROUP BY s.source_type
-            ORDER BY count DESC
+            {facet_queries}
             "#,
-            filter_where = filter_where,
-            facet_limit_idx = facet_limit_idx,
+            facet_columns = facet_columns.join(", "),
+            facet_queries = facet_queries.join(" UNION ALL "),
         );
 
-        let mut query_builder =
-            sqlx::query_as::<_, (String, String, i64)>(&query_str).bind(tantivy_query);
+        let mut query_builder = sqlx::query_as::<_, (String, String, i64)>(&query_str);
+        if has_query {
+            let tantivy_query = tantivy_query.ok_or_else(|| {
+                DatabaseError::InvalidInput(
+                    "tantivy query is required for facet counts".to_string(),
+                )
+            })?;
+            query_builder = query_builder.bind(tantivy_query);
+        }
 
         query_builder = query_builder.bind(source_ids);
 
@@ -801,10 +1044,17 @@ impl SearchDocumentRepository {
             }
         }
 
-        query_builder = query_builder.bind(FACET_CANDIDATE_LIMIT);
+        if has_query {
+            query_builder = query_builder.bind(FACET_CANDIDATE_LIMIT);
+        }
 
-        let facet_rows = query_builder.fetch_all(&self.pool).await?;
-        Ok(rows_to_facets(facet_rows))
+        let facet_rows = self
+            .fetch_all_for_user(query_builder, user_email, user_groups)
+            .await?
+            .iter()
+            .map(<(String, String, i64)>::from_row)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows_to_facets(facet_rows, dimensions))
     }
 
     pub async fn get_distinct_attribute_values(
@@ -846,7 +1096,7 @@ impl SearchDocumentRepository {
     }
 }
 
-fn rows_to_facets(rows: Vec<(String, String, i64)>) -> Vec<Facet> {
+fn rows_to_facets(rows: Vec<(String, String, i64)>, dimensions: &[FacetDimension]) -> Vec<Facet> {
     let mut facets_map: HashMap<String, Vec<FacetValue>> = HashMap::new();
     for (facet_name, value, count) in rows {
         facets_map.entry(facet_name).or_default().push(FacetValue {
@@ -854,9 +1104,63 @@ fn rows_to_facets(rows: Vec<(String, String, i64)>) -> Vec<Facet> {
             count: Some(count),
         });
     }
-    facets_map
+    dimensions
+        .iter()
+        .filter_map(|dimension| {
+            let name = dimension.as_str().to_string();
+            let values = facets_map.remove(&name)?;
+            Some(Facet { name, values })
+        })
+        .collect()
+}
+
+const ALL_FACET_DIMENSIONS: [FacetDimension; 5] = [
+    FacetDimension::SourceType,
+    FacetDimension::Author,
+    FacetDimension::ContentType,
+    FacetDimension::Week,
+    FacetDimension::Month,
+];
+
+/// Expression over `documents d` and `sources s` that yields a document's
+/// value for a facet dimension.
+fn facet_expr(dimension: FacetDimension) -> String {
+    match dimension {
+        FacetDimension::SourceType => "s.source_type::text".to_string(),
+        FacetDimension::Author => "NULLIF(d.metadata->>'author', '')".to_string(),
+        FacetDimension::ContentType => "d.content_type".to_string(),
+        FacetDimension::Week => format!(
+            "to_char(date_trunc('week', {DOCUMENT_UPDATED_AT} AT TIME ZONE 'UTC'), 'YYYY-MM-DD')"
+        ),
+        FacetDimension::Month => format!(
+            "to_char(date_trunc('month', {DOCUMENT_UPDATED_AT} AT TIME ZONE 'UTC'), 'YYYY-MM')"
+        ),
+    }
+}
+
+/// One condition per selected facet dimension (other than `exclude`),
+/// matching any of its selected values. `expr` maps a dimension to the SQL
+/// expression holding its value.
+fn facet_filter_conditions(
+    facet_filters: Option<&FacetFilters>,
+    exclude: Option<FacetDimension>,
+    expr: impl Fn(FacetDimension) -> String,
+) -> Vec<String> {
+    facet_filters
         .into_iter()
-        .map(|(name, values)| Facet { name, values })
+        .flatten()
+        .filter(|(dimension, values)| Some(**dimension) != exclude && !values.is_empty())
+        .map(|(dimension, values)| {
+            let values: Vec<String> = values
+                .iter()
+                .map(|v| format!("'{}'", v.replace('\'', "''")))
+                .collect();
+            format!(
+                "{} = ANY(ARRAY[{}]::text[])",
+                expr(*dimension),
+                values.join(", ")
+            )
+        })
         .collect()
 }
 
@@ -865,7 +1169,13 @@ fn generate_permission_filter(user_email: &str, user_groups: &[String]) -> Strin
 }
 
 // TODO: use tantivy crate for query string validation
-fn build_tantivy_query(terms: &[String], original_query: &str) -> String {
+/// `language_suffix` names the per-language stemmed aliases (`title_de`,
+/// `content_de`, ...) to search besides the unstemmed and English fields.
+fn build_tantivy_query(
+    terms: &[String],
+    original_query: &str,
+    language_suffix: Option<&str>,
+) -> String {
     let mut clauses = Vec::new();
 
     for term in terms {
@@ -875,6 +1185,10 @@ fn build_tantivy_query(terms: &[String], original_query: &str) -> String {
         clauses.push(format!("title_en:{escaped}^2"));
         clauses.push(format!("content:{escaped}"));
         clauses.push(format!("content_en:{escaped}"));
+        if let Some(suffix) = language_suffix {
+            clauses.push(format!("title_{suffix}:{escaped}^2"));
+            clauses.push(format!("content_{suffix}:{escaped}"));
+        }
     }
 
     // Phrase matching on the original query with slop and boost
@@ -883,10 +1197,78 @@ fn build_tantivy_query(terms: &[String], original_query: &str) -> String {
     clauses.push(format!("title_en:\"{escaped_phrase}\"~2^10"));
     clauses.push(format!("content:\"{escaped_phrase}\"~2^5"));
     clauses.push(format!("content_en:\"{escaped_phrase}\"~2^5"));
+    if let Some(suffix) = language_suffix {
+        clauses.push(format!("title_{suffix}:\"{escaped_phrase}\"~2^10"));
+        clauses.push(format!("content_{suffix}:\"{escaped_phrase}\"~2^5"));
+    }
 
     clauses.join(" ")
 }
 
+/// Build the Tantivy query string of a structured query's words. Each word
+/// or phrase matches any of the fields free-text terms do; conjunctions
+/// require their positive parts and exclude their negated ones.
+pub fn build_structured_query_text(text: &TextQuery, language: Option<&str>) -> String {
+    render_text_query(text, language.and_then(analyzer_suffix))
+}
+
+fn render_text_query(text: &TextQuery, language_suffix: Option<&str>) -> String {
+    match text {
+        TextQuery::All(queries) => {
+            let clauses: Vec<String> = queries
+                .iter()
+                .map(|query| match query {
+                    TextQuery::Not(negated) => {
+                        format!("-{}", render_text_query(negated, language_suffix))
+                    }
+                    query => format!("+{}", render_text_query(query, language_suffix)),
+                })
+                .collect();
+            format!("({})", clauses.join(" "))
+        }
+        TextQuery::Any(queries) => {
+            let clauses: Vec<String> = queries
+                .iter()
+                .map(|query| render_text_query(query, language_suffix))
+                .collect();
+            format!("({})", clauses.join(" "))
+        }
+        // Compiled queries only negate within a conjunction
+        TextQuery::Not(negated) => format!("(-{})", render_text_query(negated, language_suffix)),
+        TextQuery::Term(word) if word.chars().all(char::is_alphanumeric) => {
+            let escaped = escape_tantivy_term(word);
+            let mut clauses = vec![
+                format!("title:{escaped}^2"),
+                format!("title_secondary:{escaped}^2"),
+                format!("title_en:{escaped}^2"),
+                format!("content:{escaped}"),
+                format!("content_en:{escaped}"),
+            ];
+            if let Some(suffix) = language_suffix {
+                clauses.push(format!("title_{suffix}:{escaped}^2"));
+                clauses.push(format!("content_{suffix}:{escaped}"));
+            }
+            format!("({})", clauses.join(" "))
+        }
+        // Words with punctuation split into several tokens, so they match as
+        // phrases
+        TextQuery::Term(phrase) | TextQuery::Phrase(phrase) => {
+            let escaped = phrase.replace('\\', "\\\\").replace('"', "\\\"");
+            let mut clauses = vec![
+                format!("title:\"{escaped}\"^2"),
+                format!("title_en:\"{escaped}\"^2"),
+                format!("content:\"{escaped}\""),
+                format!("content_en:\"{escaped}\""),
+            ];
+            if let Some(suffix) = language_suffix {
+                clauses.push(format!("title_{suffix}:\"{escaped}\"^2"));
+                clauses.push(format!("content_{suffix}:\"{escaped}\""));
+            }
+            format!("({})", clauses.join(" "))
+        }
+    }
+}
+
 fn escape_tantivy_term(term: &str) -> String {
     let mut escaped = String::with_capacity(term.len());
     for ch in term.chars() {
@@ -931,9 +1313,11 @@ fn build_common_filters(
     source_ids: &[String],
     content_types: Option<&[String]>,
     attribute_filters: Option<&HashMap<String, AttributeFilter>>,
+    filter_sql: Option<&str>,
     user_email: Option<&str>,
     user_groups: &[String],
     date_filter: Option<&DateFilter>,
+    collection_id: Option<&str>,
 ) {
     if !source_ids.is_empty() {
         filters.push(format!("source_id = ANY(${})", param_idx));
@@ -995,6 +1379,10 @@ fn build_common_filters(
         }
     }
 
+    if let Some(filter_sql) = filter_sql {
+        filters.push(filter_sql.to_string());
+    }
+
     if let Some(df) = date_filter {
         if let Some(after) = &df.after {
             let iso = after
@@ -1016,7 +1404,20 @@ fn build_common_filters(
         }
     }
 
+    if let Some(collection_id) = collection_id {
+        filters.push(collection_filter(collection_id));
+    }
+
     if let Some(email) = user_email {
         filters.push(generate_permission_filter(email, user_groups));
     }
 }
+
+/// Restrict to documents that belong to a collection. Visibility of the
+/// collection itself is checked before searching.
+fn collection_filter(collection_id: &str) -> String {
+    format!(
+        "d.id IN (SELECT cd.document_id FROM collection_documents cd WHERE cd.collection_id = '{}')",
+        collection_id.replace('\'', "''")
+    )
+}
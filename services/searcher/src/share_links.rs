@@ -0,0 +1,173 @@
+//! Public share links for single documents.
+//!
+//! A user who can read a document may create a link to it for someone
+//! without Omni access. Links expire, may be limited to a number of
+//! fetches, and can be revoked by their creator or an admin. The admin
+//! policy decides whether sharing is allowed at all and bounds each link's
+//! lifetime and access limit. Fetching through a link is rate limited per
+//! link, and stops working once the creator can no longer read the document.
+
+use crate::models::FieldError;
+use redis::Client as RedisClient;
+use shared::db::repositories::ShareLinkPolicy;
+use time::{Duration, OffsetDateTime};
+use tracing::warn;
+
+pub fn validate_share_link_policy(policy: &ShareLinkPolicy) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+    if policy.default_ttl_hours <= 0 {
+        errors.push(FieldError::new(
+            "default_ttl_hours",
+            "must be greater than 0",
+        ));
+    }
+    if policy.max_ttl_hours < policy.default_ttl_hours {
+        errors.push(FieldError::new(
+            "max_ttl_hours",
+            "must be at least default_ttl_hours",
+        ));
+    }
+    if policy.max_accesses.is_some_and(|max| max <= 0) {
+        errors.push(FieldError::new("max_accesses", "must be greater than 0"));
+    }
+    if policy.rate_limit_per_minute <= 0 {
+        errors.push(FieldError::new(
+            "rate_limit_per_minute",
+            "must be greater than 0",
+        ));
+    }
+    errors
+}
+
+/// Expiry and access limit of a new link, from what the creator asked for
+/// and the policy's defaults and bounds.
+pub fn resolve_link_limits(
+    policy: &ShareLinkPolicy,
+    now: OffsetDateTime,
+    ttl_hours: Option<i32>,
+    max_accesses: Option<i32>,
+) -> Result<(OffsetDateTime, Option<i32>), Vec<FieldError>> {
+    let mut errors = Vec::new();
+
+    let ttl_hours = ttl_hours.unwrap_or(policy.default_ttl_hours);
+    if ttl_hours <= 0 || ttl_hours > policy.max_ttl_hours {
+        errors.push(FieldError::new(
+            "ttl_hours",
+            format!("must be between 1 and {}", policy.max_ttl_hours),
+        ));
+    }
+
+    let max_accesses = max_accesses.or(policy.max_accesses);
+    match (max_accesses, policy.max_accesses) {
+        (Some(requested), _) if requested <= 0 => {
+            errors.push(FieldError::new("max_accesses", "must be greater than 0"));
+        }
+        (Some(requested), Some(limit)) if requested > limit => {
+            errors.push(FieldError::new(
+                "max_accesses",
+                format!("must be at most {}", limit),
+            ));
+        }
+        _ => {}
+    }
+
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    Ok((now + Duration::hours(ttl_hours as i64), max_accesses))
+}
+
+/// Count a fetch through a link against its per-minute limit. Returns false
+/// once the limit is used up. Redis failures let the fetch through: the
+/// link's expiry and access limit still apply.
+pub async fn allow_fetch(redis_client: &RedisClient, link_id: &str, per_minute: i32) -> bool {
+    let window = OffsetDateTime::now_utc().unix_timestamp() / 60;
+    let key = format!("share_link_rate:{}:{}", link_id, window);
+
+    let result = async {
+        let mut conn = redis_client.get_multiplexed_async_connection().await?;
+        let (count,): (i64,) = redis::pipe()
+            .atomic()
+            .incr(&key, 1)
+            .expire(&key, 60)
+            .ignore()
+            .query_async(&mut conn)
+            .await?;
+        Ok::<_, redis::RedisError>(count)
+    }
+    .await;
+
+    match result {
+        Ok(count) => count <= per_minute as i64,
+        Err(e) => {
+            warn!("Failed to rate limit share link {}: {}", link_id, e);
+            true
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(max_accesses: Option<i32>) -> ShareLinkPolicy {
+        ShareLinkPolicy {
+            enabled: true,
+            default_ttl_hours: 24,
+            max_ttl_hours: 168,
+            max_accesses,
+            rate_limit_per_minute: 30,
+        }
+    }
+
+    #[test]
+    fn test_resolve_link_limits_applies_policy_defaults() {
+        let now = OffsetDateTime::UNIX_EPOCH;
+
+        let (expires_at, max_accesses) =
+            resolve_link_limits(&policy(Some(100)), now, None, None).unwrap();
+
+        assert_eq!(expires_at, now + Duration::hours(24));
+        assert_eq!(max_accesses, Some(100));
+
+        let (expires_at, max_accesses) =
+            resolve_link_limits(&policy(None), now, Some(168), Some(5)).unwrap();
+
+        assert_eq!(expires_at, now + Duration::hours(168));
+        assert_eq!(max_accesses, Some(5));
+    }
+
+    #[test]
+    fn test_resolve_link_limits_rejects_values_outside_policy() {
+        let now = OffsetDateTime::UNIX_EPOCH;
+
+        let errors =
+            resolve_link_limits(&policy(Some(100)), now, Some(169), Some(101)).unwrap_err();
+
+        let fields: Vec<&str> = errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(fields, vec!["ttl_hours", "max_accesses"]);
+        assert!(resolve_link_limits(&policy(None), now, Some(0), None).is_err());
+        assert!(resolve_link_limits(&policy(None), now, None, Some(0)).is_err());
+    }
+
+    #[test]
+    fn test_validate_share_link_policy() {
+        assert!(validate_share_link_policy(&policy(None)).is_empty());
+
+        let invalid = ShareLinkPolicy {
+            enabled: true,
+            default_ttl_hours: 48,
+            max_ttl_hours: 24,
+            max_accesses: Some(0),
+            rate_limit_per_minute: 0,
+        };
+        let fields: Vec<String> = validate_share_link_policy(&invalid)
+            .into_iter()
+            .map(|e| e.field)
+            .collect();
+        assert_eq!(
+            fields,
+            vec!["max_ttl_hours", "max_accesses", "rate_limit_per_minute"]
+        );
+    }
+}
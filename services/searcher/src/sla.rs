@@ -0,0 +1,421 @@
+//! Latency SLAs per search mode, with automatic degraded modes.
+//!
+//! Each search mode has a target P95 latency. The monitor keeps a rolling
+//! window of completed searches per mode; when the P95 breaches the target it
+//! steps that mode down to a lighter configuration, one level at a time, and
+//! steps back up once the P95 is comfortably under target again. The window
+//! is reset on every switch so each decision is based only on searches run
+//! under the current configuration. Switches are logged and kept for the
+//! admin API.
+
+use serde::Serialize;
+use sqlx::types::time::OffsetDateTime;
+use std::collections::VecDeque;
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+use crate::admission::LatencyWindow;
+use crate::models::SearchMode;
+
+const DEFAULT_FULLTEXT_P95_TARGET_MS: u64 = 500;
+const DEFAULT_SEMANTIC_P95_TARGET_MS: u64 = 1500;
+const DEFAULT_HYBRID_P95_TARGET_MS: u64 = 2000;
+/// A degraded mode recovers one level once its P95 falls below this fraction
+/// of the target, so a mode hovering at the target does not flap.
+const DEFAULT_RECOVERY_RATIO: f64 = 0.7;
+/// Share of hybrid semantic candidates kept once semantic k is reduced.
+const REDUCED_SEMANTIC_K_DIVISOR: i64 = 4;
+const MAX_RECORDED_SWITCHES: usize = 100;
+
+const MODES: [SearchMode; 3] = [
+    SearchMode::Fulltext,
+    SearchMode::Semantic,
+    SearchMode::Hybrid,
+];
+
+/// How far a search mode has been lightened. Each level includes the
+/// degradations of the levels before it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DegradationLevel {
+    #[default]
+    Normal,
+    /// Skip personalized re-ranking of results.
+    SkipRerank,
+    /// Fetch fewer semantic candidates for hybrid fusion.
+    ReduceSemanticK,
+    /// Skip facet counts.
+    ShrinkFacets,
+}
+
+impl DegradationLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DegradationLevel::Normal => "normal",
+            DegradationLevel::SkipRerank => "skip_rerank",
+            DegradationLevel::ReduceSemanticK => "reduce_semantic_k",
+            DegradationLevel::ShrinkFacets => "shrink_facets",
+        }
+    }
+
+    pub fn is_degraded(&self) -> bool {
+        *self > DegradationLevel::Normal
+    }
+
+    pub fn skip_rerank(&self) -> bool {
+        *self >= DegradationLevel::SkipRerank
+    }
+
+    /// Semantic candidates to fetch for hybrid fusion, given the number the
+    /// request would normally fetch. Never fewer than one page of results.
+    pub fn semantic_k(&self, candidate_limit: i64, page_size: i64) -> i64 {
+        if *self >= DegradationLevel::ReduceSemanticK {
+            (candidate_limit / REDUCED_SEMANTIC_K_DIVISOR).max(page_size)
+        } else {
+            candidate_limit
+        }
+    }
+
+    pub fn include_facets(&self) -> bool {
+        *self < DegradationLevel::ShrinkFacets
+    }
+
+    fn heavier(&self) -> Self {
+        match self {
+            DegradationLevel::Normal => DegradationLevel::SkipRerank,
+            DegradationLevel::SkipRerank => DegradationLevel::ReduceSemanticK,
+            DegradationLevel::ReduceSemanticK | DegradationLevel::ShrinkFacets => {
+                DegradationLevel::ShrinkFacets
+            }
+        }
+    }
+
+    fn lighter(&self) -> Self {
+        match self {
+            DegradationLevel::Normal | DegradationLevel::SkipRerank => DegradationLevel::Normal,
+            DegradationLevel::ReduceSemanticK => DegradationLevel::SkipRerank,
+            DegradationLevel::ShrinkFacets => DegradationLevel::ReduceSemanticK,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SlaConfig {
+    pub enabled: bool,
+    pub fulltext_p95_target: Duration,
+    pub semantic_p95_target: Duration,
+    pub hybrid_p95_target: Duration,
+    pub recovery_ratio: f64,
+}
+
+impl Default for SlaConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            fulltext_p95_target: Duration::from_millis(DEFAULT_FULLTEXT_P95_TARGET_MS),
+            semantic_p95_target: Duration::from_millis(DEFAULT_SEMANTIC_P95_TARGET_MS),
+            hybrid_p95_target: Duration::from_millis(DEFAULT_HYBRID_P95_TARGET_MS),
+            recovery_ratio: DEFAULT_RECOVERY_RATIO,
+        }
+    }
+}
+
+impl SlaConfig {
+    pub fn from_env() -> Self {
+        let target = |key: &str, default_ms: u64| Duration::from_millis(env_or(key, default_ms));
+
+        Self {
+            enabled: env_or("SEARCHER_SLA_ENABLED", true),
+            fulltext_p95_target: target(
+                "SEARCHER_SLA_FULLTEXT_P95_MS",
+                DEFAULT_FULLTEXT_P95_TARGET_MS,
+            ),
+            semantic_p95_target: target(
+                "SEARCHER_SLA_SEMANTIC_P95_MS",
+                DEFAULT_SEMANTIC_P95_TARGET_MS,
+            ),
+            hybrid_p95_target: target("SEARCHER_SLA_HYBRID_P95_MS", DEFAULT_HYBRID_P95_TARGET_MS),
+            recovery_ratio: env_or("SEARCHER_SLA_RECOVERY_RATIO", DEFAULT_RECOVERY_RATIO)
+                .clamp(0.0, 1.0),
+        }
+    }
+
+    pub fn p95_target(&self, mode: &SearchMode) -> Duration {
+        match mode {
+            SearchMode::Fulltext => self.fulltext_p95_target,
+            SearchMode::Semantic => self.semantic_p95_target,
+            SearchMode::Hybrid => self.hybrid_p95_target,
+        }
+    }
+}
+
+fn env_or<T: FromStr>(key: &str, default: T) -> T {
+    std::env::var(key)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+/// A change of degradation level for one search mode.
+#[derive(Debug, Clone, Serialize)]
+pub struct ModeSwitch {
+    pub mode: SearchMode,
+    pub from: DegradationLevel,
+    pub to: DegradationLevel,
+    /// P95 that triggered the switch.
+    pub p95_ms: u64,
+    pub target_p95_ms: u64,
+    #[serde(with = "time::serde::iso8601")]
+    pub switched_at: OffsetDateTime,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ModeSlaStatus {
+    pub mode: SearchMode,
+    pub target_p95_ms: u64,
+    /// `None` until enough searches have completed under the current level.
+    pub p95_ms: Option<u64>,
+    pub level: DegradationLevel,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SlaStatus {
+    pub enabled: bool,
+    pub modes: Vec<ModeSlaStatus>,
+    /// Most recent first.
+    pub recent_switches: Vec<ModeSwitch>,
+}
+
+#[derive(Default)]
+struct ModeState {
+    latency: LatencyWindow,
+    level: DegradationLevel,
+}
+
+#[derive(Default)]
+struct MonitorState {
+    modes: [ModeState; 3],
+    switches: VecDeque<ModeSwitch>,
+}
+
+pub struct SlaMonitor {
+    config: SlaConfig,
+    state: Mutex<MonitorState>,
+}
+
+impl SlaMonitor {
+    pub fn new(config: SlaConfig) -> Self {
+        Self {
+            config,
+            state: Mutex::new(MonitorState::default()),
+        }
+    }
+
+    /// Configuration level searches in `mode` should currently run at.
+    pub fn level(&self, mode: &SearchMode) -> DegradationLevel {
+        if !self.config.enabled {
+            return DegradationLevel::Normal;
+        }
+        self.state.lock().unwrap().modes[mode_index(mode)].level
+    }
+
+    /// Record a completed search and switch its mode's level if the SLA is
+    /// breached or has recovered.
+    pub fn record(&self, mode: &SearchMode, latency: Duration) {
+        if !self.config.enabled {
+            return;
+        }
+        let now = Instant::now();
+        let target = self.config.p95_target(mode);
+
+        let mut state = self.state.lock().unwrap();
+        let mode_state = &mut state.modes[mode_index(mode)];
+        mode_state.latency.record(now, latency);
+        let Some(p95) = mode_state.latency.p95(now) else {
+            return;
+        };
+
+        let from = mode_state.level;
+        let to = if p95 > target {
+            from.heavier()
+        } else if p95.as_secs_f64() < target.as_secs_f64() * self.config.recovery_ratio {
+            from.lighter()
+        } else {
+            from
+        };
+        if to == from {
+            return;
+        }
+
+        mode_state.level = to;
+        mode_state.latency = LatencyWindow::default();
+
+        let switch = ModeSwitch {
+            mode: mode.clone(),
+            from,
+            to,
+            p95_ms: p95.as_millis() as u64,
+            target_p95_ms: target.as_millis() as u64,
+            switched_at: OffsetDateTime::now_utc(),
+        };
+        if to > from {
+            warn!(
+                "{:?} search p95 {}ms above target {}ms, degrading from {} to {}",
+                mode,
+                switch.p95_ms,
+                switch.target_p95_ms,
+                from.as_str(),
+                to.as_str()
+            );
+        } else {
+            info!(
+                "{:?} search p95 {}ms back under target {}ms, recovering from {} to {}",
+                mode,
+                switch.p95_ms,
+                switch.target_p95_ms,
+                from.as_str(),
+                to.as_str()
+            );
+        }
+
+        if state.switches.len() >= MAX_RECORDED_SWITCHES {
+            state.switches.pop_back();
+        }
+        state.switches.push_front(switch);
+    }
+
+    pub fn status(&self) -> SlaStatus {
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap();
+        let modes = MODES
+            .iter()
+            .map(|mode| {
+                let mode_state = &mut state.modes[mode_index(mode)];
+                ModeSlaStatus {
+                    mode: mode.clone(),
+                    target_p95_ms: self.config.p95_target(mode).as_millis() as u64,
+                    p95_ms: mode_state
+                        .latency
+                        .p95(now)
+                        .map(|p95| p95.as_millis() as u64),
+                    level: mode_state.level,
+                }
+            })
+            .collect();
+
+        SlaStatus {
+            enabled: self.config.enabled,
+            modes,
+            recent_switches: state.switches.iter().cloned().collect(),
+        }
+    }
+}
+
+fn mode_index(mode: &SearchMode) -> usize {
+    match mode {
+        SearchMode::Fulltext => 0,
+        SearchMode::Semantic => 1,
+        SearchMode::Hybrid => 2,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::admission::MIN_LATENCY_SAMPLES;
+
+    fn monitor() -> SlaMonitor {
+        SlaMonitor::new(SlaConfig {
+            hybrid_p95_target: Duration::from_millis(100),
+            ..SlaConfig::default()
+        })
+    }
+
+    fn record_window(monitor: &SlaMonitor, mode: &SearchMode, latency: Duration) {
+        for _ in 0..MIN_LATENCY_SAMPLES {
+            monitor.record(mode, latency);
+        }
+    }
+
+    #[test]
+    fn test_breach_degrades_one_level_per_window() {
+        let monitor = monitor();
+        let slow = Duration::from_millis(300);
+
+        record_window(&monitor, &SearchMode::Hybrid, slow);
+        assert_eq!(
+            monitor.level(&SearchMode::Hybrid),
+            DegradationLevel::SkipRerank
+        );
+        // Other modes keep their own SLA.
+        assert_eq!(
+            monitor.level(&SearchMode::Fulltext),
+            DegradationLevel::Normal
+        );
+
+        record_window(&monitor, &SearchMode::Hybrid, slow);
+        record_window(&monitor, &SearchMode::Hybrid, slow);
+        record_window(&monitor, &SearchMode::Hybrid, slow);
+        assert_eq!(
+            monitor.level(&SearchMode::Hybrid),
+            DegradationLevel::ShrinkFacets
+        );
+
+        let status = monitor.status();
+        assert_eq!(status.recent_switches.len(), 3);
+        assert_eq!(status.recent_switches[0].to, DegradationLevel::ShrinkFacets);
+        assert_eq!(status.recent_switches[0].p95_ms, 300);
+        assert_eq!(status.recent_switches[0].target_p95_ms, 100);
+    }
+
+    #[test]
+    fn test_recovers_only_well_under_target() {
+        let held = monitor();
+        record_window(&held, &SearchMode::Hybrid, Duration::from_millis(300));
+        assert!(held.level(&SearchMode::Hybrid).is_degraded());
+
+        // Under target but above the recovery threshold: hold.
+        record_window(&held, &SearchMode::Hybrid, Duration::from_millis(90));
+        assert_eq!(
+            held.level(&SearchMode::Hybrid),
+            DegradationLevel::SkipRerank
+        );
+
+        let recovered = monitor();
+        record_window(&recovered, &SearchMode::Hybrid, Duration::from_millis(300));
+        record_window(&recovered, &SearchMode::Hybrid, Duration::from_millis(20));
+        assert_eq!(
+            recovered.level(&SearchMode::Hybrid),
+            DegradationLevel::Normal
+        );
+        assert_eq!(
+            recovered.status().recent_switches[0].from,
+            DegradationLevel::SkipRerank
+        );
+    }
+
+    #[test]
+    fn test_degraded_settings() {
+        assert_eq!(DegradationLevel::Normal.semantic_k(100, 20), 100);
+        assert_eq!(DegradationLevel::SkipRerank.semantic_k(100, 20), 100);
+        assert_eq!(DegradationLevel::ReduceSemanticK.semantic_k(100, 20), 25);
+        assert_eq!(DegradationLevel::ReduceSemanticK.semantic_k(40, 20), 20);
+        assert!(DegradationLevel::ReduceSemanticK.include_facets());
+        assert!(!DegradationLevel::ShrinkFacets.include_facets());
+        assert!(DegradationLevel::ShrinkFacets.skip_rerank());
+    }
+
+    #[test]
+    fn test_disabled_monitor_never_degrades() {
+        let monitor = SlaMonitor::new(SlaConfig {
+            enabled: false,
+            hybrid_p95_target: Duration::from_millis(100),
+            ..SlaConfig::default()
+        });
+        record_window(&monitor, &SearchMode::Hybrid, Duration::from_millis(300));
+        assert_eq!(monitor.level(&SearchMode::Hybrid), DegradationLevel::Normal);
+        assert!(monitor.status().recent_switches.is_empty());
+    }
+}
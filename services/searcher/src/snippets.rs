@@ -0,0 +1,338 @@
+//! Snippets shown under search results: short passages of a document with
+//! the matched words given as offsets, so clients can render emphasis
+//! without parsing markup out of document text.
+//!
+//! Fulltext snippets come from Postgres `ts_headline`, run with
+//! `HEADLINE_OPTIONS` so that matches and fragment boundaries are marked with
+//! characters that do not occur in document text. Semantic matches have no
+//! matched words as such: the passage of the best-matching chunk, with some
+//! of the text around it, that contains the most query words is used, with
+//! those words highlighted.
+
+use serde::{Deserialize, Serialize};
+
+const HEADLINE_START: char = '\u{E000}';
+const HEADLINE_STOP: char = '\u{E001}';
+const HEADLINE_DELIMITER: char = '\u{E002}';
+
+/// `ts_headline` options for fulltext snippets, read by `parse_headline`.
+pub const HEADLINE_OPTIONS: &str = "StartSel=\"\u{E000}\", StopSel=\"\u{E001}\", \
+     FragmentDelimiter=\"\u{E002}\", MaxFragments=3, MaxWords=30, MinWords=10";
+
+/// Bytes of document text around a semantic chunk considered for its snippet.
+const CONTEXT_LEN: usize = 160;
+
+/// Longest semantic snippet, in bytes.
+const MAX_SNIPPET_LEN: usize = 320;
+
+/// Query words too common to be worth highlighting.
+const STOP_WORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "by", "can", "do", "does", "for", "from", "how",
+    "in", "is", "it", "of", "on", "or", "the", "to", "was", "we", "what", "when", "where", "which",
+    "who", "why", "with",
+];
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snippet {
+    pub text: String,
+    /// Matched ranges of `text`, in order. Offsets count UTF-16 code units,
+    /// as JavaScript strings are indexed.
+    pub highlights: Vec<HighlightRange>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HighlightRange {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Snippet {
+    fn push(&mut self, text: &str, highlighted: bool) {
+        if text.is_empty() {
+            return;
+        }
+        let start = self.text.encode_utf16().count();
+        self.text.push_str(text);
+        if !highlighted {
+            return;
+        }
+        let end = start + text.encode_utf16().count();
+        match self.highlights.last_mut() {
+            Some(last) if last.end == start => last.end = end,
+            _ => self.highlights.push(HighlightRange { start, end }),
+        }
+    }
+
+    /// The text with highlighted ranges in `**bold**`, the form of the
+    /// `highlights` of search results.
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = String::with_capacity(self.text.len() + 4 * self.highlights.len());
+        let mut ranges = self.highlights.iter().peekable();
+        let mut offset = 0;
+        for c in self.text.chars() {
+            if ranges.peek().is_some_and(|range| range.start == offset) {
+                markdown.push_str("**");
+            }
+            markdown.push(c);
+            offset += c.len_utf16();
+            if ranges.peek().is_some_and(|range| range.end == offset) {
+                markdown.push_str("**");
+                ranges.next();
+            }
+        }
+        markdown
+    }
+}
+
+/// Split the output of `ts_headline`, run with `HEADLINE_OPTIONS`, into
+/// one snippet per fragment.
+pub fn parse_headline(headline: &str) -> Vec<Snippet> {
+    headline
+        .split(HEADLINE_DELIMITER)
+        .filter_map(|fragment| {
+            let fragment = fragment.trim();
+            let mut snippet = Snippet::default();
+            let mut highlighted = false;
+            let mut segment_start = 0;
+            for (i, c) in fragment.char_indices() {
+                if c == HEADLINE_START || c == HEADLINE_STOP {
+                    snippet.push(&fragment[segment_start..i], highlighted);
+                    highlighted = c == HEADLINE_START;
+                    segment_start = i + c.len_utf8();
+                }
+            }
+            snippet.push(&fragment[segment_start..], highlighted);
+            (!snippet.text.trim().is_empty()).then_some(snippet)
+        })
+        .collect()
+}
+
+/// The snippet of a semantic match on the chunk at `chunk_start..chunk_end`
+/// (byte offsets) of `content`, or `None` if the chunk has no words.
+pub fn semantic_snippet(
+    content: &str,
+    chunk_start: usize,
+    chunk_end: usize,
+    query: &str,
+) -> Option<Snippet> {
+    let chunk_end = chunk_end.min(content.len());
+    if chunk_start >= chunk_end {
+        return None;
+    }
+
+    let region_start = floor_char_boundary(content, chunk_start.saturating_sub(CONTEXT_LEN));
+    let region_end = floor_char_boundary(content, chunk_end + CONTEXT_LEN);
+    let words = whole_words(content, region_start, region_end);
+    let terms = query_terms(query);
+    let matches: Vec<(usize, usize)> = words
+        .iter()
+        .enumerate()
+        .filter_map(|(i, &(start, end))| {
+            matching_term(&content[start..end], &terms).map(|t| (i, t))
+        })
+        .collect();
+
+    // Windows start at a word: the chunk's first, or a little before a match
+    let window_start_at = |offset: usize| {
+        words
+            .iter()
+            .position(|&(start, _)| start >= offset)
+            .unwrap_or(words.len())
+    };
+    let mut candidates = vec![window_start_at(chunk_start)];
+    candidates.extend(
+        matches
+            .iter()
+            .map(|&(i, _)| window_start_at(words[i].0.saturating_sub(MAX_SNIPPET_LEN / 4))),
+    );
+    let (_, first, last) = candidates
+        .into_iter()
+        .enumerate()
+        .filter(|&(_, first)| first < words.len())
+        .map(|(n, first)| {
+            let limit = words[first].0 + MAX_SNIPPET_LEN;
+            let last = first
+                + words[first..]
+                    .iter()
+                    .take_while(|&&(_, end)| end <= limit)
+                    .count()
+                    .max(1)
+                - 1;
+            (n, first, last)
+        })
+        .max_by_key(|&(n, first, last)| {
+            let in_window: Vec<usize> = matches
+                .iter()
+                .filter(|&&(i, _)| (first..=last).contains(&i))
+                .map(|&(_, term)| term)
+                .collect();
+            let mut distinct = in_window.clone();
+            distinct.sort_unstable();
+            distinct.dedup();
+            // The chunk's own start wins ties, then earlier matches
+            (distinct.len(), in_window.len(), std::cmp::Reverse(n))
+        })?;
+
+    let mut snippet = Snippet::default();
+    let mut position = words[first].0;
+    for &(i, _) in matches
+        .iter()
+        .filter(|&&(i, _)| (first..=last).contains(&i))
+    {
+        let (start, end) = words[i];
+        snippet.push(&content[position..start], false);
+        snippet.push(&content[start..end], true);
+        position = end;
+    }
+    snippet.push(&content[position..words[last].1], false);
+    Some(snippet)
+}
+
+/// Byte ranges of the words of `content[start..end]`, leaving out words cut
+/// off by either end.
+fn whole_words(content: &str, start: usize, end: usize) -> Vec<(usize, usize)> {
+    let is_word_char = |c: char| c.is_alphanumeric();
+    let mut words: Vec<(usize, usize)> = Vec::new();
+    let mut word_start = None;
+    for (i, c) in content[start..end].char_indices() {
+        match (is_word_char(c), word_start) {
+            (true, None) => word_start = Some(start + i),
+            (false, Some(s)) => {
+                words.push((s, start + i));
+                word_start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(s) = word_start {
+        words.push((s, end));
+    }
+
+    if words.first().is_some_and(|&(s, _)| {
+        s == start
+            && content[..start]
+                .chars()
+                .next_back()
+                .is_some_and(is_word_char)
+    }) {
+        words.remove(0);
+    }
+    if words
+        .last()
+        .is_some_and(|&(_, e)| e == end && content[end..].chars().next().is_some_and(is_word_char))
+    {
+        words.pop();
+    }
+    words
+}
+
+/// Lowercased words of `query` worth highlighting.
+fn query_terms(query: &str) -> Vec<String> {
+    let mut terms: Vec<String> = Vec::new();
+    for word in query.split(|c: char| !c.is_alphanumeric()) {
+        let word = word.to_lowercase();
+        if !word.is_empty() && !STOP_WORDS.contains(&word.as_str()) && !terms.contains(&word) {
+            terms.push(word);
+        }
+    }
+    terms
+}
+
+/// Index of the term `word` matches: equal ignoring case, or extending a
+/// term of four or more characters, e.g. "budgets" for "budget".
+fn matching_term(word: &str, terms: &[String]) -> Option<usize> {
+    let word = word.to_lowercase();
+    terms
+        .iter()
+        .position(|term| word == *term || (term.chars().count() >= 4 && word.starts_with(term)))
+}
+
+fn floor_char_boundary(content: &str, index: usize) -> usize {
+    let mut index = index.min(content.len());
+    while !content.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn highlighted(snippet: &Snippet) -> Vec<String> {
+        let units: Vec<u16> = snippet.text.encode_utf16().collect();
+        snippet
+            .highlights
+            .iter()
+            .map(|range| String::from_utf16(&units[range.start..range.end]).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_headline_splits_fragments_and_marks_matches() {
+        let headline = format!(
+            " the {s}budget{e} for {s}Q3{e}{s}{e} \u{E002}naïve {s}budget{e} review ",
+            s = HEADLINE_START,
+            e = HEADLINE_STOP
+        );
+        let snippets = parse_headline(&headline);
+        assert_eq!(snippets.len(), 2);
+
+        assert_eq!(snippets[0].text, "the budget for Q3");
+        assert_eq!(highlighted(&snippets[0]), vec!["budget", "Q3"]);
+        assert_eq!(snippets[0].to_markdown(), "the **budget** for **Q3**");
+
+        assert_eq!(snippets[1].text, "naïve budget review");
+        assert_eq!(
+            snippets[1].highlights,
+            vec![HighlightRange { start: 6, end: 12 }]
+        );
+        assert_eq!(snippets[1].to_markdown(), "naïve **budget** review");
+    }
+
+    #[test]
+    fn test_parse_headline_without_matches() {
+        let snippets = parse_headline("Start of the document");
+        assert_eq!(snippets.len(), 1);
+        assert!(snippets[0].highlights.is_empty());
+        assert!(parse_headline("  ").is_empty());
+    }
+
+    #[test]
+    fn test_semantic_snippet_includes_context_and_highlights_query_words() {
+        let content = "Intro. The quarterly budgets were approved by finance. Outro text here.";
+        let chunk_start = content.find("were").unwrap();
+        let chunk_end = content.find(" Outro").unwrap();
+        let snippet =
+            semantic_snippet(content, chunk_start, chunk_end, "what is the budget?").unwrap();
+
+        assert_eq!(snippet.text, content.trim_end_matches('.'));
+        assert_eq!(highlighted(&snippet), vec!["budgets"]);
+    }
+
+    #[test]
+    fn test_semantic_snippet_selects_passage_with_most_query_words() {
+        let filler = "lorem ipsum dolor sit amet ".repeat(20);
+        let content = format!("{filler}the launch plan covers pricing and launch dates {filler}");
+        let snippet = semantic_snippet(&content, 0, content.len(), "launch pricing").unwrap();
+
+        assert!(snippet.text.len() <= MAX_SNIPPET_LEN);
+        assert!(
+            snippet
+                .text
+                .contains("the launch plan covers pricing and launch dates")
+        );
+        assert_eq!(highlighted(&snippet), vec!["launch", "pricing", "launch"]);
+    }
+
+    #[test]
+    fn test_semantic_snippet_without_matches_starts_at_chunk() {
+        let content = "alpha beta gamma delta";
+        let snippet = semantic_snippet(content, 6, content.len(), "zeta").unwrap();
+        assert_eq!(snippet.text, "beta gamma delta");
+        assert!(snippet.highlights.is_empty());
+
+        assert!(semantic_snippet(content, 40, 50, "zeta").is_none());
+        assert!(semantic_snippet("...", 0, 3, "zeta").is_none());
+    }
+}
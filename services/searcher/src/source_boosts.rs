@@ -0,0 +1,185 @@
+//! Per-source-type ranking multipliers for hybrid search.
+//!
+//! Admins configure deployment-wide boosts (stored in `source_type_boosts`),
+//! and a search can override any of them through `source_boosts`. Boosts
+//! scale the fused RRF score before results are sorted and paged, so a boost
+//! of 1.5 lets Confluence pages outrank Slack messages that fused slightly
+//! higher.
+
+use crate::models::{FieldError, SearchResult};
+use crate::search::source_type_to_string;
+use serde::{Deserialize, Serialize};
+use shared::models::SourceType;
+use sqlx::PgPool;
+use std::collections::HashMap;
+
+pub const MAX_SOURCE_BOOST: f32 = 10.0;
+
+pub type SourceBoosts = HashMap<SourceType, f32>;
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct SourceBoostsSettings {
+    pub boosts: SourceBoosts,
+}
+
+/// Problems with a boost map, reported against `field`.
+pub fn validate_source_boosts(field: &str, boosts: &SourceBoosts) -> Vec<FieldError> {
+    let mut errors: Vec<FieldError> = boosts
+        .iter()
+        .filter(|(_, boost)| !(boost.is_finite() && **boost > 0.0 && **boost <= MAX_SOURCE_BOOST))
+        .map(|(source_type, _)| {
+            FieldError::new(
+                format!("{}.{}", field, source_type_to_string(source_type)),
+                format!("must be greater than 0 and at most {}", MAX_SOURCE_BOOST),
+            )
+        })
+        .collect();
+    errors.sort_by(|a, b| a.field.cmp(&b.field));
+    errors
+}
+
+/// Configured boosts with per-request overrides taking precedence.
+pub fn effective_source_boosts(
+    configured: SourceBoosts,
+    overrides: Option<&SourceBoosts>,
+) -> SourceBoosts {
+    let mut boosts = configured;
+    if let Some(overrides) = overrides {
+        boosts.extend(overrides.iter().map(|(st, boost)| (*st, *boost)));
+    }
+    boosts.retain(|_, boost| *boost != 1.0);
+    boosts
+}
+
+/// Scale scores by their source type's boost. Returns whether any score
+/// changed, i.e. whether results need re-sorting. Results must have their
+/// source types populated.
+pub fn apply_source_boosts(results: &mut [SearchResult], boosts: &SourceBoosts) -> bool {
+    let boosts: HashMap<String, f32> = boosts
+        .iter()
+        .map(|(st, boost)| (source_type_to_string(st), *boost))
+        .collect();
+    let mut applied = false;
+    for result in results {
+        if let Some(boost) = result.source_type.as_deref().and_then(|st| boosts.get(st)) {
+            result.score *= boost;
+            applied = true;
+        }
+    }
+    applied
+}
+
+pub struct SourceBoostRepository {
+    pool: PgPool,
+}
+
+impl SourceBoostRepository {
+    pub fn new(pool: &PgPool) -> Self {
+        Self { pool: pool.clone() }
+    }
+
+    pub async fn get_all(&self) -> Result<SourceBoosts, sqlx::Error> {
+        let rows = sqlx::query_as::<_, (SourceType, f32)>(
+            "SELECT source_type, boost FROM source_type_boosts",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().collect())
+    }
+
+    /// Replace every configured boost with `boosts`.
+    pub async fn replace_all(&self, boosts: &SourceBoosts) -> Result<(), sqlx::Error> {
+        let (source_types, values): (Vec<String>, Vec<f32>) = boosts
+            .iter()
+            .map(|(st, boost)| (source_type_to_string(st), *boost))
+            .unzip();
+
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM source_type_boosts")
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query(
+            r#"
+            INSERT INTO source_type_boosts (source_type, boost)
+            SELECT * FROM UNNEST($1::text[], $2::real[])
+            "#,
+        )
+        .bind(&source_types)
+        .bind(&values)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(id: &str, source_type: &str, score: f32) -> SearchResult {
+        let mut result = SearchResult::for_test(id);
+        result.source_type = Some(source_type.to_string());
+        result.score = score;
+        result
+    }
+
+    #[test]
+    fn test_overrides_take_precedence_and_neutral_boosts_drop() {
+        let configured = HashMap::from([
+            (SourceType::Confluence, 1.5),
+            (SourceType::Slack, 0.8),
+            (SourceType::Jira, 1.2),
+        ]);
+        let overrides = HashMap::from([(SourceType::Slack, 1.0), (SourceType::Github, 2.0)]);
+
+        let boosts = effective_source_boosts(configured, Some(&overrides));
+        assert_eq!(
+            boosts,
+            HashMap::from([
+                (SourceType::Confluence, 1.5),
+                (SourceType::Jira, 1.2),
+                (SourceType::Github, 2.0),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_apply_source_boosts() {
+        let mut results = vec![
+            result("slack", "slack", 0.030),
+            result("confluence", "confluence", 0.025),
+            result("drive", "google_drive", 0.020),
+        ];
+        let boosts = HashMap::from([(SourceType::Confluence, 1.5), (SourceType::Slack, 0.8)]);
+
+        assert!(apply_source_boosts(&mut results, &boosts));
+        let scores: Vec<f32> = results.iter().map(|r| r.score).collect();
+        assert!((scores[0] - 0.024).abs() < 1e-6);
+        assert!((scores[1] - 0.0375).abs() < 1e-6);
+        assert!((scores[2] - 0.020).abs() < 1e-6);
+
+        assert!(!apply_source_boosts(&mut results, &HashMap::new()));
+    }
+
+    #[test]
+    fn test_validate_source_boosts() {
+        let boosts = HashMap::from([
+            (SourceType::Confluence, 1.5),
+            (SourceType::Slack, 0.0),
+            (SourceType::Jira, 11.0),
+            (SourceType::Github, f32::NAN),
+        ]);
+        let fields: Vec<String> = validate_source_boosts("source_boosts", &boosts)
+            .into_iter()
+            .map(|e| e.field)
+            .collect();
+        assert_eq!(
+            fields,
+            vec![
+                "source_boosts.github",
+                "source_boosts.jira",
+                "source_boosts.slack"
+            ]
+        );
+    }
+}
@@ -0,0 +1,488 @@
+//! Predicts which source types a query is after, from what users clicked on
+//! for similar queries.
+//!
+//! The model is a multinomial naive Bayes over query terms, retrained
+//! periodically from `search_clicks`. Depending on configuration and how
+//! confident a prediction is, the searcher either restricts retrieval to the
+//! predicted source types, boosts results from them, or leaves the query
+//! alone and searches everything.
+
+use serde::{Deserialize, Serialize};
+use shared::{DatabasePool, SourceType};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{error, info};
+
+/// Never restrict a query to more than this many source types; past that
+/// there is little left to save over searching everything.
+const MAX_ROUTED_SOURCE_TYPES: usize = 2;
+/// Laplace smoothing for term and prior counts.
+const SMOOTHING: f64 = 1.0;
+const MIN_TERM_LEN: usize = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SourceRoutingMode {
+    Off,
+    /// Boost results from predicted source types.
+    Boost,
+    /// Search only the predicted source types when confident, boost otherwise.
+    Restrict,
+}
+
+impl SourceRoutingMode {
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "off" => Some(Self::Off),
+            "boost" => Some(Self::Boost),
+            "restrict" => Some(Self::Restrict),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SourceRouterConfig {
+    pub mode: SourceRoutingMode,
+    /// Clicks older than this are ignored when training.
+    pub window_days: i64,
+    /// Below this many clicks the router always falls back to all sources.
+    pub min_clicks: u64,
+    /// Probability mass the predicted source types must cover to restrict.
+    pub restrict_confidence: f64,
+    /// Minimum probability for a source type to be boosted.
+    pub boost_min_probability: f64,
+}
+
+impl Default for SourceRouterConfig {
+    fn default() -> Self {
+        Self {
+            mode: SourceRoutingMode::Boost,
+            window_days: 90,
+            min_clicks: 200,
+            restrict_confidence: 0.9,
+            boost_min_probability: 0.3,
+        }
+    }
+}
+
+impl SourceRouterConfig {
+    pub fn from_env() -> Self {
+        fn env_or<T: std::str::FromStr>(key: &str, default: T) -> T {
+            std::env::var(key)
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default)
+        }
+
+        let defaults = Self::default();
+        Self {
+            mode: std::env::var("SEARCHER_SOURCE_ROUTING")
+                .ok()
+                .and_then(|v| SourceRoutingMode::parse(&v))
+                .unwrap_or(defaults.mode),
+            window_days: env_or("SEARCHER_SOURCE_ROUTING_WINDOW_DAYS", defaults.window_days),
+            min_clicks: env_or("SEARCHER_SOURCE_ROUTING_MIN_CLICKS", defaults.min_clicks),
+            restrict_confidence: env_or(
+                "SEARCHER_SOURCE_ROUTING_RESTRICT_CONFIDENCE",
+                defaults.restrict_confidence,
+            ),
+            boost_min_probability: env_or(
+                "SEARCHER_SOURCE_ROUTING_BOOST_MIN_PROBABILITY",
+                defaults.boost_min_probability,
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RoutingDecision {
+    Restrict,
+    Boost,
+    /// Search every source unchanged.
+    All,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourcePrediction {
+    pub source_type: SourceType,
+    pub probability: f64,
+}
+
+/// What the router did for a query; returned in search responses when the
+/// request sets `debug`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceRouting {
+    pub mode: SourceRoutingMode,
+    pub decision: RoutingDecision,
+    /// Source types restricted to or boosted, per `decision`.
+    pub source_types: Vec<SourceType>,
+    /// Probability for every candidate source type, highest first.
+    pub predictions: Vec<SourcePrediction>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl SourceRouting {
+    fn all(mode: SourceRoutingMode, reason: &str) -> Self {
+        Self {
+            mode,
+            decision: RoutingDecision::All,
+            source_types: Vec::new(),
+            predictions: Vec::new(),
+            reason: Some(reason.to_string()),
+        }
+    }
+}
+
+/// A recorded click on a search result.
+#[derive(Debug, Deserialize)]
+pub struct SearchClick {
+    pub query: String,
+    pub document_id: String,
+    pub user_id: Option<String>,
+    pub position: Option<i32>,
+    /// The search the result was clicked in, from its response's
+    /// `search_event_id`.
+    #[serde(default)]
+    pub search_event_id: Option<String>,
+}
+
+fn tokenize(query: &str) -> Vec<String> {
+    query
+        .split_whitespace()
+        // Operators such as `in:slack` are explicit filters, not routing signal.
+        .filter(|word| !word.contains(':'))
+        .flat_map(|word| word.split(|c: char| !c.is_alphanumeric()))
+        .filter(|term| term.chars().count() >= MIN_TERM_LEN)
+        .map(|term| term.to_lowercase())
+        .collect()
+}
+
+#[derive(Debug, Default)]
+pub struct RouterModel {
+    term_counts: HashMap<String, HashMap<SourceType, u64>>,
+    /// Total term occurrences per source type.
+    type_terms: HashMap<SourceType, u64>,
+    type_clicks: HashMap<SourceType, u64>,
+    total_clicks: u64,
+}
+
+impl RouterModel {
+    /// Build a model from `(query, clicked source type, click count)` rows.
+    pub fn train<'a>(rows: impl IntoIterator<Item = (&'a str, SourceType, u64)>) -> Self {
+        let mut model = Self::default();
+        for (query, source_type, clicks) in rows {
+            *model.type_clicks.entry(source_type).or_default() += clicks;
+            model.total_clicks += clicks;
+            for term in tokenize(query) {
+                *model
+                    .term_counts
+                    .entry(term)
+                    .or_default()
+                    .entry(source_type)
+                    .or_default() += clicks;
+                *model.type_terms.entry(source_type).or_default() += clicks;
+            }
+        }
+        model
+    }
+
+    pub fn total_clicks(&self) -> u64 {
+        self.total_clicks
+    }
+
+    /// Posterior over `candidates` given the query's known terms, highest
+    /// first. Returns `None` when no term in the query was seen in training.
+    fn predict(&self, query: &str, candidates: &[SourceType]) -> Option<Vec<SourcePrediction>> {
+        let terms: Vec<&HashMap<SourceType, u64>> = tokenize(query)
+            .iter()
+            .filter_map(|term| self.term_counts.get(term))
+            .collect();
+        if terms.is_empty() || candidates.is_empty() {
+            return None;
+        }
+
+        let vocabulary = self.term_counts.len() as f64;
+        let log_scores: Vec<f64> = candidates
+            .iter()
+            .map(|source_type| {
+                let clicks = self.type_clicks.get(source_type).copied().unwrap_or(0) as f64;
+                let prior = (clicks + SMOOTHING)
+                    / (self.total_clicks as f64 + SMOOTHING * candidates.len() as f64);
+                let type_terms = self.type_terms.get(source_type).copied().unwrap_or(0) as f64;
+                let likelihood: f64 = terms
+                    .iter()
+                    .map(|counts| {
+                        let count = counts.get(source_type).copied().unwrap_or(0) as f64;
+                        ((count + SMOOTHING) / (type_terms + SMOOTHING * vocabulary)).ln()
+                    })
+                    .sum();
+                prior.ln() + likelihood
+            })
+            .collect();
+
+        // Softmax in log space to avoid underflow on long queries.
+        let max = log_scores.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let weights: Vec<f64> = log_scores.iter().map(|s| (s - max).exp()).collect();
+        let total: f64 = weights.iter().sum();
+
+        let mut predictions: Vec<SourcePrediction> = candidates
+            .iter()
+            .zip(weights)
+            .map(|(source_type, weight)| SourcePrediction {
+                source_type: *source_type,
+                probability: weight / total,
+            })
+            .collect();
+        predictions.sort_by(|a, b| b.probability.total_cmp(&a.probability));
+        Some(predictions)
+    }
+
+    /// Decide how to route `query` across the `candidates` source types.
+    pub fn route(
+        &self,
+        query: &str,
+        candidates: &[SourceType],
+        config: &SourceRouterConfig,
+    ) -> SourceRouting {
+        if self.total_clicks < config.min_clicks {
+            return SourceRouting::all(config.mode, "insufficient_training_data");
+        }
+        if candidates.len() < 2 {
+            return SourceRouting::all(config.mode, "single_source_type");
+        }
+        let Some(predictions) = self.predict(query, candidates) else {
+            return SourceRouting::all(config.mode, "no_known_terms");
+        };
+
+        let mut covered = 0.0;
+        let mut top = Vec::new();
+        for prediction in predictions.iter().take(MAX_ROUTED_SOURCE_TYPES) {
+            top.push(prediction.source_type);
+            covered += prediction.probability;
+            if covered >= config.restrict_confidence {
+                break;
+            }
+        }
+        // A source type nobody has clicked on yet may be new; restricting
+        // would hide it until it has built up clicks.
+        let all_trained = candidates
+            .iter()
+            .all(|source_type| self.type_clicks.contains_key(source_type));
+
+        let (decision, source_types, reason) = if config.mode == SourceRoutingMode::Restrict
+            && covered >= config.restrict_confidence
+            && top.len() < candidates.len()
+            && all_trained
+        {
+            (RoutingDecision::Restrict, top, None)
+        } else {
+            let boosted: Vec<SourceType> = predictions
+                .iter()
+                .filter(|p| p.probability >= config.boost_min_probability)
+                .map(|p| p.source_type)
+                .take(MAX_ROUTED_SOURCE_TYPES)
+                .collect();
+            if boosted.is_empty() {
+                (RoutingDecision::All, boosted, Some("low_confidence"))
+            } else {
+                (RoutingDecision::Boost, boosted, None)
+            }
+        };
+
+        SourceRouting {
+            mode: config.mode,
+            decision,
+            source_types,
+            predictions,
+            reason: reason.map(str::to_string),
+        }
+    }
+}
+
+pub struct SourceRouter {
+    model: RwLock<Arc<RouterModel>>,
+    db_pool: DatabasePool,
+    config: SourceRouterConfig,
+}
+
+impl SourceRouter {
+    pub fn new(db_pool: DatabasePool, config: SourceRouterConfig) -> Self {
+        Self {
+            model: RwLock::new(Arc::new(RouterModel::default())),
+            db_pool,
+            config,
+        }
+    }
+
+    pub fn config(&self) -> &SourceRouterConfig {
+        &self.config
+    }
+
+    pub async fn refresh(&self) -> anyhow::Result<()> {
+        let rows: Vec<(String, SourceType, i64)> = sqlx::query_as(
+            r#"
+            SELECT query, source_type, COUNT(*)
+            FROM search_clicks
+            WHERE created_at > NOW() - make_interval(days => $1::int)
+            GROUP BY query, source_type
+            "#,
+        )
+        .bind(self.config.window_days)
+        .fetch_all(self.db_pool.pool())
+        .await?;
+
+        let model =
+            RouterModel::train(rows.iter().map(|(query, source_type, clicks)| {
+                (query.as_str(), *source_type, *clicks as u64)
+            }));
+        info!(
+            "Source router trained on {} clicks across {} source types",
+            model.total_clicks(),
+            model.type_clicks.len()
+        );
+        *self.model.write().await = Arc::new(model);
+        Ok(())
+    }
+
+    pub fn start_background_refresh(self: &Arc<Self>, interval_secs: u64) {
+        let router = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(interval_secs));
+            loop {
+                interval.tick().await;
+                if let Err(e) = router.refresh().await {
+                    error!("Failed to refresh source router: {}", e);
+                }
+            }
+        });
+    }
+
+    /// Route `query` across the distinct source types of the active sources.
+    /// Returns `None` when routing is turned off.
+    pub async fn route(&self, query: &str, active: &[SourceType]) -> Option<SourceRouting> {
+        if self.config.mode == SourceRoutingMode::Off {
+            return None;
+        }
+        let candidates: Vec<SourceType> = active
+            .iter()
+            .copied()
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+
+        let model = self.model.read().await.clone();
+        Some(model.route(query, &candidates, &self.config))
+    }
+
+    /// Store a click, resolving the document's source. Returns `false` when
+    /// the document does not exist.
+    pub async fn record_click(&self, click: &SearchClick) -> anyhow::Result<bool> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO search_clicks
+                (user_id, query, document_id, source_id, source_type, position, search_event_id)
+            SELECT $1, $2, d.id, s.id, s.source_type, $4,
+                   (SELECT id FROM search_events WHERE id = $5)
+            FROM documents d
+            JOIN sources s ON s.id = d.source_id
+            WHERE d.id = $3
+            "#,
+        )
+        .bind(click.user_id.as_deref())
+        .bind(&click.query)
+        .bind(&click.document_id)
+        .bind(click.position)
+        .bind(click.search_event_id.as_deref())
+        .execute(self.db_pool.pool())
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(mode: SourceRoutingMode) -> SourceRouterConfig {
+        SourceRouterConfig {
+            mode,
+            min_clicks: 10,
+            ..Default::default()
+        }
+    }
+
+    fn trained_model() -> RouterModel {
+        RouterModel::train([
+            ("standup notes", SourceType::Slack, 20),
+            ("sprint standup", SourceType::Slack, 10),
+            ("JIRA-123 login bug", SourceType::Jira, 15),
+            ("login bug regression", SourceType::Jira, 10),
+            ("quarterly planning doc", SourceType::GoogleDrive, 12),
+            ("onboarding doc", SourceType::GoogleDrive, 8),
+        ])
+    }
+
+    const CANDIDATES: &[SourceType] =
+        &[SourceType::Slack, SourceType::Jira, SourceType::GoogleDrive];
+
+    #[test]
+    fn test_tokenize_skips_operators_and_short_terms() {
+        assert_eq!(
+            tokenize("in:slack Q3 standup-notes a"),
+            vec!["q3", "standup", "notes"]
+        );
+    }
+
+    #[test]
+    fn test_restricts_when_confident() {
+        let routing =
+            trained_model().route("standup", CANDIDATES, &config(SourceRoutingMode::Restrict));
+        assert_eq!(routing.decision, RoutingDecision::Restrict);
+        assert_eq!(routing.source_types, vec![SourceType::Slack]);
+        assert_eq!(routing.predictions.len(), 3);
+        assert_eq!(routing.predictions[0].source_type, SourceType::Slack);
+    }
+
+    #[test]
+    fn test_boost_mode_never_restricts() {
+        let routing =
+            trained_model().route("standup", CANDIDATES, &config(SourceRoutingMode::Boost));
+        assert_eq!(routing.decision, RoutingDecision::Boost);
+        assert_eq!(routing.source_types, vec![SourceType::Slack]);
+    }
+
+    #[test]
+    fn test_untrained_candidate_prevents_restriction() {
+        let mut candidates = CANDIDATES.to_vec();
+        candidates.push(SourceType::Confluence);
+        let routing =
+            trained_model().route("standup", &candidates, &config(SourceRoutingMode::Restrict));
+        assert_eq!(routing.decision, RoutingDecision::Boost);
+    }
+
+    #[test]
+    fn test_falls_back_to_all_sources() {
+        let model = trained_model();
+        let cfg = config(SourceRoutingMode::Restrict);
+
+        let routing = model.route("completely unseen words", CANDIDATES, &cfg);
+        assert_eq!(routing.decision, RoutingDecision::All);
+        assert_eq!(routing.reason.as_deref(), Some("no_known_terms"));
+
+        let strict = SourceRouterConfig {
+            min_clicks: 1000,
+            ..cfg
+        };
+        let routing = model.route("standup", CANDIDATES, &strict);
+        assert_eq!(routing.decision, RoutingDecision::All);
+        assert_eq!(
+            routing.reason.as_deref(),
+            Some("insufficient_training_data")
+        );
+    }
+}
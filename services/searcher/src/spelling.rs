@@ -0,0 +1,324 @@
+//! "Did you mean" suggestions for queries that find little.
+//!
+//! Misspelled query words are corrected against the term dictionary the
+//! indexer periodically rebuilds from corpus lexemes (`corpus_terms`). A word
+//! counts as misspelled when the dictionary does not contain it; candidates
+//! are dictionary terms within a small edit distance, closest first and then
+//! most common first. Operators such as `in:slack` are never corrected.
+
+use shared::DatabasePool;
+use shared::db::repositories::CorpusStatsRepository;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{error, info};
+
+const MIN_WORD_LEN: usize = 3;
+/// Words this short tolerate a single edit; longer words tolerate two.
+const SHORT_WORD_LEN: usize = 4;
+/// Candidates kept per misspelled word.
+const CANDIDATES_PER_WORD: usize = 3;
+
+#[derive(Debug, Clone)]
+pub struct SpellingConfig {
+    pub enabled: bool,
+    /// Suggest corrections only when the query found at most this many
+    /// results.
+    pub max_results: i64,
+    pub max_suggestions: usize,
+}
+
+impl Default for SpellingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_results: 3,
+            max_suggestions: 3,
+        }
+    }
+}
+
+impl SpellingConfig {
+    pub fn from_env() -> Self {
+        fn env_or<T: std::str::FromStr>(key: &str, default: T) -> T {
+            std::env::var(key)
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default)
+        }
+
+        let defaults = Self::default();
+        Self {
+            enabled: env_or("SEARCHER_SPELL_CORRECTION_ENABLED", defaults.enabled),
+            max_results: env_or(
+                "SEARCHER_SPELL_CORRECTION_MAX_RESULTS",
+                defaults.max_results,
+            ),
+            max_suggestions: env_or(
+                "SEARCHER_SPELL_CORRECTION_MAX_SUGGESTIONS",
+                defaults.max_suggestions,
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct TermDictionary {
+    document_counts: HashMap<String, i64>,
+    /// Terms bucketed by length in characters, so candidate lookup only
+    /// compares terms that could be within the edit distance.
+    by_len: HashMap<usize, Vec<(String, i64)>>,
+}
+
+impl TermDictionary {
+    pub fn new(terms: impl IntoIterator<Item = (String, i64)>) -> Self {
+        let mut dictionary = Self::default();
+        for (term, document_count) in terms {
+            let term = term.to_lowercase();
+            dictionary
+                .by_len
+                .entry(term.chars().count())
+                .or_default()
+                .push((term.clone(), document_count));
+            dictionary.document_counts.insert(term, document_count);
+        }
+        dictionary
+    }
+
+    pub fn len(&self) -> usize {
+        self.document_counts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.document_counts.is_empty()
+    }
+
+    /// Closest dictionary terms to `word`, or nothing when `word` is a known
+    /// term or not worth correcting.
+    fn candidates(&self, word: &str) -> Vec<&str> {
+        let len = word.chars().count();
+        if len < MIN_WORD_LEN
+            || !word.chars().all(char::is_alphabetic)
+            || self.document_counts.contains_key(word)
+        {
+            return Vec::new();
+        }
+        let max_distance = if len <= SHORT_WORD_LEN { 1 } else { 2 };
+
+        let mut scored: Vec<(usize, i64, &str)> = (len.saturating_sub(max_distance)
+            ..=len + max_distance)
+            .filter_map(|candidate_len| self.by_len.get(&candidate_len))
+            .flatten()
+            .filter_map(|(term, document_count)| {
+                edit_distance(word, term, max_distance)
+                    .map(|distance| (distance, *document_count, term.as_str()))
+            })
+            .collect();
+        scored.sort_by(|a, b| {
+            a.0.cmp(&b.0)
+                .then_with(|| b.1.cmp(&a.1))
+                .then_with(|| a.2.cmp(b.2))
+        });
+        scored
+            .into_iter()
+            .take(CANDIDATES_PER_WORD)
+            .map(|(_, _, term)| term)
+            .collect()
+    }
+
+    /// Corrected versions of `query`, best first. The first suggestion
+    /// corrects every misspelled word; the rest swap in runner-up candidates
+    /// for one word at a time.
+    pub fn suggest(&self, query: &str, max_suggestions: usize) -> Vec<String> {
+        let tokens: Vec<&str> = query.split_whitespace().collect();
+        let corrections: Vec<(usize, &str, Vec<&str>)> = tokens
+            .iter()
+            .enumerate()
+            .filter(|(_, token)| !token.contains(':'))
+            .filter_map(|(i, token)| {
+                let core = token.trim_matches(|c: char| !c.is_alphanumeric());
+                let candidates = self.candidates(&core.to_lowercase());
+                (!candidates.is_empty()).then_some((i, core, candidates))
+            })
+            .collect();
+        if corrections.is_empty() {
+            return Vec::new();
+        }
+
+        let build = |alternate: Option<(usize, &str)>| -> String {
+            let mut corrected: Vec<String> = tokens.iter().map(|t| t.to_string()).collect();
+            for (i, core, candidates) in &corrections {
+                let replacement = match alternate {
+                    Some((index, term)) if index == *i => term,
+                    _ => candidates[0],
+                };
+                corrected[*i] = tokens[*i].replacen(core, replacement, 1);
+            }
+            corrected.join(" ")
+        };
+
+        let mut suggestions = vec![build(None)];
+        for rank in 1..CANDIDATES_PER_WORD {
+            for (i, _, candidates) in &corrections {
+                if let Some(term) = candidates.get(rank) {
+                    let suggestion = build(Some((*i, term)));
+                    if !suggestions.contains(&suggestion) {
+                        suggestions.push(suggestion);
+                    }
+                }
+            }
+        }
+        suggestions.truncate(max_suggestions);
+        suggestions
+    }
+}
+
+/// Optimal string alignment distance (Levenshtein plus adjacent
+/// transpositions), or `None` when it exceeds `max`.
+fn edit_distance(a: &str, b: &str, max: usize) -> Option<usize> {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    if a.len().abs_diff(b.len()) > max {
+        return None;
+    }
+
+    let mut prev_prev: Vec<usize> = vec![0; b.len() + 1];
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        let mut current = vec![i; b.len() + 1];
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            current[j] = (prev[j] + 1)
+                .min(current[j - 1] + 1)
+                .min(prev[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                current[j] = current[j].min(prev_prev[j - 2] + 1);
+            }
+        }
+        // Transpositions reach back two rows, so both must be out of range.
+        if current.iter().chain(&prev).all(|&d| d > max) {
+            return None;
+        }
+        prev_prev = std::mem::replace(&mut prev, current);
+    }
+
+    let distance = prev[b.len()];
+    (distance <= max).then_some(distance)
+}
+
+pub struct SpellChecker {
+    dictionary: RwLock<Arc<TermDictionary>>,
+    db_pool: DatabasePool,
+    config: SpellingConfig,
+}
+
+impl SpellChecker {
+    pub fn new(db_pool: DatabasePool, config: SpellingConfig) -> Self {
+        Self {
+            dictionary: RwLock::new(Arc::new(TermDictionary::default())),
+            db_pool,
+            config,
+        }
+    }
+
+    pub fn config(&self) -> &SpellingConfig {
+        &self.config
+    }
+
+    pub async fn refresh(&self) -> anyhow::Result<()> {
+        let terms = CorpusStatsRepository::new(self.db_pool.pool())
+            .get_term_dictionary()
+            .await?;
+        let dictionary = TermDictionary::new(terms);
+        info!("Spell checker loaded {} terms", dictionary.len());
+        *self.dictionary.write().await = Arc::new(dictionary);
+        Ok(())
+    }
+
+    pub fn start_background_refresh(self: &Arc<Self>, interval_secs: u64) {
+        let checker = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(interval_secs));
+            loop {
+                interval.tick().await;
+                if let Err(e) = checker.refresh().await {
+                    error!("Failed to refresh spell checker: {}", e);
+                }
+            }
+        });
+    }
+
+    /// Suggestions for a query that found `total_count` results. `None` when
+    /// spell correction is off, the query found enough, or nothing in it
+    /// looks misspelled.
+    pub async fn did_you_mean(&self, query: &str, total_count: i64) -> Option<Vec<String>> {
+        if !self.config.enabled || total_count > self.config.max_results {
+            return None;
+        }
+        let dictionary = self.dictionary.read().await.clone();
+        if dictionary.is_empty() {
+            return None;
+        }
+        let suggestions = dictionary.suggest(query, self.config.max_suggestions);
+        (!suggestions.is_empty()).then_some(suggestions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dictionary() -> TermDictionary {
+        TermDictionary::new(
+            [
+                ("quarterly", 40),
+                ("planning", 55),
+                ("plankton", 2),
+                ("roadmap", 30),
+                ("onboarding", 25),
+                ("boarding", 3),
+                ("the", 500),
+                ("policy", 60),
+                ("police", 5),
+            ]
+            .into_iter()
+            .map(|(term, count)| (term.to_string(), count)),
+        )
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("planning", "planning", 2), Some(0));
+        assert_eq!(edit_distance("planing", "planning", 2), Some(1));
+        // Adjacent transposition is a single edit
+        assert_eq!(edit_distance("roadmpa", "roadmap", 2), Some(1));
+        assert_eq!(edit_distance("onbaording", "onboarding", 1), Some(1));
+        assert_eq!(edit_distance("policy", "plankton", 2), None);
+    }
+
+    #[test]
+    fn test_suggest_corrects_misspelled_words_only() {
+        let suggestions = dictionary().suggest("quartely planing roadmap", 3);
+        assert_eq!(suggestions[0], "quarterly planning roadmap");
+    }
+
+    #[test]
+    fn test_suggest_prefers_closer_then_more_common_terms() {
+        // "polcy" is one edit from "policy" and two from "police"
+        let suggestions = dictionary().suggest("travel polcy", 3);
+        assert_eq!(suggestions, vec!["travel policy", "travel police"]);
+    }
+
+    #[test]
+    fn test_suggest_skips_operators_and_keeps_punctuation() {
+        let suggestions = dictionary().suggest("in:slack \"onbaording\" doc", 3);
+        assert_eq!(suggestions[0], "in:slack \"onboarding\" doc");
+    }
+
+    #[test]
+    fn test_no_suggestions_for_known_or_short_words() {
+        assert!(dictionary().suggest("the planning roadmap", 3).is_empty());
+        assert!(dictionary().suggest("q3 okr", 3).is_empty());
+        assert!(dictionary().suggest("zzzzzzzz", 3).is_empty());
+    }
+}
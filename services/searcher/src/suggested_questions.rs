@@ -1,23 +1,47 @@
+//! "Try asking" suggestions for the home screen.
+//!
+//! A user's recently indexed documents, limited to what they can read, are
+//! grouped into topics by their summary embeddings, and one suggestion is
+//! generated from a central document of each topic, largest topics first.
+//! Random readable documents fill in when there are too few topics, e.g.
+//! when summary embeddings are not enabled.
+//!
+//! Suggestions are cached per user, and only served while the user is in the
+//! workspace they were drawn from. They are regenerated in the background
+//! when a source they were drawn from finishes a sync, and on a schedule for
+//! users who asked for suggestions recently.
+
+use crate::document_clusters;
 use crate::models::{SuggestedQuestion, SuggestedQuestionsResponse};
+use crate::suggestion_prefetch::{PrefetchRequest, PrefetchSender};
 use crate::{Result as SearcherResult, SearcherError};
 use anyhow::{Context, Result, anyhow};
 use dashmap::DashSet;
 use futures_util::StreamExt;
 use redis::AsyncCommands;
 use redis::Client as RedisClient;
+use serde::{Deserialize, Serialize};
+use shared::db::repositories::{EmbeddedDocument, SyncRunRepository, WorkspaceRepository};
+use shared::traits::Repository;
 use shared::utils::safe_str_slice;
 use shared::{
-    AIClient, DatabasePool, DocumentRepository, GroupRepository, ObjectStorage, SourceType,
+    AIClient, DatabasePool, Document, DocumentRepository, GroupRepository, ObjectStorage,
+    SourceType, UserRepository,
 };
-use std::collections::HashSet;
+use std::collections::{BTreeSet, HashSet};
 use std::sync::Arc;
+use time::{Duration as TimeDuration, OffsetDateTime};
 use tracing::{debug, error, info, warn};
 
 // Bump the version suffix whenever the generation prompt or validation changes
 // so previously cached (and now undesirable) suggestions are invalidated.
-const REDIS_CACHE_KEY: &str = "suggested_questions:v3";
+const REDIS_CACHE_KEY: &str = "suggested_questions:v4";
+/// Sorted set of user ids, scored by when they last asked for suggestions.
+const ACTIVE_USERS_KEY: &str = "suggested_questions:active_users";
 const CACHE_TTL_SECONDS: u64 = 86400; // 24 hours
 const MAX_RETRIES: usize = 5;
+/// Documents of a topic tried, most central first, before moving on.
+const MAX_DOCUMENTS_PER_TOPIC: usize = 2;
 /// Source types whose documents are predominantly code or technical material and
 /// therefore make poor "Try asking" suggestions (e.g. a GitHub repo yields queries
 /// like "Git repository object storage documentation"). They are excluded from the
@@ -59,12 +83,129 @@ Output only the task instruction itself (or SKIP), with no quotes and no prefix
 Document excerpt:
 {content}"#;
 
+#[derive(Debug, Clone)]
+pub struct SuggestedQuestionsConfig {
+    /// Suggestions generated per user, and so the most topics formed.
+    pub max_questions: usize,
+    /// How far back "recently indexed" reaches.
+    pub recent_days: i64,
+    /// Most recent documents grouped into topics.
+    pub cluster_documents: usize,
+    /// How often suggestions of active users are regenerated.
+    pub refresh_interval_secs: u64,
+    /// Users who asked for suggestions within this many days are active.
+    pub active_user_days: i64,
+}
+
+impl Default for SuggestedQuestionsConfig {
+    fn default() -> Self {
+        Self {
+            max_questions: 12,
+            recent_days: 30,
+            cluster_documents: 300,
+            refresh_interval_secs: 6 * 3600,
+            active_user_days: 7,
+        }
+    }
+}
+
+impl SuggestedQuestionsConfig {
+    pub fn from_env() -> Self {
+        fn env_or<T: std::str::FromStr>(key: &str, default: T) -> T {
+            std::env::var(key)
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default)
+        }
+
+        let defaults = Self::default();
+        Self {
+            max_questions: env_or("SUGGESTED_QUESTIONS_MAX", defaults.max_questions).max(1),
+            recent_days: env_or("SUGGESTED_QUESTIONS_RECENT_DAYS", defaults.recent_days).max(1),
+            cluster_documents: env_or(
+                "SUGGESTED_QUESTIONS_CLUSTER_DOCUMENTS",
+                defaults.cluster_documents,
+            ),
+            refresh_interval_secs: env_or(
+                "SUGGESTED_QUESTIONS_REFRESH_INTERVAL_SECS",
+                defaults.refresh_interval_secs,
+            )
+            .max(60),
+            active_user_days: env_or(
+                "SUGGESTED_QUESTIONS_ACTIVE_USER_DAYS",
+                defaults.active_user_days,
+            )
+            .max(1),
+        }
+    }
+}
+
+/// A user's cached suggestions.
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedSuggestions {
+    questions: Vec<SuggestedQuestion>,
+    /// Sources of the documents the suggestions were drawn from. A sync of
+    /// any of them completing after `generated_at` makes them stale.
+    source_ids: Vec<String>,
+    /// Unix time generation started.
+    generated_at: i64,
+    /// Workspace of the user when generation started.
+    #[serde(default)]
+    workspace_id: String,
+}
+
+/// A document suggestions may be generated from.
+struct Candidate {
+    id: String,
+    source_id: String,
+    title: String,
+    content_id: Option<String>,
+}
+
+impl From<&Document> for Candidate {
+    fn from(doc: &Document) -> Self {
+        Self {
+            id: doc.id.clone(),
+            source_id: doc.source_id.clone(),
+            title: doc.title.clone(),
+            content_id: doc.content_id.clone(),
+        }
+    }
+}
+
+impl From<&EmbeddedDocument> for Candidate {
+    fn from(doc: &EmbeddedDocument) -> Self {
+        Self {
+            id: doc.id.clone(),
+            source_id: doc.source_id.clone(),
+            title: doc.title.clone(),
+            content_id: doc.content_id.clone(),
+        }
+    }
+}
+
+/// Suggestions generated so far for one user.
+struct Generation {
+    questions: Vec<SuggestedQuestion>,
+    source_ids: BTreeSet<String>,
+    started_at: i64,
+    workspace_id: String,
+    // Random fetches across retry attempts can re-draw the same document, and
+    // distinct documents can yield identical questions. Track both so the
+    // suggestions we return stay unique.
+    seen_doc_ids: HashSet<String>,
+    seen_questions: HashSet<String>,
+}
+
+#[derive(Clone)]
 pub struct SuggestedQuestionsGenerator {
     redis_client: RedisClient,
     db_pool: DatabasePool,
     content_storage: Arc<dyn ObjectStorage>,
     ai_client: AIClient,
+    config: SuggestedQuestionsConfig,
     in_flight: Arc<DashSet<String>>,
+    prefetch: Option<PrefetchSender>,
 }
 
 impl SuggestedQuestionsGenerator {
@@ -79,128 +220,285 @@ impl SuggestedQuestionsGenerator {
             db_pool,
             content_storage,
             ai_client,
+            config: SuggestedQuestionsConfig::default(),
             in_flight: Arc::new(DashSet::new()),
+            prefetch: None,
         }
     }
 
+    pub fn with_config(mut self, config: SuggestedQuestionsConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Send newly generated suggestions to be prefetched.
+    pub fn with_prefetch(mut self, prefetch: PrefetchSender) -> Self {
+        self.prefetch = Some(prefetch);
+        self
+    }
+
+    /// A page of the user's cached suggestions. Missing suggestions are
+    /// generated in the background and stale ones regenerated, so a first
+    /// request returns none.
     pub async fn get_suggested_questions(
         &self,
+        user_id: &str,
         user_email: &str,
+        workspace_id: &str,
+        limit: usize,
+        offset: usize,
     ) -> SearcherResult<SuggestedQuestionsResponse> {
         let mut redis = self.redis_client.get_multiplexed_async_connection().await?;
 
-        if let Ok(cached) = redis
-            .get::<_, String>(format!("{}:{}", REDIS_CACHE_KEY, user_email))
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        if let Err(e) = redis
+            .zadd::<_, _, _, ()>(ACTIVE_USERS_KEY, user_id, now)
             .await
         {
-            info!("Cache hit for suggested questions");
-            let response: SuggestedQuestionsResponse =
-                serde_json::from_str(&cached).map_err(|e| SearcherError::Serialization(e))?;
-            return Ok(response);
+            warn!("Failed to record suggested questions activity: {}", e);
         }
 
+        let questions = match Self::cached(&mut redis, user_email, workspace_id)
+            .await
+            .map_err(SearcherError::Serialization)?
+        {
+            Some(cached) => {
+                info!("Cache hit for suggested questions");
+                if self.is_stale(&cached).await {
+                    info!(
+                        "Suggested questions of user {} are stale, regenerating",
+                        user_email
+                    );
+                    self.spawn_generation(user_id, user_email);
+                }
+                cached.questions
+            }
+            None => {
+                info!("Cache miss for suggested questions");
+                self.spawn_generation(user_id, user_email);
+                Vec::new()
+            }
+        };
+
+        Ok(SuggestedQuestionsResponse::page(questions, limit, offset))
+    }
+
+    /// The user's cached suggestions, unless they were drawn from another
+    /// workspace than `workspace_id`.
+    async fn cached(
+        redis: &mut redis::aio::MultiplexedConnection,
+        user_email: &str,
+        workspace_id: &str,
+    ) -> std::result::Result<Option<CachedSuggestions>, serde_json::Error> {
+        let cached: Option<String> = redis
+            .get(format!("{}:{}", REDIS_CACHE_KEY, user_email))
+            .await
+            .unwrap_or_default();
+        let cached: Option<CachedSuggestions> =
+            cached.map(|json| serde_json::from_str(&json)).transpose()?;
+        Ok(cached.filter(|cached| cached.workspace_id == workspace_id))
+    }
+
+    /// Whether a source the suggestions came from synced since.
+    async fn is_stale(&self, cached: &CachedSuggestions) -> bool {
+        let Ok(generated_at) = OffsetDateTime::from_unix_timestamp(cached.generated_at) else {
+            return true;
+        };
+        SyncRunRepository::new(self.db_pool.pool())
+            .any_completed_since(&cached.source_ids, generated_at)
+            .await
+            .unwrap_or_else(|e| {
+                warn!("Failed to check suggested questions freshness: {}", e);
+                false
+            })
+    }
+
+    fn spawn_generation(&self, user_id: &str, user_email: &str) {
         // Check for existing in-flight suggested question generation tasks
-        info!(
-            "Cache miss for suggested questions, checking for in-flight generations for this user before proceeding."
-        );
         if self.in_flight.contains(user_email) {
             info!(
                 "Suggested questions generation already in progress for user {}",
                 user_email
             );
-            return Ok(SuggestedQuestionsResponse { questions: vec![] });
+            return;
         }
 
         info!(
             "No in-flight generation found, starting new generation task for user {}",
             user_email
         );
-        tokio::spawn({
-            let user_email = user_email.to_string();
-            let in_flight = Arc::clone(&self.in_flight);
-            let db_pool = self.db_pool.clone();
-            let redis_client = self.redis_client.clone();
-            let content_storage = self.content_storage.clone();
-            let ai_client = self.ai_client.clone();
-            async move {
-                if in_flight.insert(user_email.clone()) {
-                    match Self::generate_and_cache_questions(
-                        db_pool,
-                        redis_client,
-                        content_storage,
-                        ai_client,
-                        &user_email,
-                    )
-                    .await
-                    {
-                        Ok(count) => {
-                            info!(
-                                "Successfully generated and cached {} suggested questions for user {}",
-                                count, user_email
-                            );
-                            // Remove the user from the in-flight map to allow future requests to go through
-                            in_flight.remove(&user_email);
-                        }
-                        Err(e) => {
-                            error!(
-                                "Failed to generate suggested questions for user {}: {:?}",
-                                user_email, e
-                            );
-                            // Remove the user from the in-flight map to allow future requests to go through
-                            in_flight.remove(&user_email);
-                        }
-                    }
-                } else {
-                    info!(
-                        "Another generation task started for user {} while we were waiting",
-                        user_email
-                    );
+        let generator = self.clone();
+        let user_id = user_id.to_string();
+        let user_email = user_email.to_string();
+        tokio::spawn(async move {
+            generator.generate_for_user(&user_id, &user_email).await;
+        });
+    }
+
+    async fn generate_for_user(&self, user_id: &str, user_email: &str) {
+        if !self.in_flight.insert(user_email.to_string()) {
+            info!(
+                "Another generation task started for user {} while we were waiting",
+                user_email
+            );
+            return;
+        }
+
+        match self.generate_and_cache_questions(user_email).await {
+            Ok(questions) => {
+                info!(
+                    "Successfully generated and cached {} suggested questions for user {}",
+                    questions.len(),
+                    user_email
+                );
+                if let Some(prefetch) = &self.prefetch {
+                    let _ = prefetch.send(PrefetchRequest {
+                        user_id: user_id.to_string(),
+                        user_email: user_email.to_string(),
+                        queries: questions.into_iter().map(|q| q.question).collect(),
+                        ttl_secs: CACHE_TTL_SECONDS,
+                    });
+                }
+            }
+            Err(e) => {
+                error!(
+                    "Failed to generate suggested questions for user {}: {:?}",
+                    user_email, e
+                );
+            }
+        }
+        // Remove the user from the in-flight map to allow future requests to go through
+        self.in_flight.remove(user_email);
+    }
+
+    /// Regenerate, every refresh interval, the suggestions of users who
+    /// asked for them recently and whose suggestions are older than that or
+    /// stale.
+    pub fn start_scheduler(self: &Arc<Self>) {
+        let generator = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(
+                generator.config.refresh_interval_secs,
+            ));
+            // The first tick completes immediately; start one interval in
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                if let Err(e) = generator.refresh_active_users().await {
+                    error!("Failed to refresh suggested questions: {}", e);
                 }
             }
         });
+    }
 
-        Ok(SuggestedQuestionsResponse { questions: vec![] })
+    async fn refresh_active_users(&self) -> Result<()> {
+        let mut redis = self.redis_client.get_multiplexed_async_connection().await?;
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        let active_since = now - self.config.active_user_days * 86400;
+        redis
+            .zrembyscore::<_, _, _, ()>(ACTIVE_USERS_KEY, "-inf", format!("({}", active_since))
+            .await?;
+        let user_ids: Vec<String> = redis
+            .zrangebyscore(ACTIVE_USERS_KEY, active_since, "+inf")
+            .await?;
+
+        let user_repo = UserRepository::new(self.db_pool.pool());
+        let mut refreshed = 0;
+        for user_id in &user_ids {
+            let Some(user) = user_repo.find_by_id(user_id.clone()).await? else {
+                continue;
+            };
+            let due = match Self::cached(&mut redis, &user.email, &user.workspace_id).await {
+                Ok(Some(cached)) => {
+                    now - cached.generated_at >= self.config.refresh_interval_secs as i64
+                        || self.is_stale(&cached).await
+                }
+                Ok(None) | Err(_) => true,
+            };
+            if due {
+                self.generate_for_user(&user.id, &user.email).await;
+                refreshed += 1;
+            }
+        }
+
+        info!(
+            "Refreshed suggested questions of {} of {} active users",
+            refreshed,
+            user_ids.len()
+        );
+        Ok(())
     }
 
     async fn generate_and_cache_questions(
-        db_pool: DatabasePool,
-        redis_client: RedisClient,
-        content_storage: Arc<dyn ObjectStorage>,
-        ai_client: AIClient,
+        &self,
         user_email: &str,
-    ) -> Result<usize> {
-        let mut questions = Vec::new();
-        // Random fetches across retry attempts can re-draw the same document, and
-        // distinct documents can yield identical questions. Track both so the
-        // suggestions we return stay unique.
-        let mut seen_doc_ids: HashSet<String> = HashSet::new();
-        let mut seen_questions: HashSet<String> = HashSet::new();
-        let mut attempts = 0;
+    ) -> Result<Vec<SuggestedQuestion>> {
+        let num_questions = self.config.max_questions;
+        let workspace_id = WorkspaceRepository::new(self.db_pool.pool())
+            .workspace_id_for_email(user_email)
+            .await?;
+        let mut generation = Generation {
+            questions: Vec::new(),
+            source_ids: BTreeSet::new(),
+            started_at: OffsetDateTime::now_utc().unix_timestamp(),
+            workspace_id,
+            seen_doc_ids: HashSet::new(),
+            seen_questions: HashSet::new(),
+        };
+
+        let group_repo = GroupRepository::new(self.db_pool.pool());
+        let user_groups: Vec<String> = group_repo
+            .find_groups_for_user(user_email)
+            .await
+            .unwrap_or_default();
 
-        let num_questions = 9;
+        match self.fetch_topics(user_email, &user_groups).await {
+            Ok(topics) => {
+                info!(
+                    "Grouped recent documents of user {} into {} topic(s)",
+                    user_email,
+                    topics.len()
+                );
+                for topic in topics {
+                    for candidate in &topic {
+                        generation.source_ids.insert(candidate.source_id.clone());
+                    }
+                    if let Err(e) = self
+                        .suggest_from(user_email, topic, &mut generation, 1)
+                        .await
+                    {
+                        warn!("Failed to generate a suggestion for a topic: {}", e);
+                    }
+                    if generation.questions.len() >= num_questions {
+                        break;
+                    }
+                }
+            }
+            Err(e) => warn!(
+                "Failed to group recent documents of user {} into topics: {}",
+                user_email, e
+            ),
+        }
+
+        let mut attempts = 0;
         info!(
             "Beginning suggestion generation loop (target: {} suggestions, max attempts: {})",
             num_questions, MAX_RETRIES
         );
 
-        let doc_repo = DocumentRepository::new(&db_pool.pool());
-        while questions.len() < num_questions && attempts < MAX_RETRIES {
+        let doc_repo = DocumentRepository::new(self.db_pool.pool());
+        while generation.questions.len() < num_questions && attempts < MAX_RETRIES {
             attempts += 1;
-            let needed = num_questions - questions.len();
+            let needed = num_questions - generation.questions.len();
             debug!(
                 "Attempt {}/{}: Need {} more question(s)",
                 attempts, MAX_RETRIES, needed
             );
 
-            let group_repo = GroupRepository::new(db_pool.pool());
-            let user_groups: Vec<String> = group_repo
-                .find_groups_for_user(user_email)
-                .await
-                .unwrap_or_default();
             // Exclude documents already consumed in earlier attempts at the query
             // level, so every fetched document is new and attempts don't spin on
-            // re-drawn rows (the seen_doc_ids guard below stays as a safety net).
-            let already_seen: Vec<String> = seen_doc_ids.iter().cloned().collect();
+            // re-drawn rows (the seen_doc_ids guard stays as a safety net).
+            let already_seen: Vec<String> = generation.seen_doc_ids.iter().cloned().collect();
             match doc_repo
                 .fetch_random_documents(
                     user_email,
@@ -218,118 +516,9 @@ impl SuggestedQuestionsGenerator {
                         num_docs_fetched
                     );
 
-                    let content_ids: Vec<String> =
-                        docs.iter().filter_map(|d| d.content_id.clone()).collect();
-
-                    debug!(
-                        "Fetching content IDs {:?} for generating suggested questions",
-                        content_ids
-                    );
-                    let content_map = content_storage.batch_get_text(content_ids).await?;
-
-                    // Build contents vector in the same order as documents
-                    let contents: Vec<String> = docs
-                        .iter()
-                        .map(|doc| {
-                            let x = doc
-                                .content_id
-                                .as_ref()
-                                .and_then(|cid| content_map.get(cid).cloned())
-                                .with_context(|| {
-                                    format!("Failed to get content for document {}", doc.id)
-                                });
-                            x
-                        })
-                        .collect::<Result<Vec<_>>>()?;
-
-                    for (doc, content) in docs.into_iter().zip(contents) {
-                        // Skip documents already handled in an earlier attempt so we
-                        // neither spend an AI call nor surface a duplicate suggestion.
-                        if !seen_doc_ids.insert(doc.id.clone()) {
-                            continue;
-                        }
-
-                        debug!(
-                            "Processing document {} [id={}] (content length: {} chars)",
-                            doc.title,
-                            doc.id,
-                            content.len()
-                        );
-
-                        // Every third suggestion is a task instruction; the rest are questions.
-                        let use_task_prompt = questions.len() % 3 == 2;
-                        let result = if use_task_prompt {
-                            Self::generate_task_from_document(&ai_client, &doc.id, &content).await
-                        } else {
-                            Self::generate_question_from_document(&ai_client, &doc.id, &content)
-                                .await
-                        };
-                        match result {
-                            Ok(question) => {
-                                // Two different documents can produce the same generic
-                                // suggestion; keep the displayed suggestions distinct.
-                                if !seen_questions.insert(question.to_lowercase()) {
-                                    debug!(
-                                        "Skipping duplicate suggestion text from document {}",
-                                        doc.id
-                                    );
-                                    continue;
-                                }
-                                questions.push(SuggestedQuestion {
-                                    question: question.clone(),
-                                    document_id: doc.id.clone(),
-                                });
-                                info!(
-                                    "Generated suggestion {}/{}: \"{}\" (from document: {})",
-                                    questions.len(),
-                                    num_questions,
-                                    question,
-                                    doc.id
-                                );
-
-                                // Cache the questions
-                                debug!("Serializing {} question(s) to JSON", questions.len());
-                                let response = SuggestedQuestionsResponse {
-                                    questions: questions.clone(),
-                                };
-                                let json_str = serde_json::to_string(&response)
-                                    .context("Failed to serialize questions to JSON")?;
-
-                                debug!("Connecting to Redis to cache questions");
-                                let mut redis_conn = redis_client
-                                    .get_multiplexed_async_connection()
-                                    .await
-                                    .context("Failed to connect to Redis")?;
-
-                                let cache_key = format!("{}:{}", REDIS_CACHE_KEY, user_email);
-                                debug!(
-                                    "Caching questions in Redis with key: {}, TTL: {}s",
-                                    cache_key, CACHE_TTL_SECONDS
-                                );
-                                redis_conn
-                                    .set_ex::<_, _, ()>(cache_key, &json_str, CACHE_TTL_SECONDS)
-                                    .await
-                                    .context("Failed to cache questions in Redis")?;
-
-                                info!(
-                                    "Successfully cached {} suggested suggestion(s) in Redis (TTL: {} hours)",
-                                    response.questions.len(),
-                                    CACHE_TTL_SECONDS / 3600
-                                );
-                            }
-                            Err(e) => {
-                                warn!("Failed to generate suggestion for document {}: {}", doc.id, e);
-                            }
-                        }
-
-                        if questions.len() >= num_questions {
-                            info!(
-                                "Target of {} suggestions reached, stopping generation",
-                                num_questions
-                            );
-                            break;
-                        }
-                    }
+                    let candidates = docs.iter().map(Candidate::from).collect();
+                    self.suggest_from(user_email, candidates, &mut generation, needed)
+                        .await?;
 
                     if num_docs_fetched < needed {
                         debug!(
@@ -348,7 +537,7 @@ impl SuggestedQuestionsGenerator {
             }
         }
 
-        if questions.is_empty() {
+        if generation.questions.is_empty() {
             error!(
                 "Failed to generate any suggestions after {} attempts",
                 attempts
@@ -361,11 +550,187 @@ impl SuggestedQuestionsGenerator {
 
         info!(
             "Suggestion generation complete: {} suggestion(s) generated after {} attempt(s)",
-            questions.len(),
+            generation.questions.len(),
             attempts
         );
 
-        Ok(questions.len())
+        Ok(generation.questions)
+    }
+
+    /// The user's recently indexed documents grouped into topics, largest
+    /// first, each holding its most central documents.
+    async fn fetch_topics(
+        &self,
+        user_email: &str,
+        user_groups: &[String],
+    ) -> Result<Vec<Vec<Candidate>>> {
+        let since = OffsetDateTime::now_utc() - TimeDuration::days(self.config.recent_days);
+        let docs = DocumentRepository::new(self.db_pool.pool())
+            .fetch_recent_embedded_documents(
+                user_email,
+                user_groups,
+                since,
+                self.config.cluster_documents,
+                SUGGESTION_EXCLUDED_SOURCE_TYPES,
+            )
+            .await?;
+
+        // Embeddings of different models are not comparable; keep those of
+        // the model that embedded the newest document
+        let Some(model_name) = docs.first().map(|d| d.model_name.clone()) else {
+            return Ok(Vec::new());
+        };
+        let docs: Vec<EmbeddedDocument> = docs
+            .into_iter()
+            .filter(|d| d.model_name == model_name)
+            .collect();
+
+        let embeddings: Vec<Vec<f32>> = docs.iter().map(|d| d.embedding.to_vec()).collect();
+        Ok(
+            document_clusters::cluster(&embeddings, self.config.max_questions)
+                .into_iter()
+                .map(|cluster| {
+                    cluster
+                        .members
+                        .into_iter()
+                        .take(MAX_DOCUMENTS_PER_TOPIC)
+                        .map(|i| Candidate::from(&docs[i]))
+                        .collect()
+                })
+                .collect(),
+        )
+    }
+
+    /// Generate up to `max_new` suggestions from `candidates` in order,
+    /// caching the user's suggestions after each one.
+    async fn suggest_from(
+        &self,
+        user_email: &str,
+        candidates: Vec<Candidate>,
+        generation: &mut Generation,
+        max_new: usize,
+    ) -> Result<()> {
+        let content_ids: Vec<String> = candidates
+            .iter()
+            .filter_map(|d| d.content_id.clone())
+            .collect();
+
+        debug!(
+            "Fetching content IDs {:?} for generating suggested questions",
+            content_ids
+        );
+        let content_map = self.content_storage.batch_get_text(content_ids).await?;
+
+        // Build contents vector in the same order as documents
+        let contents: Vec<String> = candidates
+            .iter()
+            .map(|doc| {
+                doc.content_id
+                    .as_ref()
+                    .and_then(|cid| content_map.get(cid).cloned())
+                    .with_context(|| format!("Failed to get content for document {}", doc.id))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut added = 0;
+        for (doc, content) in candidates.into_iter().zip(contents) {
+            // Skip documents already handled in an earlier attempt so we
+            // neither spend an AI call nor surface a duplicate suggestion.
+            if !generation.seen_doc_ids.insert(doc.id.clone()) {
+                continue;
+            }
+
+            debug!(
+                "Processing document {} [id={}] (content length: {} chars)",
+                doc.title,
+                doc.id,
+                content.len()
+            );
+
+            // Every third suggestion is a task instruction; the rest are questions.
+            let use_task_prompt = generation.questions.len() % 3 == 2;
+            let result = if use_task_prompt {
+                Self::generate_task_from_document(&self.ai_client, &doc.id, &content).await
+            } else {
+                Self::generate_question_from_document(&self.ai_client, &doc.id, &content).await
+            };
+            match result {
+                Ok(question) => {
+                    // Two different documents can produce the same generic
+                    // suggestion; keep the displayed suggestions distinct.
+                    if !generation.seen_questions.insert(question.to_lowercase()) {
+                        debug!(
+                            "Skipping duplicate suggestion text from document {}",
+                            doc.id
+                        );
+                        continue;
+                    }
+                    generation.questions.push(SuggestedQuestion {
+                        question: question.clone(),
+                        document_id: doc.id.clone(),
+                    });
+                    generation.source_ids.insert(doc.source_id);
+                    added += 1;
+                    info!(
+                        "Generated suggestion {}/{}: \"{}\" (from document: {})",
+                        generation.questions.len(),
+                        self.config.max_questions,
+                        question,
+                        doc.id
+                    );
+
+                    self.cache(user_email, generation).await?;
+                }
+                Err(e) => {
+                    warn!("Failed to generate suggestion for document {}: {}", doc.id, e);
+                }
+            }
+
+            if added >= max_new || generation.questions.len() >= self.config.max_questions {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn cache(&self, user_email: &str, generation: &Generation) -> Result<()> {
+        debug!(
+            "Serializing {} question(s) to JSON",
+            generation.questions.len()
+        );
+        let cached = CachedSuggestions {
+            questions: generation.questions.clone(),
+            source_ids: generation.source_ids.iter().cloned().collect(),
+            generated_at: generation.started_at,
+            workspace_id: generation.workspace_id.clone(),
+        };
+        let json_str =
+            serde_json::to_string(&cached).context("Failed to serialize questions to JSON")?;
+
+        debug!("Connecting to Redis to cache questions");
+        let mut redis_conn = self
+            .redis_client
+            .get_multiplexed_async_connection()
+            .await
+            .context("Failed to connect to Redis")?;
+
+        let cache_key = format!("{}:{}", REDIS_CACHE_KEY, user_email);
+        debug!(
+            "Caching questions in Redis with key: {}, TTL: {}s",
+            cache_key, CACHE_TTL_SECONDS
+        );
+        redis_conn
+            .set_ex::<_, _, ()>(cache_key, &json_str, CACHE_TTL_SECONDS)
+            .await
+            .context("Failed to cache questions in Redis")?;
+
+        info!(
+            "Successfully cached {} suggested suggestion(s) in Redis (TTL: {} hours)",
+            cached.questions.len(),
+            CACHE_TTL_SECONDS / 3600
+        );
+        Ok(())
     }
 
     async fn generate_suggestion_from_document(
@@ -0,0 +1,147 @@
+//! Warming the caches behind suggested questions.
+//!
+//! A suggestion on the home page is a query nobody has searched yet, so
+//! clicking one would otherwise wait on a cold query embedding and a full
+//! search. Once a user's suggestions are generated, a background worker
+//! embeds each of them into the query embedding cache and, optionally, runs
+//! the search the web app will send for it so the results are already in the
+//! search cache.
+
+use crate::AppState;
+use crate::models::{SearchMode, SearchRequest};
+use crate::search::SearchEngine;
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
+use tracing::{debug, error, info, warn};
+
+/// Results per page of the web app's search page, which prefetched searches
+/// must match to share its cache entries.
+const RESULTS_PAGE_SIZE: i64 = 20;
+
+#[derive(Debug, Clone)]
+pub struct SuggestionPrefetchConfig {
+    pub embeddings: bool,
+    /// Also run each suggestion's search. Results stay cached for
+    /// `SEARCH_CACHE_TTL_SECS`, so this only pays off when suggestions are
+    /// clicked soon after they are generated.
+    pub results: bool,
+}
+
+impl Default for SuggestionPrefetchConfig {
+    fn default() -> Self {
+        Self {
+            embeddings: true,
+            results: false,
+        }
+    }
+}
+
+impl SuggestionPrefetchConfig {
+    pub fn from_env() -> Self {
+        fn env_or<T: std::str::FromStr>(key: &str, default: T) -> T {
+            std::env::var(key)
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default)
+        }
+
+        let defaults = Self::default();
+        Self {
+            embeddings: env_or("SUGGESTION_PREFETCH_EMBEDDINGS", defaults.embeddings),
+            results: env_or("SUGGESTION_PREFETCH_RESULTS", defaults.results),
+        }
+    }
+}
+
+/// Suggestions just generated for a user.
+#[derive(Debug, Clone)]
+pub struct PrefetchRequest {
+    pub user_id: String,
+    pub user_email: String,
+    pub queries: Vec<String>,
+    /// How long the suggestions themselves stay cached.
+    pub ttl_secs: u64,
+}
+
+pub type PrefetchSender = UnboundedSender<PrefetchRequest>;
+
+/// Prefetch suggestions sent on `requests`, one user at a time.
+pub fn spawn_worker(
+    state: AppState,
+    config: SuggestionPrefetchConfig,
+    mut requests: UnboundedReceiver<PrefetchRequest>,
+) {
+    tokio::spawn(async move {
+        while let Some(request) = requests.recv().await {
+            if !config.embeddings && !config.results {
+                continue;
+            }
+            if let Err(e) = prefetch(&state, &config, &request).await {
+                error!(
+                    "Failed to prefetch suggestions for user {}: {}",
+                    request.user_email, e
+                );
+            }
+        }
+    });
+}
+
+async fn prefetch(
+    state: &AppState,
+    config: &SuggestionPrefetchConfig,
+    request: &PrefetchRequest,
+) -> anyhow::Result<()> {
+    let search_engine = SearchEngine::new(
+        state.db_pool.clone(),
+        state.redis_client.clone(),
+        state.ai_client.clone(),
+        state.config.clone(),
+        state.operator_registry.clone(),
+        state.source_router.clone(),
+        state.query_intent.clone(),
+        state.query_language.clone(),
+        state.query_understanding.clone(),
+        state.sla_monitor.clone(),
+        state.spell_checker.clone(),
+        state.learned_ranker.clone(),
+    )
+    .await?;
+    let prefetch_results = config.results && state.config.search_cache_ttl_secs > 0;
+
+    let mut embedded = 0;
+    let mut searched = 0;
+    for query in &request.queries {
+        // Searching embeds the query too, reading the embedding cached here
+        if config.embeddings {
+            match search_engine
+                .prefetch_query_embedding(query, request.ttl_secs)
+                .await
+            {
+                Ok(()) => embedded += 1,
+                Err(e) => warn!("Failed to prefetch embedding of '{}': {}", query, e),
+            }
+        }
+
+        if prefetch_results {
+            let search = SearchRequest {
+                query: query.clone(),
+                limit: Some(RESULTS_PAGE_SIZE),
+                offset: Some(0),
+                mode: Some(SearchMode::Hybrid),
+                user_id: Some(request.user_id.clone()),
+                user_email: Some(request.user_email.clone()),
+                ..Default::default()
+            };
+            match search_engine.search(search).await {
+                Ok(_) => searched += 1,
+                Err(e) => warn!("Failed to prefetch results of '{}': {}", query, e),
+            }
+        }
+        debug!("Prefetched suggestion '{}'", query);
+    }
+
+    info!(
+        "Prefetched {} suggestion embedding(s) and {} search(es) for user {}",
+        embedded, searched, request.user_email
+    );
+    Ok(())
+}
@@ -1,24 +1,142 @@
+//! In-memory title index behind document typeahead.
+//!
+//! Every word-suffix of each normalized title is a key of an FST, so a query
+//! matches titles containing a word starting with it. When too few titles
+//! match exactly, the same FST is searched again allowing one or two edits,
+//! depending on the query's length. Entries carry their document's
+//! permissions so suggestions are trimmed per user before ranking.
+//!
+//! The indexer publishes which documents changed (see
+//! [`shared::typeahead_updates`]), and only those are reloaded. Changed
+//! entries are kept beside the FST and scanned until enough accumulate to
+//! rebuild it from memory. A periodic full refresh from the database covers
+//! notifications missed while unsubscribed.
+
+use anyhow::Result;
 use fst::automaton::Str;
 use fst::{Automaton, IntoStreamer, Map, MapBuilder, Streamer};
-use shared::{DatabasePool, DocumentRepository};
-use std::collections::HashSet;
+use futures_util::StreamExt;
+use serde::Deserialize;
+use shared::typeahead_updates::{TYPEAHEAD_UPDATES_CHANNEL, TitleUpdate};
+use shared::{DatabasePool, DocumentRepository, TitleEntry};
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
-use tokio::sync::RwLock;
-use tracing::{error, info};
+use std::time::Duration;
+use tokio::sync::{Mutex, RwLock};
+use tracing::{debug, error, info, warn};
 
 use crate::models::TypeaheadResult;
 
+/// Entries changed since the FST was built, beyond which it is rebuilt.
+const MAX_PENDING_ENTRIES: usize = 1024;
+/// Queries shorter than this only match exactly.
+const FUZZY_MIN_CHARS: usize = 4;
+/// Queries at least this long may match with two edits instead of one.
+const FUZZY_TWO_EDITS_MIN_CHARS: usize = 8;
+const FUZZY_MATCH_SCORE: i64 = 5_000;
+const UPDATE_LISTENER_RETRY_DELAY: Duration = Duration::from_secs(5);
+
 pub struct TypeaheadEntry {
     pub document_id: String,
+    pub external_id: String,
     pub title: String,
     pub url: Option<String>,
     pub source_id: String,
+    normalized: String,
+    permissions: EntryPermissions,
+}
+
+impl TypeaheadEntry {
+    fn from_row(row: TitleEntry) -> Option<Self> {
+        let normalized = normalize(&row.title);
+        if normalized.is_empty() {
+            return None;
+        }
+        Some(Self {
+            document_id: row.id,
+            external_id: row.external_id,
+            title: row.title,
+            url: row.url,
+            source_id: row.source_id,
+            normalized,
+            permissions: EntryPermissions::parse(row.permissions),
+        })
+    }
+}
+
+/// Principals that may read a document, lowercased. Unparseable
+/// permissions grant nobody.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct EntryPermissions {
+    public: bool,
+    users: Vec<String>,
+    groups: Vec<String>,
+}
+
+impl EntryPermissions {
+    fn parse(value: serde_json::Value) -> Self {
+        let mut permissions: Self = serde_json::from_value(value).unwrap_or_default();
+        for principal in permissions
+            .users
+            .iter_mut()
+            .chain(permissions.groups.iter_mut())
+        {
+            *principal = principal.to_lowercase();
+        }
+        permissions
+    }
+}
+
+/// Who suggestions are for: the same principals the search permission
+/// filter grants (public documents, the user, their email domain and their
+/// groups). Workspace boundaries are left to the database check that follows.
+pub struct Viewer {
+    email: Option<String>,
+    groups: HashSet<String>,
+}
+
+impl Viewer {
+    /// Sees public documents only.
+    pub fn anonymous() -> Self {
+        Self {
+            email: None,
+            groups: HashSet::new(),
+        }
+    }
+
+    pub fn user(email: &str, groups: &[String]) -> Self {
+        let email = email.to_lowercase();
+        let mut principals: HashSet<String> = groups.iter().map(|g| g.to_lowercase()).collect();
+        if let Some(domain) = email.split('@').nth(1).filter(|d| !d.is_empty()) {
+            principals.insert(domain.to_string());
+        }
+        Self {
+            email: Some(email),
+            groups: principals,
+        }
+    }
+
+    fn can_read(&self, permissions: &EntryPermissions) -> bool {
+        permissions.public
+            || self
+                .email
+                .as_ref()
+                .is_some_and(|email| permissions.users.contains(email))
+            || permissions.groups.iter().any(|g| self.groups.contains(g))
+    }
 }
 
 struct TitleData {
+    /// Every word-suffix of the titles built in, mapped to its entry's slot.
     fst: Map<Vec<u8>>,
-    entries: Vec<TypeaheadEntry>,
-    normalized_titles: Vec<String>,
+    /// Entries by slot; `None` once removed or replaced.
+    entries: Vec<Option<Arc<TypeaheadEntry>>>,
+    /// Slot of each live entry by (source id, external id).
+    slots: HashMap<(String, String), usize>,
+    /// Slots added since the FST was built, matched by scanning.
+    pending: Vec<usize>,
+    removed: usize,
 }
 
 impl TitleData {
@@ -28,54 +146,21 @@ impl TitleData {
         Self {
             fst,
             entries: Vec::new(),
-            normalized_titles: Vec::new(),
+            slots: HashMap::new(),
+            pending: Vec::new(),
+            removed: 0,
         }
     }
-}
-
-#[derive(Clone)]
-pub struct TitleIndex {
-    data: Arc<RwLock<TitleData>>,
-    db_pool: DatabasePool,
-}
 
-impl TitleIndex {
-    pub fn new(db_pool: DatabasePool) -> Self {
-        Self {
-            data: Arc::new(RwLock::new(TitleData::empty())),
-            db_pool,
-        }
-    }
-
-    pub async fn refresh(&self) -> anyhow::Result<()> {
-        let repo = DocumentRepository::new(self.db_pool.pool());
-        let rows = repo.fetch_all_title_entries().await?;
-
-        let mut entries: Vec<TypeaheadEntry> = Vec::with_capacity(rows.len());
-        let mut normalized_titles: Vec<String> = Vec::with_capacity(rows.len());
+    fn build(entries: Vec<Arc<TypeaheadEntry>>) -> Result<Self> {
         let mut keys: Vec<(Vec<u8>, u64)> = Vec::new();
+        let mut slots = HashMap::with_capacity(entries.len());
 
-        for row in rows {
-            let normalized = normalize(&row.title);
-            if normalized.is_empty() {
-                continue;
-            }
-            let idx = entries.len() as u32;
-            entries.push(TypeaheadEntry {
-                document_id: row.id,
-                title: row.title,
-                url: row.url,
-                source_id: row.source_id,
-            });
-            normalized_titles.push(normalized.clone());
-
-            for word_start in std::iter::once(0).chain(
-                normalized
-                    .char_indices()
-                    .filter(|(_, c)| *c == ' ')
-                    .map(|(i, _)| i + 1),
-            ) {
-                let suffix = &normalized[word_start..];
+        for (slot, entry) in entries.iter().enumerate() {
+            slots.insert((entry.source_id.clone(), entry.external_id.clone()), slot);
+            let idx = slot as u32;
+            for word_start in word_starts(&entry.normalized) {
+                let suffix = &entry.normalized[word_start..];
                 let mut key = Vec::with_capacity(suffix.len() + 1 + 4);
                 key.extend_from_slice(suffix.as_bytes());
                 key.push(0x00);
@@ -90,40 +175,111 @@ impl TitleIndex {
         for (key, idx) in &keys {
             builder.insert(key, *idx)?;
         }
-        let fst = builder.into_map();
 
-        let new_data = TitleData {
-            fst,
-            entries,
-            normalized_titles,
-        };
-        let mut data = self.data.write().await;
-        *data = new_data;
+        Ok(Self {
+            fst: builder.into_map(),
+            entries: entries.into_iter().map(Some).collect(),
+            slots,
+            pending: Vec::new(),
+            removed: 0,
+        })
+    }
 
-        info!("Typeahead index refreshed with {} entries", keys.len());
-        Ok(())
+    fn live_entries(&self) -> Vec<Arc<TypeaheadEntry>> {
+        self.entries.iter().flatten().cloned().collect()
     }
 
-    pub async fn search(&self, query: &str, limit: usize) -> Vec<TypeaheadResult> {
-        let normalized = normalize(query);
-        if normalized.is_empty() {
-            return Vec::new();
+    fn insert(&mut self, entry: Arc<TypeaheadEntry>) {
+        self.remove(&entry.source_id, &entry.external_id);
+        let slot = self.entries.len();
+        self.slots
+            .insert((entry.source_id.clone(), entry.external_id.clone()), slot);
+        self.entries.push(Some(entry));
+        self.pending.push(slot);
+    }
+
+    fn remove(&mut self, source_id: &str, external_id: &str) {
+        if let Some(slot) = self
+            .slots
+            .remove(&(source_id.to_string(), external_id.to_string()))
+        {
+            self.entries[slot] = None;
+            self.removed += 1;
+        }
+    }
+
+    fn remove_source(&mut self, source_id: &str) {
+        let external_ids: Vec<String> = self
+            .slots
+            .keys()
+            .filter(|(s, _)| s == source_id)
+            .map(|(_, external_id)| external_id.clone())
+            .collect();
+        for external_id in external_ids {
+            self.remove(source_id, &external_id);
         }
+    }
 
-        let data = self.data.read().await;
-        let automaton = Str::new(&normalized).starts_with();
-        let mut stream = data.fst.search(automaton).into_stream();
+    fn needs_compaction(&self) -> bool {
+        self.pending.len() > MAX_PENDING_ENTRIES
+            || self.removed > MAX_PENDING_ENTRIES.max(self.entries.len() / 4)
+    }
 
+    fn readable(&self, slot: usize, viewer: &Viewer) -> Option<&TypeaheadEntry> {
+        self.entries
+            .get(slot)?
+            .as_deref()
+            .filter(|entry| viewer.can_read(&entry.permissions))
+    }
+
+    /// Best `limit` titles `viewer` may read for an already normalized
+    /// query. Fuzzy matches only fill in when there are too few prefix
+    /// matches, and always rank below them.
+    fn search(&self, query: &str, viewer: &Viewer, limit: usize) -> Vec<TypeaheadResult> {
         let mut seen = HashSet::new();
         let mut candidates: Vec<(i64, usize)> = Vec::new();
+
+        let mut stream = self.fst.search(Str::new(query).starts_with()).into_stream();
         while let Some((_key_bytes, idx)) = stream.next() {
             let idx = idx as usize;
             if !seen.insert(idx) {
                 continue;
             }
-            if let Some(full_title) = data.normalized_titles.get(idx) {
-                let score = score_match(&normalized, full_title);
-                candidates.push((score, idx));
+            if let Some(entry) = self.readable(idx, viewer) {
+                candidates.push((score_match(query, &entry.normalized), idx));
+            }
+        }
+        for &idx in &self.pending {
+            if let Some(entry) = self.readable(idx, viewer)
+                && word_starts(&entry.normalized)
+                    .any(|start| entry.normalized[start..].starts_with(query))
+            {
+                seen.insert(idx);
+                candidates.push((score_match(query, &entry.normalized), idx));
+            }
+        }
+
+        let max_distance = fuzzy_distance(query);
+        if candidates.len() < limit && max_distance > 0 {
+            let automaton = FuzzyPrefix {
+                query: query.as_bytes(),
+                max_distance,
+            };
+            let mut stream = self.fst.search(automaton).into_stream();
+            let mut fuzzy_idxs = Vec::new();
+            while let Some((_key_bytes, idx)) = stream.next() {
+                fuzzy_idxs.push(idx as usize);
+            }
+            for idx in fuzzy_idxs.into_iter().chain(self.pending.iter().copied()) {
+                if seen.contains(&idx) {
+                    continue;
+                }
+                if let Some(entry) = self.readable(idx, viewer)
+                    && let Some(score) = score_fuzzy_match(query, &entry.normalized, max_distance)
+                {
+                    seen.insert(idx);
+                    candidates.push((score, idx));
+                }
             }
         }
 
@@ -133,7 +289,7 @@ impl TitleIndex {
             .iter()
             .take(limit)
             .filter_map(|(_, idx)| {
-                data.entries.get(*idx).map(|entry| TypeaheadResult {
+                self.entries[*idx].as_ref().map(|entry| TypeaheadResult {
                     document_id: entry.document_id.clone(),
                     title: entry.title.clone(),
                     url: entry.url.clone(),
@@ -142,6 +298,89 @@ impl TitleIndex {
             })
             .collect()
     }
+}
+
+#[derive(Clone)]
+pub struct TitleIndex {
+    data: Arc<RwLock<TitleData>>,
+    /// Serializes refreshes and updates. Rebuilds happen outside the data
+    /// lock, so without it an update applied meanwhile would be lost.
+    writer: Arc<Mutex<()>>,
+    db_pool: DatabasePool,
+}
+
+impl TitleIndex {
+    pub fn new(db_pool: DatabasePool) -> Self {
+        Self {
+            data: Arc::new(RwLock::new(TitleData::empty())),
+            writer: Arc::new(Mutex::new(())),
+            db_pool,
+        }
+    }
+
+    pub async fn refresh(&self) -> anyhow::Result<()> {
+        let _writer = self.writer.lock().await;
+        let repo = DocumentRepository::new(self.db_pool.pool());
+        let rows = repo.fetch_all_title_entries().await?;
+
+        let entries: Vec<Arc<TypeaheadEntry>> = rows
+            .into_iter()
+            .filter_map(TypeaheadEntry::from_row)
+            .map(Arc::new)
+            .collect();
+        let new_data = TitleData::build(entries)?;
+        let entry_count = new_data.slots.len();
+        let mut data = self.data.write().await;
+        *data = new_data;
+
+        info!("Typeahead index refreshed with {} entries", entry_count);
+        Ok(())
+    }
+
+    /// Reload the documents `update` names, dropping those that no longer
+    /// exist.
+    pub async fn apply_update(&self, update: &TitleUpdate) -> anyhow::Result<()> {
+        let _writer = self.writer.lock().await;
+        let rows = DocumentRepository::new(self.db_pool.pool())
+            .fetch_source_title_entries(&update.source_id, update.external_ids.as_deref())
+            .await?;
+
+        let needs_compaction = {
+            let mut data = self.data.write().await;
+            match &update.external_ids {
+                Some(external_ids) => {
+                    for external_id in external_ids {
+                        data.remove(&update.source_id, external_id);
+                    }
+                }
+                None => data.remove_source(&update.source_id),
+            }
+            for entry in rows.into_iter().filter_map(TypeaheadEntry::from_row) {
+                data.insert(Arc::new(entry));
+            }
+            data.needs_compaction()
+        };
+
+        if needs_compaction {
+            let entries = self.data.read().await.live_entries();
+            let compacted = TitleData::build(entries)?;
+            debug!(
+                "Typeahead index compacted to {} entries",
+                compacted.slots.len()
+            );
+            *self.data.write().await = compacted;
+        }
+        Ok(())
+    }
+
+    pub async fn search(&self, query: &str, viewer: &Viewer, limit: usize) -> Vec<TypeaheadResult> {
+        let normalized = normalize(query);
+        if normalized.is_empty() {
+            return Vec::new();
+        }
+
+        self.data.read().await.search(&normalized, viewer, limit)
+    }
 
     pub fn start_background_refresh(self: &Arc<Self>, interval_secs: u64) {
         let index = Arc::clone(self);
@@ -155,6 +394,172 @@ impl TitleIndex {
             }
         });
     }
+
+    /// Apply the indexer's title updates as they are published. After a
+    /// lost subscription the index is refreshed in full, since updates
+    /// published meanwhile were missed.
+    pub fn start_update_listener(self: &Arc<Self>, redis_client: redis::Client) {
+        let index = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut resubscribing = false;
+            loop {
+                match redis_client.get_async_pubsub().await {
+                    Ok(mut pubsub) => match pubsub.subscribe(TYPEAHEAD_UPDATES_CHANNEL).await {
+                        Ok(()) => {
+                            if resubscribing && let Err(e) = index.refresh().await {
+                                error!("Failed to refresh typeahead index: {}", e);
+                            }
+                            resubscribing = true;
+                            let mut messages = pubsub.on_message();
+                            while let Some(message) = messages.next().await {
+                                index.handle_update_message(&message).await;
+                            }
+                            warn!("Typeahead update subscription closed");
+                        }
+                        Err(e) => error!("Failed to subscribe to typeahead updates: {}", e),
+                    },
+                    Err(e) => error!("Failed to connect for typeahead updates: {}", e),
+                }
+                tokio::time::sleep(UPDATE_LISTENER_RETRY_DELAY).await;
+            }
+        });
+    }
+
+    async fn handle_update_message(&self, message: &redis::Msg) {
+        let update = message
+            .get_payload::<String>()
+            .map_err(anyhow::Error::from)
+            .and_then(|payload| Ok(serde_json::from_str::<TitleUpdate>(&payload)?));
+        match update {
+            Ok(update) => {
+                if let Err(e) = self.apply_update(&update).await {
+                    error!(
+                        "Failed to apply typeahead update for source {}: {}",
+                        update.source_id, e
+                    );
+                }
+            }
+            Err(e) => warn!("Ignoring malformed typeahead update: {}", e),
+        }
+    }
+}
+
+/// Byte offsets at which the words of a normalized title start.
+fn word_starts(normalized: &str) -> impl Iterator<Item = usize> + '_ {
+    std::iter::once(0).chain(
+        normalized
+            .char_indices()
+            .filter(|(_, c)| *c == ' ')
+            .map(|(i, _)| i + 1),
+    )
+}
+
+/// Edits a query may be away from a title, by its length.
+fn fuzzy_distance(query: &str) -> usize {
+    match query.chars().count() {
+        n if n < FUZZY_MIN_CHARS => 0,
+        n if n < FUZZY_TWO_EDITS_MIN_CHARS => 1,
+        _ => 2,
+    }
+}
+
+/// Matches keys with a prefix within `max_distance` edits of `query`.
+/// Edits are counted in bytes, so a substituted non-ASCII character may
+/// count twice.
+struct FuzzyPrefix<'a> {
+    query: &'a [u8],
+    max_distance: usize,
+}
+
+enum FuzzyState {
+    /// Edit distances from each prefix of the query to the key so far.
+    Row(Vec<usize>),
+    Matched,
+}
+
+impl FuzzyPrefix<'_> {
+    fn settle(&self, row: Vec<usize>) -> FuzzyState {
+        if row[self.query.len()] <= self.max_distance {
+            FuzzyState::Matched
+        } else {
+            FuzzyState::Row(row)
+        }
+    }
+}
+
+impl Automaton for FuzzyPrefix<'_> {
+    type State = FuzzyState;
+
+    fn start(&self) -> FuzzyState {
+        self.settle((0..=self.query.len()).collect())
+    }
+
+    fn is_match(&self, state: &FuzzyState) -> bool {
+        matches!(state, FuzzyState::Matched)
+    }
+
+    fn can_match(&self, state: &FuzzyState) -> bool {
+        match state {
+            FuzzyState::Matched => true,
+            FuzzyState::Row(row) => row.iter().min().is_some_and(|d| *d <= self.max_distance),
+        }
+    }
+
+    fn will_always_match(&self, state: &FuzzyState) -> bool {
+        self.is_match(state)
+    }
+
+    fn accept(&self, state: &FuzzyState, byte: u8) -> FuzzyState {
+        match state {
+            FuzzyState::Matched => FuzzyState::Matched,
+            FuzzyState::Row(row) => self.settle(next_edit_row(self.query, row, byte)),
+        }
+    }
+}
+
+fn next_edit_row(query: &[u8], row: &[usize], byte: u8) -> Vec<usize> {
+    let mut next = Vec::with_capacity(row.len());
+    next.push(row[0] + 1);
+    for (i, &q) in query.iter().enumerate() {
+        let substitution = row[i] + usize::from(q != byte);
+        next.push(substitution.min(row[i + 1] + 1).min(next[i] + 1));
+    }
+    next
+}
+
+/// Fewest edits turning `query` into some prefix of `text`.
+fn prefix_distance(query: &[u8], text: &[u8]) -> usize {
+    let mut row: Vec<usize> = (0..=query.len()).collect();
+    let mut best = row[query.len()];
+    for &byte in text {
+        row = next_edit_row(query, &row, byte);
+        best = best.min(row[query.len()]);
+        // Row minimums never decrease, so nothing later can do better
+        if row.iter().min().is_some_and(|d| *d >= best) {
+            break;
+        }
+    }
+    best
+}
+
+fn score_fuzzy_match(query: &str, title: &str, max_distance: usize) -> Option<i64> {
+    let (distance, start) = word_starts(title)
+        .map(|start| {
+            (
+                prefix_distance(query.as_bytes(), &title.as_bytes()[start..]),
+                start,
+            )
+        })
+        .min()?;
+    if distance > max_distance {
+        return None;
+    }
+
+    let mut score = FUZZY_MATCH_SCORE - 1_000 * distance as i64;
+    if start == 0 {
+        score += 500;
+    }
+    Some(score - title.len() as i64)
 }
 
 pub fn normalize(title: &str) -> String {
@@ -402,4 +807,134 @@ mod tests {
             "multi-word prefix match should score >= 10,000, got {score}"
         );
     }
+
+    fn entry(
+        external_id: &str,
+        title: &str,
+        permissions: serde_json::Value,
+    ) -> Arc<TypeaheadEntry> {
+        Arc::new(
+            TypeaheadEntry::from_row(TitleEntry {
+                id: format!("doc-{external_id}"),
+                external_id: external_id.to_string(),
+                title: title.to_string(),
+                url: None,
+                source_id: "source".to_string(),
+                permissions,
+            })
+            .unwrap(),
+        )
+    }
+
+    fn public() -> serde_json::Value {
+        serde_json::json!({"public": true, "users": [], "groups": []})
+    }
+
+    fn titles(results: Vec<TypeaheadResult>) -> Vec<String> {
+        results.into_iter().map(|r| r.title).collect()
+    }
+
+    #[test]
+    fn test_prefix_distance() {
+        assert_eq!(prefix_distance(b"budget", b"budget q4"), 0);
+        assert_eq!(prefix_distance(b"budgt", b"budget q4"), 1);
+        assert_eq!(prefix_distance(b"budgte", b"budget q4"), 1);
+        assert_eq!(prefix_distance(b"bduget", b"budget q4"), 2);
+        assert_eq!(prefix_distance(b"roadmap", b"budget q4"), 6);
+    }
+
+    #[test]
+    fn test_fuzzy_matches_fill_in_below_prefix_matches() {
+        let data = TitleData::build(vec![
+            entry("1", "Quarterly Budget", public()),
+            entry("2", "Budgeting Handbook", public()),
+        ])
+        .unwrap();
+        let viewer = Viewer::anonymous();
+
+        assert_eq!(
+            titles(data.search("budgte", &viewer, 5)),
+            vec!["Budgeting Handbook", "Quarterly Budget"]
+        );
+        assert_eq!(
+            titles(data.search("budgeti", &viewer, 5)),
+            vec!["Budgeting Handbook", "Quarterly Budget"],
+            "the exact prefix match should rank above the fuzzy one"
+        );
+        assert!(data.search("bdgt", &viewer, 5).is_empty());
+        assert!(
+            data.search("bud", &viewer, 5).len() == 2,
+            "short queries still match by prefix"
+        );
+    }
+
+    #[test]
+    fn test_updates_apply_before_and_after_compaction() {
+        let mut data = TitleData::build(vec![
+            entry("1", "Quarterly Budget", public()),
+            entry("2", "Hiring Plan", public()),
+        ])
+        .unwrap();
+        let viewer = Viewer::anonymous();
+
+        data.insert(entry("1", "Annual Report", public()));
+        data.insert(entry("3", "Annual Budget", public()));
+        data.remove("source", "2");
+        assert_eq!(
+            titles(data.search("budget", &viewer, 5)),
+            vec!["Annual Budget"]
+        );
+        assert_eq!(
+            titles(data.search("annual", &viewer, 5)),
+            vec!["Annual Report", "Annual Budget"]
+        );
+        assert!(data.search("hiring", &viewer, 5).is_empty());
+
+        let data = TitleData::build(data.live_entries()).unwrap();
+        assert!(data.pending.is_empty());
+        assert_eq!(
+            titles(data.search("budget", &viewer, 5)),
+            vec!["Annual Budget"]
+        );
+        assert!(data.search("hiring", &viewer, 5).is_empty());
+    }
+
+    #[test]
+    fn test_search_only_returns_titles_the_viewer_can_read() {
+        let data = TitleData::build(vec![
+            entry("1", "Public Plan", public()),
+            entry(
+                "2",
+                "Alice Plan",
+                serde_json::json!({"public": false, "users": ["Alice@Example.com"], "groups": []}),
+            ),
+            entry(
+                "3",
+                "Team Plan",
+                serde_json::json!({"public": false, "users": [], "groups": ["eng@example.com"]}),
+            ),
+            entry(
+                "4",
+                "Company Plan",
+                serde_json::json!({"public": false, "users": [], "groups": ["example.com"]}),
+            ),
+            entry("5", "Broken Plan", serde_json::json!("not permissions")),
+        ])
+        .unwrap();
+
+        let mut anonymous = titles(data.search("plan", &Viewer::anonymous(), 10));
+        anonymous.sort();
+        assert_eq!(anonymous, vec!["Public Plan"]);
+
+        let alice = Viewer::user("alice@example.com", &["ENG@example.com".to_string()]);
+        let mut visible = titles(data.search("plan", &alice, 10));
+        visible.sort();
+        assert_eq!(
+            visible,
+            vec!["Alice Plan", "Company Plan", "Public Plan", "Team Plan"]
+        );
+
+        let bob = Viewer::user("bob@other.com", &[]);
+        assert_eq!(titles(data.search("plan", &bob, 10)), vec!["Public Plan"]);
+    }
 }
@@ -4,6 +4,14 @@ use axum::{
     body::Body,
     http::{Method, Request, StatusCode},
 };
+use omni_searcher::admission::{AdmissionConfig, AdmissionController};
+use omni_searcher::learned_ranking::{LearnedRanker, LearnedRankingConfig};
+use omni_searcher::query_intent::{QueryIntentClassifier, QueryIntentConfig};
+use omni_searcher::query_language::{QueryLanguage, QueryLanguageConfig};
+use omni_searcher::query_understanding::{QueryUnderstanding, QueryUnderstandingConfig};
+use omni_searcher::sla::{SlaConfig, SlaMonitor};
+use omni_searcher::source_router::{SourceRouter, SourceRouterConfig};
+use omni_searcher::spelling::{SpellChecker, SpellingConfig};
 use omni_searcher::{
     AppState, create_app, operator_registry::OperatorRegistry,
     suggested_questions::SuggestedQuestionsGenerator, typeahead::TitleIndex,
@@ -21,10 +29,43 @@ pub struct SearcherTestFixture {
     pub test_env: TestEnvironment,
     pub app: Router,
     pub title_index: Arc<TitleIndex>,
+    pub source_router: Arc<SourceRouter>,
+    pub sla_monitor: Arc<SlaMonitor>,
+    pub spell_checker: Arc<SpellChecker>,
+    pub learned_ranker: Arc<LearnedRanker>,
 }
 
 impl SearcherTestFixture {
     pub async fn new() -> Result<Self> {
+        Self::with_source_router_config(SourceRouterConfig::default()).await
+    }
+
+    pub async fn with_source_router_config(
+        source_router_config: SourceRouterConfig,
+    ) -> Result<Self> {
+        Self::build(source_router_config, 0, false).await
+    }
+
+    pub async fn with_coarse_retrieval_documents(coarse_retrieval_documents: i64) -> Result<Self> {
+        Self::build(
+            SourceRouterConfig::default(),
+            coarse_retrieval_documents,
+            false,
+        )
+        .await
+    }
+
+    /// A fixture whose reads on behalf of a user run as the document reader
+    /// role, so Postgres enforces document permissions too
+    pub async fn with_row_level_security() -> Result<Self> {
+        Self::build(SourceRouterConfig::default(), 0, true).await
+    }
+
+    async fn build(
+        source_router_config: SourceRouterConfig,
+        coarse_retrieval_documents: i64,
+        row_level_security: bool,
+    ) -> Result<Self> {
         let test_env = TestEnvironment::new().await?;
 
         // Create test AI client and config
@@ -37,8 +78,21 @@ impl SearcherTestFixture {
             rrf_k: 60.0,
             semantic_search_timeout_ms: 5000,
             rag_context_window: 2,
+            search_cache_ttl_secs: 300,
             recency_boost_weight: 0.2,
             recency_half_life_days: 30.0,
+            hybrid_recency_weight: 0.2,
+            recency_half_life_days_by_source_type: Default::default(),
+            personalization_enabled: true,
+            personalization_weight: 0.3,
+            coarse_retrieval_documents,
+            direct_query_embeddings: false,
+            rerank_enabled: false,
+            rerank_url: None,
+            rerank_model: None,
+            rerank_top_n: 50,
+            rerank_timeout_ms: 1000,
+            row_level_security,
         };
 
         // Create content storage using PostgresStorage directly
@@ -54,6 +108,32 @@ impl SearcherTestFixture {
         ));
 
         let title_index = Arc::new(TitleIndex::new(test_env.db_pool.clone()));
+        let source_router = Arc::new(SourceRouter::new(
+            test_env.db_pool.clone(),
+            source_router_config,
+        ));
+
+        let query_intent = Arc::new(QueryIntentClassifier::new(
+            ai_client.clone(),
+            QueryIntentConfig::default(),
+        ));
+        let query_language = Arc::new(QueryLanguage::new(
+            ai_client.clone(),
+            QueryLanguageConfig::default(),
+        ));
+        let query_understanding = Arc::new(QueryUnderstanding::new(
+            ai_client.clone(),
+            QueryUnderstandingConfig::default(),
+        ));
+        let sla_monitor = Arc::new(SlaMonitor::new(SlaConfig::default()));
+        let spell_checker = Arc::new(SpellChecker::new(
+            test_env.db_pool.clone(),
+            SpellingConfig::default(),
+        ));
+        let learned_ranker = Arc::new(LearnedRanker::new(
+            test_env.db_pool.clone(),
+            LearnedRankingConfig::default(),
+        ));
 
         let app_state = AppState {
             db_pool: test_env.db_pool.clone(),
@@ -64,6 +144,14 @@ impl SearcherTestFixture {
             suggested_questions_generator,
             title_index: title_index.clone(),
             operator_registry: Arc::new(OperatorRegistry::new(test_env.redis_client.clone())),
+            admission: Arc::new(AdmissionController::new(AdmissionConfig::default())),
+            source_router: source_router.clone(),
+            query_intent,
+            query_language,
+            query_understanding,
+            sla_monitor: sla_monitor.clone(),
+            spell_checker: spell_checker.clone(),
+            learned_ranker: learned_ranker.clone(),
         };
 
         let app = create_app(app_state);
@@ -72,6 +160,10 @@ impl SearcherTestFixture {
             test_env,
             app,
             title_index,
+            source_router,
+            sla_monitor,
+            spell_checker,
+            learned_ranker,
         })
     }
 
@@ -162,7 +254,40 @@ impl SearcherTestFixture {
         Ok((status, json))
     }
 
-    /// Helper method to make typeahead requests
+    /// Helper method to make streaming search requests. Returns the name and
+    /// JSON data of each server-sent event, in order.
+    pub async fn search_stream(&self, body: Value) -> Result<(StatusCode, Vec<(String, Value)>)> {
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri("/search/stream")
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))?;
+
+        let response = self.app.clone().oneshot(request).await?;
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await?;
+
+        let mut events = Vec::new();
+        for block in String::from_utf8_lossy(&body).split("\n\n") {
+            let mut name = String::from("message");
+            let mut data = String::new();
+            for line in block.lines() {
+                if let Some(value) = line.strip_prefix("event:") {
+                    name = value.trim().to_string();
+                } else if let Some(value) = line.strip_prefix("data:") {
+                    data.push_str(value.trim_start());
+                }
+            }
+            if !data.is_empty() {
+                events.push((name, serde_json::from_str(&data)?));
+            }
+        }
+
+        Ok((status, events))
+    }
+
+    /// Helper method to make typeahead requests as `user1`, who can read
+    /// every seeded document
     pub async fn typeahead(
         &self,
         query: &str,
@@ -170,12 +295,15 @@ impl SearcherTestFixture {
     ) -> Result<(StatusCode, Value)> {
         let uri = if let Some(limit) = limit {
             format!(
-                "/typeahead?q={}&limit={}",
+                "/typeahead?q={}&limit={}&user_email=user1",
                 urlencoding::encode(query),
                 limit
             )
         } else {
-            format!("/typeahead?q={}", urlencoding::encode(query))
+            format!(
+                "/typeahead?q={}&user_email=user1",
+                urlencoding::encode(query)
+            )
         };
 
         let request = Request::builder()
@@ -6,9 +6,18 @@ use axum::{
     http::{Method, Request, StatusCode},
 };
 use common::SearcherTestFixture;
+use omni_indexer::term_dictionary::{self, TermDictionaryConfig, TermDictionaryMode};
+use omni_searcher::models::SearchMode;
+use omni_searcher::source_router::{SourceRouterConfig, SourceRoutingMode};
 use serde_json::{json, Value};
-use shared::db::repositories::{GroupRepository, PersonRepository, PersonUpsert};
-use shared::models::DocumentPermissions;
+use shared::db::repositories::{
+    GroupRepository, MaintenanceSearchVisibility, NewRankingProfile, PersonRepository,
+    PersonUpsert, RankingProfileRepository, SourceMaintenanceRepository, WorkspaceRepository,
+};
+use shared::db::row_level_security;
+use shared::models::{DocumentPermissions, DEFAULT_WORKSPACE_ID};
+use shared::ranking::{preference_pairs, RankingProfile};
+use shared::typeahead_updates::TitleUpdate;
 use tower::ServiceExt;
 use ulid::Ulid;
 
@@ -613,6 +622,47 @@ async fn test_hybrid_search() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_streaming_search_sends_partial_then_final() -> Result<()> {
+    let fixture = SearcherTestFixture::new().await?;
+    let _doc_ids = fixture.seed_search_data().await?;
+
+    let body = json!({
+        "query": "rust programming",
+        "mode": "hybrid",
+        "include_facets": false
+    });
+    let (status, events) = fixture.search_stream(body.clone()).await?;
+    assert_eq!(status, StatusCode::OK);
+    let names: Vec<&str> = events.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(names, vec!["partial", "final"]);
+
+    let partial = &events[0].1;
+    assert_eq!(partial["stage"], "partial");
+    assert!(!result_titles(&partial["response"]).is_empty());
+    assert_match_type(&partial["response"], "fulltext");
+
+    let final_event = &events[1].1;
+    assert_eq!(final_event["stage"], "final");
+    let (status, response) = fixture.search_with_body(body).await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        result_document_ids(&final_event["response"]),
+        result_document_ids(&response),
+        "Final event should carry the same ranking as /search"
+    );
+
+    // Fulltext search has nothing to refine, so only the final event is sent
+    let (status, events) = fixture
+        .search_stream(json!({ "query": "rust programming", "mode": "fulltext" }))
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    let names: Vec<&str> = events.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(names, vec!["final"]);
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_hybrid_pagination_matches_fused_ranking() -> Result<()> {
     let fixture = SearcherTestFixture::new().await?;
@@ -718,6 +768,76 @@ async fn test_hybrid_dedupes_after_retrievers_pick_different_duplicates() -> Res
     Ok(())
 }
 
+#[tokio::test]
+async fn test_semantic_search_narrowed_by_summary_embeddings() -> Result<()> {
+    let fixture = SearcherTestFixture::with_coarse_retrieval_documents(1).await?;
+    let pool = fixture.test_env.db_pool.pool();
+    let query = "coarseretrievalneedle";
+
+    let chunk_match = insert_public_document_with_embedding(
+        pool,
+        TEST_SOURCE_ID,
+        "coarse-chunk-match",
+        "Chunk Match",
+        "chunk content",
+        query,
+        "2026-01-01T00:00:00Z",
+    )
+    .await?;
+    let summary_match = insert_public_document_with_embedding(
+        pool,
+        TEST_SOURCE_ID,
+        "coarse-summary-match",
+        "Summary Match",
+        "summary content",
+        "unrelated chunk text",
+        "2026-01-01T00:00:00Z",
+    )
+    .await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO document_summary_embeddings (document_id, embedding, model_name, dimensions)
+        VALUES ($1, $2, 'test-model', 1024)
+        "#,
+    )
+    .bind(&summary_match)
+    .bind(shared::test_environment::generate_test_embedding(query))
+    .execute(pool)
+    .await?;
+
+    let (status, response) = fixture
+        .search_with_body(json!({
+            "query": query,
+            "mode": "semantic",
+            "limit": 1,
+            "include_facets": false
+        }))
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        result_document_ids(&response),
+        vec![summary_match.clone()],
+        "Only chunks of the best summary match should be searched"
+    );
+
+    // Fewer summaries than requested results: all chunks are searched
+    let (status, response) = fixture
+        .search_with_body(json!({
+            "query": query,
+            "mode": "semantic",
+            "limit": 10,
+            "include_facets": false
+        }))
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    let document_ids = result_document_ids(&response);
+    assert_eq!(document_ids.first(), Some(&chunk_match));
+    assert!(document_ids.contains(&summary_match));
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_search_with_limit() -> Result<()> {
     let fixture = SearcherTestFixture::new().await?;
@@ -790,6 +910,80 @@ async fn test_search_with_limit() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_semantic_search_in_embedding_namespace() -> Result<()> {
+    let fixture = SearcherTestFixture::new().await?;
+    let pool = fixture.test_env.db_pool.pool();
+    let query = "namespaceexperimentneedle";
+
+    let production_match = insert_public_document_with_embedding(
+        pool,
+        TEST_SOURCE_ID,
+        "namespace-production-match",
+        "Production Match",
+        "production content",
+        query,
+        "2026-01-01T00:00:00Z",
+    )
+    .await?;
+    let experiment_match = insert_public_document_with_embedding(
+        pool,
+        TEST_SOURCE_ID,
+        "namespace-experiment-match",
+        "Experiment Match",
+        "experiment content",
+        "unrelated production chunk",
+        "2026-01-01T00:00:00Z",
+    )
+    .await?;
+
+    sqlx::query(
+        "INSERT INTO embedding_experiments (name, chunk_size, chunking_mode) VALUES ('small-chunks', 128, 'fixed')",
+    )
+    .execute(pool)
+    .await?;
+    sqlx::query(
+        r#"
+        INSERT INTO embeddings (id, document_id, chunk_index, chunk_start_offset, chunk_end_offset, embedding, model_name, dimensions, namespace)
+        VALUES ($1, $2, 0, 0, 10, $3, 'test-model', 1024, 'small-chunks')
+        "#,
+    )
+    .bind(Ulid::new().to_string())
+    .bind(&experiment_match)
+    .bind(shared::test_environment::generate_test_embedding(query))
+    .execute(pool)
+    .await?;
+
+    let (status, response) = fixture
+        .search_with_body(json!({
+            "query": query,
+            "mode": "semantic",
+            "limit": 1,
+            "include_facets": false
+        }))
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(result_document_ids(&response), vec![production_match]);
+
+    let (status, response) = fixture
+        .search_with_body(json!({
+            "query": query,
+            "mode": "semantic",
+            "limit": 10,
+            "include_facets": false,
+            "embedding_namespace": "small-chunks"
+        }))
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        result_document_ids(&response),
+        vec![experiment_match],
+        "Only the experiment's shadow rows should be searched"
+    );
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_include_facets_false_preserves_total_count() -> Result<()> {
     let fixture = SearcherTestFixture::new().await?;
@@ -998,6 +1192,156 @@ async fn test_fulltext_dedupes_by_source_type_and_external_id() -> Result<()> {
     Ok(())
 }
 
+/// Facet values of a response as `(value, count)` pairs, in response order.
+fn facet_counts(response: &Value, name: &str) -> Vec<(String, i64)> {
+    response["facets"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|facet| facet["name"] == name)
+        .unwrap_or_else(|| panic!("missing {} facet: {:?}", name, response["facets"]))["values"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|v| {
+            (
+                v["value"].as_str().unwrap().to_string(),
+                v["count"].as_i64().unwrap(),
+            )
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_facet_dimensions_and_multi_select_filters() -> Result<()> {
+    let fixture = SearcherTestFixture::new().await?;
+    let pool = fixture.test_env.db_pool.pool();
+    let content_storage = shared::ContentStorage::new(pool.clone());
+
+    for (external_id, author, content_type, updated_at) in [
+        (
+            "facetrich_1",
+            "alice",
+            "text/markdown",
+            "2026-09-02T10:00:00Z",
+        ),
+        (
+            "facetrich_2",
+            "alice",
+            "application/pdf",
+            "2026-10-13T10:00:00Z",
+        ),
+        (
+            "facetrich_3",
+            "bob",
+            "text/markdown",
+            "2026-10-14T10:00:00Z",
+        ),
+    ] {
+        let doc_id = Ulid::new().to_string();
+        let content = "facetrich quarterly report for facet dimension tests";
+        let content_id = content_storage.store_text(content.to_string()).await?;
+        sqlx::query(
+            r#"
+            INSERT INTO documents (id, source_id, external_id, title, content_id, content_type, content, metadata, permissions, attributes, created_at, updated_at)
+            VALUES ($1, '01JGF7V3E0Y2R1X8P5Q7W9T4N7', $2, $2, $3, $4, $5, $6, '{"public": true, "users": [], "groups": []}', '{}', NOW(), NOW())
+            "#,
+        )
+        .bind(&doc_id)
+        .bind(external_id)
+        .bind(&content_id)
+        .bind(content_type)
+        .bind(content)
+        .bind(json!({"author": author, "updated_at": updated_at}))
+        .execute(pool)
+        .await?;
+    }
+
+    let (status, response) = fixture
+        .search_with_body(json!({
+            "query": "facetrich",
+            "mode": "fulltext",
+            "facets": ["author", "content_type", "week", "month"]
+        }))
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(response["total_count"], 3);
+    let names: Vec<&str> = response["facets"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|facet| facet["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, vec!["author", "content_type", "week", "month"]);
+    assert_eq!(
+        facet_counts(&response, "author"),
+        vec![("alice".to_string(), 2), ("bob".to_string(), 1)]
+    );
+    assert_eq!(
+        facet_counts(&response, "content_type"),
+        vec![
+            ("text/markdown".to_string(), 2),
+            ("application/pdf".to_string(), 1)
+        ]
+    );
+    assert_eq!(
+        facet_counts(&response, "week"),
+        vec![("2026-10-12".to_string(), 2), ("2026-08-31".to_string(), 1)]
+    );
+    assert_eq!(
+        facet_counts(&response, "month"),
+        vec![("2026-10".to_string(), 2), ("2026-09".to_string(), 1)]
+    );
+
+    // Each facet is narrowed by the other selections but not its own.
+    let (status, response) = fixture
+        .search_with_body(json!({
+            "query": "facetrich",
+            "mode": "fulltext",
+            "facets": ["author", "month"],
+            "facet_filters": {"author": ["alice"], "month": ["2026-10"]}
+        }))
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(result_titles(&response), vec!["facetrich_2"]);
+    assert_eq!(
+        facet_counts(&response, "author"),
+        vec![("alice".to_string(), 1), ("bob".to_string(), 1)]
+    );
+    assert_eq!(
+        facet_counts(&response, "month"),
+        vec![("2026-10".to_string(), 1), ("2026-09".to_string(), 1)]
+    );
+    let active_filters: Vec<&str> = response["active_filters"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|filter| filter["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(active_filters, vec!["facet:author", "facet:month"]);
+
+    // Values within a dimension are OR'ed.
+    let (status, response) = fixture
+        .search_with_body(json!({
+            "query": "facetrich",
+            "mode": "fulltext",
+            "facet_filters": {"author": ["alice", "bob"], "content_type": ["text/markdown"]}
+        }))
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(response["total_count"], 2);
+
+    let (status, _) = fixture
+        .search_with_body(json!({
+            "query": "facetrich",
+            "facet_filters": {"month": ["last month"]}
+        }))
+        .await?;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_unfiltered_facets_with_source_type_filter() -> Result<()> {
     let fixture = SearcherTestFixture::new().await?;
@@ -1163,59 +1507,526 @@ async fn test_permission_filtering() -> Result<()> {
 }
 
 #[tokio::test]
-async fn test_highlighting() -> Result<()> {
+async fn test_workspace_isolation() -> Result<()> {
     let fixture = SearcherTestFixture::new().await?;
     let _doc_ids = fixture.seed_search_data().await?;
+    let pool = fixture.test_env.db_pool.pool();
+
+    // user1 is in every document's users list, but belongs to another workspace
+    let workspaces = WorkspaceRepository::new(pool);
+    let workspace = workspaces.create("Research", "research").await?;
+    let user_id = Ulid::new().to_string();
+    sqlx::query(
+        r#"
+        INSERT INTO users (id, email, password_hash, workspace_id, created_at, updated_at)
+        VALUES ($1, 'user1', 'hash', $2, NOW(), NOW())
+        "#,
+    )
+    .bind(&user_id)
+    .bind(&workspace.id)
+    .execute(pool)
+    .await?;
 
     let (status, response) = fixture
-        .search("memory safety", Some("fulltext"), None)
+        .search_with_user("guide", Some("fulltext"), None, Some("user1"))
         .await?;
     assert_eq!(status, StatusCode::OK);
-
     let results = response["results"].as_array().unwrap();
-    assert!(!results.is_empty(), "Expected results for 'memory safety'");
-
-    // Find "Rust Programming Guide" — it contains "memory safety" and should have highlights
-    let rust_guide = results
-        .iter()
-        .find(|r| r["document"]["title"].as_str().unwrap() == "Rust Programming Guide")
-        .expect("Expected Rust Programming Guide in results for 'memory safety'");
-
-    let highlights = rust_guide["highlights"].as_array().unwrap();
     assert!(
-        !highlights.is_empty(),
-        "Expected non-empty highlights for 'memory safety' query"
+        results.is_empty(),
+        "user1 should not see documents of another workspace, got {}",
+        results.len()
     );
 
-    let highlight_text = highlights
-        .iter()
-        .map(|h| h.as_str().unwrap_or(""))
-        .collect::<Vec<_>>()
-        .join(" ");
-    assert!(
-        highlight_text.contains("**memory**") || highlight_text.contains("**safety**"),
-        "Expected bold markers in highlights, got: {}",
-        highlight_text
-    );
+    // Moving the source into user1's workspace makes its documents visible
+    sqlx::query("UPDATE sources SET workspace_id = $1")
+        .bind(&workspace.id)
+        .execute(pool)
+        .await?;
 
-    // Highlight count should respect the SQL snippet limit (3)
+    let (status, response) = fixture
+        .search_with_user("guide", Some("fulltext"), None, Some("user1"))
+        .await?;
+    assert_eq!(status, StatusCode::OK);
     assert!(
-        highlights.len() <= 3,
-        "Expected at most 3 highlight snippets, got {}",
-        highlights.len()
+        !response["results"].as_array().unwrap().is_empty(),
+        "user1 should see documents of their own workspace"
     );
 
     Ok(())
 }
 
+/// Insert a user with `email` into `workspace_id` and return their id.
+async fn insert_workspace_user(
+    pool: &sqlx::PgPool,
+    email: &str,
+    workspace_id: &str,
+) -> Result<String> {
+    let user_id = Ulid::new().to_string();
+    sqlx::query(
+        r#"
+        INSERT INTO users (id, email, password_hash, workspace_id, created_at, updated_at)
+        VALUES ($1, $2, 'hash', $3, NOW(), NOW())
+        "#,
+    )
+    .bind(&user_id)
+    .bind(email)
+    .bind(workspace_id)
+    .execute(pool)
+    .await?;
+    Ok(user_id)
+}
+
 #[tokio::test]
-async fn test_attribute_filtering() -> Result<()> {
+async fn test_people_search_is_scoped_to_workspace() -> Result<()> {
     let fixture = SearcherTestFixture::new().await?;
-    let _doc_ids = fixture.seed_search_data().await?;
+    let pool = fixture.test_env.db_pool.pool();
+    seed_people(pool).await;
 
-    // category=programming should match Docs 1, 3, 4
-    let (status, response) = fixture
-        .search_with_body(json!({
+    let workspace = WorkspaceRepository::new(pool)
+        .create("Research", "research")
+        .await?;
+    PersonRepository::new(pool)
+        .upsert_people_batch(&[PersonUpsert {
+            email: "sam.research@example.com".to_string(),
+            display_name: Some("Sam Research".to_string()),
+            workspace_id: workspace.id.clone(),
+        }])
+        .await?;
+    insert_workspace_user(pool, "researcher@example.com", &workspace.id).await?;
+    insert_workspace_user(pool, "analyst@example.com", DEFAULT_WORKSPACE_ID).await?;
+
+    let emails = |people: &Value| -> Vec<String> {
+        people
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|p| p["email"].as_str().unwrap().to_string())
+            .collect()
+    };
+
+    let (status, response) = get_json(
+        &fixture,
+        "/people/search?q=sam&user_email=researcher%40example.com",
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        emails(&response["people"]),
+        vec!["sam.research@example.com"]
+    );
+
+    let (status, response) = get_json(
+        &fixture,
+        "/people/search?q=sam&user_email=analyst%40example.com",
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK);
+    let found = emails(&response["people"]);
+    assert!(found.contains(&"sam.wilson@example.com".to_string()));
+    assert!(!found.contains(&"sam.research@example.com".to_string()));
+
+    // Typeahead people suggestions follow the same scoping
+    let people_suggestions = |email: &'static str| {
+        let fixture = &fixture;
+        async move {
+            let (status, response) = get_json(
+                fixture,
+                &format!("/typeahead?q=sam&types=people&user_email={}", email),
+            )
+            .await?;
+            assert_eq!(status, StatusCode::OK);
+            let group = response["groups"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .find(|g| g["type"] == "people")
+                .cloned()
+                .unwrap_or_else(|| json!({"suggestions": []}));
+            Ok::<_, anyhow::Error>(emails(&group["suggestions"]))
+        }
+    };
+    assert_eq!(
+        people_suggestions("researcher%40example.com").await?,
+        vec!["sam.research@example.com"]
+    );
+    assert!(!people_suggestions("analyst%40example.com")
+        .await?
+        .contains(&"sam.research@example.com".to_string()));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_recent_searches_are_scoped_to_workspace() -> Result<()> {
+    let fixture = SearcherTestFixture::new().await?;
+    fixture.seed_search_data().await?;
+    let pool = fixture.test_env.db_pool.pool();
+    let workspaces = WorkspaceRepository::new(pool);
+    let workspace = workspaces.create("Research", "research").await?;
+    let user_id = insert_workspace_user(pool, "mover@example.com", DEFAULT_WORKSPACE_ID).await?;
+
+    let recent = |user_id: &str| {
+        let uri = format!("/recent-searches?user_id={}", user_id);
+        let fixture = &fixture;
+        async move {
+            let (status, response) = get_json(fixture, &uri).await?;
+            assert_eq!(status, StatusCode::OK);
+            Ok::<_, anyhow::Error>(response["searches"].clone())
+        }
+    };
+
+    let (status, _) = fixture
+        .search_with_body(json!({"query": "rust guide", "user_id": user_id}))
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(recent(&user_id).await?, json!(["rust guide"]));
+
+    // Searches made in the old workspace stay there
+    workspaces.assign_user(&user_id, &workspace.id).await?;
+    assert_eq!(recent(&user_id).await?, json!([]));
+
+    let (status, _) = fixture
+        .search_with_body(json!({"query": "kubernetes", "user_id": user_id}))
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(recent(&user_id).await?, json!(["kubernetes"]));
+
+    workspaces
+        .assign_user(&user_id, DEFAULT_WORKSPACE_ID)
+        .await?;
+    assert_eq!(recent(&user_id).await?, json!(["rust guide"]));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_shared_collections_stay_in_owner_workspace() -> Result<()> {
+    let fixture = SearcherTestFixture::new().await?;
+    fixture.seed_search_data().await?;
+    let pool = fixture.test_env.db_pool.pool();
+    let workspace = WorkspaceRepository::new(pool)
+        .create("Research", "research")
+        .await?;
+    let owner_id = insert_workspace_user(pool, "owner@example.com", DEFAULT_WORKSPACE_ID).await?;
+    let colleague_id =
+        insert_workspace_user(pool, "colleague@example.com", DEFAULT_WORKSPACE_ID).await?;
+    let outsider_id = insert_workspace_user(pool, "outsider@example.com", &workspace.id).await?;
+
+    let (status, collection) = send_json(
+        &fixture,
+        Method::POST,
+        "/collections",
+        Some(json!({"user_id": owner_id, "name": "Handbook", "visibility": "public"})),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::CREATED);
+    let collection_id = collection["id"].as_str().unwrap().to_string();
+
+    let (status, listed) =
+        get_json(&fixture, &format!("/collections?user_id={}", colleague_id)).await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(listed.as_array().unwrap().len(), 1);
+
+    // Public means public within the owner's workspace
+    let (status, listed) =
+        get_json(&fixture, &format!("/collections?user_id={}", outsider_id)).await?;
+    assert_eq!(status, StatusCode::OK);
+    assert!(listed.as_array().unwrap().is_empty());
+    let (status, _) = get_json(
+        &fixture,
+        &format!("/collections/{}?user_id={}", collection_id, outsider_id),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_suggested_questions_are_dropped_after_workspace_change() -> Result<()> {
+    let fixture = SearcherTestFixture::new().await?;
+    let pool = fixture.test_env.db_pool.pool();
+    let workspaces = WorkspaceRepository::new(pool);
+    let workspace = workspaces.create("Research", "research").await?;
+    let user_id = insert_workspace_user(pool, "asker@example.com", DEFAULT_WORKSPACE_ID).await?;
+
+    let cached = json!({
+        "questions": [{"question": "What is the rust guide about?", "document_id": "doc"}],
+        "source_ids": [],
+        "generated_at": time::OffsetDateTime::now_utc().unix_timestamp(),
+        "workspace_id": DEFAULT_WORKSPACE_ID
+    });
+    let mut redis = fixture
+        .test_env
+        .redis_client
+        .get_multiplexed_async_connection()
+        .await?;
+    redis::cmd("SET")
+        .arg("suggested_questions:v4:asker@example.com")
+        .arg(cached.to_string())
+        .query_async::<()>(&mut redis)
+        .await?;
+
+    let uri = format!("/suggested-questions?user_id={}", user_id);
+    let (status, response) = get_json(&fixture, &uri).await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(response["questions"].as_array().unwrap().len(), 1);
+
+    // Suggestions drawn from the old workspace's documents are not served
+    workspaces.assign_user(&user_id, &workspace.id).await?;
+    let (status, response) = get_json(&fixture, &uri).await?;
+    assert_eq!(status, StatusCode::OK);
+    assert!(response["questions"].as_array().unwrap().is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_row_level_security_hides_restricted_documents() -> Result<()> {
+    let fixture = SearcherTestFixture::new().await?;
+    let _doc_ids = fixture.seed_search_data().await?;
+    let pool = fixture.test_env.db_pool.pool();
+    assert!(row_level_security::is_available(pool).await?);
+
+    // No permission filter in the query: Postgres alone decides what is visible
+    let visible_to = |email: &'static str| async move {
+        let mut tx = row_level_security::begin_as_user(pool, email, &[]).await?;
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM documents")
+            .fetch_one(&mut *tx)
+            .await?;
+        tx.rollback().await?;
+        Ok::<_, anyhow::Error>(count)
+    };
+
+    assert!(visible_to("user1").await? > 0);
+    assert_eq!(visible_to("nobody@example.com").await?, 0);
+
+    // Outside the transaction the service sees every document
+    let total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM documents")
+        .fetch_one(pool)
+        .await?;
+    assert!(total > 0);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_row_level_security_guards_user_document_reads() -> Result<()> {
+    let fixture = SearcherTestFixture::with_row_level_security().await?;
+    let doc_ids = fixture.seed_search_data().await?;
+    let pool = fixture.test_env.db_pool.pool();
+
+    let (user1_id, user2_id) = (Ulid::new().to_string(), Ulid::new().to_string());
+    for (id, email) in [(&user1_id, "user1"), (&user2_id, "user2")] {
+        sqlx::query(
+            r#"
+            INSERT INTO users (id, email, password_hash, created_at, updated_at)
+            VALUES ($1, $2, 'hash', NOW(), NOW())
+            "#,
+        )
+        .bind(id)
+        .bind(email)
+        .execute(pool)
+        .await?;
+    }
+
+    // Typeahead: "Rust Programming Guide" is readable by user1 alone
+    let typeahead_titles = |email: &'static str| {
+        let fixture = &fixture;
+        async move {
+            let (status, response) = get_json(
+                fixture,
+                &format!("/typeahead?q=rust%20programming&user_email={}", email),
+            )
+            .await?;
+            assert_eq!(status, StatusCode::OK);
+            Ok::<_, anyhow::Error>(
+                response["results"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|r| r["title"].as_str().unwrap().to_string())
+                    .collect::<Vec<_>>(),
+            )
+        }
+    };
+    assert!(
+        typeahead_titles("user1")
+            .await?
+            .contains(&"Rust Programming Guide".to_string())
+    );
+    assert!(
+        !typeahead_titles("user2")
+            .await?
+            .contains(&"Rust Programming Guide".to_string())
+    );
+
+    // Collections: only documents the user can read are added
+    let (status, collection) = send_json(
+        &fixture,
+        Method::POST,
+        "/collections",
+        Some(json!({"user_id": user2_id, "name": "Readable only"})),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::CREATED);
+    let collection_id = collection["id"].as_str().unwrap();
+    let (status, added) = send_json(
+        &fixture,
+        Method::POST,
+        &format!("/collections/{}/documents", collection_id),
+        Some(json!({
+            "user_id": user2_id,
+            "user_email": "user2",
+            "document_ids": [doc_ids[0], doc_ids[1]]
+        })),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(added["added"], 1);
+
+    // Share links: created and fetched only while the creator can read
+    send_json(
+        &fixture,
+        Method::PUT,
+        "/admin/share-link-policy",
+        Some(json!({
+            "enabled": true,
+            "default_ttl_hours": 24,
+            "max_ttl_hours": 168,
+            "max_accesses": 10,
+            "rate_limit_per_minute": 30
+        })),
+    )
+    .await?;
+    let share_uri = format!("/documents/{}/share-links", doc_ids[1]);
+    let (status, _) = send_json(
+        &fixture,
+        Method::POST,
+        &format!("/documents/{}/share-links", doc_ids[0]),
+        Some(json!({"user_id": user2_id})),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, created) = send_json(
+        &fixture,
+        Method::POST,
+        &share_uri,
+        Some(json!({"user_id": user2_id})),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::CREATED);
+    let shared_uri = format!("/shared/{}", created["token"].as_str().unwrap());
+    let (status, _) = get_json(&fixture, &shared_uri).await?;
+    assert_eq!(status, StatusCode::OK);
+
+    sqlx::query(
+        r#"UPDATE documents SET permissions = '{"users": ["user1"], "groups": []}' WHERE id = $1"#,
+    )
+    .bind(&doc_ids[1])
+    .execute(pool)
+    .await?;
+    let (status, _) = get_json(&fixture, &shared_uri).await?;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_highlighting() -> Result<()> {
+    let fixture = SearcherTestFixture::new().await?;
+    let _doc_ids = fixture.seed_search_data().await?;
+
+    let (status, response) = fixture
+        .search("memory safety", Some("fulltext"), None)
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+
+    let results = response["results"].as_array().unwrap();
+    assert!(!results.is_empty(), "Expected results for 'memory safety'");
+
+    // Find "Rust Programming Guide" — it contains "memory safety" and should have highlights
+    let rust_guide = results
+        .iter()
+        .find(|r| r["document"]["title"].as_str().unwrap() == "Rust Programming Guide")
+        .expect("Expected Rust Programming Guide in results for 'memory safety'");
+
+    let highlights = rust_guide["highlights"].as_array().unwrap();
+    assert!(
+        !highlights.is_empty(),
+        "Expected non-empty highlights for 'memory safety' query"
+    );
+
+    let highlight_text = highlights
+        .iter()
+        .map(|h| h.as_str().unwrap_or(""))
+        .collect::<Vec<_>>()
+        .join(" ");
+    assert!(
+        highlight_text.contains("**memory**") || highlight_text.contains("**safety**"),
+        "Expected bold markers in highlights, got: {}",
+        highlight_text
+    );
+
+    // Highlight count should respect the SQL snippet limit (3)
+    assert!(
+        highlights.len() <= 3,
+        "Expected at most 3 highlight snippets, got {}",
+        highlights.len()
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_search_snippets_with_match_offsets() -> Result<()> {
+    let fixture = SearcherTestFixture::new().await?;
+    let _doc_ids = fixture.seed_search_data().await?;
+
+    let (status, response) = fixture
+        .search("memory safety", Some("fulltext"), None)
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+
+    let results = response["results"].as_array().unwrap();
+    let rust_guide = results
+        .iter()
+        .find(|r| r["document"]["title"].as_str().unwrap() == "Rust Programming Guide")
+        .expect("Expected Rust Programming Guide in results for 'memory safety'");
+
+    // Snippets carry the same matches as highlights, as UTF-16 offsets into plain text
+    let snippets = rust_guide["snippets"].as_array().unwrap();
+    assert!(!snippets.is_empty() && snippets.len() <= 3);
+    let mut matched_words = Vec::new();
+    for snippet in snippets {
+        let text: Vec<u16> = snippet["text"].as_str().unwrap().encode_utf16().collect();
+        for range in snippet["highlights"].as_array().unwrap() {
+            let start = range["start"].as_u64().unwrap() as usize;
+            let end = range["end"].as_u64().unwrap() as usize;
+            matched_words.push(String::from_utf16(&text[start..end])?.to_lowercase());
+        }
+    }
+    assert!(!matched_words.is_empty(), "Expected highlighted words in snippets");
+    assert!(
+        matched_words
+            .iter()
+            .all(|word| word.starts_with("memor") || word.starts_with("safe")),
+        "Unexpected highlighted words: {:?}",
+        matched_words
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_attribute_filtering() -> Result<()> {
+    let fixture = SearcherTestFixture::new().await?;
+    let _doc_ids = fixture.seed_search_data().await?;
+
+    // category=programming should match Docs 1, 3, 4
+    let (status, response) = fixture
+        .search_with_body(json!({
             "query": "search OR programming OR API OR guide",
             "attribute_filters": {"category": "programming"},
             "limit": 10
@@ -1288,31 +2099,132 @@ async fn test_attribute_filtering() -> Result<()> {
 }
 
 #[tokio::test]
-async fn test_cache_behavior() -> Result<()> {
+async fn test_metadata_filter_expressions() -> Result<()> {
     let fixture = SearcherTestFixture::new().await?;
     let _doc_ids = fixture.seed_search_data().await?;
 
-    let query = "rust programming";
-
-    let (status1, response1) = fixture.search(query, Some("fulltext"), None).await?;
-    assert_eq!(status1, StatusCode::OK);
-
-    // Verify a search cache key exists in Redis after the first query
-    let mut conn = fixture
-        .test_env
-        .redis_client
-        .get_multiplexed_async_connection()
+    // Of the programming documents, only the Rust guide was updated before
+    // midday on March 5th
+    let (status, response) = fixture
+        .search_with_body(json!({
+            "query": "search OR programming OR API OR guide",
+            "filter": {"type": "and", "filters": [
+                {"type": "condition", "field": "attributes.category", "op": "=", "value": "programming"},
+                {"type": "condition", "field": "updated_at", "op": "<", "value": "2026-03-05T12:00:00Z"}
+            ]},
+            "limit": 10
+        }))
         .await?;
-    let keys: Vec<String> = redis::cmd("KEYS")
-        .arg("search:*")
-        .query_async(&mut conn)
+    assert_eq!(status, StatusCode::OK);
+    let titles: Vec<&str> = response["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["document"]["title"].as_str().unwrap())
+        .collect();
+    assert_eq!(titles, vec!["Rust Programming Guide"]);
+
+    // A filter alone scopes the search like other filters do
+    let (status, response) = fixture
+        .search_with_body(json!({
+            "query": "",
+            "filter": {"type": "condition", "field": "title", "op": "like", "value": "%Guide"},
+            "limit": 10
+        }))
         .await?;
-    assert!(
-        !keys.is_empty(),
-        "Expected at least one search:* cache key in Redis after first query"
-    );
+    assert_eq!(status, StatusCode::OK);
+    let results = response["results"].as_array().unwrap();
+    assert!(!results.is_empty(), "Expected documents titled '...Guide'");
+    for result in results {
+        let title = result["document"]["title"].as_str().unwrap();
+        assert!(title.ends_with("Guide"), "Unexpected document '{}'", title);
+    }
 
-    // Second identical query should return the same results
+    let (status, body) = fixture
+        .search_with_body(json!({
+            "query": "guide",
+            "filter": {"type": "condition", "field": "owner", "op": "=", "value": "alice"}
+        }))
+        .await?;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["fields"][0]["field"], "filter");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_query_understanding() -> Result<()> {
+    let fixture = SearcherTestFixture::new().await?;
+    let _doc_ids = fixture.seed_search_data().await?;
+
+    let (status, response) = fixture
+        .search_with_body(json!({"query": "slides about rust", "mode": "fulltext"}))
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert!(response["total_count"].as_i64().unwrap() > 0);
+    assert!(response.get("query_understanding").is_none());
+
+    // Understood, the query only matches presentations, of which there are
+    // none
+    let (status, response) = fixture
+        .search_with_body(json!({
+            "query": "slides about rust",
+            "mode": "fulltext",
+            "understand_query": true
+        }))
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(response["total_count"], 0);
+    assert_eq!(response["query"], "slides about rust");
+    let understood = &response["query_understanding"];
+    assert_eq!(understood["query"], "rust");
+    assert_eq!(understood["content_types"], json!(["presentation"]));
+    assert_eq!(understood["source"], "rules");
+
+    // Content types the request sets win over understood ones
+    let (status, response) = fixture
+        .search_with_body(json!({
+            "query": "rust guide this year",
+            "mode": "fulltext",
+            "content_types": ["documentation"],
+            "understand_query": true
+        }))
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    let understood = &response["query_understanding"];
+    assert_eq!(understood["query"], "rust guide");
+    assert!(understood.get("content_types").is_none());
+    assert!(understood["after"].is_string());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_cache_behavior() -> Result<()> {
+    let fixture = SearcherTestFixture::new().await?;
+    let _doc_ids = fixture.seed_search_data().await?;
+
+    let query = "rust programming";
+
+    let (status1, response1) = fixture.search(query, Some("fulltext"), None).await?;
+    assert_eq!(status1, StatusCode::OK);
+
+    // Verify a search cache key exists in Redis after the first query
+    let mut conn = fixture
+        .test_env
+        .redis_client
+        .get_multiplexed_async_connection()
+        .await?;
+    let keys: Vec<String> = redis::cmd("KEYS")
+        .arg("search:*")
+        .query_async(&mut conn)
+        .await?;
+    assert!(
+        !keys.is_empty(),
+        "Expected at least one search:* cache key in Redis after first query"
+    );
+
+    // Second identical query should return the same results
     let (status2, response2) = fixture.search(query, Some("fulltext"), None).await?;
     assert_eq!(status2, StatusCode::OK);
 
@@ -1410,6 +2322,70 @@ async fn test_typeahead_limit_respected() -> Result<()> {
     Ok(())
 }
 
+fn typeahead_titles(response: &Value) -> Vec<String> {
+    response["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["title"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_typeahead_fuzzy_match() -> Result<()> {
+    let fixture = SearcherTestFixture::new().await?;
+    let _doc_ids = fixture.seed_search_data().await?;
+
+    let (status, response) = fixture.typeahead("plannnig", None).await?;
+    assert_eq!(status, StatusCode::OK);
+    let titles = typeahead_titles(&response);
+    assert!(
+        titles.iter().any(|t| t == "Q4 Planning Meeting"),
+        "Expected a misspelled query to find 'Q4 Planning Meeting', got: {:?}",
+        titles
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_typeahead_applies_incremental_updates() -> Result<()> {
+    let fixture = SearcherTestFixture::new().await?;
+    let _doc_ids = fixture.seed_search_data().await?;
+    let pool = fixture.test_env.db_pool.pool();
+    let update = TitleUpdate::documents(TEST_SOURCE_ID, vec!["typeahead_incremental".to_string()]);
+
+    sqlx::query(
+        r#"
+        INSERT INTO documents (id, source_id, external_id, title, content_type, metadata, permissions, attributes, created_at, updated_at)
+        VALUES ($1, $2, 'typeahead_incremental', 'Xylograph Rollout Checklist', 'document', '{}', '{"public": true, "users": [], "groups": []}', '{}', NOW(), NOW())
+        "#,
+    )
+    .bind(Ulid::new().to_string())
+    .bind(TEST_SOURCE_ID)
+    .execute(pool)
+    .await?;
+
+    let (_, response) = fixture.typeahead("xylograph", None).await?;
+    assert!(typeahead_titles(&response).is_empty());
+
+    fixture.title_index.apply_update(&update).await?;
+    let (_, response) = fixture.typeahead("xylograph", None).await?;
+    assert_eq!(
+        typeahead_titles(&response),
+        vec!["Xylograph Rollout Checklist"]
+    );
+
+    sqlx::query("DELETE FROM documents WHERE external_id = 'typeahead_incremental'")
+        .execute(pool)
+        .await?;
+    fixture.title_index.apply_update(&update).await?;
+    let (_, response) = fixture.typeahead("xylograph", None).await?;
+    assert!(typeahead_titles(&response).is_empty());
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_score_threshold_filters_low_relevance() -> Result<()> {
     let fixture = SearcherTestFixture::new().await?;
@@ -1543,6 +2519,38 @@ async fn test_invalid_search_mode() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_search_validation_reports_field_errors() -> Result<()> {
+    let fixture = SearcherTestFixture::new().await?;
+
+    let (status, body) = fixture
+        .search_with_body(json!({
+            "query": "test",
+            "limit": 500,
+            "document_content_start_line": 5
+        }))
+        .await?;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    let fields: Vec<&str> = body["fields"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|f| f["field"].as_str().unwrap())
+        .collect();
+    assert_eq!(fields, vec!["limit", "document_content_start_line"]);
+
+    let (status, body) = fixture
+        .search_with_body(json!({
+            "query": "test",
+            "source_types": ["google_drive", "not_a_source"]
+        }))
+        .await?;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["fields"][0]["field"], "source_types[1]");
+
+    Ok(())
+}
+
 async fn seed_people(pool: &sqlx::PgPool) {
     let person_repo = PersonRepository::new(pool);
     person_repo
@@ -1550,18 +2558,22 @@ async fn seed_people(pool: &sqlx::PgPool) {
             PersonUpsert {
                 email: "alice.smith@example.com".to_string(),
                 display_name: Some("Alice Smith".to_string()),
+                workspace_id: DEFAULT_WORKSPACE_ID.to_string(),
             },
             PersonUpsert {
                 email: "bob.jones@example.com".to_string(),
                 display_name: Some("Bob Jones".to_string()),
+                workspace_id: DEFAULT_WORKSPACE_ID.to_string(),
             },
             PersonUpsert {
                 email: "sam.wilson@example.com".to_string(),
                 display_name: Some("Sam Wilson".to_string()),
+                workspace_id: DEFAULT_WORKSPACE_ID.to_string(),
             },
             PersonUpsert {
                 email: "samantha.lee@example.com".to_string(),
                 display_name: Some("Samantha Lee".to_string()),
+                workspace_id: DEFAULT_WORKSPACE_ID.to_string(),
             },
         ])
         .await
@@ -1577,7 +2589,9 @@ async fn test_person_search_by_name() -> Result<()> {
     let person_repo = PersonRepository::new(pool);
 
     // Search for "sam" — should match Sam Wilson (via email token "sam")
-    let results = person_repo.search_people("sam", 10).await?;
+    let results = person_repo
+        .search_people("sam", DEFAULT_WORKSPACE_ID, 10)
+        .await?;
     assert!(
         !results.is_empty(),
         "Expected at least 1 result for 'sam', got 0",
@@ -1586,17 +2600,23 @@ async fn test_person_search_by_name() -> Result<()> {
     assert!(emails.contains(&"sam.wilson@example.com"));
 
     // Search for "samantha" — should match Samantha Lee
-    let results = person_repo.search_people("samantha", 10).await?;
+    let results = person_repo
+        .search_people("samantha", DEFAULT_WORKSPACE_ID, 10)
+        .await?;
     assert!(!results.is_empty(), "Expected results for 'samantha'");
     assert_eq!(results[0].email, "samantha.lee@example.com");
 
     // Search for "alice" — should match Alice Smith
-    let results = person_repo.search_people("alice", 10).await?;
+    let results = person_repo
+        .search_people("alice", DEFAULT_WORKSPACE_ID, 10)
+        .await?;
     assert!(!results.is_empty(), "Expected results for 'alice'");
     assert_eq!(results[0].email, "alice.smith@example.com");
 
     // Search for a non-existent name
-    let results = person_repo.search_people("zzzznotaperson", 10).await?;
+    let results = person_repo
+        .search_people("zzzznotaperson", DEFAULT_WORKSPACE_ID, 10)
+        .await?;
     assert!(results.is_empty());
 
     Ok(())
@@ -2562,3 +3582,1260 @@ async fn test_multilingual_cross_script_no_interference() -> Result<()> {
 
     Ok(())
 }
+
+async fn record_click(fixture: &SearcherTestFixture, body: Value) -> Result<StatusCode> {
+    let request = Request::builder()
+        .method(Method::POST)
+        .uri("/search/clicks")
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))?;
+    let response = fixture.app.clone().oneshot(request).await?;
+    Ok(response.status())
+}
+
+#[tokio::test]
+async fn test_source_routing_learns_from_clicks() -> Result<()> {
+    let fixture = SearcherTestFixture::with_source_router_config(SourceRouterConfig {
+        mode: SourceRoutingMode::Restrict,
+        min_clicks: 10,
+        ..Default::default()
+    })
+    .await?;
+    let pool = fixture.test_env.db_pool.pool();
+    let content_storage = shared::ContentStorage::new(pool.clone());
+
+    let slack_source_id = Ulid::new().to_string();
+    sqlx::query(
+        r#"
+        INSERT INTO sources (id, name, source_type, config, created_by, created_at, updated_at)
+        VALUES ($1, 'Slack Routing Test Source', 'slack', '{}', '01JGF7V3E0Y2R1X8P5Q7W9T4N6', NOW(), NOW())
+        "#,
+    )
+    .bind(&slack_source_id)
+    .execute(pool)
+    .await?;
+
+    let mut doc_ids = Vec::new();
+    for (source_id, external_id, title, content) in [
+        (
+            slack_source_id.clone(),
+            "routeprobe_slack",
+            "Routeprobe standup thread",
+            "routeprobe standup notes from the platform channel",
+        ),
+        (
+            "01JGF7V3E0Y2R1X8P5Q7W9T4N7".to_string(),
+            "routeprobe_local",
+            "Routeprobe design doc",
+            "routeprobe design doc covering the standup rotation tooling",
+        ),
+    ] {
+        let doc_id = Ulid::new().to_string();
+        let content_id = content_storage.store_text(content.to_string()).await?;
+        sqlx::query(
+            r#"
+            INSERT INTO documents (id, source_id, external_id, title, content_id, content_type, content, metadata, permissions, attributes, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, 'document', $6, '{}', '{"public": true, "users": [], "groups": []}', '{}', NOW(), NOW())
+            "#,
+        )
+        .bind(&doc_id)
+        .bind(&source_id)
+        .bind(external_id)
+        .bind(title)
+        .bind(&content_id)
+        .bind(content)
+        .execute(pool)
+        .await?;
+        doc_ids.push(doc_id);
+    }
+    let (slack_doc, local_doc) = (&doc_ids[0], &doc_ids[1]);
+
+    // Before any clicks the router has nothing to go on.
+    let (status, response) = fixture
+        .search_with_body(json!({"query": "routeprobe standup", "debug": true}))
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(response["source_routing"]["decision"], "all");
+    assert_eq!(
+        response["source_routing"]["reason"],
+        "insufficient_training_data"
+    );
+    assert_eq!(response["results"].as_array().unwrap().len(), 2);
+
+    for position in 0..10 {
+        for (query, document_id) in [("standup notes", slack_doc), ("design doc", local_doc)] {
+            let status = record_click(
+                &fixture,
+                json!({"query": query, "document_id": document_id, "position": position % 2}),
+            )
+            .await?;
+            assert_eq!(status, StatusCode::NO_CONTENT);
+        }
+    }
+    let status = record_click(
+        &fixture,
+        json!({"query": "standup notes", "document_id": "missing"}),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    fixture.source_router.refresh().await?;
+
+    let (status, response) = fixture
+        .search_with_body(json!({"query": "routeprobe standup", "debug": true}))
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    let routing = &response["source_routing"];
+    assert_eq!(routing["decision"], "restrict");
+    assert_eq!(routing["source_types"], json!(["slack"]));
+    assert_eq!(routing["predictions"][0]["source_type"], "slack");
+    assert_eq!(result_document_ids(&response), vec![slack_doc.clone()]);
+    // Routing does not show up as a user-applied filter.
+    assert!(response.get("active_filters").is_none());
+
+    // Without `debug` the routing details stay out of the response.
+    let (_, response) = fixture
+        .search_with_body(json!({"query": "routeprobe standup"}))
+        .await?;
+    assert!(response.get("source_routing").is_none());
+    assert_eq!(result_document_ids(&response), vec![slack_doc.clone()]);
+
+    // An explicit source filter always wins over the router.
+    let (_, response) = fixture
+        .search_with_body(json!({
+            "query": "routeprobe standup",
+            "source_types": ["local_files"],
+            "debug": true
+        }))
+        .await?;
+    assert!(response.get("source_routing").is_none());
+    assert_eq!(result_document_ids(&response), vec![local_doc.clone()]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_personalization_boosts_authored_documents() -> Result<()> {
+    let fixture = SearcherTestFixture::new().await?;
+    let pool = fixture.test_env.db_pool.pool();
+    let content_storage = shared::ContentStorage::new(pool.clone());
+
+    let mut doc_ids = Vec::new();
+    for (external_id, title, author) in [
+        ("personaprobe_other", "Personaprobe notes A", "Someone Else"),
+        ("personaprobe_mine", "Personaprobe notes B", "Ada Lovelace"),
+    ] {
+        let doc_id = Ulid::new().to_string();
+        let content = "personaprobe rollout checklist for the quarterly release";
+        let content_id = content_storage.store_text(content.to_string()).await?;
+        sqlx::query(
+            r#"
+            INSERT INTO documents (id, source_id, external_id, title, content_id, content_type, content, metadata, permissions, attributes, created_at, updated_at)
+            VALUES ($1, '01JGF7V3E0Y2R1X8P5Q7W9T4N7', $2, $3, $4, 'document', $5, $6, '{"public": true, "users": [], "groups": []}', '{}', NOW(), NOW())
+            "#,
+        )
+        .bind(&doc_id)
+        .bind(external_id)
+        .bind(title)
+        .bind(&content_id)
+        .bind(content)
+        .bind(json!({ "author": author }))
+        .execute(pool)
+        .await?;
+        doc_ids.push(doc_id);
+    }
+    let mine = &doc_ids[1];
+
+    // The people directory maps the searching user's email to the display
+    // name connectors put in `author`.
+    PersonRepository::new(pool)
+        .upsert_people_batch(&[PersonUpsert {
+            email: "ada@example.com".to_string(),
+            display_name: Some("Ada Lovelace".to_string()),
+            workspace_id: DEFAULT_WORKSPACE_ID.to_string(),
+        }])
+        .await?;
+
+    let (status, response) = fixture
+        .search_with_body(json!({
+            "query": "personaprobe",
+            "user_email": "ada@example.com",
+            "debug": true
+        }))
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(result_document_ids(&response)[0], *mine);
+
+    let boosted = response["personalization"]["boosted"].as_array().unwrap();
+    assert_eq!(boosted.len(), 1);
+    assert_eq!(boosted[0]["document_id"], *mine);
+    assert_eq!(boosted[0]["signals"], json!(["authored"]));
+
+    // Anonymous searches are never personalized.
+    let (_, response) = fixture
+        .search_with_body(json!({"query": "personaprobe", "debug": true}))
+        .await?;
+    assert!(response.get("personalization").is_none());
+
+    Ok(())
+}
+
+async fn get_json(fixture: &SearcherTestFixture, uri: &str) -> Result<(StatusCode, Value)> {
+    let request = Request::builder()
+        .method(Method::GET)
+        .uri(uri)
+        .body(Body::empty())?;
+    let response = fixture.app.clone().oneshot(request).await?;
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await?;
+    Ok((status, serde_json::from_slice(&body)?))
+}
+
+#[tokio::test]
+async fn test_ai_answer_records_context_provenance() -> Result<()> {
+    let fixture = SearcherTestFixture::new().await?;
+    fixture.seed_search_data().await?;
+    let pool = fixture.test_env.db_pool.pool();
+
+    let request = Request::builder()
+        .method(Method::POST)
+        .uri("/search/ai-answer")
+        .header("content-type", "application/json")
+        .body(Body::from(
+            json!({
+                "query": "search engine architecture",
+                "user_email": "user1",
+                "user_id": "01JGF7V3E0Y2R1X8P5Q7W9T4N6"
+            })
+            .to_string(),
+        ))?;
+    let response = fixture.app.clone().oneshot(request).await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let provenance_id = response
+        .headers()
+        .get(omni_searcher::handlers::RAG_PROVENANCE_HEADER)
+        .expect("AI answers carry their provenance id")
+        .to_str()?
+        .to_string();
+
+    let (status, record) = get_json(
+        &fixture,
+        &format!("/admin/rag-provenance/{}", provenance_id),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(record["query"], "search engine architecture");
+    assert_eq!(record["user_id"], "01JGF7V3E0Y2R1X8P5Q7W9T4N6");
+    assert_eq!(record["permission_snapshot"]["user_email"], "user1");
+
+    let context = record["context"].as_array().unwrap();
+    assert!(!context.is_empty());
+    for entry in context {
+        assert!(entry["document_updated_at"].is_string());
+        assert!(entry["permissions"].is_object());
+    }
+
+    // Semantic context is traceable to the exact embedded chunks.
+    let chunk_ids: Vec<String> = context
+        .iter()
+        .flat_map(|entry| entry["chunks"].as_array().unwrap())
+        .map(|chunk| chunk["chunk_id"].as_str().unwrap().to_string())
+        .collect();
+    assert!(!chunk_ids.is_empty());
+    let known: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM embeddings WHERE id = ANY($1)")
+        .bind(&chunk_ids)
+        .fetch_one(pool)
+        .await?;
+    assert_eq!(known, chunk_ids.len() as i64);
+
+    let document_id = context[0]["document_id"].as_str().unwrap();
+    let (status, records) = get_json(
+        &fixture,
+        &format!(
+            "/admin/rag-provenance?user_id=01JGF7V3E0Y2R1X8P5Q7W9T4N6&document_id={}",
+            document_id
+        ),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(records.as_array().unwrap().len(), 1);
+    assert_eq!(records[0]["id"], provenance_id.as_str());
+
+    let (status, _) = get_json(&fixture, "/admin/rag-provenance/missing").await?;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_answer_cites_context_documents_and_chunks() -> Result<()> {
+    let fixture = SearcherTestFixture::new().await?;
+    fixture.seed_search_data().await?;
+
+    let body = json!({
+        "query": "search engine architecture",
+        "user_email": "user1"
+    });
+    let request = Request::builder()
+        .method(Method::POST)
+        .uri("/answer")
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))?;
+    let response = fixture.app.clone().oneshot(request).await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let provenance_id = response
+        .headers()
+        .get(omni_searcher::handlers::RAG_PROVENANCE_HEADER)
+        .expect("answers carry their provenance id")
+        .to_str()?
+        .to_string();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await?;
+    let answer: Value = serde_json::from_slice(&bytes)?;
+
+    assert_eq!(answer["answer"], "Mock streamed answer [1]");
+    assert_eq!(answer["provenance_id"], provenance_id.as_str());
+    let citations = answer["citations"].as_array().unwrap();
+    assert_eq!(citations.len(), 1);
+    assert_eq!(citations[0]["marker"], 1);
+
+    // The citation points at the first context entry of the provenance record.
+    let (_, record) = get_json(
+        &fixture,
+        &format!("/admin/rag-provenance/{}", provenance_id),
+    )
+    .await?;
+    assert_eq!(
+        citations[0]["document_id"],
+        record["context"][0]["document_id"]
+    );
+    assert_eq!(citations[0]["chunks"], record["context"][0]["chunks"]);
+
+    let request = Request::builder()
+        .method(Method::POST)
+        .uri("/answer/stream")
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))?;
+    let response = fixture.app.clone().oneshot(request).await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await?;
+    let events = String::from_utf8(bytes.to_vec())?;
+    assert!(events.contains("event: delta"));
+    let final_data = events
+        .split("event: final\n")
+        .nth(1)
+        .and_then(|event| event.lines().next())
+        .and_then(|line| line.strip_prefix("data: "))
+        .expect("stream ends with a final event");
+    let final_event: Value = serde_json::from_str(final_data)?;
+    assert_eq!(final_event["type"], "final");
+    assert_eq!(final_event["answer"], "Mock streamed answer [1]");
+    assert_eq!(final_event["citations"][0]["marker"], 1);
+
+    Ok(())
+}
+
+async fn send_json(
+    fixture: &SearcherTestFixture,
+    method: Method,
+    uri: &str,
+    body: Option<Value>,
+) -> Result<(StatusCode, Value)> {
+    let request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("content-type", "application/json")
+        .body(body.map_or_else(Body::empty, |b| Body::from(b.to_string())))?;
+    let response = fixture.app.clone().oneshot(request).await?;
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await?;
+    let json = if body.is_empty() {
+        Value::Null
+    } else {
+        serde_json::from_slice(&body)?
+    };
+    Ok((status, json))
+}
+
+#[tokio::test]
+async fn test_collections_scope_search_and_enforce_sharing() -> Result<()> {
+    let fixture = SearcherTestFixture::new().await?;
+    let doc_ids = fixture.seed_search_data().await?;
+    let pool = fixture.test_env.db_pool.pool();
+
+    let owner_id = "01JGF7V3E0Y2R1X8P5Q7W9T4N6";
+    let teammate_id = Ulid::new().to_string();
+    sqlx::query(
+        r#"
+        INSERT INTO users (id, email, password_hash, created_at, updated_at)
+        VALUES ($1, 'teammate@example.com', 'hash', NOW(), NOW())
+        "#,
+    )
+    .bind(&teammate_id)
+    .execute(pool)
+    .await?;
+    let group_repo = GroupRepository::new(pool);
+    let group = group_repo
+        .upsert_group(
+            TEST_SOURCE_ID,
+            "planning@example.com",
+            Some("Planning"),
+            None,
+        )
+        .await?;
+    group_repo
+        .sync_group_members(&group.id, &["teammate@example.com".into()])
+        .await?;
+
+    let (status, collection) = send_json(
+        &fixture,
+        Method::POST,
+        "/collections",
+        Some(json!({"user_id": owner_id, "name": "Q3 planning docs"})),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(collection["visibility"], "private");
+    let collection_id = collection["id"].as_str().unwrap().to_string();
+
+    let members = vec![doc_ids[0].clone(), doc_ids[1].clone()];
+    let (status, added) = send_json(
+        &fixture,
+        Method::POST,
+        &format!("/collections/{}/documents", collection_id),
+        Some(json!({
+            "user_id": owner_id,
+            "user_email": "user1",
+            "document_ids": members
+        })),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(added["added"], 2);
+
+    // Search is restricted to the collection's documents.
+    let (status, response) = fixture
+        .search_with_body(json!({
+            "query": "",
+            "user_email": "user1",
+            "user_id": owner_id,
+            "collection_id": collection_id
+        }))
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    let mut found = result_document_ids(&response);
+    found.sort();
+    let mut expected = members.clone();
+    expected.sort();
+    assert_eq!(found, expected);
+
+    // A private collection is invisible to everyone else.
+    let (status, _) = fixture
+        .search_with_body(json!({
+            "query": "",
+            "user_email": "teammate@example.com",
+            "user_id": teammate_id,
+            "collection_id": collection_id
+        }))
+        .await?;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // Sharing with the team makes it visible to group members, read-only.
+    let (status, updated) = send_json(
+        &fixture,
+        Method::PUT,
+        &format!("/collections/{}", collection_id),
+        Some(json!({
+            "user_id": owner_id,
+            "visibility": "team",
+            "team_groups": ["Planning@example.com"]
+        })),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(updated["team_groups"], json!(["planning@example.com"]));
+
+    let teammate_query = format!("user_id={}&user_email=teammate%40example.com", teammate_id);
+    let (status, listed) = get_json(&fixture, &format!("/collections?{}", teammate_query)).await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(listed.as_array().unwrap().len(), 1);
+    assert_eq!(listed[0]["document_count"], 2);
+
+    let (status, _) = send_json(
+        &fixture,
+        Method::DELETE,
+        &format!("/collections/{}?{}", collection_id, teammate_query),
+        None,
+    )
+    .await?;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    // Team collections need someone to share with.
+    let (status, _) = send_json(
+        &fixture,
+        Method::POST,
+        "/collections",
+        Some(json!({"user_id": owner_id, "name": "Empty team", "visibility": "team"})),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _) = send_json(
+        &fixture,
+        Method::DELETE,
+        &format!("/collections/{}?user_id={}", collection_id, owner_id),
+        None,
+    )
+    .await?;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_source_maintenance_labels_or_hides_documents() -> Result<()> {
+    let fixture = SearcherTestFixture::new().await?;
+    fixture.seed_search_data().await?;
+    let maintenance_repo = SourceMaintenanceRepository::new(fixture.test_env.db_pool.pool());
+
+    // Stale: documents are still returned, flagged as possibly out of date.
+    maintenance_repo
+        .start(
+            TEST_SOURCE_ID,
+            MaintenanceSearchVisibility::Stale,
+            Some("Rotating credentials"),
+            None,
+        )
+        .await?;
+    let (status, response) = fixture.search("API", Some("fulltext"), None).await?;
+    assert_eq!(status, StatusCode::OK);
+    let results = response["results"].as_array().unwrap();
+    assert!(!results.is_empty());
+    assert!(results.iter().all(|r| r["possibly_stale"] == true));
+
+    // Hidden: the source drops out of fulltext and semantic retrieval.
+    maintenance_repo
+        .start(
+            TEST_SOURCE_ID,
+            MaintenanceSearchVisibility::Hidden,
+            None,
+            None,
+        )
+        .await?;
+    let (status, response) = fixture
+        .search("meeting planning Q4", Some("hybrid"), None)
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert!(result_titles(&response).is_empty());
+
+    // Ending maintenance restores normal results.
+    assert!(maintenance_repo.end(TEST_SOURCE_ID).await?);
+    let (status, response) = fixture
+        .search("rust programming", Some("fulltext"), None)
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    let results = response["results"].as_array().unwrap();
+    assert!(!results.is_empty());
+    assert!(results.iter().all(|r| r.get("possibly_stale").is_none()));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_sla_breach_switches_hybrid_search_to_degraded_mode() -> Result<()> {
+    let fixture = SearcherTestFixture::new().await?;
+    fixture.seed_search_data().await?;
+
+    let (status, sla) = get_json(&fixture, "/admin/search-sla").await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(sla["modes"].as_array().unwrap().len(), 3);
+    assert!(sla["modes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|mode| mode["level"] == "normal"));
+
+    let (_, response) = fixture
+        .search("search engine", Some("hybrid"), None)
+        .await?;
+    assert!(response["facets"].is_array());
+
+    // Each window of slow hybrid searches steps one level further down.
+    for _ in 0..3 {
+        for _ in 0..20 {
+            fixture
+                .sla_monitor
+                .record(&SearchMode::Hybrid, std::time::Duration::from_secs(10));
+        }
+    }
+
+    let (status, response) = fixture
+        .search("search engine architecture", Some("hybrid"), None)
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert!(!response["results"].as_array().unwrap().is_empty());
+    assert!(response["facets"].is_null());
+
+    let (_, sla) = get_json(&fixture, "/admin/search-sla").await?;
+    let hybrid = sla["modes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|mode| mode["mode"] == "hybrid")
+        .unwrap();
+    assert_eq!(hybrid["level"], "shrink_facets");
+    let fulltext = sla["modes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|mode| mode["mode"] == "fulltext")
+        .unwrap();
+    assert_eq!(fulltext["level"], "normal");
+
+    let switches = sla["recent_switches"].as_array().unwrap();
+    assert_eq!(switches.len(), 3);
+    assert_eq!(switches[0]["mode"], "hybrid");
+    assert_eq!(switches[0]["from"], "reduce_semantic_k");
+    assert_eq!(switches[0]["to"], "shrink_facets");
+    assert_eq!(switches[0]["p95_ms"], 10_000);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_spell_correction_suggests_and_auto_corrects() -> Result<()> {
+    let fixture = SearcherTestFixture::new().await?;
+    let pool = fixture.test_env.db_pool.pool();
+    let content_storage = shared::ContentStorage::new(pool.clone());
+
+    for (external_id, title, content) in [
+        (
+            "spellprobe_1",
+            "Spellprobe quarterly roadmap",
+            "spellprobe roadmap for the quarterly planning cycle",
+        ),
+        (
+            "spellprobe_2",
+            "Spellprobe retrospective",
+            "notes from the spellprobe retrospective",
+        ),
+    ] {
+        let content_id = content_storage.store_text(content.to_string()).await?;
+        sqlx::query(
+            r#"
+            INSERT INTO documents (id, source_id, external_id, title, content_id, content_type, content, metadata, permissions, attributes, created_at, updated_at)
+            VALUES ($1, '01JGF7V3E0Y2R1X8P5Q7W9T4N7', $2, $3, $4, 'document', $5, '{}', '{"public": true, "users": [], "groups": []}', '{}', NOW(), NOW())
+            "#,
+        )
+        .bind(Ulid::new().to_string())
+        .bind(external_id)
+        .bind(title)
+        .bind(&content_id)
+        .bind(content)
+        .execute(pool)
+        .await?;
+    }
+
+    let run = term_dictionary::refresh(
+        pool,
+        &TermDictionaryConfig::default(),
+        TermDictionaryMode::Full,
+    )
+    .await?;
+    assert!(run.terms > 0);
+    fixture.spell_checker.refresh().await?;
+
+    let (status, response) = fixture.search("spelprobe", None, None).await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(response["total_count"], 0);
+    assert_eq!(response["did_you_mean"][0], "spellprobe");
+    assert!(response.get("corrected_query").is_none());
+
+    // Queries that find enough, or have nothing misspelled, get no suggestions.
+    let (_, response) = fixture.search("spellprobe", None, None).await?;
+    assert_eq!(response["results"].as_array().unwrap().len(), 2);
+    assert!(response.get("did_you_mean").is_none());
+
+    let (status, response) = fixture
+        .search_with_body(json!({"query": "spelprobe", "auto_correct": true}))
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(response["query"], "spelprobe");
+    assert_eq!(response["corrected_query"], "spellprobe");
+    assert_eq!(response["results"].as_array().unwrap().len(), 2);
+    assert!(response.get("did_you_mean").is_none());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_source_boosts_from_settings_and_request() -> Result<()> {
+    let fixture = SearcherTestFixture::new().await?;
+    let pool = fixture.test_env.db_pool.pool();
+    let confluence_source_id = Ulid::new().to_string();
+    let query = "sourceboostneedle";
+
+    sqlx::query(
+        r#"
+        INSERT INTO sources (id, name, source_type, config, created_by, created_at, updated_at)
+        VALUES ($1, 'Boosted Confluence', 'confluence', '{}', '01JGF7V3E0Y2R1X8P5Q7W9T4N6', NOW(), NOW())
+        "#,
+    )
+    .bind(&confluence_source_id)
+    .execute(pool)
+    .await?;
+
+    let local_doc_id = insert_public_document_with_embedding(
+        pool,
+        TEST_SOURCE_ID,
+        "source-boost-local",
+        "SourceBoostNeedle Local Notes",
+        "sourceboostneedle sourceboostneedle sourceboostneedle",
+        query,
+        "2026-01-01T00:00:00Z",
+    )
+    .await?;
+    let confluence_doc_id = insert_public_document_with_embedding(
+        pool,
+        &confluence_source_id,
+        "source-boost-confluence",
+        "Team Page",
+        "the sourceboostneedle page",
+        "unrelated embedding text",
+        "2026-01-01T00:00:00Z",
+    )
+    .await?;
+
+    let body = |source_boosts: Option<Value>| {
+        let mut body = json!({
+            "query": query,
+            "mode": "hybrid",
+            "limit": 10,
+            "include_facets": false
+        });
+        if let Some(source_boosts) = source_boosts {
+            body["source_boosts"] = source_boosts;
+        }
+        body
+    };
+
+    let (status, response) = fixture.search_with_body(body(None)).await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        result_document_ids(&response),
+        vec![local_doc_id.clone(), confluence_doc_id.clone()]
+    );
+
+    let (status, _) = send_json(
+        &fixture,
+        Method::PUT,
+        "/admin/source-boosts",
+        Some(json!({ "boosts": { "confluence": 10.0 } })),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK);
+    let (status, settings) = get_json(&fixture, "/admin/source-boosts").await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(settings["boosts"], json!({ "confluence": 10.0 }));
+
+    // Configured boosts apply immediately, despite the cached unboosted response
+    let (status, response) = fixture.search_with_body(body(None)).await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        result_document_ids(&response),
+        vec![confluence_doc_id.clone(), local_doc_id.clone()]
+    );
+
+    // A request override replaces the configured boost for that source type
+    let (status, response) = fixture
+        .search_with_body(body(Some(json!({ "confluence": 1.0 }))))
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        result_document_ids(&response),
+        vec![local_doc_id, confluence_doc_id]
+    );
+
+    let (status, response) = fixture
+        .search_with_body(body(Some(json!({ "slack": 0.0 }))))
+        .await?;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(response["fields"][0]["field"], "source_boosts.slack");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_hybrid_recency_decay_request_overrides() -> Result<()> {
+    let fixture = SearcherTestFixture::new().await?;
+    let pool = fixture.test_env.db_pool.pool();
+    let query = "recencydecayneedle";
+    let recent = time::OffsetDateTime::now_utc()
+        .format(&time::format_description::well_known::Rfc3339)?;
+
+    let old_doc_id = insert_public_document_with_embedding(
+        pool,
+        TEST_SOURCE_ID,
+        "recency-decay-old",
+        "RecencyDecayNeedle Archive",
+        "recencydecayneedle recencydecayneedle recencydecayneedle",
+        query,
+        "2020-01-01T00:00:00Z",
+    )
+    .await?;
+    let recent_doc_id = insert_public_document_with_embedding(
+        pool,
+        TEST_SOURCE_ID,
+        "recency-decay-recent",
+        "Weekly Update",
+        "the recencydecayneedle update",
+        "unrelated embedding text",
+        &recent,
+    )
+    .await?;
+
+    let body = |recency_decay: Value| {
+        json!({
+            "query": query,
+            "mode": "hybrid",
+            "limit": 10,
+            "include_facets": false,
+            "recency_decay": recency_decay
+        })
+    };
+
+    let (status, response) = fixture.search_with_body(body(json!({ "weight": 0.0 }))).await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        result_document_ids(&response),
+        vec![old_doc_id.clone(), recent_doc_id.clone()]
+    );
+
+    let (status, response) = fixture
+        .search_with_body(body(json!({
+            "weight": 5.0,
+            "half_life_days_by_source_type": { "local_files": 1.0 }
+        })))
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        result_document_ids(&response),
+        vec![recent_doc_id, old_doc_id]
+    );
+
+    let (status, response) = fixture
+        .search_with_body(body(json!({ "half_life_days": 0.0 })))
+        .await?;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(response["fields"][0]["field"], "recency_decay.half_life_days");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_dedupe_collapses_same_content_across_sources() -> Result<()> {
+    let fixture = SearcherTestFixture::new().await?;
+    let pool = fixture.test_env.db_pool.pool();
+    let query = "dedupeneedle";
+    let content = "dedupeneedle quarterly roadmap shared in drive and attached in slack";
+
+    let drive_doc_id = insert_public_document_with_embedding(
+        pool,
+        TEST_SOURCE_ID,
+        "dedupe-drive",
+        "DedupeNeedle Roadmap",
+        content,
+        query,
+        "2026-01-01T00:00:00Z",
+    )
+    .await?;
+    let slack_doc_id = insert_public_document_with_embedding(
+        pool,
+        TEST_SOURCE_ID,
+        "dedupe-slack",
+        "roadmap.pdf",
+        content,
+        query,
+        "2026-01-01T00:00:00Z",
+    )
+    .await?;
+    sqlx::query("UPDATE documents SET content_fingerprint = $1 WHERE id = ANY($2)")
+        .bind("dedupe-test-fingerprint")
+        .bind(vec![drive_doc_id.clone(), slack_doc_id.clone()])
+        .execute(pool)
+        .await?;
+
+    let body = |dedupe: Option<bool>| {
+        json!({
+            "query": query,
+            "mode": "fulltext",
+            "limit": 10,
+            "include_facets": false,
+            "dedupe": dedupe
+        })
+    };
+
+    let (status, response) = fixture.search_with_body(body(None)).await?;
+    assert_eq!(status, StatusCode::OK);
+    let results = response["results"].as_array().unwrap();
+    assert_eq!(results.len(), 1);
+    let duplicates = results[0]["duplicates"].as_array().unwrap();
+    assert_eq!(duplicates.len(), 1);
+    let mut ids = vec![
+        results[0]["document"]["id"].as_str().unwrap().to_string(),
+        duplicates[0]["document_id"].as_str().unwrap().to_string(),
+    ];
+    ids.sort();
+    let mut expected = vec![drive_doc_id, slack_doc_id];
+    expected.sort();
+    assert_eq!(ids, expected);
+
+    let (status, response) = fixture.search_with_body(body(Some(false))).await?;
+    assert_eq!(status, StatusCode::OK);
+    let results = response["results"].as_array().unwrap();
+    assert_eq!(results.len(), 2);
+    assert!(results.iter().all(|r| r.get("duplicates").is_none()));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_document_scoped_search_returns_matches_in_document_order() -> Result<()> {
+    let fixture = SearcherTestFixture::new().await?;
+    let pool = fixture.test_env.db_pool.pool();
+    let mut lines: Vec<String> = (1..=30).map(|n| format!("filler line {}", n)).collect();
+    lines[24] = "Rollback the release if the canary fails".to_string();
+    lines[4] = "Before the release, freeze merges".to_string();
+    let content = lines.join("\n");
+
+    let doc_id = insert_public_document_with_embedding(
+        pool,
+        TEST_SOURCE_ID,
+        "scoped-runbook",
+        "Release runbook",
+        &content,
+        "release runbook",
+        "2026-01-01T00:00:00Z",
+    )
+    .await?;
+
+    let (status, response) = fixture
+        .search_with_body(json!({
+            "query": "release rollback",
+            "mode": "fulltext",
+            "document_id": doc_id,
+        }))
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(response["total_count"], 2);
+    let results = response["results"].as_array().unwrap();
+    assert_eq!(results.len(), 2);
+
+    assert_eq!(results[0]["match_type"], "fulltext");
+    assert_eq!(
+        results[0]["location"],
+        json!({"start_line": 3, "end_line": 7, "match_start_line": 5, "match_end_line": 5})
+    );
+    assert!(
+        results[0]["highlights"][0]
+            .as_str()
+            .unwrap()
+            .contains("5 | Before the release, freeze merges")
+    );
+    assert_eq!(results[1]["location"]["match_start_line"], 25);
+    assert!(results[1]["score"].as_f64().unwrap() > results[0]["score"].as_f64().unwrap());
+
+    let (status, response) = fixture
+        .search_with_body(json!({
+            "query": "release rollback",
+            "mode": "fulltext",
+            "document_id": doc_id,
+            "limit": 1,
+            "offset": 1,
+        }))
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(response["has_more"], false);
+    let results = response["results"].as_array().unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0]["location"]["match_start_line"], 25);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_share_links_follow_policy_and_limits() -> Result<()> {
+    let fixture = SearcherTestFixture::new().await?;
+    let doc_ids = fixture.seed_search_data().await?;
+    let pool = fixture.test_env.db_pool.pool();
+
+    let sharer_id = Ulid::new().to_string();
+    let other_id = Ulid::new().to_string();
+    for (id, email) in [(&sharer_id, "user1"), (&other_id, "user2")] {
+        sqlx::query(
+            r#"
+            INSERT INTO users (id, email, password_hash, created_at, updated_at)
+            VALUES ($1, $2, 'hash', NOW(), NOW())
+            "#,
+        )
+        .bind(id)
+        .bind(email)
+        .execute(pool)
+        .await?;
+    }
+    let share_uri = format!("/documents/{}/share-links", doc_ids[0]);
+
+    // Sharing is off until an admin enables it.
+    let (status, _) = send_json(
+        &fixture,
+        Method::POST,
+        &share_uri,
+        Some(json!({"user_id": sharer_id})),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, _) = send_json(
+        &fixture,
+        Method::PUT,
+        "/admin/share-link-policy",
+        Some(json!({
+            "enabled": true,
+            "default_ttl_hours": 24,
+            "max_ttl_hours": 168,
+            "max_accesses": 2,
+            "rate_limit_per_minute": 30
+        })),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK);
+
+    // Only documents the user can read can be shared, within the policy. The
+    // user's access is looked up from their id, whatever email is sent.
+    let (status, _) = send_json(
+        &fixture,
+        Method::POST,
+        &share_uri,
+        Some(json!({"user_id": other_id, "user_email": "user1"})),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = send_json(
+        &fixture,
+        Method::POST,
+        &share_uri,
+        Some(json!({"user_id": Ulid::new().to_string()})),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = send_json(
+        &fixture,
+        Method::POST,
+        &share_uri,
+        Some(json!({"user_id": sharer_id, "ttl_hours": 500})),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+    let (status, created) = send_json(
+        &fixture,
+        Method::POST,
+        &share_uri,
+        Some(json!({"user_id": sharer_id})),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(created["link"]["max_accesses"], 2);
+    let token = created["token"].as_str().unwrap().to_string();
+    assert!(token.starts_with("omni_share_"));
+
+    // Each fetch counts until the access limit is used up.
+    for _ in 0..2 {
+        let (status, shared) = get_json(&fixture, &format!("/shared/{}", token)).await?;
+        assert_eq!(status, StatusCode::OK);
+        assert!(!shared["title"].as_str().unwrap().is_empty());
+    }
+    let (status, _) = get_json(&fixture, &format!("/shared/{}", token)).await?;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, links) =
+        get_json(&fixture, &format!("{}?user_id={}", share_uri, sharer_id)).await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(links[0]["access_count"], 2);
+
+    // Revoked links stop working; only the creator or an admin can revoke.
+    let (_, created) = send_json(
+        &fixture,
+        Method::POST,
+        &share_uri,
+        Some(json!({"user_id": sharer_id, "max_accesses": 1})),
+    )
+    .await?;
+    let link_id = created["link"]["id"].as_str().unwrap().to_string();
+    let token = created["token"].as_str().unwrap().to_string();
+
+    let (_, active) = get_json(&fixture, "/admin/share-links").await?;
+    assert_eq!(active.as_array().unwrap().len(), 1);
+
+    let (status, _) = send_json(
+        &fixture,
+        Method::DELETE,
+        &format!("/share-links/{}?user_id={}", link_id, Ulid::new()),
+        None,
+    )
+    .await?;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = send_json(
+        &fixture,
+        Method::DELETE,
+        &format!("/share-links/{}?user_id={}", link_id, sharer_id),
+        None,
+    )
+    .await?;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let (status, _) = get_json(&fixture, &format!("/shared/{}", token)).await?;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_search_analytics_reports_queries_and_clicks() -> Result<()> {
+    let fixture = SearcherTestFixture::new().await?;
+    let _doc_ids = fixture.seed_search_data().await?;
+
+    let mut event_ids = Vec::new();
+    for query in [
+        "Rust Programming",
+        "rust   programming",
+        "zzqx nothing matches",
+    ] {
+        let (status, response) = fixture
+            .search_with_body(json!({"query": query, "mode": "fulltext"}))
+            .await?;
+        assert_eq!(status, StatusCode::OK);
+        event_ids.push(response["search_event_id"].as_str().unwrap().to_string());
+    }
+    assert_ne!(event_ids[0], event_ids[1]);
+
+    let (status, response) = fixture
+        .search_with_body(json!({"query": "rust programming", "mode": "fulltext"}))
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    let clicked_id = result_document_ids(&response)[0].clone();
+    let status = send_json(
+        &fixture,
+        Method::POST,
+        "/feedback/click",
+        Some(json!({
+            "query": "rust programming",
+            "document_id": clicked_id,
+            "position": 0,
+            "search_event_id": response["search_event_id"],
+        })),
+    )
+    .await?
+    .0;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let (status, report) = get_json(&fixture, "/admin/search-analytics?days=1&limit=5").await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(report["searches"], 4);
+    assert_eq!(report["unique_queries"], 2);
+    assert_eq!(report["zero_result_searches"], 1);
+    assert_eq!(report["clicked_searches"], 1);
+
+    let top = &report["top_queries"][0];
+    assert_eq!(top["query"], "rust programming");
+    assert_eq!(top["searches"], 3);
+    assert_eq!(top["clicked_searches"], 1);
+    assert!(top["avg_result_count"].as_f64().unwrap() > 0.0);
+
+    let zero_result = report["zero_result_queries"].as_array().unwrap();
+    assert_eq!(zero_result.len(), 1);
+    assert_eq!(zero_result[0]["query"], "zzqx nothing matches");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_learned_ranking_profile_reorders_hybrid_results() -> Result<()> {
+    let fixture = SearcherTestFixture::new().await?;
+    let _doc_ids = fixture.seed_search_data().await?;
+    let pool = fixture.test_env.db_pool.pool();
+    let body = json!({"query": "rust programming", "mode": "hybrid", "limit": 5});
+
+    let (status, response) = fixture.search_with_body(body.clone()).await?;
+    assert_eq!(status, StatusCode::OK);
+    let ids = result_document_ids(&response);
+    assert!(
+        ids.len() >= 2,
+        "expected several hybrid results, got {:?}",
+        ids
+    );
+    let event_id = response["search_event_id"].as_str().unwrap().to_string();
+
+    // The results shown are logged with their ranking features, in order
+    let (impressions,): (Value,) =
+        sqlx::query_as("SELECT impressions FROM search_events WHERE id = $1")
+            .bind(&event_id)
+            .fetch_one(pool)
+            .await?;
+    let logged_ids: Vec<&str> = impressions
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|i| i["document_id"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        logged_ids,
+        ids.iter().map(String::as_str).collect::<Vec<_>>()
+    );
+
+    // Opening the second result prefers it over the first
+    let status = send_json(
+        &fixture,
+        Method::POST,
+        "/feedback/click",
+        Some(json!({
+            "query": "rust programming",
+            "document_id": ids[1],
+            "position": 1,
+            "search_event_id": event_id,
+        })),
+    )
+    .await?
+    .0;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let repo = RankingProfileRepository::new(pool);
+    let since = time::OffsetDateTime::now_utc() - time::Duration::days(1);
+    let searches = repo.clicked_searches(since).await?;
+    assert_eq!(searches.len(), 1);
+    let pairs = preference_pairs(&searches[0].impressions, &searches[0].clicked_document_ids);
+    assert!(!pairs.is_empty());
+
+    // A profile that ranks by clicks alone puts the opened document first
+    repo.create(
+        &NewRankingProfile {
+            weights: RankingProfile {
+                click_prior: 10.0,
+                ..RankingProfile::default()
+            },
+            training_searches: 1,
+            training_pairs: pairs.len() as i32,
+            baseline_accuracy: Some(0.0),
+            accuracy: Some(1.0),
+        },
+        true,
+    )
+    .await?;
+    fixture.learned_ranker.refresh().await?;
+
+    let (status, response) = fixture.search_with_body(body).await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(result_document_ids(&response)[0], ids[1]);
+
+    Ok(())
+}
@@ -0,0 +1,378 @@
+//! End-to-end fixture spanning the connector → indexer → searcher pipeline.
+//!
+//! Connectors are represented by synthetic `ConnectorEvent`s pushed onto the
+//! event queue under a realtime sync run. The indexer's `QueueProcessor` runs
+//! against the same test database as the searcher app, and a stand-in for the
+//! Python embedding service drains the embedding queue using the same
+//! deterministic embeddings as the mock AI server, so hybrid and semantic
+//! queries behave like they do in production.
+
+use anyhow::{Result, anyhow};
+use axum::{
+    Router,
+    body::Body,
+    http::{Method, Request, StatusCode},
+};
+use omni_indexer::QueueProcessor;
+use omni_searcher::admission::{AdmissionConfig, AdmissionController};
+use omni_searcher::learned_ranking::{LearnedRanker, LearnedRankingConfig};
+use omni_searcher::query_intent::{QueryIntentClassifier, QueryIntentConfig};
+use omni_searcher::query_language::{QueryLanguage, QueryLanguageConfig};
+use omni_searcher::query_understanding::{QueryUnderstanding, QueryUnderstandingConfig};
+use omni_searcher::sla::{SlaConfig, SlaMonitor};
+use omni_searcher::source_router::{SourceRouter, SourceRouterConfig};
+use omni_searcher::spelling::{SpellChecker, SpellingConfig};
+use omni_searcher::{
+    create_app, operator_registry::OperatorRegistry,
+    suggested_questions::SuggestedQuestionsGenerator, typeahead::TitleIndex,
+};
+use pgvector::Vector;
+use serde_json::Value;
+use shared::db::repositories::{DocumentRepository, EmbeddingRepository, SyncRunRepository};
+use shared::embedding_queue::EmbeddingQueue;
+use shared::models::{ConnectorEvent, DocumentMetadata, DocumentPermissions, Embedding, SyncType};
+use shared::queue::EventQueue;
+use shared::storage::postgres::PostgresStorage;
+use shared::test_environment::{TestEnvironment, generate_test_embedding};
+use shared::{AIClient, ObjectStorage, SearcherConfig};
+use sqlx::types::time::OffsetDateTime;
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+use tokio::time::{Duration, Instant, sleep};
+use tower::ServiceExt;
+use ulid::Ulid;
+
+pub const TEST_SOURCE_ID: &str = "01JGF7V3E0Y2R1X8P5Q7W9T4N7";
+
+/// Model name the mock AI server reports; embeddings written by the test
+/// worker must match it to be picked up by semantic search.
+const TEST_EMBEDDING_MODEL: &str = "test-model";
+const TEST_EMBEDDING_BATCH_SIZE: i32 = 32;
+
+pub struct PipelineFixture {
+    pub test_env: TestEnvironment,
+    pub searcher: Router,
+    pub content_storage: Arc<dyn ObjectStorage>,
+    pub sync_run_id: String,
+    event_queue: EventQueue,
+    title_index: Arc<TitleIndex>,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl PipelineFixture {
+    pub async fn new() -> Result<Self> {
+        // TODO: Audit that the environment access only happens in single-threaded code.
+        unsafe {
+            std::env::set_var(
+                "ENCRYPTION_KEY",
+                "test_master_key_that_is_long_enough_32_chars",
+            );
+            std::env::set_var("ENCRYPTION_SALT", "test_salt_16_chars");
+        }
+
+        let test_env = TestEnvironment::new().await?;
+        let pool = test_env.db_pool.pool().clone();
+        let ai_client = AIClient::new(test_env.mock_ai_server.base_url.clone());
+        let content_storage: Arc<dyn ObjectStorage> = Arc::new(PostgresStorage::new(pool.clone()));
+        let embedding_queue = EmbeddingQueue::new(pool.clone());
+
+        let indexer_state = omni_indexer::AppState {
+            db_pool: test_env.db_pool.clone(),
+            redis_client: test_env.redis_client.clone(),
+            ai_client: ai_client.clone(),
+            content_storage: content_storage.clone(),
+            embedding_queue: embedding_queue.clone(),
+            row_level_security: false,
+        };
+        let processor =
+            QueueProcessor::new(indexer_state).with_poll_interval(Duration::from_millis(100));
+        let processor_task = tokio::spawn(async move {
+            let _ = processor.start().await;
+        });
+
+        let embedding_task = tokio::spawn(run_test_embedder(
+            embedding_queue,
+            DocumentRepository::new(&pool),
+            EmbeddingRepository::new(&pool),
+            content_storage.clone(),
+        ));
+
+        let title_index = Arc::new(TitleIndex::new(test_env.db_pool.clone()));
+        let query_intent = Arc::new(QueryIntentClassifier::new(
+            ai_client.clone(),
+            QueryIntentConfig::default(),
+        ));
+        let query_language = Arc::new(QueryLanguage::new(
+            ai_client.clone(),
+            QueryLanguageConfig::default(),
+        ));
+        let query_understanding = Arc::new(QueryUnderstanding::new(
+            ai_client.clone(),
+            QueryUnderstandingConfig::default(),
+        ));
+        let searcher_state = omni_searcher::AppState {
+            db_pool: test_env.db_pool.clone(),
+            redis_client: test_env.redis_client.clone(),
+            ai_client: ai_client.clone(),
+            config: SearcherConfig {
+                port: 8002,
+                database: test_env.database_config(),
+                redis: test_env.redis_config(),
+                ai_service_url: test_env.mock_ai_server.base_url.clone(),
+                rrf_k: 60.0,
+                semantic_search_timeout_ms: 5000,
+                rag_context_window: 2,
+                search_cache_ttl_secs: 300,
+                recency_boost_weight: 0.2,
+                recency_half_life_days: 30.0,
+                hybrid_recency_weight: 0.2,
+                recency_half_life_days_by_source_type: Default::default(),
+                personalization_enabled: true,
+                personalization_weight: 0.3,
+                coarse_retrieval_documents: 0,
+                direct_query_embeddings: false,
+                rerank_enabled: false,
+                rerank_url: None,
+                rerank_model: None,
+                rerank_top_n: 50,
+                rerank_timeout_ms: 1000,
+                row_level_security: false,
+            },
+            content_storage: content_storage.clone(),
+            suggested_questions_generator: Arc::new(SuggestedQuestionsGenerator::new(
+                test_env.redis_client.clone(),
+                test_env.db_pool.clone(),
+                content_storage.clone(),
+                ai_client,
+            )),
+            title_index: title_index.clone(),
+            operator_registry: Arc::new(OperatorRegistry::new(test_env.redis_client.clone())),
+            admission: Arc::new(AdmissionController::new(AdmissionConfig::default())),
+            source_router: Arc::new(SourceRouter::new(
+                test_env.db_pool.clone(),
+                SourceRouterConfig::default(),
+            )),
+            query_intent,
+            query_language,
+            query_understanding,
+            sla_monitor: Arc::new(SlaMonitor::new(SlaConfig::default())),
+            spell_checker: Arc::new(SpellChecker::new(
+                test_env.db_pool.clone(),
+                SpellingConfig::default(),
+            )),
+            learned_ranker: Arc::new(LearnedRanker::new(
+                test_env.db_pool.clone(),
+                LearnedRankingConfig::default(),
+            )),
+        };
+
+        // Realtime runs are dequeued as soon as events arrive, so tests do not
+        // wait on the batching thresholds used for full and incremental syncs.
+        let sync_run = SyncRunRepository::new(&pool)
+            .create(TEST_SOURCE_ID, SyncType::Realtime, "manual")
+            .await?;
+
+        Ok(Self {
+            searcher: create_app(searcher_state),
+            content_storage,
+            sync_run_id: sync_run.id,
+            event_queue: EventQueue::new(pool),
+            title_index,
+            tasks: vec![processor_task, embedding_task],
+            test_env,
+        })
+    }
+
+    /// Store `content` and push a `DocumentCreated` event for it, as a
+    /// connector would. Returns the external document id.
+    pub async fn create_document(
+        &self,
+        external_id: &str,
+        title: &str,
+        content: &str,
+        permissions: DocumentPermissions,
+    ) -> Result<String> {
+        let content_id = self
+            .content_storage
+            .store_content(content.as_bytes(), None)
+            .await?;
+
+        let event = ConnectorEvent::DocumentCreated {
+            sync_run_id: self.sync_run_id.clone(),
+            source_id: TEST_SOURCE_ID.to_string(),
+            document_id: external_id.to_string(),
+            content_id,
+            metadata: DocumentMetadata {
+                title: Some(title.to_string()),
+                author: None,
+                created_at: Some(OffsetDateTime::now_utc()),
+                updated_at: Some(OffsetDateTime::now_utc()),
+                content_type: None,
+                mime_type: Some("text/plain".to_string()),
+                size: Some(content.len().to_string()),
+                url: Some(format!("https://example.com/docs/{}", external_id)),
+                path: None,
+                extra: None,
+            },
+            permissions,
+            attributes: None,
+        };
+        self.push_event(&event).await?;
+
+        Ok(external_id.to_string())
+    }
+
+    pub async fn delete_document(&self, external_id: &str) -> Result<()> {
+        self.push_event(&ConnectorEvent::DocumentDeleted {
+            sync_run_id: self.sync_run_id.clone(),
+            source_id: TEST_SOURCE_ID.to_string(),
+            document_id: external_id.to_string(),
+        })
+        .await
+    }
+
+    pub async fn push_event(&self, event: &ConnectorEvent) -> Result<()> {
+        self.event_queue.enqueue(TEST_SOURCE_ID, event).await?;
+        Ok(())
+    }
+
+    /// Wait until every pushed event has been indexed and every queued
+    /// document has been embedded, then refresh the searcher's title index.
+    pub async fn wait_until_settled(&self, timeout: Duration) -> Result<()> {
+        let pool = self.test_env.db_pool.pool();
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            let (pending_events, pending_embeddings): (i64, i64) = sqlx::query_as(
+                r#"
+                SELECT
+                    (SELECT COUNT(*) FROM connector_events_queue
+                     WHERE status IN ('pending', 'processing')),
+                    (SELECT COUNT(*) FROM embedding_queue
+                     WHERE status IN ('pending', 'processing'))
+                "#,
+            )
+            .fetch_one(pool)
+            .await?;
+
+            if pending_events == 0 && pending_embeddings == 0 {
+                break;
+            }
+            if Instant::now() >= deadline {
+                return Err(anyhow!(
+                    "pipeline did not settle within {:?}: {} events and {} embeddings outstanding",
+                    timeout,
+                    pending_events,
+                    pending_embeddings
+                ));
+            }
+            sleep(Duration::from_millis(100)).await;
+        }
+
+        let (failed_events,): (i64,) = sqlx::query_as(
+            "SELECT COUNT(*) FROM connector_events_queue WHERE status IN ('failed', 'dead_letter')",
+        )
+        .fetch_one(pool)
+        .await?;
+        if failed_events > 0 {
+            return Err(anyhow!("{} events failed to index", failed_events));
+        }
+
+        self.title_index.refresh().await?;
+        Ok(())
+    }
+
+    /// POST `body` to the searcher's `/search` endpoint.
+    pub async fn search(&self, body: Value) -> Result<(StatusCode, Value)> {
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri("/search")
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))?;
+
+        let response = self.searcher.clone().oneshot(request).await?;
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await?;
+        Ok((status, serde_json::from_slice(&bytes)?))
+    }
+}
+
+impl Drop for PipelineFixture {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+/// Stand-in for the AI service's embedding worker: embeds each queued
+/// document as a single chunk and marks the queue entries completed.
+async fn run_test_embedder(
+    queue: EmbeddingQueue,
+    documents: DocumentRepository,
+    embeddings: EmbeddingRepository,
+    content_storage: Arc<dyn ObjectStorage>,
+) {
+    loop {
+        let items = match queue.dequeue_batch(TEST_EMBEDDING_BATCH_SIZE).await {
+            Ok(items) => items,
+            Err(e) => {
+                eprintln!("Test embedder failed to dequeue: {}", e);
+                Vec::new()
+            }
+        };
+        if items.is_empty() {
+            sleep(Duration::from_millis(100)).await;
+            continue;
+        }
+
+        for item in items {
+            let result =
+                embed_document(&item.document_id, &documents, &embeddings, &content_storage).await;
+            let marked = match result {
+                Ok(()) => queue.mark_completed(std::slice::from_ref(&item.id)).await,
+                Err(e) => queue.mark_failed(&item.id, &e.to_string()).await,
+            };
+            if let Err(e) = marked {
+                eprintln!(
+                    "Test embedder failed to update queue item {}: {}",
+                    item.id, e
+                );
+            }
+        }
+    }
+}
+
+async fn embed_document(
+    document_id: &str,
+    documents: &DocumentRepository,
+    embeddings: &EmbeddingRepository,
+    content_storage: &Arc<dyn ObjectStorage>,
+) -> Result<()> {
+    // Deleted before the embedder got to it: nothing to do.
+    let Some(document) = documents.find_by_id(document_id).await? else {
+        return Ok(());
+    };
+    let Some(content_id) = document.content_id else {
+        return Ok(());
+    };
+    let content = String::from_utf8(content_storage.get_content(&content_id).await?)?;
+
+    embeddings
+        .bulk_create(vec![Embedding {
+            id: Ulid::new().to_string(),
+            document_id: document.id,
+            chunk_index: 0,
+            chunk_start_offset: 0,
+            chunk_end_offset: content.chars().count() as i32,
+            embedding: Vector::from(generate_test_embedding(&content)),
+            model_name: TEST_EMBEDDING_MODEL.to_string(),
+            dimensions: 1024,
+            page_number: None,
+            heading_path: None,
+            created_at: OffsetDateTime::now_utc(),
+        }])
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,200 @@
+mod pipeline;
+
+use omni_connector_manager::compliance::{ComplianceProcessor, subject_identifiers};
+use pipeline::PipelineFixture;
+use serde_json::{Value, json};
+use shared::db::repositories::{
+    ComplianceRequestKind, ComplianceRequestRepository, ComplianceRequestStatus, ErasureAction,
+};
+use shared::models::DocumentPermissions;
+use tokio::time::Duration;
+
+const SETTLE_TIMEOUT: Duration = Duration::from_secs(30);
+
+fn external_ids(response: &Value) -> Vec<String> {
+    response["results"]
+        .as_array()
+        .expect("results should be an array")
+        .iter()
+        .map(|r| r["document"]["external_id"].as_str().unwrap().to_string())
+        .collect()
+}
+
+fn users(users: &[&str]) -> DocumentPermissions {
+    DocumentPermissions {
+        public: false,
+        users: users.iter().map(|u| u.to_string()).collect(),
+        groups: vec![],
+    }
+}
+
+#[tokio::test]
+async fn test_connector_events_are_searchable_end_to_end() {
+    let fixture = PipelineFixture::new().await.unwrap();
+
+    fixture
+        .create_document(
+            "runbook",
+            "Database Failover Runbook",
+            "Steps for promoting the replica during a database failover: pause writes, \
+             promote the standby and repoint the connection pooler.",
+            users(&["alice@example.com"]),
+        )
+        .await
+        .unwrap();
+    fixture
+        .create_document(
+            "offsite",
+            "Team Offsite Agenda",
+            "Agenda for the team offsite: planning session, hiking trip and dinner.",
+            users(&["alice@example.com", "bob@example.com"]),
+        )
+        .await
+        .unwrap();
+
+    fixture.wait_until_settled(SETTLE_TIMEOUT).await.unwrap();
+
+    let (embedded,): (i64,) = sqlx::query_as(
+        "SELECT COUNT(DISTINCT document_id) FROM embeddings WHERE model_name = 'test-model'",
+    )
+    .fetch_one(fixture.test_env.db_pool.pool())
+    .await
+    .unwrap();
+    assert_eq!(embedded, 2, "both documents should be embedded");
+
+    for mode in ["fulltext", "semantic", "hybrid"] {
+        let (status, response) = fixture
+            .search(json!({
+                "query": "database failover",
+                "mode": mode,
+                "user_email": "alice@example.com",
+            }))
+            .await
+            .unwrap();
+        assert_eq!(status, 200, "{} search failed: {}", mode, response);
+        let ids = external_ids(&response);
+        assert_eq!(
+            ids.first().map(String::as_str),
+            Some("runbook"),
+            "{} search should rank the runbook first, got {:?}",
+            mode,
+            ids
+        );
+    }
+
+    // Permissions flow through the pipeline: bob can only see the offsite doc.
+    let (status, response) = fixture
+        .search(json!({
+            "query": "database failover",
+            "mode": "fulltext",
+            "user_email": "bob@example.com",
+        }))
+        .await
+        .unwrap();
+    assert_eq!(status, 200);
+    assert!(!external_ids(&response).contains(&"runbook".to_string()));
+}
+
+#[tokio::test]
+async fn test_deleted_documents_drop_out_of_search() {
+    let fixture = PipelineFixture::new().await.unwrap();
+
+    fixture
+        .create_document(
+            "retired",
+            "Legacy Billing Migration",
+            "Notes on migrating the legacy billing system to the new invoicing platform.",
+            users(&["alice@example.com"]),
+        )
+        .await
+        .unwrap();
+    fixture.wait_until_settled(SETTLE_TIMEOUT).await.unwrap();
+
+    let query = json!({
+        "query": "legacy billing migration",
+        "mode": "hybrid",
+        "user_email": "alice@example.com",
+    });
+    let (_, response) = fixture.search(query.clone()).await.unwrap();
+    assert_eq!(external_ids(&response), vec!["retired".to_string()]);
+
+    fixture.delete_document("retired").await.unwrap();
+    fixture.wait_until_settled(SETTLE_TIMEOUT).await.unwrap();
+
+    let (status, response) = fixture.search(query).await.unwrap();
+    assert_eq!(status, 200);
+    assert!(
+        external_ids(&response).is_empty(),
+        "deleted document still returned: {}",
+        response
+    );
+}
+
+#[tokio::test]
+async fn test_erased_documents_drop_out_of_cached_search() {
+    let fixture = PipelineFixture::new().await.unwrap();
+
+    fixture
+        .create_document(
+            "interview",
+            "Interview Debrief",
+            "Interview debrief for jane.doe@example.com: strong system design round.",
+            users(&["alice@example.com"]),
+        )
+        .await
+        .unwrap();
+    fixture
+        .create_document(
+            "hiring",
+            "Hiring Plan",
+            "Hiring plan for the platform team, including the interview debrief process.",
+            users(&["alice@example.com"]),
+        )
+        .await
+        .unwrap();
+    fixture.wait_until_settled(SETTLE_TIMEOUT).await.unwrap();
+
+    // The second search of the same query would be served from the cache
+    let query = json!({
+        "query": "interview debrief",
+        "mode": "fulltext",
+        "user_email": "alice@example.com",
+    });
+    let (_, response) = fixture.search(query.clone()).await.unwrap();
+    assert!(external_ids(&response).contains(&"interview".to_string()));
+
+    let pool = fixture.test_env.db_pool.pool();
+    let repo = ComplianceRequestRepository::new(pool);
+    let request = repo
+        .create(
+            ComplianceRequestKind::Erasure,
+            Some(ErasureAction::Delete),
+            "jane.doe@example.com",
+            &subject_identifiers("jane.doe@example.com", None, &[]),
+            None,
+        )
+        .await
+        .unwrap();
+    let claimed = repo.claim_pending(1).await.unwrap().pop().unwrap();
+    ComplianceProcessor::new(
+        pool.clone(),
+        fixture.test_env.redis_client.clone(),
+        fixture.content_storage.clone(),
+        3600,
+    )
+    .run(claimed)
+    .await;
+    let request = repo.get(&request.id).await.unwrap().unwrap();
+    assert_eq!(request.status, ComplianceRequestStatus::Completed);
+    assert_eq!(request.document_count, 1);
+
+    let (status, response) = fixture.search(query).await.unwrap();
+    assert_eq!(status, 200);
+    let ids = external_ids(&response);
+    assert!(
+        !ids.contains(&"interview".to_string()),
+        "erased document still returned: {}",
+        response
+    );
+    assert_eq!(ids, vec!["hiring".to_string()]);
+}
@@ -0,0 +1,195 @@
+//! Audit trail of searches, document changes and admin actions.
+//!
+//! Entries go to the append-only `audit_log` table (migration 150) through
+//! [`record`] and [`record_all`], which log failures rather than failing the
+//! request being audited. The retention job in the connector manager deletes
+//! entries older than `AUDIT_LOG_RETENTION_DAYS`.
+//!
+//! Handlers take the acting [`Actor`] as an extractor. The web app sends the
+//! signed-in user in the `x-omni-actor-id` and `x-omni-actor-email` headers;
+//! without them the actor is the calling service, as authenticated by
+//! `service_auth::internal`.
+
+use crate::db::repositories::AuditLogRepository;
+use crate::service_auth::internal::InternalCaller;
+use async_trait::async_trait;
+use axum::extract::FromRequestParts;
+use http::HeaderMap;
+use http::request::Parts;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use sqlx::PgPool;
+use std::convert::Infallible;
+use tracing::error;
+
+pub const ACTOR_ID_HEADER: &str = "x-omni-actor-id";
+pub const ACTOR_EMAIL_HEADER: &str = "x-omni-actor-email";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, sqlx::Type, PartialEq, Eq)]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ActorType {
+    User,
+    /// An Omni service acting on its own behalf; the id is its caller name.
+    Service,
+    /// A connector syncing a source; the id is the source id.
+    Connector,
+    /// Background jobs such as retention.
+    System,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, sqlx::Type, PartialEq, Eq)]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    Search,
+    DocumentRead,
+    DocumentIndexed,
+    DocumentDeleted,
+    /// Settings, source ownership, keys, rules and other admin changes; the
+    /// `change` detail says which.
+    ConfigurationChanged,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Actor {
+    pub actor_type: ActorType,
+    pub id: Option<String>,
+    pub email: Option<String>,
+}
+
+impl Actor {
+    pub fn user(id: Option<&str>, email: Option<&str>) -> Self {
+        Self {
+            actor_type: ActorType::User,
+            id: id.map(str::to_string),
+            email: email.map(str::to_string),
+        }
+    }
+
+    pub fn service(name: Option<&str>) -> Self {
+        Self {
+            actor_type: ActorType::Service,
+            id: name.map(str::to_string),
+            email: None,
+        }
+    }
+
+    pub fn connector(source_id: &str) -> Self {
+        Self {
+            actor_type: ActorType::Connector,
+            id: Some(source_id.to_string()),
+            email: None,
+        }
+    }
+
+    pub fn system() -> Self {
+        Self {
+            actor_type: ActorType::System,
+            id: None,
+            email: None,
+        }
+    }
+
+    /// The user named in the actor headers, or else the calling service.
+    pub fn from_headers(headers: &HeaderMap, caller: Option<&str>) -> Self {
+        let header = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+        };
+        match (header(ACTOR_ID_HEADER), header(ACTOR_EMAIL_HEADER)) {
+            (None, None) => Self::service(caller),
+            (id, email) => Self::user(id, email),
+        }
+    }
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for Actor {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let caller = parts
+            .extensions
+            .get::<InternalCaller>()
+            .map(|caller| caller.0.as_str());
+        Ok(Self::from_headers(&parts.headers, caller))
+    }
+}
+
+/// An entry to add to the audit log.
+#[derive(Debug, Clone)]
+pub struct AuditEvent {
+    pub actor: Actor,
+    pub action: AuditAction,
+    pub resource_type: Option<String>,
+    pub resource_id: Option<String>,
+    pub details: JsonValue,
+}
+
+impl AuditEvent {
+    pub fn new(actor: Actor, action: AuditAction) -> Self {
+        Self {
+            actor,
+            action,
+            resource_type: None,
+            resource_id: None,
+            details: JsonValue::Object(Default::default()),
+        }
+    }
+
+    pub fn resource(mut self, resource_type: &str, resource_id: impl Into<String>) -> Self {
+        self.resource_type = Some(resource_type.to_string());
+        self.resource_id = Some(resource_id.into());
+        self
+    }
+
+    pub fn details(mut self, details: JsonValue) -> Self {
+        self.details = details;
+        self
+    }
+}
+
+/// Add `event` to the audit log, logging rather than returning failures.
+pub async fn record(pool: &PgPool, event: AuditEvent) {
+    record_all(pool, vec![event]).await;
+}
+
+/// Add `events` to the audit log in one statement, logging rather than
+/// returning failures.
+pub async fn record_all(pool: &PgPool, events: Vec<AuditEvent>) {
+    if events.is_empty() {
+        return;
+    }
+    if let Err(e) = AuditLogRepository::new(pool).insert(&events).await {
+        error!("Failed to record {} audit event(s): {}", events.len(), e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http::HeaderValue;
+
+    #[test]
+    fn test_actor_headers_name_the_user_over_the_caller() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(
+            Actor::from_headers(&headers, Some("web")),
+            Actor::service(Some("web"))
+        );
+
+        headers.insert(
+            ACTOR_EMAIL_HEADER,
+            HeaderValue::from_static("ada@example.com"),
+        );
+        headers.insert(ACTOR_ID_HEADER, HeaderValue::from_static(" "));
+        assert_eq!(
+            Actor::from_headers(&headers, Some("web")),
+            Actor::user(None, Some("ada@example.com"))
+        );
+    }
+}
@@ -5,6 +5,7 @@ use serde::{Deserialize, Serialize};
 use std::pin::Pin;
 use tracing::error;
 
+use crate::fault_injection::{self, FaultTarget};
 use crate::telemetry::http_client::RequestBuilderExt;
 
 #[derive(Serialize)]
@@ -40,6 +41,29 @@ pub struct PromptRequest {
     pub stream: Option<bool>,
 }
 
+/// Cohere-compatible rerank request, also accepted by Jina, vLLM and
+/// Infinity.
+#[derive(Serialize)]
+pub struct RerankRequest<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<&'a str>,
+    pub query: &'a str,
+    pub documents: &'a [String],
+    pub top_n: usize,
+}
+
+#[derive(Deserialize)]
+pub struct RerankResponse {
+    pub results: Vec<RerankResult>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RerankResult {
+    /// Position of the document in the request.
+    pub index: usize,
+    pub relevance_score: f32,
+}
+
 #[derive(Clone)]
 pub struct AIClient {
     client: Client,
@@ -73,6 +97,7 @@ impl AIClient {
         chunking_mode: Option<String>,
         priority: Option<String>,
     ) -> Result<Vec<TextEmbedding>> {
+        fault_injection::inject(FaultTarget::Ai, "generate_embeddings").await?;
         let request = EmbeddingRequest {
             texts,
             task,
@@ -107,6 +132,12 @@ impl AIClient {
                             model_name: Some(embedding_response.model_name.clone()),
                         });
                     }
+                    let returned = fault_injection::partial_len(
+                        FaultTarget::Ai,
+                        "generate_embeddings",
+                        result.len(),
+                    );
+                    result.truncate(returned);
 
                     Ok(result)
                 } else {
@@ -142,11 +173,54 @@ impl AIClient {
         Ok(vec![0.0; 1024])
     }
 
+    /// Score `documents` against `query` with the cross-encoder at `url`,
+    /// returning the results most relevant first.
+    pub async fn rerank(
+        &self,
+        url: &str,
+        model: Option<&str>,
+        query: &str,
+        documents: &[String],
+    ) -> Result<Vec<RerankResult>> {
+        fault_injection::inject(FaultTarget::Ai, "rerank").await?;
+        let request = RerankRequest {
+            model,
+            query,
+            documents,
+            top_n: documents.len(),
+        };
+
+        let response = self
+            .client
+            .post(url)
+            .json(&request)
+            .with_trace_context()
+            .send()
+            .await
+            .map_err(|e| anyhow!("Failed to connect to rerank API: {:?}", e))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let resp_text = response.text().await.unwrap_or_default();
+            return Err(anyhow!(
+                "Rerank API failed with error: [{}] {:?}",
+                status,
+                resp_text
+            ));
+        }
+
+        let mut results = response.json::<RerankResponse>().await?.results;
+        results.retain(|result| result.index < documents.len());
+        results.sort_by(|a, b| b.relevance_score.total_cmp(&a.relevance_score));
+        Ok(results)
+    }
+
     /// Stream AI response from the prompt endpoint
     pub async fn stream_prompt(
         &self,
         prompt: &str,
     ) -> Result<Pin<Box<dyn Stream<Item = Result<String>> + Send>>> {
+        fault_injection::inject(FaultTarget::Ai, "stream_prompt").await?;
         let request = PromptRequest {
             prompt: prompt.to_string(),
             max_tokens: Some(512),
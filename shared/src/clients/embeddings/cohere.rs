@@ -0,0 +1,121 @@
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+use super::{EmbeddingProvider, EmbeddingTask, classify_error_response};
+use crate::rate_limiter::{RateLimiter, RetryableError};
+use crate::telemetry::http_client::RequestBuilderExt;
+
+#[derive(Serialize)]
+struct CohereEmbedRequest<'a> {
+    texts: &'a [String],
+    model: &'a str,
+    input_type: &'static str,
+    embedding_types: [&'static str; 1],
+    truncate: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    output_dimension: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct CohereEmbedResponse {
+    embeddings: CohereEmbeddingsByType,
+}
+
+#[derive(Deserialize)]
+struct CohereEmbeddingsByType {
+    float: Vec<Vec<f32>>,
+}
+
+/// Client for the Cohere v2 `/embed` API.
+pub struct CohereEmbeddingProvider {
+    client: Client,
+    api_key: String,
+    model: String,
+    api_url: String,
+    dimensions: Option<usize>,
+    rate_limiter: RateLimiter,
+}
+
+impl CohereEmbeddingProvider {
+    const MAX_BATCH_SIZE: usize = 96;
+    const REQUESTS_PER_SECOND: u32 = 30;
+    const MAX_RETRIES: u32 = 5;
+
+    pub fn new(api_key: String, model: String, api_url: String, dimensions: Option<usize>) -> Self {
+        Self {
+            client: Client::new(),
+            api_key,
+            model,
+            api_url: api_url.trim_end_matches('/').to_string(),
+            dimensions,
+            rate_limiter: RateLimiter::new(Self::REQUESTS_PER_SECOND, Self::MAX_RETRIES)
+                .with_api_family("cohere_embeddings"),
+        }
+    }
+
+    fn input_type(task: EmbeddingTask) -> &'static str {
+        match task {
+            EmbeddingTask::Query => "search_query",
+            EmbeddingTask::Passage => "search_document",
+        }
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for CohereEmbeddingProvider {
+    fn provider_type(&self) -> &'static str {
+        "cohere"
+    }
+
+    fn model_name(&self) -> &str {
+        &self.model
+    }
+
+    fn max_batch_size(&self) -> usize {
+        Self::MAX_BATCH_SIZE
+    }
+
+    fn dimensions(&self) -> Option<usize> {
+        self.dimensions
+    }
+
+    async fn embed_batch(&self, texts: &[String], task: EmbeddingTask) -> Result<Vec<Vec<f32>>> {
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+        let request = CohereEmbedRequest {
+            texts,
+            model: &self.model,
+            input_type: Self::input_type(task),
+            embedding_types: ["float"],
+            truncate: "NONE",
+            output_dimension: self.dimensions,
+        };
+
+        let response: CohereEmbedResponse = self
+            .rate_limiter
+            .execute_with_retry(|| async {
+                let response = self
+                    .client
+                    .post(format!("{}/v2/embed", self.api_url))
+                    .bearer_auth(&self.api_key)
+                    .json(&request)
+                    .with_trace_context()
+                    .send()
+                    .await
+                    .map_err(|e| RetryableError::Transient(e.into()))?;
+                if !response.status().is_success() {
+                    return Err(classify_error_response("cohere", response).await);
+                }
+                response
+                    .json::<CohereEmbedResponse>()
+                    .await
+                    .map_err(|e| RetryableError::Permanent(anyhow!(e)))
+            })
+            .await?;
+
+        Ok(response.embeddings.float)
+    }
+}
@@ -0,0 +1,477 @@
+//! Embedding providers that Rust services can call without going through the
+//! AI service. The current provider comes from the `embedding_providers`
+//! settings table, so a deployment switches backends there rather than in
+//! code.
+
+mod cohere;
+mod openai;
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use reqwest::{Response, StatusCode};
+use serde_json::Value as JsonValue;
+use sqlx::PgPool;
+use tokio::sync::Mutex;
+
+use crate::clients::ai::AIClient;
+use crate::db::repositories::EmbeddingProviderRepository;
+use crate::encryption::{EncryptedData, EncryptionService};
+use crate::rate_limiter::RetryableError;
+
+pub use cohere::CohereEmbeddingProvider;
+pub use openai::OpenAIEmbeddingProvider;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmbeddingTask {
+    Query,
+    Passage,
+}
+
+impl EmbeddingTask {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EmbeddingTask::Query => "query",
+            EmbeddingTask::Passage => "passage",
+        }
+    }
+}
+
+/// Vectors for a batch of texts, with the model and dimension metadata that
+/// is stored next to them in the `embeddings` table.
+#[derive(Debug, Clone)]
+pub struct Embeddings {
+    pub vectors: Vec<Vec<f32>>,
+    pub model_name: String,
+    pub dimensions: i16,
+}
+
+#[async_trait]
+pub trait EmbeddingProvider: Send + Sync {
+    fn provider_type(&self) -> &'static str;
+
+    fn model_name(&self) -> &str;
+
+    /// Largest number of texts accepted by a single `embed_batch` call.
+    fn max_batch_size(&self) -> usize;
+
+    /// Configured output dimensions, when the provider lets us choose them.
+    fn dimensions(&self) -> Option<usize> {
+        None
+    }
+
+    /// Embeds at most `max_batch_size` texts, one vector per text.
+    async fn embed_batch(&self, texts: &[String], task: EmbeddingTask) -> Result<Vec<Vec<f32>>>;
+}
+
+/// Embeds `texts` in batches sized for the provider and checks that every
+/// text got a vector of the same length.
+pub async fn embed_texts(
+    provider: &dyn EmbeddingProvider,
+    texts: &[String],
+    task: EmbeddingTask,
+) -> Result<Embeddings> {
+    let batch_size = provider.max_batch_size().max(1);
+    let mut vectors = Vec::with_capacity(texts.len());
+    for batch in texts.chunks(batch_size) {
+        let batch_vectors = provider.embed_batch(batch, task).await?;
+        if batch_vectors.len() != batch.len() {
+            return Err(anyhow!(
+                "{} provider returned {} embeddings for {} texts",
+                provider.provider_type(),
+                batch_vectors.len(),
+                batch.len()
+            ));
+        }
+        vectors.extend(batch_vectors);
+    }
+
+    let dimensions = vectors
+        .first()
+        .map(|v| v.len())
+        .or(provider.dimensions())
+        .unwrap_or(0);
+    if let Some(v) = vectors.iter().find(|v| v.len() != dimensions) {
+        return Err(anyhow!(
+            "{} provider returned embeddings of mixed dimensions ({} and {})",
+            provider.provider_type(),
+            dimensions,
+            v.len()
+        ));
+    }
+    if let Some(expected) = provider.dimensions()
+        && !vectors.is_empty()
+        && expected != dimensions
+    {
+        return Err(anyhow!(
+            "{} provider returned {}-dimensional embeddings, expected {}",
+            provider.provider_type(),
+            dimensions,
+            expected
+        ));
+    }
+
+    Ok(Embeddings {
+        vectors,
+        model_name: provider.model_name().to_string(),
+        dimensions: i16::try_from(dimensions)
+            .map_err(|_| anyhow!("Embedding dimensions {} out of range", dimensions))?,
+    })
+}
+
+/// Provider settings parsed from an `embedding_providers` row.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EmbeddingProviderConfig {
+    OpenAI {
+        api_key: String,
+        model: String,
+        api_url: String,
+        dimensions: Option<usize>,
+    },
+    Cohere {
+        api_key: String,
+        model: String,
+        api_url: String,
+        dimensions: Option<usize>,
+    },
+    /// An OpenAI-compatible embedding server running inside the deployment,
+    /// e.g. the ONNX-backed `embeddings` container.
+    Local { api_url: String, model: String },
+    /// Providers without a Rust client (Jina, Bedrock) are served by the AI
+    /// service.
+    AIService,
+}
+
+impl EmbeddingProviderConfig {
+    /// Parses the decrypted `config` JSON of a provider row. Keys follow the
+    /// camelCase names the web app writes (`apiKey`, `apiUrl`, ...).
+    pub fn from_record(provider_type: &str, config: &JsonValue) -> Result<Self> {
+        let str_field = |key: &str| {
+            config
+                .get(key)
+                .and_then(|v| v.as_str())
+                .filter(|s| !s.is_empty())
+                .map(str::to_string)
+        };
+        let dimensions = config
+            .get("dimensions")
+            .and_then(|v| v.as_u64())
+            .map(|d| d as usize);
+
+        match provider_type.to_lowercase().as_str() {
+            "openai" => Ok(Self::OpenAI {
+                api_key: str_field("apiKey")
+                    .ok_or_else(|| anyhow!("apiKey is required for OpenAI provider"))?,
+                model: str_field("model").unwrap_or_else(|| "text-embedding-3-small".to_string()),
+                api_url: str_field("apiUrl")
+                    .unwrap_or_else(|| "https://api.openai.com/v1".to_string()),
+                dimensions: dimensions.or(Some(1024)),
+            }),
+            "cohere" => Ok(Self::Cohere {
+                api_key: str_field("apiKey")
+                    .ok_or_else(|| anyhow!("apiKey is required for Cohere provider"))?,
+                model: str_field("model").unwrap_or_else(|| "embed-v4.0".to_string()),
+                api_url: str_field("apiUrl")
+                    .unwrap_or_else(|| "https://api.cohere.com".to_string()),
+                dimensions,
+            }),
+            "local" => Ok(Self::Local {
+                api_url: str_field("apiUrl")
+                    .ok_or_else(|| anyhow!("apiUrl is required for local provider"))?,
+                model: str_field("model")
+                    .ok_or_else(|| anyhow!("model is required for local provider"))?,
+            }),
+            "jina" | "bedrock" => Ok(Self::AIService),
+            other => Err(anyhow!("Unknown embedding provider type: {}", other)),
+        }
+    }
+
+    pub fn build(self, ai_client: &AIClient) -> Arc<dyn EmbeddingProvider> {
+        match self {
+            Self::OpenAI {
+                api_key,
+                model,
+                api_url,
+                dimensions,
+            } => Arc::new(OpenAIEmbeddingProvider::new(
+                Some(api_key),
+                model,
+                api_url,
+                dimensions,
+            )),
+            Self::Cohere {
+                api_key,
+                model,
+                api_url,
+                dimensions,
+            } => Arc::new(CohereEmbeddingProvider::new(
+                api_key, model, api_url, dimensions,
+            )),
+            Self::Local { api_url, model } => {
+                Arc::new(OpenAIEmbeddingProvider::local(model, api_url))
+            }
+            Self::AIService => Arc::new(AIServiceEmbeddingProvider::new(ai_client.clone())),
+        }
+    }
+}
+
+/// Sends texts to the AI service's `/embeddings` endpoint, which embeds with
+/// whatever provider it has loaded.
+pub struct AIServiceEmbeddingProvider {
+    client: AIClient,
+}
+
+impl AIServiceEmbeddingProvider {
+    const MAX_BATCH_SIZE: usize = 64;
+
+    pub fn new(client: AIClient) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for AIServiceEmbeddingProvider {
+    fn provider_type(&self) -> &'static str {
+        "ai_service"
+    }
+
+    fn model_name(&self) -> &str {
+        ""
+    }
+
+    fn max_batch_size(&self) -> usize {
+        Self::MAX_BATCH_SIZE
+    }
+
+    async fn embed_batch(&self, texts: &[String], task: EmbeddingTask) -> Result<Vec<Vec<f32>>> {
+        let embeddings = self
+            .client
+            .generate_embeddings_with_options(
+                texts.to_vec(),
+                Some(task.as_str().to_string()),
+                None,
+                Some("none".to_string()),
+                Some("high".to_string()),
+            )
+            .await?;
+        embeddings
+            .into_iter()
+            .map(|e| {
+                e.chunk_embeddings
+                    .into_iter()
+                    .next()
+                    .ok_or_else(|| anyhow!("AI service returned no embedding for a text"))
+            })
+            .collect()
+    }
+}
+
+/// Resolves the current provider from settings, caching it briefly so a
+/// settings change is picked up without a restart.
+pub struct EmbeddingProviderResolver {
+    repo: EmbeddingProviderRepository,
+    ai_client: AIClient,
+    cached: Mutex<Option<(Instant, Arc<dyn EmbeddingProvider>)>>,
+}
+
+impl EmbeddingProviderResolver {
+    const CACHE_TTL: Duration = Duration::from_secs(90);
+
+    pub fn new(pool: &PgPool, ai_client: AIClient) -> Self {
+        Self {
+            repo: EmbeddingProviderRepository::new(pool),
+            ai_client,
+            cached: Mutex::new(None),
+        }
+    }
+
+    pub async fn current(&self) -> Result<Arc<dyn EmbeddingProvider>> {
+        let mut cached = self.cached.lock().await;
+        if let Some((loaded_at, provider)) = cached.as_ref()
+            && loaded_at.elapsed() < Self::CACHE_TTL
+        {
+            return Ok(provider.clone());
+        }
+
+        let config = match self.repo.get_current().await? {
+            Some(record) => {
+                let config = decrypt_config(record.config)?;
+                EmbeddingProviderConfig::from_record(&record.provider_type, &config)?
+            }
+            None => EmbeddingProviderConfig::AIService,
+        };
+        let provider = config.build(&self.ai_client);
+        *cached = Some((Instant::now(), provider.clone()));
+        Ok(provider)
+    }
+}
+
+/// Provider configs may be stored as `{"encrypted_data": ...}` by the web app.
+fn decrypt_config(config: JsonValue) -> Result<JsonValue> {
+    match config.get("encrypted_data") {
+        Some(encrypted_data) => {
+            let encrypted_data: EncryptedData = serde_json::from_value(encrypted_data.clone())?;
+            EncryptionService::new()?.decrypt_json(&encrypted_data)
+        }
+        None => Ok(config),
+    }
+}
+
+/// Maps an HTTP error response from an embedding API onto retry semantics:
+/// 429 waits for `Retry-After`, 5xx backs off, anything else fails fast.
+async fn classify_error_response(provider: &str, response: Response) -> RetryableError {
+    let status = response.status();
+    if status == StatusCode::TOO_MANY_REQUESTS {
+        let retry_after = response
+            .headers()
+            .get("Retry-After")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<u64>().ok())
+            .map(Duration::from_secs);
+        return match retry_after {
+            Some(retry_after) => RetryableError::RateLimited {
+                retry_after,
+                message: format!("{} embeddings rate limit exceeded", provider),
+            },
+            None => {
+                RetryableError::Throttled(anyhow!("{} embeddings rate limit exceeded", provider))
+            }
+        };
+    }
+
+    let error_text = response.text().await.unwrap_or_default();
+    let error = anyhow!(
+        "{} embeddings API returned HTTP {}: {}",
+        provider,
+        status,
+        error_text
+    );
+    if status.is_server_error() {
+        RetryableError::Transient(error)
+    } else {
+        RetryableError::Permanent(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct FakeProvider {
+        batch_size: usize,
+        dimensions: usize,
+        calls: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl EmbeddingProvider for FakeProvider {
+        fn provider_type(&self) -> &'static str {
+            "fake"
+        }
+
+        fn model_name(&self) -> &str {
+            "fake-model"
+        }
+
+        fn max_batch_size(&self) -> usize {
+            self.batch_size
+        }
+
+        async fn embed_batch(
+            &self,
+            texts: &[String],
+            _task: EmbeddingTask,
+        ) -> Result<Vec<Vec<f32>>> {
+            assert!(texts.len() <= self.batch_size);
+            let call = self.calls.fetch_add(1, Ordering::SeqCst);
+            // The third batch comes back with the wrong size when dimensions
+            // is odd, so tests can exercise validation.
+            let dims = if call == 2 && self.dimensions % 2 == 1 {
+                self.dimensions + 1
+            } else {
+                self.dimensions
+            };
+            Ok(texts.iter().map(|_| vec![0.5; dims]).collect())
+        }
+    }
+
+    fn texts(n: usize) -> Vec<String> {
+        (0..n).map(|i| format!("text {}", i)).collect()
+    }
+
+    #[tokio::test]
+    async fn test_embed_texts_splits_into_provider_batches() {
+        let provider = FakeProvider {
+            batch_size: 4,
+            dimensions: 8,
+            calls: AtomicUsize::new(0),
+        };
+
+        let embeddings = embed_texts(&provider, &texts(10), EmbeddingTask::Passage)
+            .await
+            .unwrap();
+
+        assert_eq!(provider.calls.load(Ordering::SeqCst), 3);
+        assert_eq!(embeddings.vectors.len(), 10);
+        assert_eq!(embeddings.dimensions, 8);
+        assert_eq!(embeddings.model_name, "fake-model");
+    }
+
+    #[tokio::test]
+    async fn test_embed_texts_rejects_mixed_dimensions() {
+        let provider = FakeProvider {
+            batch_size: 2,
+            dimensions: 3,
+            calls: AtomicUsize::new(0),
+        };
+
+        let result = embed_texts(&provider, &texts(6), EmbeddingTask::Query).await;
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_config_from_record_applies_provider_defaults() {
+        let config =
+            EmbeddingProviderConfig::from_record("openai", &json!({"apiKey": "sk-test"})).unwrap();
+        assert_eq!(
+            config,
+            EmbeddingProviderConfig::OpenAI {
+                api_key: "sk-test".to_string(),
+                model: "text-embedding-3-small".to_string(),
+                api_url: "https://api.openai.com/v1".to_string(),
+                dimensions: Some(1024),
+            }
+        );
+
+        let config = EmbeddingProviderConfig::from_record(
+            "cohere",
+            &json!({"apiKey": "co-test", "dimensions": 512}),
+        )
+        .unwrap();
+        assert_eq!(
+            config,
+            EmbeddingProviderConfig::Cohere {
+                api_key: "co-test".to_string(),
+                model: "embed-v4.0".to_string(),
+                api_url: "https://api.cohere.com".to_string(),
+                dimensions: Some(512),
+            }
+        );
+
+        let config = EmbeddingProviderConfig::from_record("jina", &json!({})).unwrap();
+        assert_eq!(config, EmbeddingProviderConfig::AIService);
+    }
+
+    #[test]
+    fn test_config_from_record_requires_credentials() {
+        assert!(EmbeddingProviderConfig::from_record("openai", &json!({})).is_err());
+        assert!(EmbeddingProviderConfig::from_record("cohere", &json!({"apiKey": ""})).is_err());
+        assert!(EmbeddingProviderConfig::from_record("local", &json!({"model": "nomic"})).is_err());
+        assert!(EmbeddingProviderConfig::from_record("unknown", &json!({})).is_err());
+    }
+}
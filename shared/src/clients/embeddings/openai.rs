@@ -0,0 +1,138 @@
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+use super::{EmbeddingProvider, EmbeddingTask, classify_error_response};
+use crate::rate_limiter::{RateLimiter, RetryableError};
+use crate::telemetry::http_client::RequestBuilderExt;
+
+#[derive(Serialize)]
+struct OpenAIEmbeddingRequest<'a> {
+    input: &'a [String],
+    model: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dimensions: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct OpenAIEmbeddingResponse {
+    data: Vec<OpenAIEmbeddingData>,
+}
+
+#[derive(Deserialize)]
+struct OpenAIEmbeddingData {
+    index: usize,
+    embedding: Vec<f32>,
+}
+
+/// Client for the OpenAI `/embeddings` API and OpenAI-compatible servers,
+/// including the self-hosted ONNX embedding server used by `local`
+/// providers.
+pub struct OpenAIEmbeddingProvider {
+    client: Client,
+    api_key: Option<String>,
+    model: String,
+    api_url: String,
+    dimensions: Option<usize>,
+    max_batch_size: usize,
+    rate_limiter: RateLimiter,
+    provider_type: &'static str,
+}
+
+impl OpenAIEmbeddingProvider {
+    const MAX_BATCH_SIZE: usize = 2048;
+    const LOCAL_MAX_BATCH_SIZE: usize = 32;
+    const REQUESTS_PER_SECOND: u32 = 50;
+    const MAX_RETRIES: u32 = 5;
+
+    pub fn new(
+        api_key: Option<String>,
+        model: String,
+        api_url: String,
+        dimensions: Option<usize>,
+    ) -> Self {
+        Self {
+            client: Client::new(),
+            api_key,
+            model,
+            api_url: api_url.trim_end_matches('/').to_string(),
+            dimensions,
+            max_batch_size: Self::MAX_BATCH_SIZE,
+            rate_limiter: RateLimiter::new(Self::REQUESTS_PER_SECOND, Self::MAX_RETRIES)
+                .with_api_family("openai_embeddings"),
+            provider_type: "openai",
+        }
+    }
+
+    /// A local server needs no key and keeps the model's native dimensions;
+    /// batches stay small since it usually runs on CPU.
+    pub fn local(model: String, api_url: String) -> Self {
+        Self {
+            max_batch_size: Self::LOCAL_MAX_BATCH_SIZE,
+            rate_limiter: RateLimiter::new(Self::REQUESTS_PER_SECOND, Self::MAX_RETRIES)
+                .with_api_family("local_embeddings"),
+            provider_type: "local",
+            ..Self::new(None, model, api_url, None)
+        }
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for OpenAIEmbeddingProvider {
+    fn provider_type(&self) -> &'static str {
+        self.provider_type
+    }
+
+    fn model_name(&self) -> &str {
+        &self.model
+    }
+
+    fn max_batch_size(&self) -> usize {
+        self.max_batch_size
+    }
+
+    fn dimensions(&self) -> Option<usize> {
+        self.dimensions
+    }
+
+    async fn embed_batch(&self, texts: &[String], _task: EmbeddingTask) -> Result<Vec<Vec<f32>>> {
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+        let request = OpenAIEmbeddingRequest {
+            input: texts,
+            model: &self.model,
+            dimensions: self.dimensions,
+        };
+
+        let mut response: OpenAIEmbeddingResponse = self
+            .rate_limiter
+            .execute_with_retry(|| async {
+                let mut builder = self
+                    .client
+                    .post(format!("{}/embeddings", self.api_url))
+                    .json(&request);
+                if let Some(api_key) = &self.api_key {
+                    builder = builder.bearer_auth(api_key);
+                }
+                let response = builder
+                    .with_trace_context()
+                    .send()
+                    .await
+                    .map_err(|e| RetryableError::Transient(e.into()))?;
+                if !response.status().is_success() {
+                    return Err(classify_error_response(self.provider_type, response).await);
+                }
+                response
+                    .json::<OpenAIEmbeddingResponse>()
+                    .await
+                    .map_err(|e| RetryableError::Permanent(anyhow!(e)))
+            })
+            .await?;
+
+        // The API documents `index` rather than guaranteeing input order.
+        response.data.sort_by_key(|d| d.index);
+        Ok(response.data.into_iter().map(|d| d.embedding).collect())
+    }
+}
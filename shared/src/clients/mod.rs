@@ -1,2 +1,3 @@
 pub mod ai;
 pub mod docling;
+pub mod embeddings;
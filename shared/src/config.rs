@@ -1,5 +1,6 @@
-use std::env;
-use std::process;
+use crate::config_loader::ConfigLoader;
+use crate::models::SourceType;
+use std::collections::HashMap;
 use url::Url;
 
 #[derive(Debug, Clone)]
@@ -24,8 +25,37 @@ pub struct SearcherConfig {
     pub rrf_k: f32,
     pub semantic_search_timeout_ms: u64,
     pub rag_context_window: i32,
+    /// How long search responses stay cached; zero disables the cache.
+    pub search_cache_ttl_secs: u64,
     pub recency_boost_weight: f32,
     pub recency_half_life_days: f32,
+    /// Weight of the time-decay factor applied to fused hybrid scores; zero
+    /// disables it.
+    pub hybrid_recency_weight: f32,
+    /// Half-lives overriding `recency_half_life_days` for the hybrid decay,
+    /// e.g. shorter for chat than for wiki pages.
+    pub recency_half_life_days_by_source_type: HashMap<SourceType, f32>,
+    /// Off switch for per-user ranking signals (authorship, views, teams).
+    pub personalization_enabled: bool,
+    pub personalization_weight: f32,
+    /// Documents kept by the summary-embedding stage before chunk-level
+    /// semantic search; zero searches all chunks.
+    pub coarse_retrieval_documents: i64,
+    /// Embed search queries with the configured provider directly instead of
+    /// through the AI service.
+    pub direct_query_embeddings: bool,
+    /// Rerank hybrid results with the cross-encoder at `rerank_url` unless
+    /// the request says otherwise.
+    pub rerank_enabled: bool,
+    /// Cohere-compatible rerank endpoint (`POST {query, documents, top_n}`).
+    pub rerank_url: Option<String>,
+    pub rerank_model: Option<String>,
+    /// Fused candidates sent to the reranker.
+    pub rerank_top_n: usize,
+    pub rerank_timeout_ms: u64,
+    /// Also enforce document permissions with Postgres row-level security by
+    /// running user searches as the `omni_document_reader` role.
+    pub row_level_security: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -34,64 +64,41 @@ pub struct IndexerConfig {
     pub redis: RedisConfig,
     pub port: u16,
     pub ai_service_url: String,
+    /// Also enforce document permissions with Postgres row-level security on
+    /// reads made for a user, as the searcher does for searches.
+    pub row_level_security: bool,
 }
 
-fn get_required_env(key: &str) -> String {
-    env::var(key).unwrap_or_else(|_| {
-        eprintln!("ERROR: Required environment variable '{}' is not set", key);
-        eprintln!("Please set this variable in your .env file or environment");
-        process::exit(1);
-    })
-}
-
-fn get_optional_env(key: &str, default: &str) -> String {
-    env::var(key).unwrap_or_else(|_| default.to_string())
-}
-
-fn parse_port(port_str: &str, var_name: &str) -> u16 {
-    port_str.parse::<u16>().unwrap_or_else(|_| {
-        eprintln!(
-            "ERROR: Invalid port number in '{}': '{}'",
-            var_name, port_str
-        );
-        eprintln!("Port must be a number between 1 and 65535");
-        process::exit(1);
-    })
-}
-
-fn validate_url(url: &str, var_name: &str) -> String {
-    if url.is_empty() {
-        eprintln!("ERROR: Environment variable '{}' cannot be empty", var_name);
-        process::exit(1);
-    }
-
+/// Accept only the URL schemes services connect with.
+fn parse_url(url: &str) -> Result<String, String> {
     if !url.starts_with("http://")
         && !url.starts_with("https://")
         && !url.starts_with("redis://")
         && !url.starts_with("postgresql://")
     {
-        eprintln!("ERROR: Invalid URL format in '{}': '{}'", var_name, url);
-        eprintln!("URL must start with http://, https://, redis://, or postgresql://");
-        process::exit(1);
+        return Err("must start with http://, https://, redis://, or postgresql://".to_string());
     }
+    Url::parse(url).map_err(|e| e.to_string())?;
+    Ok(url.to_string())
+}
 
-    url.to_string()
+fn check_port(loader: &mut ConfigLoader, key: &str, port: u16) {
+    loader.check(key, port > 0, "a port between 1 and 65535");
 }
 
 impl DatabaseConfig {
     pub fn from_env() -> Self {
-        let database_host = get_required_env("DATABASE_HOST");
-        let database_username = get_required_env("DATABASE_USERNAME");
-        let database_name = get_required_env("DATABASE_NAME");
-        let database_password = get_required_env("DATABASE_PASSWORD");
-        let database_port = get_optional_env("DATABASE_PORT", "5432");
-
-        let port = parse_port(&database_port, "DATABASE_PORT");
+        ConfigLoader::load_or_exit(Self::load)
+    }
 
-        // Check if SSL should be required
-        let require_ssl = get_optional_env("DATABASE_SSL", "false")
-            .parse::<bool>()
-            .unwrap_or(false);
+    pub fn load(loader: &mut ConfigLoader) -> Self {
+        let database_host: String = loader.required("DATABASE_HOST");
+        let database_username: String = loader.required("DATABASE_USERNAME");
+        let database_name: String = loader.required("DATABASE_NAME");
+        let database_password = loader.secret("DATABASE_PASSWORD");
+        let port: u16 = loader.optional("DATABASE_PORT", "5432");
+        check_port(loader, "DATABASE_PORT", port);
+        let require_ssl: bool = loader.optional("DATABASE_SSL", "false");
 
         // Construct base URL
         let base_url = format!(
@@ -99,38 +106,21 @@ impl DatabaseConfig {
             database_username, database_password, database_host, port, database_name
         );
 
-        // Parse URL and add SSL parameter if required
-        let mut url = Url::parse(&base_url).unwrap_or_else(|e| {
-            eprintln!("ERROR: Failed to parse database URL: {}", e);
-            eprintln!("URL: {}", base_url);
-            process::exit(1);
-        });
-
-        if require_ssl {
-            url.query_pairs_mut().append_pair("sslmode", "require");
-        }
-
-        let database_url = url.to_string();
-
-        let max_connections_str = get_optional_env("DB_MAX_CONNECTIONS", "10");
-        let max_connections = max_connections_str.parse::<u32>().unwrap_or_else(|_| {
-            eprintln!(
-                "ERROR: Invalid max connections in 'DB_MAX_CONNECTIONS': '{}'",
-                max_connections_str
-            );
-            eprintln!("Must be a positive number");
-            process::exit(1);
-        });
-
-        let acquire_timeout_str = get_optional_env("DB_ACQUIRE_TIMEOUT_SECONDS", "3");
-        let acquire_timeout_seconds = acquire_timeout_str.parse::<u64>().unwrap_or_else(|_| {
-            eprintln!(
-                "ERROR: Invalid timeout in 'DB_ACQUIRE_TIMEOUT_SECONDS': '{}'",
-                acquire_timeout_str
-            );
-            eprintln!("Must be a positive number");
-            process::exit(1);
-        });
+        // Add SSL parameter if required. With missing settings the URL may not
+        // parse, but those are already reported and the config is unused.
+        let database_url = match Url::parse(&base_url) {
+            Ok(mut url) => {
+                if require_ssl {
+                    url.query_pairs_mut().append_pair("sslmode", "require");
+                }
+                url.to_string()
+            }
+            Err(_) => base_url,
+        };
+
+        let max_connections: u32 = loader.optional("DB_MAX_CONNECTIONS", "10");
+        loader.check("DB_MAX_CONNECTIONS", max_connections > 0, "at least 1");
+        let acquire_timeout_seconds: u64 = loader.optional("DB_ACQUIRE_TIMEOUT_SECONDS", "3");
 
         Self {
             database_url,
@@ -143,8 +133,11 @@ impl DatabaseConfig {
 
 impl RedisConfig {
     pub fn from_env() -> Self {
-        let redis_url = get_required_env("REDIS_URL");
-        let redis_url = validate_url(&redis_url, "REDIS_URL");
+        ConfigLoader::load_or_exit(Self::load)
+    }
+
+    pub fn load(loader: &mut ConfigLoader) -> Self {
+        let redis_url = loader.required_with("REDIS_URL", parse_url);
 
         Self { redis_url }
     }
@@ -152,54 +145,89 @@ impl RedisConfig {
 
 impl SearcherConfig {
     pub fn from_env() -> Self {
-        let database = DatabaseConfig::from_env();
-        let redis = RedisConfig::from_env();
-
-        let port_str = get_required_env("PORT");
-        let port = parse_port(&port_str, "PORT");
-
-        let ai_service_url = get_required_env("AI_SERVICE_URL");
-        let ai_service_url = validate_url(&ai_service_url, "AI_SERVICE_URL");
-
-        let rrf_k = get_optional_env("RRF_K", "60.0")
-            .parse::<f32>()
-            .unwrap_or_else(|_| {
-                eprintln!("ERROR: Invalid value for RRF_K");
-                eprintln!("Must be a positive float");
-                process::exit(1);
-            });
-
-        let semantic_search_timeout_ms = get_optional_env("SEMANTIC_SEARCH_TIMEOUT_MS", "5000")
-            .parse::<u64>()
-            .unwrap_or_else(|_| {
-                eprintln!("ERROR: Invalid value for SEMANTIC_SEARCH_TIMEOUT_MS");
-                eprintln!("Must be a positive integer");
-                process::exit(1);
-            });
-
-        let rag_context_window = get_optional_env("RAG_CONTEXT_WINDOW", "2")
-            .parse::<i32>()
-            .unwrap_or_else(|_| {
-                eprintln!("ERROR: Invalid value for RAG_CONTEXT_WINDOW");
-                eprintln!("Must be a positive integer");
-                process::exit(1);
-            });
-
-        let recency_boost_weight = get_optional_env("RECENCY_BOOST_WEIGHT", "0.2")
-            .parse::<f32>()
-            .unwrap_or_else(|_| {
-                eprintln!("ERROR: Invalid value for RECENCY_BOOST_WEIGHT");
-                eprintln!("Must be a non-negative float");
-                process::exit(1);
-            });
-
-        let recency_half_life_days = get_optional_env("RECENCY_HALF_LIFE_DAYS", "30.0")
-            .parse::<f32>()
-            .unwrap_or_else(|_| {
-                eprintln!("ERROR: Invalid value for RECENCY_HALF_LIFE_DAYS");
-                eprintln!("Must be a positive float");
-                process::exit(1);
-            });
+        ConfigLoader::load_or_exit(Self::load)
+    }
+
+    pub fn load(loader: &mut ConfigLoader) -> Self {
+        let database = DatabaseConfig::load(loader);
+        let redis = RedisConfig::load(loader);
+
+        let port: u16 = loader.required("PORT");
+        check_port(loader, "PORT", port);
+        let ai_service_url = loader.required_with("AI_SERVICE_URL", parse_url);
+
+        let rrf_k: f32 = loader.optional("RRF_K", "60.0");
+        loader.check("RRF_K", rrf_k > 0.0, "a positive number");
+
+        let semantic_search_timeout_ms: u64 = loader.optional("SEMANTIC_SEARCH_TIMEOUT_MS", "5000");
+        let rag_context_window: i32 = loader.optional("RAG_CONTEXT_WINDOW", "2");
+        loader.check(
+            "RAG_CONTEXT_WINDOW",
+            rag_context_window >= 0,
+            "a non-negative integer",
+        );
+
+        let search_cache_ttl_secs: u64 = loader.optional("SEARCH_CACHE_TTL_SECS", "300");
+
+        let recency_boost_weight: f32 = loader.optional("RECENCY_BOOST_WEIGHT", "0.2");
+        loader.check(
+            "RECENCY_BOOST_WEIGHT",
+            is_non_negative(recency_boost_weight),
+            "a non-negative number",
+        );
+        let recency_half_life_days: f32 = loader.optional("RECENCY_HALF_LIFE_DAYS", "30.0");
+        loader.check(
+            "RECENCY_HALF_LIFE_DAYS",
+            recency_half_life_days.is_finite() && recency_half_life_days > 0.0,
+            "a positive number",
+        );
+        let hybrid_recency_weight: f32 = loader.optional("HYBRID_RECENCY_WEIGHT", "0.2");
+        loader.check(
+            "HYBRID_RECENCY_WEIGHT",
+            is_non_negative(hybrid_recency_weight),
+            "a non-negative number",
+        );
+        let recency_half_life_days_by_source_type = loader.optional_with(
+            "RECENCY_HALF_LIFE_DAYS_BY_SOURCE_TYPE",
+            "",
+            parse_half_lives_by_source_type,
+        );
+
+        let personalization_enabled: bool = loader.optional("PERSONALIZATION_ENABLED", "true");
+        let personalization_weight: f32 = loader.optional("PERSONALIZATION_WEIGHT", "0.3");
+        loader.check(
+            "PERSONALIZATION_WEIGHT",
+            is_non_negative(personalization_weight),
+            "a non-negative number",
+        );
+
+        let coarse_retrieval_documents: i64 = loader.optional("COARSE_RETRIEVAL_DOCUMENTS", "0");
+        loader.check(
+            "COARSE_RETRIEVAL_DOCUMENTS",
+            coarse_retrieval_documents >= 0,
+            "a non-negative integer",
+        );
+
+        let direct_query_embeddings: bool = loader.optional("DIRECT_QUERY_EMBEDDINGS", "false");
+
+        let rerank_enabled: bool = loader.optional("RERANK_ENABLED", "false");
+        let rerank_url = loader.optional_with("RERANK_URL", "", |url| {
+            if url.is_empty() {
+                Ok(None)
+            } else {
+                parse_url(url).map(Some)
+            }
+        });
+        loader.check(
+            "RERANK_URL",
+            !rerank_enabled || rerank_url.is_some(),
+            "set when RERANK_ENABLED is true",
+        );
+        let rerank_model: String = loader.optional("RERANK_MODEL", "");
+        let rerank_top_n: usize = loader.optional("RERANK_TOP_N", "50");
+        loader.check("RERANK_TOP_N", rerank_top_n > 0, "a positive integer");
+        let rerank_timeout_ms: u64 = loader.optional("RERANK_TIMEOUT_MS", "1000");
+        let row_level_security: bool = loader.optional("SEARCH_ROW_LEVEL_SECURITY", "false");
 
         Self {
             database,
@@ -209,28 +237,73 @@ impl SearcherConfig {
             rrf_k,
             semantic_search_timeout_ms,
             rag_context_window,
+            search_cache_ttl_secs,
             recency_boost_weight,
             recency_half_life_days,
+            hybrid_recency_weight,
+            recency_half_life_days_by_source_type,
+            personalization_enabled,
+            personalization_weight,
+            coarse_retrieval_documents,
+            direct_query_embeddings,
+            rerank_enabled,
+            rerank_url,
+            rerank_model: Some(rerank_model).filter(|model| !model.is_empty()),
+            rerank_top_n,
+            rerank_timeout_ms,
+            row_level_security,
         }
     }
 }
 
+fn is_non_negative(value: f32) -> bool {
+    value.is_finite() && value >= 0.0
+}
+
+/// Parse `slack=7,confluence=180` into half-lives in days per source type.
+pub fn parse_half_lives_by_source_type(value: &str) -> Result<HashMap<SourceType, f32>, String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (source_type, days) = pair
+                .split_once('=')
+                .ok_or_else(|| format!("'{}' is not a source_type=days pair", pair))?;
+            let source_type: SourceType =
+                serde_json::from_value(serde_json::Value::String(source_type.trim().to_string()))
+                    .map_err(|_| format!("unknown source type '{}'", source_type.trim()))?;
+            let days = days
+                .trim()
+                .parse::<f32>()
+                .ok()
+                .filter(|d| d.is_finite() && *d > 0.0)
+                .ok_or_else(|| format!("half-life for '{}' must be a positive number", pair))?;
+            Ok((source_type, days))
+        })
+        .collect()
+}
+
 impl IndexerConfig {
     pub fn from_env() -> Self {
-        let database = DatabaseConfig::from_env();
-        let redis = RedisConfig::from_env();
+        ConfigLoader::load_or_exit(Self::load)
+    }
 
-        let port_str = get_required_env("PORT");
-        let port = parse_port(&port_str, "PORT");
+    pub fn load(loader: &mut ConfigLoader) -> Self {
+        let database = DatabaseConfig::load(loader);
+        let redis = RedisConfig::load(loader);
 
-        let ai_service_url = get_required_env("AI_SERVICE_URL");
-        let ai_service_url = validate_url(&ai_service_url, "AI_SERVICE_URL");
+        let port: u16 = loader.required("PORT");
+        check_port(loader, "PORT", port);
+        let ai_service_url = loader.required_with("AI_SERVICE_URL", parse_url);
+        let row_level_security: bool = loader.optional("SEARCH_ROW_LEVEL_SECURITY", "false");
 
         Self {
             database,
             redis,
             port,
             ai_service_url,
+            row_level_security,
         }
     }
 }
@@ -0,0 +1,407 @@
+//! Layered service configuration: environment variables, then an optional
+//! config file, then built-in defaults.
+//!
+//! `OMNI_CONFIG_FILE` names a file of `KEY=value` lines (the `.env` format).
+//! [`load_env_files`] merges it into the process environment without
+//! overriding variables that are already set, so every setting can come from
+//! either place.
+//!
+//! Typed configs read their settings through a [`ConfigLoader`], which
+//! records where each value came from and collects every invalid or missing
+//! setting instead of stopping at (or silently defaulting past) the first.
+//! A misconfigured service reports all of its problems at startup, and
+//! `--print-config` shows the resolved configuration without starting it.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::process;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+pub const CONFIG_FILE_ENV: &str = "OMNI_CONFIG_FILE";
+pub const PRINT_CONFIG_FLAG: &str = "--print-config";
+
+/// Keys whose value came from the config file rather than the environment.
+static FILE_KEYS: OnceLock<HashSet<String>> = OnceLock::new();
+
+/// Load `.env` from the working directory, then the file named by
+/// `OMNI_CONFIG_FILE`. Neither overrides variables that are already set.
+/// Exits if the config file cannot be read.
+pub fn load_env_files() {
+    dotenvy::dotenv().ok();
+
+    let Some(path) = std::env::var(CONFIG_FILE_ENV)
+        .ok()
+        .filter(|p| !p.trim().is_empty())
+    else {
+        return;
+    };
+    let read_error = |e: dotenvy::Error| -> ! {
+        eprintln!(
+            "ERROR: Cannot read config file '{}' (from {}): {}",
+            path, CONFIG_FILE_ENV, e
+        );
+        process::exit(1);
+    };
+    let entries = dotenvy::from_path_iter(&path).unwrap_or_else(|e| read_error(e));
+    let mut file_keys = HashSet::new();
+    for entry in entries {
+        let (key, _) = entry.unwrap_or_else(|e| read_error(e));
+        if std::env::var_os(&key).is_none() {
+            file_keys.insert(key);
+        }
+    }
+    dotenvy::from_path(&path).unwrap_or_else(|e| read_error(e));
+    FILE_KEYS.set(file_keys).ok();
+}
+
+/// Whether the service was started with `--print-config`.
+pub fn print_config_requested() -> bool {
+    std::env::args().any(|arg| arg == PRINT_CONFIG_FLAG)
+}
+
+/// Resolve a service's configuration with `load` and print it, or print its
+/// problems. Returns the process exit code.
+pub fn print_config<T>(service: &str, load: impl FnOnce(&mut ConfigLoader) -> T) -> i32 {
+    let mut loader = ConfigLoader::from_env();
+    load(&mut loader);
+
+    println!("Configuration for {}", service);
+    if let Ok(path) = std::env::var(CONFIG_FILE_ENV) {
+        println!("Config file: {}", path);
+    }
+    print!("{}", loader.report());
+
+    if loader.errors.is_empty() {
+        0
+    } else {
+        eprint!("{}", ConfigErrors(loader.errors));
+        1
+    }
+}
+
+/// Where a setting's value came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigOrigin {
+    Env,
+    File,
+    Default,
+    /// Required but not set anywhere.
+    Missing,
+}
+
+impl fmt::Display for ConfigOrigin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ConfigOrigin::Env => "env",
+            ConfigOrigin::File => "file",
+            ConfigOrigin::Default => "default",
+            ConfigOrigin::Missing => "missing",
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ConfigEntry {
+    pub key: String,
+    pub value: Option<String>,
+    pub origin: ConfigOrigin,
+    pub secret: bool,
+}
+
+/// The settings a config read, one line each, with secrets masked.
+pub struct ConfigReport<'a>(&'a [ConfigEntry]);
+
+impl fmt::Display for ConfigReport<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self.0.iter().map(|e| e.key.len()).max().unwrap_or(0);
+        for entry in self.0 {
+            let value = match (&entry.value, entry.secret) {
+                (None, _) => "<unset>",
+                (Some(_), true) => "********",
+                (Some(value), false) => value.as_str(),
+            };
+            writeln!(
+                f,
+                "  {:width$} = {} ({})",
+                entry.key,
+                value,
+                entry.origin,
+                width = width
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Every problem found while loading a config.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigErrors(pub Vec<String>);
+
+impl fmt::Display for ConfigErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "ERROR: Invalid configuration ({} problem{}):",
+            self.0.len(),
+            if self.0.len() == 1 { "" } else { "s" }
+        )?;
+        for error in &self.0 {
+            writeln!(f, "  - {}", error)?;
+        }
+        writeln!(
+            f,
+            "Set these in the environment or in the file named by {}. Run with {} to see the resolved configuration.",
+            CONFIG_FILE_ENV, PRINT_CONFIG_FLAG
+        )
+    }
+}
+
+pub struct ConfigLoader {
+    env: HashMap<String, String>,
+    file_keys: HashSet<String>,
+    entries: Vec<ConfigEntry>,
+    errors: Vec<String>,
+    invalid_keys: HashSet<String>,
+}
+
+impl ConfigLoader {
+    pub fn from_env() -> Self {
+        Self::new(
+            std::env::vars().collect(),
+            FILE_KEYS.get().cloned().unwrap_or_default(),
+        )
+    }
+
+    /// A loader over the given variables, of which `file_keys` came from the
+    /// config file.
+    pub fn new(env: HashMap<String, String>, file_keys: HashSet<String>) -> Self {
+        Self {
+            env,
+            file_keys,
+            entries: Vec::new(),
+            errors: Vec::new(),
+            invalid_keys: HashSet::new(),
+        }
+    }
+
+    /// Load a config with `load`, exiting with every problem found if any
+    /// setting is missing or invalid.
+    pub fn load_or_exit<T>(load: impl FnOnce(&mut ConfigLoader) -> T) -> T {
+        let mut loader = Self::from_env();
+        let config = load(&mut loader);
+        if let Err(errors) = loader.finish() {
+            eprint!("{}", errors);
+            process::exit(1);
+        }
+        config
+    }
+
+    pub fn finish(self) -> Result<Vec<ConfigEntry>, ConfigErrors> {
+        if self.errors.is_empty() {
+            Ok(self.entries)
+        } else {
+            Err(ConfigErrors(self.errors))
+        }
+    }
+
+    pub fn report(&self) -> ConfigReport<'_> {
+        ConfigReport(&self.entries)
+    }
+
+    /// A setting that must be set.
+    pub fn required<T>(&mut self, key: &str) -> T
+    where
+        T: FromStr + Default,
+        T::Err: fmt::Display,
+    {
+        self.read(key, None, false, |v| {
+            v.parse().map_err(|e: T::Err| e.to_string())
+        })
+    }
+
+    /// A required setting whose value is never printed.
+    pub fn secret(&mut self, key: &str) -> String {
+        self.read(key, None, true, |v| Ok(v.to_string()))
+    }
+
+    /// A setting with a default, given as it would be written in the
+    /// environment. Empty values count as unset.
+    pub fn optional<T>(&mut self, key: &str, default: &str) -> T
+    where
+        T: FromStr + Default,
+        T::Err: fmt::Display,
+    {
+        self.read(key, Some(default), false, |v| {
+            v.parse().map_err(|e: T::Err| e.to_string())
+        })
+    }
+
+    /// A setting with a default and a custom parser, for values such as
+    /// URLs or lists that need more than `FromStr`.
+    pub fn optional_with<T: Default>(
+        &mut self,
+        key: &str,
+        default: &str,
+        parse: impl FnOnce(&str) -> Result<T, String>,
+    ) -> T {
+        self.read(key, Some(default), false, parse)
+    }
+
+    /// A required setting with a custom parser.
+    pub fn required_with<T: Default>(
+        &mut self,
+        key: &str,
+        parse: impl FnOnce(&str) -> Result<T, String>,
+    ) -> T {
+        self.read(key, None, false, parse)
+    }
+
+    /// Record that `key` does not meet `requirement` unless `valid`. Keys
+    /// that already failed to parse are not reported twice.
+    pub fn check(&mut self, key: &str, valid: bool, requirement: &str) {
+        if !valid && self.invalid_keys.insert(key.to_string()) {
+            let value = self
+                .entries
+                .iter()
+                .rev()
+                .find(|e| e.key == key)
+                .filter(|e| !e.secret)
+                .and_then(|e| e.value.clone())
+                .unwrap_or_default();
+            self.errors
+                .push(format!("{}='{}': must be {}", key, value, requirement));
+        }
+    }
+
+    fn lookup(&self, key: &str) -> Option<(String, ConfigOrigin)> {
+        let value = self.env.get(key).filter(|v| !v.trim().is_empty())?;
+        let origin = if self.file_keys.contains(key) {
+            ConfigOrigin::File
+        } else {
+            ConfigOrigin::Env
+        };
+        Some((value.clone(), origin))
+    }
+
+    fn read<T: Default>(
+        &mut self,
+        key: &str,
+        default: Option<&str>,
+        secret: bool,
+        parse: impl FnOnce(&str) -> Result<T, String>,
+    ) -> T {
+        let (value, origin) = match (self.lookup(key), default) {
+            (Some(found), _) => found,
+            (None, Some(default)) => (default.to_string(), ConfigOrigin::Default),
+            (None, None) => {
+                self.entries.push(ConfigEntry {
+                    key: key.to_string(),
+                    value: None,
+                    origin: ConfigOrigin::Missing,
+                    secret,
+                });
+                self.invalid_keys.insert(key.to_string());
+                self.errors.push(format!("{} is required but not set", key));
+                return T::default();
+            }
+        };
+
+        let parsed = parse(value.trim());
+        if let Err(e) = &parsed {
+            let shown = if secret { "********" } else { value.as_str() };
+            self.invalid_keys.insert(key.to_string());
+            self.errors
+                .push(format!("{}='{}' (from {}): {}", key, shown, origin, e));
+        }
+        self.entries.push(ConfigEntry {
+            key: key.to_string(),
+            value: Some(value),
+            origin,
+            secret,
+        });
+        parsed.unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn loader(env: &[(&str, &str)], file_keys: &[&str]) -> ConfigLoader {
+        ConfigLoader::new(
+            env.iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            file_keys.iter().map(|k| k.to_string()).collect(),
+        )
+    }
+
+    #[test]
+    fn test_reads_values_and_origins() {
+        let mut loader = loader(
+            &[
+                ("PORT", "3001"),
+                ("RRF_K", "45"),
+                ("DB_PASSWORD", "hunter2"),
+            ],
+            &["RRF_K"],
+        );
+        assert_eq!(loader.required::<u16>("PORT"), 3001);
+        assert_eq!(loader.optional::<f32>("RRF_K", "60.0"), 45.0);
+        assert!(loader.optional::<bool>("FEATURE_ENABLED", "true"));
+        assert_eq!(loader.secret("DB_PASSWORD"), "hunter2");
+
+        let entries = loader.finish().unwrap();
+        let origins: Vec<ConfigOrigin> = entries.iter().map(|e| e.origin).collect();
+        assert_eq!(
+            origins,
+            vec![
+                ConfigOrigin::Env,
+                ConfigOrigin::File,
+                ConfigOrigin::Default,
+                ConfigOrigin::Env
+            ]
+        );
+        let report = ConfigReport(&entries).to_string();
+        assert!(report.contains("RRF_K           = 45 (file)"));
+        assert!(report.contains("DB_PASSWORD     = ******** (env)"));
+        assert!(!report.contains("hunter2"));
+    }
+
+    #[test]
+    fn test_collects_every_error() {
+        let mut loader = loader(
+            &[("PORT", "http"), ("MAX_SYNCS", "0"), ("DB_SSL", "yes")],
+            &[],
+        );
+        let _: u16 = loader.required("PORT");
+        let _: String = loader.required("DATABASE_HOST");
+        let max_syncs: usize = loader.optional("MAX_SYNCS", "10");
+        loader.check("MAX_SYNCS", max_syncs >= 1, "at least 1");
+        let _: bool = loader.optional("DB_SSL", "false");
+        loader.check("DB_SSL", false, "never reported twice");
+
+        let errors = loader.finish().unwrap_err();
+        assert_eq!(
+            errors.0,
+            vec![
+                "PORT='http' (from env): invalid digit found in string",
+                "DATABASE_HOST is required but not set",
+                "MAX_SYNCS='0': must be at least 1",
+                "DB_SSL='yes' (from env): provided string was not `true` or `false`",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_empty_values_count_as_unset() {
+        let mut loader = loader(&[("RRF_K", " "), ("PORT", "")], &[]);
+        assert_eq!(loader.optional::<f32>("RRF_K", "60.0"), 60.0);
+        let _: u16 = loader.required("PORT");
+        assert_eq!(
+            loader.finish().unwrap_err().0,
+            vec!["PORT is required but not set"]
+        );
+    }
+}
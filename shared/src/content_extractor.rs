@@ -11,6 +11,14 @@ use zip::ZipArchive;
 #[path = "content_extractor_xlsx.rs"]
 mod xlsx_extractor;
 
+#[path = "content_extractor_tables.rs"]
+mod tables;
+
+pub use tables::{
+    ExtractedTable, TABLES_HEADING, extract_tables, has_extractable_tables, render_tables,
+    with_structured_tables,
+};
+
 const DEFAULT_SPREADSHEET_MAX_EXTRACTED_ROWS: usize = 1000;
 
 /// Extract human-readable text content from raw file bytes based on MIME type.
@@ -194,6 +202,26 @@ fn strip_html_tags(html: &str) -> String {
     decoded.split_whitespace().collect::<Vec<_>>().join(" ")
 }
 
+/// Separates pages in extracted PDF text. Kept in the stored content so chunk
+/// offsets can be mapped back to page numbers.
+pub const PAGE_BREAK: char = '\x0c';
+
+/// Trims surrounding whitespace without dropping page breaks, so blank
+/// leading pages still count towards page numbers.
+fn trim_preserving_page_breaks(text: &str) -> &str {
+    text.trim_matches(|c: char| c.is_whitespace() && c != PAGE_BREAK)
+}
+
+/// 1-based page containing `byte_offset` of extracted text, or `None` when
+/// the text has no page breaks.
+pub fn page_at_offset(text: &str, byte_offset: usize) -> Option<i32> {
+    if !text.contains(PAGE_BREAK) {
+        return None;
+    }
+    let preceding = &text.as_bytes()[..byte_offset.min(text.len())];
+    Some(preceding.iter().filter(|&&b| b == PAGE_BREAK as u8).count() as i32 + 1)
+}
+
 fn extract_pdf_text(data: &[u8]) -> Result<String> {
     let data_owned = data.to_vec();
     let result = std::panic::catch_unwind(move || {
@@ -202,7 +230,8 @@ fn extract_pdf_text(data: &[u8]) -> Result<String> {
     });
 
     match result {
-        Ok(Ok(text)) => Ok(text.trim().to_string()),
+        Ok(Ok(text)) if text.trim().is_empty() => Ok(String::new()),
+        Ok(Ok(text)) => Ok(trim_preserving_page_breaks(&text).to_string()),
         Ok(Err(e)) => {
             warn!("Skipping PDF with unextractable text: {}", e);
             Ok(String::new())
@@ -668,6 +697,25 @@ mod tests {
         assert!(result.is_empty());
     }
 
+    #[test]
+    fn test_trim_preserving_page_breaks() {
+        assert_eq!(
+            trim_preserving_page_breaks("\x0c\n first page \x0csecond page\n\x0c \n"),
+            "\x0c\n first page \x0csecond page\n\x0c"
+        );
+        assert_eq!(trim_preserving_page_breaks("  text  "), "text");
+    }
+
+    #[test]
+    fn test_page_at_offset() {
+        let text = "first\x0csecond\x0c\x0cfourth";
+        assert_eq!(page_at_offset(text, 0), Some(1));
+        assert_eq!(page_at_offset(text, text.find("second").unwrap()), Some(2));
+        assert_eq!(page_at_offset(text, text.find("fourth").unwrap()), Some(4));
+        assert_eq!(page_at_offset(text, text.len() + 10), Some(4));
+        assert_eq!(page_at_offset("no pages", 3), None);
+    }
+
     #[test]
     fn test_strip_html_tags_fallback() {
         let html = "<div><script>var x=1;</script>Hello <b>world</b>&nbsp;&amp; bye</div>";
@@ -0,0 +1,365 @@
+use anyhow::{Context, Result, anyhow};
+use calamine::{Reader, open_workbook_auto_from_rs};
+use docx_rs::read_docx;
+use std::io::Cursor;
+use tracing::warn;
+
+use super::{effective_mime_type, extract_paragraph_text, is_textual_spreadsheet_cell};
+
+/// Top-level heading of the section that structured tables are rendered
+/// under, so chunks in it carry a heading path of `Tables > <table name>`.
+pub const TABLES_HEADING: &str = "Tables";
+
+/// Rows above the header row are usually a title or notes, so the header is
+/// looked for among the first few rows.
+const MAX_HEADER_SCAN_ROWS: usize = 5;
+
+/// A table with its header row, so each cell can be read in context.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtractedTable {
+    /// Sheet name for spreadsheets, "Table N" for tables in documents.
+    pub name: String,
+    pub headers: Vec<String>,
+    /// Cells by column, aligned with `headers`. Empty cells are empty strings.
+    pub rows: Vec<Vec<String>>,
+}
+
+impl ExtractedTable {
+    /// Build a table from a grid of cells, taking the first row that looks
+    /// like a header (at least two cells, all text) as the header. Returns
+    /// `None` when there is no header or no data below it.
+    pub fn from_grid(name: &str, grid: Vec<Vec<String>>) -> Option<Self> {
+        let mut rows = grid
+            .into_iter()
+            .map(|row| {
+                row.into_iter()
+                    .map(|cell| cell.split_whitespace().collect::<Vec<_>>().join(" "))
+                    .collect::<Vec<String>>()
+            })
+            .filter(|row| row.iter().any(|cell| !cell.is_empty()));
+
+        let headers = rows.by_ref().take(MAX_HEADER_SCAN_ROWS).find(|row| {
+            let filled: Vec<&String> = row.iter().filter(|cell| !cell.is_empty()).collect();
+            filled.len() >= 2 && filled.iter().all(|cell| is_textual_spreadsheet_cell(cell))
+        })?;
+        let rows: Vec<Vec<String>> = rows.collect();
+        if rows.is_empty() {
+            return None;
+        }
+
+        let width = rows
+            .iter()
+            .map(Vec::len)
+            .chain(std::iter::once(headers.len()))
+            .max()
+            .unwrap_or_default();
+        let headers = (0..width)
+            .map(|i| match headers.get(i) {
+                Some(header) if !header.is_empty() => header.clone(),
+                _ => format!("Column {}", i + 1),
+            })
+            .collect();
+
+        Some(Self {
+            name: name.to_string(),
+            headers,
+            rows,
+        })
+    }
+
+    /// One line per row naming each filled cell by its header, e.g.
+    /// `Team: Platform | Quarter: Q3 | Budget: 120000`.
+    pub fn records(&self) -> impl Iterator<Item = String> + '_ {
+        self.rows.iter().filter_map(|row| {
+            let fields: Vec<String> = row
+                .iter()
+                .zip(&self.headers)
+                .filter(|(cell, _)| !cell.is_empty())
+                .map(|(cell, header)| format!("{}: {}", header, cell))
+                .collect();
+            (!fields.is_empty()).then(|| fields.join(" | "))
+        })
+    }
+}
+
+/// Whether `extract_tables` understands the file's format.
+pub fn has_extractable_tables(mime_type: &str, filename: Option<&str>) -> bool {
+    matches!(
+        effective_mime_type(mime_type, filename).as_str(),
+        "text/csv"
+            | "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"
+            | "application/vnd.ms-excel"
+            | "application/vnd.openxmlformats-officedocument.wordprocessingml.document"
+    )
+}
+
+/// Extract the tables of a spreadsheet or Word document, reading at most
+/// `max_rows` rows in total. Other formats have none.
+pub fn extract_tables(
+    data: &[u8],
+    mime_type: &str,
+    filename: Option<&str>,
+    max_rows: usize,
+) -> Result<Vec<ExtractedTable>> {
+    let effective_mime = effective_mime_type(mime_type, filename);
+    let grids: Vec<(String, Vec<Vec<String>>)> = match effective_mime.as_str() {
+        "text/csv" => {
+            let name = filename
+                .and_then(|f| f.rsplit('/').next())
+                .and_then(|f| f.rsplit_once('.').map(|(stem, _)| stem).or(Some(f)))
+                .filter(|stem| !stem.is_empty())
+                .unwrap_or("Table 1");
+            let text = String::from_utf8_lossy(data);
+            vec![(name.to_string(), parse_csv(&text, max_rows))]
+        }
+        "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet" => {
+            super::xlsx_extractor::extract_xlsx_sheet_grids(data, max_rows)?
+        }
+        "application/vnd.ms-excel" => extract_xls_grids(data, max_rows)?,
+        "application/vnd.openxmlformats-officedocument.wordprocessingml.document" => {
+            extract_docx_grids(data, max_rows)?
+        }
+        _ => Vec::new(),
+    };
+
+    Ok(grids
+        .into_iter()
+        .filter_map(|(name, grid)| ExtractedTable::from_grid(&name, grid))
+        .collect())
+}
+
+/// Render tables as a markdown section of header-qualified records, so that
+/// every chunk of a table says what its numbers mean.
+pub fn render_tables(tables: &[ExtractedTable]) -> String {
+    let mut text = String::new();
+    for table in tables {
+        let records: Vec<String> = table.records().collect();
+        if records.is_empty() {
+            continue;
+        }
+        if text.is_empty() {
+            text.push_str(&format!("# {}\n", TABLES_HEADING));
+        }
+        text.push_str(&format!("\n## {}\n", table.name));
+        for record in records {
+            text.push_str(&record);
+            text.push('\n');
+        }
+    }
+    text
+}
+
+/// Combine a file's extracted text with its structured tables. Spreadsheets
+/// are all table, so the records replace their flattened text; other
+/// documents keep their text with the records after it.
+pub fn with_structured_tables(
+    text: String,
+    tables: &[ExtractedTable],
+    mime_type: &str,
+    filename: Option<&str>,
+) -> String {
+    let rendered = render_tables(tables);
+    if rendered.is_empty() {
+        return text;
+    }
+    if super::is_spreadsheet_mime(&effective_mime_type(mime_type, filename))
+        || text.trim().is_empty()
+    {
+        return rendered;
+    }
+    format!("{}\n\n{}", text.trim_end(), rendered)
+}
+
+/// Minimal RFC 4180 parsing: quoted fields may contain commas, newlines and
+/// doubled quotes.
+fn parse_csv(text: &str, max_rows: usize) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if rows.len() >= max_rows {
+            return rows;
+        }
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' if in_quotes => in_quotes = false,
+            '"' if field.is_empty() => in_quotes = true,
+            ',' if !in_quotes => row.push(std::mem::take(&mut field)),
+            '\n' if !in_quotes => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            '\r' if !in_quotes => {}
+            _ => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    rows.truncate(max_rows);
+    rows
+}
+
+fn extract_xls_grids(data: &[u8], max_rows: usize) -> Result<Vec<(String, Vec<Vec<String>>)>> {
+    let mut workbook =
+        open_workbook_auto_from_rs(Cursor::new(data)).context("Failed to open Excel workbook")?;
+
+    let mut grids = Vec::new();
+    let mut rows_read = 0usize;
+    for sheet_name in workbook.sheet_names().to_owned() {
+        let Ok(range) = workbook.worksheet_range(&sheet_name) else {
+            continue;
+        };
+        let rows: Vec<Vec<String>> = range
+            .rows()
+            .take(max_rows.saturating_sub(rows_read))
+            .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+            .collect();
+        rows_read += rows.len();
+        grids.push((sheet_name, rows));
+    }
+
+    Ok(grids)
+}
+
+fn extract_docx_grids(data: &[u8], max_rows: usize) -> Result<Vec<(String, Vec<Vec<String>>)>> {
+    let data_owned = data.to_vec();
+    let result = std::panic::catch_unwind(move || {
+        let docx = read_docx(&data_owned).context("Failed to read DOCX")?;
+        let mut grids = Vec::new();
+        let mut rows_read = 0usize;
+
+        for child in &docx.document.children {
+            let docx_rs::DocumentChild::Table(table) = child else {
+                continue;
+            };
+            let mut rows = Vec::new();
+            for row in &table.rows {
+                if rows_read >= max_rows {
+                    break;
+                }
+                let docx_rs::TableChild::TableRow(row) = row;
+                let cells = row
+                    .cells
+                    .iter()
+                    .map(|cell| {
+                        let docx_rs::TableRowChild::TableCell(cell) = cell;
+                        let mut cell_text = String::new();
+                        for content in &cell.children {
+                            if let docx_rs::TableCellContent::Paragraph(p) = content {
+                                if !cell_text.is_empty() {
+                                    cell_text.push(' ');
+                                }
+                                extract_paragraph_text(p, &mut cell_text);
+                            }
+                        }
+                        cell_text
+                    })
+                    .collect();
+                rows.push(cells);
+                rows_read += 1;
+            }
+            grids.push((format!("Table {}", grids.len() + 1), rows));
+        }
+
+        Ok(grids)
+    });
+
+    match result {
+        Ok(grids) => grids,
+        Err(_) => {
+            warn!("DOCX table extraction panicked — likely a malformed document");
+            Err(anyhow!(
+                "DOCX table extraction panicked due to malformed content"
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(rows: &[&[&str]]) -> Vec<Vec<String>> {
+        rows.iter()
+            .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_from_grid_skips_title_rows_and_names_unlabelled_columns() {
+        let table = ExtractedTable::from_grid(
+            "Budget",
+            grid(&[
+                &["FY2025 budget"],
+                &[],
+                &["Team", "Quarter", "", "Budget"],
+                &["Platform", "Q3", "approved", "$120,000"],
+                &["Search", "Q3", "", "95000"],
+            ]),
+        )
+        .unwrap();
+
+        assert_eq!(table.headers, vec!["Team", "Quarter", "Column 3", "Budget"]);
+        let records: Vec<String> = table.records().collect();
+        assert_eq!(
+            records,
+            vec![
+                "Team: Platform | Quarter: Q3 | Column 3: approved | Budget: $120,000",
+                "Team: Search | Quarter: Q3 | Budget: 95000",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_from_grid_requires_header_and_data() {
+        assert!(ExtractedTable::from_grid("Numbers", grid(&[&["1", "2"], &["3", "4"]])).is_none());
+        assert!(ExtractedTable::from_grid("Header only", grid(&[&["Name", "Age"]])).is_none());
+    }
+
+    #[test]
+    fn test_extract_csv_tables_handles_quotes() {
+        let csv = "Team,Notes,Budget\r\nPlatform,\"Infra, \"\"core\"\"\nteam\",120000\n";
+        let tables =
+            extract_tables(csv.as_bytes(), "text/csv", Some("exports/budget.csv"), 100).unwrap();
+
+        assert_eq!(tables.len(), 1);
+        assert_eq!(tables[0].name, "budget");
+        assert_eq!(
+            tables[0].records().collect::<Vec<_>>(),
+            vec!["Team: Platform | Notes: Infra, \"core\" team | Budget: 120000"]
+        );
+    }
+
+    #[test]
+    fn test_with_structured_tables_replaces_spreadsheet_text_only() {
+        let tables = vec![
+            ExtractedTable::from_grid("Sheet1", grid(&[&["Team", "Budget"], &["Platform", "5"]]))
+                .unwrap(),
+        ];
+        let rendered = "# Tables\n\n## Sheet1\nTeam: Platform | Budget: 5\n";
+
+        assert_eq!(
+            with_structured_tables("Team\tBudget".to_string(), &tables, "text/csv", None),
+            rendered
+        );
+        let docx = with_structured_tables(
+            "Intro\n".to_string(),
+            &tables,
+            "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+            None,
+        );
+        assert_eq!(docx, format!("Intro\n\n{}", rendered));
+        assert_eq!(
+            with_structured_tables("Intro".to_string(), &[], "text/csv", None),
+            "Intro"
+        );
+    }
+}
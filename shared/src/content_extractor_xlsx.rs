@@ -12,6 +12,8 @@ pub(super) fn extract_xlsx_text_filtered(data: &[u8], max_rows: usize) -> Result
 
 const MAX_XLSX_SHARED_STRINGS_TEXT_BYTES: usize = 32 * 1024 * 1024;
 const MAX_XLSX_SHARED_STRING_COUNT: usize = 1_000_000;
+/// Columns past this are dropped from table grids.
+const MAX_XLSX_TABLE_COLUMNS: usize = 256;
 
 fn extract_xlsx_text_streaming(data: &[u8], max_rows: usize) -> Result<String> {
     let cursor = Cursor::new(data);
@@ -46,6 +48,40 @@ fn extract_xlsx_text_streaming(data: &[u8], max_rows: usize) -> Result<String> {
     Ok(text.trim().to_string())
 }
 
+/// Cell text of each sheet laid out by column, up to `max_rows` non-empty
+/// rows across the workbook. Unlike the flattened text, numeric cells are
+/// kept, since a table puts them in context.
+pub(super) fn extract_xlsx_sheet_grids(
+    data: &[u8],
+    max_rows: usize,
+) -> Result<Vec<(String, Vec<Vec<String>>)>> {
+    let cursor = Cursor::new(data);
+    let mut archive = ZipArchive::new(cursor).context("Failed to read XLSX as ZIP")?;
+
+    let shared_strings = read_xlsx_shared_strings(&mut archive)?;
+    let sheets = read_xlsx_sheet_entries(&mut archive)?;
+
+    let mut grids = Vec::new();
+    let mut rows_read = 0usize;
+    for (sheet_name, sheet_path) in sheets {
+        if rows_read >= max_rows {
+            break;
+        }
+        let rows = read_xlsx_sheet_grid(
+            &mut archive,
+            &sheet_path,
+            &shared_strings,
+            max_rows - rows_read,
+        )?;
+        rows_read += rows.len();
+        if !rows.is_empty() {
+            grids.push((sheet_name, rows));
+        }
+    }
+
+    Ok(grids)
+}
+
 fn read_xlsx_sheet_entries(
     archive: &mut ZipArchive<Cursor<&[u8]>>,
 ) -> Result<Vec<(String, String)>> {
@@ -316,6 +352,116 @@ fn read_xlsx_sheet_rows(
     Ok(text)
 }
 
+fn read_xlsx_sheet_grid(
+    archive: &mut ZipArchive<Cursor<&[u8]>>,
+    sheet_path: &str,
+    shared_strings: &[String],
+    max_rows: usize,
+) -> Result<Vec<Vec<String>>> {
+    let file = archive
+        .by_name(sheet_path)
+        .with_context(|| format!("Failed to read worksheet {} from XLSX", sheet_path))?;
+    let mut reader = XmlReader::from_reader(BufReader::new(file));
+    reader.config_mut().trim_text(true);
+    let mut buf = Vec::new();
+    let mut rows = Vec::new();
+
+    let mut inside_row = false;
+    let mut inside_value = false;
+    let mut inside_inline_text = false;
+    let mut current_row: Vec<String> = Vec::new();
+    let mut current_column = 0usize;
+    let mut current_cell_type: Option<String> = None;
+    let mut current_cell_value = String::new();
+    let mut current_inline_text = String::new();
+
+    while rows.len() < max_rows {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(e)) if e.name().as_ref() == b"row" => {
+                inside_row = true;
+                current_row.clear();
+            }
+            Ok(Event::Start(e)) if inside_row && e.name().as_ref() == b"c" => {
+                // Empty cells are usually left out, so place each cell by
+                // its reference (e.g. "C7") rather than by its position.
+                current_column = xml_attr_value(&e, b"r")
+                    .and_then(|reference| xlsx_column_index(&reference))
+                    .unwrap_or(current_row.len());
+                current_cell_type = xml_attr_value(&e, b"t");
+                current_cell_value.clear();
+                current_inline_text.clear();
+            }
+            Ok(Event::Start(e)) if inside_row && e.name().as_ref() == b"v" => {
+                inside_value = true;
+            }
+            Ok(Event::Start(e)) if inside_row && e.name().as_ref() == b"t" => {
+                inside_inline_text = true;
+            }
+            Ok(Event::Text(e)) if inside_value => {
+                current_cell_value.push_str(&String::from_utf8_lossy(e.as_ref()));
+            }
+            Ok(Event::Text(e)) if inside_inline_text => {
+                current_inline_text.push_str(&String::from_utf8_lossy(e.as_ref()));
+            }
+            Ok(Event::End(e)) if e.name().as_ref() == b"v" => {
+                inside_value = false;
+            }
+            Ok(Event::End(e)) if e.name().as_ref() == b"t" => {
+                inside_inline_text = false;
+            }
+            Ok(Event::End(e)) if e.name().as_ref() == b"c" => {
+                let cell_text = resolve_xlsx_cell_text(
+                    current_cell_type.as_deref(),
+                    &current_cell_value,
+                    &current_inline_text,
+                    shared_strings,
+                )
+                .unwrap_or_default();
+                let trimmed = cell_text.trim();
+                if !trimmed.is_empty() && current_column < MAX_XLSX_TABLE_COLUMNS {
+                    if current_row.len() <= current_column {
+                        current_row.resize(current_column + 1, String::new());
+                    }
+                    current_row[current_column] = trimmed.to_string();
+                }
+            }
+            Ok(Event::End(e)) if e.name().as_ref() == b"row" => {
+                if !current_row.is_empty() {
+                    rows.push(std::mem::take(&mut current_row));
+                }
+                inside_row = false;
+            }
+            Ok(Event::Eof) => break,
+            Err(e) => {
+                return Err(anyhow!(
+                    "Error reading XLSX worksheet {}: {}",
+                    sheet_path,
+                    e
+                ));
+            }
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    Ok(rows)
+}
+
+/// Zero-based column of a cell reference such as "AB12".
+fn xlsx_column_index(reference: &str) -> Option<usize> {
+    let letters: Vec<u8> = reference
+        .bytes()
+        .take_while(u8::is_ascii_alphabetic)
+        .collect();
+    if letters.is_empty() || letters.len() > 3 {
+        return None;
+    }
+    let column = letters.iter().fold(0usize, |column, letter| {
+        column * 26 + (letter.to_ascii_uppercase() - b'A' + 1) as usize
+    });
+    Some(column - 1)
+}
+
 fn resolve_xlsx_cell_text(
     cell_type: Option<&str>,
     cell_value: &str,
@@ -388,6 +534,40 @@ mod tests {
         assert!(!result.contains("$10.00"));
     }
 
+    #[test]
+    fn test_extract_xlsx_tables_keep_numbers_in_column_context() {
+        let data = create_test_xlsx_with_shared_strings(&[
+            &["Team", "Quarter", "Budget"],
+            &["Platform", "Q3", "120000"],
+            &["Search", "Q4", "95000"],
+        ]);
+        let tables = crate::content_extractor::extract_tables(
+            &data,
+            "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+            None,
+            10,
+        )
+        .unwrap();
+
+        assert_eq!(tables.len(), 1);
+        assert_eq!(tables[0].name, "Sheet1");
+        assert_eq!(
+            tables[0].records().collect::<Vec<_>>(),
+            vec![
+                "Team: Platform | Quarter: Q3 | Budget: 120000",
+                "Team: Search | Quarter: Q4 | Budget: 95000",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_xlsx_column_index() {
+        assert_eq!(xlsx_column_index("A1"), Some(0));
+        assert_eq!(xlsx_column_index("c7"), Some(2));
+        assert_eq!(xlsx_column_index("AB12"), Some(27));
+        assert_eq!(xlsx_column_index("12"), None);
+    }
+
     #[test]
     fn test_extract_xlsx_streaming_applies_row_limit() {
         let data = create_test_xlsx(&[&["Header"], &["Alice"], &["Bob"], &["Carol"]]);
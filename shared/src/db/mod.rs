@@ -1,6 +1,7 @@
 pub mod error;
 pub mod pool;
 pub mod repositories;
+pub mod row_level_security;
 
 pub use error::DatabaseError;
 pub use pool::DatabasePool;
@@ -0,0 +1,145 @@
+use crate::audit::{ActorType, AuditAction, AuditEvent};
+use crate::db::error::DatabaseError;
+use crate::utils::generate_ulid;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use sqlx::{FromRow, PgPool, types::time::OffsetDateTime};
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct AuditLogEntry {
+    pub id: String,
+    pub actor_type: ActorType,
+    pub actor_id: Option<String>,
+    pub actor_email: Option<String>,
+    pub action: AuditAction,
+    pub resource_type: Option<String>,
+    pub resource_id: Option<String>,
+    pub details: JsonValue,
+    #[serde(with = "time::serde::iso8601")]
+    pub created_at: OffsetDateTime,
+}
+
+/// Narrows an audit log listing. `actor` matches an actor id or,
+/// case-insensitively, an actor email; `from` is inclusive and `to` exclusive.
+#[derive(Debug, Clone, Default)]
+pub struct AuditLogFilter {
+    pub actor: Option<String>,
+    pub action: Option<AuditAction>,
+    pub from: Option<OffsetDateTime>,
+    pub to: Option<OffsetDateTime>,
+}
+
+pub struct AuditLogRepository {
+    pool: PgPool,
+}
+
+impl AuditLogRepository {
+    pub fn new(pool: &PgPool) -> Self {
+        Self { pool: pool.clone() }
+    }
+
+    pub async fn insert(&self, events: &[AuditEvent]) -> Result<u64, DatabaseError> {
+        if events.is_empty() {
+            return Ok(0);
+        }
+
+        let ids: Vec<String> = events.iter().map(|_| generate_ulid()).collect();
+        let actor_types: Vec<ActorType> = events.iter().map(|e| e.actor.actor_type).collect();
+        let actor_ids: Vec<Option<String>> = events.iter().map(|e| e.actor.id.clone()).collect();
+        let actor_emails: Vec<Option<String>> =
+            events.iter().map(|e| e.actor.email.clone()).collect();
+        let actions: Vec<AuditAction> = events.iter().map(|e| e.action).collect();
+        let resource_types: Vec<Option<String>> =
+            events.iter().map(|e| e.resource_type.clone()).collect();
+        let resource_ids: Vec<Option<String>> =
+            events.iter().map(|e| e.resource_id.clone()).collect();
+        let details: Vec<JsonValue> = events.iter().map(|e| e.details.clone()).collect();
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO audit_log
+                (id, actor_type, actor_id, actor_email, action, resource_type, resource_id, details)
+            SELECT * FROM UNNEST(
+                $1::text[], $2::text[], $3::text[], $4::text[], $5::text[], $6::text[],
+                $7::text[], $8::jsonb[]
+            )
+            "#,
+        )
+        .bind(&ids)
+        .bind(&actor_types)
+        .bind(&actor_ids)
+        .bind(&actor_emails)
+        .bind(&actions)
+        .bind(&resource_types)
+        .bind(&resource_ids)
+        .bind(&details)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    pub async fn search(
+        &self,
+        filter: &AuditLogFilter,
+        limit: i64,
+        offset: i64,
+    ) -> Result<(Vec<AuditLogEntry>, i64), DatabaseError> {
+        let total: i64 = sqlx::query_scalar(
+            r#"
+            SELECT COUNT(*)
+            FROM audit_log
+            WHERE ($1::text IS NULL OR actor_id = $1 OR lower(actor_email) = lower($1))
+              AND ($2::text IS NULL OR action = $2)
+              AND ($3::timestamptz IS NULL OR created_at >= $3)
+              AND ($4::timestamptz IS NULL OR created_at < $4)
+            "#,
+        )
+        .bind(&filter.actor)
+        .bind(filter.action)
+        .bind(filter.from)
+        .bind(filter.to)
+        .fetch_one(&self.pool)
+        .await?;
+
+        let entries = sqlx::query_as::<_, AuditLogEntry>(
+            r#"
+            SELECT id, actor_type, actor_id, actor_email, action, resource_type, resource_id,
+                   details, created_at
+            FROM audit_log
+            WHERE ($1::text IS NULL OR actor_id = $1 OR lower(actor_email) = lower($1))
+              AND ($2::text IS NULL OR action = $2)
+              AND ($3::timestamptz IS NULL OR created_at >= $3)
+              AND ($4::timestamptz IS NULL OR created_at < $4)
+            ORDER BY created_at DESC, id DESC
+            LIMIT $5 OFFSET $6
+            "#,
+        )
+        .bind(&filter.actor)
+        .bind(filter.action)
+        .bind(filter.from)
+        .bind(filter.to)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok((entries, total))
+    }
+
+    /// Delete entries recorded before `before`. The table's trigger rejects
+    /// any other delete, so this marks its transaction as the retention job.
+    pub async fn delete_older_than(&self, before: OffsetDateTime) -> Result<u64, DatabaseError> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("SELECT set_config('omni.audit_log_retention', 'on', true)")
+            .execute(&mut *tx)
+            .await?;
+        let result = sqlx::query("DELETE FROM audit_log WHERE created_at < $1")
+            .bind(before)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        Ok(result.rows_affected())
+    }
+}
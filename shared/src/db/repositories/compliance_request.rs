@@ -0,0 +1,628 @@
+use crate::db::error::DatabaseError;
+use crate::models::Document;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use sqlx::{FromRow, PgPool, types::time::OffsetDateTime};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+pub enum ComplianceRequestKind {
+    /// Export everything held about the subject.
+    Access,
+    /// Delete or redact everything held about the subject.
+    Erasure,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+pub enum ErasureAction {
+    /// Delete matching documents from the index.
+    Delete,
+    /// Replace the subject's identifiers in matching documents.
+    Redact,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+pub enum ComplianceRequestStatus {
+    Pending,
+    Running,
+    Completed,
+    Failed,
+    /// A completed access request whose download link lapsed.
+    Expired,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+pub enum ComplianceDocumentAction {
+    Exported,
+    Deleted,
+    Redacted,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ComplianceRequest {
+    pub id: String,
+    pub kind: ComplianceRequestKind,
+    pub erasure_action: Option<ErasureAction>,
+    pub subject_email: String,
+    pub identifiers: Vec<String>,
+    pub requested_by: Option<String>,
+    pub status: ComplianceRequestStatus,
+    pub document_count: i32,
+    pub size_bytes: i64,
+    #[serde(skip)]
+    pub part_content_ids: Vec<String>,
+    #[serde(skip)]
+    pub download_token: Option<String>,
+    #[serde(with = "time::serde::iso8601::option")]
+    pub expires_at: Option<OffsetDateTime>,
+    pub error_message: Option<String>,
+    #[serde(with = "time::serde::iso8601")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::iso8601::option")]
+    pub started_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::iso8601::option")]
+    pub completed_at: Option<OffsetDateTime>,
+}
+
+impl ComplianceRequest {
+    /// Whether the access export can currently be downloaded.
+    pub fn is_downloadable(&self, now: OffsetDateTime) -> bool {
+        self.kind == ComplianceRequestKind::Access
+            && self.status == ComplianceRequestStatus::Completed
+            && self.expires_at.is_some_and(|e| e > now)
+    }
+}
+
+/// A document that belongs to a request's audit record.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ComplianceRequestDocument {
+    pub document_id: String,
+    pub source_id: String,
+    pub external_id: String,
+    pub match_reasons: Vec<String>,
+    pub action: ComplianceDocumentAction,
+    #[serde(with = "time::serde::iso8601")]
+    pub processed_at: OffsetDateTime,
+}
+
+/// A document matching a subject, with where the match was found: `author`,
+/// `title`, `metadata`, `attributes` or `content`.
+#[derive(Debug, Clone, FromRow)]
+pub struct SubjectDocument {
+    #[sqlx(flatten)]
+    pub document: Document,
+    pub match_reasons: Vec<String>,
+}
+
+/// The redacted fields of a document. The caller stores the redacted content
+/// under `content_id`; `content` is the same text for the search index.
+pub struct RedactedDocument<'a> {
+    pub title: &'a str,
+    pub content_id: Option<&'a str>,
+    pub content: Option<&'a str>,
+    pub metadata: &'a JsonValue,
+    pub attributes: &'a JsonValue,
+}
+
+const REQUEST_COLUMNS: &str = r#"
+    id, kind, erasure_action, subject_email, identifiers, requested_by, status,
+    document_count, size_bytes, part_content_ids, download_token, expires_at,
+    error_message, created_at, started_at, completed_at
+"#;
+
+/// Patterns matching any of `identifiers` as a case-insensitive substring.
+pub fn identifier_patterns(identifiers: &[String]) -> Vec<String> {
+    identifiers
+        .iter()
+        .map(|identifier| {
+            let escaped = identifier
+                .replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_");
+            format!("%{}%", escaped)
+        })
+        .collect()
+}
+
+pub struct ComplianceRequestRepository {
+    pool: PgPool,
+}
+
+impl ComplianceRequestRepository {
+    pub fn new(pool: &PgPool) -> Self {
+        Self { pool: pool.clone() }
+    }
+
+    pub async fn create(
+        &self,
+        kind: ComplianceRequestKind,
+        erasure_action: Option<ErasureAction>,
+        subject_email: &str,
+        identifiers: &[String],
+        requested_by: Option<&str>,
+    ) -> Result<ComplianceRequest, DatabaseError> {
+        let query = format!(
+            "INSERT INTO compliance_requests \
+             (id, kind, erasure_action, subject_email, identifiers, requested_by) \
+             VALUES ($1, $2, $3, $4, $5, $6) RETURNING {REQUEST_COLUMNS}"
+        );
+        let request = sqlx::query_as::<_, ComplianceRequest>(&query)
+            .bind(crate::utils::generate_ulid())
+            .bind(kind)
+            .bind(erasure_action)
+            .bind(subject_email)
+            .bind(identifiers)
+            .bind(requested_by)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(request)
+    }
+
+    pub async fn get(&self, id: &str) -> Result<Option<ComplianceRequest>, DatabaseError> {
+        let query = format!("SELECT {REQUEST_COLUMNS} FROM compliance_requests WHERE id = $1");
+        let request = sqlx::query_as::<_, ComplianceRequest>(&query)
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(request)
+    }
+
+    /// Most recent requests first, optionally only those for one subject.
+    pub async fn list_recent(
+        &self,
+        subject_email: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<ComplianceRequest>, DatabaseError> {
+        let query = format!(
+            "SELECT {REQUEST_COLUMNS} FROM compliance_requests \
+             WHERE $1::text IS NULL OR lower(subject_email) = lower($1) \
+             ORDER BY created_at DESC LIMIT $2"
+        );
+        let requests = sqlx::query_as::<_, ComplianceRequest>(&query)
+            .bind(subject_email)
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(requests)
+    }
+
+    /// The documents a request exported, deleted or redacted.
+    pub async fn list_documents(
+        &self,
+        request_id: &str,
+    ) -> Result<Vec<ComplianceRequestDocument>, DatabaseError> {
+        let documents = sqlx::query_as::<_, ComplianceRequestDocument>(
+            r#"
+            SELECT document_id, source_id, external_id, match_reasons, action, processed_at
+            FROM compliance_request_documents
+            WHERE request_id = $1
+            ORDER BY processed_at, document_id
+            "#,
+        )
+        .bind(request_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(documents)
+    }
+
+    /// Whether the subject already has a request queued or in progress.
+    pub async fn has_active_request(&self, subject_email: &str) -> Result<bool, DatabaseError> {
+        let active: bool = sqlx::query_scalar(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM compliance_requests
+                WHERE lower(subject_email) = lower($1) AND status IN ('pending', 'running')
+            )
+            "#,
+        )
+        .bind(subject_email)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(active)
+    }
+
+    pub async fn count_running(&self) -> Result<i64, DatabaseError> {
+        let count: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM compliance_requests WHERE status = 'running'")
+                .fetch_one(&self.pool)
+                .await?;
+
+        Ok(count)
+    }
+
+    /// Move up to `limit` pending requests, oldest first, to `running`.
+    pub async fn claim_pending(&self, limit: i64) -> Result<Vec<ComplianceRequest>, DatabaseError> {
+        let query = format!(
+            r#"
+            UPDATE compliance_requests
+            SET status = 'running', started_at = NOW(), heartbeat_at = NOW()
+            WHERE id IN (
+                SELECT id FROM compliance_requests
+                WHERE status = 'pending'
+                ORDER BY created_at
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING {REQUEST_COLUMNS}
+            "#
+        );
+        let requests = sqlx::query_as::<_, ComplianceRequest>(&query)
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(requests)
+    }
+
+    /// Documents after `after_id` that the subject authored or that mention
+    /// any of the `patterns` from [`identifier_patterns`], in id order.
+    /// Permissions are not matched: being able to read a document does not
+    /// make it about the subject.
+    pub async fn find_subject_documents(
+        &self,
+        patterns: &[String],
+        after_id: &str,
+        limit: i64,
+    ) -> Result<Vec<SubjectDocument>, DatabaseError> {
+        let documents = sqlx::query_as::<_, SubjectDocument>(
+            r#"
+            SELECT id, source_id, external_id, title, content_id, content_type,
+                   file_size, file_extension, url,
+                   metadata, permissions, attributes, created_at, updated_at, last_indexed_at,
+                   ARRAY_REMOVE(ARRAY[
+                       CASE WHEN metadata->>'author' ILIKE ANY($1) THEN 'author' END,
+                       CASE WHEN title ILIKE ANY($1) THEN 'title' END,
+                       CASE WHEN metadata::text ILIKE ANY($1) THEN 'metadata' END,
+                       CASE WHEN attributes::text ILIKE ANY($1) THEN 'attributes' END,
+                       CASE WHEN content ILIKE ANY($1) THEN 'content' END
+                   ], NULL) AS match_reasons
+            FROM documents
+            WHERE id > $2
+              AND (
+                  title ILIKE ANY($1)
+                  OR metadata::text ILIKE ANY($1)
+                  OR attributes::text ILIKE ANY($1)
+                  OR content ILIKE ANY($1)
+              )
+            ORDER BY id
+            LIMIT $3
+            "#,
+        )
+        .bind(patterns)
+        .bind(after_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(documents)
+    }
+
+    /// Add documents to a request's audit record.
+    pub async fn record_documents(
+        &self,
+        request_id: &str,
+        documents: &[SubjectDocument],
+        action: ComplianceDocumentAction,
+    ) -> Result<(), DatabaseError> {
+        let mut tx = self.pool.begin().await?;
+        for document in documents {
+            insert_audit_row(&mut tx, request_id, document, action).await?;
+        }
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Delete documents and record them in the request's audit record in one
+    /// transaction. Returns the content ids the documents and their versions
+    /// referenced, for deleting blobs that are no longer referenced.
+    pub async fn delete_documents(
+        &self,
+        request_id: &str,
+        documents: &[SubjectDocument],
+    ) -> Result<Vec<String>, DatabaseError> {
+        let ids: Vec<String> = documents.iter().map(|d| d.document.id.clone()).collect();
+
+        let mut tx = self.pool.begin().await?;
+        for document in documents {
+            insert_audit_row(
+                &mut tx,
+                request_id,
+                document,
+                ComplianceDocumentAction::Deleted,
+            )
+            .await?;
+        }
+        let content_ids: Vec<String> = sqlx::query_scalar(
+            r#"
+            SELECT DISTINCT content_id::text FROM (
+                SELECT content_id FROM documents WHERE id = ANY($1)
+                UNION ALL
+                SELECT content_id FROM document_versions WHERE document_id = ANY($1)
+            ) refs
+            WHERE content_id IS NOT NULL
+            "#,
+        )
+        .bind(&ids)
+        .fetch_all(&mut *tx)
+        .await?;
+        // Embeddings, versions and other per-document rows cascade
+        sqlx::query("DELETE FROM documents WHERE id = ANY($1)")
+            .bind(&ids)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        Ok(content_ids)
+    }
+
+    /// Replace a document's fields with their redacted versions, drop the
+    /// versions holding its previous content and record it in the request's
+    /// audit record, in one transaction. Returns the content ids the document
+    /// no longer references.
+    pub async fn redact_document(
+        &self,
+        request_id: &str,
+        document: &SubjectDocument,
+        redacted: &RedactedDocument<'_>,
+    ) -> Result<Vec<String>, DatabaseError> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            r#"
+            UPDATE documents
+            SET title = $2,
+                content_id = COALESCE($3, content_id),
+                content = COALESCE($4, content),
+                metadata = $5,
+                attributes = $6,
+                last_indexed_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(&document.document.id)
+        .bind(redacted.title)
+        .bind(redacted.content_id)
+        .bind(redacted.content)
+        .bind(redacted.metadata)
+        .bind(redacted.attributes)
+        .execute(&mut *tx)
+        .await?;
+        let mut released: Vec<String> = sqlx::query_scalar(
+            r#"
+            DELETE FROM document_versions v
+            USING documents d
+            WHERE v.document_id = $1 AND d.id = v.document_id
+              AND v.content_id IS DISTINCT FROM d.content_id
+              AND v.content_id IS NOT NULL
+            RETURNING v.content_id::text
+            "#,
+        )
+        .bind(&document.document.id)
+        .fetch_all(&mut *tx)
+        .await?;
+        sqlx::query("UPDATE document_versions SET title = $2 WHERE document_id = $1")
+            .bind(&document.document.id)
+            .bind(redacted.title)
+            .execute(&mut *tx)
+            .await?;
+        insert_audit_row(
+            &mut tx,
+            request_id,
+            document,
+            ComplianceDocumentAction::Redacted,
+        )
+        .await?;
+        tx.commit().await?;
+
+        if let Some(previous) = &document.document.content_id
+            && redacted.content_id.is_some_and(|id| id != previous)
+            && !released.contains(previous)
+        {
+            released.push(previous.clone());
+        }
+        Ok(released)
+    }
+
+    /// The subset of `content_ids` nothing references any more.
+    pub async fn unreferenced_content_ids(
+        &self,
+        content_ids: &[String],
+    ) -> Result<Vec<String>, DatabaseError> {
+        let ids: Vec<String> = sqlx::query_scalar(
+            "SELECT id::text FROM content_blobs WHERE id = ANY($1) AND ref_count = 0",
+        )
+        .bind(content_ids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(ids)
+    }
+
+    /// Remove the subject from the people directory.
+    pub async fn delete_person(&self, email: &str) -> Result<bool, DatabaseError> {
+        let result = sqlx::query("DELETE FROM people WHERE lower(email) = lower($1)")
+            .bind(email)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Record progress of a running request and refresh its heartbeat.
+    pub async fn record_progress(
+        &self,
+        id: &str,
+        document_count: i32,
+        size_bytes: i64,
+        part_content_ids: &[String],
+    ) -> Result<(), DatabaseError> {
+        sqlx::query(
+            r#"
+            UPDATE compliance_requests
+            SET document_count = $2, size_bytes = $3, part_content_ids = $4, heartbeat_at = NOW()
+            WHERE id = $1 AND status = 'running'
+            "#,
+        )
+        .bind(id)
+        .bind(document_count)
+        .bind(size_bytes)
+        .bind(part_content_ids)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Complete a request. Access requests pass the export's download token
+    /// and link expiry.
+    pub async fn complete(
+        &self,
+        id: &str,
+        document_count: i32,
+        size_bytes: i64,
+        part_content_ids: &[String],
+        download: Option<(&str, OffsetDateTime)>,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query(
+            r#"
+            UPDATE compliance_requests
+            SET status = 'completed', document_count = $2, size_bytes = $3,
+                part_content_ids = $4, download_token = $5, expires_at = $6,
+                heartbeat_at = NOW(), completed_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(document_count)
+        .bind(size_bytes)
+        .bind(part_content_ids)
+        .bind(download.map(|(token, _)| token))
+        .bind(download.map(|(_, expires_at)| expires_at))
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Mark a request failed. The caller deletes any parts it had written;
+    /// documents already erased stay in the audit record.
+    pub async fn fail(&self, id: &str, error_message: &str) -> Result<(), DatabaseError> {
+        sqlx::query(
+            r#"
+            UPDATE compliance_requests
+            SET status = 'failed', error_message = $2, part_content_ids = '{}',
+                completed_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(error_message)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Fail running requests whose heartbeat is older than
+    /// `stale_after_seconds`. Returns the parts they had written.
+    pub async fn fail_orphaned(
+        &self,
+        stale_after_seconds: i64,
+    ) -> Result<Vec<String>, DatabaseError> {
+        let parts: Vec<Vec<String>> = sqlx::query_scalar(
+            r#"
+            WITH orphaned AS (
+                SELECT id, part_content_ids FROM compliance_requests
+                WHERE status = 'running'
+                  AND heartbeat_at < NOW() - make_interval(secs => $1)
+                FOR UPDATE
+            )
+            UPDATE compliance_requests r
+            SET status = 'failed', error_message = 'Request stopped unexpectedly',
+                part_content_ids = '{}', completed_at = NOW()
+            FROM orphaned o
+            WHERE r.id = o.id
+            RETURNING o.part_content_ids
+            "#,
+        )
+        .bind(stale_after_seconds as f64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(parts.into_iter().flatten().collect())
+    }
+
+    /// Expire access exports whose download link has lapsed. Returns the
+    /// parts to delete from storage.
+    pub async fn expire_completed(&self) -> Result<Vec<String>, DatabaseError> {
+        let parts: Vec<Vec<String>> = sqlx::query_scalar(
+            r#"
+            WITH lapsed AS (
+                SELECT id, part_content_ids FROM compliance_requests
+                WHERE status = 'completed' AND expires_at <= NOW()
+                FOR UPDATE
+            )
+            UPDATE compliance_requests r
+            SET status = 'expired', part_content_ids = '{}', download_token = NULL
+            FROM lapsed l
+            WHERE r.id = l.id
+            RETURNING l.part_content_ids
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(parts.into_iter().flatten().collect())
+    }
+}
+
+async fn insert_audit_row(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    request_id: &str,
+    document: &SubjectDocument,
+    action: ComplianceDocumentAction,
+) -> Result<(), DatabaseError> {
+    sqlx::query(
+        r#"
+        INSERT INTO compliance_request_documents
+            (request_id, document_id, source_id, external_id, match_reasons, action)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        ON CONFLICT (request_id, document_id) DO NOTHING
+        "#,
+    )
+    .bind(request_id)
+    .bind(&document.document.id)
+    .bind(&document.document.source_id)
+    .bind(&document.document.external_id)
+    .bind(&document.match_reasons)
+    .bind(action)
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identifier_patterns_escape_like_wildcards() {
+        let patterns =
+            identifier_patterns(&["jane_doe@example.com".to_string(), "100% Jane".to_string()]);
+        assert_eq!(patterns[0], "%jane\\_doe@example.com%");
+        assert_eq!(patterns[1], "%100\\% Jane%");
+    }
+}
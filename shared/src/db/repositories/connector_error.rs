@@ -0,0 +1,102 @@
+use crate::db::error::DatabaseError;
+use crate::models::ConnectorErrorCategory;
+use crate::utils::generate_ulid;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use sqlx::{FromRow, PgPool, types::time::OffsetDateTime};
+
+/// An error a connector reported for a source, optionally during a sync run.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ConnectorErrorRecord {
+    pub id: String,
+    pub source_id: String,
+    pub sync_run_id: Option<String>,
+    pub category: ConnectorErrorCategory,
+    pub message: String,
+    pub details: Option<JsonValue>,
+    #[serde(with = "time::serde::iso8601")]
+    pub created_at: OffsetDateTime,
+}
+
+pub struct ConnectorErrorRepository {
+    pool: PgPool,
+}
+
+impl ConnectorErrorRepository {
+    pub fn new(pool: &PgPool) -> Self {
+        Self { pool: pool.clone() }
+    }
+
+    pub async fn record(
+        &self,
+        source_id: &str,
+        sync_run_id: Option<&str>,
+        category: ConnectorErrorCategory,
+        message: &str,
+        details: Option<&JsonValue>,
+    ) -> Result<ConnectorErrorRecord, DatabaseError> {
+        let record = sqlx::query_as::<_, ConnectorErrorRecord>(
+            r#"
+            INSERT INTO connector_errors (id, source_id, sync_run_id, category, message, details)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING id, source_id, sync_run_id, category, message, details, created_at
+            "#,
+        )
+        .bind(generate_ulid())
+        .bind(source_id)
+        .bind(sync_run_id)
+        .bind(category)
+        .bind(message)
+        .bind(details)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(record)
+    }
+
+    /// The most recent error of each of `source_ids` that has reported one.
+    pub async fn latest_for_sources(
+        &self,
+        source_ids: &[String],
+    ) -> Result<Vec<ConnectorErrorRecord>, DatabaseError> {
+        if source_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let records = sqlx::query_as::<_, ConnectorErrorRecord>(
+            r#"
+            SELECT DISTINCT ON (source_id)
+                   id, source_id, sync_run_id, category, message, details, created_at
+            FROM connector_errors
+            WHERE source_id = ANY($1)
+            ORDER BY source_id, created_at DESC, id DESC
+            "#,
+        )
+        .bind(source_ids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(records)
+    }
+
+    /// Delete errors reported before `before`, keeping each source's most
+    /// recent one so its health still shows why it last failed.
+    pub async fn delete_older_than(&self, before: OffsetDateTime) -> Result<u64, DatabaseError> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM connector_errors ce
+            WHERE ce.created_at < $1
+              AND EXISTS (
+                  SELECT 1 FROM connector_errors newer
+                  WHERE newer.source_id = ce.source_id
+                    AND newer.created_at > ce.created_at
+              )
+            "#,
+        )
+        .bind(before)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
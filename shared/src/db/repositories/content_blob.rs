@@ -1,6 +1,6 @@
 use crate::db::error::DatabaseError;
 use serde::Serialize;
-use sqlx::{FromRow, PgPool, Row};
+use sqlx::{FromRow, PgPool, Row, types::time::OffsetDateTime};
 
 /// Represents an orphan blob ready for deletion
 #[derive(Debug, FromRow)]
@@ -22,6 +22,41 @@ pub struct OrphanStats {
     pub orphan_size_bytes: i64,
 }
 
+/// A completed GC pass, recorded so reclaimed storage can be tracked over time.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct GCRun {
+    pub id: String,
+    #[serde(with = "time::serde::iso8601")]
+    pub started_at: OffsetDateTime,
+    #[serde(with = "time::serde::iso8601")]
+    pub completed_at: OffsetDateTime,
+    pub dry_run: bool,
+    pub orphans_marked: i64,
+    pub orphans_unmarked: i64,
+    pub blobs_deleted: i64,
+    pub bytes_reclaimed: i64,
+    pub error_count: i32,
+}
+
+/// Storage reclaimed by GC across all recorded runs.
+#[derive(Debug, Serialize)]
+pub struct ReclaimedStorageStats {
+    pub total_runs: i64,
+    pub blobs_deleted: i64,
+    pub bytes_reclaimed: i64,
+    pub bytes_reclaimed_last_30_days: i64,
+    /// Most recent runs first.
+    pub recent_runs: Vec<GCRun>,
+}
+
+/// The stored type and size of a blob.
+#[derive(Debug, Clone, FromRow)]
+pub struct BlobInfo {
+    pub id: String,
+    pub content_type: Option<String>,
+    pub size_bytes: i64,
+}
+
 pub struct ContentBlobRepository {
     pool: PgPool,
 }
@@ -31,16 +66,25 @@ impl ContentBlobRepository {
         Self { pool: pool.clone() }
     }
 
-    /// Mark blobs as orphaned if they are not referenced by any document,
-    /// upload, or any pending/processing queue event.
+    /// Type and size of the given blobs, without loading their content.
+    pub async fn find_info(&self, ids: &[String]) -> Result<Vec<BlobInfo>, DatabaseError> {
+        let blobs = sqlx::query_as::<_, BlobInfo>(
+            "SELECT id, content_type, size_bytes FROM content_blobs WHERE id = ANY($1)",
+        )
+        .bind(ids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(blobs)
+    }
+
+    /// Mark blobs as orphaned if nothing references them (`ref_count`, kept
+    /// up to date by triggers on documents, uploads and export archives) and
+    /// no pending/processing queue event points at them.
     /// Returns the number of blobs marked.
     ///
-    /// Bounded to MARK_ORPHANS_BATCH rows per call: the previous unbounded
-    /// `NOT IN` anti-joins against the full `content_blobs` table materialized
-    /// hash tables over every row and took 30+ hours to complete on production
-    /// data (5M+ blobs). NOT EXISTS plus an explicit row cap lets the planner
-    /// use an indexed anti-join and keeps each GC pass predictable — the
-    /// remainder gets picked up on the next scheduled tick.
+    /// Bounded to MARK_ORPHANS_BATCH rows per call so each GC pass stays
+    /// predictable — the remainder gets picked up on the next scheduled tick.
     pub async fn mark_orphans(&self) -> Result<i64, DatabaseError> {
         const MARK_ORPHANS_BATCH: i64 = 100_000;
 
@@ -49,18 +93,13 @@ impl ContentBlobRepository {
             WITH candidates AS (
                 SELECT cb.id
                 FROM content_blobs cb
-                WHERE cb.orphaned_at IS NULL
-                  AND NOT EXISTS (
-                      SELECT 1 FROM documents d WHERE d.content_id = cb.id
-                  )
+                WHERE cb.ref_count = 0
+                  AND cb.orphaned_at IS NULL
                   AND NOT EXISTS (
                       SELECT 1 FROM connector_events_queue q
                       WHERE q.status IN ('pending', 'processing')
                         AND q.payload->>'content_id' = cb.id::text
                   )
-                  AND NOT EXISTS (
-                      SELECT 1 FROM uploads u WHERE u.content_id = cb.id
-                  )
                 LIMIT $1
             )
             UPDATE content_blobs cb
@@ -76,8 +115,9 @@ impl ContentBlobRepository {
         Ok(result.rows_affected() as i64)
     }
 
-    /// Unmark blobs that are no longer orphaned (got re-referenced).
-    /// Returns the number of blobs unmarked.
+    /// Unmark blobs that are no longer orphaned. Gaining a reference clears
+    /// `orphaned_at` in the ref-count trigger; this catches blobs that only a
+    /// queue event refers to. Returns the number of blobs unmarked.
     pub async fn unmark_non_orphans(&self) -> Result<i64, DatabaseError> {
         let result = sqlx::query(
             r#"
@@ -85,17 +125,12 @@ impl ContentBlobRepository {
             SET orphaned_at = NULL
             WHERE cb.orphaned_at IS NOT NULL
               AND (
-                  EXISTS (
-                      SELECT 1 FROM documents d WHERE d.content_id = cb.id
-                  )
+                  cb.ref_count > 0
                   OR EXISTS (
                       SELECT 1 FROM connector_events_queue q
                       WHERE q.status IN ('pending', 'processing')
                         AND q.payload->>'content_id' = cb.id::text
                   )
-                  OR EXISTS (
-                      SELECT 1 FROM uploads u WHERE u.content_id = cb.id
-                  )
               )
             "#,
         )
@@ -117,6 +152,7 @@ impl ContentBlobRepository {
             SELECT id, size_bytes
             FROM content_blobs
             WHERE orphaned_at IS NOT NULL
+            AND ref_count = 0
             AND orphaned_at < CURRENT_TIMESTAMP - INTERVAL '1 day' * $1
             ORDER BY orphaned_at
             LIMIT $2
@@ -141,18 +177,13 @@ impl ContentBlobRepository {
             SELECT
                 COUNT(*) FILTER (
                     WHERE orphaned_at IS NULL
-                    AND id NOT IN (
-                        SELECT DISTINCT content_id FROM documents WHERE content_id IS NOT NULL
-                    )
+                    AND ref_count = 0
                     AND id NOT IN (
                         SELECT DISTINCT payload->>'content_id'
                         FROM connector_events_queue
                         WHERE status IN ('pending', 'processing')
                         AND payload->>'content_id' IS NOT NULL
                     )
-                    AND id NOT IN (
-                        SELECT DISTINCT content_id FROM uploads
-                    )
                 ) as unmarked_orphans,
                 COUNT(*) FILTER (
                     WHERE orphaned_at IS NOT NULL
@@ -177,4 +208,72 @@ impl ContentBlobRepository {
             orphan_size_bytes: row.get("orphan_size_bytes"),
         })
     }
+
+    pub async fn record_gc_run(&self, run: &GCRun) -> Result<(), DatabaseError> {
+        sqlx::query(
+            r#"
+            INSERT INTO content_gc_runs (
+                id, started_at, completed_at, dry_run, orphans_marked, orphans_unmarked,
+                blobs_deleted, bytes_reclaimed, error_count
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            "#,
+        )
+        .bind(&run.id)
+        .bind(run.started_at)
+        .bind(run.completed_at)
+        .bind(run.dry_run)
+        .bind(run.orphans_marked)
+        .bind(run.orphans_unmarked)
+        .bind(run.blobs_deleted)
+        .bind(run.bytes_reclaimed)
+        .bind(run.error_count)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Totals over all recorded GC runs, with the `recent_limit` latest runs.
+    pub async fn get_reclaimed_storage_stats(
+        &self,
+        recent_limit: i64,
+    ) -> Result<ReclaimedStorageStats, DatabaseError> {
+        let row = sqlx::query(
+            r#"
+            SELECT
+                COUNT(*) as total_runs,
+                COALESCE(SUM(blobs_deleted), 0)::BIGINT as blobs_deleted,
+                COALESCE(SUM(bytes_reclaimed), 0)::BIGINT as bytes_reclaimed,
+                COALESCE(
+                    SUM(bytes_reclaimed) FILTER (WHERE started_at >= NOW() - INTERVAL '30 days'),
+                    0
+                )::BIGINT as bytes_reclaimed_last_30_days
+            FROM content_gc_runs
+            "#,
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        let recent_runs = sqlx::query_as::<_, GCRun>(
+            r#"
+            SELECT id, started_at, completed_at, dry_run, orphans_marked, orphans_unmarked,
+                   blobs_deleted, bytes_reclaimed, error_count
+            FROM content_gc_runs
+            ORDER BY started_at DESC
+            LIMIT $1
+            "#,
+        )
+        .bind(recent_limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(ReclaimedStorageStats {
+            total_runs: row.get("total_runs"),
+            blobs_deleted: row.get("blobs_deleted"),
+            bytes_reclaimed: row.get("bytes_reclaimed"),
+            bytes_reclaimed_last_30_days: row.get("bytes_reclaimed_last_30_days"),
+            recent_runs,
+        })
+    }
 }
@@ -0,0 +1,430 @@
+use crate::db::error::DatabaseError;
+use serde::Serialize;
+use sqlx::{FromRow, PgPool, Postgres, Transaction, types::time::OffsetDateTime};
+
+/// Document and chunk counts for one language within one source.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct SourceLanguageStats {
+    pub source_id: String,
+    pub source_name: String,
+    pub language: String,
+    pub document_count: i64,
+    pub chunk_count: i64,
+    #[serde(with = "time::serde::iso8601")]
+    pub refreshed_at: OffsetDateTime,
+}
+
+/// A term dictionary refresh.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct TermDictionaryRun {
+    pub id: i64,
+    pub mode: String,
+    pub status: String,
+    /// Documents indexed since this time were refreshed; unset for all
+    /// documents.
+    #[serde(with = "time::serde::iso8601::option")]
+    pub changed_since: Option<OffsetDateTime>,
+    pub documents_refreshed: i64,
+    pub documents_removed: i64,
+    /// Terms in the dictionary afterwards.
+    pub terms: i64,
+    pub error: Option<String>,
+    #[serde(with = "time::serde::iso8601")]
+    pub started_at: OffsetDateTime,
+    #[serde(with = "time::serde::iso8601::option")]
+    pub finished_at: Option<OffsetDateTime>,
+}
+
+/// What applying staged terms changed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TermDictionaryUpdate {
+    pub documents_refreshed: u64,
+    pub documents_removed: u64,
+    pub terms: u64,
+}
+
+/// Serializes changes to the term counts, so concurrent refreshes cannot
+/// apply the same staged terms twice.
+const TERM_COUNTS_LOCK_KEY: i64 = 0x636f_7270_7573_7465;
+
+async fn lock_term_counts(tx: &mut Transaction<'_, Postgres>) -> Result<(), DatabaseError> {
+    sqlx::query("SELECT pg_advisory_xact_lock($1)")
+        .bind(TERM_COUNTS_LOCK_KEY)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+pub struct CorpusStatsRepository {
+    pool: PgPool,
+}
+
+impl CorpusStatsRepository {
+    pub fn new(pool: &PgPool) -> Self {
+        Self { pool: pool.clone() }
+    }
+
+    /// Record the detected primary language for each `(document_id, language)`
+    /// pair, replacing any earlier detection.
+    pub async fn upsert_document_languages(
+        &self,
+        languages: &[(String, String)],
+    ) -> Result<u64, DatabaseError> {
+        if languages.is_empty() {
+            return Ok(0);
+        }
+
+        let document_ids: Vec<&str> = languages.iter().map(|(id, _)| id.as_str()).collect();
+        let codes: Vec<&str> = languages.iter().map(|(_, lang)| lang.as_str()).collect();
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO document_languages (document_id, language, detected_at)
+            SELECT t.document_id, t.language, NOW()
+            FROM UNNEST($1::text[], $2::text[]) AS t(document_id, language)
+            JOIN documents d ON d.id = t.document_id
+            ON CONFLICT (document_id) DO UPDATE
+            SET language = EXCLUDED.language, detected_at = EXCLUDED.detected_at
+            "#,
+        )
+        .bind(&document_ids)
+        .bind(&codes)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Rebuild the per-source language rollup from `document_languages` and
+    /// `embeddings`. Chunks are counted once per chunk index, so documents
+    /// embedded by several models are not double-counted. Returns the number
+    /// of rollup rows written.
+    pub async fn refresh_language_stats(&self) -> Result<u64, DatabaseError> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM corpus_language_stats")
+            .execute(&mut *tx)
+            .await?;
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO corpus_language_stats
+                (source_id, language, document_count, chunk_count, refreshed_at)
+            SELECT d.source_id,
+                   COALESCE(dl.language, 'und') AS language,
+                   COUNT(*) AS document_count,
+                   COALESCE(SUM(c.chunk_count), 0) AS chunk_count,
+                   NOW()
+            FROM documents d
+            LEFT JOIN document_languages dl ON dl.document_id = d.id
+            LEFT JOIN (
+                SELECT document_id, COUNT(DISTINCT chunk_index) AS chunk_count
+                FROM embeddings
+                WHERE namespace IS NULL
+                GROUP BY document_id
+            ) c ON c.document_id = d.id
+            GROUP BY d.source_id, COALESCE(dl.language, 'und')
+            "#,
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Ids of documents indexed at or after `since` (every document when
+    /// unset) ordered by id, the first `limit` after `after_id`.
+    pub async fn documents_indexed_since(
+        &self,
+        since: Option<OffsetDateTime>,
+        after_id: &str,
+        limit: i64,
+    ) -> Result<Vec<String>, DatabaseError> {
+        let ids = sqlx::query_scalar::<_, String>(
+            r#"
+            SELECT id FROM documents
+            WHERE ($1::timestamptz IS NULL OR last_indexed_at >= $1)
+              AND id > $2
+            ORDER BY id
+            LIMIT $3
+            "#,
+        )
+        .bind(since)
+        .bind(after_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(ids)
+    }
+
+    /// Tokenize the documents' titles and the first `content_chars`
+    /// characters of their content into unstemmed lexemes, keeping
+    /// alphabetic terms of 3 to 32 characters. The terms are staged as
+    /// pending until `apply_pending_terms`. Returns the number of documents
+    /// tokenized.
+    pub async fn stage_document_terms(
+        &self,
+        document_ids: &[String],
+        content_chars: i32,
+    ) -> Result<u64, DatabaseError> {
+        if document_ids.is_empty() {
+            return Ok(0);
+        }
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO document_lexemes (document_id, terms, pending_terms, refreshed_at)
+            SELECT d.id, '{}',
+                   ARRAY(
+                       SELECT word
+                       FROM unnest(tsvector_to_array(to_tsvector(
+                           'simple', d.title || ' ' || LEFT(COALESCE(d.content, ''), $2)
+                       ))) AS word
+                       WHERE length(word) BETWEEN 3 AND 32
+                         AND word ~ '^[[:alpha:]]+$'
+                   ),
+                   NOW()
+            FROM documents d
+            WHERE d.id = ANY($1)
+            ON CONFLICT (document_id) DO UPDATE
+            SET pending_terms = EXCLUDED.pending_terms,
+                refreshed_at = EXCLUDED.refreshed_at
+            "#,
+        )
+        .bind(document_ids)
+        .bind(content_chars.max(0))
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Fold staged terms into the term counts, subtract the terms of
+    /// documents deleted since they were counted, and rebuild the
+    /// spell-correction term dictionary from the counts: terms in at least
+    /// `min_document_count` documents, at most `max_terms` of the most
+    /// common.
+    pub async fn apply_pending_terms(
+        &self,
+        min_document_count: i64,
+        max_terms: i64,
+    ) -> Result<TermDictionaryUpdate, DatabaseError> {
+        let mut tx = self.pool.begin().await?;
+        lock_term_counts(&mut tx).await?;
+
+        let removed: Vec<String> = sqlx::query_scalar(
+            r#"
+            UPDATE document_lexemes dl
+            SET pending_terms = '{}'
+            WHERE NOT EXISTS (SELECT 1 FROM documents d WHERE d.id = dl.document_id)
+            RETURNING dl.document_id
+            "#,
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO corpus_term_counts (term, document_count)
+            SELECT term, SUM(delta)
+            FROM (
+                SELECT unnest(pending_terms) AS term, 1 AS delta
+                FROM document_lexemes WHERE pending_terms IS NOT NULL
+                UNION ALL
+                SELECT unnest(terms), -1
+                FROM document_lexemes WHERE pending_terms IS NOT NULL
+            ) deltas
+            GROUP BY term
+            HAVING SUM(delta) <> 0
+            ON CONFLICT (term) DO UPDATE
+            SET document_count = corpus_term_counts.document_count + EXCLUDED.document_count
+            "#,
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query("DELETE FROM corpus_term_counts WHERE document_count <= 0")
+            .execute(&mut *tx)
+            .await?;
+
+        let refreshed = sqlx::query(
+            r#"
+            UPDATE document_lexemes
+            SET terms = pending_terms, pending_terms = NULL
+            WHERE pending_terms IS NOT NULL
+            "#,
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query("DELETE FROM document_lexemes WHERE document_id = ANY($1)")
+            .bind(&removed)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query("DELETE FROM corpus_terms")
+            .execute(&mut *tx)
+            .await?;
+        let terms = sqlx::query(
+            r#"
+            INSERT INTO corpus_terms (term, document_count, refreshed_at)
+            SELECT term, document_count, NOW()
+            FROM corpus_term_counts
+            WHERE document_count >= $1
+            ORDER BY document_count DESC, term
+            LIMIT $2
+            "#,
+        )
+        .bind(min_document_count)
+        .bind(max_terms)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        let removed = removed.len() as u64;
+        Ok(TermDictionaryUpdate {
+            documents_refreshed: refreshed.rows_affected().saturating_sub(removed),
+            documents_removed: removed,
+            terms: terms.rows_affected(),
+        })
+    }
+
+    /// Record the start of a term dictionary refresh. Returns its id and
+    /// the time the last completed refresh started, if any.
+    pub async fn start_term_dictionary_run(
+        &self,
+        mode: &str,
+        incremental: bool,
+    ) -> Result<(i64, Option<OffsetDateTime>), DatabaseError> {
+        let changed_since: Option<OffsetDateTime> = if incremental {
+            sqlx::query_scalar(
+                "SELECT MAX(started_at) FROM term_dictionary_runs WHERE status = 'completed'",
+            )
+            .fetch_one(&self.pool)
+            .await?
+        } else {
+            None
+        };
+
+        let id = sqlx::query_scalar(
+            r#"
+            INSERT INTO term_dictionary_runs (mode, changed_since)
+            VALUES ($1, $2)
+            RETURNING id
+            "#,
+        )
+        .bind(mode)
+        .bind(changed_since)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok((id, changed_since))
+    }
+
+    pub async fn complete_term_dictionary_run(
+        &self,
+        id: i64,
+        update: &TermDictionaryUpdate,
+    ) -> Result<TermDictionaryRun, DatabaseError> {
+        let run = sqlx::query_as::<_, TermDictionaryRun>(
+            r#"
+            UPDATE term_dictionary_runs
+            SET status = 'completed', documents_refreshed = $2, documents_removed = $3,
+                terms = $4, finished_at = NOW()
+            WHERE id = $1
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .bind(update.documents_refreshed as i64)
+        .bind(update.documents_removed as i64)
+        .bind(update.terms as i64)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(run)
+    }
+
+    pub async fn fail_term_dictionary_run(
+        &self,
+        id: i64,
+        error: &str,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query(
+            r#"
+            UPDATE term_dictionary_runs
+            SET status = 'failed', error = $2, finished_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(error)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// The most recent term dictionary refreshes, newest first.
+    pub async fn recent_term_dictionary_runs(
+        &self,
+        limit: i64,
+    ) -> Result<Vec<TermDictionaryRun>, DatabaseError> {
+        let runs = sqlx::query_as::<_, TermDictionaryRun>(
+            "SELECT * FROM term_dictionary_runs ORDER BY started_at DESC, id DESC LIMIT $1",
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(runs)
+    }
+
+    pub async fn last_completed_term_dictionary_run(
+        &self,
+    ) -> Result<Option<TermDictionaryRun>, DatabaseError> {
+        let run = sqlx::query_as::<_, TermDictionaryRun>(
+            r#"
+            SELECT * FROM term_dictionary_runs
+            WHERE status = 'completed'
+            ORDER BY started_at DESC, id DESC
+            LIMIT 1
+            "#,
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(run)
+    }
+
+    /// Every `(term, document_count)` pair in the term dictionary.
+    pub async fn get_term_dictionary(&self) -> Result<Vec<(String, i64)>, DatabaseError> {
+        let terms =
+            sqlx::query_as::<_, (String, i64)>("SELECT term, document_count FROM corpus_terms")
+                .fetch_all(&self.pool)
+                .await?;
+
+        Ok(terms)
+    }
+
+    /// Rollup rows for active sources, largest languages first within each
+    /// source.
+    pub async fn get_language_stats(&self) -> Result<Vec<SourceLanguageStats>, DatabaseError> {
+        let stats = sqlx::query_as::<_, SourceLanguageStats>(
+            r#"
+            SELECT s.id AS source_id, s.name AS source_name, cls.language,
+                   cls.document_count, cls.chunk_count, cls.refreshed_at
+            FROM corpus_language_stats cls
+            JOIN sources s ON s.id = cls.source_id
+            WHERE s.is_deleted = false
+            ORDER BY s.name, cls.document_count DESC, cls.language
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(stats)
+    }
+}
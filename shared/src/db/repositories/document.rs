@@ -1,32 +1,108 @@
 use crate::{
     SourceType,
-    db::error::DatabaseError,
-    models::{AttributeFilter, DateFilter, Document},
+    db::{error::DatabaseError, row_level_security},
+    models::{AttributeFilter, DEFAULT_WORKSPACE_ID, DateFilter, Document},
+    utils::content_fingerprint,
 };
 use serde_json::Value as JsonValue;
-use sqlx::{FromRow, PgPool};
+use sqlx::{FromRow, PgPool, Row};
 use std::collections::HashMap;
 use time::{self, OffsetDateTime};
 
+/// When a document was last updated, as reported by its source when valid,
+/// on the `d` documents alias.
+pub const DOCUMENT_UPDATED_AT: &str = "COALESCE(\
+     CASE WHEN d.metadata->>'updated_at' IS NOT NULL \
+          AND pg_input_is_valid(d.metadata->>'updated_at', 'timestamptz') \
+     THEN (d.metadata->>'updated_at')::timestamptz END, \
+     d.updated_at)";
+
 #[derive(FromRow)]
 pub struct TitleEntry {
     pub id: String,
+    pub external_id: String,
     pub title: String,
     pub url: Option<String>,
     pub source_id: String,
+    pub permissions: JsonValue,
+}
+
+/// A document with its summary embedding.
+#[derive(Debug, Clone, FromRow)]
+pub struct EmbeddedDocument {
+    pub id: String,
+    pub source_id: String,
+    pub title: String,
+    pub content_id: Option<String>,
+    pub embedding: pgvector::Vector,
+    pub model_name: String,
+}
+
+/// What a document's content was last indexed as: the SHA-256 of its content
+/// blob, computed at store time for deduplication, and when the document last
+/// changed.
+#[derive(Debug, Clone, FromRow)]
+pub struct ContentVersion {
+    pub content_hash: Option<String>,
+    pub updated_at: OffsetDateTime,
+}
+
+/// Result of [`DocumentRepository::upsert_if_unmodified`].
+#[derive(Debug)]
+pub enum DocumentUpsertOutcome {
+    Created(Document),
+    Updated(Document),
+    /// The precondition did not hold. Carries the document as currently
+    /// stored, or `None` if there is no document to update.
+    PreconditionFailed(Option<Document>),
 }
 
 pub struct DocumentRepository {
     pool: PgPool,
+    row_level_security: bool,
 }
 
 impl DocumentRepository {
     pub fn new(pool: &PgPool) -> Self {
-        Self { pool: pool.clone() }
+        Self {
+            pool: pool.clone(),
+            row_level_security: false,
+        }
+    }
+
+    /// Also have Postgres enforce the permissions of the user a read is made
+    /// for, by running it as the document reader role.
+    pub fn with_row_level_security(mut self, enabled: bool) -> Self {
+        self.row_level_security = enabled;
+        self
+    }
+
+    /// Run a query reading documents for `user_email`, inside a row-level
+    /// security transaction when enabled.
+    async fn fetch_all_for_user<'q, T>(
+        &self,
+        query: sqlx::query::QueryAs<'q, sqlx::Postgres, T, sqlx::postgres::PgArguments>,
+        user_email: Option<&str>,
+        user_groups: &[String],
+    ) -> Result<Vec<T>, DatabaseError>
+    where
+        T: Send + Unpin + for<'r> FromRow<'r, sqlx::postgres::PgRow>,
+    {
+        match user_email {
+            Some(email) if self.row_level_security => {
+                let mut tx =
+                    row_level_security::begin_as_user(&self.pool, email, user_groups).await?;
+                let rows = query.fetch_all(&mut *tx).await?;
+                tx.commit().await?;
+                Ok(rows)
+            }
+            _ => Ok(query.fetch_all(&self.pool).await?),
+        }
     }
 
     /// Generate SQL condition to check if user has permission to access document.
-    /// Checks: public access, direct user access, domain-wide access, and group membership.
+    /// Checks: public access, direct user access, domain-wide access, and group membership,
+    /// limited to documents of the user's workspace.
     fn generate_permission_filter(&self, user_email: &str, user_groups: &[String]) -> String {
         generate_permission_filter(user_email, user_groups)
     }
@@ -48,6 +124,49 @@ impl DocumentRepository {
         Ok(document)
     }
 
+    pub async fn find_content_version(
+        &self,
+        id: &str,
+    ) -> Result<Option<ContentVersion>, DatabaseError> {
+        let version = sqlx::query_as::<_, ContentVersion>(
+            r#"
+            SELECT cb.sha256_hash AS content_hash, d.updated_at
+            FROM documents d
+            LEFT JOIN content_blobs cb ON cb.id = d.content_id
+            WHERE d.id = $1
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(version)
+    }
+
+    /// Content fingerprints of the given documents, keyed by document id.
+    /// Documents without a fingerprint are omitted.
+    pub async fn find_content_fingerprints(
+        &self,
+        ids: &[String],
+    ) -> Result<HashMap<String, String>, DatabaseError> {
+        if ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let rows = sqlx::query_as::<_, (String, String)>(
+            r#"
+            SELECT id, content_fingerprint
+            FROM documents
+            WHERE id = ANY($1) AND content_fingerprint IS NOT NULL
+            "#,
+        )
+        .bind(ids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().collect())
+    }
+
     pub async fn find_by_ids(&self, ids: &[String]) -> Result<Vec<Document>, DatabaseError> {
         if ids.is_empty() {
             return Ok(vec![]);
@@ -98,7 +217,7 @@ impl DocumentRepository {
     pub async fn fetch_all_title_entries(&self) -> Result<Vec<TitleEntry>, DatabaseError> {
         let entries = sqlx::query_as::<_, TitleEntry>(
             r#"
-            SELECT d.id, d.title, d.url, d.source_id
+            SELECT d.id, d.external_id, d.title, d.url, d.source_id, d.permissions
             FROM documents d
             JOIN sources s ON d.source_id = s.id
             WHERE NOT s.is_deleted
@@ -110,6 +229,102 @@ impl DocumentRepository {
         Ok(entries)
     }
 
+    /// Title entries of a source's documents, or of just those with the
+    /// given external ids. Empty for deleted sources.
+    pub async fn fetch_source_title_entries(
+        &self,
+        source_id: &str,
+        external_ids: Option<&[String]>,
+    ) -> Result<Vec<TitleEntry>, DatabaseError> {
+        let entries = sqlx::query_as::<_, TitleEntry>(
+            r#"
+            SELECT d.id, d.external_id, d.title, d.url, d.source_id, d.permissions
+            FROM documents d
+            JOIN sources s ON d.source_id = s.id
+            WHERE d.source_id = $1
+              AND NOT s.is_deleted
+              AND ($2::text[] IS NULL OR d.external_id = ANY($2))
+            "#,
+        )
+        .bind(source_id)
+        .bind(external_ids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(entries)
+    }
+
+    /// Return the subset of `document_ids` the user may read, in no
+    /// particular order. With no user only public documents pass. Used to
+    /// permission-trim candidates ranked outside the database (e.g. the
+    /// in-memory typeahead index).
+    pub async fn filter_accessible_ids(
+        &self,
+        document_ids: &[String],
+        user_email: Option<&str>,
+        user_groups: &[String],
+    ) -> Result<Vec<String>, DatabaseError> {
+        if document_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let permission_filter = match user_email {
+            Some(email) => self.generate_permission_filter(email, user_groups),
+            None => format!(
+                "permissions @@@ 'public:true' AND source_id IN \
+                 (SELECT ws.id FROM sources ws WHERE ws.workspace_id = '{}')",
+                DEFAULT_WORKSPACE_ID
+            ),
+        };
+        let query = format!(
+            r#"
+            SELECT d.id
+            FROM documents d
+            WHERE d.id = ANY($1)
+                AND {}
+            "#,
+            permission_filter
+        );
+
+        let ids: Vec<(String,)> = self
+            .fetch_all_for_user(
+                sqlx::query_as(&query).bind(document_ids),
+                user_email,
+                user_groups,
+            )
+            .await?;
+
+        Ok(ids.into_iter().map(|(id,)| id).collect())
+    }
+
+    /// The document, if `user_email` can read it.
+    pub async fn find_by_id_for_user(
+        &self,
+        id: &str,
+        user_email: &str,
+        user_groups: &[String],
+    ) -> Result<Option<Document>, DatabaseError> {
+        let query = format!(
+            r#"
+            SELECT id, source_id, external_id, title, content_id, content_type,
+                   file_size, file_extension, url,
+                   metadata, permissions, attributes, created_at, updated_at, last_indexed_at
+            FROM documents
+            WHERE id = $1 AND {}
+            "#,
+            self.generate_permission_filter(user_email, user_groups)
+        );
+        let documents = self
+            .fetch_all_for_user(
+                sqlx::query_as::<_, Document>(&query).bind(id),
+                Some(user_email),
+                user_groups,
+            )
+            .await?;
+
+        Ok(documents.into_iter().next())
+    }
+
     pub async fn fetch_random_documents(
         &self,
         user_email: &str,
@@ -154,22 +369,68 @@ impl DocumentRepository {
         Ok(documents)
     }
 
+    /// The user's most recently indexed documents since `since` that have a
+    /// summary embedding, newest first. Used to group what the user can read
+    /// into topics; documents from `excluded_source_types` are skipped.
+    pub async fn fetch_recent_embedded_documents(
+        &self,
+        user_email: &str,
+        user_groups: &[String],
+        since: OffsetDateTime,
+        limit: usize,
+        excluded_source_types: &[SourceType],
+    ) -> Result<Vec<EmbeddedDocument>, DatabaseError> {
+        let permission_filter = self.generate_permission_filter(user_email, user_groups);
+        let query = format!(
+            r#"
+            SELECT d.id, d.source_id, d.title, d.content_id, se.embedding, se.model_name
+            FROM documents d
+            JOIN document_summary_embeddings se ON se.document_id = d.id
+            WHERE d.content_id IS NOT NULL
+                AND d.last_indexed_at >= $1
+                AND NOT EXISTS (
+                    SELECT 1
+                    FROM sources s
+                    WHERE s.id = d.source_id
+                        AND (s.is_deleted OR s.source_type = ANY($3))
+                )
+                AND {}
+            ORDER BY d.last_indexed_at DESC
+            LIMIT $2
+        "#,
+            permission_filter
+        );
+
+        let documents = sqlx::query_as::<_, EmbeddedDocument>(&query)
+            .bind(since)
+            .bind(limit as i64)
+            .bind(excluded_source_types)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(documents)
+    }
+
+    /// Ids of sources whose documents may appear in search: not deleted and
+    /// not hidden by maintenance mode.
     pub async fn fetch_active_source_ids(
         &self,
         source_types: Option<&[SourceType]>,
     ) -> Result<Vec<String>, DatabaseError> {
-        let source_ids: Vec<String> = if let Some(source_types) = source_types {
-            sqlx::query_scalar(
-                r#"SELECT id FROM sources WHERE source_type = ANY($1) AND NOT is_deleted"#,
-            )
-            .bind(source_types)
-            .fetch_all(&self.pool)
-            .await?
-        } else {
-            sqlx::query_scalar(r#"SELECT id FROM sources WHERE NOT is_deleted"#)
-                .fetch_all(&self.pool)
-                .await?
-        };
+        let source_ids: Vec<String> = sqlx::query_scalar(
+            r#"
+            SELECT s.id FROM sources s
+            WHERE NOT s.is_deleted
+              AND ($1::text[] IS NULL OR s.source_type = ANY($1))
+              AND NOT EXISTS (
+                  SELECT 1 FROM source_maintenance m
+                  WHERE m.source_id = s.id AND m.search_visibility = 'hidden'
+              )
+            "#,
+        )
+        .bind(source_types)
+        .fetch_all(&self.pool)
+        .await?;
 
         Ok(source_ids)
     }
@@ -473,6 +734,40 @@ impl DocumentRepository {
         Ok(updated_document)
     }
 
+    /// Replaces permissions only, leaving content, embeddings and versions
+    /// alone. With `descendants_path`, documents of the same source whose
+    /// `metadata.path` is under that path are updated too. Returns the number
+    /// of documents whose permissions actually changed.
+    pub async fn update_permissions(
+        &self,
+        source_id: &str,
+        external_id: &str,
+        permissions: &JsonValue,
+        descendants_path: Option<&str>,
+    ) -> Result<u64, DatabaseError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE documents
+            SET permissions = $3
+            WHERE source_id = $1
+              AND (
+                  external_id = $2
+                  OR ($4::text IS NOT NULL
+                      AND starts_with(metadata->>'path', rtrim($4, '/') || '/'))
+              )
+              AND permissions IS DISTINCT FROM $3
+            "#,
+        )
+        .bind(source_id)
+        .bind(external_id)
+        .bind(permissions)
+        .bind(descendants_path)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
     pub async fn delete(&self, id: &str) -> Result<bool, DatabaseError> {
         let result = sqlx::query("DELETE FROM documents WHERE id = $1")
             .bind(id)
@@ -490,8 +785,8 @@ impl DocumentRepository {
     ) -> Result<Document, DatabaseError> {
         let upserted_document = sqlx::query_as::<_, Document>(
             r#"
-            INSERT INTO documents (id, source_id, external_id, title, content_id, content_type, file_size, file_extension, url, metadata, permissions, attributes, created_at, updated_at, last_indexed_at, content)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
+            INSERT INTO documents (id, source_id, external_id, title, content_id, content_type, file_size, file_extension, url, metadata, permissions, attributes, created_at, updated_at, last_indexed_at, content, content_fingerprint)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17)
             ON CONFLICT (source_id, external_id)
             DO UPDATE SET
                 title = COALESCE(NULLIF(EXCLUDED.title, ''), documents.title),
@@ -505,7 +800,8 @@ impl DocumentRepository {
                 attributes = COALESCE(EXCLUDED.attributes, documents.attributes),
                 updated_at = EXCLUDED.updated_at,
                 last_indexed_at = CURRENT_TIMESTAMP,
-                content = EXCLUDED.content
+                content = EXCLUDED.content,
+                content_fingerprint = EXCLUDED.content_fingerprint
             RETURNING id, source_id, external_id, title, content_id, content_type,
                       file_size, file_extension, url,
                       metadata, permissions, attributes, created_at, updated_at, last_indexed_at
@@ -527,12 +823,110 @@ impl DocumentRepository {
         .bind(&document.updated_at)
         .bind(&document.last_indexed_at)
         .bind(content)
+        .bind(content_fingerprint(content))
         .fetch_one(&self.pool)
         .await?;
 
         Ok(upserted_document)
     }
 
+    /// Creates or updates the document keyed on (source_id, external_id) in a
+    /// single statement, so concurrent writers never hit a unique violation.
+    ///
+    /// With `expected_updated_at`, the document must already exist and still
+    /// carry that `updated_at`; otherwise nothing is written.
+    pub async fn upsert_if_unmodified(
+        &self,
+        document: Document,
+        content: &str,
+        expected_updated_at: Option<OffsetDateTime>,
+    ) -> Result<DocumentUpsertOutcome, DatabaseError> {
+        let Some(expected_updated_at) = expected_updated_at else {
+            let row = sqlx::query(
+                r#"
+                INSERT INTO documents (id, source_id, external_id, title, content_id, content_type, metadata, permissions, attributes, content, content_fingerprint)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+                ON CONFLICT (source_id, external_id)
+                DO UPDATE SET
+                    title = EXCLUDED.title,
+                    content_id = EXCLUDED.content_id,
+                    content_type = EXCLUDED.content_type,
+                    metadata = EXCLUDED.metadata,
+                    permissions = EXCLUDED.permissions,
+                    updated_at = CURRENT_TIMESTAMP,
+                    last_indexed_at = CURRENT_TIMESTAMP,
+                    content = EXCLUDED.content,
+                    content_fingerprint = EXCLUDED.content_fingerprint
+                RETURNING id, source_id, external_id, title, content_id, content_type,
+                          file_size, file_extension, url,
+                          metadata, permissions, attributes, created_at, updated_at, last_indexed_at,
+                          (xmax = 0) AS inserted
+                "#,
+            )
+            .bind(&document.id)
+            .bind(&document.source_id)
+            .bind(&document.external_id)
+            .bind(&document.title)
+            .bind(&document.content_id)
+            .bind(&document.content_type)
+            .bind(&document.metadata)
+            .bind(&document.permissions)
+            .bind(&document.attributes)
+            .bind(content)
+            .bind(content_fingerprint(content))
+            .fetch_one(&self.pool)
+            .await?;
+
+            let upserted = Document::from_row(&row)?;
+            return Ok(if row.try_get::<bool, _>("inserted")? {
+                DocumentUpsertOutcome::Created(upserted)
+            } else {
+                DocumentUpsertOutcome::Updated(upserted)
+            });
+        };
+
+        let updated = sqlx::query_as::<_, Document>(
+            r#"
+            UPDATE documents
+            SET title = $3,
+                content_id = $4,
+                content_type = $5,
+                metadata = $6,
+                permissions = $7,
+                updated_at = CURRENT_TIMESTAMP,
+                last_indexed_at = CURRENT_TIMESTAMP,
+                content = $8,
+                content_fingerprint = $9
+            WHERE source_id = $1 AND external_id = $2 AND updated_at = $10
+            RETURNING id, source_id, external_id, title, content_id, content_type,
+                      file_size, file_extension, url,
+                      metadata, permissions, attributes, created_at, updated_at, last_indexed_at
+            "#,
+        )
+        .bind(&document.source_id)
+        .bind(&document.external_id)
+        .bind(&document.title)
+        .bind(&document.content_id)
+        .bind(&document.content_type)
+        .bind(&document.metadata)
+        .bind(&document.permissions)
+        .bind(content)
+        .bind(content_fingerprint(content))
+        .bind(expected_updated_at)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match updated {
+            Some(document) => Ok(DocumentUpsertOutcome::Updated(document)),
+            None => {
+                let current = self
+                    .find_by_external_id(&document.source_id, &document.external_id)
+                    .await?;
+                Ok(DocumentUpsertOutcome::PreconditionFailed(current))
+            }
+        }
+    }
+
     /// Directly populates the content field since we use the ParadeDB BM25 index now
     pub async fn batch_upsert(
         &self,
@@ -568,6 +962,8 @@ impl DocumentRepository {
             documents.iter().map(|d| d.updated_at).collect();
         let last_indexed_ats: Vec<sqlx::types::time::OffsetDateTime> =
             documents.iter().map(|d| d.last_indexed_at).collect();
+        let fingerprints: Vec<Option<String>> =
+            contents.iter().map(|c| content_fingerprint(c)).collect();
 
         let upserted_documents = sqlx::query_as::<_, Document>(
             r#"
@@ -587,14 +983,15 @@ impl DocumentRepository {
                 created_at,
                 updated_at,
                 last_indexed_at,
-                content
+                content,
+                content_fingerprint
             )
             SELECT *
             FROM UNNEST(
                 $1::text[], $2::text[], $3::text[], $4::text[], $5::text[], $6::text[],
                 $7::bigint[], $8::text[], $9::text[], $10::jsonb[], $11::jsonb[], $12::jsonb[],
-                $13::timestamptz[], $14::timestamptz[], $15::timestamptz[], $16::text[]
-            ) AS t(id, source_id, external_id, title, content_id, content_type, file_size, file_extension, url, metadata, permissions, attributes, created_at, updated_at, last_indexed_at, content)
+                $13::timestamptz[], $14::timestamptz[], $15::timestamptz[], $16::text[], $17::text[]
+            ) AS t(id, source_id, external_id, title, content_id, content_type, file_size, file_extension, url, metadata, permissions, attributes, created_at, updated_at, last_indexed_at, content, content_fingerprint)
             ON CONFLICT (source_id, external_id)
             DO UPDATE SET
                 title = COALESCE(NULLIF(EXCLUDED.title, ''), documents.title),
@@ -608,7 +1005,8 @@ impl DocumentRepository {
                 attributes = COALESCE(EXCLUDED.attributes, documents.attributes),
                 updated_at = EXCLUDED.updated_at,
                 last_indexed_at = CURRENT_TIMESTAMP,
-                content = EXCLUDED.content
+                content = EXCLUDED.content,
+                content_fingerprint = EXCLUDED.content_fingerprint
             RETURNING id, source_id, external_id, title, content_id, content_type,
                       file_size, file_extension, url,
                       metadata, permissions, attributes, created_at, updated_at, last_indexed_at
@@ -630,6 +1028,7 @@ impl DocumentRepository {
         .bind(&updated_ats)
         .bind(&last_indexed_ats)
         .bind(&contents)
+        .bind(&fingerprints)
         .fetch_all(&self.pool)
         .await?;
 
@@ -651,7 +1050,8 @@ impl DocumentRepository {
 }
 
 /// Generate SQL condition to check if user has permission to access document.
-/// Checks: public access, direct user access, domain-wide access, and group membership.
+/// Checks: public access, direct user access, domain-wide access, and group membership,
+/// limited to documents of the user's workspace.
 ///
 /// Uses ParadeDB fielded queries so permission checks are evaluated by the
 /// BM25 index instead of as JSONB heap filters. The permissions field must be
@@ -677,8 +1077,21 @@ pub fn generate_permission_filter(user_email: &str, user_groups: &[String]) -> S
     }
 
     format!(
-        "permissions @@@ '{}'",
-        terms.join(" OR ").replace('\'', "''")
+        "permissions @@@ '{}' AND {}",
+        terms.join(" OR ").replace('\'', "''"),
+        generate_workspace_filter(user_email)
+    )
+}
+
+/// Generate SQL condition restricting documents to the sources of the user's
+/// workspace. Emails without a user account belong to the default workspace.
+pub fn generate_workspace_filter(user_email: &str) -> String {
+    format!(
+        "source_id IN (SELECT ws.id FROM sources ws WHERE ws.workspace_id = COALESCE(\
+         (SELECT wu.workspace_id FROM users wu WHERE lower(wu.email) = lower('{}') LIMIT 1), \
+         '{}'))",
+        user_email.replace('\'', "''"),
+        DEFAULT_WORKSPACE_ID
     )
 }
 
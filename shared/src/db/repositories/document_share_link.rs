@@ -0,0 +1,221 @@
+use crate::db::error::DatabaseError;
+use base64::{Engine, engine::general_purpose};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::{FromRow, PgPool, types::time::OffsetDateTime};
+
+const SHARE_TOKEN_PREFIX: &str = "omni_share_";
+/// Characters of the plaintext token kept for display, e.g. `omni_share_AbC1`.
+const DISPLAY_PREFIX_LEN: usize = 15;
+
+/// A link that lets anyone holding its token read one document until it
+/// expires, runs out of accesses, or is revoked. Only the SHA-256 of the
+/// token is stored; the plaintext is returned once, at creation.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct DocumentShareLink {
+    pub id: String,
+    pub document_id: String,
+    pub created_by: String,
+    pub token_prefix: String,
+    #[serde(with = "time::serde::iso8601")]
+    pub expires_at: OffsetDateTime,
+    pub max_accesses: Option<i32>,
+    pub access_count: i64,
+    #[serde(with = "time::serde::iso8601::option")]
+    pub last_accessed_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::iso8601::option")]
+    pub revoked_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::iso8601")]
+    pub created_at: OffsetDateTime,
+}
+
+/// Admin controls over share links.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ShareLinkPolicy {
+    pub enabled: bool,
+    pub default_ttl_hours: i32,
+    pub max_ttl_hours: i32,
+    /// Upper bound on a link's `max_accesses`; links created while it is set
+    /// must have one.
+    pub max_accesses: Option<i32>,
+    pub rate_limit_per_minute: i32,
+}
+
+const LINK_COLUMNS: &str = "id, document_id, created_by, token_prefix, expires_at, max_accesses, \
+     access_count, last_accessed_at, revoked_at, created_at";
+
+/// Conditions under which a link still grants access.
+const LINK_ACTIVE: &str = "revoked_at IS NULL AND expires_at > NOW() \
+     AND (max_accesses IS NULL OR access_count < max_accesses)";
+
+pub fn hash_share_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+fn generate_share_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    format!(
+        "{}{}",
+        SHARE_TOKEN_PREFIX,
+        general_purpose::URL_SAFE_NO_PAD.encode(bytes)
+    )
+}
+
+pub struct DocumentShareLinkRepository {
+    pool: PgPool,
+}
+
+impl DocumentShareLinkRepository {
+    pub fn new(pool: &PgPool) -> Self {
+        Self { pool: pool.clone() }
+    }
+
+    pub async fn get_policy(&self) -> Result<ShareLinkPolicy, DatabaseError> {
+        let policy = sqlx::query_as::<_, ShareLinkPolicy>(
+            "SELECT enabled, default_ttl_hours, max_ttl_hours, max_accesses, rate_limit_per_minute \
+             FROM share_link_policy",
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(policy)
+    }
+
+    /// Replace the policy. Existing links keep their expiry and access limit.
+    pub async fn update_policy(&self, policy: &ShareLinkPolicy) -> Result<(), DatabaseError> {
+        sqlx::query(
+            r#"
+            UPDATE share_link_policy
+            SET enabled = $1, default_ttl_hours = $2, max_ttl_hours = $3, max_accesses = $4,
+                rate_limit_per_minute = $5, updated_at = NOW()
+            "#,
+        )
+        .bind(policy.enabled)
+        .bind(policy.default_ttl_hours)
+        .bind(policy.max_ttl_hours)
+        .bind(policy.max_accesses)
+        .bind(policy.rate_limit_per_minute)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Create a link to a document. Returns the stored link and its token,
+    /// which cannot be recovered later.
+    pub async fn create(
+        &self,
+        document_id: &str,
+        created_by: &str,
+        expires_at: OffsetDateTime,
+        max_accesses: Option<i32>,
+    ) -> Result<(DocumentShareLink, String), DatabaseError> {
+        let token = generate_share_token();
+        let query = format!(
+            "INSERT INTO document_share_links \
+             (id, document_id, created_by, token_hash, token_prefix, expires_at, max_accesses) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING {LINK_COLUMNS}"
+        );
+        let link = sqlx::query_as::<_, DocumentShareLink>(&query)
+            .bind(crate::utils::generate_ulid())
+            .bind(document_id)
+            .bind(created_by)
+            .bind(hash_share_token(&token))
+            .bind(&token[..DISPLAY_PREFIX_LEN])
+            .bind(expires_at)
+            .bind(max_accesses)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok((link, token))
+    }
+
+    /// Links a user created for a document, newest first, including revoked
+    /// and expired ones.
+    pub async fn list_for_document(
+        &self,
+        document_id: &str,
+        created_by: &str,
+    ) -> Result<Vec<DocumentShareLink>, DatabaseError> {
+        let query = format!(
+            "SELECT {LINK_COLUMNS} FROM document_share_links \
+             WHERE document_id = $1 AND created_by = $2 ORDER BY created_at DESC"
+        );
+        let links = sqlx::query_as::<_, DocumentShareLink>(&query)
+            .bind(document_id)
+            .bind(created_by)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(links)
+    }
+
+    /// Links that still grant access, newest first.
+    pub async fn list_active(&self) -> Result<Vec<DocumentShareLink>, DatabaseError> {
+        let query = format!(
+            "SELECT {LINK_COLUMNS} FROM document_share_links WHERE {LINK_ACTIVE} \
+             ORDER BY created_at DESC"
+        );
+        let links = sqlx::query_as::<_, DocumentShareLink>(&query)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(links)
+    }
+
+    /// Revoke a link, only if `created_by` created it when given. Returns
+    /// false if there is no such unrevoked link.
+    pub async fn revoke(&self, id: &str, created_by: Option<&str>) -> Result<bool, DatabaseError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE document_share_links SET revoked_at = NOW()
+            WHERE id = $1 AND ($2::text IS NULL OR created_by = $2) AND revoked_at IS NULL
+            "#,
+        )
+        .bind(id)
+        .bind(created_by)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// The link a token belongs to, if it still grants access.
+    pub async fn find_active_by_token(
+        &self,
+        token: &str,
+    ) -> Result<Option<DocumentShareLink>, DatabaseError> {
+        let query = format!(
+            "SELECT {LINK_COLUMNS} FROM document_share_links \
+             WHERE token_hash = $1 AND {LINK_ACTIVE}"
+        );
+        let link = sqlx::query_as::<_, DocumentShareLink>(&query)
+            .bind(hash_share_token(token))
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(link)
+    }
+
+    /// Count one access to a link. Returns `None` if the link stopped
+    /// granting access in the meantime, e.g. another fetch used up its
+    /// last access.
+    pub async fn record_access(
+        &self,
+        id: &str,
+    ) -> Result<Option<DocumentShareLink>, DatabaseError> {
+        let query = format!(
+            "UPDATE document_share_links \
+             SET access_count = access_count + 1, last_accessed_at = NOW() \
+             WHERE id = $1 AND {LINK_ACTIVE} RETURNING {LINK_COLUMNS}"
+        );
+        let link = sqlx::query_as::<_, DocumentShareLink>(&query)
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(link)
+    }
+}
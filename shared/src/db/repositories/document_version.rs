@@ -0,0 +1,109 @@
+use crate::db::error::DatabaseError;
+use serde::Serialize;
+use sqlx::{FromRow, PgPool, types::time::OffsetDateTime};
+
+/// Content a document was indexed with at some point. Versions are recorded
+/// by a trigger on `documents` whenever its content changes.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct DocumentVersion {
+    pub document_id: String,
+    pub version: i32,
+    pub title: String,
+    /// None once the content blob is gone.
+    pub content_id: Option<String>,
+    pub content_hash: Option<String>,
+    pub size_bytes: Option<i64>,
+    #[serde(with = "time::serde::iso8601")]
+    pub indexed_at: OffsetDateTime,
+}
+
+pub struct DocumentVersionRepository {
+    pool: PgPool,
+}
+
+impl DocumentVersionRepository {
+    pub fn new(pool: &PgPool) -> Self {
+        Self { pool: pool.clone() }
+    }
+
+    /// Every retained version of a document, newest first.
+    pub async fn list(&self, document_id: &str) -> Result<Vec<DocumentVersion>, DatabaseError> {
+        let versions = sqlx::query_as::<_, DocumentVersion>(
+            r#"
+            SELECT dv.document_id::text AS document_id, dv.version, dv.title,
+                   dv.content_id::text AS content_id, cb.sha256_hash::text AS content_hash,
+                   cb.size_bytes, dv.indexed_at
+            FROM document_versions dv
+            LEFT JOIN content_blobs cb ON cb.id = dv.content_id
+            WHERE dv.document_id = $1
+            ORDER BY dv.version DESC
+            "#,
+        )
+        .bind(document_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(versions)
+    }
+
+    pub async fn find(
+        &self,
+        document_id: &str,
+        version: i32,
+    ) -> Result<Option<DocumentVersion>, DatabaseError> {
+        let version = sqlx::query_as::<_, DocumentVersion>(
+            r#"
+            SELECT dv.document_id::text AS document_id, dv.version, dv.title,
+                   dv.content_id::text AS content_id, cb.sha256_hash::text AS content_hash,
+                   cb.size_bytes, dv.indexed_at
+            FROM document_versions dv
+            LEFT JOIN content_blobs cb ON cb.id = dv.content_id
+            WHERE dv.document_id = $1 AND dv.version = $2
+            "#,
+        )
+        .bind(document_id)
+        .bind(version)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(version)
+    }
+
+    /// Delete versions beyond the newest `max_versions` of each document, and
+    /// versions indexed more than `max_age_days` ago. The newest version of a
+    /// document, its current content, is always kept. Limited to
+    /// `document_ids` when given. Returns the number of versions deleted.
+    pub async fn prune(
+        &self,
+        document_ids: Option<&[String]>,
+        max_versions: i64,
+        max_age_days: Option<i64>,
+    ) -> Result<u64, DatabaseError> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM document_versions dv
+            USING (
+                SELECT document_id, version,
+                       ROW_NUMBER() OVER (PARTITION BY document_id ORDER BY version DESC) AS rank
+                FROM document_versions
+                WHERE $1::text[] IS NULL OR document_id = ANY($1)
+            ) ranked
+            WHERE dv.document_id = ranked.document_id
+              AND dv.version = ranked.version
+              AND ranked.rank > 1
+              AND (
+                  ranked.rank > $2
+                  OR ($3::bigint IS NOT NULL
+                      AND dv.indexed_at < NOW() - make_interval(days => $3::int))
+              )
+            "#,
+        )
+        .bind(document_ids)
+        .bind(max_versions)
+        .bind(max_age_days)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
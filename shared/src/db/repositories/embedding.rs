@@ -21,9 +21,9 @@ impl EmbeddingRepository {
     ) -> Result<Vec<Embedding>, DatabaseError> {
         let embeddings = sqlx::query_as::<_, Embedding>(
             r#"
-            SELECT id, document_id, chunk_index, chunk_start_offset, chunk_end_offset, embedding, model_name, dimensions, created_at
+            SELECT id, document_id, chunk_index, chunk_start_offset, chunk_end_offset, embedding, model_name, dimensions, page_number, heading_path, created_at
             FROM embeddings
-            WHERE document_id = $1
+            WHERE document_id = $1 AND namespace IS NULL
             ORDER BY chunk_index
             "#,
         )
@@ -37,9 +37,9 @@ impl EmbeddingRepository {
     pub async fn create(&self, embedding: Embedding) -> Result<Embedding, DatabaseError> {
         let created_embedding = sqlx::query_as::<_, Embedding>(
             r#"
-            INSERT INTO embeddings (id, document_id, chunk_index, chunk_start_offset, chunk_end_offset, embedding, model_name, dimensions)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
-            RETURNING id, document_id, chunk_index, chunk_start_offset, chunk_end_offset, embedding, model_name, dimensions, created_at
+            INSERT INTO embeddings (id, document_id, chunk_index, chunk_start_offset, chunk_end_offset, embedding, model_name, dimensions, page_number, heading_path)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            RETURNING id, document_id, chunk_index, chunk_start_offset, chunk_end_offset, embedding, model_name, dimensions, page_number, heading_path, created_at
             "#,
         )
         .bind(&embedding.id)
@@ -50,6 +50,8 @@ impl EmbeddingRepository {
         .bind(&embedding.embedding)
         .bind(&embedding.model_name)
         .bind(&embedding.dimensions)
+        .bind(embedding.page_number)
+        .bind(&embedding.heading_path)
         .fetch_one(&self.pool)
         .await
         .map_err(|e| match e {
@@ -80,18 +82,23 @@ impl EmbeddingRepository {
             embeddings.iter().map(|e| e.embedding.clone()).collect();
         let model_names: Vec<String> = embeddings.iter().map(|e| e.model_name.clone()).collect();
         let dimensions_values: Vec<i16> = embeddings.iter().map(|e| e.dimensions).collect();
+        let page_numbers: Vec<Option<i32>> = embeddings.iter().map(|e| e.page_number).collect();
+        let heading_paths: Vec<Option<String>> =
+            embeddings.iter().map(|e| e.heading_path.clone()).collect();
 
         let mut tx = self.pool.begin().await?;
 
         sqlx::query(
             r#"
-            INSERT INTO embeddings (id, document_id, chunk_index, chunk_start_offset, chunk_end_offset, embedding, model_name, dimensions)
-            SELECT * FROM UNNEST($1::text[], $2::text[], $3::int4[], $4::int4[], $5::int4[], $6::vector[], $7::text[], $8::int2[])
-            ON CONFLICT (document_id, chunk_index, model_name) DO UPDATE
+            INSERT INTO embeddings (id, document_id, chunk_index, chunk_start_offset, chunk_end_offset, embedding, model_name, dimensions, page_number, heading_path)
+            SELECT * FROM UNNEST($1::text[], $2::text[], $3::int4[], $4::int4[], $5::int4[], $6::vector[], $7::text[], $8::int2[], $9::int4[], $10::text[])
+            ON CONFLICT (document_id, chunk_index, model_name, namespace) DO UPDATE
             SET chunk_start_offset = EXCLUDED.chunk_start_offset,
                 chunk_end_offset = EXCLUDED.chunk_end_offset,
                 embedding = EXCLUDED.embedding,
-                dimensions = EXCLUDED.dimensions
+                dimensions = EXCLUDED.dimensions,
+                page_number = EXCLUDED.page_number,
+                heading_path = EXCLUDED.heading_path
             "#,
         )
         .bind(&ids)
@@ -102,6 +109,8 @@ impl EmbeddingRepository {
         .bind(&embedding_vectors)
         .bind(&model_names)
         .bind(&dimensions_values)
+        .bind(&page_numbers)
+        .bind(&heading_paths)
         .execute(&mut *tx)
         .await?;
 
@@ -109,12 +118,14 @@ impl EmbeddingRepository {
         Ok(())
     }
 
-    /// Find surrounding chunks for multiple center chunks from the same document with context window
+    /// Find surrounding chunks for multiple center chunks from the same document with context window.
+    /// `namespace` selects a chunking experiment's shadow rows instead of production.
     pub async fn find_surrounding_chunks_for_document(
         &self,
         document_id: &str,
         center_chunk_indices: &[i32],
         context_window: i32,
+        namespace: Option<&str>,
     ) -> Result<Vec<Embedding>, DatabaseError> {
         if center_chunk_indices.is_empty() {
             return Ok(vec![]);
@@ -135,16 +146,18 @@ impl EmbeddingRepository {
 
         let embeddings = sqlx::query_as::<_, Embedding>(
             r#"
-            SELECT id, document_id, chunk_index, chunk_start_offset, chunk_end_offset, embedding, model_name, dimensions, created_at
+            SELECT id, document_id, chunk_index, chunk_start_offset, chunk_end_offset, embedding, model_name, dimensions, page_number, heading_path, created_at
             FROM embeddings
             WHERE document_id = $1
               AND chunk_index = ANY($2)
               AND model_name = (SELECT config->>'model' FROM embedding_providers WHERE is_current = TRUE AND is_deleted = FALSE LIMIT 1)
+              AND namespace IS NOT DISTINCT FROM $3
             ORDER BY chunk_index
             "#,
         )
         .bind(document_id)
         .bind(&indices)
+        .bind(namespace)
         .fetch_all(&self.pool)
         .await?;
 
@@ -170,7 +183,7 @@ impl EmbeddingRepository {
                 e.chunk_end_offset
             FROM embeddings e
             JOIN documents d ON e.document_id = d.id
-            WHERE e.dimensions = $3
+            WHERE e.dimensions = $3 AND e.namespace IS NULL
             ORDER BY e.embedding <=> $1
             LIMIT $2
             "#,
@@ -0,0 +1,452 @@
+use crate::db::error::DatabaseError;
+use crate::utils::generate_ulid;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool, types::time::OffsetDateTime};
+
+/// Migration work yields to production items, which are queued at priority 0.
+pub const MIGRATION_QUEUE_PRIORITY: i16 = -1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+pub enum EmbeddingMigrationStatus {
+    /// Documents are being embedded with the target provider while the
+    /// current provider keeps serving searches.
+    Running,
+    /// The target provider is current and the old model's vectors are gone.
+    Completed,
+    Cancelled,
+    Failed,
+}
+
+impl EmbeddingMigrationStatus {
+    pub fn is_active(&self) -> bool {
+        matches!(self, EmbeddingMigrationStatus::Running)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct EmbeddingMigration {
+    pub id: String,
+    pub to_provider_id: String,
+    pub from_model: String,
+    pub to_model: String,
+    pub status: EmbeddingMigrationStatus,
+    pub total_documents: i32,
+    pub error_message: Option<String>,
+    #[serde(with = "time::serde::iso8601")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::iso8601::option")]
+    pub completed_at: Option<OffsetDateTime>,
+}
+
+/// How far a migration's queued work has got.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct EmbeddingMigrationProgress {
+    /// Documents with production embeddings from the target model.
+    pub migrated_documents: i64,
+    /// Queued for the target model and not yet embedded, including retries.
+    pub pending_documents: i64,
+    /// Gave up after repeated failures; re-embedded with the target model
+    /// after the cutover.
+    pub quarantined_documents: i64,
+}
+
+impl EmbeddingMigrationProgress {
+    /// Share of the migration's queued documents that are no longer waiting.
+    pub fn percent(&self, total_documents: i32) -> f64 {
+        if total_documents <= 0 {
+            return 100.0;
+        }
+        let done = (total_documents as i64 - self.pending_documents).max(0);
+        (done as f64 / total_documents as f64 * 100.0).min(100.0)
+    }
+}
+
+/// Outcome of switching a finished migration's target provider in.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct EmbeddingMigrationCutover {
+    pub retired_embeddings: u64,
+    /// Documents the migration never reached, queued for production
+    /// re-embedding with the new current provider.
+    pub requeued_documents: u64,
+}
+
+const MIGRATION_COLUMNS: &str = r#"
+    id, to_provider_id, from_model, to_model, status, total_documents,
+    error_message, created_at, completed_at
+"#;
+
+pub struct EmbeddingMigrationRepository {
+    pool: PgPool,
+}
+
+impl EmbeddingMigrationRepository {
+    pub fn new(pool: &PgPool) -> Self {
+        Self { pool: pool.clone() }
+    }
+
+    /// Start migrating from the current provider's model to that of
+    /// `to_provider_id`, queueing every document with content for the target.
+    /// Fails with a constraint violation if a migration is already running.
+    pub async fn create(&self, to_provider_id: &str) -> Result<EmbeddingMigration, DatabaseError> {
+        let mut tx = self.pool.begin().await?;
+
+        let from_model: Option<String> = sqlx::query_scalar(
+            "SELECT config->>'model' FROM embedding_providers WHERE is_current = TRUE AND is_deleted = FALSE LIMIT 1",
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .flatten();
+        let Some(from_model) = from_model else {
+            return Err(DatabaseError::InvalidInput(
+                "No current embedding provider to migrate from".to_string(),
+            ));
+        };
+
+        let target: Option<(bool, Option<String>)> = sqlx::query_as(
+            "SELECT is_current, config->>'model' FROM embedding_providers WHERE id = $1 AND is_deleted = FALSE",
+        )
+        .bind(to_provider_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let to_model = match target {
+            None => {
+                return Err(DatabaseError::InvalidInput(format!(
+                    "Embedding provider {} does not exist",
+                    to_provider_id
+                )));
+            }
+            Some((true, _)) => {
+                return Err(DatabaseError::InvalidInput(format!(
+                    "Embedding provider {} is already current",
+                    to_provider_id
+                )));
+            }
+            Some((false, model)) => model.filter(|m| !m.is_empty()).ok_or_else(|| {
+                DatabaseError::InvalidInput(format!(
+                    "Embedding provider {} has no model configured",
+                    to_provider_id
+                ))
+            })?,
+        };
+        // Vectors are told apart by model name alone
+        if to_model == from_model {
+            return Err(DatabaseError::InvalidInput(format!(
+                "Embedding provider {} uses the current model {}",
+                to_provider_id, to_model
+            )));
+        }
+
+        let query = format!(
+            "INSERT INTO embedding_migrations (id, to_provider_id, from_model, to_model) \
+             VALUES ($1, $2, $3, $4) RETURNING {MIGRATION_COLUMNS}"
+        );
+        let result = sqlx::query_as::<_, EmbeddingMigration>(&query)
+            .bind(generate_ulid())
+            .bind(to_provider_id)
+            .bind(&from_model)
+            .bind(&to_model)
+            .fetch_one(&mut *tx)
+            .await;
+        let mut migration = match result {
+            Ok(migration) => migration,
+            Err(sqlx::Error::Database(e)) if e.is_unique_violation() => {
+                return Err(DatabaseError::ConstraintViolation(
+                    "An embedding migration is already running".to_string(),
+                ));
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        let document_ids: Vec<String> =
+            sqlx::query_scalar("SELECT id FROM documents WHERE content_id IS NOT NULL")
+                .fetch_all(&mut *tx)
+                .await?;
+        let ids: Vec<String> = document_ids.iter().map(|_| generate_ulid()).collect();
+        sqlx::query(
+            r#"
+            INSERT INTO embedding_queue (id, document_id, migration_id, priority)
+            SELECT q.id, q.document_id, $3, $4
+            FROM UNNEST($1::text[], $2::text[]) AS q(id, document_id)
+            "#,
+        )
+        .bind(&ids)
+        .bind(&document_ids)
+        .bind(&migration.id)
+        .bind(MIGRATION_QUEUE_PRIORITY)
+        .execute(&mut *tx)
+        .await?;
+
+        migration.total_documents = document_ids.len() as i32;
+        sqlx::query("UPDATE embedding_migrations SET total_documents = $2 WHERE id = $1")
+            .bind(&migration.id)
+            .bind(migration.total_documents)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(migration)
+    }
+
+    pub async fn get(&self, id: &str) -> Result<Option<EmbeddingMigration>, DatabaseError> {
+        let query = format!("SELECT {MIGRATION_COLUMNS} FROM embedding_migrations WHERE id = $1");
+        let migration = sqlx::query_as::<_, EmbeddingMigration>(&query)
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(migration)
+    }
+
+    pub async fn get_running(&self) -> Result<Option<EmbeddingMigration>, DatabaseError> {
+        let query = format!(
+            "SELECT {MIGRATION_COLUMNS} FROM embedding_migrations WHERE status = 'running'"
+        );
+        let migration = sqlx::query_as::<_, EmbeddingMigration>(&query)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(migration)
+    }
+
+    /// Most recent migrations first.
+    pub async fn list_recent(&self, limit: i64) -> Result<Vec<EmbeddingMigration>, DatabaseError> {
+        let query = format!(
+            "SELECT {MIGRATION_COLUMNS} FROM embedding_migrations ORDER BY created_at DESC LIMIT $1"
+        );
+        let migrations = sqlx::query_as::<_, EmbeddingMigration>(&query)
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(migrations)
+    }
+
+    pub async fn progress(
+        &self,
+        migration: &EmbeddingMigration,
+    ) -> Result<EmbeddingMigrationProgress, DatabaseError> {
+        let progress = sqlx::query_as::<_, EmbeddingMigrationProgress>(
+            r#"
+            SELECT
+                (SELECT COUNT(DISTINCT document_id) FROM embeddings
+                 WHERE model_name = $2 AND namespace IS NULL) AS migrated_documents,
+                COUNT(*) FILTER (
+                    WHERE status IN ('pending', 'processing', 'failed')
+                ) AS pending_documents,
+                COUNT(*) FILTER (WHERE status = 'quarantined') AS quarantined_documents
+            FROM embedding_queue
+            WHERE migration_id = $1
+            "#,
+        )
+        .bind(&migration.id)
+        .bind(&migration.to_model)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(progress)
+    }
+
+    /// Make the migration's target provider current and retire the old
+    /// model's vectors. Documents without target embeddings keep their old
+    /// vectors, which stop being searched, and are queued for production
+    /// re-embedding.
+    pub async fn cut_over(&self, id: &str) -> Result<EmbeddingMigrationCutover, DatabaseError> {
+        let mut tx = self.pool.begin().await?;
+
+        let query = format!(
+            "SELECT {MIGRATION_COLUMNS} FROM embedding_migrations WHERE id = $1 FOR UPDATE"
+        );
+        let migration = sqlx::query_as::<_, EmbeddingMigration>(&query)
+            .bind(id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or(DatabaseError::NotFound)?;
+        if !migration.status.is_active() {
+            return Err(DatabaseError::ConstraintViolation(format!(
+                "Embedding migration {} is not running",
+                id
+            )));
+        }
+
+        let target_available: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM embedding_providers WHERE id = $1 AND is_deleted = FALSE)",
+        )
+        .bind(&migration.to_provider_id)
+        .fetch_one(&mut *tx)
+        .await?;
+        if !target_available {
+            return Err(DatabaseError::InvalidInput(format!(
+                "Target embedding provider {} was deleted",
+                migration.to_provider_id
+            )));
+        }
+
+        sqlx::query(
+            r#"
+            UPDATE embedding_providers
+            SET is_current = (id = $1), updated_at = NOW()
+            WHERE is_current = TRUE OR id = $1
+            "#,
+        )
+        .bind(&migration.to_provider_id)
+        .execute(&mut *tx)
+        .await?;
+
+        let retired = sqlx::query(
+            r#"
+            DELETE FROM embeddings e
+            WHERE e.namespace IS NULL
+              AND e.model_name = $1
+              AND EXISTS (
+                  SELECT 1 FROM embeddings n
+                  WHERE n.document_id = e.document_id
+                    AND n.model_name = $2
+                    AND n.namespace IS NULL
+              )
+            "#,
+        )
+        .bind(&migration.from_model)
+        .bind(&migration.to_model)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        // Summaries hold one vector per document, so they cannot be written
+        // for both models; they are rebuilt as documents are next embedded
+        sqlx::query("DELETE FROM document_summary_embeddings WHERE model_name = $1")
+            .bind(&migration.from_model)
+            .execute(&mut *tx)
+            .await?;
+
+        let uncovered: Vec<String> = sqlx::query_scalar(
+            r#"
+            SELECT DISTINCT document_id FROM embeddings
+            WHERE model_name = $1 AND namespace IS NULL
+            "#,
+        )
+        .bind(&migration.from_model)
+        .fetch_all(&mut *tx)
+        .await?;
+        let ids: Vec<String> = uncovered.iter().map(|_| generate_ulid()).collect();
+        let requeued = sqlx::query(
+            r#"
+            INSERT INTO embedding_queue (id, document_id)
+            SELECT q.id, q.document_id
+            FROM UNNEST($1::text[], $2::text[]) AS q(id, document_id)
+            WHERE NOT EXISTS (
+                SELECT 1 FROM embedding_queue p
+                WHERE p.document_id = q.document_id
+                  AND p.namespace IS NULL
+                  AND p.migration_id IS NULL
+                  AND p.status IN ('pending', 'processing')
+            )
+            "#,
+        )
+        .bind(&ids)
+        .bind(&uncovered)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        sqlx::query(
+            r#"
+            UPDATE embedding_migrations
+            SET status = 'completed', completed_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(EmbeddingMigrationCutover {
+            retired_embeddings: retired,
+            requeued_documents: requeued,
+        })
+    }
+
+    /// Stop a running migration without switching providers, dropping its
+    /// queued work and the target model's vectors. Returns false if the
+    /// migration was not running.
+    pub async fn cancel(&self, id: &str) -> Result<bool, DatabaseError> {
+        self.stop(id, EmbeddingMigrationStatus::Cancelled, None)
+            .await
+    }
+
+    pub async fn fail(&self, id: &str, error_message: &str) -> Result<bool, DatabaseError> {
+        self.stop(id, EmbeddingMigrationStatus::Failed, Some(error_message))
+            .await
+    }
+
+    async fn stop(
+        &self,
+        id: &str,
+        status: EmbeddingMigrationStatus,
+        error_message: Option<&str>,
+    ) -> Result<bool, DatabaseError> {
+        let mut tx = self.pool.begin().await?;
+
+        let to_model: Option<String> = sqlx::query_scalar(
+            r#"
+            UPDATE embedding_migrations
+            SET status = $2, error_message = $3, completed_at = NOW()
+            WHERE id = $1 AND status = 'running'
+            RETURNING to_model
+            "#,
+        )
+        .bind(id)
+        .bind(status)
+        .bind(error_message)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(to_model) = to_model else {
+            return Ok(false);
+        };
+
+        sqlx::query("DELETE FROM embedding_queue WHERE migration_id = $1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        // The target never became current, so nothing searches these
+        sqlx::query("DELETE FROM embeddings WHERE model_name = $1 AND namespace IS NULL")
+            .bind(&to_model)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn progress(pending_documents: i64) -> EmbeddingMigrationProgress {
+        EmbeddingMigrationProgress {
+            migrated_documents: 0,
+            pending_documents,
+            quarantined_documents: 0,
+        }
+    }
+
+    #[test]
+    fn test_percent_counts_documents_no_longer_pending() {
+        assert_eq!(progress(100).percent(100), 0.0);
+        assert_eq!(progress(25).percent(100), 75.0);
+        assert_eq!(progress(0).percent(100), 100.0);
+        // Retries of dual-written documents can briefly exceed the total
+        assert_eq!(progress(120).percent(100), 0.0);
+    }
+
+    #[test]
+    fn test_percent_of_empty_migration_is_complete() {
+        assert_eq!(progress(0).percent(0), 100.0);
+    }
+}
@@ -1,5 +1,14 @@
 use crate::db::error::DatabaseError;
-use sqlx::PgPool;
+use serde_json::Value as JsonValue;
+use sqlx::{FromRow, PgPool};
+
+#[derive(Debug, Clone, FromRow)]
+pub struct EmbeddingProviderRecord {
+    pub id: String,
+    pub provider_type: String,
+    /// Provider settings, possibly wrapped as `{"encrypted_data": ...}`.
+    pub config: JsonValue,
+}
 
 #[derive(Clone)]
 pub struct EmbeddingProviderRepository {
@@ -20,4 +29,14 @@ impl EmbeddingProviderRepository {
 
         Ok(row.0)
     }
+
+    pub async fn get_current(&self) -> Result<Option<EmbeddingProviderRecord>, DatabaseError> {
+        let record = sqlx::query_as::<_, EmbeddingProviderRecord>(
+            "SELECT id, provider_type, config FROM embedding_providers WHERE is_current = TRUE AND is_deleted = FALSE LIMIT 1",
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(record)
+    }
 }
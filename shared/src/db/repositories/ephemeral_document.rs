@@ -0,0 +1,200 @@
+use crate::db::error::DatabaseError;
+use crate::db::row_level_security;
+use crate::models::SourceType;
+use crate::utils::generate_ulid;
+use serde::Serialize;
+use sqlx::{FromRow, PgPool, types::time::OffsetDateTime};
+
+/// A file uploaded into a chat and indexed as a regular document until it
+/// expires.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct EphemeralDocument {
+    pub document_id: String,
+    pub user_id: String,
+    pub chat_id: Option<String>,
+    pub filename: String,
+    #[serde(with = "time::serde::iso8601")]
+    pub expires_at: OffsetDateTime,
+    #[serde(with = "time::serde::iso8601")]
+    pub created_at: OffsetDateTime,
+}
+
+pub struct EphemeralDocumentRepository {
+    pool: PgPool,
+    row_level_security: bool,
+}
+
+impl EphemeralDocumentRepository {
+    pub fn new(pool: &PgPool) -> Self {
+        Self {
+            pool: pool.clone(),
+            row_level_security: false,
+        }
+    }
+
+    /// Also have Postgres enforce the permissions of the uploader when
+    /// listing their uploads, by reading as the document reader role.
+    pub fn with_row_level_security(mut self, enabled: bool) -> Self {
+        self.row_level_security = enabled;
+        self
+    }
+
+    /// The user's hidden chat upload source, created on first use. It is
+    /// inactive so the scheduler never tries to sync it.
+    pub async fn ensure_upload_source(&self, user_id: &str) -> Result<String, DatabaseError> {
+        sqlx::query(
+            r#"
+            INSERT INTO sources (id, name, source_type, config, is_active, created_by)
+            VALUES ($1, 'Chat uploads', $2, '{}', false, $3)
+            ON CONFLICT (created_by) WHERE source_type = 'chat_upload' AND NOT is_deleted
+            DO NOTHING
+            "#,
+        )
+        .bind(generate_ulid())
+        .bind(SourceType::ChatUpload)
+        .bind(user_id)
+        .execute(&self.pool)
+        .await?;
+
+        let source_id: String = sqlx::query_scalar(
+            r#"
+            SELECT id::text
+            FROM sources
+            WHERE created_by = $1 AND source_type = $2 AND NOT is_deleted
+            "#,
+        )
+        .bind(user_id)
+        .bind(SourceType::ChatUpload)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(source_id)
+    }
+
+    /// Whether `chat_id` is a chat of `user_id`.
+    pub async fn chat_belongs_to_user(
+        &self,
+        chat_id: &str,
+        user_id: &str,
+    ) -> Result<bool, DatabaseError> {
+        let exists: bool = sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM chats WHERE id = $1 AND user_id = $2)",
+        )
+        .bind(chat_id)
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(exists)
+    }
+
+    pub async fn create(
+        &self,
+        document_id: &str,
+        user_id: &str,
+        chat_id: Option<&str>,
+        filename: &str,
+        expires_at: OffsetDateTime,
+    ) -> Result<EphemeralDocument, DatabaseError> {
+        let document = sqlx::query_as::<_, EphemeralDocument>(
+            r#"
+            INSERT INTO ephemeral_documents (document_id, user_id, chat_id, filename, expires_at)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING document_id::text AS document_id, user_id::text AS user_id,
+                      chat_id::text AS chat_id, filename, expires_at, created_at
+            "#,
+        )
+        .bind(document_id)
+        .bind(user_id)
+        .bind(chat_id)
+        .bind(filename)
+        .bind(expires_at)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(document)
+    }
+
+    /// A user's unexpired uploads whose document `user_email` can read,
+    /// newest first, optionally limited to one chat.
+    pub async fn list(
+        &self,
+        user_id: &str,
+        user_email: &str,
+        chat_id: Option<&str>,
+    ) -> Result<Vec<EphemeralDocument>, DatabaseError> {
+        let query = sqlx::query_as::<_, EphemeralDocument>(
+            r#"
+            SELECT e.document_id::text AS document_id, e.user_id::text AS user_id,
+                   e.chat_id::text AS chat_id, e.filename, e.expires_at, e.created_at
+            FROM ephemeral_documents e
+            JOIN documents d ON d.id = e.document_id
+            WHERE e.user_id = $1
+              AND ($2::text IS NULL OR e.chat_id = $2)
+              AND e.expires_at > NOW()
+              AND d.permissions->'users' ? $3
+            ORDER BY e.created_at DESC
+            "#,
+        )
+        .bind(user_id)
+        .bind(chat_id)
+        .bind(user_email);
+
+        let documents = if self.row_level_security {
+            // Uploads are shared with the uploader alone, never a group
+            let mut tx = row_level_security::begin_as_user(&self.pool, user_email, &[]).await?;
+            let documents = query.fetch_all(&mut *tx).await?;
+            tx.commit().await?;
+            documents
+        } else {
+            query.fetch_all(&self.pool).await?
+        };
+
+        Ok(documents)
+    }
+
+    pub async fn find(
+        &self,
+        document_id: &str,
+    ) -> Result<Option<EphemeralDocument>, DatabaseError> {
+        let document = sqlx::query_as::<_, EphemeralDocument>(
+            r#"
+            SELECT document_id::text AS document_id, user_id::text AS user_id,
+                   chat_id::text AS chat_id, filename, expires_at, created_at
+            FROM ephemeral_documents
+            WHERE document_id = $1
+            "#,
+        )
+        .bind(document_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(document)
+    }
+
+    /// Delete up to `limit` documents that have expired or whose chat was
+    /// deleted, along with their chunks and embeddings. Returns the ids of
+    /// the deleted documents.
+    pub async fn delete_expired(&self, limit: i64) -> Result<Vec<String>, DatabaseError> {
+        let ids: Vec<String> = sqlx::query_scalar(
+            r#"
+            DELETE FROM documents
+            WHERE id IN (
+                SELECT e.document_id
+                FROM ephemeral_documents e
+                WHERE e.expires_at <= NOW()
+                   OR (e.chat_id IS NOT NULL
+                       AND NOT EXISTS (SELECT 1 FROM chats c WHERE c.id = e.chat_id))
+                ORDER BY e.expires_at
+                LIMIT $1
+            )
+            RETURNING id::text
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(ids)
+    }
+}
@@ -0,0 +1,123 @@
+use crate::db::error::DatabaseError;
+use serde::Serialize;
+use sqlx::{FromRow, PgPool, types::time::OffsetDateTime};
+
+/// How quickly a source's changes became searchable over a window. The row
+/// with no `source_id` covers all sources together.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct SourceFreshness {
+    pub source_id: Option<String>,
+    pub source_name: Option<String>,
+    /// Changes in the window that became searchable.
+    pub samples: i64,
+    /// Of `samples`, those that became searchable within the SLO.
+    pub within_slo: i64,
+    /// Changes in the window not yet searchable.
+    pub pending: i64,
+    /// Of `pending`, those already older than the SLO.
+    pub pending_over_slo: i64,
+    /// Seconds from enqueue to searchable; `None` without samples.
+    pub p50_secs: Option<f64>,
+    pub p95_secs: Option<f64>,
+    pub p99_secs: Option<f64>,
+    pub max_secs: Option<f64>,
+}
+
+pub struct FreshnessRepository {
+    pool: PgPool,
+}
+
+impl FreshnessRepository {
+    pub fn new(pool: &PgPool) -> Self {
+        Self { pool: pool.clone() }
+    }
+
+    /// Record that documents were indexed from the given connector events,
+    /// paired by position; a document may appear once per event. A document
+    /// is searchable now unless it still has embeddings queued, in which case
+    /// it stays pending until they complete. A change arriving while an
+    /// earlier one is still pending keeps the earlier enqueue time.
+    pub async fn record_indexed(
+        &self,
+        document_ids: &[String],
+        event_ids: &[String],
+    ) -> Result<u64, DatabaseError> {
+        if document_ids.is_empty() {
+            return Ok(0);
+        }
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO document_freshness
+                (document_id, source_id, enqueued_at, indexed_at, searchable_at)
+            SELECT d.id, d.source_id, MIN(q.created_at), NOW(),
+                   CASE WHEN EXISTS (
+                       SELECT 1 FROM embedding_queue eq
+                       WHERE eq.document_id = d.id
+                         AND eq.namespace IS NULL
+                         AND eq.migration_id IS NULL
+                         AND eq.status IN ('pending', 'processing', 'failed')
+                   ) THEN NULL ELSE NOW() END
+            FROM UNNEST($1::text[], $2::text[]) AS indexed(document_id, event_id)
+            JOIN documents d ON d.id = indexed.document_id
+            JOIN connector_events_queue q ON q.id = indexed.event_id
+            GROUP BY d.id, d.source_id
+            ON CONFLICT (document_id) DO UPDATE
+            SET enqueued_at = CASE
+                    WHEN document_freshness.searchable_at IS NULL
+                        THEN LEAST(document_freshness.enqueued_at, EXCLUDED.enqueued_at)
+                    ELSE EXCLUDED.enqueued_at
+                END,
+                indexed_at = EXCLUDED.indexed_at,
+                searchable_at = EXCLUDED.searchable_at
+            "#,
+        )
+        .bind(document_ids)
+        .bind(event_ids)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Freshness of changes enqueued since `since`, per source with changes
+    /// in the window, followed by the row for all sources.
+    pub async fn source_freshness(
+        &self,
+        since: OffsetDateTime,
+        slo_secs: i64,
+    ) -> Result<Vec<SourceFreshness>, DatabaseError> {
+        let rows = sqlx::query_as::<_, SourceFreshness>(
+            r#"
+            SELECT f.source_id::text AS source_id,
+                   s.name AS source_name,
+                   COUNT(f.latency_secs) AS samples,
+                   COUNT(*) FILTER (WHERE f.latency_secs <= $2) AS within_slo,
+                   COUNT(*) FILTER (WHERE f.latency_secs IS NULL) AS pending,
+                   COUNT(*) FILTER (
+                       WHERE f.latency_secs IS NULL
+                         AND f.enqueued_at < NOW() - make_interval(secs => $2)
+                   ) AS pending_over_slo,
+                   percentile_cont(0.5) WITHIN GROUP (ORDER BY f.latency_secs) AS p50_secs,
+                   percentile_cont(0.95) WITHIN GROUP (ORDER BY f.latency_secs) AS p95_secs,
+                   percentile_cont(0.99) WITHIN GROUP (ORDER BY f.latency_secs) AS p99_secs,
+                   MAX(f.latency_secs) AS max_secs
+            FROM (
+                SELECT source_id, enqueued_at,
+                       EXTRACT(EPOCH FROM searchable_at - enqueued_at)::float8 AS latency_secs
+                FROM document_freshness
+                WHERE enqueued_at >= $1
+            ) f
+            JOIN sources s ON s.id = f.source_id
+            GROUP BY GROUPING SETS ((f.source_id, s.name), ())
+            ORDER BY GROUPING(f.source_id), s.name
+            "#,
+        )
+        .bind(since)
+        .bind(slo_secs as f64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+}
@@ -1,7 +1,14 @@
-use crate::{db::error::DatabaseError, models::Group};
+use crate::{
+    db::error::DatabaseError,
+    models::{DEFAULT_WORKSPACE_ID, Group},
+};
 use sqlx::PgPool;
 use ulid::Ulid;
 
+/// How many levels of groups nested in other groups are followed when
+/// resolving a user's groups. Guards against membership cycles.
+pub const MAX_GROUP_NESTING_DEPTH: i32 = 10;
+
 pub struct GroupRepository {
     pool: PgPool,
 }
@@ -91,26 +98,83 @@ impl GroupRepository {
         Ok(count as usize)
     }
 
-    /// Find all group emails that a user belongs to (across all sources)
+    /// Find the effective groups of a user: the groups they are a member of,
+    /// plus the groups those are members of, up to `MAX_GROUP_NESTING_DEPTH`
+    /// levels. Only groups synced by live sources of the user's workspace count.
     pub async fn find_groups_for_user(
         &self,
         user_email: &str,
     ) -> Result<Vec<String>, DatabaseError> {
         let group_emails: Vec<String> = sqlx::query_scalar(
             r#"
-            SELECT DISTINCT g.email
-            FROM groups g
-            JOIN group_memberships gm ON g.id = gm.group_id
-            WHERE lower(gm.member_email) = lower($1)
+            WITH RECURSIVE workspace_groups AS (
+                SELECT g.id, g.email
+                FROM groups g
+                JOIN sources s ON s.id = g.source_id AND NOT s.is_deleted
+                WHERE s.workspace_id = COALESCE(
+                    (SELECT workspace_id FROM users WHERE lower(email) = lower($1) LIMIT 1),
+                    $2
+                )
+            ),
+            effective_groups (email, depth) AS (
+                SELECT wg.email, 1
+                FROM workspace_groups wg
+                JOIN group_memberships gm ON gm.group_id = wg.id
+                WHERE lower(gm.member_email) = lower($1)
+                UNION
+                SELECT wg.email, eg.depth + 1
+                FROM effective_groups eg
+                JOIN group_memberships gm ON lower(gm.member_email) = lower(eg.email)
+                JOIN workspace_groups wg ON wg.id = gm.group_id
+                WHERE eg.depth < $3
+            )
+            SELECT DISTINCT email FROM effective_groups
             "#,
         )
         .bind(user_email)
+        .bind(DEFAULT_WORKSPACE_ID)
+        .bind(MAX_GROUP_NESTING_DEPTH)
         .fetch_all(&self.pool)
         .await?;
 
         Ok(group_emails)
     }
 
+    /// Delete groups that the latest completed full sync of their source no
+    /// longer reported, i.e. groups removed upstream. Sources whose latest full
+    /// sync reported no groups at all are skipped, as are sources with group
+    /// syncs still waiting in the event queue.
+    pub async fn delete_stale(&self) -> Result<u64, DatabaseError> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM groups g
+            USING (
+                SELECT DISTINCT ON (source_id) source_id, started_at
+                FROM sync_runs
+                WHERE sync_type = 'full' AND status = 'completed'
+                ORDER BY source_id, started_at DESC
+            ) latest
+            WHERE g.source_id = latest.source_id
+              AND g.synced_at < latest.started_at
+              AND EXISTS (
+                  SELECT 1 FROM groups fresh
+                  WHERE fresh.source_id = latest.source_id
+                    AND fresh.synced_at >= latest.started_at
+              )
+              AND NOT EXISTS (
+                  SELECT 1 FROM connector_events_queue q
+                  WHERE q.source_id = g.source_id
+                    AND q.event_type = 'group_membership_sync'
+                    AND q.status IN ('pending', 'processing', 'failed')
+              )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
     /// Delete all groups (and cascade memberships) for a source
     pub async fn delete_by_source(&self, source_id: &str) -> Result<u64, DatabaseError> {
         let result = sqlx::query("DELETE FROM groups WHERE source_id = $1")
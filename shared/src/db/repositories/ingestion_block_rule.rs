@@ -0,0 +1,273 @@
+use crate::db::error::DatabaseError;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool, types::time::OffsetDateTime};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+pub enum BlockRuleKind {
+    /// The document path is the pattern or lies beneath it.
+    Folder,
+    /// Case-insensitive glob (`*`, `?`) on the document's file name.
+    FileName,
+    /// Regular expression on the extracted text.
+    Content,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct IngestionBlockRule {
+    pub id: String,
+    pub name: String,
+    pub kind: BlockRuleKind,
+    pub pattern: String,
+    pub source_id: Option<String>,
+    pub enabled: bool,
+    pub match_count: i64,
+    #[serde(with = "time::serde::iso8601::option")]
+    pub last_matched_at: Option<OffsetDateTime>,
+    pub created_by: Option<String>,
+    #[serde(with = "time::serde::iso8601")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::iso8601")]
+    pub updated_at: OffsetDateTime,
+}
+
+/// A document held back by a block rule.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct QuarantinedEvent {
+    pub id: String,
+    pub rule_id: Option<String>,
+    pub rule_name: String,
+    pub source_id: String,
+    pub external_id: String,
+    pub title: String,
+    pub url: Option<String>,
+    pub path: Option<String>,
+    pub matched_value: String,
+    pub sync_run_id: Option<String>,
+    #[serde(with = "time::serde::iso8601")]
+    pub created_at: OffsetDateTime,
+}
+
+/// A blocked document to record, before it has an id or timestamp.
+#[derive(Debug, Clone)]
+pub struct NewQuarantinedEvent {
+    /// Unset for documents held back by a source's PII policy.
+    pub rule_id: Option<String>,
+    pub rule_name: String,
+    pub source_id: String,
+    pub external_id: String,
+    pub title: String,
+    pub url: Option<String>,
+    pub path: Option<String>,
+    pub matched_value: String,
+    pub sync_run_id: Option<String>,
+}
+
+/// Fields of a rule an admin can change. `None` leaves the field as is.
+#[derive(Debug, Clone, Default)]
+pub struct BlockRuleUpdate {
+    pub name: Option<String>,
+    pub pattern: Option<String>,
+    pub enabled: Option<bool>,
+}
+
+const RULE_COLUMNS: &str = r#"
+    id, name, kind, pattern, source_id, enabled, match_count, last_matched_at,
+    created_by, created_at, updated_at
+"#;
+
+pub struct IngestionBlockRuleRepository {
+    pool: PgPool,
+}
+
+impl IngestionBlockRuleRepository {
+    pub fn new(pool: &PgPool) -> Self {
+        Self { pool: pool.clone() }
+    }
+
+    pub async fn create(
+        &self,
+        name: &str,
+        kind: BlockRuleKind,
+        pattern: &str,
+        source_id: Option<&str>,
+        created_by: Option<&str>,
+    ) -> Result<IngestionBlockRule, DatabaseError> {
+        let query = format!(
+            "INSERT INTO ingestion_block_rules (id, name, kind, pattern, source_id, created_by) \
+             VALUES ($1, $2, $3, $4, $5, $6) RETURNING {RULE_COLUMNS}"
+        );
+        let rule = sqlx::query_as::<_, IngestionBlockRule>(&query)
+            .bind(crate::utils::generate_ulid())
+            .bind(name)
+            .bind(kind)
+            .bind(pattern)
+            .bind(source_id)
+            .bind(created_by)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(rule)
+    }
+
+    pub async fn get(&self, id: &str) -> Result<Option<IngestionBlockRule>, DatabaseError> {
+        let query = format!("SELECT {RULE_COLUMNS} FROM ingestion_block_rules WHERE id = $1");
+        let rule = sqlx::query_as::<_, IngestionBlockRule>(&query)
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(rule)
+    }
+
+    pub async fn list(&self) -> Result<Vec<IngestionBlockRule>, DatabaseError> {
+        let query = format!("SELECT {RULE_COLUMNS} FROM ingestion_block_rules ORDER BY created_at");
+        let rules = sqlx::query_as::<_, IngestionBlockRule>(&query)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rules)
+    }
+
+    pub async fn list_enabled(&self) -> Result<Vec<IngestionBlockRule>, DatabaseError> {
+        let query = format!(
+            "SELECT {RULE_COLUMNS} FROM ingestion_block_rules \
+             WHERE enabled = TRUE ORDER BY created_at"
+        );
+        let rules = sqlx::query_as::<_, IngestionBlockRule>(&query)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rules)
+    }
+
+    pub async fn update(
+        &self,
+        id: &str,
+        update: &BlockRuleUpdate,
+    ) -> Result<Option<IngestionBlockRule>, DatabaseError> {
+        let query = format!(
+            "UPDATE ingestion_block_rules \
+             SET name = COALESCE($2, name), \
+                 pattern = COALESCE($3, pattern), \
+                 enabled = COALESCE($4, enabled), \
+                 updated_at = NOW() \
+             WHERE id = $1 RETURNING {RULE_COLUMNS}"
+        );
+        let rule = sqlx::query_as::<_, IngestionBlockRule>(&query)
+            .bind(id)
+            .bind(update.name.as_deref())
+            .bind(update.pattern.as_deref())
+            .bind(update.enabled)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(rule)
+    }
+
+    pub async fn delete(&self, id: &str) -> Result<bool, DatabaseError> {
+        let result = sqlx::query("DELETE FROM ingestion_block_rules WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Record blocked documents and bump the match counters of their rules.
+    pub async fn record_quarantined(
+        &self,
+        events: &[NewQuarantinedEvent],
+    ) -> Result<(), DatabaseError> {
+        if events.is_empty() {
+            return Ok(());
+        }
+
+        let ids: Vec<String> = events
+            .iter()
+            .map(|_| crate::utils::generate_ulid())
+            .collect();
+        let rule_ids: Vec<Option<&str>> = events.iter().map(|e| e.rule_id.as_deref()).collect();
+        let rule_names: Vec<&str> = events.iter().map(|e| e.rule_name.as_str()).collect();
+        let source_ids: Vec<&str> = events.iter().map(|e| e.source_id.as_str()).collect();
+        let external_ids: Vec<&str> = events.iter().map(|e| e.external_id.as_str()).collect();
+        let titles: Vec<&str> = events.iter().map(|e| e.title.as_str()).collect();
+        let urls: Vec<Option<&str>> = events.iter().map(|e| e.url.as_deref()).collect();
+        let paths: Vec<Option<&str>> = events.iter().map(|e| e.path.as_deref()).collect();
+        let matched_values: Vec<&str> = events.iter().map(|e| e.matched_value.as_str()).collect();
+        let sync_run_ids: Vec<Option<&str>> =
+            events.iter().map(|e| e.sync_run_id.as_deref()).collect();
+
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO ingestion_quarantined_events
+                (id, rule_id, rule_name, source_id, external_id, title, url, path,
+                 matched_value, sync_run_id)
+            SELECT * FROM UNNEST($1::text[], $2::text[], $3::text[], $4::text[], $5::text[],
+                                 $6::text[], $7::text[], $8::text[], $9::text[], $10::text[])
+            "#,
+        )
+        .bind(&ids)
+        .bind(&rule_ids)
+        .bind(&rule_names)
+        .bind(&source_ids)
+        .bind(&external_ids)
+        .bind(&titles)
+        .bind(&urls)
+        .bind(&paths)
+        .bind(&matched_values)
+        .bind(&sync_run_ids)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            UPDATE ingestion_block_rules r
+            SET match_count = r.match_count + m.matches,
+                last_matched_at = NOW()
+            FROM (
+                SELECT rule_id, COUNT(*) AS matches
+                FROM UNNEST($1::text[]) AS t(rule_id)
+                GROUP BY rule_id
+            ) m
+            WHERE r.id = m.rule_id
+            "#,
+        )
+        .bind(&rule_ids)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Most recent quarantined events first, optionally for one rule or source.
+    pub async fn list_quarantined(
+        &self,
+        rule_id: Option<&str>,
+        source_id: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<QuarantinedEvent>, DatabaseError> {
+        let events = sqlx::query_as::<_, QuarantinedEvent>(
+            r#"
+            SELECT id, rule_id, rule_name, source_id, external_id, title, url, path,
+                   matched_value, sync_run_id, created_at
+            FROM ingestion_quarantined_events
+            WHERE ($1::text IS NULL OR rule_id = $1)
+              AND ($2::text IS NULL OR source_id = $2)
+            ORDER BY created_at DESC, id DESC
+            LIMIT $3
+            "#,
+        )
+        .bind(rule_id)
+        .bind(source_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(events)
+    }
+}
@@ -0,0 +1,195 @@
+use crate::db::error::DatabaseError;
+use serde::Serialize;
+use sqlx::{PgPool, Row};
+
+/// Total number of rows matching an integrity check, plus the first few ids.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct IntegritySample {
+    pub count: i64,
+    pub sample_ids: Vec<String>,
+}
+
+/// Queries behind the index integrity checker. Every check returns a bounded
+/// sample alongside the full count, and every repair is capped per call so a
+/// large backlog is worked through over several runs.
+pub struct IntegrityRepository {
+    pool: PgPool,
+}
+
+impl IntegrityRepository {
+    pub fn new(pool: &PgPool) -> Self {
+        Self { pool: pool.clone() }
+    }
+
+    async fn sample(
+        &self,
+        query: &str,
+        bind: Option<i64>,
+        limit: i64,
+    ) -> Result<IntegritySample, DatabaseError> {
+        let mut q = sqlx::query(query);
+        if let Some(value) = bind {
+            q = q.bind(value);
+        }
+        let rows = q.bind(limit).fetch_all(&self.pool).await?;
+
+        let count = rows
+            .first()
+            .map(|row| row.try_get::<i64, _>("total"))
+            .transpose()?
+            .unwrap_or(0);
+        let sample_ids = rows
+            .iter()
+            .map(|row| row.try_get::<String, _>("id"))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(IntegritySample { count, sample_ids })
+    }
+
+    /// Documents whose content blob is gone. The foreign key nulls
+    /// `content_id` when a blob is deleted, so these are the rows left behind.
+    pub async fn documents_missing_content(
+        &self,
+        limit: i64,
+    ) -> Result<IntegritySample, DatabaseError> {
+        self.sample(
+            r#"
+            SELECT d.id, COUNT(*) OVER () AS total
+            FROM documents d
+            WHERE d.content_id IS NULL
+            ORDER BY d.id
+            LIMIT $1
+            "#,
+            None,
+            limit,
+        )
+        .await
+    }
+
+    /// Blobs older than `min_age_secs` that nothing references and that GC has
+    /// not picked up yet. The age floor skips blobs whose event has not been
+    /// enqueued yet.
+    pub async fn unreferenced_blobs(
+        &self,
+        min_age_secs: i64,
+        limit: i64,
+    ) -> Result<IntegritySample, DatabaseError> {
+        self.sample(
+            r#"
+            SELECT cb.id::text AS id, COUNT(*) OVER () AS total
+            FROM content_blobs cb
+            WHERE cb.orphaned_at IS NULL
+              AND cb.created_at < NOW() - make_interval(secs => $1::float8)
+              AND NOT EXISTS (SELECT 1 FROM documents d WHERE d.content_id = cb.id)
+              AND NOT EXISTS (
+                  SELECT 1 FROM connector_events_queue q
+                  WHERE q.status IN ('pending', 'processing')
+                    AND q.payload->>'content_id' = cb.id::text
+              )
+              AND NOT EXISTS (SELECT 1 FROM uploads u WHERE u.content_id = cb.id)
+              AND NOT EXISTS (SELECT 1 FROM document_versions dv WHERE dv.content_id = cb.id)
+            ORDER BY cb.created_at
+            LIMIT $2
+            "#,
+            Some(min_age_secs),
+            limit,
+        )
+        .await
+    }
+
+    /// Documents with content, indexed more than `sla_hours` ago, that have no
+    /// embeddings and nothing queued to produce them. Quarantined documents are
+    /// left to an operator rather than re-queued.
+    pub async fn documents_missing_embeddings(
+        &self,
+        sla_hours: i64,
+        limit: i64,
+    ) -> Result<IntegritySample, DatabaseError> {
+        self.sample(
+            r#"
+            SELECT d.id, COUNT(*) OVER () AS total
+            FROM documents d
+            WHERE d.content_id IS NOT NULL
+              AND d.last_indexed_at < NOW() - make_interval(hours => $1::int)
+              AND NOT EXISTS (
+                  SELECT 1 FROM embeddings e
+                  WHERE e.document_id = d.id AND e.namespace IS NULL
+              )
+              AND NOT EXISTS (
+                  SELECT 1 FROM embedding_queue q
+                  WHERE q.document_id = d.id
+                    AND q.namespace IS NULL
+                    AND q.migration_id IS NULL
+                    AND q.status IN ('pending', 'processing', 'quarantined')
+              )
+            ORDER BY d.last_indexed_at
+            LIMIT $2
+            "#,
+            Some(sla_hours),
+            limit,
+        )
+        .await
+    }
+
+    /// Embedding rows whose document no longer exists. The cascading foreign
+    /// key should prevent these; they appear when it was dropped or bypassed,
+    /// e.g. during a migration.
+    pub async fn dangling_embeddings(&self, limit: i64) -> Result<IntegritySample, DatabaseError> {
+        self.sample(
+            r#"
+            SELECT e.id::text AS id, COUNT(*) OVER () AS total
+            FROM embeddings e
+            WHERE NOT EXISTS (SELECT 1 FROM documents d WHERE d.id = e.document_id)
+            ORDER BY e.id
+            LIMIT $1
+            "#,
+            None,
+            limit,
+        )
+        .await
+    }
+
+    /// Hand up to `limit` unreferenced blobs to GC by marking them orphaned;
+    /// GC deletes them once the retention period has passed.
+    pub async fn mark_unreferenced_blobs(
+        &self,
+        min_age_secs: i64,
+        limit: i64,
+    ) -> Result<u64, DatabaseError> {
+        let ids = self
+            .unreferenced_blobs(min_age_secs, limit)
+            .await?
+            .sample_ids;
+        if ids.is_empty() {
+            return Ok(0);
+        }
+
+        let result = sqlx::query(
+            "UPDATE content_blobs SET orphaned_at = CURRENT_TIMESTAMP WHERE id = ANY($1) AND orphaned_at IS NULL",
+        )
+        .bind(&ids)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    pub async fn delete_dangling_embeddings(&self, limit: i64) -> Result<u64, DatabaseError> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM embeddings
+            WHERE id IN (
+                SELECT e.id
+                FROM embeddings e
+                WHERE NOT EXISTS (SELECT 1 FROM documents d WHERE d.id = e.document_id)
+                LIMIT $1
+            )
+            "#,
+        )
+        .bind(limit)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,202 @@
+use crate::db::error::DatabaseError;
+use serde::Serialize;
+use sqlx::{FromRow, PgPool, types::time::OffsetDateTime};
+
+/// A document URL due for verification.
+#[derive(Debug, Clone, FromRow)]
+pub struct LinkCheckCandidate {
+    pub document_id: String,
+    pub source_id: String,
+    pub url: String,
+}
+
+/// Outcome of checking one document URL.
+#[derive(Debug, Clone)]
+pub struct LinkCheckRecord {
+    pub document_id: String,
+    pub source_id: String,
+    pub url: String,
+    pub status: String,
+    pub http_status: Option<i32>,
+    pub final_url: Option<String>,
+    pub error_message: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct DeadLink {
+    pub document_id: String,
+    pub title: String,
+    pub url: String,
+    pub http_status: Option<i32>,
+    #[serde(with = "time::serde::iso8601")]
+    pub checked_at: OffsetDateTime,
+}
+
+/// Per-source counts of the most recent check for each document.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct SourceLinkSummary {
+    pub source_id: String,
+    pub source_name: String,
+    pub checked: i64,
+    pub ok: i64,
+    pub redirected: i64,
+    pub dead: i64,
+    pub unverifiable: i64,
+    pub errors: i64,
+    #[serde(with = "time::serde::iso8601::option")]
+    pub last_checked_at: Option<OffsetDateTime>,
+}
+
+pub struct LinkCheckRepository {
+    pool: PgPool,
+}
+
+impl LinkCheckRepository {
+    pub fn new(pool: &PgPool) -> Self {
+        Self { pool: pool.clone() }
+    }
+
+    /// Pick up to `per_source` random documents with an http(s) URL from each
+    /// active source, skipping documents checked within `recheck_after_hours`.
+    pub async fn sample_candidates(
+        &self,
+        per_source: i64,
+        recheck_after_hours: i64,
+    ) -> Result<Vec<LinkCheckCandidate>, DatabaseError> {
+        let candidates = sqlx::query_as::<_, LinkCheckCandidate>(
+            r#"
+            SELECT document_id, source_id, url
+            FROM (
+                SELECT d.id AS document_id, d.source_id, d.url,
+                       ROW_NUMBER() OVER (PARTITION BY d.source_id ORDER BY random()) AS rn
+                FROM documents d
+                JOIN sources s ON s.id = d.source_id
+                LEFT JOIN document_link_checks lc ON lc.document_id = d.id
+                WHERE s.is_deleted = false
+                  AND d.url ~* '^https?://'
+                  AND (lc.checked_at IS NULL
+                       OR lc.checked_at < NOW() - make_interval(hours => $2::int))
+            ) sampled
+            WHERE rn <= $1
+            "#,
+        )
+        .bind(per_source)
+        .bind(recheck_after_hours)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(candidates)
+    }
+
+    pub async fn record_results(&self, records: &[LinkCheckRecord]) -> Result<u64, DatabaseError> {
+        if records.is_empty() {
+            return Ok(0);
+        }
+
+        let document_ids: Vec<&str> = records.iter().map(|r| r.document_id.as_str()).collect();
+        let source_ids: Vec<&str> = records.iter().map(|r| r.source_id.as_str()).collect();
+        let urls: Vec<&str> = records.iter().map(|r| r.url.as_str()).collect();
+        let statuses: Vec<&str> = records.iter().map(|r| r.status.as_str()).collect();
+        let http_statuses: Vec<Option<i32>> = records.iter().map(|r| r.http_status).collect();
+        let final_urls: Vec<Option<&str>> =
+            records.iter().map(|r| r.final_url.as_deref()).collect();
+        let errors: Vec<Option<&str>> =
+            records.iter().map(|r| r.error_message.as_deref()).collect();
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO document_link_checks
+                (document_id, source_id, url, status, http_status, final_url, error_message, checked_at)
+            SELECT t.document_id, t.source_id, t.url, t.status, t.http_status, t.final_url,
+                   t.error_message, NOW()
+            FROM UNNEST($1::text[], $2::text[], $3::text[], $4::text[], $5::int4[], $6::text[], $7::text[])
+                AS t(document_id, source_id, url, status, http_status, final_url, error_message)
+            JOIN documents d ON d.id = t.document_id
+            ON CONFLICT (document_id) DO UPDATE
+            SET url = EXCLUDED.url,
+                status = EXCLUDED.status,
+                http_status = EXCLUDED.http_status,
+                final_url = EXCLUDED.final_url,
+                error_message = EXCLUDED.error_message,
+                checked_at = EXCLUDED.checked_at
+            "#,
+        )
+        .bind(&document_ids)
+        .bind(&source_ids)
+        .bind(&urls)
+        .bind(&statuses)
+        .bind(&http_statuses)
+        .bind(&final_urls)
+        .bind(&errors)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Point a document at the URL it was permanently redirected to. Only
+    /// applies if the stored URL is still the one that was checked, so a
+    /// connector update in the meantime wins.
+    pub async fn update_document_url(
+        &self,
+        document_id: &str,
+        checked_url: &str,
+        new_url: &str,
+    ) -> Result<bool, DatabaseError> {
+        let result = sqlx::query("UPDATE documents SET url = $3 WHERE id = $1 AND url = $2")
+            .bind(document_id)
+            .bind(checked_url)
+            .bind(new_url)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn get_source_summaries(&self) -> Result<Vec<SourceLinkSummary>, DatabaseError> {
+        let summaries = sqlx::query_as::<_, SourceLinkSummary>(
+            r#"
+            SELECT s.id AS source_id, s.name AS source_name,
+                   COUNT(*) AS checked,
+                   COUNT(*) FILTER (WHERE lc.status = 'ok') AS ok,
+                   COUNT(*) FILTER (WHERE lc.status = 'redirected') AS redirected,
+                   COUNT(*) FILTER (WHERE lc.status = 'dead') AS dead,
+                   COUNT(*) FILTER (WHERE lc.status = 'unverifiable') AS unverifiable,
+                   COUNT(*) FILTER (WHERE lc.status = 'error') AS errors,
+                   MAX(lc.checked_at) AS last_checked_at
+            FROM document_link_checks lc
+            JOIN sources s ON s.id = lc.source_id
+            WHERE s.is_deleted = false
+            GROUP BY s.id, s.name
+            ORDER BY s.name
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(summaries)
+    }
+
+    pub async fn get_dead_links(
+        &self,
+        source_id: &str,
+        limit: i64,
+    ) -> Result<Vec<DeadLink>, DatabaseError> {
+        let links = sqlx::query_as::<_, DeadLink>(
+            r#"
+            SELECT d.id AS document_id, d.title, lc.url, lc.http_status, lc.checked_at
+            FROM document_link_checks lc
+            JOIN documents d ON d.id = lc.document_id
+            WHERE lc.source_id = $1 AND lc.status = 'dead'
+            ORDER BY lc.checked_at DESC
+            LIMIT $2
+            "#,
+        )
+        .bind(source_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(links)
+    }
+}
@@ -1,25 +1,118 @@
+pub mod audit_log;
+pub mod compliance_request;
 pub mod configuration;
 pub mod connector_config;
+pub mod connector_error;
 pub mod content_blob;
+pub mod corpus_stats;
 pub mod document;
+pub mod document_share_link;
+pub mod document_version;
 pub mod embedding;
+pub mod embedding_migration;
 pub mod embedding_provider;
+pub mod ephemeral_document;
+pub mod freshness;
 pub mod group;
+pub mod ingestion_block_rule;
+pub mod integrity;
+pub mod link_check;
 pub mod person;
+pub mod pipeline_trace;
+pub mod push_api_key;
+pub mod ranking_profile;
 pub mod service_credentials;
 pub mod source;
+pub mod source_export;
+pub mod source_maintenance;
+pub mod source_migration;
+pub mod source_ownership;
+pub mod source_pii_policy;
+pub mod source_reindex;
+pub mod source_retention;
+pub mod source_stats;
+pub mod storage_snapshot;
 pub mod sync_run;
+pub mod sync_state;
 pub mod user;
+pub mod vector_index_build;
+pub mod workspace;
 
+pub use audit_log::{AuditLogEntry, AuditLogFilter, AuditLogRepository};
+pub use compliance_request::{
+    ComplianceDocumentAction, ComplianceRequest, ComplianceRequestDocument, ComplianceRequestKind,
+    ComplianceRequestRepository, ComplianceRequestStatus, ErasureAction, RedactedDocument,
+    SubjectDocument, identifier_patterns,
+};
 pub use configuration::ConfigurationRepository;
 pub use connector_config::ConnectorConfigRepository;
-pub use content_blob::{ContentBlobRepository, OrphanStats};
-pub use document::{DocumentRepository, TitleEntry};
+pub use connector_error::{ConnectorErrorRecord, ConnectorErrorRepository};
+pub use content_blob::{
+    BlobInfo, ContentBlobRepository, GCRun, OrphanStats, ReclaimedStorageStats,
+};
+pub use corpus_stats::{
+    CorpusStatsRepository, SourceLanguageStats, TermDictionaryRun, TermDictionaryUpdate,
+};
+pub use document::{
+    ContentVersion, DocumentRepository, DocumentUpsertOutcome, EmbeddedDocument, TitleEntry,
+};
+pub use document_share_link::{
+    DocumentShareLink, DocumentShareLinkRepository, ShareLinkPolicy, hash_share_token,
+};
+pub use document_version::{DocumentVersion, DocumentVersionRepository};
 pub use embedding::EmbeddingRepository;
+pub use embedding_migration::{
+    EmbeddingMigration, EmbeddingMigrationCutover, EmbeddingMigrationProgress,
+    EmbeddingMigrationRepository, EmbeddingMigrationStatus,
+};
 pub use embedding_provider::EmbeddingProviderRepository;
+pub use ephemeral_document::{EphemeralDocument, EphemeralDocumentRepository};
+pub use freshness::{FreshnessRepository, SourceFreshness};
 pub use group::GroupRepository;
+pub use ingestion_block_rule::{
+    BlockRuleKind, BlockRuleUpdate, IngestionBlockRule, IngestionBlockRuleRepository,
+    NewQuarantinedEvent, QuarantinedEvent,
+};
+pub use integrity::{IntegrityRepository, IntegritySample};
+pub use link_check::{
+    DeadLink, LinkCheckCandidate, LinkCheckRecord, LinkCheckRepository, SourceLinkSummary,
+};
 pub use person::{PersonRepository, PersonSearchResult, PersonUpsert};
-pub use service_credentials::ServiceCredentialsRepo;
+pub use pipeline_trace::{DocumentPipelineTrace, PipelineTraceRepository};
+pub use push_api_key::{PushApiKey, PushApiKeyRepository, hash_push_api_key};
+pub use ranking_profile::{
+    ClickedSearch, NewRankingProfile, RankingProfileRepository, StoredRankingProfile,
+};
+pub use service_credentials::{ExpiringCredential, ServiceCredentialsRepo};
 pub use source::SourceRepository;
-pub use sync_run::SyncRunRepository;
+pub use source_export::{SourceExport, SourceExportRepository, SourceExportStatus};
+pub use source_maintenance::{
+    MaintenanceSearchVisibility, SourceMaintenance, SourceMaintenanceRepository,
+};
+pub use source_migration::{SourceDocumentReassignment, SourceMigrationRepository};
+pub use source_ownership::{
+    OrphanedSource, SourceCoOwner, SourceOwnership, SourceOwnershipRepository,
+};
+pub use source_pii_policy::{
+    CustomPiiPattern, PiiAction, PiiDetector, SourcePiiPolicy, SourcePiiPolicyRepository,
+};
+pub use source_reindex::{
+    SourceReindexJob, SourceReindexProgress, SourceReindexRepository, SourceReindexStatus,
+};
+pub use source_retention::{
+    PurgedBatch, RetentionPurge, RetentionPurgeStatus, RetentionPurgedDocument,
+    SourceRetentionPolicy, SourceRetentionRepository, SourceRetentionSummary,
+};
+pub use source_stats::{SourceDailyStats, SourceStatsRepository};
+pub use storage_snapshot::{SourceStorageSnapshot, StorageSnapshotRepository};
+pub use sync_run::{
+    SourceRateLimitSummary, SourceSyncHealthSummary, SyncRunFilter, SyncRunPeriodStats,
+    SyncRunRepository, SyncRunStatsPeriod,
+};
+pub use sync_state::{SyncStateEntry, SyncStateRepository};
 pub use user::UserRepository;
+pub use vector_index_build::{
+    VectorIndexBuild, VectorIndexBuildProgress, VectorIndexBuildRepository, VectorIndexBuildStatus,
+    VectorIndexMethod,
+};
+pub use workspace::WorkspaceRepository;
@@ -7,6 +7,8 @@ use ulid::Ulid;
 pub struct PersonUpsert {
     pub email: String,
     pub display_name: Option<String>,
+    /// Workspace of the source the person was seen in.
+    pub workspace_id: String,
 }
 
 #[derive(Debug, Clone, sqlx::FromRow)]
@@ -39,7 +41,7 @@ impl PersonRepository {
 
         for person in people {
             let id = Ulid::new().to_string();
-            let result = sqlx::query(
+            let person_id: String = sqlx::query_scalar(
                 r#"
                 INSERT INTO people (id, email, display_name, updated_at)
                 VALUES ($1, $2, $3, NOW())
@@ -52,15 +54,28 @@ impl PersonRepository {
                         ELSE people.display_name
                     END,
                     updated_at = NOW()
+                RETURNING id
                 "#,
             )
             .bind(&id)
             .bind(&person.email)
             .bind(&person.display_name)
+            .fetch_one(&self.pool)
+            .await?;
+
+            sqlx::query(
+                r#"
+                INSERT INTO person_workspaces (person_id, workspace_id)
+                VALUES ($1, $2)
+                ON CONFLICT DO NOTHING
+                "#,
+            )
+            .bind(&person_id)
+            .bind(&person.workspace_id)
             .execute(&self.pool)
             .await?;
 
-            affected += result.rows_affected();
+            affected += 1;
         }
 
         Ok(affected)
@@ -87,9 +102,11 @@ impl PersonRepository {
         Ok(person)
     }
 
+    /// People of `workspace_id` matching `query`, best first.
     pub async fn search_people(
         &self,
         query: &str,
+        workspace_id: &str,
         limit: i64,
     ) -> Result<Vec<PersonSearchResult>, DatabaseError> {
         // Use pdb.parse with lenient mode to search across all indexed fields
@@ -103,12 +120,17 @@ impl PersonRepository {
                 query_string => $1,
                 lenient => true
             )
+            AND EXISTS (
+                SELECT 1 FROM person_workspaces pw
+                WHERE pw.person_id = p.id AND pw.workspace_id = $3
+            )
             ORDER BY score DESC
             LIMIT $2
             "#,
         )
         .bind(query)
         .bind(limit)
+        .bind(workspace_id)
         .fetch_all(&self.pool)
         .await?;
 
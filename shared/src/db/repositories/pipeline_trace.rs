@@ -0,0 +1,308 @@
+use crate::db::error::DatabaseError;
+use serde::Serialize;
+use sqlx::{FromRow, PgPool, types::time::OffsetDateTime};
+
+/// Connector events kept per trace, newest first.
+const MAX_TRACED_EVENTS: i64 = 50;
+
+/// A status a queue item entered, and when. Queues keep only the latest
+/// timestamps of an item, so transitions are reconstructed from them and
+/// earlier retries are not shown individually.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StatusTransition {
+    pub status: String,
+    #[serde(with = "time::serde::iso8601")]
+    pub at: OffsetDateTime,
+}
+
+/// Transitions of a queue item: `pending` at creation, `processing` when last
+/// picked up, and its current status once it finished.
+pub fn status_transitions(
+    status: &str,
+    created_at: OffsetDateTime,
+    processing_started_at: Option<OffsetDateTime>,
+    finished_at: Option<OffsetDateTime>,
+) -> Vec<StatusTransition> {
+    let mut transitions = vec![StatusTransition {
+        status: "pending".to_string(),
+        at: created_at,
+    }];
+    if let Some(at) = processing_started_at {
+        transitions.push(StatusTransition {
+            status: "processing".to_string(),
+            at,
+        });
+    }
+    if !matches!(status, "pending" | "processing") {
+        let at = finished_at.or(processing_started_at).unwrap_or(created_at);
+        transitions.push(StatusTransition {
+            status: status.to_string(),
+            at,
+        });
+    }
+    transitions
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct ConnectorEventTrace {
+    pub id: String,
+    pub sync_run_id: String,
+    pub event_type: String,
+    pub status: String,
+    pub retry_count: Option<i32>,
+    pub error_message: Option<String>,
+    /// Content blob the event pointed at; absent for deletions.
+    pub content_id: Option<String>,
+    #[serde(with = "time::serde::iso8601")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::iso8601::option")]
+    pub processing_started_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::iso8601::option")]
+    pub processed_at: Option<OffsetDateTime>,
+    #[sqlx(skip)]
+    pub transitions: Vec<StatusTransition>,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct ContentBlobTrace {
+    pub id: String,
+    pub content_type: Option<String>,
+    pub size_bytes: i64,
+    pub sha256_hash: Option<String>,
+    pub storage_backend: String,
+    pub ref_count: i32,
+    #[serde(with = "time::serde::iso8601")]
+    pub created_at: OffsetDateTime,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct EmbeddingQueueTrace {
+    pub id: String,
+    pub status: String,
+    pub priority: i16,
+    pub retry_count: i32,
+    pub error_message: Option<String>,
+    pub quarantined_chunks: i32,
+    #[serde(with = "time::serde::iso8601")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::iso8601::option")]
+    pub processing_started_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::iso8601::option")]
+    pub processed_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::iso8601::option")]
+    pub quarantined_at: Option<OffsetDateTime>,
+    #[sqlx(skip)]
+    pub transitions: Vec<StatusTransition>,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct ModelChunkCount {
+    pub model_name: String,
+    pub chunks: i64,
+    #[serde(with = "time::serde::iso8601")]
+    pub first_embedded_at: OffsetDateTime,
+    #[serde(with = "time::serde::iso8601")]
+    pub last_embedded_at: OffsetDateTime,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DocumentPipelineTrace {
+    pub document_id: String,
+    pub source_id: String,
+    pub external_id: String,
+    pub title: String,
+    #[serde(with = "time::serde::iso8601")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::iso8601")]
+    pub updated_at: OffsetDateTime,
+    #[serde(with = "time::serde::iso8601")]
+    pub last_indexed_at: OffsetDateTime,
+    /// Events for this document from its connector, newest first.
+    pub connector_events: Vec<ConnectorEventTrace>,
+    /// The blob holding the document's current content.
+    pub content_blob: Option<ContentBlobTrace>,
+    /// Embedding queue items for the document, newest first.
+    pub embedding_queue: Vec<EmbeddingQueueTrace>,
+    /// Stored chunks per embedding model.
+    pub chunks: Vec<ModelChunkCount>,
+    /// Chunks of the current content skipped after failing repeatedly.
+    pub quarantined_chunks: i64,
+}
+
+#[derive(FromRow)]
+struct DocumentRow {
+    id: String,
+    source_id: String,
+    external_id: String,
+    title: String,
+    content_id: Option<String>,
+    created_at: OffsetDateTime,
+    updated_at: OffsetDateTime,
+    last_indexed_at: OffsetDateTime,
+}
+
+/// Assembles a document's path through the ingestion pipeline from the
+/// connector event queue, content blobs, embedding queue and embeddings.
+pub struct PipelineTraceRepository {
+    pool: PgPool,
+}
+
+impl PipelineTraceRepository {
+    pub fn new(pool: &PgPool) -> Self {
+        Self { pool: pool.clone() }
+    }
+
+    pub async fn trace_document(
+        &self,
+        document_id: &str,
+    ) -> Result<Option<DocumentPipelineTrace>, DatabaseError> {
+        let document = sqlx::query_as::<_, DocumentRow>(
+            r#"
+            SELECT id, source_id, external_id, title, content_id,
+                   created_at, updated_at, last_indexed_at
+            FROM documents
+            WHERE id = $1
+            "#,
+        )
+        .bind(document_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        let Some(document) = document else {
+            return Ok(None);
+        };
+
+        let mut connector_events = sqlx::query_as::<_, ConnectorEventTrace>(
+            r#"
+            SELECT id, sync_run_id, event_type, status, retry_count, error_message,
+                   payload->>'content_id' AS content_id,
+                   created_at, processing_started_at, processed_at
+            FROM connector_events_queue
+            WHERE source_id = $1 AND payload->>'document_id' = $2
+            ORDER BY created_at DESC
+            LIMIT $3
+            "#,
+        )
+        .bind(&document.source_id)
+        .bind(&document.external_id)
+        .bind(MAX_TRACED_EVENTS)
+        .fetch_all(&self.pool)
+        .await?;
+        for event in &mut connector_events {
+            event.transitions = status_transitions(
+                &event.status,
+                event.created_at,
+                event.processing_started_at,
+                event.processed_at,
+            );
+        }
+
+        let content_blob = match &document.content_id {
+            Some(content_id) => {
+                sqlx::query_as::<_, ContentBlobTrace>(
+                    r#"
+                    SELECT id, content_type, size_bytes, sha256_hash, storage_backend,
+                           ref_count, created_at
+                    FROM content_blobs
+                    WHERE id = $1
+                    "#,
+                )
+                .bind(content_id)
+                .fetch_optional(&self.pool)
+                .await?
+            }
+            None => None,
+        };
+
+        let mut embedding_queue = sqlx::query_as::<_, EmbeddingQueueTrace>(
+            r#"
+            SELECT id, status, priority, retry_count, error_message, quarantined_chunks,
+                   created_at, processing_started_at, processed_at, quarantined_at
+            FROM embedding_queue
+            WHERE document_id = $1
+            ORDER BY created_at DESC
+            "#,
+        )
+        .bind(document_id)
+        .fetch_all(&self.pool)
+        .await?;
+        for item in &mut embedding_queue {
+            item.transitions = status_transitions(
+                &item.status,
+                item.created_at,
+                item.processing_started_at,
+                item.processed_at.or(item.quarantined_at),
+            );
+        }
+
+        let chunks = sqlx::query_as::<_, ModelChunkCount>(
+            r#"
+            SELECT model_name, COUNT(*) AS chunks,
+                   MIN(created_at) AS first_embedded_at,
+                   MAX(created_at) AS last_embedded_at
+            FROM embeddings
+            WHERE document_id = $1 AND namespace IS NULL
+            GROUP BY model_name
+            ORDER BY model_name
+            "#,
+        )
+        .bind(document_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let quarantined_chunks = sqlx::query_scalar::<_, i64>(
+            r#"
+            SELECT COUNT(*)
+            FROM embedding_chunk_failures
+            WHERE document_id = $1 AND content_id = $2 AND status = 'quarantined'
+            "#,
+        )
+        .bind(document_id)
+        .bind(&document.content_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(Some(DocumentPipelineTrace {
+            document_id: document.id,
+            source_id: document.source_id,
+            external_id: document.external_id,
+            title: document.title,
+            created_at: document.created_at,
+            updated_at: document.updated_at,
+            last_indexed_at: document.last_indexed_at,
+            connector_events,
+            content_blob,
+            embedding_queue,
+            chunks,
+            quarantined_chunks,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_transitions() {
+        let created = OffsetDateTime::from_unix_timestamp(1_767_261_600).unwrap();
+        let started = created + time::Duration::minutes(1);
+        let finished = created + time::Duration::minutes(2);
+
+        let pending = status_transitions("pending", created, None, None);
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].status, "pending");
+
+        let processing = status_transitions("processing", created, Some(started), None);
+        assert_eq!(processing.last().unwrap().status, "processing");
+        assert_eq!(processing.last().unwrap().at, started);
+
+        let completed = status_transitions("completed", created, Some(started), Some(finished));
+        let statuses: Vec<&str> = completed.iter().map(|t| t.status.as_str()).collect();
+        assert_eq!(statuses, ["pending", "processing", "completed"]);
+        assert_eq!(completed[2].at, finished);
+
+        let dead = status_transitions("dead_letter", created, None, None);
+        assert_eq!(dead[1].status, "dead_letter");
+        assert_eq!(dead[1].at, created);
+    }
+}
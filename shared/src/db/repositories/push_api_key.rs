@@ -0,0 +1,132 @@
+use crate::db::error::DatabaseError;
+use base64::{Engine, engine::general_purpose};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::{FromRow, PgPool, types::time::OffsetDateTime};
+
+const PUSH_KEY_PREFIX: &str = "omni_push_";
+/// Characters of the plaintext key kept for display, e.g. `omni_push_AbC1`.
+const DISPLAY_PREFIX_LEN: usize = 14;
+
+/// An API key external systems use to push documents into a push source.
+/// Only the SHA-256 of the key is stored; the plaintext is returned once, at
+/// creation.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct PushApiKey {
+    pub id: String,
+    pub source_id: String,
+    pub key_prefix: String,
+    pub name: String,
+    pub created_by: Option<String>,
+    #[serde(with = "time::serde::iso8601::option")]
+    pub last_used_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::iso8601::option")]
+    pub revoked_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::iso8601")]
+    pub created_at: OffsetDateTime,
+}
+
+const KEY_COLUMNS: &str =
+    "id, source_id, key_prefix, name, created_by, last_used_at, revoked_at, created_at";
+
+pub fn hash_push_api_key(key: &str) -> String {
+    format!("{:x}", Sha256::digest(key.as_bytes()))
+}
+
+fn generate_push_api_key() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    format!(
+        "{}{}",
+        PUSH_KEY_PREFIX,
+        general_purpose::URL_SAFE_NO_PAD.encode(bytes)
+    )
+}
+
+pub struct PushApiKeyRepository {
+    pool: PgPool,
+}
+
+impl PushApiKeyRepository {
+    pub fn new(pool: &PgPool) -> Self {
+        Self { pool: pool.clone() }
+    }
+
+    /// Create a key for a source. Returns the stored key and its plaintext,
+    /// which cannot be recovered later.
+    pub async fn create(
+        &self,
+        source_id: &str,
+        name: &str,
+        created_by: Option<&str>,
+    ) -> Result<(PushApiKey, String), DatabaseError> {
+        let key = generate_push_api_key();
+        let query = format!(
+            "INSERT INTO push_api_keys (id, source_id, key_hash, key_prefix, name, created_by) \
+             VALUES ($1, $2, $3, $4, $5, $6) RETURNING {KEY_COLUMNS}"
+        );
+        let api_key = sqlx::query_as::<_, PushApiKey>(&query)
+            .bind(crate::utils::generate_ulid())
+            .bind(source_id)
+            .bind(hash_push_api_key(&key))
+            .bind(&key[..DISPLAY_PREFIX_LEN])
+            .bind(name)
+            .bind(created_by)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok((api_key, key))
+    }
+
+    /// Keys of a source, newest first, including revoked ones.
+    pub async fn list_for_source(&self, source_id: &str) -> Result<Vec<PushApiKey>, DatabaseError> {
+        let query = format!(
+            "SELECT {KEY_COLUMNS} FROM push_api_keys WHERE source_id = $1 ORDER BY created_at DESC"
+        );
+        let keys = sqlx::query_as::<_, PushApiKey>(&query)
+            .bind(source_id)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(keys)
+    }
+
+    /// Revoke a key. Returns false if the source has no such active key.
+    pub async fn revoke(&self, source_id: &str, key_id: &str) -> Result<bool, DatabaseError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE push_api_keys SET revoked_at = NOW()
+            WHERE id = $1 AND source_id = $2 AND revoked_at IS NULL
+            "#,
+        )
+        .bind(key_id)
+        .bind(source_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Look up the active key matching a plaintext key and record its use.
+    /// Keys of inactive or deleted sources don't authenticate.
+    pub async fn authenticate(&self, key: &str) -> Result<Option<PushApiKey>, DatabaseError> {
+        let api_key = sqlx::query_as::<_, PushApiKey>(
+            r#"
+            UPDATE push_api_keys k SET last_used_at = NOW()
+            FROM sources s
+            WHERE k.key_hash = $1
+              AND k.revoked_at IS NULL
+              AND s.id = k.source_id
+              AND s.is_active AND NOT s.is_deleted
+            RETURNING k.id, k.source_id, k.key_prefix, k.name, k.created_by,
+                      k.last_used_at, k.revoked_at, k.created_at
+            "#,
+        )
+        .bind(hash_push_api_key(key))
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(api_key)
+    }
+}
@@ -0,0 +1,142 @@
+use crate::db::error::DatabaseError;
+use crate::ranking::{RankingImpression, RankingProfile};
+use serde::Serialize;
+use sqlx::types::Json;
+use sqlx::{FromRow, PgPool, types::time::OffsetDateTime};
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct StoredRankingProfile {
+    pub id: String,
+    pub weights: Json<RankingProfile>,
+    pub training_searches: i32,
+    pub training_pairs: i32,
+    pub baseline_accuracy: Option<f64>,
+    pub accuracy: Option<f64>,
+    pub is_active: bool,
+    #[serde(with = "time::serde::iso8601")]
+    pub created_at: OffsetDateTime,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewRankingProfile {
+    pub weights: RankingProfile,
+    pub training_searches: i32,
+    pub training_pairs: i32,
+    pub baseline_accuracy: Option<f64>,
+    pub accuracy: Option<f64>,
+}
+
+/// A logged search with at least one result opened.
+#[derive(Debug, Clone, FromRow)]
+pub struct ClickedSearch {
+    pub impressions: Json<Vec<RankingImpression>>,
+    pub clicked_document_ids: Vec<String>,
+}
+
+pub struct RankingProfileRepository {
+    pool: PgPool,
+}
+
+impl RankingProfileRepository {
+    pub fn new(pool: &PgPool) -> Self {
+        Self { pool: pool.clone() }
+    }
+
+    pub async fn active(&self) -> Result<Option<StoredRankingProfile>, DatabaseError> {
+        let profile = sqlx::query_as::<_, StoredRankingProfile>(
+            "SELECT * FROM ranking_profiles WHERE is_active",
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(profile)
+    }
+
+    /// Store a trained profile, replacing the active one when `activate` is
+    /// set.
+    pub async fn create(
+        &self,
+        profile: &NewRankingProfile,
+        activate: bool,
+    ) -> Result<StoredRankingProfile, DatabaseError> {
+        let mut tx = self.pool.begin().await?;
+
+        if activate {
+            sqlx::query("UPDATE ranking_profiles SET is_active = FALSE WHERE is_active")
+                .execute(&mut *tx)
+                .await?;
+        }
+        let stored = sqlx::query_as::<_, StoredRankingProfile>(
+            r#"
+            INSERT INTO ranking_profiles
+                (id, weights, training_searches, training_pairs, baseline_accuracy, accuracy,
+                 is_active)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING *
+            "#,
+        )
+        .bind(ulid::Ulid::new().to_string())
+        .bind(Json(&profile.weights))
+        .bind(profile.training_searches)
+        .bind(profile.training_pairs)
+        .bind(profile.baseline_accuracy)
+        .bind(profile.accuracy)
+        .bind(activate)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(stored)
+    }
+
+    /// Typed searches since `since` that logged their results' ranking
+    /// features and had at least one of them opened, oldest first.
+    pub async fn clicked_searches(
+        &self,
+        since: OffsetDateTime,
+    ) -> Result<Vec<ClickedSearch>, DatabaseError> {
+        let searches = sqlx::query_as::<_, ClickedSearch>(
+            r#"
+            SELECT e.impressions,
+                   ARRAY_AGG(DISTINCT c.document_id) AS clicked_document_ids
+            FROM search_events e
+            JOIN search_clicks c ON c.search_event_id = e.id
+            WHERE e.created_at >= $1
+              AND e.impressions IS NOT NULL
+              AND NOT e.is_generated
+            GROUP BY e.id
+            ORDER BY e.created_at
+            "#,
+        )
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(searches)
+    }
+
+    /// Clicks per document since `since`, for the `limit` most opened
+    /// documents.
+    pub async fn document_clicks(
+        &self,
+        since: OffsetDateTime,
+        limit: i64,
+    ) -> Result<Vec<(String, i64)>, DatabaseError> {
+        let rows = sqlx::query_as::<_, (String, i64)>(
+            r#"
+            SELECT document_id, COUNT(*)
+            FROM search_clicks
+            WHERE created_at >= $1
+            GROUP BY document_id
+            ORDER BY COUNT(*) DESC
+            LIMIT $2
+            "#,
+        )
+        .bind(since)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+}
@@ -1,10 +1,20 @@
 use anyhow::Result;
 use serde_json::Value as JsonValue;
-use sqlx::PgPool;
+use sqlx::{FromRow, PgPool, types::time::OffsetDateTime};
 
 use crate::encryption::{EncryptedData, EncryptionService};
 use crate::models::{ServiceCredential, Source, SourceScope};
 
+/// An OAuth credential whose access token expires soon.
+#[derive(Debug, Clone, FromRow)]
+pub struct ExpiringCredential {
+    #[sqlx(flatten)]
+    pub credential: ServiceCredential,
+    /// Whether syncs of the source use this credential (see
+    /// `find_owner_credential`), rather than a single user's tool calls.
+    pub owns_source: bool,
+}
+
 /// Service credentials repository with encryption support.
 pub struct ServiceCredentialsRepo {
     pool: PgPool,
@@ -155,13 +165,15 @@ impl ServiceCredentialsRepo {
     }
 
     /// Update credentials and refresh-related fields on a credential row.
+    /// Writing new tokens clears any earlier refresh failure.
     pub async fn update_credentials(&self, creds: &ServiceCredential) -> Result<()> {
         let encrypted_credentials = self.encrypt_credentials(creds)?;
 
         sqlx::query(
             r#"
             UPDATE service_credentials
-            SET credentials = $2, config = $3, expires_at = $4, updated_at = CURRENT_TIMESTAMP
+            SET credentials = $2, config = $3, expires_at = $4, updated_at = CURRENT_TIMESTAMP,
+                refresh_failed_at = NULL, refresh_error = NULL
             WHERE id = $1
             "#,
         )
@@ -175,6 +187,69 @@ impl ServiceCredentialsRepo {
         Ok(())
     }
 
+    /// OAuth credentials of live sources whose access token expires before
+    /// `before`. Skips credentials that already failed to refresh and haven't
+    /// been re-authorized since, and sources with a running sync, whose
+    /// connector may be refreshing the same token.
+    pub async fn find_oauth_expiring_before(
+        &self,
+        before: OffsetDateTime,
+    ) -> Result<Vec<ExpiringCredential>> {
+        let mut expiring = sqlx::query_as::<_, ExpiringCredential>(
+            r#"
+            SELECT sc.*,
+                   (sc.user_id IS NULL OR (s.scope = 'user' AND sc.user_id = s.created_by))
+                       AS owns_source
+            FROM service_credentials sc
+            JOIN sources s ON s.id = sc.source_id AND NOT s.is_deleted
+            WHERE sc.auth_type = 'oauth'
+              AND sc.expires_at IS NOT NULL
+              AND sc.expires_at < $1
+              AND (sc.refresh_failed_at IS NULL OR sc.refresh_failed_at < sc.updated_at)
+              AND NOT EXISTS (
+                  SELECT 1 FROM sync_runs sr
+                  WHERE sr.source_id = sc.source_id AND sr.status = 'running'
+              )
+            ORDER BY sc.expires_at
+            "#,
+        )
+        .bind(before)
+        .fetch_all(&self.pool)
+        .await?;
+
+        for entry in &mut expiring {
+            self.decrypt_credentials_in_place(&mut entry.credential)?;
+        }
+
+        Ok(expiring)
+    }
+
+    /// Record that a credential's token can't be refreshed until it is
+    /// re-authorized. Only applies while the row is as it was at `updated_at`,
+    /// so a failure doesn't flag tokens rotated in the meantime; returns
+    /// whether it did.
+    pub async fn mark_refresh_failed(
+        &self,
+        id: &str,
+        updated_at: OffsetDateTime,
+        error: &str,
+    ) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE service_credentials
+            SET refresh_failed_at = CURRENT_TIMESTAMP, refresh_error = $3
+            WHERE id = $1 AND updated_at = $2
+            "#,
+        )
+        .bind(id)
+        .bind(updated_at)
+        .bind(error)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
     /// Find any per-user OAuth credential for sources matching the given
     /// source types and provider. Used for recovering a missing MCP catalog
     /// when a connector registers with `mcp_catalog_loaded: false`.
@@ -1,4 +1,8 @@
-use crate::{db::error::DatabaseError, models::Source, traits::Repository};
+use crate::{
+    db::error::DatabaseError,
+    models::{DEFAULT_WORKSPACE_ID, Source},
+    traits::Repository,
+};
 use async_trait::async_trait;
 use sqlx::PgPool;
 use std::collections::HashMap;
@@ -18,7 +22,8 @@ impl SourceRepository {
             r#"
             SELECT id, name, source_type, config, is_active, is_deleted, scope,
                    user_filter_mode, user_whitelist, user_blacklist,
-                   connector_state, checkpoint, sync_interval_seconds, created_at, updated_at, created_by
+                   connector_state, checkpoint, sync_interval_seconds, created_at, updated_at, created_by,
+                   workspace_id
             FROM sources
             WHERE source_type = $1 AND is_deleted = false
             ORDER BY created_at DESC
@@ -31,12 +36,58 @@ impl SourceRepository {
         Ok(sources)
     }
 
+    /// Sources whose name contains `query` (case-insensitive) and that the
+    /// user can see: org-scoped sources of the user's workspace plus the
+    /// personal ones the user owns or co-owns.
+    pub async fn search_visible_sources(
+        &self,
+        query: &str,
+        user_id: &str,
+        limit: i64,
+    ) -> Result<Vec<Source>, DatabaseError> {
+        let pattern = format!(
+            "%{}%",
+            query
+                .replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_")
+        );
+        let sources = sqlx::query_as::<_, Source>(
+            r#"
+            SELECT id, name, source_type, config, is_active, is_deleted, scope,
+                   user_filter_mode, user_whitelist, user_blacklist,
+                   NULL::jsonb AS connector_state, NULL::jsonb AS checkpoint,
+                   sync_interval_seconds, created_at, updated_at, created_by,
+                   workspace_id
+            FROM sources
+            WHERE is_deleted = false
+              AND name ILIKE $1
+              AND workspace_id = COALESCE((SELECT workspace_id FROM users WHERE id = $2), $5)
+              AND (scope = 'org' OR created_by = $2
+                   OR EXISTS (SELECT 1 FROM source_co_owners co
+                              WHERE co.source_id = sources.id AND co.user_id = $2))
+            ORDER BY starts_with(lower(name), lower($3)) DESC, name
+            LIMIT $4
+            "#,
+        )
+        .bind(pattern)
+        .bind(user_id)
+        .bind(query)
+        .bind(limit)
+        .bind(DEFAULT_WORKSPACE_ID)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(sources)
+    }
+
     pub async fn find_all_sources(&self) -> Result<Vec<Source>, DatabaseError> {
         let sources = sqlx::query_as::<_, Source>(
             r#"
             SELECT id, name, source_type, config, is_active, is_deleted, scope,
                    user_filter_mode, user_whitelist, user_blacklist,
-                   connector_state, checkpoint, sync_interval_seconds, created_at, updated_at, created_by
+                   connector_state, checkpoint, sync_interval_seconds, created_at, updated_at, created_by,
+                   workspace_id
             FROM sources
             WHERE is_deleted = false
             ORDER BY created_at DESC
@@ -57,7 +108,8 @@ impl SourceRepository {
             SELECT id, name, source_type, config, is_active, is_deleted, scope,
                    user_filter_mode, user_whitelist, user_blacklist,
                    NULL::jsonb AS connector_state, NULL::jsonb AS checkpoint,
-                   sync_interval_seconds, created_at, updated_at, created_by
+                   sync_interval_seconds, created_at, updated_at, created_by,
+                   workspace_id
             FROM sources
             WHERE is_deleted = false
             ORDER BY created_at DESC
@@ -74,7 +126,8 @@ impl SourceRepository {
             r#"
             SELECT id, name, source_type, config, is_active, is_deleted, scope,
                    user_filter_mode, user_whitelist, user_blacklist,
-                   connector_state, checkpoint, sync_interval_seconds, created_at, updated_at, created_by
+                   connector_state, checkpoint, sync_interval_seconds, created_at, updated_at, created_by,
+                   workspace_id
             FROM sources
             WHERE is_active = true AND is_deleted = false
             ORDER BY created_at DESC
@@ -91,7 +144,8 @@ impl SourceRepository {
             r#"
             SELECT id, name, source_type, config, is_active, is_deleted, scope,
                    user_filter_mode, user_whitelist, user_blacklist,
-                   connector_state, checkpoint, sync_interval_seconds, created_at, updated_at, created_by
+                   connector_state, checkpoint, sync_interval_seconds, created_at, updated_at, created_by,
+                   workspace_id
             FROM sources
             WHERE is_active = false OR is_deleted = true
             ORDER BY created_at DESC
@@ -135,7 +189,8 @@ impl SourceRepository {
             r#"
             SELECT id, name, source_type, config, is_active, is_deleted, scope,
                    user_filter_mode, user_whitelist, user_blacklist,
-                   connector_state, checkpoint, sync_interval_seconds, created_at, updated_at, created_by
+                   connector_state, checkpoint, sync_interval_seconds, created_at, updated_at, created_by,
+                   workspace_id
             FROM sources
             WHERE is_active = true AND is_deleted = false
             "#,
@@ -207,6 +262,24 @@ impl SourceRepository {
 
         Ok(rows.into_iter().collect())
     }
+
+    /// The `locale` set in each source's config, for sources that have one.
+    pub async fn fetch_source_locale_map(
+        &self,
+        source_ids: &[String],
+    ) -> Result<HashMap<String, String>, DatabaseError> {
+        if source_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let rows: Vec<(String, String)> = sqlx::query_as(
+            "SELECT id, config->>'locale' FROM sources WHERE id = ANY($1) AND config->>'locale' IS NOT NULL",
+        )
+        .bind(source_ids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().collect())
+    }
 }
 
 #[async_trait]
@@ -216,7 +289,8 @@ impl Repository<Source, String> for SourceRepository {
             r#"
             SELECT id, name, source_type, config, is_active, is_deleted, scope,
                    user_filter_mode, user_whitelist, user_blacklist,
-                   connector_state, checkpoint, sync_interval_seconds, created_at, updated_at, created_by
+                   connector_state, checkpoint, sync_interval_seconds, created_at, updated_at, created_by,
+                   workspace_id
             FROM sources
             WHERE id = $1
             "#,
@@ -233,7 +307,8 @@ impl Repository<Source, String> for SourceRepository {
             r#"
             SELECT id, name, source_type, config, is_active, is_deleted, scope,
                    user_filter_mode, user_whitelist, user_blacklist,
-                   connector_state, checkpoint, sync_interval_seconds, created_at, updated_at, created_by
+                   connector_state, checkpoint, sync_interval_seconds, created_at, updated_at, created_by,
+                   workspace_id
             FROM sources
             WHERE is_deleted = false
             ORDER BY created_at DESC
@@ -251,11 +326,12 @@ impl Repository<Source, String> for SourceRepository {
     async fn create(&self, source: Source) -> Result<Source, DatabaseError> {
         let created_source = sqlx::query_as::<_, Source>(
             r#"
-            INSERT INTO sources (id, name, source_type, config, is_active, created_by)
-            VALUES ($1, $2, $3, $4, $5, $6)
+            INSERT INTO sources (id, name, source_type, config, is_active, created_by, workspace_id)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
             RETURNING id, name, source_type, config, is_active, is_deleted, scope,
                       user_filter_mode, user_whitelist, user_blacklist,
-                      connector_state, checkpoint, sync_interval_seconds, created_at, updated_at, created_by
+                      connector_state, checkpoint, sync_interval_seconds, created_at, updated_at, created_by,
+                      workspace_id
             "#,
         )
         .bind(&source.id)
@@ -264,6 +340,7 @@ impl Repository<Source, String> for SourceRepository {
         .bind(&source.config)
         .bind(source.is_active)
         .bind(&source.created_by)
+        .bind(&source.workspace_id)
         .fetch_one(&self.pool)
         .await
         .map_err(|e| match e {
@@ -284,7 +361,8 @@ impl Repository<Source, String> for SourceRepository {
             WHERE id = $1
             RETURNING id, name, source_type, config, is_active, is_deleted, scope,
                       user_filter_mode, user_whitelist, user_blacklist,
-                      connector_state, checkpoint, sync_interval_seconds, created_at, updated_at, created_by
+                      connector_state, checkpoint, sync_interval_seconds, created_at, updated_at, created_by,
+                      workspace_id
             "#,
         )
         .bind(&id)
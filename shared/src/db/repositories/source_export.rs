@@ -0,0 +1,284 @@
+use crate::db::error::DatabaseError;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool, types::time::OffsetDateTime};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+pub enum SourceExportStatus {
+    Pending,
+    Running,
+    Completed,
+    Failed,
+    /// Completed, but the download link lapsed and the archive was deleted.
+    Expired,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct SourceExport {
+    pub id: String,
+    pub source_id: String,
+    pub requested_by: Option<String>,
+    pub status: SourceExportStatus,
+    pub document_count: i32,
+    pub size_bytes: i64,
+    #[serde(skip)]
+    pub part_content_ids: Vec<String>,
+    #[serde(skip)]
+    pub download_token: Option<String>,
+    #[serde(with = "time::serde::iso8601::option")]
+    pub expires_at: Option<OffsetDateTime>,
+    pub error_message: Option<String>,
+    #[serde(with = "time::serde::iso8601")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::iso8601::option")]
+    pub started_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::iso8601::option")]
+    pub completed_at: Option<OffsetDateTime>,
+}
+
+impl SourceExport {
+    /// Whether the archive can currently be downloaded.
+    pub fn is_downloadable(&self, now: OffsetDateTime) -> bool {
+        self.status == SourceExportStatus::Completed && self.expires_at.is_some_and(|e| e > now)
+    }
+}
+
+const EXPORT_COLUMNS: &str = r#"
+    id, source_id, requested_by, status, document_count, size_bytes, part_content_ids,
+    download_token, expires_at, error_message, created_at, started_at, completed_at
+"#;
+
+pub struct SourceExportRepository {
+    pool: PgPool,
+}
+
+impl SourceExportRepository {
+    pub fn new(pool: &PgPool) -> Self {
+        Self { pool: pool.clone() }
+    }
+
+    pub async fn create(
+        &self,
+        source_id: &str,
+        requested_by: Option<&str>,
+    ) -> Result<SourceExport, DatabaseError> {
+        let query = format!(
+            "INSERT INTO source_exports (id, source_id, requested_by) VALUES ($1, $2, $3) \
+             RETURNING {EXPORT_COLUMNS}"
+        );
+        let export = sqlx::query_as::<_, SourceExport>(&query)
+            .bind(crate::utils::generate_ulid())
+            .bind(source_id)
+            .bind(requested_by)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(export)
+    }
+
+    pub async fn get(&self, id: &str) -> Result<Option<SourceExport>, DatabaseError> {
+        let query = format!("SELECT {EXPORT_COLUMNS} FROM source_exports WHERE id = $1");
+        let export = sqlx::query_as::<_, SourceExport>(&query)
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(export)
+    }
+
+    /// Most recent exports of a source first.
+    pub async fn list_for_source(
+        &self,
+        source_id: &str,
+        limit: i64,
+    ) -> Result<Vec<SourceExport>, DatabaseError> {
+        let query = format!(
+            "SELECT {EXPORT_COLUMNS} FROM source_exports WHERE source_id = $1 \
+             ORDER BY created_at DESC LIMIT $2"
+        );
+        let exports = sqlx::query_as::<_, SourceExport>(&query)
+            .bind(source_id)
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(exports)
+    }
+
+    /// Whether the source already has an export queued or in progress.
+    pub async fn has_active_export(&self, source_id: &str) -> Result<bool, DatabaseError> {
+        let active: bool = sqlx::query_scalar(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM source_exports
+                WHERE source_id = $1 AND status IN ('pending', 'running')
+            )
+            "#,
+        )
+        .bind(source_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(active)
+    }
+
+    pub async fn count_running(&self) -> Result<i64, DatabaseError> {
+        let count: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM source_exports WHERE status = 'running'")
+                .fetch_one(&self.pool)
+                .await?;
+
+        Ok(count)
+    }
+
+    /// Move up to `limit` pending exports, oldest first, to `running`.
+    pub async fn claim_pending(&self, limit: i64) -> Result<Vec<SourceExport>, DatabaseError> {
+        let query = format!(
+            r#"
+            UPDATE source_exports
+            SET status = 'running', started_at = NOW(), heartbeat_at = NOW()
+            WHERE id IN (
+                SELECT id FROM source_exports
+                WHERE status = 'pending'
+                ORDER BY created_at
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING {EXPORT_COLUMNS}
+            "#
+        );
+        let exports = sqlx::query_as::<_, SourceExport>(&query)
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(exports)
+    }
+
+    /// Record progress of a running export and refresh its heartbeat.
+    pub async fn record_progress(
+        &self,
+        id: &str,
+        document_count: i32,
+        size_bytes: i64,
+        part_content_ids: &[String],
+    ) -> Result<(), DatabaseError> {
+        sqlx::query(
+            r#"
+            UPDATE source_exports
+            SET document_count = $2, size_bytes = $3, part_content_ids = $4, heartbeat_at = NOW()
+            WHERE id = $1 AND status = 'running'
+            "#,
+        )
+        .bind(id)
+        .bind(document_count)
+        .bind(size_bytes)
+        .bind(part_content_ids)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn complete(
+        &self,
+        id: &str,
+        document_count: i32,
+        size_bytes: i64,
+        part_content_ids: &[String],
+        download_token: &str,
+        expires_at: OffsetDateTime,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query(
+            r#"
+            UPDATE source_exports
+            SET status = 'completed', document_count = $2, size_bytes = $3,
+                part_content_ids = $4, download_token = $5, expires_at = $6,
+                heartbeat_at = NOW(), completed_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(document_count)
+        .bind(size_bytes)
+        .bind(part_content_ids)
+        .bind(download_token)
+        .bind(expires_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Mark an export failed. The caller deletes any parts it had written.
+    pub async fn fail(&self, id: &str, error_message: &str) -> Result<(), DatabaseError> {
+        sqlx::query(
+            r#"
+            UPDATE source_exports
+            SET status = 'failed', error_message = $2, part_content_ids = '{}',
+                completed_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(error_message)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Fail running exports whose heartbeat is older than `stale_after_seconds`,
+    /// e.g. because the process running them restarted. Returns the parts they
+    /// had written.
+    pub async fn fail_orphaned(
+        &self,
+        stale_after_seconds: i64,
+    ) -> Result<Vec<String>, DatabaseError> {
+        let parts: Vec<Vec<String>> = sqlx::query_scalar(
+            r#"
+            WITH orphaned AS (
+                SELECT id, part_content_ids FROM source_exports
+                WHERE status = 'running'
+                  AND heartbeat_at < NOW() - make_interval(secs => $1)
+                FOR UPDATE
+            )
+            UPDATE source_exports e
+            SET status = 'failed', error_message = 'Export stopped unexpectedly',
+                part_content_ids = '{}', completed_at = NOW()
+            FROM orphaned o
+            WHERE e.id = o.id
+            RETURNING o.part_content_ids
+            "#,
+        )
+        .bind(stale_after_seconds as f64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(parts.into_iter().flatten().collect())
+    }
+
+    /// Expire completed exports whose download link has lapsed. Returns the
+    /// parts to delete from storage.
+    pub async fn expire_completed(&self) -> Result<Vec<String>, DatabaseError> {
+        let parts: Vec<Vec<String>> = sqlx::query_scalar(
+            r#"
+            WITH lapsed AS (
+                SELECT id, part_content_ids FROM source_exports
+                WHERE status = 'completed' AND expires_at <= NOW()
+                FOR UPDATE
+            )
+            UPDATE source_exports e
+            SET status = 'expired', part_content_ids = '{}', download_token = NULL
+            FROM lapsed l
+            WHERE e.id = l.id
+            RETURNING l.part_content_ids
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(parts.into_iter().flatten().collect())
+    }
+}
@@ -0,0 +1,126 @@
+use crate::db::error::DatabaseError;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool, types::time::OffsetDateTime};
+
+/// How search treats documents of a source in maintenance.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+pub enum MaintenanceSearchVisibility {
+    /// Excluded from search results.
+    Hidden,
+    /// Returned, but labelled as possibly stale.
+    #[default]
+    Stale,
+}
+
+/// A source an admin has taken offline. Scheduled syncs are skipped and manual
+/// triggers rejected until maintenance ends.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct SourceMaintenance {
+    pub source_id: String,
+    pub search_visibility: MaintenanceSearchVisibility,
+    pub reason: Option<String>,
+    pub started_by: Option<String>,
+    #[serde(with = "time::serde::iso8601")]
+    pub started_at: OffsetDateTime,
+}
+
+pub struct SourceMaintenanceRepository {
+    pool: PgPool,
+}
+
+impl SourceMaintenanceRepository {
+    pub fn new(pool: &PgPool) -> Self {
+        Self { pool: pool.clone() }
+    }
+
+    pub async fn get(&self, source_id: &str) -> Result<Option<SourceMaintenance>, DatabaseError> {
+        let maintenance = sqlx::query_as::<_, SourceMaintenance>(
+            r#"
+            SELECT source_id, search_visibility, reason, started_by, started_at
+            FROM source_maintenance
+            WHERE source_id = $1
+            "#,
+        )
+        .bind(source_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(maintenance)
+    }
+
+    pub async fn list(&self) -> Result<Vec<SourceMaintenance>, DatabaseError> {
+        let maintenance = sqlx::query_as::<_, SourceMaintenance>(
+            r#"
+            SELECT source_id, search_visibility, reason, started_by, started_at
+            FROM source_maintenance
+            ORDER BY started_at DESC
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(maintenance)
+    }
+
+    /// Put a source into maintenance, or update the settings of an ongoing
+    /// maintenance window. `started_at` is kept when already in maintenance.
+    pub async fn start(
+        &self,
+        source_id: &str,
+        search_visibility: MaintenanceSearchVisibility,
+        reason: Option<&str>,
+        started_by: Option<&str>,
+    ) -> Result<SourceMaintenance, DatabaseError> {
+        let maintenance = sqlx::query_as::<_, SourceMaintenance>(
+            r#"
+            INSERT INTO source_maintenance (source_id, search_visibility, reason, started_by)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (source_id) DO UPDATE
+            SET search_visibility = EXCLUDED.search_visibility,
+                reason = EXCLUDED.reason,
+                started_by = EXCLUDED.started_by
+            RETURNING source_id, search_visibility, reason, started_by, started_at
+            "#,
+        )
+        .bind(source_id)
+        .bind(search_visibility)
+        .bind(reason)
+        .bind(started_by)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(maintenance)
+    }
+
+    /// End maintenance. Returns false if the source was not in maintenance.
+    pub async fn end(&self, source_id: &str) -> Result<bool, DatabaseError> {
+        let result = sqlx::query("DELETE FROM source_maintenance WHERE source_id = $1")
+            .bind(source_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Ids of sources in maintenance whose documents should be labelled as
+    /// possibly stale, restricted to `source_ids`.
+    pub async fn find_stale_source_ids(
+        &self,
+        source_ids: &[String],
+    ) -> Result<Vec<String>, DatabaseError> {
+        let ids = sqlx::query_scalar(
+            r#"
+            SELECT source_id
+            FROM source_maintenance
+            WHERE search_visibility = 'stale' AND source_id = ANY($1)
+            "#,
+        )
+        .bind(source_ids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(ids)
+    }
+}
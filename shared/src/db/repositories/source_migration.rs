@@ -0,0 +1,170 @@
+use crate::db::error::DatabaseError;
+use crate::models::SourceType;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+
+/// Outcome of moving one source's documents to another source.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceDocumentReassignment {
+    pub from_source_id: String,
+    pub to_source_id: String,
+    pub moved_documents: u64,
+    /// Documents left on the old source because the new one already has a
+    /// document with the same external id.
+    pub skipped_documents: u64,
+    /// Whether the connector state, checkpoint and sync state keys were
+    /// carried over.
+    pub sync_state_moved: bool,
+    pub moved_sync_state_keys: u64,
+}
+
+pub struct SourceMigrationRepository {
+    pool: PgPool,
+}
+
+impl SourceMigrationRepository {
+    pub fn new(pool: &PgPool) -> Self {
+        Self { pool: pool.clone() }
+    }
+
+    /// Move the documents of `from_source_id` to `to_source_id`, so a
+    /// recreated source picks up where the old one left off instead of
+    /// indexing everything again. Embeddings, versions and other rows keyed
+    /// by document follow their documents. With `include_sync_state`, the
+    /// old source's connector state, checkpoint and sync state keys replace
+    /// the new source's, so its next sync is incremental.
+    ///
+    /// Both sources must be of the same type and neither may be syncing. The
+    /// old source may already be deleted, as long as cleanup has not removed
+    /// it yet.
+    pub async fn reassign_documents(
+        &self,
+        from_source_id: &str,
+        to_source_id: &str,
+        include_sync_state: bool,
+    ) -> Result<SourceDocumentReassignment, DatabaseError> {
+        if from_source_id == to_source_id {
+            return Err(DatabaseError::InvalidInput(
+                "Cannot reassign documents of a source to itself".to_string(),
+            ));
+        }
+
+        let mut tx = self.pool.begin().await?;
+
+        // Locking in id order keeps two opposite reassignments from deadlocking
+        let sources: Vec<(String, SourceType, bool)> = sqlx::query_as(
+            "SELECT id, source_type, is_deleted FROM sources WHERE id = ANY($1) ORDER BY id FOR UPDATE",
+        )
+        .bind([from_source_id, to_source_id])
+        .fetch_all(&mut *tx)
+        .await?;
+        let find = |id: &str| sources.iter().find(|(source_id, _, _)| source_id == id);
+        let (Some((_, from_type, _)), Some((_, to_type, to_deleted))) =
+            (find(from_source_id), find(to_source_id))
+        else {
+            return Err(DatabaseError::NotFound);
+        };
+        if *to_deleted {
+            return Err(DatabaseError::InvalidInput(format!(
+                "Target source {} is deleted",
+                to_source_id
+            )));
+        }
+        if from_type != to_type {
+            return Err(DatabaseError::InvalidInput(format!(
+                "Connector types differ: source {} is {:?}, source {} is {:?}",
+                from_source_id, from_type, to_source_id, to_type
+            )));
+        }
+
+        let syncing: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM sync_runs WHERE source_id = ANY($1) AND status = 'running')",
+        )
+        .bind([from_source_id, to_source_id])
+        .fetch_one(&mut *tx)
+        .await?;
+        if syncing {
+            return Err(DatabaseError::ConstraintViolation(format!(
+                "A sync is running for source {} or {}",
+                from_source_id, to_source_id
+            )));
+        }
+
+        let moved_documents = sqlx::query(
+            r#"
+            UPDATE documents d
+            SET source_id = $2
+            WHERE d.source_id = $1
+              AND NOT EXISTS (
+                  SELECT 1 FROM documents t
+                  WHERE t.source_id = $2 AND t.external_id = d.external_id
+              )
+            "#,
+        )
+        .bind(from_source_id)
+        .bind(to_source_id)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+        let skipped_documents: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM documents WHERE source_id = $1")
+                .bind(from_source_id)
+                .fetch_one(&mut *tx)
+                .await?;
+
+        // Per-document tables that also carry the source for its reports
+        for table in ["document_freshness", "document_link_checks"] {
+            sqlx::query(&format!(
+                r#"
+                UPDATE {table} r
+                SET source_id = d.source_id
+                FROM documents d
+                WHERE r.document_id = d.id AND r.source_id = $1 AND d.source_id = $2
+                "#
+            ))
+            .bind(from_source_id)
+            .bind(to_source_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        let mut moved_sync_state_keys = 0;
+        if include_sync_state {
+            sqlx::query(
+                r#"
+                UPDATE sources t
+                SET connector_state = s.connector_state, checkpoint = s.checkpoint, updated_at = NOW()
+                FROM sources s
+                WHERE t.id = $2 AND s.id = $1
+                "#,
+            )
+            .bind(from_source_id)
+            .bind(to_source_id)
+            .execute(&mut *tx)
+            .await?;
+            sqlx::query("DELETE FROM source_sync_state WHERE source_id = $1")
+                .bind(to_source_id)
+                .execute(&mut *tx)
+                .await?;
+            moved_sync_state_keys = sqlx::query(
+                "UPDATE source_sync_state SET source_id = $2, updated_at = NOW() WHERE source_id = $1",
+            )
+            .bind(from_source_id)
+            .bind(to_source_id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        }
+
+        tx.commit().await?;
+
+        Ok(SourceDocumentReassignment {
+            from_source_id: from_source_id.to_string(),
+            to_source_id: to_source_id.to_string(),
+            moved_documents,
+            skipped_documents: skipped_documents as u64,
+            sync_state_moved: include_sync_state,
+            moved_sync_state_keys,
+        })
+    }
+}
@@ -0,0 +1,284 @@
+use crate::db::error::DatabaseError;
+use crate::models::{SourceScope, SourceType};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool, types::time::OffsetDateTime};
+
+/// A user who shares ownership of a source with its owner (`created_by`).
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct SourceCoOwner {
+    pub source_id: String,
+    pub user_id: String,
+    pub email: String,
+    pub is_active: bool,
+    pub added_by: Option<String>,
+    #[serde(with = "time::serde::iso8601")]
+    pub created_at: OffsetDateTime,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct SourceOwnership {
+    pub source_id: String,
+    pub owner_id: String,
+    pub owner_email: Option<String>,
+    pub owner_active: bool,
+    pub service_principal_id: Option<String>,
+    pub service_principal_email: Option<String>,
+    #[sqlx(skip)]
+    pub co_owners: Vec<SourceCoOwner>,
+}
+
+/// A source whose owner has been deactivated or deleted.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct OrphanedSource {
+    pub source_id: String,
+    pub name: String,
+    pub source_type: SourceType,
+    pub scope: SourceScope,
+    pub owner_id: String,
+    pub owner_email: Option<String>,
+    /// Whether an active service principal or co-owner still keeps the
+    /// source syncing.
+    pub has_active_principal: bool,
+}
+
+pub struct SourceOwnershipRepository {
+    pool: PgPool,
+}
+
+impl SourceOwnershipRepository {
+    pub fn new(pool: &PgPool) -> Self {
+        Self { pool: pool.clone() }
+    }
+
+    pub async fn get(&self, source_id: &str) -> Result<Option<SourceOwnership>, DatabaseError> {
+        let ownership = sqlx::query_as::<_, SourceOwnership>(
+            r#"
+            SELECT s.id AS source_id, s.created_by AS owner_id,
+                   owner.email AS owner_email, COALESCE(owner.is_active, false) AS owner_active,
+                   s.service_principal_id, principal.email AS service_principal_email
+            FROM sources s
+            LEFT JOIN users owner ON owner.id = s.created_by
+            LEFT JOIN users principal ON principal.id = s.service_principal_id
+            WHERE s.id = $1 AND NOT s.is_deleted
+            "#,
+        )
+        .bind(source_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let Some(mut ownership) = ownership else {
+            return Ok(None);
+        };
+        ownership.co_owners = self.list_co_owners(source_id).await?;
+        Ok(Some(ownership))
+    }
+
+    pub async fn list_co_owners(
+        &self,
+        source_id: &str,
+    ) -> Result<Vec<SourceCoOwner>, DatabaseError> {
+        let co_owners = sqlx::query_as::<_, SourceCoOwner>(
+            r#"
+            SELECT co.source_id, co.user_id, u.email, u.is_active, co.added_by, co.created_at
+            FROM source_co_owners co
+            JOIN users u ON u.id = co.user_id
+            WHERE co.source_id = $1
+            ORDER BY co.created_at
+            "#,
+        )
+        .bind(source_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(co_owners)
+    }
+
+    /// Email connectors act as for a source: the service principal if set and
+    /// active, else the owner if active, else the longest-standing active
+    /// co-owner. `None` when none of them is active.
+    pub async fn resolve_principal_email(
+        &self,
+        source_id: &str,
+    ) -> Result<Option<String>, DatabaseError> {
+        let email = sqlx::query_scalar::<_, String>(
+            r#"
+            SELECT u.email
+            FROM sources s
+            LEFT JOIN source_co_owners co ON co.source_id = s.id
+            JOIN users u ON u.id IN (s.service_principal_id, s.created_by, co.user_id)
+            WHERE s.id = $1 AND u.is_active
+            ORDER BY CASE WHEN u.id = s.service_principal_id THEN 0
+                          WHEN u.id = s.created_by THEN 1
+                          ELSE 2 END,
+                     co.created_at
+            LIMIT 1
+            "#,
+        )
+        .bind(source_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(email)
+    }
+
+    /// Make `new_owner_id` the owner of the source. The new owner stops being
+    /// a co-owner; the previous owner becomes one if
+    /// `keep_previous_as_co_owner`. The owner credential of a personal source
+    /// moves with it. Returns false if the source does not exist.
+    pub async fn transfer_ownership(
+        &self,
+        source_id: &str,
+        new_owner_id: &str,
+        keep_previous_as_co_owner: bool,
+    ) -> Result<bool, DatabaseError> {
+        let mut tx = self.pool.begin().await?;
+
+        let previous = sqlx::query_as::<_, (String, SourceScope)>(
+            "SELECT created_by, scope FROM sources WHERE id = $1 AND NOT is_deleted FOR UPDATE",
+        )
+        .bind(source_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some((previous_owner_id, scope)) = previous else {
+            return Ok(false);
+        };
+        if previous_owner_id == new_owner_id {
+            return Ok(true);
+        }
+
+        sqlx::query(
+            "UPDATE sources SET created_by = $2, updated_at = CURRENT_TIMESTAMP WHERE id = $1",
+        )
+        .bind(source_id)
+        .bind(new_owner_id)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query("DELETE FROM source_co_owners WHERE source_id = $1 AND user_id = $2")
+            .bind(source_id)
+            .bind(new_owner_id)
+            .execute(&mut *tx)
+            .await?;
+
+        if keep_previous_as_co_owner {
+            sqlx::query(
+                r#"
+                INSERT INTO source_co_owners (source_id, user_id, added_by)
+                SELECT $1, id, $3 FROM users WHERE id = $2
+                ON CONFLICT (source_id, user_id) DO NOTHING
+                "#,
+            )
+            .bind(source_id)
+            .bind(&previous_owner_id)
+            .bind(new_owner_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        if scope == SourceScope::User {
+            sqlx::query(
+                "UPDATE service_credentials SET user_id = $3 WHERE source_id = $1 AND user_id = $2",
+            )
+            .bind(source_id)
+            .bind(&previous_owner_id)
+            .bind(new_owner_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    pub async fn add_co_owner(
+        &self,
+        source_id: &str,
+        user_id: &str,
+        added_by: Option<&str>,
+    ) -> Result<SourceCoOwner, DatabaseError> {
+        let co_owner = sqlx::query_as::<_, SourceCoOwner>(
+            r#"
+            WITH inserted AS (
+                INSERT INTO source_co_owners (source_id, user_id, added_by)
+                VALUES ($1, $2, $3)
+                ON CONFLICT (source_id, user_id) DO UPDATE SET user_id = EXCLUDED.user_id
+                RETURNING source_id, user_id, added_by, created_at
+            )
+            SELECT i.source_id, i.user_id, u.email, u.is_active, i.added_by, i.created_at
+            FROM inserted i
+            JOIN users u ON u.id = i.user_id
+            "#,
+        )
+        .bind(source_id)
+        .bind(user_id)
+        .bind(added_by)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(co_owner)
+    }
+
+    pub async fn remove_co_owner(
+        &self,
+        source_id: &str,
+        user_id: &str,
+    ) -> Result<bool, DatabaseError> {
+        let result =
+            sqlx::query("DELETE FROM source_co_owners WHERE source_id = $1 AND user_id = $2")
+                .bind(source_id)
+                .bind(user_id)
+                .execute(&self.pool)
+                .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Set or clear the user whose email connectors act as for the source.
+    pub async fn set_service_principal(
+        &self,
+        source_id: &str,
+        user_id: Option<&str>,
+    ) -> Result<bool, DatabaseError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE sources
+            SET service_principal_id = $2, updated_at = CURRENT_TIMESTAMP
+            WHERE id = $1 AND NOT is_deleted
+            "#,
+        )
+        .bind(source_id)
+        .bind(user_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Sources whose owner is deactivated or no longer exists. Chat upload
+    /// sources are skipped; they go away with their user.
+    pub async fn find_orphaned(&self) -> Result<Vec<OrphanedSource>, DatabaseError> {
+        let sources = sqlx::query_as::<_, OrphanedSource>(
+            r#"
+            SELECT s.id AS source_id, s.name, s.source_type, s.scope,
+                   s.created_by AS owner_id, owner.email AS owner_email,
+                   EXISTS (
+                       SELECT 1 FROM users u
+                       WHERE u.is_active
+                         AND (u.id = s.service_principal_id
+                              OR u.id IN (SELECT co.user_id FROM source_co_owners co
+                                          WHERE co.source_id = s.id))
+                   ) AS has_active_principal
+            FROM sources s
+            LEFT JOIN users owner ON owner.id = s.created_by
+            WHERE NOT s.is_deleted
+              AND s.source_type <> 'chat_upload'
+              AND NOT COALESCE(owner.is_active, false)
+            ORDER BY s.created_at
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(sources)
+    }
+}
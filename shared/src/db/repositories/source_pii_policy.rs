@@ -0,0 +1,151 @@
+use crate::db::error::DatabaseError;
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use sqlx::{FromRow, PgPool, types::time::OffsetDateTime};
+
+/// What the indexer does with a document of the source in which personal
+/// data was detected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+pub enum PiiAction {
+    /// Index the content with each match replaced by a placeholder.
+    Redact,
+    /// Index the content as it is, recording only what was found.
+    TagOnly,
+    /// Quarantine the document instead of indexing it.
+    Block,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+pub enum PiiDetector {
+    Email,
+    Phone,
+    CreditCard,
+}
+
+impl PiiDetector {
+    pub const ALL: [PiiDetector; 3] = [Self::Email, Self::Phone, Self::CreditCard];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Email => "email",
+            Self::Phone => "phone",
+            Self::CreditCard => "credit_card",
+        }
+    }
+}
+
+/// An admin-defined detector: a regular expression on the extracted text.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CustomPiiPattern {
+    /// Entity type recorded for matches, e.g. `employee_id`.
+    pub name: String,
+    pub pattern: String,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct SourcePiiPolicy {
+    pub source_id: String,
+    pub action: PiiAction,
+    pub detectors: Vec<PiiDetector>,
+    pub custom_patterns: Json<Vec<CustomPiiPattern>>,
+    pub updated_by: Option<String>,
+    #[serde(with = "time::serde::iso8601")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::iso8601")]
+    pub updated_at: OffsetDateTime,
+}
+
+const POLICY_COLUMNS: &str =
+    "source_id, action, detectors, custom_patterns, updated_by, created_at, updated_at";
+
+pub struct SourcePiiPolicyRepository {
+    pool: PgPool,
+}
+
+impl SourcePiiPolicyRepository {
+    pub fn new(pool: &PgPool) -> Self {
+        Self { pool: pool.clone() }
+    }
+
+    pub async fn get(&self, source_id: &str) -> Result<Option<SourcePiiPolicy>, DatabaseError> {
+        let query =
+            format!("SELECT {POLICY_COLUMNS} FROM source_pii_policies WHERE source_id = $1");
+        let policy = sqlx::query_as::<_, SourcePiiPolicy>(&query)
+            .bind(source_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(policy)
+    }
+
+    /// The policies of those of `source_ids` that have one.
+    pub async fn list_for_sources(
+        &self,
+        source_ids: &[String],
+    ) -> Result<Vec<SourcePiiPolicy>, DatabaseError> {
+        if source_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let query =
+            format!("SELECT {POLICY_COLUMNS} FROM source_pii_policies WHERE source_id = ANY($1)");
+        let policies = sqlx::query_as::<_, SourcePiiPolicy>(&query)
+            .bind(source_ids)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(policies)
+    }
+
+    /// Create or replace the source's policy. Fails with `NotFound` when the
+    /// source or the updating user does not exist.
+    pub async fn upsert(
+        &self,
+        source_id: &str,
+        action: PiiAction,
+        detectors: &[PiiDetector],
+        custom_patterns: &[CustomPiiPattern],
+        updated_by: Option<&str>,
+    ) -> Result<SourcePiiPolicy, DatabaseError> {
+        let query = format!(
+            "INSERT INTO source_pii_policies \
+                 (source_id, action, detectors, custom_patterns, updated_by) \
+             VALUES ($1, $2, $3, $4, $5) \
+             ON CONFLICT (source_id) DO UPDATE \
+             SET action = EXCLUDED.action, \
+                 detectors = EXCLUDED.detectors, \
+                 custom_patterns = EXCLUDED.custom_patterns, \
+                 updated_by = EXCLUDED.updated_by, \
+                 updated_at = NOW() \
+             RETURNING {POLICY_COLUMNS}"
+        );
+        let result = sqlx::query_as::<_, SourcePiiPolicy>(&query)
+            .bind(source_id)
+            .bind(action)
+            .bind(detectors)
+            .bind(Json(custom_patterns))
+            .bind(updated_by)
+            .fetch_one(&self.pool)
+            .await;
+        match result {
+            Ok(policy) => Ok(policy),
+            Err(sqlx::Error::Database(e)) if e.is_foreign_key_violation() => {
+                Err(DatabaseError::NotFound)
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub async fn delete(&self, source_id: &str) -> Result<bool, DatabaseError> {
+        let result = sqlx::query("DELETE FROM source_pii_policies WHERE source_id = $1")
+            .bind(source_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
@@ -0,0 +1,361 @@
+use crate::db::error::DatabaseError;
+use crate::models::Document;
+use crate::utils::generate_ulid;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool, types::time::OffsetDateTime};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+pub enum SourceReindexStatus {
+    /// Documents are being cleared and run through the indexing pipeline.
+    Reindexing,
+    /// Every document was reindexed; waiting for their embeddings.
+    Embedding,
+    Completed,
+    Failed,
+}
+
+impl SourceReindexStatus {
+    pub fn is_active(&self) -> bool {
+        matches!(
+            self,
+            SourceReindexStatus::Reindexing | SourceReindexStatus::Embedding
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct SourceReindexJob {
+    pub id: String,
+    pub source_id: String,
+    pub status: SourceReindexStatus,
+    /// Documents with content when the job started.
+    pub total_documents: i32,
+    pub reindexed_documents: i32,
+    pub embeddings_cleared: i64,
+    pub error_message: Option<String>,
+    #[serde(with = "time::serde::iso8601")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::iso8601::option")]
+    pub completed_at: Option<OffsetDateTime>,
+}
+
+/// Embedding work queued for a source since its reindex started.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct SourceReindexProgress {
+    /// Documents of the source embedded since the job started.
+    pub embedded_documents: i64,
+    /// Queued and not yet embedded, including retries.
+    pub pending_documents: i64,
+    pub quarantined_documents: i64,
+}
+
+impl SourceReindexProgress {
+    /// Share of the job's documents reindexed and embedded again.
+    pub fn percent(&self, job: &SourceReindexJob) -> f64 {
+        if job.total_documents <= 0 {
+            return 100.0;
+        }
+        (self.embedded_documents as f64 / job.total_documents as f64 * 100.0).min(100.0)
+    }
+}
+
+const JOB_COLUMNS: &str = r#"
+    id, source_id, status, total_documents, reindexed_documents, embeddings_cleared,
+    error_message, created_at, completed_at
+"#;
+
+pub struct SourceReindexRepository {
+    pool: PgPool,
+}
+
+impl SourceReindexRepository {
+    pub fn new(pool: &PgPool) -> Self {
+        Self { pool: pool.clone() }
+    }
+
+    /// Start reindexing a source. Fails with `NotFound` for an unknown or
+    /// deleted source, and with a constraint violation if the source is
+    /// already being reindexed.
+    pub async fn create(&self, source_id: &str) -> Result<SourceReindexJob, DatabaseError> {
+        let exists: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM sources WHERE id = $1 AND is_deleted = FALSE)",
+        )
+        .bind(source_id)
+        .fetch_one(&self.pool)
+        .await?;
+        if !exists {
+            return Err(DatabaseError::NotFound);
+        }
+
+        let query = format!(
+            r#"
+            INSERT INTO source_reindex_jobs (id, source_id, total_documents)
+            SELECT $1, $2, COUNT(*)
+            FROM documents
+            WHERE source_id = $2 AND content_id IS NOT NULL
+            RETURNING {JOB_COLUMNS}
+            "#
+        );
+        let result = sqlx::query_as::<_, SourceReindexJob>(&query)
+            .bind(generate_ulid())
+            .bind(source_id)
+            .fetch_one(&self.pool)
+            .await;
+        match result {
+            Ok(job) => Ok(job),
+            Err(sqlx::Error::Database(e)) if e.is_unique_violation() => {
+                Err(DatabaseError::ConstraintViolation(format!(
+                    "Source {} is already being reindexed",
+                    source_id
+                )))
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub async fn get(&self, id: &str) -> Result<Option<SourceReindexJob>, DatabaseError> {
+        let query = format!("SELECT {JOB_COLUMNS} FROM source_reindex_jobs WHERE id = $1");
+        let job = sqlx::query_as::<_, SourceReindexJob>(&query)
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(job)
+    }
+
+    /// Most recent jobs of a source first.
+    pub async fn list_for_source(
+        &self,
+        source_id: &str,
+        limit: i64,
+    ) -> Result<Vec<SourceReindexJob>, DatabaseError> {
+        let query = format!(
+            "SELECT {JOB_COLUMNS} FROM source_reindex_jobs WHERE source_id = $1 \
+             ORDER BY created_at DESC LIMIT $2"
+        );
+        let jobs = sqlx::query_as::<_, SourceReindexJob>(&query)
+            .bind(source_id)
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(jobs)
+    }
+
+    pub async fn list_embedding(&self) -> Result<Vec<SourceReindexJob>, DatabaseError> {
+        let query = format!(
+            "SELECT {JOB_COLUMNS} FROM source_reindex_jobs WHERE status = 'embedding' \
+             ORDER BY created_at"
+        );
+        let jobs = sqlx::query_as::<_, SourceReindexJob>(&query)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(jobs)
+    }
+
+    /// The source's documents with content and ids after `after_id`, in id
+    /// order.
+    pub async fn documents_after(
+        &self,
+        source_id: &str,
+        after_id: &str,
+        limit: i64,
+    ) -> Result<Vec<Document>, DatabaseError> {
+        let documents = sqlx::query_as::<_, Document>(
+            r#"
+            SELECT id, source_id, external_id, title, content_id, content_type,
+                   file_size, file_extension, url,
+                   metadata, permissions, attributes, created_at, updated_at, last_indexed_at
+            FROM documents
+            WHERE source_id = $1 AND content_id IS NOT NULL AND id > $2
+            ORDER BY id
+            LIMIT $3
+            "#,
+        )
+        .bind(source_id)
+        .bind(after_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(documents)
+    }
+
+    /// Delete the documents' current embeddings and summary embeddings, so
+    /// reindexing queues them to be embedded again. Returns the number of
+    /// embeddings deleted.
+    pub async fn clear_embeddings(&self, document_ids: &[String]) -> Result<u64, DatabaseError> {
+        let mut tx = self.pool.begin().await?;
+
+        let cleared =
+            sqlx::query("DELETE FROM embeddings WHERE document_id = ANY($1) AND namespace IS NULL")
+                .bind(document_ids)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+        sqlx::query("DELETE FROM document_summary_embeddings WHERE document_id = ANY($1)")
+            .bind(document_ids)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(cleared)
+    }
+
+    pub async fn record_progress(
+        &self,
+        id: &str,
+        reindexed_documents: i32,
+        embeddings_cleared: i64,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query(
+            r#"
+            UPDATE source_reindex_jobs
+            SET reindexed_documents = reindexed_documents + $2,
+                embeddings_cleared = embeddings_cleared + $3
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(reindexed_documents)
+        .bind(embeddings_cleared)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn mark_embedding(&self, id: &str) -> Result<(), DatabaseError> {
+        sqlx::query(
+            "UPDATE source_reindex_jobs SET status = 'embedding' WHERE id = $1 AND status = 'reindexing'",
+        )
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn complete(&self, id: &str) -> Result<(), DatabaseError> {
+        sqlx::query(
+            r#"
+            UPDATE source_reindex_jobs
+            SET status = 'completed', completed_at = NOW()
+            WHERE id = $1 AND status = 'embedding'
+            "#,
+        )
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn fail(&self, id: &str, error_message: &str) -> Result<(), DatabaseError> {
+        sqlx::query(
+            r#"
+            UPDATE source_reindex_jobs
+            SET status = 'failed', error_message = $2, completed_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(error_message)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Fail jobs left reindexing by a previous process.
+    pub async fn fail_orphaned(&self) -> Result<Vec<SourceReindexJob>, DatabaseError> {
+        let query = format!(
+            r#"
+            UPDATE source_reindex_jobs
+            SET status = 'failed', error_message = 'Reindex stopped unexpectedly',
+                completed_at = NOW()
+            WHERE status = 'reindexing'
+            RETURNING {JOB_COLUMNS}
+            "#
+        );
+        let jobs = sqlx::query_as::<_, SourceReindexJob>(&query)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(jobs)
+    }
+
+    pub async fn progress(
+        &self,
+        job: &SourceReindexJob,
+    ) -> Result<SourceReindexProgress, DatabaseError> {
+        let progress = sqlx::query_as::<_, SourceReindexProgress>(
+            r#"
+            SELECT
+                (SELECT COUNT(DISTINCT e.document_id)
+                 FROM embeddings e
+                 JOIN documents d ON d.id = e.document_id
+                 WHERE d.source_id = $1
+                   AND e.namespace IS NULL
+                   AND e.created_at >= $2) AS embedded_documents,
+                COUNT(DISTINCT q.document_id) FILTER (
+                    WHERE q.status IN ('pending', 'processing', 'failed')
+                ) AS pending_documents,
+                COUNT(DISTINCT q.document_id) FILTER (
+                    WHERE q.status = 'quarantined'
+                ) AS quarantined_documents
+            FROM embedding_queue q
+            JOIN documents d ON d.id = q.document_id
+            WHERE d.source_id = $1
+              AND q.namespace IS NULL
+              AND q.migration_id IS NULL
+              AND q.created_at >= $2
+            "#,
+        )
+        .bind(&job.source_id)
+        .bind(job.created_at)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(progress)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(total_documents: i32) -> SourceReindexJob {
+        SourceReindexJob {
+            id: "job".to_string(),
+            source_id: "source".to_string(),
+            status: SourceReindexStatus::Embedding,
+            total_documents,
+            reindexed_documents: total_documents,
+            embeddings_cleared: 0,
+            error_message: None,
+            created_at: OffsetDateTime::UNIX_EPOCH,
+            completed_at: None,
+        }
+    }
+
+    fn progress(embedded_documents: i64) -> SourceReindexProgress {
+        SourceReindexProgress {
+            embedded_documents,
+            pending_documents: 0,
+            quarantined_documents: 0,
+        }
+    }
+
+    #[test]
+    fn test_percent_counts_documents_embedded_again() {
+        assert_eq!(progress(0).percent(&job(200)), 0.0);
+        assert_eq!(progress(50).percent(&job(200)), 25.0);
+        // Documents added to the source mid-job are embedded too
+        assert_eq!(progress(250).percent(&job(200)), 100.0);
+        assert_eq!(progress(0).percent(&job(0)), 100.0);
+    }
+}
@@ -0,0 +1,408 @@
+use super::document::DOCUMENT_UPDATED_AT;
+use crate::db::error::DatabaseError;
+use crate::utils::generate_ulid;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool, types::time::OffsetDateTime};
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct SourceRetentionPolicy {
+    pub source_id: String,
+    /// Documents last updated longer ago than this are purged.
+    pub retention_days: i32,
+    pub updated_by: Option<String>,
+    #[serde(with = "time::serde::iso8601")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::iso8601")]
+    pub updated_at: OffsetDateTime,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+pub enum RetentionPurgeStatus {
+    Running,
+    Completed,
+    Failed,
+}
+
+/// One run of a source's retention policy.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct RetentionPurge {
+    pub id: String,
+    pub source_id: String,
+    pub retention_days: i32,
+    /// Documents last updated before this were purged.
+    #[serde(with = "time::serde::iso8601")]
+    pub cutoff: OffsetDateTime,
+    pub status: RetentionPurgeStatus,
+    pub documents_purged: i32,
+    pub embeddings_purged: i64,
+    pub error_message: Option<String>,
+    #[serde(with = "time::serde::iso8601")]
+    pub started_at: OffsetDateTime,
+    #[serde(with = "time::serde::iso8601::option")]
+    pub completed_at: Option<OffsetDateTime>,
+}
+
+/// The tombstone of a purged document.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct RetentionPurgedDocument {
+    pub document_id: String,
+    pub external_id: String,
+    #[serde(with = "time::serde::iso8601")]
+    pub document_updated_at: OffsetDateTime,
+    #[serde(with = "time::serde::iso8601")]
+    pub purged_at: OffsetDateTime,
+}
+
+/// What one batch of a purge removed.
+#[derive(Debug, Clone)]
+pub struct PurgedBatch {
+    pub documents: Vec<RetentionPurgedDocument>,
+    pub embeddings: u64,
+}
+
+/// A source's policy with the totals of its purges.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct SourceRetentionSummary {
+    pub source_id: String,
+    pub source_name: String,
+    pub retention_days: i32,
+    pub purges: i64,
+    pub documents_purged: i64,
+    pub embeddings_purged: i64,
+    #[serde(with = "time::serde::iso8601::option")]
+    pub last_purged_at: Option<OffsetDateTime>,
+    /// Status of the most recent purge, if any ran.
+    pub last_status: Option<RetentionPurgeStatus>,
+}
+
+const POLICY_COLUMNS: &str = "source_id, retention_days, updated_by, created_at, updated_at";
+
+const PURGE_COLUMNS: &str = r#"
+    id, source_id, retention_days, cutoff, status, documents_purged, embeddings_purged,
+    error_message, started_at, completed_at
+"#;
+
+pub struct SourceRetentionRepository {
+    pool: PgPool,
+}
+
+impl SourceRetentionRepository {
+    pub fn new(pool: &PgPool) -> Self {
+        Self { pool: pool.clone() }
+    }
+
+    pub async fn get_policy(
+        &self,
+        source_id: &str,
+    ) -> Result<Option<SourceRetentionPolicy>, DatabaseError> {
+        let query =
+            format!("SELECT {POLICY_COLUMNS} FROM source_retention_policies WHERE source_id = $1");
+        let policy = sqlx::query_as::<_, SourceRetentionPolicy>(&query)
+            .bind(source_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(policy)
+    }
+
+    /// Policies of sources that are not deleted.
+    pub async fn list_policies(&self) -> Result<Vec<SourceRetentionPolicy>, DatabaseError> {
+        let policies = sqlx::query_as::<_, SourceRetentionPolicy>(
+            r#"
+            SELECT p.source_id, p.retention_days, p.updated_by, p.created_at, p.updated_at
+            FROM source_retention_policies p
+            JOIN sources s ON s.id = p.source_id
+            WHERE s.is_deleted = FALSE
+            ORDER BY p.source_id
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(policies)
+    }
+
+    /// Create or replace the source's policy. Fails with `NotFound` when the
+    /// source or the updating user does not exist.
+    pub async fn upsert_policy(
+        &self,
+        source_id: &str,
+        retention_days: i32,
+        updated_by: Option<&str>,
+    ) -> Result<SourceRetentionPolicy, DatabaseError> {
+        let query = format!(
+            "INSERT INTO source_retention_policies (source_id, retention_days, updated_by) \
+             VALUES ($1, $2, $3) \
+             ON CONFLICT (source_id) DO UPDATE \
+             SET retention_days = EXCLUDED.retention_days, \
+                 updated_by = EXCLUDED.updated_by, \
+                 updated_at = NOW() \
+             RETURNING {POLICY_COLUMNS}"
+        );
+        let result = sqlx::query_as::<_, SourceRetentionPolicy>(&query)
+            .bind(source_id)
+            .bind(retention_days)
+            .bind(updated_by)
+            .fetch_one(&self.pool)
+            .await;
+        match result {
+            Ok(policy) => Ok(policy),
+            Err(sqlx::Error::Database(e)) if e.is_foreign_key_violation() => {
+                Err(DatabaseError::NotFound)
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub async fn delete_policy(&self, source_id: &str) -> Result<bool, DatabaseError> {
+        let result = sqlx::query("DELETE FROM source_retention_policies WHERE source_id = $1")
+            .bind(source_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Whether the source has documents last updated before `cutoff`. Age is
+    /// measured from [`DOCUMENT_UPDATED_AT`], so a resync does not reset it.
+    pub async fn has_expired(
+        &self,
+        source_id: &str,
+        cutoff: OffsetDateTime,
+    ) -> Result<bool, DatabaseError> {
+        let query = format!(
+            "SELECT EXISTS(SELECT 1 FROM documents d \
+             WHERE d.source_id = $1 AND {DOCUMENT_UPDATED_AT} < $2)"
+        );
+        let expired: bool = sqlx::query_scalar(&query)
+            .bind(source_id)
+            .bind(cutoff)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(expired)
+    }
+
+    pub async fn start_purge(
+        &self,
+        policy: &SourceRetentionPolicy,
+        cutoff: OffsetDateTime,
+    ) -> Result<RetentionPurge, DatabaseError> {
+        let query = format!(
+            "INSERT INTO retention_purges (id, source_id, retention_days, cutoff) \
+             VALUES ($1, $2, $3, $4) \
+             RETURNING {PURGE_COLUMNS}"
+        );
+        let purge = sqlx::query_as::<_, RetentionPurge>(&query)
+            .bind(generate_ulid())
+            .bind(&policy.source_id)
+            .bind(policy.retention_days)
+            .bind(cutoff)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(purge)
+    }
+
+    /// Delete up to `limit` of the purge's expired documents, leaving a
+    /// tombstone for each. Their embeddings, chunks and versions cascade.
+    /// Returns an empty batch once nothing is left to purge.
+    pub async fn purge_batch(
+        &self,
+        purge: &RetentionPurge,
+        limit: i64,
+    ) -> Result<PurgedBatch, DatabaseError> {
+        let mut tx = self.pool.begin().await?;
+
+        let query = format!(
+            r#"
+            WITH expired AS (
+                SELECT d.id, d.external_id, {DOCUMENT_UPDATED_AT} AS document_updated_at
+                FROM documents d
+                WHERE d.source_id = $2 AND {DOCUMENT_UPDATED_AT} < $3
+                ORDER BY d.id
+                LIMIT $4
+                FOR UPDATE SKIP LOCKED
+            )
+            INSERT INTO retention_purged_documents
+                (purge_id, document_id, external_id, document_updated_at)
+            SELECT $1, id, external_id, document_updated_at FROM expired
+            RETURNING document_id, external_id, document_updated_at, purged_at
+            "#
+        );
+        let documents = sqlx::query_as::<_, RetentionPurgedDocument>(&query)
+            .bind(&purge.id)
+            .bind(&purge.source_id)
+            .bind(purge.cutoff)
+            .bind(limit)
+            .fetch_all(&mut *tx)
+            .await?;
+        if documents.is_empty() {
+            return Ok(PurgedBatch {
+                documents,
+                embeddings: 0,
+            });
+        }
+
+        let document_ids: Vec<&str> = documents.iter().map(|d| d.document_id.as_str()).collect();
+        let embeddings: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM embeddings WHERE document_id = ANY($1)")
+                .bind(&document_ids)
+                .fetch_one(&mut *tx)
+                .await?;
+        sqlx::query("DELETE FROM documents WHERE id = ANY($1)")
+            .bind(&document_ids)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query(
+            r#"
+            UPDATE retention_purges
+            SET documents_purged = documents_purged + $2,
+                embeddings_purged = embeddings_purged + $3
+            WHERE id = $1
+            "#,
+        )
+        .bind(&purge.id)
+        .bind(documents.len() as i32)
+        .bind(embeddings)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(PurgedBatch {
+            documents,
+            embeddings: embeddings as u64,
+        })
+    }
+
+    pub async fn complete_purge(&self, id: &str) -> Result<RetentionPurge, DatabaseError> {
+        let query = format!(
+            "UPDATE retention_purges SET status = 'completed', completed_at = NOW() \
+             WHERE id = $1 RETURNING {PURGE_COLUMNS}"
+        );
+        let purge = sqlx::query_as::<_, RetentionPurge>(&query)
+            .bind(id)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(purge)
+    }
+
+    pub async fn fail_purge(&self, id: &str, error_message: &str) -> Result<(), DatabaseError> {
+        sqlx::query(
+            r#"
+            UPDATE retention_purges
+            SET status = 'failed', error_message = $2, completed_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(error_message)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Fail purges left running by a previous process. What they purged
+    /// stays purged and recorded; the next run picks up the rest.
+    pub async fn fail_orphaned(&self) -> Result<u64, DatabaseError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE retention_purges
+            SET status = 'failed', error_message = 'Purge stopped unexpectedly',
+                completed_at = NOW()
+            WHERE status = 'running'
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    pub async fn get_purge(&self, id: &str) -> Result<Option<RetentionPurge>, DatabaseError> {
+        let query = format!("SELECT {PURGE_COLUMNS} FROM retention_purges WHERE id = $1");
+        let purge = sqlx::query_as::<_, RetentionPurge>(&query)
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(purge)
+    }
+
+    /// Most recent purges of a source first.
+    pub async fn list_purges(
+        &self,
+        source_id: &str,
+        limit: i64,
+    ) -> Result<Vec<RetentionPurge>, DatabaseError> {
+        let query = format!(
+            "SELECT {PURGE_COLUMNS} FROM retention_purges WHERE source_id = $1 \
+             ORDER BY started_at DESC LIMIT $2"
+        );
+        let purges = sqlx::query_as::<_, RetentionPurge>(&query)
+            .bind(source_id)
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(purges)
+    }
+
+    /// Tombstones of the documents a purge removed, oldest document first.
+    pub async fn list_purged_documents(
+        &self,
+        purge_id: &str,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<RetentionPurgedDocument>, DatabaseError> {
+        let documents = sqlx::query_as::<_, RetentionPurgedDocument>(
+            r#"
+            SELECT document_id, external_id, document_updated_at, purged_at
+            FROM retention_purged_documents
+            WHERE purge_id = $1
+            ORDER BY document_updated_at, document_id
+            LIMIT $2 OFFSET $3
+            "#,
+        )
+        .bind(purge_id)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(documents)
+    }
+
+    /// Every policy of a source that is not deleted, by source name.
+    pub async fn summaries(&self) -> Result<Vec<SourceRetentionSummary>, DatabaseError> {
+        let summaries = sqlx::query_as::<_, SourceRetentionSummary>(
+            r#"
+            SELECT p.source_id,
+                   s.name AS source_name,
+                   p.retention_days,
+                   COUNT(r.id) AS purges,
+                   COALESCE(SUM(r.documents_purged), 0)::bigint AS documents_purged,
+                   COALESCE(SUM(r.embeddings_purged), 0)::bigint AS embeddings_purged,
+                   MAX(r.completed_at) FILTER (WHERE r.status = 'completed') AS last_purged_at,
+                   (SELECT status FROM retention_purges
+                    WHERE source_id = p.source_id
+                    ORDER BY started_at DESC
+                    LIMIT 1) AS last_status
+            FROM source_retention_policies p
+            JOIN sources s ON s.id = p.source_id
+            LEFT JOIN retention_purges r ON r.source_id = p.source_id
+            WHERE s.is_deleted = FALSE
+            GROUP BY p.source_id, s.name, p.retention_days
+            ORDER BY s.name
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(summaries)
+    }
+}
@@ -0,0 +1,105 @@
+use crate::db::error::DatabaseError;
+use serde::Serialize;
+use sqlx::{FromRow, PgPool, types::time::OffsetDateTime};
+
+/// A source's footprint as of the last refresh on one day.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct SourceDailyStats {
+    /// Midnight UTC of the day.
+    #[serde(with = "time::serde::iso8601")]
+    pub day: OffsetDateTime,
+    pub document_count: i64,
+    pub content_bytes: i64,
+    pub embedded_document_count: i64,
+    pub chunk_count: i64,
+    /// Mean time since the source's documents were first indexed.
+    pub avg_document_age_seconds: Option<f64>,
+    #[serde(with = "time::serde::iso8601")]
+    pub refreshed_at: OffsetDateTime,
+}
+
+pub struct SourceStatsRepository {
+    pool: PgPool,
+}
+
+impl SourceStatsRepository {
+    pub fn new(pool: &PgPool) -> Self {
+        Self { pool: pool.clone() }
+    }
+
+    /// Rewrite today's snapshot for every source that isn't deleted, and drop
+    /// snapshots older than `retention_days`. Chunks are counted once per
+    /// chunk index, as in the corpus language rollup. Returns the number of
+    /// snapshots written.
+    pub async fn refresh_daily_stats(&self, retention_days: i64) -> Result<u64, DatabaseError> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO source_daily_stats
+                (source_id, day, document_count, content_bytes, embedded_document_count,
+                 chunk_count, avg_document_age_seconds, refreshed_at)
+            SELECT s.id,
+                   date_trunc('day', NOW() AT TIME ZONE 'UTC') AT TIME ZONE 'UTC',
+                   COUNT(d.id),
+                   COALESCE(SUM(cb.size_bytes), 0)::bigint,
+                   COUNT(c.document_id),
+                   COALESCE(SUM(c.chunk_count), 0)::bigint,
+                   AVG(EXTRACT(EPOCH FROM NOW() - d.created_at))::float8,
+                   NOW()
+            FROM sources s
+            LEFT JOIN documents d ON d.source_id = s.id
+            LEFT JOIN content_blobs cb ON cb.id = d.content_id
+            LEFT JOIN (
+                SELECT document_id, COUNT(DISTINCT chunk_index) AS chunk_count
+                FROM embeddings
+                WHERE namespace IS NULL
+                GROUP BY document_id
+            ) c ON c.document_id = d.id
+            WHERE s.is_deleted = false
+            GROUP BY s.id
+            ON CONFLICT (source_id, day) DO UPDATE
+            SET document_count = EXCLUDED.document_count,
+                content_bytes = EXCLUDED.content_bytes,
+                embedded_document_count = EXCLUDED.embedded_document_count,
+                chunk_count = EXCLUDED.chunk_count,
+                avg_document_age_seconds = EXCLUDED.avg_document_age_seconds,
+                refreshed_at = EXCLUDED.refreshed_at
+            "#,
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query("DELETE FROM source_daily_stats WHERE day < NOW() - make_interval(days => $1)")
+            .bind(retention_days as i32)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Snapshots of a source from `since` on, oldest first.
+    pub async fn get_daily_stats(
+        &self,
+        source_id: &str,
+        since: OffsetDateTime,
+    ) -> Result<Vec<SourceDailyStats>, DatabaseError> {
+        let stats = sqlx::query_as::<_, SourceDailyStats>(
+            r#"
+            SELECT day, document_count, content_bytes, embedded_document_count, chunk_count,
+                   avg_document_age_seconds, refreshed_at
+            FROM source_daily_stats
+            WHERE source_id = $1 AND day >= $2
+            ORDER BY day
+            "#,
+        )
+        .bind(source_id)
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(stats)
+    }
+}
@@ -0,0 +1,168 @@
+use crate::db::error::DatabaseError;
+use serde::Serialize;
+use sqlx::{FromRow, PgPool, types::time::OffsetDateTime};
+
+/// What a source stored as of the last analysis on one day.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct SourceStorageSnapshot {
+    pub source_id: String,
+    pub source_name: String,
+    /// Midnight UTC of the day.
+    #[serde(with = "time::serde::iso8601")]
+    pub day: OffsetDateTime,
+    pub document_count: i64,
+    pub document_bytes: i64,
+    pub content_bytes: i64,
+    pub embedding_count: i64,
+    pub embedding_bytes: i64,
+    pub version_count: i64,
+    pub version_bytes: i64,
+    pub prunable_version_bytes: i64,
+    #[serde(with = "time::serde::iso8601")]
+    pub refreshed_at: OffsetDateTime,
+}
+
+pub struct StorageSnapshotRepository {
+    pool: PgPool,
+}
+
+impl StorageSnapshotRepository {
+    pub fn new(pool: &PgPool) -> Self {
+        Self { pool: pool.clone() }
+    }
+
+    /// Rewrite today's snapshot for every source that isn't deleted, and drop
+    /// snapshots older than `retention_days`. Versions count as superseded
+    /// unless they are a document's newest; those the version retention policy
+    /// (`max_versions`, `max_age_days`) would prune are also counted as
+    /// prunable. A version whose blob is still the document's current content
+    /// adds no bytes. Returns the number of snapshots written.
+    pub async fn refresh_snapshots(
+        &self,
+        max_versions: i64,
+        max_age_days: Option<i64>,
+        retention_days: i64,
+    ) -> Result<u64, DatabaseError> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO source_storage_snapshots
+                (source_id, day, document_count, document_bytes, content_bytes, embedding_count,
+                 embedding_bytes, version_count, version_bytes, prunable_version_bytes,
+                 refreshed_at)
+            SELECT s.id,
+                   date_trunc('day', NOW() AT TIME ZONE 'UTC') AT TIME ZONE 'UTC',
+                   COALESCE(d.document_count, 0),
+                   COALESCE(d.document_bytes, 0),
+                   COALESCE(d.content_bytes, 0),
+                   COALESCE(e.embedding_count, 0),
+                   COALESCE(e.embedding_bytes, 0),
+                   COALESCE(v.version_count, 0),
+                   COALESCE(v.version_bytes, 0),
+                   COALESCE(v.prunable_version_bytes, 0),
+                   NOW()
+            FROM sources s
+            LEFT JOIN (
+                SELECT d.source_id,
+                       COUNT(*) AS document_count,
+                       SUM(pg_column_size(d.*))::bigint AS document_bytes,
+                       COALESCE(SUM(cb.size_bytes), 0)::bigint AS content_bytes
+                FROM documents d
+                LEFT JOIN content_blobs cb ON cb.id = d.content_id
+                GROUP BY d.source_id
+            ) d ON d.source_id = s.id
+            LEFT JOIN (
+                SELECT d.source_id,
+                       COUNT(*) AS embedding_count,
+                       SUM(pg_column_size(e.*))::bigint AS embedding_bytes
+                FROM embeddings e
+                JOIN documents d ON d.id = e.document_id
+                GROUP BY d.source_id
+            ) e ON e.source_id = s.id
+            LEFT JOIN (
+                SELECT d.source_id,
+                       COUNT(*) AS version_count,
+                       COALESCE(SUM(cb.size_bytes), 0)::bigint AS version_bytes,
+                       COALESCE(SUM(cb.size_bytes) FILTER (
+                           WHERE ranked.rank > $1
+                              OR ($2::bigint IS NOT NULL
+                                  AND ranked.indexed_at < NOW() - make_interval(days => $2::int))
+                       ), 0)::bigint AS prunable_version_bytes
+                FROM (
+                    SELECT document_id, content_id, indexed_at,
+                           ROW_NUMBER() OVER (PARTITION BY document_id ORDER BY version DESC) AS rank
+                    FROM document_versions
+                ) ranked
+                JOIN documents d ON d.id = ranked.document_id
+                LEFT JOIN content_blobs cb
+                    ON cb.id = ranked.content_id
+                   AND ranked.content_id IS DISTINCT FROM d.content_id
+                WHERE ranked.rank > 1
+                GROUP BY d.source_id
+            ) v ON v.source_id = s.id
+            WHERE s.is_deleted = false
+            ON CONFLICT (source_id, day) DO UPDATE
+            SET document_count = EXCLUDED.document_count,
+                document_bytes = EXCLUDED.document_bytes,
+                content_bytes = EXCLUDED.content_bytes,
+                embedding_count = EXCLUDED.embedding_count,
+                embedding_bytes = EXCLUDED.embedding_bytes,
+                version_count = EXCLUDED.version_count,
+                version_bytes = EXCLUDED.version_bytes,
+                prunable_version_bytes = EXCLUDED.prunable_version_bytes,
+                refreshed_at = EXCLUDED.refreshed_at
+            "#,
+        )
+        .bind(max_versions)
+        .bind(max_age_days)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            "DELETE FROM source_storage_snapshots WHERE day < NOW() - make_interval(days => $1)",
+        )
+        .bind(retention_days as i32)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Snapshots of sources that aren't deleted from `since` on, oldest first.
+    pub async fn get_snapshots(
+        &self,
+        since: OffsetDateTime,
+    ) -> Result<Vec<SourceStorageSnapshot>, DatabaseError> {
+        let snapshots = sqlx::query_as::<_, SourceStorageSnapshot>(
+            r#"
+            SELECT ss.source_id, s.name AS source_name, ss.day, ss.document_count,
+                   ss.document_bytes, ss.content_bytes, ss.embedding_count, ss.embedding_bytes,
+                   ss.version_count, ss.version_bytes, ss.prunable_version_bytes, ss.refreshed_at
+            FROM source_storage_snapshots ss
+            JOIN sources s ON s.id = ss.source_id
+            WHERE s.is_deleted = false AND ss.day >= $1
+            ORDER BY ss.day, ss.source_id
+            "#,
+        )
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(snapshots)
+    }
+
+    /// Size of the content blobs nothing references any more, which the next
+    /// GC passes will reclaim.
+    pub async fn unreferenced_content_bytes(&self) -> Result<i64, DatabaseError> {
+        let bytes: i64 = sqlx::query_scalar(
+            "SELECT COALESCE(SUM(size_bytes), 0)::bigint FROM content_blobs WHERE ref_count = 0",
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(bytes)
+    }
+}
@@ -1,9 +1,11 @@
 use crate::{
     db::error::DatabaseError,
     models::{SyncRun, SyncStatus, SyncType},
+    rate_limiter::RateLimitStats,
     utils::generate_ulid,
 };
-use sqlx::{Error as SqlxError, PgPool};
+use serde::{Deserialize, Serialize};
+use sqlx::{Error as SqlxError, FromRow, PgPool};
 use time::OffsetDateTime;
 
 const RUNNING_SYNC_SLOT_INDEX: &str = "idx_sync_runs_one_running_per_source_slot";
@@ -17,6 +19,81 @@ fn map_sqlx_error(error: SqlxError) -> DatabaseError {
     DatabaseError::Connection(error)
 }
 
+/// Narrows a sync run history listing. Runs are matched on when they started;
+/// `from` is inclusive and `to` exclusive.
+#[derive(Debug, Clone, Default)]
+pub struct SyncRunFilter {
+    pub source_id: Option<String>,
+    pub status: Option<SyncStatus>,
+    pub from: Option<OffsetDateTime>,
+    pub to: Option<OffsetDateTime>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncRunStatsPeriod {
+    #[default]
+    Day,
+    Week,
+    Month,
+}
+
+impl SyncRunStatsPeriod {
+    fn as_str(&self) -> &'static str {
+        match self {
+            SyncRunStatsPeriod::Day => "day",
+            SyncRunStatsPeriod::Week => "week",
+            SyncRunStatsPeriod::Month => "month",
+        }
+    }
+}
+
+/// Sync runs started within one period, in UTC.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct SyncRunPeriodStats {
+    #[serde(with = "time::serde::iso8601")]
+    pub period_start: OffsetDateTime,
+    pub total_runs: i64,
+    pub completed_runs: i64,
+    pub failed_runs: i64,
+    pub cancelled_runs: i64,
+    pub running_runs: i64,
+    pub documents_scanned: i64,
+    pub documents_processed: i64,
+    pub documents_updated: i64,
+    /// Mean duration of the runs that have finished.
+    pub avg_duration_seconds: Option<f64>,
+}
+
+/// Upstream rate limit usage of one source's syncs against one API family.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct SourceRateLimitSummary {
+    pub source_id: String,
+    pub api_family: String,
+    pub requests: i64,
+    pub wait_ms_total: i64,
+    pub wait_ms_max: i64,
+    pub backoff_ms_total: i64,
+    pub throttled: i64,
+    pub retries: i64,
+    /// Rate in force at the most recent report, in requests per second.
+    pub current_rps: i32,
+    #[serde(with = "time::serde::iso8601")]
+    pub last_reported_at: OffsetDateTime,
+}
+
+/// How one source's recent syncs went.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct SourceSyncHealthSummary {
+    pub source_id: String,
+    #[serde(with = "time::serde::iso8601::option")]
+    pub last_sync_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::iso8601::option")]
+    pub last_successful_sync_at: Option<OffsetDateTime>,
+    /// Runs that failed since the last successful one.
+    pub consecutive_failures: i64,
+}
+
 #[derive(Clone)]
 pub struct SyncRunRepository {
     pool: PgPool,
@@ -290,6 +367,33 @@ impl SyncRunRepository {
         Ok(sync_run)
     }
 
+    /// Whether any of `source_ids` finished a sync after `since`.
+    pub async fn any_completed_since(
+        &self,
+        source_ids: &[String],
+        since: OffsetDateTime,
+    ) -> Result<bool, DatabaseError> {
+        if source_ids.is_empty() {
+            return Ok(false);
+        }
+
+        let completed: bool = sqlx::query_scalar(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM sync_runs
+                WHERE source_id = ANY($1) AND status = $2 AND completed_at > $3
+            )
+            "#,
+        )
+        .bind(source_ids)
+        .bind(SyncStatus::Completed)
+        .bind(since)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(completed)
+    }
+
     pub async fn get_running_for_source(
         &self,
         source_id: &str,
@@ -477,6 +581,31 @@ impl SyncRunRepository {
         Ok(result.rows_affected() > 0)
     }
 
+    /// Like `update_activity`, also recording the phase and expected total a
+    /// connector reported. Fields left as `None` keep their previous value.
+    pub async fn update_progress(
+        &self,
+        id: &str,
+        phase: Option<&str>,
+        documents_total: Option<i32>,
+    ) -> Result<bool, DatabaseError> {
+        let result = sqlx::query(
+            "UPDATE sync_runs
+             SET phase = COALESCE($3, phase),
+                 documents_total = COALESCE($4, documents_total),
+                 last_activity_at = CURRENT_TIMESTAMP, updated_at = CURRENT_TIMESTAMP
+             WHERE id = $1 AND status = $2",
+        )
+        .bind(id)
+        .bind(SyncStatus::Running)
+        .bind(phase)
+        .bind(documents_total)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
     pub async fn find_latest_for_sources(
         &self,
         source_ids: &[String],
@@ -548,4 +677,220 @@ impl SyncRunRepository {
 
         Ok(sync_runs)
     }
+
+    /// One page of the runs matching `filter`, newest first, with the total
+    /// number of matching runs.
+    pub async fn search(
+        &self,
+        filter: &SyncRunFilter,
+        limit: i64,
+        offset: i64,
+    ) -> Result<(Vec<SyncRun>, i64), DatabaseError> {
+        let total: i64 = sqlx::query_scalar(
+            r#"
+            SELECT COUNT(*)
+            FROM sync_runs
+            WHERE ($1::text IS NULL OR source_id = $1)
+              AND ($2::text IS NULL OR status = $2)
+              AND ($3::timestamptz IS NULL OR started_at >= $3)
+              AND ($4::timestamptz IS NULL OR started_at < $4)
+            "#,
+        )
+        .bind(&filter.source_id)
+        .bind(filter.status)
+        .bind(filter.from)
+        .bind(filter.to)
+        .fetch_one(&self.pool)
+        .await?;
+
+        let sync_runs = sqlx::query_as::<_, SyncRun>(
+            r#"
+            SELECT id, source_id, sync_type, started_at, completed_at, status, trigger_type,
+                   documents_scanned, documents_processed, documents_updated, error_message,
+                   NULL::jsonb AS checkpoint, created_at, updated_at
+            FROM sync_runs
+            WHERE ($1::text IS NULL OR source_id = $1)
+              AND ($2::text IS NULL OR status = $2)
+              AND ($3::timestamptz IS NULL OR started_at >= $3)
+              AND ($4::timestamptz IS NULL OR started_at < $4)
+            ORDER BY started_at DESC, created_at DESC, id DESC
+            LIMIT $5 OFFSET $6
+            "#,
+        )
+        .bind(&filter.source_id)
+        .bind(filter.status)
+        .bind(filter.from)
+        .bind(filter.to)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok((sync_runs, total))
+    }
+
+    /// Aggregates of the runs matching `filter`, bucketed by the period they
+    /// started in, newest period first.
+    pub async fn stats_by_period(
+        &self,
+        filter: &SyncRunFilter,
+        period: SyncRunStatsPeriod,
+    ) -> Result<Vec<SyncRunPeriodStats>, DatabaseError> {
+        let stats = sqlx::query_as::<_, SyncRunPeriodStats>(
+            r#"
+            SELECT date_trunc($5, started_at AT TIME ZONE 'UTC') AT TIME ZONE 'UTC' AS period_start,
+                   COUNT(*) AS total_runs,
+                   COUNT(*) FILTER (WHERE status = 'completed') AS completed_runs,
+                   COUNT(*) FILTER (WHERE status = 'failed') AS failed_runs,
+                   COUNT(*) FILTER (WHERE status = 'cancelled') AS cancelled_runs,
+                   COUNT(*) FILTER (WHERE status = 'running') AS running_runs,
+                   COALESCE(SUM(documents_scanned), 0)::bigint AS documents_scanned,
+                   COALESCE(SUM(documents_processed), 0)::bigint AS documents_processed,
+                   COALESCE(SUM(documents_updated), 0)::bigint AS documents_updated,
+                   AVG(EXTRACT(EPOCH FROM completed_at - started_at))
+                       FILTER (WHERE completed_at IS NOT NULL)::float8 AS avg_duration_seconds
+            FROM sync_runs
+            WHERE ($1::text IS NULL OR source_id = $1)
+              AND ($2::text IS NULL OR status = $2)
+              AND ($3::timestamptz IS NULL OR started_at >= $3)
+              AND ($4::timestamptz IS NULL OR started_at < $4)
+            GROUP BY 1
+            ORDER BY 1 DESC
+            "#,
+        )
+        .bind(&filter.source_id)
+        .bind(filter.status)
+        .bind(filter.from)
+        .bind(filter.to)
+        .bind(period.as_str())
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(stats)
+    }
+
+    /// Add rate limit usage reported for a run to what it has reported so
+    /// far, one row per API family.
+    pub async fn record_rate_limits(
+        &self,
+        sync_run_id: &str,
+        stats: &[RateLimitStats],
+    ) -> Result<(), DatabaseError> {
+        let mut tx = self.pool.begin().await?;
+        for s in stats {
+            sqlx::query(
+                r#"
+                INSERT INTO sync_run_rate_limits
+                    (sync_run_id, api_family, requests, wait_ms_total, wait_ms_max,
+                     backoff_ms_total, throttled, retries, current_rps)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                ON CONFLICT (sync_run_id, api_family) DO UPDATE SET
+                    requests = sync_run_rate_limits.requests + EXCLUDED.requests,
+                    wait_ms_total = sync_run_rate_limits.wait_ms_total + EXCLUDED.wait_ms_total,
+                    wait_ms_max = GREATEST(sync_run_rate_limits.wait_ms_max, EXCLUDED.wait_ms_max),
+                    backoff_ms_total = sync_run_rate_limits.backoff_ms_total + EXCLUDED.backoff_ms_total,
+                    throttled = sync_run_rate_limits.throttled + EXCLUDED.throttled,
+                    retries = sync_run_rate_limits.retries + EXCLUDED.retries,
+                    current_rps = EXCLUDED.current_rps,
+                    updated_at = NOW()
+                "#,
+            )
+            .bind(sync_run_id)
+            .bind(&s.api_family)
+            .bind(s.requests as i64)
+            .bind(s.wait_ms_total as i64)
+            .bind(s.wait_ms_max as i64)
+            .bind(s.backoff_ms_total as i64)
+            .bind(s.throttled as i64)
+            .bind(s.retries as i64)
+            .bind(s.current_rps as i32)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Rate limit usage of each source's runs started since `since`, per API
+    /// family.
+    pub async fn rate_limit_summary(
+        &self,
+        source_ids: &[String],
+        since: OffsetDateTime,
+    ) -> Result<Vec<SourceRateLimitSummary>, DatabaseError> {
+        let summaries = sqlx::query_as::<_, SourceRateLimitSummary>(
+            r#"
+            SELECT sr.source_id::text AS source_id,
+                   rl.api_family,
+                   SUM(rl.requests)::bigint AS requests,
+                   SUM(rl.wait_ms_total)::bigint AS wait_ms_total,
+                   MAX(rl.wait_ms_max) AS wait_ms_max,
+                   SUM(rl.backoff_ms_total)::bigint AS backoff_ms_total,
+                   SUM(rl.throttled)::bigint AS throttled,
+                   SUM(rl.retries)::bigint AS retries,
+                   (ARRAY_AGG(rl.current_rps ORDER BY rl.updated_at DESC))[1] AS current_rps,
+                   MAX(rl.updated_at) AS last_reported_at
+            FROM sync_run_rate_limits rl
+            JOIN sync_runs sr ON sr.id = rl.sync_run_id
+            WHERE sr.source_id = ANY($1)
+              AND sr.started_at >= $2
+            GROUP BY sr.source_id, rl.api_family
+            ORDER BY sr.source_id, rl.api_family
+            "#,
+        )
+        .bind(source_ids)
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(summaries)
+    }
+
+    /// Summarize the runs of the given types of each of `source_ids`. Sources
+    /// without runs get an empty summary.
+    pub async fn health_summary(
+        &self,
+        source_ids: &[String],
+        sync_types: &[SyncType],
+    ) -> Result<Vec<SourceSyncHealthSummary>, DatabaseError> {
+        if source_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let type_strs: Vec<String> = sync_types.iter().map(|t| t.to_string()).collect();
+        let summaries = sqlx::query_as::<_, SourceSyncHealthSummary>(
+            r#"
+            WITH runs AS (
+                SELECT source_id, status, started_at, completed_at
+                FROM sync_runs
+                WHERE source_id = ANY($1)
+                  AND (cardinality($2::text[]) = 0 OR sync_type::text = ANY($2))
+            ),
+            last_success AS (
+                SELECT source_id, MAX(started_at) AS started_at, MAX(completed_at) AS completed_at
+                FROM runs
+                WHERE status = 'completed'
+                GROUP BY source_id
+            )
+            SELECT ids.source_id,
+                   (SELECT MAX(r.started_at) FROM runs r
+                    WHERE r.source_id = ids.source_id) AS last_sync_at,
+                   ls.completed_at AS last_successful_sync_at,
+                   (SELECT COUNT(*) FROM runs r
+                    WHERE r.source_id = ids.source_id
+                      AND r.status = 'failed'
+                      AND (ls.started_at IS NULL OR r.started_at > ls.started_at)
+                   ) AS consecutive_failures
+            FROM UNNEST($1::text[]) AS ids(source_id)
+            LEFT JOIN last_success ls ON ls.source_id = ids.source_id
+            ORDER BY ids.source_id
+            "#,
+        )
+        .bind(source_ids)
+        .bind(&type_strs)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(summaries)
+    }
 }
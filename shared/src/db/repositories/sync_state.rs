@@ -0,0 +1,122 @@
+use crate::db::error::DatabaseError;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use sqlx::{FromRow, PgPool, types::time::OffsetDateTime};
+
+/// A value a connector keeps between syncs of a source, such as a change
+/// cursor or the ETags of items it has already indexed. `version` goes up on
+/// every write so writers can compare-and-swap.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct SyncStateEntry {
+    pub source_id: String,
+    pub key: String,
+    pub value: JsonValue,
+    pub version: i64,
+    #[serde(with = "time::serde::iso8601")]
+    pub updated_at: OffsetDateTime,
+}
+
+const ENTRY_COLUMNS: &str = "source_id, key, value, version, updated_at";
+
+pub struct SyncStateRepository {
+    pool: PgPool,
+}
+
+impl SyncStateRepository {
+    pub fn new(pool: &PgPool) -> Self {
+        Self { pool: pool.clone() }
+    }
+
+    pub async fn get(
+        &self,
+        source_id: &str,
+        key: &str,
+    ) -> Result<Option<SyncStateEntry>, DatabaseError> {
+        let query = format!(
+            "SELECT {ENTRY_COLUMNS} FROM source_sync_state WHERE source_id = $1 AND key = $2"
+        );
+        let entry = sqlx::query_as::<_, SyncStateEntry>(&query)
+            .bind(source_id)
+            .bind(key)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(entry)
+    }
+
+    /// Write a value regardless of what is stored.
+    pub async fn set(
+        &self,
+        source_id: &str,
+        key: &str,
+        value: &JsonValue,
+    ) -> Result<SyncStateEntry, DatabaseError> {
+        let query = format!(
+            "INSERT INTO source_sync_state (source_id, key, value) VALUES ($1, $2, $3) \
+             ON CONFLICT (source_id, key) DO UPDATE \
+             SET value = EXCLUDED.value, version = source_sync_state.version + 1, updated_at = NOW() \
+             RETURNING {ENTRY_COLUMNS}"
+        );
+        let entry = sqlx::query_as::<_, SyncStateEntry>(&query)
+            .bind(source_id)
+            .bind(key)
+            .bind(value)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(entry)
+    }
+
+    /// Write a value only if the stored version is `expected_version`, or with
+    /// `None` only if the key doesn't exist yet. Returns `None` when the
+    /// precondition failed because someone else wrote first.
+    pub async fn compare_and_swap(
+        &self,
+        source_id: &str,
+        key: &str,
+        expected_version: Option<i64>,
+        value: &JsonValue,
+    ) -> Result<Option<SyncStateEntry>, DatabaseError> {
+        let entry = match expected_version {
+            None => {
+                let query = format!(
+                    "INSERT INTO source_sync_state (source_id, key, value) VALUES ($1, $2, $3) \
+                     ON CONFLICT (source_id, key) DO NOTHING RETURNING {ENTRY_COLUMNS}"
+                );
+                sqlx::query_as::<_, SyncStateEntry>(&query)
+                    .bind(source_id)
+                    .bind(key)
+                    .bind(value)
+                    .fetch_optional(&self.pool)
+                    .await?
+            }
+            Some(version) => {
+                let query = format!(
+                    "UPDATE source_sync_state \
+                     SET value = $3, version = version + 1, updated_at = NOW() \
+                     WHERE source_id = $1 AND key = $2 AND version = $4 \
+                     RETURNING {ENTRY_COLUMNS}"
+                );
+                sqlx::query_as::<_, SyncStateEntry>(&query)
+                    .bind(source_id)
+                    .bind(key)
+                    .bind(value)
+                    .bind(version)
+                    .fetch_optional(&self.pool)
+                    .await?
+            }
+        };
+
+        Ok(entry)
+    }
+
+    pub async fn delete(&self, source_id: &str, key: &str) -> Result<bool, DatabaseError> {
+        let result = sqlx::query("DELETE FROM source_sync_state WHERE source_id = $1 AND key = $2")
+            .bind(source_id)
+            .bind(key)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
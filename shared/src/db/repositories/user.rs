@@ -19,7 +19,7 @@ impl UserRepository {
         let user = sqlx::query_as::<_, User>(
             r#"
             SELECT id, email, password_hash, full_name, avatar_url,
-                   role, is_active, created_at, updated_at, last_login_at
+                   role, is_active, created_at, updated_at, last_login_at, workspace_id
             FROM users
             WHERE email = $1
             "#,
@@ -41,7 +41,7 @@ impl UserRepository {
         let users = sqlx::query_as::<_, User>(
             r#"
             SELECT id, email, password_hash, full_name, avatar_url,
-                   role, is_active, created_at, updated_at, last_login_at
+                   role, is_active, created_at, updated_at, last_login_at, workspace_id
             FROM users
             WHERE role = $1
             ORDER BY created_at DESC
@@ -61,7 +61,7 @@ impl Repository<User, String> for UserRepository {
         let user = sqlx::query_as::<_, User>(
             r#"
             SELECT id, email, full_name, avatar_url,
-                   role, is_active, created_at, updated_at, last_login_at, auth_method, domain
+                   role, is_active, created_at, updated_at, last_login_at, auth_method, domain, workspace_id
             FROM users
             WHERE id = $1
             "#,
@@ -77,7 +77,7 @@ impl Repository<User, String> for UserRepository {
         let users = sqlx::query_as::<_, User>(
             r#"
             SELECT id, email, full_name, avatar_url,
-                   role, is_active, created_at, updated_at, last_login_at
+                   role, is_active, created_at, updated_at, last_login_at, workspace_id
             FROM users
             ORDER BY created_at DESC
             LIMIT $1 OFFSET $2
@@ -100,10 +100,10 @@ impl Repository<User, String> for UserRepository {
 
         let created_user = sqlx::query_as::<_, User>(
             r#"
-            INSERT INTO users (id, email, password_hash, full_name, avatar_url, role, is_active)
-            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            INSERT INTO users (id, email, password_hash, full_name, avatar_url, role, is_active, workspace_id)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
             RETURNING id, email, full_name, avatar_url,
-                      role, is_active, created_at, updated_at, last_login_at
+                      role, is_active, created_at, updated_at, last_login_at, workspace_id
             "#,
         )
         .bind(&user.id)
@@ -113,6 +113,7 @@ impl Repository<User, String> for UserRepository {
         .bind(&user.avatar_url)
         .bind(role_str)
         .bind(user.is_active)
+        .bind(&user.workspace_id)
         .fetch_one(&self.pool)
         .await
         .map_err(|e| match e {
@@ -138,7 +139,7 @@ impl Repository<User, String> for UserRepository {
             SET email = $2, password_hash = $3, full_name = $4, avatar_url = $5, role = $6, is_active = $7
             WHERE id = $1
             RETURNING id, email, password_hash, full_name, avatar_url,
-                      role, is_active, created_at, updated_at, last_login_at
+                      role, is_active, created_at, updated_at, last_login_at, workspace_id
             "#
         )
         .bind(&id)
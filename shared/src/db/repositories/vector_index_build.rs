@@ -0,0 +1,273 @@
+use crate::db::error::DatabaseError;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool, types::time::OffsetDateTime};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+pub enum VectorIndexMethod {
+    Hnsw,
+    Ivfflat,
+}
+
+impl VectorIndexMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VectorIndexMethod::Hnsw => "hnsw",
+            VectorIndexMethod::Ivfflat => "ivfflat",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+pub enum VectorIndexBuildStatus {
+    Pending,
+    /// CREATE INDEX CONCURRENTLY is running under the temporary name.
+    Building,
+    /// The new index is valid and replacing the old one.
+    Swapping,
+    Completed,
+    Failed,
+}
+
+impl VectorIndexBuildStatus {
+    pub fn is_active(&self) -> bool {
+        matches!(
+            self,
+            VectorIndexBuildStatus::Pending
+                | VectorIndexBuildStatus::Building
+                | VectorIndexBuildStatus::Swapping
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct VectorIndexBuild {
+    pub id: String,
+    pub dimensions: i32,
+    pub method: VectorIndexMethod,
+    pub m: Option<i32>,
+    pub ef_construction: Option<i32>,
+    pub lists: Option<i32>,
+    pub status: VectorIndexBuildStatus,
+    #[serde(skip)]
+    pub backend_pid: Option<i32>,
+    pub error_message: Option<String>,
+    #[serde(with = "time::serde::iso8601")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::iso8601::option")]
+    pub started_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::iso8601::option")]
+    pub completed_at: Option<OffsetDateTime>,
+}
+
+/// A row of `pg_stat_progress_create_index` for a running build.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct VectorIndexBuildProgress {
+    pub phase: String,
+    pub blocks_done: i64,
+    pub blocks_total: i64,
+    pub tuples_done: i64,
+    pub tuples_total: i64,
+}
+
+impl VectorIndexBuildProgress {
+    /// Completion of the current phase, when Postgres reports a total for it.
+    pub fn phase_percent(&self) -> Option<f64> {
+        let (done, total) = if self.tuples_total > 0 {
+            (self.tuples_done, self.tuples_total)
+        } else if self.blocks_total > 0 {
+            (self.blocks_done, self.blocks_total)
+        } else {
+            return None;
+        };
+        Some((done as f64 / total as f64 * 100.0).min(100.0))
+    }
+}
+
+const BUILD_COLUMNS: &str = r#"
+    id, dimensions, method, m, ef_construction, lists, status, backend_pid,
+    error_message, created_at, started_at, completed_at
+"#;
+
+pub struct VectorIndexBuildRepository {
+    pool: PgPool,
+}
+
+impl VectorIndexBuildRepository {
+    pub fn new(pool: &PgPool) -> Self {
+        Self { pool: pool.clone() }
+    }
+
+    /// Queue a build. Fails with a constraint violation if the index for these
+    /// dimensions already has a build in flight.
+    pub async fn create(
+        &self,
+        dimensions: i32,
+        method: VectorIndexMethod,
+        m: Option<i32>,
+        ef_construction: Option<i32>,
+        lists: Option<i32>,
+    ) -> Result<VectorIndexBuild, DatabaseError> {
+        let query = format!(
+            "INSERT INTO vector_index_builds (id, dimensions, method, m, ef_construction, lists) \
+             VALUES ($1, $2, $3, $4, $5, $6) RETURNING {BUILD_COLUMNS}"
+        );
+        let result = sqlx::query_as::<_, VectorIndexBuild>(&query)
+            .bind(crate::utils::generate_ulid())
+            .bind(dimensions)
+            .bind(method)
+            .bind(m)
+            .bind(ef_construction)
+            .bind(lists)
+            .fetch_one(&self.pool)
+            .await;
+
+        match result {
+            Ok(build) => Ok(build),
+            Err(sqlx::Error::Database(e)) if e.is_unique_violation() => {
+                Err(DatabaseError::ConstraintViolation(format!(
+                    "A build of the {}-dimension vector index is already in progress",
+                    dimensions
+                )))
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub async fn get(&self, id: &str) -> Result<Option<VectorIndexBuild>, DatabaseError> {
+        let query = format!("SELECT {BUILD_COLUMNS} FROM vector_index_builds WHERE id = $1");
+        let build = sqlx::query_as::<_, VectorIndexBuild>(&query)
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(build)
+    }
+
+    /// Most recent builds first.
+    pub async fn list_recent(&self, limit: i64) -> Result<Vec<VectorIndexBuild>, DatabaseError> {
+        let query = format!(
+            "SELECT {BUILD_COLUMNS} FROM vector_index_builds ORDER BY created_at DESC LIMIT $1"
+        );
+        let builds = sqlx::query_as::<_, VectorIndexBuild>(&query)
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(builds)
+    }
+
+    pub async fn mark_building(&self, id: &str, backend_pid: i32) -> Result<(), DatabaseError> {
+        sqlx::query(
+            r#"
+            UPDATE vector_index_builds
+            SET status = 'building', backend_pid = $2, started_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(backend_pid)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn mark_swapping(&self, id: &str) -> Result<(), DatabaseError> {
+        sqlx::query("UPDATE vector_index_builds SET status = 'swapping' WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn complete(&self, id: &str) -> Result<(), DatabaseError> {
+        sqlx::query(
+            r#"
+            UPDATE vector_index_builds
+            SET status = 'completed', backend_pid = NULL, completed_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Mark a build failed. The caller drops the partial index.
+    pub async fn fail(&self, id: &str, error_message: &str) -> Result<(), DatabaseError> {
+        sqlx::query(
+            r#"
+            UPDATE vector_index_builds
+            SET status = 'failed', error_message = $2, backend_pid = NULL, completed_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(error_message)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Fail every build still marked in flight, cancelling any CREATE INDEX a
+    /// previous process left running. Returns the failed builds so the caller
+    /// can drop their partial indexes.
+    pub async fn fail_orphaned(&self) -> Result<Vec<VectorIndexBuild>, DatabaseError> {
+        // The pid may have been reused since, so only cancel index builds
+        sqlx::query(
+            r#"
+            SELECT pg_cancel_backend(a.pid)
+            FROM vector_index_builds b
+            JOIN pg_stat_activity a ON a.pid = b.backend_pid
+            WHERE b.status IN ('building', 'swapping')
+              AND a.state = 'active'
+              AND a.query ILIKE 'CREATE INDEX CONCURRENTLY%'
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        let query = format!(
+            r#"
+            UPDATE vector_index_builds
+            SET status = 'failed', error_message = 'Build stopped unexpectedly',
+                backend_pid = NULL, completed_at = NOW()
+            WHERE status IN ('pending', 'building', 'swapping')
+            RETURNING {BUILD_COLUMNS}
+            "#
+        );
+        let builds = sqlx::query_as::<_, VectorIndexBuild>(&query)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(builds)
+    }
+
+    /// Live progress of a running build, if its CREATE INDEX is still in
+    /// progress.
+    pub async fn progress(
+        &self,
+        backend_pid: i32,
+    ) -> Result<Option<VectorIndexBuildProgress>, DatabaseError> {
+        let progress = sqlx::query_as::<_, VectorIndexBuildProgress>(
+            r#"
+            SELECT phase, blocks_done, blocks_total, tuples_done, tuples_total
+            FROM pg_stat_progress_create_index
+            WHERE pid = $1
+            "#,
+        )
+        .bind(backend_pid)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(progress)
+    }
+}
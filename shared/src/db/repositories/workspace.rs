@@ -0,0 +1,115 @@
+use crate::db::error::DatabaseError;
+use crate::models::{DEFAULT_WORKSPACE_ID, SourceType, Workspace};
+use crate::utils::generate_ulid;
+use sqlx::PgPool;
+
+pub struct WorkspaceRepository {
+    pool: PgPool,
+}
+
+impl WorkspaceRepository {
+    pub fn new(pool: &PgPool) -> Self {
+        Self { pool: pool.clone() }
+    }
+
+    pub async fn list(&self) -> Result<Vec<Workspace>, DatabaseError> {
+        let workspaces = sqlx::query_as::<_, Workspace>(
+            "SELECT id, name, slug, created_at, updated_at FROM workspaces ORDER BY created_at",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(workspaces)
+    }
+
+    pub async fn find_by_id(&self, id: &str) -> Result<Option<Workspace>, DatabaseError> {
+        let workspace = sqlx::query_as::<_, Workspace>(
+            "SELECT id, name, slug, created_at, updated_at FROM workspaces WHERE id = $1",
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(workspace)
+    }
+
+    pub async fn create(&self, name: &str, slug: &str) -> Result<Workspace, DatabaseError> {
+        let workspace = sqlx::query_as::<_, Workspace>(
+            r#"
+            INSERT INTO workspaces (id, name, slug)
+            VALUES ($1, $2, $3)
+            RETURNING id, name, slug, created_at, updated_at
+            "#,
+        )
+        .bind(generate_ulid())
+        .bind(name)
+        .bind(slug)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
+                DatabaseError::ConstraintViolation("Workspace slug already exists".to_string())
+            }
+            _ => DatabaseError::from(e),
+        })?;
+
+        Ok(workspace)
+    }
+
+    /// Workspace of the user with `user_id`, or the default workspace when
+    /// there is no such user.
+    pub async fn workspace_id_for_user(&self, user_id: &str) -> Result<String, DatabaseError> {
+        let workspace_id: Option<String> =
+            sqlx::query_scalar("SELECT workspace_id FROM users WHERE id = $1")
+                .bind(user_id)
+                .fetch_optional(&self.pool)
+                .await?;
+
+        Ok(workspace_id.unwrap_or_else(|| DEFAULT_WORKSPACE_ID.to_string()))
+    }
+
+    /// Workspace of the user with `email`. Emails without a user, such as
+    /// service accounts and external share recipients, belong to the default
+    /// workspace.
+    pub async fn workspace_id_for_email(&self, email: &str) -> Result<String, DatabaseError> {
+        let workspace_id: Option<String> = sqlx::query_scalar(
+            "SELECT workspace_id FROM users WHERE lower(email) = lower($1) LIMIT 1",
+        )
+        .bind(email)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(workspace_id.unwrap_or_else(|| DEFAULT_WORKSPACE_ID.to_string()))
+    }
+
+    /// Move a user to another workspace. Sources the user already created stay
+    /// in their workspace, except their chat uploads, which follow them.
+    /// Returns whether the user exists.
+    pub async fn assign_user(
+        &self,
+        user_id: &str,
+        workspace_id: &str,
+    ) -> Result<bool, DatabaseError> {
+        let mut tx = self.pool.begin().await?;
+        let result =
+            sqlx::query("UPDATE users SET workspace_id = $2, updated_at = NOW() WHERE id = $1")
+                .bind(user_id)
+                .bind(workspace_id)
+                .execute(&mut *tx)
+                .await?;
+        sqlx::query(
+            r#"
+            UPDATE sources SET workspace_id = $2, updated_at = NOW()
+            WHERE created_by = $1 AND source_type = $3
+            "#,
+        )
+        .bind(user_id)
+        .bind(workspace_id)
+        .bind(SourceType::ChatUpload)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
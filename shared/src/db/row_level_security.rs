@@ -0,0 +1,82 @@
+//! Row-level security for document reads made on behalf of a user.
+//!
+//! Queries run in a [`begin_as_user`] transaction as the `omni_document_reader`
+//! role, for which Postgres only returns the documents the user may see
+//! (migration 149). This backs up the permission filter the queries already
+//! carry, so a query that leaves it out can't return restricted documents.
+
+use crate::db::error::DatabaseError;
+use sqlx::{PgPool, Postgres, Transaction};
+
+/// Role whose document reads are limited by the `documents_user_visibility`
+/// policy.
+pub const DOCUMENT_READER_ROLE: &str = "omni_document_reader";
+
+/// Start a transaction in which only documents visible to `user_email` can be
+/// read. `user_groups` are the group emails the user belongs to; the user's
+/// domain is added as in `generate_permission_filter`.
+pub async fn begin_as_user(
+    pool: &PgPool,
+    user_email: &str,
+    user_groups: &[String],
+) -> Result<Transaction<'static, Postgres>, DatabaseError> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        "SELECT set_config('omni.user_email', $1, true), set_config('omni.user_groups', $2, true)",
+    )
+    .bind(user_email)
+    .bind(serde_json::Value::from(user_group_terms(user_email, user_groups)).to_string())
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(&format!("SET LOCAL ROLE {DOCUMENT_READER_ROLE}"))
+        .execute(&mut *tx)
+        .await?;
+
+    Ok(tx)
+}
+
+/// Whether the connected user can assume the reader role, i.e. whether
+/// migration 149 could set it up.
+pub async fn is_available(pool: &PgPool) -> Result<bool, DatabaseError> {
+    let available: bool = sqlx::query_scalar(
+        r#"
+        SELECT CASE
+            WHEN EXISTS (SELECT 1 FROM pg_roles WHERE rolname = $1)
+            THEN pg_has_role(current_user, $1, 'MEMBER')
+            ELSE false
+        END
+        "#,
+    )
+    .bind(DOCUMENT_READER_ROLE)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(available)
+}
+
+fn user_group_terms(user_email: &str, user_groups: &[String]) -> Vec<String> {
+    let mut terms = user_groups.to_vec();
+    if let Some(domain) = user_email.split('@').nth(1)
+        && !domain.is_empty()
+    {
+        terms.push(domain.to_string());
+    }
+    terms
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn group_terms_include_the_users_domain() {
+        let groups = vec!["eng@example.com".to_string()];
+        assert_eq!(
+            user_group_terms("ada@example.com", &groups),
+            vec!["eng@example.com".to_string(), "example.com".to_string()]
+        );
+        assert_eq!(user_group_terms("service-account", &groups), groups);
+    }
+}
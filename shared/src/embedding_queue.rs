@@ -12,6 +12,8 @@ pub enum EmbeddingQueueStatus {
     Processing,
     Completed,
     Failed,
+    /// Exhausted its retries; left for an operator to inspect and retry.
+    Quarantined,
 }
 
 impl std::fmt::Display for EmbeddingQueueStatus {
@@ -21,6 +23,7 @@ impl std::fmt::Display for EmbeddingQueueStatus {
             EmbeddingQueueStatus::Processing => write!(f, "processing"),
             EmbeddingQueueStatus::Completed => write!(f, "completed"),
             EmbeddingQueueStatus::Failed => write!(f, "failed"),
+            EmbeddingQueueStatus::Quarantined => write!(f, "quarantined"),
         }
     }
 }
@@ -34,6 +37,7 @@ impl std::str::FromStr for EmbeddingQueueStatus {
             "processing" => Ok(EmbeddingQueueStatus::Processing),
             "completed" => Ok(EmbeddingQueueStatus::Completed),
             "failed" => Ok(EmbeddingQueueStatus::Failed),
+            "quarantined" => Ok(EmbeddingQueueStatus::Quarantined),
             _ => Err(anyhow::anyhow!("Invalid embedding queue status: {}", s)),
         }
     }
@@ -77,6 +81,23 @@ impl sqlx::FromRow<'_, sqlx::postgres::PgRow> for EmbeddingQueueItem {
     }
 }
 
+/// A chunk of a document that kept failing to embed and is now skipped, while
+/// the rest of the document is indexed.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct QuarantinedChunk {
+    pub id: String,
+    pub document_id: String,
+    pub content_id: String,
+    pub chunk_start_offset: i32,
+    pub chunk_end_offset: i32,
+    pub attempts: i32,
+    pub error_message: Option<String>,
+    #[serde(with = "time::serde::iso8601")]
+    pub created_at: sqlx::types::time::OffsetDateTime,
+    #[serde(with = "time::serde::iso8601")]
+    pub updated_at: sqlx::types::time::OffsetDateTime,
+}
+
 #[derive(Clone)]
 pub struct EmbeddingQueue {
     pool: PgPool,
@@ -93,6 +114,15 @@ impl EmbeddingQueue {
     }
 
     pub async fn enqueue(&self, document_id: String) -> Result<Option<String>> {
+        self.enqueue_with_priority(document_id, 0).await
+    }
+
+    /// Enqueue ahead of every item with a lower `priority`; the default is 0.
+    pub async fn enqueue_with_priority(
+        &self,
+        document_id: String,
+        priority: i16,
+    ) -> Result<Option<String>> {
         if !self.provider_repo.has_active_provider().await? {
             return Ok(None);
         }
@@ -101,16 +131,18 @@ impl EmbeddingQueue {
 
         let result = sqlx::query(
             r#"
-            INSERT INTO embedding_queue (id, document_id)
-            SELECT $1, $2
+            INSERT INTO embedding_queue (id, document_id, priority)
+            SELECT $1, $2, $3
             WHERE NOT EXISTS (
                 SELECT 1 FROM embedding_queue
-                WHERE document_id = $2 AND status IN ('pending', 'processing')
+                WHERE document_id = $2 AND namespace IS NULL AND migration_id IS NULL
+                  AND status IN ('pending', 'processing')
             )
             "#,
         )
         .bind(&id)
         .bind(&document_id)
+        .bind(priority)
         .execute(&self.pool)
         .await?;
 
@@ -138,7 +170,8 @@ impl EmbeddingQueue {
                 SELECT $1, $2
                 WHERE NOT EXISTS (
                     SELECT 1 FROM embedding_queue
-                    WHERE document_id = $2 AND status IN ('pending', 'processing')
+                    WHERE document_id = $2 AND namespace IS NULL AND migration_id IS NULL
+                      AND status IN ('pending', 'processing')
                 )
                 "#,
             )
@@ -190,6 +223,8 @@ impl EmbeddingQueue {
                 SELECT 1
                 FROM embedding_queue q
                 WHERE q.document_id = input.document_id
+                  AND q.namespace IS NULL
+                  AND q.migration_id IS NULL
                   AND q.status IN ('pending', 'processing')
             )
             AND NOT EXISTS (
@@ -197,6 +232,7 @@ impl EmbeddingQueue {
                 FROM embeddings e
                 WHERE e.document_id = input.document_id
                   AND e.model_name = provider.model_name
+                  AND e.namespace IS NULL
             )
             RETURNING id
             "#,
@@ -221,7 +257,7 @@ impl EmbeddingQueue {
                 FROM embedding_queue
                 WHERE status = $3
                    OR (status = $4 AND retry_count < 3)
-                ORDER BY created_at
+                ORDER BY priority DESC, created_at
                 LIMIT $1
                 FOR UPDATE SKIP LOCKED
             )
@@ -358,6 +394,88 @@ impl EmbeddingQueue {
         Ok(result.rows_affected() as i64)
     }
 
+    /// Queue items that exhausted their retries, most recently quarantined first.
+    pub async fn list_quarantined_items(&self, limit: i64) -> Result<Vec<EmbeddingQueueItem>> {
+        let items = sqlx::query_as::<_, EmbeddingQueueItem>(
+            r#"
+            SELECT *
+            FROM embedding_queue
+            WHERE status = $1
+            ORDER BY quarantined_at DESC
+            LIMIT $2
+            "#,
+        )
+        .bind(EmbeddingQueueStatus::Quarantined.to_string())
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(items)
+    }
+
+    /// Quarantined chunks, most recently failed first.
+    pub async fn list_quarantined_chunks(&self, limit: i64) -> Result<Vec<QuarantinedChunk>> {
+        let chunks = sqlx::query_as::<_, QuarantinedChunk>(
+            r#"
+            SELECT id, document_id, content_id, chunk_start_offset, chunk_end_offset,
+                   attempts, error_message, created_at, updated_at
+            FROM embedding_chunk_failures
+            WHERE status = 'quarantined'
+            ORDER BY updated_at DESC
+            LIMIT $1
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(chunks)
+    }
+
+    /// Give a quarantined item a fresh set of retries. Returns false if no
+    /// quarantined item has this id.
+    pub async fn retry_quarantined_item(&self, id: &str) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE embedding_queue
+            SET status = $2,
+                retry_count = 0,
+                quarantined_at = NULL,
+                updated_at = CURRENT_TIMESTAMP
+            WHERE id = $1 AND status = $3
+            "#,
+        )
+        .bind(id)
+        .bind(EmbeddingQueueStatus::Pending.to_string())
+        .bind(EmbeddingQueueStatus::Quarantined.to_string())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Release a quarantined chunk and re-queue its document so the chunk is
+    /// attempted again. Returns the document id, or None if no quarantined
+    /// chunk has this id.
+    pub async fn retry_quarantined_chunk(&self, id: &str) -> Result<Option<String>> {
+        let document_id: Option<String> = sqlx::query_scalar(
+            r#"
+            DELETE FROM embedding_chunk_failures
+            WHERE id = $1 AND status = 'quarantined'
+            RETURNING document_id
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        if let Some(document_id) = &document_id {
+            self.enqueue(document_id.clone()).await?;
+        }
+
+        Ok(document_id)
+    }
+
     pub async fn get_queue_stats(&self) -> Result<QueueStats> {
         let row = sqlx::query(
             r#"
@@ -365,7 +483,8 @@ impl EmbeddingQueue {
                 COUNT(*) FILTER (WHERE status = $1) as pending,
                 COUNT(*) FILTER (WHERE status = $2) as processing,
                 COUNT(*) FILTER (WHERE status = $3) as completed,
-                COUNT(*) FILTER (WHERE status = $4) as failed
+                COUNT(*) FILTER (WHERE status = $4) as failed,
+                COUNT(*) FILTER (WHERE status = $5) as quarantined
             FROM embedding_queue
             "#,
         )
@@ -373,6 +492,7 @@ impl EmbeddingQueue {
         .bind(EmbeddingQueueStatus::Processing.to_string())
         .bind(EmbeddingQueueStatus::Completed.to_string())
         .bind(EmbeddingQueueStatus::Failed.to_string())
+        .bind(EmbeddingQueueStatus::Quarantined.to_string())
         .fetch_one(&self.pool)
         .await?;
 
@@ -381,6 +501,7 @@ impl EmbeddingQueue {
             processing: row.try_get::<i64, _>("processing").unwrap_or(0),
             completed: row.try_get::<i64, _>("completed").unwrap_or(0),
             failed: row.try_get::<i64, _>("failed").unwrap_or(0),
+            quarantined: row.try_get::<i64, _>("quarantined").unwrap_or(0),
         })
     }
 }
@@ -391,4 +512,5 @@ pub struct QueueStats {
     pub processing: i64,
     pub completed: i64,
     pub failed: i64,
+    pub quarantined: i64,
 }
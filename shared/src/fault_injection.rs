@@ -0,0 +1,213 @@
+//! Fault injection for resilience testing.
+//!
+//! Built with the `fault-injection` feature, the event queue, the AI client and
+//! object storage consult the injector before each operation, which may delay
+//! it, fail it, or let only part of a batch through. Faults are configured per
+//! component from `FAULT_INJECTION_<QUEUE|AI|STORAGE>_*` environment variables
+//! or at runtime with [`configure`], and nothing is injected until they are.
+//!
+//! Without the feature every hook compiles to a no-op.
+
+use std::fmt;
+use thiserror::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FaultTarget {
+    Queue,
+    Ai,
+    Storage,
+}
+
+impl FaultTarget {
+    pub const ALL: [FaultTarget; 3] = [FaultTarget::Queue, FaultTarget::Ai, FaultTarget::Storage];
+
+    fn env_prefix(&self) -> &'static str {
+        match self {
+            FaultTarget::Queue => "FAULT_INJECTION_QUEUE",
+            FaultTarget::Ai => "FAULT_INJECTION_AI",
+            FaultTarget::Storage => "FAULT_INJECTION_STORAGE",
+        }
+    }
+}
+
+impl fmt::Display for FaultTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FaultTarget::Queue => write!(f, "queue"),
+            FaultTarget::Ai => write!(f, "ai"),
+            FaultTarget::Storage => write!(f, "storage"),
+        }
+    }
+}
+
+/// Faults injected into one component. Rates are probabilities per operation.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FaultConfig {
+    pub error_rate: f64,
+    pub delay_rate: f64,
+    pub delay_ms: u64,
+    /// Batch operations handle a random prefix of their items and then fail,
+    /// or for reads, return fewer items.
+    pub partial_rate: f64,
+}
+
+impl FaultConfig {
+    pub fn from_env(target: FaultTarget) -> Self {
+        fn env_or<T: std::str::FromStr>(key: &str, default: T) -> T {
+            std::env::var(key)
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default)
+        }
+
+        let prefix = target.env_prefix();
+        let defaults = Self::default();
+        Self {
+            error_rate: env_or(&format!("{prefix}_ERROR_RATE"), defaults.error_rate),
+            delay_rate: env_or(&format!("{prefix}_DELAY_RATE"), defaults.delay_rate),
+            delay_ms: env_or(&format!("{prefix}_DELAY_MS"), defaults.delay_ms),
+            partial_rate: env_or(&format!("{prefix}_PARTIAL_RATE"), defaults.partial_rate),
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.error_rate > 0.0
+            || (self.delay_rate > 0.0 && self.delay_ms > 0)
+            || self.partial_rate > 0.0
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("Injected {target} fault in {operation}")]
+pub struct InjectedFault {
+    pub target: FaultTarget,
+    pub operation: &'static str,
+}
+
+#[cfg(feature = "fault-injection")]
+mod injector {
+    use super::{FaultConfig, FaultTarget, InjectedFault};
+    use rand::Rng;
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::{LazyLock, RwLock};
+    use std::time::Duration;
+    use tracing::warn;
+
+    static FAULTS: LazyLock<RwLock<HashMap<FaultTarget, FaultConfig>>> = LazyLock::new(|| {
+        let faults = FaultTarget::ALL
+            .into_iter()
+            .map(|target| (target, FaultConfig::from_env(target)))
+            .filter(|(_, config)| config.is_active())
+            .collect::<HashMap<_, _>>();
+        for (target, config) in &faults {
+            warn!("Fault injection enabled for {}: {:?}", target, config);
+        }
+        RwLock::new(faults)
+    });
+
+    static INJECTED: [AtomicU64; 3] = [AtomicU64::new(0), AtomicU64::new(0), AtomicU64::new(0)];
+
+    fn config(target: FaultTarget) -> Option<FaultConfig> {
+        FAULTS.read().unwrap().get(&target).cloned()
+    }
+
+    fn roll(rate: f64) -> bool {
+        rate > 0.0 && rand::thread_rng().gen_bool(rate.min(1.0))
+    }
+
+    fn record(target: FaultTarget) {
+        INJECTED[target as usize].fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Replace the faults injected into `target`, overriding its environment.
+    pub fn configure(target: FaultTarget, config: FaultConfig) {
+        let mut faults = FAULTS.write().unwrap();
+        if config.is_active() {
+            faults.insert(target, config);
+        } else {
+            faults.remove(&target);
+        }
+    }
+
+    /// Stop injecting faults into every component.
+    pub fn reset() {
+        FAULTS.write().unwrap().clear();
+    }
+
+    /// Faults injected into `target` so far, including delays.
+    pub fn injected_count(target: FaultTarget) -> u64 {
+        INJECTED[target as usize].load(Ordering::Relaxed)
+    }
+
+    /// Delay or fail `operation` as configured for `target`.
+    pub async fn inject(target: FaultTarget, operation: &'static str) -> Result<(), InjectedFault> {
+        let Some(config) = config(target) else {
+            return Ok(());
+        };
+        if roll(config.delay_rate) && config.delay_ms > 0 {
+            record(target);
+            tokio::time::sleep(Duration::from_millis(config.delay_ms)).await;
+        }
+        if roll(config.error_rate) {
+            record(target);
+            warn!("Injecting {} fault in {}", target, operation);
+            return Err(InjectedFault { target, operation });
+        }
+        Ok(())
+    }
+
+    /// How many of a batch's `len` items `operation` gets to handle.
+    pub fn partial_len(target: FaultTarget, operation: &'static str, len: usize) -> usize {
+        let Some(config) = config(target) else {
+            return len;
+        };
+        if len == 0 || !roll(config.partial_rate) {
+            return len;
+        }
+        record(target);
+        let kept = rand::thread_rng().gen_range(0..len);
+        warn!(
+            "Injecting partial {} failure in {}: {} of {} items",
+            target, operation, kept, len
+        );
+        kept
+    }
+}
+
+#[cfg(feature = "fault-injection")]
+pub use injector::{configure, inject, injected_count, partial_len, reset};
+
+/// Delay or fail `operation` as configured for `target`.
+#[cfg(not(feature = "fault-injection"))]
+#[inline(always)]
+pub async fn inject(_target: FaultTarget, _operation: &'static str) -> Result<(), InjectedFault> {
+    Ok(())
+}
+
+/// How many of a batch's `len` items `operation` gets to handle.
+#[cfg(not(feature = "fault-injection"))]
+#[inline(always)]
+pub fn partial_len(_target: FaultTarget, _operation: &'static str, len: usize) -> usize {
+    len
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fault_config_is_active() {
+        assert!(!FaultConfig::default().is_active());
+        let delay_without_duration = FaultConfig {
+            delay_rate: 1.0,
+            ..Default::default()
+        };
+        assert!(!delay_without_duration.is_active());
+        let partial = FaultConfig {
+            partial_rate: 0.5,
+            ..Default::default()
+        };
+        assert!(partial.is_active());
+    }
+}
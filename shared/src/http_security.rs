@@ -0,0 +1,403 @@
+//! CORS policy and security response headers shared by every HTTP service.
+//!
+//! Settings come from the environment. Each setting can be given per service
+//! (`SEARCHER_CORS_ALLOWED_ORIGINS`) or for all services
+//! (`CORS_ALLOWED_ORIGINS`); the service-specific variable wins. Services sit
+//! behind the web app, which calls them server-side, so by default no origin
+//! is allowed cross-origin access.
+//!
+//! | Setting                            | Default                                      |
+//! |------------------------------------|----------------------------------------------|
+//! | `CORS_ALLOWED_ORIGINS`             | none; `*` allows any origin                  |
+//! | `CORS_ALLOWED_METHODS`             | `GET,POST,PUT,PATCH,DELETE`                  |
+//! | `CORS_ALLOWED_HEADERS`             | `authorization,content-type`; `*` allows any |
+//! | `CORS_ALLOW_CREDENTIALS`           | `false`                                      |
+//! | `CORS_MAX_AGE_SECONDS`             | `600`                                        |
+//! | `SECURITY_HEADERS_ENABLED`         | `true`                                       |
+//! | `SECURITY_HSTS_MAX_AGE_SECONDS`    | `0` (no `Strict-Transport-Security`)         |
+//! | `SECURITY_CONTENT_SECURITY_POLICY` | `default-src 'none'; frame-ancestors 'none'` |
+//!
+//! Empty variables count as unset. Malformed entries are logged and ignored
+//! rather than failing startup.
+
+use futures_util::future::BoxFuture;
+use http::{HeaderName, HeaderValue, Method, Request, Response, header};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tower::layer::util::{Identity, Stack};
+use tower::{Layer, Service, ServiceBuilder};
+use tower_http::cors::{AllowHeaders, AllowOrigin, CorsLayer};
+use tracing::warn;
+
+const DEFAULT_ALLOWED_METHODS: &str = "GET,POST,PUT,PATCH,DELETE";
+const DEFAULT_ALLOWED_HEADERS: &str = "authorization,content-type";
+const DEFAULT_MAX_AGE_SECONDS: u64 = 600;
+const DEFAULT_CONTENT_SECURITY_POLICY: &str = "default-src 'none'; frame-ancestors 'none'";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AllowedOrigins {
+    Any,
+    List(Vec<HeaderValue>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AllowedHeaders {
+    Any,
+    List(Vec<HeaderName>),
+}
+
+#[derive(Debug, Clone)]
+pub struct HttpSecurityConfig {
+    pub allowed_origins: AllowedOrigins,
+    pub allowed_methods: Vec<Method>,
+    pub allowed_headers: AllowedHeaders,
+    /// Ignored with `AllowedOrigins::Any`, which browsers never combine with
+    /// credentials.
+    pub allow_credentials: bool,
+    pub max_age: Duration,
+    pub security_headers_enabled: bool,
+    /// `Strict-Transport-Security` max-age; zero leaves the header off, for
+    /// deployments that do not terminate TLS in front of the service.
+    pub hsts_max_age_seconds: u64,
+    pub content_security_policy: String,
+}
+
+impl Default for HttpSecurityConfig {
+    fn default() -> Self {
+        Self {
+            allowed_origins: AllowedOrigins::List(Vec::new()),
+            allowed_methods: parse_list(DEFAULT_ALLOWED_METHODS, "CORS_ALLOWED_METHODS"),
+            allowed_headers: AllowedHeaders::List(parse_list(
+                DEFAULT_ALLOWED_HEADERS,
+                "CORS_ALLOWED_HEADERS",
+            )),
+            allow_credentials: false,
+            max_age: Duration::from_secs(DEFAULT_MAX_AGE_SECONDS),
+            security_headers_enabled: true,
+            hsts_max_age_seconds: 0,
+            content_security_policy: DEFAULT_CONTENT_SECURITY_POLICY.to_string(),
+        }
+    }
+}
+
+impl HttpSecurityConfig {
+    /// Settings for `service`, the prefix of its service-specific variables
+    /// (e.g. `"SEARCHER"`).
+    pub fn from_env(service: &str) -> Self {
+        Self::from_lookup(service, |key| std::env::var(key).ok())
+    }
+
+    fn from_lookup(service: &str, lookup: impl Fn(&str) -> Option<String>) -> Self {
+        let settings = ServiceSettings { service, lookup };
+
+        let defaults = Self::default();
+        let allowed_origins = match settings.get("CORS_ALLOWED_ORIGINS") {
+            Some(origins) if origins == "*" => AllowedOrigins::Any,
+            Some(origins) => AllowedOrigins::List(parse_list(&origins, "CORS_ALLOWED_ORIGINS")),
+            None => defaults.allowed_origins,
+        };
+        let allowed_headers = match settings.get("CORS_ALLOWED_HEADERS") {
+            Some(headers) if headers == "*" => AllowedHeaders::Any,
+            Some(headers) => AllowedHeaders::List(parse_list(&headers, "CORS_ALLOWED_HEADERS")),
+            None => defaults.allowed_headers,
+        };
+
+        let mut allow_credentials = settings
+            .parse("CORS_ALLOW_CREDENTIALS")
+            .unwrap_or(defaults.allow_credentials);
+        if allow_credentials && allowed_origins == AllowedOrigins::Any {
+            warn!(
+                "{}: CORS credentials cannot be allowed for any origin; list the origins instead",
+                service
+            );
+            allow_credentials = false;
+        }
+
+        Self {
+            allowed_origins,
+            allowed_methods: settings
+                .get("CORS_ALLOWED_METHODS")
+                .map(|methods| parse_list(&methods.to_uppercase(), "CORS_ALLOWED_METHODS"))
+                .unwrap_or(defaults.allowed_methods),
+            allowed_headers,
+            allow_credentials,
+            max_age: settings
+                .parse("CORS_MAX_AGE_SECONDS")
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.max_age),
+            security_headers_enabled: settings
+                .parse("SECURITY_HEADERS_ENABLED")
+                .unwrap_or(defaults.security_headers_enabled),
+            hsts_max_age_seconds: settings
+                .parse("SECURITY_HSTS_MAX_AGE_SECONDS")
+                .unwrap_or(defaults.hsts_max_age_seconds),
+            content_security_policy: settings
+                .get("SECURITY_CONTENT_SECURITY_POLICY")
+                .unwrap_or(defaults.content_security_policy),
+        }
+    }
+
+    pub fn cors_layer(&self) -> CorsLayer {
+        let allow_origin = match &self.allowed_origins {
+            AllowedOrigins::Any => AllowOrigin::any(),
+            AllowedOrigins::List(origins) => AllowOrigin::list(origins.iter().cloned()),
+        };
+        // Mirroring the requested headers allows any of them, and unlike a
+        // wildcard can be combined with credentials.
+        let allow_headers = match &self.allowed_headers {
+            AllowedHeaders::Any => AllowHeaders::mirror_request(),
+            AllowedHeaders::List(headers) => AllowHeaders::list(headers.iter().cloned()),
+        };
+
+        CorsLayer::new()
+            .allow_origin(allow_origin)
+            .allow_methods(self.allowed_methods.clone())
+            .allow_headers(allow_headers)
+            .allow_credentials(self.allow_credentials)
+            .max_age(self.max_age)
+    }
+
+    pub fn security_headers_layer(&self) -> SecurityHeadersLayer {
+        let mut headers = Vec::new();
+        if self.security_headers_enabled {
+            headers.push((
+                header::X_CONTENT_TYPE_OPTIONS,
+                HeaderValue::from_static("nosniff"),
+            ));
+            headers.push((header::X_FRAME_OPTIONS, HeaderValue::from_static("DENY")));
+            headers.push((
+                header::REFERRER_POLICY,
+                HeaderValue::from_static("no-referrer"),
+            ));
+            if !self.content_security_policy.is_empty() {
+                match HeaderValue::from_str(&self.content_security_policy) {
+                    Ok(csp) => headers.push((header::CONTENT_SECURITY_POLICY, csp)),
+                    Err(_) => warn!(
+                        "Ignoring invalid SECURITY_CONTENT_SECURITY_POLICY '{}'",
+                        self.content_security_policy
+                    ),
+                }
+            }
+            if self.hsts_max_age_seconds > 0 {
+                let hsts = format!("max-age={}; includeSubDomains", self.hsts_max_age_seconds);
+                if let Ok(hsts) = HeaderValue::from_str(&hsts) {
+                    headers.push((header::STRICT_TRANSPORT_SECURITY, hsts));
+                }
+            }
+        }
+        SecurityHeadersLayer {
+            headers: Arc::new(headers),
+        }
+    }
+
+    /// CORS handling and security headers, to add to a service's
+    /// `ServiceBuilder` in place of `CorsLayer::permissive()`.
+    pub fn layer(&self) -> ServiceBuilder<Stack<CorsLayer, Stack<SecurityHeadersLayer, Identity>>> {
+        ServiceBuilder::new()
+            .layer(self.security_headers_layer())
+            .layer(self.cors_layer())
+    }
+}
+
+struct ServiceSettings<'a, F> {
+    service: &'a str,
+    lookup: F,
+}
+
+impl<F: Fn(&str) -> Option<String>> ServiceSettings<'_, F> {
+    fn get(&self, key: &str) -> Option<String> {
+        (self.lookup)(&format!("{}_{}", self.service, key))
+            .or_else(|| (self.lookup)(key))
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    }
+
+    fn parse<T: std::str::FromStr>(&self, key: &str) -> Option<T> {
+        let value = self.get(key)?;
+        match value.parse() {
+            Ok(parsed) => Some(parsed),
+            Err(_) => {
+                warn!("Ignoring invalid {} value '{}'", key, value);
+                None
+            }
+        }
+    }
+}
+
+fn parse_list<T: std::str::FromStr>(value: &str, key: &str) -> Vec<T> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .filter_map(|item| match item.parse() {
+            Ok(parsed) => Some(parsed),
+            Err(_) => {
+                warn!("Ignoring invalid {} entry '{}'", key, item);
+                None
+            }
+        })
+        .collect()
+}
+
+/// Adds a fixed set of headers to every response that does not already set
+/// them.
+#[derive(Debug, Clone)]
+pub struct SecurityHeadersLayer {
+    headers: Arc<Vec<(HeaderName, HeaderValue)>>,
+}
+
+impl<S> Layer<S> for SecurityHeadersLayer {
+    type Service = SecurityHeaders<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        SecurityHeaders {
+            inner,
+            headers: self.headers.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SecurityHeaders<S> {
+    inner: S,
+    headers: Arc<Vec<(HeaderName, HeaderValue)>>,
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for SecurityHeaders<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<ReqBody>) -> Self::Future {
+        let future = self.inner.call(request);
+        let headers = self.headers.clone();
+        Box::pin(async move {
+            let mut response = future.await?;
+            for (name, value) in headers.iter() {
+                response
+                    .headers_mut()
+                    .entry(name)
+                    .or_insert_with(|| value.clone());
+            }
+            Ok(response)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Router, body::Body, routing::get};
+    use std::collections::HashMap;
+    use tower::ServiceExt;
+
+    fn config(vars: &[(&str, &str)]) -> HttpSecurityConfig {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        HttpSecurityConfig::from_lookup("SEARCHER", |key| vars.get(key).cloned())
+    }
+
+    fn app(config: &HttpSecurityConfig) -> Router {
+        Router::new()
+            .route("/search", get(|| async { "ok" }))
+            .layer(config.layer())
+    }
+
+    async fn send(app: Router, method: Method, origin: &str) -> Response<axum::body::Body> {
+        let request = Request::builder()
+            .method(method)
+            .uri("/search")
+            .header(header::ORIGIN, origin)
+            .header(header::ACCESS_CONTROL_REQUEST_METHOD, "POST")
+            .body(Body::empty())
+            .unwrap();
+        app.oneshot(request).await.unwrap()
+    }
+
+    #[test]
+    fn test_service_settings_override_shared_ones() {
+        let config = config(&[
+            ("CORS_ALLOWED_ORIGINS", "https://shared.example.com"),
+            (
+                "SEARCHER_CORS_ALLOWED_ORIGINS",
+                "https://omni.example.com, https://bad\u{1}.example.com",
+            ),
+            ("CORS_ALLOWED_METHODS", "get,post"),
+            ("CORS_MAX_AGE_SECONDS", "soon"),
+        ]);
+        assert_eq!(
+            config.allowed_origins,
+            AllowedOrigins::List(vec![HeaderValue::from_static("https://omni.example.com")])
+        );
+        assert_eq!(config.allowed_methods, vec![Method::GET, Method::POST]);
+        assert_eq!(config.max_age, Duration::from_secs(DEFAULT_MAX_AGE_SECONDS));
+    }
+
+    #[test]
+    fn test_credentials_never_allowed_for_any_origin() {
+        let config = config(&[
+            ("CORS_ALLOWED_ORIGINS", "*"),
+            ("CORS_ALLOW_CREDENTIALS", "true"),
+        ]);
+        assert_eq!(config.allowed_origins, AllowedOrigins::Any);
+        assert!(!config.allow_credentials);
+        // Would panic when building the layer if credentials were kept
+        let _ = config.cors_layer();
+    }
+
+    #[tokio::test]
+    async fn test_default_rejects_cross_origin_and_sets_security_headers() {
+        let response = send(
+            app(&config(&[])),
+            Method::OPTIONS,
+            "https://evil.example.com",
+        )
+        .await;
+        assert!(
+            response
+                .headers()
+                .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+                .is_none()
+        );
+
+        let response = send(app(&config(&[])), Method::GET, "https://evil.example.com").await;
+        let headers = response.headers();
+        assert_eq!(headers[header::X_CONTENT_TYPE_OPTIONS], "nosniff");
+        assert_eq!(headers[header::X_FRAME_OPTIONS], "DENY");
+        assert_eq!(
+            headers[header::CONTENT_SECURITY_POLICY],
+            DEFAULT_CONTENT_SECURITY_POLICY
+        );
+        assert!(headers.get(header::STRICT_TRANSPORT_SECURITY).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_allowed_origin_gets_cors_headers() {
+        let config = config(&[
+            ("CORS_ALLOWED_ORIGINS", "https://omni.example.com"),
+            ("SECURITY_HSTS_MAX_AGE_SECONDS", "31536000"),
+            ("SECURITY_HEADERS_ENABLED", "true"),
+        ]);
+
+        let response = send(app(&config), Method::OPTIONS, "https://omni.example.com").await;
+        assert_eq!(
+            response.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+            "https://omni.example.com"
+        );
+        assert_eq!(
+            response.headers()[header::STRICT_TRANSPORT_SECURITY],
+            "max-age=31536000; includeSubDomains"
+        );
+    }
+}
@@ -1,5 +1,7 @@
+pub mod audit;
 pub mod clients;
 pub mod config;
+pub mod config_loader;
 pub mod constants;
 pub mod content_chunker;
 pub mod content_extractor;
@@ -7,13 +9,18 @@ pub mod content_storage;
 pub mod db;
 pub mod embedding_queue;
 pub mod encryption;
+pub mod fault_injection;
+pub mod http_security;
 pub mod models;
 pub mod queue;
+pub mod ranking;
 pub mod rate_limiter;
+pub mod search_cache;
 pub mod service_auth;
 pub mod storage;
 pub mod telemetry;
 pub mod traits;
+pub mod typeahead_updates;
 pub mod utils;
 
 pub mod test_utils;
@@ -30,11 +37,11 @@ pub use db::repositories::{
     SourceRepository, TitleEntry, UserRepository,
 };
 pub use db::{DatabaseError, DatabasePool};
-pub use embedding_queue::{EmbeddingQueue, EmbeddingQueueItem};
+pub use embedding_queue::{EmbeddingQueue, EmbeddingQueueItem, QuarantinedChunk};
 pub use encryption::{EncryptedData, EncryptionService};
 pub use models::*;
 pub use queue::{EventQueue, QueueStats, QueueSummary};
-pub use rate_limiter::{RateLimiter, RetryableError};
+pub use rate_limiter::{AdaptiveRateConfig, RateLimitStats, RateLimiter, RetryableError};
 pub use service_auth::{ServiceAuth, create_service_auth};
 pub use storage::{
     ContentMetadata as StorageContentMetadata, ObjectStorage, StorageError,
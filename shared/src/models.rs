@@ -41,6 +41,29 @@ pub struct User {
     pub updated_at: OffsetDateTime,
     #[serde(with = "time::serde::iso8601::option")]
     pub last_login_at: Option<OffsetDateTime>,
+    #[serde(default = "default_workspace_id")]
+    pub workspace_id: String,
+}
+
+/// Workspace that users and sources belong to unless assigned to another one.
+/// Created by migration 148; single-tenant deployments only use this one.
+pub const DEFAULT_WORKSPACE_ID: &str = "00000000000000000000000000";
+
+fn default_workspace_id() -> String {
+    DEFAULT_WORKSPACE_ID.to_string()
+}
+
+/// A tenant of the deployment. Users only see documents of sources in their
+/// own workspace.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Workspace {
+    pub id: String,
+    pub name: String,
+    pub slug: String,
+    #[serde(with = "time::serde::iso8601")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::iso8601")]
+    pub updated_at: OffsetDateTime,
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, sqlx::Type, PartialEq)]
@@ -85,6 +108,8 @@ pub struct Source {
     #[serde(with = "time::serde::iso8601")]
     pub updated_at: OffsetDateTime,
     pub created_by: String,
+    #[serde(default = "default_workspace_id")]
+    pub workspace_id: String,
 }
 
 impl Source {
@@ -159,6 +184,8 @@ pub struct Embedding {
     pub embedding: Vector,
     pub model_name: String,
     pub dimensions: i16,
+    pub page_number: Option<i32>, // 1-based page the chunk starts on, for paged documents
+    pub heading_path: Option<String>, // Headings enclosing the chunk start, e.g. "A > B"
     #[serde(with = "time::serde::iso8601")]
     pub created_at: OffsetDateTime,
 }
@@ -170,6 +197,7 @@ pub enum SourceType {
     GoogleDrive,
     Gmail,
     GoogleChat,
+    GoogleCalendar,
     Confluence,
     Jira,
     Slack,
@@ -192,6 +220,12 @@ pub enum SourceType {
     Nextcloud,
     GoogleAds,
     Darwinbox,
+    Zendesk,
+    S3,
+    /// Documents POSTed by external systems to the push connector.
+    Push,
+    /// Files uploaded into chats; one hidden source per user.
+    ChatUpload,
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, sqlx::Type, PartialEq)]
@@ -217,6 +251,34 @@ pub enum ServiceProvider {
     #[serde(rename = "google_ads")]
     GoogleAds,
     Darwinbox,
+    Zendesk,
+    S3,
+}
+
+impl ServiceProvider {
+    /// The provider key, as stored in `service_credentials.provider` and
+    /// `connector_configs.provider`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Google => "google",
+            Self::Slack => "slack",
+            Self::Atlassian => "atlassian",
+            Self::Github => "github",
+            Self::Microsoft => "microsoft",
+            Self::Notion => "notion",
+            Self::Hubspot => "hubspot",
+            Self::Fireflies => "fireflies",
+            Self::Imap => "imap",
+            Self::Clickup => "clickup",
+            Self::Linear => "linear",
+            Self::PaperlessNgx => "paperless_ngx",
+            Self::Nextcloud => "nextcloud",
+            Self::GoogleAds => "google_ads",
+            Self::Darwinbox => "darwinbox",
+            Self::Zendesk => "zendesk",
+            Self::S3 => "s3",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, sqlx::Type, PartialEq)]
@@ -891,6 +953,19 @@ pub enum ConnectorEvent {
         group_name: Option<String>,
         member_emails: Vec<String>,
     },
+    /// Replaces a document's permissions without touching its content, so
+    /// sharing changes don't need a full re-index. When `descendants_path` is
+    /// set, every document of the source whose `metadata.path` lies under it
+    /// gets the same permissions — use this for folders whose items inherit
+    /// their access from the folder.
+    PermissionsChanged {
+        sync_run_id: String,
+        source_id: String,
+        document_id: String,
+        permissions: DocumentPermissions,
+        #[serde(default)]
+        descendants_path: Option<String>,
+    },
 }
 
 impl ConnectorEvent {
@@ -900,6 +975,7 @@ impl ConnectorEvent {
             ConnectorEvent::DocumentUpdated { sync_run_id, .. } => sync_run_id,
             ConnectorEvent::DocumentDeleted { sync_run_id, .. } => sync_run_id,
             ConnectorEvent::GroupMembershipSync { sync_run_id, .. } => sync_run_id,
+            ConnectorEvent::PermissionsChanged { sync_run_id, .. } => sync_run_id,
         }
     }
 
@@ -909,6 +985,7 @@ impl ConnectorEvent {
             ConnectorEvent::DocumentUpdated { source_id, .. } => source_id,
             ConnectorEvent::DocumentDeleted { source_id, .. } => source_id,
             ConnectorEvent::GroupMembershipSync { source_id, .. } => source_id,
+            ConnectorEvent::PermissionsChanged { source_id, .. } => source_id,
         }
     }
 
@@ -918,6 +995,7 @@ impl ConnectorEvent {
             ConnectorEvent::DocumentUpdated { document_id, .. } => document_id,
             ConnectorEvent::DocumentDeleted { document_id, .. } => document_id,
             ConnectorEvent::GroupMembershipSync { group_email, .. } => group_email,
+            ConnectorEvent::PermissionsChanged { document_id, .. } => document_id,
         }
     }
 }
@@ -951,6 +1029,8 @@ pub struct ChunkResult {
     pub chunk_start_offset: i32,
     pub chunk_end_offset: i32,
     pub chunk_index: i32,
+    pub page_number: Option<i32>,
+    pub heading_path: Option<String>,
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, sqlx::Type, PartialEq, Eq, Hash)]
@@ -1046,6 +1126,124 @@ pub enum SyncStatus {
     Cancelled,
 }
 
+/// Broad cause of a connector error, so that admins can tell failures that
+/// need their attention (expired credentials, revoked access, bad
+/// configuration) from ones that resolve on their own.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, sqlx::Type, PartialEq, Eq, Hash)]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectorErrorCategory {
+    /// Credentials are missing, invalid, expired or revoked.
+    Authentication,
+    /// Credentials are valid but lack access to what the connector needs.
+    Permission,
+    /// The source's configuration is invalid or refers to something that
+    /// doesn't exist.
+    Configuration,
+    /// The upstream service throttled the connector.
+    RateLimit,
+    /// The upstream service was unreachable or timed out.
+    Network,
+    /// The upstream service returned a server error.
+    Upstream,
+    Unknown,
+}
+
+impl ConnectorErrorCategory {
+    /// Whether the error persists until someone fixes the source's
+    /// credentials, access or configuration.
+    pub fn requires_action(&self) -> bool {
+        matches!(
+            self,
+            ConnectorErrorCategory::Authentication
+                | ConnectorErrorCategory::Permission
+                | ConnectorErrorCategory::Configuration
+        )
+    }
+
+    /// Best guess at the category of an uncategorized error message, such as
+    /// one a connector failed a sync with.
+    pub fn classify(message: &str) -> Self {
+        let message = message.to_lowercase();
+        let tokens: Vec<&str> = message
+            .split(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+            .filter(|t| !t.is_empty())
+            .collect();
+
+        CONNECTOR_ERROR_HINTS
+            .iter()
+            .find(|(_, hint_tokens, hint_phrases)| {
+                tokens.iter().any(|t| hint_tokens.contains(t))
+                    || hint_phrases.iter().any(|p| message.contains(p))
+            })
+            .map(|(category, _, _)| *category)
+            .unwrap_or(ConnectorErrorCategory::Unknown)
+    }
+}
+
+/// Words (matched whole) and phrases that hint at each error category, in the
+/// order they are checked.
+const CONNECTOR_ERROR_HINTS: &[(ConnectorErrorCategory, &[&str], &[&str])] = &[
+    (
+        ConnectorErrorCategory::RateLimit,
+        &["429", "ratelimit", "rate_limit", "throttled"],
+        &["rate limit", "too many requests", "quota"],
+    ),
+    (
+        ConnectorErrorCategory::Authentication,
+        &[
+            "401",
+            "unauthorized",
+            "unauthenticated",
+            "invalid_grant",
+            "invalid_auth",
+        ],
+        &[
+            "authentication",
+            "expired",
+            "revoked",
+            "credentials",
+            "invalid token",
+            "api key",
+        ],
+    ),
+    (
+        ConnectorErrorCategory::Permission,
+        &["403", "forbidden"],
+        &[
+            "permission",
+            "access denied",
+            "insufficient scope",
+            "not authorized",
+        ],
+    ),
+    (
+        ConnectorErrorCategory::Upstream,
+        &["500", "502", "503", "504"],
+        &[
+            "internal server error",
+            "bad gateway",
+            "service unavailable",
+        ],
+    ),
+    (
+        ConnectorErrorCategory::Network,
+        &["timeout"],
+        &[
+            "timed out",
+            "connection refused",
+            "connection reset",
+            "dns error",
+            "error sending request",
+        ],
+    ),
+    (
+        ConnectorErrorCategory::Configuration,
+        &["404", "config", "configuration", "missing", "invalid"],
+        &["not found", "does not exist"],
+    ),
+];
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct SyncRun {
     pub id: String,
@@ -1385,6 +1583,7 @@ mod tests {
             created_at: OffsetDateTime::now_utc(),
             updated_at: OffsetDateTime::now_utc(),
             created_by: "admin".to_string(),
+            workspace_id: DEFAULT_WORKSPACE_ID.to_string(),
         }
     }
 
@@ -1536,4 +1735,60 @@ mod tests {
         assert_eq!(deleted.source_id(), "src-2");
         assert_eq!(deleted.document_id(), "doc-2");
     }
+
+    #[test]
+    fn test_classify_connector_errors() {
+        let cases = [
+            (
+                "Token has been expired or revoked: invalid_grant",
+                ConnectorErrorCategory::Authentication,
+            ),
+            (
+                "HTTP 401 Unauthorized from https://api.example.com",
+                ConnectorErrorCategory::Authentication,
+            ),
+            (
+                "Missing 'api_key' in credentials",
+                ConnectorErrorCategory::Authentication,
+            ),
+            (
+                "Google API error 403: insufficient permissions",
+                ConnectorErrorCategory::Permission,
+            ),
+            (
+                "Rate limited: 429 Too Many Requests",
+                ConnectorErrorCategory::RateLimit,
+            ),
+            (
+                "Upstream returned 503 Service Unavailable",
+                ConnectorErrorCategory::Upstream,
+            ),
+            (
+                "error sending request: operation timed out",
+                ConnectorErrorCategory::Network,
+            ),
+            (
+                "Base path does not exist: /data/docs",
+                ConnectorErrorCategory::Configuration,
+            ),
+            ("Invalid JQL filter", ConnectorErrorCategory::Configuration),
+            ("something odd happened", ConnectorErrorCategory::Unknown),
+        ];
+
+        for (message, expected) in cases {
+            assert_eq!(
+                ConnectorErrorCategory::classify(message),
+                expected,
+                "message: {}",
+                message
+            );
+        }
+        // Numbers only count as status codes when they stand alone
+        assert_eq!(
+            ConnectorErrorCategory::classify("Processed 4010 files"),
+            ConnectorErrorCategory::Unknown
+        );
+        assert!(ConnectorErrorCategory::Authentication.requires_action());
+        assert!(!ConnectorErrorCategory::RateLimit.requires_action());
+    }
 }
@@ -1,3 +1,4 @@
+use crate::fault_injection::{self, FaultTarget, InjectedFault};
 use crate::utils::generate_ulid;
 use anyhow::Result;
 use sqlx::{PgPool, Row};
@@ -12,6 +13,7 @@ fn event_type_str(event: &ConnectorEvent) -> &'static str {
         ConnectorEvent::DocumentUpdated { .. } => "document_updated",
         ConnectorEvent::DocumentDeleted { .. } => "document_deleted",
         ConnectorEvent::GroupMembershipSync { .. } => "group_membership_sync",
+        ConnectorEvent::PermissionsChanged { .. } => "permissions_changed",
     }
 }
 
@@ -26,6 +28,7 @@ impl EventQueue {
     }
 
     pub async fn enqueue(&self, source_id: &str, event: &ConnectorEvent) -> Result<String> {
+        fault_injection::inject(FaultTarget::Queue, "enqueue").await?;
         let id = generate_ulid();
         let event_type = event_type_str(event);
 
@@ -54,6 +57,10 @@ impl EventQueue {
         if events.is_empty() {
             return Ok(Vec::new());
         }
+        fault_injection::inject(FaultTarget::Queue, "enqueue_batch").await?;
+        let accepted =
+            fault_injection::partial_len(FaultTarget::Queue, "enqueue_batch", events.len());
+        let (events, rejected) = events.split_at(accepted);
 
         let mut ids: Vec<String> = Vec::with_capacity(events.len());
         let mut sync_run_ids: Vec<String> = Vec::with_capacity(events.len());
@@ -83,6 +90,13 @@ impl EventQueue {
         .execute(&self.pool)
         .await?;
 
+        if !rejected.is_empty() {
+            return Err(InjectedFault {
+                target: FaultTarget::Queue,
+                operation: "enqueue_batch",
+            }
+            .into());
+        }
         Ok(ids)
     }
 
@@ -96,6 +110,7 @@ impl EventQueue {
         batch_size: i32,
         max_bytes: i64,
     ) -> Result<Vec<ConnectorEventQueueItem>> {
+        fault_injection::inject(FaultTarget::Queue, "dequeue_batch").await?;
         // Dequeue the oldest pending events across all sync_runs, bounded by both
         // count and referenced content size. Downstream processing groups by
         // sync_run_id for progress updates.
@@ -168,6 +183,7 @@ impl EventQueue {
         sync_type: SyncType,
         max_bytes: i64,
     ) -> Result<Vec<ConnectorEventQueueItem>> {
+        fault_injection::inject(FaultTarget::Queue, "dequeue_batch").await?;
         let rows = sqlx::query(
             r#"
             WITH candidates AS (
@@ -238,6 +254,7 @@ impl EventQueue {
         batch_size: i32,
         max_bytes: i64,
     ) -> Result<Vec<ConnectorEventQueueItem>> {
+        fault_injection::inject(FaultTarget::Queue, "dequeue_batch").await?;
         let rows = sqlx::query(
             r#"
             WITH candidates AS (
@@ -321,10 +338,14 @@ impl EventQueue {
                 error_message: row.get("error_message"),
             });
         }
+        // Dropped items stay claimed, as when a worker dies mid-batch
+        let kept = fault_injection::partial_len(FaultTarget::Queue, "dequeue_batch", events.len());
+        events.truncate(kept);
         events
     }
 
     pub async fn mark_completed(&self, event_id: &str) -> Result<()> {
+        fault_injection::inject(FaultTarget::Queue, "mark_completed").await?;
         sqlx::query(
             r#"
             UPDATE connector_events_queue
@@ -340,6 +361,7 @@ impl EventQueue {
     }
 
     pub async fn mark_failed(&self, event_id: &str, error: &str) -> Result<()> {
+        fault_injection::inject(FaultTarget::Queue, "mark_failed").await?;
         let result = sqlx::query(
             r#"
             UPDATE connector_events_queue
@@ -571,6 +593,7 @@ impl EventQueue {
         if event_ids.is_empty() {
             return Ok(0);
         }
+        fault_injection::inject(FaultTarget::Queue, "mark_events_completed_batch").await?;
 
         let result = sqlx::query(
             r#"
@@ -593,6 +616,7 @@ impl EventQueue {
         if event_ids_with_errors.is_empty() {
             return Ok(0);
         }
+        fault_injection::inject(FaultTarget::Queue, "mark_events_failed_batch").await?;
 
         let event_ids: Vec<String> = event_ids_with_errors
             .iter()
@@ -631,6 +655,7 @@ impl EventQueue {
         if event_ids_with_errors.is_empty() {
             return Ok(0);
         }
+        fault_injection::inject(FaultTarget::Queue, "mark_events_dead_letter_batch").await?;
 
         let event_ids: Vec<String> = event_ids_with_errors
             .iter()
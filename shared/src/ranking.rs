@@ -0,0 +1,334 @@
+//! Learned ranking for hybrid search.
+//!
+//! Each fused candidate is described by a handful of features: its BM25 score
+//! relative to the query's best fulltext hit, its semantic similarity, how
+//! recently it changed, how often it has been opened, and its source type. A
+//! ranking profile holds one weight per feature and per source type, and
+//! scores a candidate by the exponentiated weighted sum.
+//!
+//! The searcher logs the features of every result it shows. Profiles are
+//! trained offline from those logs with a pairwise logistic loss: a clicked
+//! result should outscore the unclicked results shown above it, and the first
+//! unclicked one shown below it.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Half-life of the recency feature. Fixed so that features logged at
+/// different times stay comparable.
+pub const RECENCY_HALF_LIFE_DAYS: f64 = 30.0;
+/// Bound on the weighted sum before exponentiating, to keep scores finite.
+const MAX_LOGIT: f32 = 30.0;
+
+/// What the profile knows about one candidate for a query.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RankingFeatures {
+    /// BM25 score over the query's best fulltext score; 0 when the document
+    /// was not a fulltext hit.
+    pub bm25: f32,
+    /// Similarity of the best matching chunk; 0 when the document was not a
+    /// semantic hit.
+    pub semantic: f32,
+    /// `0.5^(age / RECENCY_HALF_LIFE_DAYS)`: 1 for a document changed now.
+    pub recency: f32,
+    /// `ln(1 + clicks) / ln(1 + most clicks on any document)` over recent
+    /// clicks.
+    pub click_prior: f32,
+    pub source_type: Option<String>,
+}
+
+/// Recency feature for a document `age_days` old.
+pub fn recency_feature(age_days: f64) -> f32 {
+    0.5_f64.powf(age_days.max(0.0) / RECENCY_HALF_LIFE_DAYS) as f32
+}
+
+/// Click-through prior feature for a document opened `clicks` times, when the
+/// most opened document was opened `max_clicks` times.
+pub fn click_prior_feature(clicks: u64, max_clicks: u64) -> f32 {
+    if max_clicks == 0 {
+        return 0.0;
+    }
+    ((clicks.min(max_clicks) as f64).ln_1p() / (max_clicks as f64).ln_1p()) as f32
+}
+
+/// Weights for each feature.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RankingProfile {
+    pub bm25: f32,
+    pub semantic: f32,
+    pub recency: f32,
+    pub click_prior: f32,
+    /// Added for a candidate of the source type; types not listed add 0.
+    #[serde(default)]
+    pub source_types: HashMap<String, f32>,
+}
+
+impl RankingProfile {
+    /// Fulltext and semantic relevance weighted equally, nothing else: the
+    /// starting point for training and what a trained profile must beat.
+    pub fn baseline() -> Self {
+        Self {
+            bm25: 1.0,
+            semantic: 1.0,
+            ..Self::default()
+        }
+    }
+
+    pub fn logit(&self, features: &RankingFeatures) -> f32 {
+        let source_type = features
+            .source_type
+            .as_ref()
+            .and_then(|st| self.source_types.get(st))
+            .copied()
+            .unwrap_or_default();
+        self.bm25 * features.bm25
+            + self.semantic * features.semantic
+            + self.recency * features.recency
+            + self.click_prior * features.click_prior
+            + source_type
+    }
+
+    /// Fused score for a candidate. Always positive, so multiplicative boosts
+    /// applied after fusion keep their meaning.
+    pub fn score(&self, features: &RankingFeatures) -> f32 {
+        self.logit(features).clamp(-MAX_LOGIT, MAX_LOGIT).exp()
+    }
+
+    /// Add `scale` times the candidate's feature vector to the weights.
+    fn add_scaled(&mut self, features: &RankingFeatures, scale: f32) {
+        self.bm25 += scale * features.bm25;
+        self.semantic += scale * features.semantic;
+        self.recency += scale * features.recency;
+        self.click_prior += scale * features.click_prior;
+        if let Some(source_type) = &features.source_type {
+            *self.source_types.entry(source_type.clone()).or_default() += scale;
+        }
+    }
+
+    fn add_weights(&mut self, other: &Self, scale: f32) {
+        self.bm25 += scale * other.bm25;
+        self.semantic += scale * other.semantic;
+        self.recency += scale * other.recency;
+        self.click_prior += scale * other.click_prior;
+        for (source_type, weight) in &other.source_types {
+            *self.source_types.entry(source_type.clone()).or_default() += scale * weight;
+        }
+    }
+}
+
+/// A result shown for a search, in the order shown.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RankingImpression {
+    pub document_id: String,
+    pub features: RankingFeatures,
+}
+
+/// A preference learned from clicks: `preferred` should outrank `other`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreferencePair {
+    pub preferred: RankingFeatures,
+    pub other: RankingFeatures,
+}
+
+/// Preferences implied by the clicks on one search's results: each clicked
+/// result over every unclicked result shown above it, and over the first
+/// unclicked result shown below it.
+pub fn preference_pairs(
+    impressions: &[RankingImpression],
+    clicked: &[String],
+) -> Vec<PreferencePair> {
+    let is_clicked = |impression: &RankingImpression| clicked.contains(&impression.document_id);
+    let mut pairs = Vec::new();
+    for (position, impression) in impressions.iter().enumerate() {
+        if !is_clicked(impression) {
+            continue;
+        }
+        let skipped_above = impressions[..position].iter().filter(|i| !is_clicked(i));
+        let next_below = impressions[position + 1..].iter().find(|i| !is_clicked(i));
+        for other in skipped_above.chain(next_below) {
+            pairs.push(PreferencePair {
+                preferred: impression.features.clone(),
+                other: other.features.clone(),
+            });
+        }
+    }
+    pairs
+}
+
+#[derive(Debug, Clone)]
+pub struct TrainingOptions {
+    pub epochs: usize,
+    pub learning_rate: f32,
+    /// L2 penalty pulling weights back towards the baseline.
+    pub regularization: f32,
+}
+
+impl Default for TrainingOptions {
+    fn default() -> Self {
+        Self {
+            epochs: 200,
+            learning_rate: 0.5,
+            regularization: 0.01,
+        }
+    }
+}
+
+/// Fit a profile to the preferences by full-batch gradient descent on the
+/// pairwise logistic loss, starting from the baseline.
+pub fn train(pairs: &[PreferencePair], options: &TrainingOptions) -> RankingProfile {
+    let baseline = RankingProfile::baseline();
+    let mut profile = baseline.clone();
+    if pairs.is_empty() {
+        return profile;
+    }
+
+    let step = options.learning_rate / pairs.len() as f32;
+    for _ in 0..options.epochs {
+        let mut gradient = RankingProfile::default();
+        for pair in pairs {
+            let margin = profile.logit(&pair.preferred) - profile.logit(&pair.other);
+            // Derivative of ln(1 + e^-margin), negated
+            let error = 1.0 / (1.0 + margin.exp());
+            gradient.add_scaled(&pair.preferred, error);
+            gradient.add_scaled(&pair.other, -error);
+        }
+        profile.add_weights(&gradient, step);
+        // Decay towards the baseline rather than towards zero, so features
+        // the clicks say nothing about keep their baseline weight
+        let mut drift = profile.clone();
+        drift.add_weights(&baseline, -1.0);
+        profile.add_weights(&drift, -options.learning_rate * options.regularization);
+    }
+    profile
+}
+
+/// Fraction of pairs the profile orders correctly; ties count as half. `None`
+/// without pairs.
+pub fn pairwise_accuracy(profile: &RankingProfile, pairs: &[PreferencePair]) -> Option<f64> {
+    if pairs.is_empty() {
+        return None;
+    }
+    let correct: f64 = pairs
+        .iter()
+        .map(|pair| {
+            let margin = profile.logit(&pair.preferred) - profile.logit(&pair.other);
+            if margin > 0.0 {
+                1.0
+            } else if margin == 0.0 {
+                0.5
+            } else {
+                0.0
+            }
+        })
+        .sum();
+    Some(correct / pairs.len() as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn features(bm25: f32, semantic: f32, source_type: &str) -> RankingFeatures {
+        RankingFeatures {
+            bm25,
+            semantic,
+            recency: 0.0,
+            click_prior: 0.0,
+            source_type: Some(source_type.to_string()),
+        }
+    }
+
+    fn impression(id: &str, features: RankingFeatures) -> RankingImpression {
+        RankingImpression {
+            document_id: id.to_string(),
+            features,
+        }
+    }
+
+    #[test]
+    fn test_preference_pairs_from_clicks() {
+        let impressions = vec![
+            impression("a", features(1.0, 0.0, "slack")),
+            impression("b", features(0.8, 0.0, "slack")),
+            impression("c", features(0.6, 0.0, "slack")),
+            impression("d", features(0.4, 0.0, "slack")),
+            impression("e", features(0.2, 0.0, "slack")),
+        ];
+
+        let pairs = preference_pairs(&impressions, &["c".to_string(), "d".to_string()]);
+
+        // c over a and b, then over e; d over a and b, then over e
+        let bm25s: Vec<(f32, f32)> = pairs
+            .iter()
+            .map(|p| (p.preferred.bm25, p.other.bm25))
+            .collect();
+        assert_eq!(
+            bm25s,
+            vec![
+                (0.6, 1.0),
+                (0.6, 0.8),
+                (0.6, 0.2),
+                (0.4, 1.0),
+                (0.4, 0.8),
+                (0.4, 0.2)
+            ]
+        );
+        assert!(preference_pairs(&impressions, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_training_learns_preferred_source_type() {
+        // Users keep skipping Slack results with slightly better text matches
+        // to open Confluence pages
+        let pairs: Vec<PreferencePair> = (0..50)
+            .map(|i| PreferencePair {
+                preferred: features(0.5, 0.5 + i as f32 * 0.001, "confluence"),
+                other: features(0.6, 0.6, "slack"),
+            })
+            .collect();
+        let baseline = RankingProfile::baseline();
+        assert_eq!(pairwise_accuracy(&baseline, &pairs), Some(0.0));
+
+        let profile = train(&pairs, &TrainingOptions::default());
+
+        assert_eq!(pairwise_accuracy(&profile, &pairs), Some(1.0));
+        assert!(profile.source_types["confluence"] > profile.source_types["slack"]);
+    }
+
+    #[test]
+    fn test_training_without_pairs_keeps_baseline() {
+        assert_eq!(
+            train(&[], &TrainingOptions::default()),
+            RankingProfile::baseline()
+        );
+        assert_eq!(pairwise_accuracy(&RankingProfile::baseline(), &[]), None);
+    }
+
+    #[test]
+    fn test_score_is_positive_and_ordered() {
+        let profile = RankingProfile {
+            bm25: 2.0,
+            semantic: 1.0,
+            recency: 0.5,
+            click_prior: 1.0,
+            source_types: HashMap::from([("slack".to_string(), -100.0)]),
+        };
+        let slack = features(1.0, 1.0, "slack");
+        let docs = features(0.1, 0.1, "google_drive");
+
+        assert!(profile.score(&slack) > 0.0);
+        assert!(profile.score(&docs) > profile.score(&slack));
+        assert!(profile.score(&features(1e9, 0.0, "docs")).is_finite());
+    }
+
+    #[test]
+    fn test_feature_scaling() {
+        assert_eq!(recency_feature(0.0), 1.0);
+        assert!((recency_feature(RECENCY_HALF_LIFE_DAYS) - 0.5).abs() < 1e-6);
+        assert_eq!(recency_feature(-5.0), 1.0);
+        assert_eq!(click_prior_feature(0, 0), 0.0);
+        assert_eq!(click_prior_feature(0, 10), 0.0);
+        assert_eq!(click_prior_feature(10, 10), 1.0);
+        assert!(click_prior_feature(3, 10) < 1.0);
+    }
+}
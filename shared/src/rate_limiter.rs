@@ -1,9 +1,10 @@
 use anyhow::Result;
 use governor::{Quota, RateLimiter as GovernorRateLimiter};
 use rand::{Rng, thread_rng};
+use serde::{Deserialize, Serialize};
 use std::num::NonZeroU32;
-use std::sync::Arc;
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
 use std::time::Duration;
 use std::time::Instant;
 use tokio::time::sleep;
@@ -17,6 +18,10 @@ pub enum RetryableError {
         retry_after: Duration,
         message: String,
     },
+    /// Upstream throttling (HTTP 429, quota exceeded) without a
+    /// server-specified wait. Retried with exponential backoff like
+    /// `Transient`, but also counts as a throttle signal for adaptive limiters.
+    Throttled(anyhow::Error),
     /// Transient error — retried with exponential backoff.
     Transient(anyhow::Error),
     /// Permanent error — not retried.
@@ -34,6 +39,7 @@ impl std::fmt::Display for RetryableError {
                 "Rate limited: {} (retry after {:?})",
                 message, retry_after
             ),
+            Self::Throttled(e) => write!(f, "Throttled: {}", e),
             Self::Transient(e) => write!(f, "{}", e),
             Self::Permanent(e) => write!(f, "{}", e),
         }
@@ -48,50 +54,318 @@ impl From<anyhow::Error> for RetryableError {
     }
 }
 
+type DirectLimiter = GovernorRateLimiter<
+    governor::state::direct::NotKeyed,
+    governor::state::InMemoryState,
+    governor::clock::DefaultClock,
+>;
+
+/// Bounds and step sizes for a limiter that tunes its own rate from upstream
+/// throttling (AIMD): each throttle response multiplies the rate by
+/// `decrease_factor`, and every `increase_after` consecutive successes add
+/// `increase_step` back, never leaving `[min_rps, max_rps]`.
+#[derive(Debug, Clone, Copy)]
+pub struct AdaptiveRateConfig {
+    pub min_rps: u32,
+    pub max_rps: u32,
+    pub decrease_factor: f64,
+    pub increase_step: u32,
+    pub increase_after: u32,
+    /// Throttle responses closer together than this count as one, so a burst
+    /// of in-flight 429s doesn't collapse the rate straight to `min_rps`.
+    pub decrease_cooldown: Duration,
+}
+
+impl AdaptiveRateConfig {
+    pub fn new(min_rps: u32, max_rps: u32) -> Self {
+        Self {
+            min_rps: min_rps.max(1),
+            max_rps: max_rps.max(min_rps).max(1),
+            decrease_factor: 0.5,
+            increase_step: 1,
+            increase_after: 100,
+            decrease_cooldown: Duration::from_secs(1),
+        }
+    }
+
+    fn clamp(&self, rps: u32) -> u32 {
+        rps.clamp(self.min_rps, self.max_rps)
+    }
+}
+
+/// Usage of one API family's rate limit: tokens taken from the limiter, time
+/// spent waiting for them, and how often upstream pushed back.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateLimitStats {
+    pub api_family: String,
+    /// Requests admitted by the limiter, including retries.
+    pub requests: u64,
+    /// Time spent waiting for the limiter to admit requests.
+    pub wait_ms_total: u64,
+    pub wait_ms_max: u64,
+    /// Time spent sleeping between retries.
+    pub backoff_ms_total: u64,
+    /// Upstream throttle responses (HTTP 429 or quota errors).
+    pub throttled: u64,
+    pub retries: u64,
+    /// The rate enforced when the stats were taken, in requests per second.
+    pub current_rps: u32,
+}
+
+impl RateLimitStats {
+    /// Fold `other` into these stats; `current_rps` takes the later value.
+    pub fn merge(&mut self, other: &RateLimitStats) {
+        self.requests += other.requests;
+        self.wait_ms_total += other.wait_ms_total;
+        self.wait_ms_max = self.wait_ms_max.max(other.wait_ms_max);
+        self.backoff_ms_total += other.backoff_ms_total;
+        self.throttled += other.throttled;
+        self.retries += other.retries;
+        self.current_rps = other.current_rps;
+    }
+
+    /// Combine stats from several limiters into one entry per API family,
+    /// sorted by family.
+    pub fn merge_by_family<'a>(
+        stats: impl IntoIterator<Item = &'a RateLimitStats>,
+    ) -> Vec<RateLimitStats> {
+        let mut by_family: std::collections::BTreeMap<&str, RateLimitStats> =
+            std::collections::BTreeMap::new();
+        for s in stats {
+            by_family
+                .entry(&s.api_family)
+                .or_insert_with(|| RateLimitStats {
+                    api_family: s.api_family.clone(),
+                    ..Default::default()
+                })
+                .merge(s);
+        }
+        by_family.into_values().collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.requests == 0 && self.throttled == 0 && self.retries == 0
+    }
+}
+
+#[derive(Default)]
+struct RateLimitCounters {
+    requests: AtomicU64,
+    wait_ms_total: AtomicU64,
+    wait_ms_max: AtomicU64,
+    backoff_ms_total: AtomicU64,
+    throttled: AtomicU64,
+    retries: AtomicU64,
+}
+
+const DEFAULT_API_FAMILY: &str = "default";
+
 #[derive(Clone)]
 pub struct RateLimiter {
-    limiter: Arc<
-        GovernorRateLimiter<
-            governor::state::direct::NotKeyed,
-            governor::state::InMemoryState,
-            governor::clock::DefaultClock,
-        >,
-    >,
+    limiter: Arc<RwLock<Arc<DirectLimiter>>>,
     max_retries: u32,
     request_count: Arc<AtomicU64>,
     last_log_time: Arc<std::sync::Mutex<Instant>>,
-    configured_rps: u32,
+    current_rps: Arc<AtomicU32>,
+    adaptive: Option<AdaptiveRateConfig>,
+    consecutive_successes: Arc<AtomicU32>,
+    last_decrease: Arc<std::sync::Mutex<Option<Instant>>>,
+    /// Set once the limiter has tuned itself; seeding is ignored after that.
+    tuned: Arc<AtomicBool>,
+    api_family: Arc<str>,
+    counters: Arc<RateLimitCounters>,
 }
 
 impl RateLimiter {
     const MAX_BACKOFF: Duration = Duration::from_secs(32); // Maximum backoff time in seconds
 
     pub fn new(requests_per_second: u32, max_retries: u32) -> Self {
-        let requests_per_second_nz = NonZeroU32::new(requests_per_second).expect(
-            format!(
-                "Invalid requests_per_second for RateLimiter: {}",
-                requests_per_second
-            )
-            .as_str(),
-        );
-        let quota = Quota::per_second(requests_per_second_nz);
-        let limiter = Arc::new(GovernorRateLimiter::direct(quota));
+        let limiter = Arc::new(Self::build_limiter(requests_per_second));
 
         debug!(
             "Creating rate limit with limit of {} requests per second",
             requests_per_second
         );
         Self {
-            limiter,
+            limiter: Arc::new(RwLock::new(limiter)),
             max_retries,
             request_count: Arc::new(AtomicU64::new(0)),
             last_log_time: Arc::new(std::sync::Mutex::new(Instant::now())),
-            configured_rps: requests_per_second,
+            current_rps: Arc::new(AtomicU32::new(requests_per_second)),
+            adaptive: None,
+            consecutive_successes: Arc::new(AtomicU32::new(0)),
+            last_decrease: Arc::new(std::sync::Mutex::new(None)),
+            tuned: Arc::new(AtomicBool::new(false)),
+            api_family: Arc::from(DEFAULT_API_FAMILY),
+            counters: Arc::new(RateLimitCounters::default()),
+        }
+    }
+
+    /// Tag the limiter's stats with the upstream API it guards (e.g.
+    /// `"gmail"`), so usage can be broken down per quota.
+    pub fn with_api_family(mut self, api_family: &str) -> Self {
+        self.api_family = Arc::from(api_family);
+        self
+    }
+
+    pub fn api_family(&self) -> &str {
+        &self.api_family
+    }
+
+    /// Usage since the limiter was created or stats were last taken.
+    pub fn stats(&self) -> RateLimitStats {
+        let counters = &self.counters;
+        RateLimitStats {
+            api_family: self.api_family.to_string(),
+            requests: counters.requests.load(Ordering::Relaxed),
+            wait_ms_total: counters.wait_ms_total.load(Ordering::Relaxed),
+            wait_ms_max: counters.wait_ms_max.load(Ordering::Relaxed),
+            backoff_ms_total: counters.backoff_ms_total.load(Ordering::Relaxed),
+            throttled: counters.throttled.load(Ordering::Relaxed),
+            retries: counters.retries.load(Ordering::Relaxed),
+            current_rps: self.current_rps(),
+        }
+    }
+
+    /// Like [`stats`](Self::stats), but resets the counters so each call
+    /// reports only the usage since the previous one.
+    pub fn take_stats(&self) -> RateLimitStats {
+        let counters = &self.counters;
+        RateLimitStats {
+            api_family: self.api_family.to_string(),
+            requests: counters.requests.swap(0, Ordering::Relaxed),
+            wait_ms_total: counters.wait_ms_total.swap(0, Ordering::Relaxed),
+            wait_ms_max: counters.wait_ms_max.swap(0, Ordering::Relaxed),
+            backoff_ms_total: counters.backoff_ms_total.swap(0, Ordering::Relaxed),
+            throttled: counters.throttled.swap(0, Ordering::Relaxed),
+            retries: counters.retries.swap(0, Ordering::Relaxed),
+            current_rps: self.current_rps(),
+        }
+    }
+
+    fn record_wait(&self, wait: Duration) {
+        let wait_ms = wait.as_millis() as u64;
+        self.counters.requests.fetch_add(1, Ordering::Relaxed);
+        self.counters
+            .wait_ms_total
+            .fetch_add(wait_ms, Ordering::Relaxed);
+        self.counters
+            .wait_ms_max
+            .fetch_max(wait_ms, Ordering::Relaxed);
+    }
+
+    async fn backoff(&self, wait: Duration) {
+        self.counters.retries.fetch_add(1, Ordering::Relaxed);
+        self.counters
+            .backoff_ms_total
+            .fetch_add(wait.as_millis() as u64, Ordering::Relaxed);
+        sleep(wait).await;
+    }
+
+    /// Let the limiter tune its rate from throttle responses seen by
+    /// `execute_with_retry` (or reported via `record_throttled` /
+    /// `record_success`). The rate passed to `new` is the starting point,
+    /// clamped into the configured bounds.
+    pub fn with_adaptive(mut self, config: AdaptiveRateConfig) -> Self {
+        let initial = config.clamp(self.current_rps());
+        self.adaptive = Some(config);
+        self.set_rate(initial);
+        self
+    }
+
+    fn build_limiter(requests_per_second: u32) -> DirectLimiter {
+        let requests_per_second_nz = NonZeroU32::new(requests_per_second).unwrap_or_else(|| {
+            panic!(
+                "Invalid requests_per_second for RateLimiter: {}",
+                requests_per_second
+            )
+        });
+        GovernorRateLimiter::direct(Quota::per_second(requests_per_second_nz))
+    }
+
+    fn set_rate(&self, requests_per_second: u32) {
+        let new_limiter = Arc::new(Self::build_limiter(requests_per_second));
+        *self.limiter.write().unwrap() = new_limiter;
+        self.current_rps
+            .store(requests_per_second, Ordering::Relaxed);
+    }
+
+    /// The rate currently enforced, in requests per second.
+    pub fn current_rps(&self) -> u32 {
+        self.current_rps.load(Ordering::Relaxed)
+    }
+
+    /// Restore a previously learned rate (e.g. persisted per source), clamped
+    /// into the adaptive bounds. No-op for fixed-rate limiters, and once the
+    /// limiter has tuned itself, so a stale persisted value can't override
+    /// what it has observed live.
+    pub fn seed_rate(&self, requests_per_second: u32) {
+        let Some(config) = self.adaptive else {
+            return;
+        };
+        if self.tuned.load(Ordering::Relaxed) {
+            return;
+        }
+        let rps = config.clamp(requests_per_second);
+        if rps != self.current_rps() {
+            info!("Restoring learned rate limit of {} req/sec", rps);
+            self.set_rate(rps);
+        }
+    }
+
+    /// Report an upstream throttle response. Adaptive limiters back off
+    /// multiplicatively, at most once per `decrease_cooldown`.
+    pub fn record_throttled(&self) {
+        self.counters.throttled.fetch_add(1, Ordering::Relaxed);
+        let Some(config) = self.adaptive else {
+            return;
+        };
+        self.consecutive_successes.store(0, Ordering::Relaxed);
+
+        let mut last_decrease = self.last_decrease.lock().unwrap();
+        if last_decrease.is_some_and(|at| at.elapsed() < config.decrease_cooldown) {
+            return;
+        }
+        *last_decrease = Some(Instant::now());
+
+        let current = self.current_rps();
+        let lowered = config.clamp((current as f64 * config.decrease_factor).floor() as u32);
+        if lowered < current {
+            warn!(
+                "Upstream throttling, lowering rate limit from {} to {} req/sec",
+                current, lowered
+            );
+            self.tuned.store(true, Ordering::Relaxed);
+            self.set_rate(lowered);
+        }
+    }
+
+    /// Report a successful request. Adaptive limiters probe upward by
+    /// `increase_step` after every `increase_after` consecutive successes.
+    pub fn record_success(&self) {
+        let Some(config) = self.adaptive else {
+            return;
+        };
+        let successes = self.consecutive_successes.fetch_add(1, Ordering::Relaxed) + 1;
+        if successes < config.increase_after {
+            return;
+        }
+        self.consecutive_successes.store(0, Ordering::Relaxed);
+
+        let current = self.current_rps();
+        let raised = config.clamp(current.saturating_add(config.increase_step));
+        if raised > current {
+            debug!("Raising rate limit from {} to {} req/sec", current, raised);
+            self.tuned.store(true, Ordering::Relaxed);
+            self.set_rate(raised);
         }
     }
 
     pub async fn check_rate_limit(&self) -> Result<()> {
-        self.limiter.until_ready().await;
+        let limiter = self.limiter.read().unwrap().clone();
+        let started = Instant::now();
+        limiter.until_ready().await;
+        self.record_wait(started.elapsed());
 
         self.request_count.fetch_add(1, Ordering::Relaxed);
 
@@ -104,7 +378,8 @@ impl RateLimiter {
 
             info!(
                 "Rate limiter stats: actual={:.2} req/sec, limit={} req/sec",
-                actual_rps, self.configured_rps
+                actual_rps,
+                self.current_rps()
             );
 
             *last_log = Instant::now();
@@ -125,41 +400,52 @@ impl RateLimiter {
             self.check_rate_limit().await?;
 
             match operation().await {
-                Ok(result) => return Ok(result),
-                Err(e) => match e {
-                    RetryableError::Permanent(e) => return Err(e),
-                    RetryableError::RateLimited {
-                        retry_after,
-                        ref message,
-                    } => {
-                        if retries >= self.max_retries {
-                            return Err(anyhow::anyhow!("{}", e));
-                        }
-                        retries += 1;
-                        warn!(
-                            "Rate limited: {}, retry {} of {}, waiting {:?}",
-                            message, retries, self.max_retries, retry_after
-                        );
-                        sleep(retry_after).await;
+                Ok(result) => {
+                    self.record_success();
+                    return Ok(result);
+                }
+                Err(e) => {
+                    if matches!(
+                        e,
+                        RetryableError::RateLimited { .. } | RetryableError::Throttled(_)
+                    ) {
+                        self.record_throttled();
                     }
-                    RetryableError::Transient(e) => {
-                        if retries >= self.max_retries {
-                            return Err(e);
+                    match e {
+                        RetryableError::Permanent(e) => return Err(e),
+                        RetryableError::RateLimited {
+                            retry_after,
+                            ref message,
+                        } => {
+                            if retries >= self.max_retries {
+                                return Err(anyhow::anyhow!("{}", e));
+                            }
+                            retries += 1;
+                            warn!(
+                                "Rate limited: {}, retry {} of {}, waiting {:?}",
+                                message, retries, self.max_retries, retry_after
+                            );
+                            self.backoff(retry_after).await;
                         }
-                        retries += 1;
-                        let jitter = thread_rng().gen_range(0..1000);
-                        let wait_time = delay + Duration::from_millis(jitter);
-                        warn!(
-                            "Transient error: {}, retry {} of {}, waiting {:?}",
-                            e, retries, self.max_retries, wait_time
-                        );
-                        sleep(wait_time).await;
-                        delay = delay.saturating_mul(2);
-                        if delay > Self::MAX_BACKOFF {
-                            delay = Self::MAX_BACKOFF;
+                        RetryableError::Throttled(e) | RetryableError::Transient(e) => {
+                            if retries >= self.max_retries {
+                                return Err(e);
+                            }
+                            retries += 1;
+                            let jitter = thread_rng().gen_range(0..1000);
+                            let wait_time = delay + Duration::from_millis(jitter);
+                            warn!(
+                                "Transient error: {}, retry {} of {}, waiting {:?}",
+                                e, retries, self.max_retries, wait_time
+                            );
+                            self.backoff(wait_time).await;
+                            delay = delay.saturating_mul(2);
+                            if delay > Self::MAX_BACKOFF {
+                                delay = Self::MAX_BACKOFF;
+                            }
                         }
                     }
-                },
+                }
             }
         }
     }
@@ -276,6 +562,159 @@ mod tests {
         assert_eq!(attempts.load(Ordering::SeqCst), 1);
     }
 
+    #[tokio::test]
+    async fn test_adaptive_limiter_backs_off_on_throttle() {
+        let config = AdaptiveRateConfig {
+            decrease_cooldown: Duration::ZERO,
+            ..AdaptiveRateConfig::new(10, 200)
+        };
+        let limiter = RateLimiter::new(100, 3).with_adaptive(config);
+        assert_eq!(limiter.current_rps(), 100);
+
+        limiter.record_throttled();
+        assert_eq!(limiter.current_rps(), 50);
+        limiter.record_throttled();
+        limiter.record_throttled();
+        limiter.record_throttled();
+        assert_eq!(limiter.current_rps(), 10, "never drops below min_rps");
+    }
+
+    #[tokio::test]
+    async fn test_adaptive_limiter_cooldown_coalesces_throttle_bursts() {
+        let limiter = RateLimiter::new(100, 3).with_adaptive(AdaptiveRateConfig::new(1, 100));
+
+        for _ in 0..5 {
+            limiter.record_throttled();
+        }
+        assert_eq!(limiter.current_rps(), 50);
+    }
+
+    #[tokio::test]
+    async fn test_adaptive_limiter_probes_upward_after_successes() {
+        let config = AdaptiveRateConfig {
+            increase_after: 3,
+            ..AdaptiveRateConfig::new(1, 12)
+        };
+        let limiter = RateLimiter::new(10, 3).with_adaptive(config);
+
+        for _ in 0..9 {
+            limiter.record_success();
+        }
+        assert_eq!(limiter.current_rps(), 12, "never exceeds max_rps");
+    }
+
+    #[tokio::test]
+    async fn test_throttled_error_is_retried_and_lowers_rate() {
+        let limiter = RateLimiter::new(100, 3).with_adaptive(AdaptiveRateConfig::new(1, 100));
+        let attempts = Arc::new(AtomicU32::new(0));
+        let attempts_clone = Arc::clone(&attempts);
+
+        let result = limiter
+            .execute_with_retry(|| {
+                let attempts = Arc::clone(&attempts_clone);
+                async move {
+                    if attempts.fetch_add(1, Ordering::SeqCst) == 0 {
+                        Err(RetryableError::Throttled(anyhow!("429 too many requests")))
+                    } else {
+                        Ok("success")
+                    }
+                }
+            })
+            .await;
+
+        assert!(result.is_ok());
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+        assert_eq!(limiter.current_rps(), 50);
+    }
+
+    #[tokio::test]
+    async fn test_seed_rate_is_clamped_and_ignored_when_fixed() {
+        let fixed = RateLimiter::new(20, 3);
+        fixed.seed_rate(5);
+        assert_eq!(fixed.current_rps(), 20);
+
+        let adaptive = RateLimiter::new(20, 3).with_adaptive(AdaptiveRateConfig::new(2, 40));
+        adaptive.seed_rate(7);
+        assert_eq!(adaptive.current_rps(), 7);
+        adaptive.seed_rate(1000);
+        assert_eq!(adaptive.current_rps(), 40);
+
+        // Once tuned from live traffic, persisted rates no longer apply.
+        adaptive.record_throttled();
+        assert_eq!(adaptive.current_rps(), 20);
+        adaptive.seed_rate(30);
+        assert_eq!(adaptive.current_rps(), 20);
+    }
+
+    #[tokio::test]
+    async fn test_stats_count_requests_throttles_and_retries() {
+        let limiter = RateLimiter::new(100, 3).with_api_family("gmail");
+        let attempts = Arc::new(AtomicU32::new(0));
+        let attempts_clone = Arc::clone(&attempts);
+
+        limiter
+            .execute_with_retry(|| {
+                let attempts = Arc::clone(&attempts_clone);
+                async move {
+                    if attempts.fetch_add(1, Ordering::SeqCst) == 0 {
+                        Err(RetryableError::RateLimited {
+                            retry_after: Duration::from_millis(10),
+                            message: "429 too many requests".to_string(),
+                        })
+                    } else {
+                        Ok(())
+                    }
+                }
+            })
+            .await
+            .unwrap();
+
+        let stats = limiter.take_stats();
+        assert_eq!(stats.api_family, "gmail");
+        assert_eq!(stats.requests, 2);
+        assert_eq!(stats.throttled, 1);
+        assert_eq!(stats.retries, 1);
+        assert!(stats.backoff_ms_total >= 10);
+        assert_eq!(stats.current_rps, 100);
+
+        let after = limiter.stats();
+        assert!(after.is_empty(), "take_stats resets the counters");
+    }
+
+    #[test]
+    fn test_merge_stats_by_family() {
+        let stats = [
+            RateLimitStats {
+                api_family: "drive".to_string(),
+                requests: 5,
+                wait_ms_max: 40,
+                throttled: 1,
+                current_rps: 50,
+                ..Default::default()
+            },
+            RateLimitStats {
+                api_family: "admin".to_string(),
+                requests: 2,
+                ..Default::default()
+            },
+            RateLimitStats {
+                api_family: "drive".to_string(),
+                requests: 3,
+                wait_ms_max: 10,
+                current_rps: 25,
+                ..Default::default()
+            },
+        ];
+
+        let merged = RateLimitStats::merge_by_family(&stats);
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].api_family, "admin");
+        assert_eq!(merged[1].requests, 8);
+        assert_eq!(merged[1].wait_ms_max, 40);
+        assert_eq!(merged[1].throttled, 1);
+        assert_eq!(merged[1].current_rps, 25);
+    }
+
     #[tokio::test]
     async fn test_rate_limited_uses_retry_after() {
         let limiter = RateLimiter::new(100, 3);
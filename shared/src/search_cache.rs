@@ -0,0 +1,65 @@
+//! Invalidation of the searcher's result cache.
+//!
+//! Each source has a generation counter in Redis. A cached search response
+//! records the generations of the sources it searched, and is only served
+//! while they are unchanged. Whatever changes what search returns for a
+//! source bumps its generation: the indexer after it writes documents of the
+//! source, and the connector manager when it erases, redacts, hides or moves
+//! them. Responses that could now be stale are dropped without tracking which
+//! cache keys mention the source.
+
+use tracing::warn;
+
+const SOURCE_GENERATION_KEY_PREFIX: &str = "search_cache:generation:";
+
+pub fn source_generation_key(source_id: &str) -> String {
+    format!("{}{}", SOURCE_GENERATION_KEY_PREFIX, source_id)
+}
+
+/// Current generation of each source, in order; 0 for sources never
+/// invalidated.
+pub async fn source_generations(
+    conn: &mut redis::aio::MultiplexedConnection,
+    source_ids: &[String],
+) -> redis::RedisResult<Vec<u64>> {
+    if source_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    let keys: Vec<String> = source_ids
+        .iter()
+        .map(|id| source_generation_key(id))
+        .collect();
+    let generations: Vec<Option<u64>> = redis::cmd("MGET").arg(&keys).query_async(conn).await?;
+    Ok(generations
+        .into_iter()
+        .map(Option::unwrap_or_default)
+        .collect())
+}
+
+/// Invalidate cached searches over any of `source_ids`. Failures are logged
+/// only: stale responses still expire with the cache TTL.
+pub async fn invalidate_sources<'a>(
+    redis_client: &redis::Client,
+    source_ids: impl IntoIterator<Item = &'a str>,
+) {
+    let keys: Vec<String> = source_ids.into_iter().map(source_generation_key).collect();
+    if keys.is_empty() {
+        return;
+    }
+
+    let mut pipe = redis::pipe();
+    for key in &keys {
+        pipe.incr(key, 1).ignore();
+    }
+    let result = match redis_client.get_multiplexed_async_connection().await {
+        Ok(mut conn) => pipe.query_async::<()>(&mut conn).await,
+        Err(e) => Err(e),
+    };
+    if let Err(e) = result {
+        warn!(
+            "Failed to invalidate search cache for {} sources: {}",
+            keys.len(),
+            e
+        );
+    }
+}
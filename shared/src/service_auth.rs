@@ -7,6 +7,13 @@ use serde::{Deserialize, Serialize};
 
 use crate::models::{AuthType, ServiceCredential, ServiceProvider};
 
+pub mod internal;
+pub mod token_lifecycle;
+
+pub use token_lifecycle::{
+    TokenLifecycleManager, TokenRefreshError, TokenRefreshSummary, TokenRefresher,
+};
+
 /// Trait for service authentication
 #[async_trait]
 pub trait ServiceAuth: Send + Sync {
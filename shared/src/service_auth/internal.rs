@@ -0,0 +1,437 @@
+//! Authentication of requests between Omni's own services.
+//!
+//! The rest of `service_auth` signs Omni's requests to the providers it
+//! indexes. Here Omni is also the side checking the credential, so besides
+//! the key a service sends this holds the keys it accepts and the middleware
+//! enforcing them, which `ServiceAuth` has no place for.
+//!
+//! The indexer, searcher, connector manager and sandbox are only meant to be
+//! called by the web app, the AI service, connectors and each other. With
+//! internal auth enabled they reject any request that does not carry one of
+//! their accepted service keys in the `x-omni-service-key` header, except on
+//! open paths such as health checks.
+//!
+//! | Setting                    | Default                                                   |
+//! |----------------------------|-----------------------------------------------------------|
+//! | `INTERNAL_AUTH_ENABLED`    | `false`                                                   |
+//! | `INTERNAL_AUTH_KEYS`       | none; comma-separated `caller:key` pairs                  |
+//! | `INTERNAL_AUTH_OPEN_PATHS` | `/health,/shared/*`; a trailing `*` matches a path prefix |
+//! | `INTERNAL_AUTH_KEY`        | none; the key this service sends to others                |
+//!
+//! As with `http_security`, each setting can be given per service
+//! (`SANDBOX_INTERNAL_AUTH_KEYS`) or for all services, so every service can
+//! accept its own set of callers. A caller may be listed with several keys:
+//! to rotate a key, add the new one, move the caller over, then drop the old
+//! one.
+//!
+//! Keys are accepted no matter which caller name they are listed under; the
+//! name only shows up in logs. Enabling internal auth without any keys
+//! rejects every request to a path that is not open.
+
+use futures_util::future::BoxFuture;
+use http::{HeaderMap, HeaderValue, Request, Response, StatusCode};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tower::{Layer, Service};
+use tracing::{debug, warn};
+
+/// Header carrying the caller's service key.
+pub const SERVICE_KEY_HEADER: &str = "x-omni-service-key";
+
+/// Health checks, and the searcher's document share links, which are opened
+/// from outside the cluster with only the link's token.
+const DEFAULT_OPEN_PATHS: &str = "/health,/shared/*";
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct ServiceKey {
+    pub caller: String,
+    pub key: String,
+}
+
+impl std::fmt::Debug for ServiceKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ServiceKey")
+            .field("caller", &self.caller)
+            .field("key", &"<redacted>")
+            .finish()
+    }
+}
+
+/// Request extension naming the service that authenticated the request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InternalCaller(pub String);
+
+#[derive(Clone)]
+pub struct InternalAuthConfig {
+    pub enabled: bool,
+    pub accepted_keys: Vec<ServiceKey>,
+    pub open_paths: Vec<String>,
+    /// Sent by this service with its own requests to other services.
+    pub outgoing_key: Option<String>,
+}
+
+impl std::fmt::Debug for InternalAuthConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("InternalAuthConfig")
+            .field("enabled", &self.enabled)
+            .field("accepted_keys", &self.accepted_keys)
+            .field("open_paths", &self.open_paths)
+            .field(
+                "outgoing_key",
+                &self.outgoing_key.as_ref().map(|_| "<redacted>"),
+            )
+            .finish()
+    }
+}
+
+impl Default for InternalAuthConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            accepted_keys: Vec::new(),
+            open_paths: parse_open_paths(DEFAULT_OPEN_PATHS),
+            outgoing_key: None,
+        }
+    }
+}
+
+impl InternalAuthConfig {
+    /// Settings for `service`, the prefix of its service-specific variables
+    /// (e.g. `"SEARCHER"`).
+    pub fn from_env(service: &str) -> Self {
+        Self::from_lookup(service, |key| std::env::var(key).ok())
+    }
+
+    fn from_lookup(service: &str, lookup: impl Fn(&str) -> Option<String>) -> Self {
+        let get = |key: &str| {
+            lookup(&format!("{}_{}", service, key))
+                .or_else(|| lookup(key))
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+
+        let defaults = Self::default();
+        let enabled = match get("INTERNAL_AUTH_ENABLED") {
+            Some(value) => value.parse().unwrap_or_else(|_| {
+                // Fail closed: a typo should not leave the service open
+                warn!("Invalid INTERNAL_AUTH_ENABLED value '{}'; enabling", value);
+                true
+            }),
+            None => defaults.enabled,
+        };
+        let accepted_keys = get("INTERNAL_AUTH_KEYS")
+            .map(|keys| parse_service_keys(&keys))
+            .unwrap_or(defaults.accepted_keys);
+        if enabled && accepted_keys.is_empty() {
+            warn!(
+                "{}: internal auth is enabled but no keys are accepted; all requests to non-open paths will be rejected",
+                service
+            );
+        }
+
+        Self {
+            enabled,
+            accepted_keys,
+            open_paths: get("INTERNAL_AUTH_OPEN_PATHS")
+                .map(|paths| parse_open_paths(&paths))
+                .unwrap_or(defaults.open_paths),
+            outgoing_key: get("INTERNAL_AUTH_KEY"),
+        }
+    }
+
+    /// Middleware rejecting unauthenticated requests, to add to a service's
+    /// `ServiceBuilder` after the `HttpSecurityConfig` layer so that CORS
+    /// preflights are answered first.
+    pub fn layer(&self) -> InternalAuthLayer {
+        InternalAuthLayer {
+            config: Arc::new(self.clone()),
+        }
+    }
+
+    /// Headers to send with requests to other services; empty without an
+    /// outgoing key.
+    pub fn outgoing_headers(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        if let Some(key) = &self.outgoing_key {
+            match HeaderValue::from_str(key) {
+                Ok(mut value) => {
+                    value.set_sensitive(true);
+                    headers.insert(SERVICE_KEY_HEADER, value);
+                }
+                Err(_) => warn!("INTERNAL_AUTH_KEY is not a valid header value; not sending it"),
+            }
+        }
+        headers
+    }
+
+    fn is_open(&self, path: &str) -> bool {
+        self.open_paths
+            .iter()
+            .any(|open| match open.strip_suffix('*') {
+                Some(prefix) => path.starts_with(prefix),
+                None => path == open,
+            })
+    }
+
+    /// The caller owning `key`, if it is accepted.
+    fn authenticate(&self, key: &str) -> Option<&str> {
+        // Check every key so the time taken doesn't reveal which one matched
+        let mut caller = None;
+        for accepted in &self.accepted_keys {
+            if keys_match(&accepted.key, key) && caller.is_none() {
+                caller = Some(accepted.caller.as_str());
+            }
+        }
+        caller
+    }
+}
+
+fn parse_service_keys(value: &str) -> Vec<ServiceKey> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .enumerate()
+        .filter_map(|(index, entry)| match entry.split_once(':') {
+            Some((caller, key)) if !caller.trim().is_empty() && !key.trim().is_empty() => {
+                Some(ServiceKey {
+                    caller: caller.trim().to_string(),
+                    key: key.trim().to_string(),
+                })
+            }
+            // Never log the entry itself, it may be a bare key
+            _ => {
+                warn!(
+                    "Ignoring INTERNAL_AUTH_KEYS entry {}: expected 'caller:key'",
+                    index + 1
+                );
+                None
+            }
+        })
+        .collect()
+}
+
+fn parse_open_paths(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|path| !path.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Constant-time comparison. Hashing first makes the comparison independent
+/// of the keys' lengths too.
+fn keys_match(expected: &str, given: &str) -> bool {
+    let expected = Sha256::digest(expected.as_bytes());
+    let given = Sha256::digest(given.as_bytes());
+    expected
+        .iter()
+        .zip(given.iter())
+        .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+        == 0
+}
+
+#[derive(Debug, Clone)]
+pub struct InternalAuthLayer {
+    config: Arc<InternalAuthConfig>,
+}
+
+impl<S> Layer<S> for InternalAuthLayer {
+    type Service = InternalAuth<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        InternalAuth {
+            inner,
+            config: self.config.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct InternalAuth<S> {
+    inner: S,
+    config: Arc<InternalAuthConfig>,
+}
+
+impl<S, ReqBody> Service<Request<ReqBody>> for InternalAuth<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<axum::body::Body>>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: Request<ReqBody>) -> Self::Future {
+        let config = &self.config;
+        if !config.enabled || config.is_open(request.uri().path()) {
+            return Box::pin(self.inner.call(request));
+        }
+
+        let caller = request
+            .headers()
+            .get(SERVICE_KEY_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|key| config.authenticate(key))
+            .map(str::to_string);
+        match caller {
+            Some(caller) => {
+                debug!(
+                    caller,
+                    path = request.uri().path(),
+                    "Authenticated internal request"
+                );
+                request.extensions_mut().insert(InternalCaller(caller));
+                Box::pin(self.inner.call(request))
+            }
+            None => {
+                warn!(
+                    method = %request.method(),
+                    path = request.uri().path(),
+                    "Rejected request without a valid service key"
+                );
+                Box::pin(async { Ok(unauthorized()) })
+            }
+        }
+    }
+}
+
+fn unauthorized() -> Response<axum::body::Body> {
+    let body = serde_json::json!({ "error": "Missing or invalid service key" });
+    let mut response = Response::new(axum::body::Body::from(body.to_string()));
+    *response.status_mut() = StatusCode::UNAUTHORIZED;
+    response.headers_mut().insert(
+        http::header::CONTENT_TYPE,
+        HeaderValue::from_static("application/json"),
+    );
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Router, body::Body, routing::get};
+    use std::collections::HashMap;
+    use tower::ServiceExt;
+
+    fn config(vars: &[(&str, &str)]) -> InternalAuthConfig {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        InternalAuthConfig::from_lookup("INDEXER", |key| vars.get(key).cloned())
+    }
+
+    async fn status(config: &InternalAuthConfig, path: &str, key: Option<&str>) -> StatusCode {
+        let app = Router::new()
+            .route("/health", get(|| async { "ok" }))
+            .route("/documents", get(|| async { "ok" }))
+            .route("/metrics/queue", get(|| async { "ok" }))
+            .layer(config.layer());
+        let mut request = Request::builder().uri(path);
+        if let Some(key) = key {
+            request = request.header(SERVICE_KEY_HEADER, key);
+        }
+        app.oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[test]
+    fn test_service_keys_are_parsed_per_service() {
+        let config = config(&[
+            ("INTERNAL_AUTH_ENABLED", "true"),
+            ("INTERNAL_AUTH_KEYS", "web:shared-key"),
+            (
+                "INDEXER_INTERNAL_AUTH_KEYS",
+                "web:old-key, web:new-key, bare-key, :no-caller",
+            ),
+            ("INTERNAL_AUTH_KEY", "indexer-key"),
+        ]);
+        assert_eq!(
+            config.accepted_keys,
+            vec![
+                ServiceKey {
+                    caller: "web".to_string(),
+                    key: "old-key".to_string(),
+                },
+                ServiceKey {
+                    caller: "web".to_string(),
+                    key: "new-key".to_string(),
+                },
+            ]
+        );
+        assert_eq!(
+            config.open_paths,
+            vec!["/health".to_string(), "/shared/*".to_string()]
+        );
+        assert_eq!(
+            config.outgoing_headers()[SERVICE_KEY_HEADER],
+            HeaderValue::from_static("indexer-key")
+        );
+        assert!(!format!("{:?}", config).contains("new-key"));
+    }
+
+    #[test]
+    fn test_invalid_enabled_value_fails_closed() {
+        assert!(config(&[("INTERNAL_AUTH_ENABLED", "yes please")]).enabled);
+        assert!(!config(&[]).enabled);
+    }
+
+    #[tokio::test]
+    async fn test_disabled_accepts_everything() {
+        assert_eq!(
+            status(&config(&[]), "/documents", None).await,
+            StatusCode::OK
+        );
+    }
+
+    #[tokio::test]
+    async fn test_enabled_requires_an_accepted_key_outside_open_paths() {
+        let config = config(&[
+            ("INTERNAL_AUTH_ENABLED", "true"),
+            ("INTERNAL_AUTH_KEYS", "web:old-key,web:new-key"),
+            ("INTERNAL_AUTH_OPEN_PATHS", "/health,/metrics/*"),
+        ]);
+
+        assert_eq!(status(&config, "/health", None).await, StatusCode::OK);
+        assert_eq!(
+            status(&config, "/metrics/queue", None).await,
+            StatusCode::OK
+        );
+        assert_eq!(
+            status(&config, "/documents", None).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            status(&config, "/documents", Some("wrong-key")).await,
+            StatusCode::UNAUTHORIZED
+        );
+        // Both keys work while one is being rotated out
+        assert_eq!(
+            status(&config, "/documents", Some("old-key")).await,
+            StatusCode::OK
+        );
+        assert_eq!(
+            status(&config, "/documents", Some("new-key")).await,
+            StatusCode::OK
+        );
+    }
+
+    #[tokio::test]
+    async fn test_enabled_without_keys_rejects_requests() {
+        let config = config(&[("INTERNAL_AUTH_ENABLED", "true")]);
+        assert_eq!(
+            status(&config, "/documents", Some("")).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(status(&config, "/health", None).await, StatusCode::OK);
+    }
+}
@@ -0,0 +1,695 @@
+use anyhow::anyhow;
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{Value as JsonValue, json};
+use sqlx::PgPool;
+use time::{Duration, OffsetDateTime};
+use tracing::{debug, info, warn};
+
+use crate::db::repositories::{
+    ConnectorConfigRepository, ConnectorErrorRepository, ServiceCredentialsRepo,
+};
+use crate::models::{
+    AuthType, ConnectorErrorCategory, ServiceCredential, ServiceProvider, Source, SourceScope,
+};
+
+/// How long before an access token expires it is refreshed.
+pub const DEFAULT_REFRESH_MARGIN: Duration = Duration::minutes(15);
+
+const GOOGLE_TOKEN_URI: &str = "https://oauth2.googleapis.com/token";
+const ATLASSIAN_TOKEN_URI: &str = "https://auth.atlassian.com/oauth/token";
+
+/// OAuth error codes (RFC 6749 §5.2) meaning the grant or the client is no
+/// longer valid, so retrying the refresh won't help.
+const REAUTHORIZE_ERROR_CODES: &[&str] =
+    &["invalid_grant", "invalid_client", "unauthorized_client"];
+
+#[derive(Debug, thiserror::Error)]
+pub enum TokenRefreshError {
+    /// Someone has to re-authorize the credential before it works again.
+    #[error("Re-authorization required: {0}")]
+    ReauthorizationRequired(String),
+    /// The refresh may succeed if retried later.
+    #[error(transparent)]
+    Transient(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenEndpointAuthMethod {
+    ClientSecretPost,
+    ClientSecretBasic,
+    None,
+}
+
+impl TokenEndpointAuthMethod {
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "client_secret_post" => Some(Self::ClientSecretPost),
+            "client_secret_basic" => Some(Self::ClientSecretBasic),
+            "none" => Some(Self::None),
+            _ => None,
+        }
+    }
+}
+
+/// The OAuth client a credential was issued to, and where to refresh it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OAuthClient {
+    pub client_id: String,
+    pub client_secret: Option<String>,
+    pub token_uri: String,
+    pub auth_method: TokenEndpointAuthMethod,
+}
+
+impl OAuthClient {
+    /// Credentials written by the OAuth callback carry their client and token
+    /// endpoint. Older ones fall back to the provider's connector config
+    /// (`oauth_client_id`, `oauth_client_secret`, `oauth_token_endpoint`) and
+    /// the refresher's default endpoint.
+    pub fn resolve(
+        credentials: &JsonValue,
+        connector_config: Option<&JsonValue>,
+        default_token_uri: Option<&str>,
+    ) -> Result<Self, TokenRefreshError> {
+        let lookup = |credentials_key: &str, config_key: &str| {
+            non_empty_str(credentials, credentials_key)
+                .or_else(|| connector_config.and_then(|config| non_empty_str(config, config_key)))
+        };
+
+        let client_id = lookup("client_id", "oauth_client_id").ok_or_else(|| {
+            TokenRefreshError::ReauthorizationRequired("no OAuth client is configured".to_string())
+        })?;
+        let client_secret = lookup("client_secret", "oauth_client_secret");
+        let token_uri = lookup("token_uri", "oauth_token_endpoint")
+            .or_else(|| default_token_uri.map(String::from))
+            .ok_or_else(|| anyhow!("No token endpoint to refresh the OAuth token with"))?;
+        let auth_method = connector_config
+            .and_then(|config| config.get("oauth_token_endpoint_auth_method"))
+            .and_then(|v| v.as_str())
+            .and_then(TokenEndpointAuthMethod::parse)
+            .unwrap_or(if client_secret.is_some() {
+                TokenEndpointAuthMethod::ClientSecretPost
+            } else {
+                TokenEndpointAuthMethod::None
+            });
+
+        Ok(Self {
+            client_id,
+            client_secret,
+            token_uri,
+            auth_method,
+        })
+    }
+}
+
+fn non_empty_str(value: &JsonValue, key: &str) -> Option<String> {
+    value
+        .get(key)
+        .and_then(|v| v.as_str())
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+}
+
+/// Tokens issued by a refresh.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RefreshedToken {
+    pub access_token: String,
+    /// Set when the provider rotated the refresh token.
+    pub refresh_token: Option<String>,
+    pub expires_at: Option<OffsetDateTime>,
+    pub scopes: Option<Vec<String>>,
+}
+
+impl RefreshedToken {
+    /// Write the new tokens into `creds`, keeping the credential fields they
+    /// don't replace (client, token endpoint, provider-specific keys).
+    pub fn apply_to(&self, creds: &mut ServiceCredential) {
+        let mut credentials = creds.credentials.as_object().cloned().unwrap_or_default();
+        credentials.insert("access_token".to_string(), json!(self.access_token));
+        if let Some(refresh_token) = &self.refresh_token {
+            credentials.insert("refresh_token".to_string(), json!(refresh_token));
+        }
+        // Some connectors keep the expiry, in unix seconds, in the credentials.
+        if let Some(expires_at) = self.expires_at
+            && credentials.contains_key("expires_at")
+        {
+            credentials.insert("expires_at".to_string(), json!(expires_at.unix_timestamp()));
+        }
+        creds.credentials = JsonValue::Object(credentials);
+        creds.expires_at = self.expires_at;
+
+        if let (Some(scopes), Some(config)) = (&self.scopes, creds.config.as_object_mut()) {
+            config.insert("granted_scopes".to_string(), json!(scopes));
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    #[serde(default)]
+    refresh_token: Option<String>,
+    #[serde(default)]
+    expires_in: Option<i64>,
+    #[serde(default)]
+    scope: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenErrorResponse {
+    error: String,
+    #[serde(default)]
+    error_description: Option<String>,
+}
+
+/// Turn a token endpoint response into the refreshed token, or into the
+/// reason the refresh failed.
+pub fn parse_token_response(
+    status: u16,
+    body: &str,
+    now: OffsetDateTime,
+) -> Result<RefreshedToken, TokenRefreshError> {
+    if (200..300).contains(&status) {
+        let token: TokenResponse = serde_json::from_str(body)
+            .map_err(|e| anyhow!("Invalid token endpoint response: {}", e))?;
+        return Ok(RefreshedToken {
+            access_token: token.access_token,
+            refresh_token: token.refresh_token,
+            expires_at: token.expires_in.map(|secs| now + Duration::seconds(secs)),
+            scopes: token
+                .scope
+                .map(|scope| scope.split_whitespace().map(String::from).collect()),
+        });
+    }
+
+    match serde_json::from_str::<TokenErrorResponse>(body) {
+        Ok(error)
+            if (status == 400 || status == 401)
+                && REAUTHORIZE_ERROR_CODES.contains(&error.error.as_str()) =>
+        {
+            Err(TokenRefreshError::ReauthorizationRequired(
+                match error.error_description {
+                    Some(description) => format!("{}: {}", error.error, description),
+                    None => error.error,
+                },
+            ))
+        }
+        _ => Err(anyhow!("Token endpoint returned HTTP {} - {}", status, body).into()),
+    }
+}
+
+/// Exchanges a refresh token for a new access token at a provider.
+#[async_trait]
+pub trait TokenRefresher: Send + Sync {
+    /// Token endpoint for credentials that don't name one.
+    fn default_token_uri(&self) -> Option<&str> {
+        None
+    }
+
+    async fn refresh(
+        &self,
+        client: &OAuthClient,
+        refresh_token: &str,
+    ) -> Result<RefreshedToken, TokenRefreshError>;
+}
+
+/// The RFC 6749 refresh grant, which most providers accept as-is.
+pub struct OAuthTokenRefresher {
+    http: reqwest::Client,
+    default_token_uri: Option<&'static str>,
+    json_body: bool,
+}
+
+impl OAuthTokenRefresher {
+    pub fn standard() -> Self {
+        Self {
+            http: reqwest::Client::builder()
+                .timeout(std::time::Duration::from_secs(30))
+                .build()
+                .unwrap_or_default(),
+            default_token_uri: None,
+            json_body: false,
+        }
+    }
+
+    /// Google, whose older credentials don't record their token endpoint.
+    pub fn google() -> Self {
+        Self {
+            default_token_uri: Some(GOOGLE_TOKEN_URI),
+            ..Self::standard()
+        }
+    }
+
+    /// Atlassian takes a JSON body, and rotates the refresh token on every
+    /// refresh.
+    pub fn atlassian() -> Self {
+        Self {
+            default_token_uri: Some(ATLASSIAN_TOKEN_URI),
+            json_body: true,
+            ..Self::standard()
+        }
+    }
+}
+
+#[async_trait]
+impl TokenRefresher for OAuthTokenRefresher {
+    fn default_token_uri(&self) -> Option<&str> {
+        self.default_token_uri
+    }
+
+    async fn refresh(
+        &self,
+        client: &OAuthClient,
+        refresh_token: &str,
+    ) -> Result<RefreshedToken, TokenRefreshError> {
+        let mut params = vec![
+            ("grant_type", "refresh_token"),
+            ("refresh_token", refresh_token),
+        ];
+        if client.auth_method != TokenEndpointAuthMethod::ClientSecretBasic {
+            params.push(("client_id", client.client_id.as_str()));
+        }
+        if client.auth_method == TokenEndpointAuthMethod::ClientSecretPost
+            && let Some(secret) = &client.client_secret
+        {
+            params.push(("client_secret", secret.as_str()));
+        }
+
+        let mut request = self.http.post(&client.token_uri);
+        request = if self.json_body {
+            let body: serde_json::Map<String, JsonValue> = params
+                .iter()
+                .map(|(key, value)| (key.to_string(), json!(value)))
+                .collect();
+            request.json(&body)
+        } else {
+            request.form(&params)
+        };
+        if client.auth_method == TokenEndpointAuthMethod::ClientSecretBasic {
+            request = request.basic_auth(&client.client_id, client.client_secret.as_deref());
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| anyhow!("Token endpoint request failed: {}", e))?;
+        let status = response.status().as_u16();
+        let body = response
+            .text()
+            .await
+            .map_err(|e| anyhow!("Failed to read token endpoint response: {}", e))?;
+
+        parse_token_response(status, &body, OffsetDateTime::now_utc())
+    }
+}
+
+/// Outcome of one pass over expiring credentials.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TokenRefreshSummary {
+    pub refreshed: usize,
+    pub reauthorization_required: usize,
+    pub failed: usize,
+}
+
+/// When the access token of an OAuth credential expires, from the row or, for
+/// credentials written before the row tracked it, the credentials JSON.
+pub fn token_expires_at(creds: &ServiceCredential) -> Option<OffsetDateTime> {
+    creds.expires_at.or_else(|| {
+        creds
+            .credentials
+            .get("expires_at")
+            .and_then(|v| v.as_i64())
+            .and_then(|secs| OffsetDateTime::from_unix_timestamp(secs).ok())
+    })
+}
+
+/// Whether `creds` is an OAuth credential whose access token expires within
+/// `margin` of `now`. Tokens without a known expiry are left alone.
+pub fn needs_refresh(creds: &ServiceCredential, margin: Duration, now: OffsetDateTime) -> bool {
+    creds.auth_type == AuthType::OAuth
+        && token_expires_at(creds).is_some_and(|expires_at| expires_at <= now + margin)
+}
+
+/// Whether syncs of `source` use `creds`, rather than one user's tool calls.
+pub fn owns_source(source: &Source, creds: &ServiceCredential) -> bool {
+    match (&creds.user_id, source.scope) {
+        (None, _) => true,
+        (Some(user_id), SourceScope::User) => *user_id == source.created_by,
+        (Some(_), SourceScope::Org) => false,
+    }
+}
+
+/// Keeps the OAuth access tokens in `service_credentials` valid: refreshes
+/// them shortly before they expire, persists rotated refresh tokens, and
+/// flags credentials that can only be fixed by re-authorizing. Such failures
+/// are also reported as authentication errors of the source they sync, so
+/// they show up in its health.
+pub struct TokenLifecycleManager {
+    pool: PgPool,
+    margin: Duration,
+    refreshers: Vec<(ServiceProvider, Box<dyn TokenRefresher>)>,
+    fallback: Box<dyn TokenRefresher>,
+}
+
+impl TokenLifecycleManager {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool,
+            margin: DEFAULT_REFRESH_MARGIN,
+            refreshers: vec![
+                (
+                    ServiceProvider::Google,
+                    Box::new(OAuthTokenRefresher::google()),
+                ),
+                (
+                    ServiceProvider::GoogleAds,
+                    Box::new(OAuthTokenRefresher::google()),
+                ),
+                (
+                    ServiceProvider::Atlassian,
+                    Box::new(OAuthTokenRefresher::atlassian()),
+                ),
+            ],
+            fallback: Box::new(OAuthTokenRefresher::standard()),
+        }
+    }
+
+    pub fn with_margin(mut self, margin: Duration) -> Self {
+        self.margin = margin;
+        self
+    }
+
+    /// Use `refresher` for the credentials of `provider`.
+    pub fn with_refresher(
+        mut self,
+        provider: ServiceProvider,
+        refresher: impl TokenRefresher + 'static,
+    ) -> Self {
+        self.refreshers.retain(|(p, _)| *p != provider);
+        self.refreshers.push((provider, Box::new(refresher)));
+        self
+    }
+
+    fn refresher(&self, provider: ServiceProvider) -> &dyn TokenRefresher {
+        self.refreshers
+            .iter()
+            .find(|(p, _)| *p == provider)
+            .map(|(_, refresher)| refresher.as_ref())
+            .unwrap_or(self.fallback.as_ref())
+    }
+
+    /// Refresh the credential of `source` if its access token is about to
+    /// expire, returning the credential to use.
+    pub async fn ensure_fresh(
+        &self,
+        source: &Source,
+        mut creds: ServiceCredential,
+    ) -> Result<ServiceCredential, TokenRefreshError> {
+        if !needs_refresh(&creds, self.margin, OffsetDateTime::now_utc()) {
+            return Ok(creds);
+        }
+
+        let repo = ServiceCredentialsRepo::new(self.pool.clone())?;
+        let owns_source = owns_source(source, &creds);
+        self.refresh_and_record(&repo, &mut creds, owns_source)
+            .await?;
+        Ok(creds)
+    }
+
+    /// Refresh every OAuth credential whose access token expires within the
+    /// margin.
+    pub async fn refresh_expiring(&self) -> anyhow::Result<TokenRefreshSummary> {
+        let repo = ServiceCredentialsRepo::new(self.pool.clone())?;
+        let expiring = repo
+            .find_oauth_expiring_before(OffsetDateTime::now_utc() + self.margin)
+            .await?;
+
+        let mut summary = TokenRefreshSummary::default();
+        for entry in expiring {
+            let mut creds = entry.credential;
+            match self
+                .refresh_and_record(&repo, &mut creds, entry.owns_source)
+                .await
+            {
+                Ok(()) => summary.refreshed += 1,
+                Err(TokenRefreshError::ReauthorizationRequired(_)) => {
+                    summary.reauthorization_required += 1
+                }
+                Err(TokenRefreshError::Transient(e)) => {
+                    warn!(
+                        "Failed to refresh OAuth token of credential {} (source {}), will retry: {}",
+                        creds.id, creds.source_id, e
+                    );
+                    summary.failed += 1;
+                }
+            }
+        }
+
+        Ok(summary)
+    }
+
+    async fn refresh_and_record(
+        &self,
+        repo: &ServiceCredentialsRepo,
+        creds: &mut ServiceCredential,
+        owns_source: bool,
+    ) -> Result<(), TokenRefreshError> {
+        let loaded_at = creds.updated_at;
+        match self.refresh(creds).await {
+            Ok(refreshed) => {
+                refreshed.apply_to(creds);
+                repo.update_credentials(creds).await?;
+                info!(
+                    "Refreshed OAuth token of credential {} (source {}), expires at {:?}",
+                    creds.id, creds.source_id, creds.expires_at
+                );
+                Ok(())
+            }
+            Err(TokenRefreshError::ReauthorizationRequired(reason)) => {
+                // A concurrent refresh may have rotated the refresh token we
+                // used; its tokens are fine, so there is nothing to flag.
+                if !repo
+                    .mark_refresh_failed(&creds.id, loaded_at, &reason)
+                    .await?
+                {
+                    debug!(
+                        "Credential {} changed while refreshing it, ignoring failed refresh: {}",
+                        creds.id, reason
+                    );
+                    return Err(anyhow!("Credential changed while refreshing it").into());
+                }
+
+                warn!(
+                    "OAuth token of credential {} (source {}) can't be refreshed, re-authorization required: {}",
+                    creds.id, creds.source_id, reason
+                );
+                if owns_source {
+                    ConnectorErrorRepository::new(&self.pool)
+                        .record(
+                            &creds.source_id,
+                            None,
+                            ConnectorErrorCategory::Authentication,
+                            &format!(
+                                "The {} connection has to be re-authorized: {}",
+                                creds.provider.as_str(),
+                                reason
+                            ),
+                            Some(&json!({ "credential_id": creds.id })),
+                        )
+                        .await
+                        .map_err(|e| anyhow!("Failed to record connector error: {}", e))?;
+                }
+                Err(TokenRefreshError::ReauthorizationRequired(reason))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn refresh(
+        &self,
+        creds: &ServiceCredential,
+    ) -> Result<RefreshedToken, TokenRefreshError> {
+        let refresh_token =
+            non_empty_str(&creds.credentials, "refresh_token").ok_or_else(|| {
+                TokenRefreshError::ReauthorizationRequired(
+                    "the credential has no refresh token".to_string(),
+                )
+            })?;
+
+        let connector_config = ConnectorConfigRepository::new(self.pool.clone())
+            .get_by_provider(creds.provider.as_str())
+            .await?
+            .map(|row| row.config);
+        let refresher = self.refresher(creds.provider);
+        let client = OAuthClient::resolve(
+            &creds.credentials,
+            connector_config.as_ref(),
+            refresher.default_token_uri(),
+        )?;
+
+        refresher.refresh(&client, &refresh_token).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn noon() -> OffsetDateTime {
+        OffsetDateTime::from_unix_timestamp(1_767_268_800).unwrap()
+    }
+
+    fn oauth_credential(
+        credentials: JsonValue,
+        expires_at: Option<OffsetDateTime>,
+    ) -> ServiceCredential {
+        ServiceCredential {
+            id: "cred-1".to_string(),
+            source_id: "src-1".to_string(),
+            user_id: None,
+            provider: ServiceProvider::Atlassian,
+            auth_type: AuthType::OAuth,
+            principal_email: None,
+            credentials,
+            config: json!({}),
+            expires_at,
+            last_validated_at: None,
+            created_at: OffsetDateTime::now_utc(),
+            updated_at: OffsetDateTime::now_utc(),
+        }
+    }
+
+    #[test]
+    fn test_needs_refresh_within_margin() {
+        let now = noon();
+        let margin = Duration::minutes(15);
+
+        let expiring = oauth_credential(json!({}), Some(now + Duration::minutes(10)));
+        assert!(needs_refresh(&expiring, margin, now));
+
+        let valid = oauth_credential(json!({}), Some(now + Duration::hours(1)));
+        assert!(!needs_refresh(&valid, margin, now));
+
+        let unknown = oauth_credential(json!({}), None);
+        assert!(!needs_refresh(&unknown, margin, now));
+
+        let legacy = oauth_credential(json!({ "expires_at": now.unix_timestamp() - 60 }), None);
+        assert!(needs_refresh(&legacy, margin, now));
+
+        let mut api_key = expiring.clone();
+        api_key.auth_type = AuthType::ApiKey;
+        assert!(!needs_refresh(&api_key, margin, now));
+    }
+
+    #[test]
+    fn test_resolve_client_falls_back_to_connector_config() {
+        let stored = OAuthClient::resolve(
+            &json!({
+                "client_id": "stored-id",
+                "client_secret": "stored-secret",
+                "token_uri": "https://example.com/token",
+            }),
+            Some(&json!({ "oauth_client_id": "config-id" })),
+            Some(GOOGLE_TOKEN_URI),
+        )
+        .unwrap();
+        assert_eq!(stored.client_id, "stored-id");
+        assert_eq!(stored.token_uri, "https://example.com/token");
+        assert_eq!(
+            stored.auth_method,
+            TokenEndpointAuthMethod::ClientSecretPost
+        );
+
+        let legacy = OAuthClient::resolve(
+            &json!({ "access_token": "a", "refresh_token": "r" }),
+            Some(&json!({
+                "oauth_client_id": "config-id",
+                "oauth_client_secret": "config-secret",
+                "oauth_token_endpoint_auth_method": "client_secret_basic",
+            })),
+            Some(GOOGLE_TOKEN_URI),
+        )
+        .unwrap();
+        assert_eq!(legacy.client_id, "config-id");
+        assert_eq!(legacy.client_secret.as_deref(), Some("config-secret"));
+        assert_eq!(legacy.token_uri, GOOGLE_TOKEN_URI);
+        assert_eq!(
+            legacy.auth_method,
+            TokenEndpointAuthMethod::ClientSecretBasic
+        );
+
+        assert!(matches!(
+            OAuthClient::resolve(&json!({}), None, Some(GOOGLE_TOKEN_URI)),
+            Err(TokenRefreshError::ReauthorizationRequired(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_token_response() {
+        let now = noon();
+
+        let refreshed = parse_token_response(
+            200,
+            r#"{"access_token":"new","refresh_token":"rotated","expires_in":3600,"scope":"read write"}"#,
+            now,
+        )
+        .unwrap();
+        assert_eq!(refreshed.access_token, "new");
+        assert_eq!(refreshed.refresh_token.as_deref(), Some("rotated"));
+        assert_eq!(refreshed.expires_at, Some(now + Duration::hours(1)));
+        assert_eq!(
+            refreshed.scopes,
+            Some(vec!["read".to_string(), "write".to_string()])
+        );
+
+        assert!(matches!(
+            parse_token_response(
+                400,
+                r#"{"error":"invalid_grant","error_description":"Token has been revoked"}"#,
+                now
+            ),
+            Err(TokenRefreshError::ReauthorizationRequired(reason))
+                if reason == "invalid_grant: Token has been revoked"
+        ));
+        assert!(matches!(
+            parse_token_response(429, r#"{"error":"rate_limited"}"#, now),
+            Err(TokenRefreshError::Transient(_))
+        ));
+        assert!(matches!(
+            parse_token_response(503, "Service Unavailable", now),
+            Err(TokenRefreshError::Transient(_))
+        ));
+    }
+
+    #[test]
+    fn test_apply_refreshed_token_keeps_other_fields() {
+        let now = noon();
+        let mut creds = oauth_credential(
+            json!({
+                "access_token": "old",
+                "refresh_token": "old-refresh",
+                "client_id": "client",
+                "expires_at": now.unix_timestamp(),
+            }),
+            Some(now),
+        );
+
+        RefreshedToken {
+            access_token: "new".to_string(),
+            refresh_token: None,
+            expires_at: Some(now + Duration::hours(1)),
+            scopes: Some(vec!["read".to_string()]),
+        }
+        .apply_to(&mut creds);
+
+        assert_eq!(creds.credentials["access_token"], "new");
+        assert_eq!(creds.credentials["refresh_token"], "old-refresh");
+        assert_eq!(creds.credentials["client_id"], "client");
+        assert_eq!(
+            creds.credentials["expires_at"],
+            (now + Duration::hours(1)).unix_timestamp()
+        );
+        assert_eq!(creds.expires_at, Some(now + Duration::hours(1)));
+        assert_eq!(creds.config["granted_scopes"], json!(["read"]));
+    }
+}
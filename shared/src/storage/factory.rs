@@ -35,10 +35,10 @@ impl StorageFactory {
     pub async fn from_env(pool: PgPool) -> Result<Arc<dyn ObjectStorage>, StorageError> {
         let backend = StorageBackend::from_env();
 
-        match backend {
+        let storage: Arc<dyn ObjectStorage> = match backend {
             StorageBackend::Postgres => {
                 info!("Initializing PostgreSQL storage backend");
-                Ok(Arc::new(PostgresStorage::new(pool)))
+                Arc::new(PostgresStorage::new(pool))
             }
             StorageBackend::S3 => {
                 info!("Initializing S3 storage backend");
@@ -62,9 +62,13 @@ impl StorageFactory {
                 }
 
                 let s3_storage = S3Storage::new(bucket, region, endpoint, pool).await?;
-                Ok(Arc::new(s3_storage))
+                Arc::new(s3_storage)
             }
-        }
+        };
+
+        #[cfg(feature = "fault-injection")]
+        let storage = super::fault_injecting::FaultInjectingStorage::wrap(storage);
+        Ok(storage)
     }
 
     /// Create storage backend with explicit configuration
@@ -0,0 +1,90 @@
+use super::{ContentMetadata, ObjectStorage, StorageError};
+use crate::fault_injection::{self, FaultTarget, InjectedFault};
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+impl From<InjectedFault> for StorageError {
+    fn from(fault: InjectedFault) -> Self {
+        StorageError::Backend(fault.to_string())
+    }
+}
+
+/// Storage backend that passes every operation through the fault injector
+/// before delegating to the wrapped backend.
+pub struct FaultInjectingStorage {
+    inner: Arc<dyn ObjectStorage>,
+}
+
+impl FaultInjectingStorage {
+    pub fn wrap(inner: Arc<dyn ObjectStorage>) -> Arc<dyn ObjectStorage> {
+        Arc::new(Self { inner })
+    }
+}
+
+#[async_trait]
+impl ObjectStorage for FaultInjectingStorage {
+    async fn store_content(
+        &self,
+        content: &[u8],
+        prefix: Option<&str>,
+    ) -> Result<String, StorageError> {
+        fault_injection::inject(FaultTarget::Storage, "store_content").await?;
+        self.inner.store_content(content, prefix).await
+    }
+
+    async fn store_content_with_type(
+        &self,
+        content: &[u8],
+        content_type: Option<&str>,
+        prefix: Option<&str>,
+    ) -> Result<String, StorageError> {
+        fault_injection::inject(FaultTarget::Storage, "store_content").await?;
+        self.inner
+            .store_content_with_type(content, content_type, prefix)
+            .await
+    }
+
+    async fn get_content(&self, content_id: &str) -> Result<Vec<u8>, StorageError> {
+        fault_injection::inject(FaultTarget::Storage, "get_content").await?;
+        self.inner.get_content(content_id).await
+    }
+
+    async fn delete_content(&self, content_id: &str) -> Result<(), StorageError> {
+        fault_injection::inject(FaultTarget::Storage, "delete_content").await?;
+        self.inner.delete_content(content_id).await
+    }
+
+    async fn get_content_size(&self, content_id: &str) -> Result<i64, StorageError> {
+        fault_injection::inject(FaultTarget::Storage, "get_content_size").await?;
+        self.inner.get_content_size(content_id).await
+    }
+
+    async fn batch_get_text(
+        &self,
+        content_ids: Vec<String>,
+    ) -> Result<HashMap<String, String>, StorageError> {
+        fault_injection::inject(FaultTarget::Storage, "batch_get_text").await?;
+        let mut texts = self.inner.batch_get_text(content_ids).await?;
+        let kept =
+            fault_injection::partial_len(FaultTarget::Storage, "batch_get_text", texts.len());
+        let dropped: Vec<String> = texts.keys().skip(kept).cloned().collect();
+        for content_id in dropped {
+            texts.remove(&content_id);
+        }
+        Ok(texts)
+    }
+
+    async fn get_content_metadata(
+        &self,
+        content_id: &str,
+    ) -> Result<ContentMetadata, StorageError> {
+        fault_injection::inject(FaultTarget::Storage, "get_content_metadata").await?;
+        self.inner.get_content_metadata(content_id).await
+    }
+
+    async fn find_by_hash(&self, sha256_hash: &str) -> Result<Option<String>, StorageError> {
+        fault_injection::inject(FaultTarget::Storage, "find_by_hash").await?;
+        self.inner.find_by_hash(sha256_hash).await
+    }
+}
@@ -1,8 +1,9 @@
 use super::{ObjectStorage, StorageError};
-use crate::db::repositories::{ContentBlobRepository, OrphanStats};
+use crate::db::repositories::{ContentBlobRepository, GCRun, OrphanStats, ReclaimedStorageStats};
 use anyhow::Result;
 use serde::Serialize;
 use sqlx::PgPool;
+use sqlx::types::time::OffsetDateTime;
 use std::sync::Arc;
 use tracing::{debug, info, warn};
 
@@ -83,8 +84,9 @@ impl ContentBlobGC {
         }
     }
 
-    /// Run full GC cycle
+    /// Run full GC cycle and record it in the run history
     pub async fn run(&self) -> Result<GCResult> {
+        let started_at = OffsetDateTime::now_utc();
         info!("Starting content blob garbage collection");
         info!(
             "Config: retention_days={}, batch_size={}, dry_run={}",
@@ -117,6 +119,21 @@ impl ContentBlobGC {
             result.errors.len()
         );
 
+        let run = GCRun {
+            id: crate::utils::generate_ulid(),
+            started_at,
+            completed_at: OffsetDateTime::now_utc(),
+            dry_run: self.config.dry_run,
+            orphans_marked: result.orphans_marked,
+            orphans_unmarked: result.orphans_unmarked,
+            blobs_deleted: result.blobs_deleted,
+            bytes_reclaimed: result.bytes_reclaimed,
+            error_count: result.errors.len() as i32,
+        };
+        if let Err(e) = self.repo.record_gc_run(&run).await {
+            warn!("Failed to record GC run: {}", e);
+        }
+
         Ok(result)
     }
 
@@ -187,6 +204,14 @@ impl ContentBlobGC {
             .get_orphan_stats(self.config.retention_days)
             .await?)
     }
+
+    /// Get storage reclaimed by past runs
+    pub async fn get_reclaimed_storage_stats(
+        &self,
+        recent_limit: i64,
+    ) -> Result<ReclaimedStorageStats> {
+        Ok(self.repo.get_reclaimed_storage_stats(recent_limit).await?)
+    }
 }
 
 #[cfg(test)]
@@ -1,4 +1,6 @@
 pub mod factory;
+#[cfg(feature = "fault-injection")]
+pub mod fault_injecting;
 pub mod gc;
 pub mod postgres;
 pub mod s3;
@@ -353,6 +353,11 @@ impl MockAIServer {
             Json(GenerateResponse { response })
         }
 
+        // Mock streaming prompt endpoint, used for AI answers
+        async fn mock_prompt() -> &'static str {
+            "Mock streamed answer [1]"
+        }
+
         async fn health() -> (axum::http::StatusCode, &'static str) {
             (axum::http::StatusCode::OK, "OK")
         }
@@ -361,6 +366,7 @@ impl MockAIServer {
             .route("/embeddings", post(mock_embeddings))
             .route("/rag", post(mock_rag))
             .route("/generate", post(mock_generate))
+            .route("/prompt", post(mock_prompt))
             .route("/health", get(health));
 
         // Find available port
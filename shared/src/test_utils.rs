@@ -1,3 +1,5 @@
+pub mod mock_connector;
+
 use crate::db::pool::DatabasePool;
 use crate::db::repositories::DocumentRepository;
 use crate::models::Document;
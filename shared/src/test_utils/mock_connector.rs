@@ -0,0 +1,534 @@
+//! In-process stand-in for a connector service, for connector-manager and
+//! end-to-end tests.
+//!
+//! The mock speaks the connector HTTP contract (`/health`, `/manifest`,
+//! `/sync`, `/sync/:id`, `/cancel`, `/action`) and records every request.
+//! What happens after a sync trigger is scripted per test with
+//! [`SyncBehavior`]; behaviors other than [`SyncBehavior::Idle`] call back into
+//! the connector manager's SDK endpoints (heartbeat, content, events,
+//! complete/fail), so the manager must be listening on a real address set via
+//! [`MockConnector::set_manager_url`].
+
+use crate::models::{ConnectorEvent, DocumentMetadata, DocumentPermissions};
+use axum::{
+    Router,
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+    routing::{get, post},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value as JsonValue, json};
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use tokio::net::TcpListener;
+use tokio::sync::oneshot;
+use tokio::time::{Duration, sleep};
+use tracing::warn;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedSyncRequest {
+    pub sync_run_id: String,
+    pub source_id: String,
+    pub sync_mode: String,
+    #[serde(default)]
+    pub last_sync_at: Option<String>,
+    #[serde(default)]
+    pub checkpoint: Option<JsonValue>,
+    #[serde(default)]
+    pub is_resume: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedCancelRequest {
+    pub sync_run_id: String,
+}
+
+/// What the mock does after accepting a sync trigger.
+#[derive(Debug, Clone, Default)]
+pub enum SyncBehavior {
+    /// Accept the trigger and report the sync as running until it is
+    /// cancelled. Makes no SDK callbacks.
+    #[default]
+    Idle,
+    /// Emit `documents` small documents, then complete.
+    Complete { documents: usize },
+    /// Heartbeat every `heartbeat_interval` until `duration` has passed, then
+    /// complete. Stops early, without completing, if cancelled.
+    Slow {
+        duration: Duration,
+        heartbeat_interval: Duration,
+    },
+    /// Report the sync as failed with `message` after `after`.
+    Fail { message: String, after: Duration },
+    /// Emit `documents` documents of `content_bytes` each in batches of
+    /// `batch_size`, heartbeating between batches, then complete.
+    Huge {
+        documents: usize,
+        content_bytes: usize,
+        batch_size: usize,
+    },
+}
+
+struct MockState {
+    sync_requests: Mutex<Vec<RecordedSyncRequest>>,
+    cancel_requests: Mutex<Vec<RecordedCancelRequest>>,
+    action_requests: Mutex<Vec<JsonValue>>,
+    sync_response_status: Mutex<StatusCode>,
+    sync_response_body: Mutex<JsonValue>,
+    /// Remaining sync triggers to reject with 503 before accepting again.
+    flaky_triggers: Mutex<u32>,
+    behavior: Mutex<SyncBehavior>,
+    manifest: Mutex<JsonValue>,
+    action_response: Mutex<JsonValue>,
+    manager_url: Mutex<Option<String>>,
+    active_syncs: Mutex<HashSet<String>>,
+    /// When false, `GET /sync/{id}` returns 404 — lets a test exercise the
+    /// "connector hasn't implemented the status endpoint" path (current
+    /// Rust connectors).
+    status_endpoint_enabled: Mutex<bool>,
+    http: reqwest::Client,
+}
+
+impl MockState {
+    fn is_active(&self, source_id: &str) -> bool {
+        self.active_syncs.lock().unwrap().contains(source_id)
+    }
+
+    fn finish(&self, source_id: &str) {
+        self.active_syncs.lock().unwrap().remove(source_id);
+    }
+}
+
+pub struct MockConnector {
+    pub base_url: String,
+    port: u16,
+    state: Arc<MockState>,
+    server_handle: Mutex<tokio::task::JoinHandle<()>>,
+    shutdown_tx: Mutex<Option<oneshot::Sender<()>>>,
+}
+
+impl MockConnector {
+    pub async fn start() -> anyhow::Result<Self> {
+        let state = Arc::new(MockState {
+            sync_requests: Mutex::new(Vec::new()),
+            cancel_requests: Mutex::new(Vec::new()),
+            action_requests: Mutex::new(Vec::new()),
+            sync_response_status: Mutex::new(StatusCode::OK),
+            sync_response_body: Mutex::new(json!({"status": "accepted"})),
+            flaky_triggers: Mutex::new(0),
+            behavior: Mutex::new(SyncBehavior::Idle),
+            manifest: Mutex::new(json!({
+                "name": "mock-connector",
+                "version": "1.0.0",
+                "sync_modes": ["full", "incremental"],
+                "actions": []
+            })),
+            action_response: Mutex::new(json!({"status": "success", "result": {}})),
+            manager_url: Mutex::new(None),
+            active_syncs: Mutex::new(HashSet::new()),
+            status_endpoint_enabled: Mutex::new(true),
+            http: reqwest::Client::new(),
+        });
+
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let port = listener.local_addr()?.port();
+
+        let (shutdown_tx, shutdown_rx) = oneshot::channel();
+        let server_handle = spawn_server(listener, shutdown_rx, state.clone());
+
+        sleep(Duration::from_millis(50)).await;
+
+        Ok(Self {
+            base_url: format!("http://127.0.0.1:{}", port),
+            port,
+            state,
+            server_handle: Mutex::new(server_handle),
+            shutdown_tx: Mutex::new(Some(shutdown_tx)),
+        })
+    }
+
+    /// Kill the HTTP server without restarting. Simulates a connector that
+    /// is down but whose registration is still live in Redis (i.e. within
+    /// the 90s TTL window). Probes will fail with connection-refused.
+    ///
+    /// Uses graceful_shutdown (not `abort()`) so axum actually closes
+    /// pooled keep-alive connections — otherwise reqwest's idle conn on the
+    /// caller side would happily roundtrip to still-alive handler tasks.
+    pub async fn stop(&self) {
+        if let Some(tx) = self.shutdown_tx.lock().unwrap().take() {
+            let _ = tx.send(());
+        }
+        let handle = {
+            let mut h = self.server_handle.lock().unwrap();
+            std::mem::replace(&mut *h, tokio::spawn(async {}))
+        };
+        let _ = tokio::time::timeout(Duration::from_secs(2), handle).await;
+    }
+
+    /// Kill and restart the HTTP server on the same port. In-memory state
+    /// (`active_syncs`) is cleared — matching what a real connector process
+    /// loses on restart, and stopping any scripted sync still in flight.
+    /// Recorded history (sync, cancel and action requests) is preserved so
+    /// tests can still inspect it.
+    pub async fn restart(&self) -> anyhow::Result<()> {
+        self.stop().await;
+        self.state.active_syncs.lock().unwrap().clear();
+
+        // Give the kernel a moment to release the port.
+        let listener = loop {
+            match TcpListener::bind(format!("127.0.0.1:{}", self.port)).await {
+                Ok(l) => break l,
+                Err(_) => sleep(Duration::from_millis(20)).await,
+            }
+        };
+
+        let (shutdown_tx, shutdown_rx) = oneshot::channel();
+        let new_handle = spawn_server(listener, shutdown_rx, self.state.clone());
+        *self.server_handle.lock().unwrap() = new_handle;
+        *self.shutdown_tx.lock().unwrap() = Some(shutdown_tx);
+        sleep(Duration::from_millis(50)).await;
+        Ok(())
+    }
+
+    pub fn set_sync_response(&self, status: StatusCode, body: JsonValue) {
+        *self.state.sync_response_status.lock().unwrap() = status;
+        *self.state.sync_response_body.lock().unwrap() = body;
+    }
+
+    pub fn set_status_endpoint_enabled(&self, enabled: bool) {
+        *self.state.status_endpoint_enabled.lock().unwrap() = enabled;
+    }
+
+    /// Script what happens after subsequent sync triggers are accepted.
+    pub fn set_behavior(&self, behavior: SyncBehavior) {
+        *self.state.behavior.lock().unwrap() = behavior;
+    }
+
+    /// Reject the next `count` sync triggers with 503 before accepting again.
+    /// Rejected triggers are not recorded.
+    pub fn fail_next_triggers(&self, count: u32) {
+        *self.state.flaky_triggers.lock().unwrap() = count;
+    }
+
+    /// Base URL of the connector manager, for SDK callbacks.
+    pub fn set_manager_url(&self, url: impl Into<String>) {
+        *self.state.manager_url.lock().unwrap() = Some(url.into());
+    }
+
+    /// Replace the manifest served at `/manifest`.
+    pub fn set_manifest(&self, manifest: JsonValue) {
+        *self.state.manifest.lock().unwrap() = manifest;
+    }
+
+    /// Replace the body returned for every `/action` call.
+    pub fn set_action_response(&self, response: JsonValue) {
+        *self.state.action_response.lock().unwrap() = response;
+    }
+
+    pub fn get_sync_requests(&self) -> Vec<RecordedSyncRequest> {
+        self.state.sync_requests.lock().unwrap().clone()
+    }
+
+    pub fn get_cancel_requests(&self) -> Vec<RecordedCancelRequest> {
+        self.state.cancel_requests.lock().unwrap().clone()
+    }
+
+    pub fn get_action_requests(&self) -> Vec<JsonValue> {
+        self.state.action_requests.lock().unwrap().clone()
+    }
+
+    /// Whether a sync for `source_id` is currently running on the mock.
+    pub fn is_sync_active(&self, source_id: &str) -> bool {
+        self.state.is_active(source_id)
+    }
+}
+
+fn spawn_server(
+    listener: TcpListener,
+    shutdown_rx: oneshot::Receiver<()>,
+    state: Arc<MockState>,
+) -> tokio::task::JoinHandle<()> {
+    let app = Router::new()
+        .route("/health", get(health))
+        .route("/manifest", get(handle_manifest))
+        .route("/sync", post(handle_sync))
+        .route("/sync/:sync_run_id", get(handle_sync_status))
+        .route("/cancel", post(handle_cancel))
+        .route("/action", post(handle_action))
+        .with_state(state);
+
+    tokio::spawn(async move {
+        let _ = axum::serve(listener, app)
+            .with_graceful_shutdown(async move {
+                let _ = shutdown_rx.await;
+            })
+            .await;
+    })
+}
+
+async fn health() -> StatusCode {
+    StatusCode::OK
+}
+
+async fn handle_manifest(State(state): State<Arc<MockState>>) -> Json<JsonValue> {
+    Json(state.manifest.lock().unwrap().clone())
+}
+
+async fn handle_sync(
+    State(state): State<Arc<MockState>>,
+    Json(request): Json<RecordedSyncRequest>,
+) -> (StatusCode, Json<JsonValue>) {
+    {
+        let mut flaky = state.flaky_triggers.lock().unwrap();
+        if *flaky > 0 {
+            *flaky -= 1;
+            return (
+                StatusCode::SERVICE_UNAVAILABLE,
+                Json(json!({"error": "mock connector unavailable"})),
+            );
+        }
+    }
+
+    let source_id = request.source_id.clone();
+    {
+        let mut active = state.active_syncs.lock().unwrap();
+        if active.contains(&source_id) {
+            return (
+                StatusCode::CONFLICT,
+                Json(json!({"error": "sync already running for this source"})),
+            );
+        }
+        active.insert(source_id.clone());
+    }
+    state.sync_requests.lock().unwrap().push(request.clone());
+
+    let status = *state.sync_response_status.lock().unwrap();
+    let body = state.sync_response_body.lock().unwrap().clone();
+    if status.is_success() {
+        let behavior = state.behavior.lock().unwrap().clone();
+        if !matches!(behavior, SyncBehavior::Idle) {
+            tokio::spawn(run_sync(state.clone(), request, behavior));
+        }
+    }
+    (status, Json(body))
+}
+
+async fn handle_sync_status(
+    State(state): State<Arc<MockState>>,
+    Path(sync_run_id): Path<String>,
+) -> (StatusCode, Json<JsonValue>) {
+    if !*state.status_endpoint_enabled.lock().unwrap() {
+        return (StatusCode::NOT_FOUND, Json(json!({"error": "not found"})));
+    }
+    let source_id = state
+        .sync_requests
+        .lock()
+        .unwrap()
+        .iter()
+        .find(|r| r.sync_run_id == sync_run_id)
+        .map(|r| r.source_id.clone());
+    let running = source_id.is_some_and(|s| state.is_active(&s));
+    (StatusCode::OK, Json(json!({"running": running})))
+}
+
+async fn handle_cancel(
+    State(state): State<Arc<MockState>>,
+    Json(request): Json<RecordedCancelRequest>,
+) -> StatusCode {
+    let source_id = state
+        .sync_requests
+        .lock()
+        .unwrap()
+        .iter()
+        .find(|r| r.sync_run_id == request.sync_run_id)
+        .map(|r| r.source_id.clone());
+    if let Some(source_id) = source_id {
+        state.finish(&source_id);
+    }
+    state.cancel_requests.lock().unwrap().push(request);
+    StatusCode::OK
+}
+
+async fn handle_action(
+    State(state): State<Arc<MockState>>,
+    Json(request): Json<JsonValue>,
+) -> Json<JsonValue> {
+    state.action_requests.lock().unwrap().push(request);
+    Json(state.action_response.lock().unwrap().clone())
+}
+
+/// Drive one scripted sync against the manager's SDK endpoints. Any SDK
+/// failure is logged and ends the run; the manager's stale-sync detection is
+/// then what a test observes, as it would be for a crashed connector.
+async fn run_sync(state: Arc<MockState>, request: RecordedSyncRequest, behavior: SyncBehavior) {
+    let Some(manager_url) = state.manager_url.lock().unwrap().clone() else {
+        warn!(
+            "Mock connector has no manager URL; ignoring {:?} for sync {}",
+            behavior, request.sync_run_id
+        );
+        return;
+    };
+    let sdk = SdkCallbacks {
+        state: &state,
+        manager_url: &manager_url,
+        request: &request,
+    };
+
+    if let Err(e) = sdk.run(behavior).await {
+        warn!("Mock connector sync {} aborted: {}", request.sync_run_id, e);
+    }
+    state.finish(&request.source_id);
+}
+
+struct SdkCallbacks<'a> {
+    state: &'a MockState,
+    manager_url: &'a str,
+    request: &'a RecordedSyncRequest,
+}
+
+impl SdkCallbacks<'_> {
+    async fn run(&self, behavior: SyncBehavior) -> anyhow::Result<()> {
+        match behavior {
+            SyncBehavior::Idle => Ok(()),
+            SyncBehavior::Complete { documents } => {
+                self.emit_documents(0, documents, 64).await?;
+                self.complete().await
+            }
+            SyncBehavior::Slow {
+                duration,
+                heartbeat_interval,
+            } => {
+                let deadline = tokio::time::Instant::now() + duration;
+                while tokio::time::Instant::now() < deadline {
+                    if !self.is_active() {
+                        return Ok(());
+                    }
+                    self.post("heartbeat", None).await?;
+                    sleep(heartbeat_interval).await;
+                }
+                self.complete().await
+            }
+            SyncBehavior::Fail { message, after } => {
+                sleep(after).await;
+                if !self.is_active() {
+                    return Ok(());
+                }
+                self.post("fail", Some(json!({ "error": message }))).await
+            }
+            SyncBehavior::Huge {
+                documents,
+                content_bytes,
+                batch_size,
+            } => {
+                let batch_size = batch_size.max(1);
+                let mut start = 0;
+                while start < documents {
+                    if !self.is_active() {
+                        return Ok(());
+                    }
+                    let count = batch_size.min(documents - start);
+                    self.emit_documents(start, count, content_bytes).await?;
+                    self.post("heartbeat", None).await?;
+                    start += count;
+                }
+                self.complete().await
+            }
+        }
+    }
+
+    fn is_active(&self) -> bool {
+        self.state.is_active(&self.request.source_id)
+    }
+
+    async fn complete(&self) -> anyhow::Result<()> {
+        if self.is_active() {
+            self.post("complete", None).await?;
+        }
+        Ok(())
+    }
+
+    async fn post(&self, action: &str, body: Option<JsonValue>) -> anyhow::Result<()> {
+        let url = format!(
+            "{}/sdk/sync/{}/{}",
+            self.manager_url, self.request.sync_run_id, action
+        );
+        let mut request = self.state.http.post(&url);
+        if let Some(body) = body {
+            request = request.json(&body);
+        }
+        request.send().await?.error_for_status()?;
+        Ok(())
+    }
+
+    /// Store content for and emit `count` documents numbered from `start`.
+    async fn emit_documents(
+        &self,
+        start: usize,
+        count: usize,
+        content_bytes: usize,
+    ) -> anyhow::Result<()> {
+        let mut events = Vec::with_capacity(count);
+        for n in start..start + count {
+            let content = synthetic_content(n, content_bytes);
+            let stored: JsonValue = self
+                .state
+                .http
+                .post(format!("{}/sdk/content", self.manager_url))
+                .json(&json!({
+                    "sync_run_id": self.request.sync_run_id,
+                    "content": content,
+                    "content_type": "text/plain",
+                }))
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+            let content_id = stored["content_id"]
+                .as_str()
+                .ok_or_else(|| anyhow::anyhow!("content response missing content_id"))?
+                .to_string();
+
+            events.push(ConnectorEvent::DocumentCreated {
+                sync_run_id: self.request.sync_run_id.clone(),
+                source_id: self.request.source_id.clone(),
+                document_id: format!("mock-doc-{}", n),
+                content_id,
+                metadata: DocumentMetadata {
+                    title: Some(format!("Mock document {}", n)),
+                    url: Some(format!("https://mock.invalid/docs/{}", n)),
+                    mime_type: Some("text/plain".to_string()),
+                    ..Default::default()
+                },
+                permissions: DocumentPermissions {
+                    public: true,
+                    users: vec![],
+                    groups: vec![],
+                },
+                attributes: None,
+            });
+        }
+
+        self.state
+            .http
+            .post(format!("{}/sdk/events/batch", self.manager_url))
+            .json(&json!({
+                "sync_run_id": self.request.sync_run_id,
+                "source_id": self.request.source_id,
+                "events": events,
+            }))
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+/// Deterministic filler text of roughly `bytes` bytes for document `n`.
+fn synthetic_content(n: usize, bytes: usize) -> String {
+    let sentence = format!("Mock document {} contains synthetic searchable text. ", n);
+    let mut content = sentence.repeat(bytes / sentence.len() + 1);
+    content.truncate(bytes.max(sentence.len()));
+    content
+}
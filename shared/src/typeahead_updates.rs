@@ -0,0 +1,119 @@
+//! Notifications that keep the searcher's typeahead index current.
+//!
+//! The indexer publishes which documents it created, changed or removed on a
+//! Redis channel after writing them. The searcher reloads just those
+//! documents into its in-memory title index instead of rebuilding it.
+//! Delivery is best effort: a searcher that was not subscribed misses the
+//! update and relies on its periodic full refresh.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tracing::warn;
+
+pub const TYPEAHEAD_UPDATES_CHANNEL: &str = "typeahead:updates";
+
+/// Documents of one source whose titles, permissions or existence changed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TitleUpdate {
+    pub source_id: String,
+    /// External ids of the changed documents; `None` when any document of
+    /// the source may have changed.
+    pub external_ids: Option<Vec<String>>,
+}
+
+impl TitleUpdate {
+    pub fn documents(source_id: &str, external_ids: Vec<String>) -> Self {
+        Self {
+            source_id: source_id.to_string(),
+            external_ids: Some(external_ids),
+        }
+    }
+
+    pub fn source(source_id: &str) -> Self {
+        Self {
+            source_id: source_id.to_string(),
+            external_ids: None,
+        }
+    }
+}
+
+/// Group changed documents by source, widening a source to all its
+/// documents when any of its changes does.
+pub fn group_by_source(updates: impl IntoIterator<Item = TitleUpdate>) -> Vec<TitleUpdate> {
+    let mut by_source: BTreeMap<String, Option<Vec<String>>> = BTreeMap::new();
+    for update in updates {
+        let entry = by_source
+            .entry(update.source_id)
+            .or_insert_with(|| Some(Vec::new()));
+        match (entry.as_mut(), update.external_ids) {
+            (Some(ids), Some(new_ids)) => ids.extend(new_ids),
+            _ => *entry = None,
+        }
+    }
+    by_source
+        .into_iter()
+        .map(|(source_id, external_ids)| TitleUpdate {
+            source_id,
+            external_ids: external_ids.map(|mut ids| {
+                ids.sort();
+                ids.dedup();
+                ids
+            }),
+        })
+        .collect()
+}
+
+/// Publish `updates`. Failures are logged only: the searcher still picks
+/// the changes up on its next full refresh.
+pub async fn publish(redis_client: &redis::Client, updates: impl IntoIterator<Item = TitleUpdate>) {
+    let updates = group_by_source(updates);
+    if updates.is_empty() {
+        return;
+    }
+
+    let mut pipe = redis::pipe();
+    for update in &updates {
+        match serde_json::to_string(update) {
+            Ok(payload) => {
+                pipe.publish(TYPEAHEAD_UPDATES_CHANNEL, payload).ignore();
+            }
+            Err(e) => warn!("Failed to serialize typeahead update: {}", e),
+        }
+    }
+    let result = match redis_client.get_multiplexed_async_connection().await {
+        Ok(mut conn) => pipe.query_async::<()>(&mut conn).await,
+        Err(e) => Err(e),
+    };
+    if let Err(e) = result {
+        warn!(
+            "Failed to publish typeahead updates for {} sources: {}",
+            updates.len(),
+            e
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_group_by_source_merges_documents_and_widens_to_sources() {
+        let grouped = group_by_source([
+            TitleUpdate::documents("b", vec!["2".into(), "1".into()]),
+            TitleUpdate::documents("a", vec!["x".into()]),
+            TitleUpdate::documents("b", vec!["1".into()]),
+            TitleUpdate::documents("c", vec!["y".into()]),
+            TitleUpdate::source("c"),
+            TitleUpdate::documents("c", vec!["z".into()]),
+        ]);
+        assert_eq!(
+            grouped,
+            vec![
+                TitleUpdate::documents("a", vec!["x".into()]),
+                TitleUpdate::documents("b", vec!["1".into(), "2".into()]),
+                TitleUpdate::source("c"),
+            ]
+        );
+    }
+}
@@ -1,3 +1,4 @@
+use sha2::{Digest, Sha256};
 use std::sync::{LazyLock, Mutex};
 use ulid::Generator;
 
@@ -134,6 +135,31 @@ pub fn normalize_whitespace(text: &str) -> String {
     final_result.trim().to_string()
 }
 
+/// Documents with fewer words than this get no fingerprint, so short
+/// messages like "thanks!" are never treated as duplicates of each other.
+const MIN_FINGERPRINT_WORDS: usize = 20;
+
+/// Fingerprint identifying near-identical document text across sources.
+///
+/// The same file extracted by different connectors tends to differ only in
+/// whitespace, punctuation and casing, so the hash covers just the lowercased
+/// alphanumeric words. Returns `None` for text too short to fingerprint.
+pub fn content_fingerprint(text: &str) -> Option<String> {
+    let mut hasher = Sha256::new();
+    let mut words = 0;
+    for word in text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+    {
+        if words > 0 {
+            hasher.update(b" ");
+        }
+        hasher.update(word.to_lowercase().as_bytes());
+        words += 1;
+    }
+    (words >= MIN_FINGERPRINT_WORDS).then(|| format!("{:x}", hasher.finalize()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -272,4 +298,25 @@ mod tests {
         let expected = "Hello\n\nworld foo\n\nbar";
         assert_eq!(normalize_whitespace(input), expected);
     }
+
+    #[test]
+    fn test_content_fingerprint_ignores_formatting() {
+        let text = "Quarterly planning notes: the team agreed to ship search dedupe, \
+                    migrate the indexer, and review onboarding docs before the offsite in May.";
+        let reformatted = "QUARTERLY PLANNING NOTES\n\nThe team agreed to ship search dedupe; \
+                           migrate the indexer - and review onboarding docs before the offsite in May";
+
+        assert!(content_fingerprint(text).is_some());
+        assert_eq!(content_fingerprint(text), content_fingerprint(reformatted));
+        assert_ne!(
+            content_fingerprint(text),
+            content_fingerprint(&text.replace("May", "June"))
+        );
+    }
+
+    #[test]
+    fn test_content_fingerprint_skips_short_text() {
+        assert_eq!(content_fingerprint("thanks, looks good to me!"), None);
+        assert_eq!(content_fingerprint(""), None);
+    }
 }
@@ -0,0 +1,89 @@
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use shared::audit::{Actor, AuditAction, AuditEvent};
+    use shared::db::repositories::{AuditLogFilter, AuditLogRepository};
+    use shared::test_environment::TestEnvironment;
+    use time::{Duration, OffsetDateTime};
+
+    #[tokio::test]
+    async fn test_search_filters_by_actor_and_action() {
+        let env = TestEnvironment::new().await.unwrap();
+        let repo = AuditLogRepository::new(env.db_pool.pool());
+
+        let ada = Actor::user(Some("user-1"), Some("Ada@Example.com"));
+        let inserted = repo
+            .insert(&[
+                AuditEvent::new(ada.clone(), AuditAction::Search)
+                    .details(json!({ "query": "roadmap" })),
+                AuditEvent::new(ada, AuditAction::DocumentRead).resource("document", "doc-1"),
+                AuditEvent::new(Actor::connector("source-1"), AuditAction::DocumentIndexed)
+                    .resource("document", "doc-1"),
+            ])
+            .await
+            .unwrap();
+        assert_eq!(inserted, 3);
+
+        let by_email = AuditLogFilter {
+            actor: Some("ada@example.com".to_string()),
+            ..Default::default()
+        };
+        let (entries, total) = repo.search(&by_email, 10, 0).await.unwrap();
+        assert_eq!(total, 2);
+        assert!(
+            entries
+                .iter()
+                .all(|e| e.actor_id.as_deref() == Some("user-1"))
+        );
+
+        let reads = AuditLogFilter {
+            actor: Some("user-1".to_string()),
+            action: Some(AuditAction::DocumentRead),
+            ..Default::default()
+        };
+        let (entries, total) = repo.search(&reads, 10, 0).await.unwrap();
+        assert_eq!(total, 1);
+        assert_eq!(entries[0].resource_id.as_deref(), Some("doc-1"));
+
+        let future = AuditLogFilter {
+            from: Some(OffsetDateTime::now_utc() + Duration::hours(1)),
+            ..Default::default()
+        };
+        let (_, total) = repo.search(&future, 10, 0).await.unwrap();
+        assert_eq!(total, 0);
+    }
+
+    #[tokio::test]
+    async fn test_entries_are_append_only_except_for_retention() {
+        let env = TestEnvironment::new().await.unwrap();
+        let pool = env.db_pool.pool();
+        let repo = AuditLogRepository::new(pool);
+        repo.insert(&[AuditEvent::new(
+            Actor::system(),
+            AuditAction::ConfigurationChanged,
+        )])
+        .await
+        .unwrap();
+
+        let updated = sqlx::query("UPDATE audit_log SET action = 'search'")
+            .execute(pool)
+            .await;
+        assert!(updated.is_err(), "updates should be rejected");
+        let deleted = sqlx::query("DELETE FROM audit_log").execute(pool).await;
+        assert!(
+            deleted.is_err(),
+            "deletes outside retention should be rejected"
+        );
+
+        let kept = repo
+            .delete_older_than(OffsetDateTime::now_utc() - Duration::days(1))
+            .await
+            .unwrap();
+        assert_eq!(kept, 0);
+        let pruned = repo
+            .delete_older_than(OffsetDateTime::now_utc() + Duration::minutes(1))
+            .await
+            .unwrap();
+        assert_eq!(pruned, 1);
+    }
+}
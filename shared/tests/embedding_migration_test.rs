@@ -0,0 +1,152 @@
+#[cfg(test)]
+mod tests {
+    use shared::db::repositories::{EmbeddingMigrationRepository, EmbeddingMigrationStatus};
+    use shared::embedding_queue::EmbeddingQueue;
+    use shared::test_utils::BaseTestFixture;
+    use sqlx::PgPool;
+    use ulid::Ulid;
+
+    const TEST_SOURCE_ID: &str = "01JGF7V3E0Y2R1X8P5Q7W9T4N7";
+
+    async fn insert_provider(pool: &PgPool, model: &str, is_current: bool) -> String {
+        let id = Ulid::new().to_string();
+        sqlx::query(
+            r#"
+            INSERT INTO embedding_providers (id, name, provider_type, config, is_current, is_deleted)
+            VALUES ($1, $2, 'local', jsonb_build_object('model', $2::text), $3, FALSE)
+            "#,
+        )
+        .bind(&id)
+        .bind(model)
+        .bind(is_current)
+        .execute(pool)
+        .await
+        .unwrap();
+        id
+    }
+
+    async fn create_document(pool: &PgPool) -> String {
+        let doc_id = Ulid::new().to_string();
+        let content_id = Ulid::new().to_string();
+        sqlx::query(
+            r#"
+            INSERT INTO content_blobs (id, content, size_bytes, storage_backend)
+            VALUES ($1, 'content', 7, 'postgres')
+            "#,
+        )
+        .bind(&content_id)
+        .execute(pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            r#"
+            INSERT INTO documents (id, source_id, external_id, title, content_id, metadata, permissions, attributes, created_at, updated_at)
+            VALUES ($1, $2, $3, 'Test Doc', $4, '{}', '{"users":["u1"]}', '{}', NOW(), NOW())
+            "#,
+        )
+        .bind(&doc_id)
+        .bind(TEST_SOURCE_ID)
+        .bind(format!("ext-{}", doc_id))
+        .bind(&content_id)
+        .execute(pool)
+        .await
+        .unwrap();
+        doc_id
+    }
+
+    async fn insert_embedding(pool: &PgPool, document_id: &str, model: &str) {
+        sqlx::query(
+            r#"
+            INSERT INTO embeddings (id, document_id, chunk_index, chunk_start_offset, chunk_end_offset, embedding, model_name, dimensions)
+            VALUES ($1, $2, 0, 0, 7, '[0.1,0.2,0.3]', $3, 3)
+            "#,
+        )
+        .bind(Ulid::new().to_string())
+        .bind(document_id)
+        .bind(model)
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    async fn embedding_models(pool: &PgPool, document_id: &str) -> Vec<String> {
+        sqlx::query_scalar(
+            "SELECT model_name FROM embeddings WHERE document_id = $1 ORDER BY model_name",
+        )
+        .bind(document_id)
+        .fetch_all(pool)
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_create_queues_documents_without_blocking_production_work() {
+        let fixture = BaseTestFixture::new().await.unwrap();
+        let pool = fixture.db_pool().pool().clone();
+        insert_provider(&pool, "old-model", true).await;
+        let target_id = insert_provider(&pool, "new-model", false).await;
+        let doc_id = create_document(&pool).await;
+
+        let repo = EmbeddingMigrationRepository::new(&pool);
+        let migration = repo.create(&target_id).await.unwrap();
+        assert_eq!(migration.from_model, "old-model");
+        assert_eq!(migration.to_model, "new-model");
+        assert_eq!(migration.total_documents, 1);
+        assert!(repo.create(&target_id).await.is_err());
+
+        let progress = repo.progress(&migration).await.unwrap();
+        assert_eq!(progress.pending_documents, 1);
+        assert_eq!(progress.migrated_documents, 0);
+
+        // A queued migration item is not production work for the document
+        let queued = EmbeddingQueue::new(pool.clone())
+            .enqueue(doc_id)
+            .await
+            .unwrap();
+        assert!(queued.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_cut_over_switches_provider_and_retires_old_vectors() {
+        let fixture = BaseTestFixture::new().await.unwrap();
+        let pool = fixture.db_pool().pool().clone();
+        let current_id = insert_provider(&pool, "old-model", true).await;
+        let target_id = insert_provider(&pool, "new-model", false).await;
+        let migrated_doc = create_document(&pool).await;
+        let unmigrated_doc = create_document(&pool).await;
+        insert_embedding(&pool, &migrated_doc, "old-model").await;
+        insert_embedding(&pool, &unmigrated_doc, "old-model").await;
+
+        let repo = EmbeddingMigrationRepository::new(&pool);
+        let migration = repo.create(&target_id).await.unwrap();
+        insert_embedding(&pool, &migrated_doc, "new-model").await;
+        sqlx::query("DELETE FROM embedding_queue WHERE migration_id = $1")
+            .bind(&migration.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let cutover = repo.cut_over(&migration.id).await.unwrap();
+        assert_eq!(cutover.retired_embeddings, 1);
+        assert_eq!(cutover.requeued_documents, 1);
+
+        let current: String = sqlx::query_scalar(
+            "SELECT id FROM embedding_providers WHERE is_current = TRUE AND is_deleted = FALSE",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(current, target_id);
+        assert_ne!(current, current_id);
+
+        assert_eq!(embedding_models(&pool, &migrated_doc).await, ["new-model"]);
+        assert_eq!(
+            embedding_models(&pool, &unmigrated_doc).await,
+            ["old-model"]
+        );
+
+        let migration = repo.get(&migration.id).await.unwrap().unwrap();
+        assert_eq!(migration.status, EmbeddingMigrationStatus::Completed);
+        assert!(!repo.cancel(&migration.id).await.unwrap());
+    }
+}
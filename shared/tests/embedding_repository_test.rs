@@ -42,6 +42,8 @@ mod tests {
                 embedding: Vector::from(vec![0.1, 0.2, 0.3]),
                 model_name: "test-model".to_string(),
                 dimensions: 3,
+                page_number: None,
+                heading_path: None,
                 created_at: OffsetDateTime::now_utc(),
             },
             Embedding {
@@ -53,6 +55,8 @@ mod tests {
                 embedding: Vector::from(vec![0.4, 0.5, 0.6]),
                 model_name: "test-model".to_string(),
                 dimensions: 3,
+                page_number: None,
+                heading_path: None,
                 created_at: OffsetDateTime::now_utc(),
             },
         ];
@@ -91,6 +95,8 @@ mod tests {
                 embedding: Vector::from(vec![0.1, 0.2, 0.3]),
                 model_name: "test-model".to_string(),
                 dimensions: 3,
+                page_number: None,
+                heading_path: None,
                 created_at: OffsetDateTime::now_utc(),
             },
             Embedding {
@@ -102,6 +108,8 @@ mod tests {
                 embedding: Vector::from(vec![0.4, 0.5, 0.6]),
                 model_name: "test-model".to_string(),
                 dimensions: 3,
+                page_number: None,
+                heading_path: None,
                 created_at: OffsetDateTime::now_utc(),
             },
             // Document 2 - 3 chunks
@@ -114,6 +122,8 @@ mod tests {
                 embedding: Vector::from(vec![0.7, 0.8, 0.9]),
                 model_name: "test-model".to_string(),
                 dimensions: 3,
+                page_number: None,
+                heading_path: None,
                 created_at: OffsetDateTime::now_utc(),
             },
             Embedding {
@@ -125,6 +135,8 @@ mod tests {
                 embedding: Vector::from(vec![1.0, 1.1, 1.2]),
                 model_name: "test-model".to_string(),
                 dimensions: 3,
+                page_number: None,
+                heading_path: None,
                 created_at: OffsetDateTime::now_utc(),
             },
             Embedding {
@@ -136,6 +148,8 @@ mod tests {
                 embedding: Vector::from(vec![1.3, 1.4, 1.5]),
                 model_name: "test-model".to_string(),
                 dimensions: 3,
+                page_number: None,
+                heading_path: None,
                 created_at: OffsetDateTime::now_utc(),
             },
             // Document 3 - 1 chunk
@@ -148,6 +162,8 @@ mod tests {
                 embedding: Vector::from(vec![1.6, 1.7, 1.8]),
                 model_name: "test-model".to_string(),
                 dimensions: 3,
+                page_number: None,
+                heading_path: None,
                 created_at: OffsetDateTime::now_utc(),
             },
         ];
@@ -185,6 +201,8 @@ mod tests {
             embedding: Vector::from(vec![0.1, 0.2, 0.3]),
             model_name: "test-model".to_string(),
             dimensions: 3,
+            page_number: None,
+            heading_path: None,
             created_at: OffsetDateTime::now_utc(),
         };
 
@@ -200,6 +218,8 @@ mod tests {
             embedding: Vector::from(vec![0.9, 0.8, 0.7]), // Different embedding
             model_name: "test-model".to_string(),         // Same model_name
             dimensions: 3,
+            page_number: None,
+            heading_path: None,
             created_at: OffsetDateTime::now_utc(),
         };
 
@@ -254,6 +274,8 @@ mod tests {
                     ]),
                     model_name: "test-model".to_string(),
                     dimensions: 3,
+                    page_number: None,
+                    heading_path: None,
                     created_at: OffsetDateTime::now_utc(),
                 });
             }
@@ -297,6 +319,8 @@ mod tests {
                 embedding: Vector::from(vec![0.1, 0.2, 0.3]),
                 model_name: "test-model".to_string(),
                 dimensions: 3,
+                page_number: None,
+                heading_path: None,
                 created_at: OffsetDateTime::now_utc(),
             },
             Embedding {
@@ -308,6 +332,8 @@ mod tests {
                 embedding: Vector::from(vec![0.4, 0.5, 0.6]),
                 model_name: "test-model".to_string(),
                 dimensions: 3,
+                page_number: None,
+                heading_path: None,
                 created_at: OffsetDateTime::now_utc(),
             },
             // Document 2 - 1 chunk
@@ -320,6 +346,8 @@ mod tests {
                 embedding: Vector::from(vec![0.7, 0.8, 0.9]),
                 model_name: "test-model".to_string(),
                 dimensions: 3,
+                page_number: None,
+                heading_path: None,
                 created_at: OffsetDateTime::now_utc(),
             },
             // Document 3 - 1 chunk
@@ -332,6 +360,8 @@ mod tests {
                 embedding: Vector::from(vec![1.0, 1.1, 1.2]),
                 model_name: "test-model".to_string(),
                 dimensions: 3,
+                page_number: None,
+                heading_path: None,
                 created_at: OffsetDateTime::now_utc(),
             },
         ];
@@ -0,0 +1,82 @@
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use shared::db::repositories::{RetentionPurgeStatus, SourceRetentionRepository};
+    use shared::test_utils::{BaseTestFixture, TEST_SOURCE_ID};
+    use sqlx::PgPool;
+    use time::{Duration, OffsetDateTime};
+    use ulid::Ulid;
+
+    async fn create_document(pool: &PgPool, metadata: serde_json::Value) -> String {
+        let doc_id = Ulid::new().to_string();
+        sqlx::query(
+            r#"
+            INSERT INTO documents (id, source_id, external_id, title, content, metadata, permissions, attributes, created_at, updated_at)
+            VALUES ($1, $2, $3, 'Test Doc', 'content', $4, '{"public":true}', '{}', NOW(), NOW())
+            "#,
+        )
+        .bind(&doc_id)
+        .bind(TEST_SOURCE_ID)
+        .bind(format!("ext-{}", doc_id))
+        .bind(metadata)
+        .execute(pool)
+        .await
+        .unwrap();
+        doc_id
+    }
+
+    #[tokio::test]
+    async fn test_purge_removes_documents_updated_before_the_cutoff() {
+        let fixture = BaseTestFixture::new().await.unwrap();
+        let pool = fixture.db_pool().pool();
+        let repo = SourceRetentionRepository::new(pool);
+
+        let policy = repo.upsert_policy(TEST_SOURCE_ID, 540, None).await.unwrap();
+        let old = create_document(pool, json!({ "updated_at": "2020-01-01T00:00:00Z" })).await;
+        let recent = create_document(pool, json!({})).await;
+        // Unparseable source timestamps fall back to when the row was updated
+        let unparseable = create_document(pool, json!({ "updated_at": "last week" })).await;
+
+        let cutoff = OffsetDateTime::now_utc() - Duration::days(540);
+        assert!(repo.has_expired(TEST_SOURCE_ID, cutoff).await.unwrap());
+
+        let purge = repo.start_purge(&policy, cutoff).await.unwrap();
+        let batch = repo.purge_batch(&purge, 10).await.unwrap();
+        assert_eq!(batch.documents.len(), 1);
+        assert_eq!(batch.documents[0].document_id, old);
+        assert!(
+            repo.purge_batch(&purge, 10)
+                .await
+                .unwrap()
+                .documents
+                .is_empty()
+        );
+        assert!(!repo.has_expired(TEST_SOURCE_ID, cutoff).await.unwrap());
+
+        let remaining: Vec<String> =
+            sqlx::query_scalar("SELECT id FROM documents WHERE source_id = $1 ORDER BY id")
+                .bind(TEST_SOURCE_ID)
+                .fetch_all(pool)
+                .await
+                .unwrap();
+        let mut expected = vec![recent, unparseable];
+        expected.sort();
+        assert_eq!(remaining, expected);
+
+        let purge = repo.complete_purge(&purge.id).await.unwrap();
+        assert_eq!(purge.status, RetentionPurgeStatus::Completed);
+        assert_eq!(purge.documents_purged, 1);
+
+        let tombstones = repo.list_purged_documents(&purge.id, 10, 0).await.unwrap();
+        assert_eq!(tombstones.len(), 1);
+        assert_eq!(tombstones[0].external_id, format!("ext-{}", old));
+
+        let summaries = repo.summaries().await.unwrap();
+        assert_eq!(summaries.len(), 1);
+        assert_eq!(summaries[0].documents_purged, 1);
+        assert_eq!(
+            summaries[0].last_status,
+            Some(RetentionPurgeStatus::Completed)
+        );
+    }
+}
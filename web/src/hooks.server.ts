@@ -6,9 +6,11 @@ import { validateApiKey } from '$lib/server/apiKeys.js'
 import { rateLimit } from '$lib/server/rateLimit.js'
 import { Logger } from '$lib/server/logger.js'
 import { initTelemetry, extractTraceContext, getRequestId } from '$lib/server/telemetry.js'
+import { installInternalAuth, runAsActor } from '$lib/server/internalAuth.js'
 
 // Initialize OpenTelemetry on module load
 initTelemetry()
+installInternalAuth()
 
 const handleAuth: Handle = async ({ event, resolve }) => {
     // 1. Try API key auth (Authorization: Bearer omni_* or X-API-Key header)
@@ -88,6 +90,13 @@ const handlePasswordChange: Handle = async ({ event, resolve }) => {
     return resolve(event)
 }
 
+// Requests to internal services made while handling this one name its user,
+// for their audit logs
+const handleAuditActor: Handle = async ({ event, resolve }) => {
+    const user = event.locals.user
+    return runAsActor(user ? { id: user.id, email: user.email } : null, () => resolve(event))
+}
+
 const handleLogging: Handle = async ({ event, resolve }) => {
     // Extract trace context from incoming request headers
     const headers: Record<string, string | undefined> = {}
@@ -129,7 +138,7 @@ const handleLogging: Handle = async ({ event, resolve }) => {
     return response
 }
 
-export const handle = sequence(handleLogging, handleAuth, handlePasswordChange)
+export const handle = sequence(handleLogging, handleAuth, handleAuditActor, handlePasswordChange)
 
 export const handleError: HandleServerError = ({ error, event }) => {
     const logger = event.locals.logger || new Logger('error')
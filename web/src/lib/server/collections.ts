@@ -0,0 +1,30 @@
+import { env } from '$env/dynamic/private'
+import { json } from '@sveltejs/kit'
+
+/**
+ * Forward a collections API call to the searcher, which owns collection
+ * visibility and ownership rules, and relay its status and body.
+ */
+export async function forwardToSearcher(
+    fetchFn: typeof fetch,
+    path: string,
+    init: RequestInit = {},
+): Promise<Response> {
+    try {
+        const response = await fetchFn(`${env.SEARCHER_URL}/collections${path}`, {
+            ...init,
+            headers: { 'Content-Type': 'application/json' },
+        })
+        if (response.status === 204) {
+            return new Response(null, { status: 204 })
+        }
+        const body = await response.json().catch(() => ({}))
+        return json(body, { status: response.status })
+    } catch {
+        return json({ error: 'Search service unavailable' }, { status: 502 })
+    }
+}
+
+export function userQuery(user: { id: string; email: string }): string {
+    return new URLSearchParams({ user_id: user.id, user_email: user.email }).toString()
+}
@@ -11,6 +11,18 @@ import {
 } from 'drizzle-orm/pg-core'
 import type { MessageParam } from '@anthropic-ai/sdk/resources/messages.js'
 
+/// Tenants sharing the deployment. Users only see documents of sources in
+/// their own workspace.
+export const workspaces = pgTable('workspaces', {
+    id: text('id').primaryKey(),
+    name: text('name').notNull(),
+    slug: text('slug').notNull().unique(),
+    createdAt: timestamp('created_at', { withTimezone: true, mode: 'date' }).notNull().defaultNow(),
+    updatedAt: timestamp('updated_at', { withTimezone: true, mode: 'date' }).notNull().defaultNow(),
+})
+
+export const DEFAULT_WORKSPACE_ID = '00000000000000000000000000'
+
 export const user = pgTable('users', {
     id: text('id').primaryKey(),
     email: text('email').notNull().unique(),
@@ -20,6 +32,10 @@ export const user = pgTable('users', {
     authMethod: text('auth_method').notNull().default('password'),
     domain: text('domain'),
     mustChangePassword: boolean('must_change_password').notNull().default(false),
+    workspaceId: text('workspace_id')
+        .notNull()
+        .default(DEFAULT_WORKSPACE_ID)
+        .references(() => workspaces.id),
     createdAt: timestamp('created_at', { withTimezone: true, mode: 'date' }).notNull().defaultNow(),
     updatedAt: timestamp('updated_at', { withTimezone: true, mode: 'date' }).notNull().defaultNow(),
 })
@@ -51,6 +67,11 @@ export const sources = pgTable('sources', {
         .notNull()
         .references(() => user.id),
     syncIntervalSeconds: integer('sync_interval_seconds'),
+    /// Defaults to the creator's workspace (set by a database trigger).
+    workspaceId: text('workspace_id')
+        .notNull()
+        .default(DEFAULT_WORKSPACE_ID)
+        .references(() => workspaces.id),
 })
 
 export const documents = pgTable('documents', {
@@ -101,6 +122,10 @@ export const serviceCredentials = pgTable('service_credentials', {
     config: jsonb('config').notNull().default({}),
     expiresAt: timestamp('expires_at', { withTimezone: true, mode: 'date' }),
     lastValidatedAt: timestamp('last_validated_at', { withTimezone: true, mode: 'date' }),
+    /// Set when the OAuth token could not be refreshed and the credential has
+    /// to be re-authorized. Ignored once `updatedAt` is later.
+    refreshFailedAt: timestamp('refresh_failed_at', { withTimezone: true, mode: 'date' }),
+    refreshError: text('refresh_error'),
     createdAt: timestamp('created_at', { withTimezone: true, mode: 'date' }).notNull().defaultNow(),
     updatedAt: timestamp('updated_at', { withTimezone: true, mode: 'date' }).notNull().defaultNow(),
 })
@@ -66,7 +66,12 @@ export async function getGoogleSources(): Promise<Source[]> {
         .from(sources)
         .where(
             and(
-                inArray(sources.sourceType, ['google_drive', 'gmail', 'google_chat']),
+                inArray(sources.sourceType, [
+                    'google_drive',
+                    'gmail',
+                    'google_chat',
+                    'google_calendar',
+                ]),
                 eq(sources.isDeleted, false),
             ),
         )
@@ -78,7 +83,12 @@ export async function getActiveGoogleSources(): Promise<Source[]> {
         .from(sources)
         .where(
             and(
-                inArray(sources.sourceType, ['google_drive', 'gmail', 'google_chat']),
+                inArray(sources.sourceType, [
+                    'google_drive',
+                    'gmail',
+                    'google_chat',
+                    'google_calendar',
+                ]),
                 eq(sources.isActive, true),
                 eq(sources.isDeleted, false),
             ),
@@ -0,0 +1,71 @@
+import { describe, expect, it, vi } from 'vitest'
+import {
+    ACTOR_EMAIL_HEADER,
+    ACTOR_ID_HEADER,
+    SERVICE_KEY_HEADER,
+    runAsActor,
+    withServiceKey,
+} from './internalAuth'
+
+vi.mock('$env/dynamic/private', () => ({ env: {} }))
+vi.mock('./config.js', () => ({ getConfig: vi.fn() }))
+
+function sentHeaders(baseFetch: ReturnType<typeof vi.fn>): Headers {
+    const init = baseFetch.mock.calls[0][1] as RequestInit | undefined
+    return new Headers(init?.headers)
+}
+
+describe('withServiceKey', () => {
+    const serviceUrls = ['http://searcher:3001', 'http://indexer:3002/']
+
+    it('adds the key to requests for internal services', async () => {
+        const baseFetch = vi.fn().mockResolvedValue(new Response('ok'))
+        const fetchWithKey = withServiceKey(baseFetch, 'web-key', serviceUrls)
+
+        await fetchWithKey('http://searcher:3001/search', {
+            method: 'POST',
+            headers: { 'Content-Type': 'application/json' },
+        })
+
+        const headers = sentHeaders(baseFetch)
+        expect(headers.get(SERVICE_KEY_HEADER)).toBe('web-key')
+        expect(headers.get('content-type')).toBe('application/json')
+    })
+
+    it('keeps the headers of a Request', async () => {
+        const baseFetch = vi.fn().mockResolvedValue(new Response('ok'))
+        const fetchWithKey = withServiceKey(baseFetch, 'web-key', serviceUrls)
+
+        await fetchWithKey(
+            new Request('http://indexer:3002/documents', { headers: { 'X-Request-Id': 'r-1' } }),
+        )
+
+        const headers = sentHeaders(baseFetch)
+        expect(headers.get(SERVICE_KEY_HEADER)).toBe('web-key')
+        expect(headers.get('x-request-id')).toBe('r-1')
+    })
+
+    it('leaves requests to other hosts untouched', async () => {
+        const baseFetch = vi.fn().mockResolvedValue(new Response('ok'))
+        const fetchWithKey = withServiceKey(baseFetch, 'web-key', serviceUrls)
+        const init = { headers: { Accept: 'application/json' } }
+
+        await fetchWithKey('https://oauth.example.com/token', init)
+
+        expect(baseFetch).toHaveBeenCalledWith('https://oauth.example.com/token', init)
+    })
+
+    it('names the current actor', async () => {
+        const baseFetch = vi.fn().mockResolvedValue(new Response('ok'))
+        const fetchWithKey = withServiceKey(baseFetch, null, serviceUrls)
+
+        await runAsActor({ id: 'user-1', email: 'ada@example.com' }, () =>
+            fetchWithKey('http://searcher:3001/search'),
+        )
+
+        const headers = sentHeaders(baseFetch)
+        expect(headers.get(ACTOR_ID_HEADER)).toBe('user-1')
+        expect(headers.get(ACTOR_EMAIL_HEADER)).toBe('ada@example.com')
+        expect(headers.has(SERVICE_KEY_HEADER)).toBe(false)
+    })
+})
@@ -0,0 +1,101 @@
+import { AsyncLocalStorage } from 'node:async_hooks'
+import { env } from '$env/dynamic/private'
+import { getConfig } from './config.js'
+import { createLogger } from './logger.js'
+
+const logger = createLogger('internal-auth')
+
+// The searcher, indexer and connector manager reject requests without one of
+// their accepted service keys once they have internal auth enabled.
+export const SERVICE_KEY_HEADER = 'x-omni-service-key'
+
+// The signed-in user a request to an internal service is made for, recorded
+// by the services in their audit log.
+export const ACTOR_ID_HEADER = 'x-omni-actor-id'
+export const ACTOR_EMAIL_HEADER = 'x-omni-actor-email'
+
+export interface Actor {
+    id: string
+    email: string
+}
+
+const actorStorage = new AsyncLocalStorage<Actor | null>()
+
+let installed = false
+
+/** Run `fn` with `actor` as the user behind its requests to internal services. */
+export function runAsActor<T>(actor: Actor | null, fn: () => T): T {
+    return actorStorage.run(actor, fn)
+}
+
+function requestUrl(input: RequestInfo | URL): string {
+    if (typeof input === 'string') return input
+    if (input instanceof URL) return input.href
+    return input.url
+}
+
+function originOf(url: string): string | null {
+    try {
+        return new URL(url).origin
+    } catch {
+        return null
+    }
+}
+
+/**
+ * Wrap `baseFetch` so that requests to any of `serviceUrls` carry `key`, when
+ * there is one, and the current actor. Requests elsewhere are left alone, as
+ * are headers a request already sets.
+ */
+export function withServiceKey(
+    baseFetch: typeof fetch,
+    key: string | null,
+    serviceUrls: string[],
+    currentActor: () => Actor | null | undefined = () => actorStorage.getStore(),
+): typeof fetch {
+    const origins = new Set(serviceUrls.map(originOf).filter((origin) => origin !== null))
+
+    return (input, init) => {
+        const origin = originOf(requestUrl(input))
+        if (!origin || !origins.has(origin)) {
+            return baseFetch(input, init)
+        }
+
+        // Headers passed in `init` replace a Request's own, so merge both
+        const headers = new Headers(input instanceof Request ? input.headers : undefined)
+        new Headers(init?.headers).forEach((value, name) => headers.set(name, value))
+        if (key && !headers.has(SERVICE_KEY_HEADER)) {
+            headers.set(SERVICE_KEY_HEADER, key)
+        }
+        const actor = currentActor()
+        if (actor && !headers.has(ACTOR_ID_HEADER) && !headers.has(ACTOR_EMAIL_HEADER)) {
+            headers.set(ACTOR_ID_HEADER, actor.id)
+            headers.set(ACTOR_EMAIL_HEADER, actor.email)
+        }
+        return baseFetch(input, { ...init, headers })
+    }
+}
+
+/**
+ * Send this app's service key, from `WEB_INTERNAL_AUTH_KEY` or
+ * `INTERNAL_AUTH_KEY`, and the current actor with every server-side request
+ * to an Omni service.
+ */
+export function installInternalAuth() {
+    if (installed) {
+        return
+    }
+    const key = (env.WEB_INTERNAL_AUTH_KEY || env.INTERNAL_AUTH_KEY)?.trim() || null
+
+    const { services } = getConfig()
+    globalThis.fetch = withServiceKey(globalThis.fetch, key, [
+        services.searcherUrl,
+        services.indexerUrl,
+        services.connectorManagerUrl,
+        services.aiServiceUrl,
+    ])
+    installed = true
+    if (key) {
+        logger.info('Sending service key with requests to internal services')
+    }
+}
@@ -0,0 +1,26 @@
+import { env } from '$env/dynamic/private'
+import { json } from '@sveltejs/kit'
+
+/**
+ * Forward a share link call to the searcher, which checks the sharing policy
+ * and the user's access to the document, and relay its status and body.
+ */
+export async function forwardToSearcher(
+    fetchFn: typeof fetch,
+    path: string,
+    init: RequestInit = {},
+): Promise<Response> {
+    try {
+        const response = await fetchFn(`${env.SEARCHER_URL}${path}`, {
+            ...init,
+            headers: { 'Content-Type': 'application/json' },
+        })
+        if (response.status === 204) {
+            return new Response(null, { status: 204 })
+        }
+        const body = await response.json().catch(() => ({}))
+        return json(body, { status: response.status })
+    } catch {
+        return json({ error: 'Search service unavailable' }, { status: 502 })
+    }
+}
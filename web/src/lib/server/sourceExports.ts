@@ -0,0 +1,36 @@
+import { json } from '@sveltejs/kit'
+import { getConfig } from '$lib/server/config'
+
+type ExportBody = { download_url?: string | null } & Record<string, unknown>
+
+/** Point connector-manager's download path at the web app's proxy route. */
+function withProxiedDownloadUrl(body: ExportBody): ExportBody {
+    return body.download_url ? { ...body, download_url: `/api${body.download_url}` } : body
+}
+
+/**
+ * Forward a source export API call to the connector-manager, which owns the
+ * export jobs, and relay its status and body.
+ */
+export async function forwardToConnectorManager(
+    fetchFn: typeof fetch,
+    path: string,
+    init: RequestInit = {},
+): Promise<Response> {
+    try {
+        const response = await fetchFn(`${getConfig().services.connectorManagerUrl}${path}`, {
+            ...init,
+            headers: { 'Content-Type': 'application/json' },
+        })
+        const body = await response.json().catch(() => ({}))
+        if (!response.ok) {
+            return json(body, { status: response.status })
+        }
+        const proxied = Array.isArray(body)
+            ? body.map(withProxiedDownloadUrl)
+            : withProxiedDownloadUrl(body)
+        return json(proxied, { status: response.status })
+    } catch {
+        return json({ error: 'Connector manager unavailable' }, { status: 502 })
+    }
+}
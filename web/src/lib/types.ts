@@ -2,6 +2,7 @@ export enum SourceType {
     GOOGLE_DRIVE = 'google_drive',
     GMAIL = 'gmail',
     GOOGLE_CHAT = 'google_chat',
+    GOOGLE_CALENDAR = 'google_calendar',
     CONFLUENCE = 'confluence',
     JIRA = 'jira',
     SLACK = 'slack',
@@ -23,6 +24,9 @@ export enum SourceType {
     NEXTCLOUD = 'nextcloud',
     GOOGLE_ADS = 'google_ads',
     DARWINBOX = 'darwinbox',
+    ZENDESK = 'zendesk',
+    S3 = 's3',
+    PUSH = 'push',
 }
 
 export enum ServiceProvider {
@@ -41,6 +45,8 @@ export enum ServiceProvider {
     NEXTCLOUD = 'nextcloud',
     GOOGLE_ADS = 'google_ads',
     DARWINBOX = 'darwinbox',
+    ZENDESK = 'zendesk',
+    S3 = 's3',
 }
 
 export enum AuthType {
@@ -59,6 +65,10 @@ export interface WebSourceConfig {
     respect_robots_txt: boolean
     include_subdomains: boolean
     blacklist_patterns: string[]
+    whitelist_patterns: string[]
+    use_sitemap: boolean
+    sitemap_path: string | null
+    render_javascript: boolean
     user_agent: string | null
 }
 
@@ -68,6 +78,7 @@ export interface ConfluenceSourceConfig {
 
 export interface JiraSourceConfig {
     project_filters?: string[]
+    jql_filter?: string
 }
 
 export interface ClickUpSourceConfig {
@@ -90,8 +101,10 @@ export interface GoogleChatSourceConfig {
 export interface FilesystemSourceConfig {
     base_path: string
     file_extensions?: string[]
+    include_patterns?: string[]
     exclude_patterns?: string[]
     max_file_size_bytes?: number
+    extract_archives?: boolean
 }
 
 export interface HubspotSourceConfig {
@@ -144,6 +157,19 @@ export interface NextcloudSourceConfig {
     sync_enabled: boolean
 }
 
+export interface S3SourceConfig {
+    bucket: string
+    region?: string
+    /** Custom endpoint for S3-compatible stores (MinIO, R2, ...) */
+    endpoint_url?: string
+    prefixes: string[]
+    mime_type_allowlist: string[]
+    mime_type_denylist: string[]
+    /** 0 = unlimited */
+    max_file_size: number
+    sync_enabled: boolean
+}
+
 export interface GoogleAdsSourceConfig {
     customer_ids: string[]
     login_customer_id?: string
@@ -176,10 +202,12 @@ export interface DarwinboxSourceConfig {
     }
 }
 
-export const DEFAULT_SYNC_INTERVAL_SECONDS: Record<SourceType, number> = {
+// Push sources receive documents over the push API and are never scheduled
+export const DEFAULT_SYNC_INTERVAL_SECONDS: Record<SourceType, number | null> = {
     [SourceType.GOOGLE_DRIVE]: 1800,
     [SourceType.GMAIL]: 1800,
     [SourceType.GOOGLE_CHAT]: 1800,
+    [SourceType.GOOGLE_CALENDAR]: 3600,
     [SourceType.SLACK]: 1800,
     [SourceType.OUTLOOK]: 1800,
     [SourceType.ONE_DRIVE]: 1800,
@@ -201,6 +229,9 @@ export const DEFAULT_SYNC_INTERVAL_SECONDS: Record<SourceType, number> = {
     [SourceType.NEXTCLOUD]: 3600,
     [SourceType.GOOGLE_ADS]: 3600,
     [SourceType.DARWINBOX]: 3600,
+    [SourceType.ZENDESK]: 3600,
+    [SourceType.S3]: 3600,
+    [SourceType.PUSH]: null,
 }
 
 export const EMBEDDING_PROVIDER_TYPES = ['local', 'jina', 'openai', 'cohere', 'bedrock'] as const
@@ -72,13 +72,24 @@ export interface Document {
     attributes?: Record<string, any>
 }
 
+// A passage of a result with its matched words as UTF-16 offsets into text
+export interface Snippet {
+    text: string
+    highlights: { start: number; end: number }[]
+}
+
 export interface SearchResult {
     document: Document
     score: number
     highlights: string[]
+    snippets?: Snippet[]
     match_type: string
     source_type: string
     content?: string
+    // 1-based page of the match, for PDFs
+    page?: number
+    // Headings enclosing the match, e.g. "Runbook > Incident Response"
+    heading_path?: string
 }
 
 export interface FacetValue {
@@ -99,6 +110,29 @@ export interface SearchResponse {
     query: string
     facets?: Facet[]
     active_filters?: Facet[]
+    intent?: QueryIntent
+    // Filters taken out of the query when the request set understand_query
+    query_understanding?: UnderstoodQuery
+    // Sent with clicks on the results so they count towards this search
+    search_event_id?: string
+}
+
+export interface QueryIntent {
+    intent: 'navigational' | 'search' | 'question'
+    confidence: number
+    source: 'heuristic' | 'model'
+    // Whether an AI answer is likely to help; false for navigational queries
+    suggest_answer: boolean
+}
+
+export interface UnderstoodQuery {
+    // What is left of the query once the filters are taken out
+    query: string
+    source_types?: string[]
+    content_types?: string[]
+    after?: string
+    before?: string
+    source: 'rules' | 'model'
 }
 
 export interface SearchRequest {
@@ -110,6 +144,9 @@ export interface SearchRequest {
     mode?: 'fulltext' | 'semantic' | 'hybrid'
     user_id?: string
     user_configuration?: UserConfiguration
+    collection_id?: string
+    // Turn filters written in words, e.g. "slides last quarter", into filters
+    understand_query?: boolean
 }
 
 export interface RecentSearchesResponse {
@@ -123,6 +160,8 @@ export interface SuggestedQuestion {
 
 export interface SuggestedQuestionsResponse {
     questions: SuggestedQuestion[]
+    total_count: number
+    has_more: boolean
 }
 
 export interface TypeaheadResult {
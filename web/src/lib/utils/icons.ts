@@ -28,6 +28,8 @@ import githubIcon from '$lib/images/icons/github.svg'
 import nextcloudIcon from '$lib/images/icons/nextcloud.svg'
 import paperlessIcon from '$lib/images/icons/paperless.svg'
 import imapIcon from '$lib/images/icons/imap.svg'
+import zendeskIcon from '$lib/images/icons/zendesk.svg'
+import s3Icon from '$lib/images/icons/s3.svg'
 
 // Google Workspace MIME types
 const GOOGLE_DOCS_MIMETYPES = [
@@ -55,6 +57,7 @@ const SOURCE_TYPE_ICONS: Record<string, string> = {
     [SourceType.GOOGLE_DRIVE]: googleDriveIcon,
     [SourceType.GMAIL]: gmailIcon,
     [SourceType.GOOGLE_CHAT]: googleChatIcon,
+    [SourceType.GOOGLE_CALENDAR]: googleIcon,
     [SourceType.SLACK]: slackIcon,
     [SourceType.CONFLUENCE]: confluenceIcon,
     [SourceType.JIRA]: jiraIcon,
@@ -74,6 +77,8 @@ const SOURCE_TYPE_ICONS: Record<string, string> = {
     [SourceType.IMAP]: imapIcon,
     [SourceType.GOOGLE_ADS]: googleAdsIcon,
     [SourceType.DARWINBOX]: darwinboxIcon,
+    [SourceType.ZENDESK]: zendeskIcon,
+    [SourceType.S3]: s3Icon,
 }
 
 // Get icon based on source type and content type
@@ -153,6 +158,8 @@ export function inferSourceFromUrl(url: string): SourceType | null {
     if (urlLower.includes('mail.google.com') || urlLower.includes('gmail.com'))
         return SourceType.GMAIL
     if (urlLower.includes('chat.google.com')) return SourceType.GOOGLE_CHAT
+    if (urlLower.includes('calendar.google.com') || urlLower.includes('google.com/calendar'))
+        return SourceType.GOOGLE_CALENDAR
     if (urlLower.includes('slack.com')) return SourceType.SLACK
     if (urlLower.includes('atlassian.net/spaces')) return SourceType.CONFLUENCE
     if (urlLower.includes('atlassian.net/jira')) return SourceType.JIRA
@@ -205,6 +212,7 @@ export function getSourceDisplayName(sourceType: SourceType) {
         [SourceType.GOOGLE_DRIVE]: 'Google Drive',
         [SourceType.GMAIL]: 'Gmail',
         [SourceType.GOOGLE_CHAT]: 'Google Chat',
+        [SourceType.GOOGLE_CALENDAR]: 'Google Calendar',
         [SourceType.CONFLUENCE]: 'Confluence',
         [SourceType.JIRA]: 'Jira',
         [SourceType.SLACK]: 'Slack',
@@ -226,6 +234,9 @@ export function getSourceDisplayName(sourceType: SourceType) {
         [SourceType.PAPERLESS_NGX]: 'Paperless-ngx',
         [SourceType.GOOGLE_ADS]: 'Google Ads',
         [SourceType.DARWINBOX]: 'Darwinbox',
+        [SourceType.ZENDESK]: 'Zendesk',
+        [SourceType.S3]: 'Amazon S3',
+        [SourceType.PUSH]: 'Push API',
     }
 
     return sourceDisplayNames[sourceType]
@@ -28,6 +28,7 @@ const sourceNouns: Record<string, string> = {
     [SourceType.GOOGLE_DRIVE]: 'documents',
     [SourceType.GMAIL]: 'threads',
     [SourceType.GOOGLE_CHAT]: 'conversations',
+    [SourceType.GOOGLE_CALENDAR]: 'events',
     [SourceType.SLACK]: 'threads',
     [SourceType.CONFLUENCE]: 'pages',
     [SourceType.JIRA]: 'issues',
@@ -47,6 +48,9 @@ const sourceNouns: Record<string, string> = {
     [SourceType.PAPERLESS_NGX]: 'documents',
     [SourceType.NEXTCLOUD]: 'files',
     [SourceType.GOOGLE_ADS]: 'records',
+    [SourceType.ZENDESK]: 'tickets',
+    [SourceType.S3]: 'files',
+    [SourceType.PUSH]: 'documents',
 }
 
 export function getSourceNoun(sourceType: SourceType): string {
@@ -24,6 +24,59 @@ async function triggerReindex() {
     }
 }
 
+interface EmbeddingMigration {
+    id: string
+    to_provider_id: string
+    from_model: string
+    to_model: string
+    status: string
+    total_documents: number
+    progress?: {
+        migrated_documents: number
+        pending_documents: number
+        quarantined_documents: number
+        percent: number
+    } | null
+}
+
+async function getRunningMigration() {
+    try {
+        const response = await fetch(`${env.INDEXER_URL}/admin/embedding-migrations`)
+        if (!response.ok) return null
+        const migrations = (await response.json()) as EmbeddingMigration[]
+        const running = migrations.find((m) => m.status === 'running')
+        if (!running) return null
+
+        const statusResponse = await fetch(
+            `${env.INDEXER_URL}/admin/embedding-migrations/${running.id}`,
+        )
+        if (!statusResponse.ok) return null
+        const migration = (await statusResponse.json()) as EmbeddingMigration
+        return {
+            id: migration.id,
+            toProviderId: migration.to_provider_id,
+            fromModel: migration.from_model,
+            toModel: migration.to_model,
+            totalDocuments: migration.total_documents,
+            migratedDocuments: migration.progress?.migrated_documents ?? 0,
+            quarantinedDocuments: migration.progress?.quarantined_documents ?? 0,
+            percent: Math.floor(migration.progress?.percent ?? 0),
+        }
+    } catch (err) {
+        console.error('Failed to load embedding migration status:', err)
+        return null
+    }
+}
+
+async function indexerError(response: Response): Promise<string> {
+    const text = await response.text().catch(() => '')
+    try {
+        return JSON.parse(text).error || text
+    } catch {
+        return text || response.statusText
+    }
+}
+
 function stripSecrets(config: Record<string, unknown>): Record<string, unknown> {
     const { apiKey, ...rest } = config
     return rest
@@ -118,9 +171,13 @@ async function testEmbeddingProviderConnection(
 export const load: PageServerLoad = async ({ locals }) => {
     requireAdmin(locals)
 
-    const providers = await listActiveProviders()
+    const [providers, migration] = await Promise.all([
+        listActiveProviders(),
+        getRunningMigration(),
+    ])
 
     return {
+        migration,
         providers: providers.map((p) => ({
             id: p.id,
             name: p.name,
@@ -226,6 +283,35 @@ export const actions: Actions = {
         const id = formData.get('id') as string
         if (!id) return fail(400, { error: 'Provider ID is required' })
 
+        const [current, target] = await Promise.all([getCurrentProvider(), getProvider(id)])
+        if (!target) return fail(404, { error: 'Provider not found' })
+
+        const currentModel = (current?.config as Record<string, unknown> | undefined)?.model
+        const targetModel = (target.config as Record<string, unknown>).model
+        if (current && currentModel !== targetModel) {
+            // Vectors of different models can't be compared, so re-embed
+            // everything with the new model before switching over
+            try {
+                const response = await fetch(`${env.INDEXER_URL}/admin/embedding-migrations`, {
+                    method: 'POST',
+                    headers: { 'Content-Type': 'application/json' },
+                    body: JSON.stringify({ provider_id: id }),
+                })
+                if (!response.ok) {
+                    return fail(response.status, { error: await indexerError(response) })
+                }
+
+                return {
+                    success: true,
+                    message:
+                        'Re-embedding started. The provider becomes current once every document uses the new model.',
+                }
+            } catch (err) {
+                console.error('Failed to start embedding migration:', err)
+                return fail(500, { error: 'Failed to start re-embedding' })
+            }
+        }
+
         try {
             await setCurrentProvider(id)
             await triggerReindex()
@@ -236,6 +322,29 @@ export const actions: Actions = {
             return fail(500, { error: 'Failed to switch provider' })
         }
     },
+
+    cancelMigration: async ({ request, locals }) => {
+        requireAdmin(locals)
+
+        const formData = await request.formData()
+        const id = formData.get('id') as string
+        if (!id) return fail(400, { error: 'Migration ID is required' })
+
+        try {
+            const response = await fetch(
+                `${env.INDEXER_URL}/admin/embedding-migrations/${encodeURIComponent(id)}/cancel`,
+                { method: 'POST' },
+            )
+            if (!response.ok) {
+                return fail(response.status, { error: await indexerError(response) })
+            }
+
+            return { success: true, message: 'Re-embedding cancelled' }
+        } catch (err) {
+            console.error('Failed to cancel embedding migration:', err)
+            return fail(500, { error: 'Failed to cancel re-embedding' })
+        }
+    },
 }
 
 function parseConfig(formData: FormData, providerType: string): EmbeddingProviderConfig {
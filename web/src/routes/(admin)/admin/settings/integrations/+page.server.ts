@@ -33,15 +33,19 @@ const CONNECTOR_DISPLAY_ORDER: string[] = [
     'github',
     // CRM & sales
     'hubspot',
+    // Support
+    'zendesk',
     // Meetings
     'fireflies',
     // HRIS
     'darwinbox',
     // Other
     'nextcloud',
+    's3',
     'web',
     'filesystem',
     'paperless_ngx',
+    'push',
 ]
 
 interface ConnectorInfo {
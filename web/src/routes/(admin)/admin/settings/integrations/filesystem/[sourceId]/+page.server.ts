@@ -55,8 +55,10 @@ export const actions: Actions = {
 
         const basePath = (formData.get('basePath') as string) || ''
         const fileExtensions = formData.getAll('fileExtensions') as string[]
+        const includePatterns = formData.getAll('includePatterns') as string[]
         const excludePatterns = formData.getAll('excludePatterns') as string[]
         const maxFileSizeMb = parseInt(formData.get('maxFileSizeMb') as string) || 10
+        const extractArchives = formData.has('extractArchives')
 
         if (!basePath.trim()) {
             throw error(400, 'Base path is required when filesystem indexing is enabled')
@@ -70,8 +72,10 @@ export const actions: Actions = {
             const config: FilesystemSourceConfig = {
                 base_path: basePath,
                 file_extensions: fileExtensions.length > 0 ? fileExtensions : undefined,
+                include_patterns: includePatterns.length > 0 ? includePatterns : undefined,
                 exclude_patterns: excludePatterns.length > 0 ? excludePatterns : undefined,
                 max_file_size_bytes: maxFileSizeMb * 1024 * 1024,
+                extract_archives: extractArchives,
             }
 
             await updateSourceById(source.id, {
@@ -43,10 +43,12 @@ export const actions: Actions = {
 
         const isActive = formData.has('enabled')
         const projectFilters = formData.getAll('projectFilters') as string[]
+        const jqlFilter = ((formData.get('jqlFilter') as string | null) ?? '').trim()
 
         try {
             const config: JiraSourceConfig = {
                 project_filters: projectFilters.length > 0 ? projectFilters : undefined,
+                jql_filter: jqlFilter || undefined,
             }
 
             await updateSourceById(source.id, {
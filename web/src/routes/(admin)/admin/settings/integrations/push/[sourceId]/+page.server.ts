@@ -0,0 +1,99 @@
+import { error, fail } from '@sveltejs/kit'
+import type { PageServerLoad, Actions } from './$types'
+import { requireAdmin } from '$lib/server/authHelpers'
+import { getSourceById, updateSourceById } from '$lib/server/db/sources'
+import { getConfig } from '$lib/server/config'
+import { SourceType } from '$lib/types'
+
+export interface PushApiKey {
+    id: string
+    source_id: string
+    key_prefix: string
+    name: string
+    created_by: string | null
+    last_used_at: string | null
+    revoked_at: string | null
+    created_at: string
+}
+
+async function requirePushSource(sourceId: string) {
+    const source = await getSourceById(sourceId)
+    if (!source) {
+        throw error(404, 'Source not found')
+    }
+    if (source.sourceType !== SourceType.PUSH) {
+        throw error(400, 'Invalid source type for this page')
+    }
+    return source
+}
+
+export const load: PageServerLoad = async ({ params, locals, fetch }) => {
+    requireAdmin(locals)
+
+    const source = await requirePushSource(params.sourceId)
+
+    const connectorManagerUrl = getConfig().services.connectorManagerUrl
+    const response = await fetch(`${connectorManagerUrl}/sources/${source.id}/push-keys`)
+    const keys: PushApiKey[] = response.ok ? await response.json() : []
+
+    return { source, keys }
+}
+
+export const actions: Actions = {
+    save: async ({ request, params, locals }) => {
+        requireAdmin(locals)
+        const source = await requirePushSource(params.sourceId)
+
+        const formData = await request.formData()
+        await updateSourceById(source.id, { isActive: formData.has('enabled') })
+
+        return { saved: true }
+    },
+
+    createKey: async ({ request, params, locals, fetch }) => {
+        const user = requireAdmin(locals).user
+        const source = await requirePushSource(params.sourceId)
+
+        const formData = await request.formData()
+        const name = (formData.get('name') as string | null)?.trim() ?? ''
+        if (!name) {
+            return fail(400, { error: 'Key name is required' })
+        }
+
+        const connectorManagerUrl = getConfig().services.connectorManagerUrl
+        const response = await fetch(`${connectorManagerUrl}/sources/${source.id}/push-keys`, {
+            method: 'POST',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify({ name, created_by: user.id }),
+        })
+        if (!response.ok) {
+            console.error('Failed to create push API key:', await response.text())
+            return fail(500, { error: 'Failed to create API key' })
+        }
+
+        const created = await response.json()
+        return { createdKey: created.key as string, createdKeyName: name }
+    },
+
+    revokeKey: async ({ request, params, locals, fetch }) => {
+        requireAdmin(locals)
+        const source = await requirePushSource(params.sourceId)
+
+        const formData = await request.formData()
+        const keyId = formData.get('keyId') as string | null
+        if (!keyId) {
+            return fail(400, { error: 'Key ID is required' })
+        }
+
+        const connectorManagerUrl = getConfig().services.connectorManagerUrl
+        const response = await fetch(
+            `${connectorManagerUrl}/sources/${source.id}/push-keys/${keyId}`,
+            { method: 'DELETE' },
+        )
+        if (!response.ok) {
+            return fail(response.status, { error: 'Failed to revoke API key' })
+        }
+
+        return { revoked: true }
+    },
+}
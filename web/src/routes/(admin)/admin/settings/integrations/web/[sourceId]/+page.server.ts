@@ -59,6 +59,10 @@ export const actions: Actions = {
         const respectRobotsTxt = formData.has('respectRobotsTxt')
         const includeSubdomains = formData.has('includeSubdomains')
         const blacklistPatterns = formData.getAll('blacklistPatterns') as string[]
+        const whitelistPatterns = formData.getAll('whitelistPatterns') as string[]
+        const useSitemap = formData.has('useSitemap')
+        const sitemapPath = ((formData.get('sitemapPath') as string) || '').trim() || null
+        const renderJavascript = formData.has('renderJavascript')
         const userAgent = (formData.get('userAgent') as string) || null
 
         if (!rootUrl.trim()) {
@@ -73,6 +77,10 @@ export const actions: Actions = {
                 respect_robots_txt: respectRobotsTxt,
                 include_subdomains: includeSubdomains,
                 blacklist_patterns: blacklistPatterns,
+                whitelist_patterns: whitelistPatterns,
+                use_sitemap: useSitemap,
+                sitemap_path: sitemapPath,
+                render_javascript: renderJavascript,
                 user_agent: userAgent,
             }
 
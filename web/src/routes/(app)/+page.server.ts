@@ -38,15 +38,8 @@ export const load: PageServerLoad = async ({ locals, fetch }) => {
     // Fetch suggested questions
     let suggestedQuestions: SuggestedQuestion[] = []
     try {
-        const suggestedResponse = await fetch(`${env.SEARCHER_URL}/suggested-questions`, {
-            method: 'POST',
-            headers: {
-                'Content-Type': 'application/json',
-            },
-            body: JSON.stringify({
-                user_id: locals.user?.id,
-            }),
-        })
+        const params = new URLSearchParams({ user_id: locals.user?.id ?? '' })
+        const suggestedResponse = await fetch(`${env.SEARCHER_URL}/suggested-questions?${params}`)
 
         if (suggestedResponse.ok) {
             const data: SuggestedQuestionsResponse = await suggestedResponse.json()
@@ -0,0 +1,35 @@
+import { json } from '@sveltejs/kit'
+import type { RequestHandler } from './$types.js'
+import { forwardToSearcher, userQuery } from '$lib/server/collections.js'
+
+export const GET: RequestHandler = async ({ fetch, locals }) => {
+    if (!locals.user?.id) {
+        return json({ error: 'User not authenticated' }, { status: 401 })
+    }
+
+    return forwardToSearcher(fetch, `?${userQuery(locals.user)}`)
+}
+
+export const POST: RequestHandler = async ({ request, fetch, locals }) => {
+    if (!locals.user?.id) {
+        return json({ error: 'User not authenticated' }, { status: 401 })
+    }
+
+    let body: Record<string, unknown>
+    try {
+        body = await request.json()
+    } catch {
+        return json({ error: 'Invalid JSON in request body' }, { status: 400 })
+    }
+
+    return forwardToSearcher(fetch, '', {
+        method: 'POST',
+        body: JSON.stringify({
+            name: body.name,
+            description: body.description,
+            visibility: body.visibility,
+            team_groups: body.team_groups,
+            user_id: locals.user.id,
+        }),
+    })
+}
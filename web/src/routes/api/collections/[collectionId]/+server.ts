@@ -0,0 +1,51 @@
+import { json } from '@sveltejs/kit'
+import type { RequestHandler } from './$types.js'
+import { forwardToSearcher, userQuery } from '$lib/server/collections.js'
+
+export const GET: RequestHandler = async ({ params, fetch, locals }) => {
+    if (!locals.user?.id) {
+        return json({ error: 'User not authenticated' }, { status: 401 })
+    }
+
+    return forwardToSearcher(
+        fetch,
+        `/${encodeURIComponent(params.collectionId)}?${userQuery(locals.user)}`,
+    )
+}
+
+export const PUT: RequestHandler = async ({ params, request, fetch, locals }) => {
+    if (!locals.user?.id) {
+        return json({ error: 'User not authenticated' }, { status: 401 })
+    }
+
+    let body: Record<string, unknown>
+    try {
+        body = await request.json()
+    } catch {
+        return json({ error: 'Invalid JSON in request body' }, { status: 400 })
+    }
+
+    return forwardToSearcher(fetch, `/${encodeURIComponent(params.collectionId)}`, {
+        method: 'PUT',
+        body: JSON.stringify({
+            name: body.name,
+            description: body.description,
+            visibility: body.visibility,
+            team_groups: body.team_groups,
+            user_id: locals.user.id,
+            user_email: locals.user.email,
+        }),
+    })
+}
+
+export const DELETE: RequestHandler = async ({ params, fetch, locals }) => {
+    if (!locals.user?.id) {
+        return json({ error: 'User not authenticated' }, { status: 401 })
+    }
+
+    return forwardToSearcher(
+        fetch,
+        `/${encodeURIComponent(params.collectionId)}?${userQuery(locals.user)}`,
+        { method: 'DELETE' },
+    )
+}
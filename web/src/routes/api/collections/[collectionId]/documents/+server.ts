@@ -0,0 +1,29 @@
+import { json } from '@sveltejs/kit'
+import type { RequestHandler } from './$types.js'
+import { forwardToSearcher } from '$lib/server/collections.js'
+
+export const POST: RequestHandler = async ({ params, request, fetch, locals }) => {
+    if (!locals.user?.id) {
+        return json({ error: 'User not authenticated' }, { status: 401 })
+    }
+
+    let body: { document_ids?: unknown }
+    try {
+        body = await request.json()
+    } catch {
+        return json({ error: 'Invalid JSON in request body' }, { status: 400 })
+    }
+
+    if (!Array.isArray(body.document_ids) || body.document_ids.length === 0) {
+        return json({ error: 'document_ids is required' }, { status: 400 })
+    }
+
+    return forwardToSearcher(fetch, `/${encodeURIComponent(params.collectionId)}/documents`, {
+        method: 'POST',
+        body: JSON.stringify({
+            document_ids: body.document_ids,
+            user_id: locals.user.id,
+            user_email: locals.user.email,
+        }),
+    })
+}
@@ -0,0 +1,12 @@
+import { json } from '@sveltejs/kit'
+import type { RequestHandler } from './$types.js'
+import { forwardToSearcher, userQuery } from '$lib/server/collections.js'
+
+export const DELETE: RequestHandler = async ({ params, fetch, locals }) => {
+    if (!locals.user?.id) {
+        return json({ error: 'User not authenticated' }, { status: 401 })
+    }
+
+    const path = `/${encodeURIComponent(params.collectionId)}/documents/${encodeURIComponent(params.documentId)}`
+    return forwardToSearcher(fetch, `${path}?${userQuery(locals.user)}`, { method: 'DELETE' })
+}
@@ -0,0 +1,41 @@
+import { json } from '@sveltejs/kit'
+import type { RequestHandler } from './$types.js'
+import { forwardToSearcher } from '$lib/server/shareLinks.js'
+
+export const GET: RequestHandler = async ({ params, fetch, locals }) => {
+    if (!locals.user?.id) {
+        return json({ error: 'User not authenticated' }, { status: 401 })
+    }
+
+    const query = new URLSearchParams({ user_id: locals.user.id })
+    return forwardToSearcher(
+        fetch,
+        `/documents/${encodeURIComponent(params.documentId)}/share-links?${query}`,
+    )
+}
+
+export const POST: RequestHandler = async ({ params, request, fetch, locals }) => {
+    if (!locals.user?.id) {
+        return json({ error: 'User not authenticated' }, { status: 401 })
+    }
+
+    let body: { ttl_hours?: unknown; max_accesses?: unknown }
+    try {
+        body = await request.json()
+    } catch {
+        return json({ error: 'Invalid JSON in request body' }, { status: 400 })
+    }
+
+    return forwardToSearcher(
+        fetch,
+        `/documents/${encodeURIComponent(params.documentId)}/share-links`,
+        {
+            method: 'POST',
+            body: JSON.stringify({
+                user_id: locals.user.id,
+                ttl_hours: body.ttl_hours,
+                max_accesses: body.max_accesses,
+            }),
+        },
+    )
+}
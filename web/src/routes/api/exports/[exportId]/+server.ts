@@ -0,0 +1,13 @@
+import { error } from '@sveltejs/kit'
+import type { RequestHandler } from './$types'
+import { forwardToConnectorManager } from '$lib/server/sourceExports'
+
+export const GET: RequestHandler = async ({ params, locals, fetch }) => {
+    if (!locals.user) {
+        throw error(401, 'Unauthorized')
+    }
+    if (locals.user.role !== 'admin') {
+        throw error(403, 'Admin access required')
+    }
+    return forwardToConnectorManager(fetch, `/exports/${params.exportId}`)
+}
@@ -0,0 +1,36 @@
+import { error, json } from '@sveltejs/kit'
+import type { RequestHandler } from './$types'
+import { getConfig } from '$lib/server/config'
+
+/** Stream an export archive from the connector-manager. The token in the link is the credential. */
+export const GET: RequestHandler = async ({ params, url, locals, fetch }) => {
+    if (!locals.user) {
+        throw error(401, 'Unauthorized')
+    }
+
+    const token = url.searchParams.get('token')
+    if (!token) {
+        throw error(400, 'Download token is required')
+    }
+
+    const query = new URLSearchParams({ token }).toString()
+    const response = await fetch(
+        `${getConfig().services.connectorManagerUrl}/exports/${params.exportId}/download?${query}`,
+    ).catch(() => null)
+    if (!response) {
+        return json({ error: 'Connector manager unavailable' }, { status: 502 })
+    }
+    if (!response.ok) {
+        const body = await response.json().catch(() => ({}))
+        return json(body, { status: response.status })
+    }
+
+    const headers = new Headers()
+    for (const name of ['content-type', 'content-length', 'content-disposition']) {
+        const value = response.headers.get(name)
+        if (value) {
+            headers.set(name, value)
+        }
+    }
+    return new Response(response.body, { status: 200, headers })
+}
@@ -29,6 +29,7 @@ export const POST: RequestHandler = async ({ request, fetch, locals }) => {
         user_email: locals.user?.email,
         user_id: locals.user?.id,
         user_configuration: locals.user?.configuration,
+        collection_id: searchRequest.collection_id,
     }
 
     logger.debug('Sending search request to searcher service', {
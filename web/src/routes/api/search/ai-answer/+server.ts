@@ -38,6 +38,7 @@ export const POST: RequestHandler = async ({ request, fetch, locals }) => {
                 user_email: locals.user?.email,
                 user_id: locals.user?.id,
                 user_configuration: locals.user?.configuration,
+                collection_id: searchRequest.collection_id,
             }),
         })
 
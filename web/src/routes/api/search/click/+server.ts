@@ -0,0 +1,53 @@
+import { env } from '$env/dynamic/private'
+import { json } from '@sveltejs/kit'
+import type { RequestHandler } from './$types.js'
+
+// Records a search result click for the searcher's source router and search
+// analytics. Failures are logged and swallowed: click tracking must never get
+// in the user's way.
+export const POST: RequestHandler = async ({ request, fetch, locals }) => {
+    const logger = locals.logger.child('search-click-api')
+
+    let click: {
+        query?: string
+        document_id?: string
+        position?: number
+        search_event_id?: string
+    }
+    try {
+        click = await request.json()
+    } catch {
+        return json({ error: 'Invalid JSON in request body' }, { status: 400 })
+    }
+
+    if (!click.query?.trim() || !click.document_id) {
+        return json({ error: 'query and document_id are required' }, { status: 400 })
+    }
+
+    try {
+        const response = await fetch(`${env.SEARCHER_URL}/feedback/click`, {
+            method: 'POST',
+            headers: {
+                'Content-Type': 'application/json',
+            },
+            body: JSON.stringify({
+                query: click.query.trim(),
+                document_id: click.document_id,
+                position: click.position,
+                search_event_id: click.search_event_id,
+                user_id: locals.user?.id,
+            }),
+        })
+
+        if (!response.ok) {
+            logger.warn('Failed to record search click', {
+                status: response.status,
+                documentId: click.document_id,
+            })
+        }
+    } catch (error) {
+        logger.warn('Error calling searcher to record search click', error)
+    }
+
+    return new Response(null, { status: 204 })
+}
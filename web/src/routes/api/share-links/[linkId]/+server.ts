@@ -0,0 +1,14 @@
+import { json } from '@sveltejs/kit'
+import type { RequestHandler } from './$types.js'
+import { forwardToSearcher } from '$lib/server/shareLinks.js'
+
+export const DELETE: RequestHandler = async ({ params, fetch, locals }) => {
+    if (!locals.user?.id) {
+        return json({ error: 'User not authenticated' }, { status: 401 })
+    }
+
+    const query = new URLSearchParams({ user_id: locals.user.id })
+    return forwardToSearcher(fetch, `/share-links/${encodeURIComponent(params.linkId)}?${query}`, {
+        method: 'DELETE',
+    })
+}
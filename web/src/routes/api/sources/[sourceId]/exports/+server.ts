@@ -0,0 +1,26 @@
+import { error } from '@sveltejs/kit'
+import type { RequestHandler } from './$types'
+import { forwardToConnectorManager } from '$lib/server/sourceExports'
+
+function requireAdmin(locals: App.Locals) {
+    if (!locals.user) {
+        throw error(401, 'Unauthorized')
+    }
+    if (locals.user.role !== 'admin') {
+        throw error(403, 'Admin access required')
+    }
+    return locals.user
+}
+
+export const GET: RequestHandler = async ({ params, locals, fetch }) => {
+    requireAdmin(locals)
+    return forwardToConnectorManager(fetch, `/sources/${params.sourceId}/exports`)
+}
+
+export const POST: RequestHandler = async ({ params, locals, fetch }) => {
+    const user = requireAdmin(locals)
+    return forwardToConnectorManager(fetch, `/sources/${params.sourceId}/exports`, {
+        method: 'POST',
+        body: JSON.stringify({ requested_by: user.id }),
+    })
+}
@@ -0,0 +1,74 @@
+import { json, error } from '@sveltejs/kit'
+import type { RequestHandler } from './$types'
+import { getConfig } from '$lib/server/config'
+import { logger } from '$lib/server/logger'
+
+// API keys that external systems use to push documents into a push source.
+// Key management lives in connector-manager; this route only adds admin auth.
+
+function requireAdminUser(locals: App.Locals) {
+    if (!locals.user) {
+        throw error(401, 'Unauthorized')
+    }
+    if (locals.user.role !== 'admin') {
+        throw error(403, 'Admin access required')
+    }
+    return locals.user
+}
+
+async function errorMessage(response: Response, fallback: string): Promise<string> {
+    try {
+        const body = await response.json()
+        return body.error || fallback
+    } catch {
+        return fallback
+    }
+}
+
+export const GET: RequestHandler = async ({ params, locals, fetch }) => {
+    requireAdminUser(locals)
+
+    const connectorManagerUrl = getConfig().services.connectorManagerUrl
+    const response = await fetch(`${connectorManagerUrl}/sources/${params.sourceId}/push-keys`)
+
+    if (!response.ok) {
+        const message = await errorMessage(response, 'Failed to list API keys')
+        logger.error(`Failed to list push API keys for source ${params.sourceId}`, {
+            error: message,
+            status: response.status,
+        })
+        throw error(response.status, message)
+    }
+
+    return json(await response.json())
+}
+
+export const POST: RequestHandler = async ({ params, locals, request, fetch }) => {
+    const user = requireAdminUser(locals)
+
+    const body = await request.json().catch(() => {
+        throw error(400, 'Expected JSON request body')
+    })
+    const name = typeof body?.name === 'string' ? body.name.trim() : ''
+    if (!name) {
+        throw error(400, 'Key name is required')
+    }
+
+    const connectorManagerUrl = getConfig().services.connectorManagerUrl
+    const response = await fetch(`${connectorManagerUrl}/sources/${params.sourceId}/push-keys`, {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify({ name, created_by: user.id }),
+    })
+
+    if (!response.ok) {
+        const message = await errorMessage(response, 'Failed to create API key')
+        logger.error(`Failed to create push API key for source ${params.sourceId}`, {
+            error: message,
+            status: response.status,
+        })
+        throw error(response.status, message)
+    }
+
+    return json(await response.json(), { status: 201 })
+}
@@ -0,0 +1,28 @@
+import { error } from '@sveltejs/kit'
+import type { RequestHandler } from './$types'
+import { getConfig } from '$lib/server/config'
+import { logger } from '$lib/server/logger'
+
+export const DELETE: RequestHandler = async ({ params, locals, fetch }) => {
+    if (!locals.user) {
+        throw error(401, 'Unauthorized')
+    }
+    if (locals.user.role !== 'admin') {
+        throw error(403, 'Admin access required')
+    }
+
+    const connectorManagerUrl = getConfig().services.connectorManagerUrl
+    const response = await fetch(
+        `${connectorManagerUrl}/sources/${params.sourceId}/push-keys/${params.keyId}`,
+        { method: 'DELETE' },
+    )
+
+    if (!response.ok) {
+        logger.error(`Failed to revoke push API key ${params.keyId}`, {
+            status: response.status,
+        })
+        throw error(response.status, 'Failed to revoke API key')
+    }
+
+    return new Response(null, { status: 204 })
+}
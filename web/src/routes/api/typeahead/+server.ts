@@ -4,7 +4,7 @@ import type { RequestHandler } from './$types.js'
 
 export const GET: RequestHandler = async ({ fetch, locals, url }) => {
     if (!locals.user?.id) {
-        return json({ results: [], query: '' })
+        return json({ results: [], groups: [], query: '' })
     }
 
     const query = url.searchParams.get('q') || ''
@@ -14,18 +14,26 @@ export const GET: RequestHandler = async ({ fetch, locals, url }) => {
         const typeaheadUrl = new URL(`${env.SEARCHER_URL}/typeahead`)
         typeaheadUrl.searchParams.set('q', query)
         typeaheadUrl.searchParams.set('limit', limit)
+        typeaheadUrl.searchParams.set('user_id', locals.user.id)
+        typeaheadUrl.searchParams.set('user_email', locals.user.email)
+        for (const param of ['types', 'people_limit', 'sources_limit', 'queries_limit']) {
+            const value = url.searchParams.get(param)
+            if (value) {
+                typeaheadUrl.searchParams.set(param, value)
+            }
+        }
 
         const response = await fetch(typeaheadUrl.toString())
 
         if (!response.ok) {
             console.error('Typeahead service error:', response.status, response.statusText)
-            return json({ results: [], query })
+            return json({ results: [], groups: [], query })
         }
 
         const data = await response.json()
         return json(data)
     } catch (error) {
         console.error('Error calling typeahead service:', error)
-        return json({ results: [], query })
+        return json({ results: [], groups: [], query })
     }
 }
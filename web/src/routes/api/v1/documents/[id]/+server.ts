@@ -3,7 +3,11 @@ import { json } from '@sveltejs/kit'
 import type { SearchResponse } from '$lib/types/search'
 import type { RequestHandler } from './$types'
 
-export const GET: RequestHandler = async ({ params, url, fetch, locals }) => {
+// Validators the searcher derives from the document's content hash, passed
+// through so clients can revalidate with If-None-Match / If-Modified-Since.
+const CACHE_HEADERS = ['etag', 'last-modified', 'cache-control']
+
+export const GET: RequestHandler = async ({ params, url, request, fetch, locals }) => {
     if (!locals.user) {
         return json({ error: 'Unauthorized' }, { status: 401 })
     }
@@ -44,13 +48,33 @@ export const GET: RequestHandler = async ({ params, url, fetch, locals }) => {
 
     logger.debug('Document content request', { documentId })
 
+    const searcherHeaders: Record<string, string> = { 'Content-Type': 'application/json' }
+    for (const name of ['if-none-match', 'if-modified-since']) {
+        const value = request.headers.get(name)
+        if (value) {
+            searcherHeaders[name] = value
+        }
+    }
+
     try {
         const response = await fetch(`${env.SEARCHER_URL}/search`, {
             method: 'POST',
-            headers: { 'Content-Type': 'application/json' },
+            headers: searcherHeaders,
             body: JSON.stringify(queryData),
         })
 
+        const cacheHeaders = new Headers()
+        for (const name of CACHE_HEADERS) {
+            const value = response.headers.get(name)
+            if (value) {
+                cacheHeaders.set(name, value)
+            }
+        }
+
+        if (response.status === 304) {
+            return new Response(null, { status: 304, headers: cacheHeaders })
+        }
+
         if (!response.ok) {
             // Searcher returns 500 for nonexistent document IDs (no dedicated 404)
             if (response.status === 500) {
@@ -94,18 +118,21 @@ export const GET: RequestHandler = async ({ params, url, fetch, locals }) => {
             return json({ error: 'Access denied for this source type' }, { status: 403 })
         }
 
-        return json({
-            id: doc.id,
-            title: doc.title,
-            url: doc.url,
-            source_type: docSourceType,
-            content_type: doc.content_type,
-            content,
-            match_type: result.match_type,
-            metadata: doc.metadata ?? {},
-            created_at: doc.created_at,
-            updated_at: doc.updated_at,
-        })
+        return json(
+            {
+                id: doc.id,
+                title: doc.title,
+                url: doc.url,
+                source_type: docSourceType,
+                content_type: doc.content_type,
+                content,
+                match_type: result.match_type,
+                metadata: doc.metadata ?? {},
+                created_at: doc.created_at,
+                updated_at: doc.updated_at,
+            },
+            { headers: cacheHeaders },
+        )
     } catch (error) {
         logger.error('Document content request failed', error as Error, { documentId })
         return json({ error: 'Failed to fetch document' }, { status: 500 })
@@ -0,0 +1,72 @@
+import { json, error } from '@sveltejs/kit'
+import type { RequestHandler } from './$types'
+import { getConfig } from '$lib/server/config'
+import { logger } from '$lib/server/logger'
+
+// Workspaces isolate tenants sharing the deployment. They live in
+// connector-manager; this route only adds admin auth.
+
+function requireAdminUser(locals: App.Locals) {
+    if (!locals.user) {
+        throw error(401, 'Unauthorized')
+    }
+    if (locals.user.role !== 'admin') {
+        throw error(403, 'Admin access required')
+    }
+    return locals.user
+}
+
+async function errorMessage(response: Response, fallback: string): Promise<string> {
+    try {
+        const body = await response.json()
+        return body.error || fallback
+    } catch {
+        return fallback
+    }
+}
+
+export const GET: RequestHandler = async ({ locals, fetch }) => {
+    requireAdminUser(locals)
+
+    const connectorManagerUrl = getConfig().services.connectorManagerUrl
+    const response = await fetch(`${connectorManagerUrl}/workspaces`)
+
+    if (!response.ok) {
+        const message = await errorMessage(response, 'Failed to list workspaces')
+        logger.error('Failed to list workspaces', { error: message, status: response.status })
+        throw error(response.status, message)
+    }
+
+    return json(await response.json())
+}
+
+export const POST: RequestHandler = async ({ locals, request, fetch }) => {
+    requireAdminUser(locals)
+
+    const body = await request.json().catch(() => {
+        throw error(400, 'Expected JSON request body')
+    })
+    const name = typeof body?.name === 'string' ? body.name.trim() : ''
+    const slug = typeof body?.slug === 'string' ? body.slug.trim() : ''
+    if (!name || !slug) {
+        throw error(400, 'Workspace name and slug are required')
+    }
+
+    const connectorManagerUrl = getConfig().services.connectorManagerUrl
+    const response = await fetch(`${connectorManagerUrl}/workspaces`, {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify({ name, slug }),
+    })
+
+    if (!response.ok) {
+        const message = await errorMessage(response, 'Failed to create workspace')
+        logger.error(`Failed to create workspace ${slug}`, {
+            error: message,
+            status: response.status,
+        })
+        throw error(response.status, message)
+    }
+
+    return json(await response.json(), { status: 201 })
+}
@@ -0,0 +1,28 @@
+import { error } from '@sveltejs/kit'
+import type { RequestHandler } from './$types'
+import { getConfig } from '$lib/server/config'
+import { logger } from '$lib/server/logger'
+
+export const PUT: RequestHandler = async ({ params, locals, fetch }) => {
+    if (!locals.user) {
+        throw error(401, 'Unauthorized')
+    }
+    if (locals.user.role !== 'admin') {
+        throw error(403, 'Admin access required')
+    }
+
+    const connectorManagerUrl = getConfig().services.connectorManagerUrl
+    const response = await fetch(
+        `${connectorManagerUrl}/workspaces/${params.workspaceId}/users/${params.userId}`,
+        { method: 'PUT' },
+    )
+
+    if (!response.ok) {
+        logger.error(`Failed to move user ${params.userId} to workspace ${params.workspaceId}`, {
+            status: response.status,
+        })
+        throw error(response.status, 'Failed to move user to workspace')
+    }
+
+    return new Response(null, { status: 204 })
+}